@@ -0,0 +1,27 @@
+//  BUILD.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 12:59:49
+//  Last edited:
+//    16 Oct 2026, 12:59:49
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Build script for the `brane-api` crate. Generates the OpenAPI
+//!   document that it serves at `/openapi.json`.
+// 
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+
+/***** ENTRYPOINT *****/
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let document: serde_json::Value = specifications::openapi::document(env!("CARGO_PKG_VERSION"))?;
+    let out: PathBuf = PathBuf::from(env::var("OUT_DIR")?);
+    fs::write(out.join("openapi.json"), serde_json::to_string_pretty(&document)?)?;
+    Ok(())
+}
@@ -0,0 +1,226 @@
+//  APPROVALS.rs
+//    by agent
+//
+//  Created:
+//    16 Oct 2026, 15:02:41
+//  Last edited:
+//    16 Oct 2026, 15:02:41
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines functions that handle REST-functions to the `/approvals`
+//!   path, which lets approvers list and decide on the approvals that
+//!   workflows wait for.
+//
+
+use chrono::{DateTime, Utc};
+use log::{debug, error, info, warn};
+use tonic::transport::Channel;
+use warp::{Rejection, Reply};
+use warp::http::{HeaderValue, Response, StatusCode};
+use warp::hyper::Body;
+
+use brane_cfg::node::NodeConfig;
+use brane_tsk::grpc::{Approval, DecideApprovalRequest, DriverServiceClient, ListApprovalsRequest};
+use brane_tsk::tools::inject_client;
+use specifications::approvals::{ApprovalDecision, ApprovalInfo};
+
+pub use crate::errors::ApprovalError as Error;
+use crate::events::{driver_address, respond, status_of};
+use crate::server::ClientIdentity;
+use crate::spec::Context;
+
+
+/***** UNIT TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_info_of() {
+        let approval: Approval = Approval{ id: "abc-0".into(), uuid: "abc".into(), message: "Release the results?".into(), requested: "2026-10-16T15:02:41+02:00".into() };
+        let info: ApprovalInfo = info_of(approval).unwrap();
+        assert_eq!(info.id, "abc-0");
+        assert_eq!(info.session, "abc");
+        assert_eq!(info.message, "Release the results?");
+        assert_eq!(info.requested.to_rfc3339(), "2026-10-16T13:02:41+00:00");
+
+        // Approvals with a malformed timestamp are dropped
+        assert!(info_of(Approval{ id: "abc-1".into(), uuid: "abc".into(), message: String::new(), requested: "yesterday".into() }).is_none());
+    }
+}
+
+
+
+
+
+/***** CONSTANTS *****/
+/// The maximum size of a decision on an approval, in bytes.
+pub const MAX_DECISION: u64 = 64 * 1024;
+
+
+
+
+
+/***** HELPER MACROS *****/
+/// Quits a path callback with a SecretError.
+macro_rules! fail {
+    () => {
+        return Err(warp::reject::custom(Error::SecretError))
+    };
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Converts an approval as sent by the driver to the one we send to clients.
+///
+/// # Arguments
+/// - `approval`: The Approval sent by the driver.
+///
+/// # Returns
+/// The matching ApprovalInfo, or `None` if the driver sent a malformed timestamp (which is logged).
+fn info_of(approval: Approval) -> Option<ApprovalInfo> {
+    let requested: DateTime<Utc> = match DateTime::parse_from_rfc3339(&approval.requested) {
+        Ok(requested) => requested.with_timezone(&Utc),
+        Err(err)      => { warn!("Driver sent malformed timestamp '{}' for approval '{}' (skipping it): {}", approval.requested, approval.id, err); return None; },
+    };
+    Some(ApprovalInfo{ id: approval.id, session: approval.uuid, message: approval.message, requested })
+}
+
+/// Connects to the driver of the instance.
+///
+/// # Arguments
+/// - `context`: The Context that contains stuff we need to run.
+///
+/// # Returns
+/// A client for the driver, or a response that explains to the client why it is unavailable.
+///
+/// # Errors
+/// This function may error (i.e., reject the request) if we failed to load the node config.
+async fn connect(context: &Context) -> Result<Result<(String, DriverServiceClient<Channel>), Response<Body>>, Rejection> {
+    let node_config: NodeConfig = match NodeConfig::from_path(&context.node_config_path) {
+        Ok(config) => config,
+        Err(err)   => {
+            error!("Failed to load NodeConfig file: {}", err);
+            fail!();
+        },
+    };
+    if !node_config.node.is_central() {
+        error!("Provided node config file '{}' is not for a central node", context.node_config_path.display());
+        fail!();
+    }
+    let address: String = driver_address(node_config.node.central());
+
+    match DriverServiceClient::connect(address.clone()).await {
+        Ok(client) => Ok(Ok((address, client))),
+        Err(err)   => {
+            error!("{}", Error::DriverConnectError{ address, err });
+            Ok(Err(respond(StatusCode::SERVICE_UNAVAILABLE, "The driver is unavailable\n")))
+        },
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Lists the approvals that workflows are waiting for.
+///
+/// # Arguments
+/// - `identity`: The identity of the client, as given by its certificate. The driver only lists the approvals of the sessions it may use, or all of them if it is an approver.
+/// - `context`: The Context that contains stuff we need to run.
+///
+/// # Returns
+/// A response that can be send to client. It contains the pending approvals as a JSON list of `ApprovalInfo`s (oldest first), or explains why they could not be listed otherwise.
+///
+/// # Errors
+/// This function may error (i.e., reject the request) if we failed to load the node config or serialize the response.
+pub async fn list(identity: Option<ClientIdentity>, context: Context) -> Result<impl Reply, Rejection> {
+    debug!("Handling GET on `/approvals` (i.e., list pending approvals)...");
+    let identity: ClientIdentity = match identity {
+        Some(identity) => identity,
+        None           => { return Ok(respond(StatusCode::UNAUTHORIZED, "Listing approvals requires a client certificate\n")); },
+    };
+    let (address, mut client): (String, DriverServiceClient<Channel>) = match connect(&context).await? {
+        Ok(client)    => client,
+        Err(response) => { return Ok(response); },
+    };
+
+    // Ask the driver on behalf of the client, so that it only lists what the client may see
+    let mut request: tonic::Request<ListApprovalsRequest> = tonic::Request::new(ListApprovalsRequest{});
+    inject_client(&identity.name, &mut request);
+    let approvals: Vec<Approval> = match client.list_approvals(request).await {
+        Ok(reply) => reply.into_inner().approvals,
+        Err(err)  => {
+            let response: Response<Body> = respond(status_of(err.code()), format!("{}\n", err.message()));
+            debug!("{}", Error::DriverRequestError{ address, what: "list approvals", err });
+            return Ok(response);
+        },
+    };
+    let approvals: Vec<ApprovalInfo> = approvals.into_iter().filter_map(info_of).collect();
+
+    // Now serialize them
+    let body: String = match serde_json::to_string(&approvals) {
+        Ok(body) => body,
+        Err(err) => {
+            error!("{}", Error::SerializeError{ err });
+            fail!();
+        }
+    };
+    let body_len: usize = body.len();
+
+    // Create the respones around it
+    let mut response = Response::new(Body::from(body));
+    response.headers_mut().insert(
+        "Content-Length",
+        HeaderValue::from(body_len),
+    );
+
+    // Done
+    Ok(response)
+}
+
+
+
+/// Approves or rejects a pending approval, resuming the workflow waiting for it.
+///
+/// # Arguments
+/// - `id`: The identifier of the approval to decide on.
+/// - `identity`: The identity of the client, as given by its certificate. The driver only accepts decisions of approvers.
+/// - `context`: The Context that contains stuff we need to run.
+/// - `decision`: The ApprovalDecision sent as the body of the request.
+///
+/// # Returns
+/// A response that can be send to client. It is empty if the decision was recorded, or explains why it was not otherwise.
+///
+/// # Errors
+/// This function may error (i.e., reject the request) if we failed to load the node config.
+pub async fn decide(id: String, identity: Option<ClientIdentity>, context: Context, decision: ApprovalDecision) -> Result<impl Reply, Rejection> {
+    debug!("Handling POST on `/approvals/{}` (i.e., decide on approval)...", id);
+    let identity: ClientIdentity = match identity {
+        Some(identity) => identity,
+        None           => { return Ok(respond(StatusCode::UNAUTHORIZED, "Deciding on approvals requires a client certificate\n")); },
+    };
+    let (address, mut client): (String, DriverServiceClient<Channel>) = match connect(&context).await? {
+        Ok(client)    => client,
+        Err(response) => { return Ok(response); },
+    };
+
+    // Forward the decision on behalf of the client, so that the driver checks whether it is an approver (and records who made it)
+    let approve: bool = decision.approve;
+    let mut request: tonic::Request<DecideApprovalRequest> = tonic::Request::new(DecideApprovalRequest{ id: id.clone(), approve, reason: decision.reason });
+    inject_client(&identity.name, &mut request);
+    if let Err(err) = client.decide_approval(request).await {
+        let response: Response<Body> = respond(status_of(err.code()), format!("{}\n", err.message()));
+        debug!("{}", Error::DriverRequestError{ address, what: "decide on approval", err });
+        return Ok(response);
+    }
+
+    info!("Client '{}' {} approval '{}'", identity.name, if approve { "granted" } else { "rejected" }, id);
+    Ok(respond(StatusCode::OK, ""))
+}
@@ -0,0 +1,443 @@
+//  DASHBOARD.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 12:42:38
+//  Last edited:
+//    16 Oct 2026, 13:20:15
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Defines functions that handle REST-functions to the `/dashboard`
+//!   path, which shows instance administrators the state of their
+//!   instance at a glance.
+// 
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::future::join_all;
+use log::{debug, error, warn};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use tonic::transport::Channel;
+use warp::{Rejection, Reply};
+use warp::http::{HeaderValue, Response, StatusCode};
+use warp::hyper::Body;
+
+use brane_cfg::infra::InfraFile;
+use brane_cfg::node::{CentralConfig, CentralTlsConfig, NodeConfig};
+use brane_tsk::grpc::{Approval, DriverServiceClient, GetTimingsRequest, ListApprovalsRequest, ListSessionsRequest, SessionInfo};
+use specifications::health::ComponentHealth;
+
+pub use crate::errors::DashboardError as Error;
+use crate::health;
+use crate::server::ClientIdentity;
+use crate::spec::Context;
+
+
+/***** UNIT TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_authorize() {
+        let tls: CentralTlsConfig = CentralTlsConfig {
+            api                 : ([ 127, 0, 0, 1 ], 50051).into(),
+            drv                 : ([ 127, 0, 0, 1 ], 50053).into(),
+            internal_api        : None,
+            internal_drv        : None,
+            require_client_auth : true,
+            approvers           : vec![],
+            admins              : vec![ "alice".into() ],
+        };
+        let alice: ClientIdentity = ClientIdentity{ name: "alice".into() };
+        let bob: ClientIdentity = ClientIdentity{ name: "bob".into() };
+
+        assert!(authorize(Some(&tls), Some(&alice)).is_none());
+        assert_eq!(authorize(Some(&tls), Some(&bob)).map(|res| res.status()), Some(StatusCode::FORBIDDEN));
+        assert_eq!(authorize(Some(&tls), None).map(|res| res.status()), Some(StatusCode::UNAUTHORIZED));
+        // Without TLS, nobody can prove who they are
+        assert_eq!(authorize(None, Some(&alice)).map(|res| res.status()), Some(StatusCode::FORBIDDEN));
+    }
+}
+
+
+
+
+
+/***** CONSTANTS *****/
+/// The number of most recent tasks shown on the dashboard.
+pub const RECENT_TASKS: usize = 20;
+
+/// How long a collected status of the instance is served to dashboards before we collect it again.
+pub const STATUS_TTL: Duration = Duration::from_secs(5);
+
+/// The page of the dashboard, which polls `/dashboard/status` to fill itself.
+const PAGE: &str = include_str!("./dashboard.html");
+
+
+
+
+
+/***** HELPER MACROS *****/
+/// Quits a path callback with a SecretError.
+macro_rules! fail {
+    () => {
+        return Err(warp::reject::custom(Error::SecretError))
+    };
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Checks whether the given client may see the dashboard.
+///
+/// # Arguments
+/// - `tls`: The TLS configuration of the central node, which lists the administrators (if any).
+/// - `identity`: The identity of the client, as given by its certificate (if it presented one).
+///
+/// # Returns
+/// `None` if the client is an administrator, or a response that refuses it otherwise.
+fn authorize(tls: Option<&CentralTlsConfig>, identity: Option<&ClientIdentity>) -> Option<Response<Body>> {
+    let (status, body): (StatusCode, &str) = match identity {
+        Some(identity) if tls.map(|tls| tls.admins.contains(&identity.name)).unwrap_or(false) => { return None; },
+        Some(_)                                                                               => (StatusCode::FORBIDDEN, "Only administrators may see the dashboard\n"),
+        None                                                                                  => (StatusCode::UNAUTHORIZED, "The dashboard requires a client certificate\n"),
+    };
+    let mut response = Response::new(Body::from(body));
+    *response.status_mut() = status;
+    Some(response)
+}
+
+/// Loads the node config of the central node.
+///
+/// # Arguments
+/// - `context`: The Context that contains stuff we need to run.
+///
+/// # Returns
+/// The NodeConfig, or `None` if we failed to load it or it is not for a central node (which is logged).
+fn load_node_config(context: &Context) -> Option<NodeConfig> {
+    let node_config: NodeConfig = match NodeConfig::from_path(&context.node_config_path) {
+        Ok(config) => config,
+        Err(err)   => {
+            error!("Failed to load NodeConfig file: {}", err);
+            return None;
+        },
+    };
+    if !node_config.node.is_central() {
+        error!("Provided node config file '{}' is not for a central node", context.node_config_path.display());
+        return None;
+    }
+    Some(node_config)
+}
+
+/// Asks the registry of the given location whether it is alive.
+///
+/// # Arguments
+/// - `context`: The Context that contains stuff we need to run.
+/// - `name`: The name of the location.
+/// - `registry`: The address of the location's registry.
+///
+/// # Returns
+/// The DomainStatus of the location.
+async fn probe_domain(context: &Context, name: String, registry: String) -> DomainStatus {
+    let health: ComponentHealth = health::registry(context, name.clone(), registry.clone()).await;
+    DomainStatus{ name, registry, healthy: health.healthy, error: health.error }
+}
+
+/// Asks the driver which sessions it knows and which tasks they ran last.
+///
+/// # Arguments
+/// - `central`: The part of the node config that tells us where the driver lives.
+///
+/// # Returns
+/// The sessions known to the driver, the approvals their workflows wait for (oldest first) and the most recent tasks run in any of them (most recent first).
+///
+/// # Errors
+/// This function errors if we failed to reach the driver. Sessions of which we fail to get the timings are skipped for the tasks.
+async fn collect_sessions(central: &CentralConfig) -> Result<(Vec<SessionStatus>, Vec<ApprovalStatus>, Vec<RecentTask>), Error> {
+    let address: String = crate::events::driver_address(central);
+    let mut client: DriverServiceClient<Channel> = match DriverServiceClient::connect(address.clone()).await {
+        Ok(client) => client,
+        Err(err)   => { return Err(Error::DriverConnectError{ address, err }); },
+    };
+
+    // List the sessions
+    let infos: Vec<SessionInfo> = match client.list_sessions(ListSessionsRequest{}).await {
+        Ok(reply) => reply.into_inner().sessions,
+        Err(err)  => { return Err(Error::DriverRequestError{ address, what: "list sessions", err }); },
+    };
+
+    // List the approvals their workflows wait for (as the node itself, so we see all of them)
+    let approvals: Vec<Approval> = match client.list_approvals(ListApprovalsRequest{}).await {
+        Ok(reply) => reply.into_inner().approvals,
+        Err(err)  => { return Err(Error::DriverRequestError{ address, what: "list approvals", err }); },
+    };
+
+    // Collect the tasks they ran
+    let mut tasks: Vec<RecentTask> = vec![];
+    for session in &infos {
+        let timings: String = match client.get_timings(GetTimingsRequest{ uuid: session.uuid.clone() }).await {
+            Ok(reply) => reply.into_inner().timings,
+            Err(err)  => { warn!("{} (skipping its tasks)", Error::DriverRequestError{ address: address.clone(), what: "get session timings", err }); continue; },
+        };
+        let timings: Vec<TaskTiming> = match serde_json::from_str(&timings) {
+            Ok(timings) => timings,
+            Err(err)    => { warn!("{} (skipping its tasks)", Error::TimingsParseError{ session: session.uuid.clone(), err }); continue; },
+        };
+        tasks.extend(timings.into_iter().map(|timing| RecentTask{ name: timing.name, location: timing.location, start: timing.start, end: timing.end }));
+    }
+    tasks.sort_by(|lhs, rhs| rhs.end.cmp(&lhs.end));
+    tasks.truncate(RECENT_TASKS);
+
+    // Done; the identifiers of sessions (and thus of approvals) are as good as credentials, so we do not show them
+    let sessions: Vec<SessionStatus> = infos.into_iter().map(|info| SessionStatus{ created: info.created, running: info.running, attached: info.attached }).collect();
+    let approvals: Vec<ApprovalStatus> = approvals.into_iter().map(|approval| ApprovalStatus{ message: approval.message, requested: approval.requested }).collect();
+    Ok((sessions, approvals, tasks))
+}
+
+/// Collects the status of the instance.
+///
+/// Parts of the instance that cannot be reached are reported as such instead of failing, as the dashboard is most useful exactly when something is down.
+///
+/// # Arguments
+/// - `context`: The Context that contains stuff we need to run.
+/// - `central`: The central node config that tells us where everything lives.
+///
+/// # Returns
+/// The DashboardStatus of the instance.
+///
+/// # Errors
+/// This function errors (i.e., rejects the request) if we failed to load the infrastructure file.
+async fn collect_status(context: &Context, central: &CentralConfig) -> Result<DashboardStatus, Rejection> {
+    // Load the infrastructure file
+    let infra: InfraFile = match InfraFile::from_path(&central.paths.infra) {
+        Ok(infra) => infra,
+        Err(err)  => {
+            error!("Failed to open infrastructure file '{}': {}", central.paths.infra.display(), err);
+            fail!();
+        },
+    };
+
+    // Probe all domains at once, so that unreachable ones do not add up
+    let mut domains: Vec<DomainStatus> = join_all(infra.into_iter().map(|(name, info)| probe_domain(context, name, info.registry.to_string()))).await;
+    domains.sort_by(|lhs, rhs| lhs.name.cmp(&rhs.name));
+
+    // Ask the driver about its sessions
+    let (sessions, approvals, tasks): (Option<Vec<SessionStatus>>, Option<Vec<ApprovalStatus>>, Vec<RecentTask>) = match collect_sessions(central).await {
+        Ok((sessions, approvals, tasks)) => (Some(sessions), Some(approvals), tasks),
+        Err(err)                         => { warn!("{}", err); (None, None, vec![]) },
+    };
+
+    // Count the packages and datasets
+    let packages: Option<usize> = match context.db.packages().await {
+        Ok(packages) => Some(packages.len()),
+        Err(err)     => { warn!("Failed to get packages: {}", err); None },
+    };
+    let datasets: Option<usize> = match crate::data::collect(context, false).await {
+        Ok(datasets) => Some(datasets.len()),
+        Err(_)       => None,
+    };
+
+    Ok(DashboardStatus{ domains, sessions, approvals, tasks, packages, datasets, degraded: context.db.is_degraded() })
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// The part of a task timing (as sent by the driver) that we show.
+#[derive(Deserialize)]
+struct TaskTiming {
+    /// The name of the task (as `package::function`).
+    name     : String,
+    /// The location where the task was run.
+    location : String,
+    /// When the task was started, in milliseconds since the Unix epoch.
+    start    : i64,
+    /// When the task was done, in milliseconds since the Unix epoch.
+    end      : i64,
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines the status of a single domain in the instance.
+#[derive(Clone, Debug, Serialize)]
+pub struct DomainStatus {
+    /// The name of the domain's location.
+    pub name     : String,
+    /// The address of the domain's registry.
+    pub registry : String,
+    /// Whether the domain's registry responded to us.
+    pub healthy  : bool,
+    /// Why the domain is not healthy, if it isn't.
+    pub error    : Option<String>,
+}
+
+/// Defines a session that is known to the driver. Its identifier is left out on purpose, since anyone who knows it may control the session.
+#[derive(Clone, Debug, Serialize)]
+pub struct SessionStatus {
+    /// When the session was created (or resumed), as an RFC 3339 timestamp.
+    pub created  : String,
+    /// Whether the session is running a workflow.
+    pub running  : bool,
+    /// Whether a client is following the session's workflow.
+    pub attached : bool,
+}
+
+/// Defines an approval that a workflow is waiting for. Its identifier is left out on purpose, since it contains that of the session.
+#[derive(Clone, Debug, Serialize)]
+pub struct ApprovalStatus {
+    /// The message that describes what should be approved.
+    pub message   : String,
+    /// When the workflow asked for the approval, as an RFC 3339 timestamp.
+    pub requested : String,
+}
+
+/// Defines a task that was recently run in the instance.
+#[derive(Clone, Debug, Serialize)]
+pub struct RecentTask {
+    /// The name of the task (as `package::function`).
+    pub name     : String,
+    /// The location where the task was run.
+    pub location : String,
+    /// When the task was started, in milliseconds since the Unix epoch.
+    pub start    : i64,
+    /// When the task was done, in milliseconds since the Unix epoch.
+    pub end      : i64,
+}
+
+/// Defines the status of the instance as shown on the dashboard.
+#[derive(Clone, Debug, Serialize)]
+pub struct DashboardStatus {
+    /// The domains registered in the instance, ordered by name.
+    pub domains   : Vec<DomainStatus>,
+    /// The sessions known to the driver (oldest first), or `None` if the driver could not be reached.
+    pub sessions  : Option<Vec<SessionStatus>>,
+    /// The approvals that workflows are waiting for (oldest first), or `None` if the driver could not be reached.
+    pub approvals : Option<Vec<ApprovalStatus>>,
+    /// The most recent tasks run in any of the sessions (most recent first).
+    pub tasks     : Vec<RecentTask>,
+    /// The number of package versions in the instance, or `None` if the package database (and its cache) are unavailable.
+    pub packages  : Option<usize>,
+    /// The number of datasets in the instance, or `None` if they could not be collected.
+    pub datasets  : Option<usize>,
+    /// Whether the package database is unavailable (i.e., packages are served from the cache).
+    pub degraded  : bool,
+}
+
+
+
+/// Caches the status of the instance, so that it is collected at most once per TTL no matter how many dashboards poll it.
+#[derive(Debug)]
+pub struct StatusCache {
+    /// How long a collected status is served before it is collected again.
+    ttl    : Duration,
+    /// The last collected status and when it was collected. Kept locked while collecting, so that concurrent requests wait for it instead of collecting it too.
+    status : Mutex<Option<(Instant, DashboardStatus)>>,
+}
+
+impl StatusCache {
+    /// Constructor for the StatusCache.
+    ///
+    /// # Arguments
+    /// - `ttl`: How long a collected status is served before it is collected again (see `STATUS_TTL`).
+    ///
+    /// # Returns
+    /// A new, empty StatusCache.
+    #[inline]
+    pub fn new(ttl: Duration) -> Self { Self { ttl, status: Mutex::new(None) } }
+}
+
+
+
+/// Serves the page of the dashboard.
+///
+/// # Arguments
+/// - `identity`: The identity of the client, which must be an administrator.
+/// - `context`: The Context that contains stuff we need to run.
+///
+/// # Returns
+/// A response that can be send to client. Specifically, it will contain the HTML of the dashboard, which fetches the actual status from `/dashboard/status`.
+///
+/// # Errors
+/// This function may error (i.e., reject the request) if we failed to load the node config.
+pub async fn page(identity: Option<ClientIdentity>, context: Context) -> Result<impl Reply, Rejection> {
+    debug!("Handling GET on `/dashboard` (i.e., get the dashboard page)...");
+    let node_config: NodeConfig = match load_node_config(&context) {
+        Some(config) => config,
+        None         => { fail!(); },
+    };
+    if let Some(refusal) = authorize(node_config.node.central().tls.as_ref(), identity.as_ref()) { return Ok(refusal); }
+    Ok(warp::reply::html(PAGE).into_response())
+}
+
+
+
+/// Returns the status of the instance for the dashboard.
+///
+/// The status is collected at most once every few seconds (see `STATUS_TTL`), since the dashboard polls it and collecting it queries the driver for every session.
+///
+/// # Arguments
+/// - `identity`: The identity of the client, which must be an administrator.
+/// - `context`: The Context that contains stuff we need to run.
+/// - `cache`: The StatusCache with the last collected status.
+///
+/// # Returns
+/// A response that can be send to client. Specifically, it will contain the DashboardStatus of the instance.
+///
+/// # Errors
+/// This function may error (i.e., reject the request) if we failed to load the node config or infrastructure file.
+pub async fn status(identity: Option<ClientIdentity>, context: Context, cache: Arc<StatusCache>) -> Result<impl Reply, Rejection> {
+    debug!("Handling GET on `/dashboard/status` (i.e., get the status of the instance)...");
+    let node_config: NodeConfig = match load_node_config(&context) {
+        Some(config) => config,
+        None         => { fail!(); },
+    };
+    if let Some(refusal) = authorize(node_config.node.central().tls.as_ref(), identity.as_ref()) { return Ok(refusal); }
+
+    // Serve the cached status if it is recent enough
+    let status: DashboardStatus = {
+        let mut cached = cache.status.lock().await;
+        match &*cached {
+            Some((collected, status)) if collected.elapsed() < cache.ttl => status.clone(),
+            _ => {
+                let status: DashboardStatus = collect_status(&context, node_config.node.central()).await?;
+                *cached = Some((Instant::now(), status.clone()));
+                status
+            },
+        }
+    };
+
+    // Now serialize it
+    let body: String = match serde_json::to_string(&status) {
+        Ok(body) => body,
+        Err(err) => {
+            error!("{}", Error::SerializeError{ err });
+            fail!();
+        }
+    };
+    let body_len: usize = body.len();
+
+    // Create the respones around it
+    let mut response = Response::new(Body::from(body));
+    response.headers_mut().insert(
+        "Content-Length",
+        HeaderValue::from(body_len),
+    );
+
+    // Done
+    Ok(response)
+}
+
+
+
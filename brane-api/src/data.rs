@@ -27,6 +27,7 @@ use brane_prx::spec::NewPathRequestTlsOptions;
 use specifications::data::{AssetInfo, DataInfo};
 
 pub use crate::errors::DataError as Error;
+use crate::federation;
 use crate::spec::Context;
 
 
@@ -43,19 +44,18 @@ macro_rules! fail {
 
 
 /***** LIBRARY *****/
-/// Lists the datasets that are known in the instance.
+/// Collects the datasets that are known in the instance by asking every location's registry.
 /// 
 /// # Arguments
 /// - `context`: The Context that contains stuff we need to run.
+/// - `federate`: Whether to also collect the datasets known by the peer instances we federate with.
 /// 
 /// # Returns
-/// A response that can be send to client. Specifically, it will contains a map (i.e., `HashMap`) of DataInfo structs that describe all the known datasets and where they live (mapped by their name).
+/// A map of DataInfo structs that describe all the known datasets and where they live (mapped by their identifier, i.e., `NAME@VERSION`). Locations (or peers) that we fail to reach are skipped.
 /// 
 /// # Errors
-/// This function may error (i.e., reject the request) if we failed to load the infrastructure file.
-pub async fn list(context: Context) -> Result<impl Reply, Rejection> {
-    debug!("Handling GET on `/data/info` (i.e., list all datasets)...");
-
+/// This function may error (i.e., reject the request) if we failed to load the node config or infrastructure file.
+pub async fn collect(context: &Context, federate: bool) -> Result<HashMap<String, DataInfo>, Rejection> {
     // Load the node config file
     let node_config: NodeConfig = match NodeConfig::from_path(&context.node_config_path) {
         Ok(config) => config,
@@ -118,10 +118,12 @@ pub async fn list(context: Context) -> Result<impl Reply, Rejection> {
             }  
         };
 
-        // Merge that into the existing mapping of DataInfos
-        for (n, d) in local_sets {
+        // Merge that into the existing mapping of DataInfos (by identifier, so different versions remain separate)
+        for d in local_sets.into_values() {
+            let n: String = d.id();
             if let Some(info) = datasets.get_mut(&n) {
                 // Add this location
+                if d.revision > 0 { info.revisions.insert(loc_name.clone(), d.revision); }
                 info.access.insert(loc_name.clone(), d.access);
             } else {
                 datasets.insert(n, d.into_data_info(loc_name.clone()));
@@ -129,6 +131,39 @@ pub async fn list(context: Context) -> Result<impl Reply, Rejection> {
         }
     }
 
+    // If asked, merge the datasets of our peers into that as well
+    if federate {
+        for (n, d) in federation::collect_data(context, &node_config.node.central().peers).await {
+            if let Some(info) = datasets.get_mut(&n) {
+                info.revisions.extend(d.revisions);
+                info.access.extend(d.access);
+            } else {
+                datasets.insert(n, d);
+            }
+        }
+    }
+
+    // Done
+    Ok(datasets)
+}
+
+
+
+/// Lists the datasets that are known in the instance.
+/// 
+/// # Arguments
+/// - `federated`: The value of the `X-Brane-Federated`-header, if any. If given, the request comes from a peer, and we do not ask our own peers.
+/// - `context`: The Context that contains stuff we need to run.
+/// 
+/// # Returns
+/// A response that can be send to client. Specifically, it will contains a map (i.e., `HashMap`) of DataInfo structs that describe all the known datasets and where they live (mapped by their identifier, i.e., `NAME@VERSION`).
+/// 
+/// # Errors
+/// This function may error (i.e., reject the request) if we failed to load the infrastructure file.
+pub async fn list(federated: Option<String>, context: Context) -> Result<impl Reply, Rejection> {
+    debug!("Handling GET on `/data/info` (i.e., list all datasets)...");
+    let datasets: HashMap<String, DataInfo> = collect(&context, federated.is_none()).await?;
+
     // Now serialize this map
     let body: String = match serde_json::to_string(&datasets) {
         Ok(body) => body,
@@ -156,6 +191,7 @@ pub async fn list(context: Context) -> Result<impl Reply, Rejection> {
 /// 
 /// # Arguments
 /// - `name`: The name of the dataset to query about.
+/// - `federated`: The value of the `X-Brane-Federated`-header, if any. If given, the request comes from a peer, and we do not ask our own peers.
 /// - `context`: The Context that contains stuff we need to run.
 /// 
 /// # Returns
@@ -163,7 +199,7 @@ pub async fn list(context: Context) -> Result<impl Reply, Rejection> {
 /// 
 /// # Errors
 /// This function may error (i.e., reject the request) if the given name was not known.
-pub async fn get(name: String, context: Context) -> Result<impl Reply, Rejection> {
+pub async fn get(name: String, federated: Option<String>, context: Context) -> Result<impl Reply, Rejection> {
     debug!("Handling GET on `/data/info/{}` (i.e., get dataset info)...", name);
 
     // Load the node config file
@@ -228,11 +264,28 @@ pub async fn get(name: String, context: Context) -> Result<impl Reply, Rejection
             }  
         };
 
-        // Either add or set that as the result
-        if let Some(info) = &mut dataset {
-            info.access.insert(loc_name, local_set.access);
-        } else {
-            dataset = Some(local_set.into_data_info(loc_name));
+        // Either add or set that as the result (keeping the most recent version if the name resolved to different ones)
+        match &mut dataset {
+            Some(info) if info.version == local_set.resolved_version() => {
+                if local_set.revision > 0 { info.revisions.insert(loc_name.clone(), local_set.revision); }
+                info.access.insert(loc_name, local_set.access);
+            },
+            Some(info) if info.version > local_set.resolved_version()  => {},
+            _                                                          => { dataset = Some(local_set.into_data_info(loc_name)); },
+        }
+    }
+
+    // Do the same for what our peers know about it, unless we are asked by a peer ourselves
+    if federated.is_none() {
+        for peer_set in federation::get_data(&context, &node_config.node.central().peers, &name).await {
+            match &mut dataset {
+                Some(info) if info.version == peer_set.version => {
+                    info.revisions.extend(peer_set.revisions);
+                    info.access.extend(peer_set.access);
+                },
+                Some(info) if info.version > peer_set.version  => {},
+                _                                              => { dataset = Some(peer_set); },
+            }
         }
     }
 
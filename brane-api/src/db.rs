@@ -0,0 +1,311 @@
+//  DB.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 10:38:44
+//  Last edited:
+//    16 Oct 2026, 12:46:13
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Manages the connection to the Scylla database that stores the
+//!   package metadata. If the database cannot be reached, the API runs in
+//!   a read-only, degraded mode that serves the package metadata it last
+//!   cached on disk until the database is back.
+// 
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use log::{debug, info, warn};
+use scylla::{IntoTypedRows, Session, SessionBuilder};
+use serde::{Deserialize, Serialize};
+use tokio::fs as tfs;
+use warp::http::{HeaderValue, StatusCode};
+use warp::hyper::Body;
+use warp::reply::Response;
+
+use brane_cfg::spec::Address;
+
+pub use crate::errors::DatabaseError as Error;
+use crate::errors::ApiError;
+use crate::packages::{self, PackageUdt};
+use crate::scan;
+
+
+/***** CONSTANTS *****/
+/// The number of seconds that clients are told to wait (using `Retry-After`) before retrying a request that needs the database during an outage.
+pub const RETRY_AFTER: u64 = 10;
+
+/// The name of the file in the packages directory in which we cache the package metadata.
+pub const CACHE_FILE: &str = ".packages.json";
+
+/// The number of times we try to connect to the database on startup before we start in degraded mode.
+pub const STARTUP_ATTEMPTS: usize = 5;
+
+/// The time we wait after the first failed connection attempt. It doubles for every next attempt, up to `MAX_BACKOFF`.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+/// The maximum time we wait between connection attempts.
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Connects to the Scylla database once, making sure the keyspace and packages table exist.
+/// 
+/// # Arguments
+/// - `host`: The address of the Scylla database.
+/// 
+/// # Returns
+/// A new Session with the database.
+/// 
+/// # Errors
+/// This function errors if we failed to connect or to define the keyspace or table.
+async fn connect(host: &Address) -> Result<Session, ApiError> {
+    debug!("Connecting to scylla...");
+    let scylla: Session = match SessionBuilder::new()
+        .known_node(&host.to_string())
+        .connection_timeout(Duration::from_secs(3))
+        .build()
+        .await
+    {
+        Ok(scylla) => scylla,
+        Err(err)   => { return Err(ApiError::ScyllaConnectError{ host: host.clone(), err }); },
+    };
+    debug!("Connected successfully.");
+
+    debug!("Ensuring keyspace & database...");
+    if let Err(err) = scylla.query("CREATE KEYSPACE IF NOT EXISTS brane WITH replication = {'class': 'SimpleStrategy', 'replication_factor' : 1};", &[]).await {
+        return Err(ApiError::KeyspaceDefineError{ err });
+    }
+    if let Err(err) = packages::ensure_db_table(&scylla).await { return Err(ApiError::TableDefineError{ err }); }
+    if let Err(err) = scan::ensure_db_table(&scylla).await { return Err(ApiError::ScanTableDefineError{ err }); }
+    Ok(scylla)
+}
+
+/// Queries all packages in the Scylla database.
+/// 
+/// # Arguments
+/// - `scylla`: The Scylla database session that allows us to talk to it.
+/// 
+/// # Returns
+/// A list of CachedPackages, one per package version in the database.
+/// 
+/// # Errors
+/// This function errors if we failed to query the database or to parse its response.
+async fn query_packages(scylla: &Session) -> Result<Vec<CachedPackage>, Error> {
+    let rows = match scylla.query("SELECT file, package FROM brane.packages", &[]).await {
+        Ok(res)  => res.rows.unwrap_or_default(),
+        Err(err) => { return Err(Error::PackagesQueryError{ err }); },
+    };
+
+    let mut packages: Vec<CachedPackage> = Vec::with_capacity(rows.len());
+    for row in rows.into_typed::<(String, PackageUdt)>() {
+        let (file, package): (String, PackageUdt) = match row {
+            Ok(row)  => row,
+            Err(err) => { return Err(Error::PackageRowError{ err }); },
+        };
+        packages.push(CachedPackage{ file: file.into(), package });
+    }
+    Ok(packages)
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines a package as we remember it in the cache on disk.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct CachedPackage {
+    /// The path of the package's image file.
+    pub file    : PathBuf,
+    /// The metadata of the package.
+    pub package : PackageUdt,
+}
+
+
+
+/// Wraps the connection to the Scylla database, which may not be there if the database is unavailable.
+pub struct Database {
+    /// The address of the Scylla database.
+    host       : Address,
+    /// The path of the file in which we cache the package metadata, if caching is enabled.
+    cache_path : Option<PathBuf>,
+    /// The session with the database, or `None` if we are in degraded mode.
+    session    : RwLock<Option<Arc<Session>>>,
+}
+
+impl Database {
+    /// Constructor for the Database that does not connect yet.
+    /// 
+    /// # Arguments
+    /// - `host`: The address of the Scylla database.
+    /// - `cache_path`: The path of the file in which we cache the package metadata for when the database is unavailable. If `None`, nothing is cached (i.e., the `enable_cache` feature is disabled).
+    /// 
+    /// # Returns
+    /// A new Database in degraded mode. Call `Database::connect()` to connect it.
+    pub fn new(host: impl Into<Address>, cache_path: Option<PathBuf>) -> Self {
+        Self {
+            host       : host.into(),
+            cache_path,
+            session    : RwLock::new(None),
+        }
+    }
+
+
+
+    /// Connects to the database, retrying with exponential backoff if that fails.
+    /// 
+    /// Once connected, the package cache is refreshed.
+    /// 
+    /// # Arguments
+    /// - `attempts`: The number of times to try before we give up, or `None` to keep trying forever.
+    /// 
+    /// # Returns
+    /// Whether we are connected now.
+    pub async fn connect(&self, attempts: Option<usize>) -> bool {
+        let mut backoff : Duration = INITIAL_BACKOFF;
+        let mut i       : usize    = 0;
+        loop {
+            i += 1;
+            match connect(&self.host).await {
+                Ok(scylla) => {
+                    *self.session.write().unwrap() = Some(Arc::new(scylla));
+                    if let Err(err) = self.refresh_cache().await { warn!("Failed to refresh package cache: {}", err); }
+                    return true;
+                },
+                Err(err) => {
+                    if matches!(attempts, Some(attempts) if i >= attempts) {
+                        warn!("{} (giving up after {} attempts)", err, i);
+                        return false;
+                    }
+                    warn!("{} (retrying in {} seconds)", err, backoff.as_secs());
+                },
+            }
+
+            // Wait before the next attempt
+            tokio::time::sleep(backoff).await;
+            backoff = std::cmp::min(2 * backoff, MAX_BACKOFF);
+        }
+    }
+
+    /// Keeps trying to connect to the database in the background until it succeeds, after which the API leaves degraded mode.
+    pub fn reconnect_in_background(self: &Arc<Self>) {
+        let this: Arc<Self> = self.clone();
+        tokio::spawn(async move {
+            this.connect(None).await;
+            info!("Connected to Scylla database; leaving degraded mode");
+        });
+    }
+
+
+
+    /// Returns the session with the database, if we have one.
+    /// 
+    /// # Returns
+    /// The Session with the database, or `None` if it is unavailable (and we are in degraded mode). Callers that want to change the database should respond with `unavailable()` in that case.
+    #[inline]
+    pub fn session(&self) -> Option<Arc<Session>> { self.session.read().unwrap().clone() }
+
+    /// Returns whether we are in degraded mode (i.e., we have no connection to the database).
+    #[inline]
+    pub fn is_degraded(&self) -> bool { self.session.read().unwrap().is_none() }
+
+    /// Returns all known packages.
+    /// 
+    /// The packages are read from the database if it is available, or from the cache on disk otherwise.
+    /// 
+    /// # Returns
+    /// A list of CachedPackages, one per package version.
+    /// 
+    /// # Errors
+    /// This function errors if the database is unavailable and we failed to read the cache, or there is none.
+    pub async fn packages(&self) -> Result<Vec<CachedPackage>, Error> {
+        if let Some(scylla) = self.session() {
+            match query_packages(&scylla).await {
+                Ok(packages) => { return Ok(packages); },
+                Err(err)     => { warn!("{} (serving packages from cache)", err); },
+            }
+        }
+        self.read_cache().await
+    }
+
+
+
+    /// Writes the packages currently in the database to the cache on disk.
+    /// 
+    /// Should be called after every change to the database, so that the cache is up-to-date for the next outage. Does nothing if caching is disabled.
+    /// 
+    /// # Errors
+    /// This function errors if the database is unavailable or we failed to write the cache.
+    pub async fn refresh_cache(&self) -> Result<(), Error> {
+        let cache_path: &Path = match &self.cache_path {
+            Some(path) => path,
+            None       => { return Ok(()); },
+        };
+        let scylla: Arc<Session> = match self.session() {
+            Some(scylla) => scylla,
+            None         => { return Err(Error::Unavailable); },
+        };
+        let packages: Vec<CachedPackage> = query_packages(&scylla).await?;
+
+        // Write it to a temporary file first, so a crash does not leave a half-written cache
+        debug!("Caching {} packages to '{}'...", packages.len(), cache_path.display());
+        let raw: String = match serde_json::to_string(&packages) {
+            Ok(raw)  => raw,
+            Err(err) => { return Err(Error::CacheSerializeError{ err }); },
+        };
+        let temp_path: PathBuf = cache_path.with_extension("json.part");
+        if let Err(err) = tfs::write(&temp_path, raw).await { return Err(Error::CacheWriteError{ path: temp_path, err }); }
+        if let Err(err) = tfs::rename(&temp_path, cache_path).await { return Err(Error::CacheWriteError{ path: cache_path.into(), err }); }
+        Ok(())
+    }
+
+    /// Reads the packages from the cache on disk.
+    /// 
+    /// # Returns
+    /// The cached list of packages.
+    /// 
+    /// # Errors
+    /// This function errors if there is no cache (or caching is disabled) or we failed to read it.
+    async fn read_cache(&self) -> Result<Vec<CachedPackage>, Error> {
+        let path: &Path = match &self.cache_path {
+            Some(path) if path.exists() => path,
+            _                           => { return Err(Error::Unavailable); },
+        };
+        let raw: String = match tfs::read_to_string(path).await {
+            Ok(raw)  => raw,
+            Err(err) => { return Err(Error::CacheReadError{ path: path.into(), err }); },
+        };
+        match serde_json::from_str(&raw) {
+            Ok(packages) => Ok(packages),
+            Err(err)     => Err(Error::CacheParseError{ path: path.into(), err }),
+        }
+    }
+}
+
+
+
+/// Constructs the response for requests that need the database while it is unavailable.
+/// 
+/// # Returns
+/// A response with status code 503 (Service Unavailable) and a `Retry-After` header.
+pub fn unavailable() -> Response {
+    let body: &'static str = "The package database is temporarily unavailable; try again later\n";
+    let mut response = Response::new(Body::from(body));
+    *response.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+    response.headers_mut().insert(
+        "Retry-After",
+        HeaderValue::from(RETRY_AFTER),
+    );
+    response.headers_mut().insert(
+        "Content-Length",
+        HeaderValue::from(body.len()),
+    );
+    response
+}
@@ -14,6 +14,7 @@
 
 use std::error::Error;
 use std::fmt::{Display, Formatter, Result as FResult};
+use std::net::SocketAddr;
 use std::path::PathBuf;
 
 use enum_debug::EnumDebug as _;
@@ -32,12 +33,21 @@ use specifications::version::Version;
 pub enum ApiError {
     /// Could not create a Scylla session
     ScyllaConnectError{ host: Address, err: NewSessionError },
+    /// Could not define the `brane` keyspace in the Scylla database.
+    KeyspaceDefineError{ err: scylla::transport::errors::QueryError },
+    /// Could not define the packages table in the Scylla database.
+    TableDefineError{ err: PackageError },
+    /// Could not define the package scans table in the Scylla database.
+    ScanTableDefineError{ err: ScanError },
 }
 
 impl Display for ApiError {
     fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
         match self {
             ApiError::ScyllaConnectError{ host, err } => write!(f, "Could not connect to Scylla host '{}': {}", host, err),
+            ApiError::KeyspaceDefineError{ err }      => write!(f, "Failed to ensure database keyspace: {}", err),
+            ApiError::TableDefineError{ err }         => write!(f, "Failed to ensure database table: {}", err),
+            ApiError::ScanTableDefineError{ err }     => write!(f, "Failed to ensure scan database table: {}", err),
         }
     }
 }
@@ -46,11 +56,53 @@ impl Error for ApiError {}
 
 
 
+/// Contains errors relating to the package database and the cache of it on disk.
+#[derive(Debug)]
+pub enum DatabaseError {
+    /// The Scylla database cannot be reached, and there is no cache to fall back to either.
+    Unavailable,
+    /// Failed to query the packages in the Scylla database.
+    PackagesQueryError{ err: scylla::transport::errors::QueryError },
+    /// Failed to parse a package row returned by the Scylla database.
+    PackageRowError{ err: scylla::cql_to_rust::FromRowError },
+
+    /// Failed to read the package cache.
+    CacheReadError{ path: PathBuf, err: std::io::Error },
+    /// Failed to parse the package cache.
+    CacheParseError{ path: PathBuf, err: serde_json::Error },
+    /// Failed to serialize the package cache.
+    CacheSerializeError{ err: serde_json::Error },
+    /// Failed to write the package cache.
+    CacheWriteError{ path: PathBuf, err: std::io::Error },
+}
+
+impl Display for DatabaseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use DatabaseError::*;
+        match self {
+            Unavailable               => write!(f, "The package database is unavailable"),
+            PackagesQueryError{ err } => write!(f, "Failed to query packages from the Scylla database: {}", err),
+            PackageRowError{ err }    => write!(f, "Failed to parse package returned by the Scylla database: {}", err),
+
+            CacheReadError{ path, err }  => write!(f, "Failed to read package cache '{}': {}", path.display(), err),
+            CacheParseError{ path, err } => write!(f, "Failed to parse package cache '{}' as JSON: {}", path.display(), err),
+            CacheSerializeError{ err }   => write!(f, "Failed to serialize package cache: {}", err),
+            CacheWriteError{ path, err } => write!(f, "Failed to write package cache '{}': {}", path.display(), err),
+        }
+    }
+}
+
+impl Error for DatabaseError {}
+
+
+
 /// Contains errors relating to the `/infra` path (and nested).
 #[derive(Debug)]
 pub enum InfraError {
     /// Failed to open/load the infrastructure file.
     InfrastructureOpenError{ path: PathBuf, err: brane_cfg::infra::Error },
+    /// Failed to hash the infrastructure file.
+    InfrastructureHashError{ err: brane_cfg::infra::Error },
     /// Failed to serialize the response body.
     SerializeError{ what: &'static str, err: serde_json::Error },
 
@@ -66,6 +118,8 @@ pub enum InfraError {
     ResponseParseError{ address: String, raw: String, err: serde_json::Error },
     /// Failed to re-serialize the parsed body
     CapabilitiesSerializeError{ err: serde_json::Error },
+    /// Failed to load, update or sign the CA log.
+    CaLogError{ err: brane_cfg::pins::Error },
 
     /// An internal error occurred that we would not like to divulge.
     SecretError,
@@ -76,6 +130,7 @@ impl Display for InfraError {
         use InfraError::*;
         match self {
             InfrastructureOpenError{ path, err } => write!(f, "Failed to open infrastructure file '{}': {}", path.display(), err),
+            InfrastructureHashError{ err }       => write!(f, "Failed to hash infrastructure file: {}", err),
             SerializeError{ what, err }          => write!(f, "Failed to serialize {}: {}", what, err),
 
             ProxyError{ err }                        => write!(f, "Failed to send request through Brane proxy service: {}", err),
@@ -84,6 +139,7 @@ impl Display for InfraError {
             ResponseBodyError{ address, err }        => write!(f, "Failed to get body of response sent by '{}': {}", address, err),
             ResponseParseError{ address, raw, err }  => write!(f, "Failed to parse '{}' as valid JSON sent by '{}': {}", raw, address, err),
             CapabilitiesSerializeError{ err }        => write!(f, "Failed to re-serialize capabilities: {}", err),
+            CaLogError{ err }                        => write!(f, "Failed to maintain CA log: {}", err),
 
             SecretError => write!(f, "An internal error has occurred"),
         }
@@ -163,14 +219,12 @@ pub enum PackageError {
     /// Failed to insert a new package in the database.
     PackageInsertError{ name: String, err: scylla::transport::errors::QueryError },
 
-    /// Failed to query for the given package in the Scylla database.
-    VersionsQueryError{ name: String, err: scylla::transport::errors::QueryError },
+    /// Failed to get the known packages from the database (or its cache).
+    DbError{ err: DatabaseError },
     /// Failed to parse a Version string
     VersionParseError{ raw: String, err: specifications::version::ParseError },
     /// No versions found for the given package
     NoVersionsFound{ name: String },
-    /// Failed to query the database for the file of the given package.
-    PathQueryError{ name: String, version: Version, err: scylla::transport::errors::QueryError },
     /// The given package was unknown.
     UnknownPackage{ name: String, version: Version },
     /// Failed to get the metadata of a file.
@@ -181,6 +235,14 @@ pub enum PackageError {
     FileReadError{ path: PathBuf, err: std::io::Error },
     /// Failed to send a file chunk.
     FileSendError{ path: PathBuf, err: warp::hyper::Error },
+    /// Failed to read the manifest list of a multi-arch package.
+    ManifestListReadError{ err: specifications::arch::ManifestListError },
+    /// The given package has not been built for the requested architecture.
+    UnsupportedArch{ name: String, version: Version, arch: specifications::arch::Arch },
+    /// The image of the given package lives in the OCI registry, so it cannot be downloaded from us.
+    ImageInRegistry{ name: String, version: Version, reference: String },
+    /// Failed to serialize the description of the OCI registry.
+    RegistryInfoSerializeError{ err: serde_json::Error },
 
     /// Failed to load the node config.
     NodeConfigLoadError{ err: brane_cfg::node::Error },
@@ -220,6 +282,14 @@ pub enum PackageError {
     PackageInfoParseError{ path: PathBuf, err: serde_yaml::Error },
     /// Failed to move the temporary image to its final destination.
     FileMoveError{ from: PathBuf, to: PathBuf, err: std::io::Error },
+    /// The manifest list of the uploaded package lists an image that was not in the tar file.
+    ArchImageMissing{ file: String, path: PathBuf },
+    /// Failed to copy the extracted manifest list to its final destination.
+    ManifestListCopyError{ from: PathBuf, to: PathBuf, err: std::io::Error },
+    /// Failed to scan the uploaded image(s) for vulnerabilities.
+    ImageScanError{ name: String, version: Version, err: ScanError },
+    /// Failed to store the scan result of the uploaded package.
+    ScanStoreError{ err: ScanError },
 }
 
 impl Display for PackageError {
@@ -234,15 +304,18 @@ impl Display for PackageError {
             PackageTableDefineError{ err }  => write!(f, "Failed to define the 'brane.packages' table in the Scylla database: {}", err),
             PackageInsertError{ name, err } => write!(f, "Failed to insert package '{}' into the Scylla database: {}", name, err),
 
-            VersionsQueryError{ name, err }      => write!(f, "Failed to query versions for package '{}' from the Scylla database: {}", name, err),
-            VersionParseError{ raw, err }        => write!(f, "Failed to parse '{}' as a valid version string: {}", raw, err),
-            NoVersionsFound{ name }              => write!(f, "No versions found for package '{}'", name),
-            PathQueryError{ name, version, err } => write!(f, "Failed to get path of package '{}', version {}: {}", name, version, err),
-            UnknownPackage{ name, version }      => write!(f, "No package '{}' exists (or has version {})", name, version),
-            FileMetadataError{ path, err }       => write!(f, "Failed to get metadata of file '{}': {}", path.display(), err),
-            FileOpenError{ path, err }           => write!(f, "Failed to open file '{}': {}", path.display(), err),
-            FileReadError{ path, err }           => write!(f, "Failed to read file '{}': {}", path.display(), err),
-            FileSendError{ path, err }           => write!(f, "Failed to send chunk of file '{}': {}", path.display(), err),
+            DbError{ err }                              => write!(f, "Failed to get packages: {}", err),
+            VersionParseError{ raw, err }               => write!(f, "Failed to parse '{}' as a valid version string: {}", raw, err),
+            NoVersionsFound{ name }                     => write!(f, "No versions found for package '{}'", name),
+            UnknownPackage{ name, version }             => write!(f, "No package '{}' exists (or has version {})", name, version),
+            FileMetadataError{ path, err }              => write!(f, "Failed to get metadata of file '{}': {}", path.display(), err),
+            FileOpenError{ path, err }                  => write!(f, "Failed to open file '{}': {}", path.display(), err),
+            FileReadError{ path, err }                  => write!(f, "Failed to read file '{}': {}", path.display(), err),
+            FileSendError{ path, err }                  => write!(f, "Failed to send chunk of file '{}': {}", path.display(), err),
+            ManifestListReadError{ err }                => write!(f, "Failed to read manifest list: {}", err),
+            UnsupportedArch{ name, version, arch }      => write!(f, "Package '{}' (version {}) has not been built for architecture '{}'", name, version, arch),
+            ImageInRegistry{ name, version, reference } => write!(f, "The image of package '{}' (version {}) lives in the OCI registry as '{}'; pull it from there instead", name, version, reference),
+            RegistryInfoSerializeError{ err }           => write!(f, "Failed to serialize OCI registry info: {}", err),
 
             NodeConfigLoadError{ err }                       => write!(f, "Failed to load node config file: {}", err),
             NodeConfigUnexpectedKind{ path, got, expected }  => write!(f, "Given node config file '{}' is for a {} node, but expected a {} node", path.display(), got.variant(), expected.variant()),
@@ -263,8 +336,332 @@ impl Display for PackageError {
             PackageInfoReadError{ path, err }                => write!(f, "Failed to read extracted package info file '{}': {}", path.display(), err),
             PackageInfoParseError{ path, err }               => write!(f, "Failed to parse extracted package info file '{}' as YAML: {}", path.display(), err),
             FileMoveError{ from, to, err }                   => write!(f, "Failed to move '{}' to '{}': {}", from.display(), to.display(), err),
+            ArchImageMissing{ file, path }                   => write!(f, "Tar file '{}' does not have entry '{}', even though its manifest list mentions it", path.display(), file),
+            ManifestListCopyError{ from, to, err }           => write!(f, "Failed to copy manifest list '{}' to '{}': {}", from.display(), to.display(), err),
+            ImageScanError{ name, version, err }             => write!(f, "Failed to scan image of package '{}' (version {}): {}", name, version, err),
+            ScanStoreError{ err }                            => write!(f, "Failed to store scan result: {}", err),
         }
     }
 }
 
 impl Error for PackageError {}
+
+
+
+/// Contains errors relating to scanning package images for vulnerabilities.
+#[derive(Debug)]
+pub enum ScanError {
+    /// The configured scanner command is empty.
+    CommandEmpty,
+    /// Failed to launch the scanner command.
+    CommandLaunchError{ command: String, err: std::io::Error },
+    /// The scanner command did not succeed.
+    CommandFailure{ command: String, code: i32, stderr: String },
+    /// Failed to open the image to send it to the scanning service.
+    ImageOpenError{ path: PathBuf, err: std::io::Error },
+    /// Failed to send the image to the scanning service.
+    RequestError{ address: String, err: reqwest::Error },
+    /// The scanning service responded with a non-success status code.
+    RequestFailure{ address: String, code: reqwest::StatusCode, response: Option<String> },
+    /// Failed to read the response of the scanning service.
+    ResponseReadError{ address: String, err: reqwest::Error },
+    /// Failed to parse the report of the scanner.
+    ReportParseError{ path: PathBuf, err: serde_json::Error },
+
+    /// Failed to define the package scans table in the Scylla database.
+    TableDefineError{ err: scylla::transport::errors::QueryError },
+    /// Failed to serialize the findings of a scan.
+    FindingsSerializeError{ err: serde_json::Error },
+    /// Failed to insert a scan result in the database.
+    ScanInsertError{ name: String, version: String, err: scylla::transport::errors::QueryError },
+    /// Failed to query the scan results in the database.
+    ScansQueryError{ err: scylla::transport::errors::QueryError },
+    /// Failed to parse a scan row returned by the database.
+    ScanRowError{ err: scylla::cql_to_rust::FromRowError },
+}
+
+impl Display for ScanError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use ScanError::*;
+        match self {
+            CommandEmpty                              => write!(f, "Scanner command is empty"),
+            CommandLaunchError{ command, err }        => write!(f, "Failed to launch scanner command '{}': {}", command, err),
+            CommandFailure{ command, code, stderr }   => write!(f, "Scanner command '{}' failed with exit code {}\n\nstderr:\n{}\n\n", command, code, stderr),
+            ImageOpenError{ path, err }               => write!(f, "Failed to open image '{}': {}", path.display(), err),
+            RequestError{ address, err }              => write!(f, "Failed to send image to scanning service '{}': {}", address, err),
+            RequestFailure{ address, code, response } => write!(f, "Scanning service '{}' returned status {} ({}){}", address, code.as_u16(), code.canonical_reason().unwrap_or("???"), if let Some(response) = response { format!(": {}", response) } else { String::new() }),
+            ResponseReadError{ address, err }         => write!(f, "Failed to read response of scanning service '{}': {}", address, err),
+            ReportParseError{ path, err }             => write!(f, "Failed to parse scan report of image '{}' as trivy JSON: {}", path.display(), err),
+
+            TableDefineError{ err }               => write!(f, "Failed to define the 'brane.package_scans' table in the Scylla database: {}", err),
+            FindingsSerializeError{ err }         => write!(f, "Failed to serialize scan findings: {}", err),
+            ScanInsertError{ name, version, err } => write!(f, "Failed to insert scan result of package '{}' (version {}) into the Scylla database: {}", name, version, err),
+            ScansQueryError{ err }                => write!(f, "Failed to query scan results from the Scylla database: {}", err),
+            ScanRowError{ err }                   => write!(f, "Failed to parse scan result returned by the Scylla database: {}", err),
+        }
+    }
+}
+
+impl Error for ScanError {}
+
+
+
+/// Contains errors relating to the `/search` path.
+#[derive(Debug)]
+pub enum SearchError {
+    /// Failed to get the packages from the database (or its cache).
+    DbError{ err: DatabaseError },
+    /// Failed to serialize the response body.
+    SerializeError{ err: serde_json::Error },
+
+    /// An internal error occurred that we would not like to divulge.
+    SecretError,
+}
+
+impl Display for SearchError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use SearchError::*;
+        match self {
+            DbError{ err }        => write!(f, "Failed to get packages: {}", err),
+            SerializeError{ err } => write!(f, "Failed to serialize search results: {}", err),
+
+            SecretError => write!(f, "An internal error has occurred"),
+        }
+    }
+}
+
+impl Error for SearchError {}
+
+impl warp::reject::Reject for SearchError {}
+
+
+
+/// Contains errors relating to federating with peer instances.
+#[derive(Debug)]
+pub enum FederationError {
+    /// Failed to build a request to a peer.
+    RequestBuildError{ peer: String, address: String, err: reqwest::Error },
+    /// Failed to create a new port on the proxy.
+    ProxyError{ peer: String, err: brane_prx::client::Error },
+    /// Failed to send a request to a peer.
+    RequestError{ peer: String, address: String, err: reqwest::Error },
+    /// The peer responded with a non-success status code.
+    RequestFailure{ peer: String, address: String, code: StatusCode, message: Option<String> },
+    /// Failed to get the body of a response.
+    ResponseBodyError{ peer: String, address: String, err: reqwest::Error },
+    /// Failed to parse the body of a response.
+    ResponseParseError{ peer: String, address: String, err: serde_json::Error },
+    /// Failed to parse the registry address returned by a peer.
+    RegistryParseError{ peer: String, address: String, raw: String, err: brane_cfg::errors::AddressParseError },
+}
+
+impl Display for FederationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use FederationError::*;
+        match self {
+            RequestBuildError{ peer, address, err }        => write!(f, "Failed to build request to '{}' of peer '{}': {}", address, peer, err),
+            ProxyError{ peer, err }                        => write!(f, "Failed to prepare sending a request to peer '{}' using the proxy service: {}", peer, err),
+            RequestError{ peer, address, err }             => write!(f, "Failed to send GET-request to '{}' of peer '{}': {}", address, peer, err),
+            RequestFailure{ peer, address, code, message } => write!(f, "Request to '{}' of peer '{}' failed with status code {} ({}){}", address, peer, code.as_u16(), code.canonical_reason().unwrap_or("???"), if let Some(message) = message { format!(": {}", message) } else { String::new() }),
+            ResponseBodyError{ peer, address, err }        => write!(f, "Failed to get the response body received from '{}' of peer '{}': {}", address, peer, err),
+            ResponseParseError{ peer, address, err }       => write!(f, "Failed to parse response from '{}' of peer '{}' as JSON: {}", address, peer, err),
+            RegistryParseError{ peer, address, raw, err }  => write!(f, "Failed to parse registry address '{}' received from '{}' of peer '{}': {}", raw, address, peer, err),
+        }
+    }
+}
+
+impl Error for FederationError {}
+
+
+
+/// Contains errors relating to collecting the status of the instance for the dashboard.
+#[derive(Debug)]
+pub enum DashboardError {
+    /// Failed to connect to the driver.
+    DriverConnectError{ address: String, err: tonic::transport::Error },
+    /// The driver failed to answer a request.
+    DriverRequestError{ address: String, what: &'static str, err: tonic::Status },
+    /// Failed to parse the timings of a session sent by the driver.
+    TimingsParseError{ session: String, err: serde_json::Error },
+
+    /// Failed to serialize the response body.
+    SerializeError{ err: serde_json::Error },
+
+    /// An internal error occurred that we would not like to divulge.
+    SecretError,
+}
+
+impl Display for DashboardError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use DashboardError::*;
+        match self {
+            DriverConnectError{ address, err }       => write!(f, "Failed to connect to driver at '{}': {}", address, err),
+            DriverRequestError{ address, what, err } => write!(f, "Failed to {} at driver '{}': {}", what, address, err),
+            TimingsParseError{ session, err }        => write!(f, "Failed to parse timings of session '{}' sent by driver: {}", session, err),
+
+            SerializeError{ err } => write!(f, "Failed to serialize dashboard status: {}", err),
+
+            SecretError => write!(f, "An internal error has occurred"),
+        }
+    }
+}
+
+impl Error for DashboardError {}
+
+impl warp::reject::Reject for DashboardError {}
+
+
+
+/// Contains errors relating to forwarding events to the driver.
+#[derive(Debug)]
+pub enum EventError {
+    /// Failed to connect to the driver.
+    DriverConnectError{ address: String, err: tonic::transport::Error },
+    /// The driver refused or failed to deliver the event.
+    DriverRequestError{ address: String, err: tonic::Status },
+
+    /// An internal error occurred that we would not like to divulge.
+    SecretError,
+}
+
+impl Display for EventError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use EventError::*;
+        match self {
+            DriverConnectError{ address, err } => write!(f, "Failed to connect to driver at '{}': {}", address, err),
+            DriverRequestError{ address, err } => write!(f, "Failed to send event at driver '{}': {}", address, err),
+
+            SecretError => write!(f, "An internal error has occurred"),
+        }
+    }
+}
+
+impl Error for EventError {}
+
+impl warp::reject::Reject for EventError {}
+
+
+
+/// Contains errors relating to forwarding approvals and decisions on them between clients and the driver.
+#[derive(Debug)]
+pub enum ApprovalError {
+    /// Failed to connect to the driver.
+    DriverConnectError{ address: String, err: tonic::transport::Error },
+    /// The driver refused or failed to answer a request.
+    DriverRequestError{ address: String, what: &'static str, err: tonic::Status },
+
+    /// Failed to serialize the response body.
+    SerializeError{ err: serde_json::Error },
+
+    /// An internal error occurred that we would not like to divulge.
+    SecretError,
+}
+
+impl Display for ApprovalError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use ApprovalError::*;
+        match self {
+            DriverConnectError{ address, err }       => write!(f, "Failed to connect to driver at '{}': {}", address, err),
+            DriverRequestError{ address, what, err } => write!(f, "Failed to {} at driver '{}': {}", what, address, err),
+
+            SerializeError{ err } => write!(f, "Failed to serialize list of approvals: {}", err),
+
+            SecretError => write!(f, "An internal error has occurred"),
+        }
+    }
+}
+
+impl Error for ApprovalError {}
+
+impl warp::reject::Reject for ApprovalError {}
+
+
+
+/// Contains errors relating to probing the components of the instance.
+#[derive(Debug)]
+pub enum HealthError {
+    /// The component did not respond in time.
+    Timeout{ timeout: std::time::Duration },
+    /// The probe of the component panicked.
+    ProbePanicked{ err: tokio::task::JoinError },
+
+    /// Failed to create a new port on the proxy.
+    ProxyError{ err: brane_prx::client::Error },
+    /// Failed to send a request to the given address.
+    RequestError{ address: String, err: reqwest::Error },
+    /// The request was not met with a success status code.
+    RequestFailure{ address: String, code: StatusCode },
+
+    /// We are not connected to the Scylla database.
+    ScyllaUnavailable,
+    /// Failed to query the Scylla database.
+    ScyllaQueryError{ err: scylla::transport::errors::QueryError },
+
+    /// Failed to parse the address of a gRPC service.
+    EndpointParseError{ address: String, err: warp::http::uri::InvalidUri },
+    /// Failed to connect to a gRPC service.
+    GrpcConnectError{ address: String, err: tonic::transport::Error },
+    /// A gRPC service failed to answer a request.
+    GrpcRequestError{ address: String, err: tonic::Status },
+    /// Failed to reach the Kafka brokers.
+    KafkaError{ brokers: String, err: rdkafka::error::KafkaError },
+
+    /// Failed to serialize the response body.
+    SerializeError{ err: serde_json::Error },
+
+    /// An internal error occurred that we would not like to divulge.
+    SecretError,
+}
+
+impl Display for HealthError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use HealthError::*;
+        match self {
+            Timeout{ timeout }   => write!(f, "Did not respond within {}s", timeout.as_secs()),
+            ProbePanicked{ err } => write!(f, "Probe panicked: {}", err),
+
+            ProxyError{ err }               => write!(f, "Failed to send request through Brane proxy service: {}", err),
+            RequestError{ address, err }    => write!(f, "Failed to send GET-request to '{}': {}", address, err),
+            RequestFailure{ address, code } => write!(f, "Request to '{}' failed with status code {} ({})", address, code.as_u16(), code.canonical_reason().unwrap_or("???")),
+
+            ScyllaUnavailable       => write!(f, "Not connected to the Scylla database"),
+            ScyllaQueryError{ err } => write!(f, "Failed to query the Scylla database: {}", err),
+
+            EndpointParseError{ address, err } => write!(f, "Failed to parse '{}' as a gRPC address: {}", address, err),
+            GrpcConnectError{ address, err }   => write!(f, "Failed to connect to gRPC service at '{}': {}", address, err),
+            GrpcRequestError{ address, err }   => write!(f, "Request to gRPC service at '{}' failed: {}", address, err),
+            KafkaError{ brokers, err }         => write!(f, "Failed to reach Kafka brokers '{}': {}", brokers, err),
+
+            SerializeError{ err } => write!(f, "Failed to serialize instance health: {}", err),
+
+            SecretError => write!(f, "An internal error has occurred"),
+        }
+    }
+}
+
+impl Error for HealthError {}
+
+impl warp::reject::Reject for HealthError {}
+
+
+
+/// Contains errors relating to serving the API over TLS.
+#[derive(Debug)]
+pub enum ServerError {
+    /// Failed to load the server certificate, key or client CA.
+    ConfigLoadError{ err: brane_cfg::certs::Error },
+    /// Failed to create a new TcpListener and bind it to the given address.
+    ServerBindError{ address: SocketAddr, err: std::io::Error },
+}
+
+impl Display for ServerError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use ServerError::*;
+        match self {
+            ConfigLoadError{ err }          => write!(f, "Failed to load TLS configuration: {}", err),
+            ServerBindError{ address, err } => write!(f, "Failed to bind new TCP server to '{}': {}", address, err),
+        }
+    }
+}
+
+impl Error for ServerError {}
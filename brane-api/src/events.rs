@@ -0,0 +1,186 @@
+//  EVENTS.rs
+//    by agent
+//
+//  Created:
+//    16 Oct 2026, 13:20:15
+//  Last edited:
+//    16 Oct 2026, 13:20:15
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines functions that handle REST-functions to the `/events`
+//!   path, which lets external systems (e.g., webhooks) resume workflows
+//!   that wait for an event.
+//
+
+use bytes::Bytes;
+use log::{debug, error, info};
+use tonic::Code;
+use tonic::transport::Channel;
+use warp::{Rejection, Reply};
+use warp::http::{Response, StatusCode};
+use warp::hyper::Body;
+
+use brane_cfg::node::{CentralConfig, NodeConfig};
+use brane_tsk::grpc::{DriverServiceClient, SendEventRequest};
+use brane_tsk::tools::inject_client;
+
+pub use crate::errors::EventError as Error;
+use crate::server::ClientIdentity;
+use crate::spec::Context;
+
+
+/***** UNIT TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_of() {
+        assert_eq!(status_of(Code::InvalidArgument), StatusCode::BAD_REQUEST);
+        assert_eq!(status_of(Code::PermissionDenied), StatusCode::FORBIDDEN);
+        assert_eq!(status_of(Code::NotFound), StatusCode::NOT_FOUND);
+        assert_eq!(status_of(Code::Unavailable), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(status_of(Code::Internal), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
+
+
+
+
+
+/***** CONSTANTS *****/
+/// The maximum size of the payload of an event, in bytes.
+pub const MAX_PAYLOAD: u64 = 1024 * 1024;
+
+
+
+
+
+/***** HELPER MACROS *****/
+/// Quits a path callback with a SecretError.
+macro_rules! fail {
+    () => {
+        return Err(warp::reject::custom(Error::SecretError))
+    };
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Returns the HTTP status code with which we relay a status code returned by the driver.
+///
+/// # Arguments
+/// - `code`: The gRPC status code returned by the driver.
+///
+/// # Returns
+/// The matching HTTP StatusCode.
+pub(crate) fn status_of(code: Code) -> StatusCode {
+    match code {
+        Code::InvalidArgument  => StatusCode::BAD_REQUEST,
+        Code::PermissionDenied => StatusCode::FORBIDDEN,
+        Code::NotFound         => StatusCode::NOT_FOUND,
+        Code::Unavailable      => StatusCode::SERVICE_UNAVAILABLE,
+        _                      => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// Creates a plain-text response with the given status code.
+///
+/// # Arguments
+/// - `status`: The StatusCode of the response.
+/// - `body`: The body of the response.
+///
+/// # Returns
+/// A new Response.
+pub(crate) fn respond(status: StatusCode, body: impl Into<String>) -> Response<Body> {
+    let mut response = Response::new(Body::from(body.into()));
+    *response.status_mut() = status;
+    response
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Returns the address on which the driver can be reached by the other services on the central node.
+///
+/// If the driver serves over TLS, this is its internal plaintext address.
+///
+/// # Arguments
+/// - `central`: The central node config that tells us where the driver lives.
+///
+/// # Returns
+/// The address of the driver as an `http://` URL.
+pub fn driver_address(central: &CentralConfig) -> String {
+    let port: u16 = match central.tls.as_ref().and_then(|tls| tls.internal_drv) {
+        Some(internal) => internal.port(),
+        None           => central.ports.drv.port(),
+    };
+    format!("http://{}:{}", central.names.drv, port)
+}
+
+
+
+/// Sends an external event to a session, resuming the workflow thread(s) waiting for it.
+///
+/// # Arguments
+/// - `app_id`: The identifier of the session to send the event to.
+/// - `name`: The name of the event.
+/// - `identity`: The identity of the client, as given by its certificate. Only clients that identify themselves may send events, and only to the sessions they may use on the driver.
+/// - `context`: The Context that contains stuff we need to run.
+/// - `payload`: The body of the request, which is passed to the workflow as the (UTF-8) payload of the event.
+///
+/// # Returns
+/// A response that can be send to client. It is empty if the event was delivered, or explains why it was not otherwise.
+///
+/// # Errors
+/// This function may error (i.e., reject the request) if we failed to load the node config.
+pub async fn send(app_id: String, name: String, identity: Option<ClientIdentity>, context: Context, payload: Bytes) -> Result<impl Reply, Rejection> {
+    debug!("Handling POST on `/events/{}/{}` (i.e., send event to session)...", app_id, name);
+    let identity: ClientIdentity = match identity {
+        Some(identity) => identity,
+        None           => { return Ok(respond(StatusCode::UNAUTHORIZED, "Sending events requires a client certificate\n")); },
+    };
+    let payload: String = match String::from_utf8(payload.to_vec()) {
+        Ok(payload) => payload,
+        Err(_)      => { return Ok(respond(StatusCode::BAD_REQUEST, "The payload of an event must be UTF-8\n")); },
+    };
+
+    // Find the driver
+    let node_config: NodeConfig = match NodeConfig::from_path(&context.node_config_path) {
+        Ok(config) => config,
+        Err(err)   => {
+            error!("Failed to load NodeConfig file: {}", err);
+            fail!();
+        },
+    };
+    if !node_config.node.is_central() {
+        error!("Provided node config file '{}' is not for a central node", context.node_config_path.display());
+        fail!();
+    }
+    let address: String = driver_address(node_config.node.central());
+
+    // Forward the event to it, on behalf of the client so that the driver checks whether it may use the session
+    let mut request: tonic::Request<SendEventRequest> = tonic::Request::new(SendEventRequest{ uuid: app_id.clone(), name: name.clone(), payload });
+    inject_client(&identity.name, &mut request);
+    let mut client: DriverServiceClient<Channel> = match DriverServiceClient::connect(address.clone()).await {
+        Ok(client) => client,
+        Err(err)   => {
+            error!("{}", Error::DriverConnectError{ address, err });
+            return Ok(respond(StatusCode::SERVICE_UNAVAILABLE, "The driver is unavailable\n"));
+        },
+    };
+    if let Err(err) = client.send_event(request).await {
+        let response: Response<Body> = respond(status_of(err.code()), format!("{}\n", err.message()));
+        debug!("{}", Error::DriverRequestError{ address, err });
+        return Ok(response);
+    }
+
+    info!("Client '{}' sent event '{}' to session '{}'", identity.name, name, app_id);
+    Ok(respond(StatusCode::OK, ""))
+}
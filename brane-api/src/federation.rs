@@ -0,0 +1,251 @@
+//  FEDERATION.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 10:51:50
+//  Last edited:
+//    16 Oct 2026, 10:51:50
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements federation with peer instances. The API forwards package
+//!   and dataset queries to the `brane-api` of every configured peer, and
+//!   merges their answers with its own. Locations hosted by a peer are
+//!   referred to as `<peer>:<location>`.
+// 
+
+use std::collections::HashMap;
+use std::str::FromStr as _;
+
+use log::{debug, warn};
+use reqwest::StatusCode;
+
+use brane_cfg::infra::{peer_location, split_peer_location};
+use brane_cfg::node::PeerConfig;
+use brane_cfg::spec::Address;
+use brane_prx::spec::NewPathRequestTlsOptions;
+use specifications::data::DataInfo;
+use specifications::search::{SearchKind, SearchResult, SearchResults};
+
+pub use crate::errors::FederationError as Error;
+use crate::spec::Context;
+
+
+/***** CONSTANTS *****/
+/// The header that marks requests sent by a federating peer. Such requests are only answered with what this instance knows itself, so that queries do not propagate transitively through the peers of peers.
+pub const FEDERATED_HEADER: &str = "X-Brane-Federated";
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Sends a GET-request to the API of a peer instance.
+/// 
+/// The request is marked as federated, and sent through the proxy using the peer's trust anchors.
+/// 
+/// # Arguments
+/// - `context`: The Context that contains stuff we need to run.
+/// - `peer`: The name of the peer to query.
+/// - `config`: The PeerConfig that describes how to reach the peer.
+/// - `path`: The path (without leading slash) to query at the peer's API.
+/// - `query`: Any query parameters to add to the request.
+/// 
+/// # Returns
+/// The body of the response, or `None` if the peer responded with 404 (Not Found).
+/// 
+/// # Errors
+/// This function errors if we failed to reach the peer or it responded with another non-success status code.
+async fn peer_get(context: &Context, peer: &str, config: &PeerConfig, path: &str, query: &[(&str, &str)]) -> Result<Option<String>, Error> {
+    let address: String = format!("{}/{}", config.api, path);
+    debug!("Querying peer '{}' at '{}'...", peer, address);
+
+    // Build the request, marking it as coming from a peer
+    let client: reqwest::Client = reqwest::Client::new();
+    let request: reqwest::Request = match client.get(&address).query(query).header(FEDERATED_HEADER, "true").build() {
+        Ok(request) => request,
+        Err(err)    => { return Err(Error::RequestBuildError{ peer: peer.into(), address, err }); },
+    };
+
+    // Send it through the proxy, which verifies the peer (and identifies us) using its certificates
+    let res: reqwest::Response = match context.proxy.execute(client, request, Some(NewPathRequestTlsOptions{ location: peer.into(), use_client_auth: true })).await {
+        Ok(res) => match res {
+            Ok(res)  => res,
+            Err(err) => { return Err(Error::RequestError{ peer: peer.into(), address, err }); },
+        },
+        Err(err) => { return Err(Error::ProxyError{ peer: peer.into(), err }); },
+    };
+    if res.status() == StatusCode::NOT_FOUND { return Ok(None); }
+    if !res.status().is_success() {
+        return Err(Error::RequestFailure{ peer: peer.into(), address, code: res.status(), message: res.text().await.ok() });
+    }
+
+    // Return the body
+    match res.text().await {
+        Ok(body) => Ok(Some(body)),
+        Err(err) => Err(Error::ResponseBodyError{ peer: peer.into(), address, err }),
+    }
+}
+
+/// Rewrites the locations in a DataInfo received from a peer such that they refer to that peer's locations.
+/// 
+/// # Arguments
+/// - `peer`: The name of the peer that sent the DataInfo.
+/// - `info`: The DataInfo to rewrite.
+/// 
+/// # Returns
+/// The same DataInfo, but with every location `<location>` replaced by `<peer>:<location>`. Locations that the peer itself got from one of its peers are dropped.
+fn localize(peer: &str, mut info: DataInfo) -> DataInfo {
+    info.access = info.access.into_iter()
+        .filter(|(loc, _)| split_peer_location(loc).is_none())
+        .map(|(loc, access)| (peer_location(peer, loc), access))
+        .collect();
+    info.revisions = info.revisions.into_iter()
+        .filter(|(loc, _)| split_peer_location(loc).is_none())
+        .map(|(loc, revision)| (peer_location(peer, loc), revision))
+        .collect();
+    info.residency = info.residency.map(|locs| locs.into_iter().map(|loc| peer_location(peer, loc)).collect());
+    info
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Collects the datasets that are known by the given peer instances.
+/// 
+/// # Arguments
+/// - `context`: The Context that contains stuff we need to run.
+/// - `peers`: The peers to query.
+/// 
+/// # Returns
+/// A map of DataInfo structs that describe all the datasets of the peers (mapped by their identifier, i.e., `NAME@VERSION`). They only list locations hosted by the peers, as `<peer>:<location>`. Peers that we fail to reach are skipped.
+pub async fn collect_data(context: &Context, peers: &HashMap<String, PeerConfig>) -> HashMap<String, DataInfo> {
+    let mut datasets: HashMap<String, DataInfo> = HashMap::new();
+    for (peer, config) in peers {
+        // Ask the peer for everything it knows
+        let body: String = match peer_get(context, peer, config, "data/info", &[]).await {
+            Ok(Some(body)) => body,
+            Ok(None)       => { continue; },
+            Err(err)       => {
+                warn!("{} (skipping peer)", err);
+                continue;
+            },
+        };
+        let peer_sets: HashMap<String, DataInfo> = match serde_json::from_str(&body) {
+            Ok(sets) => sets,
+            Err(err) => {
+                debug!("Received body: \"\"\"{}\"\"\"", body);
+                warn!("{} (skipping peer)", Error::ResponseParseError{ peer: peer.clone(), address: format!("{}/data/info", config.api), err });
+                continue;
+            },
+        };
+
+        // Merge them with those of the other peers
+        for (id, info) in peer_sets {
+            let info: DataInfo = localize(peer, info);
+            if info.access.is_empty() { continue; }
+            match datasets.get_mut(&id) {
+                Some(existing) => {
+                    existing.revisions.extend(info.revisions);
+                    existing.access.extend(info.access);
+                },
+                None           => { datasets.insert(id, info); },
+            }
+        }
+    }
+    datasets
+}
+
+/// Retrieves what the given peer instances know about the given dataset.
+/// 
+/// # Arguments
+/// - `context`: The Context that contains stuff we need to run.
+/// - `peers`: The peers to query.
+/// - `name`: The name of the dataset to query about.
+/// 
+/// # Returns
+/// A list with a DataInfo for every peer that knows the dataset. They only list locations hosted by the peers, as `<peer>:<location>`. Peers that we fail to reach are skipped.
+pub async fn get_data(context: &Context, peers: &HashMap<String, PeerConfig>, name: &str) -> Vec<DataInfo> {
+    let mut infos: Vec<DataInfo> = Vec::with_capacity(peers.len());
+    for (peer, config) in peers {
+        let path: String = format!("data/info/{}", name);
+        let body: String = match peer_get(context, peer, config, &path, &[]).await {
+            Ok(Some(body)) => body,
+            Ok(None)       => { continue; },
+            Err(err)       => {
+                warn!("{} (skipping peer)", err);
+                continue;
+            },
+        };
+        match serde_json::from_str(&body) {
+            Ok(info) => {
+                let info: DataInfo = localize(peer, info);
+                if !info.access.is_empty() { infos.push(info); }
+            },
+            Err(err) => {
+                debug!("Received body: \"\"\"{}\"\"\"", body);
+                warn!("{} (skipping peer)", Error::ResponseParseError{ peer: peer.clone(), address: format!("{}/{}", config.api, path), err });
+            },
+        }
+    }
+    infos
+}
+
+/// Searches the packages (including registered workflows) of the given peer instances.
+/// 
+/// # Arguments
+/// - `context`: The Context that contains stuff we need to run.
+/// - `peers`: The peers to query.
+/// - `term`: The term to search for.
+/// 
+/// # Returns
+/// A list of SearchResults, one per matching package or workflow version per peer. Peers that we fail to reach are skipped.
+pub async fn search_packages(context: &Context, peers: &HashMap<String, PeerConfig>, term: &str) -> Vec<SearchResult> {
+    let mut results: Vec<SearchResult> = vec![];
+    for (peer, config) in peers {
+        let body: String = match peer_get(context, peer, config, "search", &[ ("q", term) ]).await {
+            Ok(Some(body)) => body,
+            Ok(None)       => { continue; },
+            Err(err)       => {
+                warn!("{} (skipping peer)", err);
+                continue;
+            },
+        };
+        match serde_json::from_str::<SearchResults>(&body) {
+            Ok(peer_results) => { results.extend(peer_results.results.into_iter().filter(|r| r.kind != SearchKind::Dataset)); },
+            Err(err)         => {
+                debug!("Received body: \"\"\"{}\"\"\"", body);
+                warn!("{} (skipping peer)", Error::ResponseParseError{ peer: peer.clone(), address: format!("{}/search", config.api), err });
+            },
+        }
+    }
+    results
+}
+
+/// Resolves the address of the registry of a location hosted by a peer instance.
+/// 
+/// # Arguments
+/// - `context`: The Context that contains stuff we need to run.
+/// - `peer`: The name of the peer hosting the location.
+/// - `config`: The PeerConfig that describes how to reach the peer.
+/// - `location`: The name of the location within the peer.
+/// 
+/// # Returns
+/// The address of the location's registry, or `None` if the peer does not know the location.
+/// 
+/// # Errors
+/// This function errors if we failed to reach the peer or its response was ill-formed.
+pub async fn get_registry(context: &Context, peer: &str, config: &PeerConfig, location: &str) -> Result<Option<Address>, Error> {
+    let path: String = format!("infra/registries/{}", location);
+    let raw: String = match peer_get(context, peer, config, &path, &[]).await? {
+        Some(raw) => raw,
+        None      => { return Ok(None); },
+    };
+    match Address::from_str(raw.trim()) {
+        Ok(address) => Ok(Some(address)),
+        Err(err)    => Err(Error::RegistryParseError{ peer: peer.into(), address: format!("{}/{}", config.api, path), raw, err }),
+    }
+}
@@ -14,23 +14,280 @@
  *   Contains code for the health part of the brane API.
 **/
 
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use log::{debug, error, warn};
+use rdkafka::ClientConfig;
+use rdkafka::consumer::{BaseConsumer, Consumer as _};
+use scylla::Session;
+use tonic::transport::{Channel, Endpoint};
 use warp::reply::Response;
-use warp::http::HeaderValue;
+use warp::http::{HeaderValue, StatusCode};
 use warp::hyper::Body;
 use warp::{Reply, Rejection};
 
+use brane_cfg::infra::InfraFile;
+use brane_cfg::node::{CentralConfig, NodeConfig, PlannerTransport};
+use brane_prx::spec::NewPathRequestTlsOptions;
+use brane_tsk::grpc::{DriverServiceClient, ListSessionsRequest};
+use specifications::health::{ComponentHealth, InstanceHealth};
+
+pub use crate::errors::HealthError as Error;
+use crate::spec::Context;
+
+
+/***** CONSTANTS *****/
+/// How long we give a component to respond before we consider it unhealthy.
+pub const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Runs a single probe of a component, timing it (and giving up after `PROBE_TIMEOUT`).
+/// 
+/// # Arguments
+/// - `name`: The name of the component.
+/// - `probe`: The future that probes the component.
+/// 
+/// # Returns
+/// The ComponentHealth of the component.
+async fn time(name: impl Into<String>, probe: impl Future<Output = Result<(), Error>>) -> ComponentHealth {
+    let name: String = name.into();
+    let start: Instant = Instant::now();
+    let err: Error = match tokio::time::timeout(PROBE_TIMEOUT, probe).await {
+        Ok(Ok(()))   => { return ComponentHealth{ name, healthy: true, latency_ms: Some(start.elapsed().as_millis() as u64), error: None }; },
+        Ok(Err(err)) => err,
+        Err(_)       => Error::Timeout{ timeout: PROBE_TIMEOUT },
+    };
+    warn!("Component '{}' is unhealthy: {}", name, err);
+    let latency_ms: Option<u64> = if matches!(err, Error::RequestFailure{ .. }) { Some(start.elapsed().as_millis() as u64) } else { None };
+    ComponentHealth{ name, healthy: false, latency_ms, error: Some(err.to_string()) }
+}
+
+/// Probes a service that has a `/health` path with a plain GET-request.
+/// 
+/// # Arguments
+/// - `address`: The address of the service.
+/// 
+/// # Errors
+/// This function errors if the service did not respond with a success status code.
+async fn probe_http(address: String) -> Result<(), Error> {
+    let address: String = format!("{}/health", address);
+    let res: reqwest::Response = match reqwest::get(&address).await {
+        Ok(res)  => res,
+        Err(err) => { return Err(Error::RequestError{ address, err }); },
+    };
+    if !res.status().is_success() { return Err(Error::RequestFailure{ address, code: res.status() }); }
+    Ok(())
+}
+
+/// Probes the Scylla database with a trivial query.
+/// 
+/// # Arguments
+/// - `session`: The session with the database, or `None` if we are disconnected (i.e., in degraded mode).
+/// 
+/// # Errors
+/// This function errors if we are not connected or the query failed.
+async fn probe_scylla(session: Option<Arc<Session>>) -> Result<(), Error> {
+    let session: Arc<Session> = match session {
+        Some(session) => session,
+        None          => { return Err(Error::ScyllaUnavailable); },
+    };
+    match session.query("SELECT now() FROM system.local", &[]).await {
+        Ok(_)    => Ok(()),
+        Err(err) => Err(Error::ScyllaQueryError{ err }),
+    }
+}
+
+/// Probes the driver by listing its sessions.
+/// 
+/// # Arguments
+/// - `central`: The part of the node config that tells us where the driver lives.
+/// 
+/// # Errors
+/// This function errors if we failed to reach the driver.
+async fn probe_drv(central: &CentralConfig) -> Result<(), Error> {
+    let address: String = format!("http://{}:{}", central.names.drv, central.ports.drv.port());
+    let mut client: DriverServiceClient<Channel> = match DriverServiceClient::connect(address.clone()).await {
+        Ok(client) => client,
+        Err(err)   => { return Err(Error::GrpcConnectError{ address, err }); },
+    };
+    match client.list_sessions(ListSessionsRequest{}).await {
+        Ok(_)    => Ok(()),
+        Err(err) => Err(Error::GrpcRequestError{ address, err }),
+    }
+}
+
+/// Probes the planner. If it is reached over Kafka, this probes the Kafka brokers instead; otherwise, we connect to its gRPC service.
+/// 
+/// # Arguments
+/// - `central`: The part of the node config that tells us how the planner is reached.
+/// 
+/// # Errors
+/// This function errors if we failed to reach the planner (or the brokers).
+async fn probe_plr(central: &CentralConfig) -> Result<(), Error> {
+    match &central.planner {
+        PlannerTransport::Kafka => {
+            let brokers: String = central.services.brokers.iter().map(|a| a.to_string()).collect::<Vec<String>>().join(",");
+            let consumer: BaseConsumer = match ClientConfig::new().set("bootstrap.servers", &brokers).create() {
+                Ok(consumer) => consumer,
+                Err(err)     => { return Err(Error::KafkaError{ brokers, err }); },
+            };
+
+            // Fetching the metadata blocks, so do it in a separate thread
+            match tokio::task::spawn_blocking(move || consumer.fetch_metadata(None, PROBE_TIMEOUT).map(|_| ()).map_err(|err| Error::KafkaError{ brokers, err })).await {
+                Ok(res)  => res,
+                Err(err) => Err(Error::ProbePanicked{ err }),
+            }
+        },
+
+        PlannerTransport::Grpc{ address, .. } => {
+            let address: String = address.to_string();
+            let endpoint: Endpoint = match Endpoint::from_shared(address.clone()) {
+                Ok(endpoint) => endpoint,
+                Err(err)     => { return Err(Error::EndpointParseError{ address, err }); },
+            };
+            match endpoint.connect().await {
+                Ok(_)    => Ok(()),
+                Err(err) => Err(Error::GrpcConnectError{ address, err }),
+            }
+        },
+    }
+}
+
+/// Probes the registry of a worker domain through the proxy.
+/// 
+/// # Arguments
+/// - `context`: The Context that contains stuff we need to run.
+/// - `location`: The name of the location.
+/// - `registry`: The address of the location's registry.
+/// 
+/// # Errors
+/// This function errors if the registry did not respond with a success status code.
+async fn probe_registry(context: &Context, location: String, registry: String) -> Result<(), Error> {
+    let address: String = format!("{}/health", registry);
+    match context.proxy.get(&address, Some(NewPathRequestTlsOptions{ location, use_client_auth: false })).await {
+        Ok(Ok(res)) if res.status().is_success() => Ok(()),
+        Ok(Ok(res))                              => Err(Error::RequestFailure{ address, code: res.status() }),
+        Ok(Err(err))                             => Err(Error::RequestError{ address, err }),
+        Err(err)                                 => Err(Error::ProxyError{ err }),
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Handles a GET on the `/health` path, returning that this service is alive.
+/// 
+/// # Arguments
+/// - `context`: The Context that tells us whether the package database is available.
+/// 
+/// # Returns
+/// The response that can be send back to the client. Contains the string "OK!\n", or "DEGRADED!\n" if the package database is unavailable and we only serve cached package metadata.
+pub async fn handle(context: Context) -> Result<impl Reply, Rejection> {
+    let body: &'static str = if context.db.is_degraded() { "DEGRADED!\n" } else { "OK!\n" };
+    let mut response = Response::new(Body::from(body));
+
+    response.headers_mut().insert(
+        "Content-Length",
+        HeaderValue::from(body.len()),
+    );
+
+    Ok(response)
+}
+
+
+
+/// Probes the registry of a single worker domain.
+/// 
+/// # Arguments
+/// - `context`: The Context that contains stuff we need to run.
+/// - `location`: The name of the location.
+/// - `registry`: The address of the location's registry.
+/// 
+/// # Returns
+/// The ComponentHealth of the registry, named `registry/<location>`.
+pub async fn registry(context: &Context, location: String, registry: String) -> ComponentHealth {
+    time(format!("registry/{}", location), probe_registry(context, location, registry)).await
+}
+
+
+
+/// Handles a GET on the `/health/instance` path, returning the health of every component of the instance.
+/// 
+/// All components are polled at the same time, so the request takes at most `PROBE_TIMEOUT` (give or take).
+/// 
+/// # Arguments
+/// - `context`: The Context that contains stuff we need to run.
+/// 
+/// # Returns
+/// The response that can be send back to the client. Contains the InstanceHealth as JSON, with a 200 OK if all components are healthy or a 503 SERVICE UNAVAILABLE otherwise (so that monitoring may alert on partial outages by status code alone).
+/// 
+/// # Errors
+/// This function may error (i.e., reject the request) if we failed to load the node config or infrastructure file.
+pub async fn instance(context: Context) -> Result<impl Reply, Rejection> {
+    debug!("Handling GET on `/health/instance` (i.e., get the health of the instance)...");
+
+    // Load the node config file
+    let node_config: NodeConfig = match NodeConfig::from_path(&context.node_config_path) {
+        Ok(config) => config,
+        Err(err)   => {
+            error!("Failed to load NodeConfig file: {}", err);
+            return Err(warp::reject::custom(Error::SecretError));
+        },
+    };
+    if !node_config.node.is_central() {
+        error!("Provided node config file '{}' is not for a central node", context.node_config_path.display());
+        return Err(warp::reject::custom(Error::SecretError));
+    }
+    let central: &CentralConfig = node_config.node.central();
+
+    // Load the infrastructure file
+    let infra: InfraFile = match InfraFile::from_path(&central.paths.infra) {
+        Ok(infra) => infra,
+        Err(err)  => {
+            error!("Failed to open infrastructure file '{}': {}", central.paths.infra.display(), err);
+            return Err(warp::reject::custom(Error::SecretError));
+        },
+    };
+    let mut locations: Vec<(String, String)> = infra.into_iter().map(|(name, info)| (name, info.registry.to_string())).collect();
+    locations.sort();
+
+    // Poll everything at once
+    let (scylla, prx, drv, plr, registries): (ComponentHealth, ComponentHealth, ComponentHealth, ComponentHealth, Vec<ComponentHealth>) = futures::join!(
+        time("scylla", probe_scylla(context.db.session())),
+        time("prx", probe_http(node_config.services.prx.to_string())),
+        time("drv", probe_drv(central)),
+        time("plr", probe_plr(central)),
+        futures::future::join_all(locations.into_iter().map(|(location, address)| registry(&context, location, address))),
+    );
+    let health: InstanceHealth = InstanceHealth::new(vec![ scylla, prx, drv, plr ].into_iter().chain(registries).collect());
 
-///
-///
-///
-pub async fn handle() -> Result<impl Reply, Rejection> {
-    let mut response = Response::new(Body::from("OK!\n"));
+    // Serialize it
+    let body: String = match serde_json::to_string(&health) {
+        Ok(body) => body,
+        Err(err) => {
+            error!("{}", Error::SerializeError{ err });
+            return Err(warp::reject::custom(Error::SecretError));
+        },
+    };
+    let body_len: usize = body.len();
 
+    // Create the respones around it
+    let mut response = Response::new(Body::from(body));
+    if !health.healthy { *response.status_mut() = StatusCode::SERVICE_UNAVAILABLE; }
     response.headers_mut().insert(
         "Content-Length",
-        HeaderValue::from(4),
+        HeaderValue::from(body_len),
     );
 
+    // Done
     Ok(response)
 }
 
@@ -13,22 +13,103 @@
 // 
 
 use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
-use log::{debug, error};
+use log::{debug, error, warn};
 use warp::{Reply, Rejection};
 use warp::hyper::{Body, Response};
 use warp::hyper::header::HeaderValue;
 
 use brane_cfg::spec::Address;
-use brane_cfg::infra::{InfraFile, InfraLocation};
-use brane_cfg::node::NodeConfig;
+use brane_cfg::infra::{hash_config_file, split_peer_location, ConfigHashes, InfraFile, InfraLocation};
+use brane_cfg::node::{NodeConfig, PeerConfig};
+use brane_cfg::pins::{fingerprint, load_log_key, CaLog, SignedCaLog};
 use brane_prx::spec::NewPathRequestTlsOptions;
 use specifications::package::Capability;
+use specifications::registry::LocationAvailability;
 
 pub use crate::errors::InfraError as Error;
+use crate::federation;
 use crate::spec::Context;
 
 
+/***** HELPER FUNCTIONS *****/
+/// Collects the hashes that the configuration files of the given location (or the central node) are expected to have.
+/// 
+/// # Arguments
+/// - `loc`: The location that the hashes are asked of. If omitted, only the hash of the `infra.yml` file is returned.
+/// - `context`: The Context that contains stuff we need to run.
+/// 
+/// # Returns
+/// A response that can be send to client. Specifically, it will contain the serialized `ConfigHashes`.
+/// 
+/// # Errors
+/// This function may error (i.e., reject the request) if we failed to load or hash the infrastructure file.
+async fn config_hashes(loc: Option<String>, context: Context) -> Result<Response<Body>, Rejection> {
+    // Load the node config file
+    let node_config: NodeConfig = match NodeConfig::from_path(&context.node_config_path) {
+        Ok(config) => config,
+        Err(err)   => {
+            error!("Failed to load NodeConfig file: {}", err);
+            return Err(warp::reject::custom(Error::SecretError));
+        },
+    };
+    if !node_config.node.is_central() {
+        error!("Provided node config file '{}' is not for a central node", context.node_config_path.display());
+        return Err(warp::reject::custom(Error::SecretError));
+    }
+
+    // Load the infrastructure file
+    let infra_path: &Path = &node_config.node.central().paths.infra;
+    let infra: InfraFile = match InfraFile::from_path(infra_path) {
+        Ok(infra) => infra,
+        Err(err)  => {
+            error!("{}", Error::InfrastructureOpenError{ path: infra_path.into(), err });
+            return Err(warp::reject::custom(Error::SecretError));
+        },
+    };
+
+    // Collect the hashes: the infra file is shared by everyone, the rest is per-location
+    let mut hashes: ConfigHashes = match loc {
+        Some(loc) => match infra.get(&loc) {
+            Some(info) => ConfigHashes{ infra: None, ..info.expected.clone() },
+            None       => { return Err(warp::reject::not_found()); },
+        },
+        None => ConfigHashes::default(),
+    };
+    hashes.infra = match hash_config_file(infra_path) {
+        Ok(hash) => Some(hash),
+        Err(err) => {
+            error!("{}", Error::InfrastructureHashError{ err });
+            return Err(warp::reject::custom(Error::SecretError));
+        },
+    };
+
+    // Serialize them
+    let body: String = match serde_json::to_string(&hashes) {
+        Ok(body) => body,
+        Err(err) => {
+            error!("{}", Error::SerializeError{ what: "configuration hashes", err });
+            return Err(warp::reject::custom(Error::SecretError));
+        }
+    };
+    let body_len: usize = body.len();
+
+    // Create the respones around it
+    let mut response = Response::new(Body::from(body));
+    response.headers_mut().insert(
+        "Content-Length",
+        HeaderValue::from(body_len),
+    );
+
+    // Done
+    Ok(response)
+}
+
+
+
+
+
 /***** LIBRARY *****/
 /// Lists the registries at each location.
 /// 
@@ -97,14 +178,14 @@ pub async fn registries(context: Context) -> Result<impl Reply, Rejection> {
 /// Returns the registry address for the requested location.
 /// 
 /// # Arguments
-/// - `loc`: The location that the address is asked of.
+/// - `loc`: The location that the address is asked of. If it is hosted by a peer instance (i.e., `<peer>:<location>`), the peer is asked instead.
 /// - `context`: The Context that contains stuff we need to run.
 /// 
 /// # Returns
 /// A response that can be send to client. Specifically, it will contains the address of the registry as plain text.
 /// 
 /// # Errors
-/// This function may error (i.e., reject the request) if we failed to load the infrastructure file.
+/// This function may error (i.e., reject the request) if we failed to load the infrastructure file or to reach the peer hosting the location.
 pub async fn get_registry(loc: String, context: Context) -> Result<impl Reply, Rejection> {
     debug!("Handling GET on `/infra/registries/{}` (i.e., get location registry address)...", loc);
 
@@ -130,14 +211,31 @@ pub async fn get_registry(loc: String, context: Context) -> Result<impl Reply, R
         },
     };
 
-    // Find the location requested
-    let info: &InfraLocation = match infra.get(&loc) {
-        Some(info) => info,
-        None       => { return Err(warp::reject::not_found()); },
+    // Find the location requested, either in our own infrastructure or in that of a peer
+    let registry: Address = match infra.get(&loc) {
+        Some(info) => info.registry.clone(),
+        None       => {
+            let (peer, peer_loc): (&str, &str) = match split_peer_location(&loc) {
+                Some(split) => split,
+                None        => { return Err(warp::reject::not_found()); },
+            };
+            let config: &PeerConfig = match node_config.node.central().peers.get(peer) {
+                Some(config) => config,
+                None         => { return Err(warp::reject::not_found()); },
+            };
+            match federation::get_registry(&context, peer, config, peer_loc).await {
+                Ok(Some(registry)) => registry,
+                Ok(None)           => { return Err(warp::reject::not_found()); },
+                Err(err)           => {
+                    error!("{}", err);
+                    return Err(warp::reject::custom(Error::SecretError));
+                },
+            }
+        },
     };
 
     // Create a body with the registry
-    let body     : String = info.registry.serialize().to_string();
+    let body     : String = registry.serialize().to_string();
     let body_len : usize  = body.len();
 
     // Create the respones around it
@@ -223,13 +321,15 @@ pub async fn get_capabilities(loc: String, context: Context) -> Result<impl Repl
             return Err(warp::reject::custom(Error::SecretError));
         },
     };
-    let capabilities : HashSet<Capability> = match serde_json::from_str(&capabilities) {
+    let mut capabilities : HashSet<Capability> = match serde_json::from_str(&capabilities) {
         Ok(caps) => caps,
         Err(err) => {
             error!("{}", Error::ResponseParseError{ address: reg_addr, raw: capabilities, err });
             return Err(warp::reject::custom(Error::SecretError));
         },
     };
+    // Add those that the administrator advertises for it in the infrastructure file
+    capabilities.extend(info.capabilities.iter().copied());
 
     // Create a body with the registry (re-serialize for full correctness)
     let body     : String = match serde_json::to_string(&capabilities) { Ok(body) => body, Err(err) => { error!("{}", Error::CapabilitiesSerializeError{ err }); return Err(warp::reject::custom(Error::SecretError)); }, };
@@ -245,3 +345,214 @@ pub async fn get_capabilities(loc: String, context: Context) -> Result<impl Repl
     // Done
     Ok(response)
 }
+
+
+
+/// Returns what the requested location has available locally (i.e., its capabilities and the packages and datasets it has cached).
+/// 
+/// # Arguments
+/// - `loc`: The location that the availability is asked of.
+/// - `context`: The Context that contains stuff we need to run.
+/// 
+/// # Returns
+/// A response that can be send to client. Specifically, it will contain the location's `LocationAvailability`, with the capabilities the administrator advertises for it added.
+/// 
+/// # Errors
+/// This function may error (i.e., reject the request) if we failed to load the infrastructure file or contact the requested domain.
+pub async fn get_availability(loc: String, context: Context) -> Result<impl Reply, Rejection> {
+    debug!("Handling GET on `/infra/availability/{}` (i.e., get location availability)...", loc);
+
+    // Load the node config file
+    let node_config: NodeConfig = match NodeConfig::from_path(&context.node_config_path) {
+        Ok(config) => config,
+        Err(err)   => {
+            error!("Failed to load NodeConfig file: {}", err);
+            return Err(warp::reject::custom(Error::SecretError));
+        },
+    };
+    if !node_config.node.is_central() {
+        error!("Provided node config file '{}' is not for a central node", context.node_config_path.display());
+        return Err(warp::reject::custom(Error::SecretError));
+    }
+
+    // Load the infrastructure file
+    let infra: InfraFile = match InfraFile::from_path(&node_config.node.central().paths.infra) {
+        Ok(infra) => infra,
+        Err(err)  => {
+            error!("{}", Error::InfrastructureOpenError{ path: node_config.node.central().paths.infra.clone(), err });
+            return Err(warp::reject::custom(Error::SecretError));
+        },
+    };
+
+    // Find the location requested
+    let info: &InfraLocation = match infra.get(&loc) {
+        Some(info) => info,
+        None       => { return Err(warp::reject::not_found()); },
+    };
+
+    // Ask the location what it has
+    let reg_addr: String = format!("{}/infra/availability", info.registry);
+    let res: reqwest::Response = match context.proxy.get(&reg_addr, Some(NewPathRequestTlsOptions{ use_client_auth: false, location: loc })).await {
+        Ok(res)  => match res {
+            Ok(res)  => res,
+            Err(err) => {
+                error!("{}", Error::RequestError{ address: reg_addr, err });
+                return Err(warp::reject::custom(Error::SecretError));
+            },
+        },
+        Err(err) => {
+            error!("{}", Error::ProxyError{ err });
+            return Err(warp::reject::custom(Error::SecretError));
+        },
+    };
+    if !res.status().is_success() {
+        error!("{}", Error::RequestFailure{ address: reg_addr, code: res.status(), message: res.text().await.ok() });
+        return Err(warp::reject::custom(Error::SecretError));
+    }
+
+    // Parse the body as the proper JSON
+    let availability : String               = match res.text().await {
+        Ok(availability) => availability,
+        Err(err)         => {
+            error!("{}", Error::ResponseBodyError{ address: reg_addr, err });
+            return Err(warp::reject::custom(Error::SecretError));
+        },
+    };
+    let mut availability : LocationAvailability = match serde_json::from_str(&availability) {
+        Ok(availability) => availability,
+        Err(err)         => {
+            error!("{}", Error::ResponseParseError{ address: reg_addr, raw: availability, err });
+            return Err(warp::reject::custom(Error::SecretError));
+        },
+    };
+    // Add the capabilities that the administrator advertises for it in the infrastructure file
+    availability.capabilities.extend(info.capabilities.iter().copied());
+
+    // Create a body with the availability (re-serialize for full correctness)
+    let body     : String = match serde_json::to_string(&availability) { Ok(body) => body, Err(err) => { error!("{}", Error::SerializeError{ what: "location availability", err }); return Err(warp::reject::custom(Error::SecretError)); }, };
+    let body_len : usize  = body.len();
+
+    // Create the respones around it
+    let mut response = Response::new(Body::from(body));
+    response.headers_mut().insert(
+        "Content-Length",
+        HeaderValue::from(body_len),
+    );
+
+    // Done
+    Ok(response)
+}
+
+
+
+/// Returns the hash of the infrastructure file as it is known to the central node.
+/// 
+/// # Arguments
+/// - `context`: The Context that contains stuff we need to run.
+/// 
+/// # Returns
+/// A response that can be send to client. Specifically, it will contain a `ConfigHashes` with only the `infra` hash.
+/// 
+/// # Errors
+/// This function may error (i.e., reject the request) if we failed to load or hash the infrastructure file.
+#[inline]
+pub async fn config(context: Context) -> Result<impl Reply, Rejection> {
+    debug!("Handling GET on `/infra/config` (i.e., get central configuration hashes)...");
+    config_hashes(None, context).await
+}
+
+/// Returns the hashes that the configuration files of the requested location are expected to have, such that a node can detect drift.
+/// 
+/// # Arguments
+/// - `loc`: The location that the hashes are asked of.
+/// - `context`: The Context that contains stuff we need to run.
+/// 
+/// # Returns
+/// A response that can be send to client. Specifically, it will contain a `ConfigHashes` with the hash of the infrastructure file and any expected hashes for the location's `backend.yml` and `policies.yml`.
+/// 
+/// # Errors
+/// This function may error (i.e., reject the request) if we failed to load or hash the infrastructure file.
+#[inline]
+pub async fn get_config(loc: String, context: Context) -> Result<impl Reply, Rejection> {
+    debug!("Handling GET on `/infra/config/{}` (i.e., get location configuration hashes)...", loc);
+    config_hashes(Some(loc), context).await
+}
+
+
+
+/// Returns the signed log of the root certificates of every location and peer, recording any newly seen ones first.
+/// 
+/// # Arguments
+/// - `context`: The Context that contains stuff we need to run.
+/// 
+/// # Returns
+/// A response that can be send to client. Specifically, it will contain the serialized `SignedCaLog`.
+/// 
+/// # Errors
+/// This function may error (i.e., reject the request) if we failed to load the infrastructure file or to load, update or sign the log.
+pub async fn ca_log(context: Context) -> Result<impl Reply, Rejection> {
+    debug!("Handling GET on `/infra/ca-log` (i.e., get CA transparency log)...");
+
+    // Load the node config file
+    let node_config: NodeConfig = match NodeConfig::from_path(&context.node_config_path) {
+        Ok(config) => config,
+        Err(err)   => {
+            error!("Failed to load NodeConfig file: {}", err);
+            return Err(warp::reject::custom(Error::SecretError));
+        },
+    };
+    if !node_config.node.is_central() {
+        error!("Provided node config file '{}' is not for a central node", context.node_config_path.display());
+        return Err(warp::reject::custom(Error::SecretError));
+    }
+
+    // Load the infrastructure file
+    let infra_path: &Path = &node_config.node.central().paths.infra;
+    let infra: InfraFile = match InfraFile::from_path(infra_path) {
+        Ok(infra) => infra,
+        Err(err)  => {
+            error!("{}", Error::InfrastructureOpenError{ path: infra_path.into(), err });
+            return Err(warp::reject::custom(Error::SecretError));
+        },
+    };
+
+    // Load the log and record the current certificates of every location and peer in it
+    let certs: &Path = &node_config.paths.certs;
+    let log_path: PathBuf = certs.join(CaLog::FILENAME);
+    let mut log: CaLog = match CaLog::from_path(&log_path) {
+        Ok(log)  => log,
+        Err(err) => {
+            error!("{}", Error::CaLogError{ err });
+            return Err(warp::reject::custom(Error::SecretError));
+        },
+    };
+    let mut changed: bool = false;
+    for name in infra.iter().map(|(name, _)| name).chain(node_config.node.central().peers.keys()) {
+        match fingerprint(certs.join(name).join("ca.pem")) {
+            Ok(fingerprint) => { changed |= log.append(name, fingerprint); },
+            Err(err)        => { warn!("Not recording CA certificate of '{}' in the CA log: {}", name, err); },
+        }
+    }
+    if changed {
+        if let Err(err) = log.to_path(&log_path) {
+            error!("{}", Error::CaLogError{ err });
+            return Err(warp::reject::custom(Error::SecretError));
+        }
+    }
+
+    // Sign it
+    let log: SignedCaLog = match load_log_key(certs.join(CaLog::KEY_FILENAME)) {
+        Ok(key)  => log.sign(&key),
+        Err(err) => {
+            error!("{}", Error::CaLogError{ err });
+            return Err(warp::reject::custom(Error::SecretError));
+        },
+    };
+    match serde_json::to_string(&log) {
+        Ok(body) => Ok(body),
+        Err(err) => {
+            error!("{}", Error::SerializeError{ what: "CA log", err });
+            Err(warp::reject::custom(Error::SecretError))
+        },
+    }
+}
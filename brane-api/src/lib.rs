@@ -16,9 +16,19 @@
 // Declare modules
 pub mod errors;
 pub mod spec;
+pub mod db;
 pub mod health;
 pub mod version;
 pub mod infra;
 pub mod packages;
+pub mod scan;
 pub mod data;
+pub mod search;
+pub mod federation;
+pub mod dashboard;
+pub mod events;
+pub mod approvals;
+pub mod openapi;
+pub mod server;
+pub mod routes;
 pub mod schema;
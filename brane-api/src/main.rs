@@ -14,26 +14,19 @@
 
 use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::Duration;
 
 use clap::Parser;
 use dotenvy::dotenv;
-use juniper::EmptySubscription;
-use log::{debug, error, info, LevelFilter};
-use scylla::{Session, SessionBuilder};
-use warp::Filter;
+use log::{debug, error, info, warn, LevelFilter};
 
 use brane_cfg::node::NodeConfig;
 use brane_prx::client::ProxyClient;
+use brane_shr::otel;
 
-use brane_api::errors::ApiError;
+use brane_api::db::{self, Database};
 use brane_api::spec::Context;
-use brane_api::schema::{Mutations, Query, Schema};
-use brane_api::health;
-use brane_api::version;
-use brane_api::infra;
-use brane_api::data;
-use brane_api::packages;
+use brane_api::routes;
+use brane_api::server;
 
 
 /***** ARGUMENTS *****/
@@ -80,117 +73,49 @@ async fn main() {
         },
     };
     if !node_config.node.is_central() { error!("Given NodeConfig file '{}' does not have properties for a worker node.", opts.node_config_path.display()); std::process::exit(1); }
+    info!("Enabled features: {}", node_config.features);
 
-    // Configure Scylla.
-    debug!("Connecting to scylla...");
-    let scylla = match SessionBuilder::new()
-        .known_node(&node_config.node.central().services.scylla.to_string())
-        .connection_timeout(Duration::from_secs(3))
-        .build()
-        .await
-    {
-        Ok(scylla)  => scylla,
-        Err(reason) => { error!("{}", ApiError::ScyllaConnectError{ host: node_config.node.central().services.scylla.clone(), err: reason }); std::process::exit(-1); }
-    };
-    debug!("Connected successfully.");
+    // Set up tracing, exporting spans if the node is configured to
+    if let Err(err) = otel::init("brane-api", node_config.tracing.as_ref()) { warn!("{} (spans will not be exported)", err); }
 
-    debug!("Ensuring keyspace & database...");
-    if let Err(err) = ensure_db_keyspace(&scylla).await { error!("Failed to ensure database keyspace: {}", err) };
-    if let Err(err) = packages::ensure_db_table(&scylla).await { error!("Failed to ensure database table: {}", err) };
+    // Configure Scylla, falling back to a read-only degraded mode if it stays unavailable
+    let cache_path: Option<PathBuf> = if node_config.features.enable_cache { Some(node_config.paths.packages.join(db::CACHE_FILE)) } else { None };
+    let db: Arc<Database> = Arc::new(Database::new(&node_config.node.central().services.scylla, cache_path));
+    if !db.connect(Some(db::STARTUP_ATTEMPTS)).await {
+        warn!("Scylla database is unavailable; starting in read-only degraded mode (serving cached packages) until it is back");
+        db.reconnect_in_background();
+    }
 
-    // Configure Juniper.
+    // Configure the context and the routes
     let node_config_path : PathBuf          = opts.node_config_path;
-    let scylla                              = Arc::new(scylla);
     let proxy            : Arc<ProxyClient> = Arc::new(ProxyClient::new(node_config.services.prx));
-    let context = warp::any().map(move || Context {
-        node_config_path : node_config_path.clone(),
-        scylla           : scylla.clone(),
-        proxy            : proxy.clone(),
+    let routes = routes::routes(Context {
+        node_config_path,
+        db,
+        proxy,
     });
 
-    let schema = Schema::new(Query {}, Mutations {}, EmptySubscription::new());
-    let graphql_filter = juniper_warp::make_graphql_filter(schema, context.clone().boxed());
-    let graphql = warp::path("graphql").and(graphql_filter);
-
-    // Configure Warp.
-    // Configure the data one
-    let list_datasets = warp::path("data")
-        .and(warp::path("info"))
-        .and(warp::path::end())
-        .and(warp::get())
-        .and(context.clone())
-        .and_then(data::list);
-    let get_dataset = warp::path("data")
-        .and(warp::path("info"))
-        .and(warp::path::param())
-        .and(warp::path::end())
-        .and(warp::get())
-        .and(context.clone())
-        .and_then(data::get);
-    let data = list_datasets.or(get_dataset);
-
-    // Configure the packages one
-    let download_package = warp::path("packages")
-        .and(warp::get())
-        .and(warp::path::param())
-        .and(warp::path::param())
-        .and(warp::path::end())
-        .and(context.clone())
-        .and_then(packages::download);
-    let upload_package = warp::path("packages")
-        .and(warp::path::end())
-        .and(warp::post())
-        .and(warp::filters::body::stream())
-        .and(context.clone())
-        .and_then(packages::upload);
-    let packages = download_package.or(upload_package);
-
-    // Configure infra
-    let list_registries = warp::get()
-        .and(warp::path("infra"))
-        .and(warp::path("registries"))
-        .and(warp::path::end())
-        .and(context.clone())
-        .and_then(infra::registries);
-    let get_registry = warp::get()
-        .and(warp::path("infra"))
-        .and(warp::path("registries"))
-        .and(warp::path::param())
-        .and(warp::path::end())
-        .and(context.clone())
-        .and_then(infra::get_registry);
-    let get_capabilities = warp::get()
-        .and(warp::path("infra"))
-        .and(warp::path("capabilities"))
-        .and(warp::path::param())
-        .and(warp::path::end())
-        .and(context.clone())
-        .and_then(infra::get_capabilities);
-    let infra = get_registry.or(list_registries.or(get_capabilities));
-    
-    // Configure the health & version
-    let health = warp::path("health")
-        .and(warp::path::end())
-        .and_then(health::handle);
-    let version = warp::path("version")
-        .and(warp::path::end())
-        .and_then(version::handle);
-
-    // Construct the final routes
-    let routes = data.or(packages.or(infra.or(health.or(version.or(graphql))))).with(warp::log("brane-api"));
-
-    // Run the server
-    warp::serve(routes).run(node_config.node.central().ports.api).await;
-}
+    // Run the server until we are asked to stop; if TLS is configured, we only serve in plaintext on the internal address (if any)
+    match node_config.node.central().tls.clone() {
+        Some(tls) => {
+            if let Some(internal) = tls.internal_api {
+                info!("Serving in plaintext on internal address '{}'", internal);
+                let (_, server) = warp::serve(routes.clone()).bind_with_graceful_shutdown(internal, otel::shutdown_signal());
+                tokio::spawn(server);
+            }
+            if let Some(Err(err)) = otel::until_shutdown(server::serve_tls(&node_config.paths.certs, &tls, routes)).await {
+                error!("Failed to serve over TLS: {}", err);
+                otel::shutdown().await;
+                std::process::exit(1);
+            }
+        },
 
-///
-///
-///
-pub async fn ensure_db_keyspace(scylla: &Session) -> Result<scylla::QueryResult, scylla::transport::errors::QueryError> {
-    let query = r#"
-        CREATE KEYSPACE IF NOT EXISTS brane
-        WITH replication = {'class': 'SimpleStrategy', 'replication_factor' : 1};
-    "#;
+        None => {
+            let (_, server) = warp::serve(routes).bind_with_graceful_shutdown(node_config.node.central().ports.api, otel::shutdown_signal());
+            server.await;
+        },
+    }
 
-    scylla.query(query, &[]).await
+    // Export the spans of the last requests before we go
+    otel::shutdown().await;
 }
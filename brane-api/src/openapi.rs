@@ -0,0 +1,45 @@
+//  OPENAPI.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 12:59:49
+//  Last edited:
+//    16 Oct 2026, 12:59:49
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Handles the `/openapi.json` path, which serves the OpenAPI document
+//!   of the REST routes of the API (as generated at build time from
+//!   `specifications::openapi`).
+// 
+
+use log::debug;
+use warp::{Rejection, Reply};
+use warp::http::{HeaderValue, Response};
+use warp::hyper::Body;
+
+
+/***** CONSTANTS *****/
+/// The OpenAPI document, as generated by the build script.
+pub const DOCUMENT: &str = include_str!(concat!(env!("OUT_DIR"), "/openapi.json"));
+
+
+
+
+
+/***** LIBRARY *****/
+/// Serves the OpenAPI document of the API.
+///
+/// # Returns
+/// A response that can be send to client. Specifically, it will contain the OpenAPI (3.0) document as JSON.
+///
+/// # Errors
+/// This function doesn't typically error.
+pub async fn handle() -> Result<impl Reply, Rejection> {
+    debug!("Handling GET on `/openapi.json` (i.e., get the OpenAPI document)...");
+    let mut response = Response::new(Body::from(DOCUMENT));
+    response.headers_mut().insert("Content-Type", HeaderValue::from_static("application/json"));
+    response.headers_mut().insert("Content-Length", HeaderValue::from(DOCUMENT.len()));
+    Ok(response)
+}
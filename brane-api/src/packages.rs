@@ -26,6 +26,7 @@ use rand::distributions::Alphanumeric;
 use scylla::cql_to_rust::FromCqlVal;
 use scylla::macros::{FromUserType, IntoUserType};
 use scylla::Session;
+use serde::{Deserialize, Serialize};
 // use tar::Archive;
 use tempfile::TempDir;
 use tokio::fs as tfs;
@@ -39,14 +40,27 @@ use warp::hyper::body::{Bytes, Sender};
 use warp::reply::Response;
 use warp::{http::StatusCode, Rejection, Reply};
 
-use brane_cfg::node::{NodeConfig, NodeKind};
+use brane_cfg::node::{NodeConfig, NodeKind, ScanAction};
+use specifications::arch::{Arch, ManifestList};
 use specifications::package::PackageInfo;
+use specifications::registry::ImageRegistryInfo;
 use specifications::version::Version;
 
 pub use crate::errors::PackageError as Error;
+use crate::db::{self, CachedPackage};
+use crate::errors::DatabaseError;
+use crate::scan::{self, ScanReport};
 use crate::spec::Context;
 
 
+/***** CONSTANTS *****/
+/// The prefix of the file of packages whose image lives in the OCI registry instead of with us. The rest of the file is the image's reference in the registry.
+const REGISTRY_PREFIX: &str = "oci://";
+
+
+
+
+
 /***** HELPER MACROS *****/
 /// Macro that early quits from a warp function by printing the error and then returning a 500.
 macro_rules! fail {
@@ -90,7 +104,7 @@ macro_rules! fail {
 
 /***** AUXILLARY STRUCTS *****/
 /// Defines the contents of a single Scylla database row that describes a package.
-#[derive(Clone, IntoUserType, FromUserType)]
+#[derive(Clone, Deserialize, IntoUserType, FromUserType, Serialize)]
 pub struct PackageUdt {
     pub created: i64,
     pub description: String,
@@ -147,6 +161,54 @@ impl TryFrom<PackageInfo> for PackageUdt {
 
 
 /***** AUXILLARY FUNCTIONS *****/
+/// Returns the path of the manifest list that is stored next to the given (default) image of a package.
+/// 
+/// # Arguments
+/// - `file`: The path of the default image, as stored in the database.
+/// 
+/// # Returns
+/// The path of the manifest list. Only packages that have been built for multiple architectures have one.
+#[inline]
+fn manifest_path(file: &Path) -> PathBuf { file.with_extension("yml") }
+
+/// Returns the path of the image for a non-default architecture that is stored next to the given (default) image of a package.
+/// 
+/// # Arguments
+/// - `file`: The path of the default image, as stored in the database.
+/// - `arch`: The architecture of the image to return the path of.
+/// 
+/// # Returns
+/// The path of the image, as `<name>-<version>-<arch>.tar`.
+#[inline]
+fn arch_path(file: &Path, arch: Arch) -> PathBuf {
+    file.with_file_name(format!("{}-{}.tar", file.file_stem().map(|s| s.to_string_lossy()).unwrap_or_default(), arch))
+}
+
+/// Returns the reference of a package's image in the OCI registry, if it lives there instead of with us.
+/// 
+/// # Arguments
+/// - `file`: The file of the package, as stored in the database.
+/// 
+/// # Returns
+/// The reference of the image (as `<address>/<name>:<version>`), or `None` if the file is a local image file.
+#[inline]
+fn registry_reference(file: &Path) -> Option<&str> { file.to_str().and_then(|file| file.strip_prefix(REGISTRY_PREFIX)) }
+
+/// Returns whether the given entry of an uploaded package archive is the image for a non-default architecture (i.e., `image-<arch>.tar`).
+/// 
+/// # Arguments
+/// - `entry`: The path of the entry in the archive.
+/// 
+/// # Returns
+/// True if it is, or false otherwise.
+#[inline]
+fn is_arch_image(entry: &Path) -> bool {
+    let entry: Cow<str> = entry.to_string_lossy();
+    entry.starts_with("image-") && entry.ends_with(".tar") && !entry.contains('/')
+}
+
+
+
 /// Ensures that the packages table is present in the given Scylla database.
 /// 
 /// # Arguments
@@ -239,43 +301,90 @@ async fn insert_package_into_db(scylla: &Arc<Session>, package: &PackageInfo, pa
 
 
 /***** LIBRARY *****/
+/// Defines the query parameters of the `/packages/<name>/<version>` path.
+#[derive(Clone, Debug, Deserialize)]
+pub struct DownloadQuery {
+    /// The architecture for which to download the image. If omitted, the default image of the package is returned.
+    #[serde(default)]
+    pub arch : Option<Arch>,
+}
+
+/// Reports the OCI registry in which package images may be stored instead of in this service, such that clients know where to push them.
+/// 
+/// # Arguments
+/// - `context`: The Context that describes some properties of the running environment, such as where to find the node config file.
+/// 
+/// # Returns
+/// A reply with as body the ImageRegistryInfo that describes the registry.
+/// 
+/// # Errors
+/// This function errors (with a 404) if this instance does not use an OCI registry, or if we failed to load the node config.
+pub async fn registry(context: Context) -> Result<impl Reply, Rejection> {
+    info!("Handling GET on '/packages/registry' (i.e., get OCI registry)");
+
+    // Find the registry in the node config
+    let node_config: NodeConfig = match NodeConfig::from_path(&context.node_config_path) {
+        Ok(config) => config,
+        Err(err)   => { fail!(Error::NodeConfigLoadError{ err }); },
+    };
+    let info: ImageRegistryInfo = match node_config.image_registry {
+        Some(registry) => ImageRegistryInfo{ address: registry.address },
+        None           => {
+            debug!("No OCI registry configured");
+            return Err(warp::reject::not_found());
+        },
+    };
+
+    // Serialize it
+    let body: String = match serde_json::to_string(&info) {
+        Ok(body) => body,
+        Err(err) => { fail!(Error::RegistryInfoSerializeError{ err }); },
+    };
+    let body_len: usize = body.len();
+    let mut response: Response = Response::new(Body::from(body));
+    response.headers_mut().insert(
+        "Content-Length",
+        HeaderValue::from(body_len),
+    );
+    Ok(response)
+}
+
 /// Downloads a file from the `brane-api` "registry" to the client.
 /// 
 /// # Arguments
 /// - `name`: The name of the package (container) to download.
 /// - `version`: The version of the package (container) to download. May be 'latest'.
+/// - `query`: The DownloadQuery that determines for which architecture to download the image.
 /// - `context`: The Context that describes some properties of the running environment, such as the location where the container images are stored.
 /// 
 /// # Returns
 /// A reply with as body the container archive. This archive will likely not be compressed (for now).
 /// 
 /// # Errors
-/// This function errors if resolving a 'latest' version failed, the requested package/version pair did not exist, the Scylla database was unreachable or we failed to read the image file.
-pub async fn download(name: String, version: String, context: Context) -> Result<impl Reply, Rejection> {
+/// This function errors if resolving a 'latest' version failed, the requested package/version pair did not exist (or has not been built for the requested architecture, or its image lives in the OCI registry) or we failed to read the image file. If the Scylla database is unreachable, the package is served from the cache instead; only if there is no cache, a 503 is returned.
+pub async fn download(name: String, version: String, query: DownloadQuery, context: Context) -> Result<impl Reply, Rejection> {
     info!("Handling GET on '/packages/{}/{}' (i.e., pull package)", name, version);
 
-    // Attempt to resolve the version from the Scylla database in the context
+    // Get the known versions of the package from the Scylla database in the context (or the cache, if it is unavailable)
+    let known: Vec<CachedPackage> = match context.db.packages().await {
+        Ok(packages)                    => packages.into_iter().filter(|p| p.package.name == name).collect(),
+        Err(DatabaseError::Unavailable) => { return Ok(db::unavailable()); },
+        Err(err)                        => { fail!(Error::DbError{ err }); },
+    };
+
+    // Attempt to resolve the version
     debug!("Resolving version '{}'...", version);
     let version: Version = if version.to_lowercase() == "latest" {
-        let versions = match context.scylla.query("SELECT version FROM brane.packages WHERE name=?", vec![ &name ]).await {
-            Ok(versions) => versions,
-            Err(err)     => { fail!(Error::VersionsQueryError{ name, err }); },
-        };
         let mut latest: Option<Version> = None;
-        if let Some(rows) = versions.rows {
-            for row in rows {
-                // Get the string value
-                let version: &str = row.columns[0].as_ref().unwrap().as_text().unwrap();
-
-                // Attempt to parse
-                let version: Version = match Version::from_str(version) {
-                    Ok(version) => version,
-                    Err(err)    => { fail!(Error::VersionParseError{ raw: version.into(), err }); }
-                };
-
-                // Finally, find the most recent one
-                if latest.is_none() || version > *latest.as_ref().unwrap() { latest = Some(version); }
-            }
+        for package in &known {
+            // Attempt to parse
+            let version: Version = match Version::from_str(&package.package.version) {
+                Ok(version) => version,
+                Err(err)    => { fail!(Error::VersionParseError{ raw: package.package.version.clone(), err }); }
+            };
+
+            // Find the most recent one
+            if latest.is_none() || version > *latest.as_ref().unwrap() { latest = Some(version); }
         }
 
         // Error if none was found
@@ -293,21 +402,41 @@ pub async fn download(name: String, version: String, context: Context) -> Result
         }
     };
 
-    // With the version resolved, query the filename
+    // With the version resolved, find the filename
     debug!("Retrieving filename for package '{}'@{}", name, version);
-    let file: PathBuf = match context.scylla.query("SELECT file FROM brane.packages WHERE name=? AND version=?", vec![ &name, &version.to_string() ]).await {
-        Ok(file) => if let Some(rows) = file.rows {
-            if rows.is_empty() {
-                error!("{}", Error::UnknownPackage{ name, version });
-                return Err(warp::reject::not_found());
-            }
-            if rows.len() > 1  { panic!("Database contains {} entries with the same name & version ('{}' & '{}')", rows.len(), name, version); }
-            rows[0].columns[0].as_ref().unwrap().as_text().unwrap().into()
-        } else {
+    let sversion: String = version.to_string();
+    let file: PathBuf = match known.into_iter().find(|p| p.package.version == sversion) {
+        Some(package) => package.file,
+        None          => {
             error!("{}", Error::UnknownPackage{ name, version });
             return Err(warp::reject::not_found());
         },
-        Err(err) => { fail!(Error::PathQueryError{ name, version,err }); },
+    };
+
+    // Images that live in the OCI registry have to be pulled from there
+    if let Some(reference) = registry_reference(&file) {
+        error!("{}", Error::ImageInRegistry{ name, version, reference: reference.into() });
+        return Err(warp::reject::not_found());
+    }
+
+    // Select the image for the requested architecture
+    let file: PathBuf = match query.arch {
+        Some(arch) if manifest_path(&file).exists() => {
+            let list: ManifestList = match ManifestList::from_path(manifest_path(&file)) {
+                Ok(list) => list,
+                Err(err) => { fail!(Error::ManifestListReadError{ err }); },
+            };
+            match list.images.iter().position(|i| i.arch == arch) {
+                Some(0) => file,
+                Some(_) => arch_path(&file, arch),
+                None    => {
+                    error!("{}", Error::UnsupportedArch{ name, version, arch });
+                    return Err(warp::reject::not_found());
+                },
+            }
+        },
+        // Packages without a manifest list have only been built for a single architecture, so we cannot do better than the default image
+        _ => file,
     };
 
     // Retrieve the size of the file for the content length
@@ -370,7 +499,7 @@ pub async fn download(name: String, version: String, context: Context) -> Result
 /// The Warp reply that contains the status code of the thing (e.g., OK if everything went fine).
 /// 
 /// # Errors
-/// This function errors if we fail to either write the package info to the Scylla database or the package archive to the local filesystem.
+/// This function errors if we fail to either write the package info to the Scylla database or the package archive to the local filesystem. The archive may omit the image if this instance uses an OCI registry, in which case the image is expected to have been pushed there already. If the Scylla database is unavailable, a 503 is returned instead, since we cannot accept new packages in degraded mode.
 pub async fn upload<S, B>(package_archive: S, context: Context) -> Result<impl Reply, Rejection>
 where
     S: StreamExt<Item = Result<B, warp::Error>> + Unpin,
@@ -379,6 +508,12 @@ where
     info!("Handling POST on '/packages' (i.e., upload new package)");
     let mut package_archive = package_archive;
 
+    // We can only accept new packages if the database is there
+    let scylla: Arc<Session> = match context.db.session() {
+        Some(scylla) => scylla,
+        None         => { return Ok(db::unavailable()); },
+    };
+
 
 
     /* Step 0: Load config files */
@@ -438,6 +573,7 @@ where
     // Re-open the file
     debug!("Extracting submitted archive file...");
     let info_path  : PathBuf = tempdir_path.join("package.yml");
+    let list_path  : PathBuf = tempdir_path.join(ManifestList::FILENAME);
     let image_path : PathBuf = node_config.paths.packages.join(format!("{}.tar", id));
     {
         let handle: tfs::File = match tfs::File::open(&tar_path).await {
@@ -455,8 +591,10 @@ where
             Err(err)    => { fail!(Error::TarEntriesError { path: tar_path, err }); },
         };
         let mut i: usize = 0;
-        let mut did_info  : bool = false;
-        let mut did_image : bool = false;
+        let mut did_info   : bool                  = false;
+        let mut did_image  : bool                  = false;
+        let mut did_list   : bool                  = false;
+        let mut arch_files : Vec<(String, PathBuf)> = vec![];
         while let Some(entry) = entries.next().await {
             // Unwrap the entry
             let mut entry: Entry<_> = match entry {
@@ -481,6 +619,18 @@ where
                 debug!("Extracting '{}/image.tar' to '{}'...", tar_path.display(), image_path.display());
                 if let Err(err) = entry.unpack(&image_path).await { fail!(Error::TarFileUnpackError{ file: PathBuf::from("image.tar"), tarball: tar_path, target: image_path, err }); }
                 did_image = true;
+            } else if entry_path == PathBuf::from(ManifestList::FILENAME) {
+                // Extract as such
+                debug!("Extracting '{}/{}' to '{}'...", tar_path.display(), ManifestList::FILENAME, list_path.display());
+                if let Err(err) = entry.unpack(&list_path).await { fail!(Error::TarFileUnpackError{ file: PathBuf::from(ManifestList::FILENAME), tarball: tar_path, target: list_path, err }); }
+                did_list = true;
+            } else if is_arch_image(&entry_path) {
+                // Extract it as the image for another architecture
+                let file   : String  = entry_path.to_string_lossy().into();
+                let target : PathBuf = node_config.paths.packages.join(format!("{}-{}", id, file));
+                debug!("Extracting '{}/{}' to '{}'...", tar_path.display(), file, target.display());
+                if let Err(err) = entry.unpack(&target).await { fail!(Error::TarFileUnpackError{ file: file.into(), tarball: tar_path, target, err }); }
+                arch_files.push((file, target));
             } else {
                 debug!("Ignoring irrelevant entry '{}' in '{}'", entry_path.display(), tar_path.display());
             }
@@ -489,8 +639,8 @@ where
             i += 1;
         }
 
-        // Assert that both of our relevant files must have been present
-        if !did_info || !did_image { fail!(Error::TarMissingEntries { expected: vec![ "package.yml", "image.tar" ], path: tar_path }); }
+        // Assert that both of our relevant files must have been present (unless the image lives in the OCI registry)
+        if !did_info || (!did_image && node_config.image_registry.is_none()) { fail!(Error::TarMissingEntries { expected: vec![ "package.yml", "image.tar" ], path: tar_path }); }
     }
 
 
@@ -507,6 +657,65 @@ where
         Err(err) => { fail!(Error::PackageInfoParseError{ path: info_path, err }); },
     };
 
+    // If the image is not in the archive, then it lives in the OCI registry (which we checked we have)
+    if !did_image {
+        let reference: String = node_config.image_registry.as_ref().unwrap().reference(&info.name, &info.version);
+        debug!("Image of package '{}' (version {}) lives in OCI registry as '{}'", info.name, info.version, reference);
+
+        // We can only scan images that are uploaded to us, so refuse the package if we must reject vulnerable images (or it would be an easy way around that)
+        if let Some(scanner) = &node_config.node.central().scanner {
+            if scanner.action == ScanAction::Reject {
+                warn!("Refusing package '{}' (version {}), since its image lives in the OCI registry and cannot be scanned for vulnerabilities", info.name, info.version);
+                let body: String = format!("Package '{}' (version {}) must be uploaded with its image, since this instance rejects images with vulnerabilities and cannot scan images in the OCI registry\n", info.name, info.version);
+                let mut response = Response::new(Body::from(body));
+                *response.status_mut() = StatusCode::FORBIDDEN;
+                return Ok(response);
+            }
+            warn!("Not scanning image of package '{}' (version {}) for vulnerabilities, since it lives in the OCI registry", info.name, info.version);
+        }
+        let result_path: PathBuf = format!("{}{}", REGISTRY_PREFIX, reference).into();
+
+        // Insert the package with that instead of a file
+        debug!("Inserting package '{}' (version {}) into Scylla DB...", info.name, info.version);
+        match insert_package_into_db(&scylla, &info, &result_path).await {
+            Ok(_)                                      => {},
+            Err(err @ Error::PackageInsertError{ .. }) => { error!("{}", err); return Ok(db::unavailable()); },
+            Err(err)                                   => { fail!(err); },
+        }
+        if let Err(err) = context.db.refresh_cache().await { warn!("Failed to refresh package cache: {}", err); }
+        debug!("Upload of package '{}' (version {}) complete.", info.name, info.version);
+        return Ok(StatusCode::OK.into_response());
+    }
+
+    // Scan the image(s) for vulnerabilities before accepting them, if we are configured to
+    let scan_result: Option<(ScanReport, bool)> = if let Some(scanner) = &node_config.node.central().scanner {
+        let mut images: Vec<&Path> = vec![ &image_path ];
+        images.extend(arch_files.iter().map(|(_, path)| path.as_path()));
+        let report: ScanReport = match scan::scan(scanner, &images).await {
+            Ok(report) => report,
+            Err(err)   => { fail!(image_path, Error::ImageScanError{ name: info.name, version: info.version, err }); },
+        };
+        let flagged: bool = report.max_severity().map(|severity| severity >= scanner.threshold).unwrap_or(false);
+        debug!("Scan of package '{}' (version {}) found {}", info.name, info.version, report.summary());
+
+        // Refuse the package if it is too vulnerable (but remember why, so it can be queried)
+        if flagged && scanner.action == ScanAction::Reject {
+            warn!("Refusing package '{}' (version {}), since it has vulnerabilities of severity {} or higher ({})", info.name, info.version, scanner.threshold, report.summary());
+            if let Err(err) = scan::insert_scan_into_db(&scylla, &info.name, &info.version.to_string(), &report, flagged).await { warn!("{}", Error::ScanStoreError{ err }); }
+            for image in images {
+                if let Err(err) = tfs::remove_file(image).await { warn!("Failed to remove image '{}': {}", image.display(), err); }
+            }
+
+            let body: String = format!("Package '{}' (version {}) has vulnerabilities of severity {} or higher ({}); query its 'packageScans' for details\n", info.name, info.version, scanner.threshold, report.summary());
+            let mut response = Response::new(Body::from(body));
+            *response.status_mut() = StatusCode::FORBIDDEN;
+            return Ok(response);
+        }
+        Some((report, flagged))
+    } else {
+        None
+    };
+
     // Copy the image tar to the proper location
     let result_path: PathBuf = node_config.paths.packages.join(format!("{}-{}.tar", info.name, info.version));
     debug!("Moving image '{}' to '{}'...", image_path.display(), result_path.display());
@@ -514,10 +723,62 @@ where
         fail!(image_path, Error::FileMoveError{ from: image_path, to: result_path, err });
     }
 
+    // Do the same for the images of the other architectures, if any, and store the manifest list that describes them next to the default image
+    let list: Option<ManifestList> = if did_list {
+        match ManifestList::from_path(&list_path) {
+            Ok(list) => Some(list),
+            Err(err) => { fail!(result_path, Error::ManifestListReadError{ err }); },
+        }
+    } else {
+        None
+    };
+    if let Some(list) = &list {
+        for image in list.images.iter().skip(1) {
+            let arch_image: &PathBuf = match arch_files.iter().find(|(file, _)| file == &image.file) {
+                Some((_, path)) => path,
+                None            => { fail!(result_path, Error::ArchImageMissing{ file: image.file.clone(), path: tar_path }); },
+            };
+            let target: PathBuf = arch_path(&result_path, image.arch);
+            debug!("Moving image '{}' to '{}'...", arch_image.display(), target.display());
+            if let Err(err) = tfs::rename(arch_image, &target).await {
+                fail!(result_path, Error::FileMoveError{ from: arch_image.clone(), to: target, err });
+            }
+        }
+        let target: PathBuf = manifest_path(&result_path);
+        if let Err(err) = tfs::copy(&list_path, &target).await {
+            fail!(result_path, Error::ManifestListCopyError{ from: list_path, to: target, err });
+        }
+    } else if let Err(err) = tfs::remove_file(manifest_path(&result_path)).await {
+        // An older upload of this version may have had more architectures than this one
+        if err.kind() != std::io::ErrorKind::NotFound { warn!("Failed to remove outdated manifest list '{}': {}", manifest_path(&result_path).display(), err); }
+    }
+    for (file, path) in &arch_files {
+        if path.exists() {
+            debug!("Removing image '{}', since it is not in the manifest list", file);
+            if let Err(err) = tfs::remove_file(path).await { warn!("Failed to remove image '{}': {}", path.display(), err); }
+        }
+    }
+
     // Call the insert function to store the dataset in the registry
     debug!("Inserting package '{}' (version {}) into Scylla DB...", info.name, info.version);
-    if let Err(err) = insert_package_into_db(&context.scylla, &info, &result_path).await {
-        fail!(result_path, err);
+    match insert_package_into_db(&scylla, &info, &result_path).await {
+        Ok(_) => {},
+        Err(err @ Error::PackageInsertError{ .. }) => {
+            // The database most likely went down while we were busy, so let the client try again later
+            error!("{}", err);
+            if let Err(err) = tfs::remove_file(&result_path).await { warn!("Failed to remove image '{}': {}", result_path.display(), err); }
+            return Ok(db::unavailable());
+        },
+        Err(err) => { fail!(result_path, err); },
+    }
+
+    // Remember the new package for the next outage
+    if let Err(err) = context.db.refresh_cache().await { warn!("Failed to refresh package cache: {}", err); }
+
+    // Store the scan result alongside it
+    if let Some((report, flagged)) = scan_result {
+        if flagged { warn!("Accepted package '{}' (version {}), but flagged it for having vulnerabilities of severity {} or higher ({})", info.name, info.version, node_config.node.central().scanner.as_ref().unwrap().threshold, report.summary()); }
+        if let Err(err) = scan::insert_scan_into_db(&scylla, &info.name, &info.version.to_string(), &report, flagged).await { warn!("{}", Error::ScanStoreError{ err }); }
     }
 
 
@@ -525,7 +786,7 @@ where
     /* Step 4: Done */
     // The package has now been added
     debug!("Upload of package '{}' (version {}) complete.", info.name, info.version);
-    Ok(StatusCode::OK)
+    Ok(StatusCode::OK.into_response())
 
     // Note that the temporary directory is automagically removed
 }
@@ -0,0 +1,259 @@
+//  ROUTES.rs
+//    by agent
+//
+//  Created:
+//    16 Oct 2026, 13:23:02
+//  Last edited:
+//    16 Oct 2026, 13:23:02
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines the tree of REST routes that `brane-api` serves.
+//
+
+use std::sync::Arc;
+
+use juniper::EmptySubscription;
+use warp::{Filter, Rejection, Reply};
+
+use specifications::openapi::{Method, API_VERSION_HEADER};
+
+use crate::spec::Context;
+use crate::schema::{Mutations, Query, Schema};
+use crate::health;
+use crate::version;
+use crate::infra;
+use crate::data;
+use crate::packages;
+use crate::search;
+use crate::dashboard::{self, StatusCache};
+use crate::events;
+use crate::approvals;
+use crate::openapi;
+use crate::server::ClientIdentity;
+use crate::federation::FEDERATED_HEADER;
+
+
+/***** CONSTANTS *****/
+/// The routes that are served but deliberately not described by `specifications::openapi::OPERATIONS`, as `(method, path)` (with path parameters as `{name}`).
+pub const UNDOCUMENTED: &[(Method, &str)] = &[
+    // GraphQL describes itself
+    (Method::Get, "/graphql"),
+    (Method::Post, "/graphql"),
+    // Search has its own query and result types (see `search`)
+    (Method::Get, "/search"),
+    // The dashboard is for browsers, not clients
+    (Method::Get, "/dashboard"),
+    (Method::Get, "/dashboard/status"),
+    // Clients check the version through the header on every response instead
+    (Method::Get, "/version"),
+    (Method::Get, "/version/features"),
+    (Method::Get, "/openapi.json"),
+];
+
+
+
+
+
+/***** LIBRARY *****/
+/// Builds the tree of routes that `brane-api` serves.
+///
+/// Every route is either described by `specifications::openapi::OPERATIONS` or listed in [`UNDOCUMENTED`].
+///
+/// # Arguments
+/// - `context`: The Context that is given to every route.
+///
+/// # Returns
+/// A warp Filter that serves the routes.
+pub fn routes(context: Context) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone + Send + Sync + 'static {
+    let context = warp::any().map(move || context.clone());
+
+    // Configure Juniper.
+    let schema = Schema::new(Query {}, Mutations {}, EmptySubscription::new());
+    let graphql_filter = juniper_warp::make_graphql_filter(schema, context.clone().boxed());
+    let graphql = warp::path("graphql").and(warp::path::end()).and(graphql_filter);
+
+    // Configure Warp.
+    // Configure the data one
+    let list_datasets = warp::path("data")
+        .and(warp::path("info"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(warp::header::optional::<String>(FEDERATED_HEADER))
+        .and(context.clone())
+        .and_then(data::list);
+    let get_dataset = warp::path("data")
+        .and(warp::path("info"))
+        .and(warp::path::param())
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(warp::header::optional::<String>(FEDERATED_HEADER))
+        .and(context.clone())
+        .and_then(data::get);
+    let data = list_datasets.or(get_dataset);
+
+    // Configure the packages one
+    let package_registry = warp::path("packages")
+        .and(warp::path("registry"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(context.clone())
+        .and_then(packages::registry);
+    let download_package = warp::path("packages")
+        .and(warp::get())
+        .and(warp::path::param())
+        .and(warp::path::param())
+        .and(warp::path::end())
+        .and(warp::query::<packages::DownloadQuery>())
+        .and(context.clone())
+        .and_then(packages::download);
+    let upload_package = warp::path("packages")
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(warp::filters::body::stream())
+        .and(context.clone())
+        .and_then(packages::upload);
+    let packages = package_registry.or(download_package).or(upload_package);
+
+    // Configure infra
+    let list_registries = warp::get()
+        .and(warp::path("infra"))
+        .and(warp::path("registries"))
+        .and(warp::path::end())
+        .and(context.clone())
+        .and_then(infra::registries);
+    let get_registry = warp::get()
+        .and(warp::path("infra"))
+        .and(warp::path("registries"))
+        .and(warp::path::param())
+        .and(warp::path::end())
+        .and(context.clone())
+        .and_then(infra::get_registry);
+    let get_capabilities = warp::get()
+        .and(warp::path("infra"))
+        .and(warp::path("capabilities"))
+        .and(warp::path::param())
+        .and(warp::path::end())
+        .and(context.clone())
+        .and_then(infra::get_capabilities);
+    let get_availability = warp::get()
+        .and(warp::path("infra"))
+        .and(warp::path("availability"))
+        .and(warp::path::param())
+        .and(warp::path::end())
+        .and(context.clone())
+        .and_then(infra::get_availability);
+    let get_central_config = warp::get()
+        .and(warp::path("infra"))
+        .and(warp::path("config"))
+        .and(warp::path::end())
+        .and(context.clone())
+        .and_then(infra::config);
+    let get_config = warp::get()
+        .and(warp::path("infra"))
+        .and(warp::path("config"))
+        .and(warp::path::param())
+        .and(warp::path::end())
+        .and(context.clone())
+        .and_then(infra::get_config);
+    let get_ca_log = warp::get()
+        .and(warp::path("infra"))
+        .and(warp::path("ca-log"))
+        .and(warp::path::end())
+        .and(context.clone())
+        .and_then(infra::ca_log);
+    let infra = get_registry.or(list_registries.or(get_capabilities.or(get_availability.or(get_central_config.or(get_config.or(get_ca_log))))));
+
+    // Configure the instance-wide search
+    let search = warp::get()
+        .and(warp::path("search"))
+        .and(warp::path::end())
+        .and(warp::query::<search::SearchQuery>())
+        .and(warp::header::optional::<String>(FEDERATED_HEADER))
+        .and(context.clone())
+        .and_then(search::search);
+
+    // Configure the dashboard, which is only for administrators
+    let status_cache: Arc<StatusCache> = Arc::new(StatusCache::new(dashboard::STATUS_TTL));
+    let status_cache = warp::any().map(move || status_cache.clone());
+    let dashboard_page = warp::get()
+        .and(warp::path("dashboard"))
+        .and(warp::path::end())
+        .and(warp::ext::optional::<ClientIdentity>())
+        .and(context.clone())
+        .and_then(dashboard::page);
+    let dashboard_status = warp::get()
+        .and(warp::path("dashboard"))
+        .and(warp::path("status"))
+        .and(warp::path::end())
+        .and(warp::ext::optional::<ClientIdentity>())
+        .and(context.clone())
+        .and(status_cache)
+        .and_then(dashboard::status);
+    let dashboard = dashboard_page.or(dashboard_status);
+
+    // Configure the events, which resume workflows waiting for them
+    let events = warp::post()
+        .and(warp::path("events"))
+        .and(warp::path::param())
+        .and(warp::path::param())
+        .and(warp::path::end())
+        .and(warp::ext::optional::<ClientIdentity>())
+        .and(context.clone())
+        .and(warp::body::content_length_limit(events::MAX_PAYLOAD))
+        .and(warp::body::bytes())
+        .and_then(events::send);
+
+    // Configure the approvals, which approvers decide on to resume workflows
+    let list_approvals = warp::get()
+        .and(warp::path("approvals"))
+        .and(warp::path::end())
+        .and(warp::ext::optional::<ClientIdentity>())
+        .and(context.clone())
+        .and_then(approvals::list);
+    let decide_approval = warp::post()
+        .and(warp::path("approvals"))
+        .and(warp::path::param())
+        .and(warp::path::end())
+        .and(warp::ext::optional::<ClientIdentity>())
+        .and(context.clone())
+        .and(warp::body::content_length_limit(approvals::MAX_DECISION))
+        .and(warp::body::json())
+        .and_then(approvals::decide);
+    let approvals = list_approvals.or(decide_approval);
+    
+    // Configure the health & version
+    let health = warp::get()
+        .and(warp::path("health"))
+        .and(warp::path::end())
+        .and(context.clone())
+        .and_then(health::handle);
+    let instance_health = warp::get()
+        .and(warp::path("health"))
+        .and(warp::path("instance"))
+        .and(warp::path::end())
+        .and(context.clone())
+        .and_then(health::instance);
+    let health = health.or(instance_health);
+    let version = warp::get()
+        .and(warp::path("version"))
+        .and(warp::path::end())
+        .and_then(version::handle);
+    let features = warp::get()
+        .and(warp::path("version"))
+        .and(warp::path("features"))
+        .and(warp::path::end())
+        .and(context.clone())
+        .and_then(version::features);
+    let version = version.or(features);
+    let openapi = warp::get()
+        .and(warp::path("openapi.json"))
+        .and(warp::path::end())
+        .and_then(openapi::handle);
+
+    // Construct the final routes
+    data.or(packages.or(infra.or(search.or(dashboard.or(events.or(approvals.or(health.or(version.or(openapi.or(graphql))))))))))
+        .with(warp::reply::with::header(API_VERSION_HEADER, env!("CARGO_PKG_VERSION")))
+        .with(warp::log("brane-api"))
+}
@@ -0,0 +1,331 @@
+//  SCAN.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 11:54:24
+//  Last edited:
+//    16 Oct 2026, 11:54:24
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements the vulnerability scanning of the images of uploaded
+//!   packages, and remembers the results in the Scylla database.
+// 
+
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use chrono::Utc;
+use log::debug;
+use reqwest::Body;
+use scylla::Session;
+use serde::{Deserialize, Serialize};
+use tokio::fs as tfs;
+use tokio::process::Command;
+use tokio_util::codec::{BytesCodec, FramedRead};
+
+use brane_cfg::node::{ScannerBackend, ScannerConfig, Severity};
+
+pub use crate::errors::ScanError as Error;
+
+
+/***** AUXILLARY STRUCTS *****/
+/// Defines the parts of a trivy JSON report that we are interested in.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct TrivyReport {
+    /// The results per scanned target (e.g., OS packages, language packages) in the image.
+    #[serde(default)]
+    results : Vec<TrivyResult>,
+}
+
+/// Defines the result of scanning a single target in a trivy JSON report.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct TrivyResult {
+    /// The vulnerabilities found in this target. Note that trivy writes this as `null` if there are none.
+    #[serde(default)]
+    vulnerabilities : Option<Vec<TrivyVulnerability>>,
+}
+
+/// Defines a single vulnerability in a trivy JSON report.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct TrivyVulnerability {
+    /// The identifier of the vulnerability (e.g., the CVE number).
+    #[serde(rename = "VulnerabilityID")]
+    vulnerability_id : String,
+    /// The name of the package in the image that is vulnerable.
+    pkg_name         : String,
+    /// The severity of the vulnerability, in all-caps.
+    severity         : String,
+}
+
+
+
+/// Defines a single vulnerability found in the image of a package.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Finding {
+    /// The identifier of the vulnerability (e.g., the CVE number).
+    pub id       : String,
+    /// The name of the package in the image that is vulnerable.
+    pub package  : String,
+    /// How severe the vulnerability is.
+    pub severity : Severity,
+}
+
+/// Defines the result of scanning the image(s) of a package.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ScanReport {
+    /// The vulnerabilities found.
+    pub findings : Vec<Finding>,
+}
+
+impl ScanReport {
+    /// Returns the severity of the worst vulnerability in this report.
+    /// 
+    /// # Returns
+    /// The highest severity of all findings, or `None` if nothing was found.
+    #[inline]
+    pub fn max_severity(&self) -> Option<Severity> { self.findings.iter().map(|finding| finding.severity).max() }
+
+    /// Returns a short summary of the findings in this report (e.g., `2 critical, 1 low`).
+    /// 
+    /// # Returns
+    /// The summary, or `no vulnerabilities` if nothing was found.
+    pub fn summary(&self) -> String {
+        let counts: Vec<String> = [ Severity::Critical, Severity::High, Severity::Medium, Severity::Low, Severity::Unknown ].iter()
+            .map(|severity| (*severity, self.findings.iter().filter(|finding| finding.severity == *severity).count()))
+            .filter(|(_, count)| *count > 0)
+            .map(|(severity, count)| format!("{} {}", count, severity))
+            .collect();
+        if counts.is_empty() { "no vulnerabilities".into() } else { counts.join(", ") }
+    }
+}
+
+impl From<TrivyReport> for ScanReport {
+    fn from(value: TrivyReport) -> Self {
+        Self {
+            findings : value.results.into_iter().flat_map(|result| result.vulnerabilities.unwrap_or_default()).map(|vuln| Finding {
+                id       : vuln.vulnerability_id,
+                package  : vuln.pkg_name,
+                severity : Severity::from_str(&vuln.severity).unwrap_or(Severity::Unknown),
+            }).collect(),
+        }
+    }
+}
+
+/// Defines the scan result of a package as stored in the Scylla database.
+#[derive(Clone, Debug)]
+pub struct ScanRow {
+    /// The name of the scanned package.
+    pub name             : String,
+    /// The version of the scanned package.
+    pub version          : String,
+    /// When the package was scanned, in milliseconds since the Unix epoch.
+    pub scanned          : i64,
+    /// The severity of the worst vulnerability found, if any.
+    pub max_severity     : Option<String>,
+    /// Whether the package had vulnerabilities at or above the configured threshold.
+    pub flagged          : bool,
+    /// The findings, as a JSON list of [`Finding`]s.
+    pub findings_as_json : String,
+}
+
+
+
+
+
+/***** SCANNERS *****/
+/// Scans an image by running a local command.
+/// 
+/// # Arguments
+/// - `command`: The command to run, in which `{image}` is replaced with the path of the image.
+/// - `image`: The path of the image tar to scan.
+/// 
+/// # Returns
+/// The report that the command wrote to stdout.
+/// 
+/// # Errors
+/// This function errors if the command could not be run or did not succeed.
+async fn scan_command(command: &[String], image: &Path) -> Result<String, Error> {
+    // Prepare the command
+    let (exec, args): (&String, &[String]) = match command.split_first() {
+        Some(parts) => parts,
+        None        => { return Err(Error::CommandEmpty); },
+    };
+    let image: String = image.display().to_string();
+    let mut cmd: Command = Command::new(exec);
+    cmd.args(args.iter().map(|arg| arg.replace("{image}", &image)));
+
+    // Run it
+    debug!("Running {:?}...", cmd);
+    let output = match cmd.output().await {
+        Ok(output) => output,
+        Err(err)   => { return Err(Error::CommandLaunchError{ command: format!("{:?}", cmd), err }); },
+    };
+    if !output.status.success() {
+        return Err(Error::CommandFailure{ command: format!("{:?}", cmd), code: output.status.code().unwrap_or(-1), stderr: String::from_utf8_lossy(&output.stderr).to_string() });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Scans an image by sending it to an external scanning service.
+/// 
+/// # Arguments
+/// - `address`: The URL of the scanning service.
+/// - `image`: The path of the image tar to scan.
+/// 
+/// # Returns
+/// The report that the service responded with.
+/// 
+/// # Errors
+/// This function errors if the image could not be sent or the service did not succeed.
+async fn scan_http(address: &str, image: &Path) -> Result<String, Error> {
+    // Open the image as a stream
+    let handle: tfs::File = match tfs::File::open(image).await {
+        Ok(handle) => handle,
+        Err(err)   => { return Err(Error::ImageOpenError{ path: image.into(), err }); },
+    };
+
+    // Send it off
+    debug!("Sending image '{}' to scanner '{}'...", image.display(), address);
+    let client: reqwest::Client = reqwest::Client::new();
+    let res: reqwest::Response = match client.post(address).header("Content-Type", "application/x-tar").body(Body::wrap_stream(FramedRead::new(handle, BytesCodec::new()))).send().await {
+        Ok(res)  => res,
+        Err(err) => { return Err(Error::RequestError{ address: address.into(), err }); },
+    };
+    if !res.status().is_success() {
+        return Err(Error::RequestFailure{ address: address.into(), code: res.status(), response: res.text().await.ok() });
+    }
+    match res.text().await {
+        Ok(report) => Ok(report),
+        Err(err)   => Err(Error::ResponseReadError{ address: address.into(), err }),
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Scans the given image(s) of a package for vulnerabilities.
+/// 
+/// # Arguments
+/// - `config`: The scanner to use.
+/// - `images`: The image tars to scan (i.e., one per architecture the package was built for).
+/// 
+/// # Returns
+/// A ScanReport with the findings of all images combined.
+/// 
+/// # Errors
+/// This function errors if any of the images could not be scanned, or if the scanner returned a report that we could not parse.
+pub async fn scan(config: &ScannerConfig, images: &[&Path]) -> Result<ScanReport, Error> {
+    let mut report: ScanReport = ScanReport::default();
+    for image in images {
+        debug!("Scanning image '{}' for vulnerabilities...", image.display());
+        let raw: String = match &config.backend {
+            ScannerBackend::Command{ command } => scan_command(command, image).await?,
+            ScannerBackend::Http{ address }    => scan_http(address, image).await?,
+        };
+        let trivy: TrivyReport = match serde_json::from_str(&raw) {
+            Ok(trivy) => trivy,
+            Err(err)  => { return Err(Error::ReportParseError{ path: image.into(), err }); },
+        };
+        report.findings.extend(ScanReport::from(trivy).findings);
+    }
+    Ok(report)
+}
+
+
+
+/// Ensures that the package scans table is present in the given Scylla database.
+/// 
+/// # Arguments
+/// - `scylla`: The Scylla database session that allows us to talk to it.
+/// 
+/// # Returns
+/// Nothing, but does change the target Scylla database to include the new table if it didn't already.
+/// 
+/// # Errors
+/// This function errors if the communication with the given database failed.
+pub async fn ensure_db_table(scylla: &Session) -> Result<(), Error> {
+    if let Err(err) = scylla.query(
+        "CREATE TABLE IF NOT EXISTS brane.package_scans (
+              name text
+            , version text
+            , scanned bigint
+            , max_severity text
+            , flagged boolean
+            , findings_as_json text
+            , PRIMARY KEY (name, version)
+        )",
+        &[],
+    ).await {
+        return Err(Error::TableDefineError{ err });
+    }
+    Ok(())
+}
+
+/// Stores the scan result of a package in the given Scylla database, replacing any older one of the same version.
+/// 
+/// # Arguments
+/// - `scylla`: The Scylla database session that allows us to talk to it.
+/// - `name`: The name of the scanned package.
+/// - `version`: The version of the scanned package.
+/// - `report`: The findings of the scan.
+/// - `flagged`: Whether the package had vulnerabilities at or above the configured threshold.
+/// 
+/// # Errors
+/// This function errors if the report could not be serialized or the communication with the given database failed.
+pub async fn insert_scan_into_db(scylla: &Arc<Session>, name: &str, version: &str, report: &ScanReport, flagged: bool) -> Result<(), Error> {
+    let findings_as_json: String = match serde_json::to_string(&report.findings) {
+        Ok(findings) => findings,
+        Err(err)     => { return Err(Error::FindingsSerializeError{ err }); },
+    };
+
+    if let Err(err) = scylla.query(
+        "INSERT INTO brane.package_scans (
+              name
+            , version
+            , scanned
+            , max_severity
+            , flagged
+            , findings_as_json
+        ) VALUES(?, ?, ?, ?, ?, ?)
+        ",
+        (name, version, Utc::now().timestamp_millis(), report.max_severity().map(|severity| severity.to_string()), flagged, findings_as_json),
+    ).await {
+        return Err(Error::ScanInsertError{ name: name.into(), version: version.into(), err });
+    }
+    Ok(())
+}
+
+/// Queries all scan results in the given Scylla database.
+/// 
+/// # Arguments
+/// - `scylla`: The Scylla database session that allows us to talk to it.
+/// 
+/// # Returns
+/// A list of ScanRows, one per scanned package version.
+/// 
+/// # Errors
+/// This function errors if we failed to query the database or to parse its response.
+pub async fn query_scans(scylla: &Session) -> Result<Vec<ScanRow>, Error> {
+    let rows = match scylla.query("SELECT name, version, scanned, max_severity, flagged, findings_as_json FROM brane.package_scans", &[]).await {
+        Ok(res)  => res.rows.unwrap_or_default(),
+        Err(err) => { return Err(Error::ScansQueryError{ err }); },
+    };
+
+    let mut scans: Vec<ScanRow> = Vec::with_capacity(rows.len());
+    for row in rows.into_typed::<(String, String, i64, Option<String>, bool, String)>() {
+        let (name, version, scanned, max_severity, flagged, findings_as_json) = match row {
+            Ok(row)  => row,
+            Err(err) => { return Err(Error::ScanRowError{ err }); },
+        };
+        scans.push(ScanRow{ name, version, scanned, max_severity, flagged, findings_as_json });
+    }
+    Ok(scans)
+}
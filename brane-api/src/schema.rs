@@ -16,15 +16,16 @@ use std::path::PathBuf;
 use std::str::FromStr;
 
 use chrono::{DateTime, TimeZone, Utc};
-use juniper::{graphql_object, EmptySubscription, FieldResult, GraphQLObject, RootNode};
-use log::{debug, info};
-use scylla::IntoTypedRows;
+use juniper::{graphql_object, EmptySubscription, FieldError, FieldResult, GraphQLObject, RootNode};
+use log::{debug, info, warn};
 use uuid::Uuid;
 
 use specifications::version::Version;
 
+use crate::db::RETRY_AFTER;
 use crate::spec::Context;
 use crate::packages::PackageUdt;
+use crate::scan::{self, ScanRow};
 
 pub type Schema = RootNode<'static, Query, Mutations, EmptySubscription<Context>>;
 impl juniper::Context for Context {}
@@ -64,6 +65,31 @@ impl From<PackageUdt> for Package {
     }
 }
 
+#[derive(Clone, Debug, GraphQLObject)]
+pub struct PackageScan {
+    pub name: String,
+    pub version: String,
+    pub scanned: DateTime<Utc>,
+    pub max_severity: Option<String>,
+    pub flagged: bool,
+    pub findings_as_json: String,
+}
+
+impl From<ScanRow> for PackageScan {
+    fn from(row: ScanRow) -> Self {
+        let scanned = Utc.timestamp_millis_opt(row.scanned).unwrap();
+
+        PackageScan {
+            name: row.name,
+            version: row.version,
+            scanned,
+            max_severity: row.max_severity,
+            flagged: row.flagged,
+            findings_as_json: row.findings_as_json,
+        }
+    }
+}
+
 pub struct Query;
 
 #[graphql_object(context = Context)]
@@ -86,78 +112,101 @@ impl Query {
         context: &Context,
     ) -> FieldResult<Vec<Package>> {
         info!("Handling GRAPHQL on '/graphql' (i.e., get packages list)");
-        let scylla = context.scylla.clone();
-
-        let like = format!("%{}%", term.unwrap_or_default());
-        let query = "SELECT package FROM brane.packages WHERE name LIKE ? ALLOW FILTERING";
+        let term: String = term.unwrap_or_default();
 
-        debug!("Querying Scylla database...");
+        // Note that this falls back to the cached packages if the database is unavailable
+        debug!("Querying packages...");
         let mut packages: Vec<Package> = vec![];
-        if let Some(rows) = scylla.query(query, &(like,)).await?.rows {
 
-            // Search for all matches of this package
-            for row in rows.into_typed::<(PackageUdt,)>() {
-                let (package,) = row?;
+        // Search for all matches of this package
+        for package in context.db.packages().await?.into_iter().map(|cached| cached.package) {
+            if !package.name.contains(&term) { continue; }
+            if let Some(name) = &name {
+                if name != &package.name {
+                    continue;
+                }
+            }
+
+            packages.push(package.into());
+        }
+
+        // Now find the target version if relevant
+        if let Some(version) = version {
+            let target_version: Version = Version::from_str(&version)?;
+            let mut package: Option<Package> = None;
+            let mut version: Option<Version> = None;
+            if target_version.is_latest() {
+                for p in packages {
+                    // Find the one with the highest version
+
+                    // Parse it as a version
+                    let pversion: Version = match Version::from_str(&p.version) {
+                        Ok(version) => version,
+                        Err(_)      => { continue; },
+                    };
 
-                if let Some(name) = &name {
-                    if name != &package.name {
-                        continue;
+                    // Compare
+                    if package.is_none() || &pversion > version.as_ref().unwrap() {
+                        package = Some(p);
+                        version = Some(pversion);
                     }
                 }
+            } else {
+                for p in packages {
+                    // Find the first matching one
 
-                packages.push(package.into());
-            }
+                    // Parse it as a version
+                    let pversion: Version = match Version::from_str(&p.version) {
+                        Ok(version) => version,
+                        Err(_)      => { continue; },
+                    };
 
-            // Now find the target version if relevant
-            if let Some(version) = version {
-                let target_version: Version = Version::from_str(&version)?;
-                let mut package: Option<Package> = None;
-                let mut version: Option<Version> = None;
-                if target_version.is_latest() {
-                    for p in packages {
-                        // Find the one with the highest version
-
-                        // Parse it as a version
-                        let pversion: Version = match Version::from_str(&p.version) {
-                            Ok(version) => version,
-                            Err(_)      => { continue; },
-                        };
-
-                        // Compare
-                        if package.is_none() || &pversion > version.as_ref().unwrap() {
-                            package = Some(p);
-                            version = Some(pversion);
-                        }
-                    }
-                } else {
-                    for p in packages {
-                        // Find the first matching one
-
-                        // Parse it as a version
-                        let pversion: Version = match Version::from_str(&p.version) {
-                            Ok(version) => version,
-                            Err(_)      => { continue; },
-                        };
-
-                        // Compare
-                        if target_version == pversion {
-                            package = Some(p);
-                        }
+                    // Compare
+                    if target_version == pversion {
+                        package = Some(p);
                     }
                 }
-
-                // Overwrite the list
-                packages = if let Some(package) = package {
-                    vec![ package ]
-                } else {
-                    vec![]
-                };
             }
+
+            // Overwrite the list
+            packages = if let Some(package) = package {
+                vec![ package ]
+            } else {
+                vec![]
+            };
         }
 
         debug!("Returning {} packages", packages.len());
         Ok(packages)
     }
+
+    ///
+    ///
+    ///
+    async fn package_scans(
+        name: Option<String>,
+        version: Option<String>,
+        flagged: Option<bool>,
+        context: &Context,
+    ) -> FieldResult<Vec<PackageScan>> {
+        info!("Handling GRAPHQL on '/graphql' (i.e., get package scans list)");
+        let scylla = match context.db.session() {
+            Some(scylla) => scylla,
+            None         => { return Err(FieldError::from(format!("The package database is temporarily unavailable; try again in {} seconds", RETRY_AFTER))); },
+        };
+
+        // Only keep the scans that match all given filters
+        debug!("Querying package scans...");
+        let scans: Vec<PackageScan> = scan::query_scans(&scylla).await?.into_iter()
+            .filter(|row| name.as_ref().map(|name| name == &row.name).unwrap_or(true))
+            .filter(|row| version.as_ref().map(|version| version == &row.version).unwrap_or(true))
+            .filter(|row| flagged.map(|flagged| flagged == row.flagged).unwrap_or(true))
+            .map(PackageScan::from)
+            .collect();
+
+        debug!("Returning {} package scans", scans.len());
+        Ok(scans)
+    }
 }
 
 pub struct Mutations;
@@ -185,7 +234,10 @@ impl Mutations {
         context: &Context,
     ) -> FieldResult<&str> {
         info!("Handling GRAPHQL on '/graphql' (i.e., unpublish package)");
-        let scylla = context.scylla.clone();
+        let scylla = match context.db.session() {
+            Some(scylla) => scylla,
+            None         => { return Err(FieldError::from(format!("The package database is temporarily unavailable; try again in {} seconds", RETRY_AFTER))); },
+        };
 
         // Get the image file first, tho
         debug!("Querying file path from Scylla database...");
@@ -203,6 +255,9 @@ impl Mutations {
             // Delete the file
             debug!("Deleting container file '{}'...", file.display());
             tokio::fs::remove_file(&file).await?;
+
+            // Forget it for the next outage too
+            if let Err(err) = context.db.refresh_cache().await { warn!("Failed to refresh package cache: {}", err); }
         }
 
         Ok("OK!")
@@ -0,0 +1,280 @@
+//  SEARCH.rs
+//    by agent
+//
+//  Created:
+//    16 Oct 2026, 08:13:58
+//  Last edited:
+//    16 Oct 2026, 10:51:50
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines functions that handle REST-functions to the `/search` path,
+//!   which searches the packages, registered workflows and datasets of
+//!   the instance at once.
+//
+
+use std::collections::HashMap;
+use std::str::FromStr as _;
+
+use log::{debug, error};
+use serde::Deserialize;
+use warp::{Rejection, Reply};
+use warp::http::{HeaderValue, Response};
+use warp::hyper::Body;
+
+use brane_cfg::node::NodeConfig;
+use specifications::data::DataInfo;
+use specifications::package::PackageKind;
+use specifications::search::{SearchKind, SearchResult, SearchResults};
+
+pub use crate::errors::SearchError as Error;
+use crate::db::{self, CachedPackage};
+use crate::errors::DatabaseError;
+use crate::federation;
+use crate::spec::Context;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use specifications::version::Version;
+    use uuid::Uuid;
+
+    use crate::packages::PackageUdt;
+    use super::*;
+
+
+    /// Returns a CachedPackage of the given name, kind and description.
+    fn package(name: &str, kind: &str, description: &str) -> CachedPackage {
+        CachedPackage {
+            file    : format!("/packages/{}.tar", name).into(),
+            package : PackageUdt {
+                created           : 0,
+                description       : description.into(),
+                detached          : false,
+                digest            : String::new(),
+                functions_as_json : "{}".into(),
+                id                : Uuid::nil(),
+                kind              : kind.into(),
+                name              : name.into(),
+                owners            : vec![],
+                types_as_json     : "{}".into(),
+                version           : "1.0.0".into(),
+            },
+        }
+    }
+
+    /// Returns a DataInfo of the given name and description.
+    fn dataset(name: &str, description: Option<&str>) -> (String, DataInfo) {
+        let info: DataInfo = DataInfo {
+            name        : name.into(),
+            version     : Version::new(1, 0, 0),
+            owners      : None,
+            description : description.map(String::from),
+            created     : Utc::now(),
+            residency   : None,
+            size        : None,
+            revisions   : HashMap::new(),
+            access      : HashMap::new(),
+        };
+        (info.id(), info)
+    }
+
+    /// Tests that packages are matched on name and description, and that workflows published as packages are found as such.
+    #[test]
+    fn test_search_packages() {
+        let packages = || vec![ package("hello", "ecu", "Says hello"), package("pipeline", "dsl", "Trains a model"), package("tool", "cwl", ""), package("broken", "???", "") ];
+
+        let kinds: Vec<(String, SearchKind)> = search_packages("", packages()).into_iter().map(|r| (r.name, r.kind)).collect();
+        assert_eq!(kinds, vec![ ("hello".into(), SearchKind::Package), ("pipeline".into(), SearchKind::Workflow), ("tool".into(), SearchKind::Workflow), ("broken".into(), SearchKind::Package) ]);
+
+        // The term is matched case-insensitively on the name or the description
+        let names: Vec<String> = search_packages("model", packages()).into_iter().map(|r| r.name).collect();
+        assert_eq!(names, vec![ "pipeline".to_string() ]);
+        let names: Vec<String> = search_packages("hel", packages()).into_iter().map(|r| r.name).collect();
+        assert_eq!(names, vec![ "hello".to_string() ]);
+
+        // Empty descriptions are omitted
+        assert_eq!(search_packages("tool", packages())[0].description, None);
+    }
+
+    /// Tests that datasets are matched on name and description.
+    #[test]
+    fn test_search_datasets() {
+        let datasets: HashMap<String, DataInfo> = HashMap::from([ dataset("census", Some("Population counts")), dataset("weather", None) ]);
+
+        let results: Vec<SearchResult> = search_datasets("population", datasets.clone());
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].kind, SearchKind::Dataset);
+        assert_eq!(results[0].name, "census");
+        assert_eq!(results[0].version.as_deref(), Some("1.0.0"));
+        assert_eq!(search_datasets("", datasets.clone()).len(), 2);
+        assert!(search_datasets("hello", datasets).is_empty());
+    }
+}
+
+
+
+
+
+/***** HELPER MACROS *****/
+/// Quits a path callback with a SecretError.
+macro_rules! fail {
+    () => {
+        return Err(warp::reject::custom(Error::SecretError))
+    };
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Checks whether the given term matches any of the given fields.
+///
+/// # Arguments
+/// - `term`: The (already lowercased) term to search for. An empty term matches everything.
+/// - `fields`: The fields to search in.
+///
+/// # Returns
+/// True if any of the fields contains the term (case-insensitive), or false otherwise.
+#[inline]
+fn matches<'a>(term: &str, fields: impl IntoIterator<Item = Option<&'a str>>) -> bool {
+    term.is_empty() || fields.into_iter().flatten().any(|f| f.to_lowercase().contains(term))
+}
+
+/// Searches the given packages.
+///
+/// Packages that contain a workflow (i.e., of the `dsl` or `cwl` kind) are how workflows are registered in the instance, and are thus returned as workflows.
+///
+/// # Arguments
+/// - `term`: The (already lowercased) term to search for.
+/// - `packages`: The packages to search through.
+///
+/// # Returns
+/// A list of SearchResults, one per matching package version.
+fn search_packages(term: &str, packages: Vec<CachedPackage>) -> Vec<SearchResult> {
+    packages.into_iter()
+        .map(|cached| cached.package)
+        .filter(|package| matches(term, [ Some(package.name.as_str()), Some(package.description.as_str()) ]))
+        .map(|package| SearchResult {
+            kind        : match PackageKind::from_str(&package.kind) {
+                Ok(PackageKind::Dsl) | Ok(PackageKind::Cwl) => SearchKind::Workflow,
+                _                                           => SearchKind::Package,
+            },
+            name        : package.name,
+            version     : Some(package.version),
+            description : if !package.description.is_empty() { Some(package.description) } else { None },
+        })
+        .collect()
+}
+
+/// Searches the datasets known in the instance.
+///
+/// # Arguments
+/// - `term`: The (already lowercased) term to search for.
+/// - `datasets`: The datasets to search through.
+///
+/// # Returns
+/// A list of SearchResults, one per matching dataset version.
+fn search_datasets(term: &str, datasets: HashMap<String, DataInfo>) -> Vec<SearchResult> {
+    datasets.into_values()
+        .filter(|info| matches(term, [ Some(info.name.as_str()), info.description.as_deref() ]))
+        .map(|info| SearchResult {
+            kind        : SearchKind::Dataset,
+            name        : info.name,
+            version     : Some(info.version.to_string()),
+            description : info.description,
+        })
+        .collect()
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines the query parameters of the `/search` path.
+#[derive(Clone, Debug, Deserialize)]
+pub struct SearchQuery {
+    /// The term to search for. If omitted, everything matches.
+    #[serde(default)]
+    pub q    : String,
+    /// If given, only returns results of this kind (the facets still count all kinds).
+    #[serde(default)]
+    pub kind : Option<SearchKind>,
+}
+
+
+
+/// Searches the packages, registered workflows and datasets in the instance (and in the peer instances we federate with) for the given term.
+///
+/// # Arguments
+/// - `query`: The SearchQuery with the term (and optional kind) to search for.
+/// - `federated`: The value of the `X-Brane-Federated`-header, if any. If given, the request comes from a peer, and we do not ask our own peers.
+/// - `context`: The Context that contains stuff we need to run.
+///
+/// # Returns
+/// A response that can be send to client. Specifically, it will contain a SearchResults struct with the matches grouped by kind, and the number of matches per kind as facets.
+///
+/// # Errors
+/// This function may error (i.e., reject the request) if we failed to query the database or load the infrastructure file. If the database is unavailable, packages are searched in the cache instead; only if there is no cache, a 503 is returned.
+pub async fn search(query: SearchQuery, federated: Option<String>, context: Context) -> Result<impl Reply, Rejection> {
+    debug!("Handling GET on `/search` (i.e., search the instance for '{}')...", query.q);
+    let term: String = query.q.to_lowercase();
+
+    // Collect the matches of every kind
+    let mut results: Vec<SearchResult> = match context.db.packages().await {
+        Ok(packages)                    => search_packages(&term, packages),
+        Err(DatabaseError::Unavailable) => { return Ok(db::unavailable()); },
+        Err(err)                        => {
+            error!("{}", Error::DbError{ err });
+            fail!();
+        },
+    };
+    results.extend(search_datasets(&term, crate::data::collect(&context, federated.is_none()).await?));
+
+    // Add the packages and workflows of our peers, skipping those we know ourselves
+    if federated.is_none() {
+        match NodeConfig::from_path(&context.node_config_path) {
+            Ok(node_config) => {
+                for result in federation::search_packages(&context, &node_config.node.central().peers, &query.q).await {
+                    if !results.iter().any(|r| r.kind == result.kind && r.name == result.name && r.version == result.version) { results.push(result); }
+                }
+            },
+            Err(err) => { error!("Failed to load NodeConfig file: {} (skipping peers)", err); },
+        }
+    }
+
+    // Count them before we filter
+    let mut facets: HashMap<SearchKind, usize> = SearchKind::all().into_iter().map(|k| (k, 0)).collect();
+    for result in &results {
+        *facets.entry(result.kind).or_default() += 1;
+    }
+    if let Some(kind) = query.kind {
+        results.retain(|r| r.kind == kind);
+    }
+    results.sort_by(|r1, r2| (r1.kind, &r1.name, &r1.version).cmp(&(r2.kind, &r2.name, &r2.version)));
+
+    // Now serialize them
+    let body: String = match serde_json::to_string(&SearchResults{ facets, results }) {
+        Ok(body) => body,
+        Err(err) => {
+            error!("{}", Error::SerializeError{ err });
+            fail!();
+        }
+    };
+    let body_len: usize = body.len();
+
+    // Create the respones around it
+    let mut response = Response::new(Body::from(body));
+    response.headers_mut().insert(
+        "Content-Length",
+        HeaderValue::from(body_len),
+    );
+
+    // Done
+    Ok(response)
+}
@@ -0,0 +1,125 @@
+//  SERVER.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 12:51:53
+//  Last edited:
+//    16 Oct 2026, 13:13:46
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Serves the API over TLS, telling the routes who the client is by
+//!   the certificate it authenticated itself with.
+// 
+
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+
+use log::{debug, error, info};
+use rustls::ServerConfig;
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::server::TlsStream;
+use warp::{Filter, Reply};
+use warp::hyper::server::conn::Http;
+use warp::hyper::service::{service_fn, Service as _};
+
+use brane_cfg::certs::{extract_client_name, load_server_config};
+use brane_cfg::node::CentralTlsConfig;
+
+pub use crate::errors::ServerError as Error;
+
+
+/***** LIBRARY *****/
+/// The identity of a client that authenticated itself with a certificate, which `serve_tls()` attaches to every request of that client.
+///
+/// Routes may read it with `warp::ext::optional::<ClientIdentity>()`. Requests that arrive in plaintext, or from clients without a (valid) certificate, do not carry one.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ClientIdentity {
+    /// The name of the client, as given by the `CN` field of its certificate.
+    pub name : String,
+}
+
+
+
+
+/// Serves the given warp filter over TLS.
+///
+/// Every request of a client that authenticated itself with a certificate carries its [`ClientIdentity`] as an extension.
+///
+/// # Arguments
+/// - `certs`: The directory with the server's certificate (`server.pem`), its key (`server-key.pem`) and the CA that signed the clients (`ca.pem`).
+/// - `tls`: The CentralTlsConfig that determines where to serve and whether clients must authenticate themselves.
+/// - `filter`: The warp filter to serve.
+///
+/// # Returns
+/// Nothing - and by that we mean it typically doesn't really return until the server is stopped for some reason.
+///
+/// # Errors
+/// This function errors if we failed to load the certificates or to bind to the address.
+pub async fn serve_tls<F, E>(certs: impl AsRef<Path>, tls: &CentralTlsConfig, filter: F) -> Result<(), Error>
+where
+    F: 'static + Send + Sync + Clone + Filter<Extract = E, Error = warp::Rejection>,
+    E: Reply,
+{
+    let certs: &Path = certs.as_ref();
+
+    // Load the TLS config first
+    debug!("Loading cryptography...");
+    let config: ServerConfig = match load_server_config(certs.join("server.pem"), certs.join("server-key.pem"), certs.join("ca.pem"), tls.require_client_auth) {
+        Ok(config) => config,
+        Err(err)   => { return Err(Error::ConfigLoadError{ err }); },
+    };
+    let acceptor: TlsAcceptor = TlsAcceptor::from(Arc::new(config));
+
+    // Start a TCP listener
+    let address: SocketAddr = tls.api;
+    debug!("Starting TLS server on '{}'...", address);
+    let server: TcpListener = match TcpListener::bind(&address).await {
+        Ok(server) => server,
+        Err(err)   => { return Err(Error::ServerBindError{ address, err }); },
+    };
+
+
+
+    // Enter the game loop; we await new connections
+    info!("Ready for TLS connections on '{}' (client certificates {})", address, if tls.require_client_auth { "required" } else { "optional" });
+    loop {
+        let (socket, client_addr) = match server.accept().await {
+            Ok(res)  => res,
+            Err(err) => {
+                error!("Failed to accept incoming connection: {}", err);
+                continue;
+            },
+        };
+
+        // Do the handshake and serve the connection in the background, so slow clients do not block others
+        let acceptor: TlsAcceptor = acceptor.clone();
+        let filter: F = filter.clone();
+        tokio::spawn(async move {
+            let stream: TlsStream<TcpStream> = match acceptor.accept(socket).await {
+                Ok(stream) => stream,
+                Err(err)   => {
+                    error!("Failed to accept incoming connection from '{}' with TLS: {}", client_addr, err);
+                    return;
+                },
+            };
+            let identity: Option<ClientIdentity> = stream.get_ref().1.peer_certificates()
+                .and_then(|certs| certs.first().and_then(|cert| extract_client_name(&cert.0)))
+                .map(|name| ClientIdentity{ name });
+            debug!("Accepted TLS connection from '{}' (client: {})", client_addr, identity.as_ref().map(|id| id.name.as_str()).unwrap_or("<anonymous>"));
+
+            // Tell the routes who they are talking to
+            let mut service = warp::service(filter);
+            let service = service_fn(move |mut req| {
+                if let Some(identity) = &identity { req.extensions_mut().insert(identity.clone()); }
+                service.call(req)
+            });
+            if let Err(err) = Http::new().serve_connection(stream, service).await {
+                error!("Failed to handle incoming request from '{}': {}", client_addr, err);
+            }
+        });
+    }
+}
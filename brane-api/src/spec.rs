@@ -15,10 +15,10 @@
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use scylla::Session;
-
 use brane_prx::client::ProxyClient;
 
+use crate::db::Database;
+
 
 /***** LIBRARY *****/
 /// Defines the context of all the path calls.
@@ -26,8 +26,8 @@ use brane_prx::client::ProxyClient;
 pub struct Context {
     /// Points to the `node.yml` file we use in warp functions.
     pub node_config_path : PathBuf,
-    /// Points to the Scylla database where we store package information.
-    pub scylla           : Arc<Session>,
+    /// Points to the Scylla database where we store package information (if it is available).
+    pub db               : Arc<Database>,
     /// The proxy client through which we send our requests.
     pub proxy            : Arc<ProxyClient>,
 }
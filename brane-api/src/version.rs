@@ -12,11 +12,16 @@
  *   Handles the /version path from in the API.
 **/
 
+use log::{debug, error};
 use warp::reply::Response;
 use warp::http::HeaderValue;
 use warp::hyper::Body;
 use warp::{Reply, Rejection};
 
+use brane_cfg::node::NodeConfig;
+
+use crate::spec::Context;
+
 
 /***** HANDLER *****/
 /// Handles the '/version' path.
@@ -35,3 +40,27 @@ pub async fn handle() -> Result<impl Reply, Rejection> {
 
     Ok(response)
 }
+
+
+
+/// Handles the '/version/features' path.
+/// 
+/// # Arguments
+/// - `context`: The Context that tells us where to find the node config file.
+/// 
+/// # Returns
+/// The FeatureFlags of the central node as JSON with '200 OK', so that clients may adapt to the subsystems it has enabled.
+/// 
+/// # Errors
+/// This function errors if we failed to load the node config file.
+pub async fn features(context: Context) -> Result<impl Reply, Rejection> {
+    debug!("Handling GET on `/version/features` (i.e., get feature flags)...");
+    let node_config: NodeConfig = match NodeConfig::from_path(&context.node_config_path) {
+        Ok(config) => config,
+        Err(err)   => {
+            error!("Failed to load NodeConfig file: {}", err);
+            return Err(warp::reject::reject());
+        },
+    };
+    Ok(warp::reply::json(&node_config.features))
+}
@@ -0,0 +1,87 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use warp::{Filter, Rejection, Reply};
+use warp::http::StatusCode;
+use warp::test::RequestBuilder;
+
+use brane_api::db::Database;
+use brane_api::routes::{routes, UNDOCUMENTED};
+use brane_api::spec::Context;
+use brane_cfg::spec::Address;
+use brane_prx::client::ProxyClient;
+use specifications::openapi::{Method, OPERATIONS};
+
+
+/***** HELPER FUNCTIONS *****/
+/// Returns the routes of the API with a context that points to nothing, so that every handler fails quickly.
+fn test_routes() -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone + Send + Sync + 'static {
+    routes(Context {
+        node_config_path : PathBuf::from("/nonexistent/node.yml"),
+        db               : Arc::new(Database::new(Address::hostname("localhost", 1), None)),
+        proxy            : Arc::new(ProxyClient::new(Address::hostname("localhost", 1))),
+    })
+}
+
+/// Returns every documented or deliberately undocumented route, as `(method, path)`.
+fn known() -> Vec<(Method, &'static str)> {
+    OPERATIONS.iter().map(|op| (op.method, op.path)).chain(UNDOCUMENTED.iter().copied()).collect()
+}
+
+/// Returns whether the given path matches the given pattern, where path parameters (`{name}`) match any segment.
+fn matches(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('/').collect();
+    let path: Vec<&str> = path.split('/').collect();
+    pattern.len() == path.len() && pattern.iter().zip(path.iter()).all(|(pattern, path)| (pattern.starts_with('{') && pattern.ends_with('}')) || pattern == path)
+}
+
+/// Returns whether the routes resolve the given request to a handler, i.e., whether they do not refuse it as an unknown path or method.
+///
+/// The handlers themselves may still fail, since they run without a node config or database.
+async fn resolves(routes: &(impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone + Send + Sync + 'static), method: Method, path: &str) -> bool {
+    let request = || -> RequestBuilder { warp::test::request().method(&method.as_str().to_uppercase()).path(path).body("") };
+
+    // If a handler replied, it was resolved (even if it replied with a 404)
+    if request().filter(routes).await.is_ok() { return true; }
+    // Otherwise, warp prefers the rejections of handlers (or their arguments) over those of routes that did not match
+    let status: StatusCode = request().reply(routes).await.status();
+    status != StatusCode::NOT_FOUND && status != StatusCode::METHOD_NOT_ALLOWED
+}
+
+
+
+
+
+/***** TESTS *****/
+#[tokio::test]
+async fn test_operations_are_served() {
+    let routes = test_routes();
+    for (method, path) in known() {
+        // Fill in the path parameters
+        let path: String = path.split('/').map(|segment| if segment.starts_with('{') { "x" } else { segment }).collect::<Vec<&str>>().join("/");
+        assert!(resolves(&routes, method, &path).await, "{} {} is described but not served", method.as_str().to_uppercase(), path);
+    }
+}
+
+#[tokio::test]
+async fn test_routes_are_described() {
+    let routes = test_routes();
+    let known: Vec<(Method, &'static str)> = known();
+
+    // Probe every path made of the segments we know about (and an unknown one, for the parameters), up to the longest known path
+    let mut segments: HashSet<&str> = known.iter().flat_map(|(_, path)| path.split('/')).filter(|segment| !segment.is_empty() && !segment.starts_with('{')).collect();
+    segments.insert("x");
+    let depth: usize = known.iter().map(|(_, path)| path.split('/').count() - 1).max().unwrap_or(0);
+    let mut paths: Vec<String> = vec![ String::new() ];
+    for _ in 0..depth {
+        paths = paths.iter().flat_map(|path| segments.iter().map(move |segment| format!("{}/{}", path, segment))).collect();
+        for path in &paths {
+            for method in [ Method::Get, Method::Post ] {
+                if resolves(&routes, method, path).await {
+                    assert!(known.iter().any(|(m, pattern)| *m == method && matches(pattern, path)), "{} {} is served but not described", method.as_str().to_uppercase(), path);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use std::fs;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use reqwest::{Certificate, Client, Identity};
+use warp::Filter;
+
+use brane_api::server::{serve_tls, ClientIdentity};
+use brane_cfg::node::CentralTlsConfig;
+
+
+/***** HELPER FUNCTIONS *****/
+/// Returns the directory with the test certificates (a CA, a server certificate for `localhost` and a client certificate for `alice`).
+fn certs() -> PathBuf { PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("certs") }
+
+/// Starts a TLS server on a free port with a single route that returns the name of the client.
+///
+/// # Arguments
+/// - `require_client_auth`: Whether the server requires clients to present a certificate.
+///
+/// # Returns
+/// The port on which the server listens.
+async fn start(require_client_auth: bool) -> u16 {
+    let port: u16 = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let address: SocketAddr = ([ 127, 0, 0, 1 ], port).into();
+    let tls: CentralTlsConfig = CentralTlsConfig { api: address, drv: address, internal_api: None, internal_drv: None, require_client_auth, approvers: vec![], admins: vec![] };
+
+    let whoami = warp::path("whoami")
+        .and(warp::ext::optional::<ClientIdentity>())
+        .map(|identity: Option<ClientIdentity>| identity.map(|identity| identity.name).unwrap_or_else(|| "<anonymous>".into()));
+    tokio::spawn(async move { serve_tls(certs(), &tls, whoami).await.unwrap(); });
+
+    // Wait until it accepts connections
+    for _ in 0..100 {
+        if tokio::net::TcpStream::connect(address).await.is_ok() { break; }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    port
+}
+
+/// Returns a client that trusts the test CA.
+///
+/// # Arguments
+/// - `identity`: Whether the client presents the certificate of `alice`.
+fn client(identity: bool) -> Client {
+    let mut builder = Client::builder()
+        .use_rustls_tls()
+        .add_root_certificate(Certificate::from_pem(&fs::read(certs().join("ca.pem")).unwrap()).unwrap());
+    if identity { builder = builder.identity(Identity::from_pem(&fs::read(certs().join("client-id.pem")).unwrap()).unwrap()); }
+    builder.build().unwrap()
+}
+
+/// Asks the server on the given port who we are.
+async fn whoami(client: Client, port: u16) -> Result<String, reqwest::Error> {
+    client.get(format!("https://localhost:{}/whoami", port)).send().await?.text().await
+}
+
+
+
+
+
+/***** TESTS *****/
+#[tokio::test]
+async fn test_refuses_clients_without_certificate() {
+    let port: u16 = start(true).await;
+    assert!(whoami(client(false), port).await.is_err(), "Client without a certificate was let in");
+    assert_eq!(whoami(client(true), port).await.unwrap(), "alice");
+}
+
+#[tokio::test]
+async fn test_accepts_anonymous_clients_if_allowed() {
+    let port: u16 = start(false).await;
+    assert_eq!(whoami(client(false), port).await.unwrap(), "<anonymous>");
+    assert_eq!(whoami(client(true), port).await.unwrap(), "alice");
+}
@@ -25,11 +25,12 @@ use enum_debug::EnumDebug;
 use serde::{Deserialize, Serialize};
 use serde_json_any_key::any_key_map;
 
-use brane_dsl::spec::MergeStrategy;
+use brane_dsl::spec::{MergeStrategy, RetryPolicy};
 use specifications::data::AvailabilityKind;
 use specifications::package::Capability;
 use specifications::version::Version;
 
+use crate::capabilities::{EngineCapability, EngineRequirements};
 use crate::data_type::DataType;
 use crate::locations::{Location, Locations};
 use crate::state::TableList;
@@ -56,6 +57,20 @@ pub struct Workflow {
     pub graph : Arc<Vec<Edge>>,
     /// Contains the parts of the graph that are callable.
     pub funcs : Arc<HashMap<usize, Vec<Edge>>>,
+
+    /// The capabilities that an engine needs to have to run this workflow.
+    #[serde(default)]
+    pub requires : EngineRequirements,
+
+    /// The parameters that this workflow declares, which have already been bound to a value at compile time.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub params : Vec<WorkflowParam>,
+
+    /// The locations where the restricted datasets used by this workflow, and the intermediate results derived from them, may be processed. Inputs that are absent may be processed anywhere.
+    /// 
+    /// The compiler fills this in as a hint, after which the planner overwrites it with what it derived from the data index itself.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty", with = "any_key_map")]
+    pub residency : HashMap<DataName, Vec<Location>>,
 }
 
 impl Workflow {
@@ -67,16 +82,52 @@ impl Workflow {
     /// - `funcs`: Auxillary edges that provide a kind of function-like paradigm to the edges.
     /// 
     /// # Returns
-    /// A new Workflow instance.
+    /// A new Workflow instance, which requires whatever engine capabilities are used by its edges.
     #[inline]
     pub fn new(table: SymTable, graph: Vec<Edge>, funcs: HashMap<usize, Vec<Edge>>) -> Self {
+        let requires: EngineRequirements = EngineRequirements::from_edges(&table, &graph, &funcs);
         Self {
             table : Arc::new(table),
 
             graph : Arc::new(graph),
             funcs : Arc::new(funcs),
+
+            requires,
+
+            params    : vec![],
+            residency : HashMap::new(),
         }
     }
+
+
+
+    /// Sets a workflow-wide retry policy, which applies to every task call that does not have its own.
+    /// 
+    /// # Arguments
+    /// - `policy`: The RetryPolicy to use for task calls without one.
+    pub fn set_default_retry(&mut self, policy: RetryPolicy) {
+        let graph: &mut Vec<Edge> = Arc::make_mut(&mut self.graph);
+        let funcs: &mut HashMap<usize, Vec<Edge>> = Arc::make_mut(&mut self.funcs);
+        for edge in graph.iter_mut().chain(funcs.values_mut().flat_map(|f| f.iter_mut())) {
+            if let Edge::Node{ retry, .. } = edge {
+                if retry.is_none() { *retry = Some(policy); }
+                self.requires.require(EngineCapability::Retries);
+            }
+        }
+    }
+
+
+
+    /// Returns whether this workflow calls any tasks, either in its main graph or in any of its functions.
+    /// 
+    /// Workflows that do not (e.g., arithmetic or index queries) are pure computation, and need no planning.
+    /// 
+    /// # Returns
+    /// True if there is at least one `Edge::Node` in this workflow, or false otherwise.
+    #[inline]
+    pub fn has_tasks(&self) -> bool {
+        self.graph.iter().chain(self.funcs.values().flat_map(|f| f.iter())).any(|edge| matches!(edge, Edge::Node{ .. }))
+    }
 }
 
 impl Default for Workflow {
@@ -87,12 +138,31 @@ impl Default for Workflow {
 
             graph : Arc::new(vec![]),
             funcs : Arc::new(HashMap::new()),
+
+            requires : EngineRequirements::default(),
+
+            params    : vec![],
+            residency : HashMap::new(),
         }
     }
 }
 
 
 
+/// Defines a parameter that a workflow declares (i.e., `param <name>: <type>;`).
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct WorkflowParam {
+    /// The name of the parameter.
+    pub name      : String,
+    /// The type of the parameter.
+    pub data_type : DataType,
+    /// The default value of the parameter as it would be given on the command line, if it has any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default   : Option<String>,
+}
+
+
+
 /// Defines the SymTable, which is like a symbol table (very much so, even) but now specific to Workflowland.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct SymTable {
@@ -203,6 +273,9 @@ pub enum TaskDef {
         /// Any requirements required for this task.
         #[serde(rename = "r")]
         requirements : HashSet<Capability>,
+        /// The names of the secrets that the worker should inject into this task's container.
+        #[serde(rename = "s", default, skip_serializing_if = "Vec::is_empty")]
+        secrets      : Vec<String>,
     },
 
     /// Defines a transfer task, i.e., a data transfer between two domains.
@@ -289,22 +362,28 @@ pub enum Edge {
     Node {
         /// The task to call
         #[serde(rename = "t")]
-        task   : usize,
+        task    : usize,
         /// An additional list that may or may not restrict locations.
         #[serde(rename = "l")]
-        locs   : Locations,
-        /// Annotation about where the task will be run. This is not meant to be populated by anyone except the planner.
+        locs    : Locations,
+        /// Annotation about where the task will be run. The compiler only sets it as a hint if the user pinned the task to a single location; otherwise, it is populated by the planner.
         #[serde(rename = "s")]
-        at     : Option<Location>,
+        at      : Option<Location>,
         /// Reference to any input datasets/results that are being input to this node together with how they might be accessed. This latter part is populated during planning.
         #[serde(rename = "i", with = "any_key_map")]
-        input  : HashMap<DataName, Option<AvailabilityKind>>,
+        input   : HashMap<DataName, Option<AvailabilityKind>>,
         /// Reference to the result if this call generates one.
         #[serde(rename = "r")]
-        result : Option<String>,
+        result  : Option<String>,
+        /// How often to retry the task if it fails, if at all.
+        #[serde(rename = "y", default, skip_serializing_if = "Option::is_none")]
+        retry   : Option<RetryPolicy>,
+        /// The time (in seconds) after which the task is killed if it has not completed yet, if any.
+        #[serde(rename = "o", default, skip_serializing_if = "Option::is_none")]
+        timeout : Option<u64>,
         /// The next edge to execute (usually the next one)
         #[serde(rename = "n")]
-        next   : usize,
+        next    : usize,
     },
     /// A Linear edge is simple a series of instructions that are run, after which is goes to one new edge.
     #[serde(rename = "lin")]
@@ -642,11 +721,24 @@ pub enum EdgeInstr {
         #[serde(rename = "t")]
         res_type : DataType,
     },
-    /// Gets the i'th element of the top element on the stack (as an Array). Note that the array itself should be on the second-to-top value on the stack, and the index the top one.
+    /// Groups the previous N key/value pairs on the stack into a Map.
+    /// 
+    /// # Stack layout
+    /// - N pairs of a string key followed by a value of the same data type (stored in the Map instruction itself) on top of the stack.
+    #[serde(rename = "map")]
+    Map {
+        /// The number of key/value pairs.
+        #[serde(rename = "l")]
+        length   : usize,
+        /// The data type of the values in this Map ('Map' excluded)
+        #[serde(rename = "t")]
+        res_type : DataType,
+    },
+    /// Gets the i'th element of the top element on the stack (as an Array), or the element with key i (as a Map). Note that the array itself should be on the second-to-top value on the stack, and the index the top one.
     /// 
     /// # Stack layout
-    /// - An integral value on top of the stack that is the index.
-    /// - An array value on second-to-top of the stack that is indexed.
+    /// - An integral value (or, for maps, a string value) on top of the stack that is the index.
+    /// - An array (or map) value on second-to-top of the stack that is indexed.
     #[serde(rename = "arx")]
     ArrayIndex {
         /// The data type of this index expression
@@ -766,6 +858,7 @@ impl Display for EdgeInstr {
             Ge{ .. } => write!(f, ".ge"),
 
             Array{ .. }      => write!(f, ".arr"),
+            Map{ .. }        => write!(f, ".map"),
             ArrayIndex{ .. } => write!(f, ".arr_idx"),
             Instance{ .. }   => write!(f, ".inst"),
             Proj{ .. }       => write!(f, ".proj"),
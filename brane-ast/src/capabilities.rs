@@ -0,0 +1,313 @@
+//  CAPABILITIES.rs
+//    by agent
+//
+//  Created:
+//    16 Oct 2026, 09:25:13
+//  Last edited:
+//    16 Oct 2026, 11:59:34
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines the capabilities that an engine (i.e., a VM) needs to have
+//!   in order to run a compiled Workflow, such that an instance can
+//!   reject workflows that use features it does not know about instead
+//!   of failing halfway through.
+//
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use specifications::version::Version;
+
+use crate::ast::{Edge, EdgeInstr, SymTable};
+use crate::errors::CompatibilityError;
+use crate::spec::BuiltinFunctions;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use brane_shr::utilities::{create_data_index, create_package_index};
+    use brane_dsl::ParserOptions;
+
+    use crate::ast::Workflow;
+    use crate::compile::{compile_program, CompileResult};
+    use super::*;
+
+
+    /// Compiles the given BraneScript snippet, returning the requirements that the compiler recorded.
+    fn requires(code: &str) -> EngineRequirements {
+        let workflow: Workflow = match compile_program(code.as_bytes(), &create_package_index(), &create_data_index(), &ParserOptions::bscript()) {
+            CompileResult::Workflow(workflow, _) => workflow,
+            CompileResult::Err(errs)             => { panic!("Failed to compile {:?}: {}", code, errs.iter().map(|e| e.to_string()).collect::<Vec<String>>().join("; ")); },
+            CompileResult::Eof(err)              => { panic!("Failed to compile {:?}: {}", code, err); },
+            _                                    => { unreachable!(); },
+        };
+        // What the compiler records must be what the driver deduces itself
+        assert_eq!(workflow.requires, EngineRequirements::from_edges(&workflow.table, &workflow.graph, &workflow.funcs));
+        workflow.requires
+    }
+
+    /// Returns the names of the given capabilities, as they are stored.
+    fn names(capabilities: &[EngineCapability]) -> BTreeSet<String> { capabilities.iter().map(|c| c.name().to_string()).collect() }
+
+    #[test]
+    fn test_capability_names() {
+        for capability in EngineCapability::ALL {
+            assert_eq!(EngineCapability::from_str(capability.name()), Ok(capability));
+            assert_eq!(capability.to_string(), capability.name());
+        }
+        assert_eq!(EngineCapability::from_str("teleport"), Err(()));
+    }
+
+    #[test]
+    fn test_since() {
+        // Capabilities introduced after the last release are supported since this engine
+        let engine: Version = Version::from_str(env!("CARGO_PKG_VERSION")).unwrap();
+        for capability in EngineCapability::ALL {
+            assert!(capability.since() <= engine, "Capability '{}' is supported since {}, which is newer than this engine ({})", capability, capability.since(), engine);
+        }
+    }
+
+    #[test]
+    fn test_from_edges() {
+        // Workflows that only use what the first release knew require nothing
+        let reqs: EngineRequirements = requires("println(\"Hello, world!\");\nlet xs := [ 1, 2, 3 ];\nprintln(len(xs));\n");
+        assert!(reqs.capabilities.is_empty());
+        assert_eq!(reqs.version, Version::default());
+
+        // Builtins are found in the main graph...
+        assert_eq!(requires("lock(\"api\");\nunlock(\"api\");\n").capabilities, names(&[ EngineCapability::Locks ]));
+        assert_eq!(requires("println(to_upper(\"hello\"));\n").capabilities, names(&[ EngineCapability::Strings ]));
+        // ...and in the bodies of functions
+        let reqs: EngineRequirements = requires("func pause() {\n    sleep(1);\n}\npause();\nnotify(\"ops\", \"done\");\n");
+        assert_eq!(reqs.capabilities, names(&[ EngineCapability::Sleep, EngineCapability::Notify ]));
+        assert_eq!(reqs.version, EngineCapability::Sleep.since().max(EngineCapability::Notify.since()));
+
+        // Annotations on task calls are found on the nodes
+        let reqs: EngineRequirements = requires("import hello_world;\n@retry(2)\n@timeout(10)\nhello_world();\n");
+        assert_eq!(reqs.capabilities, names(&[ EngineCapability::Retries, EngineCapability::Timeouts ]));
+        assert!(requires("import hello_world;\nhello_world();\n").capabilities.is_empty());
+    }
+
+    #[test]
+    fn test_check() {
+        let mut reqs: EngineRequirements = EngineRequirements::default();
+        assert!(reqs.check().is_ok());
+        reqs.require(EngineCapability::Locks);
+        assert!(reqs.check().is_ok());
+
+        // Capabilities from newer engines are reported by name, together with the version that supports them
+        let newer: EngineRequirements = EngineRequirements{ capabilities: BTreeSet::from([ "teleport".into(), "time_travel".into() ]), version: Version::new(9, 0, 0) };
+        reqs.merge(&newer);
+        assert_eq!(reqs.version, Version::new(9, 0, 0));
+        match reqs.check() {
+            Err(CompatibilityError::MissingCapabilities{ missing, version }) => {
+                assert_eq!(missing, vec![ "teleport".to_string(), "time_travel".to_string() ]);
+                assert_eq!(version, Version::new(9, 0, 0));
+            },
+            Ok(_) => { panic!("Accepted capabilities that this engine does not know"); },
+        }
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Returns the version of this engine, i.e., of this crate.
+#[inline]
+fn engine_version() -> Version { Version::from_str(env!("CARGO_PKG_VERSION")).unwrap_or_default() }
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines the capabilities of an engine that a Workflow may require beyond those of the first stable release.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum EngineCapability {
+    /// The `lock` and `unlock` builtins.
+    Locks,
+    /// The `sleep` and `wait_until` builtins.
+    Sleep,
+    /// The `wait_for_event` builtin.
+    Events,
+    /// The `require_approval` builtin.
+    Approvals,
+    /// The `notify` builtin.
+    Notify,
+    /// Retry policies on task calls (i.e., `@retry(...)`).
+    Retries,
+    /// Timeouts on task calls (i.e., `@timeout(...)`).
+    Timeouts,
+    /// Map literals and the `keys` builtin.
+    Maps,
+    /// The string builtins (`split`, `join`, `to_upper`, `substring`, `contains`, `parse_int` and `parse_real`).
+    Strings,
+    /// The index query builtins (`list_packages`, `list_datasets` and `dataset_size`).
+    Indices,
+}
+
+impl EngineCapability {
+    /// All the capabilities that this engine supports.
+    pub const ALL: [Self; 10] = [ Self::Locks, Self::Sleep, Self::Events, Self::Approvals, Self::Notify, Self::Retries, Self::Timeouts, Self::Maps, Self::Strings, Self::Indices ];
+
+
+
+    /// Returns the identifier of this capability as it is stored in a Workflow.
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        use EngineCapability::*;
+        match self {
+            Locks     => "locks",
+            Sleep     => "sleep",
+            Events    => "events",
+            Approvals => "approvals",
+            Notify    => "notify",
+            Retries   => "retries",
+            Timeouts  => "timeouts",
+            Maps      => "maps",
+            Strings   => "strings",
+            Indices   => "indices",
+        }
+    }
+
+    /// Returns the first version of the engine that supports this capability.
+    /// 
+    /// Capabilities that have been introduced since the last release are supported since the version of this engine (i.e., of this crate). Once released, they should be pinned to the version they were released in.
+    #[inline]
+    pub fn since(&self) -> Version {
+        use EngineCapability::*;
+        match self {
+            Locks | Sleep | Events | Approvals | Notify | Retries | Timeouts | Maps | Strings | Indices => engine_version(),
+        }
+    }
+}
+
+impl Display for EngineCapability {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for EngineCapability {
+    type Err = ();
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL.into_iter().find(|c| c.name() == s).ok_or(())
+    }
+}
+
+
+
+/// Defines what a Workflow requires of the engine that runs it.
+///
+/// Capabilities are stored by name, so that an engine that does not know a capability can still tell which it is missing.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct EngineRequirements {
+    /// The names of the capabilities required.
+    #[serde(rename = "c", default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub capabilities : BTreeSet<String>,
+    /// The minimum version of the engine that supports all of them.
+    #[serde(rename = "v", default)]
+    pub version      : Version,
+}
+
+impl EngineRequirements {
+    /// Deduces the requirements of the given graph.
+    ///
+    /// # Arguments
+    /// - `table`: The workflow table that the edges refer to.
+    /// - `graph`: The main edges of the workflow.
+    /// - `funcs`: The bodies of the functions in the workflow.
+    ///
+    /// # Returns
+    /// A new EngineRequirements that lists every capability used by the edges.
+    pub fn from_edges(table: &SymTable, graph: &[Edge], funcs: &HashMap<usize, Vec<Edge>>) -> Self {
+        let mut res: Self = Self::default();
+        for edge in graph.iter().chain(funcs.values().flatten()) {
+            match edge {
+                Edge::Node{ retry, timeout, .. } => {
+                    if retry.is_some() { res.require(EngineCapability::Retries); }
+                    if timeout.is_some() { res.require(EngineCapability::Timeouts); }
+                },
+
+                Edge::Linear{ instrs, .. } => {
+                    for instr in instrs {
+                        if let EdgeInstr::Map{ .. } = instr { res.require(EngineCapability::Maps); continue; }
+
+                        // Otherwise, only builtins are relevant, which have no body of their own
+                        let def: usize = match instr {
+                            EdgeInstr::Function{ def } if !funcs.contains_key(def) => *def,
+                            _                                                      => { continue; },
+                        };
+                        if def < table.funcs.offset() || def - table.funcs.offset() >= table.funcs.len() { continue; }
+                        let name: &str = &table.funcs[def].name;
+                        if name == BuiltinFunctions::Lock.name() || name == BuiltinFunctions::Unlock.name() {
+                            res.require(EngineCapability::Locks);
+                        } else if name == BuiltinFunctions::Sleep.name() || name == BuiltinFunctions::WaitUntil.name() {
+                            res.require(EngineCapability::Sleep);
+                        } else if name == BuiltinFunctions::WaitForEvent.name() {
+                            res.require(EngineCapability::Events);
+                        } else if name == BuiltinFunctions::RequireApproval.name() {
+                            res.require(EngineCapability::Approvals);
+                        } else if name == BuiltinFunctions::Notify.name() {
+                            res.require(EngineCapability::Notify);
+                        } else if name == BuiltinFunctions::Keys.name() {
+                            res.require(EngineCapability::Maps);
+                        } else if [ BuiltinFunctions::Split, BuiltinFunctions::Join, BuiltinFunctions::ToUpper, BuiltinFunctions::Substring, BuiltinFunctions::Contains, BuiltinFunctions::ParseInt, BuiltinFunctions::ParseReal ].iter().any(|f| name == f.name()) {
+                            res.require(EngineCapability::Strings);
+                        } else if [ BuiltinFunctions::ListPackages, BuiltinFunctions::ListDatasets, BuiltinFunctions::DatasetSize ].iter().any(|f| name == f.name()) {
+                            res.require(EngineCapability::Indices);
+                        }
+                    }
+                },
+
+                _ => {},
+            }
+        }
+        res
+    }
+
+
+
+    /// Adds the given capability to the requirements.
+    ///
+    /// # Arguments
+    /// - `capability`: The EngineCapability to require.
+    pub fn require(&mut self, capability: EngineCapability) {
+        self.capabilities.insert(capability.name().into());
+        let since: Version = capability.since();
+        if since > self.version { self.version = since; }
+    }
+
+    /// Adds the given requirements to these requirements.
+    ///
+    /// # Arguments
+    /// - `other`: The EngineRequirements to add.
+    pub fn merge(&mut self, other: &Self) {
+        self.capabilities.extend(other.capabilities.iter().cloned());
+        if other.version > self.version { self.version = other.version.clone(); }
+    }
+
+    /// Checks whether this engine meets the requirements.
+    ///
+    /// # Errors
+    /// This function errors if any of the required capabilities is unknown to this engine, listing all of them together with the version that supports them.
+    pub fn check(&self) -> Result<(), CompatibilityError> {
+        let missing: Vec<String> = self.capabilities.iter().filter(|c| EngineCapability::from_str(c).is_err()).cloned().collect();
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(CompatibilityError::MissingCapabilities{ missing, version: self.version.clone() })
+        }
+    }
+}
@@ -12,6 +12,7 @@
 //!   Defines some toplevel functions that run all traversals as desired.
 // 
 
+use std::collections::HashMap;
 use std::fmt::{Display, Formatter, Result as FResult};
 
 use brane_dsl::{Error as ParseError, ParserOptions};
@@ -21,7 +22,8 @@ use specifications::package::PackageIndex;
 
 pub use crate::errors::AstError as Error;
 pub use crate::warnings::AstWarning as Warning;
-use crate::ast::Workflow;
+use crate::ast::{DataName, Edge, Workflow, WorkflowParam};
+use crate::locations::Location;
 use crate::ast_unresolved::UnresolvedWorkflow;
 use crate::state::CompileState;
 use crate::traversals;
@@ -183,6 +185,40 @@ impl Display for CompileResult {
 
 
 
+/***** HELPER FUNCTIONS *****/
+/// Collects where the restricted inputs of the tasks in a workflow may be processed, so that the planner and workers may enforce it too.
+/// 
+/// # Arguments
+/// - `state`: The CompileState that knows the residency of the intermediate results (as derived by the `data` traversal).
+/// - `data_index`: The DataIndex that knows the residency of the datasets.
+/// - `workflow`: The Workflow of which to collect the inputs.
+/// 
+/// # Returns
+/// A map of every restricted input (and result) of the workflow's tasks to the locations where it may be processed, sorted.
+fn collect_residency(state: &CompileState, data_index: &DataIndex, workflow: &Workflow) -> HashMap<DataName, Vec<Location>> {
+    let mut residency: HashMap<DataName, Vec<Location>> = HashMap::new();
+    for edge in workflow.graph.iter().chain(workflow.funcs.values().flat_map(|f| f.iter())) {
+        if let Edge::Node{ input, result, .. } = edge {
+            for name in input.keys().cloned().chain(result.iter().cloned().map(DataName::IntermediateResult)) {
+                if residency.contains_key(&name) { continue; }
+                let locs: Option<Vec<Location>> = match &name {
+                    DataName::Data(name)               => data_index.get(name).and_then(|info| info.residency.clone()),
+                    DataName::IntermediateResult(name) => state.data.get_residency(name).map(|locs| locs.iter().cloned().collect()),
+                };
+                if let Some(mut locs) = locs {
+                    locs.sort();
+                    residency.insert(name, locs);
+                }
+            }
+        }
+    }
+    residency
+}
+
+
+
+
+
 /***** LIBRARY *****/
 /// Runs the compiler passes in-order, all of them.
 /// 
@@ -282,11 +318,13 @@ pub fn compile_snippet_to<R: std::io::Read>(state: &mut CompileState, reader: R,
         Err(ParseError::Eof{ lang, err }) => { return CompileResult::Eof(Error::ParseError{ err: ParseError::Eof { lang, err } }); },
         Err(err)                          => { return CompileResult::Err(vec![ Error::ParseError{ err } ]); },
     };
+    // Remember which parameters it declared for the workflow
+    let params: Vec<WorkflowParam> = program.params.iter().map(|p| WorkflowParam{ name: p.name.clone(), data_type: (&p.data_type).into(), default: p.default.clone() }).collect();
 
     // Run the various traversals
     // First up: program analysis (resolving symbol tables, type analysis, location analysis)
     if stage >= CompileStage::Resolve {
-        program = match traversals::resolve::do_traversal(state, package_index, data_index, program) {
+        program = match traversals::resolve::do_traversal(state, package_index, data_index, program, &mut warnings) {
             Ok(program) => program,
             Err(errs)   => { return CompileResult::Err(errs); },
         };
@@ -304,13 +342,13 @@ pub fn compile_snippet_to<R: std::io::Read>(state: &mut CompileState, reader: R,
         };
     }
     if stage >= CompileStage::Data {
-        program = match traversals::data::do_traversal(state, program) {
+        program = match traversals::data::do_traversal(state, data_index, program) {
             Ok(program) => program,
             Err(errs)   => { return CompileResult::Err(errs); },
         };
     }
     if stage >= CompileStage::Location {
-        program = match traversals::location::do_traversal(program) {
+        program = match traversals::location::do_traversal(program, options.locations.as_ref()) {
             Ok(program) => program,
             Err(errs)   => { return CompileResult::Err(errs); },
         };
@@ -352,10 +390,12 @@ pub fn compile_snippet_to<R: std::io::Read>(state: &mut CompileState, reader: R,
         // Finally, resolve the workflow
         if stage >= CompileStage::WorkflowResolve {
             // Yup resolving happening here
-            let workflow = match traversals::workflow_resolve::do_traversal(state, uworkflow) {
+            let mut workflow = match traversals::workflow_resolve::do_traversal(state, uworkflow) {
                 Ok(workflow) => workflow,
                 Err(errs)    => { return CompileResult::Err(errs); },
             };
+            workflow.params = params;
+            workflow.residency = collect_residency(state, data_index, &workflow);
 
             // We can return as a workflow
             return CompileResult::Workflow(workflow, warnings);
@@ -99,6 +99,12 @@ pub enum DataType {
         #[serde(rename = "t")]
         elem_type : Box<DataType>,
     },
+    /// Maps (i.e., a dictionary from string keys to homogeneous types).
+    #[serde(rename = "map")]
+    Map{
+        #[serde(rename = "t")]
+        elem_type : Box<DataType>,
+    },
     /// Functions (i.e., executable pieces of code). Contains both the types (and arity) of its arguments and the return type.
     #[serde(rename = "func")]
     Function{
@@ -157,6 +163,7 @@ impl DataType {
 
             // Recursive cases
             (Array{ elem_type: lhs }, Array{ elem_type: rhs }) => lhs.allowed_by(rhs),
+            (Map{ elem_type: lhs }, Map{ elem_type: rhs })     => lhs.allowed_by(rhs),
 
             // General case
             (t1, t2) => t1 == t2,
@@ -191,6 +198,7 @@ impl Display for DataType {
             Semver  => write!(f, "Semver"),
 
             Array{ elem_type }    => write!(f, "Array<{}>", elem_type),
+            Map{ elem_type }      => write!(f, "Map<{}>", elem_type),
             Function{ args, ret } => write!(f, "Func<({}){}>", args.iter().map(|t| format!("{}", t)).collect::<Vec<std::string::String>>().join(", "), if **ret != DataType::Void { format!(" -> {}", ret) } else { std::string::String::new() }),
             Class{ name }         => write!(f, "Class<{}>", name),
             Data                  => write!(f, "Data"),
@@ -215,6 +223,7 @@ impl From<brane_dsl::DataType> for DataType {
             Semver  => Self::Semver,
 
             Array(a)      => Self::Array{ elem_type: a.into() },
+            Map(m)        => Self::Map{ elem_type: m.into() },
             Function(sig) => Self::Function{ args: sig.args.into_iter().map(|d| d.into()).collect(), ret: Box::new(sig.ret.into()) },
             Class(name)   => {
                 // Match if 'Data' or 'IntermediateResult'
@@ -246,6 +255,7 @@ impl From<&brane_dsl::DataType> for DataType {
             Semver  => Self::Semver,
 
             Array(a)      => Self::Array{ elem_type: a.into() },
+            Map(m)        => Self::Map{ elem_type: m.into() },
             Function(sig) => Self::Function{ args: sig.args.iter().map(|d| d.into()).collect(), ret: Box::new((&sig.ret).into()) },
             Class(name)   => {
                 // Match if 'Data' or 'IntermediateResult'
@@ -283,6 +293,10 @@ impl From<&str> for DataType {
         } else if value.len() >= 2 && &value[value.len() - 2..] == "[]" {
             return Self::Array{ elem_type: Box::new(Self::from(&value[..value.len() - 2])) };
         }
+        // Maps are done recursively too
+        if value.len() >= 5 && value.get(..4).map(|p| p.eq_ignore_ascii_case("map<")).unwrap_or(false) && value.ends_with('>') {
+            return Self::Map{ elem_type: Box::new(Self::from(&value[4..value.len() - 1])) };
+        }
 
         // Otherwise, match literals & classes
         use DataType::*;
@@ -336,6 +336,8 @@ pub enum AstError {
     TypeError(TypeError),
     /// An error has occurred during null-analysis.
     NullError(NullError),
+    /// An error has occurred during data analysis.
+    DataError(DataError),
     /// An error has occurred during location analysis.
     LocationError(LocationError),
     /// An error has occurred while pruning the tree for compilation.
@@ -369,11 +371,35 @@ impl AstError {
             ResolveError(err)  => err.prettyprint(file, source),
             TypeError(err)     => err.prettyprint(file, source),
             NullError(err)     => err.prettyprint(file, source),
+            DataError(err)     => err.prettyprint(file, source),
             LocationError(err) => err.prettyprint(file, source),
             PruneError(err)    => err.prettyprint(file, source),
             FlattenError(err)  => err.prettyprint(file, source),
         }
     }
+
+    /// Returns the range in the source text that this error is about, if any.
+    /// 
+    /// # Returns
+    /// A reference to the TextRange, or `None` if the error does not relate to a specific part of the source.
+    #[inline]
+    pub fn range(&self) -> Option<&TextRange> {
+        use AstError::*;
+        match self {
+            ReaderReadError { .. } => None,
+            ParseError { .. }      => None,
+            WriteError{ .. }       => None,
+
+            SanityError(err)   => err.range(),
+            ResolveError(err)  => err.range(),
+            TypeError(err)     => err.range(),
+            NullError(err)     => err.range(),
+            DataError(err)     => err.range(),
+            LocationError(err) => err.range(),
+            PruneError(err)    => err.range(),
+            FlattenError(err)  => err.range(),
+        }
+    }
 }
 
 impl From<SanityError> for AstError {
@@ -400,6 +426,12 @@ impl From<NullError> for AstError {
         Self::NullError(err)
     }
 }
+impl From<DataError> for AstError {
+    #[inline]
+    fn from(err: DataError) -> Self {
+        Self::DataError(err)
+    }
+}
 impl From<LocationError> for AstError {
     #[inline]
     fn from(err: LocationError) -> Self {
@@ -432,6 +464,7 @@ impl Display for AstError {
             ResolveError(err)  => write!(f, "{}", err),
             TypeError(err)     => write!(f, "{}", err),
             NullError(err)     => write!(f, "{}", err),
+            DataError(err)     => write!(f, "{}", err),
             LocationError(err) => write!(f, "{}", err),
             PruneError(err)    => write!(f, "{}", err),
             FlattenError(err)  => write!(f, "{}", err),
@@ -470,6 +503,18 @@ impl SanityError {
             ProjError{ range, .. } => prettyprint_err(file, source, self, range),
         }
     }
+
+    /// Returns the range in the source text that this error is about, if any.
+    /// 
+    /// # Returns
+    /// A reference to the TextRange, or `None` if the error does not relate to a specific part of the source.
+    #[inline]
+    pub fn range(&self) -> Option<&TextRange> {
+        use SanityError::*;
+        match self {
+            ProjError{ range, .. } => Some(range),
+        }
+    }
 }
 
 impl Display for SanityError {
@@ -581,6 +626,43 @@ impl ResolveError {
             UndefinedVariable{ range, .. } => prettyprint_err(file, source, self, range),
         }
     }
+
+    /// Returns the range in the source text that this error is about, if any.
+    /// 
+    /// # Returns
+    /// A reference to the TextRange, or `None` if the error does not relate to a specific part of the source.
+    #[inline]
+    pub fn range(&self) -> Option<&TextRange> {
+        use ResolveError::*;
+        match self {
+            VersionParseError{ range, .. }   => Some(range),
+            UnknownPackageError{ range, .. } => Some(range),
+            FunctionImportError{ range, .. } => Some(range),
+            ClassImportError{ range, .. }    => Some(range),
+
+            FunctionDefineError{ range, .. }  => Some(range),
+            ParameterDefineError{ range, .. } => Some(range),
+
+            ClassDefineError{ range, .. }               => Some(range),
+            UndefinedClass{ range, .. }                 => Some(range),
+            DuplicateMethodAndProperty{ new_range, .. } => Some(new_range),
+            IllegalSelf{ range, .. }                    => Some(range),
+            MissingSelf{ range, .. }                    => Some(range),
+
+            UnknownMergeStrategy{ range, .. } => Some(range),
+            VariableDefineError{ range, .. }  => Some(range),
+
+            UndefinedFunction{ range, .. } => Some(range),
+
+            NonClassProjection{ range, .. } => Some(range),
+            UnknownField{ range, .. }       => Some(range),
+
+            DataIncorrectExpr{ range, .. } => Some(range),
+            UnknownDataError{ range, .. }  => Some(range),
+
+            UndefinedVariable{ range, .. } => Some(range),
+        }
+    }
 }
 
 impl Display for ResolveError {
@@ -661,6 +743,8 @@ pub enum TypeError {
 
     /// An Array had confusing types
     InconsistentArrayError{ got: DataType, expected: DataType, got_range: TextRange, expected_range: TextRange },
+    /// A Map had confusing types
+    InconsistentMapError{ got: DataType, expected: DataType, got_range: TextRange, expected_range: TextRange },
 
     /// An Array Index was used on a non-array.
     NonArrayIndexError{ got: DataType, range: TextRange },
@@ -709,6 +793,7 @@ impl TypeError {
             FunctionArityError{ got_range, expected_range, .. } => prettyprint_err_exp_got(file, source, self, expected_range, got_range),
 
             InconsistentArrayError{ got_range, expected_range, .. } => prettyprint_err_exp_got(file, source, self, expected_range, got_range),
+            InconsistentMapError{ got_range, expected_range, .. }   => prettyprint_err_exp_got(file, source, self, expected_range, got_range),
 
             NonArrayIndexError{ range, .. } => prettyprint_err(file, source, self, range),
 
@@ -716,6 +801,44 @@ impl TypeError {
             DataNoNamePropertyError{ range, .. } => prettyprint_err(file, source, self, range),
         }
     }
+
+    /// Returns the range in the source text that this error is about, if any.
+    /// 
+    /// # Returns
+    /// A reference to the TextRange, or `None` if the error does not relate to a specific part of the source.
+    #[inline]
+    pub fn range(&self) -> Option<&TextRange> {
+        use TypeError::*;
+        match self {
+            ProjOnNonClassError{ range, .. } => Some(range),
+            UnexpectedMethod{ range, .. }    => Some(range),
+            UnknownField{ range, .. }        => Some(range),
+
+            IncorrectType{ range, .. } => Some(range),
+
+            IllegalDataReturnError{ range, .. } => Some(range),
+
+            IncompatibleReturns{ got_range, .. } => Some(got_range),
+
+            ParallelNoReturn{ range, .. }         => Some(range),
+            ParallelUnexpectedReturn{ range, .. } => Some(range),
+            ParallelIncompleteReturn{ range, .. } => Some(range),
+            ParallelIllegalType{ range, .. }      => Some(range),
+            ParallelNoStrategy{ range, .. }       => Some(range),
+
+            NonFunctionCall{ range, .. }        => Some(range),
+            UndefinedFunctionCall{ range, .. }  => Some(range),
+            FunctionArityError{ got_range, .. } => Some(got_range),
+
+            InconsistentArrayError{ got_range, .. } => Some(got_range),
+            InconsistentMapError{ got_range, .. }   => Some(got_range),
+
+            NonArrayIndexError{ range, .. } => Some(range),
+
+            DataNameNotAStringError{ range, .. } => Some(range),
+            DataNoNamePropertyError{ range, .. } => Some(range),
+        }
+    }
 }
 
 impl Display for TypeError {
@@ -744,8 +867,9 @@ impl Display for TypeError {
             FunctionArityError{ name, got, expected, .. } => write!(f, "Function '{}' expected {} arguments, but {} were given", name, expected, got),
 
             InconsistentArrayError{ got, expected, .. } => write!(f, "Array expression has conflicting type requirements: started out as {}, got {}", expected, got),
+            InconsistentMapError{ got, expected, .. }   => write!(f, "Map expression has conflicting type requirements: started out as {}, got {}", expected, got),
 
-            NonArrayIndexError{ got, .. } => write!(f, "Cannot index non-Array type {}", got),
+            NonArrayIndexError{ got, .. } => write!(f, "Cannot index non-Array, non-Map type {}", got),
 
             DataNameNotAStringError{ name, got, .. } => write!(f, "Expected class {} to have a `name` property with a literal string, got {:?}", name, got),
             DataNoNamePropertyError{ name, .. }      => write!(f, "Missing `name` property for class {}", name),
@@ -784,6 +908,18 @@ impl NullError {
             IllegalNull{ range } => prettyprint_err(file, source, self, range),
         }
     }
+
+    /// Returns the range in the source text that this error is about, if any.
+    /// 
+    /// # Returns
+    /// A reference to the TextRange, or `None` if the error does not relate to a specific part of the source.
+    #[inline]
+    pub fn range(&self) -> Option<&TextRange> {
+        use NullError::*;
+        match self {
+            IllegalNull{ range, .. } => Some(range),
+        }
+    }
 }
 
 impl Display for NullError {
@@ -799,6 +935,60 @@ impl Error for NullError {}
 
 
 
+/// Defines errors that occur while analysing data dependencies.
+#[derive(Debug)]
+pub enum DataError {
+    /// An external call would process restricted datasets on a location where that is not allowed.
+    ResidencyViolation{ range: TextRange, data: Vec<String>, allowed: Vec<String> },
+}
+
+impl DataError {
+    /// Prints the error in a pretty way to stderr.
+    /// 
+    /// # Generic arguments:
+    /// - `S1`: The &str-like type of the `file` path.
+    /// - `S2`: The &str-like type of the `source` text.
+    /// 
+    /// # Arguments
+    /// - `file`: The 'path' of the file (or some other identifier) where the source text originates from.
+    /// - `source`: The source text to read the debug range from.
+    /// 
+    /// # Returns
+    /// Nothing, but does print the error to stderr.
+    #[inline]
+    pub fn prettyprint<S1: AsRef<str>, S2: AsRef<str>>(&self, file: S1, source: S2) {
+        use DataError::*;
+        match self {
+            ResidencyViolation{ range, .. } => prettyprint_err(file, source, self, range),
+        }
+    }
+
+    /// Returns the range in the source text that this error is about, if any.
+    /// 
+    /// # Returns
+    /// A reference to the TextRange, or `None` if the error does not relate to a specific part of the source.
+    #[inline]
+    pub fn range(&self) -> Option<&TextRange> {
+        use DataError::*;
+        match self {
+            ResidencyViolation{ range, .. } => Some(range),
+        }
+    }
+}
+
+impl Display for DataError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use DataError::*;
+        match self {
+            ResidencyViolation{ data, allowed, .. } => write!(f, "External function call processes restricted data ({}), but cannot run on any location where that is allowed ({})", data.iter().map(|d| format!("'{}'", d)).collect::<Vec<String>>().join(", "), if !allowed.is_empty() { allowed.iter().map(|l| format!("'{}'", l)).collect::<Vec<String>>().join(", ") } else { "none".into() }),
+        }
+    }
+}
+
+impl Error for DataError {}
+
+
+
 /// Defines errors that occur during location resolving.
 #[derive(Debug)]
 pub enum LocationError {
@@ -806,6 +996,8 @@ pub enum LocationError {
     IllegalLocation{ range: TextRange },
     /// An On-structure combination already limited the locations too much.
     OnNoLocation{ range: TextRange, reasons: Vec<TextRange> },
+    /// An On-structure or annotation referred to a location that does not exist in the instance.
+    UnknownLocation{ name: String, range: TextRange },
 
     /// The usage of On-structures and/or annotations caused a function to never-ever be able to run.
     NoLocation{ range: TextRange, reasons: Vec<TextRange> },
@@ -830,10 +1022,27 @@ impl LocationError {
         match self {
             IllegalLocation{ range, .. }       => prettyprint_err(file, source, self, range),
             OnNoLocation{ range, reasons, .. } => prettyprint_err_reasons(file, source, self, range, reasons),
+            UnknownLocation{ range, .. }       => prettyprint_err(file, source, self, range),
 
             NoLocation{ range, reasons, .. } => prettyprint_err_reasons(file, source, self, range, reasons),
         }
     }
+
+    /// Returns the range in the source text that this error is about, if any.
+    /// 
+    /// # Returns
+    /// A reference to the TextRange, or `None` if the error does not relate to a specific part of the source.
+    #[inline]
+    pub fn range(&self) -> Option<&TextRange> {
+        use LocationError::*;
+        match self {
+            IllegalLocation{ range, .. } => Some(range),
+            OnNoLocation{ range, .. }    => Some(range),
+            UnknownLocation{ range, .. } => Some(range),
+
+            NoLocation{ range, .. } => Some(range),
+        }
+    }
 }
 
 impl Display for LocationError {
@@ -841,8 +1050,9 @@ impl Display for LocationError {
     fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
         use LocationError::*;
         match self {
-            IllegalLocation{ .. } => write!(f, "On-structures can only accept string literals as location specifiers."),
-            OnNoLocation{ .. }    => write!(f, "Combination of On-structures already over-restrict locations (no location left to run any calls)."),
+            IllegalLocation{ .. }       => write!(f, "On-structures can only accept string literals as location specifiers."),
+            OnNoLocation{ .. }          => write!(f, "Combination of On-structures already over-restrict locations (no location left to run any calls)."),
+            UnknownLocation{ name, .. } => write!(f, "Unknown location '{}' (it is not part of the instance).", name),
 
             NoLocation{ .. } => write!(f, "External function call is over-restricted and has no locations left to run."),
         }
@@ -880,6 +1090,18 @@ impl PruneError {
             MissingReturn{ range, .. } => prettyprint_err(file, source, self, range),
         }
     }
+
+    /// Returns the range in the source text that this error is about, if any.
+    /// 
+    /// # Returns
+    /// A reference to the TextRange, or `None` if the error does not relate to a specific part of the source.
+    #[inline]
+    pub fn range(&self) -> Option<&TextRange> {
+        use PruneError::*;
+        match self {
+            MissingReturn{ range, .. } => Some(range),
+        }
+    }
 }
 
 impl Display for PruneError {
@@ -923,6 +1145,18 @@ impl FlattenError {
             IntermediateResultConflict{ .. } => prettyprint_err(file, source, self, &TextRange::none()),
         }
     }
+
+    /// Returns the range in the source text that this error is about, if any.
+    /// 
+    /// # Returns
+    /// A reference to the TextRange, or `None` if the error does not relate to a specific part of the source.
+    #[inline]
+    pub fn range(&self) -> Option<&TextRange> {
+        use FlattenError::*;
+        match self {
+            IntermediateResultConflict{ .. } => None,
+        }
+    }
 }
 
 impl Display for FlattenError {
@@ -936,3 +1170,24 @@ impl Display for FlattenError {
 }
 
 impl Error for FlattenError {}
+
+
+
+/// Defines errors that occur when checking whether a Workflow can be run by this engine.
+#[derive(Debug)]
+pub enum CompatibilityError {
+    /// The workflow requires capabilities that this engine does not have.
+    MissingCapabilities{ missing: Vec<String>, version: Version },
+}
+
+impl Display for CompatibilityError {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use CompatibilityError::*;
+        match self {
+            MissingCapabilities{ missing, version } => write!(f, "Workflow requires capabilities that this instance does not support: {} (upgrade the instance to at least version {})", missing.iter().map(|c| format!("'{}'", c)).collect::<Vec<String>>().join(", "), version),
+        }
+    }
+}
+
+impl Error for CompatibilityError {}
@@ -25,6 +25,7 @@ pub mod warnings;
 pub mod spec;
 pub mod data_type;
 pub mod locations;
+pub mod capabilities;
 pub mod ast;
 pub mod edgebuffer;
 pub mod ast_unresolved;
@@ -32,16 +33,18 @@ pub mod state;
 pub mod traversals;
 pub mod compile;
 pub mod fetcher;
+pub mod schedule;
+pub mod report;
 
 
 // Re-export some stuff from brane-dsl
-pub use brane_dsl::ParserOptions;
-pub use brane_dsl::spec::{MergeStrategy, TextPos, TextRange};
+pub use brane_dsl::{ParserOptions, Vocabulary};
+pub use brane_dsl::spec::{Edition, MergeStrategy, RetryPolicy, TextPos, TextRange};
 
 // Bring some stuff into the global namespace.
 pub use errors::AstError as Error;
 pub use warnings::AstWarning as Warning;
 pub use data_type::DataType;
-pub use ast::{SymTable, Workflow};
+pub use ast::{SymTable, Workflow, WorkflowParam};
 pub use ast_unresolved::UnresolvedWorkflow;
 pub use compile::{compile_program, compile_program_to, compile_snippet, compile_snippet_to, CompileResult, CompileStage};
@@ -0,0 +1,282 @@
+//  REPORT.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 12:29:25
+//  Last edited:
+//    16 Oct 2026, 12:29:25
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Statically estimates the cost and complexity of a compiled workflow
+//!   (i.e., how many tasks it runs, how parallel and deeply nested it
+//!   is and which data it needs where), so users can gauge it before
+//!   submitting it.
+// 
+
+use std::collections::{HashMap, HashSet};
+
+use specifications::data::{DataIndex, DataInfo};
+
+use crate::ast::{DataName, Edge, Workflow};
+use crate::locations::{Location, Locations};
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::SymTable;
+
+
+    /// Shorthand for creating a Node that is (optionally) pinned to a location.
+    fn node(at: Option<&str>, input: Option<&str>, result: Option<&str>, next: usize) -> Edge {
+        Edge::Node {
+            task    : 0,
+            locs    : Locations::All,
+            at      : at.map(|at| at.into()),
+            input   : input.map(|name| (DataName::IntermediateResult(name.into()), None)).into_iter().collect(),
+            result  : result.map(|result| result.into()),
+            retry   : None,
+            timeout : None,
+            next,
+        }
+    }
+
+    /// Tests the report of a workflow with a parallel part followed by a loop.
+    #[test]
+    fn test_report() {
+        let graph: Vec<Edge> = vec![
+            // Two tasks in parallel, on different locations
+            Edge::Parallel{ branches: vec![ 1, 2 ], merge: 3 },
+            node(Some("a"), None, Some("res_a"), 3),
+            node(Some("b"), None, Some("res_b"), 3),
+            Edge::Join{ merge: crate::MergeStrategy::None, next: 4 },
+            // A loop that consumes the result of the task on 'b' at 'a'
+            Edge::Loop{ cond: 5, body: 7, next: Some(8) },
+            Edge::Linear{ instrs: vec![], next: 6 },
+            Edge::Branch{ true_next: 7, false_next: Some(8), merge: Some(8) },
+            node(Some("a"), Some("res_b"), None, 5),
+            Edge::Stop{},
+        ];
+        let report: WorkflowReport = analyze(&Workflow::new(SymTable::new(), graph, HashMap::new()), &DataIndex::from_infos(vec![]).unwrap());
+        assert_eq!(report.tasks, 3);
+        assert_eq!(report.width, 2);
+        assert_eq!(report.depth, 1);
+        assert!(report.datasets.is_empty());
+        assert_eq!(report.transfers, 1);
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Walks the edges of a single body from the given edge, computing the shape of the part that is walked.
+///
+/// # Arguments
+/// - `edges`: The edges of the body to walk.
+/// - `pc`: The edge to start walking at.
+/// - `stops`: The edges at which to stop walking (e.g., the point where branches merge).
+/// - `visited`: The edges walked so far. Walking stops at any of these too, which guards against malformed graphs.
+///
+/// # Returns
+/// The number of tasks, the maximum parallel width and the loop nesting depth of the walked part, in that order.
+fn walk(edges: &[Edge], mut pc: usize, stops: &[usize], visited: &mut HashSet<usize>) -> (usize, usize, usize) {
+    let (mut tasks, mut width, mut depth): (usize, usize, usize) = (0, 0, 0);
+    loop {
+        if stops.contains(&pc) || !visited.insert(pc) { break; }
+        match edges.get(pc) {
+            Some(Edge::Node{ next, .. }) => {
+                tasks += 1;
+                width = width.max(1);
+                pc = *next;
+            },
+            Some(Edge::Linear{ next, .. }) | Some(Edge::Join{ next, .. }) | Some(Edge::Call{ next }) => { pc = *next; },
+
+            Some(Edge::Branch{ true_next, false_next, merge }) => {
+                // Only one of the branches is taken, so the widest one determines the width
+                let inner: Vec<usize> = stops.iter().copied().chain(*merge).collect();
+                let (t_tasks, t_width, t_depth): (usize, usize, usize) = walk(edges, *true_next, &inner, visited);
+                let (f_tasks, f_width, f_depth): (usize, usize, usize) = match false_next {
+                    Some(false_next) => walk(edges, *false_next, &inner, visited),
+                    None             => (0, 0, 0),
+                };
+                tasks += t_tasks + f_tasks;
+                width = width.max(t_width).max(f_width);
+                depth = depth.max(t_depth).max(f_depth);
+                match merge {
+                    Some(merge) => { pc = *merge; },
+                    None        => { break; },
+                }
+            },
+            Some(Edge::Parallel{ branches, merge }) => {
+                // All branches run simultaneously, so their widths add up
+                let inner: Vec<usize> = stops.iter().copied().chain([ *merge ]).collect();
+                let mut b_width: usize = 0;
+                for branch in branches {
+                    let (b_tasks, w, d): (usize, usize, usize) = walk(edges, *branch, &inner, visited);
+                    tasks += b_tasks;
+                    b_width += w;
+                    depth = depth.max(d);
+                }
+                width = width.max(b_width);
+                pc = *merge;
+            },
+            Some(Edge::Loop{ cond, body, next }) => {
+                // The condition runs until it branches into the body or beyond the loop; the body runs until it jumps back to the condition
+                let c_stops: Vec<usize> = stops.iter().copied().chain([ *body ]).chain(*next).collect();
+                let b_stops: Vec<usize> = stops.iter().copied().chain([ *cond ]).collect();
+                let (c_tasks, c_width, c_depth): (usize, usize, usize) = walk(edges, *cond, &c_stops, visited);
+                let (b_tasks, b_width, b_depth): (usize, usize, usize) = walk(edges, *body, &b_stops, visited);
+                tasks += c_tasks + b_tasks;
+                width = width.max(c_width).max(b_width);
+                depth = depth.max(1 + c_depth.max(b_depth));
+                match next {
+                    Some(next) => { pc = *next; },
+                    None       => { break; },
+                }
+            },
+
+            Some(Edge::Stop{}) | Some(Edge::Return{}) | None => { break; },
+        }
+    }
+    (tasks, width, depth)
+}
+
+/// Computes the minimum number of transfers needed to run a single task, given where its inputs are available.
+///
+/// # Arguments
+/// - `allowed`: The locations where the task may run, or `None` if it may run anywhere.
+/// - `inputs`: For every input of which we know where it is, the locations where it is available.
+///
+/// # Returns
+/// The number of inputs that have to be transferred if the task would be run at the best location for it.
+fn min_transfers(allowed: Option<&[Location]>, inputs: &[&[Location]]) -> usize {
+    let candidates: Vec<&Location> = match allowed {
+        Some(allowed) => allowed.iter().collect(),
+        None          => inputs.iter().flat_map(|locs| locs.iter()).collect(),
+    };
+    candidates.into_iter().map(|loc| inputs.iter().filter(|locs| !locs.contains(loc)).count()).min().unwrap_or(0)
+}
+
+/// Returns the locations where the given task may run.
+///
+/// # Arguments
+/// - `locs`: The location restrictions of the task.
+/// - `at`: The location that the task is pinned to, if any.
+///
+/// # Returns
+/// The allowed locations, or `None` if it may run anywhere.
+#[inline]
+fn allowed_locations<'a>(locs: &'a Locations, at: &'a Option<Location>) -> Option<&'a [Location]> {
+    match (at, locs) {
+        (Some(at), _)                       => Some(std::slice::from_ref(at)),
+        (None, Locations::Restricted(locs)) => Some(locs),
+        (None, Locations::All)              => None,
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines a dataset that is referenced by a workflow.
+#[derive(Clone, Debug)]
+pub struct DatasetReport {
+    /// The identifier of the dataset, as it is referenced in the workflow.
+    pub name      : String,
+    /// The size of the dataset in bytes, if the data index knows it.
+    pub size      : Option<u64>,
+    /// The locations that advertise the dataset. Empty if the data index does not know it.
+    pub locations : Vec<Location>,
+}
+
+
+
+/// Defines the static cost and complexity estimates of a workflow.
+///
+/// Note that tasks are counted once per place they appear in the workflow, not per time they are run (e.g., in loops or by functions that are called multiple times).
+#[derive(Clone, Debug)]
+pub struct WorkflowReport {
+    /// The number of task calls in the workflow.
+    pub tasks     : usize,
+    /// The maximum number of tasks that may run simultaneously, as determined by nested parallel statements.
+    pub width     : usize,
+    /// The maximum number of nested loops.
+    pub depth     : usize,
+    /// The datasets referenced by the workflow, ordered by name.
+    pub datasets  : Vec<DatasetReport>,
+    /// A lower bound to the number of inputs that have to be transferred between domains, assuming every task is scheduled at the best location for it.
+    pub transfers : usize,
+}
+
+
+
+/// Statically analyzes the given workflow to estimate its cost and complexity.
+///
+/// The main body and every function body are analyzed separately; the width and depth are the maximum of all bodies, as we cannot know statically which function is called where.
+///
+/// # Arguments
+/// - `workflow`: The (compiled) Workflow to analyze.
+/// - `dindex`: The DataIndex that tells us the size and locations of the datasets.
+///
+/// # Returns
+/// A new WorkflowReport.
+pub fn analyze(workflow: &Workflow, dindex: &DataIndex) -> WorkflowReport {
+    let bodies: Vec<&[Edge]> = std::iter::once(workflow.graph.as_slice()).chain(workflow.funcs.values().map(|edges| edges.as_slice())).collect();
+
+    // Compute the shape of every body
+    let (mut tasks, mut width, mut depth): (usize, usize, usize) = (0, 0, 0);
+    for edges in &bodies {
+        let (b_tasks, b_width, b_depth): (usize, usize, usize) = walk(edges, 0, &[], &mut HashSet::new());
+        tasks += b_tasks;
+        width = width.max(b_width);
+        depth = depth.max(b_depth);
+    }
+
+    // Find where the results are produced, as far as we know statically
+    let mut producers: HashMap<&str, &[Location]> = HashMap::new();
+    for edge in bodies.iter().flat_map(|edges| edges.iter()) {
+        if let Edge::Node{ locs, at, result: Some(result), .. } = edge {
+            if let Some(allowed) = allowed_locations(locs, at) { producers.insert(result.as_str(), allowed); }
+        }
+    }
+
+    // Collect the datasets referenced by any of the tasks
+    let mut datasets: HashMap<&str, DatasetReport> = HashMap::new();
+    for edge in bodies.iter().flat_map(|edges| edges.iter()) {
+        if let Edge::Node{ input, .. } = edge {
+            for name in input.keys() {
+                if let DataName::Data(name) = name {
+                    datasets.entry(name.as_str()).or_insert_with(|| {
+                        let info: Option<&DataInfo> = dindex.get(name);
+                        let mut locations: Vec<Location> = info.map(|info| info.access.keys().cloned().collect()).unwrap_or_default();
+                        locations.sort();
+                        DatasetReport { name: name.clone(), size: info.and_then(|info| info.size), locations }
+                    });
+                }
+            }
+        }
+    }
+
+    // Count the transfers needed per task
+    let mut transfers: usize = 0;
+    for edge in bodies.iter().flat_map(|edges| edges.iter()) {
+        if let Edge::Node{ locs, at, input, .. } = edge {
+            let inputs: Vec<&[Location]> = input.keys().filter_map(|name| match name {
+                DataName::Data(name)               => datasets.get(name.as_str()).map(|report| report.locations.as_slice()).filter(|locs| !locs.is_empty()),
+                DataName::IntermediateResult(name) => producers.get(name.as_str()).copied(),
+            }).collect();
+            transfers += min_transfers(allowed_locations(locs, at), &inputs);
+        }
+    }
+
+    // Done
+    let mut datasets: Vec<DatasetReport> = datasets.into_values().collect();
+    datasets.sort_by(|lhs, rhs| lhs.name.cmp(&rhs.name));
+    WorkflowReport { tasks, width, depth, datasets, transfers }
+}
@@ -0,0 +1,250 @@
+//  SCHEDULE.rs
+//    by agent
+//
+//  Created:
+//    16 Oct 2026, 08:46:52
+//  Last edited:
+//    16 Oct 2026, 08:46:52
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Analyzes how the tasks of a workflow were scheduled, based on how
+//!   long they took in a run. This finds the critical path of the run and
+//!   estimates what would happen if it had more parallelism or faster
+//!   locations.
+//
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use specifications::status::ResourceUsage;
+
+use crate::ast::{DataName, Edge, Workflow};
+use crate::locations::Location;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Shorthand for creating a TaskTiming.
+    fn timing(pc: usize, location: &str, start: i64, end: i64) -> TaskTiming {
+        TaskTiming { pc: (usize::MAX, pc), name: format!("task{}", pc), location: location.into(), start, end, usage: None }
+    }
+
+    /// Tests the critical path and what-if analysis of a run with a sequential and a parallel part.
+    #[test]
+    fn test_schedule() {
+        // Two tasks in parallel on different locations, followed by one that waited for both
+        let timings: Vec<TaskTiming> = vec![
+            timing(0, "a", 100, 200),
+            timing(1, "b", 100, 400),
+            timing(2, "a", 410, 510),
+        ];
+
+        // The critical path goes through the slowest of the two
+        let observed: Schedule = critical_path(&timings);
+        assert_eq!(observed.makespan, 410);
+        assert_eq!(observed.critical, vec![ 1, 2 ]);
+
+        // Making 'b' faster shortens the run, until the task on 'a' becomes critical instead
+        let faster: Schedule = with_faster_locations(&timings, &HashMap::from([ ("b".into(), 2.0) ]));
+        assert_eq!(faster.makespan, 260);
+        assert_eq!(faster.critical, vec![ 1, 2 ]);
+        let faster: Schedule = with_faster_locations(&timings, &HashMap::from([ ("b".into(), 4.0) ]));
+        assert_eq!(faster.makespan, 210);
+        assert_eq!(faster.critical, vec![ 0, 2 ]);
+    }
+
+    /// Tests the critical path of tasks that took no time at all.
+    #[test]
+    fn test_schedule_instant() {
+        let timings: Vec<TaskTiming> = vec![
+            timing(0, "a", 100, 100),
+            timing(1, "a", 100, 100),
+            timing(2, "a", 100, 150),
+        ];
+        let observed: Schedule = critical_path(&timings);
+        assert_eq!(observed.makespan, 50);
+        assert_eq!(observed.critical, vec![ 0, 1, 2 ]);
+
+        // Nothing at all has no critical path
+        let observed: Schedule = critical_path(&[]);
+        assert_eq!(observed.makespan, 0);
+        assert!(observed.critical.is_empty());
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Replays the given tasks with the given dependencies, and returns the resulting schedule.
+///
+/// # Arguments
+/// - `timings`: The TaskTimings of the tasks, ordered by start time.
+/// - `deps`: For every task, the tasks (as indices in `timings`) it has to wait for.
+/// - `gaps`: Whether to keep the time that was observed between a task and the last one it waited for (e.g., for transferring data). If false, tasks start as soon as their dependencies are done.
+/// - `speedups`: Factors by which tasks are faster per location. Locations that are not in here run at the observed speed.
+///
+/// # Returns
+/// The replayed Schedule.
+fn replay(timings: &[TaskTiming], deps: &[Vec<usize>], gaps: bool, speedups: &HashMap<Location, f64>) -> Schedule {
+    let t0: i64 = timings.iter().map(|t| t.start).min().unwrap_or(0);
+
+    // Compute when every task would be done, remembering which dependency it waited on last
+    let mut ends : Vec<i64>           = Vec::with_capacity(timings.len());
+    let mut via  : Vec<Option<usize>> = Vec::with_capacity(timings.len());
+    for (j, t) in timings.iter().enumerate() {
+        // The task starts once the last of its dependencies is done, plus the overhead that we observed after that one
+        let last     : Option<usize> = deps[j].iter().copied().max_by_key(|i| ends[*i]);
+        let observed : i64           = deps[j].iter().map(|i| timings[*i].end).max().unwrap_or(t0);
+        let overhead : i64           = if gaps { t.start - observed } else { 0 };
+        let start    : i64           = last.map(|i| ends[i]).unwrap_or(0) + overhead;
+
+        // Scale the duration by the location's speedup
+        let speedup: f64 = speedups.get(&t.location).copied().unwrap_or(1.0);
+        ends.push(start + ((t.end - t.start) as f64 / speedup).round() as i64);
+        via.push(last);
+    }
+
+    // The critical path ends in the task that finishes last
+    let mut critical: Vec<usize> = vec![];
+    let mut next: Option<usize> = (0..timings.len()).max_by_key(|j| ends[*j]);
+    while let Some(j) = next {
+        critical.push(j);
+        next = via[j];
+    }
+    critical.reverse();
+
+    // Done
+    Schedule {
+        makespan : ends.iter().copied().max().unwrap_or(0),
+        critical,
+    }
+}
+
+/// Finds the dependencies between the given tasks as they were observed, i.e., every task depends on all tasks that were done before it started.
+///
+/// # Arguments
+/// - `timings`: The TaskTimings of the tasks, ordered by start time.
+///
+/// # Returns
+/// For every task, the tasks (as indices in `timings`) it waited for.
+fn observed_deps(timings: &[TaskTiming]) -> Vec<Vec<usize>> {
+    // Only look at the tasks before it, or tasks that took no time at all would wait for themselves
+    timings.iter().enumerate().map(|(j, t)| {
+        timings[..j].iter().enumerate().filter(|(_, o)| o.end <= t.start).map(|(i, _)| i).collect()
+    }).collect()
+}
+
+/// Finds the dependencies between the given tasks in terms of data, i.e., every task depends on the tasks that produced the intermediate results it consumes.
+///
+/// # Arguments
+/// - `workflow`: The Workflow that the tasks are called in.
+/// - `timings`: The TaskTimings of the tasks, ordered by start time.
+///
+/// # Returns
+/// For every task, the tasks (as indices in `timings`) it needed data of.
+fn data_deps(workflow: &Workflow, timings: &[TaskTiming]) -> Vec<Vec<usize>> {
+    // Find the intermediate results consumed and produced by the node of every task
+    let nodes: Vec<(Vec<&str>, Option<&str>)> = timings.iter().map(|t| {
+        let edge: Option<&Edge> = if t.pc.0 == usize::MAX { workflow.graph.get(t.pc.1) } else { workflow.funcs.get(&t.pc.0).and_then(|edges| edges.get(t.pc.1)) };
+        match edge {
+            Some(Edge::Node{ input, result, .. }) => (
+                input.keys().filter_map(|name| if let DataName::IntermediateResult(name) = name { Some(name.as_str()) } else { None }).collect(),
+                result.as_deref(),
+            ),
+            _ => (vec![], None),
+        }
+    }).collect();
+
+    // Every task depends on the last task before it that produced any of its inputs
+    timings.iter().enumerate().map(|(j, t)| {
+        let mut deps: Vec<usize> = vec![];
+        for name in &nodes[j].0 {
+            if let Some(i) = (0..j).rev().find(|i| nodes[*i].1 == Some(*name) && timings[*i].end <= t.start) {
+                if !deps.contains(&i) { deps.push(i); }
+            }
+        }
+        deps
+    }).collect()
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines how long a single task call took in a run.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TaskTiming {
+    /// The edge that called the task, as a `(body, offset)` pair (where the body is `usize::MAX` for the main body).
+    pub pc       : (usize, usize),
+    /// The name of the task (as `package::function`).
+    pub name     : String,
+    /// The location where the task was run.
+    pub location : Location,
+    /// When the task was started, in milliseconds since the Unix epoch.
+    pub start    : i64,
+    /// When the task was done, in milliseconds since the Unix epoch.
+    pub end      : i64,
+    /// The resources the task consumed, if the worker measured them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage    : Option<ResourceUsage>,
+}
+
+
+
+/// Defines the (observed or estimated) schedule of the tasks in a run.
+#[derive(Clone, Debug)]
+pub struct Schedule {
+    /// The time between the start of the first task and the end of the last one, in milliseconds.
+    pub makespan : i64,
+    /// The tasks on the critical path (as indices in the analyzed TaskTimings), in the order they ran.
+    pub critical : Vec<usize>,
+}
+
+
+
+/// Finds the critical path of a run as it was observed, i.e., the chain of tasks that determined how long it took.
+///
+/// # Arguments
+/// - `timings`: The TaskTimings of the run, ordered by start time.
+///
+/// # Returns
+/// The observed Schedule.
+pub fn critical_path(timings: &[TaskTiming]) -> Schedule {
+    replay(timings, &observed_deps(timings), true, &HashMap::new())
+}
+
+/// Estimates the schedule of a run if it had unlimited parallelism, i.e., if every task would start as soon as the intermediate results it consumes are available.
+///
+/// Note that this is an optimistic estimate, as it ignores dependencies through values other than data and the overhead between tasks.
+///
+/// # Arguments
+/// - `workflow`: The Workflow that was run.
+/// - `timings`: The TaskTimings of the run, ordered by start time.
+///
+/// # Returns
+/// The estimated Schedule.
+pub fn with_parallelism(workflow: &Workflow, timings: &[TaskTiming]) -> Schedule {
+    replay(timings, &data_deps(workflow, timings), false, &HashMap::new())
+}
+
+/// Estimates the schedule of a run if some locations were faster, keeping the order in which tasks ran.
+///
+/// # Arguments
+/// - `timings`: The TaskTimings of the run, ordered by start time.
+/// - `speedups`: The factors by which tasks run faster per location (e.g., `2.0` to make them take half the time).
+///
+/// # Returns
+/// The estimated Schedule.
+pub fn with_faster_locations(timings: &[TaskTiming], speedups: &HashMap<Location, f64>) -> Schedule {
+    replay(timings, &observed_deps(timings), true, speedups)
+}
@@ -32,6 +32,48 @@ pub enum BuiltinFunctions {
 
     /// The commit_builtin-function, which turns an IntermediateResult into a Data.
     CommitResult,
+
+    /// The lock-function, which blocks until the calling branch holds the named lock. Allows parallel branches to serialize access to a shared resource.
+    Lock,
+    /// The unlock-function, which releases a named lock held by the calling branch.
+    Unlock,
+
+    /// The sleep-function, which suspends the calling branch for the given number of seconds without occupying any container.
+    Sleep,
+    /// The wait_until-function, which suspends the calling branch until the given (RFC 3339) point in time without occupying any container.
+    WaitUntil,
+    /// The wait_for_event-function, which suspends the calling branch until the named external event arrives and returns its payload.
+    WaitForEvent,
+    /// The require_approval-function, which suspends the calling branch until an authorized user approves the given message (and fails it if they reject it).
+    RequireApproval,
+
+    /// The notify-function, which sends a message to the user(s) behind one of the notification channels configured for the instance.
+    Notify,
+
+    /// The keys-function, which returns the (sorted) keys of a map. Allows maps to be iterated over.
+    Keys,
+
+    /// The split-function, which splits a string on every occurrence of a separator.
+    Split,
+    /// The join-function, which concatenates an array of strings with a separator in between.
+    Join,
+    /// The to_upper-function, which returns the uppercase version of a string.
+    ToUpper,
+    /// The substring-function, which returns the characters of a string in the given (exclusive) range.
+    Substring,
+    /// The contains-function, which returns whether a string contains another.
+    Contains,
+    /// The parse_int-function, which parses a string as an integer.
+    ParseInt,
+    /// The parse_real-function, which parses a string as a real.
+    ParseReal,
+
+    /// The list_packages-function, which returns the (sorted) names of the packages known to the instance.
+    ListPackages,
+    /// The list_datasets-function, which returns the (sorted) names of the datasets known to the instance.
+    ListDatasets,
+    /// The dataset_size-function, which returns the size (in bytes) of the given dataset, as reported by the domain that has it.
+    DatasetSize,
 }
 
 impl BuiltinFunctions {
@@ -46,6 +88,31 @@ impl BuiltinFunctions {
             Len => "len",
 
             CommitResult => "commit_result",
+
+            Lock   => "lock",
+            Unlock => "unlock",
+
+            Sleep     => "sleep",
+            WaitUntil => "wait_until",
+
+            WaitForEvent    => "wait_for_event",
+            RequireApproval => "require_approval",
+
+            Notify => "notify",
+
+            Keys => "keys",
+
+            Split     => "split",
+            Join      => "join",
+            ToUpper   => "to_upper",
+            Substring => "substring",
+            Contains  => "contains",
+            ParseInt  => "parse_int",
+            ParseReal => "parse_real",
+
+            ListPackages => "list_packages",
+            ListDatasets => "list_datasets",
+            DatasetSize  => "dataset_size",
         }
     }
 
@@ -60,6 +127,31 @@ impl BuiltinFunctions {
             Len => FunctionSignature::new(vec![ DataType::Array(Box::new(DataType::Any)) ], DataType::Integer),
 
             CommitResult => FunctionSignature::new(vec![ DataType::String, DataType::Class(BuiltinClasses::IntermediateResult.name().into()) ], DataType::Class(BuiltinClasses::Data.name().into())),
+
+            Lock   => FunctionSignature::new(vec![ DataType::String ], DataType::Void),
+            Unlock => FunctionSignature::new(vec![ DataType::String ], DataType::Void),
+
+            Sleep     => FunctionSignature::new(vec![ DataType::Integer ], DataType::Void),
+            WaitUntil => FunctionSignature::new(vec![ DataType::String ], DataType::Void),
+
+            WaitForEvent    => FunctionSignature::new(vec![ DataType::String ], DataType::String),
+            RequireApproval => FunctionSignature::new(vec![ DataType::String ], DataType::Void),
+
+            Notify => FunctionSignature::new(vec![ DataType::String, DataType::String ], DataType::Void),
+
+            Keys => FunctionSignature::new(vec![ DataType::Map(Box::new(DataType::Any)) ], DataType::Array(Box::new(DataType::String))),
+
+            Split     => FunctionSignature::new(vec![ DataType::String, DataType::String ], DataType::Array(Box::new(DataType::String))),
+            Join      => FunctionSignature::new(vec![ DataType::Array(Box::new(DataType::String)), DataType::String ], DataType::String),
+            ToUpper   => FunctionSignature::new(vec![ DataType::String ], DataType::String),
+            Substring => FunctionSignature::new(vec![ DataType::String, DataType::Integer, DataType::Integer ], DataType::String),
+            Contains  => FunctionSignature::new(vec![ DataType::String, DataType::String ], DataType::Boolean),
+            ParseInt  => FunctionSignature::new(vec![ DataType::String ], DataType::Integer),
+            ParseReal => FunctionSignature::new(vec![ DataType::String ], DataType::Real),
+
+            ListPackages => FunctionSignature::new(vec![], DataType::Array(Box::new(DataType::String))),
+            ListDatasets => FunctionSignature::new(vec![], DataType::Array(Box::new(DataType::String))),
+            DatasetSize  => FunctionSignature::new(vec![ DataType::String ], DataType::Integer),
         }
     }
 
@@ -67,11 +159,11 @@ impl BuiltinFunctions {
 
     /// Returns an array with all the builtin functions in it.
     #[inline]
-    pub fn all() -> [ Self; 4 ] { [ Self::Print, Self::PrintLn, Self::Len, Self::CommitResult ] }
+    pub fn all() -> [ Self; 22 ] { [ Self::Print, Self::PrintLn, Self::Len, Self::CommitResult, Self::Lock, Self::Unlock, Self::Sleep, Self::WaitUntil, Self::WaitForEvent, Self::RequireApproval, Self::Notify, Self::Keys, Self::Split, Self::Join, Self::ToUpper, Self::Substring, Self::Contains, Self::ParseInt, Self::ParseReal, Self::ListPackages, Self::ListDatasets, Self::DatasetSize ] }
 
     /// Returns an Array with all of the builtin functions but already casted to FunctionStates.
     #[inline]
-    pub fn all_into_state() -> [ FunctionState; 4 ] { [ Self::Print.into(), Self::PrintLn.into(), Self::Len.into(), Self::CommitResult.into() ] }
+    pub fn all_into_state() -> [ FunctionState; 22 ] { [ Self::Print.into(), Self::PrintLn.into(), Self::Len.into(), Self::CommitResult.into(), Self::Lock.into(), Self::Unlock.into(), Self::Sleep.into(), Self::WaitUntil.into(), Self::WaitForEvent.into(), Self::RequireApproval.into(), Self::Notify.into(), Self::Keys.into(), Self::Split.into(), Self::Join.into(), Self::ToUpper.into(), Self::Substring.into(), Self::Contains.into(), Self::ParseInt.into(), Self::ParseReal.into(), Self::ListPackages.into(), Self::ListDatasets.into(), Self::DatasetSize.into() ] }
 }
 
 impl From<BuiltinFunctions> for FunctionState {
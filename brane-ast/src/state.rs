@@ -24,6 +24,7 @@ use brane_dsl::{DataType, TextRange};
 use brane_dsl::data_type::{ClassSignature, FunctionSignature};
 use brane_dsl::symbol_table::{ClassEntry, FunctionEntry, SymbolTable, VarEntry};
 use brane_dsl::ast::Data;
+use brane_dsl::location::Location;
 use specifications::package::Capability;
 use specifications::version::Version;
 
@@ -710,6 +711,10 @@ impl From<&FunctionState> for FunctionEntry {
 
             arg_names    : vec![],
             requirements : None,
+            deprecated   : None,
+            aliases      : vec![],
+            secrets      : vec![],
+            docs         : None,
 
             index : usize::MAX,
 
@@ -743,6 +748,8 @@ pub struct TaskState {
     pub arg_names    : Vec<String>,
     /// Any requirements for this function.
     pub requirements : HashSet<Capability>,
+    /// The names of the secrets that this function needs.
+    pub secrets      : Vec<String>,
 
     /// The name of the package where this Task is stored.
     pub package_name    : String,
@@ -767,6 +774,10 @@ impl From<&TaskState> for FunctionEntry {
 
             arg_names    : value.arg_names.clone(),
             requirements : Some(value.requirements.clone()),
+            deprecated   : None,
+            aliases      : vec![],
+            secrets      : value.secrets.clone(),
+            docs         : None,
 
             index : usize::MAX,
 
@@ -791,6 +802,7 @@ impl From<TaskState> for TaskDef {
             }),
             args_names   : value.arg_names,
             requirements : value.requirements,
+            secrets      : value.secrets,
         }
     }
 }
@@ -847,6 +859,7 @@ impl ClassState {
 
             package_name    : self.package_name.clone(),
             package_version : self.package_version.clone(),
+            docs            : None,
 
             index : usize::MAX,
 
@@ -931,6 +944,9 @@ pub struct DataState {
     funcs : HashMap<String, HashSet<Data>>,
     /// Maps variable names (=identifiers) to their current possible list of data identifiers they may be. An empty set implies it's not a Data or IntermediateResult struct.
     vars  : HashMap<String, HashSet<Data>>,
+
+    /// Maps intermediate result names (=identifiers) to the locations where they may be processed, as inherited from restricted datasets they are derived from. Results that are absent may be processed anywhere.
+    residency : HashMap<String, HashSet<Location>>,
 }
 
 impl DataState {
@@ -943,6 +959,8 @@ impl DataState {
         Self {
             funcs : HashMap::new(),
             vars  : HashMap::new(),
+
+            residency : HashMap::new(),
         }
     }
 
@@ -986,6 +1004,16 @@ impl DataState {
         self.vars.insert(name.into(), new_ids);
     }
 
+    /// Sets the locations where the given intermediate result may be processed, overwriting any existing ones.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the intermediate result to restrict.
+    /// - `locs`: The locations where the result may be processed.
+    #[inline]
+    pub fn set_residency(&mut self, name: impl Into<String>, locs: HashSet<Location>) {
+        self.residency.insert(name.into(), locs);
+    }
+
 
 
     // /// Returns the list of possible values for the given function. If it does not exist, returns an empty one.
@@ -1034,6 +1062,18 @@ impl DataState {
         self.vars.get(name.as_ref()).unwrap_or(&*EMPTY_IDS)
     }
 
+    /// Returns the locations where the given intermediate result may be processed.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the intermediate result to get the residency of.
+    /// 
+    /// # Returns
+    /// A reference to the locations where the result may be processed, or `None` if it may be processed anywhere.
+    #[inline]
+    pub fn get_residency(&self, name: impl AsRef<str>) -> Option<&HashSet<Location>> {
+        self.residency.get(name.as_ref())
+    }
+
 
 
     /// The extend function extends this table with the given one, i.e., all of the possibilities are merged.
@@ -1058,6 +1098,9 @@ impl DataState {
                 self.vars.insert(name, ids);
             }
         }
+
+        // Results are unique, so their residency can simply be copied over
+        self.residency.extend(other.residency);
     }
 }
 
@@ -29,6 +29,7 @@ use crate::errors::AstError;
 use crate::warnings::AstWarning;
 use crate::ast;
 use crate::edgebuffer::EdgeBuffer;
+use crate::locations::{Location, Locations};
 use crate::ast_unresolved::UnresolvedWorkflow;
 use crate::state::{CompileState, TableState};
 
@@ -530,7 +531,7 @@ fn pass_expr(expr: dsl::Expr, edges: &mut EdgeBuffer, _table: &TableState) {
             });
         },
 
-        Call{ expr, args, locations, input, result, st_entry, .. } => {
+        Call{ expr, args, locations, retry, timeout, input, result, st_entry, .. } => {
             // First, write the arguments followed by the call expression
             for a in args {
                 pass_expr(*a, edges, _table);
@@ -540,14 +541,18 @@ fn pass_expr(expr: dsl::Expr, edges: &mut EdgeBuffer, _table: &TableState) {
             // We now switch depending on the type of function called
             #[allow(clippy::unnecessary_unwrap)]
             if st_entry.is_some() && st_entry.as_ref().unwrap().borrow().package_name.is_some() {
-                // It's an external call; replace with a Node edge (so sorry everyone)
+                // It's an external call; replace with a Node edge (so sorry everyone). If it may only run on a single location, we already hint the planner to put it there.
+                let locs: Locations = locations.into();
+                let at: Option<Location> = if locs.is_restrictive() && locs.restricted().len() == 1 { Some(locs.restricted()[0].clone()) } else { None };
                 edges.write(ast::Edge::Node {
-                    task   : st_entry.unwrap().borrow().index,
-                    locs   : locations.into(),
-                    at     : None,
-                    input  : input.into_iter().map(|d| (d.into(), None)).collect(),
-                    result : result.as_ref().cloned(),
-                    next   : usize::MAX,
+                    task    : st_entry.unwrap().borrow().index,
+                    locs,
+                    at,
+                    input   : input.into_iter().map(|d| (d.into(), None)).collect(),
+                    result  : result.as_ref().cloned(),
+                    retry,
+                    timeout,
+                    next    : usize::MAX,
                 });
             } else {
                 // It's a local call; replace with a Call edge
@@ -572,6 +577,23 @@ fn pass_expr(expr: dsl::Expr, edges: &mut EdgeBuffer, _table: &TableState) {
                 next : usize::MAX,
             });
         },
+        Map{ entries, data_type, .. } => {
+            // Compute all of the keys and values first
+            let entries_len: usize = entries.len();
+            for (k, v) in entries {
+                pass_expr(*k, edges, _table);
+                pass_expr(*v, edges, _table);
+            }
+
+            // Now add the Map instruction in a linear edge
+            edges.write(ast::Edge::Linear {
+                instrs: vec![ ast::EdgeInstr::Map {
+                    length   : entries_len,
+                    res_type : (&data_type).into(),
+                } ],
+                next : usize::MAX,
+            });
+        },
         ArrayIndex{ array, index, data_type, .. } => {
             // Write the array, then the index
             pass_expr(*array, edges, _table);
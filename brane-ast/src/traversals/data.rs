@@ -21,9 +21,12 @@ use log::debug;
 use uuid::Uuid;
 
 use brane_dsl::{DataType, SymbolTable};
+use brane_dsl::location::{AllowedLocations, Location};
 use brane_dsl::symbol_table::{ClassEntry, FunctionEntry, SymbolTableEntry, VarEntry};
-use brane_dsl::ast::{Block, Data, Expr, Program, Stmt};
+use brane_dsl::ast::{Block, Data, Expr, Literal, Program, Stmt};
+use specifications::data::DataIndex;
 
+pub use crate::errors::DataError as Error;
 use crate::errors::AstError;
 use crate::spec::BuiltinClasses;
 use crate::state::{CompileState, DataState};
@@ -34,7 +37,7 @@ use crate::state::{CompileState, DataState};
 mod tests {
     use brane_dsl::ParserOptions;
     use brane_shr::utilities::{create_data_index, create_package_index, test_on_dsl_files};
-    use specifications::data::DataIndex;
+    use specifications::data::{DataIndex, DataInfo};
     use specifications::package::PackageIndex;
     use super::*;
     use super::super::print::symbol_tables;
@@ -87,6 +90,34 @@ mod tests {
             println!("{}\n\n", (0..80).map(|_| '-').collect::<String>());
         });
     }
+
+    /// Tests that calls processing restricted datasets are restricted to (or rejected for) locations where that is allowed.
+    #[test]
+    fn test_data_residency() {
+        let pindex: PackageIndex = create_package_index();
+        let dindex: DataIndex    = DataIndex::from_infos(vec![ DataInfo::from_reader("name: Restricted\ncreated: \"1970-01-01T00:00:00Z\"\nresidency: [ umc_utrecht ]\naccess:\n  umc_utrecht:\n    kind: file\n    path: ./test.csv\n".as_bytes()).unwrap() ]).unwrap();
+
+        // Running the call where the data may be processed is fine, and the result inherits the restriction
+        let code: &str = "import data_test; let data := new Data{ name := \"Restricted\" }; on \"umc_utrecht\" { let res := run_script(data); aggregate(res, res); }";
+        let mut state: CompileState = CompileState::new();
+        assert!(matches!(compile_snippet_to(&mut state, code.as_bytes(), &pindex, &dindex, &ParserOptions::bscript(), CompileStage::Data), CompileResult::Program(..)));
+
+        // Running it anywhere else is not
+        let code: &str = "import data_test; let data := new Data{ name := \"Restricted\" }; on \"st_antonius\" { run_script(data); }";
+        let mut state: CompileState = CompileState::new();
+        match compile_snippet_to(&mut state, code.as_bytes(), &pindex, &dindex, &ParserOptions::bscript(), CompileStage::Data) {
+            CompileResult::Err(errs) => { assert!(matches!(errs.as_slice(), [ AstError::DataError(Error::ResidencyViolation{ .. }) ])); },
+            _                        => { panic!("Expected a residency violation"); },
+        }
+
+        // Neither is processing a result derived from it
+        let code: &str = "import data_test; let data := new Data{ name := \"Restricted\" }; let res := run_script(data); on \"st_antonius\" { aggregate(res, res); }";
+        let mut state: CompileState = CompileState::new();
+        match compile_snippet_to(&mut state, code.as_bytes(), &pindex, &dindex, &ParserOptions::bscript(), CompileStage::Data) {
+            CompileResult::Err(errs) => { assert!(matches!(errs.as_slice(), [ AstError::DataError(Error::ResidencyViolation{ .. }) ])); },
+            _                        => { panic!("Expected a residency violation"); },
+        }
+    }
 }
 
 
@@ -100,14 +131,20 @@ mod tests {
 /// - `block`: The Block to traverse.
 /// - `table`: The DataTable we use to keep track of which variable has what value.
 /// - `is_branch`: Indicates whether the current block is a branching block (true) or not (false). By "branching block", we mean a block that _might_ be taken, but not sure (or that is taken _for sure_ but with different inputs, as in the case of a loop).
+/// - `on`: The locations to which the current block is restricted by any surrounding On-structs.
+/// - `dindex`: The DataIndex we use to find out where datasets may be processed.
+/// - `errors`: A list we use to accumulate errors as they occur.
 /// 
 /// # Returns
 /// This functions returns the possible datasets that are _returned_ in this block. This is thus different from `pass_expr()`.
-fn pass_block(block: &mut Block, table: &mut DataState, is_branch: bool) -> HashSet<Data> {
+/// 
+/// # Errors
+/// If errors occur, they are appended to the `errors` list.
+fn pass_block(block: &mut Block, table: &mut DataState, is_branch: bool, on: &AllowedLocations, dindex: &DataIndex, errors: &mut Vec<Error>) -> HashSet<Data> {
     // Iterate over all the statements
     let mut ids: HashSet<Data> = HashSet::new();
     for s in &mut block.stmts {
-        let sids: HashSet<Data> = pass_stmt(s, table, is_branch, &block.table);
+        let sids: HashSet<Data> = pass_stmt(s, table, is_branch, &block.table, on, dindex, errors);
         ids.extend(sids);
     }
 
@@ -122,20 +159,26 @@ fn pass_block(block: &mut Block, table: &mut DataState, is_branch: bool) -> Hash
 /// - `table`: The DataTable we use to keep track of which variable has what value.
 /// - `is_branch`: Indicates whether the current block is a branching block (true) or not (false). By "branching block", we mean a block that _might_ be taken, but not sure (or that is taken _for sure_ but with different inputs, as in the case of a loop).
 /// - `scope`: The symbol table of the current block we are in, i.e., the current scope.
+/// - `on`: The locations to which the current block is restricted by any surrounding On-structs.
+/// - `dindex`: The DataIndex we use to find out where datasets may be processed.
+/// - `errors`: A list we use to accumulate errors as they occur.
 /// 
 /// # Returns
 /// This functions returns the possible datasets that are _returned_ in this statement. This is thus different from `pass_expr()`.
-fn pass_stmt(stmt: &mut Stmt, table: &mut DataState, is_branch: bool, scope: &Rc<RefCell<SymbolTable>>) -> HashSet<Data> {
+/// 
+/// # Errors
+/// If errors occur, they are appended to the `errors` list.
+fn pass_stmt(stmt: &mut Stmt, table: &mut DataState, is_branch: bool, scope: &Rc<RefCell<SymbolTable>>, on: &AllowedLocations, dindex: &DataIndex, errors: &mut Vec<Error>) -> HashSet<Data> {
     // Match on the exact statement
     use Stmt::*;
     match stmt {
         Block{ block, .. } => {
-            pass_block(block, table, is_branch)
+            pass_block(block, table, is_branch, on, dindex, errors)
         },
 
         FuncDef{ code, st_entry, .. } => {
             // Function bodies never branch themselves (once called, they are always executed non-branching)
-            let ids: HashSet<Data> = pass_block(code, table, false);
+            let ids: HashSet<Data> = pass_block(code, table, false, on, dindex, errors);
 
             // Push the results to the data table
             table.set_funcs(&st_entry.as_ref().unwrap().borrow().name, ids);
@@ -147,7 +190,7 @@ fn pass_stmt(stmt: &mut Stmt, table: &mut DataState, is_branch: bool, scope: &Rc
             // Simply recurse, that'll do it (we are not interested in the results, since this function never returns anyway)
             for m in methods {
                 // Function bodies never branch themselves (once called, they are always executed non-branching)
-                pass_stmt(m, table, false, scope);
+                pass_stmt(m, table, false, scope, on, dindex, errors);
             }
 
             // The definition itself doesn't return, so it doesn't introduce new identifiers
@@ -156,7 +199,7 @@ fn pass_stmt(stmt: &mut Stmt, table: &mut DataState, is_branch: bool, scope: &Rc
         Return{ expr, .. } => {
             if let Some(expr) = expr {
                 // Return whether the expression returns any datasets
-                pass_expr(expr, table)
+                pass_expr(expr, table, on, dindex, errors)
             } else {
                 // Otherwise, it doesn't return any new identifiers
                 HashSet::new()
@@ -165,13 +208,13 @@ fn pass_stmt(stmt: &mut Stmt, table: &mut DataState, is_branch: bool, scope: &Rc
 
         If{ cond, consequent, alternative, .. } => {
             // We don't care about the condition, but recurse it for any inter-expression dependencies
-            pass_expr(cond, table);
+            pass_expr(cond, table, on, dindex, errors);
 
             // Do the consequent, in a branching manner
-            let mut ids: HashSet<Data> = pass_block(consequent, table, true);
+            let mut ids: HashSet<Data> = pass_block(consequent, table, true, on, dindex, errors);
             // Do the alternative too if there is one
             if let Some(alternative) = alternative {
-                ids.extend(pass_block(alternative, table, true));
+                ids.extend(pass_block(alternative, table, true, on, dindex, errors));
             }
             // Return the found ids
             ids
@@ -197,35 +240,41 @@ fn pass_stmt(stmt: &mut Stmt, table: &mut DataState, is_branch: bool, scope: &Rc
         },
         For{ initializer, condition, increment, consequent, .. } => {
             // Do the initializer, condition and increment for traversal purposes (the order makes sense, I think - if we ever get weird behaviour, check here)
-            pass_stmt(initializer, table, is_branch, scope);
-            pass_expr(condition, table);
-            pass_stmt(increment, table, is_branch, scope);
+            pass_stmt(initializer, table, is_branch, scope, on, dindex, errors);
+            pass_expr(condition, table, on, dindex, errors);
+            pass_stmt(increment, table, is_branch, scope, on, dindex, errors);
 
             // We consider the body to be branching, since the assignment values of variables may change depending on the first or later iterations (as far as data/result input is concerned)
-            pass_block(consequent, table, true);
+            pass_block(consequent, table, true, on, dindex, errors);
             // Don't forget to run again to update the loop itself
-            pass_block(consequent, table, true)
+            pass_block(consequent, table, true, on, dindex, errors)
         },
         While{ condition, consequent, .. } => {
             // The condition is recursed only to resolve in-condition dependencies
-            pass_expr(condition, table);
+            pass_expr(condition, table, on, dindex, errors);
 
             // We consider the body to be branching, since the assignment values of variables may change depending on the first or later iterations (as far as data/result input is concerned)
-            pass_block(consequent, table, true);
+            pass_block(consequent, table, true, on, dindex, errors);
             // Don't forget to run again to update the loop itself
-            pass_block(consequent, table, true)
+            pass_block(consequent, table, true, on, dindex, errors)
         },
-        On{ block, .. } => {
-            // The location is guaranteed to be a literal, so we skip
+        On{ location, block, .. } => {
+            // Restrict the locations for the block if the location is a literal (the location traversal will complain if it isn't)
+            let mut on: AllowedLocations = on.clone();
+            if let brane_dsl::ast::Expr::Cast{ expr, .. } = location {
+                if let brane_dsl::ast::Expr::Literal{ literal: Literal::String{ value, .. } } = &**expr {
+                    on.intersection(&mut AllowedLocations::Exclusive(HashSet::from([ Location::from(value) ])));
+                }
+            }
 
             // Do the block
-            pass_block(block, table, is_branch)
+            pass_block(block, table, is_branch, &on, dindex, errors)
         },
         Parallel{ blocks, st_entry, .. } => {
             // The parallel _does_ return, Tim - or at least, we have to put it in the variable if there is one
             let mut ids: HashSet<Data> = HashSet::new();
             for b in blocks {
-                ids.extend(pass_stmt(b, table, is_branch, scope));
+                ids.extend(pass_stmt(b, table, is_branch, scope, on, dindex, errors));
             }
 
             // Put it in the variable if this Parallel is returning
@@ -240,7 +289,7 @@ fn pass_stmt(stmt: &mut Stmt, table: &mut DataState, is_branch: bool, scope: &Rc
         LetAssign{ value, st_entry, .. } |
         Assign{ value, st_entry, .. }    => {
             // Traverse the value
-            let ids: HashSet<Data> = pass_expr(value, table);
+            let ids: HashSet<Data> = pass_expr(value, table, on, dindex, errors);
 
             // Now we do the trick; if this variable originates in this scope, _or_ we are guaranteed to be executing as only branch, we override whatever input is set for the variable; otherwise, we simply extend since whatever it has, it may still have it later
             let entry: &Rc<RefCell<VarEntry>> = st_entry.as_ref().unwrap();
@@ -261,7 +310,7 @@ fn pass_stmt(stmt: &mut Stmt, table: &mut DataState, is_branch: bool, scope: &Rc
         },
         Expr{ expr, .. } => {
             // Recurse but never return
-            pass_expr(expr, table);
+            pass_expr(expr, table, on, dindex, errors);
             HashSet::new()
         },
 
@@ -275,18 +324,24 @@ fn pass_stmt(stmt: &mut Stmt, table: &mut DataState, is_branch: bool, scope: &Rc
 /// # Arguments
 /// - `expr`: The Expr to traverse.
 /// - `table`: The DataTable we use to keep track of which variable has what value.
+/// - `on`: The locations to which the current block is restricted by any surrounding On-structs.
+/// - `dindex`: The DataIndex we use to find out where datasets may be processed.
+/// - `errors`: A list we use to accumulate errors as they occur.
 /// 
 /// # Returns
 /// This function returns the possible identifiers that the evaluation of this expression can be if it concerns a Data or IntermediateResult. Note that this differs from `pass_block()` and `pass_stmt()`.
-fn pass_expr(expr: &mut Expr, table: &DataState) -> HashSet<Data> {
+/// 
+/// # Errors
+/// If errors occur, they are appended to the `errors` list.
+fn pass_expr(expr: &mut Expr, table: &mut DataState, on: &AllowedLocations, dindex: &DataIndex, errors: &mut Vec<Error>) -> HashSet<Data> {
     use Expr::*;
     match expr {
         Cast{ expr, .. } => {
             // Only dataset casts are allowed if it is a dataset itself; so we can simply recurse it
-            pass_expr(expr, table)
+            pass_expr(expr, table, on, dindex, errors)
         },
 
-        Call{ args, input, result, st_entry, .. } => {
+        Call{ args, input, result, st_entry, locations, range, .. } => {
             // Populating calls is what this traversal is all about, so let's dive into the interesting stuff
 
             // Find out if this call is external
@@ -302,12 +357,43 @@ fn pass_expr(expr: &mut Expr, table: &DataState) -> HashSet<Data> {
                 // Traverse into the arguments to find the input identifiers
                 let mut ids: HashSet<Data> = HashSet::new();
                 for a in args {
-                    ids.extend(pass_expr(a, table));
+                    ids.extend(pass_expr(a, table, on, dindex, errors));
+                }
+
+                // Find out where the inputs may be processed
+                let mut residency  : AllowedLocations = AllowedLocations::All;
+                let mut restricted : Vec<String>      = vec![];
+                for i in &ids {
+                    let (name, locs): (&String, Option<HashSet<Location>>) = match i {
+                        Data::Data(name)               => (name, dindex.get(name).and_then(|info| info.residency.as_ref()).map(|locs| locs.iter().map(Location::from).collect())),
+                        Data::IntermediateResult(name) => (name, table.get_residency(name).cloned()),
+                    };
+                    if let Some(locs) = locs {
+                        residency.intersection(&mut AllowedLocations::Exclusive(locs));
+                        restricted.push(name.clone());
+                    }
                 }
                 *input = ids.into_iter().collect();
 
+                // Make sure the call can run somewhere the inputs may be processed (skipping calls we already complained about in an earlier pass over a loop body)
+                if residency.is_exclusive() && !locations.is_empty() {
+                    let mut possible: AllowedLocations = locations.clone();
+                    possible.intersection(&mut on.clone());
+                    possible.intersection(&mut residency.clone());
+                    if possible.is_empty() {
+                        restricted.sort();
+                        let mut allowed: Vec<String> = if let AllowedLocations::Exclusive(locs) = &residency { locs.iter().map(String::from).collect() } else { unreachable!(); };
+                        allowed.sort();
+                        errors.push(Error::ResidencyViolation{ range: range.clone(), data: restricted, allowed });
+                        *locations = AllowedLocations::Exclusive(HashSet::new());
+                    } else {
+                        // Restrict the call such that the planner only considers locations where it may process its inputs
+                        locations.intersection(&mut residency.clone());
+                    }
+                }
+
                 // If this function returns an IntermediateResult, generate the ID while at it (and it wasn't done so already)
-                if result.is_none() {
+                let ids: HashSet<Data> = if result.is_none() {
                     let entry: Ref<FunctionEntry> = st_entry.as_ref().unwrap().borrow();
                     if entry.signature.ret == DataType::Class(BuiltinClasses::IntermediateResult.name().into()) {
                         // If this call is an external one _and_ it returns a result, we want to note it as such.
@@ -327,12 +413,20 @@ fn pass_expr(expr: &mut Expr, table: &DataState) -> HashSet<Data> {
                 } else {
                     // Otherwise, we don't generate a new one but return the value of result
                     HashSet::from([ Data::IntermediateResult(result.clone().unwrap()) ])
+                };
+
+                // Any result derived from restricted data inherits its restrictions
+                if let AllowedLocations::Exclusive(locs) = residency {
+                    for i in &ids {
+                        if let Data::IntermediateResult(name) = i { table.set_residency(name, locs.clone()); }
+                    }
                 }
+                ids
 
             } else {
                 // Still recurse into the arguments to catch any nested calls
                 for a in args {
-                    pass_expr(a, table);
+                    pass_expr(a, table, on, dindex, errors);
                 }
 
                 // The returned identifier is quite simply that of the function itself
@@ -348,28 +442,37 @@ fn pass_expr(expr: &mut Expr, table: &DataState) -> HashSet<Data> {
             // We are lazy, and accept state space explosion in case someone is so nuts to have an array of Data
             let mut ids: HashSet<Data> = HashSet::new();
             for v in values {
-                ids.extend(pass_expr(v, table));
+                ids.extend(pass_expr(v, table, on, dindex, errors));
             }
             ids
         },
         ArrayIndex{ array, index, .. } => {
             // Do the array first, and remember that to return
-            let ids: HashSet<Data> = pass_expr(array, table);
+            let ids: HashSet<Data> = pass_expr(array, table, on, dindex, errors);
             // We do the other side for fun as well
-            pass_expr(index, table);
+            pass_expr(index, table, on, dindex, errors);
 
             // But return the ids of the array expression, that's importat
             ids
         },
+        Map{ entries, .. } => {
+            // Like arrays, any of the values may be returned
+            let mut ids: HashSet<Data> = HashSet::new();
+            for (k, v) in entries {
+                pass_expr(k, table, on, dindex, errors);
+                ids.extend(pass_expr(v, table, on, dindex, errors));
+            }
+            ids
+        },
 
         UnaOp{ expr, .. } => {
             // Simply recurse, since there aren't really any expressions possible on datasets and such
-            pass_expr(expr, table)
+            pass_expr(expr, table, on, dindex, errors)
         },
         BinOp{ lhs, rhs, .. } => {
             // There's not really a data-changing operation, so just join and we assume it won't really matter
-            let mut ids: HashSet<Data> = pass_expr(lhs, table);
-            ids.extend(pass_expr(rhs, table));
+            let mut ids: HashSet<Data> = pass_expr(lhs, table, on, dindex, errors);
+            ids.extend(pass_expr(rhs, table, on, dindex, errors));
             ids
         },
         Proj{ st_entry, .. } => {
@@ -403,7 +506,7 @@ fn pass_expr(expr: &mut Expr, table: &DataState) -> HashSet<Data> {
             // Recurse into the properties to traverse the expressions there
             let mut name: Option<String> = None;
             for p in properties {
-                pass_expr(&mut p.value, table);
+                pass_expr(&mut p.value, table, on, dindex, errors);
 
                 // While at it, note if we find 'name' - and if we do, its value
                 if is_data && &p.name.value == "name" {
@@ -446,20 +549,27 @@ fn pass_expr(expr: &mut Expr, table: &DataState) -> HashSet<Data> {
 /// Note that type analysis must already have been performed.
 /// 
 /// # Arguments
+/// - `state`: The CompileState that keeps track of data dependencies in between snippets.
+/// - `data_index`: The DataIndex that describes where datasets may be processed.
 /// - `root`: The root node of the tree on which this compiler pass will be done.
 /// 
 /// # Returns
-/// The same nodes as went in, but now with added in `input` and `result` annotations to each external call.
+/// The same nodes as went in, but now with added in `input` and `result` annotations to each external call. Calls that process restricted datasets are additionally restricted to the locations where that is allowed.
 /// 
 /// # Errors
-/// This pass typically does not error, but the option is here for convention purposes.
-pub fn do_traversal(state: &mut CompileState, root: Program) -> Result<Program, Vec<AstError>> {
+/// This pass may throw multiple `AstError::DataError`s if external calls would process restricted datasets on locations where that is not allowed.
+pub fn do_traversal(state: &mut CompileState, data_index: &DataIndex, root: Program) -> Result<Program, Vec<AstError>> {
     let mut root = root;
 
     // Iterate over all statements to analyse dependencies
     // (The main block is obviously never branching either)
-    pass_block(&mut root.block, &mut state.data, false);
+    let mut errors: Vec<Error> = vec![];
+    pass_block(&mut root.block, &mut state.data, false, &AllowedLocations::All, data_index, &mut errors);
 
     // Done
-    Ok(root)
+    if errors.is_empty() {
+        Ok(root)
+    } else {
+        Err(errors.into_iter().map(|e| e.into()).collect())
+    }
 }
@@ -237,6 +237,7 @@ fn move_task(task: &Rc<RefCell<FunctionEntry>>, table: &mut TableState) {
             signature    : entry.signature.clone(),
             arg_names    : entry.arg_names.clone(),
             requirements : entry.requirements.clone().unwrap(),
+            secrets      : entry.secrets.clone(),
 
             package_name    : entry.package_name.clone().unwrap(),
             package_version : entry.package_version.clone().unwrap(),
@@ -562,6 +563,12 @@ fn pass_expr(expr: &mut Expr, _table: &mut TableState) {
             pass_expr(array, _table);
             pass_expr(index, _table);
         },
+        Map{ entries, .. } => {
+            for (k, v) in entries {
+                pass_expr(k, _table);
+                pass_expr(v, _table);
+            }
+        },
 
         UnaOp{ expr, .. } => {
             pass_expr(expr, _table);
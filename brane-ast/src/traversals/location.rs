@@ -91,47 +91,48 @@ mod tests {
 /// - `stmt`: The Stmt to traverse.
 /// - `locations`: The current restriction of locations as imposed by the on-structs.
 /// - `reasons`: The ranges of the on-structs that somehow restrict the current call.
+/// - `known`: The locations known in the instance, if available. Used to check that on-structs and annotations only refer to existing locations.
 /// - `errors`: A list we use to accumulate errors as they occur.
 /// 
 /// # Errors
 /// This function may error if there were semantic problems while resolving the locations.
 /// 
 /// If errors occur, they are appended to the `errors` list. The function is early-quit in that case.
-fn pass_stmt(stmt: &mut Stmt, locations: AllowedLocations, reasons: Vec<TextRange>, errors: &mut Vec<Error>) {
+fn pass_stmt(stmt: &mut Stmt, locations: AllowedLocations, reasons: Vec<TextRange>, known: Option<&HashSet<Location>>, errors: &mut Vec<Error>) {
     // Match on the exact statement
     use Stmt::*;
     #[allow(clippy::collapsible_match)]
     match stmt {
         Block{ block, .. } => {
-            pass_block(block, locations, reasons, errors);
+            pass_block(block, locations, reasons, known, errors);
         },
 
         FuncDef{ code, .. } => {
-            pass_block(code, locations, reasons, errors);
+            pass_block(code, locations, reasons, known, errors);
         },
         ClassDef{ methods, .. } => {
             for m in methods {
-                pass_stmt(m, locations.clone(), reasons.clone(), errors);
+                pass_stmt(m, locations.clone(), reasons.clone(), known, errors);
             }
         },
         Return{ expr, .. } => {
-            if let Some(expr) = expr { pass_expr(expr, locations, reasons, errors); }
+            if let Some(expr) = expr { pass_expr(expr, locations, reasons, known, errors); }
         },
 
         If{ cond, consequent, alternative, .. } => {
-            pass_expr(cond, locations.clone(), reasons.clone(), errors);
-            pass_block(consequent, locations.clone(), reasons.clone(), errors);
-            if let Some(alternative) = alternative { pass_block(alternative, locations, reasons, errors) };
+            pass_expr(cond, locations.clone(), reasons.clone(), known, errors);
+            pass_block(consequent, locations.clone(), reasons.clone(), known, errors);
+            if let Some(alternative) = alternative { pass_block(alternative, locations, reasons, known, errors) };
         },
         For{ initializer, condition, increment, consequent, .. } => {
-            pass_stmt(initializer, locations.clone(), reasons.clone(), errors);
-            pass_expr(condition, locations.clone(), reasons.clone(), errors);
-            pass_stmt(increment, locations.clone(), reasons.clone(), errors);
-            pass_block(consequent, locations, reasons, errors);
+            pass_stmt(initializer, locations.clone(), reasons.clone(), known, errors);
+            pass_expr(condition, locations.clone(), reasons.clone(), known, errors);
+            pass_stmt(increment, locations.clone(), reasons.clone(), known, errors);
+            pass_block(consequent, locations, reasons, known, errors);
         },
         While{ condition, consequent, .. } => {
-            pass_expr(condition, locations.clone(), reasons.clone(), errors);
-            pass_block(consequent, locations, reasons, errors);
+            pass_expr(condition, locations.clone(), reasons.clone(), known, errors);
+            pass_block(consequent, locations, reasons, known, errors);
         },
         On{ location, block, range, .. } => {
             // Enfore the location to be a string constant (we do always expect a cast due to type analysis).
@@ -146,6 +147,9 @@ fn pass_stmt(stmt: &mut Stmt, locations: AllowedLocations, reasons: Vec<TextRang
                 errors.push(Error::IllegalLocation { range: location.range().clone() });
                 return;
             };
+            if let Some(known) = known {
+                if !known.contains(&Location::from(&loc)) { errors.push(Error::UnknownLocation{ name: loc.clone(), range: location.range().clone() }); }
+            }
 
             // See what this additional restriction imposes
             let mut locations: AllowedLocations = locations;
@@ -158,22 +162,22 @@ fn pass_stmt(stmt: &mut Stmt, locations: AllowedLocations, reasons: Vec<TextRang
             // With the new restrictions set, recurse
             let mut reasons: Vec<TextRange> = reasons;
             reasons.push(range.clone());
-            pass_block(block, locations, reasons, errors);
+            pass_block(block, locations, reasons, known, errors);
         },
         Parallel{ blocks, .. } => {
             for b in blocks {
-                pass_stmt(b, locations.clone(), reasons.clone(), errors);
+                pass_stmt(b, locations.clone(), reasons.clone(), known, errors);
             }
         },
 
         LetAssign{ value, .. } => {
-            pass_expr(value, locations, reasons, errors);
+            pass_expr(value, locations, reasons, known, errors);
         },
         Assign{ value, .. } => {
-            pass_expr(value, locations, reasons, errors);
+            pass_expr(value, locations, reasons, known, errors);
         },
         Expr{ expr, .. } => {
-            pass_expr(expr, locations, reasons, errors);
+            pass_expr(expr, locations, reasons, known, errors);
         },
 
         // The rest no matter
@@ -187,16 +191,17 @@ fn pass_stmt(stmt: &mut Stmt, locations: AllowedLocations, reasons: Vec<TextRang
 /// - `block`: The Block to traverse.
 /// - `locations`: The current restriction of locations as imposed by the on-structs.
 /// - `reasons`: The ranges of the on-structs that somehow restrict the current call.
+/// - `known`: The locations known in the instance, if available. Used to check that on-structs and annotations only refer to existing locations.
 /// - `errors`: A list we use to accumulate errors as they occur.
 /// 
 /// # Errors
 /// This function may error if there were semantic problems while resolving the locations.
 /// 
 /// If errors occur, they are appended to the `errors` list. The function is early-quit in that case.
-fn pass_block(block: &mut Block, locations: AllowedLocations, reasons: Vec<TextRange>, errors: &mut Vec<Error>) {
+fn pass_block(block: &mut Block, locations: AllowedLocations, reasons: Vec<TextRange>, known: Option<&HashSet<Location>>, errors: &mut Vec<Error>) {
     // Simply recurse
     for s in &mut block.stmts {
-        pass_stmt(s, locations.clone(), reasons.clone(), errors);
+        pass_stmt(s, locations.clone(), reasons.clone(), known, errors);
     }
 }
 
@@ -206,6 +211,7 @@ fn pass_block(block: &mut Block, locations: AllowedLocations, reasons: Vec<TextR
 /// - `expr`: The Expr to traverse.
 /// - `on_locations`: The current restriction of locations as imposed by the on-structs.
 /// - `on_reasons`: The ranges of the on-structs that somehow restrict the current call.
+/// - `known`: The locations known in the instance, if available. Used to check that on-structs and annotations only refer to existing locations.
 /// - `errors`: A list we use to accumulate errors as they occur.
 /// 
 /// # Returns
@@ -215,22 +221,29 @@ fn pass_block(block: &mut Block, locations: AllowedLocations, reasons: Vec<TextR
 /// This function may error if there were semantic problems while resolving the locations.
 /// 
 /// If errors occur, they are appended to the `errors` list. The function is early-quit in that case.
-fn pass_expr(expr: &mut Expr, on_locations: AllowedLocations, on_reasons: Vec<TextRange>, errors: &mut Vec<Error>) {
+fn pass_expr(expr: &mut Expr, on_locations: AllowedLocations, on_reasons: Vec<TextRange>, known: Option<&HashSet<Location>>, errors: &mut Vec<Error>) {
     use Expr::*;
     match expr {
         Cast{ expr, .. } => {
-            pass_expr(expr, on_locations, on_reasons, errors);
+            pass_expr(expr, on_locations, on_reasons, known, errors);
         },
 
         Call{ expr, args, ref mut locations, range, .. } => {
             // Resolve the nested stuff first
-            pass_expr(expr, on_locations.clone(), on_reasons.clone(), errors);
+            pass_expr(expr, on_locations.clone(), on_reasons.clone(), known, errors);
             for a in args {
-                pass_expr(a, on_locations.clone(), on_reasons.clone(), errors);
+                pass_expr(a, on_locations.clone(), on_reasons.clone(), known, errors);
             }
 
-            // Add the current location if it added to the restriction
+            // Add the current location if it added to the restriction (after checking it only refers to existing locations)
             let mut on_reasons: Vec<TextRange> = on_reasons;
+            if let (AllowedLocations::Exclusive(locs), Some(known)) = (&*locations, known) {
+                let mut unknown: Vec<String> = locs.iter().filter(|l| !known.contains(l)).map(|l| l.into()).collect();
+                unknown.sort();
+                for name in unknown {
+                    errors.push(Error::UnknownLocation{ name, range: range.clone() });
+                }
+            }
             if locations.is_exclusive() { on_reasons.push(range.clone()); }
 
             // Take the union of the already imposed restrictions + those imposed by On-blocks
@@ -240,29 +253,35 @@ fn pass_expr(expr: &mut Expr, on_locations: AllowedLocations, on_reasons: Vec<Te
         },
         Array{ values, .. } => {
             for v in values {
-                pass_expr(v, on_locations.clone(), on_reasons.clone(), errors);
+                pass_expr(v, on_locations.clone(), on_reasons.clone(), known, errors);
             }
         },
         ArrayIndex{ array, index, .. } => {
-            pass_expr(array, on_locations.clone(), on_reasons.clone(), errors);
-            pass_expr(index, on_locations, on_reasons, errors);
+            pass_expr(array, on_locations.clone(), on_reasons.clone(), known, errors);
+            pass_expr(index, on_locations, on_reasons, known, errors);
+        },
+        Map{ entries, .. } => {
+            for (k, v) in entries {
+                pass_expr(k, on_locations.clone(), on_reasons.clone(), known, errors);
+                pass_expr(v, on_locations.clone(), on_reasons.clone(), known, errors);
+            }
         },
 
         UnaOp{ expr, .. } => {
-            pass_expr(expr, on_locations, on_reasons, errors);
+            pass_expr(expr, on_locations, on_reasons, known, errors);
         },
         BinOp{ lhs, rhs, .. } => {
-            pass_expr(lhs, on_locations.clone(), on_reasons.clone(), errors);
-            pass_expr(rhs, on_locations, on_reasons, errors);
+            pass_expr(lhs, on_locations.clone(), on_reasons.clone(), known, errors);
+            pass_expr(rhs, on_locations, on_reasons, known, errors);
         },
         Proj{ lhs, rhs, .. } => {
-            pass_expr(lhs, on_locations.clone(), on_reasons.clone(), errors);
-            pass_expr(rhs, on_locations, on_reasons, errors);
+            pass_expr(lhs, on_locations.clone(), on_reasons.clone(), known, errors);
+            pass_expr(rhs, on_locations, on_reasons, known, errors);
         },
 
         Instance{ properties, .. } => {
             for p in properties {
-                pass_expr(&mut p.value, on_locations.clone(), on_reasons.clone(), errors);
+                pass_expr(&mut p.value, on_locations.clone(), on_reasons.clone(), known, errors);
             }
         },
 
@@ -284,19 +303,20 @@ fn pass_expr(expr: &mut Expr, on_locations: AllowedLocations, on_reasons: Vec<Te
 /// 
 /// # Arguments
 /// - `root`: The root node of the tree on which this compiler pass will be done.
+/// - `known`: The locations known in the instance, if available. If given, on-structs and annotations may only refer to these.
 /// 
 /// # Returns
 /// The same nodes as went in, but now with no unresolved types.
 /// 
 /// # Errors
 /// This pass may throw multiple `AstError::ResolveError`s if the user made mistakes with their variable references.
-pub fn do_traversal(root: Program) -> Result<Program, Vec<AstError>> {
+pub fn do_traversal(root: Program, known: Option<&HashSet<Location>>) -> Result<Program, Vec<AstError>> {
     let mut root = root;
 
     // Iterate over all statements to build their symbol tables (if relevant)
     let mut errors: Vec<Error> = vec![];
     for s in root.block.stmts.iter_mut() {
-        pass_stmt(s, AllowedLocations::All, vec![], &mut errors);
+        pass_stmt(s, AllowedLocations::All, vec![], known, &mut errors);
     }
 
     // Done
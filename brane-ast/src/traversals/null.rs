@@ -230,6 +230,12 @@ fn pass_expr(expr: &mut Expr, errors: &mut Vec<Error>) {
             pass_expr(array, errors);
             pass_expr(index, errors);
         },
+        Map{ entries, .. } => {
+            for (k, v) in entries {
+                pass_expr(k, errors);
+                pass_expr(v, errors);
+            }
+        },
         Pattern{ exprs, .. } => {
             for e in exprs {
                 pass_expr(e, errors);
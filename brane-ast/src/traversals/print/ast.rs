@@ -88,8 +88,9 @@ fn pass_table(writer: &mut impl Write, table: &SymTable, indent: usize) -> std::
     // ...and all tasks
     for t in &table.tasks {
         match t {
-            TaskDef::Compute { package, version, function, args_names, requirements }  => {
+            TaskDef::Compute { package, version, function, args_names, requirements, secrets }  => {
                 if !requirements.is_empty() { writeln!(writer, "{}#[requirements = {:?}]", indent!(indent), requirements)?; }
+                if !secrets.is_empty() { writeln!(writer, "{}#[secrets = {:?}]", indent!(indent), secrets)?; }
                 writeln!(writer, "{}Task<Compute> {}{}::{}({}){};", indent!(indent),
                     package,
                     if !version.is_latest() { format!("<{}>", version) } else { String::new() },
@@ -124,7 +125,9 @@ fn pass_table(writer: &mut impl Write, table: &SymTable, indent: usize) -> std::
     if !table.vars.is_empty() || !table.funcs.is_empty() || !table.tasks.is_empty() || !table.classes.is_empty() { writeln!(writer)?; }
 
     // And the results
-    for (name, avail) in table.results.iter() {
+    let mut results: Vec<(&String, &String)> = table.results.iter().collect();
+    results.sort_by(|(n1, _), (n2, _)| n1.cmp(n2));
+    for (name, avail) in results {
         writeln!(writer, "{}IntermediateResult '{}' -> '{:?}'", indent!(INDENT_SIZE), name, avail)?;
     }
 
@@ -161,9 +164,13 @@ fn pass_edges(writer: &mut impl Write, index: usize, edges: &[Edge], table: &Vir
         // Match on it
         use Edge::*;
         match node {
-            Node { task, locs, at, input, result, next } => {
+            Node { task, locs, at, input, result, retry, timeout, next } => {
+                // Collect the inputs in a stable order
+                let mut inputs: Vec<String> = input.iter().map(|(name, avail)| format!("'{}'{}", name, if let Some(avail) = avail { format!(" ({:?})", avail) } else { String::new() })).collect();
+                inputs.sort();
+
                 // Write the Node as a task call
-                writeln!(writer, "{} {}Node({}){}{}{}",
+                writeln!(writer, "{} {}Node({}){}{}{}{}{}",
                     line_number!(i),
                     indent!(indent),
                     match &table.task(*task) {
@@ -172,8 +179,10 @@ fn pass_edges(writer: &mut impl Write, index: usize, edges: &[Edge], table: &Vir
                     },
                     if locs.is_restrictive() { format!(" <limited to: {}>", locs.restricted().join(",")) } else { String::new() },
                     if let Some(at) = at { format!(" @{}", at) } else { String::new() },
+                    if let Some(retry) = retry { format!(" <retry: {}x, {}ms>", retry.max_retries, retry.backoff) } else { String::new() },
+                    if let Some(timeout) = timeout { format!(" <timeout: {}s>", timeout) } else { String::new() },
                     if !input.is_empty() || result.is_some() { format!(" [{} -> {}]",
-                        if !input.is_empty() { input.iter().map(|(name, avail)| format!("'{}'{}", name, if let Some(avail) = avail { format!(" ({:?})", avail) } else { String::new() })).collect::<Vec<String>>().join(", ").to_string() } else { "''".into() },
+                        if !input.is_empty() { inputs.join(", ") } else { "''".into() },
                         if let Some(name) = result { format!("'{}'", name) } else { "''".into() },
                     ) } else { String::new() },
                 )?;
@@ -318,7 +327,7 @@ fn pass_edges(writer: &mut impl Write, index: usize, edges: &[Edge], table: &Vir
 /// 
 /// # Returns
 /// Nothing, but does print the instruction to stdout.
-fn pass_edge_instr(writer: &mut impl Write, instr: &EdgeInstr, table: &VirtualSymTable) -> std::io::Result<()> {
+pub(crate) fn pass_edge_instr(writer: &mut impl Write, instr: &EdgeInstr, table: &VirtualSymTable) -> std::io::Result<()> {
     // Match the instruction
     use EdgeInstr::*;
     match instr {
@@ -330,6 +339,7 @@ fn pass_edge_instr(writer: &mut impl Write, instr: &EdgeInstr, table: &VirtualSy
         Proj{ field } => { write!(writer, "{} {}", instr, field)?; },
 
         Array{ length, res_type } => { write!(writer, "{} {},{}", instr, res_type, length)?; },
+        Map{ length, res_type }   => { write!(writer, "{} {},{}", instr, res_type, length)?; },
         ArrayIndex{ res_type }    => { write!(writer, "{} {}", instr, res_type)?; },
         Instance{ def }           => { write!(writer, "{} {}", instr, table.class(*def).name)?; },
 
@@ -385,7 +395,9 @@ pub fn do_traversal(root: Workflow, writer: impl Write) -> Result<Workflow, Vec<
     if let Err(err) = pass_edges(&mut writer, 0, &root.graph, &table, INDENT_SIZE, &mut HashSet::new()) { return Err(vec![ Error::WriteError{ err } ]); };
 
     // Print the functions
-    for (i, f) in root.funcs.iter() {
+    let mut funcs: Vec<(&usize, &Vec<Edge>)> = root.funcs.iter().collect();
+    funcs.sort_by_key(|(i, _)| **i);
+    for (i, f) in funcs {
         if let Err(err) = writeln!(&mut writer) { return Err(vec![ Error::WriteError{ err } ]); };
         if let Err(err) = writeln!(&mut writer, "{}<Function {} ({})>", indent!(INDENT_SIZE), *i, root.table.funcs[*i].name) { return Err(vec![ Error::WriteError{ err } ]); };
         table.push(&table.func(*i).table);
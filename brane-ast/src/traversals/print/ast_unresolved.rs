@@ -329,6 +329,7 @@ pub fn pass_edge_instr(writer: &mut impl Write, instr: &EdgeInstr, table: &mut V
         Proj{ field } => { write!(writer, "{} {}", instr, field)?; },
 
         Array{ length, res_type } => { write!(writer, "{} {},{}", instr, res_type, length)?; },
+        Map{ length, res_type }   => { write!(writer, "{} {},{}", instr, res_type, length)?; },
         ArrayIndex{ res_type }    => { write!(writer, "{} {}", instr, res_type)?; },
         Instance{ def }           => { write!(writer, "{} {}", instr, table.class(*def).name)?; },
 
@@ -0,0 +1,331 @@
+//  DOT.rs
+//    by agent
+//
+//  Created:
+//    16 Oct 2026, 08:25:59
+//  Last edited:
+//    16 Oct 2026, 09:23:23
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Prints the `brane-ast` AST as a Graphviz DOT graph of its edges.
+//
+
+use std::collections::HashSet;
+use std::io::Write;
+
+pub use crate::errors::AstError as Error;
+use crate::ast::{DataName, Edge, TaskDef, Workflow};
+use crate::state::VirtualSymTable;
+use super::ast::pass_edge_instr;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+pub mod tests {
+    use brane_dsl::ParserOptions;
+    use brane_shr::utilities::{create_data_index, create_package_index, test_on_dsl_files};
+    use specifications::data::DataIndex;
+    use specifications::package::PackageIndex;
+    use super::*;
+    use crate::{compile_program, CompileResult};
+
+
+    /// Tests the traversal by rendering the graph of every test file.
+    #[test]
+    fn test_dot() {
+        test_on_dsl_files("BraneScript", |path, code| {
+            println!("{}", (0..80).map(|_| '-').collect::<String>());
+            println!("File '{}' gave us:", path.display());
+
+            // Load the package index
+            let pindex: PackageIndex = create_package_index();
+            let dindex: DataIndex    = create_data_index();
+
+            // Compile it all the way
+            let workflow: Workflow = match compile_program(code.as_bytes(), &pindex, &dindex, &ParserOptions::bscript()) {
+                CompileResult::Workflow(wf, warns) => {
+                    // Print warnings if any
+                    for w in warns {
+                        w.prettyprint(path.to_string_lossy(), &code);
+                    }
+                    wf
+                },
+                CompileResult::Eof(err) => {
+                    // Print the error
+                    err.prettyprint(path.to_string_lossy(), &code);
+                    panic!("Failed to compile file (see output above)");
+                }
+                CompileResult::Err(errs) => {
+                    // Print the errors
+                    for e in errs {
+                        e.prettyprint(path.to_string_lossy(), &code);
+                    }
+                    panic!("Failed to compile file (see output above)");
+                },
+
+                _ => { unreachable!(); },
+            };
+
+            // Now print the graph for prettyness
+            let mut dot: Vec<u8> = vec![];
+            do_traversal(workflow, &mut dot).expect("Failed to render workflow as DOT");
+            let dot: String = String::from_utf8(dot).expect("DOT output is not valid UTF-8");
+            assert!(dot.starts_with("digraph workflow {"));
+            println!("{}", dot);
+            println!("{}\n\n", (0..80).map(|_| '-').collect::<String>());
+        });
+    }
+}
+
+
+
+
+
+/***** MACROS ******/
+/// Generates the correct number of spaces for an indent.
+macro_rules! indent {
+    ($n_spaces:expr) => {
+        ((0..$n_spaces).map(|_| ' ').collect::<String>())
+    };
+}
+
+
+
+
+
+/***** CONSTANTS *****/
+/// Determines the increase in indentation for every nested level.
+const INDENT_SIZE: usize = 4;
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Escapes the given string such that it may be used within a quoted DOT identifier.
+///
+/// # Arguments
+/// - `raw`: The string to escape.
+///
+/// # Returns
+/// The escaped string.
+#[inline]
+fn escape(raw: impl AsRef<str>) -> String {
+    raw.as_ref().replace('\\', "\\\\").replace('\"', "\\\"").replace('\n', "\\n")
+}
+
+/// Returns the DOT identifier of the given dataset or intermediate result.
+///
+/// # Arguments
+/// - `name`: The DataName to generate the identifier for.
+///
+/// # Returns
+/// A (quoted) DOT identifier that is the same for every edge referring to this data.
+#[inline]
+fn data_id(name: &DataName) -> String {
+    match name {
+        DataName::Data(name)               => format!("\"data_{}\"", escape(name)),
+        DataName::IntermediateResult(name) => format!("\"result_{}\"", escape(name)),
+    }
+}
+
+
+
+/// Prints a given Edge buffer as a DOT cluster.
+///
+/// # Arguments
+/// - `writer`: The `Write`r to write to.
+/// - `prefix`: The prefix with which to make the identifiers of the edges in this buffer unique.
+/// - `edges`: The list of Edges to print.
+/// - `table`: The VirtualSymTable we use to resolve indices.
+/// - `data`: The datasets and intermediate results we've seen so far, which are printed as separate nodes later.
+///
+/// # Returns
+/// Nothing, but does print the buffer to the given writer.
+fn pass_edges(writer: &mut impl Write, prefix: &str, edges: &[Edge], table: &VirtualSymTable, data: &mut HashSet<DataName>) -> std::io::Result<()> {
+    let indent: usize = 2 * INDENT_SIZE;
+
+    // Write a node and its outgoing arrows for every edge in the buffer
+    for (i, edge) in edges.iter().enumerate() {
+        // Only draw arrows to edges that exist (the others mean we stop)
+        let arrow = |writer: &mut dyn Write, target: usize, label: Option<&str>| -> std::io::Result<()> {
+            if target >= edges.len() { return Ok(()); }
+            writeln!(writer, "{}{}_{} -> {}_{}{};", indent!(indent), prefix, i, prefix, target, if let Some(label) = label { format!(" [label=\"{}\"]", label) } else { String::new() })
+        };
+
+        use Edge::*;
+        match edge {
+            Node { task, locs, at, input, result, retry, timeout, next } => {
+                // Write the Node as a box with the task and where it may run
+                let name: String = match &table.task(*task) {
+                    TaskDef::Compute { package, version, function, .. } => format!("{}{}::{}", package, if !version.is_latest() { format!("<{}>", version) } else { String::new() }, function.name),
+                    TaskDef::Transfer {}                                => "__builtin::transfer".into(),
+                };
+                writeln!(writer, "{}{}_{} [shape=box, style=bold, label=\"{}: {}{}{}{}{}\"];", indent!(indent), prefix, i,
+                    i,
+                    escape(name),
+                    if locs.is_restrictive() { format!("\\nlimited to: {}", escape(locs.restricted().join(","))) } else { String::new() },
+                    if let Some(at) = at { format!("\\n@{}", escape(at)) } else { String::new() },
+                    if let Some(retry) = retry { format!("\\nretry: {}x, {}ms", retry.max_retries, retry.backoff) } else { String::new() },
+                    if let Some(timeout) = timeout { format!("\\ntimeout: {}s", timeout) } else { String::new() },
+                )?;
+                arrow(writer, *next, None)?;
+
+                // Write the data dependencies
+                for (name, avail) in input {
+                    writeln!(writer, "{}{} -> {}_{} [style=dashed{}];", indent!(indent), data_id(name), prefix, i,
+                        if let Some(avail) = avail { format!(", label=\"{}\"", escape(format!("{:?}", avail))) } else { String::new() },
+                    )?;
+                    data.insert(name.clone());
+                }
+                if let Some(result) = result {
+                    let result: DataName = DataName::IntermediateResult(result.clone());
+                    writeln!(writer, "{}{}_{} -> {} [style=dashed];", indent!(indent), prefix, i, data_id(&result))?;
+                    data.insert(result);
+                }
+            },
+            Linear { instrs, next } => {
+                // Write the instructions as a left-aligned list
+                let mut label: String = format!("{}: Linear\\l", i);
+                for instr in instrs {
+                    let mut buf: Vec<u8> = vec![];
+                    pass_edge_instr(&mut buf, instr, table)?;
+                    label.push_str(&escape(String::from_utf8_lossy(&buf)));
+                    label.push_str("\\l");
+                }
+                writeln!(writer, "{}{}_{} [shape=box, label=\"{}\"];", indent!(indent), prefix, i, label)?;
+                arrow(writer, *next, None)?;
+            },
+            Stop {} => {
+                writeln!(writer, "{}{}_{} [shape=doublecircle, label=\"{}: Stop\"];", indent!(indent), prefix, i, i)?;
+            },
+
+            Branch { true_next, false_next, merge } => {
+                writeln!(writer, "{}{}_{} [shape=diamond, label=\"{}: Branch\"];", indent!(indent), prefix, i, i)?;
+                arrow(writer, *true_next, Some("true"))?;
+                // Without a false branch, we jump straight to the merge point
+                if let Some(false_next) = false_next.or(*merge) { arrow(writer, false_next, Some("false"))?; }
+            },
+            Parallel { branches, merge: _ } => {
+                writeln!(writer, "{}{}_{} [shape=trapezium, label=\"{}: Parallel\"];", indent!(indent), prefix, i, i)?;
+                for (b, branch) in branches.iter().enumerate() {
+                    arrow(writer, *branch, Some(&format!("branch {}", b)))?;
+                }
+            },
+            Join { merge, next } => {
+                writeln!(writer, "{}{}_{} [shape=invtrapezium, label=\"{}: Join({:?})\"];", indent!(indent), prefix, i, i, merge)?;
+                arrow(writer, *next, None)?;
+            },
+
+            Loop { cond, body, next } => {
+                writeln!(writer, "{}{}_{} [shape=hexagon, label=\"{}: Loop\"];", indent!(indent), prefix, i, i)?;
+                arrow(writer, *cond, Some("cond"))?;
+                arrow(writer, *body, Some("body"))?;
+                if let Some(next) = next { arrow(writer, *next, Some("next"))?; }
+            },
+
+            Call { next } => {
+                writeln!(writer, "{}{}_{} [shape=cds, label=\"{}: Call\"];", indent!(indent), prefix, i, i)?;
+                arrow(writer, *next, None)?;
+            },
+            Return {} => {
+                writeln!(writer, "{}{}_{} [shape=doublecircle, label=\"{}: Return\"];", indent!(indent), prefix, i, i)?;
+            },
+        }
+    }
+
+    // Done
+    Ok(())
+}
+
+/// Prints the datasets and intermediate results that the edges depend on as separate nodes.
+///
+/// # Arguments
+/// - `writer`: The `Write`r to write to.
+/// - `data`: The datasets and intermediate results to print.
+///
+/// # Returns
+/// Nothing, but does print the nodes to the given writer.
+fn pass_data(writer: &mut impl Write, data: &HashSet<DataName>) -> std::io::Result<()> {
+    // Sort them to keep the output deterministic
+    let mut data: Vec<&DataName> = data.iter().collect();
+    data.sort_by(|d1, d2| d1.to_string().cmp(&d2.to_string()));
+
+    for name in data {
+        writeln!(writer, "{}{} [shape=cylinder, label=\"{}\"{}];", indent!(INDENT_SIZE), data_id(name), escape(name.name()), if name.is_intermediate_result() { ", style=dashed" } else { "" })?;
+    }
+
+    // Done
+    Ok(())
+}
+
+/// Prints the given Workflow as a DOT graph.
+///
+/// # Arguments
+/// - `writer`: The `Write`r to write to.
+/// - `root`: The Workflow to print.
+///
+/// # Returns
+/// Nothing, but does print the graph to the given writer.
+fn pass_workflow(writer: &mut impl Write, root: &Workflow) -> std::io::Result<()> {
+    writeln!(writer, "digraph workflow {{")?;
+    writeln!(writer, "{}node [fontname=\"monospace\"];", indent!(INDENT_SIZE))?;
+    writeln!(writer, "{}edge [fontname=\"monospace\"];", indent!(INDENT_SIZE))?;
+
+    // Print the main function body
+    let mut data: HashSet<DataName> = HashSet::new();
+    let mut table: VirtualSymTable = VirtualSymTable::with(&root.table);
+    writeln!(writer)?;
+    writeln!(writer, "{}subgraph cluster_main {{", indent!(INDENT_SIZE))?;
+    writeln!(writer, "{}label=\"<Main>\";", indent!(2 * INDENT_SIZE))?;
+    pass_edges(writer, "main", &root.graph, &table, &mut data)?;
+    writeln!(writer, "{}}}", indent!(INDENT_SIZE))?;
+
+    // Print the functions, in order of definition
+    let mut funcs: Vec<(&usize, &Vec<_>)> = root.funcs.iter().collect();
+    funcs.sort_by_key(|(i, _)| **i);
+    for (i, f) in funcs {
+        writeln!(writer)?;
+        writeln!(writer, "{}subgraph cluster_func{} {{", indent!(INDENT_SIZE), *i)?;
+        writeln!(writer, "{}label=\"<Function {} ({})>\";", indent!(2 * INDENT_SIZE), *i, escape(&root.table.funcs[*i].name))?;
+        table.push(&table.func(*i).table);
+        pass_edges(writer, &format!("func{}", *i), f, &table, &mut data)?;
+        table.pop();
+        writeln!(writer, "{}}}", indent!(INDENT_SIZE))?;
+    }
+
+    // Print the data dependencies
+    if !data.is_empty() {
+        writeln!(writer)?;
+        pass_data(writer, &data)?;
+    }
+
+    // Done
+    writeln!(writer, "}}")
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Starts printing the root of the AST (i.e., a Workflow) as a Graphviz DOT graph.
+///
+/// Every edge becomes a node in the graph, grouped per function, and every dataset or intermediate result that a task depends on or produces becomes a separate node connected with dashed arrows.
+///
+/// # Arguments
+/// - `root`: The root node of the tree on which this compiler pass will be done.
+/// - `writer`: The `Write`r to write to.
+///
+/// # Returns
+/// The same root node as went in (since this compiler pass performs no transformations on the tree).
+///
+/// # Errors
+/// This pass may error if we failed to write to the given writer.
+pub fn do_traversal(root: Workflow, writer: impl Write) -> Result<Workflow, Vec<Error>> {
+    let mut writer = writer;
+    if let Err(err) = pass_workflow(&mut writer, &root) { return Err(vec![ Error::WriteError{ err } ]); }
+    Ok(root)
+}
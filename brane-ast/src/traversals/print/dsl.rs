@@ -14,6 +14,7 @@
 
 use std::io::Write;
 
+use brane_dsl::DataType;
 use brane_dsl::location::AllowedLocations;
 use brane_dsl::ast::{self as dsl_ast, Block, Expr, Identifier, Literal, Program, Property, PropertyExpr, Stmt};
 
@@ -250,10 +251,26 @@ pub fn pass_stmt(writer: &mut impl Write, stmt: &Stmt, indent: usize) -> std::io
             writeln!(writer, "{}]", indent!(indent))?;
         },
 
-        LetAssign{ name, value, .. } => {
-            // Print the let thingy first + the name
-            write!(writer, "{}let ", indent!(indent))?;
-            pass_identifier(writer, name)?;
+        LetAssign{ name, value, param, .. } => {
+            // Print the let thingy first + the name (and the type, for parameters)
+            match param {
+                Some(data_type) => {
+                    write!(writer, "{}param ", indent!(indent))?;
+                    pass_identifier(writer, name)?;
+                    match data_type {
+                        DataType::Boolean  => write!(writer, ": bool")?,
+                        DataType::Integer  => write!(writer, ": int")?,
+                        DataType::Real     => write!(writer, ": real")?,
+                        DataType::String   => write!(writer, ": string")?,
+                        DataType::Class(n) => write!(writer, ": {}", n)?,
+                        data_type          => write!(writer, ": {}", data_type)?,
+                    }
+                },
+                None => {
+                    write!(writer, "{}let ", indent!(indent))?;
+                    pass_identifier(writer, name)?;
+                },
+            }
             // Print the expression
             write!(writer, " := ")?;
             pass_expr(writer, value, indent)?;
@@ -401,6 +418,20 @@ pub fn pass_expr(writer: &mut impl Write, expr: &Expr, indent: usize) -> std::io
             pass_expr(writer, index, indent)?;
             write!(writer, "]")?;
         },
+        Map{ entries, .. } => {
+            // Print the key/value pairs wrapped in '{}'
+            write!(writer, "{{")?;
+            let mut first = true;
+            for (k, v) in entries {
+                if first { first = false; }
+                else { write!(writer, ",")?; }
+                write!(writer, " ")?;
+                pass_expr(writer, k, indent)?;
+                write!(writer, ": ")?;
+                pass_expr(writer, v, indent)?;
+            }
+            write!(writer, "{}}}", if entries.is_empty() { "" } else { " " })?;
+        },
         Pattern{ exprs, .. } => {
             // We use ad-hoc syntax for now
             write!(writer, "Pattern<")?;
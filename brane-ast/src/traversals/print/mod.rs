@@ -10,7 +10,7 @@
 // 
 //  Description:
 //!   Prints either the `brane-dsl` AST or the `brane-ast` AST in
-//!   BraneScript-like syntax.
+//!   BraneScript-like syntax (or the latter as a Graphviz DOT graph).
 // 
 
 // Declare the modules
@@ -18,3 +18,4 @@ pub mod dsl;
 pub mod symbol_tables;
 pub mod ast_unresolved;
 pub mod ast;
+pub mod dot;
@@ -161,8 +161,10 @@ fn pass_symbol_table(writer: &mut impl Write, symbol_table: &Rc<RefCell<SymbolTa
     // Borrow the table
     let st: Ref<SymbolTable> = symbol_table.borrow();
 
-    // First, print all of its functions
-    for (name, f) in st.functions() {
+    // First, print all of its functions (sorted, to keep the output stable)
+    let mut functions: Vec<(&String, &Rc<RefCell<FunctionEntry>>)> = st.functions().collect();
+    functions.sort_by(|(n1, _), (n2, _)| n1.cmp(n2));
+    for (name, f) in functions {
         let f: Ref<FunctionEntry> = f.borrow();
         writeln!(writer, "{}{}func {}{}{}",
             indent!(indent),
@@ -173,7 +175,9 @@ fn pass_symbol_table(writer: &mut impl Write, symbol_table: &Rc<RefCell<SymbolTa
         )?;
     }
     // Next, print all of its classes
-    for (_, c) in st.classes() {
+    let mut classes: Vec<(&String, &Rc<RefCell<ClassEntry>>)> = st.classes().collect();
+    classes.sort_by(|(n1, _), (n2, _)| n1.cmp(n2));
+    for (_, c) in classes {
         let c: Ref<ClassEntry> = c.borrow();
 
         // Print the class signature header
@@ -189,7 +193,9 @@ fn pass_symbol_table(writer: &mut impl Write, symbol_table: &Rc<RefCell<SymbolTa
         writeln!(writer, "{}}}", indent!(indent))?;
     }
     // Finally, print the variables
-    for (name, v) in st.variables() {
+    let mut variables: Vec<(&String, &Rc<RefCell<VarEntry>>)> = st.variables().collect();
+    variables.sort_by(|(n1, _), (n2, _)| n1.cmp(n2));
+    for (name, v) in variables {
         let v: Ref<VarEntry> = v.borrow();
         writeln!(writer, "{}{}var {} : {},", indent!(indent), if v.index != usize::MAX { format!("{}) ", v.index) } else { String::new() }, name, v.data_type)?;
     }
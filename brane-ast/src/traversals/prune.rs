@@ -30,7 +30,7 @@ use crate::errors::AstError;
 #[cfg(test)]
 mod tests {
     use brane_dsl::ParserOptions;
-    use brane_shr::utilities::{create_data_index, create_package_index, test_on_dsl_files};
+    use brane_shr::utilities::{assert_golden, create_data_index, create_package_index, test_on_dsl_files};
     use specifications::data::DataIndex;
     use specifications::package::PackageIndex;
     use super::*;
@@ -76,8 +76,13 @@ mod tests {
             };
 
             // Now print the file for prettyness
-            dsl::do_traversal(program, std::io::stdout()).unwrap();
+            let mut output: Vec<u8> = vec![];
+            dsl::do_traversal(program, &mut output).unwrap();
+            print!("{}", String::from_utf8_lossy(&output));
             println!("{}\n\n", (0..80).map(|_| '-').collect::<String>());
+
+            // Compare it against the golden output
+            assert_golden("prune", &path, &output);
         });
     }
 }
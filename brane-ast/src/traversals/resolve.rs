@@ -15,6 +15,7 @@
 
 use std::cell::{Ref, RefCell, RefMut};
 use std::collections::HashMap;
+use std::mem;
 use std::rc::Rc;
 
 use brane_dsl::spec::MergeStrategy;
@@ -27,7 +28,9 @@ use specifications::package::{PackageIndex, PackageInfo};
 use specifications::version::Version;
 
 pub use crate::errors::ResolveError as Error;
+pub use crate::warnings::ResolveWarning as Warning;
 use crate::errors::AstError;
+use crate::warnings::AstWarning;
 use crate::spec::BuiltinClasses;
 use crate::state::CompileState;
 
@@ -36,7 +39,7 @@ use crate::state::CompileState;
 #[cfg(test)]
 pub mod tests {
     use brane_dsl::ParserOptions;
-    use brane_shr::utilities::{create_data_index, create_package_index, test_on_dsl_files};
+    use brane_shr::utilities::{assert_golden, create_data_index, create_package_index, test_on_dsl_files};
     use specifications::package::PackageIndex;
     use super::*;
     use super::super::print::symbol_tables;
@@ -81,8 +84,13 @@ pub mod tests {
             };
 
             // Now print the symbol tables for prettyness
-            symbol_tables::do_traversal(program, std::io::stdout()).unwrap();
+            let mut output: Vec<u8> = vec![];
+            symbol_tables::do_traversal(program, &mut output).unwrap();
+            print!("{}", String::from_utf8_lossy(&output));
             println!("{}\n\n", (0..80).map(|_| '-').collect::<String>());
+
+            // Compare it against the golden output
+            assert_golden("resolve", &path, &output);
         });
     }
 }
@@ -166,7 +174,7 @@ fn define_func(state: &CompileState, entry: &mut FunctionEntry, params: &mut [Id
 /// Nothing, but does add entries to the symbol table and references them in nodes.
 /// 
 /// If an error occurred, then it is appended to the `errors` list and the function returns early.
-fn pass_block(state: &CompileState, package_index: &PackageIndex, data_index: &DataIndex, block: &mut Block, parent: Option<Rc<RefCell<SymbolTable>>>, errors: &mut Vec<Error>) {
+fn pass_block(state: &CompileState, package_index: &PackageIndex, data_index: &DataIndex, block: &mut Block, parent: Option<Rc<RefCell<SymbolTable>>>, warnings: &mut Vec<Warning>, errors: &mut Vec<Error>) {
     // Update the block's range
     offset_range!(block.range, state.offset);
 
@@ -178,7 +186,7 @@ fn pass_block(state: &CompileState, package_index: &PackageIndex, data_index: &D
 
     // Go over the statements and attempt to (further) populate this symbol table
     for s in block.stmts.iter_mut() {
-        pass_stmt(state, package_index, data_index, s, &block.table, errors);
+        pass_stmt(state, package_index, data_index, s, &block.table, warnings, errors);
     }
 
     // The table should now be populated for this block
@@ -203,13 +211,13 @@ fn pass_block(state: &CompileState, package_index: &PackageIndex, data_index: &D
 /// This function may error if there were semantic problems while building the table for this statement (if any).
 /// 
 /// If an error occurred, then it is appended to the `errors` list and the function returns early.
-fn pass_stmt(state: &CompileState, package_index: &PackageIndex, data_index: &DataIndex, stmt: &mut Stmt, symbol_table: &Rc<RefCell<SymbolTable>>, errors: &mut Vec<Error>) {
+fn pass_stmt(state: &CompileState, package_index: &PackageIndex, data_index: &DataIndex, stmt: &mut Stmt, symbol_table: &Rc<RefCell<SymbolTable>>, warnings: &mut Vec<Warning>, errors: &mut Vec<Error>) {
     // Match on the exact statement
     use Stmt::*;
     match stmt {
         Block{ block, .. } => {
             // Blocks require renewed evaluation
-            pass_block(state, package_index, data_index, block, Some(symbol_table.clone()), errors);
+            pass_block(state, package_index, data_index, block, Some(symbol_table.clone()), warnings, errors);
         },
 
         Import{ ref mut name, version, ref mut st_funcs, ref mut st_classes, ref mut range, .. } => {
@@ -246,7 +254,11 @@ fn pass_stmt(state: &CompileState, package_index: &PackageIndex, data_index: &Da
                 let ret_type: DataType = DataType::from(&f.return_type);
 
                 // Wrap it in a function entry and add it to the list
-                match st.add_func(FunctionEntry::from_import(name, FunctionSignature::new(arg_types, ret_type), &info.name, info.version.clone(), arg_names, f.requirements.clone().unwrap_or_default(), TextRange::none())) {
+                let mut entry: FunctionEntry = FunctionEntry::from_import(name, FunctionSignature::new(arg_types, ret_type), &info.name, info.version.clone(), arg_names, f.requirements.clone().unwrap_or_default(), TextRange::none());
+                entry.deprecated = f.deprecated.clone();
+                entry.aliases    = f.aliases.clone().unwrap_or_default();
+                entry.secrets    = f.secrets.clone().unwrap_or_default();
+                match st.add_func(entry) {
                     Ok(entry) => { funcs.push(entry); },
                     Err(err)  => {
                         errors.push(Error::FunctionImportError{ package_name: info.name.clone(), name: name.into(), err, range: range.clone() });
@@ -291,13 +303,14 @@ fn pass_stmt(state: &CompileState, package_index: &PackageIndex, data_index: &Da
             *st_funcs   = Some(funcs);
             *st_classes = Some(classes);
         },
-        FuncDef{ ref mut ident, ref mut params, code, ref mut st_entry, ref mut range, .. } => {
+        FuncDef{ ref mut ident, ref mut params, code, docs, ref mut st_entry, ref mut range, .. } => {
             // Update the block's range
             offset_range!(ident.range, state.offset);
             offset_range!(range, state.offset);
 
             // Prepare the entry
             let mut entry: FunctionEntry = FunctionEntry::from_def(&ident.value, range.clone());
+            entry.docs = docs.clone();
             define_func(state, &mut entry, params, &code.table, errors);
 
             // We can then add the function definition to the given symbol table
@@ -313,9 +326,9 @@ fn pass_stmt(state: &CompileState, package_index: &PackageIndex, data_index: &Da
             }
 
             // Now go and populate the rest of its symbol table in the function body.
-            pass_block(state, package_index, data_index, code, Some(symbol_table.clone()), errors);
+            pass_block(state, package_index, data_index, code, Some(symbol_table.clone()), warnings, errors);
         },
-        ClassDef{ ref mut ident, ref mut props, ref mut methods, ref mut st_entry, symbol_table: c_symbol_table, ref mut range, .. } => {
+        ClassDef{ ref mut ident, ref mut props, ref mut methods, docs, ref mut st_entry, symbol_table: c_symbol_table, ref mut range } => {
             // Update the block's range
             offset_range!(ident.range, state.offset);
             offset_range!(range, state.offset);
@@ -354,7 +367,7 @@ fn pass_stmt(state: &CompileState, package_index: &PackageIndex, data_index: &Da
 
                 // Add definitions for each of its functions
                 for m in methods.iter_mut() {
-                    if let Stmt::FuncDef{ ident: m_ident, params: m_params, code: m_code, st_entry: ref mut m_st_entry, range: ref mut m_range, .. } = &mut **m {
+                    if let Stmt::FuncDef{ ident: m_ident, params: m_params, code: m_code, docs: m_docs, st_entry: ref mut m_st_entry, range: ref mut m_range, .. } = &mut **m {
                         offset_range!(m_range, state.offset);
 
                         // First, check if its name does not overlap with a property (i.e., we want one namespace for a class)
@@ -374,6 +387,7 @@ fn pass_stmt(state: &CompileState, package_index: &PackageIndex, data_index: &Da
 
                         // If it passes those checks, we create an entry for it
                         let mut entry: FunctionEntry = FunctionEntry::from_method(m_ident.value.clone(), &ident.value, m_range.clone());
+                        entry.docs = m_docs.clone();
                         define_func(state, &mut entry, m_params, &m_code.table, errors);
                         m_code.table.borrow_mut().parent = Some(symbol_table.clone());
 
@@ -395,7 +409,9 @@ fn pass_stmt(state: &CompileState, package_index: &PackageIndex, data_index: &Da
             // 2. Create a proper class entry with that table
             {
                 let mut st: RefMut<SymbolTable> = symbol_table.borrow_mut();
-                match st.add_class(ClassEntry::from_def(ClassSignature::new(&ident.value), c_symbol_table.clone(), range.clone())) {
+                let mut entry: ClassEntry = ClassEntry::from_def(ClassSignature::new(&ident.value), c_symbol_table.clone(), range.clone());
+                entry.docs = docs.clone();
+                match st.add_class(entry) {
                     Ok(entry) => { *st_entry = Some(entry); },
                     Err(err)  => {
                         errors.push(Error::ClassDefineError { name: ident.value.clone(), err, range: range.clone() });
@@ -408,7 +424,7 @@ fn pass_stmt(state: &CompileState, package_index: &PackageIndex, data_index: &Da
             for m in methods.iter_mut() {
                 if let Stmt::FuncDef{ code: m_code, .. } = &mut **m {
                     for s in &mut m_code.stmts {
-                        pass_stmt(state, package_index, data_index, s, &m_code.table, errors);
+                        pass_stmt(state, package_index, data_index, s, &m_code.table, warnings, errors);
                     }
                 } else {
                     unreachable!();
@@ -423,7 +439,7 @@ fn pass_stmt(state: &CompileState, package_index: &PackageIndex, data_index: &Da
 
             // Traverse the expression to resolve any references (by the time we reach it, the symbol table should already be sufficiently populated)
             if let Some(expr) = expr {
-                pass_expr(state, data_index, expr, symbol_table, errors);
+                pass_expr(state, data_index, expr, symbol_table, warnings, errors);
             }
         },
 
@@ -432,12 +448,12 @@ fn pass_stmt(state: &CompileState, package_index: &PackageIndex, data_index: &Da
             offset_range!(range, state.offset);
 
             // Recurse into the condition
-            pass_expr(state, data_index, cond, symbol_table, errors);
+            pass_expr(state, data_index, cond, symbol_table, warnings, errors);
 
             // Recurse into the codeblocks
-            pass_block(state, package_index, data_index, consequent, Some(symbol_table.clone()), errors);
+            pass_block(state, package_index, data_index, consequent, Some(symbol_table.clone()), warnings, errors);
             if let Some(alternative) = alternative {
-                pass_block(state, package_index, data_index, alternative, Some(symbol_table.clone()), errors);
+                pass_block(state, package_index, data_index, alternative, Some(symbol_table.clone()), warnings, errors);
             }
         },
         For{ initializer, condition, increment, consequent, ref mut range, .. } => {
@@ -451,13 +467,13 @@ fn pass_stmt(state: &CompileState, package_index: &PackageIndex, data_index: &Da
             }
 
             // Recurse into the three for-parts first
-            pass_stmt(state, package_index, data_index, initializer, &consequent.table, errors);
-            pass_expr(state, data_index, condition, &consequent.table, errors);
-            pass_stmt(state, package_index, data_index, increment, &consequent.table, errors);
+            pass_stmt(state, package_index, data_index, initializer, &consequent.table, warnings, errors);
+            pass_expr(state, data_index, condition, &consequent.table, warnings, errors);
+            pass_stmt(state, package_index, data_index, increment, &consequent.table, warnings, errors);
 
             // Recurse into the block
             for s in consequent.stmts.iter_mut() {
-                pass_stmt(state, package_index, data_index, s, &consequent.table, errors);
+                pass_stmt(state, package_index, data_index, s, &consequent.table, warnings, errors);
             }
         },
         While{ condition, consequent, ref mut range, .. } => {
@@ -465,18 +481,18 @@ fn pass_stmt(state: &CompileState, package_index: &PackageIndex, data_index: &Da
             offset_range!(range, state.offset);
 
             // Recurse into the while-part first
-            pass_expr(state, data_index, condition, symbol_table, errors);
+            pass_expr(state, data_index, condition, symbol_table, warnings, errors);
             // Recurse into the block
-            pass_block(state, package_index, data_index, consequent, Some(symbol_table.clone()), errors);
+            pass_block(state, package_index, data_index, consequent, Some(symbol_table.clone()), warnings, errors);
         },
         On{ location, block, ref mut range, .. } => {
             // Update the block's range
             offset_range!(range, state.offset);
 
             // Recurse into the location first
-            pass_expr(state, data_index, location, symbol_table, errors);
+            pass_expr(state, data_index, location, symbol_table, warnings, errors);
             // Recurse into the block
-            pass_block(state, package_index, data_index, block, Some(symbol_table.clone()), errors);
+            pass_block(state, package_index, data_index, block, Some(symbol_table.clone()), warnings, errors);
         },
         Parallel{ ref mut result, blocks, ref mut merge, ref mut st_entry, ref mut range, .. } => {
             // Update the block's range
@@ -492,7 +508,7 @@ fn pass_stmt(state: &CompileState, package_index: &PackageIndex, data_index: &Da
 
             // Now recurse into the codeblocks to resolve their references too
             for b in blocks {
-                pass_stmt(state, package_index, data_index, b, symbol_table, errors);
+                pass_stmt(state, package_index, data_index, b, symbol_table, warnings, errors);
             }
 
             // If present, declare the result as last
@@ -514,7 +530,7 @@ fn pass_stmt(state: &CompileState, package_index: &PackageIndex, data_index: &Da
             offset_range!(range, state.offset);
 
             // Recursestate,  into the expression to resolve any reference there
-            pass_expr(state, data_index, value, symbol_table, errors);
+            pass_expr(state, data_index, value, symbol_table, warnings, errors);
 
             // Attempt to declare the identifier
             let mut st: RefMut<SymbolTable> = symbol_table.borrow_mut();
@@ -529,7 +545,7 @@ fn pass_stmt(state: &CompileState, package_index: &PackageIndex, data_index: &Da
             offset_range!(range, state.offset);
 
             // Recurse into the expression to resolve any reference there
-            pass_expr(state, data_index, value, symbol_table, errors);
+            pass_expr(state, data_index, value, symbol_table, warnings, errors);
 
             // Attempt to resolve the identifier
             let st: Ref<SymbolTable> = symbol_table.borrow();
@@ -543,7 +559,7 @@ fn pass_stmt(state: &CompileState, package_index: &PackageIndex, data_index: &Da
             offset_range!(range, state.offset);
 
             // Simply recurse
-            pass_expr(state, data_index, expr, symbol_table, errors);
+            pass_expr(state, data_index, expr, symbol_table, warnings, errors);
         },
 
         // We ignore the rest
@@ -569,7 +585,7 @@ fn pass_stmt(state: &CompileState, package_index: &PackageIndex, data_index: &Da
 /// This function may error if there were semantic problems while checking the table for this statement (if any).
 /// 
 /// If an error occurred, then it is appended to the `errors` list and the function returns early.
-fn pass_expr(state: &CompileState, data_index: &DataIndex, expr: &mut Expr, symbol_table: &Rc<RefCell<SymbolTable>>, errors: &mut Vec<Error>) {
+fn pass_expr(state: &CompileState, data_index: &DataIndex, expr: &mut Expr, symbol_table: &Rc<RefCell<SymbolTable>>, warnings: &mut Vec<Warning>, errors: &mut Vec<Error>) {
     // Match on the exact expression
     use Expr::*;
     match expr {
@@ -577,7 +593,7 @@ fn pass_expr(state: &CompileState, data_index: &DataIndex, expr: &mut Expr, symb
             // Update the expr's range
             offset_range!(range, state.offset);
 
-            pass_expr(state, data_index, expr, symbol_table, errors);
+            pass_expr(state, data_index, expr, symbol_table, warnings, errors);
         },
 
         Call{ expr, args, ref mut range, .. } => {
@@ -585,23 +601,41 @@ fn pass_expr(state: &CompileState, data_index: &DataIndex, expr: &mut Expr, symb
             offset_range!(range, state.offset);
 
             // Simply recurse the called expression
-            pass_expr(state, data_index, expr, symbol_table, errors);
+            pass_expr(state, data_index, expr, symbol_table, warnings, errors);
             // If it's an identifier, set its entry to which function it is referring
             if let brane_dsl::ast::Expr::Identifier { name, ref mut st_entry, .. } = &mut **expr {
                 // Search the name
                 let st: Ref<SymbolTable> = symbol_table.borrow();
-                match st.get_func(&name.value) {
-                    Some(entry) => { *st_entry = Some(entry); },
-                    None        => {
-                        errors.push(Error::UndefinedFunction { ident: name.value.clone(), range: name.range.clone() });
-                        return;
+                let entry: Rc<RefCell<FunctionEntry>> = match st.get_func(&name.value) {
+                    Some(entry) => entry,
+                    // Functions may have been renamed in their package, so also try their older names
+                    None => match st.get_func_by_alias(&name.value) {
+                        Some(entry) => {
+                            // Refer to the function by its actual name from now on
+                            let actual: String = entry.borrow().name.clone();
+                            warnings.push(Warning::AliasedFunction{ alias: mem::replace(&mut name.value, actual.clone()), name: actual, range: name.range.clone() });
+                            entry
+                        },
+                        None => {
+                            errors.push(Error::UndefinedFunction { ident: name.value.clone(), range: name.range.clone() });
+                            return;
+                        },
+                    },
+                };
+
+                // Warn the user if the package deprecated the function
+                {
+                    let e: Ref<FunctionEntry> = entry.borrow();
+                    if let Some(deprecation) = &e.deprecated {
+                        warnings.push(Warning::DeprecatedFunction{ name: e.name.clone(), package: e.package_name.clone().unwrap_or_default(), deprecation: deprecation.clone(), range: name.range.clone() });
                     }
                 }
+                *st_entry = Some(entry);
             }
 
             // Then do the arguments
             for a in args {
-                pass_expr(state, data_index, a, symbol_table, errors);
+                pass_expr(state, data_index, a, symbol_table, warnings, errors);
             }
         },
         Array{ values, ref mut range, .. } => {
@@ -610,7 +644,7 @@ fn pass_expr(state: &CompileState, data_index: &DataIndex, expr: &mut Expr, symb
 
             // Simply recurse
             for v in values {
-                pass_expr(state, data_index, v, symbol_table, errors);
+                pass_expr(state, data_index, v, symbol_table, warnings, errors);
             }
         },
         ArrayIndex{ array, index, ref mut range, .. } => {
@@ -618,8 +652,18 @@ fn pass_expr(state: &CompileState, data_index: &DataIndex, expr: &mut Expr, symb
             offset_range!(range, state.offset);
 
             // Simply recurse
-            pass_expr(state, data_index, array, symbol_table, errors);
-            pass_expr(state, data_index, index, symbol_table, errors);
+            pass_expr(state, data_index, array, symbol_table, warnings, errors);
+            pass_expr(state, data_index, index, symbol_table, warnings, errors);
+        },
+        Map{ entries, ref mut range, .. } => {
+            // Update the expr's range
+            offset_range!(range, state.offset);
+
+            // Simply recurse
+            for (k, v) in entries {
+                pass_expr(state, data_index, k, symbol_table, warnings, errors);
+                pass_expr(state, data_index, v, symbol_table, warnings, errors);
+            }
         },
         Pattern{ exprs, ref mut range, .. } => {
             // Update the expr's range
@@ -627,7 +671,7 @@ fn pass_expr(state: &CompileState, data_index: &DataIndex, expr: &mut Expr, symb
 
             // Simply recurse
             for e in exprs {
-                pass_expr(state, data_index, e, symbol_table, errors);
+                pass_expr(state, data_index, e, symbol_table, warnings, errors);
             }
         },
 
@@ -636,15 +680,15 @@ fn pass_expr(state: &CompileState, data_index: &DataIndex, expr: &mut Expr, symb
             offset_range!(range, state.offset);
 
             // Simply recurse
-            pass_expr(state, data_index, expr, symbol_table, errors);
+            pass_expr(state, data_index, expr, symbol_table, warnings, errors);
         },
         BinOp{ lhs, rhs, ref mut range, .. } => {
             // Update the expr's range
             offset_range!(range, state.offset);
 
             // Simply recurse
-            pass_expr(state, data_index, lhs, symbol_table, errors);
-            pass_expr(state, data_index, rhs, symbol_table, errors);
+            pass_expr(state, data_index, lhs, symbol_table, warnings, errors);
+            pass_expr(state, data_index, rhs, symbol_table, warnings, errors);
         },
         Proj{ lhs, rhs, ref mut st_entry, ref mut range, .. } => {
             // Update the expr's range
@@ -654,9 +698,9 @@ fn pass_expr(state: &CompileState, data_index: &DataIndex, expr: &mut Expr, symb
             // The rhs is only Expr::Identifier
 
             // Recurse into the left-hand side first
-            pass_expr(state, data_index, lhs, symbol_table, errors);
+            pass_expr(state, data_index, lhs, symbol_table, warnings, errors);
             // Then the righthand-side (not necessary, but just in case we ever do need recursion for identifiers)
-            pass_expr(state, data_index, rhs, symbol_table, errors);
+            pass_expr(state, data_index, rhs, symbol_table, warnings, errors);
 
             // Get the rhs identifier
             let rhs_ident: &brane_dsl::ast::Identifier = if let Expr::Identifier{ name, .. } = &**rhs {
@@ -749,7 +793,7 @@ fn pass_expr(state: &CompileState, data_index: &DataIndex, expr: &mut Expr, symb
                 }
 
                 // Now traverse
-                pass_expr(state, data_index, &mut p.value, symbol_table, errors);
+                pass_expr(state, data_index, &mut p.value, symbol_table, warnings, errors);
             }
 
             // Finally, check if this dataset exists
@@ -858,7 +902,7 @@ fn pass_literal(state: &CompileState, literal: &mut Literal) {
 /// 
 /// # Errors
 /// TThis pass may throw `AstError::ResolveError`s if the user made mistakes with their variable references.
-pub fn do_traversal(state: &mut CompileState, package_index: &PackageIndex, data_index: &DataIndex, root: Program) -> Result<Program, Vec<AstError>> {
+pub fn do_traversal(state: &mut CompileState, package_index: &PackageIndex, data_index: &DataIndex, root: Program, warnings: &mut Vec<AstWarning>) -> Result<Program, Vec<AstError>> {
     let mut root = root;
 
     // Inject the state into the global symbol table
@@ -868,10 +912,12 @@ pub fn do_traversal(state: &mut CompileState, package_index: &PackageIndex, data
     }
 
     // Iterate over all statements to build their symbol tables (if relevant)
+    let mut warns: Vec<Warning> = vec![];
     let mut errors: Vec<Error> = vec![];
-    pass_block(state, package_index, data_index, &mut root.block, None, &mut errors);
+    pass_block(state, package_index, data_index, &mut root.block, None, &mut warns, &mut errors);
 
     // Done
+    warnings.append(&mut warns.into_iter().map(|w| w.into()).collect::<Vec<AstWarning>>());
     if errors.is_empty() {
         Ok(root)
     } else {
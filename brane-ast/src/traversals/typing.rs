@@ -32,7 +32,7 @@ use crate::warnings::AstWarning;
 #[cfg(test)]
 mod tests {
     use brane_dsl::ParserOptions;
-    use brane_shr::utilities::{create_data_index, create_package_index, test_on_dsl_files};
+    use brane_shr::utilities::{assert_golden, create_data_index, create_package_index, test_on_dsl_files};
     use specifications::data::DataIndex;
     use specifications::package::PackageIndex;
     use super::*;
@@ -77,8 +77,13 @@ mod tests {
             };
 
             // Now print the symbol tables for prettyness
-            symbol_tables::do_traversal(program, std::io::stdout()).unwrap();
+            let mut output: Vec<u8> = vec![];
+            symbol_tables::do_traversal(program, &mut output).unwrap();
+            print!("{}", String::from_utf8_lossy(&output));
             println!("{}\n\n", (0..80).map(|_| '-').collect::<String>());
+
+            // Compare it against the golden output
+            assert_golden("typing", &path, &output);
         });
     }
 }
@@ -472,9 +477,15 @@ fn pass_stmt(stmt: &mut Stmt, symbol_table: &Rc<RefCell<SymbolTable>>, warnings:
             None
         },
 
-        LetAssign{ value, st_entry, .. } => {
-            // Resolve the type of the expression
-            let data_type: DataType = pass_expr(value, symbol_table, errors);
+        LetAssign{ value, param, st_entry, .. } => {
+            // Resolve the type of the expression, which must be the declared one for parameters
+            let data_type: DataType = match param {
+                Some(data_type) => {
+                    *value = force_cast(value.clone(), data_type.clone(), symbol_table, errors);
+                    data_type.clone()
+                },
+                None => pass_expr(value, symbol_table, errors),
+            };
 
             // That's our type too
             let mut entry: RefMut<VarEntry> = st_entry.as_ref().unwrap().borrow_mut();
@@ -640,22 +651,61 @@ fn pass_expr(expr: &mut Expr, symbol_table: &Rc<RefCell<SymbolTable>>, errors: &
             DataType::Array(Box::new(elem_type))
         },
         ArrayIndex{ array, ref mut index, ref mut data_type, .. } => {
-            // Make sure the array evaluates to an Array type and get the inner type (no implicit casting here).
+            // Make sure the array evaluates to an Array (or Map) type and get the inner type (no implicit casting here).
             let arr_type: DataType = pass_expr(array, symbol_table, errors);
-            let elem_type: DataType = if let DataType::Array(t) = arr_type {
-                *t
-            } else {
-                errors.push(Error::NonArrayIndexError{ got: arr_type, range: array.range().clone() });
-                return DataType::Any;
+            let (elem_type, index_type): (DataType, DataType) = match arr_type {
+                DataType::Array(t) => (*t, DataType::Integer),
+                DataType::Map(t)   => (*t, DataType::String),
+                arr_type           => {
+                    errors.push(Error::NonArrayIndexError{ got: arr_type, range: array.range().clone() });
+                    return DataType::Any;
+                },
             };
             *data_type = elem_type.clone();
 
-            // Make sure the index is a number
-            *index = Box::new(force_cast((**index).clone(), DataType::Integer, symbol_table, errors));
+            // Make sure the index is a number (or a string, for maps)
+            *index = Box::new(force_cast((**index).clone(), index_type, symbol_table, errors));
 
             // Return the element type as evaluated type
             elem_type
         },
+        Map { entries, ref mut data_type, .. } => {
+            // Make sure all keys are strings and all values evaluate to the same type
+            let mut elem_type: Option<(DataType, TextRange)> = None;
+            for (k, v) in entries.iter_mut() {
+                *k = Box::new(force_cast((**k).clone(), DataType::String, symbol_table, errors));
+
+                // Evaluate the value
+                let expr_type: DataType = pass_expr(v, symbol_table, errors);
+
+                // Make sure it is the same as used before
+                if let Some((elem_type, range)) = &elem_type {
+                    if !expr_type.coercible_to(elem_type) {
+                        errors.push(Error::InconsistentMapError{ got: expr_type, expected: elem_type.clone(), got_range: v.range().clone(), expected_range: range.clone() });
+                        return DataType::Any;
+                    }
+                    // Insert a cast in the value if necessary
+                    if &expr_type != elem_type {
+                        let range: TextRange = v.range().clone();
+                        *v = Box::new(Expr::new_cast(
+                            v.clone(),
+                            elem_type.clone(),
+
+                            range,
+                        ));
+                    }
+                } else {
+                    elem_type = Some((expr_type, v.range().clone()));
+                }
+            }
+            let elem_type: DataType = elem_type.map(|(d, _)| d).unwrap_or(DataType::Any);
+
+            // Set the type internally
+            *data_type = elem_type.clone();
+
+            // Return the found type (if it's an empty map, it has type any)
+            DataType::Map(Box::new(elem_type))
+        },
         Pattern{ .. } => {
             // Let's for now not worry about this
             todo!();
@@ -29,7 +29,7 @@ use crate::state::CompileState;
 #[cfg(test)]
 mod tests {
     use brane_dsl::ParserOptions;
-    use brane_shr::utilities::{create_data_index, create_package_index, test_on_dsl_files};
+    use brane_shr::utilities::{assert_golden, create_data_index, create_package_index, test_on_dsl_files};
     use specifications::data::DataIndex;
     use specifications::package::PackageIndex;
     use super::*;
@@ -75,8 +75,13 @@ mod tests {
             };
 
             // Now print the file for prettyness
-            ast::do_traversal(workflow, std::io::stdout()).unwrap();
+            let mut output: Vec<u8> = vec![];
+            ast::do_traversal(workflow, &mut output).unwrap();
+            print!("{}", String::from_utf8_lossy(&output));
             println!("{}\n\n", (0..80).map(|_| '-').collect::<String>());
+
+            // Compare it against the golden output
+            assert_golden("workflow_resolve", &path, &output);
         });
     }
 }
@@ -176,7 +181,7 @@ fn pass_edges(edges: EdgeBuffer, target: &mut Vec<Edge>, map: &mut HashMap<EdgeB
 
             use Edge::*;
             match &e.edge {
-                Node { task, locs, input, result, .. } => {
+                Node { task, locs, input, result, retry, timeout, .. } => {
                     // The connection must be linear
                     let next: Option<EdgeBufferNodePtr> = match &e.next {
                         EdgeBufferNodeLink::Linear(next) => Some(next.clone()),
@@ -195,12 +200,14 @@ fn pass_edges(edges: EdgeBuffer, target: &mut Vec<Edge>, map: &mut HashMap<EdgeB
 
                     // The task ID should already be valid, so write that to the new buffer
                     let index: usize = write_edge!(target, Edge::Node{
-                        task   : *task,
-                        locs   : locs.clone(),
-                        at     : None,
-                        input  : input.clone(),
-                        result : result.clone(),
-                        next   : next_idx,
+                        task    : *task,
+                        locs    : locs.clone(),
+                        at      : None,
+                        input   : input.clone(),
+                        result  : result.clone(),
+                        retry   : *retry,
+                        timeout : *timeout,
+                        next    : next_idx,
                     });
                     map.insert(edges_start.clone(), index);
 
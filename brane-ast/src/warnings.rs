@@ -18,6 +18,7 @@ use console::{style, Style};
 
 use brane_dsl::TextRange;
 use brane_dsl::spec::MergeStrategy;
+use specifications::common::Deprecation;
 
 use crate::errors::{n, eprint_range};
 use crate::spec::BuiltinClasses;
@@ -64,6 +65,8 @@ pub trait Warning: Debug + Display {}
 // Defines toplevel warnings that occur in this crate.
 #[derive(Debug)]
 pub enum AstWarning {
+    /// An warning has occurred while resolving symbols.
+    ResolveWarning(ResolveWarning),
     /// An warning has occurred while analysing types.
     TypeWarning(TypeWarning),
     /// An warning has occurred while doing the actual compiling.
@@ -87,10 +90,32 @@ impl AstWarning {
     pub fn prettyprint<S1: AsRef<str>, S2: AsRef<str>>(&self, file: S1, source: S2) {
         use AstWarning::*;
         match self {
+            ResolveWarning(warn) => warn.prettyprint(file, source),
             TypeWarning(warn)    => warn.prettyprint(file, source),
             CompileWarning(warn) => warn.prettyprint(file, source),
         }
     }
+
+    /// Returns the range in the source text that this warning is about, if any.
+    /// 
+    /// # Returns
+    /// A reference to the TextRange, or `None` if the warning does not relate to a specific part of the source.
+    #[inline]
+    pub fn range(&self) -> Option<&TextRange> {
+        use AstWarning::*;
+        match self {
+            ResolveWarning(warn) => warn.range(),
+            TypeWarning(warn)    => warn.range(),
+            CompileWarning(warn) => warn.range(),
+        }
+    }
+}
+
+impl From<ResolveWarning> for AstWarning {
+    #[inline]
+    fn from(warn: ResolveWarning) -> Self {
+        Self::ResolveWarning(warn)
+    }
 }
 
 impl From<TypeWarning> for AstWarning {
@@ -112,6 +137,7 @@ impl Display for AstWarning {
     fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
         use AstWarning::*;
         match self {
+            ResolveWarning(warn) => write!(f, "{}", warn),
             TypeWarning(warn)    => write!(f, "{}", warn),
             CompileWarning(warn) => write!(f, "{}", warn),
         }
@@ -122,6 +148,70 @@ impl Warning for AstWarning {}
 
 
 
+/// Defines warnings that may occur while resolving symbols.
+#[derive(Debug)]
+pub enum ResolveWarning {
+    /// A function was called that its package marks as deprecated.
+    DeprecatedFunction{ name: String, package: String, deprecation: Deprecation, range: TextRange },
+    /// A function was called by one of its aliases (i.e., an older name).
+    AliasedFunction{ alias: String, name: String, range: TextRange },
+}
+
+impl ResolveWarning {
+    /// Prints the warning in a pretty way to stderr.
+    /// 
+    /// # Generic arguments:
+    /// - `S1`: The &str-like type of the `file` path.
+    /// - `S2`: The &str-like type of the `source` text.
+    /// 
+    /// # Arguments
+    /// - `file`: The 'path' of the file (or some other identifier) where the source text originates from.
+    /// - `source`: The source text to read the debug range from.
+    /// 
+    /// # Returns
+    /// Nothing, but does print the warning to stderr.
+    #[inline]
+    pub fn prettyprint<S1: AsRef<str>, S2: AsRef<str>>(&self, file: S1, source: S2) {
+        use ResolveWarning::*;
+        match self {
+            DeprecatedFunction{ range, .. } => prettyprint_warn(file, source, self, range),
+            AliasedFunction{ range, .. }    => prettyprint_warn(file, source, self, range),
+        }
+    }
+
+    /// Returns the range in the source text that this warning is about, if any.
+    /// 
+    /// # Returns
+    /// A reference to the TextRange, or `None` if the warning does not relate to a specific part of the source.
+    #[inline]
+    pub fn range(&self) -> Option<&TextRange> {
+        use ResolveWarning::*;
+        match self {
+            DeprecatedFunction{ range, .. } => Some(range),
+            AliasedFunction{ range, .. }    => Some(range),
+        }
+    }
+}
+
+impl Display for ResolveWarning {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use ResolveWarning::*;
+        match self {
+            DeprecatedFunction{ name, package, deprecation, .. } => write!(f, "Function '{}' in package '{}' is deprecated{}{}",
+                name, package,
+                if let Some(replacement) = &deprecation.replacement { format!("; use '{}' instead", replacement) } else { String::new() },
+                if let Some(message) = &deprecation.message { format!(" ({})", message) } else { String::new() },
+            ),
+            AliasedFunction{ alias, name, .. } => write!(f, "Function '{}' has been renamed to '{}'; calling it by its old name may stop working in a future version of its package", alias, name),
+        }
+    }
+}
+
+impl Warning for ResolveWarning {}
+
+
+
 /// Defines warnings that may occur during compilation.
 #[derive(Debug)]
 pub enum TypeWarning {
@@ -154,6 +244,20 @@ impl TypeWarning {
             ReturningIntermediateResult{ range, .. } => prettyprint_warn(file, source, self, range),
         }
     }
+
+    /// Returns the range in the source text that this warning is about, if any.
+    /// 
+    /// # Returns
+    /// A reference to the TextRange, or `None` if the warning does not relate to a specific part of the source.
+    #[inline]
+    pub fn range(&self) -> Option<&TextRange> {
+        use TypeWarning::*;
+        match self {
+            UnusedMergeStrategy{ range, .. } => Some(range),
+
+            ReturningIntermediateResult{ range, .. } => Some(range),
+        }
+    }
 }
 
 impl Display for TypeWarning {
@@ -199,6 +303,18 @@ impl CompileWarning {
             OnDeprecated{ range, .. } => prettyprint_warn(file, source, self, range),
         }
     }
+
+    /// Returns the range in the source text that this warning is about, if any.
+    /// 
+    /// # Returns
+    /// A reference to the TextRange, or `None` if the warning does not relate to a specific part of the source.
+    #[inline]
+    pub fn range(&self) -> Option<&TextRange> {
+        use CompileWarning::*;
+        match self {
+            OnDeprecated{ range, .. } => Some(range),
+        }
+    }
 }
 
 impl Display for CompileWarning {
@@ -0,0 +1,455 @@
+//  ASM.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 09:29:11
+//  Last edited:
+//    16 Oct 2026, 09:29:11
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements BraneScript's assembly, which is a textual form of a
+//!   compiled Workflow that shows its edges and their instructions one
+//!   per line. It can be disassembled from a Workflow and assembled back
+//!   into one.
+//!
+//!   Every line consists of a mnemonic followed by `key=value` fields,
+//!   where the mnemonic and keys are those of the Workflow's JSON and
+//!   the values are compact JSON. Edges are labelled with their index
+//!   (`3: nod t=0 ...`), and the instructions of a linear edge follow it
+//!   unlabelled. Directives (e.g., `.func`) define the workflow table and
+//!   start the main graph (`.graph`) or function bodies (`.body d=...`).
+//!   Anything after a `;` is a comment.
+// 
+
+use std::collections::HashMap;
+use std::io::Write;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use brane_ast::Workflow;
+use brane_ast::ast::{ClassDef, Edge, EdgeInstr, FunctionDef, SymTable, TaskDef, VarDef};
+use brane_ast::state::TableList;
+
+pub use crate::errors::AsmError as Error;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use brane_ast::{compile_program, CompileResult, ParserOptions};
+    use brane_shr::utilities::{create_data_index, create_package_index, test_on_dsl_files};
+    use specifications::data::DataIndex;
+    use specifications::package::PackageIndex;
+
+    use super::*;
+
+
+    /// Tests that disassembling and then assembling every test file gives back the same workflow.
+    #[test]
+    fn test_roundtrip() {
+        test_on_dsl_files("BraneScript", |path, code| {
+            let pindex: PackageIndex = create_package_index();
+            let dindex: DataIndex    = create_data_index();
+            let workflow: Workflow = match compile_program(code.as_bytes(), &pindex, &dindex, &ParserOptions::bscript()) {
+                CompileResult::Workflow(wf, _) => wf,
+                CompileResult::Eof(err)        => { err.prettyprint(path.to_string_lossy(), &code); panic!("Failed to compile to workflow (see output above)"); },
+                CompileResult::Err(errs)       => { for e in errs { e.prettyprint(path.to_string_lossy(), &code); } panic!("Failed to compile to workflow (see output above)"); },
+                _                              => { unreachable!(); },
+            };
+
+            // Send it through assembly
+            let mut asm: Vec<u8> = vec![];
+            if let Err(err) = disassemble(&workflow, &mut asm) { panic!("Failed to disassemble '{}': {}", path.display(), err); }
+            let asm: String = String::from_utf8(asm).unwrap();
+            let assembled: Workflow = match assemble(&asm) {
+                Ok(assembled) => assembled,
+                Err(err)      => { panic!("Failed to assemble '{}': {}\n\n{}", path.display(), err, asm); },
+            };
+
+            // The table and all edges should have survived (compared as JSON, since they do not implement PartialEq)
+            assert_eq!(serde_json::to_value(&assembled.table).unwrap(), serde_json::to_value(&workflow.table).unwrap(), "Table of '{}' changed", path.display());
+            assert_eq!(serde_json::to_value(&*assembled.graph).unwrap(), serde_json::to_value(&*workflow.graph).unwrap(), "Graph of '{}' changed", path.display());
+            assert_eq!(serde_json::to_value(&*assembled.funcs).unwrap(), serde_json::to_value(&*workflow.funcs).unwrap(), "Function bodies of '{}' changed", path.display());
+        });
+    }
+
+    /// Tests that malformed assembly is rejected with the line it is on.
+    #[test]
+    fn test_assemble_illegal() {
+        assert!(matches!(assemble("0: lin n=1"), Err(Error::EdgeOutsideSection{ line: 1 })));
+        assert!(matches!(assemble(".graph\n    int v=1"), Err(Error::InstrOutsideLinear{ line: 2 })));
+        assert!(matches!(assemble(".graph\n    1: lin n=2"), Err(Error::IllegalEdgeIndex{ line: 2, got: 1, expected: 0 })));
+        assert!(matches!(assemble(".graph\n    0: lin n"), Err(Error::IllegalField{ line: 2, .. })));
+        assert!(matches!(assemble(".graph\n    0: lin n="), Err(Error::MissingValue{ line: 2, .. })));
+        assert!(matches!(assemble(".nope"), Err(Error::UnknownDirective{ line: 1, .. })));
+        assert!(matches!(assemble(".body d=\"x\""), Err(Error::IllegalIndex{ line: 1, .. })));
+        // Comments and empty lines are fine anywhere
+        assert!(assemble("; Nothing to see\n\n.graph  ; here\n    0: stp  ; or here\n").is_ok());
+    }
+}
+
+
+
+
+
+/***** HELPER STRUCTS *****/
+/// Defines a single parsed line of assembly.
+struct Line {
+    /// The label of the line, if any (i.e., the index of the edge).
+    label    : Option<usize>,
+    /// The mnemonic or directive of the line.
+    mnemonic : String,
+    /// The fields given on the line.
+    fields   : Map<String, Value>,
+}
+
+/// Defines the part of the workflow that edges are currently added to.
+enum Section {
+    /// Still in the table.
+    Table,
+    /// The main graph.
+    Graph,
+    /// The body of the function that was last started.
+    Body,
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Formats the given value as a line of assembly.
+/// 
+/// # Arguments
+/// - `mnemonic`: The mnemonic or directive to start the line with. If omitted, uses (and removes) the `kind` of the value instead.
+/// - `value`: The value to write the fields of.
+/// 
+/// # Returns
+/// The line as a string.
+/// 
+/// # Errors
+/// This function errors if we failed to serialize the value.
+fn format_line(mnemonic: Option<&str>, value: &impl Serialize) -> Result<String, Error> {
+    let mut fields: Map<String, Value> = match serde_json::to_value(value) {
+        Ok(Value::Object(fields)) => fields,
+        Ok(_)                     => Map::new(),
+        Err(err)                  => { return Err(Error::SerializeError{ err }); },
+    };
+    // Only replace the kind by the mnemonic if we don't have one already
+    let kind: Option<Value> = if mnemonic.is_none() { fields.remove("kind") } else { None };
+
+    let mut res: String = match (mnemonic, &kind) {
+        (Some(mnemonic), _)               => mnemonic.into(),
+        (None, Some(Value::String(kind))) => kind.clone(),
+        (None, _)                         => "???".into(),
+    };
+    for (key, value) in fields {
+        res.push_str(&format!(" {}={}", key, value));
+    }
+    Ok(res)
+}
+
+/// Writes the given edges as assembly.
+/// 
+/// # Arguments
+/// - `writer`: The Writer to write to.
+/// - `table`: The workflow table, used to annotate the edges with names.
+/// - `edges`: The edges to write.
+/// 
+/// # Errors
+/// This function errors if we failed to serialize an edge or to write to the given writer.
+fn write_edges(writer: &mut impl Write, table: &SymTable, edges: &[Edge]) -> Result<(), Error> {
+    for (i, edge) in edges.iter().enumerate() {
+        // Write the edge itself (without its instructions)
+        let line: String = match edge {
+            Edge::Linear{ next, .. } => format!("lin n={}", next),
+            edge                     => format_line(None, edge)?,
+        };
+        let comment: String = match edge {
+            Edge::Node{ task, .. } if *task >= table.tasks.offset() && *task - table.tasks.offset() < table.tasks.len() => format!("  ; {}", table.tasks[*task].name()),
+            _                                                                                                        => String::new(),
+        };
+        if let Err(err) = writeln!(writer, "    {}: {}{}", i, line, comment) { return Err(Error::WriteError{ err }); }
+
+        // Write the instructions on their own lines
+        if let Edge::Linear{ instrs, .. } = edge {
+            for instr in instrs {
+                let comment: String = match instr {
+                    EdgeInstr::Function{ def } if *def >= table.funcs.offset() && *def - table.funcs.offset() < table.funcs.len() => format!("  ; {}", table.funcs[*def].name),
+                    _                                                                                                               => String::new(),
+                };
+                if let Err(err) = writeln!(writer, "        {}{}", format_line(None, instr)?, comment) { return Err(Error::WriteError{ err }); }
+            }
+        }
+    }
+    Ok(())
+}
+
+
+
+/// Parses a single line of assembly.
+/// 
+/// # Arguments
+/// - `l`: The (one-indexed) number of the line, used for debugging.
+/// - `raw`: The line to parse.
+/// 
+/// # Returns
+/// The parsed Line, or `None` if it is empty or only a comment.
+/// 
+/// # Errors
+/// This function errors if the line is not valid assembly.
+fn parse_line(l: usize, raw: &str) -> Result<Option<Line>, Error> {
+    let mut rest: &str = raw.trim_start();
+    if rest.is_empty() || rest.starts_with(';') { return Ok(None); }
+
+    // Parse the label, if any
+    let mut label: Option<usize> = None;
+    if let Some(colon) = rest.find(':') {
+        if colon > 0 && rest[..colon].chars().all(|c| c.is_ascii_digit()) {
+            label = match rest[..colon].parse() {
+                Ok(label) => Some(label),
+                Err(err)  => { return Err(Error::IllegalLabel{ line: l, raw: rest[..colon].into(), err }); },
+            };
+            rest = rest[colon + 1..].trim_start();
+        }
+    }
+
+    // Parse the mnemonic
+    let end: usize = rest.find(char::is_whitespace).unwrap_or(rest.len());
+    let mnemonic: String = rest[..end].into();
+    rest = &rest[end..];
+
+    // Parse the fields
+    let mut fields: Map<String, Value> = Map::new();
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() || rest.starts_with(';') { break; }
+
+        // Read the key...
+        let eq: usize = match rest.find('=') {
+            Some(eq) if eq > 0 && !rest[..eq].contains(char::is_whitespace) => eq,
+            _                                                                => { return Err(Error::IllegalField{ line: l, raw: rest.into() }); },
+        };
+        let key: String = rest[..eq].into();
+        rest = &rest[eq + 1..];
+
+        // ...and the (JSON) value
+        let mut values = serde_json::Deserializer::from_str(rest).into_iter::<Value>();
+        let value: Value = match values.next() {
+            Some(Ok(value)) => value,
+            Some(Err(err))  => { return Err(Error::IllegalValue{ line: l, key, err }); },
+            None            => { return Err(Error::MissingValue{ line: l, key }); },
+        };
+        rest = &rest[values.byte_offset()..];
+        fields.insert(key, value);
+    }
+
+    Ok(Some(Line{ label, mnemonic, fields }))
+}
+
+/// Parses the fields of a line as the given type.
+/// 
+/// # Arguments
+/// - `l`: The (one-indexed) number of the line, used for debugging.
+/// - `what`: What we are parsing, used for debugging.
+/// - `fields`: The fields to parse.
+/// 
+/// # Returns
+/// The parsed value.
+/// 
+/// # Errors
+/// This function errors if the fields do not describe a valid `T`.
+fn parse_fields<T: DeserializeOwned>(l: usize, what: &'static str, fields: Map<String, Value>) -> Result<T, Error> {
+    match serde_json::from_value(Value::Object(fields)) {
+        Ok(value) => Ok(value),
+        Err(err)  => Err(Error::ParseError{ line: l, what, err }),
+    }
+}
+
+/// Returns a single numeric field of a directive.
+/// 
+/// # Arguments
+/// - `l`: The (one-indexed) number of the line, used for debugging.
+/// - `directive`: The directive that has the field, used for debugging.
+/// - `fields`: The fields of the directive.
+/// - `key`: The name of the field.
+/// 
+/// # Returns
+/// The value of the field, or `0` if it was not given.
+/// 
+/// # Errors
+/// This function errors if the field was given but not a number.
+fn get_index(l: usize, directive: &str, fields: &Map<String, Value>, key: &str) -> Result<usize, Error> {
+    match fields.get(key) {
+        Some(value) => match value.as_u64() {
+            Some(value) => Ok(value as usize),
+            None        => Err(Error::IllegalIndex{ line: l, directive: directive.into(), key: key.into(), raw: value.to_string() }),
+        },
+        None => Ok(0),
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Disassembles the given Workflow, i.e., writes it as assembly.
+/// 
+/// # Arguments
+/// - `workflow`: The Workflow to disassemble.
+/// - `writer`: The Writer to write the assembly to.
+/// 
+/// # Errors
+/// This function errors if we failed to serialize parts of the workflow or to write to the given writer.
+pub fn disassemble(workflow: &Workflow, writer: &mut impl Write) -> Result<(), Error> {
+    let table: &SymTable = &workflow.table;
+
+    // Write the header
+    let mut header: Vec<String> = vec![ "; Workflow assembly (assemble with 'branec --from-asm')".into() ];
+    if !workflow.requires.capabilities.is_empty() {
+        header.push(format!("; Requires: {} (v{})", workflow.requires.capabilities.iter().cloned().collect::<Vec<String>>().join(", "), workflow.requires.version));
+    }
+    header.push(format!(".table funcs={} tasks={} classes={} vars={}", table.funcs.offset(), table.tasks.offset(), table.classes.offset(), table.vars.offset()));
+    for line in header { if let Err(err) = writeln!(writer, "{}", line) { return Err(Error::WriteError{ err }); } }
+
+    // Write the table
+    let mut lines: Vec<String> = Vec::with_capacity(table.funcs.len() + table.tasks.len() + table.classes.len() + table.vars.len() + table.results.len());
+    for f in table.funcs.iter() { lines.push(format_line(Some(".func"), f)?); }
+    for t in table.tasks.iter() { lines.push(format_line(Some(".task"), t)?); }
+    for c in table.classes.iter() { lines.push(format_line(Some(".class"), c)?); }
+    for v in table.vars.iter() { lines.push(format_line(Some(".var"), v)?); }
+    let mut results: Vec<(&String, &String)> = table.results.iter().collect();
+    results.sort();
+    for (name, loc) in results { lines.push(format!(".result n={} l={}", Value::String(name.clone()), Value::String(loc.clone()))); }
+    for line in lines { if let Err(err) = writeln!(writer, "    {}", line) { return Err(Error::WriteError{ err }); } }
+
+    // Write the main graph...
+    if let Err(err) = writeln!(writer, "\n.graph") { return Err(Error::WriteError{ err }); }
+    write_edges(writer, table, &workflow.graph)?;
+
+    // ...and the function bodies
+    let mut funcs: Vec<(&usize, &Vec<Edge>)> = workflow.funcs.iter().collect();
+    funcs.sort_by_key(|(i, _)| **i);
+    for (i, edges) in funcs {
+        let name: &str = if *i >= table.funcs.offset() && *i - table.funcs.offset() < table.funcs.len() { &table.funcs[*i].name } else { "???" };
+        if let Err(err) = writeln!(writer, "\n.body d={}  ; {}", i, name) { return Err(Error::WriteError{ err }); }
+        write_edges(writer, table, edges)?;
+    }
+
+    // Done
+    Ok(())
+}
+
+
+
+/// Assembles the given assembly into a Workflow.
+/// 
+/// # Arguments
+/// - `source`: The assembly to assemble (as written by `disassemble()`).
+/// 
+/// # Returns
+/// A new Workflow with the edges, function bodies and table in the assembly.
+/// 
+/// # Errors
+/// This function errors if the given source is not valid assembly.
+pub fn assemble(source: impl AsRef<str>) -> Result<Workflow, Error> {
+    let source: &str = source.as_ref();
+
+    // Collect everything as JSON first, so instructions can be added to their edges
+    let mut offsets : (usize, usize, usize, usize)                   = (0, 0, 0, 0);
+    let mut funcs   : Vec<FunctionDef>                               = vec![];
+    let mut tasks   : Vec<TaskDef>                                   = vec![];
+    let mut classes : Vec<ClassDef>                                  = vec![];
+    let mut vars    : Vec<VarDef>                                    = vec![];
+    let mut results : HashMap<String, String>                        = HashMap::new();
+    let mut graph   : Vec<(usize, Map<String, Value>)>               = vec![];
+    let mut bodies  : Vec<(usize, Vec<(usize, Map<String, Value>)>)> = vec![];
+    let mut section : Section                                        = Section::Table;
+    for (l, raw) in source.lines().enumerate() {
+        let l: usize = l + 1;
+        let Line{ label, mnemonic, mut fields } = match parse_line(l, raw)? {
+            Some(line) => line,
+            None       => { continue; },
+        };
+
+        match mnemonic.as_str() {
+            ".table" => {
+                offsets = (get_index(l, ".table", &fields, "funcs")?, get_index(l, ".table", &fields, "tasks")?, get_index(l, ".table", &fields, "classes")?, get_index(l, ".table", &fields, "vars")?);
+            },
+            ".func"   => { funcs.push(parse_fields(l, "function definition", fields)?); },
+            ".task"   => { tasks.push(parse_fields(l, "task definition", fields)?); },
+            ".class"  => { classes.push(parse_fields(l, "class definition", fields)?); },
+            ".var"    => { vars.push(parse_fields(l, "variable definition", fields)?); },
+            ".result" => {
+                match (fields.get("n").and_then(Value::as_str), fields.get("l").and_then(Value::as_str)) {
+                    (Some(name), Some(loc)) => { results.insert(name.into(), loc.into()); },
+                    _                       => { return Err(Error::IllegalResult{ line: l }); },
+                }
+            },
+            ".graph" => { section = Section::Graph; },
+            ".body"  => {
+                let def: usize = get_index(l, ".body", &fields, "d")?;
+                bodies.push((def, vec![]));
+                section = Section::Body;
+            },
+            directive if directive.starts_with('.') => { return Err(Error::UnknownDirective{ line: l, raw: directive.into() }); },
+
+            kind => {
+                // Find the edges we're writing to
+                let edges: &mut Vec<(usize, Map<String, Value>)> = match section {
+                    Section::Table   => { return Err(Error::EdgeOutsideSection{ line: l }); },
+                    Section::Graph   => &mut graph,
+                    Section::Body    => &mut bodies.last_mut().unwrap().1,
+                };
+                fields.insert("kind".into(), Value::String(kind.into()));
+
+                match label {
+                    // It's an edge
+                    Some(label) => {
+                        if label != edges.len() { return Err(Error::IllegalEdgeIndex{ line: l, got: label, expected: edges.len() }); }
+                        if kind == "lin" { fields.insert("i".into(), Value::Array(vec![])); }
+                        edges.push((l, fields));
+                    },
+
+                    // It's an instruction of the last edge
+                    None => match edges.last_mut().and_then(|(_, e)| e.get_mut("i")).and_then(Value::as_array_mut) {
+                        Some(instrs) => {
+                            // Parse it already to get the line number right
+                            let instr: EdgeInstr = parse_fields(l, "instruction", fields)?;
+                            match serde_json::to_value(instr) {
+                                Ok(instr) => { instrs.push(instr); },
+                                Err(err)  => { return Err(Error::SerializeError{ err }); },
+                            }
+                        },
+                        None => { return Err(Error::InstrOutsideLinear{ line: l }); },
+                    },
+                }
+            },
+        }
+    }
+
+    // Now parse the edges
+    let parse_edges = |edges: Vec<(usize, Map<String, Value>)>| -> Result<Vec<Edge>, Error> {
+        edges.into_iter().map(|(l, fields)| parse_fields(l, "edge", fields)).collect()
+    };
+    let graph: Vec<Edge> = parse_edges(graph)?;
+    let mut funcs_edges: HashMap<usize, Vec<Edge>> = HashMap::with_capacity(bodies.len());
+    for (def, edges) in bodies {
+        funcs_edges.insert(def, parse_edges(edges)?);
+    }
+
+    // Done
+    let table: SymTable = SymTable::with(
+        TableList{ data: funcs, offset: offsets.0 },
+        TableList{ data: tasks, offset: offsets.1 },
+        TableList{ data: classes, offset: offsets.2 },
+        TableList{ data: vars, offset: offsets.3 },
+        results,
+    );
+    Ok(Workflow::new(table, graph, funcs_edges))
+}
@@ -39,6 +39,10 @@ pub enum CompileError {
     OutputCreateError{ path: PathBuf, err: std::io::Error },
     /// Failed to write to the given output file.
     OutputWriteError{ name: String, err: std::io::Error },
+    /// Failed to disassemble the compiled workflow.
+    DisassembleError{ err: AsmError },
+    /// Failed to assemble the given input.
+    AssembleError{ name: String, err: AsmError },
 
     /// Compilation itself failed.
     CompileError{ errs: Vec<brane_ast::Error> },
@@ -57,6 +61,8 @@ impl Display for CompileError {
             WorkflowSerializeError{ err }            => write!(f, "Failed to serialize the compiled workflow: {}", err),
             OutputCreateError{ path, err }           => write!(f, "Failed to create output file '{}': {}", path.display(), err),
             OutputWriteError{ name, err }            => write!(f, "Failed to write to output '{}': {}", name, err),
+            DisassembleError{ err }                  => write!(f, "Failed to disassemble the compiled workflow: {}", err),
+            AssembleError{ name, err }               => write!(f, "Failed to assemble input '{}': {}", name, err),
 
             CompileError{ .. } => write!(f, "Failed to compile given workflow (see output above)"),
         }
@@ -67,6 +73,104 @@ impl Error for CompileError {}
 
 
 
+/// Collects errors that relate to analyzing execution traces.
+#[derive(Debug)]
+pub enum TraceError {
+    /// Failed to read the trace itself.
+    TraceReadError{ err: brane_exe::trace::Error },
+    /// Failed to read the workflow that was traced.
+    WorkflowReadError{ path: PathBuf, err: std::io::Error },
+    /// Failed to parse the workflow that was traced.
+    WorkflowParseError{ path: PathBuf, err: serde_json::Error },
+}
+
+impl Display for TraceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use self::TraceError::*;
+        match self {
+            TraceReadError{ err }           => write!(f, "Failed to read trace: {}", err),
+            WorkflowReadError{ path, err }  => write!(f, "Failed to read workflow file '{}': {}", path.display(), err),
+            WorkflowParseError{ path, err } => write!(f, "Failed to parse workflow file '{}' as a compiled workflow: {}", path.display(), err),
+        }
+    }
+}
+
+impl Error for TraceError {}
+
+
+
+/// Collects errors that relate to (dis)assembling workflows.
+#[derive(Debug)]
+pub enum AsmError {
+    /// Failed to write the assembly to the given writer.
+    WriteError{ err: std::io::Error },
+    /// Failed to serialize a part of the workflow.
+    SerializeError{ err: serde_json::Error },
+
+    /// A field was not given as `key=value`.
+    IllegalField{ line: usize, raw: String },
+    /// A field was given without a value.
+    MissingValue{ line: usize, key: String },
+    /// The value of a field was not valid JSON.
+    IllegalValue{ line: usize, key: String, err: serde_json::Error },
+    /// A label was not a valid index.
+    IllegalLabel{ line: usize, raw: String, err: std::num::ParseIntError },
+    /// An index in a directive was not a number.
+    IllegalIndex{ line: usize, directive: String, key: String, raw: String },
+    /// A `.result` directive did not have string fields `n` and `l`.
+    IllegalResult{ line: usize },
+    /// The given directive is not known.
+    UnknownDirective{ line: usize, raw: String },
+    /// The fields on a line did not describe the thing they should.
+    ParseError{ line: usize, what: &'static str, err: serde_json::Error },
+    /// An edge was given before any `.graph` or `.body` directive.
+    EdgeOutsideSection{ line: usize },
+    /// An edge was labelled with the wrong index.
+    IllegalEdgeIndex{ line: usize, got: usize, expected: usize },
+    /// An (unlabelled) instruction did not follow a linear edge.
+    InstrOutsideLinear{ line: usize },
+}
+
+impl Display for AsmError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use self::AsmError::*;
+        match self {
+            WriteError{ err }     => write!(f, "Failed to write assembly: {}", err),
+            SerializeError{ err } => write!(f, "Failed to serialize workflow: {}", err),
+
+            IllegalField{ line, raw }                 => write!(f, "Line {}: Expected a field as 'key=value', got '{}'", line, raw),
+            MissingValue{ line, key }                 => write!(f, "Line {}: Missing value for field '{}'", line, key),
+            IllegalValue{ line, key, err }            => write!(f, "Line {}: Failed to parse value of field '{}' as JSON: {}", line, key, err),
+            IllegalLabel{ line, raw, err }            => write!(f, "Line {}: Failed to parse label '{}' as an edge index: {}", line, raw, err),
+            IllegalIndex{ line, directive, key, raw } => write!(f, "Line {}: Expected a number for field '{}' of directive '{}', got '{}'", line, key, directive, raw),
+            IllegalResult{ line }                     => write!(f, "Line {}: Expected string fields 'n' (name) and 'l' (location) for directive '.result'", line),
+            UnknownDirective{ line, raw }             => write!(f, "Line {}: Unknown directive '{}'", line, raw),
+            ParseError{ line, what, err }             => write!(f, "Line {}: Failed to parse {}: {}", line, what, err),
+            EdgeOutsideSection{ line }                => write!(f, "Line {}: Encountered an edge before any '.graph' or '.body' directive", line),
+            IllegalEdgeIndex{ line, got, expected }   => write!(f, "Line {}: Edge is labelled {}, but it is edge {}", line, got, expected),
+            InstrOutsideLinear{ line }                => write!(f, "Line {}: Encountered an instruction that does not follow a linear edge ('lin')", line),
+        }
+    }
+}
+
+impl Error for AsmError {}
+
+
+
+/// Defines errors that occur when attempting to parse an EmitKind.
+#[derive(Debug)]
+pub struct EmitKindParseError{ pub raw: String }
+
+impl Display for EmitKindParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        write!(f, "Unknown output kind '{}' (options are 'json' or 'asm')", self.raw)
+    }
+}
+
+impl Error for EmitKindParseError {}
+
+
+
 /// Defines errors that occur when attempting to parse an IndexLocationParseError.
 #[derive(Debug)]
 pub struct IndexLocationParseError;
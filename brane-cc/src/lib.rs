@@ -13,10 +13,13 @@
 //!   BraneScript / Bakery compiler.
 //!   
 //!   Specifically, it features options to compile certain source files to
-//!   usable JSON, and it (will eventually) host options to parse & generate
-//!   BraneScript's assembly (showing the instructions and junk).
+//!   usable JSON, and it hosts options to parse & generate BraneScript's
+//!   assembly (showing the instructions and junk).
 // 
 
 // Declare modules
 pub mod errors;
+pub mod asm;
 pub mod spec;
+pub mod trace;
+pub mod report;
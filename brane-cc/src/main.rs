@@ -13,11 +13,12 @@
 // 
 
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufRead, BufReader, Cursor, Stdin, Write};
 use std::path::PathBuf;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use dotenvy::dotenv;
 use expanduser::expanduser;
 use human_panic::setup_panic;
@@ -26,22 +27,30 @@ use log::{debug, info, error, LevelFilter};
 use brane_ast::{compile_snippet, CompileResult, ParserOptions, Workflow};
 use brane_ast::state::CompileState;
 use brane_ast::traversals::print::ast;
-use brane_dsl::Language;
+use brane_dsl::{Edition, Language, Vocabulary};
+use brane_tsk::api::ApiClient;
+use brane_tsk::tls::ClientTls;
 use specifications::data::DataIndex;
 use specifications::package::PackageIndex;
 
+use brane_cc::asm;
 use brane_cc::errors::CompileError;
-use brane_cc::spec::IndexLocation;
+use brane_cc::report;
+use brane_cc::spec::{EmitKind, IndexLocation};
+use brane_cc::trace;
 
 
 /***** ARGUMENTS *****/
 /// The arguments for the `branec` binary.
 #[derive(Parser)]
-#[clap(name = "branec", author, about = "An offline compiler for BraneScript/Bakery to Workflows.")]
+#[clap(name = "branec", author, about = "An offline compiler for BraneScript/Bakery to Workflows.", args_conflicts_with_subcommands = true)]
 struct Arguments {
     /// If given, shows debug prints.
-    #[clap(long, help="If given, shows additional prints in the log.", env = "DEBUG")]
+    #[clap(long, global = true, help="If given, shows additional prints in the log.", env = "DEBUG")]
     debug : bool,
+    /// If given, does something else than compiling.
+    #[clap(subcommand)]
+    subcommand : Option<BranecSubcommand>,
 
     /// The file(s) to compile. May be '-' to compile from stdin.
     #[clap(name = "FILES", help="The input files to compile. Use '-' to read from stdin.")]
@@ -55,6 +64,9 @@ struct Arguments {
     /// The path / address of the data index.
     #[clap(short, long, default_value="~/.local/share/brane/data", help="The location to read the data index from. If it's a path, reads it from the local machine; if it's an address, attempts to read it from the Brane instance instead. You can wrap your input in 'Local<...>' or 'Remote<...>' to disambiguate between the two.")]
     data     : IndexLocation,
+    /// The directory with the certificates for remote indices.
+    #[clap(long, help="The directory with the certificates with which to fetch remote indices from 'https://' addresses. It may contain a 'ca.pem' with the CA that signed the instance's certificate and a 'client-id.pem' with the certificate and key to identify ourselves with.")]
+    certs_dir : Option<PathBuf>,
 
     /// If given, does the stream thing
     #[clap(short, long, help="If given, enters so-called _streaming mode_. This effectively emulates a REPL, where files may be given on stdin indefinitely (separated by EOF, Ctrl+D). Each file is compiled as soon as it is completely received, and the workflow for that file is written to the output file. Workflows can use definitions made in pervious workflows, just like a REPL.")]
@@ -62,12 +74,57 @@ struct Arguments {
     /// Determines the input language of the source.
     #[clap(short, long, default_value="bscript", help="Determines the language of the input files.")]
     language : Language,
+    /// Determines the edition of the input language.
+    #[clap(long, default_value="2022", help="Determines the edition of the language of the input files, which decides the words that are reserved as keywords. Can be '2022', '2026' or 'latest'. The default keeps existing files that use newer keywords as identifiers compiling; use 'latest' for new files.")]
+    edition  : Edition,
+    /// The vocabulary with which Bakery sentences are read.
+    #[clap(long = "vocabulary", help="If given, the YAML file with the vocabulary with which Bakery sentences are read (i.e., the verbs that map to functions and the filler words to ignore). Use this to write sentences with domain-specific or non-English verbs. Defaults to a built-in English vocabulary.")]
+    vocab    : Option<PathBuf>,
+    /// The values of the parameters declared by the workflow.
+    #[clap(long = "param", value_names = &["name=value"], conflicts_with = "stream", help="Gives a value to a parameter declared by the workflow (i.e., 'param <name>: <type>;'). Datasets are given as 'Data<\"<name>\">' or just their name. May be repeated; parameters with a default value may be omitted. The parameters and their defaults are embedded in the compiled workflow.")]
+    params   : Vec<String>,
     /// If given, writes the output JSON to use as little whitespace as possible.
     #[clap(short, long, help="If given, writes the output JSON in minimized format (i.e., with as little whitespace as possible). Not really readable, but perfect for transmitting it to some other program.")]
     compact  : bool,
     /// If given, does not output JSON but instead outputs an assembly-like variant of a workflow.
     #[clap(short='P', long, help="If given, does not output JSON but instead outputs an assembly-like variant of a workflow. Not really readable by machines, but easier to understand by a human (giving this ignores --compact).")]
     pretty   : bool,
+    /// Determines the form in which to write the compiled workflow.
+    #[clap(short, long, default_value="json", help="Determines the form of the output. Can be 'json' for the workflow as it is sent to a driver, or 'asm' for BraneScript assembly (i.e., the workflow's edges and instructions as text, which can be turned back into JSON using '--from-asm'). Ignored if '--pretty' is given.")]
+    emit     : EmitKind,
+    /// If given, reads assembly instead of source code.
+    #[clap(long, conflicts_with = "stream", help="If given, the input files are treated as BraneScript assembly (as written by '--emit asm') instead of source code. They are assembled back into a workflow, which is written in the form given by '--emit' (i.e., JSON by default). Useful for testing hand-written workflows.")]
+    from_asm : bool,
+    /// If given, reports the cost and complexity of the workflow instead of writing it.
+    #[clap(long, conflicts_with = "from_asm", help="If given, does not output the compiled workflow but a static report of its cost and complexity instead: the number of tasks, the maximum parallel width, the loop nesting depth, the referenced datasets with their sizes and an estimate of the minimum number of transfers between domains.")]
+    report   : bool,
+}
+
+
+
+/// Defines the subcommands of the `branec` binary that do something else than compiling.
+#[derive(Subcommand)]
+enum BranecSubcommand {
+    #[clap(name = "trace", about = "Inspects execution traces as written by 'brane run --trace'.")]
+    Trace {
+        // We subcommand further
+        #[clap(subcommand)]
+        subcommand : TraceSubcommand,
+    },
+}
+
+/// Defines the subcommands for the `branec trace` subcommand.
+#[derive(Subcommand)]
+enum TraceSubcommand {
+    #[clap(name = "analyze", about = "Reports the branch coverage and critical path of a traced run.")]
+    Analyze {
+        /// The trace to analyze.
+        #[clap(name = "TRACE", help = "The JSONL file with the trace to analyze.")]
+        trace    : PathBuf,
+        /// The workflow that was traced.
+        #[clap(short, long, help = "If given, the compiled workflow (as JSON, as written by 'branec') that was traced. Coverage is then computed relative to all of its edges instead of only the visited ones.")]
+        workflow : Option<PathBuf>,
+    },
 }
 
 
@@ -114,20 +171,72 @@ fn read_input(name: impl Into<String>, input: &mut impl BufRead) -> Result<Strin
 
 
 
+/// Writes a workflow to the given output in the desired form.
+/// 
+/// # Arguments
+/// - `workflow`: The Workflow to write.
+/// - `oname`: Some name useful for the user to identify where is being written to.
+/// - `output`: The Writer to write the output to.
+/// - `pretty`: If given, does not serialize to JSON but with `brane_ast::traversals::print::ast`.
+/// - `emit`: Whether to write the workflow as JSON or as assembly. Ignored if `pretty` is given.
+/// - `compact`: If given, serializes with as little whitespace as possible. Only relevant for JSON.
+/// 
+/// # Errors
+/// This function errors if we failed to serialize the workflow or to write to the given output.
+fn write_workflow(workflow: Workflow, oname: &str, output: &mut impl Write, pretty: bool, emit: EmitKind, compact: bool) -> Result<(), CompileError> {
+    // Serialize the output
+    let sworkflow: String = if pretty {
+        let mut res: Vec<u8> = vec![];
+        ast::do_traversal(workflow, &mut res).unwrap();
+        String::from_utf8_lossy(&res).to_string()
+    } else if emit == EmitKind::Asm {
+        let mut res: Vec<u8> = vec![];
+        if let Err(err) = asm::disassemble(&workflow, &mut res) { return Err(CompileError::DisassembleError{ err }); }
+        String::from_utf8_lossy(&res).to_string()
+    } else if !compact {
+        match serde_json::to_string_pretty(&workflow) {
+            Ok(sworkflow) => sworkflow,
+            Err(err)      => { return Err(CompileError::WorkflowSerializeError{ err }); },
+        }
+    } else {
+        match serde_json::to_string(&workflow) {
+            Ok(sworkflow) => sworkflow,
+            Err(err)      => { return Err(CompileError::WorkflowSerializeError{ err }); },
+        }
+    };
+
+    // Write it
+    debug!("Writing to '{}'...", oname);
+    if let Err(err) = writeln!(output, "{}", sworkflow) {
+        return Err(CompileError::OutputWriteError { name: oname.into(), err });
+    }
+    if let Err(err) = writeln!(output, "---END---") {
+        return Err(CompileError::OutputWriteError { name: oname.into(), err });
+    }
+
+    // Done
+    Ok(())
+}
+
+
+
 /// Compiles a snippet of BraneScript statefully.
 /// 
 /// # Arguments
 /// - `state`: The CompileState that allows us to make repeated calls that are sensible.
-/// - `lang`: The language to compile.
+/// - `options`: The ParserOptions that determine the language (and its edition) to compile.
 /// - `iname`: Some name useful for the reader to identify where is being read from.
 /// - `input`: The reader to read the snippet from.
 /// - `source`: The (automatically updated) total source, used for debugging.
 /// - `oname`: Some name useful for the user to identify where is being written to.
 /// - `output`: The Writer to write the output to.
 /// - `pretty`: If given, does not serialize to JSON but with `brane_ast::traversals::print::ast`.
+/// - `emit`: Whether to write the workflow as JSON or as assembly. Ignored if `pretty` is given.
 /// - `compact`: If given, serializes with as little whitespace as possible. Decreases the resulting size greatly, but also readability.
+/// - `report`: If given, writes a cost and complexity report of the workflow instead of the workflow itself.
 /// - `packages_loc`: Where to get the package index from. Implemented as an IndexLocation so it may be both local or remote.
 /// - `data_loc`: Where to get the data index from. Implemented as an IndexLocation so it may be both local or remote.
+/// - `tls`: How to connect to the instance if any of the indices is remote.
 /// 
 /// # Returns
 /// Nothing directly, but does write the result to `output` and appends the input snippet to `source`.
@@ -135,7 +244,7 @@ fn read_input(name: impl Into<String>, input: &mut impl BufRead) -> Result<Strin
 /// # Errors
 /// This function errors if the input is not valid BraneScript or an IO error occurred trying to read from / write to the input / output.
 #[allow(clippy::too_many_arguments)]
-pub async fn compile_iter(state: &mut CompileState, source: &mut String, lang: Language, iname: impl AsRef<str>, input: &mut impl BufRead, oname: impl AsRef<str>, output: &mut impl Write, pretty: bool, compact: bool, packages_loc: &IndexLocation, data_loc: &IndexLocation) -> Result<(), CompileError> {
+pub async fn compile_iter(state: &mut CompileState, source: &mut String, options: &ParserOptions, iname: impl AsRef<str>, input: &mut impl BufRead, oname: impl AsRef<str>, output: &mut impl Write, pretty: bool, emit: EmitKind, compact: bool, report: bool, packages_loc: &IndexLocation, data_loc: &IndexLocation, tls: &ClientTls) -> Result<(), CompileError> {
     let iname : &str = iname.as_ref();
     let oname : &str = oname.as_ref();
 
@@ -147,7 +256,7 @@ pub async fn compile_iter(state: &mut CompileState, source: &mut String, lang: L
     let pindex: PackageIndex = match packages_loc {
         IndexLocation::Remote(remote) => {
             debug!("Fetching remote package index from '{}'...", remote);
-            match brane_tsk::api::get_package_index(remote).await {
+            match brane_tsk::api::get_package_index_with(tls.api(), remote).await {
                 Ok(pindex) => pindex,
                 Err(err)   => { return Err(CompileError::RemotePackageIndexError { endpoint: remote.clone(), err }); },
             }
@@ -169,7 +278,9 @@ pub async fn compile_iter(state: &mut CompileState, source: &mut String, lang: L
     let dindex: DataIndex = match data_loc {
         IndexLocation::Remote(remote) => {
             debug!("Fetching remote data index from '{}'...", remote);
-            match brane_tsk::api::get_data_index(remote).await {
+            // Accept both the address of the API and (as before) that of its `/data/info` path
+            let api: ApiClient = ApiClient::with_client(tls.api().clone(), remote.strip_suffix("/data/info").unwrap_or(remote));
+            match api.data_index().await {
                 Ok(pindex) => pindex,
                 Err(err)   => { return Err(CompileError::RemoteDataIndexError { endpoint: remote.clone(), err }); },
             }
@@ -192,7 +303,7 @@ pub async fn compile_iter(state: &mut CompileState, source: &mut String, lang: L
     // Compile it
     debug!("Compiling workflow...");
     source.push_str(&raw);
-    let workflow: Workflow = match compile_snippet(state, raw.as_bytes(), &pindex, &dindex, &ParserOptions::new(lang)) {
+    let workflow: Workflow = match compile_snippet(state, raw.as_bytes(), &pindex, &dindex, options) {
         CompileResult::Workflow(workflow, warns) => {
             // Print any warnings (on stderr)
             for warn in warns {
@@ -225,30 +336,11 @@ pub async fn compile_iter(state: &mut CompileState, source: &mut String, lang: L
     };
     state.offset += raw.chars().filter(|c| *c == '\n').count();
 
-    // Serialize the output
-    let sworkflow: String = if pretty {
-        let mut res: Vec<u8> = vec![];
-        ast::do_traversal(workflow, &mut res).unwrap();
-        String::from_utf8_lossy(&res).to_string()
-    } else if !compact {
-        match serde_json::to_string_pretty(&workflow) {
-            Ok(sworkflow) => sworkflow,
-            Err(err)      => { return Err(CompileError::WorkflowSerializeError{ err }); },
-        }
+    // Write it (or what we think of it)
+    if report {
+        report::write(&workflow, &dindex, oname, output)?;
     } else {
-        match serde_json::to_string(&workflow) {
-            Ok(sworkflow) => sworkflow,
-            Err(err)      => { return Err(CompileError::WorkflowSerializeError{ err }); },
-        }
-    };
-
-    // Write it and update the source
-    debug!("Writing to '{}'...", oname);
-    if let Err(err) = writeln!(output, "{}", sworkflow) {
-        return Err(CompileError::OutputWriteError { name: oname.into(), err });
-    }
-    if let Err(err) = writeln!(output, "---END---") {
-        return Err(CompileError::OutputWriteError { name: oname.into(), err });
+        write_workflow(workflow, oname, output, pretty, emit, compact)?;
     }
 
     // Done
@@ -285,7 +377,40 @@ async fn main() {
         });
     }
     info!("Initializing branec v{}", env!("CARGO_PKG_VERSION"));
+
+    // Run any of the non-compiling subcommands first
+    if let Some(subcommand) = args.subcommand {
+        match subcommand {
+            BranecSubcommand::Trace{ subcommand: TraceSubcommand::Analyze{ trace, workflow } } => {
+                if let Err(err) = trace::analyze(trace, workflow) { error!("{}", err); std::process::exit(1); }
+            },
+        }
+        return;
+    }
     if args.files.is_empty() { args.files = vec![ "-".into() ]; }
+    let mut options: ParserOptions = ParserOptions::new(args.language).with_edition(args.edition);
+    if let Some(path) = args.vocab {
+        debug!("Loading vocabulary '{}'...", path.display());
+        match Vocabulary::from_path(path) {
+            Ok(vocabulary) => { options = options.with_vocabulary(vocabulary); },
+            Err(err)       => { error!("{}", err); std::process::exit(1); },
+        }
+    }
+    let mut params: HashMap<String, String> = HashMap::with_capacity(args.params.len());
+    for raw in &args.params {
+        match raw.split_once('=') {
+            Some((name, value)) if !name.trim().is_empty() => { params.insert(name.trim().into(), value.into()); },
+            _                                              => { error!("Illegal workflow parameter '{}'; expected it to be '<name>=<value>'", raw); std::process::exit(1); },
+        }
+    }
+    options = options.with_params(params);
+    let tls: ClientTls = match &args.certs_dir {
+        Some(certs_dir) => match ClientTls::from_dir(certs_dir) {
+            Ok(tls)  => tls,
+            Err(err) => { error!("{}", err); std::process::exit(1); },
+        },
+        None => ClientTls::default(),
+    };
 
 
 
@@ -322,9 +447,24 @@ async fn main() {
             ("<stdout>".into(), Box::new(std::io::stdout()))
         };
 
+        // If it's assembly, assemble it instead
+        if args.from_asm {
+            debug!("Assembling...");
+            let iname: &str = if args.files.len() == 1 { &args.files[0] } else { "<sources>" };
+            let workflow: Workflow = match asm::assemble(&source) {
+                Ok(workflow) => workflow,
+                Err(err)     => { error!("{}", CompileError::AssembleError{ name: iname.into(), err }); std::process::exit(1); },
+            };
+            if let Err(err) = write_workflow(workflow, &oname, &mut ohandle, args.pretty, args.emit, args.compact) {
+                error!("{}", err);
+                std::process::exit(1);
+            }
+            return;
+        }
+
         // Compile the entire source now
         debug!("Compiling...");
-        if let Err(err) = compile_iter(&mut CompileState::new(), &mut String::new(), args.language, if args.files.len() == 1 { &args.files[0] } else { "<sources>" }, &mut Cursor::new(source), &oname, &mut ohandle, args.pretty, args.compact, &args.packages, &args.data).await {
+        if let Err(err) = compile_iter(&mut CompileState::new(), &mut String::new(), &options, if args.files.len() == 1 { &args.files[0] } else { "<sources>" }, &mut Cursor::new(source), &oname, &mut ohandle, args.pretty, args.emit, args.compact, args.report, &args.packages, &args.data, &tls).await {
             error!("{}", err);
             std::process::exit(1);
         }
@@ -349,7 +489,7 @@ async fn main() {
         let mut source : String       = String::new();
         loop {
             // Compile that immediately
-            if let Err(err) = compile_iter(&mut state, &mut source, args.language, "<stdin>", &mut ihandle, &oname, &mut ohandle, args.pretty, args.compact, &args.packages, &args.data).await {
+            if let Err(err) = compile_iter(&mut state, &mut source, &options, "<stdin>", &mut ihandle, &oname, &mut ohandle, args.pretty, args.emit, args.compact, args.report, &args.packages, &args.data, &tls).await {
                 error!("{}", err);
                 std::process::exit(1);
             }
@@ -0,0 +1,99 @@
+//  REPORT.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 12:29:25
+//  Last edited:
+//    16 Oct 2026, 12:29:25
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Writes the static cost and complexity report of a compiled workflow
+//!   (as given by `branec --report`) for the user.
+// 
+
+use std::io::Write;
+
+use log::debug;
+
+use brane_ast::Workflow;
+use brane_ast::report::{analyze, WorkflowReport};
+use specifications::data::DataIndex;
+
+use crate::errors::CompileError;
+
+
+/***** HELPER FUNCTIONS *****/
+/// Formats the given number of bytes for the user.
+///
+/// # Arguments
+/// - `bytes`: The size to format.
+///
+/// # Returns
+/// A string with the size in the most appropriate unit.
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = [ "KiB", "MiB", "GiB", "TiB", "PiB" ];
+    if bytes < 1024 { return format!("{}B", bytes); }
+    let mut size: f64 = bytes as f64 / 1024.0;
+    let mut unit: usize = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.2}{}", size, UNITS[unit])
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Analyzes the given workflow and writes its cost and complexity report to the given output.
+///
+/// # Arguments
+/// - `workflow`: The Workflow to report on.
+/// - `dindex`: The DataIndex that tells us the size and locations of the referenced datasets.
+/// - `oname`: Some name useful for the user to identify where is being written to.
+/// - `output`: The Writer to write the report to.
+///
+/// # Errors
+/// This function errors if we failed to write to the given output.
+pub fn write(workflow: &Workflow, dindex: &DataIndex, oname: &str, output: &mut impl Write) -> Result<(), CompileError> {
+    debug!("Analyzing workflow...");
+    let report: WorkflowReport = analyze(workflow, dindex);
+
+    // Write the counts
+    debug!("Writing report to '{}'...", oname);
+    let mut lines: Vec<String> = vec![
+        format!("Tasks              : {}", report.tasks),
+        format!("Max parallel width : {}", report.width),
+        format!("Loop nesting depth : {}", report.depth),
+        format!("Min. transfers     : {}", report.transfers),
+    ];
+
+    // Write the datasets
+    if report.datasets.is_empty() {
+        lines.push("Datasets           : none".into());
+    } else {
+        let total: u64 = report.datasets.iter().filter_map(|dataset| dataset.size).sum();
+        let unknown: usize = report.datasets.iter().filter(|dataset| dataset.size.is_none()).count();
+        lines.push(format!("Datasets           : {} ({}{})", report.datasets.len(), format_size(total), if unknown > 0 { format!(", {} of unknown size", unknown) } else { String::new() }));
+        for dataset in &report.datasets {
+            lines.push(format!("  {:<24} {:>10}  {}",
+                dataset.name,
+                dataset.size.map(format_size).unwrap_or_else(|| "?".into()),
+                if dataset.locations.is_empty() { "<unknown dataset>".into() } else { dataset.locations.join(", ") },
+            ));
+        }
+    }
+    lines.push("---END---".into());
+    for line in lines {
+        if let Err(err) = writeln!(output, "{}", line) {
+            return Err(CompileError::OutputWriteError{ name: oname.into(), err });
+        }
+    }
+
+    // Done
+    Ok(())
+}
@@ -19,7 +19,7 @@ use std::str::FromStr;
 use enum_debug::EnumDebug;
 use url::Url;
 
-use crate::errors::IndexLocationParseError;
+use crate::errors::{EmitKindParseError, IndexLocationParseError};
 
 
 /***** CONSTANTS *****/
@@ -38,6 +38,39 @@ pub const REMOTE_POSTFIX : &str = ">";
 
 
 /***** LIBRARY *****/
+/// Defines the forms in which `branec` can write a compiled workflow.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum EmitKind {
+    /// Writes the workflow as JSON, i.e., as it is sent to a driver.
+    Json,
+    /// Writes the workflow as BraneScript assembly (see `crate::asm`).
+    Asm,
+}
+
+impl Display for EmitKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use EmitKind::*;
+        match self {
+            Json => write!(f, "json"),
+            Asm  => write!(f, "asm"),
+        }
+    }
+}
+
+impl FromStr for EmitKind {
+    type Err = EmitKindParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "asm"  => Ok(Self::Asm),
+            _      => Err(EmitKindParseError{ raw: s.into() }),
+        }
+    }
+}
+
+
+
 /// Defins a formatter for the IndexLocation that writes it in a `IndexLocation::FromStr`-compatible way.
 #[derive(Debug)]
 pub struct IndexLocationSerializer<'a> {
@@ -0,0 +1,186 @@
+//  TRACE.rs
+//    by agent
+//
+//  Created:
+//    16 Oct 2026, 08:42:41
+//  Last edited:
+//    16 Oct 2026, 08:42:41
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements the `trace` subcommand(s), which analyze execution traces
+//!   as written by `brane run --trace`.
+//
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use log::debug;
+
+use brane_ast::Workflow;
+use brane_exe::trace::{analyze as analyze_trace, read_trace, TraceAnalysis, TraceEvent};
+use specifications::profiling::format_duration;
+
+pub use crate::errors::TraceError as Error;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Shorthand for creating a TraceEvent on the critical path.
+    fn event(thread: usize, pc: usize, kind: &str, start: i64, end: i64) -> TraceEvent {
+        TraceEvent { thread, parent: if thread > 0 { Some(0) } else { None }, pc: (usize::MAX, pc), kind: kind.into(), branch: None, next: None, start, end }
+    }
+
+
+    #[test]
+    fn test_critical_times() {
+        // The join spans the thread it waits on, so only the time after it counts
+        let critical: Vec<TraceEvent> = vec![
+            event(0, 0, "Parallel", 0, 10),
+            event(1, 1, "Node", 12, 90),
+            event(0, 2, "Join", 10, 95),
+            event(0, 3, "Stop", 95, 96),
+        ];
+        assert_eq!(critical_times(&critical), vec![ 10, 78, 5, 1 ]);
+        assert!(critical_times(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(format_pc((usize::MAX, 4)), "<main>:4");
+        assert_eq!(format_pc((2, 7)), "<func 2>:7");
+        assert_eq!(format_fraction(3, 4), "3/4 (75.0%)");
+        // Nothing to cover is fully covered
+        assert_eq!(format_fraction(0, 0), "0/0 (100.0%)");
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Formats the given program counter for the user.
+///
+/// # Arguments
+/// - `pc`: The `(body, offset)` pair to format.
+///
+/// # Returns
+/// A string like `<main>:4` or `<func 2>:7`.
+fn format_pc(pc: (usize, usize)) -> String {
+    if pc.0 == usize::MAX { format!("<main>:{}", pc.1) } else { format!("<func {}>:{}", pc.0, pc.1) }
+}
+
+/// Computes the time spent in every edge on the given critical path.
+///
+/// Note that a join also spans the threads it waits on, so we only count the time after the previous edge on the path.
+///
+/// # Arguments
+/// - `critical`: The edges on the critical path, in the order they were executed.
+///
+/// # Returns
+/// The time spent in every edge, in microseconds.
+fn critical_times(critical: &[TraceEvent]) -> Vec<i64> {
+    let mut times: Vec<i64> = Vec::with_capacity(critical.len());
+    let mut prev_end: i64 = i64::MIN;
+    for e in critical {
+        times.push(e.end - e.start.max(prev_end));
+        prev_end = e.end;
+    }
+    times
+}
+
+/// Formats the given fraction as a percentage for the user.
+///
+/// # Arguments
+/// - `n`: The numerator.
+/// - `total`: The denominator.
+///
+/// # Returns
+/// A string like `3/4 (75.0%)`.
+fn format_fraction(n: usize, total: usize) -> String {
+    format!("{}/{} ({:.1}%)", n, total, if total > 0 { 100.0 * n as f64 / total as f64 } else { 100.0 })
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Analyzes the given execution trace and reports its branch coverage and critical path.
+///
+/// # Arguments
+/// - `trace`: The JSONL file with the trace (as written by `brane run --trace`).
+/// - `workflow`: If given, the compiled workflow (as JSON, as written by `branec`) that was traced. Coverage is then computed relative to all of its edges instead of only the visited ones.
+///
+/// # Returns
+/// Nothing, but does print the report to stdout.
+///
+/// # Errors
+/// This function errors if we failed to read the trace or the workflow.
+pub fn analyze(trace: PathBuf, workflow: Option<PathBuf>) -> Result<(), Error> {
+    // Read the trace
+    debug!("Reading trace '{}'...", trace.display());
+    let events: Vec<TraceEvent> = match read_trace(&trace) {
+        Ok(events) => events,
+        Err(err)   => { return Err(Error::TraceReadError{ err }); },
+    };
+
+    // Read the workflow, if any
+    let workflow: Option<Workflow> = match workflow {
+        Some(path) => {
+            debug!("Reading workflow '{}'...", path.display());
+            let raw: String = match fs::read_to_string(&path) {
+                Ok(raw)  => raw,
+                Err(err) => { return Err(Error::WorkflowReadError{ path, err }); },
+            };
+            match serde_json::from_str(&raw) {
+                Ok(workflow) => Some(workflow),
+                Err(err)     => { return Err(Error::WorkflowParseError{ path, err }); },
+            }
+        },
+        None => None,
+    };
+
+    // Analyze it
+    let analysis: TraceAnalysis = analyze_trace(&events, workflow.as_ref());
+
+    // Report the summary
+    println!("Trace '{}': {} edges visited by {} thread(s) in {}", trace.display(), events.len(), analysis.threads, format_duration(Duration::from_micros(analysis.duration as u64)));
+    println!();
+
+    // Report the coverage
+    println!("Coverage:");
+    match analysis.edges {
+        Some(edges) => println!("  Edges    : {}", format_fraction(analysis.visits.len(), edges)),
+        None        => println!("  Edges    : {} distinct (give the workflow to compare against all edges)", analysis.visits.len()),
+    }
+    let taken: usize = analysis.branches.values().map(|(t, f)| *t as usize + *f as usize).sum();
+    println!("  Branches : {} directions taken{}", format_fraction(taken, 2 * analysis.branches.len()), if analysis.edges.is_none() { " (of visited branches only)" } else { "" });
+    for (pc, (t, f)) in &analysis.branches {
+        if *t && *f { continue; }
+        println!("    {:<16} never took the {} branch", format_pc(*pc), match (t, f) {
+            (false, false) => "true or false",
+            (false, true)  => "true",
+            (true, false)  => "false",
+            (true, true)   => unreachable!(),
+        });
+    }
+    println!();
+
+    // Report the critical path
+    let times: Vec<i64> = critical_times(&analysis.critical);
+    println!("Critical path ({} edges, {} spent in them):", analysis.critical.len(), format_duration(Duration::from_micros(times.iter().sum::<i64>() as u64)));
+    for (e, time) in analysis.critical.iter().zip(times) {
+        println!("  {:<16} {:<10} thread {:<4} {:>10}", format_pc(e.pc), e.kind, e.thread, format_duration(Duration::from_micros(time as u64)));
+    }
+
+    // Done
+    Ok(())
+}
@@ -13,7 +13,7 @@
 //!   service to connect with its backend.
 // 
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::Write;
 use std::path::{Path, PathBuf};
@@ -25,7 +25,107 @@ use specifications::package::Capability;
 pub use crate::errors::CredsFileError as Error;
 
 
+/***** HELPER FUNCTIONS *****/
+/// Returns the default number of GPUs in a JobDefinitionSpec for serde.
+#[inline]
+fn default_gpus() -> u32 { 0 }
+
+/// Returns the default remote working directory of an SSH backend for serde.
+#[inline]
+fn default_ssh_workdir() -> PathBuf { PathBuf::from("/tmp/brane") }
+
+
+
+
+
 /***** AUXILLARY *****/
+/// Defines the resources and roles of the AWS Batch job definitions that are registered for tasks.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct JobDefinitionSpec {
+    /// The number of vCPUs to reserve for every job.
+    pub vcpus          : u32,
+    /// The amount of memory (in MiB) to reserve for every job.
+    pub memory         : u64,
+    /// The number of GPUs to reserve for every job.
+    #[serde(default = "default_gpus")]
+    pub gpus           : u32,
+    /// If given, the ARN of the IAM role that the job itself assumes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub job_role       : Option<String>,
+    /// If given, the ARN of the IAM role that AWS uses to pull the image and write the logs of the job.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub execution_role : Option<String>,
+}
+
+/// Defines which packages may be run as AWS Lambda functions instead of as AWS Batch jobs.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LambdaCredentials {
+    /// Maps package names to the name (or ARN) of the Lambda function that runs it.
+    pub functions : HashMap<String, String>,
+}
+
+/// Defines how a job node connects to AWS to run its tasks there.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AwsCredentials {
+    /// The AWS region to run the tasks in.
+    pub region            : String,
+    /// If given, the access key ID to authenticate with. Uses the default AWS credential chain (environment, profile, instance role) if omitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_key_id     : Option<String>,
+    /// The secret access key that belongs to the `access_key_id`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret_access_key : Option<String>,
+    /// If given, sends all requests to this endpoint instead of to AWS itself (e.g., a LocalStack instance).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint          : Option<String>,
+
+    /// The AWS Batch job queue to submit jobs to.
+    pub job_queue      : String,
+    /// The container registry (e.g., an ECR repository prefix) to which the job node pushes the package images, as `<registry>/<name>:<version>`.
+    pub registry       : String,
+    /// The S3 bucket through which the job node exchanges the datasets, intermediate results and return values of tasks with AWS.
+    pub bucket         : String,
+    /// The job definition used for tasks without any requirements.
+    pub job_definition : JobDefinitionSpec,
+    /// The job definitions used for tasks with requirements, per capability. Tasks with multiple requirements get the maximum of each resource.
+    #[serde(default)]
+    pub capabilities   : HashMap<Capability, JobDefinitionSpec>,
+
+    /// If given, runs the listed (requirement-free) packages as Lambda functions instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lambda : Option<LambdaCredentials>,
+}
+
+
+
+/// Defines how a job node runs its tasks directly on a remote machine over SSH, without any container runtime.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SshCredentials {
+    /// The address of the machine to connect to, as `[<user>@]<host>[:<port>]`.
+    pub address      : String,
+    /// The path to the key file to connect with.
+    pub key          : PathBuf,
+    /// The directory on the remote machine where packages are installed and tasks are run.
+    #[serde(default = "default_ssh_workdir")]
+    pub workdir      : PathBuf,
+    /// If true, assumes that every package is already installed on the remote machine as `<workdir>/packages/<name>-<version>` (with `branelet` in its root) instead of copying its filesystem over.
+    #[serde(default)]
+    pub preinstalled : bool,
+}
+
+
+
+/// Defines when a job node with a local backend offloads tasks to the cloud.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BurstConfig {
+    /// The number of tasks that may run locally at the same time before new ones are sent to the cloud instead.
+    pub max_local_tasks : usize,
+    /// The AWS account to burst to.
+    pub aws             : AwsCredentials,
+}
+
+
+
 /// Defines the possible credentials we may encounter.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "snake_case", tag = "kind")]
@@ -40,13 +140,8 @@ pub enum Credentials {
     },
 
     // Job node acting as a scheduler
-    /// Defines that this job node connects to one node by use of SSH. This effectively allows the centralized Brane manager to orchestrate over nodes instead of clusters.
-    Ssh {
-        /// The address of the machine to connect to. Should include any ports if needed.
-        address : String,
-        /// The path to the key file to connect with.
-        key     : PathBuf,
-    },
+    /// Defines that this job node connects to one node by use of SSH, where it runs branelet directly (i.e., without any container runtime).
+    Ssh(SshCredentials),
 
     // Job node acting as a cluster connector
     /// Defines that this job node connects to a backend Slurm cluster.
@@ -60,6 +155,10 @@ pub enum Credentials {
         /// The path to the Kubernetes config file to connect with.
         config  : PathBuf,
     },
+
+    // Job node acting as a cloud connector
+    /// Defines that this job node runs its tasks on AWS Batch (or AWS Lambda).
+    AwsBatch(AwsCredentials),
 }
 
 
@@ -76,6 +175,9 @@ pub struct BackendFile {
     pub capabilities : Option<HashSet<Capability>>,
     /// The method of connecting
     pub method       : Credentials,
+    /// If given, offloads tasks to the cloud when the local backend is saturated. Only used for `local` methods.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub burst        : Option<BurstConfig>,
 }
 
 impl BackendFile {
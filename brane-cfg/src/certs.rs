@@ -16,15 +16,59 @@
 use std::fs;
 use std::io;
 use std::path::Path;
+use std::sync::Arc;
 
 use log::debug;
 use rustls::{Certificate, PrivateKey, RootCertStore};
+use rustls::server::{AllowAnyAnonymousOrAuthenticatedClient, AllowAnyAuthenticatedClient, ClientCertVerifier, ServerConfig};
 use rustls_pemfile::{certs, rsa_private_keys, Item};
+use x509_parser::certificate::X509Certificate;
+use x509_parser::prelude::FromDer;
 
 pub use crate::errors::CertsError as Error;
 
 
 /***** LIBRARY *****/
+/// Retrieves the name of a client from the certificate it authenticated itself with.
+/// 
+/// # Arguments
+/// - `cert`: The raw (DER-encoded) certificate provided by the client.
+/// 
+/// # Returns
+/// The name of the client, as provided by the certificate's `CN` field.
+/// 
+/// # Errors
+/// This function errors if the certificate could not be parsed or has no such field. You should consider the client unauthenticated, in that case.
+pub fn client_name(cert: &[u8]) -> Result<String, Error> {
+    let cert: X509Certificate = match X509Certificate::from_der(cert) {
+        Ok((_, cert)) => cert,
+        Err(err)      => { return Err(Error::ClientCertParseError{ err }); },
+    };
+
+    // Get the part after 'CN=' and before end-of-string or comma (since that's canonically the domain name)
+    let subject: String = cert.subject.to_string();
+    let name_loc: usize = match subject.find("CN=") {
+        Some(name_loc) => name_loc + 3,
+        None           => { return Err(Error::ClientCertNoCN{ subject }); },
+    };
+    let name_end: usize = subject[name_loc..].find(',').map(|c| name_loc + c).unwrap_or(subject.len());
+    Ok(subject[name_loc..name_end].to_string())
+}
+
+/// Retrieves the name of a client from the certificate it authenticated itself with, for services that treat clients without one as anonymous.
+/// 
+/// # Arguments
+/// - `cert`: The raw (DER-encoded) certificate provided by the client.
+/// 
+/// # Returns
+/// The name of the client, as provided by the certificate's `CN` field, or `None` if the certificate could not be parsed or has no such field (see `client_name()`).
+pub fn extract_client_name(cert: &[u8]) -> Option<String> {
+    match client_name(cert) {
+        Ok(name) => Some(name),
+        Err(err) => { debug!("{}", err); None },
+    }
+}
+
 /// Loads a given certificate file.
 /// 
 /// # Arguments
@@ -213,3 +257,41 @@ pub fn load_certstore(storefile: impl AsRef<Path>) -> Result<RootCertStore, Erro
     // Done, for now
     Ok(store)
 }
+
+
+
+/// Creates the TLS configuration of a server that identifies itself with the given certificate / key pair and (optionally) verifies clients by the given CA.
+/// 
+/// # Arguments
+/// - `server_cert`: Path to the server's certificate file.
+/// - `server_key`: Path to the server's keyfile.
+/// - `ca_cert`: Path to the file that contains the root certificate by which clients must have been signed.
+/// - `require_client_auth`: If true, clients must present a certificate signed by the CA. Otherwise, clients without a certificate are accepted too.
+/// 
+/// # Returns
+/// A new ServerConfig that may be used to accept TLS connections.
+/// 
+/// # Errors
+/// This function errors if we failed to read any of the files or if they did not make for a valid configuration.
+pub fn load_server_config(server_cert: impl AsRef<Path>, server_key: impl AsRef<Path>, ca_cert: impl AsRef<Path>, require_client_auth: bool) -> Result<ServerConfig, Error> {
+    // Load the server key pair and the client roots
+    let (cert, key): (Certificate, PrivateKey) = load_keypair(server_cert, server_key)?;
+    let client_roots: RootCertStore = load_certstore(ca_cert)?;
+
+    // Choose how strict we are with clients
+    let verifier: Arc<dyn ClientCertVerifier> = if require_client_auth {
+        AllowAnyAuthenticatedClient::new(client_roots)
+    } else {
+        AllowAnyAnonymousOrAuthenticatedClient::new(client_roots)
+    };
+
+    // Create the config itself
+    match ServerConfig::builder()
+        .with_safe_defaults()
+        .with_client_cert_verifier(verifier)
+        .with_single_cert(vec![ cert ], key)
+    {
+        Ok(config) => Ok(config),
+        Err(err)   => Err(Error::ServerConfigError{ err }),
+    }
+}
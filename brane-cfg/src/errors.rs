@@ -37,6 +37,14 @@ pub enum CertsError {
     EmptyCertFile{ path: PathBuf },
     /// The given keyfile was empty.
     EmptyKeyFile{ path: PathBuf },
+
+    /// Failed to create a server config from the loaded certificates and key.
+    ServerConfigError{ err: rustls::Error },
+
+    /// Failed to parse the certificate of a client.
+    ClientCertParseError{ err: x509_parser::nom::Err<x509_parser::prelude::X509Error> },
+    /// The certificate of a client has no `CN` field in its subject.
+    ClientCertNoCN{ subject: String },
 }
 
 impl Display for CertsError {
@@ -52,6 +60,11 @@ impl Display for CertsError {
 
             EmptyCertFile{ path }           => write!(f, "No certificates found in file '{}'", path.display()),
             EmptyKeyFile{ path }            => write!(f, "No keys found in file '{}'", path.display()),
+
+            ServerConfigError{ err } => write!(f, "Failed to create TLS server config: {}", err),
+
+            ClientCertParseError{ err } => write!(f, "Failed to parse incoming client certificate: {}", err),
+            ClientCertNoCN{ subject }   => write!(f, "Incoming client certificate does not have a CN field specified in subject '{}'", subject),
         }
     }
 }
@@ -67,6 +80,8 @@ pub enum InfraFileError {
     FileOpenError{ path: PathBuf, err: std::io::Error },
     /// Failed to read/parse the given file as YAML.
     FileParseError{ path: PathBuf, err: serde_yaml::Error },
+    /// Failed to read the given (configuration) file to hash it.
+    FileHashError{ path: PathBuf, err: std::io::Error },
 
     /// Failed to write to the given writer.
     WriterWriteError{ err: std::io::Error },
@@ -80,6 +95,7 @@ impl Display for InfraFileError {
         match self {
             FileOpenError{ path, err }  => write!(f, "Failed to open infrastructure file '{}': {}", path.display(), err),
             FileParseError{ path, err } => write!(f, "Failed to parse infrastructure file '{}' as YAML: {}", path.display(), err),
+            FileHashError{ path, err }  => write!(f, "Failed to read configuration file '{}' to hash it: {}", path.display(), err),
 
             WriterWriteError{ err }     => write!(f, "Failed to write to given writer: {}", err),
             ConfigSerializeError{ err } => write!(f, "Failed to serialize infrastructure file to YAML: {}", err),
@@ -145,6 +161,120 @@ impl Error for AddressParseError {}
 
 
 
+/// Errors that relate to parsing IP ranges.
+#[derive(Debug)]
+pub enum IpRangeParseError {
+    /// The address part of the range was not a valid IP address.
+    IllegalAddress{ raw: String, err: std::net::AddrParseError },
+    /// The prefix length of the range was not a valid number.
+    IllegalPrefix{ raw: String, err: std::num::ParseIntError },
+    /// The prefix length of the range was too large for its address.
+    PrefixTooLarge{ raw: String, prefix: u8, max: u8 },
+}
+
+impl Display for IpRangeParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use IpRangeParseError::*;
+        match self {
+            IllegalAddress{ raw, err }         => write!(f, "Illegal IP address '{}': {}", raw, err),
+            IllegalPrefix{ raw, err }          => write!(f, "Illegal prefix length '{}': {}", raw, err),
+            PrefixTooLarge{ raw, prefix, max } => write!(f, "Prefix length {} in '{}' is too large (must be at most {})", prefix, raw, max),
+        }
+    }
+}
+
+impl Error for IpRangeParseError {}
+
+
+
+/// Errors that relate to parsing vulnerability severities.
+#[derive(Debug)]
+pub enum SeverityParseError {
+    /// The given string was not a known severity.
+    UnknownSeverity{ raw: String },
+}
+
+impl Display for SeverityParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use SeverityParseError::*;
+        match self {
+            UnknownSeverity{ raw } => write!(f, "Unknown severity '{}' (expected 'unknown', 'low', 'medium', 'high' or 'critical')", raw),
+        }
+    }
+}
+
+impl Error for SeverityParseError {}
+
+
+
+/// Errors that relate to the CA transparency log and the certificate pins of clients.
+#[derive(Debug)]
+pub enum PinError {
+    /// Failed to load a CA certificate to fingerprint.
+    CertLoadError{ err: CertsError },
+    /// The CA certificate file did not contain any certificates.
+    EmptyCaFile{ path: PathBuf },
+
+    /// Failed to open the given log or pins file.
+    FileOpenError{ what: &'static str, path: PathBuf, err: std::io::Error },
+    /// Failed to parse the given log or pins file.
+    FileParseError{ what: &'static str, path: PathBuf, err: serde_yaml::Error },
+    /// Failed to create the given log or pins file.
+    FileCreateError{ what: &'static str, path: PathBuf, err: std::io::Error },
+    /// Failed to write the given log or pins file.
+    FileWriteError{ what: &'static str, path: PathBuf, err: serde_yaml::Error },
+
+    /// Failed to read the signing key of the log.
+    KeyReadError{ path: PathBuf, err: std::io::Error },
+    /// Failed to generate a new signing key for the log.
+    KeyGenerateError,
+    /// Failed to write a newly generated signing key of the log.
+    KeyWriteError{ path: PathBuf, err: std::io::Error },
+    /// The signing key of the log was not a valid Ed25519 key.
+    KeyParseError{ path: PathBuf, err: String },
+
+    /// An entry in the log does not chain to the ones before it.
+    BrokenChain{ index: usize },
+    /// The log was signed by another key than the one we pinned.
+    LogKeyMismatch{ pinned: String, got: String },
+    /// The signature of the log is not valid.
+    IllegalSignature,
+    /// The CA certificate of a location is not the one recorded in the log.
+    CaSubstitution{ location: String, logged: Option<String>, got: String },
+    /// The log does not extend the one we saw before, i.e., it has been rolled back or rewritten.
+    LogRollback{ seen_size: usize, seen_head: String, got_size: usize },
+}
+
+impl Display for PinError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use PinError::*;
+        match self {
+            CertLoadError{ err } => write!(f, "Failed to load CA certificate: {}", err),
+            EmptyCaFile{ path }  => write!(f, "No certificates found in CA file '{}'", path.display()),
+
+            FileOpenError{ what, path, err }   => write!(f, "Failed to open {} file '{}': {}", what, path.display(), err),
+            FileParseError{ what, path, err }  => write!(f, "Failed to parse {} file '{}': {}", what, path.display(), err),
+            FileCreateError{ what, path, err } => write!(f, "Failed to create {} file '{}': {}", what, path.display(), err),
+            FileWriteError{ what, path, err }  => write!(f, "Failed to write {} file '{}': {}", what, path.display(), err),
+
+            KeyReadError{ path, err }  => write!(f, "Failed to read CA log signing key '{}': {}", path.display(), err),
+            KeyGenerateError           => write!(f, "Failed to generate a new CA log signing key"),
+            KeyWriteError{ path, err } => write!(f, "Failed to write CA log signing key '{}': {}", path.display(), err),
+            KeyParseError{ path, err } => write!(f, "Failed to parse CA log signing key '{}': {}", path.display(), err),
+
+            BrokenChain{ index }                    => write!(f, "Entry {} in the CA log does not chain to its predecessors", index),
+            LogKeyMismatch{ pinned, got }           => write!(f, "CA log is signed with key '{}', but we pinned key '{}'", got, pinned),
+            IllegalSignature                        => write!(f, "CA log has an invalid signature"),
+            CaSubstitution{ location, logged, got } => write!(f, "CA certificate of location '{}' has fingerprint '{}', but {}; refusing to trust it", location, got, if let Some(logged) = logged { format!("the CA log records '{}'", logged) } else { "the CA log does not know the location".into() }),
+            LogRollback{ seen_size, seen_head, got_size } => write!(f, "CA log with {} entries does not extend the log we saw before ({} entries with head '{}'); it has been rolled back or rewritten, so refusing to trust it", got_size, seen_size, seen_head),
+        }
+    }
+}
+
+impl Error for PinError {}
+
+
+
 /// Errors that relate to a NodeConfig.
 #[derive(Debug)]
 pub enum NodeConfigError {
@@ -220,3 +350,57 @@ impl Display for PolicyFileError {
 }
 
 impl Error for PolicyFileError {}
+
+
+
+/// Errors that relate to the SecretsFile.
+#[derive(Debug)]
+pub enum SecretsFileError {
+    /// Failed to open the given file.
+    FileOpenError{ path: PathBuf, err: std::io::Error },
+    /// Failed to read/parse the given file as YAML.
+    FileParseError{ path: PathBuf, err: serde_yaml::Error },
+
+    /// Failed to write to the given writer.
+    WriterWriteError{ err: std::io::Error },
+    /// Failed to serialze the SecretsFile.
+    ConfigSerializeError{ err: serde_yaml::Error },
+}
+
+impl Display for SecretsFileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use SecretsFileError::*;
+        match self {
+            FileOpenError{ path, err }  => write!(f, "Failed to open secrets file '{}': {}", path.display(), err),
+            FileParseError{ path, err } => write!(f, "Failed to parse secrets file '{}' as YAML: {}", path.display(), err),
+
+            WriterWriteError{ err }     => write!(f, "Failed to write to given writer: {}", err),
+            ConfigSerializeError{ err } => write!(f, "Failed to serialize secrets file to YAML: {}", err),
+        }
+    }
+}
+
+impl Error for SecretsFileError {}
+
+
+
+/// Errors that relate to resolving a SecretReference.
+#[derive(Debug)]
+pub enum SecretReferenceError {
+    /// The referenced environment variable was not set (or not UTF-8).
+    EnvError{ name: String, err: std::env::VarError },
+    /// Failed to read the referenced file.
+    FileReadError{ path: PathBuf, err: std::io::Error },
+}
+
+impl Display for SecretReferenceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use SecretReferenceError::*;
+        match self {
+            EnvError{ name, err }      => write!(f, "Failed to read secret from environment variable '{}': {}", name, err),
+            FileReadError{ path, err } => write!(f, "Failed to read secret from file '{}': {}", path.display(), err),
+        }
+    }
+}
+
+impl Error for SecretReferenceError {}
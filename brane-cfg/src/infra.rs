@@ -12,27 +12,69 @@
 //!   Implements a more up-to-date version of the infrastructure document.
 // 
 
-use std::collections::HashMap;
-use std::fs::File;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
 use std::io::Write;
 use std::path::Path;
 
+use base64ct::{Base64, Encoding};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use specifications::package::Capability;
 
 pub use crate::errors::InfraFileError as Error;
 use crate::spec::Address;
 
 
+/***** CONSTANTS *****/
+/// The separator between the name of a peer instance and the name of one of its locations, which together identify a location hosted by the peer (e.g., `partner:hospital_a`).
+pub const PEER_SEPARATOR: char = ':';
+
+
+
+
+
 /***** AUXILLARY *****/
+/// Defines the hashes of the configuration files of a node, used to detect when they have drifted from what is expected (e.g., a stale `infra.yml`).
+/// 
+/// Every hash is the base64-encoded SHA256 hash of the file's contents (see `hash_config_file()`). If a hash is omitted, it is not known or not checked.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ConfigHashes {
+    /// The hash of the `infra.yml` file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub infra    : Option<String>,
+    /// The hash of the `backend.yml` file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend  : Option<String>,
+    /// The hash of the `policies.yml` file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policies : Option<String>,
+}
+
+impl ConfigHashes {
+    /// Returns whether no hash is given at all.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.infra.is_none() && self.backend.is_none() && self.policies.is_none() }
+}
+
+
+
 /// Defines a single Location in the InfraFile.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct InfraLocation {
     /// Defines a more human-readable name for the location.
-    pub name     : String,
+    pub name         : String,
     /// The address of the delegate to connect to.
-    pub delegate : Address,
+    pub delegate     : Address,
     /// The address of the local registry to query for locally available packages, datasets and more.
-    pub registry : Address,
+    pub registry     : Address,
+    /// The hashes that the location's configuration files are expected to have. Only the `backend` and `policies` hashes are relevant here.
+    #[serde(default, skip_serializing_if = "ConfigHashes::is_empty")]
+    pub expected     : ConfigHashes,
+    /// The capabilities (e.g., `cuda` or `x86_64`) that the location is known to have, on top of those it advertises itself.
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub capabilities : HashSet<Capability>,
 }
 
 
@@ -40,6 +82,59 @@ pub struct InfraLocation {
 
 
 /***** LIBRARY *****/
+/// Returns the name by which we refer to a location hosted by a peer instance.
+/// 
+/// # Arguments
+/// - `peer`: The name of the peer instance.
+/// - `location`: The name of the location within the peer instance.
+/// 
+/// # Returns
+/// The name of the location as `<peer>:<location>`.
+#[inline]
+pub fn peer_location(peer: impl AsRef<str>, location: impl AsRef<str>) -> String { format!("{}{}{}", peer.as_ref(), PEER_SEPARATOR, location.as_ref()) }
+
+/// Splits the name of a location hosted by a peer instance in the name of the peer and the name of the location within it.
+/// 
+/// # Arguments
+/// - `location`: The name of the location to split.
+/// 
+/// # Returns
+/// A tuple of the peer's name and the location's name within it, or `None` if the location is not hosted by a peer (i.e., it does not contain a `PEER_SEPARATOR`).
+#[inline]
+pub fn split_peer_location(location: &str) -> Option<(&str, &str)> {
+    let sep: usize = location.find(PEER_SEPARATOR)?;
+    Some((&location[..sep], &location[sep + PEER_SEPARATOR.len_utf8()..]))
+}
+
+
+
+/// Computes the hash of the given configuration file, for use in `ConfigHashes`.
+/// 
+/// # Arguments
+/// - `path`: The path of the file to hash.
+/// 
+/// # Returns
+/// The base64-encoded SHA256 hash of the file's contents.
+/// 
+/// # Errors
+/// This function errors if we failed to read the given file.
+pub fn hash_config_file(path: impl AsRef<Path>) -> Result<String, Error> {
+    let path: &Path = path.as_ref();
+
+    // Read the file
+    let contents: Vec<u8> = match fs::read(path) {
+        Ok(contents) => contents,
+        Err(err)     => { return Err(Error::FileHashError{ path: path.into(), err }); },
+    };
+
+    // Hash it
+    let mut hasher: Sha256 = Sha256::new();
+    hasher.update(&contents);
+    Ok(Base64::encode_string(&hasher.finalize()))
+}
+
+
+
 /// Defines a "handle" to the document that contains the Brane instance layout.
 /// 
 /// It is recommended to only load when used, to allow system admins to update the file during runtime.
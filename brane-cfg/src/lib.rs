@@ -20,4 +20,6 @@ pub mod certs;
 pub mod backend;
 pub mod infra;
 pub mod node;
+pub mod pins;
 pub mod policies;
+pub mod secrets;
@@ -14,7 +14,7 @@
 //!   found, etc.
 // 
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter, Result as FResult};
 use std::fs::File;
 use std::io::{Read, Write};
@@ -25,8 +25,47 @@ use std::str::FromStr;
 use enum_debug::EnumDebug;
 use serde::{Deserialize, Serialize};
 
+use brane_shr::otel::TracingConfig;
+use brane_shr::telemetry::TelemetryConfig;
+use specifications::package::Capability;
+
 pub use crate::errors::NodeConfigError as Error;
-use crate::spec::Address;
+use crate::errors::SeverityParseError;
+use crate::secrets::SecretReference;
+use crate::spec::{Address, IpRange};
+
+
+/***** CONSTANTS *****/
+/// The default maximum size (in bytes) of the scratch space provisioned for every task on a worker node (1 GiB).
+pub const DEFAULT_MAX_SCRATCH_SIZE: u64 = 1024 * 1024 * 1024;
+/// The default amount of disk space (in bytes) that worker nodes always keep free (5 GiB).
+pub const DEFAULT_MIN_FREE_SPACE: u64 = 5 * 1024 * 1024 * 1024;
+/// The default port of SMTP relays used for e-mail notifications (i.e., the submission port).
+pub const DEFAULT_SMTP_PORT: u16 = 587;
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Returns the default maximum scratch size for serde.
+#[inline]
+fn default_max_scratch_size() -> u64 { DEFAULT_MAX_SCRATCH_SIZE }
+
+/// Returns the default minimum free disk space for serde.
+#[inline]
+fn default_min_free_space() -> u64 { DEFAULT_MIN_FREE_SPACE }
+
+/// Returns the default SMTP port for serde.
+#[inline]
+fn default_smtp_port() -> u16 { DEFAULT_SMTP_PORT }
+
+/// Returns `true` for serde, for feature flags that are enabled by default.
+#[inline]
+fn default_true() -> bool { true }
+
+
+
 
 
 /***** AUXILLARY *****/
@@ -84,6 +123,19 @@ pub struct NodeConfig {
     /// Defines service addresses that occur on any kind of node.
     pub services : CommonServices,
 
+    /// Defines if and where this node reports anonymized usage statistics. Omitting it means telemetry is disabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub telemetry : Option<TelemetryConfig>,
+    /// Defines if and where this node exports the spans of its services for distributed tracing. Omitting it means spans are not exported (but trace contexts are still forwarded).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tracing   : Option<TracingConfig>,
+    /// Defines the OCI (Docker) registry in which package images may be stored instead of in `brane-api`. Omitting it means all images are stored in (and downloaded from) `brane-api`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_registry : Option<ImageRegistryConfig>,
+    /// Defines which (experimental) subsystems are enabled on this node. Omitting it (or any of its flags) means the defaults are used.
+    #[serde(default, skip_serializing_if = "FeatureFlags::is_default")]
+    pub features       : FeatureFlags,
+
     /// NodeKind-specific configuration options,
     pub node : NodeKindConfig,
 }
@@ -307,6 +359,81 @@ pub struct CommonServices {
     pub prx : Address,
 }
 
+/// Defines the feature flags of a node, with which operators may roll out (experimental) subsystems gradually.
+/// 
+/// Every service reads the flags of its own node, so they may differ per node (e.g., to try a subsystem on a single worker first). Flags that a service does not know are ignored, so older services keep working with newer `node.yml` files.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct FeatureFlags {
+    /// Whether services may cache things on disk (i.e., package images on worker nodes, and package metadata for when the database is unavailable on the central node).
+    #[serde(default = "default_true")]
+    pub enable_cache          : bool,
+    /// Whether the output that tasks report while they run is streamed to the client, instead of only their final result.
+    #[serde(default = "default_true")]
+    pub enable_streaming_logs : bool,
+    /// Whether the (experimental) Kubernetes backend may be used. No service uses it yet; it is surfaced so that clients and deployments may already adapt to it.
+    #[serde(default)]
+    pub experimental_k8s      : bool,
+}
+
+impl Default for FeatureFlags {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            enable_cache          : true,
+            enable_streaming_logs : true,
+            experimental_k8s      : false,
+        }
+    }
+}
+
+impl FeatureFlags {
+    /// Returns whether all flags have their default value (in which case they are omitted when serializing).
+    #[inline]
+    pub fn is_default(&self) -> bool { *self == Self::default() }
+
+    /// Returns the names of the flags that are enabled.
+    pub fn enabled(&self) -> Vec<&'static str> {
+        let mut enabled: Vec<&'static str> = vec![];
+        if self.enable_cache { enabled.push("enable_cache"); }
+        if self.enable_streaming_logs { enabled.push("enable_streaming_logs"); }
+        if self.experimental_k8s { enabled.push("experimental_k8s"); }
+        enabled
+    }
+}
+
+impl Display for FeatureFlags {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        let enabled: Vec<&'static str> = self.enabled();
+        if enabled.is_empty() { write!(f, "none") } else { write!(f, "{}", enabled.join(", ")) }
+    }
+}
+
+/// Defines a standard OCI (Docker) registry, such as Harbor, in which package images are distributed.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImageRegistryConfig {
+    /// The address of the registry, including the project or namespace in which the images live (e.g., `harbor.example.com/brane`).
+    pub address  : String,
+    /// The username with which to pull images, if the registry requires it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username : Option<String>,
+    /// The password with which to pull images, if the registry requires it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password : Option<String>,
+}
+
+impl ImageRegistryConfig {
+    /// Returns the reference of the image of the given package in this registry.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the package.
+    /// - `version`: The (resolved) version of the package.
+    /// 
+    /// # Returns
+    /// The reference, as `<address>/<name>:<version>`.
+    #[inline]
+    pub fn reference(&self, name: impl Display, version: impl Display) -> String { format!("{}/{}:{}", self.address.trim_end_matches('/'), name, version) }
+}
+
 
 
 /// Defines the properties that are specific to a central node.
@@ -322,6 +449,25 @@ pub struct CentralConfig {
     pub services : CentralServices,
     /// Defines Kafka topics shared across services.
     pub topics   : CentralKafkaTopics,
+    /// Defines how the driver sends workflows to the planner. If omitted, they are sent over Kafka.
+    #[serde(default)]
+    pub planner  : PlannerTransport,
+    /// Defines how the planner chooses locations for tasks that the user did not pin to one. If omitted, it prefers the locations that hold the most of their inputs.
+    #[serde(default)]
+    pub strategy : PlanningStrategy,
+
+    /// Defines the channels on which workflows may notify users (through the `notify` builtin), by name.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub notifications : HashMap<String, NotificationChannel>,
+    /// Defines the peer instances that this instance federates with, by name. Their datasets become visible to this instance as living on `<peer>:<location>`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub peers         : HashMap<String, PeerConfig>,
+    /// Defines the scanner that checks the images of uploaded packages for vulnerabilities. If omitted, images are not scanned.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scanner       : Option<ScannerConfig>,
+    /// Defines if and where the API and driver services additionally serve over TLS. If omitted, they are only reachable in plaintext.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls           : Option<CentralTlsConfig>,
 }
 
 /// Defines service names used on a central node.
@@ -342,7 +488,13 @@ pub struct CentralNames {
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct CentralPaths {
     /// The path of the infrastructure file.
-    pub infra   : PathBuf,
+    pub infra       : PathBuf,
+    /// The path of the audit log, to which decisions such as approvals are appended. If omitted, they are only written to the driver's log.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audit       : Option<PathBuf>,
+    /// The directory in which the driver checkpoints running workflows, so that they can be resumed after a restart. If omitted, workflows are not checkpointed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checkpoints : Option<PathBuf>,
 }
 
 /// Defines various ports for external services on the central node.
@@ -380,6 +532,204 @@ pub struct CentralKafkaTopics {
     pub planner_results : String,
 }
 
+/// Defines how the driver sends workflows to the planner.
+#[derive(Clone, Debug, Deserialize, EnumDebug, Serialize)]
+#[serde(tag = "transport", rename_all = "snake_case")]
+pub enum PlannerTransport {
+    /// Workflows are sent on the `planner_command` topic, and plans are received on the `planner_results` topic.
+    Kafka,
+    /// Workflows are sent directly to the gRPC service of the planner, which answers with the plan. This avoids the need for Kafka.
+    Grpc {
+        /// The address on which the planner serves its gRPC service.
+        bind    : SocketAddr,
+        /// Defines how the driver reaches the planner's gRPC service.
+        address : Address,
+    },
+}
+
+impl Default for PlannerTransport {
+    #[inline]
+    fn default() -> Self { Self::Kafka }
+}
+
+/// Defines how the planner chooses a location for a task that the user did not pin to a single one. Tasks that are pinned always run where the user says.
+#[derive(Clone, Debug, Deserialize, EnumDebug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PlanningStrategy {
+    /// Chooses the location that already holds the most of the task's input datasets and intermediate results, minimizing transfers.
+    DataLocalityFirst,
+    /// Spreads tasks evenly by cycling through the possible locations.
+    RoundRobin,
+    /// Chooses the possible location with the lowest cost. Locations that are not annotated with a cost are never chosen.
+    CostAnnotated {
+        /// The cost of running a task on a location, by location name.
+        costs : HashMap<String, f64>,
+    },
+    /// Never chooses a location itself, requiring the user to pin every task to a location (using `on`-structs).
+    UserPinned,
+}
+
+impl Default for PlanningStrategy {
+    #[inline]
+    fn default() -> Self { Self::DataLocalityFirst }
+}
+
+/// Defines a peer instance that we federate with.
+/// 
+/// The peer is trusted using the certificates in the `<certs>/<name>` directory (i.e., a `ca.pem` to verify it and a `client-id.pem` to identify ourselves), just like a location.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PeerConfig {
+    /// Defines how to reach the peer's API service.
+    pub api : Address,
+}
+
+/// Defines where the externally available services of the central node serve over TLS.
+/// 
+/// The services use the `server.pem` and `server-key.pem` in the certificate directory to identify themselves, and trust clients signed by the `ca.pem` in that directory (just like the registry of a worker). If this is given, they no longer serve in plaintext on their usual ports; the other services on the node can only reach them in plaintext on the internal addresses, if those are given (which should then not be exposed outside of the node).
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CentralTlsConfig {
+    /// Where the API service serves over TLS.
+    #[serde(alias = "registry")]
+    pub api : SocketAddr,
+    /// Where the driver service serves over TLS.
+    #[serde(alias = "driver")]
+    pub drv : SocketAddr,
+    /// Where the API service serves in plaintext for the other services on the node, if at all.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub internal_api : Option<SocketAddr>,
+    /// Where the driver service serves in plaintext for the other services on the node, if at all.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub internal_drv : Option<SocketAddr>,
+
+    /// Whether clients must present a certificate signed by the CA. If false, clients without a certificate are accepted too (but clients with an invalid one are not).
+    #[serde(default)]
+    pub require_client_auth : bool,
+    /// The names of the clients (i.e., the `CN` in their certificate) that may decide on the approvals that workflows wait for. If empty, nobody may.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub approvers           : Vec<String>,
+    /// The names of the clients (i.e., the `CN` in their certificate) that may see the dashboard of the API service and use the driver sessions of every client. If empty, nobody may.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub admins              : Vec<String>,
+}
+
+
+
+/// Defines the severity of a vulnerability found in a package image, in increasing order.
+#[derive(Clone, Copy, Debug, Deserialize, EnumDebug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// The scanner did not know how severe the vulnerability is.
+    Unknown,
+    /// A vulnerability with low impact.
+    Low,
+    /// A vulnerability with medium impact.
+    Medium,
+    /// A vulnerability with high impact.
+    High,
+    /// A vulnerability with critical impact.
+    Critical,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use Severity::*;
+        match self {
+            Unknown  => write!(f, "unknown"),
+            Low      => write!(f, "low"),
+            Medium   => write!(f, "medium"),
+            High     => write!(f, "high"),
+            Critical => write!(f, "critical"),
+        }
+    }
+}
+
+impl FromStr for Severity {
+    type Err = SeverityParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Scanners tend to shout their severities, so be lenient
+        match s.to_lowercase().as_str() {
+            "unknown"  => Ok(Self::Unknown),
+            "low"      => Ok(Self::Low),
+            "medium"   => Ok(Self::Medium),
+            "high"     => Ok(Self::High),
+            "critical" => Ok(Self::Critical),
+            _          => Err(SeverityParseError::UnknownSeverity{ raw: s.into() }),
+        }
+    }
+}
+
+/// Defines what happens to uploaded packages with vulnerabilities at or above the threshold severity.
+#[derive(Clone, Copy, Debug, Deserialize, EnumDebug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanAction {
+    /// The package is refused.
+    Reject,
+    /// The package is accepted, but its scan result is marked as flagged.
+    Flag,
+}
+
+/// Defines the scanner that checks the images of uploaded packages for vulnerabilities.
+/// 
+/// Either backend must produce a report in the JSON format of [trivy](https://trivy.dev) (i.e., `trivy image --format json`).
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ScannerConfig {
+    /// Defines how to run the scanner.
+    #[serde(flatten)]
+    pub backend   : ScannerBackend,
+    /// Defines the severity from which vulnerabilities are acted upon.
+    pub threshold : Severity,
+    /// Defines what to do with packages that have vulnerabilities at or above the threshold.
+    pub action    : ScanAction,
+}
+
+/// Defines the ways in which the images of uploaded packages can be scanned.
+#[derive(Clone, Debug, Deserialize, EnumDebug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ScannerBackend {
+    /// Runs a local command, which should write the report to stdout.
+    Command {
+        /// The command to run, where any `{image}` in the arguments is replaced with the path of the image tar (e.g., `[ "trivy", "image", "--quiet", "--format", "json", "--input", "{image}" ]`).
+        command : Vec<String>,
+    },
+    /// POSTs the image tar to an external scanning service, which should respond with the report.
+    Http {
+        /// The URL of the scanning service.
+        address : String,
+    },
+}
+
+
+
+/// Defines a channel on which workflows may notify users.
+#[derive(Clone, Debug, Deserialize, EnumDebug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum NotificationChannel {
+    /// Posts messages to a Slack (or Slack-compatible) incoming webhook.
+    Slack {
+        /// The URL of the webhook.
+        webhook : String,
+    },
+    /// Sends messages as e-mails through an SMTP relay (using STARTTLS).
+    Email {
+        /// The hostname of the SMTP relay.
+        host     : String,
+        /// The port of the SMTP relay. Defaults to the submission port (587).
+        #[serde(default = "default_smtp_port")]
+        port     : u16,
+        /// The username to authenticate with, if any.
+        #[serde(default)]
+        username : Option<String>,
+        /// Where to find the password to authenticate with, if any. It is never given in the file itself.
+        #[serde(default)]
+        password : Option<SecretReference>,
+
+        /// The address to send the e-mails from.
+        from : String,
+        /// The address(es) to send the e-mails to.
+        to   : Vec<String>,
+    },
+}
 
 
 /// Defines the properties that are specific to a worker node.
@@ -397,6 +747,28 @@ pub struct WorkerConfig {
     pub ports    : WorkerPorts,
     /// Defines where to find the various worker services.
     pub services : WorkerServices,
+
+    /// Defines the maximum size (in bytes) of the private scratch space that is provisioned for every task. Packages may ask for less in their `container.yml`, but never for more.
+    #[serde(default = "default_max_scratch_size")]
+    pub max_scratch_size : u64,
+    /// Defines the amount of disk space (in bytes) that is always kept free. Tasks that would eat into it are refused, and cached package images are evicted to stay above it.
+    #[serde(default = "default_min_free_space")]
+    pub min_free_space   : u64,
+    /// Defines the X.509 proxy certificates with which XRootD and GridFTP datasets are fetched, per domain of the storage endpoint (e.g., `cern.ch`). Endpoints use the proxy of the most specific domain they are part of, or none at all if there is no such domain.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub proxies          : HashMap<String, PathBuf>,
+    /// Defines the capabilities (e.g., `cuda` or `x86_64`) that this worker advertises on top of those of its backend, such that the planner only sends it tasks it can run.
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub capabilities     : HashSet<Capability>,
+    /// Defines which clients may talk to the job service. If omitted, anyone who can reach its port may submit tasks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub firewall         : Option<JobFirewallConfig>,
+    /// If true, the containers of the same workflow run share a dedicated, internal Docker network (`brane-run-<app id>`) on which they can reach each other by package name. Otherwise, containers have no network at all.
+    #[serde(default)]
+    pub run_networks     : bool,
+    /// Defines for how long (in seconds) intermediate results are kept after they have been produced, such that users can still inspect them after their run completed. If omitted, they are kept until released by `branectl gc --results`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result_retention : Option<u64>,
 }
 
 /// Defines service names used on a worker node.
@@ -429,6 +801,14 @@ pub struct WorkerPaths {
     pub temp_data    : PathBuf,
     /// The path of the temporary results directory.
     pub temp_results : PathBuf,
+    /// The path of the content-addressed store, which keeps the package images, committed datasets and intermediate results of this node.
+    pub store        : PathBuf,
+    /// The directory of the audit log, to which every task execution, data download and result commit is appended. If omitted, they are only written to the services' logs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audit        : Option<PathBuf>,
+    /// The path of the `secrets.yml` file, which describes where to find the secrets that are injected into the containers of packages that need them. If omitted, such packages cannot run on this node.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secrets      : Option<PathBuf>,
 }
 
 /// Defines various ports for external services on the worker node.
@@ -452,3 +832,20 @@ pub struct WorkerServices {
     #[serde(alias = "checker")]
     pub chk : Address,
 }
+
+/// Defines the allowlist that the job service enforces on incoming requests. A request must satisfy every non-empty list to be accepted.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct JobFirewallConfig {
+    /// The `CN`s of the client certificates that may connect. If non-empty, the job service only accepts TLS connections authenticated with a certificate signed by `<certs>/ca.pem`, and it serves with `<certs>/server.pem` and `<certs>/server-key.pem`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub names  : Vec<String>,
+    /// The IP ranges (in CIDR notation, e.g., `10.0.0.0/8`) from which clients may connect.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ranges : Vec<IpRange>,
+}
+
+impl JobFirewallConfig {
+    /// Returns whether this firewall requires clients to authenticate with a certificate.
+    #[inline]
+    pub fn requires_tls(&self) -> bool { !self.names.is_empty() }
+}
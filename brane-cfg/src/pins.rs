@@ -0,0 +1,556 @@
+//  PINS.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 11:50:11
+//  Last edited:
+//    16 Oct 2026, 11:50:11
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements the CA transparency log that the central node keeps of
+//!   the root certificates of every domain, and the pins with which
+//!   clients and workers detect that a domain's CA has been substituted.
+// 
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::path::Path;
+
+use base64ct::{Base64, Encoding};
+use log::{debug, info};
+use ring::pkcs8::Document;
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair as _, UnparsedPublicKey, ED25519};
+use rustls::Certificate;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+pub use crate::errors::PinError as Error;
+use crate::certs::load_cert;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Generates a new (throwaway) key to sign logs with.
+    fn new_key() -> Ed25519KeyPair {
+        let pkcs8: Document = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap()
+    }
+
+    /// Creates a log with the given entries.
+    fn new_log(entries: &[(&str, &str)]) -> CaLog {
+        let mut log: CaLog = CaLog::default();
+        for (location, fingerprint) in entries { log.append(*location, *fingerprint); }
+        log
+    }
+
+
+    /// Tests appending to the log and verifying its hash chain.
+    #[test]
+    fn test_chain() {
+        let mut log: CaLog = CaLog::default();
+        assert_eq!(log.head(), "");
+        assert!(log.append("site1", "aaa"));
+        assert!(log.append("site2", "bbb"));
+        // Recording the same certificate twice does nothing, but switching back to an older one does
+        assert!(!log.append("site1", "aaa"));
+        assert!(log.append("site1", "ccc"));
+        assert!(log.append("site1", "aaa"));
+        assert_eq!(log.entries.len(), 4);
+        assert_eq!(log.latest("site1"), Some("aaa"));
+        assert_eq!(log.latest("site2"), Some("bbb"));
+        assert_eq!(log.latest("site3"), None);
+        assert_eq!(log.head(), log.entries[3].hash);
+        log.verify_chain().unwrap();
+
+        // Changing any entry breaks the chain from there on
+        let mut tampered: CaLog = log.clone();
+        tampered.entries[1].fingerprint = "evil".into();
+        assert!(matches!(tampered.verify_chain(), Err(Error::BrokenChain{ index: 1 })));
+        // ...also if the attacker rehashes it, since its successors chain to the old hash
+        tampered.entries[1].hash = hash_entry(&tampered.entries[0].hash, "site2", "evil");
+        assert!(matches!(tampered.verify_chain(), Err(Error::BrokenChain{ index: 2 })));
+        // ...and dropping entries in the middle breaks it as well
+        let mut tampered: CaLog = log.clone();
+        tampered.entries.remove(2);
+        assert!(matches!(tampered.verify_chain(), Err(Error::BrokenChain{ index: 2 })));
+    }
+
+    /// Tests signing the log and verifying that signature.
+    #[test]
+    fn test_sign() {
+        let key: Ed25519KeyPair = new_key();
+        let signed: SignedCaLog = new_log(&[ ("site1", "aaa"), ("site2", "bbb") ]).sign(&key);
+        signed.verify().unwrap();
+
+        // Another key cannot pass the signature off as its own
+        let mut forged: SignedCaLog = signed.clone();
+        forged.public_key = Base64::encode_string(new_key().public_key().as_ref());
+        assert!(matches!(forged.verify(), Err(Error::IllegalSignature)));
+        // Neither can it append to the log without re-signing it
+        let mut forged: SignedCaLog = signed.clone();
+        forged.log.append("site2", "evil");
+        assert!(matches!(forged.verify(), Err(Error::IllegalSignature)));
+        // And garbage is just that
+        let mut forged: SignedCaLog = signed;
+        forged.signature = "not base64!".into();
+        assert!(matches!(forged.verify(), Err(Error::IllegalSignature)));
+    }
+
+    /// Tests whether logs extend the heads of earlier ones.
+    #[test]
+    fn test_extends() {
+        let log: CaLog = new_log(&[ ("site1", "aaa"), ("site2", "bbb") ]);
+        let head: CaLogHead = log.current_head();
+        assert_eq!(head, CaLogHead{ size: 2, hash: log.head().into() });
+
+        // A log extends itself, its own continuations and the empty log
+        assert!(log.extends(&head));
+        assert!(new_log(&[ ("site1", "aaa"), ("site2", "bbb"), ("site1", "ccc") ]).extends(&head));
+        assert!(log.extends(&CaLog::default().current_head()));
+
+        // ...but not logs that are shorter or have another history
+        assert!(!new_log(&[ ("site1", "aaa") ]).extends(&head));
+        assert!(!new_log(&[ ("site1", "aaa"), ("site2", "evil") ]).extends(&head));
+        assert!(!new_log(&[ ("site1", "aaa"), ("site2", "evil"), ("site2", "bbb") ]).extends(&head));
+    }
+
+    /// Tests pinning certificates that the log vouches for.
+    #[test]
+    fn test_update() {
+        let key: Ed25519KeyPair = new_key();
+        let mut pins: PinStore = PinStore::default();
+        let log: SignedCaLog = new_log(&[ ("site1", "aaa"), ("site2", "bbb") ]).sign(&key);
+
+        // The first log pins its key and head, and vouches for the latest certificates only
+        pins.update("site1", "aaa", &log).unwrap();
+        assert_eq!(pins.log_key.as_deref(), Some(log.public_key.as_str()));
+        assert_eq!(pins.log_head, Some(log.log.current_head()));
+        assert!(pins.is_pinned("site1", "aaa"));
+        assert!(!pins.is_pinned("site2", "bbb"));
+        assert!(matches!(pins.update("site2", "evil", &log), Err(Error::CaSubstitution{ logged: Some(_), .. })));
+        assert!(matches!(pins.update("site3", "ccc", &log), Err(Error::CaSubstitution{ logged: None, .. })));
+
+        // Logs signed by another key are rejected, even if they are valid
+        let other: SignedCaLog = new_log(&[ ("site1", "aaa"), ("site2", "bbb") ]).sign(&new_key());
+        assert!(matches!(pins.update("site2", "bbb", &other), Err(Error::LogKeyMismatch{ .. })));
+
+        // Logs that roll back to before a change in certificates are rejected...
+        let mut newer: CaLog = log.log.clone();
+        newer.append("site2", "ddd");
+        let newer: SignedCaLog = newer.sign(&key);
+        pins.update("site2", "ddd", &newer).unwrap();
+        assert_eq!(pins.log_head, Some(newer.log.current_head()));
+        assert!(matches!(pins.update("site2", "bbb", &log), Err(Error::LogRollback{ seen_size: 3, got_size: 2, .. })));
+        // ...and so are logs that rewrite history, even if they are signed and longer
+        let forked: SignedCaLog = new_log(&[ ("site1", "aaa"), ("site2", "bbb"), ("site2", "evil"), ("site2", "ddd") ]).sign(&key);
+        assert!(matches!(pins.update("site2", "ddd", &forked), Err(Error::LogRollback{ .. })));
+
+        // The head survives a round-trip through the pins file
+        let pins: PinStore = serde_yaml::from_str(&serde_yaml::to_string(&pins).unwrap()).unwrap();
+        assert_eq!(pins.log_head, Some(newer.log.current_head()));
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Computes the hash of a log entry, which chains it to all entries before it.
+/// 
+/// # Arguments
+/// - `prev`: The hash of the previous entry (or an empty string for the first one).
+/// - `location`: The location of the entry.
+/// - `fingerprint`: The fingerprint of the entry.
+/// 
+/// # Returns
+/// The base64-encoded SHA256 hash of the entry.
+fn hash_entry(prev: &str, location: &str, fingerprint: &str) -> String {
+    let mut hasher: Sha256 = Sha256::new();
+    hasher.update(prev.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(location.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(fingerprint.as_bytes());
+    Base64::encode_string(&hasher.finalize())
+}
+
+
+
+
+
+/***** LIBRARY FUNCTIONS *****/
+/// Computes the fingerprint of the given CA certificate file.
+/// 
+/// # Arguments
+/// - `path`: The path to the `ca.pem` file to fingerprint.
+/// 
+/// # Returns
+/// The base64-encoded SHA256 hash of the (DER-encoded) first certificate in the file.
+/// 
+/// # Errors
+/// This function errors if we failed to load the file or if it contains no certificates.
+pub fn fingerprint(path: impl AsRef<Path>) -> Result<String, Error> {
+    let path: &Path = path.as_ref();
+    let certs: Vec<Certificate> = match load_cert(path) {
+        Ok(certs) => certs,
+        Err(err)  => { return Err(Error::CertLoadError{ err }); },
+    };
+    let cert: &Certificate = match certs.first() {
+        Some(cert) => cert,
+        None       => { return Err(Error::EmptyCaFile{ path: path.into() }); },
+    };
+
+    let mut hasher: Sha256 = Sha256::new();
+    hasher.update(&cert.0);
+    Ok(Base64::encode_string(&hasher.finalize()))
+}
+
+/// Loads the key with which the central node signs the CA log, generating a new one if it does not exist yet.
+/// 
+/// # Arguments
+/// - `path`: The path to the (PKCS#8-encoded) Ed25519 key.
+/// 
+/// # Returns
+/// The loaded key pair.
+/// 
+/// # Errors
+/// This function errors if we failed to read, generate or parse the key.
+pub fn load_log_key(path: impl AsRef<Path>) -> Result<Ed25519KeyPair, Error> {
+    let path: &Path = path.as_ref();
+
+    // Generate the key if it's the first time
+    if !path.exists() {
+        info!("Generating new CA log signing key '{}'...", path.display());
+        let pkcs8: Document = match Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()) {
+            Ok(pkcs8) => pkcs8,
+            Err(_)    => { return Err(Error::KeyGenerateError); },
+        };
+        if let Err(err) = fs::write(path, pkcs8.as_ref()) { return Err(Error::KeyWriteError{ path: path.into(), err }); }
+    }
+
+    // Load it
+    let raw: Vec<u8> = match fs::read(path) {
+        Ok(raw)  => raw,
+        Err(err) => { return Err(Error::KeyReadError{ path: path.into(), err }); },
+    };
+    match Ed25519KeyPair::from_pkcs8(&raw) {
+        Ok(key)  => Ok(key),
+        Err(err) => Err(Error::KeyParseError{ path: path.into(), err: err.to_string() }),
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines a single entry in the CA log, which records that a location started using a particular root certificate.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CaLogEntry {
+    /// The location (or peer instance) that uses the certificate.
+    pub location    : String,
+    /// The fingerprint of the certificate, as computed by [`fingerprint()`].
+    pub fingerprint : String,
+    /// The hash of this entry and all entries before it.
+    pub hash        : String,
+}
+
+
+
+/// Identifies a version of the CA log by its size and head, such that clients can check that later versions extend it.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CaLogHead {
+    /// The number of entries in the log.
+    pub size : usize,
+    /// The hash of the latest entry in the log (see [`CaLog::head()`]).
+    pub hash : String,
+}
+
+
+
+/// Defines the append-only log of the root certificates used by every domain. Entries are hash-chained, so the log can only be rewritten by changing its head.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct CaLog {
+    /// The entries in the log, oldest first.
+    pub entries : Vec<CaLogEntry>,
+}
+
+impl CaLog {
+    /// The name of the file in the central node's certificate directory that contains the log.
+    pub const FILENAME: &'static str = "ca-log.yml";
+    /// The name of the file in the central node's certificate directory that contains the log's signing key.
+    pub const KEY_FILENAME: &'static str = "ca-log-key.pk8";
+
+
+
+    /// Loads the log from the given path, returning an empty log if it does not exist yet.
+    /// 
+    /// # Arguments
+    /// - `path`: The path to load the log from.
+    /// 
+    /// # Returns
+    /// A new CaLog instance.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to read or parse the file.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path: &Path = path.as_ref();
+        if !path.exists() { return Ok(Self::default()); }
+
+        let handle: File = match File::open(path) {
+            Ok(handle) => handle,
+            Err(err)   => { return Err(Error::FileOpenError{ what: "CA log", path: path.into(), err }); },
+        };
+        match serde_yaml::from_reader(handle) {
+            Ok(log)  => Ok(log),
+            Err(err) => Err(Error::FileParseError{ what: "CA log", path: path.into(), err }),
+        }
+    }
+
+    /// Writes the log to the given path.
+    /// 
+    /// # Arguments
+    /// - `path`: The path to write the log to.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to create or write the file.
+    pub fn to_path(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let path: &Path = path.as_ref();
+        let handle: File = match File::create(path) {
+            Ok(handle) => handle,
+            Err(err)   => { return Err(Error::FileCreateError{ what: "CA log", path: path.into(), err }); },
+        };
+        match serde_yaml::to_writer(handle, self) {
+            Ok(_)    => Ok(()),
+            Err(err) => Err(Error::FileWriteError{ what: "CA log", path: path.into(), err }),
+        }
+    }
+
+
+
+    /// Records that the given location uses the given certificate, unless that is already the latest certificate recorded for it.
+    /// 
+    /// # Arguments
+    /// - `location`: The location that uses the certificate.
+    /// - `fingerprint`: The fingerprint of the certificate.
+    /// 
+    /// # Returns
+    /// Whether a new entry was added.
+    pub fn append(&mut self, location: impl Into<String>, fingerprint: impl Into<String>) -> bool {
+        let location: String = location.into();
+        let fingerprint: String = fingerprint.into();
+        if self.latest(&location) == Some(fingerprint.as_str()) { return false; }
+
+        debug!("Recording CA certificate '{}' for location '{}'", fingerprint, location);
+        let hash: String = hash_entry(self.head(), &location, &fingerprint);
+        self.entries.push(CaLogEntry{ location, fingerprint, hash });
+        true
+    }
+
+    /// Checks that every entry in the log chains to the ones before it.
+    /// 
+    /// # Errors
+    /// This function errors if an entry's hash does not match its contents and predecessor.
+    pub fn verify_chain(&self) -> Result<(), Error> {
+        let mut prev: &str = "";
+        for (i, entry) in self.entries.iter().enumerate() {
+            if entry.hash != hash_entry(prev, &entry.location, &entry.fingerprint) { return Err(Error::BrokenChain{ index: i }); }
+            prev = &entry.hash;
+        }
+        Ok(())
+    }
+
+    /// Signs the head of the log.
+    /// 
+    /// # Arguments
+    /// - `key`: The key to sign with (see [`load_log_key()`]).
+    /// 
+    /// # Returns
+    /// A SignedCaLog that clients can verify.
+    pub fn sign(self, key: &Ed25519KeyPair) -> SignedCaLog {
+        let signature: String = Base64::encode_string(key.sign(self.head().as_bytes()).as_ref());
+        SignedCaLog {
+            log        : self,
+            public_key : Base64::encode_string(key.public_key().as_ref()),
+            signature,
+        }
+    }
+
+
+
+    /// Returns the hash of the latest entry, which covers the entire log.
+    #[inline]
+    pub fn head(&self) -> &str { self.entries.last().map(|entry| entry.hash.as_str()).unwrap_or("") }
+
+    /// Returns the size and head of the log, which clients remember to check that the next log they see extends this one.
+    #[inline]
+    pub fn current_head(&self) -> CaLogHead { CaLogHead{ size: self.entries.len(), hash: self.head().into() } }
+
+    /// Returns whether this log extends the version of the log with the given head, i.e., it only appended entries to it.
+    /// 
+    /// Note that this assumes that this log's chain has been verified (see [`CaLog::verify_chain()`]), since we only compare the hash of the entry at the old head.
+    /// 
+    /// # Arguments
+    /// - `head`: The CaLogHead of the older version of the log.
+    /// 
+    /// # Returns
+    /// True if this log has at least as many entries and the same history up to the given head, or false if it has been rolled back or rewritten.
+    #[inline]
+    pub fn extends(&self, head: &CaLogHead) -> bool {
+        if head.size == 0 { return true; }
+        self.entries.get(head.size - 1).map(|entry| entry.hash == head.hash).unwrap_or(false)
+    }
+
+    /// Returns the fingerprint of the latest certificate recorded for the given location, if any.
+    #[inline]
+    pub fn latest(&self, location: &str) -> Option<&str> { self.entries.iter().rev().find(|entry| entry.location == location).map(|entry| entry.fingerprint.as_str()) }
+}
+
+
+
+/// Defines a CaLog together with the central node's signature on its head, as served on the `/infra/ca-log` path of the API.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SignedCaLog {
+    /// The log itself.
+    pub log        : CaLog,
+    /// The base64-encoded Ed25519 public key with which the log has been signed.
+    pub public_key : String,
+    /// The base64-encoded signature on the head of the log.
+    pub signature  : String,
+}
+
+impl SignedCaLog {
+    /// Checks that the log is intact and has been signed by its key.
+    /// 
+    /// # Errors
+    /// This function errors if the log's chain is broken or if its signature is invalid.
+    pub fn verify(&self) -> Result<(), Error> {
+        self.log.verify_chain()?;
+
+        let (public_key, signature): (Vec<u8>, Vec<u8>) = match (Base64::decode_vec(&self.public_key), Base64::decode_vec(&self.signature)) {
+            (Ok(public_key), Ok(signature)) => (public_key, signature),
+            _                               => { return Err(Error::IllegalSignature); },
+        };
+        match UnparsedPublicKey::new(&ED25519, public_key).verify(self.log.head().as_bytes(), &signature) {
+            Ok(_)  => Ok(()),
+            Err(_) => Err(Error::IllegalSignature),
+        }
+    }
+}
+
+
+
+/// Defines the certificates that a client or worker has decided to trust, together with the key of the CA log that vouched for them.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct PinStore {
+    /// The public key of the CA log, pinned the first time we saw it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_key   : Option<String>,
+    /// The head of the CA log the last time we consulted it, which every later log must extend.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_head  : Option<CaLogHead>,
+    /// The fingerprints of the CA certificates we trust, per location.
+    #[serde(default)]
+    pub locations : HashMap<String, String>,
+}
+
+impl PinStore {
+    /// The name of the file in a certificate directory that contains the pins.
+    pub const FILENAME: &'static str = "pins.yml";
+
+
+
+    /// Loads the pins from the given path, returning an empty store if it does not exist yet.
+    /// 
+    /// # Arguments
+    /// - `path`: The path to load the pins from.
+    /// 
+    /// # Returns
+    /// A new PinStore instance.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to read or parse the file.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path: &Path = path.as_ref();
+        if !path.exists() { return Ok(Self::default()); }
+
+        let handle: File = match File::open(path) {
+            Ok(handle) => handle,
+            Err(err)   => { return Err(Error::FileOpenError{ what: "pins", path: path.into(), err }); },
+        };
+        match serde_yaml::from_reader(handle) {
+            Ok(pins) => Ok(pins),
+            Err(err) => Err(Error::FileParseError{ what: "pins", path: path.into(), err }),
+        }
+    }
+
+    /// Writes the pins to the given path.
+    /// 
+    /// # Arguments
+    /// - `path`: The path to write the pins to.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to create or write the file.
+    pub fn to_path(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let path: &Path = path.as_ref();
+        let handle: File = match File::create(path) {
+            Ok(handle) => handle,
+            Err(err)   => { return Err(Error::FileCreateError{ what: "pins", path: path.into(), err }); },
+        };
+        match serde_yaml::to_writer(handle, self) {
+            Ok(_)    => Ok(()),
+            Err(err) => Err(Error::FileWriteError{ what: "pins", path: path.into(), err }),
+        }
+    }
+
+
+
+    /// Returns whether the given certificate is the one we pinned for the given location.
+    /// 
+    /// # Arguments
+    /// - `location`: The location that presents the certificate.
+    /// - `fingerprint`: The fingerprint of the certificate.
+    /// 
+    /// # Returns
+    /// True if we already trust the certificate, or false if it is newly seen (in which case it should be checked with [`PinStore::update()`]).
+    #[inline]
+    pub fn is_pinned(&self, location: &str, fingerprint: &str) -> bool { self.locations.get(location).map(|pin| pin == fingerprint).unwrap_or(false) }
+
+    /// Pins a newly seen certificate, provided that the CA log vouches for it.
+    /// 
+    /// # Arguments
+    /// - `location`: The location that presents the certificate.
+    /// - `fingerprint`: The fingerprint of the certificate.
+    /// - `log`: The signed CA log as retrieved from the central node.
+    /// 
+    /// # Errors
+    /// This function errors if the log is invalid, if it has been signed by another key than the one we pinned, if it does not extend the log we saw last time (i.e., it has been rolled back) or if it records another certificate for the location.
+    pub fn update(&mut self, location: &str, fingerprint: &str, log: &SignedCaLog) -> Result<(), Error> {
+        log.verify()?;
+        match &self.log_key {
+            Some(pinned) => if pinned != &log.public_key { return Err(Error::LogKeyMismatch{ pinned: pinned.clone(), got: log.public_key.clone() }); },
+            None         => { info!("Pinning CA log key '{}'", log.public_key); self.log_key = Some(log.public_key.clone()); },
+        }
+
+        // Make sure we're not served an older log, which may still vouch for a certificate that has since been replaced
+        if let Some(seen) = &self.log_head {
+            if !log.log.extends(seen) { return Err(Error::LogRollback{ seen_size: seen.size, seen_head: seen.hash.clone(), got_size: log.log.entries.len() }); }
+        }
+        self.log_head = Some(log.log.current_head());
+
+        // Only trust the certificate if it's the latest one known for this location
+        let logged: Option<&str> = log.log.latest(location);
+        if logged != Some(fingerprint) { return Err(Error::CaSubstitution{ location: location.into(), logged: logged.map(String::from), got: fingerprint.into() }); }
+        info!("Pinning CA certificate '{}' for location '{}'", fingerprint, location);
+        self.locations.insert(location.into(), fingerprint.into());
+        Ok(())
+    }
+}
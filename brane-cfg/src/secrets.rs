@@ -0,0 +1,308 @@
+//  SECRETS.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 12:15:53
+//  Last edited:
+//    16 Oct 2026, 13:33:30
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Defines the file that tells a worker where to find the secrets it
+//!   injects into the containers of packages that need them.
+// 
+
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter, Result as FResult};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+pub use crate::errors::SecretsFileError as Error;
+use crate::errors::SecretReferenceError;
+
+
+/***** UNIT TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_reference_resolve() {
+        // Files are read without their trailing newline
+        let path: PathBuf = std::env::temp_dir().join(format!("brane-test-secret-reference-{}", std::process::id()));
+        fs::write(&path, "hunter2\n").unwrap();
+        let secret: Result<Secret, SecretReferenceError> = SecretReference::File{ path: path.clone() }.resolve();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(secret.unwrap().expose(), "hunter2");
+        assert!(matches!(SecretReference::File{ path }.resolve(), Err(SecretReferenceError::FileReadError{ .. })));
+
+        // Environment variables are read as-is
+        std::env::set_var("BRANE_TEST_SECRET_REFERENCE", "swordfish");
+        assert_eq!(SecretReference::Env{ name: "BRANE_TEST_SECRET_REFERENCE".into() }.resolve().unwrap().expose(), "swordfish");
+        assert!(matches!(SecretReference::Env{ name: "BRANE_TEST_SECRET_REFERENCE_MISSING".into() }.resolve(), Err(SecretReferenceError::EnvError{ .. })));
+    }
+
+    #[test]
+    fn test_secret_reference_parse() {
+        let reference: SecretReference = serde_yaml::from_str("kind: env\nname: SMTP_PASSWORD\n").unwrap();
+        assert!(matches!(reference, SecretReference::Env{ name } if name == "SMTP_PASSWORD"));
+        let reference: SecretReference = serde_yaml::from_str("kind: file\npath: /run/secrets/smtp\n").unwrap();
+        assert!(matches!(reference, SecretReference::File{ path } if path == Path::new("/run/secrets/smtp")));
+
+        // Plaintext secrets are refused
+        assert!(serde_yaml::from_str::<SecretReference>("hunter2").is_err());
+    }
+
+    #[test]
+    fn test_secrets_file_allows() {
+        let file: SecretsFile = serde_yaml::from_str("kind: file\nsecrets:\n  TOKEN: abc\n  OTHER: def\naccess:\n  TOKEN:\n    packages: [ hello ]\n    digests: [ 'sha256:0123' ]\n").unwrap();
+
+        // Packages may use a secret by name or by the digest of their image
+        assert!(file.allows("TOKEN", "hello", None));
+        assert!(file.allows("TOKEN", "other", Some("sha256:0123")));
+        assert!(file.allows("TOKEN", "other", Some("0123")));
+        assert!(!file.allows("TOKEN", "other", Some("sha256:4567")));
+        assert!(!file.allows("TOKEN", "other", None));
+
+        // Secrets without an allowlist may not be used by anyone
+        assert!(!file.allows("OTHER", "hello", Some("sha256:0123")));
+        assert!(!file.allows("UNKNOWN", "hello", Some("sha256:0123")));
+    }
+}
+
+
+/***** HELPER FUNCTIONS *****/
+/// Returns the default mount of the KV secrets engine in Vault for serde.
+#[inline]
+fn default_vault_mount() -> String { "secret".into() }
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Defines the value of a single secret, which hides itself when debug-printed so it never ends up in the logs.
+#[derive(Clone, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct Secret(String);
+
+impl Secret {
+    /// Returns the actual value of the secret.
+    /// 
+    /// Only use this to give the secret to whoever needs it; never print it.
+    /// 
+    /// # Returns
+    /// The value of the secret as a string.
+    #[inline]
+    pub fn expose(&self) -> &str { &self.0 }
+}
+
+impl Debug for Secret {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult { write!(f, "<redacted>") }
+}
+
+impl From<String> for Secret {
+    #[inline]
+    fn from(value: String) -> Self { Self(value) }
+}
+impl From<&str> for Secret {
+    #[inline]
+    fn from(value: &str) -> Self { Self(value.into()) }
+}
+
+
+
+/// Refers to a secret that is kept outside of a config file, so that the config file itself never contains it in plaintext.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum SecretReference {
+    /// The secret is the value of an environment variable of the service that reads the config file.
+    Env {
+        /// The name of the environment variable.
+        name : String,
+    },
+    /// The secret is the contents of a file (e.g., a Docker secret), without any trailing newlines.
+    File {
+        /// The path to the file.
+        path : PathBuf,
+    },
+}
+
+impl SecretReference {
+    /// Reads the secret that this reference refers to.
+    /// 
+    /// # Returns
+    /// The value of the secret.
+    /// 
+    /// # Errors
+    /// This function errors if the environment variable is not set or the file could not be read.
+    pub fn resolve(&self) -> Result<Secret, SecretReferenceError> {
+        match self {
+            Self::Env{ name } => match std::env::var(name) {
+                Ok(value) => Ok(Secret(value)),
+                Err(err)  => Err(SecretReferenceError::EnvError{ name: name.clone(), err }),
+            },
+            Self::File{ path } => match fs::read_to_string(path) {
+                Ok(value) => Ok(Secret(value.trim_end_matches(['\r', '\n']).into())),
+                Err(err)  => Err(SecretReferenceError::FileReadError{ path: path.clone(), err }),
+            },
+        }
+    }
+}
+
+
+
+/// Defines a HashiCorp Vault instance that stores the secrets of a worker.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct VaultConfig {
+    /// The address of the Vault server (e.g., `https://vault:8200`).
+    pub address : String,
+    /// The token with which we authenticate to Vault.
+    pub token   : Secret,
+    /// The mount of the (version 2) KV secrets engine in which the secrets are stored.
+    #[serde(default = "default_vault_mount")]
+    pub mount   : String,
+    /// The path of the secret in the engine, of which every key is a secret known to the worker.
+    pub path    : String,
+}
+
+
+
+/// Defines the possible places where a worker keeps its secrets.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum SecretsBackend {
+    /// The secrets are stored in the file itself.
+    File {
+        /// The secrets, by name.
+        #[serde(default)]
+        secrets : HashMap<String, Secret>,
+    },
+    /// The secrets are stored in a HashiCorp Vault instance.
+    Vault(VaultConfig),
+}
+
+impl Default for SecretsBackend {
+    #[inline]
+    fn default() -> Self { Self::File{ secrets: HashMap::new() } }
+}
+
+
+
+/// Defines which packages may have a secret injected into their containers.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SecretAccess {
+    /// The names of the packages that may use the secret (in any version).
+    #[serde(default)]
+    pub packages : Vec<String>,
+    /// The digests of the package images that may use the secret, regardless of their name.
+    #[serde(default)]
+    pub digests  : Vec<String>,
+}
+
+impl SecretAccess {
+    /// Returns whether the given package may use the secret.
+    /// 
+    /// # Arguments
+    /// - `package`: The name of the package.
+    /// - `digest`: The digest of the package's image, if known. The `sha256:` prefix is optional.
+    /// 
+    /// # Returns
+    /// True if either the package or its image is on the allowlist, or false otherwise.
+    pub fn allows(&self, package: &str, digest: Option<&str>) -> bool {
+        if self.packages.iter().any(|p| p == package) { return true; }
+        match digest {
+            Some(digest) => {
+                let digest: &str = digest.trim_start_matches("sha256:");
+                self.digests.iter().any(|d| d.trim_start_matches("sha256:") == digest)
+            },
+            None => false,
+        }
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines a file that describes where a worker finds the secrets that it injects into containers (as environment variables).
+/// 
+/// Note that this struct is designed to act as a "handle"; i.e., keep it only around when using it but otherwise refer to it only by path.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct SecretsFile {
+    /// Where the secrets are stored.
+    #[serde(flatten)]
+    pub backend : SecretsBackend,
+    /// Which packages may use which secret, by name of the secret. Secrets that are not listed here are never injected.
+    #[serde(default)]
+    pub access  : HashMap<String, SecretAccess>,
+}
+
+impl SecretsFile {
+    /// Creates a new SecretsFile by loading it from the given path.
+    /// 
+    /// # Arguments
+    /// - `path`: The path to load the SecretsFile from.
+    /// 
+    /// # Returns
+    /// A new SecretsFile instance.
+    /// 
+    /// # Errors
+    /// This function may error if the SecretsFile was missing, unreadable or incorrectly formatted.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path: &Path = path.as_ref();
+
+        // Open the file
+        let handle: File = match File::open(path) {
+            Ok(handle) => handle,
+            Err(err)   => { return Err(Error::FileOpenError { path: path.into(), err }); },
+        };
+
+        // Read it with serde
+        match serde_yaml::from_reader(handle) {
+            Ok(result) => Ok(result),
+            Err(err)   => Err(Error::FileParseError { path: path.into(), err }),
+        }
+    }
+
+    /// Returns whether the given package may have the given secret injected.
+    /// 
+    /// # Arguments
+    /// - `secret`: The name of the secret.
+    /// - `package`: The name of the package.
+    /// - `digest`: The digest of the package's image, if known.
+    /// 
+    /// # Returns
+    /// True if the secret's allowlist names the package or its image, or false otherwise (including when the secret has no allowlist).
+    #[inline]
+    pub fn allows(&self, secret: &str, package: &str, digest: Option<&str>) -> bool {
+        self.access.get(secret).map(|access| access.allows(package, digest)).unwrap_or(false)
+    }
+
+    /// Writes the SecretsFile to the given writer.
+    /// 
+    /// # Arguments
+    /// - `writer`: The writer to write the SecretsFile to.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to write or failed to serialize ourselves.
+    pub fn to_writer(&self, writer: impl Write) -> Result<(), Error> {
+        let mut writer = writer;
+
+        // Serialize the config
+        let config: String = match serde_yaml::to_string(self) {
+            Ok(config) => config,
+            Err(err)   => { return Err(Error::ConfigSerializeError{ err }); },
+        };
+
+        // Write it
+        if let Err(err) = writer.write_all(config.as_bytes()) { return Err(Error::WriterWriteError{ err }); }
+        Ok(())
+    }
+}
@@ -23,7 +23,80 @@ use serde::{Deserialize, Serialize};
 use serde::ser::Serializer;
 use serde::de::{self, Deserializer, Visitor};
 
-use crate::errors::AddressParseError;
+use crate::errors::{AddressParseError, IpRangeParseError};
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Shorthand for parsing an IP address.
+    fn ip(raw: &str) -> IpAddr { IpAddr::from_str(raw).unwrap() }
+
+
+    /// Tests parsing IP ranges in CIDR notation.
+    #[test]
+    fn test_ip_range_parse() {
+        assert_eq!(IpRange::from_str("10.0.0.0/8").unwrap(), IpRange{ address: ip("10.0.0.0"), prefix: 8 });
+        assert_eq!(IpRange::from_str("0.0.0.0/0").unwrap(), IpRange{ address: ip("0.0.0.0"), prefix: 0 });
+        assert_eq!(IpRange::from_str("fd00::/16").unwrap(), IpRange{ address: ip("fd00::"), prefix: 16 });
+        // Plain addresses are ranges of only themselves
+        assert_eq!(IpRange::from_str("192.168.1.7").unwrap(), IpRange{ address: ip("192.168.1.7"), prefix: 32 });
+        assert_eq!(IpRange::from_str("::1").unwrap(), IpRange{ address: ip("::1"), prefix: 128 });
+
+        // Malformed ranges
+        assert!(matches!(IpRange::from_str("10.0.0/8"), Err(IpRangeParseError::IllegalAddress{ .. })));
+        assert!(matches!(IpRange::from_str("/8"), Err(IpRangeParseError::IllegalAddress{ .. })));
+        assert!(matches!(IpRange::from_str("10.0.0.0/"), Err(IpRangeParseError::IllegalPrefix{ .. })));
+        assert!(matches!(IpRange::from_str("10.0.0.0/-1"), Err(IpRangeParseError::IllegalPrefix{ .. })));
+        assert!(matches!(IpRange::from_str("10.0.0.0/8/8"), Err(IpRangeParseError::IllegalPrefix{ .. })));
+        assert!(matches!(IpRange::from_str("10.0.0.0/33"), Err(IpRangeParseError::PrefixTooLarge{ prefix: 33, max: 32, .. })));
+        assert!(matches!(IpRange::from_str("fd00::/129"), Err(IpRangeParseError::PrefixTooLarge{ prefix: 129, max: 128, .. })));
+
+        // They serialize to what they parse from
+        assert_eq!(IpRange::from_str("10.0.0.0/8").unwrap().to_string(), "10.0.0.0/8");
+        assert_eq!(serde_yaml::from_str::<IpRange>("192.168.1.7").unwrap().to_string(), "192.168.1.7/32");
+        assert!(serde_yaml::from_str::<IpRange>("10.0.0.0/33").is_err());
+    }
+
+    /// Tests matching addresses against IP ranges.
+    #[test]
+    fn test_ip_range_contains() {
+        let range: IpRange = IpRange::from_str("10.1.0.0/16").unwrap();
+        assert!(range.contains(&ip("10.1.0.0")));
+        assert!(range.contains(&ip("10.1.255.255")));
+        assert!(!range.contains(&ip("10.2.0.0")));
+        assert!(!range.contains(&ip("10.0.255.255")));
+
+        // Bits of the base address beyond the prefix don't matter
+        assert!(IpRange::from_str("10.1.2.3/16").unwrap().contains(&ip("10.1.200.1")));
+        // Prefixes need not be byte-aligned
+        let range: IpRange = IpRange::from_str("192.168.0.0/23").unwrap();
+        assert!(range.contains(&ip("192.168.1.255")));
+        assert!(!range.contains(&ip("192.168.2.0")));
+
+        // Edge cases of the prefix length
+        assert!(IpRange::from_str("0.0.0.0/0").unwrap().contains(&ip("203.0.113.9")));
+        assert!(IpRange::from_str("192.168.1.7").unwrap().contains(&ip("192.168.1.7")));
+        assert!(!IpRange::from_str("192.168.1.7").unwrap().contains(&ip("192.168.1.6")));
+
+        // IPv6, including the mapping of IPv4 addresses
+        let range: IpRange = IpRange::from_str("fd00::/16").unwrap();
+        assert!(range.contains(&ip("fd00:abcd::1")));
+        assert!(!range.contains(&ip("fd01::1")));
+        assert!(IpRange::from_str("::/0").unwrap().contains(&ip("2001:db8::1")));
+        assert!(IpRange::from_str("10.0.0.0/8").unwrap().contains(&ip("::ffff:10.9.8.7")));
+        assert!(!IpRange::from_str("10.0.0.0/8").unwrap().contains(&ip("::ffff:11.9.8.7")));
+        // ...but otherwise, versions never match each other
+        assert!(!IpRange::from_str("0.0.0.0/0").unwrap().contains(&ip("2001:db8::1")));
+        assert!(!IpRange::from_str("::/0").unwrap().contains(&ip("10.0.0.1")));
+    }
+}
+
+
+
 
 
 /***** LIBRARY *****/
@@ -262,3 +335,123 @@ impl From<&mut Address> for Address {
     #[inline]
     fn from(value: &mut Address) -> Self { value.clone() }
 }
+
+
+
+/// Defines a range of IP addresses in CIDR notation (e.g., `10.0.0.0/8`). A plain address is parsed as a range containing only itself.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct IpRange {
+    /// The base address of the range.
+    pub address : IpAddr,
+    /// The number of leading bits that addresses must share with the base address to be in the range.
+    pub prefix  : u8,
+}
+
+impl IpRange {
+    /// Returns whether the given address is part of this range.
+    /// 
+    /// # Arguments
+    /// - `address`: The IpAddr to check. IPv4-mapped IPv6 addresses are matched against IPv4 ranges.
+    /// 
+    /// # Returns
+    /// True if the address is in this range, or false otherwise (including when it is of another IP version).
+    pub fn contains(&self, address: &IpAddr) -> bool {
+        // Unwrap any IPv4 address hiding in an IPv6 one
+        let address: IpAddr = match address {
+            IpAddr::V6(ip) => ip.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(*address),
+            address        => *address,
+        };
+
+        match (self.address, address) {
+            (IpAddr::V4(base), IpAddr::V4(ip)) => {
+                let mask: u32 = if self.prefix == 0 { 0 } else { u32::MAX << (32 - self.prefix as u32) };
+                (u32::from(base) & mask) == (u32::from(ip) & mask)
+            },
+            (IpAddr::V6(base), IpAddr::V6(ip)) => {
+                let mask: u128 = if self.prefix == 0 { 0 } else { u128::MAX << (128 - self.prefix as u32) };
+                (u128::from(base) & mask) == (u128::from(ip) & mask)
+            },
+            _ => false,
+        }
+    }
+}
+
+impl Display for IpRange {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        write!(f, "{}/{}", self.address, self.prefix)
+    }
+}
+
+impl Serialize for IpRange {
+    #[inline]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+impl<'de> Deserialize<'de> for IpRange {
+    #[inline]
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        /// Defines the visitor for the IpRange
+        struct IpRangeVisitor;
+        impl<'de> Visitor<'de> for IpRangeVisitor {
+            type Value = IpRange;
+
+            #[inline]
+            fn expecting(&self, f: &mut Formatter<'_>) -> FResult {
+                write!(f, "an IP range in CIDR notation")
+            }
+
+            #[inline]
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                match IpRange::from_str(v) {
+                    Ok(range) => Ok(range),
+                    Err(err)  => Err(E::custom(err)),
+                }
+            }
+        }
+
+        // Call the visitor
+        deserializer.deserialize_str(IpRangeVisitor)
+    }
+}
+impl FromStr for IpRange {
+    type Err = IpRangeParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Split the prefix off, if any
+        let (address, prefix): (&str, Option<&str>) = match s.find('/') {
+            Some(pos) => (&s[..pos], Some(&s[pos + 1..])),
+            None      => (s, None),
+        };
+
+        // Parse the address
+        let address: IpAddr = match IpAddr::from_str(address) {
+            Ok(address) => address,
+            Err(err)    => { return Err(IpRangeParseError::IllegalAddress{ raw: address.into(), err }); },
+        };
+        let max: u8 = if address.is_ipv4() { 32 } else { 128 };
+
+        // Parse the prefix, defaulting to the full address
+        let prefix: u8 = match prefix {
+            Some(prefix) => match u8::from_str(prefix) {
+                Ok(prefix) => prefix,
+                Err(err)   => { return Err(IpRangeParseError::IllegalPrefix{ raw: prefix.into(), err }); },
+            },
+            None => max,
+        };
+        if prefix > max { return Err(IpRangeParseError::PrefixTooLarge{ raw: s.into(), prefix, max }); }
+
+        // Done
+        Ok(Self{ address, prefix })
+    }
+}
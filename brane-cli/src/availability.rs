@@ -0,0 +1,294 @@
+//  AVAILABILITY.rs
+//    by agent
+//
+//  Created:
+//    16 Oct 2026, 08:12:36
+//  Last edited:
+//    16 Oct 2026, 12:59:49
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements the `package availability` and `data availability`
+//!   subcommands, which show per location of the remote instance
+//!   whether a package or dataset is available there.
+//
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use console::style;
+use indicatif::HumanBytes;
+use prettytable::format::FormatBuilder;
+use prettytable::Table;
+use reqwest::{Client, ClientBuilder, Proxy, Response};
+use reqwest::tls::{Certificate, Identity};
+use tokio::fs as tfs;
+
+use brane_cfg::spec::Address;
+use brane_shr::utilities::is_ip_addr;
+use brane_tsk::api::ApiClient;
+use specifications::data::{DataIndex, DataInfo};
+use specifications::package::{PackageIndex, PackageInfo};
+use specifications::registry::{CachedPackage, LocationAvailability, RegistryConfig};
+use specifications::version::Version;
+
+pub use crate::errors::AvailabilityError as Error;
+use crate::utils::get_registry_file;
+
+
+/***** HELPER FUNCTIONS *****/
+/// Fetches the registry addresses of all locations in the remote instance.
+///
+/// # Arguments
+/// - `endpoint`: The `brane-api` endpoint to ask.
+///
+/// # Returns
+/// A map of location names to the addresses of their `brane-reg` services.
+///
+/// # Errors
+/// This function errors if the request failed or the remote returned something illegal.
+async fn get_registries(endpoint: &str) -> Result<HashMap<String, Address>, Error> {
+    let address: String = format!("{}/infra/registries", endpoint);
+    let res: Response = match reqwest::get(&address).await {
+        Ok(res)  => res,
+        Err(err) => { return Err(Error::RequestError{ what: "registries", address, err }); },
+    };
+    if !res.status().is_success() { return Err(Error::RequestFailure{ address, code: res.status(), message: res.text().await.ok() }); }
+    let body: String = match res.text().await {
+        Ok(body) => body,
+        Err(err) => { return Err(Error::ResponseTextError{ address, err }); },
+    };
+    match serde_json::from_str(&body) {
+        Ok(registries) => Ok(registries),
+        Err(err)       => Err(Error::ResponseParseError{ address, raw: body, err }),
+    }
+}
+
+/// Asks the registry of the given location what it has available.
+///
+/// # Arguments
+/// - `certs_dir`: The folder with certificates that we can use to prove who we are to each location.
+/// - `proxy_addr`: If given, the request is proxied through this address.
+/// - `location`: The name of the location to ask.
+/// - `registry`: The address of its `brane-reg` service.
+///
+/// # Returns
+/// The LocationAvailability reported by the location.
+///
+/// # Errors
+/// This function errors if we failed to load the certificates for this location, or if the request failed.
+async fn get_availability(certs_dir: &Path, proxy_addr: &Option<String>, location: &str, registry: &Address) -> Result<LocationAvailability, Error> {
+    // Load the certificates for this location
+    let cert_dir : PathBuf = certs_dir.join(location);
+    let idfile   : PathBuf = cert_dir.join("client-id.pem");
+    let cafile   : PathBuf = cert_dir.join("ca.pem");
+    let identity: Identity = match tfs::read(&idfile).await {
+        Ok(raw) => match Identity::from_pem(&raw) {
+            Ok(identity) => identity,
+            Err(err)     => { return Err(Error::IdentityFileError{ path: idfile, err }); },
+        },
+        Err(err) => { return Err(Error::FileReadError{ what: "client identity", path: idfile, err }); },
+    };
+    let ca_cert: Certificate = match tfs::read(&cafile).await {
+        Ok(raw) => match Certificate::from_pem(&raw) {
+            Ok(root) => root,
+            Err(err) => { return Err(Error::CertificateError{ path: cafile, err }); },
+        },
+        Err(err) => { return Err(Error::FileReadError{ what: "server cert root", path: cafile, err }); },
+    };
+
+    // Build the client
+    let address: String = format!("{}/infra/availability", registry);
+    let mut client: ClientBuilder = Client::builder()
+        .use_rustls_tls()
+        .add_root_certificate(ca_cert)
+        .identity(identity)
+        .tls_sni(!is_ip_addr(&address));
+    if let Some(proxy_addr) = proxy_addr {
+        client = client.proxy(match Proxy::all(proxy_addr) {
+            Ok(proxy) => proxy,
+            Err(err)  => { return Err(Error::ProxyCreateError{ address: proxy_addr.into(), err }) },
+        });
+    }
+    let client: Client = match client.build() {
+        Ok(client) => client,
+        Err(err)   => { return Err(Error::ClientCreateError{ err }); },
+    };
+
+    // Send the request
+    debug!("Fetching availability of location '{}' from '{}'...", location, address);
+    let res: Response = match client.get(&address).send().await {
+        Ok(res)  => res,
+        Err(err) => { return Err(Error::RequestError{ what: "availability", address, err }); },
+    };
+    if !res.status().is_success() { return Err(Error::RequestFailure{ address, code: res.status(), message: res.text().await.ok() }); }
+    let body: String = match res.text().await {
+        Ok(body) => body,
+        Err(err) => { return Err(Error::ResponseTextError{ address, err }); },
+    };
+    match serde_json::from_str(&body) {
+        Ok(availability) => Ok(availability),
+        Err(err)         => Err(Error::ResponseParseError{ address, raw: body, err }),
+    }
+}
+
+/// Asks every location in the remote instance what it has available.
+///
+/// # Arguments
+/// - `endpoint`: The `brane-api` endpoint that we use to discover the locations.
+/// - `certs_dir`: The folder with certificates that we can use to prove who we are to each location.
+/// - `proxy_addr`: If given, the requests to the locations are proxied through this address.
+///
+/// # Returns
+/// A list of (location, availability) pairs, sorted by location. Locations that we failed to reach are not treated as an error, but listed with the reason why instead.
+///
+/// # Errors
+/// This function errors if we failed to discover the locations.
+async fn get_availabilities(endpoint: &str, certs_dir: &Path, proxy_addr: &Option<String>) -> Result<Vec<(String, Result<LocationAvailability, Error>)>, Error> {
+    let mut registries: Vec<(String, Address)> = get_registries(endpoint).await?.into_iter().collect();
+    registries.sort_by(|(l1, _), (l2, _)| l1.cmp(l2));
+
+    let mut availabilities: Vec<(String, Result<LocationAvailability, Error>)> = Vec::with_capacity(registries.len());
+    for (location, registry) in registries {
+        let availability: Result<LocationAvailability, Error> = get_availability(certs_dir, proxy_addr, &location, &registry).await;
+        if let Err(err) = &availability { debug!("Failed to fetch availability of location '{}': {}", location, err); }
+        availabilities.push((location, availability));
+    }
+    Ok(availabilities)
+}
+
+/// Prints the given rows as a table, in the same style as the other tables of the CLI.
+///
+/// # Arguments
+/// - `header`: The names of the columns.
+/// - `rows`: The rows to print.
+fn print_table(header: &[&str], rows: Vec<Vec<String>>) {
+    let format = FormatBuilder::new()
+        .column_separator('\0')
+        .borders('\0')
+        .padding(1, 1)
+        .build();
+    let mut table = Table::new();
+    table.set_format(format);
+    table.add_row(header.iter().collect());
+    for row in rows {
+        table.add_row(row.iter().collect());
+    }
+    table.printstd();
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Shows per location of the remote instance whether the given package has been cached there, and what the location supports.
+///
+/// Locations that have not cached the package pull it from the central registry when they first run it, so this mostly explains differences in start-up time and capabilities.
+///
+/// # Arguments
+/// - `name`: The name of the package.
+/// - `version`: The version of the package (may be 'latest').
+/// - `certs_dir`: The folder with certificates that we can use to prove who we are to each location.
+/// - `proxy_addr`: If given, the requests to the locations are proxied through this address.
+///
+/// # Errors
+/// This function errors if we failed to read the login file, if the package is unknown or if we failed to discover the locations.
+pub async fn package(name: String, version: Version, certs_dir: impl AsRef<Path>, proxy_addr: &Option<String>) -> Result<(), Error> {
+    let config: RegistryConfig = match get_registry_file() {
+        Ok(config) => config,
+        Err(err)   => { return Err(Error::RegistryFileError{ err }); },
+    };
+
+    // Resolve the package in the central index first
+    let graphql_addr: String = format!("{}/graphql", config.url);
+    let pindex: PackageIndex = match brane_tsk::api::get_package_index(&graphql_addr).await {
+        Ok(pindex) => pindex,
+        Err(err)   => { return Err(Error::PackageIndexError{ address: graphql_addr, err }); },
+    };
+    let info: &PackageInfo = match pindex.get(&name, Some(&version)) {
+        Some(info) => info,
+        None       => { return Err(Error::UnknownPackage{ name, version }); },
+    };
+    let sversion: String = info.version.to_string();
+
+    // Build a row per location
+    let mut rows: Vec<Vec<String>> = vec![];
+    for (location, availability) in get_availabilities(&config.url, certs_dir.as_ref(), proxy_addr).await? {
+        let availability: LocationAvailability = match availability {
+            Ok(availability) => availability,
+            Err(_)           => { rows.push(vec![ location, "unreachable".into(), "-".into(), "-".into(), "-".into() ]); continue; },
+        };
+
+        let cached: Vec<&CachedPackage> = availability.packages.iter().filter(|p| p.name == name).collect();
+        let present: Option<&CachedPackage> = cached.iter().find(|p| p.version == sversion).copied();
+        let mut versions: Vec<&str> = cached.iter().map(|p| p.version.as_str()).collect();
+        versions.sort();
+        let mut capabilities: Vec<String> = availability.capabilities.iter().map(|c| format!("{:?}", c)).collect();
+        capabilities.sort();
+
+        rows.push(vec![
+            location,
+            if present.is_some() { "present".into() } else { "missing".into() },
+            if versions.is_empty() { "-".into() } else { versions.join(", ") },
+            present.map(|p| HumanBytes(p.size).to_string()).unwrap_or_else(|| "-".into()),
+            if capabilities.is_empty() { "-".into() } else { capabilities.join(", ") },
+        ]);
+    }
+
+    println!("Availability of package {} (version {}):", style(&name).bold().cyan(), style(&sversion).bold());
+    print_table(&[ "LOCATION", "STATUS", "CACHED VERSIONS", "SIZE", "CAPABILITIES" ], rows);
+    Ok(())
+}
+
+/// Shows per location of the remote instance whether the given dataset is available there.
+///
+/// # Arguments
+/// - `name`: The name of the dataset.
+/// - `certs_dir`: The folder with certificates that we can use to prove who we are to each location.
+/// - `proxy_addr`: If given, the requests to the locations are proxied through this address.
+///
+/// # Errors
+/// This function errors if we failed to read the login file, if the dataset is unknown or if we failed to discover the locations.
+pub async fn data(name: String, certs_dir: impl AsRef<Path>, proxy_addr: &Option<String>) -> Result<(), Error> {
+    let config: RegistryConfig = match get_registry_file() {
+        Ok(config) => config,
+        Err(err)   => { return Err(Error::RegistryFileError{ err }); },
+    };
+
+    // Resolve the dataset in the central index first
+    let data_addr: String = format!("{}/data/info", config.url);
+    let dindex: DataIndex = match ApiClient::new(&config.url).data_index().await {
+        Ok(dindex) => dindex,
+        Err(err)   => { return Err(Error::DataIndexError{ address: data_addr, err }); },
+    };
+    let info: &DataInfo = match dindex.get(&name) {
+        Some(info) => info,
+        None       => { return Err(Error::UnknownDataset{ name }); },
+    };
+
+    // Build a row per location
+    let mut rows: Vec<Vec<String>> = vec![];
+    for (location, availability) in get_availabilities(&config.url, certs_dir.as_ref(), proxy_addr).await? {
+        let allowed: &str = match &info.residency {
+            Some(residency) if !residency.contains(&location) => "no",
+            _                                                  => "yes",
+        };
+        match availability {
+            Ok(availability) => match availability.datasets.get(&info.id()) {
+                Some(size) => rows.push(vec![ location, "present".into(), HumanBytes(*size).to_string(), allowed.into() ]),
+                None       => rows.push(vec![ location, "missing".into(), "-".into(), allowed.into() ]),
+            },
+            Err(_) => {
+                // We can still tell what the central index thinks
+                let status: &str = if info.access.contains_key(&location) { "unreachable (advertised)" } else { "unreachable" };
+                rows.push(vec![ location, status.into(), "-".into(), allowed.into() ]);
+            },
+        }
+    }
+
+    println!("Availability of dataset {}:", style(&name).bold().cyan());
+    print_table(&[ "LOCATION", "STATUS", "SIZE", "PROCESSING ALLOWED" ], rows);
+    Ok(())
+}
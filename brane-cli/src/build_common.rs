@@ -20,8 +20,10 @@ use std::process::Command;
 
 use console::style;
 use file_lock::{FileLock, FileOptions};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
 
-use specifications::arch::Arch;
+use specifications::arch::{Arch, ArchImage, ManifestList};
 
 use crate::errors::BuildError;
 
@@ -53,6 +55,9 @@ pub const BRANELET_URL: &str = concat!(
     "/branelet"
 );
 
+/// The name of the file in a package directory that marks it as built with `brane build --dev`.
+pub const DEV_INFO_FILE: &str = "dev.yml";
+
 
 
 
@@ -133,6 +138,75 @@ impl Drop for LockHandle {
 
 
 
+/// Remembers that a package was built in development mode, i.e., that its working directory should be mounted from the host instead of taken from its image.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DevInfo {
+    /// The working directory on the host that is mounted over the one in the image when the package is run locally.
+    pub workdir    : PathBuf,
+    /// The hash of the Dockerfile the image was built from, which tells us whether it can be reused by the next development build.
+    pub dockerfile : String,
+}
+
+impl DevInfo {
+    /// Constructor for the DevInfo.
+    /// 
+    /// # Arguments
+    /// - `workdir`: The working directory on the host to mount.
+    /// - `dockerfile`: The contents of the Dockerfile the image was built from.
+    /// 
+    /// # Returns
+    /// A new DevInfo instance.
+    pub fn new(workdir: impl Into<PathBuf>, dockerfile: impl AsRef<str>) -> Self {
+        let mut hasher: Sha256 = Sha256::new();
+        hasher.update(dockerfile.as_ref().as_bytes());
+        Self {
+            workdir    : workdir.into(),
+            dockerfile : format!("{:x}", hasher.finalize()),
+        }
+    }
+
+    /// Reads the DevInfo of the package in the given directory, if it was built in development mode.
+    /// 
+    /// # Arguments
+    /// - `package_dir`: The directory of the package (version) to read it from.
+    /// 
+    /// # Returns
+    /// The DevInfo, or `None` if the package was not built in development mode (or its file could not be read, in which case we act as if it wasn't).
+    pub fn from_package_dir(package_dir: &Path) -> Option<Self> {
+        let path: PathBuf = package_dir.join(DEV_INFO_FILE);
+        if !path.exists() { return None; }
+        let raw: String = match fs::read_to_string(&path) {
+            Ok(raw)  => raw,
+            Err(err) => { warn!("Failed to read development info '{}': {} (ignoring it)", path.display(), err); return None; },
+        };
+        match serde_yaml::from_str(&raw) {
+            Ok(info) => Some(info),
+            Err(err) => { warn!("Failed to parse development info '{}': {} (ignoring it)", path.display(), err); None },
+        }
+    }
+
+    /// Writes this DevInfo to the given package directory, marking it as built in development mode.
+    /// 
+    /// # Arguments
+    /// - `package_dir`: The directory of the package (version) to write it to.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to serialize or write the file.
+    pub fn to_package_dir(&self, package_dir: &Path) -> Result<(), BuildError> {
+        let path: PathBuf = package_dir.join(DEV_INFO_FILE);
+        let raw: String = match serde_yaml::to_string(self) {
+            Ok(raw)  => raw,
+            Err(err) => { return Err(BuildError::DevInfoSerializeError{ err }); },
+        };
+        match fs::write(&path, raw) {
+            Ok(_)    => Ok(()),
+            Err(err) => Err(BuildError::DevInfoWriteError{ path, err }),
+        }
+    }
+}
+
+
+
 
 
 /***** COMMON FUNCTIONS *****/
@@ -169,23 +243,27 @@ pub fn clean_directory(
 
 
 
-/// Builds the docker image in the given package directory.
+/// Builds the docker image in the given package directory, once for every given architecture.
+/// 
+/// The image for the first architecture is written to `image.tar`, the others to `image-<arch>.tar` (see `ManifestList::image_file()`).
 /// 
 /// # Generic types
 ///  - `P`: The Path-like type of the container directory path.
 /// 
 /// # Arguments
-///  - `arch`: The architecture for which to build this image.
+///  - `arches`: The architectures for which to build this image. Should not be empty.
 ///  - `package_dir`: The build directory for this image. We expect the actual image files to be under ./container.
 ///  - `tag`: Tag to give to the image so we can find it later (probably just <package name>:<package version>)
 /// 
 /// # Errors
 /// This function fails if Buildx could not be test-ran, it could not run the Docker build command or the Docker build command did not return a successfull exit code.
 pub fn build_docker_image<P: AsRef<Path>>(
-    arch        : Arch,
+    arches      : &[Arch],
     package_dir : P,
     tag         : String,
 ) -> Result<(), BuildError> {
+    let package_dir: &Path = package_dir.as_ref();
+
     // Prepare the command to check for buildx (and launch the buildx image, presumably)
     let mut command = Command::new("docker");
     command.arg("buildx");
@@ -198,31 +276,58 @@ pub fn build_docker_image<P: AsRef<Path>>(
         return Err(BuildError::BuildKitError{ command: format!("{:?}", command), code: buildx.status.code().unwrap_or(-1), stdout: String::from_utf8_lossy(&buildx.stdout).to_string(), stderr: String::from_utf8_lossy(&buildx.stdout).to_string() });
     }
 
-    // Next, launch the command to actually build the image
-    let mut command = Command::new("docker");
-    command.arg("buildx");
-    command.arg("build");
-    command.arg("--output");
-    command.arg("type=docker,dest=image.tar");
-    command.arg("--tag");
-    command.arg(tag);
-    command.arg("--platform");
-    command.arg(format!("linux/{}", arch.to_docker()));
-    command.arg("--build-arg");
-    command.arg(format!("BRANELET_ARCH={}", arch));
-    command.arg("--build-arg");
-    command.arg(format!("JUICEFS_ARCH={}", arch.to_juicefs()));
-    command.arg(".");
-    command.current_dir(package_dir);
-    let output = match command.status() {
-        Ok(output) => output,
-        Err(err)   => { return Err(BuildError::ImageBuildLaunchError{ command: format!("{:?}", command), err }); }
-    };
-    // Check if it was successfull
-    if !output.success() {
-        return Err(BuildError::ImageBuildError{ command: format!("{:?}", command), code: output.code().unwrap_or(-1) });
+    // Next, launch the command to actually build the image for every architecture
+    for (i, arch) in arches.iter().enumerate() {
+        let mut command = Command::new("docker");
+        command.arg("buildx");
+        command.arg("build");
+        command.arg("--output");
+        command.arg(format!("type=docker,dest={}", ManifestList::image_file(*arch, i == 0)));
+        command.arg("--tag");
+        command.arg(&tag);
+        command.arg("--platform");
+        command.arg(format!("linux/{}", arch.to_docker()));
+        command.arg("--build-arg");
+        command.arg(format!("BRANELET_ARCH={}", arch));
+        command.arg("--build-arg");
+        command.arg(format!("JUICEFS_ARCH={}", arch.to_juicefs()));
+        command.arg(".");
+        command.current_dir(package_dir);
+        let output = match command.status() {
+            Ok(output) => output,
+            Err(err)   => { return Err(BuildError::ImageBuildLaunchError{ command: format!("{:?}", command), err }); }
+        };
+        // Check if it was successfull
+        if !output.success() {
+            return Err(BuildError::ImageBuildError{ command: format!("{:?}", command), code: output.code().unwrap_or(-1) });
+        }
     }
 
     // Done! :D
     Ok(())
 }
+
+/// Writes the manifest list of a package that has just been built, which lists the image (and its digest) for every architecture.
+/// 
+/// # Arguments
+///  - `arches`: The architectures for which the image has been built, in the same order as given to `build_docker_image()`.
+///  - `package_dir`: The package directory that contains the images.
+/// 
+/// # Returns
+/// The new ManifestList, which has also been written to the package directory.
+/// 
+/// # Errors
+/// This function fails if we could not get the digest of any of the images or failed to write the list.
+pub async fn write_manifest_list(arches: &[Arch], package_dir: &Path) -> Result<ManifestList, BuildError> {
+    let mut list: ManifestList = ManifestList::default();
+    for (i, arch) in arches.iter().enumerate() {
+        let file: String = ManifestList::image_file(*arch, i == 0);
+        let digest: String = match brane_tsk::docker::get_digest(package_dir.join(&file)).await {
+            Ok(digest) => digest,
+            Err(err)   => { return Err(BuildError::DigestError{ err }); }
+        };
+        list.images.push(ArchImage{ arch: *arch, file, digest: Some(digest) });
+    }
+    if let Err(err) = list.to_path(package_dir.join(ManifestList::FILENAME)) { return Err(BuildError::ManifestListWriteError{ err }); }
+    Ok(list)
+}
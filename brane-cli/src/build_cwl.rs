@@ -0,0 +1,861 @@
+//  BUILD CWL.rs
+//    by agent
+//
+//  Created:
+//    16 Oct 2026, 08:50:50
+//  Last edited:
+//    16 Oct 2026, 12:24:01
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements building packages from CWL documents. Every
+//!   CommandLineTool (either the document itself or the tools run by the
+//!   steps of a Workflow) becomes a function of an ECU package, which is
+//!   run by a generated script that builds the tool's command line.
+//
+
+use std::collections::HashMap;
+use std::fmt::Write as FmtWrite;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_yaml::{Mapping, Value as YValue};
+
+use specifications::arch::Arch;
+use specifications::common::Parameter;
+use specifications::container::{Action, ActionCommand, ContainerInfo, Entrypoint};
+use specifications::package::PackageKind;
+use specifications::version::Version;
+
+use crate::build_common::LockHandle;
+use crate::build_ecu;
+use crate::errors::BuildError;
+use crate::utils::ensure_package_dir;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Writes a CWL document to the given directory.
+    fn write(dir: &Path, name: &str, contents: &str) -> PathBuf {
+        let path: PathBuf = dir.join(name);
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+
+    /// Tests translating CWL identifiers and types.
+    #[test]
+    fn test_identifier_type() {
+        assert_eq!(identifier("#main/input-file"), "input_file");
+        assert_eq!(identifier("word.count"), "word_count");
+        assert_eq!(identifier("1st"), "_1st");
+
+        let parse = |raw: &str| -> Option<(String, bool)> { translate_type(&serde_yaml::from_str(raw).unwrap()) };
+        assert_eq!(parse("long"), Some(("integer".into(), false)));
+        assert_eq!(parse("double?"), Some(("real".into(), true)));
+        assert_eq!(parse("string[]"), Some(("string[]".into(), false)));
+        assert_eq!(parse("[ 'null', boolean ]"), Some(("boolean".into(), true)));
+        assert_eq!(parse("{ type: array, items: int }"), Some(("integer[]".into(), false)));
+        assert_eq!(parse("File"), None);
+        assert_eq!(parse("[ int, string ]"), None);
+        assert_eq!(parse("{ type: array, items: 'int[]' }"), None);
+    }
+
+    /// Tests translating a single CommandLineTool.
+    #[test]
+    fn test_translate_tool() {
+        let dir = tempfile::tempdir().unwrap();
+        let file: PathBuf = write(dir.path(), "wc.cwl", r#"
+cwlVersion: v1.2
+class: CommandLineTool
+id: word-count
+doc: Counts the words in a file.
+s:softwareVersion: 1.2.3
+baseCommand: wc
+arguments: [ "-w" ]
+requirements:
+  DockerRequirement:
+    dockerPull: ubuntu:22.04
+  EnvVarRequirement:
+    envDef:
+      LANG: C
+inputs:
+  input_file:
+    type: string
+    inputBinding:
+      position: 1
+  verbose:
+    type: boolean?
+    inputBinding:
+      prefix: --verbose
+outputs:
+  count:
+    type: stdout
+stdout: count.txt
+"#);
+
+        let (info, script): (ContainerInfo, String) = translate(&file).unwrap();
+        assert_eq!(info.name, "word-count");
+        assert_eq!(info.version, Version::new(1, 2, 3));
+        assert_eq!(info.description.as_deref(), Some("Counts the words in a file."));
+        assert_eq!(info.base.as_deref(), Some("ubuntu:22.04"));
+        assert_eq!(info.environment, Some(HashMap::from([ ("LANG".into(), "C".into()) ])));
+        assert_eq!(info.dependencies, Some(vec![ "jq".into() ]));
+
+        // The tool becomes a function with the same inputs and its stdout as output
+        let action: &Action = &info.actions["word_count"];
+        let inputs: &[Parameter] = action.input.as_deref().unwrap();
+        assert_eq!(inputs.iter().map(|p| (p.name.as_str(), p.data_type.as_str(), p.optional)).collect::<Vec<_>>(), vec![ ("input_file", "string", None), ("verbose", "boolean", Some(true)) ]);
+        let outputs: &[Parameter] = action.output.as_deref().unwrap();
+        assert_eq!(outputs.iter().map(|p| (p.name.as_str(), p.data_type.as_str())).collect::<Vec<_>>(), vec![ ("count", "string") ]);
+
+        // The script builds the command line in order of position (and name)
+        assert!(script.contains("'word_count')\n    set -- 'wc'\n"));
+        let (w, verbose, input): (usize, usize, usize) = (script.find("'-w'").unwrap(), script.find("'--verbose'").unwrap(), script.find("$INPUT_FILE").unwrap());
+        assert!(w < verbose && verbose < input);
+        assert!(script.contains("    \"$@\" > 'count.txt'\n"));
+        assert!(script.contains("jq -Rs '{\"count\": .}' < 'count.txt'"));
+    }
+
+    /// Tests that `jq` is only installed if the script needs it.
+    #[test]
+    fn test_translate_jq() {
+        let dir = tempfile::tempdir().unwrap();
+        let file: PathBuf = write(dir.path(), "touch.cwl", r#"
+class: CommandLineTool
+baseCommand: [ touch, /tmp/done ]
+inputs:
+  force:
+    type: boolean
+    inputBinding:
+      prefix: -f
+  unused: string
+outputs: []
+"#);
+        let (info, script): (ContainerInfo, String) = translate(&file).unwrap();
+        assert_eq!(info.name, "touch");
+        assert_eq!(info.dependencies, None);
+        assert!(!script.contains("jq"));
+        assert!(script.contains("if [ \"$FORCE\" = \"true\" ]; then set -- \"$@\" '-f'; fi"));
+    }
+
+    /// Tests translating a Workflow, of which every step becomes a function.
+    #[test]
+    fn test_translate_workflow() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "touch.cwl", "class: CommandLineTool\nbaseCommand: touch\noutputs: []\n");
+        let file: PathBuf = write(dir.path(), "pipeline.cwl", r#"
+class: Workflow
+id: my_pipeline
+requirements:
+  - class: DockerRequirement
+    dockerPull: alpine:3.18
+steps:
+  mark:
+    run: touch.cwl
+  greet:
+    run:
+      class: CommandLineTool
+      baseCommand: echo
+      inputs:
+        name:
+          type: string
+          inputBinding: {}
+      outputs:
+        greeting:
+          type: stdout
+"#);
+        let (info, script): (ContainerInfo, String) = translate(&file).unwrap();
+        assert_eq!(info.name, "my-pipeline");
+        assert_eq!(info.version, DEFAULT_VERSION);
+        assert_eq!(info.base.as_deref(), Some("alpine:3.18"));
+        let mut functions: Vec<&str> = info.actions.keys().map(String::as_str).collect();
+        functions.sort();
+        assert_eq!(functions, vec![ "greet", "mark" ]);
+        assert!(script.find("'mark')").unwrap() < script.find("'greet')").unwrap());
+        assert!(script.contains("'greet')\n    set -- 'echo'\n"));
+        assert!(script.contains(&format!("# Generated by Brane from '{}'", file.display())));
+    }
+
+    /// Tests that unsupported parts of CWL are refused.
+    #[test]
+    fn test_translate_unsupported() {
+        let dir = tempfile::tempdir().unwrap();
+        let unsupported = |name: &str, contents: &str| -> String {
+            match translate(&write(dir.path(), name, contents)) {
+                Err(BuildError::CwlUnsupported{ what, .. }) => what,
+                res                                         => { panic!("Expected an unsupported error, got {:?}", res.map(|(info, _)| info.name)); },
+            }
+        };
+
+        assert!(unsupported("expr.cwl", "class: CommandLineTool\nbaseCommand: echo\narguments: [ $(inputs.x) ]\n").starts_with("expressions"));
+        assert!(unsupported("file.cwl", "class: CommandLineTool\nbaseCommand: cat\ninputs:\n  x: File\n").starts_with("the type of input 'x'"));
+        assert!(unsupported("outs.cwl", "class: CommandLineTool\nbaseCommand: ls\noutputs:\n  a: stdout\n  b: stdout\n").starts_with("multiple outputs"));
+        assert!(unsupported("glob.cwl", "class: CommandLineTool\nbaseCommand: ls\noutputs:\n  a:\n    type: string\n    outputBinding:\n      glob: '*.txt'\n").starts_with("outputs that are not read from a single, literal file"));
+        assert!(unsupported("nested.cwl", "class: Workflow\nsteps:\n  inner:\n    run:\n      class: Workflow\n").starts_with("nested workflows"));
+
+        // Different images for different tools cannot be combined in one package
+        write(dir.path(), "a.cwl", "class: CommandLineTool\nbaseCommand: a\nhints:\n  DockerRequirement:\n    dockerPull: alpine\n");
+        write(dir.path(), "b.cwl", "class: CommandLineTool\nbaseCommand: b\nhints:\n  DockerRequirement:\n    dockerPull: ubuntu\n");
+        let file: PathBuf = write(dir.path(), "both.cwl", "class: Workflow\nsteps:\n  a:\n    run: a.cwl\n  b:\n    run: b.cwl\n");
+        assert!(matches!(translate(&file), Err(BuildError::CwlConflictingImages{ .. })));
+    }
+}
+
+
+
+
+
+/***** CONSTANTS *****/
+/// The name of the generated script that runs the tools in the package.
+const WRAPPER_NAME: &str = "cwl.sh";
+/// The version of packages built from CWL documents that do not specify one (as `s:softwareVersion`).
+const DEFAULT_VERSION: Version = Version::new(1, 0, 0);
+/// The file to which the stdout of a tool is written if it is an output but the tool does not name the file.
+const DEFAULT_STDOUT: &str = "cwl.stdout";
+
+
+
+
+
+/***** CWL DOCUMENTS *****/
+/// Either a single value or a list of them, which CWL allows interchangeably in a few places.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+enum OneOrMany<T> {
+    One(T),
+    Many(Vec<T>),
+}
+
+impl<T> OneOrMany<T> {
+    /// Returns the value(s) as a list.
+    fn into_vec(self) -> Vec<T> {
+        match self {
+            Self::One(value)   => vec![ value ],
+            Self::Many(values) => values,
+        }
+    }
+}
+
+
+
+/// Defines the (supported parts of the) top-level CWL documents.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "class")]
+enum CwlDocument {
+    CommandLineTool(CwlTool),
+    Workflow(CwlWorkflow),
+}
+
+/// Defines the (supported parts of a) CWL CommandLineTool.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CwlTool {
+    id           : Option<String>,
+    label        : Option<String>,
+    doc          : Option<OneOrMany<String>>,
+    #[serde(rename = "s:softwareVersion")]
+    version      : Option<String>,
+    base_command : Option<OneOrMany<String>>,
+    #[serde(default)]
+    arguments    : Vec<CwlArgument>,
+    #[serde(default)]
+    inputs       : YValue,
+    #[serde(default)]
+    outputs      : YValue,
+    stdout       : Option<String>,
+    #[serde(default)]
+    requirements : YValue,
+    #[serde(default)]
+    hints        : YValue,
+}
+
+/// Defines the (supported parts of a) CWL Workflow.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CwlWorkflow {
+    id           : Option<String>,
+    label        : Option<String>,
+    doc          : Option<OneOrMany<String>>,
+    #[serde(rename = "s:softwareVersion")]
+    version      : Option<String>,
+    #[serde(default)]
+    steps        : YValue,
+    #[serde(default)]
+    requirements : YValue,
+    #[serde(default)]
+    hints        : YValue,
+}
+
+/// Defines the (supported parts of a) step in a CWL Workflow.
+#[derive(Clone, Debug, Deserialize)]
+struct CwlStep {
+    run : YValue,
+}
+
+/// Defines the (supported parts of an) input of a CommandLineTool.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CwlInput {
+    #[serde(rename = "type")]
+    data_type     : YValue,
+    default       : Option<YValue>,
+    input_binding : Option<CwlBinding>,
+}
+
+/// Defines the (supported parts of an) output of a CommandLineTool.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CwlOutput {
+    #[serde(rename = "type")]
+    data_type      : YValue,
+    output_binding : Option<CwlOutputBinding>,
+}
+
+/// Defines how an input or argument ends up on the command line.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CwlBinding {
+    position       : Option<i64>,
+    prefix         : Option<String>,
+    separate       : Option<bool>,
+    item_separator : Option<String>,
+    value_from     : Option<String>,
+}
+
+/// Defines how the value of an output is found.
+#[derive(Clone, Debug, Deserialize)]
+struct CwlOutputBinding {
+    glob : Option<String>,
+}
+
+/// Defines an additional argument of a CommandLineTool.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+enum CwlArgument {
+    Literal(String),
+    Binding(CwlBinding),
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Turns a CWL identifier (e.g., `#main/input-file`) into a Brane identifier (e.g., `input_file`).
+///
+/// # Arguments
+/// - `id`: The CWL identifier to convert.
+///
+/// # Returns
+/// The last part of the identifier, with anything but alphanumeric characters replaced by underscores.
+fn identifier(id: &str) -> String {
+    let id: &str = id.rsplit(|c| c == '/' || c == '#').next().unwrap_or(id);
+    let mut res: String = id.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect();
+    if res.chars().next().map(|c| c.is_ascii_digit()).unwrap_or(true) { res.insert(0, '_'); }
+    res
+}
+
+/// Quotes the given string for use in a shell script.
+///
+/// # Arguments
+/// - `value`: The string to quote.
+///
+/// # Returns
+/// The string in single quotes, with any single quotes in it escaped.
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Returns whether the given string uses CWL expressions, which we do not support.
+#[inline]
+fn is_expression(value: &str) -> bool { value.contains("$(") || value.contains("${") }
+
+/// Translates a CWL type to a Brane type.
+///
+/// # Arguments
+/// - `data_type`: The CWL type to translate.
+///
+/// # Returns
+/// The Brane type (as used in `container.yml`) and whether the CWL type is optional, or None if we don't support the type.
+fn translate_type(data_type: &YValue) -> Option<(String, bool)> {
+    match data_type {
+        YValue::String(name) => {
+            let (name, optional): (&str, bool) = match name.strip_suffix('?') {
+                Some(name) => (name, true),
+                None       => (name, false),
+            };
+            if let Some(elem) = name.strip_suffix("[]") {
+                return match translate_type(&YValue::String(elem.into())) {
+                    Some((elem, false)) if !elem.ends_with("[]") => Some((format!("{}[]", elem), optional)),
+                    _                                           => None,
+                };
+            }
+            let name: &str = match name {
+                "string"           => "string",
+                "int" | "long"     => "integer",
+                "float" | "double" => "real",
+                "boolean"          => "boolean",
+                _                  => { return None; },
+            };
+            Some((name.into(), optional))
+        },
+
+        // Unions are only supported to make types optional
+        YValue::Sequence(types) => {
+            let non_null: Vec<&YValue> = types.iter().filter(|t| t.as_str() != Some("null")).collect();
+            if non_null.len() != 1 { return None; }
+            translate_type(non_null[0]).map(|(name, optional)| (name, optional || non_null.len() < types.len()))
+        },
+
+        YValue::Mapping(_) => {
+            if data_type.get("type").and_then(YValue::as_str) != Some("array") { return None; }
+            match translate_type(data_type.get("items")?)? {
+                (elem, false) if !elem.ends_with("[]") => Some((format!("{}[]", elem), false)),
+                _                                      => None,
+            }
+        },
+
+        _ => None,
+    }
+}
+
+/// Normalizes a list of CWL objects that may be given as a map (by identifier) or as a list (with `id` fields).
+///
+/// Values in the map form that are not objects are taken as the `type` of the object, as CWL allows for inputs and outputs.
+///
+/// # Arguments
+/// - `file`: The file we are parsing (for debugging purposes).
+/// - `what`: What kind of objects we are parsing (for debugging purposes).
+/// - `raw`: The raw objects to normalize.
+///
+/// # Returns
+/// The identifiers and the parsed objects, in the order they are defined.
+///
+/// # Errors
+/// This function errors if the objects are neither a map nor a list, if an object in a list does not have an identifier or if an object could not be parsed.
+fn normalize<T: DeserializeOwned>(file: &Path, what: &str, raw: YValue) -> Result<Vec<(String, T)>, BuildError> {
+    let entries: Vec<(String, YValue)> = match raw {
+        YValue::Null          => vec![],
+        YValue::Mapping(map)  => map.into_iter().map(|(id, value)| (id.as_str().unwrap_or_default().into(), value)).collect(),
+        YValue::Sequence(seq) => {
+            let mut entries: Vec<(String, YValue)> = Vec::with_capacity(seq.len());
+            for value in seq {
+                match value.get("id").and_then(YValue::as_str) {
+                    Some(id) => { entries.push((id.into(), value.clone())); },
+                    None     => { return Err(BuildError::CwlDocumentIllegal{ file: file.into(), reason: format!("one of the {} does not have an 'id'", what) }); },
+                }
+            }
+            entries
+        },
+        _ => { return Err(BuildError::CwlDocumentIllegal{ file: file.into(), reason: format!("the {} are neither a map nor a list", what) }); },
+    };
+
+    // Parse the objects themselves
+    let mut res: Vec<(String, T)> = Vec::with_capacity(entries.len());
+    for (id, value) in entries {
+        let value: YValue = if value.is_mapping() { value } else {
+            let mut map: Mapping = Mapping::new();
+            map.insert("type".into(), value);
+            YValue::Mapping(map)
+        };
+        match serde_yaml::from_value(value) {
+            Ok(value) => { res.push((identifier(&id), value)); },
+            Err(err)  => { return Err(BuildError::CwlDocumentIllegal{ file: file.into(), reason: format!("could not parse '{}' in the {}: {}", id, what, err) }); },
+        }
+    }
+    Ok(res)
+}
+
+/// Collects the requirements (or hints) we understand from a CWL document.
+///
+/// # Arguments
+/// - `file`: The file we are parsing (for debugging purposes).
+/// - `raw`: The raw requirements, as either a list of objects with a `class` or a map by class.
+/// - `hints`: Whether these are hints instead of requirements (for debugging purposes).
+/// - `image`: The Docker image to run in, which is updated if the requirements specify one.
+/// - `environment`: The environment variables to set, which is extended with the ones in the requirements.
+///
+/// # Errors
+/// This function errors if the requirements could not be parsed, if they specify a different image than the one already given or if they use expressions.
+fn collect_requirements(file: &Path, raw: YValue, hints: bool, image: &mut Option<String>, environment: &mut HashMap<String, String>) -> Result<(), BuildError> {
+    let what: &str = if hints { "hints" } else { "requirements" };
+    let requirements: Vec<(String, YValue)> = match raw {
+        YValue::Null          => vec![],
+        YValue::Mapping(map)  => map.into_iter().map(|(class, value)| (class.as_str().unwrap_or_default().into(), value)).collect(),
+        YValue::Sequence(seq) => seq.into_iter().map(|value| (value.get("class").and_then(YValue::as_str).unwrap_or_default().into(), value)).collect(),
+        _                     => { return Err(BuildError::CwlDocumentIllegal{ file: file.into(), reason: format!("the {} are neither a map nor a list", what) }); },
+    };
+
+    for (class, value) in requirements {
+        match class.as_str() {
+            "DockerRequirement" => {
+                let pull: &str = match value.get("dockerPull").and_then(YValue::as_str) {
+                    Some(pull) => pull,
+                    None       => { warn!("Ignoring DockerRequirement in the {} of '{}' without 'dockerPull'", what, file.display()); continue; },
+                };
+                match image {
+                    Some(image) if image != pull => { return Err(BuildError::CwlConflictingImages{ file: file.into(), image1: image.clone(), image2: pull.into() }); },
+                    _                            => { *image = Some(pull.into()); },
+                }
+            },
+
+            "EnvVarRequirement" => {
+                let defs: Vec<(String, String)> = match value.get("envDef") {
+                    Some(YValue::Mapping(map))  => map.iter().map(|(name, value)| (name.as_str().unwrap_or_default().into(), value.as_str().unwrap_or_default().into())).collect(),
+                    Some(YValue::Sequence(seq)) => seq.iter().map(|def| (
+                        def.get("envName").and_then(YValue::as_str).unwrap_or_default().into(),
+                        def.get("envValue").and_then(YValue::as_str).unwrap_or_default().into(),
+                    )).collect(),
+                    _ => vec![],
+                };
+                for (name, value) in defs {
+                    if is_expression(&value) { return Err(BuildError::CwlUnsupported{ file: file.into(), what: format!("expressions (in environment variable '{}')", name) }); }
+                    environment.insert(name, value);
+                }
+            },
+
+            class => { warn!("Ignoring unsupported CWL {} '{}' in '{}'", if hints { "hint" } else { "requirement" }, class, file.display()); },
+        }
+    }
+    Ok(())
+}
+
+/// Translates a single CommandLineTool into a package function.
+///
+/// # Arguments
+/// - `file`: The file that defines the tool (for debugging purposes).
+/// - `name`: The name of the function.
+/// - `tool`: The CommandLineTool to translate.
+/// - `script`: The script that runs the tools, to which we add the part that runs this one.
+///
+/// # Returns
+/// The Action that describes the function, and whether the part of the script that runs it uses `jq` (to decode inputs or encode the output).
+///
+/// # Errors
+/// This function errors if the tool uses parts of CWL that we do not support.
+fn translate_tool(file: &Path, name: &str, tool: &CwlTool, script: &mut String) -> Result<(Action, bool), BuildError> {
+    let unsupported = |what: String| BuildError::CwlUnsupported{ file: file.into(), what };
+
+    // Collect the lines that add every argument and input to the command line, together with their position
+    let mut bindings: Vec<(i64, Vec<String>)> = vec![];
+    for arg in &tool.arguments {
+        let (binding, value): (CwlBinding, String) = match arg {
+            CwlArgument::Literal(value) => (CwlBinding::default(), value.clone()),
+            CwlArgument::Binding(binding) => match &binding.value_from {
+                Some(value) => (binding.clone(), value.clone()),
+                None        => { continue; },
+            },
+        };
+        if is_expression(&value) { return Err(unsupported(format!("expressions (in the arguments of '{}')", name))); }
+        let line: String = match (&binding.prefix, binding.separate.unwrap_or(true)) {
+            (Some(prefix), true)  => format!("set -- \"$@\" {} {}", quote(prefix), quote(&value)),
+            (Some(prefix), false) => format!("set -- \"$@\" {}", quote(&format!("{}{}", prefix, value))),
+            (None, _)             => format!("set -- \"$@\" {}", quote(&value)),
+        };
+        bindings.push((binding.position.unwrap_or(0), vec![ line ]));
+    }
+
+    // Inputs are sorted by name if they have the same position
+    let mut inputs: Vec<(String, CwlInput)> = normalize(file, "inputs", tool.inputs.clone())?;
+    inputs.sort_by(|(n1, _), (n2, _)| n1.cmp(n2));
+    let mut params: Vec<Parameter> = Vec::with_capacity(inputs.len());
+    let mut uses_jq: bool = false;
+    for (iname, input) in inputs {
+        let (data_type, optional): (String, bool) = match translate_type(&input.data_type) {
+            Some(res) => res,
+            None      => { return Err(unsupported(format!("the type of input '{}' of '{}' ({})", iname, name, serde_json::to_string(&input.data_type).unwrap_or_default()))); },
+        };
+        let default: Option<serde_json::Value> = input.default.as_ref().and_then(|d| serde_json::to_value(d).ok());
+        params.push(Parameter::new(iname.clone(), data_type.clone(), if optional { Some(true) } else { None }, default, None));
+
+        // Inputs without a binding are not passed on the command line
+        let binding: &CwlBinding = match &input.input_binding {
+            Some(binding) => binding,
+            None          => { continue; },
+        };
+        if binding.value_from.is_some() { return Err(unsupported(format!("'valueFrom' (in the binding of input '{}' of '{}')", iname, name))); }
+
+        // The branelet passes the inputs as JSON in uppercase environment variables, which only booleans can be compared to without decoding them first
+        let var: String = iname.to_ascii_uppercase();
+        uses_jq |= data_type != "boolean";
+        let mut lines: Vec<String> = vec![ format!("if [ -n \"${var}\" ] && [ \"${var}\" != \"null\" ]; then", var = var) ];
+        let separate: bool = binding.separate.unwrap_or(true);
+        if data_type == "boolean" {
+            if let Some(prefix) = &binding.prefix { lines.push(format!("    if [ \"${}\" = \"true\" ]; then set -- \"$@\" {}; fi", var, quote(prefix))); }
+        } else if data_type.ends_with("[]") && binding.item_separator.is_none() {
+            if let Some(prefix) = &binding.prefix { lines.push(format!("    set -- \"$@\" {}", quote(prefix))); }
+            lines.push(format!("    eval \"set -- \\\"\\$@\\\" $(printf '%s' \"${}\" | jq -r 'map(tostring) | @sh')\"", var));
+        } else {
+            let filter: String = match &binding.item_separator {
+                Some(sep) => format!("map(tostring) | join({})", serde_json::to_string(sep).unwrap_or_default()),
+                None      => "tostring".into(),
+            };
+            lines.push(format!("    value=$(printf '%s' \"${}\" | jq -r {})", var, quote(&filter)));
+            lines.push(match (&binding.prefix, separate) {
+                (Some(prefix), true)  => format!("    set -- \"$@\" {} \"$value\"", quote(prefix)),
+                (Some(prefix), false) => format!("    set -- \"$@\" {}\"$value\"", quote(prefix)),
+                (None, _)             => "    set -- \"$@\" \"$value\"".into(),
+            });
+        }
+        lines.push("fi".into());
+        bindings.push((binding.position.unwrap_or(0), lines));
+    }
+    bindings.sort_by_key(|(position, _)| *position);
+
+    // Find the output (if any), which is either the tool's stdout or read from a file
+    let outputs: Vec<(String, CwlOutput)> = normalize(file, "outputs", tool.outputs.clone())?;
+    if outputs.len() > 1 { return Err(unsupported(format!("multiple outputs (in '{}')", name))); }
+    let output: Option<(String, String, String, bool)> = match outputs.into_iter().next() {
+        Some((oname, output)) => if output.data_type.as_str() == Some("stdout") {
+            let path: String = tool.stdout.clone().unwrap_or_else(|| DEFAULT_STDOUT.into());
+            if is_expression(&path) { return Err(unsupported(format!("expressions (in the stdout of '{}')", name))); }
+            Some((oname, "string".into(), path, true))
+        } else {
+            let data_type: String = match translate_type(&output.data_type) {
+                Some((data_type, _)) if !data_type.ends_with("[]") => data_type,
+                _                                                  => { return Err(unsupported(format!("the type of output '{}' of '{}' ({})", oname, name, serde_json::to_string(&output.data_type).unwrap_or_default()))); },
+            };
+            match output.output_binding.and_then(|b| b.glob) {
+                Some(glob) if !is_expression(&glob) && !glob.contains(['*', '?', '[']) => Some((oname, data_type, glob, false)),
+                _ => { return Err(unsupported(format!("outputs that are not read from a single, literal file (output '{}' of '{}')", oname, name))); },
+            }
+        },
+        None => None,
+    };
+
+    // Write the part of the script that runs this tool
+    let base: Vec<String> = tool.base_command.clone().map(OneOrMany::into_vec).unwrap_or_default();
+    if base.is_empty() && bindings.is_empty() { return Err(unsupported(format!("tools without a command line ('{}')", name))); }
+    let s: &mut String = script;
+    writeln_build!(s, "{})", quote(name))?;
+    writeln_build!(s, "    set -- {}", base.iter().map(|b| quote(b)).collect::<Vec<String>>().join(" "))?;
+    for (_, lines) in &bindings {
+        for line in lines { writeln_build!(s, "    {}", line)?; }
+    }
+    match &output {
+        Some((oname, data_type, path, stdout)) => {
+            uses_jq = true;
+            if *stdout { writeln_build!(s, "    \"$@\" > {}", quote(path))?; } else { writeln_build!(s, "    \"$@\"")?; }
+            let value: &str = match data_type.as_str() {
+                "integer" | "real" => "(gsub(\"^\\\\s+|\\\\s+$\"; \"\") | tonumber)",
+                "boolean"          => "(gsub(\"^\\\\s+|\\\\s+$\"; \"\") == \"true\")",
+                _                  => ".",
+            };
+            writeln_build!(s, "    echo '--> START CAPTURE'")?;
+            writeln_build!(s, "    jq -Rs {} < {}", quote(&format!("{{\"{}\": {}}}", oname, value)), quote(path))?;
+            writeln_build!(s, "    echo '--> END CAPTURE'")?;
+        },
+        None => { writeln_build!(s, "    \"$@\"")?; },
+    }
+    writeln_build!(s, "    ;;")?;
+
+    // Done
+    let description: Option<String> = tool.doc.clone().map(|d| d.into_vec().join("\n")).or_else(|| tool.label.clone());
+    Ok((Action {
+        requirements : None,
+        command      : Some(ActionCommand{ args: vec![ name.into() ], capture: Some("marked".into()) }),
+        description,
+        endpoint     : None,
+        pattern      : None,
+        input        : Some(params),
+        output       : output.map(|(oname, data_type, _, _)| vec![ Parameter::new(oname, data_type, None, None, None) ]),
+        deprecated   : None,
+        aliases      : None,
+    }, uses_jq))
+}
+
+/// Reads and parses a CWL document.
+///
+/// # Arguments
+/// - `file`: The path of the document to read.
+///
+/// # Returns
+/// The parsed CwlDocument.
+///
+/// # Errors
+/// This function errors if we failed to read or parse the file.
+fn read_document(file: &Path) -> Result<CwlDocument, BuildError> {
+    let raw: String = match fs::read_to_string(file) {
+        Ok(raw)  => raw,
+        Err(err) => { return Err(BuildError::CwlDocumentReadError{ file: file.into(), err }); },
+    };
+    match serde_yaml::from_str(&raw) {
+        Ok(document) => Ok(document),
+        Err(err)     => Err(BuildError::CwlDocumentParseError{ file: file.into(), err }),
+    }
+}
+
+/// Translates a CWL document into an ECU container file and the script that runs its tools.
+///
+/// # Arguments
+/// - `file`: The path of the CWL document.
+///
+/// # Returns
+/// The ContainerInfo of the package, and the contents of the script it runs.
+///
+/// # Errors
+/// This function errors if we failed to read the document (or any tool it references) or if it uses parts of CWL that we do not support.
+fn translate(file: &Path) -> Result<(ContainerInfo, String), BuildError> {
+    let stem: String = file.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_else(|| "cwl".into());
+
+    // Collect the tools to translate, together with their function name and the file that defines them
+    let mut image       : Option<String>          = None;
+    let mut environment : HashMap<String, String> = HashMap::new();
+    let (id, label, doc, version, tools): (Option<String>, Option<String>, Option<OneOrMany<String>>, Option<String>, Vec<(String, PathBuf, CwlTool)>) = match read_document(file)? {
+        CwlDocument::CommandLineTool(tool) => {
+            let name: String = identifier(tool.id.as_deref().unwrap_or(&stem));
+            (tool.id.clone(), tool.label.clone(), tool.doc.clone(), tool.version.clone(), vec![ (name, file.into(), tool) ])
+        },
+
+        CwlDocument::Workflow(workflow) => {
+            collect_requirements(file, workflow.requirements, false, &mut image, &mut environment)?;
+            collect_requirements(file, workflow.hints, true, &mut image, &mut environment)?;
+
+            // Every step becomes a function that runs its tool
+            let mut tools: Vec<(String, PathBuf, CwlTool)> = vec![];
+            for (sname, step) in normalize::<CwlStep>(file, "steps", workflow.steps)? {
+                let (tfile, document): (PathBuf, CwlDocument) = match step.run {
+                    YValue::String(path) => {
+                        let path: PathBuf = file.parent().map(|p| p.join(&path)).unwrap_or_else(|| path.into());
+                        let document: CwlDocument = read_document(&path)?;
+                        (path, document)
+                    },
+                    run => match serde_yaml::from_value(run) {
+                        Ok(document) => (file.into(), document),
+                        Err(err)     => { return Err(BuildError::CwlDocumentParseError{ file: file.into(), err }); },
+                    },
+                };
+                match document {
+                    CwlDocument::CommandLineTool(tool) => { tools.push((sname, tfile, tool)); },
+                    CwlDocument::Workflow(_)           => { return Err(BuildError::CwlUnsupported{ file: file.into(), what: format!("nested workflows (in step '{}')", sname) }); },
+                }
+            }
+            (workflow.id, workflow.label, workflow.doc, workflow.version, tools)
+        },
+    };
+
+    // Translate the tools
+    let mut script: String = String::new();
+    writeln_build!(script, "#!/bin/sh")?;
+    writeln_build!(script, "# Generated by Brane from '{}'", file.display())?;
+    writeln_build!(script, "set -e")?;
+    writeln_build!(script)?;
+    writeln_build!(script, "function=\"$1\"")?;
+    writeln_build!(script, "case \"$function\" in")?;
+    let mut actions: HashMap<String, Action> = HashMap::with_capacity(tools.len());
+    let mut uses_jq: bool = false;
+    for (name, tfile, tool) in tools {
+        collect_requirements(&tfile, tool.requirements.clone(), false, &mut image, &mut environment)?;
+        collect_requirements(&tfile, tool.hints.clone(), true, &mut image, &mut environment)?;
+        let (action, tool_uses_jq): (Action, bool) = translate_tool(&tfile, &name, &tool, &mut script)?;
+        actions.insert(name, action);
+        uses_jq |= tool_uses_jq;
+    }
+    writeln_build!(script, "*)")?;
+    writeln_build!(script, "    echo \"Unknown function '$function'\" >&2")?;
+    writeln_build!(script, "    exit 1")?;
+    writeln_build!(script, "    ;;")?;
+    writeln_build!(script, "esac")?;
+    if image.is_none() { warn!("CWL document '{}' does not specify a Docker image (as 'dockerPull' in a DockerRequirement); the tools must be available in the default base image", file.display()); }
+
+    // Collect the package's metadata
+    let name: String = id.map(|id| identifier(&id)).unwrap_or(stem).to_lowercase().replace('_', "-");
+    let version: Version = match version {
+        Some(version) => match Version::from_str(&version) {
+            Ok(version) => version,
+            Err(err)    => { return Err(BuildError::CwlVersionParseError{ file: file.into(), raw: version, err }); },
+        },
+        None => DEFAULT_VERSION,
+    };
+    let description: Option<String> = doc.map(|d| d.into_vec().join("\n")).or(label);
+
+    // Done
+    Ok((ContainerInfo {
+        name,
+        version,
+        kind        : PackageKind::Ecu,
+        owners      : None,
+        description,
+
+        actions,
+        entrypoint : Entrypoint{ kind: "task".into(), exec: WRAPPER_NAME.into(), content: None, delay: None },
+        types      : None,
+        scratch    : None,
+        resources  : None,
+        secrets    : None,
+
+        base            : image,
+        package_manager : None,
+        stages          : None,
+        dockerfile      : None,
+        // Only install `jq` if the script needs it, since that requires a package manager in the image
+        dependencies    : if uses_jq { Some(vec![ "jq".into() ]) } else { None },
+        environment     : if environment.is_empty() { None } else { Some(environment) },
+        files           : Some(vec![ WRAPPER_NAME.into() ]),
+        initialize      : None,
+        install         : None,
+        unpack          : None,
+    }, script))
+}
+
+
+
+
+
+/***** BUILD FUNCTIONS *****/
+/// Builds an ECU package from a CWL document (either a CommandLineTool or a Workflow whose steps run CommandLineTools).
+///
+/// Every tool becomes a function of the package, which is run by a generated script in the image of the tool's DockerRequirement. Only inputs and outputs of simple types (and arrays of them) are supported, and outputs must either be the tool's stdout or read from a single file. CWL expressions are not supported.
+///
+/// # Arguments
+///  - `arches`: The architectures to compile this image for. The first one is used for the default image.
+///  - `file`: Path to the package's main file (a CWL document, in this case).
+///  - `branelet_path`: Optional path to a custom branelet executable. If left empty, will pull the standard one from Github instead.
+///  - `keep_files`: Determines whether or not to keep the build files after building.
+///
+/// # Errors
+/// This function may error for many reasons.
+pub async fn handle(
+    arches: Vec<Arch>,
+    file: PathBuf,
+    branelet_path: Option<PathBuf>,
+    keep_files: bool,
+) -> Result<(), BuildError> {
+    debug!("Building ecu package from CWL document '{}'...", file.display());
+
+    // Translate the document to a container file
+    let (document, script): (ContainerInfo, String) = translate(&file)?;
+    debug!("Using generated script:\n\n{}\n{}\n{}\n\n", (0..80).map(|_| '-').collect::<String>(), &script, (0..80).map(|_| '-').collect::<String>());
+
+    // Write the script to a temporary directory we can build from
+    let context = match tempfile::tempdir() {
+        Ok(context) => context,
+        Err(err)    => { return Err(BuildError::CwlContextCreateError{ err }); },
+    };
+    let script_path: PathBuf = context.path().join(WRAPPER_NAME);
+    if let Err(err) = fs::write(&script_path, script) { return Err(BuildError::CwlScriptWriteError{ path: script_path, err }); }
+
+    // Prepare package directory
+    let package_dir = match ensure_package_dir(&document.name, Some(&document.version), true) {
+        Ok(package_dir) => package_dir,
+        Err(err)        => { return Err(BuildError::PackageDirError{ err }); }
+    };
+
+    // Lock the directory, build, unlock the directory
+    {
+        let _lock = LockHandle::lock(&document.name, package_dir.join(".lock"))?;
+        build_ecu::build(arches, document, context.path().into(), &package_dir, branelet_path, keep_files, false).await?;
+    };
+
+    // Done
+    Ok(())
+}
@@ -8,31 +8,33 @@ use console::style;
 use fs_extra::dir::CopyOptions;
 use path_clean::clean as clean_path;
 
-use specifications::arch::Arch;
-use specifications::container::{ContainerInfo, LocalContainerInfo};
+use specifications::arch::{Arch, ManifestList};
+use specifications::container::{ContainerInfo, LocalContainerInfo, PackageManager};
 use specifications::package::PackageInfo;
 
-use crate::build_common::{BRANELET_URL, build_docker_image, clean_directory, LockHandle};
+use crate::build_common::{BRANELET_URL, DEV_INFO_FILE, build_docker_image, clean_directory, write_manifest_list, DevInfo, LockHandle};
 use crate::errors::BuildError;
 use crate::utils::ensure_package_dir;
 
 
 /***** BUILD FUNCTIONS *****/
 /// # Arguments
-///  - `arch`: The architecture to compile this image for.
+///  - `arches`: The architectures to compile this image for. The first one is used for the default image.
 ///  - `context`: The directory to copy additional files (executable, working directory files) from.
 ///  - `file`: Path to the package's main file (a container file, in this case).
 ///  - `branelet_path`: Optional path to a custom branelet executable. If left empty, will pull the standard one from Github instead.
 ///  - `keep_files`: Determines whether or not to keep the build files after building.
+///  - `dev`: Whether to build in development mode, i.e., to run the package with its working directory mounted from `context` (see `build()`).
 /// 
 /// # Errors
 /// This function may error for many reasons.
 pub async fn handle(
-    arch: Arch,
+    arches: Vec<Arch>,
     context: PathBuf,
     file: PathBuf,
     branelet_path: Option<PathBuf>,
     keep_files: bool,
+    dev: bool,
 ) -> Result<(), BuildError> {
     debug!("Building ecu package from container file '{}'...", file.display());
     debug!("Using {} as build context", context.display());
@@ -57,7 +59,7 @@ pub async fn handle(
     // Lock the directory, build, unlock the directory
     {
         let _lock = LockHandle::lock(&document.name, package_dir.join(".lock"))?;
-        build(arch, document, context, &package_dir, branelet_path, keep_files).await?;
+        build(arches, document, context, &package_dir, branelet_path, keep_files, dev).await?;
     };
 
     // Done
@@ -68,26 +70,51 @@ pub async fn handle(
 
 /// Actually builds a new Ecu package from the given file(s).
 /// 
+/// In development mode, the image of a previous development build is reused as long as nothing but the working directory files (and the functions of the package) changed, and local runs of the package mount its working directory from `context` instead of taking it from the image. Changes to the package code thus take effect without rebuilding.
+/// 
 /// # Arguments
-///  - `arch`: The architecture to compile this image for.
+///  - `arches`: The architectures to compile this image for. The first one is used for the default image.
 ///  - `document`: The ContainerInfo document describing the package.
 ///  - `context`: The directory to copy additional files (executable, working directory files) from.
 ///  - `package_dir`: The package directory to use as the build folder.
 ///  - `branelet_path`: Optional path to a custom branelet executable. If left empty, will pull the standard one from Github instead.
 ///  - `keep_files`: Determines whether or not to keep the build files after building.
+///  - `dev`: Whether to build in development mode.
 /// 
 /// # Errors
 /// This function may error for many reasons.
-async fn build(
-    arch: Arch,
+pub(crate) async fn build(
+    arches: Vec<Arch>,
     document: ContainerInfo,
     context: PathBuf,
     package_dir: &Path,
     branelet_path: Option<PathBuf>,
     keep_files: bool,
+    dev: bool,
 ) -> Result<(), BuildError> {
-    // Prepare the build directory
     let dockerfile = generate_dockerfile(&document, &context, branelet_path.is_some())?;
+    let dev_info: DevInfo = DevInfo::new(&context, &dockerfile);
+
+    // In development mode, see if we can get away with the image of the previous build
+    if dev {
+        if let Some(digest) = reusable_digest(&arches, package_dir, &dev_info) {
+            let mut package_info = PackageInfo::from(document);
+            package_info.digest = Some(digest);
+            if let Err(err) = package_info.to_path(package_dir.join("package.yml")) {
+                return Err(BuildError::PackageFileCreateError{ err });
+            }
+            dev_info.to_package_dir(package_dir)?;
+            println!(
+                "Reused image of version {} of container (ECU) package {}; its working directory is mounted from '{}'.",
+                style(&package_info.version).bold().cyan(),
+                style(&package_info.name).bold().cyan(),
+                context.display(),
+            );
+            return Ok(());
+        }
+    }
+
+    // Prepare the build directory
     prepare_directory(
         &document,
         dockerfile,
@@ -100,7 +127,7 @@ async fn build(
     // Build Docker image
     let tag = format!("{}:{}", document.name, document.version);
     debug!("Building image '{}' in directory '{}'", tag, package_dir.display());
-    match build_docker_image(arch, package_dir, tag) {
+    match build_docker_image(&arches, package_dir, tag) {
         Ok(_) => {
             println!(
                 "Successfully built version {} of container (ECU) package {}.",
@@ -110,16 +137,21 @@ async fn build(
 
             // Create a PackageInfo and resolve the hash
             let mut package_info = PackageInfo::from(document);
-            match brane_tsk::docker::get_digest(package_dir.join("image.tar")).await {
-                Ok(digest) => { package_info.digest = Some(digest); },
-                Err(err)   => { return Err(BuildError::DigestError{ err }); }
-            }
+            let list: ManifestList = write_manifest_list(&arches, package_dir).await?;
+            package_info.digest = list.images[0].digest.clone();
 
             // Write it to package directory
             let package_path = package_dir.join("package.yml");
             if let Err(err) = package_info.to_path(&package_path) {
                 return Err(BuildError::PackageFileCreateError{ err });
             }
+
+            // Mark whether the package runs with its working directory from the host or from its image
+            if dev {
+                dev_info.to_package_dir(package_dir)?;
+            } else if package_dir.join(DEV_INFO_FILE).exists() {
+                if let Err(err) = fs::remove_file(package_dir.join(DEV_INFO_FILE)) { return Err(BuildError::FileCleanupError{ path: package_dir.join(DEV_INFO_FILE), err }); }
+            }
     
             // // Check if previous build is still loaded in Docker
             // let image_name = format!("{}:{}", package_info.name, package_info.version);
@@ -155,6 +187,31 @@ async fn build(
     Ok(())
 }
 
+/// Checks whether the image of a previous development build in the given package directory may be reused.
+/// 
+/// **Arguments**
+///  * `arches`: The architectures to build the image for.
+///  * `package_dir`: The package directory with the previous build.
+///  * `dev_info`: The DevInfo of the current build.
+/// 
+/// **Returns**  
+/// The digest of the previous image if it was built in development mode from the same Dockerfile for the same architectures, or `None` if we have to rebuild.
+fn reusable_digest(arches: &[Arch], package_dir: &Path, dev_info: &DevInfo) -> Option<String> {
+    let previous: DevInfo = DevInfo::from_package_dir(package_dir)?;
+    if previous.dockerfile != dev_info.dockerfile {
+        debug!("Dockerfile changed since the previous development build; rebuilding image");
+        return None;
+    }
+    if arches.iter().enumerate().any(|(i, arch)| !package_dir.join(ManifestList::image_file(*arch, i == 0)).exists()) {
+        debug!("Previous development build lacks an image for one of the architectures; rebuilding image");
+        return None;
+    }
+    match PackageInfo::from_path(package_dir.join("package.yml")) {
+        Ok(info) => info.digest,
+        Err(err) => { debug!("Failed to read package info of previous development build: {} (rebuilding image)", err); None },
+    }
+}
+
 /// **Edited: now returning BuildErrors.**
 /// 
 /// Generates a new DockerFile that can be used to build the package into a Docker container.
@@ -175,9 +232,25 @@ fn generate_dockerfile(
 
     // Get the base image from the document
     let base = document.base.clone().unwrap_or_else(|| String::from("ubuntu:20.04"));
+    let package_manager: PackageManager = document.package_manager.unwrap_or_else(|| PackageManager::guess(&base));
 
     // Add default heading
     writeln_build!(contents, "# Generated by Brane")?;
+
+    // Add any build stages first, each in their own image
+    for stage in document.stages.iter().flatten() {
+        writeln_build!(contents, "FROM {} AS {}", stage.base, stage.name)?;
+        writeln_build!(contents, "WORKDIR /build")?;
+        if stage.files.as_ref().map(|files| !files.is_empty()).unwrap_or(false) {
+            writeln_build!(contents, "COPY ./container/stages/{} /build", stage.name)?;
+        }
+        for line in stage.run.iter().flatten() {
+            writeln_build!(contents, "RUN {}", line)?;
+        }
+        writeln_build!(contents)?;
+    }
+
+    // The package image itself
     writeln_build!(contents, "FROM {}", base)?;
 
     // Set the architecture build args
@@ -191,21 +264,27 @@ fn generate_dockerfile(
         }
     }
 
-    // Add dependencies; write the apt-get RUN command with space for packages
-    if base.starts_with("alpine") {
-        write_build!(contents, "RUN apk add --no-cache ")?;
-    } else {
-        write_build!(contents, "RUN apt-get update && DEBIAN_FRONTEND=noninteractive apt-get install -y --allow-change-held-packages --allow-downgrades ")?;
+    // Add dependencies; write the package manager's RUN command with space for packages
+    match package_manager {
+        PackageManager::Apt  => { write_build!(contents, "RUN apt-get update && DEBIAN_FRONTEND=noninteractive apt-get install -y --allow-change-held-packages --allow-downgrades ")?; },
+        PackageManager::Apk  => { write_build!(contents, "RUN apk add --no-cache ")?; },
+        PackageManager::Dnf  => { write_build!(contents, "RUN dnf install -y ")?; },
+        PackageManager::None => {
+            // We cannot install anything, not even the defaults
+            if document.dependencies.as_ref().map(|deps| !deps.is_empty()).unwrap_or(false) { return Err(BuildError::NoPackageManager); }
+        },
     }
-    // Default dependencies
-    write_build!(contents, "fuse iptables ")?;
-    // Custom dependencies
-    if let Some(dependencies) = &document.dependencies {
-        for dependency in dependencies {
-            write_build!(contents, "{} ", dependency)?;
+    if package_manager != PackageManager::None {
+        // Default dependencies
+        write_build!(contents, "fuse iptables ")?;
+        // Custom dependencies
+        if let Some(dependencies) = &document.dependencies {
+            for dependency in dependencies {
+                write_build!(contents, "{} ", dependency)?;
+            }
         }
+        writeln_build!(contents)?;
     }
-    writeln_build!(contents)?;
 
     // Add the branelet executable
     if override_branelet {
@@ -225,6 +304,13 @@ fn generate_dockerfile(
         }
     }
 
+    // Add any custom instructions
+    if let Some(dockerfile) = &document.dockerfile {
+        for line in dockerfile {
+            writeln_build!(contents, "{}", line)?;
+        }
+    }
+
     // // Add JuiceFS
     // writeln_build!(contents, "RUN mkdir /data")?;
     // writeln_build!(contents, "ADD https://github.com/juicedata/juicefs/releases/download/v0.12.1/juicefs-0.12.1-linux-$JUICEFS_ARCH.tar.gz /juicefs-0.12.1-linux-$JUICEFS_ARCH.tar.gz")?;
@@ -242,6 +328,14 @@ fn generate_dockerfile(
     if !entrypoint.exists() || !entrypoint.is_file() { return Err(BuildError::MissingExecutable{ path: entrypoint }); }
     writeln_build!(contents, "RUN chmod +x /opt/wd/{}", &document.entrypoint.exec)?;
 
+    // Copy the artifacts of the build stages
+    for stage in document.stages.iter().flatten() {
+        for artifact in stage.artifacts.iter().flatten() {
+            let (source, target): (String, String) = stage_artifact_paths(artifact)?;
+            writeln_build!(contents, "COPY --from={} {} {}", stage.name, source, target)?;
+        }
+    }
+
     // Add the post-installation script
     if let Some(install) = &document.unpack {
         for line in install {
@@ -257,6 +351,69 @@ fn generate_dockerfile(
     Ok(contents)
 }
 
+/// Resolves where an artifact of a build stage is found in the stage, and where it should go in the package image.
+/// 
+/// **Arguments**
+///  * `artifact`: The path of the artifact as given in the container file.
+/// 
+/// **Returns**  
+/// A tuple of the path in the stage and the path in the package image, or a BuildError if the path tries to escape the stage's build directory.
+fn stage_artifact_paths(artifact: &str) -> Result<(String, String), BuildError> {
+    let path: String = clean_path(artifact);
+    if path.starts_with('/') { return Ok((path.clone(), path)); }
+    if path.contains("..") { return Err(BuildError::UnsafePath{ path }); }
+    Ok((format!("/build/{}", path), format!("/opt/wd/{}", path)))
+}
+
+/// Copies the given files from the build context to the given directory.
+/// 
+/// **Arguments**
+///  * `files`: The paths of the files (or directories) to copy, relative to the build context.
+///  * `context`: The directory to copy the files from.
+///  * `target_dir`: The directory to copy the files to, at the same relative paths.
+/// 
+/// **Returns**  
+/// Nothing if the files were copied successfully, or a BuildError otherwise.
+fn copy_context_files(files: &[String], context: &Path, target_dir: &Path) -> Result<(), BuildError> {
+    for file_path in files {
+        // Make sure the target path is safe (does not escape the working directory)
+        let target = clean_path(file_path);
+        if target.contains("..") { return Err(BuildError::UnsafePath{ path: target }) }
+        let target = target_dir.join(target);
+        let target = match fs::canonicalize(target.parent().unwrap_or_else(|| panic!("Target file '{}' for package info file does not have a parent; this should never happen!", target.display()))) {
+            Ok(target_dir) => target_dir.join(target.file_name().unwrap_or_else(|| panic!("Target file '{}' for package info file does not have a file name; this should never happen!", target.display()))),
+            Err(err)       => { return Err(BuildError::WdSourceFileCanonicalizeError{ path: target, err }); }
+        };
+        // Create the target folder if it does not exist
+        if let Some(parent) = target.parent() {
+            if !parent.exists() {
+                if let Err(err) = fs::create_dir_all(parent) { return Err(BuildError::WdDirCreateError{ path: parent.to_path_buf(), err }); };
+            }
+        }
+
+        // Resolve the source folder
+        let source = match fs::canonicalize(context.join(file_path)) {
+            Ok(source) => source,
+            Err(err)   => { return Err(BuildError::WdTargetFileCanonicalizeError{ path: target, err }); }
+        };
+
+        // Switch whether it's a directory or a file
+        if source.is_dir() {
+            // Copy everything inside the folder
+            let mut copy_options = CopyOptions::new();
+            copy_options.copy_inside = true;
+            if let Err(err) = fs_extra::dir::copy(&source, &target, &copy_options) { return Err(BuildError::WdDirCopyError{ source, target, err }); }
+        } else {
+            // Copy only the file
+            if let Err(err) = fs::copy(&source, &target) { return Err(BuildError::WdFileCopyError{ source, target, err }); }
+        }
+
+        // Done
+        debug!("Copied {} to {}", source.display(), target.display());
+    }
+    Ok(())
+}
+
 /// **Edited: now returning BuildErrors.**
 /// 
 /// Prepares the build directory for building the package.
@@ -331,42 +488,17 @@ fn prepare_directory(
     }
 
     // Copy any other files marked in the ecu document
-    if let Some(files) = &document.files {
-        for file_path in files {
-            // Make sure the target path is safe (does not escape the working directory)
-            let target = clean_path(file_path);
-            if target.contains("..") { return Err(BuildError::UnsafePath{ path: target }) }
-            let target = wd.join(target);
-            let target = match fs::canonicalize(target.parent().unwrap_or_else(|| panic!("Target file '{}' for package info file does not have a parent; this should never happen!", target.display()))) {
-                Ok(target_dir) => target_dir.join(target.file_name().unwrap_or_else(|| panic!("Target file '{}' for package info file does not have a file name; this should never happen!", target.display()))),
-                Err(err)       => { return Err(BuildError::WdSourceFileCanonicalizeError{ path: target, err }); }
-            };
-            // Create the target folder if it does not exist
-            if let Some(parent) = target.parent() {
-                if !parent.exists() {
-                    if let Err(err) = fs::create_dir_all(parent) { return Err(BuildError::WdDirCreateError{ path: parent.to_path_buf(), err }); };
-                }
-            }
-
-            // Resolve the source folder
-            let source = match fs::canonicalize(context.join(file_path)) {
-                Ok(source) => source,
-                Err(err)   => { return Err(BuildError::WdTargetFileCanonicalizeError{ path: target, err }); }
-            };
-
-            // Switch whether it's a directory or a file
-            if source.is_dir() {
-                // Copy everything inside the folder
-                let mut copy_options = CopyOptions::new();
-                copy_options.copy_inside = true;
-                if let Err(err) = fs_extra::dir::copy(&source, &target, &copy_options) { return Err(BuildError::WdDirCopyError{ source, target, err }); }
-            } else {
-                // Copy only the file
-                if let Err(err) = fs::copy(&source, &target) { return Err(BuildError::WdFileCopyError{ source, target, err }); }
+    if let Some(files) = &document.files { copy_context_files(files, context, &wd)?; }
+
+    // Copy the files of any build stages to their own directories
+    for stage in document.stages.iter().flatten() {
+        if let Some(files) = &stage.files {
+            let stage_dir: PathBuf = container_dir.join("stages").join(&stage.name);
+            if stage_dir.exists() {
+                if let Err(err) = fs::remove_dir_all(&stage_dir) { return Err(BuildError::WdClearError{ path: stage_dir, err }); }
             }
-
-            // Done
-            debug!("Copied {} to {} in the working directory", source.display(), target.display());
+            if let Err(err) = fs::create_dir_all(&stage_dir) { return Err(BuildError::WdCreateError{ path: stage_dir, err }); }
+            copy_context_files(files, context, &stage_dir)?;
         }
     }
 
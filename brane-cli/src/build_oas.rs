@@ -9,18 +9,18 @@ use brane_oas::{self, build};
 use console::style;
 use openapiv3::OpenAPI;
 
-use specifications::arch::Arch;
+use specifications::arch::{Arch, ManifestList};
 use specifications::package::{PackageKind, PackageInfo};
 use specifications::version::Version;
 
-use crate::build_common::{BRANELET_URL, build_docker_image, clean_directory, LockHandle};
+use crate::build_common::{BRANELET_URL, build_docker_image, clean_directory, write_manifest_list, LockHandle};
 use crate::errors::BuildError;
 use crate::utils::ensure_package_dir;
 
 
 /***** BUILD FUNCTIONS *****/
 /// # Arguments
-///  - `arch`: The architecture to compile this image for.
+///  - `arches`: The architectures to compile this image for. The first one is used for the default image.
 ///  - `context`: The directory to copy additional files (executable, working directory files) from.
 ///  - `file`: Path to the package's main file (a container file, in this case).
 ///  - `branelet_path`: Optional path to a custom branelet executable. If left empty, will pull the standard one from Github instead.
@@ -29,7 +29,7 @@ use crate::utils::ensure_package_dir;
 /// # Errors
 /// This function may error for many reasons.
 pub async fn handle(
-    arch: Arch,
+    arches: Vec<Arch>,
     context: PathBuf,
     file: PathBuf,
     branelet_path: Option<PathBuf>,
@@ -54,7 +54,7 @@ pub async fn handle(
     // Lock the directory, build, unlock the directory
     {
         let _lock = LockHandle::lock(&package_info.name, package_dir.join(".lock"))?;
-        build(arch, document, package_info, &package_dir, branelet_path, keep_files).await?;
+        build(arches, document, package_info, &package_dir, branelet_path, keep_files).await?;
     };
 
     // Done
@@ -105,7 +105,7 @@ fn create_package_info(
 /// Actually builds a new Ecu package from the given file(s).
 /// 
 /// # Arguments
-///  - `arch`: The architecture to compile this image for.
+///  - `arches`: The architectures to compile this image for. The first one is used for the default image.
 ///  - `document`: The OpenAPI document describing the package.
 ///  - `package_dir`: The package directory to use as the build folder.
 ///  - `package_info`: The PackageInfo document also describing the package, but in a package-kind-oblivious way.
@@ -115,7 +115,7 @@ fn create_package_info(
 /// # Errors
 /// This function may error for many reasons.
 async fn build(
-    arch: Arch,
+    arches: Vec<Arch>,
     document: OpenAPI,
     package_info: PackageInfo,
     package_dir: &Path,
@@ -139,7 +139,7 @@ async fn build(
     // Build Docker image
     let tag = format!("{}:{}", package_info.name, package_info.version);
     debug!("Building image '{}' in directory '{}'", tag, package_dir.display());
-    match build_docker_image(arch, package_dir, tag) {
+    match build_docker_image(&arches, package_dir, tag) {
         Ok(_) => {
             println!(
                 "Successfully built version {} of Web API (OAS) package {}.",
@@ -149,10 +149,8 @@ async fn build(
 
             // Resolve the digest of the package info
             let mut package_info = package_info;
-            match brane_tsk::docker::get_digest(package_dir.join("image.tar")).await {
-                Ok(digest) => { package_info.digest = Some(digest); },
-                Err(err)   => { return Err(BuildError::DigestError{ err }); }
-            }
+            let list: ManifestList = write_manifest_list(&arches, package_dir).await?;
+            package_info.digest = list.images[0].digest.clone();
 
             // Write it to package directory
             let package_path = package_dir.join("package.yml");
@@ -0,0 +1,481 @@
+//  CODEGEN.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 12:26:18
+//  Last edited:
+//    16 Oct 2026, 12:26:18
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements the `package codegen` subcommand, which generates a thin
+//!   client library for a package that calls its functions by running
+//!   one-line workflows with `brane run`.
+// 
+
+use std::fmt::{Display, Formatter, Result as FResult, Write as _};
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use console::style;
+
+use brane_dsl::DataType;
+use specifications::common::{Function, Type};
+use specifications::package::PackageInfo;
+use specifications::version::Version;
+
+pub use crate::errors::CodegenError as Error;
+use crate::utils::ensure_package_dir;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use specifications::common::{Parameter, Property};
+    use specifications::package::PackageKind;
+
+    use super::*;
+
+
+    #[test]
+    fn test_python_str() {
+        assert_eq!(python_str(""), r#""""#);
+        assert_eq!(python_str("hello"), r#""hello""#);
+        assert_eq!(python_str(r#"say "hi"\n"#), r#""say \"hi\"\\n""#);
+        assert_eq!(python_str("a\nb\r\tc"), r#""a\nb\r\tc""#);
+        assert_eq!(python_str("\0\x1b\x7f"), r#""\x00\x1b\x7f""#);
+        // Python 3 sources are UTF-8, so anything else is written as-is (unlike Rust's `{:?}`, which writes `\u{...}`)
+        assert_eq!(python_str("héllo 🦀"), "\"héllo 🦀\"");
+        assert_eq!(python_bool(true), "True");
+        assert_eq!(python_bool(false), "False");
+    }
+
+    #[test]
+    fn test_python_docstring() {
+        assert_eq!(python_docstring("Plain text."), "Plain text.");
+        assert_eq!(python_docstring(r"C:\data"), r"C:\\data");
+        assert_eq!(python_docstring(r#"Ends """ here"#), r#"Ends ""\" here"#);
+    }
+
+    #[test]
+    fn test_python_type() {
+        assert_eq!(python_type("int").as_deref(), Some("int"));
+        assert_eq!(python_type("string[]").as_deref(), Some("List[str]"));
+        assert_eq!(python_type("Data").as_deref(), Some("Data"));
+        assert_eq!(python_type("Point").as_deref(), Some(r#""Point""#));
+        // Classes are referred to by their Python name
+        assert_eq!(python_type("class").as_deref(), Some(r#""class_""#));
+        assert_eq!(python_type("void"), None);
+    }
+
+    #[test]
+    fn test_generate_python() {
+        let info: PackageInfo = PackageInfo::new(
+            "pkg".into(),
+            Version::new(1, 2, 3),
+            PackageKind::Ecu,
+            vec![],
+            r#"Says "hi" with \n in it."#.into(),
+            false,
+            HashMap::from([
+                ("lambda".into(), Function::new(vec![ Parameter::new("from".into(), "string".into(), None, None, None), Parameter::new("loud".into(), "bool".into(), Some(true), None, None) ], None, "class".into(), None)),
+            ]),
+            HashMap::from([
+                ("class".into(), Type::new("class".into(), vec![ Property::new("from".into(), "int".into(), None, None, None, None) ])),
+            ]),
+        );
+        let code: String = generate_python(&info);
+
+        assert!(code.contains("PACKAGE = \"pkg\"\nVERSION = \"1.2.3\"\n"));
+        assert!(code.contains(r#""""Says "hi" with \\n in it."#));
+        // Classes and their properties get Python names, but are known by their Brane names
+        assert!(code.contains("@dataclass\nclass class_:\n    from_: int\n"));
+        assert!(code.contains("_CLASSES = { \"class\": class_ }\n"));
+        // ...and so do functions and their parameters
+        assert!(code.contains("def lambda_(from_: str, loud: Optional[bool] = None) -> \"class_\":\n"));
+        assert!(code.contains("    return _call(\"lambda\", [from_, loud], True)\n"));
+        assert!(code.contains("_KEYWORDS = { \"False\", \"None\", \"True\", \"and\","));
+    }
+}
+
+
+
+
+
+/***** CONSTANTS *****/
+/// The words that cannot be used as identifiers in Python.
+const PYTHON_KEYWORDS: [&str; 35] = [
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del", "elif", "else", "except", "finally", "for", "from",
+    "global", "if", "import", "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while", "with", "yield",
+];
+
+/// The part of the Python client that is the same for every package. `<NAME>`, `<VERSION>`, `<NAME_LITERAL>`, `<VERSION_LITERAL>` and `<DESCRIPTION>` are replaced when generating.
+const PYTHON_PRELUDE: &str = r#"# Generated by `brane package codegen` for version <VERSION> of package '<NAME>'; do not edit.
+"""<DESCRIPTION>
+
+Every function runs a small workflow with the `brane` executable, locally by
+default. Use `configure()` to run them on a remote instance instead.
+"""
+
+from __future__ import annotations
+
+import json
+import subprocess
+from dataclasses import dataclass, fields, is_dataclass
+from typing import Any, Dict, List, Optional
+
+
+PACKAGE = <NAME_LITERAL>
+VERSION = <VERSION_LITERAL>
+
+_config: Dict[str, Any] = { "brane": "brane", "remote": None, "instance": None, "args": [] }
+
+
+class BraneError(RuntimeError):
+    """Raised when running a function of the package on Brane fails."""
+
+
+@dataclass
+class Data:
+    """Refers to a dataset (or, if returned by a function, an intermediate result) by name."""
+    name: str
+
+
+def configure(remote: Optional[str] = None, instance: Optional[str] = None, brane: str = "brane", args: Optional[List[str]] = None) -> None:
+    """Determines where the functions of this package run.
+
+    Args:
+        remote: The address of the driver of a remote instance to run on.
+        instance: The name of a known instance (see `brane instance`) to run on instead.
+        brane: The `brane` executable to use.
+        args: Any additional arguments to give to `brane run` (e.g., `["--proxy-addr", "..."]`).
+    """
+    _config.update(remote=remote, instance=instance, brane=brane, args=list(args or []))
+
+
+def _literal(value: Any) -> str:
+    """Writes a Python value as a BraneScript literal."""
+    if value is None:
+        return "null"
+    if isinstance(value, bool):
+        return "true" if value else "false"
+    if isinstance(value, (int, float)):
+        return repr(value)
+    if isinstance(value, str):
+        return json.dumps(value)
+    if isinstance(value, Data):
+        return "new Data{ name := " + json.dumps(value.name) + " }"
+    if isinstance(value, (list, tuple)):
+        return "[" + ", ".join(_literal(elem) for elem in value) + "]"
+    if is_dataclass(value):
+        return "new " + _prop(type(value).__name__) + "{ " + ", ".join(_prop(f.name) + " := " + _literal(getattr(value, f.name)) for f in fields(value)) + " }"
+    raise TypeError(f"Cannot pass value of type '{type(value).__name__}' to Brane")
+
+
+def _decode(value: Any) -> Any:
+    """Turns a value returned by `brane run --json` into its Python equivalent."""
+    if isinstance(value, str):
+        for kind in ("Data<", "IntermediateResult<"):
+            if value.startswith(kind) and value.endswith(">"):
+                return Data(value[len(kind):-1])
+        return value
+    if isinstance(value, list):
+        if len(value) == 2 and isinstance(value[0], str) and isinstance(value[1], dict) and value[0] in _CLASSES:
+            cls = _CLASSES[value[0]]
+            return cls(**{ _field(name): _decode(prop) for name, prop in value[1].items() })
+        return [ _decode(elem) for elem in value ]
+    if isinstance(value, dict):
+        return { key: _decode(elem) for key, elem in value.items() }
+    return value
+
+
+def _field(name: str) -> str:
+    """Returns the name of the Python field (or class) for a property (or class) in Brane."""
+    return name + "_" if name in _KEYWORDS else name
+
+
+def _prop(field: str) -> str:
+    """Returns the name of the property (or class) in Brane for a Python field (or class)."""
+    return field[:-1] if field.endswith("_") and field[:-1] in _KEYWORDS else field
+
+
+def _call(function: str, args: List[Any], returns: bool) -> Any:
+    """Calls a function of the package by running a one-line workflow."""
+    workflow = f"import {PACKAGE}[{VERSION}]; return {function}(" + ", ".join(_literal(arg) for arg in args) + ");"
+    cmd = [ _config["brane"], "run", "--json" ]
+    if _config["remote"] is not None:
+        cmd += [ "--remote", _config["remote"] ]
+    if _config["instance"] is not None:
+        cmd += [ "--instance", _config["instance"] ]
+    cmd += _config["args"] + [ "-" ]
+    proc = subprocess.run(cmd, input=workflow, capture_output=True, text=True)
+    if proc.returncode != 0:
+        raise BraneError(f"Calling '{function}' failed (exit code {proc.returncode}):\n{proc.stderr}")
+    if not returns:
+        return None
+
+    # The returned value is the last line of JSON (any text the workflow printed comes before it)
+    for line in reversed(proc.stdout.splitlines()):
+        try:
+            return _decode(json.loads(line))
+        except ValueError:
+            continue
+    raise BraneError(f"Calling '{function}' did not return a value:\n{proc.stdout}")
+"#;
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Defines the languages for which we can generate clients.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CodegenLanguage {
+    /// A Python 3 module.
+    Python,
+}
+
+impl CodegenLanguage {
+    /// Returns the extension of the files in this language.
+    #[inline]
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Python => "py",
+        }
+    }
+}
+
+impl Display for CodegenLanguage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use CodegenLanguage::*;
+        match self {
+            Python => write!(f, "python"),
+        }
+    }
+}
+
+impl FromStr for CodegenLanguage {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "python" | "py" => Ok(Self::Python),
+            _               => Err(Error::UnknownLanguage{ raw: s.into() }),
+        }
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Turns a Brane name into a legal Python identifier.
+/// 
+/// # Arguments
+/// - `name`: The name to convert.
+/// 
+/// # Returns
+/// The name, with an underscore appended if it is a Python keyword.
+fn python_ident(name: &str) -> String {
+    if PYTHON_KEYWORDS.contains(&name) { format!("{}_", name) } else { name.into() }
+}
+
+/// Writes the given string as a Python string literal.
+/// 
+/// # Arguments
+/// - `value`: The string to write.
+/// 
+/// # Returns
+/// A double-quoted Python string literal with the same value.
+fn python_str(value: &str) -> String {
+    let mut res: String = String::with_capacity(value.len() + 2);
+    res.push('"');
+    for c in value.chars() {
+        match c {
+            '"'                 => { res.push_str("\\\""); },
+            '\\'                => { res.push_str("\\\\"); },
+            '\n'                => { res.push_str("\\n"); },
+            '\r'                => { res.push_str("\\r"); },
+            '\t'                => { res.push_str("\\t"); },
+            // Other control characters (which are all below 0x100) are written as bytes
+            c if c.is_control() => { write!(res, "\\x{:02x}", c as u32).unwrap(); },
+            c                   => { res.push(c); },
+        }
+    }
+    res.push('"');
+    res
+}
+
+/// Writes the given boolean as a Python literal.
+/// 
+/// # Arguments
+/// - `value`: The boolean to write.
+/// 
+/// # Returns
+/// Either `True` or `False`.
+#[inline]
+fn python_bool(value: bool) -> &'static str { if value { "True" } else { "False" } }
+
+/// Escapes the given text so that it may be written in a Python docstring.
+/// 
+/// # Arguments
+/// - `text`: The text to escape.
+/// 
+/// # Returns
+/// The text, with its backslashes escaped and any triple quotes broken up so that they do not end the docstring.
+fn python_docstring(text: &str) -> String {
+    text.replace('\\', "\\\\").replace("\"\"\"", "\"\"\\\"")
+}
+
+/// Translates a Brane data type to a Python type annotation.
+/// 
+/// # Arguments
+/// - `data_type`: The Brane data type, as written in the PackageInfo.
+/// 
+/// # Returns
+/// The Python type annotation, or `None` if the type denotes that nothing is returned.
+#[inline]
+fn python_type(data_type: &str) -> Option<String> { python_type_of(&DataType::from(data_type)) }
+
+/// Translates a parsed Brane data type to a Python type annotation.
+/// 
+/// # Arguments
+/// - `data_type`: The Brane data type.
+/// 
+/// # Returns
+/// The Python type annotation, or `None` if the type denotes that nothing is returned.
+fn python_type_of(data_type: &DataType) -> Option<String> {
+    match data_type {
+        DataType::Boolean     => Some("bool".into()),
+        DataType::Integer     => Some("int".into()),
+        DataType::Real        => Some("float".into()),
+        DataType::String      => Some("str".into()),
+        DataType::Array(elem) => Some(format!("List[{}]", python_type_of(elem).unwrap_or_else(|| "Any".into()))),
+        DataType::Map(elem)   => Some(format!("Dict[str, {}]", python_type_of(elem).unwrap_or_else(|| "Any".into()))),
+        DataType::Void        => None,
+        DataType::Class(name) => match name.as_str() {
+            "" | "unit" | "void"          => None,
+            "Data" | "IntermediateResult" => Some("Data".into()),
+            "any"                         => Some("Any".into()),
+            name                          => Some(python_str(&python_ident(name))),
+        },
+        _ => Some("Any".into()),
+    }
+}
+
+/// Writes a class of the package as a Python dataclass.
+/// 
+/// # Arguments
+/// - `code`: The code to write to.
+/// - `class`: The class to write.
+fn write_python_class(code: &mut String, class: &Type) {
+    writeln!(code, "@dataclass").unwrap();
+    writeln!(code, "class {}:", python_ident(&class.name)).unwrap();
+    if class.properties.is_empty() { writeln!(code, "    pass").unwrap(); }
+    for prop in &class.properties {
+        writeln!(code, "    {}: {}", python_ident(&prop.name), python_type(&prop.data_type).unwrap_or_else(|| "None".into())).unwrap();
+    }
+    writeln!(code, "\n").unwrap();
+}
+
+/// Writes a function of the package as a Python function that calls it.
+/// 
+/// # Arguments
+/// - `code`: The code to write to.
+/// - `name`: The name of the function.
+/// - `function`: The function to write.
+fn write_python_function(code: &mut String, name: &str, function: &Function) {
+    // Optional parameters may only come after the required ones in Python, but must be passed in order to Brane, so they are only optional if nothing required follows
+    let first_optional: usize = function.parameters.iter().rposition(|p| !p.optional.unwrap_or(false)).map(|i| i + 1).unwrap_or(0);
+    let params: Vec<String> = function.parameters.iter().enumerate().map(|(i, p)| {
+        let annotation: String = python_type(&p.data_type).unwrap_or_else(|| "None".into());
+        if i >= first_optional { format!("{}: Optional[{}] = None", python_ident(&p.name), annotation) } else { format!("{}: {}", python_ident(&p.name), annotation) }
+    }).collect();
+    let returns: Option<String> = python_type(&function.return_type);
+
+    writeln!(code, "def {}({}) -> {}:", python_ident(name), params.join(", "), returns.as_deref().unwrap_or("None")).unwrap();
+    writeln!(code, "    \"\"\"Calls `{}` of the package.\"\"\"", python_docstring(name)).unwrap();
+    writeln!(code, "    return _call({}, [{}], {})", python_str(name), function.parameters.iter().map(|p| python_ident(&p.name)).collect::<Vec<String>>().join(", "), python_bool(returns.is_some())).unwrap();
+    writeln!(code, "\n").unwrap();
+}
+
+/// Generates a Python client for the given package.
+/// 
+/// # Arguments
+/// - `info`: The PackageInfo of the package to generate a client for.
+/// 
+/// # Returns
+/// The code of a Python module with a dataclass per class and a function per function of the package.
+fn generate_python(info: &PackageInfo) -> String {
+    let mut code: String = PYTHON_PRELUDE
+        .replace("<NAME_LITERAL>", &python_str(&info.name))
+        .replace("<VERSION_LITERAL>", &python_str(&info.version.to_string()))
+        .replace("<NAME>", &info.name)
+        .replace("<VERSION>", &info.version.to_string())
+        .replace("<DESCRIPTION>", &python_docstring(if info.description.is_empty() { "Client for a Brane package." } else { info.description.trim() }));
+    code.push_str("\n\n");
+
+    // Sort everything so that regenerating a client gives the same code
+    let mut classes: Vec<&Type> = info.types.values().collect();
+    classes.sort_by(|c1, c2| c1.name.cmp(&c2.name));
+    let mut functions: Vec<(&String, &Function)> = info.functions.iter().collect();
+    functions.sort_by(|(n1, _), (n2, _)| n1.cmp(n2));
+
+    code.push_str("# Classes\n");
+    for class in &classes {
+        write_python_class(&mut code, class);
+    }
+    writeln!(code, "_CLASSES = {{ {} }}", classes.iter().map(|c| format!("{}: {}", python_str(&c.name), python_ident(&c.name))).collect::<Vec<String>>().join(", ")).unwrap();
+    writeln!(code, "_KEYWORDS = {{ {} }}", PYTHON_KEYWORDS.iter().map(|k| python_str(k)).collect::<Vec<String>>().join(", ")).unwrap();
+    code.push_str("\n\n");
+
+    code.push_str("# Functions\n");
+    for (name, function) in functions {
+        write_python_function(&mut code, name, function);
+    }
+    code.truncate(code.trim_end().len());
+    code.push('\n');
+    code
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Generates a client library for a locally available package, with a typed stub per function (and class) that calls it through `brane run`.
+/// 
+/// # Arguments
+/// - `name`: The name of the package.
+/// - `version`: The version of the package.
+/// - `lang`: The language to generate the client in.
+/// - `output`: The file to write the client to. Defaults to `./<name>.<ext>`.
+/// 
+/// # Errors
+/// This function errors if the package is not available locally or if we failed to write the client.
+pub fn handle(name: String, version: Version, lang: CodegenLanguage, output: Option<PathBuf>) -> Result<(), Error> {
+    // Read the package info of the given package
+    let package_dir: PathBuf = match ensure_package_dir(&name, Some(&version), false) {
+        Ok(dir)  => dir,
+        Err(err) => { return Err(Error::PackageDirError{ name, version, err }); }
+    };
+    let info: PackageInfo = match PackageInfo::from_path(package_dir.join("package.yml")) {
+        Ok(info) => info,
+        Err(err) => { return Err(Error::PackageInfoError{ name, version, err }); }
+    };
+
+    // Generate the code and write it
+    let code: String = match lang {
+        CodegenLanguage::Python => generate_python(&info),
+    };
+    let output: PathBuf = output.unwrap_or_else(|| PathBuf::from(format!("{}.{}", info.name.replace('-', "_"), lang.extension())));
+    if let Err(err) = fs::write(&output, code) { return Err(Error::FileWriteError{ path: output, err }); }
+
+    println!("Generated {} client for package {} (version {}) in '{}'", lang, style(&info.name).bold().cyan(), style(&info.version).bold(), output.display());
+    Ok(())
+}
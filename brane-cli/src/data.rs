@@ -30,20 +30,101 @@ use prettytable::Table;
 use rand::prelude::IteratorRandom;
 use reqwest::{Client, ClientBuilder, Proxy, Response};
 use reqwest::tls::{Certificate, Identity};
-use specifications::data::{AccessKind, AssetInfo, DataIndex, DataInfo};
+use serde::Serialize;
+use specifications::data::{data_id, default_data_version, split_data_id, AccessKind, AssetInfo, DataIndex, DataInfo, Location};
 use tempfile::TempDir;
 use tokio::fs as tfs;
 use tokio::io::{AsyncWriteExt, BufReader};
 use tokio_stream::StreamExt;
 use tokio_tar::Archive;
 
+use brane_cfg::pins::{fingerprint, PinStore, SignedCaLog};
+use brane_shr::fetch::parse_digest;
 use brane_shr::fs::copy_dir_recursively_async;
 use brane_shr::utilities::is_ip_addr;
+use brane_tsk::api::ApiClient;
 use brane_tsk::spec::LOCALHOST;
 use specifications::registry::RegistryConfig;
+use specifications::version::Version;
 
 use crate::errors::DataError;
-use crate::utils::{ensure_dataset_dir, ensure_datasets_dir, get_dataset_dir, get_registry_file};
+use crate::render::{print_structured, OutputFormat};
+use crate::utils::{ensure_dataset_version_dir, ensure_datasets_dir, get_dataset_dir, get_dataset_version_dir, get_registry_file};
+
+
+/***** HELPER STRUCTS *****/
+/// Defines a single entry in the structured output of `brane data list`.
+#[derive(Serialize)]
+struct DatasetEntry {
+    /// The name (=identifier) of the dataset.
+    name    : String,
+    /// The version of the dataset.
+    version : String,
+    /// The kind of the dataset.
+    kind    : &'static str,
+    /// When the dataset was created (as an RFC 3339 timestamp).
+    created : String,
+    /// Whether the dataset is linked (i.e., its data lives outside of the datasets directory).
+    linked  : bool,
+    /// How to access the dataset (e.g., the path to its file).
+    access  : String,
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Checks that the CA certificate we have for the given location is the one we pinned, or, if it is newly seen, that the instance's CA log vouches for it.
+/// 
+/// # Arguments
+/// - `certs_dir`: The folder with certificates per location, which also contains our pins.
+/// - `endpoint`: The remote `brane-api` endpoint that serves the CA log.
+/// - `location`: The location of which to check the certificate.
+/// 
+/// # Errors
+/// This function errors if we failed to fetch the log or if it does not vouch for the certificate, in which case the certificate may have been substituted.
+async fn verify_ca_pin(certs_dir: &Path, endpoint: &str, location: &str) -> Result<(), DataError> {
+    let fingerprint: String = match fingerprint(certs_dir.join(location).join("ca.pem")) {
+        Ok(fingerprint) => fingerprint,
+        Err(err)        => { return Err(DataError::PinError{ location: location.into(), err }); },
+    };
+    let pins_path: PathBuf = certs_dir.join(PinStore::FILENAME);
+    let mut pins: PinStore = match PinStore::from_path(&pins_path) {
+        Ok(pins) => pins,
+        Err(err) => { return Err(DataError::PinError{ location: location.into(), err }); },
+    };
+    if pins.is_pinned(location, &fingerprint) { return Ok(()); }
+
+    // It's a new certificate, so consult the log
+    debug!("CA certificate of location '{}' is newly seen; consulting CA log...", location);
+    let log_addr: String = format!("{}/infra/ca-log", endpoint);
+    let res: Response = match reqwest::get(&log_addr).await {
+        Ok(res)  => res,
+        Err(err) => { return Err(DataError::RequestError{ what: "CA log", address: log_addr, err }); },
+    };
+    if !res.status().is_success() {
+        return Err(DataError::RequestFailure{ address: log_addr, code: res.status(), message: res.text().await.ok() })
+    }
+    let log: String = match res.text().await {
+        Ok(log)  => log,
+        Err(err) => { return Err(DataError::ResponseTextError{ address: log_addr, err }); },
+    };
+    let log: SignedCaLog = match serde_json::from_str(&log) {
+        Ok(log)  => log,
+        Err(err) => { return Err(DataError::CaLogParseError{ address: log_addr, err }); },
+    };
+
+    // Pin it if the log agrees
+    if let Err(err) = pins.update(location, &fingerprint, &log) { return Err(DataError::PinError{ location: location.into(), err }); }
+    match pins.to_path(&pins_path) {
+        Ok(_)    => Ok(()),
+        Err(err) => Err(DataError::PinError{ location: location.into(), err }),
+    }
+}
+
+
+
 
 
 /***** LIBRARY *****/
@@ -56,17 +137,20 @@ use crate::utils::{ensure_dataset_dir, ensure_datasets_dir, get_dataset_dir, get
 /// - `endpoint`: The remote `brane-api` endpoint that we use to download the possible registries.
 /// - `proxy_addr`: If given, the any data transfers will be proxied through this address.
 /// - `name`: The name of the dataset to download.
+/// - `version`: The version of the dataset to download.
 /// - `access`: The locations where it is available.
+/// - `residency`: The locations where it may be processed, if it is restricted. The local copy keeps this restriction.
 /// 
 /// # Returns
 /// The AccessKind with how to download the dataset if it was downloaded successfully, or `None` if it wasn't available.
 /// 
 /// # Errors
 /// This function errors if we failed to download the dataset somehow.
-pub async fn download_data(certs_dir: impl AsRef<Path>, endpoint: impl AsRef<str>, proxy_addr: &Option<String>, name: impl AsRef<str>, access: &HashMap<String, AccessKind>) -> Result<Option<AccessKind>, DataError> {
-    let certs_dir : &Path = certs_dir.as_ref();
-    let endpoint  : &str  = endpoint.as_ref();
-    let name      : &str  = name.as_ref();
+pub async fn download_data(certs_dir: impl AsRef<Path>, endpoint: impl AsRef<str>, proxy_addr: &Option<String>, name: impl AsRef<str>, version: &Version, access: &HashMap<String, AccessKind>, residency: Option<&[Location]>) -> Result<Option<AccessKind>, DataError> {
+    let certs_dir : &Path  = certs_dir.as_ref();
+    let endpoint  : &str   = endpoint.as_ref();
+    let name      : &str   = name.as_ref();
+    let id        : String = data_id(name, version);
 
 
 
@@ -97,6 +181,7 @@ pub async fn download_data(certs_dir: impl AsRef<Path>, endpoint: impl AsRef<str
 
     /* Step 2: Load the required certificates */
     debug!("Loading certificate for location '{}'...", location);
+    verify_ca_pin(certs_dir, endpoint, location).await?;
     let (identity, ca_cert): (Identity, Certificate) = {
         // Compute the paths
         let cert_dir : PathBuf = certs_dir.join(location);
@@ -135,12 +220,12 @@ pub async fn download_data(certs_dir: impl AsRef<Path>, endpoint: impl AsRef<str
         Ok(tar_dir) => tar_dir,
         Err(err)    => { return Err(DataError::TempDirError{ err }); },
     };
-    let tar_path: PathBuf = tar_dir.path().join(format!("data_{}.tar.gz", name));
+    let tar_path: PathBuf = tar_dir.path().join(format!("data_{}.tar.gz", id));
 
     // Compute the final data path in the datasets directory
-    let data_dir: PathBuf = match ensure_dataset_dir(name, true) {
+    let data_dir: PathBuf = match ensure_dataset_version_dir(name, version, true) {
         Ok(datas_dir) => datas_dir,
-        Err(err)      => { return Err(DataError::DatasetDirError { name: name.into(), err }); },
+        Err(err)      => { return Err(DataError::DatasetDirError { name: id, err }); },
     };
     let data_path: PathBuf = data_dir.join("data");
 
@@ -158,7 +243,7 @@ pub async fn download_data(certs_dir: impl AsRef<Path>, endpoint: impl AsRef<str
 
 
     /* Step 4: Build the client. */
-    let download_addr: String = format!("{}/data/download/{}", registry_addr, name);
+    let download_addr: String = format!("{}/data/download/{}", registry_addr, id);
     debug!("Sending download request to '{}'...", download_addr);
     let mut client: ClientBuilder = Client::builder()
         .use_rustls_tls()
@@ -236,9 +321,13 @@ pub async fn download_data(certs_dir: impl AsRef<Path>, endpoint: impl AsRef<str
         // Populate the info itself
         let info: DataInfo = DataInfo {
             name        : name.into(),
+            version     : version.clone(),
             owners      : None,
             description : None,
             created     : Utc::now(),
+            residency   : residency.map(|locs| locs.to_vec()),
+            size        : None,
+            revisions   : HashMap::new(),
 
             access : HashMap::from([
                 (LOCALHOST.into(), access.clone()),
@@ -304,18 +393,42 @@ pub async fn build(file: impl AsRef<Path>, workdir: impl AsRef<Path>, _keep_file
             if !path.exists()  { return Err(DataError::FileNotFoundError { path: path.clone() }); }
             // if !path.is_file() { return Err(DataError::FileNotAFileError{ path: path.clone() }); }
         },
+
+        // Remote datasets are only fetched once a task needs them
+        AccessKind::XRootD { .. } | AccessKind::GridFtp { .. } => {},
+        // The same goes for datasets registered by URL, but we can already make sure that their digest makes sense
+        AccessKind::Url { ref mut digest, .. } => {
+            *digest = match parse_digest(&*digest) {
+                Ok(digest) => digest,
+                Err(err)   => { return Err(DataError::DigestParseError{ err }); },
+            };
+        },
     }
 
 
 
     /* Step 2: Prepare the build directory. */
+    // Decide on the version, bumping the patch version of the latest one if the user did not give any
+    let datasets_dir: PathBuf = match ensure_datasets_dir(true) {
+        Ok(datasets_dir) => datasets_dir,
+        Err(err)         => { return Err(DataError::DatasetsError { err }); },
+    };
+    let index: DataIndex = match brane_tsk::local::get_data_index(datasets_dir) {
+        Ok(index) => index,
+        Err(err)  => { return Err(DataError::LocalDataIndexError { err }); },
+    };
+    let version: Version = match (info.version.take(), index.get(&info.name)) {
+        (Some(version), _) if !version.is_latest() => version,
+        (_, Some(latest))                          => Version::new(latest.version.major, latest.version.minor, latest.version.patch + 1),
+        (_, None)                                  => default_data_version(),
+    };
+    info.version = Some(version.clone());
+
     // Before we create it though, if it happens to exist, then moan about it
-    if let Ok(dir) = get_dataset_dir(&info.name) {
-        if dir.exists() { return Err(DataError::DuplicateDatasetError{ name: info.name }); }
-    }
+    if index.get(info.id()).is_some() { return Err(DataError::DuplicateDatasetError{ name: info.id() }); }
 
     // Simple use our ensure thing for this
-    let build_dir: PathBuf = match ensure_dataset_dir(&info.name, true) {
+    let build_dir: PathBuf = match ensure_dataset_version_dir(&info.name, &version, true) {
         Ok(build_dir) => build_dir,
         Err(err)      => { return Err(DataError::DatasetDirCreateError{ err }); }
     };
@@ -335,6 +448,9 @@ pub async fn build(file: impl AsRef<Path>, workdir: impl AsRef<Path>, _keep_file
                 // Update the path to the target
                 *path = target;
             },
+
+            // Nothing to copy for remote datasets
+            AccessKind::XRootD { .. } | AccessKind::GridFtp { .. } | AccessKind::Url { .. } => {},
         }
     }
 
@@ -347,7 +463,7 @@ pub async fn build(file: impl AsRef<Path>, workdir: impl AsRef<Path>, _keep_file
 
 
     /* Step 5: Done */
-    println!("Successfully built dataset {}", style(&data_info.name).bold().cyan());
+    println!("Successfully built dataset {} version {}", style(&data_info.name).bold().cyan(), style(&data_info.version).bold());
     Ok(())
 }
 
@@ -391,7 +507,7 @@ pub async fn download(names: Vec<String>, locs: Vec<String>, certs_dir: impl AsR
 
     // Fetch a new, remote DataIndex to get up-to-date entries
     let data_addr: String = format!("{}/data/info", config.url);
-    let index: DataIndex = match brane_tsk::api::get_data_index(&data_addr).await {
+    let index: DataIndex = match ApiClient::new(&config.url).data_index().await {
         Ok(dindex) => dindex,
         Err(err)   => { return Err(DataError::RemoteDataIndexError{ address: data_addr, err }); },
     };
@@ -457,7 +573,7 @@ pub async fn download(names: Vec<String>, locs: Vec<String>, certs_dir: impl AsR
             Some(access) => access.clone(),
             None         => {
                 // Attempt to download it instead
-                match download_data(certs_dir, &config.url, proxy_addr, &name, &access).await? {
+                match download_data(certs_dir, &config.url, proxy_addr, &info.name, &info.version, &access, info.residency.as_deref()).await? {
                     Some(access) => access,
                     None         => { return Err(DataError::UnavailableDataset{ name, locs: info.access.keys().cloned().collect() }); },
                 }
@@ -467,7 +583,8 @@ pub async fn download(names: Vec<String>, locs: Vec<String>, certs_dir: impl AsR
         // Write the method of access
         println!("Download {}", style("success").bold().cyan());
         match access {
-            AccessKind::File { path } => println!("(It's available under '{}')", path.display()),
+            AccessKind::File { path }                                                              => println!("(It's available under '{}')", path.display()),
+            AccessKind::XRootD { url } | AccessKind::GridFtp { url } | AccessKind::Url { url, .. } => println!("(It's available at '{}')", url),
         }
     }
 
@@ -477,12 +594,15 @@ pub async fn download(names: Vec<String>, locs: Vec<String>, certs_dir: impl AsR
 
 /// Lists all locally built/available datasets.
 /// 
+/// # Arguments
+/// - `output`: The format in which to write the list.
+/// 
 /// # Returns
-/// Nothing, but does print a neat table to stdout.
+/// Nothing, but does print a neat table (or a machine-readable list) to stdout.
 /// 
 /// # Errors
 /// This function may error if we somehow failed to discover all the files.
-pub fn list() -> Result<(), DataError> {
+pub fn list(output: OutputFormat) -> Result<(), DataError> {
     // Prepare display table.
     let format = FormatBuilder::new()
         .column_separator('\0')
@@ -491,7 +611,7 @@ pub fn list() -> Result<(), DataError> {
         .build();
     let mut table = Table::new();
     table.set_format(format);
-    table.add_row(row!["ID/NAME", "KIND", "CREATED", "LINKED?", "ACCESS"]);
+    table.add_row(row!["ID/NAME", "VERSION", "KIND", "CREATED", "LINKED?", "ACCESS"]);
 
     // Get the local datasets folder
     let datasets_dir: PathBuf = match ensure_datasets_dir(false) {
@@ -505,12 +625,17 @@ pub fn list() -> Result<(), DataError> {
         Ok(index) => index,
         Err(err)  => { return Err(DataError::LocalDataIndexError { err }); },
     };
-    for d in index {
-        // Add the name/id of the dataset
+    let mut infos: Vec<&DataInfo> = index.iter_versions().collect();
+    infos.sort_by(|lhs, rhs| lhs.name.cmp(&rhs.name).then_with(|| lhs.version.cmp(&rhs.version)));
+    let mut entries: Vec<DatasetEntry> = vec![];
+    for d in infos {
+        // Add the name/id and version of the dataset
         let name = pad_str(&d.name, 20, Alignment::Left, Some(".."));
+        let sversion = d.version.to_string();
+        let version = pad_str(&sversion, 10, Alignment::Left, Some(".."));
 
         // Add the kind of the dataset
-        let (kind, access, is_linked): (&str, String, bool) = match d.access.get("localhost").expect("Local dataset does not have 'localhost' as location; this should never happen!") {
+        let (kind, access, is_linked): (&'static str, String, bool) = match d.access.get("localhost").expect("Local dataset does not have 'localhost' as location; this should never happen!") {
             AccessKind::File { path } => {
                 // Determine if this file is linked (it is if the path points outside the data directory itself)
                 let is_linked: bool = if let Ok(dir) = get_dataset_dir(&d.name) {
@@ -522,7 +647,16 @@ pub fn list() -> Result<(), DataError> {
                 // The kind is the name, the access is the path to the file
                 ("File", path.to_string_lossy().into(), is_linked)
             },
+
+            // Remote datasets always live outside of the data directory
+            AccessKind::XRootD { url }  => ("XRootD", url.clone(), true),
+            AccessKind::GridFtp { url } => ("GridFTP", url.clone(), true),
+            AccessKind::Url { url, .. } => ("URL", url.clone(), true),
         };
+        if output != OutputFormat::Table {
+            entries.push(DatasetEntry { name: d.name.clone(), version: sversion, kind, created: d.created.to_rfc3339(), linked: is_linked, access });
+            continue;
+        }
         let sis_linked: String = if is_linked { String::from("yes") } else { String::from("no") };
         let (kind, access, is_linked): (Cow<str>, Cow<str>, Cow<str>) = (pad_str(kind, 10, Alignment::Left, Some("..")), pad_str(&access, 60, Alignment::Left, Some("..")), pad_str(&sis_linked, 5, Alignment::Left, Some("..")));
 
@@ -532,10 +666,14 @@ pub fn list() -> Result<(), DataError> {
         let created = pad_str(&created, 15, Alignment::Left, Some(".."));
 
         // Finally, add a row with it
-        table.add_row(row![name, kind, created, is_linked, access]);
+        table.add_row(row![name, version, kind, created, is_linked, access]);
     }
-    
+
     // Write to stdout and done!
+    if output != OutputFormat::Table {
+        if let Err(err) = print_structured(&entries, output) { return Err(DataError::RenderError{ err }); }
+        return Ok(());
+    }
     table.printstd();
     Ok(())
 }
@@ -573,9 +711,9 @@ pub fn path(datasets: Vec<impl AsRef<str>>) -> Result<(), DataError> {
                     AccessKind::File { path } => {
                         println!("{}", path.display());
                     },
-
-                    #[allow(unreachable_patterns)]
-                    _ => { println!("<none>") },
+                    AccessKind::XRootD { url } | AccessKind::GridFtp { url } | AccessKind::Url { url, .. } => {
+                        println!("{}", url);
+                    },
                 }
             } else {
                 return Err(DataError::UnavailableDataset{ name: d.into(), locs: info.access.keys().cloned().collect() });
@@ -592,7 +730,7 @@ pub fn path(datasets: Vec<impl AsRef<str>>) -> Result<(), DataError> {
 /// Removes the dataset with the given identifier from the local database.
 /// 
 /// # Arguments
-/// - `datasets`: The list of datasets to delete.
+/// - `datasets`: The list of datasets to delete. Plain names remove every version of a dataset, whereas `NAME@VERSION` pairs only remove that version.
 /// - `force`: Whether or not to force the removal (i.e., if true, do not ask the user for confirmation).
 /// 
 /// # Returns
@@ -605,11 +743,15 @@ pub fn remove(datasets: Vec<impl AsRef<str>>, force: bool) -> Result<(), DataErr
     for d in datasets {
         let d: &str = d.as_ref();
 
-        // Fetch the directory of this dataset
-        let dir: PathBuf = match get_dataset_dir(d) {
-            Ok(dir)  => dir,
-            Err(err) => { return Err(DataError::DatasetDirError{ name: d.into(), err }); }
+        // Fetch the directory of this dataset (or of this version of it)
+        let dir: PathBuf = match split_data_id(d) {
+            Ok((name, version)) => match if version.is_latest() { get_dataset_dir(name) } else { get_dataset_version_dir(name, &version) } {
+                Ok(dir)  => dir,
+                Err(err) => { return Err(DataError::DatasetDirError{ name: d.into(), err }); }
+            },
+            Err(err) => { return Err(DataError::IllegalDataIdentifier{ err }); },
         };
+        if !dir.exists() { return Err(DataError::UnknownDataset{ name: d.into() }); }
 
         // Ask the user if they are sure
         if !force {
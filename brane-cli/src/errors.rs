@@ -18,8 +18,9 @@ use std::path::PathBuf;
 
 use reqwest::StatusCode;
 
+use brane_ast::DataType;
 use brane_shr::debug::PrettyListFormatter;
-use specifications::package::{PackageInfoError, PackageKindError};
+use specifications::package::{PackageInfoError, PackageKind, PackageKindError};
 use specifications::container::{ContainerInfoError, Image, LocalContainerInfoError};
 use specifications::version::{ParseError as VersionParseError, Version};
 
@@ -36,26 +37,48 @@ lazy_static! { static ref CLI_LINE_SEPARATOR: String = (0..80).map(|_| '-').coll
 #[derive(Debug)]
 pub enum CliError {
     // Toplevel errors for the subcommands
+    /// Errors that occur during any of the availability command(s)
+    AvailabilityError{ err: AvailabilityError },
     /// Errors that occur during the build command
     BuildError{ err: BuildError },
+    /// Errors that occur during the package codegen command
+    CodegenError{ err: CodegenError },
     /// Errors that occur during any of the data(-related) command(s)
     DataError{ err: DataError },
     /// Errors that occur during the import command
     ImportError{ err: ImportError },
+    /// Errors that occur during some instance command
+    InstanceError{ err: InstanceError },
+    /// Errors that occur during some kernel command
+    KernelError{ err: KernelError },
     /// Errors that occur during some package command
     PackageError{ err: PackageError },
+    /// Errors that occur during the profile command
+    ProfileError{ err: ProfileError },
     /// Errors that occur during some registry command
     RegistryError{ err: RegistryError },
     /// Errors that occur during the repl command
     ReplError{ err: ReplError },
     /// Errors that occur during the run command
     RunError{ err: RunError },
+    /// Errors that occur during some runs command
+    RunsError{ err: RunsError },
+    /// Errors that occur during the new command
+    ScaffoldError{ err: ScaffoldError },
+    /// Errors that occur during some script command
+    ScriptError{ err: ScriptError },
+    /// Errors that occur during some session command
+    SessionError{ err: SessionError },
+    /// Errors that occur during some sweep command
+    SweepError{ err: SweepError },
     /// Errors that occur in the test command
     TestError{ err: TestError },
     /// Errors that occur in the verify command
     VerifyError{ err: VerifyError },
     /// Errors that occur in the version command
     VersionError{ err: VersionError },
+    /// Errors that occur in some workflow command
+    WorkflowError{ err: WorkflowError },
     /// Errors that occur in some inter-subcommand utility
     UtilError{ err: UtilError },
     /// Temporary wrapper around any anyhow error
@@ -76,18 +99,29 @@ impl Display for CliError {
     fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
         use CliError::*;
         match self {
-            BuildError{ err }    => write!(f, "{}", err),
-            DataError{ err }     => write!(f, "{}", err),
-            ImportError{ err }   => write!(f, "{}", err),
-            PackageError{ err }  => write!(f, "{}", err),
-            RegistryError{ err } => write!(f, "{}", err),
-            ReplError{ err }     => write!(f, "{}", err),
-            RunError{ err }      => write!(f, "{}", err),
-            TestError{ err }     => write!(f, "{}", err),
-            VerifyError{ err }   => write!(f, "{}", err),
-            VersionError{ err }  => write!(f, "{}", err),
-            UtilError{ err }     => write!(f, "{}", err),
-            OtherError{ err }    => write!(f, "{}", err),
+            AvailabilityError{ err } => write!(f, "{}", err),
+            BuildError{ err }        => write!(f, "{}", err),
+            CodegenError{ err }      => write!(f, "{}", err),
+            DataError{ err }         => write!(f, "{}", err),
+            ImportError{ err }       => write!(f, "{}", err),
+            InstanceError{ err }     => write!(f, "{}", err),
+            KernelError{ err }       => write!(f, "{}", err),
+            PackageError{ err }      => write!(f, "{}", err),
+            ProfileError{ err }      => write!(f, "{}", err),
+            RegistryError{ err }     => write!(f, "{}", err),
+            ReplError{ err }         => write!(f, "{}", err),
+            RunError{ err }          => write!(f, "{}", err),
+            RunsError{ err }         => write!(f, "{}", err),
+            ScaffoldError{ err }     => write!(f, "{}", err),
+            ScriptError{ err }       => write!(f, "{}", err),
+            SessionError{ err }      => write!(f, "{}", err),
+            SweepError{ err }        => write!(f, "{}", err),
+            TestError{ err }         => write!(f, "{}", err),
+            VerifyError{ err }       => write!(f, "{}", err),
+            VersionError{ err }      => write!(f, "{}", err),
+            WorkflowError{ err }     => write!(f, "{}", err),
+            UtilError{ err }         => write!(f, "{}", err),
+            OtherError{ err }        => write!(f, "{}", err),
 
             PackageFileCanonicalizeError{ path, err } => write!(f, "Could not resolve package file path '{}': {}", path.display(), err),
             WorkdirCanonicalizeError{ path, err }     => write!(f, "Could not resolve working directory '{}': {}", path.display(), err),
@@ -118,6 +152,23 @@ pub enum BuildError {
     /// Could not properly convert the OpenAPI document into a PackageInfo
     PackageInfoFromOpenAPIError{ err: anyhow::Error },
 
+    /// Could not read the given CWL document
+    CwlDocumentReadError{ file: PathBuf, err: std::io::Error },
+    /// Could not parse the given CWL document
+    CwlDocumentParseError{ file: PathBuf, err: serde_yaml::Error },
+    /// The given CWL document is not valid
+    CwlDocumentIllegal{ file: PathBuf, reason: String },
+    /// The given CWL document uses something we cannot translate to a package
+    CwlUnsupported{ file: PathBuf, what: String },
+    /// The tools in the given CWL document want to run in different images
+    CwlConflictingImages{ file: PathBuf, image1: String, image2: String },
+    /// Could not parse the version in the given CWL document
+    CwlVersionParseError{ file: PathBuf, raw: String, err: VersionParseError },
+    /// Could not create the temporary directory to build the CWL package from
+    CwlContextCreateError{ err: std::io::Error },
+    /// Could not write the script that runs the CWL tools
+    CwlScriptWriteError{ path: PathBuf, err: std::io::Error },
+
     /// A lock file exists for the current building package, so wait
     LockFileExists{ path: PathBuf },
     /// Could not create a file lock for system reasons
@@ -131,6 +182,8 @@ pub enum BuildError {
     UnsafePath{ path: String },
     /// The entrypoint executable referenced was not found
     MissingExecutable{ path: PathBuf },
+    /// The package has dependencies, but no package manager to install them with
+    NoPackageManager,
 
     /// Could not create the Dockerfile in the build directory.
     DockerfileCreateError{ path: PathBuf, err: std::io::Error },
@@ -196,6 +249,12 @@ pub enum BuildError {
     DigestError{ err: brane_tsk::docker::Error },
     /// Could not write the PackageFile to the build directory.
     PackageFileCreateError{ err: PackageInfoError },
+    /// Could not write the manifest list with the images for every architecture to the build directory.
+    ManifestListWriteError{ err: specifications::arch::ManifestListError },
+    /// Could not serialize the information about a development build.
+    DevInfoSerializeError{ err: serde_yaml::Error },
+    /// Could not write the information about a development build to the build directory.
+    DevInfoWriteError{ path: PathBuf, err: std::io::Error },
 
     // /// Failed to remove an existing build of this package/version from the docker daemon
     // DockerCleanupError{ image: String, err: ExecutorError },
@@ -239,6 +298,15 @@ impl Display for BuildError {
             VersionParseError{ err }           => write!(f, "Could not parse OAS Document version number: {}", err),
             PackageInfoFromOpenAPIError{ err } => write!(f, "Could not convert the OAS Document into a Package Info file: {}", err),
 
+            CwlDocumentReadError{ file, err }            => write!(f, "Could not read the CWL document '{}': {}", file.display(), err),
+            CwlDocumentParseError{ file, err }           => write!(f, "Could not parse the CWL document '{}' (only CommandLineTools and Workflows are supported): {}", file.display(), err),
+            CwlDocumentIllegal{ file, reason }           => write!(f, "Illegal CWL document '{}': {}", file.display(), reason),
+            CwlUnsupported{ file, what }                 => write!(f, "CWL document '{}' uses {}, which is not supported for Brane packages", file.display(), what),
+            CwlConflictingImages{ file, image1, image2 } => write!(f, "CWL document '{}' runs tools in both '{}' and '{}', but a package can only have one image", file.display(), image1, image2),
+            CwlVersionParseError{ file, raw, err }       => write!(f, "Could not parse version '{}' in CWL document '{}': {}", raw, file.display(), err),
+            CwlContextCreateError{ err }                 => write!(f, "Could not create temporary build directory for CWL package: {}", err),
+            CwlScriptWriteError{ path, err }             => write!(f, "Could not write generated script '{}': {}", path.display(), err),
+
             LockFileExists{ path }        => write!(f, "The build directory '{}' is busy; try again later (a lock file exists)", path.display()),
             LockCreateError{ path, err }  => write!(f, "Could not create lock file '{}': {}", path.display(), err),
             LockCleanupError{ path, err } => write!(f, "Could not clean the lock file ('{}') from build directory: {}", path.display(), err),
@@ -246,6 +314,7 @@ impl Display for BuildError {
             DockerfileStrWriteError{ err } => write!(f, "Could not write to the internal DockerFile: {}", err),
             UnsafePath{ path }             => write!(f, "File '{}' tries to escape package working directory; consider moving Brane's working directory up (using --workdir) and avoid '..'", path),
             MissingExecutable{ path }      => write!(f, "Could not find the package entrypoint '{}'", path.display()),
+            NoPackageManager               => write!(f, "Cannot install dependencies in a base image without a package manager (remove them, or set 'packageManager' to the one of the base image)"),
 
             DockerfileCreateError{ path, err }                  => write!(f, "Could not create Dockerfile '{}': {}", path.display(), err),
             DockerfileWriteError{ path, err }                   => write!(f, "Could not write to Dockerfile '{}': {}", path.display(), err),
@@ -278,8 +347,11 @@ impl Display for BuildError {
             ImageBuildLaunchError{ command, err }          => write!(f, "Could not run command '{}' to build the package image: {}", command, err),
             ImageBuildError{ command, code }               => write!(f, "Command '{}' to build the package image returned exit code {}", command, code),
 
-            DigestError{ err }            => write!(f, "Could not get Docker image digest: {}", err),
-            PackageFileCreateError{ err } => write!(f, "Could not write package info to build directory: {}", err),
+            DigestError{ err }             => write!(f, "Could not get Docker image digest: {}", err),
+            PackageFileCreateError{ err }  => write!(f, "Could not write package info to build directory: {}", err),
+            ManifestListWriteError{ err }  => write!(f, "Could not write manifest list to build directory: {}", err),
+            DevInfoSerializeError{ err }   => write!(f, "Could not serialize development build info: {}", err),
+            DevInfoWriteError{ path, err } => write!(f, "Could not write development build info '{}': {}", path.display(), err),
 
             // BuildError::DockerCleanupError{ image, err } => write!(f, "Could not remove existing image '{}' from docker daemon: {}", image, err),
             FileCleanupError{ path, err } => write!(f, "Could not clean file '{}' from build directory: {}", path.display(), err),
@@ -304,6 +376,67 @@ impl Error for BuildError {}
 
 
 
+/// Collects errors during the availability subcommands.
+#[derive(Debug)]
+pub enum AvailabilityError {
+    /// Failed to fetch the login file.
+    RegistryFileError{ err: UtilError },
+    /// Failed to fetch the remote package index.
+    PackageIndexError{ address: String, err: brane_tsk::errors::ApiError },
+    /// Failed to fetch the remote data index.
+    DataIndexError{ address: String, err: brane_tsk::errors::ApiError },
+    /// The given package is not known to the remote instance.
+    UnknownPackage{ name: String, version: Version },
+    /// The given dataset is not known to the remote instance.
+    UnknownDataset{ name: String },
+
+    /// Failed to open/read a given file.
+    FileReadError{ what: &'static str, path: PathBuf, err: std::io::Error },
+    /// Failed to parse an identity file.
+    IdentityFileError{ path: PathBuf, err: reqwest::Error },
+    /// Failed to parse a certificate.
+    CertificateError{ path: PathBuf, err: reqwest::Error },
+    /// Failed to create a new reqwest proxy
+    ProxyCreateError{ address: String, err: reqwest::Error },
+    /// Failed to create a new reqwest client
+    ClientCreateError{ err: reqwest::Error },
+    /// Failed to send a request.
+    RequestError{ what: &'static str, address: String, err: reqwest::Error },
+    /// The request returned a non-2xx status code.
+    RequestFailure{ address: String, code: StatusCode, message: Option<String> },
+    /// Failed to get the request body properly.
+    ResponseTextError{ address: String, err: reqwest::Error },
+    /// Failed to parse the request body.
+    ResponseParseError{ address: String, raw: String, err: serde_json::Error },
+}
+
+impl Display for AvailabilityError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use AvailabilityError::*;
+        match self {
+            RegistryFileError{ err }                 => write!(f, "{}", err),
+            PackageIndexError{ address, err }        => write!(f, "Failed to fetch package index from '{}': {}", address, err),
+            DataIndexError{ address, err }           => write!(f, "Failed to fetch data index from '{}': {}", address, err),
+            UnknownPackage{ name, version }          => write!(f, "Unknown package '{}' (version {})", name, version),
+            UnknownDataset{ name }                   => write!(f, "Unknown dataset '{}'", name),
+
+            FileReadError{ what, path, err }         => write!(f, "Failed to read {} file '{}': {}", what, path.display(), err),
+            IdentityFileError{ path, err }           => write!(f, "Failed to parse identity file '{}': {}", path.display(), err),
+            CertificateError{ path, err }            => write!(f, "Failed to parse certificate '{}': {}", path.display(), err),
+            ProxyCreateError{ address, err }         => write!(f, "Failed to create new proxy to '{}': {}", address, err),
+            ClientCreateError{ err }                 => write!(f, "Failed to create new client: {}", err),
+            RequestError{ what, address, err }       => write!(f, "Failed to send {} request to '{}': {}", what, address, err),
+            RequestFailure{ address, code, message } => write!(f, "Request to '{}' failed with status code {} ({}){}", address, code, code.canonical_reason().unwrap_or("???"), if let Some(msg) = message { format!(": {}", msg) } else { String::new() }),
+            ResponseTextError{ address, err }        => write!(f, "Failed to get body from response sent by '{}' as text: {}", address, err),
+            ResponseParseError{ address, raw, err }  => write!(f, "Failed to parse response '{}' sent by '{}' as JSON: {}", raw, address, err),
+        }
+    }
+}
+
+impl Error for AvailabilityError {}
+
+
+
 /// Collects errors during the build subcommand
 #[derive(Debug)]
 pub enum DataError {
@@ -323,6 +456,10 @@ pub enum DataError {
     IdentityFileError{ path: PathBuf, err: reqwest::Error },
     /// Failed to parse a certificate.
     CertificateError{ path: PathBuf, err: reqwest::Error },
+    /// Failed to parse the CA log sent by the instance.
+    CaLogParseError{ address: String, err: serde_json::Error },
+    /// Failed to check, update or save the pinned CA certificate of a location.
+    PinError{ location: String, err: brane_cfg::pins::Error },
     /// A directory was not a directory but a file.
     DirNotADirError{ what: &'static str, path: PathBuf },
     /// A directory could not be removed.
@@ -367,9 +504,11 @@ pub enum DataError {
     FileNotFoundError{ path: PathBuf },
     /// The given file is not a file
     FileNotAFileError{ path: PathBuf },
+    /// The digest of a dataset registered by URL is not a valid digest.
+    DigestParseError{ err: brane_shr::fetch::Error },
     /// Failed to create the dataset's directory.
     DatasetDirCreateError{ err: UtilError },
-    /// A dataset with the given name already exists.
+    /// A dataset with the given name and version already exists.
     DuplicateDatasetError{ name: String },
     /// Failed to copy the data directory over.
     DataCopyError{ err: brane_shr::fs::Error },
@@ -394,10 +533,15 @@ pub enum DataError {
 
     // /// Failed to ensure the directory of the given dataset.
     // DatasetDirError{ err: UtilError },
+    /// The given dataset identifier was not a valid `NAME[@VERSION]` pair.
+    IllegalDataIdentifier{ err: specifications::data::DataIdentifierError },
     /// Failed to ask the user for consent before removing the dataset.
     ConfirmationError{ err: std::io::Error },
     /// Failed to remove the dataset's directory
     RemoveError{ path: PathBuf, err: std::io::Error },
+
+    /// Failed to render the list of datasets in a structured format.
+    RenderError{ err: RenderError },
 }
 
 impl Display for DataError {
@@ -413,6 +557,8 @@ impl Display for DataError {
             FileReadError{ what, path, err }         => write!(f, "Failed to read {} file '{}': {}", what, path.display(), err),
             IdentityFileError{ path, err }           => write!(f, "Failed to parse identity file '{}': {}", path.display(), err),
             CertificateError{ path, err }            => write!(f, "Failed to parse certificate '{}': {}", path.display(), err),
+            CaLogParseError{ address, err }          => write!(f, "Failed to parse CA log sent by '{}': {}", address, err),
+            PinError{ location, err }                => write!(f, "Failed to verify CA certificate of location '{}': {}", location, err),
             DirNotADirError{ what, path }            => write!(f, "{} directory '{}' is not a directory", what, path.display()),
             DirRemoveError{ what, path, err }        => write!(f, "Failed to remove {} directory '{}': {}", what, path.display(), err),
             DirCreateError{ what, path, err }        => write!(f, "Failed to create {} directory '{}': {}", what, path.display(), err),
@@ -436,8 +582,9 @@ impl Display for DataError {
             FileCanonicalizeError{ path, err } => write!(f, "Failed to resolve path '{}': {}", path.display(), err),
             FileNotFoundError{ path }          => write!(f, "Referenced file '{}' not found (are you using the correct working directory?)", path.display()),
             FileNotAFileError{ path }          => write!(f, "Referenced file '{}' is not a file", path.display()),
+            DigestParseError{ err }            => write!(f, "Invalid dataset digest: {}", err),
             DatasetDirCreateError{ err }       => write!(f, "Failed to create target dataset directory in the Brane data folder: {}", err),
-            DuplicateDatasetError{ name }      => write!(f, "A dataset with the name '{}' already exists locally", name),
+            DuplicateDatasetError{ name }      => write!(f, "Dataset '{}' already exists locally (give it another version in its data.yml, or omit it to use the next one)", name),
             DataCopyError{ err }               => write!(f, "Failed to data directory: {}", err),
             DataInfoWriteError{ err }          => write!(f, "Failed to write DataInfo file: {}", err),
 
@@ -451,8 +598,11 @@ impl Display for DataError {
             UnavailableDataset{ name, locs } => write!(f, "Dataset '{}' is unavailable{}", name, if !locs.is_empty() { format!("; try {} instead", locs.iter().map(|l| format!("'{}'", l)).collect::<Vec<String>>().join(", ")) } else { String::new() }),
 
             // DatasetDirError{ err }   => write!(f, "Failed to get to-be-removed dataset directory: {}", err),
-            ConfirmationError{ err } => write!(f, "Failed to ask the user (you) for confirmation before removing a dataset: {}", err),
-            RemoveError{ path, err } => write!(f, "Failed to remove dataset directory '{}': {}", path.display(), err),
+            IllegalDataIdentifier{ err } => write!(f, "{}", err),
+            ConfirmationError{ err }     => write!(f, "Failed to ask the user (you) for confirmation before removing a dataset: {}", err),
+            RemoveError{ path, err }     => write!(f, "Failed to remove dataset directory '{}': {}", path.display(), err),
+
+            RenderError{ err } => write!(f, "{}", err),
         }
     }
 }
@@ -491,6 +641,60 @@ impl Error for ImportError {}
 
 
 
+/// Collects errors during the instance subcommands.
+#[derive(Debug)]
+pub enum InstanceError {
+    /// Failed to find or create the Brane configuration directory.
+    ConfigDirError{ err: UtilError },
+    /// Failed to open the file with the known instances.
+    FileOpenError{ path: PathBuf, err: std::io::Error },
+    /// Failed to parse the file with the known instances.
+    FileParseError{ path: PathBuf, err: serde_yaml::Error },
+    /// Failed to create a file (either the one with the known instances or the login file).
+    FileCreateError{ path: PathBuf, err: std::io::Error },
+    /// Failed to write to a file (either the one with the known instances or the login file).
+    FileWriteError{ path: PathBuf, err: serde_yaml::Error },
+
+    /// The given address is not a valid URL.
+    IllegalAddress{ address: String, err: url::ParseError },
+    /// The given address does not have a host.
+    AddressWithoutHost{ address: String },
+    /// Could not resolve the given certificate directory.
+    CertsDirCanonicalizeError{ path: PathBuf, err: std::io::Error },
+    /// Could not load the certificates with which to connect to the central services of the instance.
+    ClientTlsError{ name: String, err: brane_tsk::tls::Error },
+
+    /// An instance with the given name already exists.
+    InstanceExists{ name: String },
+    /// There is no instance with the given name.
+    UnknownInstance{ name: String },
+}
+
+impl Display for InstanceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use InstanceError::*;
+        match self {
+            ConfigDirError{ err }        => write!(f, "{}", err),
+            FileOpenError{ path, err }   => write!(f, "Could not open instances file '{}': {}", path.display(), err),
+            FileParseError{ path, err }  => write!(f, "Could not parse instances file '{}': {}", path.display(), err),
+            FileCreateError{ path, err } => write!(f, "Could not create file '{}': {}", path.display(), err),
+            FileWriteError{ path, err }  => write!(f, "Could not write to file '{}': {}", path.display(), err),
+
+            IllegalAddress{ address, err }         => write!(f, "Illegal address '{}': {}", address, err),
+            AddressWithoutHost{ address }          => write!(f, "Address '{}' does not have a (valid) host", address),
+            CertsDirCanonicalizeError{ path, err } => write!(f, "Could not resolve certificate directory '{}': {}", path.display(), err),
+            ClientTlsError{ name, err }            => write!(f, "Could not load certificates of instance '{}': {}", name, err),
+
+            InstanceExists{ name }  => write!(f, "An instance named '{}' already exists; remove it first with 'brane instance remove'", name),
+            UnknownInstance{ name } => write!(f, "Unknown instance '{}'; add it first with 'brane instance add'", name),
+        }
+    }
+}
+
+impl Error for InstanceError {}
+
+
+
 /// Lists the errors that can occur when trying to do stuff with packages
 #[derive(Debug)]
 pub enum PackageError {
@@ -517,6 +721,8 @@ pub enum PackageError {
     PackageInfoNoDigest{ path: PathBuf },
     /// Could not remove the given image from the Docker daemon
     DockerRemoveError{ image: Image, err: brane_tsk::errors::DockerError },
+    /// Failed to write the structured output.
+    RenderError{ err: RenderError },
 }
 
 impl std::fmt::Display for PackageError {
@@ -535,6 +741,7 @@ impl std::fmt::Display for PackageError {
             PackageInfoError{ path, err }                 => write!(f, "Could not load package info file '{}': {}", path.display(), err),
             PackageInfoNoDigest{ path }                   => write!(f, "Package info file '{}' has no digest set", path.display()),
             DockerRemoveError{ image, err }               => write!(f, "Failed to remove image '{}' from the local Docker daemon: {}", image.digest().unwrap_or("<no digest given>"), err),
+            RenderError{ err }                            => write!(f, "{}", err),
         }
     }
 }
@@ -543,6 +750,46 @@ impl std::error::Error for PackageError {}
 
 
 
+/// Collects errors during the profile subcommand.
+#[derive(Debug)]
+pub enum ProfileError {
+    /// Could not connect to the given address
+    ClientConnectError{ address: String, err: tonic::transport::Error },
+    /// Failed to get the timings of a run.
+    GetTimingsError{ address: String, id: String, err: tonic::Status },
+    /// The session did not run a workflow yet.
+    NoWorkflow{ id: String },
+    /// Failed to parse the workflow returned by the driver.
+    WorkflowParseError{ id: String, err: serde_json::Error },
+    /// Failed to parse the timings returned by the driver.
+    TimingsParseError{ id: String, err: serde_json::Error },
+
+    /// Failed to create the flamegraph file.
+    FlamegraphCreateError{ path: PathBuf, err: std::io::Error },
+    /// Failed to write the flamegraph file.
+    FlamegraphWriteError{ path: PathBuf, err: serde_json::Error },
+}
+
+impl Display for ProfileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use ProfileError::*;
+        match self {
+            ClientConnectError{ address, err }  => write!(f, "Could not connect to remote Brane instance '{}': {}", address, err),
+            GetTimingsError{ address, id, err } => write!(f, "Could not get timings of run '{}' on remote Brane instance '{}': remote returned status: {}", id, address, err),
+            NoWorkflow{ id }                    => write!(f, "Run '{}' did not execute a workflow yet", id),
+            WorkflowParseError{ id, err }       => write!(f, "Could not parse workflow of run '{}' returned by the remote Brane instance: {}", id, err),
+            TimingsParseError{ id, err }        => write!(f, "Could not parse timings of run '{}' returned by the remote Brane instance: {}", id, err),
+
+            FlamegraphCreateError{ path, err } => write!(f, "Could not create flamegraph file '{}': {}", path.display(), err),
+            FlamegraphWriteError{ path, err }  => write!(f, "Could not write flamegraph to '{}': {}", path.display(), err),
+        }
+    }
+}
+
+impl Error for ProfileError {}
+
+
+
 /// Collects errors during the registry subcommands
 #[derive(Debug)]
 pub enum RegistryError {
@@ -587,6 +834,8 @@ pub enum RegistryError {
     PackageInfoWriteError{ path: PathBuf, err: serde_yaml::Error },
     /// Failed to retrieve the PackageInfo
     NoPackageInfo{ url: String },
+    /// Failed to get the host architecture to pull the image for
+    HostArchError{ err: specifications::arch::ArchError },
 
     /// Failed to resolve the packages directory
     PackagesDirError{ err: UtilError },
@@ -596,12 +845,27 @@ pub enum RegistryError {
     PackageDirError{ name: String, version: Version, err: UtilError },
     /// Could not create a new temporary file
     TempFileError{ err: std::io::Error },
+    /// Could not read the manifest list of a multi-arch package
+    ManifestListReadError{ name: String, version: Version, err: specifications::arch::ManifestListError },
     /// Could not compress the package file
     CompressionError{ name: String, version: Version, path: PathBuf, err: std::io::Error },
     /// Failed to re-open the compressed package file
     PackageArchiveOpenError{ path: PathBuf, err: std::io::Error },
     /// Failed to upload the compressed file to the instance
     UploadError{ path: PathBuf, endpoint: String, err: reqwest::Error },
+
+    /// Failed to send the request for the instance's image registry
+    ImageRegistryRequestError{ url: String, err: reqwest::Error },
+    /// The instance does not have an image registry configured
+    NoImageRegistry{ url: String },
+    /// The request for the image registry was met with a non-200 status code
+    ImageRegistryRequestFailure{ url: String, status: reqwest::StatusCode },
+    /// Failed to parse the image registry info returned by the instance
+    ImageRegistryParseError{ url: String, err: reqwest::Error },
+    /// Failed to launch a docker command
+    DockerLaunchError{ command: String, err: std::io::Error },
+    /// A docker command returned a non-zero exit code
+    DockerCommandError{ command: String, code: i32, stderr: String },
 }
 
 impl Display for RegistryError {
@@ -630,14 +894,23 @@ impl Display for RegistryError {
             PackageInfoCreateError{ path, err }      => write!(f, "Could not create PackageInfo file '{}': {}", path.display(), err),
             PackageInfoWriteError{ path, err }       => write!(f, "Could not write to PackageInfo file '{}': {}", path.display(), err),
             NoPackageInfo{ url }                     => write!(f, "Server '{}' responded with empty response (is your name/version correct?)", url),
+            HostArchError{ err }                     => write!(f, "Could not get the host processor architecture: {}", err),
 
             PackagesDirError{ err }                      => write!(f, "Could not resolve the packages directory: {}", err),
             VersionsError{ name, err }                   => write!(f, "Could not get version list for package '{}': {}", name, err),
             PackageDirError{ name, version, err }        => write!(f, "Could not resolve package directory of package '{}' (version {}): {}", name, version, err),
             TempFileError{ err }                         => write!(f, "Could not create a new temporary file: {}", err),
+            ManifestListReadError{ name, version, err }  => write!(f, "Could not read manifest list of package '{}' (version {}): {}", name, version, err),
             CompressionError{ name, version, path, err } => write!(f, "Could not compress package '{}' (version {}) to '{}': {}", name, version, path.display(), err),
             PackageArchiveOpenError{ path, err }         => write!(f, "Could not re-open compressed package archive '{}': {}", path.display(), err),
             UploadError{ path, endpoint, err }           => write!(f, "Could not upload compressed package archive '{}' to '{}': {}", path.display(), endpoint, err),
+
+            ImageRegistryRequestError{ url, err }       => write!(f, "Could not send request for image registry to '{}': {}", url, err),
+            NoImageRegistry{ url }                      => write!(f, "Instance at '{}' does not have an image registry configured", url),
+            ImageRegistryRequestFailure{ url, status }  => write!(f, "Request for image registry to '{}' was met with status code {} ({})", url, status.as_u16(), status.canonical_reason().unwrap_or("???")),
+            ImageRegistryParseError{ url, err }         => write!(f, "Could not parse image registry info received from '{}': {}", url, err),
+            DockerLaunchError{ command, err }           => write!(f, "Could not run command '{}': {}", command, err),
+            DockerCommandError{ command, code, stderr } => write!(f, "Command '{}' failed with exit code {}\n\nstderr:\n{}\n", command, code, stderr),
         }
     }
 }
@@ -656,6 +929,28 @@ pub enum ReplError {
     /// Failed to create the new rustyline editor.
     EditorCreateError{ err: rustyline::error::ReadlineError },
 
+    /// Failed to create the temporary file for the `:edit` command.
+    EditFileCreateError{ err: std::io::Error },
+    /// Failed to launch the user's editor.
+    EditorLaunchError{ editor: String, err: std::io::Error },
+    /// The user's editor did not exit successfully.
+    EditorFailure{ editor: String, status: std::process::ExitStatus },
+    /// Failed to read back the file that was written with the user's editor.
+    EditFileReadError{ path: PathBuf, err: std::io::Error },
+
+    /// Failed to create the file to record the session to.
+    RecordCreateError{ path: PathBuf, err: std::io::Error },
+    /// Failed to serialize a recorded snippet.
+    RecordSerializeError{ err: serde_json::Error },
+    /// Failed to write a recorded snippet to the record file.
+    RecordWriteError{ path: PathBuf, err: std::io::Error },
+    /// Failed to open the recorded session to replay.
+    ReplayOpenError{ path: PathBuf, err: std::io::Error },
+    /// Failed to read a line of the recorded session to replay.
+    ReplayReadError{ path: PathBuf, err: std::io::Error },
+    /// A line of the recorded session to replay was not a valid recorded snippet.
+    ReplayParseError{ path: PathBuf, line: usize, err: serde_json::Error },
+
     /// Failed to initialize one of the states.
     InitializeError{ what: &'static str, err: RunError },
     /// Failed to run one of the VMs/clients.
@@ -672,6 +967,18 @@ impl Display for ReplError {
             HistoryFileError{ err }     => write!(f, "Could not get REPL history file location: {}", err),
             EditorCreateError{ err }    => write!(f, "Failed to create new rustyline editor: {}", err),
 
+            EditFileCreateError{ err }       => write!(f, "Failed to create temporary file to edit snippet in: {}", err),
+            EditorLaunchError{ editor, err } => write!(f, "Failed to launch editor '{}' (set $VISUAL or $EDITOR to use another one): {}", editor, err),
+            EditorFailure{ editor, status }  => write!(f, "Editor '{}' failed ({})", editor, status),
+            EditFileReadError{ path, err }   => write!(f, "Failed to read edited snippet '{}': {}", path.display(), err),
+
+            RecordCreateError{ path, err }      => write!(f, "Failed to create record file '{}': {}", path.display(), err),
+            RecordSerializeError{ err }         => write!(f, "Failed to serialize recorded snippet: {}", err),
+            RecordWriteError{ path, err }       => write!(f, "Failed to write recorded snippet to '{}': {}", path.display(), err),
+            ReplayOpenError{ path, err }        => write!(f, "Failed to open recorded session '{}': {}", path.display(), err),
+            ReplayReadError{ path, err }        => write!(f, "Failed to read recorded session '{}': {}", path.display(), err),
+            ReplayParseError{ path, line, err } => write!(f, "Line {} of recorded session '{}' is not a valid recorded snippet: {}", line, path.display(), err),
+
             InitializeError{ what, err } => write!(f, "Failed to initialize {} and associated structures: {}", what, err),
             RunError{ what, err }        => write!(f, "Failed to execute workflow on {}: {}", what, err),
             ProcessError{ what, err }    => write!(f, "Failed to process {} workflow results: {}", what, err),
@@ -696,6 +1003,10 @@ pub enum RunError {
     DatasetsDirError{ err: UtilError },
     /// Failed to create a temporary intermediate results directory.
     ResultsDirCreateError{ err: std::io::Error },
+    /// Failed to create the file to trace the workflow to.
+    TracerCreateError{ err: brane_exe::trace::Error },
+    /// The user asked for a VM plugin that we don't know.
+    UnknownVmPlugin{ name: String, known: Vec<String> },
 
     /// Failed to fetch the login file.
     RegistryFileError{ err: UtilError },
@@ -711,6 +1022,10 @@ pub enum RunError {
     AppIdError{ address: String, raw: String, err: brane_tsk::errors::IdError },
     /// Could not create a new session on the given address
     SessionCreateError{ address: String, err: tonic::Status },
+    /// Could not inspect an existing session on the given address
+    SessionInspectError{ address: String, err: tonic::Status },
+    /// Failed to parse the frames returned by the remote driver.
+    FramesParseError{ address: String, err: serde_json::Error },
 
     /// An error occurred while compile the given snippet. It will already have been printed to stdout.
     CompileError{ what: String, errs: Vec<brane_ast::Error> },
@@ -718,10 +1033,16 @@ pub enum RunError {
     WorkflowSerializeError{ err: serde_json::Error },
     /// Requesting a command failed
     CommandRequestError{ address: String, err: tonic::Status },
+    /// Failed to reconnect to a workflow after the connection dropped.
+    ReconnectError{ address: String, attempts: u32, err: tonic::Status },
+    /// Failed to attach to a (detached) workflow.
+    AttachError{ address: String, err: tonic::Status },
     /// Failed to parse the value returned by the remote driver.
     ValueParseError{ address: String, raw: String, err: serde_json::Error },
     /// Failed to run the workflow
     ExecError{ err: OfflineVmError },
+    /// The user asked to detach from a workflow that runs locally.
+    LocalDetachError,
 
     /// The returned dataset was unknown.
     UnknownDataset{ name: String },
@@ -734,6 +1055,8 @@ pub enum RunError {
     StdinReadError{ err: std::io::Error },
     /// Failed to read the source from a given file
     FileReadError{ path: PathBuf, err: std::io::Error },
+    /// A workflow parameter given on the command-line was not of the form `<name>=<value>`.
+    IllegalParam{ raw: String },
     // /// Failed to compile the given file (the reasons have already been printed to stderr).
     // CompileError{ path: PathBuf, errs: Vec<brane_ast::Error> },
 }
@@ -748,6 +1071,8 @@ impl Display for RunError {
             PackagesDirError{ err }        => write!(f, "Failed to get packages directory: {}", err),
             DatasetsDirError{ err }        => write!(f, "Failed to get datasets directory: {}", err),
             ResultsDirCreateError{ err }   => write!(f, "Failed to create new temporary directory as an intermediate result directory: {}", err),
+            TracerCreateError{ err }       => write!(f, "Failed to setup workflow tracing: {}", err),
+            UnknownVmPlugin{ name, known } => write!(f, "Unknown VM plugin '{}' (known plugins: {})", name, known.join(", ")),
 
             RegistryFileError{ err }                => write!(f, "Could not read registry file: {}", err),
             RemotePackageIndexError{ address, err } => write!(f, "Failed to fetch remote package index from '{}': {}", address, err),
@@ -756,12 +1081,17 @@ impl Display for RunError {
             ClientConnectError{ address, err }      => write!(f, "Could not connect to remote Brane instance '{}': {}", address, err),
             AppIdError{ address, raw, err }         => write!(f, "Could not parse '{}' send by remote '{}' as an application ID: {}", raw, address, err),
             SessionCreateError{ address, err }      => write!(f, "Could not create new session with remote Brane instance '{}': remote returned status: {}", address, err),
-
-            CompileError{ .. }                   => write!(f, "Compilation of workflow failed (see output above)"),
-            WorkflowSerializeError{ err }        => write!(f, "Failed to serialize the compiled workflow: {}", err),
-            CommandRequestError{ address, err }  => write!(f, "Could not run command on remote Brane instance '{}': request failed: remote returned status: {}", address, err),
-            ValueParseError{ address, raw, err } => write!(f, "Could not parse '{}' sent by remote '{}' as a value: {}", raw, address, err),
-            ExecError{ err }                     => write!(f, "Failed to run workflow: {}", err),
+            SessionInspectError{ address, err }     => write!(f, "Could not inspect session on remote Brane instance '{}': remote returned status: {}", address, err),
+            FramesParseError{ address, err }        => write!(f, "Could not parse session summary sent by remote '{}': {}", address, err),
+
+            CompileError{ .. }                       => write!(f, "Compilation of workflow failed (see output above)"),
+            WorkflowSerializeError{ err }            => write!(f, "Failed to serialize the compiled workflow: {}", err),
+            CommandRequestError{ address, err }      => write!(f, "Could not run command on remote Brane instance '{}': request failed: remote returned status: {}", address, err),
+            ReconnectError{ address, attempts, err } => write!(f, "Could not reconnect to remote Brane instance '{}' after {} attempt(s): {}", address, attempts, err),
+            AttachError{ address, err }              => write!(f, "Could not attach to run on remote Brane instance '{}': remote returned status: {}", address, err),
+            ValueParseError{ address, raw, err }     => write!(f, "Could not parse '{}' sent by remote '{}' as a value: {}", raw, address, err),
+            ExecError{ err }                         => write!(f, "Failed to run workflow: {}", err),
+            LocalDetachError                         => write!(f, "Cannot detach from a workflow that runs locally; give '--remote' or '--instance' to run it on a Brane instance"),
 
             UnknownDataset{ name }           => write!(f, "Unknown dataset '{}'", name),
             UnavailableDataset{ name, locs } => write!(f, "Unavailable dataset '{}'{}", name, if !locs.is_empty() { format!("; it is available at {}", PrettyListFormatter::new(locs.iter().map(|l| format!("'{}'", l)), "or")) } else { String::new() }),
@@ -769,6 +1099,7 @@ impl Display for RunError {
 
             StdinReadError{ err }      => write!(f, "Failed to read source from stdin: {}", err),
             FileReadError{ path, err } => write!(f, "Failed to read source from file '{}': {}", path.display(), err),
+            IllegalParam{ raw }        => write!(f, "Illegal workflow parameter '{}'; expected it to be '<name>=<value>'", raw),
         }
     }
 }
@@ -777,6 +1108,338 @@ impl Error for RunError {}
 
 
 
+/// Collects errors during the runs subcommand(s).
+#[derive(Debug)]
+pub enum RunsError {
+    /// Failed to fetch the login file.
+    RegistryFileError{ err: UtilError },
+    /// Could not connect to the given address
+    ClientConnectError{ address: String, err: tonic::transport::Error },
+    /// Failed to list the pending approvals.
+    ListApprovalsError{ address: String, err: tonic::Status },
+    /// Failed to decide on an approval.
+    DecideApprovalError{ address: String, id: String, err: tonic::Status },
+    /// Failed to send an event to a session.
+    SendEventError{ address: String, id: String, name: String, err: tonic::Status },
+
+    /// A what-if scenario was not given as `<location>=<factor>`.
+    IllegalSpeedup{ raw: String },
+    /// Failed to get the timings of a run.
+    GetTimingsError{ address: String, id: String, err: tonic::Status },
+    /// The session did not run a workflow yet.
+    NoWorkflow{ id: String },
+    /// Failed to parse the workflow returned by the driver.
+    WorkflowParseError{ id: String, err: serde_json::Error },
+    /// Failed to parse the timings returned by the driver.
+    TimingsParseError{ id: String, err: serde_json::Error },
+    /// Failed to fetch the registries of the locations in the instance.
+    RegistriesError{ address: String, err: brane_tsk::errors::ApiError },
+
+    /// Failed to attach to a (detached) run.
+    AttachError{ id: String, err: RunError },
+}
+
+impl Display for RunsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use RunsError::*;
+        match self {
+            RegistryFileError{ err }                 => write!(f, "{}", err),
+            ClientConnectError{ address, err }       => write!(f, "Could not connect to remote Brane instance '{}': {}", address, err),
+            ListApprovalsError{ address, err }       => write!(f, "Could not list pending approvals on remote Brane instance '{}': remote returned status: {}", address, err),
+            DecideApprovalError{ address, id, err }  => write!(f, "Could not decide on approval '{}' on remote Brane instance '{}': remote returned status: {}", id, address, err),
+            SendEventError{ address, id, name, err } => write!(f, "Could not send event '{}' to session '{}' on remote Brane instance '{}': remote returned status: {}", name, id, address, err),
+
+            IllegalSpeedup{ raw }               => write!(f, "Illegal what-if scenario '{}': expected '<location>=<factor>' with a positive factor", raw),
+            GetTimingsError{ address, id, err } => write!(f, "Could not get timings of run '{}' on remote Brane instance '{}': remote returned status: {}", id, address, err),
+            NoWorkflow{ id }                    => write!(f, "Run '{}' did not execute a workflow yet", id),
+            WorkflowParseError{ id, err }       => write!(f, "Could not parse workflow of run '{}' returned by the remote Brane instance: {}", id, err),
+            TimingsParseError{ id, err }        => write!(f, "Could not parse timings of run '{}' returned by the remote Brane instance: {}", id, err),
+            RegistriesError{ address, err }     => write!(f, "Could not fetch registries of the locations from '{}': {}", address, err),
+
+            AttachError{ id, err } => write!(f, "Could not attach to run '{}': {}", id, err),
+        }
+    }
+}
+
+impl Error for RunsError {}
+
+
+
+/// Collects errors during the session subcommands.
+#[derive(Debug)]
+pub enum SessionError {
+    /// Could not connect to the given address
+    ClientConnectError{ address: String, err: tonic::transport::Error },
+    /// Failed to list the sessions.
+    ListSessionsError{ address: String, err: tonic::Status },
+    /// Failed to kill a session.
+    KillSessionError{ address: String, id: String, err: tonic::Status },
+}
+
+impl Display for SessionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use SessionError::*;
+        match self {
+            ClientConnectError{ address, err }   => write!(f, "Could not connect to remote Brane instance '{}': {}", address, err),
+            ListSessionsError{ address, err }    => write!(f, "Could not list sessions on remote Brane instance '{}': remote returned status: {}", address, err),
+            KillSessionError{ address, id, err } => write!(f, "Could not kill session '{}' on remote Brane instance '{}': remote returned status: {}", id, address, err),
+        }
+    }
+}
+
+impl Error for SessionError {}
+
+
+
+/// Collects errors during the new subcommand.
+#[derive(Debug)]
+pub enum ScaffoldError {
+    /// The package kind cannot be scaffolded.
+    UnsupportedKind{ kind: PackageKind },
+    /// The name of the package is not valid.
+    IllegalName{ name: String, err: UtilError },
+    /// The language of the entrypoint is not one we know.
+    UnknownLanguage{ raw: String },
+
+    /// The directory to scaffold in already has files in it.
+    DirNotEmpty{ path: PathBuf },
+    /// Failed to read the directory to scaffold in.
+    DirReadError{ path: PathBuf, err: std::io::Error },
+    /// Failed to create a directory of the package.
+    DirCreateError{ path: PathBuf, err: std::io::Error },
+    /// Failed to write a file of the package.
+    FileWriteError{ path: PathBuf, err: std::io::Error },
+    /// Failed to make a file of the package executable.
+    FilePermissionsError{ path: PathBuf, err: std::io::Error },
+}
+
+impl Display for ScaffoldError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use ScaffoldError::*;
+        match self {
+            UnsupportedKind{ kind }  => write!(f, "Cannot scaffold a {} (only 'ecu' and 'oas' packages are supported)", kind.pretty()),
+            IllegalName{ name, err } => write!(f, "Illegal package name '{}': {}", name, err),
+            UnknownLanguage{ raw }   => write!(f, "Unknown entrypoint language '{}' (expected 'python' or 'bash')", raw),
+
+            DirNotEmpty{ path }               => write!(f, "Directory '{}' already exists and is not empty", path.display()),
+            DirReadError{ path, err }         => write!(f, "Could not read directory '{}': {}", path.display(), err),
+            DirCreateError{ path, err }       => write!(f, "Could not create directory '{}': {}", path.display(), err),
+            FileWriteError{ path, err }       => write!(f, "Could not write file '{}': {}", path.display(), err),
+            FilePermissionsError{ path, err } => write!(f, "Could not make file '{}' executable: {}", path.display(), err),
+        }
+    }
+}
+
+impl Error for ScaffoldError {}
+
+
+
+/// Collects errors during the package codegen subcommand.
+#[derive(Debug)]
+pub enum CodegenError {
+    /// The language to generate a client in is not one we know.
+    UnknownLanguage{ raw: String },
+
+    /// Failed to get the directory of the package.
+    PackageDirError{ name: String, version: Version, err: UtilError },
+    /// Failed to read the package info of the package.
+    PackageInfoError{ name: String, version: Version, err: specifications::package::PackageInfoError },
+    /// Failed to write the generated client.
+    FileWriteError{ path: PathBuf, err: std::io::Error },
+}
+
+impl Display for CodegenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use CodegenError::*;
+        match self {
+            UnknownLanguage{ raw } => write!(f, "Unknown language '{}' to generate a client in (expected 'python')", raw),
+
+            PackageDirError{ name, version, err }  => write!(f, "Failed to get directory of package '{}' (version {}): {}", name, version, err),
+            PackageInfoError{ name, version, err } => write!(f, "Failed to read package info for package '{}' (version {}): {}", name, version, err),
+            FileWriteError{ path, err }            => write!(f, "Could not write generated client '{}': {}", path.display(), err),
+        }
+    }
+}
+
+impl Error for CodegenError {}
+
+
+
+/// Collects errors during the kernel subcommand(s).
+#[derive(Debug)]
+pub enum KernelError {
+    /// Could not find the Jupyter kernels directory of the current user.
+    KernelsDirNotFound,
+    /// Could not find the path of the running executable.
+    ExecutableError{ err: std::io::Error },
+    /// Failed to serialize the kernel spec.
+    KernelSpecSerializeError{ err: serde_json::Error },
+    /// Failed to create the directory of the kernel.
+    KernelDirCreateError{ path: PathBuf, err: std::io::Error },
+    /// Failed to write the kernel spec.
+    KernelSpecWriteError{ path: PathBuf, err: std::io::Error },
+
+    /// Failed to read the connection file.
+    ConnectionFileReadError{ path: PathBuf, err: std::io::Error },
+    /// Failed to parse the connection file.
+    ConnectionFileParseError{ path: PathBuf, err: serde_json::Error },
+    /// The connection file asks us to sign messages in a way we do not know.
+    UnsupportedSignatureScheme{ scheme: String },
+    /// Failed to start a session on the driver.
+    InitializeError{ endpoint: String, err: RunError },
+
+    /// Failed to bind one of the sockets.
+    SocketBindError{ what: &'static str, endpoint: String, err: zeromq::ZmqError },
+    /// Failed to receive a message on one of the sockets.
+    SocketRecvError{ what: &'static str, err: zeromq::ZmqError },
+    /// Failed to send a message on one of the sockets.
+    SocketSendError{ what: &'static str, err: zeromq::ZmqError },
+}
+
+impl Display for KernelError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use KernelError::*;
+        match self {
+            KernelsDirNotFound                => write!(f, "Could not find the Jupyter kernels directory of the current user (specify one with '--dir')"),
+            ExecutableError{ err }            => write!(f, "Could not get the path of the brane executable: {}", err),
+            KernelSpecSerializeError{ err }   => write!(f, "Failed to serialize kernel spec: {}", err),
+            KernelDirCreateError{ path, err } => write!(f, "Failed to create kernel directory '{}': {}", path.display(), err),
+            KernelSpecWriteError{ path, err } => write!(f, "Failed to write kernel spec '{}': {}", path.display(), err),
+
+            ConnectionFileReadError{ path, err }  => write!(f, "Failed to read connection file '{}': {}", path.display(), err),
+            ConnectionFileParseError{ path, err } => write!(f, "Failed to parse connection file '{}': {}", path.display(), err),
+            UnsupportedSignatureScheme{ scheme }  => write!(f, "Unsupported signature scheme '{}' (expected 'hmac-sha256')", scheme),
+            InitializeError{ endpoint, err }      => write!(f, "Failed to start a session on remote '{}': {}", endpoint, err),
+
+            SocketBindError{ what, endpoint, err } => write!(f, "Failed to bind {} socket to '{}': {}", what, endpoint, err),
+            SocketRecvError{ what, err }           => write!(f, "Failed to receive message on {} socket: {}", what, err),
+            SocketSendError{ what, err }           => write!(f, "Failed to send message on {} socket: {}", what, err),
+        }
+    }
+}
+
+impl Error for KernelError {}
+
+
+
+/// Collects errors during the script subcommand(s).
+#[derive(Debug)]
+pub enum ScriptError {
+    /// Failed to read or resolve the workflow.
+    WorkflowError{ err: WorkflowError },
+    /// Failed to open the fixture file.
+    FixtureOpenError{ path: PathBuf, err: std::io::Error },
+    /// Failed to parse the fixture file.
+    FixtureParseError{ path: PathBuf, err: serde_yaml::Error },
+
+    /// Some of the tests failed.
+    TestsFailed{ failed: usize, total: usize },
+}
+
+impl Display for ScriptError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use ScriptError::*;
+        match self {
+            WorkflowError{ err }           => write!(f, "{}", err),
+            FixtureOpenError{ path, err }  => write!(f, "Failed to open fixture file '{}': {}", path.display(), err),
+            FixtureParseError{ path, err } => write!(f, "Failed to parse fixture file '{}' as YAML: {}", path.display(), err),
+
+            TestsFailed{ failed, total } => write!(f, "{} out of {} tests failed", failed, total),
+        }
+    }
+}
+
+impl Error for ScriptError {}
+
+
+
+/// Collects errors during the sweep subcommand(s).
+#[derive(Debug)]
+pub enum SweepError {
+    /// Failed to find or create the Brane configuration directory.
+    ConfigDirError{ err: UtilError },
+    /// Failed to create the directory with the sweep groups.
+    SweepsDirCreateError{ path: PathBuf, err: std::io::Error },
+    /// Failed to read the directory with the sweep groups.
+    SweepsDirReadError{ path: PathBuf, err: std::io::Error },
+    /// Failed to open a sweep group file.
+    FileOpenError{ path: PathBuf, err: std::io::Error },
+    /// Failed to parse a sweep group file.
+    FileParseError{ path: PathBuf, err: serde_yaml::Error },
+    /// Failed to create a sweep group file.
+    FileCreateError{ path: PathBuf, err: std::io::Error },
+    /// Failed to write a sweep group file.
+    FileWriteError{ path: PathBuf, err: serde_yaml::Error },
+    /// There is no sweep with the given identifier.
+    UnknownSweep{ id: String },
+
+    /// Failed to read the workflow file.
+    FileReadError{ path: PathBuf, err: std::io::Error },
+    /// Failed to open the matrix file.
+    MatrixOpenError{ path: PathBuf, err: std::io::Error },
+    /// Failed to parse the matrix file.
+    MatrixParseError{ path: PathBuf, err: serde_yaml::Error },
+    /// The matrix does not define any parameters.
+    EmptyMatrix{ path: PathBuf },
+    /// A parameter in the matrix does not have any values.
+    EmptyParameter{ name: String },
+    /// A parameter in the matrix has values of different types.
+    MixedParameter{ name: String, first: &'static str, other: &'static str },
+
+    /// Failed to prepare compiling and running on the remote instance.
+    InitializeError{ err: RunError },
+    /// Failed to compile the workflow.
+    CompileError{ err: RunError },
+    /// The placeholder for a parameter was not found in the compiled workflow.
+    ParameterNotFound{ name: String },
+    /// Failed to serialize a run's workflow.
+    WorkflowSerializeError{ err: serde_json::Error },
+    /// Could not connect to the given address
+    ClientConnectError{ address: String, err: tonic::transport::Error },
+    /// Failed to create the session for a run.
+    SessionCreateError{ address: String, err: tonic::Status },
+    /// The remote returned an invalid session identifier.
+    AppIdError{ address: String, raw: String, err: brane_tsk::errors::IdError },
+    /// Failed to submit a run.
+    ExecuteError{ address: String, session: String, err: tonic::Status },
+}
+
+impl Display for SweepError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use SweepError::*;
+        match self {
+            ConfigDirError{ err }             => write!(f, "{}", err),
+            SweepsDirCreateError{ path, err } => write!(f, "Could not create sweeps directory '{}': {}", path.display(), err),
+            SweepsDirReadError{ path, err }   => write!(f, "Could not read sweeps directory '{}': {}", path.display(), err),
+            FileOpenError{ path, err }        => write!(f, "Could not open sweep file '{}': {}", path.display(), err),
+            FileParseError{ path, err }       => write!(f, "Could not parse sweep file '{}': {}", path.display(), err),
+            FileCreateError{ path, err }      => write!(f, "Could not create sweep file '{}': {}", path.display(), err),
+            FileWriteError{ path, err }       => write!(f, "Could not write to sweep file '{}': {}", path.display(), err),
+            UnknownSweep{ id }                => write!(f, "Unknown sweep '{}'; use 'brane sweep list' to see the known sweeps", id),
+
+            FileReadError{ path, err }           => write!(f, "Could not read workflow file '{}': {}", path.display(), err),
+            MatrixOpenError{ path, err }         => write!(f, "Could not open matrix file '{}': {}", path.display(), err),
+            MatrixParseError{ path, err }        => write!(f, "Could not parse matrix file '{}' (expected a map of parameters to lists of booleans, integers, reals or strings): {}", path.display(), err),
+            EmptyMatrix{ path }                  => write!(f, "Matrix file '{}' does not define any parameters", path.display()),
+            EmptyParameter{ name }               => write!(f, "Parameter '{}' does not have any values to sweep over", name),
+            MixedParameter{ name, first, other } => write!(f, "Parameter '{}' has values of different types ({} and {})", name, first, other),
+
+            InitializeError{ err }                => write!(f, "{}", err),
+            CompileError{ err }                   => write!(f, "{}", err),
+            ParameterNotFound{ name }             => write!(f, "Could not find the definition of parameter '{}' in the compiled workflow", name),
+            WorkflowSerializeError{ err }         => write!(f, "Failed to serialize workflow: {}", err),
+            ClientConnectError{ address, err }    => write!(f, "Could not connect to remote Brane instance '{}': {}", address, err),
+            SessionCreateError{ address, err }    => write!(f, "Could not create new session with remote Brane instance '{}': remote returned status: {}", address, err),
+            AppIdError{ address, raw, err }       => write!(f, "Could not parse '{}' send by remote '{}' as an application ID: {}", raw, address, err),
+            ExecuteError{ address, session, err } => write!(f, "Could not submit run in session '{}' to remote Brane instance '{}': remote returned status: {}", session, address, err),
+        }
+    }
+}
+
+impl Error for SweepError {}
+
+
+
 /// Collects errors during the test subcommand.
 #[derive(Debug)]
 pub enum TestError {
@@ -815,6 +1478,29 @@ pub enum TestError {
     RunError{ err: RunError },
     /// Failed to read the intermediate results file.
     IntermediateResultFileReadError{ path: PathBuf, err: std::io::Error },
+
+    /// The given fixture name cannot be used as a filename.
+    IllegalFixtureName{ name: String },
+    /// Failed to get the directory with the fixtures of a package.
+    FixturesDirError{ err: UtilError },
+    /// The given fixture does not exist.
+    FixtureNotFound{ package: String, name: String, path: PathBuf },
+    /// Failed to read a fixture (or arguments) file.
+    FixtureReadError{ path: PathBuf, err: std::io::Error },
+    /// Failed to parse a fixture (or arguments) file.
+    FixtureParseError{ path: PathBuf, err: serde_json::Error },
+    /// Failed to serialize a fixture.
+    FixtureSerializeError{ err: serde_json::Error },
+    /// Failed to create the directory with the fixtures of a package.
+    FixturesDirCreateError{ path: PathBuf, err: std::io::Error },
+    /// Failed to write a fixture file.
+    FixtureWriteError{ path: PathBuf, err: std::io::Error },
+    /// The function in a fixture is not defined by the package.
+    UnknownFunction{ package: String, function: String },
+    /// A fixture does not give a value for a required argument.
+    MissingArgument{ function: String, name: String },
+    /// A fixture gives a value of the wrong type for an argument.
+    ArgumentTypeError{ function: String, name: String, got: DataType, expected: DataType },
 }
 
 impl Display for TestError {
@@ -841,6 +1527,18 @@ impl Display for TestError {
             InitializeError{ err }                       => write!(f, "Failed to initialize offline VM: {}", err),
             RunError{ err }                              => write!(f, "Failed to run offline VM: {}", err),
             IntermediateResultFileReadError{ path, err } => write!(f, "Failed to read intermediate result file '{}': {}", path.display(), err),
+
+            IllegalFixtureName{ name }                         => write!(f, "Illegal fixture name '{}' (it may not be empty, start with a dot or contain slashes)", name),
+            FixturesDirError{ err }                            => write!(f, "Failed to get fixtures directory: {}", err),
+            FixtureNotFound{ package, name, path }             => write!(f, "Package '{}' has no fixture '{}' (file '{}' not found)", package, name, path.display()),
+            FixtureReadError{ path, err }                      => write!(f, "Failed to read fixture file '{}': {}", path.display(), err),
+            FixtureParseError{ path, err }                     => write!(f, "Failed to parse fixture file '{}': {}", path.display(), err),
+            FixtureSerializeError{ err }                       => write!(f, "Failed to serialize fixture: {}", err),
+            FixturesDirCreateError{ path, err }                => write!(f, "Failed to create fixtures directory '{}': {}", path.display(), err),
+            FixtureWriteError{ path, err }                     => write!(f, "Failed to write fixture file '{}': {}", path.display(), err),
+            UnknownFunction{ package, function }               => write!(f, "Package '{}' does not define a function '{}'", package, function),
+            MissingArgument{ function, name }                  => write!(f, "Missing value for argument '{}' of function '{}'", name, function),
+            ArgumentTypeError{ function, name, got, expected } => write!(f, "Argument '{}' of function '{}' expects a value of type {}, but got {}", name, function, expected, got),
         }
     }
 }
@@ -911,6 +1609,69 @@ impl Error for VersionError {}
 
 
 
+/// Collects errors relating to the workflow command(s).
+#[derive(Debug)]
+pub enum WorkflowError {
+    /// Failed to read the source from stdin.
+    StdinReadError{ err: std::io::Error },
+    /// Failed to read the source from the given file.
+    FileReadError{ path: PathBuf, err: std::io::Error },
+    /// Failed to get the packages directory.
+    PackagesDirError{ err: UtilError },
+    /// Failed to get the datasets directory.
+    DatasetsDirError{ err: UtilError },
+    /// Failed to create the local package index.
+    LocalPackageIndexError{ err: brane_tsk::local::Error },
+    /// Failed to create the local data index.
+    LocalDataIndexError{ err: brane_tsk::local::Error },
+    /// Failed to compile the given workflow.
+    CompileError{ what: String, errs: Vec<brane_ast::Error> },
+    /// Failed to render the compiled workflow as a graph.
+    GraphRenderError{ errs: Vec<brane_ast::Error> },
+
+    /// Failed to launch Graphviz' `dot` to layout the graph.
+    DotSpawnError{ err: std::io::Error },
+    /// Failed to send the graph to Graphviz' `dot`.
+    DotWriteError{ err: std::io::Error },
+    /// Failed to wait for Graphviz' `dot` to complete.
+    DotWaitError{ err: std::io::Error },
+    /// Graphviz' `dot` failed to layout the graph.
+    DotFailure{ status: std::process::ExitStatus, stderr: String },
+
+    /// Failed to write the output to the given file.
+    OutputWriteError{ path: PathBuf, err: std::io::Error },
+    /// Failed to write the output to stdout.
+    StdoutWriteError{ err: std::io::Error },
+}
+
+impl Display for WorkflowError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use WorkflowError::*;
+        match self {
+            StdinReadError{ err }         => write!(f, "Failed to read source from stdin: {}", err),
+            FileReadError{ path, err }    => write!(f, "Failed to read source from file '{}': {}", path.display(), err),
+            PackagesDirError{ err }       => write!(f, "Failed to get packages directory: {}", err),
+            DatasetsDirError{ err }       => write!(f, "Failed to get datasets directory: {}", err),
+            LocalPackageIndexError{ err } => write!(f, "Failed to fetch local package index: {}", err),
+            LocalDataIndexError{ err }    => write!(f, "Failed to fetch local data index: {}", err),
+            CompileError{ .. }            => write!(f, "Compilation of workflow failed (see output above)"),
+            GraphRenderError{ errs }      => write!(f, "Failed to render workflow as a graph: {}", errs.iter().map(|e| e.to_string()).collect::<Vec<String>>().join(", ")),
+
+            DotSpawnError{ err }          => write!(f, "Failed to launch Graphviz' `dot` (is Graphviz installed?): {}", err),
+            DotWriteError{ err }          => write!(f, "Failed to write graph to Graphviz' `dot`: {}", err),
+            DotWaitError{ err }           => write!(f, "Failed to wait for Graphviz' `dot` to complete: {}", err),
+            DotFailure{ status, stderr }  => write!(f, "Graphviz' `dot` returned non-zero exit code {}{}", status.code().unwrap_or(-1), if !stderr.is_empty() { format!(":\n{}", stderr) } else { String::new() }),
+
+            OutputWriteError{ path, err } => write!(f, "Failed to write output to file '{}': {}", path.display(), err),
+            StdoutWriteError{ err }       => write!(f, "Failed to write output to stdout: {}", err),
+        }
+    }
+}
+
+impl Error for WorkflowError {}
+
+
+
 /// Collects errors of utilities that don't find an origin in just one subcommand.
 #[derive(Debug)]
 pub enum UtilError {
@@ -1104,3 +1865,43 @@ impl Display for DelegatesError {
 }
 
 impl Error for DelegatesError {}
+
+
+
+/// Defines errors that occur when writing structured output.
+#[derive(Debug)]
+pub enum RenderError {
+    /// Failed to serialize the output as JSON.
+    JsonSerializeError{ err: serde_json::Error },
+    /// Failed to serialize the output as YAML.
+    YamlSerializeError{ err: serde_yaml::Error },
+}
+
+impl Display for RenderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use RenderError::*;
+        match self {
+            JsonSerializeError{ err } => write!(f, "Failed to serialize output as JSON: {}", err),
+            YamlSerializeError{ err } => write!(f, "Failed to serialize output as YAML: {}", err),
+        }
+    }
+}
+
+impl Error for RenderError {}
+
+
+
+/// Defines errors that occur when parsing an OutputFormat.
+#[derive(Debug)]
+pub struct OutputFormatParseError {
+    /// The raw value that we failed to parse.
+    pub raw : String,
+}
+
+impl Display for OutputFormatParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        write!(f, "Unknown output format '{}' (options are 'table', 'json' or 'yaml')", self.raw)
+    }
+}
+
+impl Error for OutputFormatParseError {}
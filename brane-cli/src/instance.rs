@@ -0,0 +1,333 @@
+//  INSTANCE.rs
+//    by agent
+//
+//  Created:
+//    16 Oct 2026, 08:53:15
+//  Last edited:
+//    16 Oct 2026, 12:51:53
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements the `instance` subcommand(s), which manage the Brane
+//!   instances (i.e., registry and driver endpoints) that the CLI knows
+//!   of, so users can switch between them without logging in again.
+//
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use console::style;
+use prettytable::format::FormatBuilder;
+use prettytable::Table;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use brane_tsk::tls::ClientTls;
+use specifications::registry::RegistryConfig;
+
+pub use crate::errors::InstanceError as Error;
+use crate::utils::ensure_config_dir;
+
+
+/***** CONSTANTS *****/
+/// The port of the registry (i.e., `brane-api`) if the user does not specify one.
+const DEFAULT_REGISTRY_PORT: u16 = 50051;
+/// The port of the driver (i.e., `brane-drv`) if the user does not specify its address.
+const DEFAULT_DRIVER_PORT: u16 = 50053;
+
+
+
+
+
+/***** GLOBALS *****/
+lazy_static! {
+    /// The instance that the current command uses instead of the logged-in registry, if any (see `activate()`).
+    static ref ACTIVE: RwLock<Option<InstanceInfo>> = RwLock::new(None);
+    /// How we connect to the central services of the active instance (see `activate()`).
+    static ref TLS: RwLock<ClientTls> = RwLock::new(ClientTls::default());
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Returns the path of the file with the known instances.
+///
+/// # Errors
+/// This function errors if we failed to find or create the Brane configuration directory.
+fn get_instances_path() -> Result<PathBuf, Error> {
+    match ensure_config_dir(true) {
+        Ok(dir)  => Ok(dir.join("instances.yml")),
+        Err(err) => Err(Error::ConfigDirError{ err }),
+    }
+}
+
+/// Parses the given address as a URL with a host, defaulting its port if it has none.
+///
+/// # Arguments
+/// - `address`: The address to parse.
+/// - `port`: The port to use if the address has none.
+///
+/// # Returns
+/// The address as `<scheme>://<host>:<port>`.
+///
+/// # Errors
+/// This function errors if the address is not a valid URL or does not have a host.
+fn normalize_address(address: &str, port: u16) -> Result<(Url, String), Error> {
+    let url: Url = match Url::parse(address) {
+        Ok(url)  => url,
+        Err(err) => { return Err(Error::IllegalAddress{ address: address.into(), err }); },
+    };
+    let host: String = match url.host_str() {
+        Some(host) => host.into(),
+        None       => { return Err(Error::AddressWithoutHost{ address: address.into() }); },
+    };
+    let normalized: String = format!("{}://{}:{}", url.scheme(), host, url.port().unwrap_or(port));
+    Ok((url, normalized))
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines a single Brane instance that the CLI knows of.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct InstanceInfo {
+    /// The address of the instance's registry (i.e., `brane-api`).
+    pub registry  : String,
+    /// The address of the instance's driver (i.e., `brane-drv`).
+    pub driver    : String,
+    /// The username with which we sign packages for this instance.
+    pub username  : String,
+    /// The directory with the certificates for the locations of this instance, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub certs_dir : Option<PathBuf>,
+}
+
+impl InstanceInfo {
+    /// Returns the login information for the registry of this instance.
+    #[inline]
+    pub fn registry_config(&self) -> RegistryConfig { RegistryConfig{ url: self.registry.clone(), username: self.username.clone() } }
+}
+
+
+
+/// Defines the file with all the instances that the CLI knows of.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct InstancesFile {
+    /// The name of the instance that was last selected, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selected  : Option<String>,
+    /// The known instances, by name.
+    #[serde(default)]
+    pub instances : BTreeMap<String, InstanceInfo>,
+}
+
+impl InstancesFile {
+    /// Loads the InstancesFile from the given path, returning an empty one if it does not exist.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the file to load.
+    ///
+    /// # Returns
+    /// A new InstancesFile with the instances in the file.
+    ///
+    /// # Errors
+    /// This function errors if the file exists but could not be read or parsed.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path: &Path = path.as_ref();
+        if !path.exists() { return Ok(Self::default()); }
+        let handle: File = match File::open(path) {
+            Ok(handle) => handle,
+            Err(err)   => { return Err(Error::FileOpenError{ path: path.into(), err }); },
+        };
+        match serde_yaml::from_reader(handle) {
+            Ok(file) => Ok(file),
+            Err(err) => Err(Error::FileParseError{ path: path.into(), err }),
+        }
+    }
+
+    /// Writes the InstancesFile to the given path.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the file to write to.
+    ///
+    /// # Errors
+    /// This function errors if the file could not be created or written to.
+    pub fn to_path(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let path: &Path = path.as_ref();
+        let handle: File = match File::create(path) {
+            Ok(handle) => handle,
+            Err(err)   => { return Err(Error::FileCreateError{ path: path.into(), err }); },
+        };
+        match serde_yaml::to_writer(handle, self) {
+            Ok(_)    => Ok(()),
+            Err(err) => Err(Error::FileWriteError{ path: path.into(), err }),
+        }
+    }
+}
+
+
+
+/// Makes the given instance the one used by the current command, instead of the registry the user is logged-in to.
+///
+/// # Arguments
+/// - `name`: The name of the instance to use.
+///
+/// # Returns
+/// The InstanceInfo of the instance, so the caller can use its driver and certificates.
+///
+/// # Errors
+/// This function errors if we failed to read the known instances or if the instance is unknown.
+pub fn activate(name: &str) -> Result<InstanceInfo, Error> {
+    let file: InstancesFile = InstancesFile::from_path(get_instances_path()?)?;
+    let info: InstanceInfo = match file.instances.get(name) {
+        Some(info) => info.clone(),
+        None       => { return Err(Error::UnknownInstance{ name: name.into() }); },
+    };
+    debug!("Using instance '{}' (registry '{}', driver '{}')", name, info.registry, info.driver);
+
+    // Load the certificates with which we connect to the central services, if any
+    if let Some(certs_dir) = &info.certs_dir {
+        match ClientTls::from_dir(certs_dir) {
+            Ok(tls)  => { *TLS.write().unwrap() = tls; },
+            Err(err) => { return Err(Error::ClientTlsError{ name: name.into(), err }); },
+        }
+    }
+
+    *ACTIVE.write().unwrap() = Some(info.clone());
+    Ok(info)
+}
+
+/// Returns the instance used by the current command, if any was activated.
+#[inline]
+pub fn active() -> Option<InstanceInfo> { ACTIVE.read().unwrap().clone() }
+
+/// Returns how to connect to the central services of the instance used by the current command.
+/// 
+/// If the instance has a certificate directory, its top-level `ca.pem` and `client-id.pem` are used for `https://` addresses. Otherwise (or if no instance was activated), plain clients are used.
+#[inline]
+pub fn client_tls() -> ClientTls { TLS.read().unwrap().clone() }
+
+
+
+/// Adds a new instance to the ones the CLI knows of.
+///
+/// # Arguments
+/// - `name`: The name of the new instance.
+/// - `registry`: The address of the instance's registry. If it has no port, 50051 is used.
+/// - `driver`: The address of the instance's driver. If omitted, it is assumed to run on the same host as the registry, at port 50053.
+/// - `username`: The username with which we sign packages for this instance.
+/// - `certs_dir`: The directory with the certificates for the locations of this instance, if any.
+/// - `select`: Whether to immediately select the new instance.
+///
+/// # Errors
+/// This function errors if the instance already exists, if an address is invalid or if we failed to read or write the known instances.
+pub fn add(name: String, registry: String, driver: Option<String>, username: String, certs_dir: Option<PathBuf>, select: bool) -> Result<(), Error> {
+    let path: PathBuf = get_instances_path()?;
+    let mut file: InstancesFile = InstancesFile::from_path(&path)?;
+    if file.instances.contains_key(&name) { return Err(Error::InstanceExists{ name }); }
+
+    // Resolve the addresses and the certificates
+    let (url, registry): (Url, String) = normalize_address(&registry, DEFAULT_REGISTRY_PORT)?;
+    let driver: String = match driver {
+        Some(driver) => normalize_address(&driver, DEFAULT_DRIVER_PORT)?.1,
+        None         => format!("{}://{}:{}", url.scheme(), url.host_str().unwrap_or_default(), DEFAULT_DRIVER_PORT),
+    };
+    let certs_dir: Option<PathBuf> = match certs_dir {
+        Some(certs_dir) => match fs::canonicalize(&certs_dir) {
+            Ok(certs_dir) => Some(certs_dir),
+            Err(err)      => { return Err(Error::CertsDirCanonicalizeError{ path: certs_dir, err }); },
+        },
+        None => None,
+    };
+
+    // Store it
+    file.instances.insert(name.clone(), InstanceInfo{ registry, driver, username, certs_dir });
+    file.to_path(&path)?;
+    println!("Successfully added instance {}", style(&name).bold().cyan());
+
+    // Select it if told to do so
+    if select { self::select(name)?; }
+    Ok(())
+}
+
+/// Lists the instances the CLI knows of.
+///
+/// # Errors
+/// This function errors if we failed to read the known instances.
+pub fn list() -> Result<(), Error> {
+    let file: InstancesFile = InstancesFile::from_path(get_instances_path()?)?;
+
+    // Prepare display table.
+    let format = FormatBuilder::new()
+        .column_separator('\0')
+        .borders('\0')
+        .padding(1, 1)
+        .build();
+    let mut table = Table::new();
+    table.set_format(format);
+    table.add_row(row!["", "NAME", "REGISTRY", "DRIVER", "USERNAME", "CERTIFICATES"]);
+    for (name, info) in &file.instances {
+        let selected: &str = if file.selected.as_ref() == Some(name) { "*" } else { "" };
+        let certs_dir: String = info.certs_dir.as_ref().map(|c| c.display().to_string()).unwrap_or_default();
+        table.add_row(row![selected, name, info.registry, info.driver, info.username, certs_dir]);
+    }
+    table.printstd();
+
+    Ok(())
+}
+
+/// Selects the given instance, i.e., logs in to its registry so that subsequent commands use it by default.
+///
+/// # Arguments
+/// - `name`: The name of the instance to select.
+///
+/// # Errors
+/// This function errors if the instance is unknown or if we failed to read or write the known instances or the login file.
+pub fn select(name: String) -> Result<(), Error> {
+    let path: PathBuf = get_instances_path()?;
+    let mut file: InstancesFile = InstancesFile::from_path(&path)?;
+    let info: &InstanceInfo = match file.instances.get(&name) {
+        Some(info) => info,
+        None       => { return Err(Error::UnknownInstance{ name }); },
+    };
+
+    // Write the login file as `brane login` would
+    let login_path: PathBuf = path.with_file_name("registry.yml");
+    let handle: File = match File::create(&login_path) {
+        Ok(handle) => handle,
+        Err(err)   => { return Err(Error::FileCreateError{ path: login_path, err }); },
+    };
+    if let Err(err) = serde_yaml::to_writer(handle, &info.registry_config()) { return Err(Error::FileWriteError{ path: login_path, err }); }
+
+    // Remember it's selected
+    file.selected = Some(name.clone());
+    file.to_path(&path)?;
+    println!("Successfully selected instance {}", style(&name).bold().cyan());
+    Ok(())
+}
+
+/// Removes the given instance from the ones the CLI knows of.
+///
+/// Note that this does not log out if the instance is currently selected.
+///
+/// # Arguments
+/// - `name`: The name of the instance to remove.
+///
+/// # Errors
+/// This function errors if the instance is unknown or if we failed to read or write the known instances.
+pub fn remove(name: String) -> Result<(), Error> {
+    let path: PathBuf = get_instances_path()?;
+    let mut file: InstancesFile = InstancesFile::from_path(&path)?;
+    if file.instances.remove(&name).is_none() { return Err(Error::UnknownInstance{ name }); }
+    if file.selected.as_ref() == Some(&name) { file.selected = None; }
+    file.to_path(&path)?;
+    println!("Successfully removed instance {}", style(&name).bold().cyan());
+    Ok(())
+}
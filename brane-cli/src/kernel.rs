@@ -0,0 +1,712 @@
+//  KERNEL.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 12:36:58
+//  Last edited:
+//    16 Oct 2026, 13:10:36
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements a Jupyter kernel for BraneScript (and Bakery), which
+//!   compiles every cell as a snippet and runs it in a session on a remote
+//!   driver, such that the instance can be used from JupyterLab.
+// 
+
+use std::convert::TryFrom;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use sha2::Sha256;
+use tokio::sync::{Mutex, Notify};
+use tonic::Streaming;
+use tonic::transport::Channel;
+use uuid::Uuid;
+use zeromq::{PubSocket, RepSocket, RouterSocket, Socket, SocketRecv, SocketSend, ZmqMessage};
+
+use brane_ast::ParserOptions;
+use brane_dsl::Language;
+use brane_exe::FullValue;
+use brane_tsk::grpc::{CancelRequest, DriverServiceClient, ExecuteReply, KillSessionRequest};
+use specifications::status::{OutputStream, TaskOutput};
+
+pub use crate::errors::KernelError as Error;
+use crate::errors::RunError;
+use crate::render::{render_table, render_value, RenderOptions};
+use crate::run::{execute_instance_vm, initialize_instance_vm, InstanceVmState};
+
+
+/***** UNIT TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a Wire that signs with a fixed key.
+    fn signed() -> Wire { Wire { key: b"secret".to_vec(), session: "test".into() } }
+
+    /// Returns the frames of a message sent by the given Wire, for tampering.
+    fn frames(wire: &Wire) -> Vec<Bytes> {
+        wire.encode(&[ Bytes::from_static(b"frontend") ], "execute_request", &json!({}), json!({ "code": "println(42);" })).into_vec()
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        for wire in [ signed(), Wire { key: vec![], session: "test".into() } ] {
+            let msg: Message = wire.decode(ZmqMessage::try_from(frames(&wire)).unwrap()).expect("Failed to decode own message");
+            assert_eq!(msg.ids, vec![ Bytes::from_static(b"frontend") ]);
+            assert_eq!(msg.msg_type(), "execute_request");
+            assert_eq!(msg.content["code"], "println(42);");
+        }
+    }
+
+    #[test]
+    fn test_signature() {
+        let wire: Wire = signed();
+
+        // Tampered content
+        let mut msg: Vec<Bytes> = frames(&wire);
+        msg[6] = Bytes::from(json!({ "code": "println(43);" }).to_string());
+        assert!(wire.decode(ZmqMessage::try_from(msg).unwrap()).is_none());
+
+        // Wrong signatures (not hexadecimal, wrong key, missing)
+        let other: Wire = Wire { key: b"other".to_vec(), session: "test".into() };
+        for signature in [ Bytes::from_static(b"not hex"), frames(&other)[2].clone(), Bytes::new() ] {
+            let mut msg: Vec<Bytes> = frames(&wire);
+            msg[2] = signature;
+            assert!(wire.decode(ZmqMessage::try_from(msg).unwrap()).is_none());
+        }
+
+        // Unsigned messages are refused if we expect a signature
+        let unsigned: Wire = Wire { key: vec![], session: "test".into() };
+        assert!(wire.decode(ZmqMessage::try_from(frames(&unsigned)).unwrap()).is_none());
+    }
+
+    #[test]
+    fn test_is_complete() {
+        assert_eq!(is_complete("println(\"{\");")["status"], "complete");
+        assert_eq!(is_complete("func f() {")["status"], "incomplete");
+        assert_eq!(is_complete("/* {")["status"], "incomplete");
+    }
+}
+
+
+/***** CONSTANTS *****/
+/// The frame that separates the routing identities of a message from the message itself.
+const DELIMITER: &[u8] = b"<IDS|MSG>";
+
+/// The version of the Jupyter messaging protocol that we implement.
+const PROTOCOL_VERSION: &str = "5.3";
+
+
+
+
+
+/***** HELPER STRUCTS *****/
+/// Defines the connection file that Jupyter writes for every kernel it starts.
+#[derive(Clone, Debug, Deserialize)]
+struct ConnectionInfo {
+    /// The transport to listen on (usually `tcp`).
+    transport        : String,
+    /// The address to listen on.
+    ip               : String,
+    /// The port of the socket on which we receive execution requests.
+    shell_port       : u16,
+    /// The port of the socket on which we publish output and status updates.
+    iopub_port       : u16,
+    /// The port of the socket on which we may ask the frontend for input.
+    stdin_port       : u16,
+    /// The port of the socket on which we receive interrupt and shutdown requests.
+    control_port     : u16,
+    /// The port of the socket that echoes heartbeats.
+    hb_port          : u16,
+    /// The key with which messages are signed. Empty if they are not signed.
+    key              : String,
+    /// The scheme with which messages are signed.
+    signature_scheme : String,
+}
+
+impl ConnectionInfo {
+    /// Returns the ZeroMQ endpoint of the socket at the given port.
+    #[inline]
+    fn endpoint(&self, port: u16) -> String { format!("{}://{}:{}", self.transport, self.ip, port) }
+}
+
+
+
+/// Defines a message received from the frontend.
+struct Message {
+    /// The routing identities of the sender, which we have to send the reply to.
+    ids     : Vec<Bytes>,
+    /// The header of the message, which is the parent header of anything we send in response to it.
+    header  : Value,
+    /// The content of the message.
+    content : Value,
+}
+
+impl Message {
+    /// Returns the type of the message (e.g., `execute_request`).
+    #[inline]
+    fn msg_type(&self) -> &str { self.header["msg_type"].as_str().unwrap_or("") }
+}
+
+
+
+/// Translates messages from and to the frames on the wire, signing and verifying them while at it.
+#[derive(Clone, Debug)]
+struct Wire {
+    /// The key with which to sign messages. Empty if they are not signed.
+    key     : Vec<u8>,
+    /// The identifier of the kernel's session, which is put in the header of every message.
+    session : String,
+}
+
+impl Wire {
+    /// Computes the signature of the given parts of a message.
+    ///
+    /// # Arguments
+    /// - `parts`: The header, parent header, metadata and content of the message, in that order.
+    ///
+    /// # Returns
+    /// The HMAC-SHA256 of the parts as lowercase hexadecimal characters, or an empty string if messages are not signed.
+    fn sign(&self, parts: &[&[u8]]) -> String {
+        if self.key.is_empty() { return String::new(); }
+        let mut mac: Hmac<Sha256> = Hmac::new_from_slice(&self.key).expect("HMAC accepts keys of any length");
+        for part in parts { mac.update(part); }
+        format!("{:x}", mac.finalize().into_bytes())
+    }
+
+    /// Verifies the signature of the given parts of a message in constant time.
+    ///
+    /// # Arguments
+    /// - `signature`: The signature as sent along with the message (lowercase hexadecimal characters).
+    /// - `parts`: The header, parent header, metadata and content of the message, in that order.
+    ///
+    /// # Returns
+    /// Whether the signature is valid. If messages are not signed, only an empty signature is.
+    fn verify(&self, signature: &[u8], parts: &[&[u8]]) -> bool {
+        if self.key.is_empty() { return signature.is_empty(); }
+        let signature: Vec<u8> = match hex::decode(signature) {
+            Ok(signature) => signature,
+            Err(_)        => { return false; },
+        };
+        let mut mac: Hmac<Sha256> = Hmac::new_from_slice(&self.key).expect("HMAC accepts keys of any length");
+        for part in parts { mac.update(part); }
+        mac.verify_slice(&signature).is_ok()
+    }
+
+    /// Parses the frames of a received message.
+    ///
+    /// # Arguments
+    /// - `msg`: The ZmqMessage to parse.
+    ///
+    /// # Returns
+    /// The parsed Message, or `None` if it was malformed or not signed properly (which is logged).
+    fn decode(&self, msg: ZmqMessage) -> Option<Message> {
+        let frames: Vec<Bytes> = msg.into_vec();
+        let delim: usize = match frames.iter().position(|frame| frame.as_ref() == DELIMITER) {
+            Some(delim) if frames.len() >= delim + 6 => delim,
+            _                                        => { warn!("Ignoring malformed message"); return None; },
+        };
+
+        // Check the signature before we trust any of it
+        let parts: &[Bytes] = &frames[delim + 2..delim + 6];
+        if !self.verify(&frames[delim + 1], &[ &parts[0], &parts[1], &parts[2], &parts[3] ]) {
+            warn!("Ignoring message with an invalid signature");
+            return None;
+        }
+        let header: Value = match serde_json::from_slice(&parts[0]) {
+            Ok(header) => header,
+            Err(err)   => { warn!("Ignoring message with an unparseable header: {}", err); return None; },
+        };
+        let content: Value = match serde_json::from_slice(&parts[3]) {
+            Ok(content) => content,
+            Err(err)    => { warn!("Ignoring message with unparseable content: {}", err); return None; },
+        };
+        Some(Message { ids: frames[..delim].to_vec(), header, content })
+    }
+
+    /// Serializes a new message to frames.
+    ///
+    /// # Arguments
+    /// - `ids`: The routing identities (or topic) to send the message to.
+    /// - `msg_type`: The type of the message.
+    /// - `parent`: The header of the message that this message responds to.
+    /// - `content`: The content of the message.
+    ///
+    /// # Returns
+    /// A new ZmqMessage that may be sent as-is.
+    fn encode(&self, ids: &[Bytes], msg_type: &str, parent: &Value, content: Value) -> ZmqMessage {
+        let header: Value = json!({
+            "msg_id"   : Uuid::new_v4().to_string(),
+            "session"  : self.session,
+            "username" : "brane",
+            "date"     : Utc::now().to_rfc3339(),
+            "msg_type" : msg_type,
+            "version"  : PROTOCOL_VERSION,
+        });
+        let parts: [Vec<u8>; 4] = [ header.to_string().into_bytes(), parent.to_string().into_bytes(), b"{}".to_vec(), content.to_string().into_bytes() ];
+        let signature: String = self.sign(&[ &parts[0], &parts[1], &parts[2], &parts[3] ]);
+
+        let mut frames: Vec<Bytes> = ids.to_vec();
+        frames.push(Bytes::from_static(DELIMITER));
+        frames.push(Bytes::from(signature));
+        frames.extend(Vec::from(parts).into_iter().map(Bytes::from));
+        ZmqMessage::try_from(frames).expect("message always has frames")
+    }
+}
+
+
+
+/// Publishes output and status updates to every frontend that listens.
+#[derive(Clone)]
+struct Publisher {
+    /// The IOPub socket to publish on, which is shared by the shell and control handlers.
+    socket : Arc<Mutex<PubSocket>>,
+    /// The Wire to encode messages with.
+    wire   : Wire,
+}
+
+impl Publisher {
+    /// Publishes a new message. Failures are only logged, as the frontend may not be listening.
+    ///
+    /// # Arguments
+    /// - `parent`: The header of the request that caused this message.
+    /// - `msg_type`: The type of the message.
+    /// - `content`: The content of the message.
+    async fn publish(&self, parent: &Value, msg_type: &str, content: Value) {
+        let msg: ZmqMessage = self.wire.encode(&[ Bytes::from(msg_type.to_string()) ], msg_type, parent, content);
+        if let Err(err) = self.socket.lock().await.send(msg).await { warn!("Failed to publish '{}' message: {}", msg_type, err); }
+    }
+
+    /// Publishes that the kernel is (no longer) busy with the given request.
+    #[inline]
+    async fn status(&self, parent: &Value, state: &str) { self.publish(parent, "status", json!({ "execution_state": state })).await }
+}
+
+
+
+/// Defines the state of the kernel that persists across cells.
+struct Kernel {
+    /// The address of the driver we run cells on.
+    endpoint : String,
+    /// The session on the driver (and the compiler state that goes with it).
+    state    : InstanceVmState,
+    /// The language that the cells are written in.
+    language : Language,
+    /// Where we publish output.
+    iopub    : Publisher,
+    /// The number of cells executed so far.
+    count    : u64,
+}
+
+impl Kernel {
+    /// Handles an `execute_request`.
+    ///
+    /// # Arguments
+    /// - `request`: The request to handle.
+    ///
+    /// # Returns
+    /// The content of the `execute_reply`.
+    async fn execute(&mut self, request: &Message) -> Value {
+        let code: &str = request.content["code"].as_str().unwrap_or("");
+        let silent: bool = request.content["silent"].as_bool().unwrap_or(false);
+        if !silent {
+            self.count += 1;
+            self.iopub.publish(&request.header, "execute_input", json!({ "code": code, "execution_count": self.count })).await;
+        }
+
+        // Run the cell, if there is anything to run
+        let res: Result<(), (&str, String)> = if code.trim().is_empty() { Ok(()) } else { self.run(&request.header, code, silent).await };
+        match res {
+            Ok(()) => json!({ "status": "ok", "execution_count": self.count, "user_expressions": {} }),
+            Err((ename, evalue)) => {
+                let traceback: Vec<&str> = evalue.lines().collect();
+                self.iopub.publish(&request.header, "error", json!({ "ename": ename, "evalue": evalue, "traceback": traceback })).await;
+                json!({ "status": "error", "execution_count": self.count, "ename": ename, "evalue": evalue, "traceback": traceback })
+            },
+        }
+    }
+
+    /// Compiles the given cell and runs it on the driver, publishing what it prints and returns.
+    ///
+    /// # Arguments
+    /// - `parent`: The header of the request that runs the cell.
+    /// - `code`: The source text of the cell.
+    /// - `silent`: Whether to keep the result of the cell to ourselves.
+    ///
+    /// # Errors
+    /// This function errors with the name and description of the error if the cell failed to compile or run.
+    async fn run(&mut self, parent: &Value, code: &str, silent: bool) -> Result<(), (&'static str, String)> {
+        let offset: usize = self.state.state.offset;
+        let what: String = format!("<cell {}>", self.count);
+        let mut stream: Streaming<ExecuteReply> = match execute_instance_vm(&self.endpoint, &mut self.state, &what, code, false).await {
+            Ok(stream) => stream,
+            Err(RunError::CompileError{ errs, .. }) => {
+                // Report the positions relative to the cell instead of to the whole session
+                let errs: Vec<String> = errs.iter().map(|err| match err.range() {
+                    Some(range) => format!("{}:{}: {}", range.start.line.saturating_sub(offset), range.start.col, err),
+                    None        => err.to_string(),
+                }).collect();
+                return Err(("CompileError", errs.join("\n")));
+            },
+            Err(err) => { return Err(("ExecuteError", err.to_string())); },
+        };
+
+        // Follow the workflow until it is done
+        let mut failure: Option<String> = None;
+        loop {
+            let reply: ExecuteReply = match stream.message().await {
+                Ok(Some(reply)) => reply,
+                Ok(None)        => { break; },
+                Err(status)     => { return Err(("ConnectionError", format!("Lost connection to driver '{}': {}", self.endpoint, status.message()))); },
+            };
+            if reply.heartbeat { continue; }
+
+            if let Some(debug) = reply.debug { debug!("Remote: {}", debug); }
+            if let Some(soutput) = reply.output {
+                match serde_json::from_str::<(String, TaskOutput)>(&soutput) {
+                    Ok((task, output)) => {
+                        let name: &str = match output.stream { OutputStream::Stdout => "stdout", OutputStream::Stderr => "stderr" };
+                        self.iopub.publish(parent, "stream", json!({ "name": name, "text": format!("[{}] {}\n", task, output.line) })).await;
+                    },
+                    Err(err) => { warn!("Failed to parse task output '{}': {}", soutput, err); },
+                }
+            }
+            if let Some(stdout) = reply.stdout {
+                self.iopub.publish(parent, "stream", json!({ "name": "stdout", "text": stdout })).await;
+            }
+            if let Some(stderr) = reply.stderr { failure = Some(stderr); }
+            if let Some(svalue) = reply.value {
+                let value: FullValue = match serde_json::from_str(&svalue) {
+                    Ok(value) => value,
+                    Err(err)  => { return Err(("ValueParseError", format!("Failed to parse value '{}' returned by driver '{}': {}", svalue, self.endpoint, err))); },
+                };
+                if !silent && !matches!(value, FullValue::Void) {
+                    self.iopub.publish(parent, "execute_result", json!({ "execution_count": self.count, "data": display_data(&value), "metadata": {} })).await;
+                }
+            }
+            if reply.close { break; }
+        }
+        match failure {
+            Some(failure) => Err(("WorkflowError", failure)),
+            None          => Ok(()),
+        }
+    }
+
+    /// Returns the content of a `kernel_info_reply`.
+    fn info(&self) -> Value {
+        let (name, extension): (&str, &str) = match self.language {
+            Language::BraneScript => ("branescript", ".bs"),
+            Language::Bakery      => ("bakery", ".bakery"),
+        };
+        json!({
+            "status"                 : "ok",
+            "protocol_version"       : PROTOCOL_VERSION,
+            "implementation"         : "brane",
+            "implementation_version" : env!("CARGO_PKG_VERSION"),
+            "language_info"          : {
+                "name"           : name,
+                "version"        : env!("CARGO_PKG_VERSION"),
+                "mimetype"       : format!("text/x-{}", name),
+                "file_extension" : extension,
+            },
+            "banner"                 : format!("Brane {} (session '{}' on '{}')", env!("CARGO_PKG_VERSION"), self.state.session, self.endpoint),
+            "help_links"             : [],
+        })
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Escapes the given text for use in HTML.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Builds the rich representations of a value returned by a cell.
+///
+/// # Arguments
+/// - `value`: The FullValue to represent.
+///
+/// # Returns
+/// A map of MIME types to representations, as expected in the `data` of an `execute_result`.
+fn display_data(value: &FullValue) -> Value {
+    let mut data: Map<String, Value> = Map::new();
+    data.insert("text/plain".into(), Value::String(render_value(value, &RenderOptions::full())));
+    match value {
+        FullValue::Data(name) => {
+            let name: String = escape_html(&name.to_string());
+            data.insert("text/html".into(), Value::String(format!("<p>Dataset <code>{}</code>. Download it with <code>brane data download {}</code>.</p>", name, name)));
+        },
+        FullValue::IntermediateResult(name) => {
+            let name: String = escape_html(&name.to_string());
+            data.insert("text/html".into(), Value::String(format!("<p>Intermediate result <code>{}</code>. It only lives on the instance; promote it to a dataset with <code>commit_result()</code> to download it.</p>", name)));
+        },
+        value => {
+            if let Some(table) = render_table(value, &RenderOptions::default()) {
+                let mut html: Vec<u8> = vec![];
+                match table.print_html(&mut html) {
+                    Ok(_)    => { data.insert("text/html".into(), Value::String(String::from_utf8_lossy(&html).into())); },
+                    Err(err) => { warn!("Failed to render value as HTML table: {}", err); },
+                }
+            }
+        },
+    }
+    match serde_json::to_value(value) {
+        Ok(json) => { data.insert("application/json".into(), json); },
+        Err(err) => { warn!("Failed to serialize value as JSON: {}", err); },
+    }
+    Value::Object(data)
+}
+
+/// Returns the content of an `is_complete_reply` for the given code, which tells the frontend whether pressing enter should run it.
+///
+/// This uses the lexer to see whether any bracket, string or comment is left open, which is enough to continue blocks and function definitions on the next line.
+fn is_complete(code: &str) -> Value {
+    if !brane_dsl::is_complete(code) { json!({ "status": "incomplete", "indent": "    " }) } else { json!({ "status": "complete" }) }
+}
+
+/// Binds a new socket to the given endpoint.
+///
+/// # Arguments
+/// - `what`: The name of the socket, for debugging.
+/// - `endpoint`: The endpoint to bind to.
+///
+/// # Errors
+/// This function errors if we failed to bind the socket.
+async fn bind<S: Socket>(what: &'static str, endpoint: String) -> Result<S, Error> {
+    debug!("Binding {} socket to '{}'...", what, endpoint);
+    let mut socket: S = S::new();
+    match socket.bind(&endpoint).await {
+        Ok(_)    => Ok(socket),
+        Err(err) => Err(Error::SocketBindError{ what, endpoint, err }),
+    }
+}
+
+/// Handles requests on the control socket until the frontend asks us to shut down.
+///
+/// Interrupts are forwarded to the driver, which cancels the workflow of the cell that is running (if any).
+///
+/// # Arguments
+/// - `control`: The control socket.
+/// - `iopub`: The Publisher to publish status updates with.
+/// - `endpoint`: The address of the driver.
+/// - `client`: A client of the driver, independent of the one that runs cells.
+/// - `session`: The session on the driver.
+///
+/// # Errors
+/// This function errors if we failed to receive a request.
+async fn serve_control(mut control: RouterSocket, iopub: Publisher, endpoint: String, mut client: DriverServiceClient<Channel>, session: String) -> Result<(), Error> {
+    loop {
+        let msg: ZmqMessage = match control.recv().await {
+            Ok(msg)  => msg,
+            Err(err) => { return Err(Error::SocketRecvError{ what: "control", err }); },
+        };
+        let request: Message = match iopub.wire.decode(msg) {
+            Some(request) => request,
+            None          => { continue; },
+        };
+
+        iopub.status(&request.header, "busy").await;
+        let (msg_type, content): (&str, Value) = match request.msg_type() {
+            "interrupt_request" => {
+                debug!("Cancelling workflow in session '{}'...", session);
+                if let Err(err) = client.cancel(CancelRequest{ uuid: session.clone() }).await { warn!("Failed to cancel workflow on remote '{}': {}", endpoint, err.message()); }
+                ("interrupt_reply", json!({ "status": "ok" }))
+            },
+            "shutdown_request"    => ("shutdown_reply", json!({ "status": "ok", "restart": request.content["restart"].as_bool().unwrap_or(false) })),
+            "kernel_info_request" => ("kernel_info_reply", json!({ "status": "ok", "protocol_version": PROTOCOL_VERSION })),
+            other                 => { debug!("Ignoring unsupported control message '{}'", other); iopub.status(&request.header, "idle").await; continue; },
+        };
+        if let Err(err) = control.send(iopub.wire.encode(&request.ids, msg_type, &request.header, content)).await { return Err(Error::SocketSendError{ what: "control", err }); }
+        iopub.status(&request.header, "idle").await;
+        if msg_type == "shutdown_reply" { return Ok(()); }
+    }
+}
+
+/// Handles requests on the shell socket until the frontend asks us to shut down.
+///
+/// # Arguments
+/// - `shell`: The shell socket.
+/// - `kernel`: The Kernel that executes cells.
+///
+/// # Errors
+/// This function errors if we failed to receive a request or send a reply.
+async fn serve_shell(mut shell: RouterSocket, mut kernel: Kernel) -> Result<(), Error> {
+    loop {
+        let msg: ZmqMessage = match shell.recv().await {
+            Ok(msg)  => msg,
+            Err(err) => { return Err(Error::SocketRecvError{ what: "shell", err }); },
+        };
+        let request: Message = match kernel.iopub.wire.decode(msg) {
+            Some(request) => request,
+            None          => { continue; },
+        };
+
+        kernel.iopub.status(&request.header, "busy").await;
+        let reply: Option<(&str, Value)> = match request.msg_type() {
+            "kernel_info_request"  => Some(("kernel_info_reply", kernel.info())),
+            "execute_request"      => Some(("execute_reply", kernel.execute(&request).await)),
+            "is_complete_request"  => Some(("is_complete_reply", is_complete(request.content["code"].as_str().unwrap_or("")))),
+            "comm_info_request"    => Some(("comm_info_reply", json!({ "status": "ok", "comms": {} }))),
+            "shutdown_request"     => Some(("shutdown_reply", json!({ "status": "ok", "restart": request.content["restart"].as_bool().unwrap_or(false) }))),
+            other                  => { debug!("Ignoring unsupported shell message '{}'", other); None },
+        };
+        if let Some((msg_type, content)) = reply {
+            if let Err(err) = shell.send(kernel.iopub.wire.encode(&request.ids, msg_type, &request.header, content)).await { return Err(Error::SocketSendError{ what: "shell", err }); }
+        }
+        kernel.iopub.status(&request.header, "idle").await;
+        if request.msg_type() == "shutdown_request" { return Ok(()); }
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Installs the kernel spec that tells Jupyter how to start the kernel.
+///
+/// # Arguments
+/// - `remote`: The address of the driver to run cells on, if given.
+/// - `instance`: The instance whose driver to run cells on, if given instead.
+/// - `language`: The language that the cells are written in.
+/// - `name`: The name of the kernel (i.e., of its directory).
+/// - `dir`: The Jupyter kernels directory to install it in. Defaults to the one of the current user.
+///
+/// # Errors
+/// This function errors if we failed to find the kernels directory or to write the kernel spec to it.
+pub fn install(remote: Option<String>, instance: Option<String>, language: Language, name: String, dir: Option<PathBuf>) -> Result<(), Error> {
+    // Resolve where to install it
+    let dir: PathBuf = match dir {
+        Some(dir) => dir,
+        #[cfg(target_os = "macos")]
+        None => match dirs_2::home_dir() {
+            Some(home) => home.join("Library").join("Jupyter").join("kernels"),
+            None       => { return Err(Error::KernelsDirNotFound); },
+        },
+        #[cfg(not(target_os = "macos"))]
+        None => match dirs_2::data_dir() {
+            Some(data) => data.join("jupyter").join("kernels"),
+            None       => { return Err(Error::KernelsDirNotFound); },
+        },
+    };
+    let dir: PathBuf = dir.join(&name);
+
+    // Have Jupyter call this executable with the same target
+    let exe: PathBuf = match std::env::current_exe() {
+        Ok(exe)  => exe,
+        Err(err) => { return Err(Error::ExecutableError{ err }); },
+    };
+    let mut argv: Vec<String> = vec![ exe.display().to_string(), "kernel".into(), "run".into() ];
+    if let Some(remote) = remote { argv.extend([ "--remote".into(), remote ]); }
+    if let Some(instance) = instance { argv.extend([ "--instance".into(), instance ]); }
+    if language == Language::Bakery { argv.push("--bakery".into()); }
+    argv.extend([ "--connection-file".into(), "{connection_file}".into() ]);
+    let spec: Value = json!({
+        "argv"           : argv,
+        "display_name"   : match language { Language::BraneScript => "BraneScript", Language::Bakery => "Bakery" },
+        "language"       : match language { Language::BraneScript => "branescript", Language::Bakery => "bakery" },
+        "interrupt_mode" : "message",
+    });
+    let spec: String = match serde_json::to_string_pretty(&spec) {
+        Ok(spec) => spec,
+        Err(err) => { return Err(Error::KernelSpecSerializeError{ err }); },
+    };
+
+    // Write it
+    if let Err(err) = fs::create_dir_all(&dir) { return Err(Error::KernelDirCreateError{ path: dir, err }); }
+    let path: PathBuf = dir.join("kernel.json");
+    if let Err(err) = fs::write(&path, spec) { return Err(Error::KernelSpecWriteError{ path, err }); }
+
+    // Done
+    println!("Installed kernel '{}' in '{}'", name, dir.display());
+    Ok(())
+}
+
+
+
+/// Runs the kernel until the frontend shuts it down.
+///
+/// Every kernel runs its cells in a new session on the driver, which is killed again when the kernel shuts down.
+///
+/// # Arguments
+/// - `connection_file`: The connection file written by Jupyter.
+/// - `remote`: The address of the driver to run cells on.
+/// - `language`: The language that the cells are written in.
+///
+/// # Errors
+/// This function errors if we failed to read the connection file, connect to the driver or communicate with the frontend.
+pub async fn run(connection_file: PathBuf, remote: String, language: Language) -> Result<(), Error> {
+    // Read the connection file
+    let raw: String = match fs::read_to_string(&connection_file) {
+        Ok(raw)  => raw,
+        Err(err) => { return Err(Error::ConnectionFileReadError{ path: connection_file, err }); },
+    };
+    let info: ConnectionInfo = match serde_json::from_str(&raw) {
+        Ok(info) => info,
+        Err(err) => { return Err(Error::ConnectionFileParseError{ path: connection_file, err }); },
+    };
+    if !info.key.is_empty() && info.signature_scheme != "hmac-sha256" { return Err(Error::UnsupportedSignatureScheme{ scheme: info.signature_scheme }); }
+    let wire: Wire = Wire { key: info.key.clone().into_bytes(), session: Uuid::new_v4().to_string() };
+
+    // Start a session on the driver
+    let state: InstanceVmState = match initialize_instance_vm(&remote, None, ParserOptions::new(language)).await {
+        Ok(state) => state,
+        Err(err)  => { return Err(Error::InitializeError{ endpoint: remote, err }); },
+    };
+    let mut client: DriverServiceClient<Channel> = state.client.clone();
+    let session: String = state.session.to_string();
+    info!("Running cells in session '{}' on '{}'", session, remote);
+
+    // Bind the sockets
+    let shell   : RouterSocket = bind("shell", info.endpoint(info.shell_port)).await?;
+    let control : RouterSocket = bind("control", info.endpoint(info.control_port)).await?;
+    let iopub   : PubSocket    = bind("iopub", info.endpoint(info.iopub_port)).await?;
+    let mut hb  : RepSocket    = bind("heartbeat", info.endpoint(info.hb_port)).await?;
+    // We never ask for input, but the frontend expects the socket to be there
+    let _stdin  : RouterSocket = bind("stdin", info.endpoint(info.stdin_port)).await?;
+    let iopub: Publisher = Publisher { socket: Arc::new(Mutex::new(iopub)), wire };
+
+    // The heartbeat simply echoes whatever it receives
+    tokio::spawn(async move {
+        loop {
+            match hb.recv().await {
+                Ok(msg)  => { if let Err(err) = hb.send(msg).await { warn!("Failed to echo heartbeat: {}", err); } },
+                Err(err) => { warn!("Failed to receive heartbeat: {}", err); break; },
+            }
+        }
+    });
+
+    // Serve the control channel separately, such that cells may be interrupted while they run
+    let shutdown: Arc<Notify> = Arc::new(Notify::new());
+    {
+        let (iopub, endpoint, client, session, shutdown): (Publisher, String, DriverServiceClient<Channel>, String, Arc<Notify>) = (iopub.clone(), remote.clone(), client.clone(), session.clone(), shutdown.clone());
+        tokio::spawn(async move {
+            if let Err(err) = serve_control(control, iopub, endpoint, client, session).await { error!("{}", err); }
+            shutdown.notify_one();
+        });
+    }
+
+    // Serve the cells until either channel tells us to stop
+    let kernel: Kernel = Kernel { endpoint: remote.clone(), state, language, iopub, count: 0 };
+    let res: Result<(), Error> = tokio::select! {
+        res = serve_shell(shell, kernel) => res,
+        _ = shutdown.notified()          => Ok(()),
+    };
+
+    // Clean up the session before we go
+    debug!("Killing session '{}'...", session);
+    if let Err(err) = client.kill_session(KillSessionRequest{ uuid: session.clone() }).await { warn!("Failed to kill session '{}' on remote '{}': {}", session, remote, err.message()); }
+    res
+}
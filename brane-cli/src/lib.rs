@@ -7,23 +7,36 @@ extern crate prettytable;
 #[macro_use]
 extern crate lazy_static;
 
+pub mod availability;
 #[macro_use]
 pub mod build_common;
+pub mod build_cwl;
 pub mod build_ecu;
 pub mod build_oas;
+pub mod codegen;
 pub mod data;
 pub mod errors;
+pub mod instance;
+pub mod kernel;
 pub mod packages;
 pub mod planner;
+pub mod profile;
 pub mod registry;
+pub mod render;
 pub mod repl;
 pub mod run;
+pub mod runs;
+pub mod scaffold;
+pub mod script;
+pub mod session;
 pub mod spec;
+pub mod sweep;
 pub mod test;
 pub mod utils;
 pub mod verify;
 pub mod version;
 pub mod vm;
+pub mod workflow;
 
 
 
@@ -18,9 +18,10 @@ extern crate human_panic;
 use std::path::PathBuf;
 use std::process;
 use std::str::FromStr;
+use std::time::Duration;
 
 use anyhow::Result;
-use clap::Parser;
+use clap::{Args, Parser};
 use console::style;
 use dotenvy::dotenv;
 use git2::Repository;
@@ -28,13 +29,19 @@ use log::LevelFilter;
 use tempfile::tempdir;
 
 use brane_dsl::Language;
+use brane_dsl::spec::RetryPolicy;
 use brane_tsk::spec::AppId;
 use specifications::arch::Arch;
 use specifications::package::PackageKind;
+use specifications::profiling::ProfileSortKey;
+use specifications::search::SearchKind;
 use specifications::version::Version as SemVersion;
 
-use brane_cli::{build_ecu, build_oas, data, packages, registry, repl, run, test, verify, version};
+use brane_cli::{availability, build_cwl, build_ecu, build_oas, codegen, data, instance, kernel, packages, profile, registry, repl, run, runs, scaffold, script, session, sweep, test, verify, version, workflow};
+use brane_cli::codegen::CodegenLanguage;
 use brane_cli::errors::{CliError, BuildError, ImportError};
+use brane_cli::render::{OutputFormat, RenderOptions, DEFAULT_MAX_DEPTH, DEFAULT_MAX_LENGTH, DEFAULT_MAX_STRING};
+use brane_cli::run::ReconnectOptions;
 
 
 /***** ARGUMENTS *****/
@@ -45,16 +52,69 @@ struct Cli {
     debug: bool,
     #[clap(short, long, action, help = "Skip dependencies check")]
     skip_check: bool,
+    #[clap(long, default_value = "table", help = "The format in which 'list', 'data list', 'inspect' and 'search' write their output. Can be 'table', 'json' or 'yaml'.")]
+    output: OutputFormat,
     #[clap(subcommand)]
     sub_command: SubCommand,
 }
 
+/// Defines the arguments that determine how much of a returned value is printed.
+#[derive(Args)]
+struct RenderArgs {
+    #[clap(long, action, help = "If given, prints returned values in full instead of truncating large arrays, instances and strings.")]
+    full       : bool,
+    #[clap(long, default_value_t = DEFAULT_MAX_DEPTH, help = "The number of nested arrays and instances to print before collapsing them. Ignored if '--full' is given.")]
+    max_depth  : usize,
+    #[clap(long, default_value_t = DEFAULT_MAX_LENGTH, help = "The number of elements of an array (or properties of an instance) to print before truncating it. Ignored if '--full' is given.")]
+    max_length : usize,
+    #[clap(long, default_value_t = DEFAULT_MAX_STRING, help = "The number of characters of a string to print before truncating it. Ignored if '--full' is given.")]
+    max_string : usize,
+    #[clap(long, action, help = "If given, buffers the text printed by every parallel branch of a workflow and prints it contiguously under a header once the branches complete, instead of interleaving it as it arrives.")]
+    group_output : bool,
+    #[clap(long, action, help = "If given, prints the value returned by a workflow in full as a single line of JSON instead, so that other programs (e.g., clients generated with 'brane package codegen') can parse it.")]
+    json         : bool,
+}
+
+impl From<RenderArgs> for RenderOptions {
+    #[inline]
+    fn from(value: RenderArgs) -> Self {
+        if value.full { return Self { group_output: value.group_output, json: value.json, ..Self::full() }; }
+        Self {
+            max_depth  : Some(value.max_depth),
+            max_length : Some(value.max_length),
+            max_string : Some(value.max_string),
+
+            group_output : value.group_output,
+            json         : value.json,
+        }
+    }
+}
+
+/// Defines the arguments that determine how we deal with a dropped connection to a remote driver.
+#[derive(Args)]
+struct ReconnectArgs {
+    #[clap(long, default_value = "30", value_names = &["seconds"], help = "The number of seconds that the remote driver may be silent before we consider the connection lost and reconnect. Note that the driver sends heartbeats while a workflow is quiet, so this should be larger than its heartbeat interval. Irrelevant if not running remotely.")]
+    heartbeat_timeout  : u64,
+    #[clap(long, default_value = "5", help = "The number of times we try to reconnect to the remote driver before giving up on a running workflow. Use '0' to never reconnect. Irrelevant if not running remotely.")]
+    reconnect_attempts : u32,
+}
+
+impl From<ReconnectArgs> for ReconnectOptions {
+    #[inline]
+    fn from(value: ReconnectArgs) -> Self {
+        Self {
+            timeout  : Duration::from_secs(value.heartbeat_timeout),
+            attempts : value.reconnect_attempts,
+        }
+    }
+}
+
 #[derive(Parser)]
 enum SubCommand {
     #[clap(name = "build", about = "Build a package")]
     Build {
-        #[clap(short, long, help = "The architecture for which to compile the image.")]
-        arch: Option<Arch>,
+        #[clap(short, long, help = "The architecture for which to compile the image. May be given multiple times to build a multi-arch package, in which case the first one is the default image. Defaults to the host architecture.")]
+        arch: Vec<Arch>,
         #[clap(short, long, help = "Path to the directory to use as container working directory (defaults to the folder of the package file itself)")]
         workdir: Option<PathBuf>,
         #[clap(name = "FILE", help = "Path to the file to build")]
@@ -65,6 +125,8 @@ enum SubCommand {
         init: Option<PathBuf>,
         #[clap(long, action, help = "Don't delete build files")]
         keep_files: bool,
+        #[clap(long, action, help = "Development mode (ECU packages only): reuses the image of the previous development build if only the working directory files changed, and mounts the working directory into it when running the package locally (e.g., with 'brane test'). Do not push packages built this way.")]
+        dev: bool,
     },
 
     #[clap(name = "data", about = "Data-related commands.")]
@@ -76,8 +138,8 @@ enum SubCommand {
 
     #[clap(name = "import", about = "Import a package")]
     Import {
-        #[clap(short, long, help = "The architecture for which to compile the image.")]
-        arch: Option<Arch>,
+        #[clap(short, long, help = "The architecture for which to compile the image. May be given multiple times to build a multi-arch package, in which case the first one is the default image. Defaults to the host architecture.")]
+        arch: Vec<Arch>,
         #[clap(name = "REPO", help = "Name of the GitHub repository containing the package")]
         repo: String,
         #[clap(short, long, help = "Path to the directory to use as container working directory, relative to the repository (defaults to the folder of the package file itself)")]
@@ -102,6 +164,20 @@ enum SubCommand {
         syntax : String,
     },
 
+    #[clap(name = "instance", about = "Manages the Brane instances this tool knows of, to switch between them without logging in again.")]
+    Instance {
+        // We subcommand further
+        #[clap(subcommand)]
+        subcommand : InstanceSubcommand,
+    },
+
+    #[clap(name = "kernel", about = "Manages the Jupyter kernel that runs BraneScript cells in a session on a remote instance.")]
+    Kernel {
+        // We subcommand further
+        #[clap(subcommand)]
+        subcommand : KernelSubcommand,
+    },
+
     #[clap(name = "list", about = "List packages")]
     List {
         #[clap(short, long, action, help = "If given, only print the latest version of each package instead of all versions")]
@@ -127,16 +203,55 @@ enum SubCommand {
     #[clap(name = "logout", about = "Log out from a registry")]
     Logout {},
 
+    #[clap(name = "new", about = "Scaffold a new package with an example function, a test fixture and a .gitignore.")]
+    New {
+        #[clap(name = "KIND", help = "Kind of package to scaffold: ecu or oas")]
+        kind     : PackageKind,
+        #[clap(name = "NAME", help = "Name of the package")]
+        name     : String,
+        #[clap(short, long, help = "The directory to scaffold the package in (defaults to './<NAME>'). If it exists, it must be empty.")]
+        path     : Option<PathBuf>,
+        #[clap(short, long, default_value = "python", help = "The language of the example entrypoint of an ecu package: python or bash")]
+        language : String,
+    },
+
+    #[clap(name = "package", about = "Package-related commands, e.g., that query the remote instance.")]
+    Package {
+        // We subcommand further
+        #[clap(subcommand)]
+        subcommand : PackageSubcommand,
+    },
+
+    #[clap(name = "profile", about = "Shows how long the tasks, data transfers and task calls in the last run of a session on the remote instance took.")]
+    Profile {
+        #[clap(name = "ID", help = "The identifier of the session to profile.")]
+        id         : String,
+        #[clap(short, long, default_value = "total", help = "The column to sort the tables by. Can be 'total', 'mean', 'max', 'count' or 'name'.")]
+        sort       : ProfileSortKey,
+        #[clap(short, long, help = "If given, only shows this many rows per table.")]
+        limit      : Option<usize>,
+        #[clap(short, long, value_names = &["path"], help = "If given, also writes the timings as a flamegraph-style JSON tree (per location, kind, name and call) to the given file, e.g., for use with 'd3-flame-graph'.")]
+        flamegraph : Option<PathBuf>,
+        #[clap(short, long, value_names = &["address[:port]"], help = "The address of the remote driver.")]
+        remote     : String,
+    },
+
     #[clap(name = "pull", about = "Pull a package from a registry")]
     Pull {
         #[clap(name = "PACKAGES", help = "Specify one or more packages to pull from a remote. You can either give a package as 'NAME' or 'NAME:VERSION', where VERSION is assumed to be 'latest' if omitted.")]
         packages: Vec<String>,
+        #[clap(long, help = "If given, pulls from the registry of this instance (see 'brane instance') instead of the one you are logged-in to.")]
+        instance: Option<String>,
     },
 
     #[clap(name = "push", about = "Push a package to a registry")]
     Push {
         #[clap(name = "PACKAGES", help = "Specify one or more packages to push to a remote. You can either give a package as 'NAME' or 'NAME:VERSION', where VERSION is assumed to be 'latest' if omitted.")]
         packages: Vec<String>,
+        #[clap(long, help = "If given, pushes to the registry of this instance (see 'brane instance') instead of the one you are logged-in to.")]
+        instance: Option<String>,
+        #[clap(long, help = "If given, pushes the package image to the OCI registry configured by the instance instead of uploading it to the instance itself. Only the package metadata is uploaded.")]
+        registry: bool,
     },
 
     #[clap(name = "remove", about = "Remove a local package.")]
@@ -156,13 +271,24 @@ enum SubCommand {
 
         #[clap(short, long, value_names = &["address[:port]"], help = "Create a remote REPL session")]
         remote: Option<String>,
-        #[clap(short, long, value_names = &["uid"], help = "Attach to an existing remote session")]
+        #[clap(long, conflicts_with = "remote", help = "Create a remote REPL session on this instance (see 'brane instance'), using its driver, registry and certificates.")]
+        instance: Option<String>,
+        #[clap(short, long, value_names = &["uid"], help = "Attach to an existing remote session (see 'brane session list')")]
         attach: Option<AppId>,
 
         #[clap(short, long, action, help = "Use Bakery instead of BraneScript")]
         bakery: bool,
         #[clap(short, long, action, help = "Clear history before session")]
         clear: bool,
+        #[clap(long, value_names = &["path"], help = "If given, records every snippet of the session, the hash of the workflow it compiled to and what it returned (or why it failed) to this JSONL file, such that the session may be replayed with '--replay'.")]
+        record: Option<PathBuf>,
+        #[clap(long, value_names = &["path"], help = "If given, re-executes the snippets of a session recorded with '--record' instead of reading them from the terminal, warning about every snippet that compiles or returns differently than when it was recorded.")]
+        replay: Option<PathBuf>,
+
+        #[clap(flatten)]
+        render    : RenderArgs,
+        #[clap(flatten)]
+        reconnect : ReconnectArgs,
     },
 
     #[clap(name = "run", about = "Run a DSL script locally")]
@@ -179,6 +305,41 @@ enum SubCommand {
         file: PathBuf,
         #[clap(short, long, value_names = &["address[:port]"], help = "Create a remote REPL session")]
         remote: Option<String>,
+        #[clap(long, conflicts_with_all = &["remote", "trace"], help = "Run on this instance (see 'brane instance'), using its driver, registry and certificates.")]
+        instance: Option<String>,
+        #[clap(long, action, help = "If given, prints the CPU time, peak memory and I/O consumed by every task after the workflow completes. Use 'brane profile' for how long they took. Irrelevant if not running remotely.")]
+        profile: bool,
+        #[clap(long, value_names = &["path"], conflicts_with = "remote", help = "If given, records every edge visited by the workflow (with timestamps and thread ids) to the given JSONL file. Use 'branec trace analyze' to find its branch coverage and critical path. Only possible when running locally.")]
+        trace: Option<PathBuf>,
+        #[clap(long, value_names = &["name"], conflicts_with_all = &["remote", "instance"], env = "BRANE_VM_PLUGIN", help = "If given, runs the workflow with this VM plugin instead of the default one. Can be 'offline' (the default), 'mock' to pretend to run it without running any containers, or 'record' to run it while logging every task call, transfer and event. Only possible when running locally.")]
+        plugin: Option<String>,
+        #[clap(long, action, conflicts_with_all = &["profile", "trace", "plugin"], help = "If given, only submits the workflow to the remote instance and exits immediately. The driver keeps its output and result until you fetch them with 'brane runs attach'. Only possible when running remotely.")]
+        detach: bool,
+        #[clap(long, value_names = &["n"], help = "If given, retries every task call that fails up to this many times (with exponential backoff) before giving up on the workflow. Calls annotated with '@retry(...)' keep their own policy.")]
+        retries: Option<u32>,
+        #[clap(long, value_names = &["ms"], requires = "retries", help = "The time to wait before the first retry of a failed task call, in milliseconds. It is doubled for every next retry.")]
+        retry_backoff: Option<u64>,
+        #[clap(long, value_names = &["n"], help = "If given, aborts the workflow once it has taken this many steps (i.e., edges and instructions), which protects against loops that never end. When running remotely, the driver may impose a lower limit of its own.")]
+        max_steps: Option<u64>,
+        #[clap(long = "param", value_names = &["name=value"], help = "Gives a value to a parameter declared by the workflow (i.e., 'param <name>: <type>;'). Datasets are given as 'Data<\"<name>\">' or just their name. May be repeated; parameters with a default value may be omitted.")]
+        params: Vec<String>,
+
+        #[clap(flatten)]
+        render: RenderArgs,
+    },
+
+    #[clap(name = "runs", about = "Interacts with workflows that are running on a remote instance.")]
+    Runs {
+        // We subcommand further
+        #[clap(subcommand)]
+        subcommand : RunsSubcommand,
+    },
+
+    #[clap(name = "script", about = "Works on the BraneScript (or Bakery) code of a workflow itself.")]
+    Script {
+        // We subcommand further
+        #[clap(subcommand)]
+        subcommand : ScriptSubcommand,
     },
 
     #[clap(name = "test", about = "Test a package locally")]
@@ -189,12 +350,39 @@ enum SubCommand {
         version     : SemVersion,
         #[clap(short, long, help = "If given, prints the intermediate result returned by the tested function (if any). The given path should be relative to the 'result' folder.")]
         show_result : Option<PathBuf>,
+        #[clap(short, long, conflicts_with = "args", help = "If given, tests with the function and arguments saved earlier under this name (see '--save-as') instead of asking for them.")]
+        fixture     : Option<String>,
+        #[clap(short, long, help = "If given, tests with the function and arguments in this JSON file instead of asking for them. It has the same format as saved fixtures: an object with the 'function' to test and its 'args' by name (datasets are written as \"Data<name>\").")]
+        args        : Option<PathBuf>,
+        #[clap(long, help = "If given, saves the function and arguments that are tested as a fixture with this name, so the test can be repeated with '--fixture'.")]
+        save_as     : Option<String>,
+
+        #[clap(flatten)]
+        render      : RenderArgs,
     },
 
     #[clap(name = "search", about = "Search a registry for packages")]
     Search {
         #[clap(name = "TERM", help = "Term to use as search criteria")]
         term: Option<String>,
+        #[clap(short, long, action, help = "If given, searches the packages, registered workflows and datasets of the remote instance at once, grouping the results by kind.")]
+        all: bool,
+        #[clap(short, long, requires = "all", help = "If given with '--all', only shows results of this kind. Can be 'package', 'workflow' or 'dataset'.")]
+        kind: Option<SearchKind>,
+    },
+
+    #[clap(name = "session", about = "Finds and terminates (dangling) sessions on a remote instance.")]
+    Session {
+        // We subcommand further
+        #[clap(subcommand)]
+        subcommand : SessionSubcommand,
+    },
+
+    #[clap(name = "sweep", about = "Runs a workflow once for every combination of parameters in a matrix, and reports on the resulting runs.")]
+    Sweep {
+        // We subcommand further
+        #[clap(subcommand)]
+        subcommand : SweepSubcommand,
     },
 
     #[clap(name = "unpublish", about = "Remove a package from a registry")]
@@ -223,11 +411,31 @@ enum SubCommand {
         #[clap(short, long, action, help = "If given, shows the remote Driver version in an easy-to-be-parsed format. Note that, if given in combination with '--local', this one is always reported second.")]
         remote: bool,
     },
+
+    #[clap(name = "workflow", about = "Inspects compiled workflows without running them.")]
+    Workflow {
+        // We subcommand further
+        #[clap(subcommand)]
+        subcommand : WorkflowSubcommand,
+    },
 }
 
 /// Defines the subsubcommands for the data subcommand.
 #[derive(Parser)]
 enum DataSubcommand {
+    #[clap(name = "availability", about = "Shows, per location of the remote instance, whether the given dataset is available there.")]
+    Availability {
+        #[clap(name = "NAME", help = "The name of the dataset.")]
+        name : String,
+
+        /// The folder with the certificates that we use to identify ourselves.
+        #[clap(short, long, default_value = "./config/certs", help = "Path to the certificates with which we identify ourselves to each location. This should be a folder with nested folders, one for each location (and named as such) with in it 'ca.pem' and 'client-id.pem'.")]
+        certs_dir  : PathBuf,
+        /// The address to proxy the requests through.
+        #[clap(short, long, help = "If given, proxies the requests to each location through the given proxy.")]
+        proxy_addr : Option<String>,
+    },
+
     #[clap(name = "build", about = "Builds a locally available dataset from the given data.yml file and associated files (if any).")]
     Build {
         #[clap(name = "FILE", help = "Path to the file to build.")]
@@ -281,6 +489,249 @@ enum DataSubcommand {
     },
 }
 
+/// Defines the subcommands for the instance subcommand.
+#[derive(Parser)]
+enum InstanceSubcommand {
+    #[clap(name = "add", about = "Adds a new instance.")]
+    Add {
+        #[clap(name = "NAME", help = "The name by which to refer to the instance.")]
+        name      : String,
+        #[clap(long, value_names = &["address[:port]"], help = "The address of the instance's registry (port 50051 if omitted).")]
+        registry  : String,
+        #[clap(long, value_names = &["address[:port]"], help = "The address of the instance's driver. If omitted, it is assumed to run on the registry's host at port 50053.")]
+        driver    : Option<String>,
+        #[clap(short, long, help = "The username with which to sign packages for this instance.")]
+        username  : String,
+        #[clap(long, value_names = &["path"], help = "The directory with the certificates for the locations of this instance (see the '--certs-dir' option of 'brane run').")]
+        certs_dir : Option<PathBuf>,
+        #[clap(short, long, action, help = "If given, immediately selects the new instance.")]
+        select    : bool,
+    },
+
+    #[clap(name = "list", about = "Lists the known instances, marking the selected one.")]
+    List {},
+
+    #[clap(name = "select", about = "Selects an instance, i.e., logs in to its registry so subsequent commands use it by default.")]
+    Select {
+        #[clap(name = "NAME", help = "The name of the instance to select.")]
+        name : String,
+    },
+
+    #[clap(name = "remove", about = "Removes a known instance.")]
+    Remove {
+        #[clap(name = "NAME", help = "The name of the instance to remove.")]
+        name : String,
+    },
+}
+
+/// Defines the subcommands for the kernel subcommand.
+#[derive(Parser)]
+enum KernelSubcommand {
+    #[clap(name = "install", about = "Registers the kernel with Jupyter, such that it may be selected in JupyterLab or Jupyter Notebook.")]
+    Install {
+        #[clap(short, long, value_names = &["address[:port]"], required_unless_present = "instance", help = "The address of the remote driver to run cells on.")]
+        remote   : Option<String>,
+        #[clap(long, conflicts_with = "remote", help = "Run cells on the driver of this instance (see 'brane instance') instead.")]
+        instance : Option<String>,
+        #[clap(short, long, action, help = "Use Bakery instead of BraneScript")]
+        bakery   : bool,
+        #[clap(short, long, default_value = "brane", help = "The name of the kernel, which must be unique among the Jupyter kernels.")]
+        name     : String,
+        #[clap(short, long, value_names = &["path"], help = "The Jupyter kernels directory to install the kernel in. Defaults to the one of the current user.")]
+        dir      : Option<PathBuf>,
+    },
+
+    #[clap(name = "run", about = "Runs the kernel. This is called by Jupyter itself, as registered by 'brane kernel install'.")]
+    Run {
+        #[clap(short = 'f', long, value_names = &["path"], help = "The connection file written by Jupyter, which tells the kernel where to listen.")]
+        connection_file : PathBuf,
+        #[clap(short, long, value_names = &["address[:port]"], required_unless_present = "instance", help = "The address of the remote driver to run cells on.")]
+        remote          : Option<String>,
+        #[clap(long, conflicts_with = "remote", help = "Run cells on the driver of this instance (see 'brane instance') instead.")]
+        instance        : Option<String>,
+        #[clap(short, long, action, help = "Use Bakery instead of BraneScript")]
+        bakery          : bool,
+    },
+}
+
+/// Defines the subcommands for the package subcommand.
+#[derive(Parser)]
+enum PackageSubcommand {
+    #[clap(name = "availability", about = "Shows, per location of the remote instance, whether the given package is cached there and which capabilities it supports.")]
+    Availability {
+        #[clap(name = "NAME", help = "The name of the package.")]
+        name    : String,
+        #[clap(name = "VERSION", default_value = "latest", help = "The version of the package.")]
+        version : SemVersion,
+
+        /// The folder with the certificates that we use to identify ourselves.
+        #[clap(short, long, default_value = "./config/certs", help = "Path to the certificates with which we identify ourselves to each location. This should be a folder with nested folders, one for each location (and named as such) with in it 'ca.pem' and 'client-id.pem'.")]
+        certs_dir  : PathBuf,
+        /// The address to proxy the requests through.
+        #[clap(short, long, help = "If given, proxies the requests to each location through the given proxy.")]
+        proxy_addr : Option<String>,
+    },
+
+    #[clap(name = "codegen", about = "Generates a client library for a local package, with a typed function per function of the package that calls it through 'brane run'.")]
+    Codegen {
+        #[clap(name = "NAME", help = "The name of the package.")]
+        name    : String,
+        #[clap(name = "VERSION", default_value = "latest", help = "The version of the package.")]
+        version : SemVersion,
+        #[clap(short, long, default_value = "python", help = "The language to generate the client in. Only 'python' is supported for now.")]
+        lang    : CodegenLanguage,
+        #[clap(short, long, help = "The file to write the client to. Defaults to './<name>.<ext>'.")]
+        output  : Option<PathBuf>,
+    },
+}
+
+/// Defines the subcommands for the runs subcommand.
+#[derive(Parser)]
+enum RunsSubcommand {
+    #[clap(name = "approvals", about = "Lists the approvals that workflows on the remote instance are waiting for.")]
+    Approvals {
+        #[clap(short, long, value_names = &["address[:port]"], help = "The address of the remote driver.")]
+        remote : String,
+    },
+
+    #[clap(name = "approve", about = "Approves a pending approval, resuming the workflow waiting for it.")]
+    Approve {
+        #[clap(name = "ID", help = "The identifier of the approval to grant.")]
+        id     : String,
+        #[clap(short, long, value_names = &["address[:port]"], help = "The address of the remote driver.")]
+        remote : String,
+    },
+
+    #[clap(name = "reject", about = "Rejects a pending approval, failing the workflow waiting for it.")]
+    Reject {
+        #[clap(name = "ID", help = "The identifier of the approval to reject.")]
+        id     : String,
+        #[clap(long, help = "The reason for the rejection, which is reported to the workflow and recorded in the audit log.")]
+        reason : Option<String>,
+        #[clap(short, long, value_names = &["address[:port]"], help = "The address of the remote driver.")]
+        remote : String,
+    },
+
+    #[clap(name = "event", about = "Sends an event to a session, resuming the workflow that waits for it with 'wait_for_event()' (or buffering it until the workflow does).")]
+    Event {
+        #[clap(name = "ID", help = "The identifier of the session to send the event to.")]
+        id      : String,
+        #[clap(name = "NAME", help = "The name of the event.")]
+        name    : String,
+        #[clap(short, long, default_value = "", help = "The payload of the event, which 'wait_for_event()' returns.")]
+        payload : String,
+        #[clap(short, long, value_names = &["address[:port]"], help = "The address of the remote driver.")]
+        remote  : String,
+    },
+
+    #[clap(name = "analyze", about = "Shows the critical path of the last run in a session and estimates how long it would take with more parallelism or faster locations.")]
+    Analyze {
+        #[clap(name = "ID", help = "The identifier of the session to analyze.")]
+        id     : String,
+        #[clap(short, long, value_names = &["location=factor"], help = "Additionally estimates how long the run would take if the given location were the given factor faster (e.g., 'site1=2'). May be given multiple times.")]
+        faster : Vec<String>,
+        #[clap(short, long, value_names = &["address[:port]"], help = "The address of the remote driver.")]
+        remote : String,
+    },
+
+    #[clap(name = "cost", about = "Shows the CPU time, peak memory and I/O consumed by every task in the last run in a session, to help right-size the requirements of packages.")]
+    Cost {
+        #[clap(name = "ID", help = "The identifier of the session to show the cost of.")]
+        id     : String,
+        #[clap(short, long, value_names = &["address[:port]"], help = "The address of the remote driver.")]
+        remote : String,
+    },
+
+    #[clap(name = "results", about = "Lists the intermediate results produced by the tasks of the last run in a session, with where to download them (as long as their locations keep them around).")]
+    Results {
+        #[clap(name = "ID", help = "The identifier of the session to list the results of.")]
+        id     : String,
+        #[clap(short, long, value_names = &["address[:port]"], help = "The address of the remote driver.")]
+        remote : String,
+    },
+
+    #[clap(name = "attach", about = "Shows the output of a (detached) run so far and follows it until it completes, printing its result.")]
+    Attach {
+        #[clap(name = "ID", help = "The identifier of the session in which the run was submitted (as printed by 'brane run --detach').")]
+        id         : AppId,
+        #[clap(long, default_value = "./config/certs", value_names = &["path"], help = "Path to the directory with certificates that can help us prove who we are and who registries are. Used to download the dataset returned by the run, if any.")]
+        certs_dir  : PathBuf,
+        #[clap(short, long, value_names = &["address[:port]"], help = "If given, proxies any data transfers to this machine through the proxy at the given address.")]
+        proxy_addr : Option<String>,
+        #[clap(short, long, value_names = &["address[:port]"], help = "The address of the remote driver.")]
+        remote     : String,
+
+        #[clap(flatten)]
+        render     : RenderArgs,
+    },
+}
+
+/// Defines the subcommands for the script subcommand.
+#[derive(Parser)]
+enum ScriptSubcommand {
+    #[clap(name = "test", about = "Runs the unit tests of a workflow, i.e., its functions annotated with '#[test]'. They run on a dummy VM that does not run any containers, but returns mocked outputs for the tasks instead. A test fails if it errors or returns false.")]
+    Test {
+        #[clap(name = "FILE", help = "Path to the file with the workflow to test. Use '-' to read it from stdin instead.")]
+        file    : PathBuf,
+        #[clap(short, long, value_names = &["path"], help = "If given, the mocked tasks return the outputs in this YAML file, which maps the name of every function to the value it returns under 'outputs' (e.g., 'outputs: { hello_world: \"Hello, world!\" }'). Other tasks return nothing.")]
+        fixture : Option<PathBuf>,
+
+        #[clap(short, long, action, help = "Use Bakery instead of BraneScript")]
+        bakery  : bool,
+    },
+}
+
+/// Defines the subcommands for the session subcommand.
+#[derive(Parser)]
+enum SessionSubcommand {
+    #[clap(name = "list", about = "Lists the sessions on the remote instance, and whether they are running a workflow. Attach to one with 'brane repl --attach <ID>'.")]
+    List {
+        #[clap(short, long, value_names = &["address[:port]"], help = "The address of the remote driver.")]
+        remote : String,
+    },
+
+    #[clap(name = "kill", about = "Kills a session on the remote instance, aborting the workflow it is running (if any).")]
+    Kill {
+        #[clap(name = "ID", help = "The identifier of the session to kill.")]
+        id     : AppId,
+        #[clap(short, long, value_names = &["address[:port]"], help = "The address of the remote driver.")]
+        remote : String,
+    },
+}
+
+/// Defines the subcommands for the sweep subcommand.
+#[derive(Parser)]
+enum SweepSubcommand {
+    #[clap(name = "run", about = "Compiles a BraneScript workflow once and submits it to the remote instance for every combination of parameters in the given matrix, tracking the runs as one sweep.")]
+    Run {
+        #[clap(name = "FILE", help = "Path to the BraneScript file to run. It can use the parameters in the matrix as if they were global variables.")]
+        file     : PathBuf,
+        #[clap(short, long, value_names = &["path"], help = "Path to a YAML file that maps every parameter to the list of values to sweep it over (e.g., 'epochs: [ 10, 20 ]'). The values of a parameter must all be booleans, integers, reals or strings.")]
+        matrix   : PathBuf,
+        #[clap(short, long, value_names = &["address[:port]"], required_unless_present = "instance", help = "The address of the remote driver.")]
+        remote   : Option<String>,
+        #[clap(long, conflicts_with = "remote", help = "Run the sweep on this instance (see 'brane instance') instead, using its driver and registry.")]
+        instance : Option<String>,
+        #[clap(long, action, help = "If given, only submits the runs and exits immediately. Use 'brane sweep report' to see their statuses and results later.")]
+        detach   : bool,
+
+        #[clap(flatten)]
+        render   : RenderArgs,
+    },
+
+    #[clap(name = "report", about = "Shows the statuses and results of the runs in a sweep, waiting for the ones that did not complete yet.")]
+    Report {
+        #[clap(name = "ID", help = "The identifier of the sweep (as printed by 'brane sweep run').")]
+        id     : String,
+
+        #[clap(flatten)]
+        render : RenderArgs,
+    },
+
+    #[clap(name = "list", about = "Lists the sweeps submitted from this machine.")]
+    List {},
+}
+
 /// Defines the subcommands for the verify subcommand.
 #[derive(Parser)]
 enum VerifySubcommand {
@@ -291,6 +742,31 @@ enum VerifySubcommand {
     },
 }
 
+/// Defines the subcommands for the workflow subcommand.
+#[derive(Parser)]
+enum WorkflowSubcommand {
+    #[clap(name = "graph", about = "Compiles a DSL script and renders the resulting workflow graph in Graphviz' DOT format, including task locations and data dependencies.")]
+    Graph {
+        #[clap(name = "FILE", help = "Path to the file to compile. Use '-' to read from stdin instead.")]
+        file   : PathBuf,
+        #[clap(short, long, help = "If given, writes the graph to the given file instead of stdout.")]
+        output : Option<PathBuf>,
+        #[clap(long, action, help = "If given, lets Graphviz' `dot` layout the graph as an SVG image instead of emitting the DOT source. Requires Graphviz to be installed.")]
+        svg    : bool,
+
+        #[clap(short, long, action, help = "Use Bakery instead of BraneScript")]
+        bakery : bool,
+    },
+    #[clap(name = "show", about = "Parses a DSL script and lists the functions and classes it defines, together with their documentation comments ('///').")]
+    Show {
+        #[clap(name = "FILE", help = "Path to the file to parse. Use '-' to read from stdin instead.")]
+        file   : PathBuf,
+
+        #[clap(short, long, action, help = "Use Bakery instead of BraneScript")]
+        bakery : bool,
+    },
+}
+
 
 
 
@@ -350,7 +826,7 @@ async fn main() -> Result<()> {
 async fn run(options: Cli) -> Result<(), CliError> {
     use SubCommand::*;
     match options.sub_command {
-        Build { arch, workdir, file, kind, init, keep_files } => {
+        Build { arch, workdir, file, kind, init, keep_files, dev } => {
             // Resolve the working directory
             let workdir = match workdir {
                 Some(workdir) => workdir,
@@ -383,10 +859,14 @@ async fn run(options: Cli) -> Result<(), CliError> {
                 Err(err) => { return Err(CliError::BuildError{ err: BuildError::HostArchError{ err } }); }
             };
 
+            let arches: Vec<Arch> = if arch.is_empty() { vec![ host_arch ] } else { arch };
+            if dev && kind != PackageKind::Ecu { log::warn!("Development mode is only supported for ECU packages; building {} package as usual", kind); }
+
             // Build a new package with it
             match kind {
-                PackageKind::Ecu => build_ecu::handle(arch.unwrap_or(host_arch), workdir, file, init, keep_files).await.map_err(|err| CliError::BuildError{ err })?,
-                PackageKind::Oas => build_oas::handle(arch.unwrap_or(host_arch), workdir, file, init, keep_files).await.map_err(|err| CliError::BuildError{ err })?,
+                PackageKind::Ecu => build_ecu::handle(arches, workdir, file, init, keep_files, dev).await.map_err(|err| CliError::BuildError{ err })?,
+                PackageKind::Oas => build_oas::handle(arches, workdir, file, init, keep_files).await.map_err(|err| CliError::BuildError{ err })?,
+                PackageKind::Cwl => build_cwl::handle(arches, file, init, keep_files).await.map_err(|err| CliError::BuildError{ err })?,
                 _                => eprintln!("Unsupported package kind: {}", kind),
             }
         }
@@ -395,6 +875,10 @@ async fn run(options: Cli) -> Result<(), CliError> {
             // Match again
             use DataSubcommand::*;
             match subcommand {
+                Availability{ name, certs_dir, proxy_addr } => {
+                    if let Err(err) = availability::data(name, certs_dir, &proxy_addr).await { return Err(CliError::AvailabilityError{ err }); }
+                },
+
                 Build { file, workdir, keep_files, no_links } => {
                     if let Err(err) = data::build(&file, workdir.unwrap_or_else(|| file.parent().map(|p| p.into()).unwrap_or_else(|| PathBuf::from("./"))), keep_files, no_links).await { return Err(CliError::DataError { err }); }
                 },
@@ -403,7 +887,7 @@ async fn run(options: Cli) -> Result<(), CliError> {
                 },
 
                 List {} => {
-                    if let Err(err) = data::list() { return Err(CliError::DataError { err }); }
+                    if let Err(err) = data::list(options.output) { return Err(CliError::DataError { err }); }
                 },
                 Search{} => {
                     eprintln!("search is not yet implemented.");
@@ -477,19 +961,50 @@ async fn run(options: Cli) -> Result<(), CliError> {
                 Err(err) => { return Err(CliError::BuildError{ err: BuildError::HostArchError{ err } }); }
             };
 
+            let arches: Vec<Arch> = if arch.is_empty() { vec![ host_arch ] } else { arch };
+
             // Build a new package with it
             match kind {
-                PackageKind::Ecu => build_ecu::handle(arch.unwrap_or(host_arch), workdir, file, init, false).await.map_err(|err| CliError::BuildError{ err })?,
-                PackageKind::Oas => build_oas::handle(arch.unwrap_or(host_arch), workdir, file, init, false).await.map_err(|err| CliError::BuildError{ err })?,
+                PackageKind::Ecu => build_ecu::handle(arches, workdir, file, init, false, false).await.map_err(|err| CliError::BuildError{ err })?,
+                PackageKind::Oas => build_oas::handle(arches, workdir, file, init, false).await.map_err(|err| CliError::BuildError{ err })?,
+                PackageKind::Cwl => build_cwl::handle(arches, file, init, false).await.map_err(|err| CliError::BuildError{ err })?,
                 _                => eprintln!("Unsupported package kind: {}", kind),
             }
         }
 
         Inspect { name, version, syntax } => {
-            if let Err(err) = packages::inspect(name, version, syntax) { return Err(CliError::OtherError{ err }); };
+            if let Err(err) = packages::inspect(name, version, syntax, options.output) { return Err(CliError::OtherError{ err }); };
+        }
+        Instance { subcommand } => {
+            // Match the subcommand in question
+            use InstanceSubcommand::*;
+            let res = match subcommand {
+                Add { name, registry, driver, username, certs_dir, select } => instance::add(name, registry, driver, username, certs_dir, select),
+                List {}                                                     => instance::list(),
+                Select { name }                                             => instance::select(name),
+                Remove { name }                                             => instance::remove(name),
+            };
+            if let Err(err) = res { return Err(CliError::InstanceError{ err }); }
+        }
+        Kernel { subcommand } => {
+            // Match the subcommand in question
+            use KernelSubcommand::*;
+            match subcommand {
+                Install { remote, instance, bakery, name, dir } => {
+                    if let Err(err) = kernel::install(remote, instance, if bakery { Language::Bakery } else { Language::BraneScript }, name, dir) { return Err(CliError::KernelError{ err }); }
+                },
+                Run { connection_file, remote, instance, bakery } => {
+                    // Take the driver from the instance, if given
+                    let remote: String = match instance {
+                        Some(instance) => instance::activate(&instance).map_err(|err| CliError::InstanceError{ err })?.driver,
+                        None           => remote.expect("clap requires either '--remote' or '--instance'"),
+                    };
+                    if let Err(err) = kernel::run(connection_file, remote, if bakery { Language::Bakery } else { Language::BraneScript }).await { return Err(CliError::KernelError{ err }); }
+                },
+            }
         }
         List { latest } => {
-            if let Err(err) = packages::list(latest) { return Err(CliError::OtherError{ err: anyhow::anyhow!(err) }); };
+            if let Err(err) = packages::list(latest, options.output) { return Err(CliError::OtherError{ err: anyhow::anyhow!(err) }); };
         }
         Load { name, version } => {
             if let Err(err) = packages::load(name, version).await { return Err(CliError::OtherError{ err }); };
@@ -500,7 +1015,29 @@ async fn run(options: Cli) -> Result<(), CliError> {
         Logout {} => {
             if let Err(err) = registry::logout() { return Err(CliError::OtherError{ err }); };
         }
-        Pull { packages } => {
+        New { kind, name, path, language } => {
+            if let Err(err) = scaffold::handle(kind, name, path, language) { return Err(CliError::ScaffoldError{ err }); };
+        }
+        Package { subcommand } => {
+            // Match again
+            use PackageSubcommand::*;
+            match subcommand {
+                Availability{ name, version, certs_dir, proxy_addr } => {
+                    if let Err(err) = availability::package(name, version, certs_dir, &proxy_addr).await { return Err(CliError::AvailabilityError{ err }); }
+                },
+                Codegen{ name, version, lang, output } => {
+                    if let Err(err) = codegen::handle(name, version, lang, output) { return Err(CliError::CodegenError{ err }); }
+                },
+            }
+        },
+        Profile { id, sort, limit, flamegraph, remote } => {
+            if let Err(err) = profile::handle(remote, id, sort, limit, flamegraph).await { return Err(CliError::ProfileError{ err }); }
+        },
+        Pull { packages, instance } => {
+            if let Some(instance) = instance {
+                if let Err(err) = instance::activate(&instance) { return Err(CliError::InstanceError{ err }); }
+            }
+
             // Parse the NAME:VERSION pairs into a name and a version
             if packages.is_empty() { println!("Nothing to do."); return Ok(()); }
             let mut parsed: Vec<(String, SemVersion)> = Vec::with_capacity(packages.len());
@@ -514,7 +1051,11 @@ async fn run(options: Cli) -> Result<(), CliError> {
             // Now delegate the parsed pairs to the actual pull() function
             if let Err(err) = registry::pull(parsed).await { return Err(CliError::RegistryError{ err }); };
         }
-        Push{ packages } => {
+        Push{ packages, instance, registry } => {
+            if let Some(instance) = instance {
+                if let Err(err) = instance::activate(&instance) { return Err(CliError::InstanceError{ err }); }
+            }
+
             // Parse the NAME:VERSION pairs into a name and a version
             if packages.is_empty() { println!("Nothing to do."); return Ok(()); }
             let mut parsed: Vec<(String, SemVersion)> = Vec::with_capacity(packages.len());
@@ -526,7 +1067,7 @@ async fn run(options: Cli) -> Result<(), CliError> {
             }
 
             // Now delegate the parsed pairs to the actual push() function
-            if let Err(err) = registry::push(parsed).await { return Err(CliError::RegistryError{ err }); };
+            if let Err(err) = registry::push(parsed, registry).await { return Err(CliError::RegistryError{ err }); };
         }
         Remove { force, packages } => {
             // Parse the NAME:VERSION pairs into a name and a version
@@ -542,17 +1083,99 @@ async fn run(options: Cli) -> Result<(), CliError> {
             // Now delegate the parsed pairs to the actual remove() function
             if let Err(err) = packages::remove(force, parsed).await { return Err(CliError::PackageError{ err }); };
         }
-        Repl { certs_dir, proxy_addr, bakery, clear, remote, attach } => {
-            if let Err(err) = repl::start(certs_dir, proxy_addr, remote, attach, if bakery { Language::Bakery } else { Language::BraneScript }, clear).await { return Err(CliError::ReplError{ err }); };
+        Repl { mut certs_dir, proxy_addr, bakery, clear, record, replay, mut remote, instance, attach, render, reconnect } => {
+            // Take the driver and certificates from the instance, if given
+            if let Some(instance) = instance {
+                let info = instance::activate(&instance).map_err(|err| CliError::InstanceError{ err })?;
+                remote    = Some(info.driver);
+                certs_dir = info.certs_dir.unwrap_or(certs_dir);
+            }
+            if let Err(err) = repl::start(certs_dir, proxy_addr, remote, attach, if bakery { Language::Bakery } else { Language::BraneScript }, clear, record, replay, render.into(), reconnect.into()).await { return Err(CliError::ReplError{ err }); };
         }
-        Run { certs_dir, proxy_addr, bakery, file, remote } => {
-            if let Err(err) = run::handle(certs_dir, proxy_addr, if bakery { Language::Bakery } else { Language::BraneScript }, file, remote).await { return Err(CliError::RunError{ err }); };
+        Run { mut certs_dir, proxy_addr, bakery, file, mut remote, instance, profile, trace, plugin, detach, retries, retry_backoff, max_steps, params, render } => {
+            // Take the driver and certificates from the instance, if given
+            if let Some(instance) = instance {
+                let info = instance::activate(&instance).map_err(|err| CliError::InstanceError{ err })?;
+                remote    = Some(info.driver);
+                certs_dir = info.certs_dir.unwrap_or(certs_dir);
+            }
+            let retry: Option<RetryPolicy> = retries.map(|n| RetryPolicy{ max_retries: n, backoff: retry_backoff.unwrap_or_else(RetryPolicy::default_backoff) });
+            if let Err(err) = run::handle(certs_dir, proxy_addr, if bakery { Language::Bakery } else { Language::BraneScript }, file, remote, profile, trace, plugin, detach, retry, max_steps, params, render.into()).await { return Err(CliError::RunError{ err }); };
         }
-        Test { name, version, show_result } => {
-            if let Err(err) = test::handle(name, version, show_result).await { return Err(CliError::TestError{ err }); };
+        Runs{ subcommand } => {
+            // Match the subcommand in question
+            use RunsSubcommand::*;
+            match subcommand {
+                Approvals { remote } => {
+                    if let Err(err) = runs::approvals(remote).await { return Err(CliError::RunsError{ err }); }
+                },
+                Approve { id, remote } => {
+                    if let Err(err) = runs::approve(remote, id).await { return Err(CliError::RunsError{ err }); }
+                },
+                Reject { id, reason, remote } => {
+                    if let Err(err) = runs::reject(remote, id, reason).await { return Err(CliError::RunsError{ err }); }
+                },
+                Event { id, name, payload, remote } => {
+                    if let Err(err) = runs::event(remote, id, name, payload).await { return Err(CliError::RunsError{ err }); }
+                },
+                Analyze { id, faster, remote } => {
+                    if let Err(err) = runs::analyze(remote, id, faster).await { return Err(CliError::RunsError{ err }); }
+                },
+                Cost { id, remote } => {
+                    if let Err(err) = runs::cost(remote, id).await { return Err(CliError::RunsError{ err }); }
+                },
+                Results { id, remote } => {
+                    if let Err(err) = runs::results(remote, id).await { return Err(CliError::RunsError{ err }); }
+                },
+                Attach { id, certs_dir, proxy_addr, remote, render } => {
+                    if let Err(err) = runs::attach(certs_dir, proxy_addr, remote, id, render.into()).await { return Err(CliError::RunsError{ err }); }
+                },
+            }
         }
-        Search { term } => {
-            if let Err(err) = registry::search(term).await { return Err(CliError::OtherError{ err }); };
+        Script{ subcommand } => {
+            // Match the subcommand in question
+            use ScriptSubcommand::*;
+            match subcommand {
+                Test { file, fixture, bakery } => {
+                    if let Err(err) = script::test(if bakery { Language::Bakery } else { Language::BraneScript }, file, fixture).await { return Err(CliError::ScriptError{ err }); }
+                },
+            }
+        }
+        Session{ subcommand } => {
+            // Match the subcommand in question
+            use SessionSubcommand::*;
+            match subcommand {
+                List { remote } => {
+                    if let Err(err) = session::list(remote).await { return Err(CliError::SessionError{ err }); }
+                },
+                Kill { id, remote } => {
+                    if let Err(err) = session::kill(remote, id).await { return Err(CliError::SessionError{ err }); }
+                },
+            }
+        }
+        Sweep{ subcommand } => {
+            // Match the subcommand in question
+            use SweepSubcommand::*;
+            let res = match subcommand {
+                Run { file, matrix, remote, instance, detach, render } => {
+                    // Take the driver from the instance, if given
+                    let remote: String = match instance {
+                        Some(instance) => instance::activate(&instance).map_err(|err| CliError::InstanceError{ err })?.driver,
+                        None           => remote.unwrap_or_default(),
+                    };
+                    sweep::run(remote, file, matrix, detach, render.into()).await
+                },
+                Report { id, render } => sweep::report(id, render.into()).await,
+                List {}               => sweep::list(),
+            };
+            if let Err(err) = res { return Err(CliError::SweepError{ err }); }
+        }
+        Test { name, version, show_result, fixture, args, save_as, render } => {
+            if let Err(err) = test::handle(name, version, show_result, fixture, args, save_as, render.into()).await { return Err(CliError::TestError{ err }); };
+        }
+        Search { term, all, kind } => {
+            let res = if all { registry::search_all(term, kind, options.output).await } else { registry::search(term, options.output).await };
+            if let Err(err) = res { return Err(CliError::OtherError{ err }); };
         }
         Unpublish { name, version, force } => {
             if let Err(err) = registry::unpublish(name, version, force).await { return Err(CliError::OtherError{ err }); };
@@ -584,6 +1207,18 @@ async fn run(options: Cli) -> Result<(), CliError> {
                 if let Err(err) = version::handle().await { return Err(CliError::VersionError{ err }); }
             }
         }
+        Workflow{ subcommand } => {
+            // Match the subcommand in question
+            use WorkflowSubcommand::*;
+            match subcommand {
+                Graph { file, output, svg, bakery } => {
+                    if let Err(err) = workflow::graph(if bakery { Language::Bakery } else { Language::BraneScript }, file, output, svg) { return Err(CliError::WorkflowError{ err }); }
+                },
+                Show { file, bakery } => {
+                    if let Err(err) = workflow::show(if bakery { Language::Bakery } else { Language::BraneScript }, file) { return Err(CliError::WorkflowError{ err }); }
+                },
+            }
+        }
     }
 
     Ok(())
@@ -16,6 +16,7 @@ use hyper::Body;
 use indicatif::{DecimalBytes, HumanDuration};
 use prettytable::format::FormatBuilder;
 use prettytable::Table;
+use serde::Serialize;
 use tokio::fs::File as TFile;
 use tokio_stream::StreamExt;
 use tokio_util::codec::{BytesCodec, FramedRead};
@@ -28,9 +29,32 @@ use specifications::package::PackageInfo;
 use specifications::version::Version;
 
 use crate::errors::PackageError;
+use crate::render::{print_structured, OutputFormat};
 use crate::utils::{ensure_packages_dir, ensure_package_dir};
 
 
+/***** HELPER STRUCTS *****/
+/// Defines a single entry in the structured output of `brane list`.
+#[derive(Serialize)]
+struct PackageEntry {
+    /// The identifier of the package.
+    id      : String,
+    /// The name of the package.
+    name    : String,
+    /// The version of the package.
+    version : String,
+    /// The kind of the package.
+    kind    : String,
+    /// When the package was created (as an RFC 3339 timestamp).
+    created : String,
+    /// The size of the package on disk, in bytes.
+    size    : u64,
+}
+
+
+
+
+
 /***** HELPER FUNCTIONS *****/
 /// Inserts a PackageInfo in a list of PackageInfos such that it tries to only have the latest version of each package.
 /// 
@@ -70,6 +94,7 @@ fn insert_package_in_list(infos: &mut Vec<PackageInfo>, info: PackageInfo) {
 /// - `name`: The name of the package to inspect.
 /// - `version`: The version of the package to inspect.
 /// - `syntax`: The mode of syntax to use for classes & functions. Can be 'bscript', 'bakery' or 'custom'.
+/// - `output`: The format in which to write the details. If not `OutputFormat::Table`, the full PackageInfo is written instead and `syntax` is ignored.
 /// 
 /// # Returns
 /// Nothing
@@ -77,11 +102,18 @@ pub fn inspect(
     name: String,
     version: Version,
     syntax: String,
+    output: OutputFormat,
 ) -> Result<()> {
     let package_dir = ensure_package_dir(&name, Some(&version), false)?;
     let package_file = package_dir.join("package.yml");
 
     if let Ok(info) = PackageInfo::from_path(package_file) {
+        // Write it in machine-readable form if asked
+        if output != OutputFormat::Table {
+            print_structured(&info, output)?;
+            return Ok(());
+        }
+
         // _Neatly_ print it
         println!();
         println!("Package {} ({} package, version {})", style(&info.name).bold().cyan(), style(format!("{}", info.kind)).bold(), style(format!("{}", info.version)).bold());
@@ -171,16 +203,25 @@ pub fn inspect(
 /// use console::style;
 /// **Arguments**
 ///  * `latest`: If set to true, only shows latest version of each package.
+///  * `output`: The format in which to write the list.
 /// 
 /// **Returns**  
 /// Nothing other than prints on stdout if successfull, or an ExecutorError otherwise.
 pub fn list(
-    latest: bool
+    latest: bool,
+    output: OutputFormat,
 ) -> Result<(), PackageError> {
     // Get the directory with the packages
     let packages_dir = match ensure_packages_dir(false) {
         Ok(dir)     => dir,
-        Err(_)      => { println!("No packages found."); return Ok(()); }
+        Err(_)      => {
+            if output != OutputFormat::Table {
+                if let Err(err) = print_structured(&Vec::<PackageEntry>::new(), output) { return Err(PackageError::RenderError{ err }); }
+            } else {
+                println!("No packages found.");
+            }
+            return Ok(());
+        }
     };
 
     // Prepare display table.
@@ -213,6 +254,23 @@ pub fn list(
         }
     }
 
+    // Write the list in machine-readable form if asked
+    if output != OutputFormat::Table {
+        let entries: Vec<PackageEntry> = infos.into_iter().map(|entry| {
+            let package_path = packages_dir.join(&entry.name).join(entry.version.to_string());
+            PackageEntry {
+                id      : entry.id.to_string(),
+                name    : entry.name,
+                version : entry.version.to_string(),
+                kind    : entry.kind.to_string(),
+                created : entry.created.to_rfc3339(),
+                size    : dir::get_size(package_path).unwrap_or(0),
+            }
+        }).collect();
+        if let Err(err) = print_structured(&entries, output) { return Err(PackageError::RenderError{ err }); }
+        return Ok(());
+    }
+
     // With the list constructed, add each entry
     let now = Utc::now().timestamp();
     for entry in infos {
@@ -0,0 +1,209 @@
+//  PROFILE.rs
+//    by agent
+//
+//  Created:
+//    16 Oct 2026, 09:10:00
+//  Last edited:
+//    16 Oct 2026, 12:51:53
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements the `profile` subcommand, which aggregates the timings of
+//!   the tasks, data transfers and task calls of a run on a remote
+//!   instance into a report.
+//
+
+use std::fs::File;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use console::style;
+use indicatif::DecimalBytes;
+use prettytable::format::FormatBuilder;
+use prettytable::Table;
+use tonic::transport::Channel;
+
+use brane_ast::Workflow;
+use brane_ast::schedule::TaskTiming;
+use brane_tsk::grpc::{DriverServiceClient, GetTimingsRequest};
+use specifications::profiling::{format_duration, ProfileEntry, ProfileKind, ProfileReport, ProfileSample, ProfileSortKey, TransferTiming};
+use specifications::status::ResourceUsage;
+
+pub use crate::errors::ProfileError as Error;
+use crate::instance::client_tls;
+
+
+/***** HELPER FUNCTIONS *****/
+/// Describes the edge that called a task, so that calls of the same task in different places can be told apart.
+///
+/// # Arguments
+/// - `workflow`: The Workflow that was run, if known.
+/// - `timing`: The TaskTiming of the call.
+///
+/// # Returns
+/// A string of the form `<body>:<offset> (<task>)`, where the body is the name of the function that the call is in (or `<main>`).
+fn describe_edge(workflow: Option<&Workflow>, timing: &TaskTiming) -> String {
+    let body: String = if timing.pc.0 == usize::MAX {
+        "<main>".into()
+    } else {
+        workflow.and_then(|w| w.table.funcs.enumerate().find(|(i, _)| *i == timing.pc.0).map(|(_, f)| f.name.clone())).unwrap_or_else(|| format!("<func {}>", timing.pc.0))
+    };
+    format!("{}:{} ({})", body, timing.pc.1, timing.name)
+}
+
+/// Prints a table with the given entries of a profiling report.
+///
+/// # Arguments
+/// - `what`: What the entries are timings of (e.g., `Tasks`).
+/// - `entries`: The entries to print.
+/// - `duration`: The duration of the whole run, in milliseconds.
+/// - `limit`: The maximum number of entries to print, if any.
+fn print_entries(what: &str, entries: &[ProfileEntry], duration: i64, limit: Option<usize>) {
+    println!("{}:", what);
+    if entries.is_empty() { println!("  (none)"); println!(); return; }
+
+    // Prepare display table.
+    let format = FormatBuilder::new()
+        .column_separator('\0')
+        .borders('\0')
+        .padding(1, 1)
+        .build();
+    let mut table = Table::new();
+    table.set_format(format);
+    table.add_row(row!["NAME", "LOCATIONS", "COUNT", "TOTAL", "MEAN", "MIN", "MAX", "SHARE"]);
+    for e in entries.iter().take(limit.unwrap_or(usize::MAX)) {
+        let share: f64 = if duration > 0 { 100.0 * e.total as f64 / duration as f64 } else { 0.0 };
+        table.add_row(row![e.name, e.locations.join(", "), e.count, format_duration(Duration::from_millis(e.total as u64)), format_duration(Duration::from_secs_f64(e.mean() / 1_000.0)), format_duration(Duration::from_millis(e.min as u64)), format_duration(Duration::from_millis(e.max as u64)), format!("{:.1}%", share)]);
+    }
+    table.printstd();
+    if let Some(limit) = limit {
+        if entries.len() > limit { println!("  ({} more)", entries.len() - limit); }
+    }
+    println!();
+}
+
+/// Prints a table with the resources consumed by the tasks in a profiling report.
+///
+/// # Arguments
+/// - `entries`: The task entries of the report. Those without measured usage are skipped.
+/// - `limit`: The maximum number of entries to print, if any.
+fn print_usage(entries: &[ProfileEntry], limit: Option<usize>) {
+    let measured: Vec<(&ProfileEntry, &ResourceUsage)> = entries.iter().filter_map(|e| e.usage.as_ref().map(|u| (e, u))).collect();
+    if measured.is_empty() { return; }
+    println!("Resource usage:");
+
+    // Prepare display table.
+    let format = FormatBuilder::new()
+        .column_separator('\0')
+        .borders('\0')
+        .padding(1, 1)
+        .build();
+    let mut table = Table::new();
+    table.set_format(format);
+    table.add_row(row!["NAME", "CPU TIME", "PEAK MEMORY", "READ", "WRITTEN"]);
+    for (e, usage) in measured.iter().take(limit.unwrap_or(usize::MAX)) {
+        table.add_row(row![e.name, format!("{:.2}s", usage.cpu_seconds), DecimalBytes(usage.peak_memory), DecimalBytes(usage.io_read), DecimalBytes(usage.io_write)]);
+    }
+    table.printstd();
+    if let Some(limit) = limit {
+        if measured.len() > limit { println!("  ({} more)", measured.len() - limit); }
+    }
+    println!();
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Profiles the last run in a session on the remote instance, printing how long its tasks, data transfers and task calls took.
+///
+/// Note that a share may exceed 100% if things happened in parallel, as it is relative to the duration of the whole run.
+///
+/// # Arguments
+/// - `endpoint`: The `brane-drv` endpoint to connect to.
+/// - `id`: The identifier of the session to profile.
+/// - `sort`: The key to sort the tables by.
+/// - `limit`: The maximum number of rows to print per table, if any.
+/// - `flamegraph`: If given, writes the timings as a flamegraph-style JSON tree (e.g., for `d3-flame-graph`) to this file.
+///
+/// # Errors
+/// This function errors if we failed to connect to the driver, if it failed to return the timings of the run or if we failed to write the flamegraph.
+pub async fn handle(endpoint: impl AsRef<str>, id: String, sort: ProfileSortKey, limit: Option<usize>, flamegraph: Option<PathBuf>) -> Result<(), Error> {
+    let endpoint: &str = endpoint.as_ref();
+
+    // Get the timings of the run
+    debug!("Connecting to driver '{}'...", endpoint);
+    let mut client: DriverServiceClient<Channel> = match client_tls().connect_driver(endpoint).await {
+        Ok(client) => client,
+        Err(err)   => { return Err(Error::ClientConnectError{ address: endpoint.into(), err }); },
+    };
+    let reply = match client.get_timings(GetTimingsRequest{ uuid: id.clone() }).await {
+        Ok(reply) => reply.into_inner(),
+        Err(err)  => { return Err(Error::GetTimingsError{ address: endpoint.into(), id, err }); },
+    };
+    let workflow: Option<Workflow> = match reply.workflow {
+        Some(workflow) => match serde_json::from_str(&workflow) {
+            Ok(workflow) => Some(workflow),
+            Err(err)     => { return Err(Error::WorkflowParseError{ id, err }); },
+        },
+        None => { return Err(Error::NoWorkflow{ id }); },
+    };
+    let timings: Vec<TaskTiming> = match serde_json::from_str(&reply.timings) {
+        Ok(timings) => timings,
+        Err(err)    => { return Err(Error::TimingsParseError{ id, err }); },
+    };
+    // Older drivers do not time transfers
+    let transfers: Vec<TransferTiming> = if reply.transfers.is_empty() { vec![] } else {
+        match serde_json::from_str(&reply.transfers) {
+            Ok(transfers) => transfers,
+            Err(err)      => { return Err(Error::TimingsParseError{ id, err }); },
+        }
+    };
+    if timings.is_empty() && transfers.is_empty() {
+        println!("Run {} did not complete any tasks or transfers (yet)", style(&id).bold().cyan());
+        return Ok(());
+    }
+
+    // Aggregate them into a report
+    let samples: Vec<ProfileSample> = timings.iter().map(|t| ProfileSample {
+        kind     : ProfileKind::Task,
+        name     : t.name.clone(),
+        location : t.location.clone(),
+        edge     : Some(describe_edge(workflow.as_ref(), t)),
+        start    : t.start,
+        end      : t.end,
+        usage    : t.usage,
+    }).chain(transfers.into_iter().map(|t| ProfileSample {
+        kind     : ProfileKind::Transfer,
+        name     : t.name,
+        location : t.location,
+        edge     : None,
+        start    : t.start,
+        end      : t.end,
+        usage    : None,
+    })).collect();
+    let mut report: ProfileReport = ProfileReport::new(&id, &samples);
+    report.sort(sort);
+
+    // Print it
+    println!("Run {} took {} over {} task(s) and {} transfer(s)", style(&id).bold().cyan(), format_duration(Duration::from_millis(report.duration as u64)), timings.len(), samples.len() - timings.len());
+    println!();
+    print_entries("Tasks", &report.tasks, report.duration, limit);
+    print_entries("Transfers", &report.transfers, report.duration, limit);
+    print_entries("Edges", &report.edges, report.duration, limit);
+    print_usage(&report.tasks, limit);
+
+    // Write the flamegraph if told to do so
+    if let Some(path) = flamegraph {
+        let handle: File = match File::create(&path) {
+            Ok(handle) => handle,
+            Err(err)   => { return Err(Error::FlamegraphCreateError{ path, err }); },
+        };
+        if let Err(err) = serde_json::to_writer_pretty(handle, &report.flamegraph) { return Err(Error::FlamegraphWriteError{ path, err }); }
+        println!("Wrote flamegraph to '{}'", path.display());
+    }
+
+    Ok(())
+}
@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 use std::fs::{self, File};
 use std::io::prelude::*;
+use std::process::Command;
 use std::str::FromStr;
 
 use anyhow::{Context, Result};
@@ -22,11 +23,16 @@ use url::Url;
 use uuid::Uuid;
 
 use brane_tsk::local::get_package_versions;
+use specifications::arch::{Arch, ManifestList};
 use specifications::package::{PackageKind, PackageInfo};
-use specifications::registry::RegistryConfig;
+use specifications::registry::{ImageRegistryInfo, RegistryConfig};
+use specifications::search::{SearchKind, SearchResult, SearchResults};
 use specifications::version::Version;
 
+use crate::build_common::DEV_INFO_FILE;
 use crate::errors::RegistryError;
+use crate::instance::client_tls;
+use crate::render::{print_structured, OutputFormat};
 use crate::utils::{get_config_dir, get_packages_dir, get_registry_file, ensure_package_dir, ensure_packages_dir, ensure_config_dir};
 
 
@@ -70,6 +76,61 @@ pub fn get_data_endpoint() -> Result<String, RegistryError> {
     Ok(format!("{}/data", get_registry_file().map_err(|err| RegistryError::ConfigFileError{ err })?.url))
 }
 
+/// Get the search endpoint of the Brane API.
+/// 
+/// # Returns
+/// The endpoint (as a String).
+/// 
+/// # Errors
+/// This function may error if we could not find, read or parse the config file with the login data. If not found, this likely indicates the user hasn't logged-in yet.
+#[inline]
+pub fn get_search_endpoint() -> Result<String, RegistryError> {
+    Ok(format!("{}/search", get_registry_file().map_err(|err| RegistryError::ConfigFileError{ err })?.url))
+}
+
+/// Asks the remote instance in which OCI registry it stores package images.
+/// 
+/// # Returns
+/// The ImageRegistryInfo describing the registry.
+/// 
+/// # Errors
+/// This function errors if we are not logged-in, if the request failed or if the instance has no image registry configured.
+async fn get_image_registry() -> Result<ImageRegistryInfo, RegistryError> {
+    let url: String = format!("{}/registry", get_packages_endpoint()?);
+    debug!("Retrieving image registry from '{}'...", url);
+    let response: reqwest::Response = match client_tls().api().get(&url).send().await {
+        Ok(response) => response,
+        Err(err)     => { return Err(RegistryError::ImageRegistryRequestError{ url, err }); }
+    };
+    if response.status() == reqwest::StatusCode::NOT_FOUND { return Err(RegistryError::NoImageRegistry{ url }); }
+    if !response.status().is_success() { return Err(RegistryError::ImageRegistryRequestFailure{ url, status: response.status() }); }
+    match response.json().await {
+        Ok(info) => Ok(info),
+        Err(err) => Err(RegistryError::ImageRegistryParseError{ url, err }),
+    }
+}
+
+/// Runs a single `docker` command, returning an error if it did not complete successfully.
+/// 
+/// # Arguments
+/// - `args`: The arguments to pass to the `docker` executable.
+/// 
+/// # Errors
+/// This function errors if we failed to launch the command or if it returned a non-zero exit code.
+fn run_docker(args: &[&str]) -> Result<(), RegistryError> {
+    let mut command = Command::new("docker");
+    command.args(args);
+    debug!("Running {:?}...", command);
+    let output = match command.output() {
+        Ok(output) => output,
+        Err(err)   => { return Err(RegistryError::DockerLaunchError{ command: format!("{:?}", command), err }); }
+    };
+    if !output.status.success() {
+        return Err(RegistryError::DockerCommandError{ command: format!("{:?}", command), code: output.status.code().unwrap_or(-1), stderr: String::from_utf8_lossy(&output.stderr).to_string() });
+    }
+    Ok(())
+}
+
 
 
 ///
@@ -153,9 +214,13 @@ pub async fn pull(
         let package_dir = packages_dir.join(&name);
         let mut temp_file = tempfile::NamedTempFile::new().expect("Failed to create temporary file.");
 
-        // Create the target endpoint for this package
-        let url = format!("{}/{}/{}", get_packages_endpoint()?, name, version);
-        let mut package_archive: reqwest::Response = match reqwest::get(&url).await {
+        // Create the target endpoint for this package, asking for the image that matches our architecture
+        let arch: Arch = match Arch::host() {
+            Ok(arch) => arch,
+            Err(err) => { return Err(RegistryError::HostArchError{ err }); }
+        };
+        let url = format!("{}/{}/{}?arch={}", get_packages_endpoint()?, name, version, arch);
+        let mut package_archive: reqwest::Response = match client_tls().api().get(&url).send().await {
             Ok(archive) => archive,
             Err(err)    => { return Err(RegistryError::PullRequestError{ url, err }); }
         };
@@ -196,7 +261,7 @@ pub async fn pull(
         progress.finish();
 
         // Retreive package information from API.
-        let client = reqwest::Client::new();
+        let client: Client = client_tls().api().clone();
         let graphql_endpoint = get_graphql_endpoint()?;
         debug!("Fetching package metadata from '{}'...", graphql_endpoint);
 
@@ -313,10 +378,14 @@ pub async fn pull(
 /// 
 /// **Arguments**
 ///  * `packages`: A list with name/ID / version pairs of the packages to push.
+///  * `registry`: If true, pushes the package images to the OCI registry of the instance and only uploads the package metadata to the API.
 /// 
 /// **Returns**  
 /// Nothing on success, or an anyhow error on failure.
-pub async fn push(packages: Vec<(String, Version)>) -> Result<(), RegistryError> {
+pub async fn push(packages: Vec<(String, Version)>, registry: bool) -> Result<(), RegistryError> {
+    // Resolve the image registry if we are asked to use it
+    let image_registry: Option<ImageRegistryInfo> = if registry { Some(get_image_registry().await?) } else { None };
+
     // Try to get the general package directory
     let packages_dir = match ensure_packages_dir(false) {
         Ok(dir)  => dir,
@@ -350,10 +419,28 @@ pub async fn push(packages: Vec<(String, Version)>) -> Result<(), RegistryError>
             Ok(dir)  => dir,
             Err(err) => { return Err(RegistryError::PackageDirError{ name, version, err }); }
         };
+        if package_dir.join(DEV_INFO_FILE).exists() {
+            warn!("Package '{}' (version {}) was built with '--dev'; its image may not contain the latest version of its working directory (rebuild it without '--dev' first)", name, version);
+        }
         // let temp_file = match tempfile::NamedTempFile::new() {
         //     Ok(file) => file,
         //     Err(err) => { return Err(RegistryError::TempFileError{ err }); }
         // };
+        // Push the image to the OCI registry first, if we're using one
+        if let Some(image_registry) = &image_registry {
+            if package_dir.join(ManifestList::FILENAME).exists() {
+                warn!("Package '{}' (version {}) has been built for multiple architectures; only the default image is pushed to the image registry", name, version);
+            }
+
+            let image: String = format!("{}:{}", name, version);
+            let reference: String = format!("{}/{}:{}", image_registry.address.trim_end_matches('/'), name, version);
+            println!("Pushing image {} to {}...", style(&image).bold().cyan(), style(&reference).bold());
+            let image_path: String = package_dir.join("image.tar").display().to_string();
+            run_docker(&[ "load", "--input", &image_path ])?;
+            run_docker(&[ "tag", &image, &reference ])?;
+            run_docker(&[ "push", &reference ])?;
+        }
+
         let temp_path: std::path::PathBuf = std::path::PathBuf::from("/tmp/temp.tar.gz");
         let temp_file: File = File::create(&temp_path).unwrap();
 
@@ -369,10 +456,29 @@ pub async fn push(packages: Vec<(String, Version)>) -> Result<(), RegistryError>
             // return Err(RegistryError::CompressionError{ name, version, path: temp_file.path().into(), err });
             return Err(RegistryError::CompressionError { name, version, path: temp_path, err });
         };
-        if let Err(err) = tar.append_path_with_name(package_dir.join("image.tar"), "image.tar") {
-            // return Err(RegistryError::CompressionError{ name, version, path: temp_file.path().into(), err });
-            return Err(RegistryError::CompressionError { name, version, path: temp_path, err });
-        };
+        if image_registry.is_none() {
+            if let Err(err) = tar.append_path_with_name(package_dir.join("image.tar"), "image.tar") {
+                // return Err(RegistryError::CompressionError{ name, version, path: temp_file.path().into(), err });
+                return Err(RegistryError::CompressionError { name, version, path: temp_path, err });
+            };
+        }
+
+        // Add the images for the other architectures too, if the package has been built for multiple (and we're not using the image registry)
+        let list_path = package_dir.join(ManifestList::FILENAME);
+        if image_registry.is_none() && list_path.exists() {
+            let list: ManifestList = match ManifestList::from_path(&list_path) {
+                Ok(list) => list,
+                Err(err) => { return Err(RegistryError::ManifestListReadError{ name, version, err }); }
+            };
+            if let Err(err) = tar.append_path_with_name(&list_path, ManifestList::FILENAME) {
+                return Err(RegistryError::CompressionError { name, version, path: temp_path, err });
+            };
+            for image in list.images.iter().skip(1) {
+                if let Err(err) = tar.append_path_with_name(package_dir.join(&image.file), &image.file) {
+                    return Err(RegistryError::CompressionError { name, version, path: temp_path, err });
+                };
+            }
+        }
         if let Err(err) = tar.into_inner() {
             // return Err(RegistryError::CompressionError{ name, version, path: temp_file.path().into(), err });
             return Err(RegistryError::CompressionError { name, version, path: temp_path, err });
@@ -382,7 +488,7 @@ pub async fn push(packages: Vec<(String, Version)>) -> Result<(), RegistryError>
         // Upload file (with progress bar, of course)
         let url = get_packages_endpoint()?;
         debug!("Pushing package '{}' to '{}'...", temp_path.display(), url);
-        let request = Client::new().post(&url);
+        let request = client_tls().api().post(&url);
         let progress = ProgressBar::new(0);
         progress.set_style(ProgressStyle::default_bar().template("Uploading...   [{elapsed_precise}]"));
         progress.enable_steady_tick(250);
@@ -431,19 +537,24 @@ pub async fn push(packages: Vec<(String, Version)>) -> Result<(), RegistryError>
 }
 /*******/
 
-///
-///
-///
-pub async fn search(term: Option<String>) -> Result<()> {
+/// Searches the packages of the remote instance, printing them in a table.
+/// 
+/// # Arguments
+/// - `term`: The term to search for. If omitted, lists all packages.
+/// - `output`: The format in which to write the results.
+/// 
+/// # Errors
+/// This function errors if we are not logged-in or if the request to the remote instance failed.
+pub async fn search(term: Option<String>, output: OutputFormat) -> Result<()> {
     #[derive(GraphQLQuery)]
     #[graphql(
         schema_path = "src/graphql/api_schema.json",
         query_path = "src/graphql/search_packages.graphql",
-        response_derives = "Debug"
+        response_derives = "Debug, Serialize"
     )]
     pub struct SearchPackages;
 
-    let client = reqwest::Client::new();
+    let client: Client = client_tls().api().clone();
     let graphql_endpoint = get_graphql_endpoint()?;
 
     // Prepare GraphQL query.
@@ -456,6 +567,10 @@ pub async fn search(term: Option<String>) -> Result<()> {
 
     if let Some(data) = graphql_response.data {
         let packages = data.packages;
+        if output != OutputFormat::Table {
+            print_structured(&packages, output)?;
+            return Ok(());
+        }
 
         // Present results in a table.
         let format = FormatBuilder::new()
@@ -486,6 +601,60 @@ pub async fn search(term: Option<String>) -> Result<()> {
     Ok(())
 }
 
+/// Searches the packages, registered workflows and datasets of the remote instance at once, printing the results grouped by kind.
+/// 
+/// # Arguments
+/// - `term`: The term to search for. If omitted, lists everything.
+/// - `kind`: If given, only shows results of this kind.
+/// - `output`: The format in which to write the results.
+/// 
+/// # Errors
+/// This function errors if we are not logged-in or if the request to the remote instance failed.
+pub async fn search_all(term: Option<String>, kind: Option<SearchKind>, output: OutputFormat) -> Result<()> {
+    let search_endpoint = get_search_endpoint()?;
+
+    // Send the request
+    let mut query: Vec<(&str, String)> = vec![ ("q", term.unwrap_or_default()) ];
+    if let Some(kind) = kind { query.push(("kind", kind.to_string())); }
+    let response = client_tls().api().get(&search_endpoint).query(&query).send().await?;
+    if !response.status().is_success() { bail!("Search request to '{}' failed with status code {}", search_endpoint, response.status()); }
+    let results: SearchResults = response.json().await?;
+    if output != OutputFormat::Table {
+        print_structured(&results, output)?;
+        return Ok(());
+    }
+
+    // Present the results per kind
+    for k in SearchKind::all() {
+        if kind.map(|kind| kind != k).unwrap_or(false) { continue; }
+        let group: Vec<&SearchResult> = results.results.iter().filter(|r| r.kind == k).collect();
+        println!("{} ({})", style(format!("{}S", k.to_string().to_uppercase())).bold(), results.facets.get(&k).copied().unwrap_or(0));
+        if group.is_empty() { println!(); continue; }
+
+        let format = FormatBuilder::new()
+            .column_separator('\0')
+            .borders('\0')
+            .padding(1, 1)
+            .build();
+
+        let mut table = Table::new();
+        table.set_format(format);
+        table.add_row(row!["NAME", "VERSION", "DESCRIPTION"]);
+        for result in group {
+            let name = pad_str(&result.name, 20, Alignment::Left, Some(".."));
+            let version = pad_str(result.version.as_deref().unwrap_or("-"), 10, Alignment::Left, Some(".."));
+            let description = result.description.clone().unwrap_or_default();
+            let description = pad_str(&description, 50, Alignment::Left, Some(".."));
+
+            table.add_row(row![name, version, description]);
+        }
+        table.printstd();
+        println!();
+    }
+
+    Ok(())
+}
+
 ///
 ///
 ///
@@ -502,7 +671,7 @@ pub async fn unpublish(
     )]
     pub struct UnpublishPackage;
 
-    let client = reqwest::Client::new();
+    let client: Client = client_tls().api().clone();
     let graphql_endpoint = get_graphql_endpoint()?;
 
     // Ask for permission, if --force is not provided
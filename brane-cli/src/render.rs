@@ -0,0 +1,459 @@
+//  RENDER.rs
+//    by agent
+//
+//  Created:
+//    16 Oct 2026, 08:27:48
+//  Last edited:
+//    16 Oct 2026, 12:26:18
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Renders the values returned by workflows for the terminal, truncating
+//!   large values so they don't flood it. Also defines the structured
+//!   output formats of the list and inspect commands, and groups the
+//!   text printed by parallel branches of a workflow.
+//
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter, Result as FResult, Write as _};
+use std::io::Write as _;
+use std::str::FromStr;
+
+use console::style;
+use prettytable::format::FormatBuilder;
+use prettytable::{Cell, Row, Table};
+use serde::Serialize;
+
+use brane_exe::FullValue;
+
+use crate::errors::{OutputFormatParseError, RenderError};
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+
+    /// Shorthand for creating an instance with the given properties.
+    fn instance(name: &str, props: &[(&str, FullValue)]) -> FullValue {
+        FullValue::Instance(name.into(), props.iter().map(|(n, v)| (n.to_string(), v.clone())).collect())
+    }
+
+    /// Returns the contents of the cells of the given table, per row.
+    fn cells(table: &Table) -> Vec<Vec<String>> {
+        table.row_iter().map(|row| row.iter().map(|c| c.get_content()).collect()).collect()
+    }
+
+
+    #[test]
+    fn test_render_full() {
+        // Without truncation, values look like their `Display`, except that properties are sorted
+        let value: FullValue = FullValue::Array(vec![
+            FullValue::Integer(1),
+            FullValue::Real(2.5),
+            FullValue::Boolean(true),
+            FullValue::String("hello".into()),
+            FullValue::Null,
+            instance("Point", &[ ("y", FullValue::Integer(2)), ("x", FullValue::Integer(1)) ]),
+            FullValue::Map(HashMap::from([ ("b".into(), FullValue::Integer(2)), ("a".into(), FullValue::Integer(1)) ])),
+            instance("Empty", &[]),
+            FullValue::Map(HashMap::new()),
+            FullValue::Array(vec![]),
+        ]);
+        assert_eq!(render_value(&value, &RenderOptions::full()), "[1, 2.5, true, hello, null, Point { x := 1, y := 2 }, {a: 1, b: 2}, Empty {}, {}, []]");
+    }
+
+    #[test]
+    fn test_render_truncated() {
+        let opts: RenderOptions = RenderOptions { max_depth: Some(2), max_length: Some(2), max_string: Some(3), ..RenderOptions::full() };
+
+        // Long arrays, instances, maps and strings are cut off, saying how much is left
+        let value: FullValue = FullValue::Array((1..=5).map(FullValue::Integer).collect());
+        assert_eq!(render_value(&value, &opts), "[1, 2, ... (3 more)]");
+        let value: FullValue = instance("Point", &[ ("x", FullValue::Integer(1)), ("y", FullValue::Integer(2)), ("z", FullValue::Integer(3)) ]);
+        assert_eq!(render_value(&value, &opts), "Point { x := 1, y := 2, ... (1 more) }");
+        let value: FullValue = FullValue::Map(HashMap::from([ ("c".into(), FullValue::Null), ("a".into(), FullValue::Null), ("b".into(), FullValue::Null) ]));
+        assert_eq!(render_value(&value, &opts), "{a: null, b: null, ... (1 more)}");
+        // Strings are cut by characters, not bytes
+        assert_eq!(render_value(&FullValue::String("héllo".into()), &opts), "hél... (2 more characters)");
+        assert_eq!(render_value(&FullValue::String("héy".into()), &opts), "héy");
+
+        // Nested values are collapsed once they are too deep, but empty ones are always shown in full
+        let value: FullValue = FullValue::Array(vec![ FullValue::Array(vec![
+            FullValue::Array(vec![ FullValue::Integer(1), FullValue::Integer(2) ]),
+            FullValue::Array(vec![]),
+        ]) ]);
+        assert_eq!(render_value(&value, &opts), "[[[<2 elements>], []]]");
+        let value: FullValue = FullValue::Array(vec![ FullValue::Array(vec![
+            instance("Point", &[ ("x", FullValue::Integer(1)) ]),
+            FullValue::Map(HashMap::from([ ("a".into(), FullValue::Null) ])),
+        ]) ]);
+        assert_eq!(render_value(&value, &opts), "[[Point { ... }, {<1 entries>}]]");
+
+        // Nothing shown at all still says how much there is
+        let opts: RenderOptions = RenderOptions { max_length: Some(0), ..RenderOptions::full() };
+        assert_eq!(render_value(&FullValue::Array(vec![ FullValue::Null ]), &opts), "[... (1 more)]");
+    }
+
+    #[test]
+    fn test_render_table() {
+        let opts: RenderOptions = RenderOptions { max_length: Some(2), ..RenderOptions::full() };
+
+        // Every instance is a row, with the union of their properties as columns
+        let value: FullValue = FullValue::Array(vec![
+            instance("Point", &[ ("y", FullValue::Integer(2)), ("x", FullValue::Integer(1)) ]),
+            instance("Point", &[ ("x", FullValue::Integer(3)), ("label", FullValue::Array(vec![ FullValue::String("a".into()) ])) ]),
+            instance("Point", &[ ("x", FullValue::Integer(5)) ]),
+        ]);
+        let table: Table = render_table(&value, &opts).unwrap();
+        assert_eq!(cells(&table), vec![
+            vec![ "LABEL".to_string(), "X".into(), "Y".into() ],
+            vec![ "".into(), "1".into(), "2".into() ],
+            vec![ "[a]".into(), "3".into(), "".into() ],
+            vec![ "... (1 more)".into() ],
+        ]);
+
+        // Anything else is not a table
+        assert!(render_table(&FullValue::Array(vec![]), &opts).is_none());
+        assert!(render_table(&instance("Point", &[]), &opts).is_none());
+        assert!(render_table(&FullValue::Array(vec![ FullValue::Integer(1) ]), &opts).is_none());
+        assert!(render_table(&FullValue::Array(vec![ instance("Point", &[]), instance("Line", &[]) ]), &opts).is_none());
+    }
+
+    #[test]
+    fn test_output_format() {
+        for format in [ OutputFormat::Table, OutputFormat::Json, OutputFormat::Yaml ] {
+            assert_eq!(format.to_string().parse::<OutputFormat>().unwrap(), format);
+        }
+        assert_eq!("JSON".parse::<OutputFormat>().unwrap(), OutputFormat::Json);
+        assert!("xml".parse::<OutputFormat>().is_err());
+    }
+}
+
+
+
+
+
+/***** CONSTANTS *****/
+/// The default number of nested arrays and instances we show before collapsing them.
+pub const DEFAULT_MAX_DEPTH: usize = 3;
+/// The default number of elements (or properties) of an array (or instance) we show.
+pub const DEFAULT_MAX_LENGTH: usize = 16;
+/// The default number of characters of a string we show.
+pub const DEFAULT_MAX_STRING: usize = 120;
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Writes the given value to the given string, truncating it where necessary.
+///
+/// # Arguments
+/// - `buf`: The String to write to.
+/// - `value`: The FullValue to write.
+/// - `opts`: The RenderOptions that determine where we truncate.
+/// - `depth`: The number of arrays and instances we are already nested in.
+fn write_value(buf: &mut String, value: &FullValue, opts: &RenderOptions, depth: usize) {
+    let collapse: bool = opts.max_depth.map(|d| depth >= d).unwrap_or(false);
+    match value {
+        FullValue::Array(values) => {
+            if collapse && !values.is_empty() { write!(buf, "[<{} elements>]", values.len()).unwrap(); return; }
+
+            buf.push('[');
+            let n: usize = opts.max_length.map(|l| l.min(values.len())).unwrap_or(values.len());
+            for (i, v) in values[..n].iter().enumerate() {
+                if i > 0 { buf.push_str(", "); }
+                write_value(buf, v, opts, depth + 1);
+            }
+            if n < values.len() { write!(buf, "{}... ({} more)", if n > 0 { ", " } else { "" }, values.len() - n).unwrap(); }
+            buf.push(']');
+        },
+        FullValue::Instance(name, props) => {
+            if props.is_empty() { write!(buf, "{} {{}}", name).unwrap(); return; }
+            if collapse { write!(buf, "{} {{ ... }}", name).unwrap(); return; }
+
+            // Sort the properties to keep the output stable
+            let mut props: Vec<(&String, &FullValue)> = props.iter().collect();
+            props.sort_by(|(n1, _), (n2, _)| n1.cmp(n2));
+
+            write!(buf, "{} {{ ", name).unwrap();
+            let n: usize = opts.max_length.map(|l| l.min(props.len())).unwrap_or(props.len());
+            for (i, (pname, v)) in props[..n].iter().enumerate() {
+                if i > 0 { buf.push_str(", "); }
+                write!(buf, "{} := ", pname).unwrap();
+                write_value(buf, v, opts, depth + 1);
+            }
+            if n < props.len() { write!(buf, "{}... ({} more)", if n > 0 { ", " } else { "" }, props.len() - n).unwrap(); }
+            buf.push_str(" }");
+        },
+        FullValue::Map(values) => {
+            if values.is_empty() { buf.push_str("{}"); return; }
+            if collapse { write!(buf, "{{<{} entries>}}", values.len()).unwrap(); return; }
+
+            // Sort the keys to keep the output stable
+            let mut values: Vec<(&String, &FullValue)> = values.iter().collect();
+            values.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+
+            buf.push('{');
+            let n: usize = opts.max_length.map(|l| l.min(values.len())).unwrap_or(values.len());
+            for (i, (key, v)) in values[..n].iter().enumerate() {
+                if i > 0 { buf.push_str(", "); }
+                write!(buf, "{}: ", key).unwrap();
+                write_value(buf, v, opts, depth + 1);
+            }
+            if n < values.len() { write!(buf, "{}... ({} more)", if n > 0 { ", " } else { "" }, values.len() - n).unwrap(); }
+            buf.push('}');
+        },
+        FullValue::String(value) => match opts.max_string {
+            Some(max) if value.chars().count() > max => { write!(buf, "{}... ({} more characters)", value.chars().take(max).collect::<String>(), value.chars().count() - max).unwrap(); },
+            _                                        => { buf.push_str(value); },
+        },
+
+        // The rest is small enough to write as-is
+        value => { write!(buf, "{}", value).unwrap(); },
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Determines the format in which list and inspect commands write their output.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OutputFormat {
+    /// Human-readable tables (and other pretty prints).
+    Table,
+    /// Pretty-printed JSON.
+    Json,
+    /// YAML.
+    Yaml,
+}
+
+impl Default for OutputFormat {
+    #[inline]
+    fn default() -> Self { Self::Table }
+}
+
+impl Display for OutputFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use OutputFormat::*;
+        match self {
+            Table => write!(f, "table"),
+            Json  => write!(f, "json"),
+            Yaml  => write!(f, "yaml"),
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = OutputFormatParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "table" => Ok(Self::Table),
+            "json"  => Ok(Self::Json),
+            "yaml"  => Ok(Self::Yaml),
+            _       => Err(OutputFormatParseError{ raw: s.into() }),
+        }
+    }
+}
+
+
+
+/// Determines how much of a value we render, and how we print the text written by a workflow.
+#[derive(Clone, Copy, Debug)]
+pub struct RenderOptions {
+    /// The number of nested arrays and instances we show before collapsing them. `None` means we never collapse.
+    pub max_depth  : Option<usize>,
+    /// The number of elements (or properties) of an array (or instance) we show. `None` means we show all of them.
+    pub max_length : Option<usize>,
+    /// The number of characters of a string we show. `None` means we show all of them.
+    pub max_string : Option<usize>,
+
+    /// Whether to print the text written by every parallel branch contiguously (see `BranchOutput`) instead of as it arrives.
+    pub group_output : bool,
+    /// Whether to print the value returned by a workflow as a single line of JSON (in full) instead of rendering it, so that other programs can parse it.
+    pub json         : bool,
+}
+
+impl RenderOptions {
+    /// Constructor for RenderOptions that never truncates anything.
+    ///
+    /// # Returns
+    /// A new RenderOptions that renders values in full.
+    #[inline]
+    pub fn full() -> Self {
+        Self { max_depth: None, max_length: None, max_string: None, group_output: false, json: false }
+    }
+}
+
+impl Default for RenderOptions {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            max_depth  : Some(DEFAULT_MAX_DEPTH),
+            max_length : Some(DEFAULT_MAX_LENGTH),
+            max_string : Some(DEFAULT_MAX_STRING),
+
+            group_output : false,
+            json         : false,
+        }
+    }
+}
+
+
+
+/// Prints the text written by a workflow, optionally grouping the text of every parallel branch.
+///
+/// If grouping, the text of a branch is buffered until the main thread writes again or `BranchOutput::flush()` is called, after which every branch's text is printed contiguously under a header. Since the main thread waits for its branches to complete, this never reorders its own text.
+#[derive(Clone, Debug, Default)]
+pub struct BranchOutput {
+    /// Whether to buffer the text of parallel branches.
+    group   : bool,
+    /// The text buffered per branch so far, ordered by branch.
+    buffers : BTreeMap<Vec<usize>, String>,
+}
+
+impl BranchOutput {
+    /// Constructor for the BranchOutput.
+    ///
+    /// # Arguments
+    /// - `group`: Whether to group the text of parallel branches. If false, all text is printed as it arrives.
+    ///
+    /// # Returns
+    /// A new BranchOutput with nothing buffered.
+    #[inline]
+    pub fn new(group: bool) -> Self { Self { group, buffers: BTreeMap::new() } }
+
+
+
+    /// Writes text that was printed by a workflow.
+    ///
+    /// # Arguments
+    /// - `branch`: Identifies the thread that printed the text, as the index of its branch in every `parallel`-statement it is nested in (outermost first). Empty for the main thread.
+    /// - `text`: The text to write.
+    pub fn write(&mut self, branch: &[usize], text: &str) {
+        if self.group && !branch.is_empty() {
+            self.buffers.entry(branch.to_vec()).or_default().push_str(text);
+            return;
+        }
+        self.flush();
+        print!("{}", text);
+        std::io::stdout().flush().ok();
+    }
+
+    /// Prints the text buffered for every branch, each under a header that names the branch (numbered from 1, e.g., `branch 2.1` for the first branch nested in the second one).
+    pub fn flush(&mut self) {
+        for (branch, text) in std::mem::take(&mut self.buffers) {
+            let name: String = branch.iter().map(|b| (b + 1).to_string()).collect::<Vec<String>>().join(".");
+            println!("{}", style(format!("--- branch {} ---", name)).bold().dim());
+            if text.ends_with('\n') { print!("{}", text); } else { println!("{}", text); }
+        }
+        std::io::stdout().flush().ok();
+    }
+}
+
+
+
+/// Renders the given value as a string, truncating it according to the given options.
+///
+/// # Arguments
+/// - `value`: The FullValue to render.
+/// - `opts`: The RenderOptions that determine where we truncate.
+///
+/// # Returns
+/// A string representation of the value that is the same as its `Display` if nothing is truncated (except that instance properties are sorted).
+pub fn render_value(value: &FullValue, opts: &RenderOptions) -> String {
+    let mut buf: String = String::new();
+    write_value(&mut buf, value, opts, 0);
+    buf
+}
+
+/// Renders the given value as a table if it is an array of instances of the same class.
+///
+/// Every instance becomes a row and every property a column. The properties themselves are rendered like nested values, and rows beyond the maximum length are omitted.
+///
+/// # Arguments
+/// - `value`: The FullValue to render.
+/// - `opts`: The RenderOptions that determine where we truncate.
+///
+/// # Returns
+/// A Table with the instances, or None if the value is not a (non-empty) array of instances of the same class.
+pub fn render_table(value: &FullValue, opts: &RenderOptions) -> Option<Table> {
+    // Assert it's an array of instances of one class
+    let values: &[FullValue] = match value {
+        FullValue::Array(values) if !values.is_empty() => values,
+        _                                              => { return None; },
+    };
+    let class: &str = match &values[0] {
+        FullValue::Instance(name, _) => name,
+        _                            => { return None; },
+    };
+    if !values.iter().all(|v| matches!(v, FullValue::Instance(name, _) if name == class)) { return None; }
+
+    // Collect the columns, in a stable order
+    let mut columns: Vec<&String> = vec![];
+    for v in values {
+        if let FullValue::Instance(_, props) = v {
+            for name in props.keys() {
+                if !columns.contains(&name) { columns.push(name); }
+            }
+        }
+    }
+    columns.sort();
+
+    // Build the table with them
+    let format = FormatBuilder::new()
+        .column_separator('\0')
+        .borders('\0')
+        .padding(1, 1)
+        .build();
+    let mut table = Table::new();
+    table.set_format(format);
+    table.add_row(Row::new(columns.iter().map(|c| Cell::new(&c.to_uppercase())).collect()));
+    let n: usize = opts.max_length.map(|l| l.min(values.len())).unwrap_or(values.len());
+    for v in &values[..n] {
+        if let FullValue::Instance(_, props) = v {
+            table.add_row(Row::new(columns.iter().map(|c| {
+                let mut buf: String = String::new();
+                if let Some(prop) = props.get(*c) { write_value(&mut buf, prop, opts, 2); }
+                Cell::new(&buf)
+            }).collect()));
+        }
+    }
+    if n < values.len() { table.add_row(Row::new(vec![ Cell::new(&format!("... ({} more)", values.len() - n)) ])); }
+
+    // Done
+    Some(table)
+}
+
+
+
+/// Writes the given value to stdout in the given structured format.
+///
+/// # Arguments
+/// - `value`: The value to write.
+/// - `format`: The OutputFormat to write it in. Since `OutputFormat::Table` is not structured, it is written as JSON as well; callers are expected to print their own table instead.
+///
+/// # Errors
+/// This function errors if we failed to serialize the given value.
+pub fn print_structured(value: &impl Serialize, format: OutputFormat) -> Result<(), RenderError> {
+    let svalue: String = match format {
+        OutputFormat::Table | OutputFormat::Json => match serde_json::to_string_pretty(value) {
+            Ok(svalue) => svalue,
+            Err(err)   => { return Err(RenderError::JsonSerializeError{ err }); },
+        },
+        OutputFormat::Yaml => match serde_yaml::to_string(value) {
+            Ok(svalue) => svalue,
+            Err(err)   => { return Err(RenderError::YamlSerializeError{ err }); },
+        },
+    };
+    println!("{}", svalue.trim_end());
+    Ok(())
+}
@@ -13,42 +13,448 @@
 // 
 
 use std::borrow::Cow::{self, Borrowed, Owned};
-use std::fs;
-use std::path::Path;
-
-use log::warn;
+use std::env;
+use std::fs::{self, File};
+use std::io::{BufRead as _, BufReader, Write as _};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus};
+
+use chrono::{DateTime, Utc};
+use console::style;
+use log::{debug, warn};
+use prettytable::format::FormatBuilder;
+use prettytable::Table;
 use rustyline::completion::{Completer, FilenameCompleter, Pair};
 use rustyline::error::ReadlineError;
-use rustyline::highlight::{Highlighter, MatchingBracketHighlighter};
+use rustyline::highlight::Highlighter;
 use rustyline::hint::{Hinter, HistoryHinter};
-use rustyline::validate::{self, MatchingBracketValidator, Validator};
-use rustyline::{CompletionType, Config, Context, EditMode, Editor};
+use rustyline::validate::{self, Validator};
+use rustyline::{Cmd, CompletionType, Config, Context, EditMode, Editor, KeyCode, KeyEvent, Modifiers};
 use rustyline_derive::Helper;
+use serde::{Deserialize, Serialize};
+use tempfile::NamedTempFile;
 
 use brane_ast::ParserOptions;
 use brane_dsl::Language;
 use brane_exe::FullValue;
+use brane_exe::spec::FrameSummary;
 use brane_tsk::spec::AppId;
 
 pub use crate::errors::ReplError as Error;
+use crate::errors::RunError;
 use crate::utils::{ensure_config_dir, get_history_file};
-use crate::run::{initialize_instance_vm, initialize_offline_vm, process_instance_result, process_offline_result, run_instance_vm, run_offline_vm, InstanceVmState, OfflineVmState};
+use crate::render::{render_value, RenderOptions};
+use crate::run::{initialize_instance_vm, initialize_offline_vm, inspect_instance_vm, process_instance_result, process_offline_result, run_instance_vm, run_offline_vm, InstanceVmState, OfflineVmState, ReconnectOptions};
+use crate::vm::OfflineVm;
+
+
+/***** CONSTANTS *****/
+/// The keywords of BraneScript, which we highlight in the REPL.
+const KEYWORDS: [&str; 16] = [ "break", "class", "continue", "else", "for", "func", "if", "import", "in", "let", "new", "on", "parallel", "return", "unit", "while" ];
+/// The keywords of BraneScript that are literals, which we highlight differently.
+const LITERALS: [&str; 3] = [ "true", "false", "null" ];
+
+
+
+
+
+/***** SYNTAX *****/
+/// Defines the kinds of lexemes that we recognize in (partial) snippets.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum LexemeKind {
+    /// A keyword, like `let` or `func`.
+    Keyword,
+    /// A keyword literal, like `true` or `null`.
+    Literal,
+    /// An integer or real number.
+    Number,
+    /// A string literal (which may be unterminated).
+    String,
+    /// A line or block comment (which may be unterminated).
+    Comment,
+    /// An opening or closing bracket of any kind.
+    Bracket,
+}
+
+/// Defines a single lexeme in a snippet.
+#[derive(Clone, Copy, Debug)]
+struct Lexeme {
+    /// The kind of lexeme.
+    kind  : LexemeKind,
+    /// The byte offset of the start of the lexeme in the snippet.
+    start : usize,
+    /// The byte offset just past the end of the lexeme in the snippet.
+    end   : usize,
+}
+
+/// Defines what we learned by scanning a snippet, which is enough to tell whether it is complete and to highlight it.
+#[derive(Debug, Default)]
+struct Scan {
+    /// The lexemes we care about, in order.
+    lexemes      : Vec<Lexeme>,
+    /// The pairs of matching brackets, as the byte offsets of the opening and closing bracket, respectively.
+    pairs        : Vec<(usize, usize)>,
+    /// The byte offsets of the brackets that have not been closed yet.
+    open         : Vec<usize>,
+    /// The byte offset of the first closing bracket that does not match the bracket it closes, if any.
+    mismatch     : Option<usize>,
+    /// Whether the snippet ends in an unterminated string or block comment.
+    unterminated : bool,
+}
+
+
+
+/// Returns whether the given closing bracket closes the given opening bracket.
+/// 
+/// # Arguments
+/// - `open`: The opening bracket.
+/// - `close`: The closing bracket.
+/// 
+/// # Returns
+/// True if they belong together, or false otherwise.
+#[inline]
+fn closes(open: u8, close: u8) -> bool { matches!((open, close), (b'(', b')') | (b'[', b']') | (b'{', b'}')) }
+
+/// Scans the given snippet just enough to find its keywords, literals, strings, comments and brackets.
+/// 
+/// This is deliberately much more forgiving than the actual scanner in `brane-dsl`, since it has to deal with snippets that are still being typed.
+/// 
+/// # Arguments
+/// - `snippet`: The (partial) snippet to scan.
+/// 
+/// # Returns
+/// A new Scan with what we found.
+fn scan(snippet: &str) -> Scan {
+    let bytes: &[u8] = snippet.as_bytes();
+
+    let mut scan: Scan = Scan::default();
+    let mut i: usize = 0;
+    while i < bytes.len() {
+        let start: usize = i;
+        match bytes[i] {
+            // Comments
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                i = snippet[i..].find('\n').map(|n| i + n).unwrap_or(bytes.len());
+                scan.lexemes.push(Lexeme{ kind: LexemeKind::Comment, start, end: i });
+            },
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                match snippet[i + 2..].find("*/") {
+                    Some(n) => { i += 2 + n + 2; },
+                    None    => { i = bytes.len(); scan.unterminated = true; },
+                }
+                scan.lexemes.push(Lexeme{ kind: LexemeKind::Comment, start, end: i });
+            },
+
+            // Strings
+            b'"' => {
+                i += 1;
+                scan.unterminated = true;
+                while i < bytes.len() {
+                    match bytes[i] {
+                        b'\\' => { i += 2; },
+                        b'"'  => { i += 1; scan.unterminated = false; break; },
+                        _     => { i += 1; },
+                    }
+                }
+                i = i.min(bytes.len());
+                scan.lexemes.push(Lexeme{ kind: LexemeKind::String, start, end: i });
+            },
+
+            // Numbers, keywords and identifiers
+            b'0'..=b'9' => {
+                while i < bytes.len() && (bytes[i].is_ascii_digit() || bytes[i] == b'.') { i += 1; }
+                scan.lexemes.push(Lexeme{ kind: LexemeKind::Number, start, end: i });
+            },
+            b if b.is_ascii_alphabetic() || b == b'_' => {
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') { i += 1; }
+                let word: &str = &snippet[start..i];
+                if KEYWORDS.contains(&word) {
+                    scan.lexemes.push(Lexeme{ kind: LexemeKind::Keyword, start, end: i });
+                } else if LITERALS.contains(&word) {
+                    scan.lexemes.push(Lexeme{ kind: LexemeKind::Literal, start, end: i });
+                }
+            },
+
+            // Brackets
+            b'(' | b'[' | b'{' => {
+                i += 1;
+                scan.open.push(start);
+                scan.lexemes.push(Lexeme{ kind: LexemeKind::Bracket, start, end: i });
+            },
+            b')' | b']' | b'}' => {
+                i += 1;
+                match scan.open.pop() {
+                    Some(open) if closes(bytes[open], bytes[start]) => { scan.pairs.push((open, start)); },
+                    _                                               => { if scan.mismatch.is_none() { scan.mismatch = Some(start); } },
+                }
+                scan.lexemes.push(Lexeme{ kind: LexemeKind::Bracket, start, end: i });
+            },
+
+            // Anything else is not interesting
+            _ => { i += 1; },
+        }
+    }
+
+    // Done
+    scan
+}
+
+/// Highlights the given snippet as BraneScript.
+/// 
+/// # Arguments
+/// - `snippet`: The (partial) snippet to highlight.
+/// - `pos`: The byte offset of the cursor in the snippet. If it is on (or just after) a bracket, the matching bracket is highlighted as well.
+/// 
+/// # Returns
+/// The snippet with ANSI colour codes added to it.
+fn highlight_branescript(snippet: &str, pos: usize) -> String {
+    let scan: Scan = scan(snippet);
+
+    // Find the bracket matching the one at the cursor, if any
+    let partner: Option<usize> = [ Some(pos), pos.checked_sub(1) ].iter().flatten().copied().find_map(|cursor| scan.pairs.iter().find_map(|(open, close)| {
+        if *open == cursor { Some(*close) } else if *close == cursor { Some(*open) } else { None }
+    }));
+
+    // Wrap the lexemes in their colours
+    let mut res: String = String::with_capacity(2 * snippet.len());
+    let mut last: usize = 0;
+    for lexeme in &scan.lexemes {
+        let colour: Option<&str> = match lexeme.kind {
+            LexemeKind::Keyword => Some("\x1b[1;35m"),
+            LexemeKind::Literal => Some("\x1b[36m"),
+            LexemeKind::Number  => Some("\x1b[33m"),
+            LexemeKind::String  => Some("\x1b[32m"),
+            LexemeKind::Comment => Some("\x1b[2m"),
+            LexemeKind::Bracket => if partner == Some(lexeme.start) { Some("\x1b[1;34m") } else { None },
+        };
+        if let Some(colour) = colour {
+            res.push_str(&snippet[last..lexeme.start]);
+            res.push_str(colour);
+            res.push_str(&snippet[lexeme.start..lexeme.end]);
+            res.push_str("\x1b[0m");
+            last = lexeme.end;
+        }
+    }
+    res.push_str(&snippet[last..]);
+    res
+}
+
+
+
+
+
+/***** RECORDING *****/
+/// Defines a single snippet of a recorded REPL session, which is stored as one line of the record file.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RecordEntry {
+    /// The number of the prompt at which the snippet was given.
+    pub index     : u32,
+    /// When the snippet was run.
+    pub timestamp : DateTime<Utc>,
+    /// The snippet itself.
+    pub snippet   : String,
+    /// The hash of the workflow the snippet compiled to, or `None` if it did not compile.
+    pub workflow  : Option<String>,
+    /// The value returned by the workflow, or `None` if it failed.
+    pub output    : Option<FullValue>,
+    /// Why the snippet failed, if it did.
+    pub error     : Option<String>,
+}
+
+/// Records the snippets of a REPL session to a JSONL file, such that the session may be replayed later.
+struct Recorder {
+    /// The path of the record file.
+    path   : PathBuf,
+    /// The handle to the record file.
+    handle : File,
+}
+
+impl Recorder {
+    /// Constructor for the Recorder that creates (or truncates) the given record file.
+    /// 
+    /// # Arguments
+    /// - `path`: The path of the record file.
+    /// 
+    /// # Returns
+    /// A new Recorder that writes to the given file.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to create the file.
+    fn new(path: PathBuf) -> Result<Self, Error> {
+        match File::create(&path) {
+            Ok(handle) => Ok(Self{ path, handle }),
+            Err(err)   => Err(Error::RecordCreateError{ path, err }),
+        }
+    }
+
+    /// Appends the given entry to the record file.
+    /// 
+    /// Failing to do so is not fatal to the session, so we only warn about it.
+    /// 
+    /// # Arguments
+    /// - `entry`: The RecordEntry to write.
+    fn record(&mut self, entry: &RecordEntry) {
+        let line: String = match serde_json::to_string(entry) {
+            Ok(line) => line,
+            Err(err) => { warn!("{}", Error::RecordSerializeError{ err }); return; },
+        };
+        if let Err(err) = writeln!(self.handle, "{}", line).and_then(|_| self.handle.flush()) {
+            warn!("{}", Error::RecordWriteError{ path: self.path.clone(), err });
+        }
+    }
+}
+
+
+
+/// Loads the entries of a recorded REPL session.
+/// 
+/// # Arguments
+/// - `path`: The path of the record file, as written with `--record`.
+/// 
+/// # Returns
+/// The RecordEntries in the file, in the order they were recorded.
+/// 
+/// # Errors
+/// This function errors if we failed to read the file or any of its lines was not a valid entry.
+fn load_record(path: &Path) -> Result<Vec<RecordEntry>, Error> {
+    let handle: File = match File::open(path) {
+        Ok(handle) => handle,
+        Err(err)   => { return Err(Error::ReplayOpenError{ path: path.into(), err }); },
+    };
+
+    let mut entries: Vec<RecordEntry> = vec![];
+    for (i, line) in BufReader::new(handle).lines().enumerate() {
+        let line: String = match line {
+            Ok(line) => line,
+            Err(err) => { return Err(Error::ReplayReadError{ path: path.into(), err }); },
+        };
+        if line.trim().is_empty() { continue; }
+        match serde_json::from_str(&line) {
+            Ok(entry) => { entries.push(entry); },
+            Err(err)  => { return Err(Error::ReplayParseError{ path: path.into(), line: i + 1, err }); },
+        }
+    }
+    Ok(entries)
+}
+
+/// Warns if a replayed snippet behaved differently than when it was recorded.
+/// 
+/// # Arguments
+/// - `recorded`: The RecordEntry as it was recorded.
+/// - `replayed`: The RecordEntry of the replayed snippet.
+fn compare_replay(recorded: &RecordEntry, replayed: &RecordEntry) {
+    if recorded.workflow != replayed.workflow {
+        warn!("Snippet {} compiled to a different workflow than when it was recorded", recorded.index);
+    }
+    match (&recorded.error, &replayed.error) {
+        (None, Some(_))    => { warn!("Snippet {} failed, but succeeded when it was recorded", recorded.index); },
+        (Some(_), None)    => { warn!("Snippet {} succeeded, but failed when it was recorded", recorded.index); },
+        (Some(_), Some(_)) => {},
+        (None, None)       => {
+            if recorded.output != replayed.output { warn!("Snippet {} returned a different value than when it was recorded", recorded.index); }
+        },
+    }
+}
+
+
+
 
 
 /***** HELPER FUNCTIONS *****/
+/// Defines what to do after a line has been checked for REPL magicks.
+#[derive(Clone, Debug)]
+enum Magick {
+    /// The REPL should stop.
+    Quit,
+    /// The magick handled the line, so the REPL should continue with the next one.
+    Handled,
+    /// The magick produced a snippet that should be run instead of the line.
+    Run(String),
+}
+
+/// Defines where the REPL reads its snippets from.
+enum Source<'a> {
+    /// The user types them in the terminal.
+    Terminal(&'a mut Editor<ReplHelper>),
+    /// They are replayed from a recorded session, after which the REPL stops.
+    Replay(std::vec::IntoIter<RecordEntry>),
+}
+
+
+
+/// Lets the user write a snippet in their own editor.
+/// 
+/// The editor is taken from `$VISUAL` or `$EDITOR` (in that order), falling back to `vi` if neither is set.
+/// 
+/// # Arguments
+/// - `language`: The language of the snippet, which determines the extension of the file we open.
+/// 
+/// # Returns
+/// The snippet written by the user, or `None` if they left the file empty.
+/// 
+/// # Errors
+/// This function errors if we failed to create or read the temporary file, or if the editor failed.
+fn edit_snippet(language: Language) -> Result<Option<String>, Error> {
+    // Create the file to edit
+    let file: NamedTempFile = match tempfile::Builder::new().prefix("brane-repl-").suffix(match language { Language::BraneScript => ".bs", Language::Bakery => ".bakery" }).tempfile() {
+        Ok(file) => file,
+        Err(err) => { return Err(Error::EditFileCreateError{ err }); },
+    };
+
+    // Open it in the editor
+    let editor: String = env::var("VISUAL").or_else(|_| env::var("EDITOR")).ok().filter(|editor| !editor.trim().is_empty()).unwrap_or_else(|| "vi".into());
+    let mut args = editor.split_whitespace();
+    let mut cmd: Command = Command::new(args.next().unwrap_or("vi"));
+    cmd.args(args);
+    cmd.arg(file.path());
+    debug!("Opening snippet in editor: {:?}", cmd);
+    let status: ExitStatus = match cmd.status() {
+        Ok(status) => status,
+        Err(err)   => { return Err(Error::EditorLaunchError{ editor, err }); },
+    };
+    if !status.success() { return Err(Error::EditorFailure{ editor, status }); }
+
+    // Read back what they wrote
+    let snippet: String = match fs::read_to_string(file.path()) {
+        Ok(snippet) => snippet,
+        Err(err)    => { return Err(Error::EditFileReadError{ path: file.path().into(), err }); },
+    };
+    let snippet: &str = snippet.trim_end();
+    if snippet.trim_start().is_empty() { return Ok(None); }
+    Ok(Some(snippet.into()))
+}
+
 /// Handles magicks in the REPL.
 /// 
 /// # Arguments
 /// - `line`: The line given by the user.
+/// - `language`: The language we're REPLing, which determines how snippets written with `:edit` are shown.
 /// 
 /// # Returns
-/// If a magics was triggered, returns if that trigger should break the REPL (i.e., returns `Some(true)` if so or `Some(false)` if the REPL can continue but not with this line). If the line was not a REPL magick, then `None` is returned.
-fn repl_magicks(line: impl AsRef<str>) -> Option<bool> {
+/// If a magick was triggered, returns what the REPL should do next. If the line was not a REPL magick, then `None` is returned.
+fn repl_magicks(line: impl AsRef<str>, language: Language) -> Option<Magick> {
     let line: &str = line.as_ref();
 
     // Switch on the command given
     if line == "exit" || line == "quit" || line == "q" {
-        Some(true)
+        Some(Magick::Quit)
+
+    } else if line.trim() == ":edit" {
+        match edit_snippet(language) {
+            Ok(Some(snippet)) => {
+                // Show what we're about to run
+                match language {
+                    Language::BraneScript => println!("{}", highlight_branescript(&snippet, usize::MAX)),
+                    Language::Bakery      => println!("{}", snippet),
+                }
+                Some(Magick::Run(snippet))
+            },
+            Ok(None) => Some(Magick::Handled),
+            Err(err) => {
+                error!("{}", err);
+                Some(Magick::Handled)
+            },
+        }
 
     } else if line == "help" {
         println!("You found the secret REPL-commands!");
@@ -57,16 +463,109 @@ fn repl_magicks(line: impl AsRef<str>) -> Option<bool> {
         println!("Supported commands:");
         println!("  `exit`, `quit` or `q`   Exits the REPL. The same can be achieved by hitting `Ctrl+C` or `Ctrl+D`.");
         println!("  `help`                  Prints this overview.");
+        println!("  `:edit`                 Opens a new snippet in `$VISUAL` or `$EDITOR` (`vi` by default), which is run as soon as you save it and close the editor.");
         println!();
         println!("Any other statement that is not one of the commands above is interpreted as the language you're REPLing.");
+        println!("Statements with unclosed brackets, strings or comments continue on the next line when you hit `Enter`.");
+        println!("Use `Ctrl+R` to search the history, or `PageUp` and `PageDown` to go through the history entries that start with what you typed so far.");
         println!();
-        Some(false)
+        Some(Magick::Handled)
 
     } else {
         None
     }
 }
 
+/// Reads the next snippet to run.
+/// 
+/// # Arguments
+/// - `source`: The Source to read the snippet from.
+/// - `count`: The number of the current prompt.
+/// - `language`: The language we're REPLing.
+/// 
+/// # Returns
+/// What the REPL should do next, together with the recorded entry of the snippet if it is replayed.
+fn read_snippet(source: &mut Source, count: u32, language: Language) -> (Magick, Option<RecordEntry>) {
+    match source {
+        Source::Terminal(rl) => {
+            // Prepare the prompt with the current iteration number
+            let p = format!("{}> ", count);
+
+            // Write the prompt in a coloured way
+            rl.helper_mut().expect("No helper").colored_prompt = format!("\x1b[1;32m{}\x1b[0m", p);
+
+            // Find a line to read
+            match rl.readline(&p) {
+                Ok(line) => {
+                    // The command checked out, so add it to the history
+                    rl.add_history_entry(&line);
+
+                    // Fetch REPL magicks
+                    match repl_magicks(&line, language) {
+                        Some(Magick::Run(snippet)) => { rl.add_history_entry(&snippet); (Magick::Run(snippet), None) },
+                        Some(magick)               => (magick, None),
+                        None                       => (Magick::Run(line), None),
+                    }
+                },
+                Err(ReadlineError::Interrupted) => {
+                    println!("Keyboard interrupt received, exiting...");
+                    (Magick::Quit, None)
+                }
+                Err(ReadlineError::Eof) => (Magick::Quit, None),
+                Err(err) => {
+                    error!("Failed to get new line: {}", err);
+                    (Magick::Quit, None)
+                },
+            }
+        },
+
+        Source::Replay(entries) => match entries.next() {
+            Some(entry) => {
+                // Show the snippet as if it was typed
+                match language {
+                    Language::BraneScript => println!("{}{}", style(format!("{}> ", count)).bold().green(), highlight_branescript(&entry.snippet, usize::MAX)),
+                    Language::Bakery      => println!("{}{}", style(format!("{}> ", count)).bold().green(), entry.snippet),
+                }
+                (Magick::Run(entry.snippet.clone()), Some(entry))
+            },
+            None => (Magick::Quit, None),
+        },
+    }
+}
+
+/// Prints the variables defined in an (attached) session.
+/// 
+/// # Arguments
+/// - `session`: The ID of the session that the frames belong to.
+/// - `frames`: The FrameSummaries of the session, as returned by the remote driver.
+/// - `render`: Determines how much of the values we print.
+fn print_frames(session: &AppId, frames: &[FrameSummary], render: &RenderOptions) {
+    let n_vars: usize = frames.iter().map(|f| f.vars.len()).sum();
+    if n_vars == 0 {
+        println!("Attached to session {}, which defines no variables yet.\n", style(session).bold().cyan());
+        return;
+    }
+
+    // Show them as a table
+    println!("Attached to session {}, which defines:", style(session).bold().cyan());
+    let format = FormatBuilder::new()
+        .column_separator('\0')
+        .borders('\0')
+        .padding(1, 1)
+        .build();
+    let mut table = Table::new();
+    table.set_format(format);
+    table.add_row(row!["FRAME", "NAME", "TYPE", "VALUE"]);
+    for frame in frames {
+        let fname: &str = frame.function.as_deref().unwrap_or("<main>");
+        for var in &frame.vars {
+            table.add_row(row![fname, var.name, var.data_type, render_value(&var.value, render)]);
+        }
+    }
+    table.printstd();
+    println!();
+}
+
 
 
 
@@ -75,12 +574,10 @@ fn repl_magicks(line: impl AsRef<str>) -> Option<bool> {
 /// Implements the helper for the Repl (auto-completion and syntax highlighting and such)
 #[derive(Helper)]
 struct ReplHelper {
+    /// The language we're REPLing. We only highlight BraneScript, but validate brackets, strings and comments for either language.
+    language       : Language,
     /// The completer: we auto-complete filenames, like the standard terminal
     completer      : FilenameCompleter,
-    /// Highlighter: we highlight matching brackets
-    highlighter    : MatchingBracketHighlighter,
-    /// We even validate for matching brackets
-    validator      : MatchingBracketValidator,
     /// We hint based on the user's history
     hinter         : HistoryHinter,
     /// Does something with being a coloured prompt(?)
@@ -140,15 +637,19 @@ impl Highlighter for ReplHelper {
         line: &'l str,
         pos: usize,
     ) -> Cow<'l, str> {
-        self.highlighter.highlight(line, pos)
+        match self.language {
+            Language::BraneScript => Owned(highlight_branescript(line, pos)),
+            Language::Bakery      => Borrowed(line),
+        }
     }
 
     fn highlight_char(
         &self,
-        line: &str,
-        pos: usize,
+        _line: &str,
+        _pos: usize,
     ) -> bool {
-        self.highlighter.highlight_char(line, pos)
+        // Any character may change the highlighting (e.g., by opening a string)
+        self.language == Language::BraneScript
     }
 }
 
@@ -157,11 +658,20 @@ impl Validator for ReplHelper {
         &self,
         ctx: &mut validate::ValidationContext,
     ) -> rustyline::Result<validate::ValidationResult> {
-        self.validator.validate(ctx)
+        // Statements continue on the next line for as long as something is left open
+        let input: &str = ctx.input();
+        let scan: Scan = scan(input);
+        if let Some(pos) = scan.mismatch {
+            Ok(validate::ValidationResult::Invalid(Some(format!(" - Mismatched bracket '{}'", &input[pos..pos + 1]))))
+        } else if scan.unterminated || !scan.open.is_empty() {
+            Ok(validate::ValidationResult::Incomplete)
+        } else {
+            Ok(validate::ValidationResult::Valid(None))
+        }
     }
 
     fn validate_while_typing(&self) -> bool {
-        self.validator.validate_while_typing()
+        false
     }
 }
 
@@ -179,24 +689,29 @@ impl Validator for ReplHelper {
 /// - `attach`: If not None, defines the session ID of an existing session to connect to.
 /// - `language`: The language with which to compile the file.
 /// - `clear`: Whether or not to clear the history of the REPL before beginning.
+/// - `record`: If given, records every snippet, the hash of the workflow it compiled to and its result or error to this JSONL file.
+/// - `replay`: If given, runs the snippets in this file (as written with `record`) instead of reading them from the terminal, warning if any of them behaves differently than when it was recorded.
+/// - `render`: Determines how much of the returned values we print.
+/// - `reconnect`: Determines how we deal with a dropped connection to the remote driver. Irrelevant if not running remotely.
 /// 
 /// # Errors
 /// This function errors if we could not properly read from/write to the terminal. Additionally, it may error if any of the given statements fails for whatever reason.
-pub async fn start(certs_dir: impl AsRef<Path>, proxy_addr: Option<String>, remote: Option<String>, attach: Option<AppId>, language: Language, clear: bool) -> Result<(), Error> {
+#[allow(clippy::too_many_arguments)]
+pub async fn start(certs_dir: impl AsRef<Path>, proxy_addr: Option<String>, remote: Option<String>, attach: Option<AppId>, language: Language, clear: bool, record: Option<PathBuf>, replay: Option<PathBuf>, render: RenderOptions, reconnect: ReconnectOptions) -> Result<(), Error> {
     // Build the config for the rustyline REPL.
     let config = Config::builder()
         .history_ignore_space(true)
+        .history_ignore_dups(true)
         .completion_type(CompletionType::Circular)
         .edit_mode(EditMode::Emacs)
         .build();
 
     // Build the helper for the REPL
     let repl_helper = ReplHelper {
+        language,
         completer: FilenameCompleter::new(),
-        highlighter: MatchingBracketHighlighter::new(),
         hinter: HistoryHinter {},
         colored_prompt: "".to_owned(),
-        validator: MatchingBracketValidator::new(),
     };
 
     // Get the history file, clearing it if necessary
@@ -217,17 +732,29 @@ pub async fn start(certs_dir: impl AsRef<Path>, proxy_addr: Option<String>, remo
         Err(err) => { return Err(Error::EditorCreateError{ err }); },
     };
     rl.set_helper(Some(repl_helper));
+    rl.bind_sequence(KeyEvent(KeyCode::PageUp, Modifiers::NONE), Cmd::HistorySearchBackward);
+    rl.bind_sequence(KeyEvent(KeyCode::PageDown, Modifiers::NONE), Cmd::HistorySearchForward);
     if let Err(err) = rl.load_history(&history_file) { warn!("Could not load REPL history from '{}': {}", history_file.display(), err); }
 
     // Prepare the parser options
     let options: ParserOptions = ParserOptions::new(language);
 
+    // Prepare where snippets come from and where they go
+    let source: Source = match replay {
+        Some(path) => Source::Replay(load_record(&path)?.into_iter()),
+        None       => Source::Terminal(&mut rl),
+    };
+    let recorder: Option<Recorder> = match record {
+        Some(path) => Some(Recorder::new(path)?),
+        None       => None,
+    };
+
     // Initialization done; run the REPL
     println!("Welcome to the Brane REPL, press Ctrl+D to exit.\n");
     if let Some(remote) = remote {
-        remote_repl(&mut rl, certs_dir, proxy_addr, remote, attach, options).await?;
+        remote_repl(source, recorder, certs_dir, proxy_addr, remote, attach, options, &render, reconnect).await?;
     } else {
-        local_repl(&mut rl, options).await?;
+        local_repl(source, recorder, options, &render).await?;
     }
 
     // Try to save the history if we exited cleanly
@@ -244,71 +771,72 @@ pub async fn start(certs_dir: impl AsRef<Path>, proxy_addr: Option<String>, remo
 /// Runs the given file on the remote instance.
 /// 
 /// # Arguments
-/// - `rl`: The REPL interface we use to do the R-part of a REPL.
+/// - `source`: The Source we read snippets from to do the R-part of a REPL.
+/// - `recorder`: If given, records every snippet (and what it did) to a file.
 /// - `certs_dir`: The directory with certificates proving our identity.
 /// - `proxy_addr`: The address to proxy any data transfers through if they occur.
 /// - `endpoint`: The `brane-drv` endpoint to connect to.
 /// - `attach`: If given, uses the given ID to attach to an existing session instead of creating a new one.
 /// - `options`: The ParseOptions that specify how to parse the incoming source.
+/// - `render`: Determines how much of the returned values we print.
+/// - `reconnect`: Determines how we deal with a dropped connection to the driver.
 /// 
 /// # Returns
 /// Nothing, but does print results and such to stdout. Might also produce new datasets.
-async fn remote_repl(rl: &mut Editor<ReplHelper>, certs_dir: impl AsRef<Path>, proxy_addr: Option<String>, endpoint: impl AsRef<str>, attach: Option<AppId>, options: ParserOptions) -> Result<(), Error> {
+#[allow(clippy::too_many_arguments)]
+async fn remote_repl(mut source: Source<'_>, mut recorder: Option<Recorder>, certs_dir: impl AsRef<Path>, proxy_addr: Option<String>, endpoint: impl AsRef<str>, attach: Option<AppId>, options: ParserOptions, render: &RenderOptions, reconnect: ReconnectOptions) -> Result<(), Error> {
     let certs_dir : &Path = certs_dir.as_ref();
     let endpoint  : &str  = endpoint.as_ref();
 
     // First we initialize the remote thing
+    let language: Language = options.lang;
+    let attached: bool = attach.is_some();
     let mut state: InstanceVmState = match initialize_instance_vm(endpoint, attach, options).await {
         Ok(state) => state,
         Err(err)  => { return Err(Error::InitializeError{ what: "remote instance client", err }); },
     };
+    state.reconnect    = reconnect;
+    state.group_output = render.group_output;
+
+    // If we're continuing someone else's session, show what they left us
+    if attached {
+        match inspect_instance_vm(endpoint, &mut state).await {
+            Ok(frames) => print_frames(&state.session, &frames, render),
+            Err(err)   => { warn!("Could not inspect session '{}': {}", state.session, err); },
+        }
+    }
 
     // Next, enter the L in REPL
     let mut count: u32 = 1;
     loop {
-        // Prepare the prompt with the current iteration number
-        let p = format!("{}> ", count);
-
-        // Write the prompt in a coloured way
-        rl.helper_mut().expect("No helper").colored_prompt = format!("\x1b[1;32m{}\x1b[0m", p);
-
-        // Find a line to read
-        match rl.readline(&p) {
-            Ok(line) => {
-                // The command checked out, so add it to the history
-                rl.add_history_entry(&line.replace('\n', " "));
-
-                // Fetch REPL magicks
-                if let Some(quit) = repl_magicks(&line) { if quit { break; } else { continue; } }
-
-                // Next, we run the VM (one snippet only ayway)
-                let res: FullValue = match run_instance_vm(endpoint, &mut state, "<stdin>", &line).await {
-                    Ok(res) => res,
-                    Err(_)  => { continue; },
-                };
-
-                // Then, we collect and process the result
-                if let Err(err) = process_instance_result(certs_dir, &proxy_addr, res).await {
-                    error!("{}", Error::ProcessError { what: "remote instance VM", err });
-                    continue;
-                }
+        // Read the next snippet
+        let (line, recorded): (String, Option<RecordEntry>) = match read_snippet(&mut source, count, language) {
+            (Magick::Quit, _)             => { break; },
+            (Magick::Handled, _)          => { continue; },
+            (Magick::Run(line), recorded) => (line, recorded),
+        };
 
-                // Go to the next iteration
-                count += 1;
-                state.state.offset += 1 + line.chars().filter(|c| *c == '\n').count();
-            },
-            Err(ReadlineError::Interrupted) => {
-                println!("Keyboard interrupt received, exiting...");
-                break;
-            }
-            Err(ReadlineError::Eof) => {
-                break;
-            },
-            Err(err) => {
-                error!("Failed to get new line: {}", err);
-                break;
-            },
+        // Next, we run the VM (one snippet only ayway)
+        let res: Result<FullValue, RunError> = run_instance_vm(endpoint, &mut state, "<stdin>", &line).await;
+
+        // Record what happened, and compare it to what happened before if we're replaying
+        let entry: RecordEntry = RecordEntry{ index: count, timestamp: Utc::now(), snippet: line.clone(), workflow: state.hash.clone(), output: res.as_ref().ok().cloned(), error: res.as_ref().err().map(|err| err.to_string()) };
+        if let Some(recorder) = &mut recorder { recorder.record(&entry); }
+        if let Some(recorded) = &recorded { compare_replay(recorded, &entry); }
+        let res: FullValue = match res {
+            Ok(res) => res,
+            Err(_)  => { continue; },
+        };
+
+        // Then, we collect and process the result
+        if let Err(err) = process_instance_result(certs_dir, &proxy_addr, res, render).await {
+            error!("{}", Error::ProcessError { what: "remote instance VM", err });
+            continue;
         }
+
+        // Go to the next iteration
+        count += 1;
+        state.state.offset += 1 + line.chars().filter(|c| *c == '\n').count();
     }
 
     // Done
@@ -320,64 +848,53 @@ async fn remote_repl(rl: &mut Editor<ReplHelper>, certs_dir: impl AsRef<Path>, p
 /// Runs the given file on the local machine.
 /// 
 /// # Arguments
-/// - `rl`: The REPL interface we use to do the R-part of a REPL.
+/// - `source`: The Source we read snippets from to do the R-part of a REPL.
+/// - `recorder`: If given, records every snippet (and what it did) to a file.
 /// - `options`: The ParseOptions that specify how to parse the incoming source.
+/// - `render`: Determines how much of the returned values we print.
 /// 
 /// # Returns
 /// Nothing, but does print results and such to stdout. Might also produce new datasets.
-async fn local_repl(rl: &mut Editor<ReplHelper>, options: ParserOptions) -> Result<(), Error> {
+async fn local_repl(mut source: Source<'_>, mut recorder: Option<Recorder>, options: ParserOptions, render: &RenderOptions) -> Result<(), Error> {
     // First we initialize the remote thing
+    let language: Language = options.lang;
     let mut state: OfflineVmState = match initialize_offline_vm(options) {
         Ok(state) => state,
         Err(err)  => { return Err(Error::InitializeError{ what: "offline VM", err }); },
     };
+    if render.group_output { state.vm = state.vm.take().map(OfflineVm::with_grouped_output); }
 
     // With the VM setup, enter the L in the REPL
     let mut count: u32 = 1;
     loop {
-        // Prepare the prompt with the current iteration number
-        let p = format!("{}> ", count);
-
-        // Write the prompt in a coloured way
-        rl.helper_mut().expect("No helper").colored_prompt = format!("\x1b[1;32m{}\x1b[0m", p);
-
-        // Find a line to read
-        match rl.readline(&p) {
-            Ok(line) => {
-                // The command checked out, so add it to the history
-                rl.add_history_entry(&line.replace('\n', " "));
-
-                // Fetch REPL magicks
-                if let Some(quit) = repl_magicks(&line) { if quit { break; } else { continue; } }
-
-                // Next, we run the VM (one snippet only ayway)
-                let res: FullValue = match run_offline_vm(&mut state, "<stdin>", &line).await {
-                    Ok(res)  => res,
-                    Err(err) => { return Err(Error::RunError{ what: "offline VM", err }); },
-                };
-
-                // Then, we collect and process the result
-                if let Err(err) = process_offline_result(res) {
-                    error!("{}", Error::ProcessError { what: "offline VM", err });
-                    continue;
-                }
+        // Read the next snippet
+        let (line, recorded): (String, Option<RecordEntry>) = match read_snippet(&mut source, count, language) {
+            (Magick::Quit, _)             => { break; },
+            (Magick::Handled, _)          => { continue; },
+            (Magick::Run(line), recorded) => (line, recorded),
+        };
 
-                // Go to the next iteration
-                count += 1;
-                state.state.offset += 1 + line.chars().filter(|c| *c == '\n').count();
-            },
-            Err(ReadlineError::Interrupted) => {
-                println!("Keyboard interrupt received, exiting...");
-                break;
-            }
-            Err(ReadlineError::Eof) => {
-                break;
-            },
-            Err(err) => {
-                error!("Failed to get new line: {}", err);
-                break;
-            },
+        // Next, we run the VM (one snippet only ayway)
+        let res: Result<FullValue, RunError> = run_offline_vm(&mut state, "<stdin>", &line).await;
+
+        // Record what happened, and compare it to what happened before if we're replaying
+        let entry: RecordEntry = RecordEntry{ index: count, timestamp: Utc::now(), snippet: line.clone(), workflow: state.hash.clone(), output: res.as_ref().ok().cloned(), error: res.as_ref().err().map(|err| err.to_string()) };
+        if let Some(recorder) = &mut recorder { recorder.record(&entry); }
+        if let Some(recorded) = &recorded { compare_replay(recorded, &entry); }
+        let res: FullValue = match res {
+            Ok(res)  => res,
+            Err(err) => { return Err(Error::RunError{ what: "offline VM", err }); },
+        };
+
+        // Then, we collect and process the result
+        if let Err(err) = process_offline_result(res, render) {
+            error!("{}", Error::ProcessError { what: "offline VM", err });
+            continue;
         }
+
+        // Go to the next iteration
+        count += 1;
+        state.state.offset += 1 + line.chars().filter(|c| *c == '\n').count();
     }
 
     // Done
@@ -13,32 +13,49 @@
 // 
 
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::io::Read;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
 use console::style;
+use indicatif::{DecimalBytes, ProgressBar, ProgressStyle};
+use prettytable::format::FormatBuilder;
+use prettytable::Table;
+use sha2::{Digest as _, Sha256};
 use tempfile::{tempdir, TempDir};
+use tokio::time::{sleep, timeout};
+use tonic::{Code, Status, Streaming};
 use tonic::transport::Channel;
 
-use brane_ast::{compile_snippet, CompileResult, ParserOptions, Workflow};
+use brane_ast::{compile_snippet, CompileResult, ParserOptions, RetryPolicy, Workflow};
 use brane_ast::state::CompileState;
 // use brane_cfg::certs::{load_cert, load_keypair};
 use brane_dsl::Language;
 use brane_exe::FullValue;
+use brane_exe::plugins::{PluginEntry, PluginRegistry};
+use brane_exe::spec::FrameSummary;
+use brane_exe::trace::Tracer;
 use brane_tsk::spec::{LOCALHOST, AppId};
-use brane_tsk::grpc::{CreateSessionRequest, DriverServiceClient, ExecuteRequest};
+use brane_tsk::grpc::{CancelRequest, CreateSessionRequest, DriverServiceClient, ExecuteReply, ExecuteRequest, InspectSessionRequest, ReconnectRequest};
+use brane_tsk::api::ApiClient;
+use brane_tsk::tls::ClientTls;
+use brane_tsk::tools::is_conflict;
 use specifications::data::{AccessKind, DataIndex, DataInfo};
 use specifications::package::PackageIndex;
 use specifications::registry::RegistryConfig;
+use specifications::status::{OutputStream, ResourceUsage, TaskOutput, TaskProgress};
 
 pub use crate::errors::RunError as Error;
 use crate::errors::OfflineVmError;
 use crate::data;
+use crate::instance::client_tls;
+use crate::render::{render_table, render_value, BranchOutput, RenderOptions};
 use crate::utils::{ensure_datasets_dir, ensure_packages_dir, get_datasets_dir, get_packages_dir, get_registry_file};
-use crate::vm::OfflineVm;
+use crate::vm::{plugins, OfflineVm};
 
 
 /***** HELPER FUNCTIONS *****/
@@ -58,7 +75,7 @@ use crate::vm::OfflineVm;
 /// 
 /// # Errors
 /// This function errors if the given string was not a valid workflow. If that's the case, it's also pretty-printed to stdout with source context.
-fn compile(state: &mut CompileState, source: &mut String, pindex: &PackageIndex, dindex: &DataIndex, options: &ParserOptions, what: impl AsRef<str>, snippet: impl AsRef<str>) -> Result<Workflow, Error> {
+pub(crate) fn compile(state: &mut CompileState, source: &mut String, pindex: &PackageIndex, dindex: &DataIndex, options: &ParserOptions, what: impl AsRef<str>, snippet: impl AsRef<str>) -> Result<Workflow, Error> {
     let what    : &str = what.as_ref();
     let snippet : &str = snippet.as_ref();
 
@@ -101,11 +118,115 @@ fn compile(state: &mut CompileState, source: &mut String, pindex: &PackageIndex,
     Ok(workflow)
 }
 
+/// Computes a hash of the given workflow, such that it may be recognized when it is compiled again later.
+/// 
+/// # Arguments
+/// - `workflow`: The Workflow to hash.
+/// 
+/// # Returns
+/// The SHA-256 digest of the workflow's JSON representation (with its maps in sorted order), as 64 lowercase hexadecimal characters.
+pub(crate) fn workflow_hash(workflow: &Workflow) -> String {
+    // Going through a `Value` sorts the maps, which are otherwise serialized in arbitrary order
+    let json: String = match serde_json::to_value(workflow) {
+        Ok(json) => json.to_string(),
+        Err(err) => { warn!("Failed to serialize workflow for hashing: {}", err); String::new() },
+    };
+    let mut hasher: Sha256 = Sha256::new();
+    hasher.update(json.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Prints the value returned by a workflow, as a table if it's an array of instances.
+/// 
+/// # Arguments
+/// - `result`: The value to print.
+/// - `render`: Determines how much of the value we print.
+fn print_result(result: &FullValue, render: &RenderOptions) {
+    if render.json {
+        match serde_json::to_string(result) {
+            Ok(json) => println!("{}", json),
+            Err(err) => warn!("Failed to serialize returned value as JSON: {}", err),
+        }
+        return;
+    }
+    match render_table(result, render) {
+        Some(table) => {
+            println!("\nWorkflow returned value of type {}:", style(result.data_type()).bold().cyan());
+            table.printstd();
+        },
+        None => println!("\nWorkflow returned value {}", style(format!("'{}'", render_value(result, render))).bold().cyan()),
+    }
+}
+
+/// Decides whether the given status means that we lost the connection to the driver, rather than that the driver reported an error.
+/// 
+/// # Arguments
+/// - `status`: The status to examine.
+/// 
+/// # Returns
+/// True if reconnecting may help, or false otherwise.
+fn is_connection_loss(status: &Status) -> bool {
+    matches!(status.code(), Code::Unavailable | Code::Cancelled | Code::DeadlineExceeded)
+}
+
+/// Reconnects to the replies of the workflow running in the given session, retrying with a growing delay.
+/// 
+/// # Arguments
+/// - `endpoint`: The `brane-drv` endpoint that we are connected to (used for debugging only).
+/// - `state`: The InstanceVmState that we use to connect to the driver.
+/// - `after`: The sequence number of the last reply that we received.
+/// - `err`: The status that made us lose the connection.
+/// 
+/// # Returns
+/// The stream of replies, starting with the ones that we missed.
+/// 
+/// # Errors
+/// This function errors if we failed to reconnect within the configured number of attempts, or if the driver can no longer give us the replies that we missed.
+async fn reconnect(endpoint: &str, state: &mut InstanceVmState, after: u64, mut err: Status) -> Result<Streaming<ExecuteReply>, Error> {
+    for attempt in 1..=state.reconnect.attempts {
+        warn!("Lost connection to remote '{}' ({}); reconnecting (attempt {}/{})...", endpoint, err.message(), attempt, state.reconnect.attempts);
+        sleep(Duration::from_secs(attempt as u64)).await;
+
+        // Ask the driver to continue where we left off
+        let request = ReconnectRequest { uuid: state.session.to_string(), after };
+        match state.client.reconnect(request).await {
+            Ok(response) => {
+                debug!("Reconnected to session '{}' after reply {}", state.session, after);
+                return Ok(response.into_inner());
+            },
+            Err(status) => {
+                if !is_connection_loss(&status) { return Err(Error::ReconnectError{ address: endpoint.into(), attempts: attempt, err: status }); }
+                err = status;
+            },
+        }
+    }
+    Err(Error::ReconnectError{ address: endpoint.into(), attempts: state.reconnect.attempts, err })
+}
+
 
 
 
 
 /***** AUXILLARY *****/
+/// Defines how we deal with a connection to a remote driver that drops while a workflow is running.
+#[derive(Clone, Copy, Debug)]
+pub struct ReconnectOptions {
+    /// How long the driver may be silent (including heartbeats) before we consider the connection lost.
+    pub timeout  : Duration,
+    /// How many times we try to reconnect before giving up.
+    pub attempts : u32,
+}
+
+impl Default for ReconnectOptions {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            timeout  : Duration::from_secs(30),
+            attempts : 5,
+        }
+    }
+}
+
 /// A helper struct that contains what we need to know about a compiler + VM state for the offline use-case.
 pub struct OfflineVmState {
     /// The temporary directory where we store results.
@@ -121,6 +242,10 @@ pub struct OfflineVmState {
     pub source  : String,
     /// Any compiler options we apply.
     pub options : ParserOptions,
+    /// The retry policy to apply to every task call that does not have its own, if any.
+    pub retry   : Option<RetryPolicy>,
+    /// The hash of the last workflow that was compiled, if any (see `workflow_hash()`).
+    pub hash    : Option<String>,
 
     /// The state of the VM, i.e., the VM. This is wrapped in an 'Option' so we can easily take it if the OfflineVmState is only mutably borrowed.
     pub vm : Option<OfflineVm>,
@@ -134,16 +259,30 @@ pub struct InstanceVmState {
     pub dindex : Arc<DataIndex>,
 
     /// The state of the compiler.
-    pub state   : CompileState,
+    pub state     : CompileState,
     /// The associated source string, which we use for debugging.
-    pub source  : String,
+    pub source    : String,
     /// Any compiler options we apply.
-    pub options : ParserOptions,
+    pub options   : ParserOptions,
+    /// The retry policy to apply to every task call that does not have its own, if any.
+    pub retry     : Option<RetryPolicy>,
+    /// The hash of the last workflow that was compiled, if any (see `workflow_hash()`).
+    pub hash      : Option<String>,
+    /// The maximum number of steps that the driver may let a workflow take, if we want it to be lower than its own limit.
+    pub max_steps : Option<u64>,
 
     /// The ID for this session.
     pub session : AppId,
     /// The client which we use to communicate to the VM.
     pub client  : DriverServiceClient<Channel>,
+
+    /// The resources consumed by every task that completed in this session so far, as (task name, usage) pairs.
+    pub usage : Vec<(String, ResourceUsage)>,
+
+    /// How we deal with a dropped connection to the driver.
+    pub reconnect    : ReconnectOptions,
+    /// Whether to print the text written by every parallel branch contiguously instead of as it arrives.
+    pub group_output : bool,
 }
 
 
@@ -207,6 +346,8 @@ pub fn initialize_offline_vm(options: ParserOptions) -> Result<OfflineVmState, E
         state  : CompileState::new(),
         source : String::new(),
         options,
+        retry  : None,
+        hash   : None,
 
         vm : Some(OfflineVm::new(packages_dir, datasets_dir, temp_dir_path, package_index, data_index)),
     })
@@ -235,20 +376,27 @@ pub async fn initialize_instance_vm(endpoint: impl AsRef<str>, attach: Option<Ap
 
     // We fetch a local copy of the indices for compiling
     debug!("Fetching global package & data indices from '{}'...", config.url);
+    let tls: ClientTls = client_tls();
+    let api: ApiClient = ApiClient::with_client(tls.api().clone(), &config.url);
     let package_addr: String = format!("{}/graphql", config.url);
-    let pindex: Arc<PackageIndex> = match brane_tsk::api::get_package_index(&package_addr).await {
+    let pindex: Arc<PackageIndex> = match brane_tsk::api::get_package_index_with(tls.api(), &package_addr).await {
         Ok(pindex) => Arc::new(pindex),
         Err(err)   => { return Err(Error::RemotePackageIndexError{ address: package_addr, err }); },
     };
     let data_addr: String = format!("{}/data/info", config.url);
-    let dindex: Arc<DataIndex> = match brane_tsk::api::get_data_index(&data_addr).await {
+    let dindex: Arc<DataIndex> = match api.data_index().await {
         Ok(dindex) => Arc::new(dindex),
         Err(err)   => { return Err(Error::RemoteDataIndexError{ address: data_addr, err }); },
     };
+    // The locations are only used to check location annotations, so we can do without them
+    let options: ParserOptions = match api.locations().await {
+        Ok(locations) => options.with_locations(locations),
+        Err(err)      => { warn!("Failed to fetch locations of the instance (location annotations will not be checked at compile time): {}", err); options },
+    };
 
     // Connect to the server with gRPC
     debug!("Connecting to driver '{}'...", endpoint);
-    let mut client: DriverServiceClient<Channel> = match DriverServiceClient::connect(endpoint.to_string()).await {
+    let mut client: DriverServiceClient<Channel> = match tls.connect_driver(endpoint).await {
         Ok(client) => client,
         Err(err)   => { return Err(Error::ClientConnectError{ address: endpoint.into(), err }); }
     };
@@ -279,17 +427,55 @@ pub async fn initialize_instance_vm(endpoint: impl AsRef<str>, attach: Option<Ap
         pindex,
         dindex,
 
-        state  : CompileState::new(),
-        source : String::new(),
+        state     : CompileState::new(),
+        source    : String::new(),
         options,
+        retry     : None,
+        hash      : None,
+        max_steps : None,
 
         session,
         client,
+
+        usage : vec![],
+
+        reconnect    : ReconnectOptions::default(),
+        group_output : false,
     })
 }
 
 
 
+/// Function that summarizes the variables defined in the session of the given InstanceVmState.
+/// 
+/// This is useful when attaching to an existing session, to show what has been defined in it so far.
+/// 
+/// # Arguments
+/// - `endpoint`: The `brane-drv` endpoint that hosts the session.
+/// - `state`: The InstanceVmState with the session to inspect.
+/// 
+/// # Returns
+/// A list of FrameSummaries, starting with the main frame.
+/// 
+/// # Errors
+/// This function errors if the session does not exist or we failed to parse the reply of the remote.
+pub async fn inspect_instance_vm(endpoint: impl AsRef<str>, state: &mut InstanceVmState) -> Result<Vec<FrameSummary>, Error> {
+    let endpoint: &str = endpoint.as_ref();
+
+    debug!("Inspecting session '{}'...", state.session);
+    let request = InspectSessionRequest { uuid: state.session.to_string() };
+    let raw: String = match state.client.inspect_session(request).await {
+        Ok(reply) => reply.into_inner().frames,
+        Err(err)  => { return Err(Error::SessionInspectError{ address: endpoint.into(), err }); },
+    };
+    match serde_json::from_str(&raw) {
+        Ok(frames) => Ok(frames),
+        Err(err)   => Err(Error::FramesParseError{ address: endpoint.into(), err }),
+    }
+}
+
+
+
 /// Function that executes the given workflow snippet to completion on the local machine, returning the result it returns.
 /// 
 /// # Arguments
@@ -307,7 +493,10 @@ pub async fn run_offline_vm(state: &mut OfflineVmState, what: impl AsRef<str>, s
     let snippet: &str  = snippet.as_ref();
 
     // Compile the workflow
-    let workflow: Workflow = compile(&mut state.state, &mut state.source, &state.pindex, &state.dindex, &state.options, what, snippet)?;
+    state.hash = None;
+    let mut workflow: Workflow = compile(&mut state.state, &mut state.source, &state.pindex, &state.dindex, &state.options, what, snippet)?;
+    if let Some(retry) = state.retry { workflow.set_default_retry(retry); }
+    state.hash = Some(workflow_hash(&workflow));
 
     // Run it in the local VM (which is a bit ugly do to the need to consume the VM itself)
     let res: (OfflineVm, Result<FullValue, OfflineVmError>) = state.vm.take().unwrap().exec(workflow).await;
@@ -325,26 +514,26 @@ pub async fn run_offline_vm(state: &mut OfflineVmState, what: impl AsRef<str>, s
     Ok(res)
 }
 
-/// Function that executes the given workflow snippet to completion on the Brane instance, returning the result it returns.
+/// Compiles the given workflow snippet and starts executing it on the Brane instance.
 /// 
 /// # Arguments
 /// - `endpoint`: The `brane-drv` endpoint that we will connect to to run stuff (used for debugging only).
 /// - `state`: The InstanceVmState that we use to connect to the driver.
 /// - `what`: The thing we're running. Either a filename, or something like '<stdin>'.
 /// - `snippet`: The snippet (as raw text) to compile and run.
+/// - `detach`: Whether the driver should keep (and persist) the output of the workflow until it expires, since we will not follow it.
 /// 
 /// # Returns
-/// The FullValue that the workflow returned, if any. If there was no value, returns FullValue::Void instead.
+/// The stream of replies of the workflow.
 /// 
 /// # Errors
-/// This function errors if we failed to compile the workflow, communicate with the remote driver or remote execution failed somehow.
-pub async fn run_instance_vm(endpoint: impl AsRef<str>, state: &mut InstanceVmState, what: impl AsRef<str>, snippet: impl AsRef<str>) -> Result<FullValue, Error> {
-    let endpoint: &str = endpoint.as_ref();
-    let what: &str     = what.as_ref();
-    let snippet: &str  = snippet.as_ref();
-
+/// This function errors if we failed to compile the workflow or communicate with the remote driver.
+pub(crate) async fn execute_instance_vm(endpoint: &str, state: &mut InstanceVmState, what: &str, snippet: &str, detach: bool) -> Result<Streaming<ExecuteReply>, Error> {
     // Compile the workflow
-    let workflow: Workflow = compile(&mut state.state, &mut state.source, &state.pindex, &state.dindex, &state.options, what, snippet)?;
+    state.hash = None;
+    let mut workflow: Workflow = compile(&mut state.state, &mut state.source, &state.pindex, &state.dindex, &state.options, what, snippet)?;
+    if let Some(retry) = state.retry { workflow.set_default_retry(retry); }
+    state.hash = Some(workflow_hash(&workflow));
 
     // Serialize the workflow
     let sworkflow: String = match serde_json::to_string(&workflow) {
@@ -354,38 +543,123 @@ pub async fn run_instance_vm(endpoint: impl AsRef<str>, state: &mut InstanceVmSt
 
     // Prepare the request to execute this command
     let request = ExecuteRequest {
-        uuid  : state.session.to_string(),
-        input : sworkflow,
+        uuid      : state.session.to_string(),
+        input     : sworkflow,
+        detach,
+        max_steps : state.max_steps,
     };
 
     // Run it
-    let response = match state.client.execute(request).await {
-        Ok(response) => response,
-        Err(err)     => { return Err(Error::CommandRequestError{ address: endpoint.into(), err }); }
-    };
-    let mut stream = response.into_inner();
+    match state.client.execute(request).await {
+        Ok(response) => Ok(response.into_inner()),
+        Err(err)     => Err(Error::CommandRequestError{ address: endpoint.into(), err }),
+    }
+}
 
+/// Follows the replies of a workflow running on the Brane instance until it completes, printing its output and returning its result.
+/// 
+/// # Arguments
+/// - `endpoint`: The `brane-drv` endpoint that we will connect to to run stuff (used for debugging only).
+/// - `state`: The InstanceVmState that we use to connect to the driver.
+/// - `stream`: The stream of replies of the workflow.
+/// 
+/// # Returns
+/// The FullValue that the workflow returned, if any. If there was no value, returns FullValue::Void instead.
+/// 
+/// # Errors
+/// This function errors if we failed to parse the result or lost the connection to the remote driver for good.
+async fn follow_instance_vm(endpoint: &str, state: &mut InstanceVmState, mut stream: Streaming<ExecuteReply>) -> Result<FullValue, Error> {
     // Switch on the type of message that the remote returned
     let mut res: FullValue = FullValue::Void;
+    let mut progress: Option<ProgressBar> = None;
+    let mut output: BranchOutput = BranchOutput::new(state.group_output);
+    // Drivers that number their replies send heartbeats and let us reconnect; for older ones, we just wait for what they send
+    let mut resumable : bool = false;
+    let mut last      : u64  = 0;
+    let mut failed    : bool = false;
     loop {
+        // Wait for the next message, treating a silent driver as a dropped connection
+        let message: Result<Option<ExecuteReply>, Status> = if resumable {
+            match timeout(state.reconnect.timeout, stream.message()).await {
+                Ok(message) => message,
+                Err(_)      => Err(Status::deadline_exceeded(format!("no message received in {}s", state.reconnect.timeout.as_secs()))),
+            }
+        } else {
+            stream.message().await
+        };
+
         // Match on the message
-        match stream.message().await {
+        match message {
             // The message itself went alright
             Ok(Some(reply)) => {
+                // Heartbeats only tell us the connection is alive, and replies that we already saw may be replayed after reconnecting
+                if reply.heartbeat || reply.seq != 0 { resumable = true; }
+                if reply.heartbeat || (reply.seq != 0 && reply.seq <= last) { continue; }
+                last = reply.seq;
+
                 // The remote send us some debug message
                 if let Some(debug) = reply.debug {
                     debug!("Remote: {}", debug);
                 }
 
+                // The remote send us a progress update of a running task
+                if let Some(sprogress) = reply.progress {
+                    debug!("Remote returned progress");
+                    match serde_json::from_str::<(String, TaskProgress)>(&sprogress) {
+                        Ok((task, tprogress)) => {
+                            // Update the bar (creating it if this is the first update)
+                            let bar: &ProgressBar = progress.get_or_insert_with(|| {
+                                let bar = ProgressBar::new(100);
+                                bar.set_style(
+                                    ProgressStyle::default_bar()
+                                        .template("{prefix} [{elapsed_precise}] {bar:40.cyan/blue} {pos:>3}% {wide_msg}")
+                                        .progress_chars("##-"),
+                                );
+                                bar
+                            });
+                            bar.set_prefix(format!("Task '{}'", task));
+                            if let Some(percentage) = tprogress.percentage { bar.set_position(percentage as u64); }
+                            bar.set_message(tprogress.message.unwrap_or_default());
+                        },
+                        Err(err) => { warn!("Failed to parse progress update '{}': {}", sprogress, err); },
+                    }
+                }
+
+                // The remote send us the resources that a completed task consumed
+                if let Some(susage) = reply.usage {
+                    debug!("Remote returned resource usage");
+                    match serde_json::from_str::<(String, ResourceUsage)>(&susage) {
+                        Ok(usage) => { state.usage.push(usage); },
+                        Err(err)  => { warn!("Failed to parse resource usage '{}': {}", susage, err); },
+                    }
+                }
+
+                // The remote send us a line of output of a running task
+                if let Some(soutput) = reply.output {
+                    match serde_json::from_str::<(String, TaskOutput)>(&soutput) {
+                        Ok((task, output)) => {
+                            if let Some(bar) = progress.take() { bar.finish_and_clear(); }
+                            match output.stream {
+                                OutputStream::Stdout => println!("{} {}", style(format!("[{}]", task)).dim(), output.line),
+                                OutputStream::Stderr => eprintln!("{} {}", style(format!("[{}]", task)).dim(), output.line),
+                            }
+                        },
+                        Err(err) => { warn!("Failed to parse task output '{}': {}", soutput, err); },
+                    }
+                }
+
                 // The remote send us a normal text message
                 if let Some(stdout) = reply.stdout {
                     debug!("Remote returned stdout");
-                    print!("{}", stdout);
+                    if let Some(bar) = progress.take() { bar.finish_and_clear(); }
+                    let branch: Vec<usize> = reply.branch.iter().map(|b| *b as usize).collect();
+                    output.write(&branch, &stdout);
                 }
 
                 // The remote send us an error
                 if let Some(stderr) = reply.stderr {
                     debug!("Remote returned error");
+                    if let Some(bar) = progress.take() { bar.finish_and_clear(); }
                     eprintln!("{}", stderr);
                 }
 
@@ -405,13 +679,30 @@ pub async fn run_instance_vm(endpoint: impl AsRef<str>, state: &mut InstanceVmSt
 
                 // The remote is done with this
                 if reply.close {
+                    if let Some(bar) = progress.take() { bar.finish_and_clear(); }
+                    output.flush();
                     println!();
                     break;
                 }
             }
+            Err(status) if resumable && !failed && is_connection_loss(&status) => {
+                // We lost the connection, so pick up where we left off
+                if let Some(bar) = progress.take() { bar.finish_and_clear(); }
+                stream = reconnect(endpoint, state, last, status).await?;
+            }
             Err(status) => {
-                // Did not receive the message properly
-                eprintln!("\nStatus error: {}", status.message());
+                // Did not receive the message properly; conflicts are not a failure of the instance, so we say so
+                if is_conflict(&status) {
+                    eprintln!("\n{}: {}", style("Commit conflict").bold().yellow(), status.message());
+                } else {
+                    eprintln!("\nStatus error: {}", status.message());
+                }
+                failed = true;
+            }
+            Ok(None) if resumable && !failed => {
+                // Stream closed before the workflow was done, so the connection dropped
+                if let Some(bar) = progress.take() { bar.finish_and_clear(); }
+                stream = reconnect(endpoint, state, last, Status::unavailable("stream closed early")).await?;
             }
             Ok(None) => {
                 // Stream closed by the remote for some rason
@@ -420,10 +711,124 @@ pub async fn run_instance_vm(endpoint: impl AsRef<str>, state: &mut InstanceVmSt
         }
     }
 
+    // Print whatever the branches wrote before the stream ended
+    output.flush();
+
     // Done
     Ok(res)
 }
 
+/// Function that executes the given workflow snippet to completion on the Brane instance, returning the result it returns.
+/// 
+/// Hitting Ctrl+C while it runs cancels the workflow on the instance, including the tasks it is waiting for.
+/// 
+/// # Arguments
+/// - `endpoint`: The `brane-drv` endpoint that we will connect to to run stuff (used for debugging only).
+/// - `state`: The InstanceVmState that we use to connect to the driver.
+/// - `what`: The thing we're running. Either a filename, or something like '<stdin>'.
+/// - `snippet`: The snippet (as raw text) to compile and run.
+/// 
+/// # Returns
+/// The FullValue that the workflow returned, if any. If there was no value, returns FullValue::Void instead.
+/// 
+/// # Errors
+/// This function errors if we failed to compile the workflow, communicate with the remote driver or remote execution failed somehow.
+pub async fn run_instance_vm(endpoint: impl AsRef<str>, state: &mut InstanceVmState, what: impl AsRef<str>, snippet: impl AsRef<str>) -> Result<FullValue, Error> {
+    let endpoint: &str = endpoint.as_ref();
+    let stream: Streaming<ExecuteReply> = execute_instance_vm(endpoint, state, what.as_ref(), snippet.as_ref(), false).await?;
+
+    // Follow it, cancelling the workflow if the user hits Ctrl+C
+    let mut client: DriverServiceClient<Channel> = state.client.clone();
+    let session: String = state.session.to_string();
+    let follow = follow_instance_vm(endpoint, state, stream);
+    tokio::pin!(follow);
+    tokio::select! {
+        res = &mut follow => res,
+        _ = tokio::signal::ctrl_c() => {
+            // Ask the driver to cancel it, then keep following it to see how it ends
+            eprintln!("\nCancelling workflow (press Ctrl+C again to quit immediately)...");
+            if let Err(err) = client.cancel(CancelRequest{ uuid: session }).await { warn!("Failed to cancel workflow on remote '{}': {}", endpoint, err.message()); }
+            tokio::select! {
+                res = &mut follow => res,
+                _ = tokio::signal::ctrl_c() => { std::process::exit(130); },
+            }
+        },
+    }
+}
+
+/// Function that submits the given workflow snippet to the Brane instance without waiting for it, i.e., the driver keeps its output until someone attaches to it.
+/// 
+/// # Arguments
+/// - `endpoint`: The `brane-drv` endpoint that we will connect to to run stuff (used for debugging only).
+/// - `state`: The InstanceVmState that we use to connect to the driver.
+/// - `what`: The thing we're running. Either a filename, or something like '<stdin>'.
+/// - `snippet`: The snippet (as raw text) to compile and run.
+/// 
+/// # Errors
+/// This function errors if we failed to compile the workflow or communicate with the remote driver.
+pub async fn submit_instance_vm(endpoint: impl AsRef<str>, state: &mut InstanceVmState, what: impl AsRef<str>, snippet: impl AsRef<str>) -> Result<(), Error> {
+    execute_instance_vm(endpoint.as_ref(), state, what.as_ref(), snippet.as_ref(), true).await?;
+    Ok(())
+}
+
+/// Function that attaches to the last workflow in the session of the given InstanceVmState, printing all of its output so far and following it until it completes.
+/// 
+/// # Arguments
+/// - `endpoint`: The `brane-drv` endpoint that we will connect to to run stuff (used for debugging only).
+/// - `state`: The InstanceVmState that we use to connect to the driver.
+/// 
+/// # Returns
+/// The FullValue that the workflow returned, if any. If there was no value, returns FullValue::Void instead.
+/// 
+/// # Errors
+/// This function errors if the session has no workflow (with its output) to attach to, or for any reason that following it may fail.
+pub async fn attach_instance_vm(endpoint: impl AsRef<str>, state: &mut InstanceVmState) -> Result<FullValue, Error> {
+    let endpoint: &str = endpoint.as_ref();
+
+    debug!("Attaching to session '{}'...", state.session);
+    let request = ReconnectRequest { uuid: state.session.to_string(), after: 0 };
+    let stream: Streaming<ExecuteReply> = match state.client.reconnect(request).await {
+        Ok(response) => response.into_inner(),
+        Err(err)     => { return Err(Error::AttachError{ address: endpoint.into(), err }); },
+    };
+    follow_instance_vm(endpoint, state, stream).await
+}
+
+
+
+/// Prints the resources consumed by the tasks in a remote workflow execution, so users can right-size the requirements of their packages.
+/// 
+/// # Arguments
+/// - `usage`: The (task name, usage) pairs to print, in the order in which the tasks completed.
+/// 
+/// # Returns
+/// Nothing, but does print a table to stdout.
+pub fn process_instance_usage(usage: &[(String, ResourceUsage)]) {
+    if usage.is_empty() { println!("No tasks were executed."); return; }
+
+    // Prepare display table.
+    let format = FormatBuilder::new()
+        .column_separator('\0')
+        .borders('\0')
+        .padding(1, 1)
+        .build();
+    let mut table = Table::new();
+    table.set_format(format);
+    table.add_row(row!["TASK", "CPU TIME", "PEAK MEMORY", "READ", "WRITTEN"]);
+
+    // Add each task, as well as a total
+    let mut total: ResourceUsage = ResourceUsage::default();
+    for (name, usage) in usage {
+        table.add_row(row![name, format!("{:.2}s", usage.cpu_seconds), DecimalBytes(usage.peak_memory), DecimalBytes(usage.io_read), DecimalBytes(usage.io_write)]);
+        total.merge(usage);
+    }
+    table.add_row(row![style("<total>").bold(), format!("{:.2}s", total.cpu_seconds), DecimalBytes(total.peak_memory), DecimalBytes(total.io_read), DecimalBytes(total.io_write)]);
+
+    // Write to stdout and done!
+    println!("\nResource usage:");
+    table.printstd();
+}
+
 
 
 /// Processes the given result of an offline workflow execution.
@@ -431,16 +836,17 @@ pub async fn run_instance_vm(endpoint: impl AsRef<str>, state: &mut InstanceVmSt
 /// # Arguments
 /// - `result_dir`: The directory where temporary results are stored.
 /// - `result`: The value to process.
+/// - `render`: Determines how much of the value we print.
 /// 
 /// # Returns
 /// Nothing, but does print any result to stdout.
 /// 
 /// # Errors
 /// This function may error if we failed to get an up-to-date data index.
-pub fn process_offline_result(result: FullValue) -> Result<(), Error> {
+pub fn process_offline_result(result: FullValue, render: &RenderOptions) -> Result<(), Error> {
     // We only print
     if result != FullValue::Void {
-        println!("\nWorkflow returned value {}", style(format!("'{}'", result)).bold().cyan());
+        print_result(&result, render);
 
         // Treat some values special
         match result {
@@ -475,7 +881,8 @@ pub fn process_offline_result(result: FullValue) -> Result<(), Error> {
 
                 // Write the method of access
                 match access {
-                    AccessKind::File { path } => println!("(It's available under '{}')", path.display()),
+                    AccessKind::File { path }                                                              => println!("(It's available under '{}')", path.display()),
+                    AccessKind::XRootD { url } | AccessKind::GridFtp { url } | AccessKind::Url { url, .. } => println!("(It's available at '{}')", url),
                 }
             },
 
@@ -495,18 +902,19 @@ pub fn process_offline_result(result: FullValue) -> Result<(), Error> {
 /// - `proxy_addr`: If given, proxies all data transfers through the proxy at the given location.
 /// - `result_dir`: The directory where temporary results are stored.
 /// - `result`: The value to process.
+/// - `render`: Determines how much of the value we print.
 /// 
 /// # Returns
 /// Nothing, but does print any result to stdout. It may also download a remote dataset if one is given.
 /// 
 /// # Errors
 /// This function may error if the given result was a dataset and we failed to retrieve it.
-pub async fn process_instance_result(certs_dir: impl AsRef<Path>, proxy_addr: &Option<String>, result: FullValue) -> Result<(), Error> {
+pub async fn process_instance_result(certs_dir: impl AsRef<Path>, proxy_addr: &Option<String>, result: FullValue, render: &RenderOptions) -> Result<(), Error> {
     let certs_dir   : &Path = certs_dir.as_ref();
 
     // We only print
     if result != FullValue::Void {
-        println!("\nWorkflow returned value {}", style(format!("'{}'", result)).bold().cyan());
+        print_result(&result, render);
 
         // Treat some values special
         match result {
@@ -525,7 +933,7 @@ pub async fn process_instance_result(certs_dir: impl AsRef<Path>, proxy_addr: &O
 
                 // Fetch a new, local DataIndex to get up-to-date entries
                 let data_addr: String = format!("{}/data/info", config.url);
-                let index: DataIndex = match brane_tsk::api::get_data_index(&data_addr).await {
+                let index: DataIndex = match ApiClient::with_client(client_tls().api().clone(), &config.url).data_index().await {
                     Ok(dindex) => dindex,
                     Err(err)   => { return Err(Error::RemoteDataIndexError{ address: data_addr, err }); },
                 };
@@ -539,7 +947,7 @@ pub async fn process_instance_result(certs_dir: impl AsRef<Path>, proxy_addr: &O
                     Some(access) => access.clone(),
                     None         => {
                         // Attempt to download it instead
-                        match data::download_data(certs_dir, &config.url, proxy_addr, &name, &info.access).await {
+                        match data::download_data(certs_dir, &config.url, proxy_addr, &info.name, &info.version, &info.access, info.residency.as_deref()).await {
                             Ok(Some(access)) => access,
                             Ok(None)         => { return Err(Error::UnavailableDataset{ name: name.into(), locs: info.access.keys().cloned().collect() }); },
                             Err(err)         => { return Err(Error::DataDownloadError{ err }); },
@@ -549,7 +957,8 @@ pub async fn process_instance_result(certs_dir: impl AsRef<Path>, proxy_addr: &O
 
                 // Write the method of access
                 match access {
-                    AccessKind::File { path } => println!("(It's available under '{}')", path.display()),
+                    AccessKind::File { path }                                                              => println!("(It's available under '{}')", path.display()),
+                    AccessKind::XRootD { url } | AccessKind::GridFtp { url } | AccessKind::Url { url, .. } => println!("(It's available at '{}')", url),
                 }
             },
 
@@ -575,10 +984,21 @@ pub async fn process_instance_result(certs_dir: impl AsRef<Path>, proxy_addr: &O
 /// - `remote`: Whether to (and what) remote Brane instance to run the file on instead.
 /// - `language`: The language with which to compile the file.
 /// - `file`: The file to read and run. Can also be '-', in which case it is read from stdin instead.
+/// - `profile`: Whether to print the resources consumed by every task after the workflow completes (remote runs only).
+/// - `trace`: If given, records the edges visited by the workflow to this JSONL file (local runs only).
+/// - `plugin`: If given, the name of the VM plugin to run the workflow with instead of the default one (local runs only).
+/// - `detach`: Whether to only submit the workflow, leaving its output on the driver until someone attaches to it with `brane runs attach` (remote runs only).
+/// - `retry`: If given, the retry policy to apply to every task call in the workflow that does not have its own (i.e., a `@retry(...)`-annotation).
+/// - `max_steps`: If given, the maximum number of steps (i.e., edges and instructions) that the workflow may take before it is aborted. Remote instances may impose a lower limit.
+/// - `params`: The values of the parameters declared by the workflow, each given as `<name>=<value>`.
+/// - `render`: Determines how much of the returned value we print.
 /// 
 /// # Returns
 /// Nothing, but does print results and such to stdout. Might also produce new datasets.
-pub async fn handle(certs_dir: impl AsRef<Path>, proxy_addr: Option<String>, language: Language, file: PathBuf, remote: Option<String>) -> Result<(), Error> {
+#[allow(clippy::too_many_arguments)]
+pub async fn handle(certs_dir: impl AsRef<Path>, proxy_addr: Option<String>, language: Language, file: PathBuf, remote: Option<String>, profile: bool, trace: Option<PathBuf>, plugin: Option<String>, detach: bool, retry: Option<RetryPolicy>, max_steps: Option<u64>, params: Vec<String>, render: RenderOptions) -> Result<(), Error> {
+    if detach && remote.is_none() { return Err(Error::LocalDetachError); }
+
     // Either read the file or read stdin
     let (what, source_code): (Cow<str>, String) = if file == PathBuf::from("-") {
         let mut result: String = String::new();
@@ -591,14 +1011,21 @@ pub async fn handle(certs_dir: impl AsRef<Path>, proxy_addr: Option<String>, lan
         }
     };
 
-    // Prepare the parser options
-    let options: ParserOptions = ParserOptions::new(language);
+    // Prepare the parser options, which bind the workflow's parameters
+    let mut bound: HashMap<String, String> = HashMap::with_capacity(params.len());
+    for raw in params {
+        match raw.split_once('=') {
+            Some((name, value)) if !name.trim().is_empty() => { bound.insert(name.trim().into(), value.into()); },
+            _                                              => { return Err(Error::IllegalParam{ raw }); },
+        }
+    }
+    let options: ParserOptions = ParserOptions::new(language).with_params(bound);
 
     // Now switch on remote or local mode
     if let Some(remote) = remote {
-        remote_run(certs_dir, proxy_addr, remote, options, what, source_code).await
+        remote_run(certs_dir, proxy_addr, remote, options, what, source_code, profile, detach, retry, max_steps, &render).await
     } else {
-        local_run(options, what, source_code).await
+        local_run(options, what, source_code, trace, plugin, retry, max_steps, &render).await
     }
 }
 
@@ -613,10 +1040,16 @@ pub async fn handle(certs_dir: impl AsRef<Path>, proxy_addr: Option<String>, lan
 /// - `options`: The ParseOptions that specify how to parse the incoming source.
 /// - `what`: A description of the source we're reading (e.g., the filename or `<stdin>`)
 /// - `source`: The source code to read.
+/// - `profile`: Whether to print the resources consumed by every task after the workflow completes.
+/// - `detach`: Whether to only submit the workflow instead of waiting for it.
+/// - `retry`: The retry policy to apply to every task call without one, if any.
+/// - `max_steps`: The maximum number of steps that the workflow may take, if any. The driver may impose a lower limit.
+/// - `render`: Determines how much of the returned value we print.
 /// 
 /// # Returns
 /// Nothing, but does print results and such to stdout. Might also produce new datasets.
-async fn remote_run(certs_dir: impl AsRef<Path>, proxy_addr: Option<String>, endpoint: impl AsRef<str>, options: ParserOptions, what: impl AsRef<str>, source: impl AsRef<str>) -> Result<(), Error> {
+#[allow(clippy::too_many_arguments)]
+async fn remote_run(certs_dir: impl AsRef<Path>, proxy_addr: Option<String>, endpoint: impl AsRef<str>, options: ParserOptions, what: impl AsRef<str>, source: impl AsRef<str>, profile: bool, detach: bool, retry: Option<RetryPolicy>, max_steps: Option<u64>, render: &RenderOptions) -> Result<(), Error> {
     let certs_dir : &Path = certs_dir.as_ref();
     let endpoint  : &str  = endpoint.as_ref();
     let what      : &str  = what.as_ref();
@@ -624,10 +1057,21 @@ async fn remote_run(certs_dir: impl AsRef<Path>, proxy_addr: Option<String>, end
 
     // First we initialize the remote thing
     let mut state: InstanceVmState = initialize_instance_vm(endpoint, None, options).await?;
+    state.retry        = retry;
+    state.max_steps    = max_steps;
+    state.group_output = render.group_output;
+    // If detached, we only submit it and leave the rest to whoever attaches later
+    if detach {
+        submit_instance_vm(endpoint, &mut state, what, source).await?;
+        println!("Submitted workflow in session {}", style(&state.session).bold().cyan());
+        println!("Run 'brane runs attach {} --remote {}' to see its output and result.", state.session, endpoint);
+        return Ok(());
+    }
     // Next, we run the VM (one snippet only ayway)
     let res: FullValue = run_instance_vm(endpoint, &mut state, what, source).await?;
     // Then, we collect and process the result
-    process_instance_result(certs_dir, &proxy_addr, res).await?;
+    process_instance_result(certs_dir, &proxy_addr, res, render).await?;
+    if profile { process_instance_usage(&state.usage); }
 
     // Done
     Ok(())
@@ -639,19 +1083,43 @@ async fn remote_run(certs_dir: impl AsRef<Path>, proxy_addr: Option<String>, end
 /// - `options`: The ParseOptions that specify how to parse the incoming source.
 /// - `what`: A description of the source we're reading (e.g., the filename or `<stdin>`)
 /// - `source`: The source code to read.
+/// - `trace`: If given, records the edges visited by the workflow to this JSONL file.
+/// - `plugin`: If given, the name of the VM plugin to run the workflow with instead of the default one.
+/// - `retry`: The retry policy to apply to every task call without one, if any.
+/// - `max_steps`: The maximum number of steps that the workflow may take, if any.
+/// - `render`: Determines how much of the returned value we print.
 /// 
 /// # Returns
 /// Nothing, but does print results and such to stdout. Might also produce new datasets.
-async fn local_run(options: ParserOptions, what: impl AsRef<str>, source: impl AsRef<str>) -> Result<(), Error> {
+#[allow(clippy::too_many_arguments)]
+async fn local_run(options: ParserOptions, what: impl AsRef<str>, source: impl AsRef<str>, trace: Option<PathBuf>, plugin: Option<String>, retry: Option<RetryPolicy>, max_steps: Option<u64>, render: &RenderOptions) -> Result<(), Error> {
     let what      : &str  = what.as_ref();
     let source    : &str  = source.as_ref();
 
     // First we initialize the remote thing
     let mut state: OfflineVmState = initialize_offline_vm(options)?;
+    state.retry = retry;
+    if let Some(max_steps) = max_steps { state.vm = state.vm.take().map(|vm| vm.with_max_steps(max_steps)); }
+    if render.group_output { state.vm = state.vm.take().map(OfflineVm::with_grouped_output); }
+    if let Some(trace) = trace {
+        let tracer: Tracer = match Tracer::new(trace) {
+            Ok(tracer) => tracer,
+            Err(err)   => { return Err(Error::TracerCreateError{ err }); },
+        };
+        state.vm = state.vm.take().map(|vm| vm.with_tracer(tracer));
+    }
+    if let Some(plugin) = plugin {
+        let registry: PluginRegistry<OfflineVm> = plugins();
+        let entry: PluginEntry<OfflineVm> = match registry.get(&plugin) {
+            Some(entry) => entry,
+            None        => { return Err(Error::UnknownVmPlugin{ name: plugin, known: registry.names().into_iter().map(String::from).collect() }); },
+        };
+        state.vm = state.vm.take().map(|vm| vm.with_plugin(entry));
+    }
     // Next, we run the VM (one snippet only ayway)
     let res: FullValue = run_offline_vm(&mut state, what, source).await?;
     // Then, we collect and process the result
-    process_offline_result(res)?;
+    process_offline_result(res, render)?;
 
     // Done
     Ok(())
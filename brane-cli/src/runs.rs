@@ -0,0 +1,441 @@
+//  RUNS.rs
+//    by agent
+//
+//  Created:
+//    16 Oct 2026, 08:02:35
+//  Last edited:
+//    16 Oct 2026, 13:20:15
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements the `runs` subcommand(s), which interact with workflows
+//!   that are currently running on a remote instance.
+//
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+use chrono::{Local, TimeZone as _, Utc};
+use console::style;
+use indicatif::DecimalBytes;
+use prettytable::format::FormatBuilder;
+use prettytable::Table;
+use tonic::transport::Channel;
+
+use brane_ast::{Edge, ParserOptions, Workflow};
+use brane_ast::locations::Location;
+use brane_ast::schedule::{critical_path, with_faster_locations, with_parallelism, Schedule, TaskTiming};
+use brane_dsl::Language;
+use brane_exe::FullValue;
+use brane_tsk::api::ApiClient;
+use brane_tsk::grpc::{Approval, DecideApprovalRequest, DriverServiceClient, GetTimingsRequest, ListApprovalsRequest, SendEventRequest};
+use brane_tsk::spec::AppId;
+use specifications::profiling::format_duration;
+use specifications::registry::RegistryConfig;
+use specifications::status::ResourceUsage;
+
+pub use crate::errors::RunsError as Error;
+use crate::instance::client_tls;
+use crate::render::RenderOptions;
+use crate::run::{attach_instance_vm, initialize_instance_vm, process_instance_result, InstanceVmState};
+use crate::utils::get_registry_file;
+
+
+/***** HELPER FUNCTIONS *****/
+/// Connects to the given remote driver.
+///
+/// # Arguments
+/// - `endpoint`: The `brane-drv` endpoint to connect to.
+///
+/// # Returns
+/// A new DriverServiceClient connected to the endpoint.
+///
+/// # Errors
+/// This function errors if we failed to connect.
+async fn connect(endpoint: &str) -> Result<DriverServiceClient<Channel>, Error> {
+    debug!("Connecting to driver '{}'...", endpoint);
+    match client_tls().connect_driver(endpoint).await {
+        Ok(client) => Ok(client),
+        Err(err)   => Err(Error::ClientConnectError{ address: endpoint.into(), err }),
+    }
+}
+
+/// Decides on a pending approval in the remote instance, in the name of the client certificate of the active instance.
+///
+/// # Arguments
+/// - `endpoint`: The `brane-drv` endpoint to connect to.
+/// - `id`: The identifier of the approval to decide on.
+/// - `approve`: Whether to approve (true) or reject (false) it.
+/// - `reason`: An optional reason for the decision that ends up in the audit log.
+///
+/// # Errors
+/// This function errors if we failed to connect to the driver or if the driver refused the decision (e.g., because we are not one of its approvers).
+async fn decide(endpoint: impl AsRef<str>, id: String, approve: bool, reason: Option<String>) -> Result<(), Error> {
+    let endpoint: &str = endpoint.as_ref();
+
+    // The driver knows who we are by the client certificate we connect with
+    let mut client: DriverServiceClient<Channel> = connect(endpoint).await?;
+    let request = DecideApprovalRequest { id: id.clone(), approve, reason };
+    if let Err(err) = client.decide_approval(request).await {
+        return Err(Error::DecideApprovalError{ address: endpoint.into(), id, err });
+    }
+
+    println!("Successfully {} approval {}", if approve { "granted" } else { "rejected" }, style(id).bold().cyan());
+    Ok(())
+}
+
+/// Fetches the workflow and the timings of the tasks of the last run in a session from the remote driver.
+///
+/// # Arguments
+/// - `endpoint`: The `brane-drv` endpoint to connect to.
+/// - `id`: The identifier of the session to fetch them of.
+///
+/// # Returns
+/// The Workflow that was run and the TaskTimings of the tasks that completed, sorted by when they were started.
+///
+/// # Errors
+/// This function errors if we failed to connect to the driver, if it failed to return the timings or if they were ill-formed.
+async fn get_timings(endpoint: &str, id: &str) -> Result<(Workflow, Vec<TaskTiming>), Error> {
+    let mut client: DriverServiceClient<Channel> = connect(endpoint).await?;
+    let reply = match client.get_timings(GetTimingsRequest{ uuid: id.into() }).await {
+        Ok(reply) => reply.into_inner(),
+        Err(err)  => { return Err(Error::GetTimingsError{ address: endpoint.into(), id: id.into(), err }); },
+    };
+    let workflow: Workflow = match reply.workflow {
+        Some(workflow) => match serde_json::from_str(&workflow) {
+            Ok(workflow) => workflow,
+            Err(err)     => { return Err(Error::WorkflowParseError{ id: id.into(), err }); },
+        },
+        None => { return Err(Error::NoWorkflow{ id: id.into() }); },
+    };
+    let mut timings: Vec<TaskTiming> = match serde_json::from_str(&reply.timings) {
+        Ok(timings) => timings,
+        Err(err)    => { return Err(Error::TimingsParseError{ id: id.into(), err }); },
+    };
+    timings.sort_by_key(|t| t.start);
+    Ok((workflow, timings))
+}
+
+/// Parses a what-if scenario given on the command-line.
+///
+/// # Arguments
+/// - `raw`: The scenario to parse, as `<location>=<factor>`.
+///
+/// # Returns
+/// The location and the factor by which it would be faster.
+///
+/// # Errors
+/// This function errors if the scenario is not of that form or the factor is not a positive number.
+fn parse_speedup(raw: String) -> Result<(Location, f64), Error> {
+    let (location, factor): (&str, &str) = match raw.split_once('=') {
+        Some(pair) => pair,
+        None       => { return Err(Error::IllegalSpeedup{ raw }); },
+    };
+    match factor.trim().parse::<f64>() {
+        Ok(factor) if factor > 0.0 && factor.is_finite() => Ok((location.trim().into(), factor)),
+        _                                                 => Err(Error::IllegalSpeedup{ raw }),
+    }
+}
+
+/// Prints a (what-if) schedule compared to the observed one.
+///
+/// # Arguments
+/// - `scenario`: A description of the scenario.
+/// - `schedule`: The estimated Schedule in this scenario.
+/// - `observed`: The observed makespan, in milliseconds.
+/// - `timings`: The TaskTimings the schedule refers to.
+fn print_scenario(scenario: &str, schedule: &Schedule, observed: i64, timings: &[TaskTiming]) {
+    let speedup: f64 = if schedule.makespan > 0 { observed as f64 / schedule.makespan as f64 } else { 1.0 };
+    let critical: Vec<&str> = schedule.critical.iter().map(|i| timings[*i].name.as_str()).collect();
+    println!("  {:<40} {:>10} ({:.2}x)  critical: {}", scenario, format_duration(Duration::from_millis(schedule.makespan as u64)), speedup, critical.join(" -> "));
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Lists the approvals that workflows in the remote instance are waiting for.
+///
+/// # Arguments
+/// - `endpoint`: The `brane-drv` endpoint to connect to.
+///
+/// # Errors
+/// This function errors if we failed to connect to the driver or if it failed to list the approvals.
+pub async fn approvals(endpoint: impl AsRef<str>) -> Result<(), Error> {
+    let endpoint: &str = endpoint.as_ref();
+
+    let mut client: DriverServiceClient<Channel> = connect(endpoint).await?;
+    let approvals: Vec<Approval> = match client.list_approvals(ListApprovalsRequest{}).await {
+        Ok(reply) => reply.into_inner().approvals,
+        Err(err)  => { return Err(Error::ListApprovalsError{ address: endpoint.into(), err }); },
+    };
+
+    // Prepare display table.
+    let format = FormatBuilder::new()
+        .column_separator('\0')
+        .borders('\0')
+        .padding(1, 1)
+        .build();
+    let mut table = Table::new();
+    table.set_format(format);
+    table.add_row(row!["ID", "SESSION", "REQUESTED", "MESSAGE"]);
+    for approval in approvals {
+        table.add_row(row![approval.id, approval.uuid, approval.requested, approval.message]);
+    }
+    table.printstd();
+
+    Ok(())
+}
+
+/// Approves a pending approval in the remote instance, resuming the workflow waiting for it.
+///
+/// # Arguments
+/// - `endpoint`: The `brane-drv` endpoint to connect to.
+/// - `id`: The identifier of the approval to grant.
+///
+/// # Errors
+/// This function errors if we failed to connect to the driver or if the driver refused the decision (e.g., because we are not one of its approvers).
+#[inline]
+pub async fn approve(endpoint: impl AsRef<str>, id: String) -> Result<(), Error> { decide(endpoint, id, true, None).await }
+
+/// Rejects a pending approval in the remote instance, which makes the workflow waiting for it fail.
+///
+/// # Arguments
+/// - `endpoint`: The `brane-drv` endpoint to connect to.
+/// - `id`: The identifier of the approval to reject.
+/// - `reason`: An optional reason for the rejection that is reported to the workflow and ends up in the audit log.
+///
+/// # Errors
+/// This function errors if we failed to connect to the driver or if the driver refused the decision (e.g., because we are not one of its approvers).
+#[inline]
+pub async fn reject(endpoint: impl AsRef<str>, id: String, reason: Option<String>) -> Result<(), Error> { decide(endpoint, id, false, reason).await }
+
+/// Sends an event to a session in the remote instance, resuming the workflow waiting for it (or buffering it until the workflow does).
+///
+/// # Arguments
+/// - `endpoint`: The `brane-drv` endpoint to connect to.
+/// - `id`: The identifier of the session to send the event to.
+/// - `name`: The name of the event.
+/// - `payload`: The payload that the waiting workflow receives.
+///
+/// # Errors
+/// This function errors if we failed to connect to the driver or if the driver refused the event (e.g., because the session does not exist).
+pub async fn event(endpoint: impl AsRef<str>, id: String, name: String, payload: String) -> Result<(), Error> {
+    let endpoint: &str = endpoint.as_ref();
+
+    let mut client: DriverServiceClient<Channel> = connect(endpoint).await?;
+    if let Err(err) = client.send_event(SendEventRequest{ uuid: id.clone(), name: name.clone(), payload }).await {
+        return Err(Error::SendEventError{ address: endpoint.into(), id, name, err });
+    }
+
+    println!("Successfully sent event {} to session {}", style(name).bold().cyan(), style(id).bold().cyan());
+    Ok(())
+}
+
+/// Analyzes the critical path of the last run in a session on the remote instance, and estimates how long it would have taken in a few what-if scenarios.
+///
+/// # Arguments
+/// - `endpoint`: The `brane-drv` endpoint to connect to.
+/// - `id`: The identifier of the session to analyze.
+/// - `faster`: Additional what-if scenarios to estimate, as `<location>=<factor>` (e.g., `site1=2` if `site1` would be twice as fast).
+///
+/// # Errors
+/// This function errors if a scenario is illegal, if we failed to connect to the driver or if it failed to return the timings of the run.
+pub async fn analyze(endpoint: impl AsRef<str>, id: String, faster: Vec<String>) -> Result<(), Error> {
+    let endpoint: &str = endpoint.as_ref();
+
+    // Parse the scenarios first, so we fail early on typos
+    let faster: Vec<(Location, f64)> = faster.into_iter().map(parse_speedup).collect::<Result<_, _>>()?;
+
+    // Get the timings of the run
+    let (workflow, timings): (Workflow, Vec<TaskTiming>) = get_timings(endpoint, &id).await?;
+    if timings.is_empty() {
+        println!("Run {} did not complete any tasks (yet)", style(&id).bold().cyan());
+        return Ok(());
+    }
+
+    // Show the observed critical path
+    let observed: Schedule = critical_path(&timings);
+    println!("Run {} took {} over {} task(s)", style(&id).bold().cyan(), format_duration(Duration::from_millis(observed.makespan as u64)), timings.len());
+    println!();
+    println!("Critical path:");
+    let format = FormatBuilder::new()
+        .column_separator('\0')
+        .borders('\0')
+        .padding(1, 1)
+        .build();
+    let mut table = Table::new();
+    table.set_format(format);
+    table.add_row(row!["TASK", "LOCATION", "DURATION", "SHARE"]);
+    for i in &observed.critical {
+        let t: &TaskTiming = &timings[*i];
+        let share: f64 = if observed.makespan > 0 { 100.0 * (t.end - t.start) as f64 / observed.makespan as f64 } else { 0.0 };
+        table.add_row(row![t.name, t.location, format_duration(Duration::from_millis((t.end - t.start) as u64)), format!("{:.1}%", share)]);
+    }
+    table.printstd();
+    println!();
+
+    // Show the what-if scenarios: unlimited parallelism, every location that was used twice as fast and whatever the user asked for
+    println!("What-if scenarios:");
+    print_scenario("Unlimited parallelism", &with_parallelism(&workflow, &timings), observed.makespan, &timings);
+    let mut locations: Vec<&Location> = timings.iter().map(|t| &t.location).collect();
+    locations.sort();
+    locations.dedup();
+    for location in locations {
+        print_scenario(&format!("'{}' 2x faster", location), &with_faster_locations(&timings, &HashMap::from([ (location.clone(), 2.0) ])), observed.makespan, &timings);
+    }
+    for (location, factor) in faster {
+        print_scenario(&format!("'{}' {}x faster", location, factor), &with_faster_locations(&timings, &HashMap::from([ (location, factor) ])), observed.makespan, &timings);
+    }
+
+    Ok(())
+}
+
+/// Shows the resources consumed by the tasks of the last run in a session on the remote instance, so that users can right-size the requirements of their packages.
+///
+/// # Arguments
+/// - `endpoint`: The `brane-drv` endpoint to connect to.
+/// - `id`: The identifier of the session to show the cost of.
+///
+/// # Errors
+/// This function errors if we failed to connect to the driver or if it failed to return the timings of the run.
+pub async fn cost(endpoint: impl AsRef<str>, id: String) -> Result<(), Error> {
+    let endpoint: &str = endpoint.as_ref();
+
+    // Get the tasks of the run
+    let (_, timings): (Workflow, Vec<TaskTiming>) = get_timings(endpoint, &id).await?;
+    if timings.is_empty() {
+        println!("Run {} did not complete any tasks (yet)", style(&id).bold().cyan());
+        return Ok(());
+    }
+
+    // Show a row per task call, with what it consumed (if the worker measured it)
+    let format = FormatBuilder::new()
+        .column_separator('\0')
+        .borders('\0')
+        .padding(1, 1)
+        .build();
+    let mut table = Table::new();
+    table.set_format(format);
+    table.add_row(row!["TASK", "LOCATION", "DURATION", "CPU TIME", "AVG CORES", "PEAK MEMORY", "READ", "WRITTEN"]);
+    let mut total: ResourceUsage = ResourceUsage::default();
+    let mut unmeasured: usize = 0;
+    for t in &timings {
+        match &t.usage {
+            Some(usage) => {
+                let cores: f64 = if t.end > t.start { 1_000.0 * usage.cpu_seconds / (t.end - t.start) as f64 } else { 0.0 };
+                table.add_row(row![t.name, t.location, format_duration(Duration::from_millis((t.end - t.start) as u64)), format!("{:.2}s", usage.cpu_seconds), format!("{:.2}", cores), DecimalBytes(usage.peak_memory), DecimalBytes(usage.io_read), DecimalBytes(usage.io_write)]);
+                total.merge(usage);
+            },
+            None => {
+                table.add_row(row![t.name, t.location, format_duration(Duration::from_millis((t.end - t.start) as u64)), "-", "-", "-", "-", "-"]);
+                unmeasured += 1;
+            },
+        }
+    }
+    let duration: i64 = timings.iter().map(|t| t.end - t.start).sum();
+    table.add_row(row![style("<total>").bold(), "", format_duration(Duration::from_millis(duration as u64)), format!("{:.2}s", total.cpu_seconds), "", DecimalBytes(total.peak_memory), DecimalBytes(total.io_read), DecimalBytes(total.io_write)]);
+    table.printstd();
+    if unmeasured > 0 {
+        println!();
+        println!("The resource usage of {} task(s) was not measured by their worker.", unmeasured);
+    }
+
+    Ok(())
+}
+
+/// Lists the intermediate results produced by the tasks of the last run in a session, together with where they may be downloaded.
+///
+/// Note that whether a result can still be downloaded depends on the policy of the location that produced it and on how long that location keeps results around.
+///
+/// # Arguments
+/// - `endpoint`: The `brane-drv` endpoint to connect to.
+/// - `id`: The identifier of the session to list the results of.
+///
+/// # Errors
+/// This function errors if we failed to read the login file, to connect to the driver or if it failed to return the timings of the run.
+pub async fn results(endpoint: impl AsRef<str>, id: String) -> Result<(), Error> {
+    let endpoint: &str = endpoint.as_ref();
+
+    // Get the tasks of the run
+    let (workflow, timings): (Workflow, Vec<TaskTiming>) = get_timings(endpoint, &id).await?;
+    if timings.is_empty() {
+        println!("Run {} did not complete any tasks (yet)", style(&id).bold().cyan());
+        return Ok(());
+    }
+
+    // Resolve where the registries of the locations live, so we can tell where to download the results
+    let config: RegistryConfig = match get_registry_file() {
+        Ok(config) => config,
+        Err(err)   => { return Err(Error::RegistryFileError{ err }); },
+    };
+    let infra_addr: String = format!("{}/infra/registries", config.url);
+    let registries: HashMap<String, String> = match ApiClient::new(&config.url).list_registries().await {
+        Ok(registries) => registries,
+        Err(err)       => { return Err(Error::RegistriesError{ address: infra_addr, err }); },
+    };
+
+    // Show a row per task, with the result it produced (if any)
+    let format = FormatBuilder::new()
+        .column_separator('\0')
+        .borders('\0')
+        .padding(1, 1)
+        .build();
+    let mut table = Table::new();
+    table.set_format(format);
+    table.add_row(row!["TASK", "LOCATION", "FINISHED", "RESULT", "DOWNLOAD"]);
+    for t in &timings {
+        let edge: Option<&Edge> = if t.pc.0 == usize::MAX { workflow.graph.get(t.pc.1) } else { workflow.funcs.get(&t.pc.0).and_then(|edge| edge.get(t.pc.1)) };
+        let result: Option<&String> = match edge {
+            Some(Edge::Node{ result, .. }) => result.as_ref(),
+            _                              => None,
+        };
+        let finished: String = match Utc.timestamp_millis_opt(t.end).single() {
+            Some(end) => end.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S").to_string(),
+            None      => "<unknown>".into(),
+        };
+        let download: String = match (result, registries.get(t.location.as_str())) {
+            (Some(result), Some(registry)) => format!("{}/results/download/{}", registry, result),
+            _                              => "-".into(),
+        };
+        table.add_row(row![t.name, t.location, finished, result.map(|r| r.as_str()).unwrap_or("-"), download]);
+    }
+    table.printstd();
+    println!();
+    println!("Note that downloading a result is subject to the policy of its location, and that locations only keep results for a limited time.");
+
+    Ok(())
+}
+
+/// Attaches to a (detached) run on the remote instance, printing all of its output so far and following it until it completes.
+///
+/// # Arguments
+/// - `certs_dir`: The directory with certificates proving our identity.
+/// - `proxy_addr`: The address to proxy any data transfers through if they occur.
+/// - `endpoint`: The `brane-drv` endpoint to connect to.
+/// - `id`: The identifier of the session in which the run was submitted.
+/// - `render`: Determines how much of the returned value we print.
+///
+/// # Errors
+/// This function errors if we failed to connect to the driver, if the session has no run to attach to or if processing its result failed.
+pub async fn attach(certs_dir: impl AsRef<Path>, proxy_addr: Option<String>, endpoint: impl AsRef<str>, id: AppId, render: RenderOptions) -> Result<(), Error> {
+    let endpoint: &str = endpoint.as_ref();
+
+    // Connect to the session and follow its last run
+    let mut state: InstanceVmState = match initialize_instance_vm(endpoint, Some(id.clone()), ParserOptions::new(Language::BraneScript)).await {
+        Ok(state) => state,
+        Err(err)  => { return Err(Error::AttachError{ id: id.into(), err }); },
+    };
+    state.group_output = render.group_output;
+    let res: FullValue = match attach_instance_vm(endpoint, &mut state).await {
+        Ok(res)  => res,
+        Err(err) => { return Err(Error::AttachError{ id: id.into(), err }); },
+    };
+
+    // Process its result as if we had run it ourselves
+    if let Err(err) = process_instance_result(certs_dir, &proxy_addr, res, &render).await { return Err(Error::AttachError{ id: id.into(), err }); }
+    Ok(())
+}
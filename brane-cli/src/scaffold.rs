@@ -0,0 +1,279 @@
+//  SCAFFOLD.rs
+//    by agent
+//
+//  Created:
+//    16 Oct 2026, 09:16:41
+//  Last edited:
+//    16 Oct 2026, 09:16:41
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements the `new` subcommand, which scaffolds a new package with
+//!   an example function, a test fixture and a `.gitignore`, ready to be
+//!   built with `brane build`.
+//
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use console::style;
+
+use specifications::package::PackageKind;
+
+pub use crate::errors::ScaffoldError as Error;
+use crate::utils::assert_valid_bakery_name;
+
+
+/***** TEMPLATES *****/
+/// The container file of a new code package. `<NAME>`, `<EXEC>` and `<DEPENDENCIES>` are replaced when scaffolding.
+const ECU_CONTAINER: &str = r#"# CONTAINER.yml
+#
+# Defines the <NAME> package. Build it with:
+#   brane build container.yml
+# and test it with:
+#   brane test <NAME> --args tests/hello.json
+#
+
+# Define the file metadata
+name: <NAME>
+version: 1.0.0
+kind: ecu
+
+# Define the files that are part of the package (relative to this file)
+files:
+- <EXEC>
+
+# Define the file to call whenever a function is called
+entrypoint:
+  kind: task
+  exec: <EXEC>
+<DEPENDENCIES>
+# Define the functions in this package
+actions:
+  hello:
+    command:
+      # The arguments to pass to the entrypoint (i.e., sys.argv in Python or $@ in bash)
+      args:
+      - hello
+    input:
+    # Inputs are passed as JSON in environment variables named after them (in uppercase), i.e., 'NAME'
+    - name: name
+      type: string
+    output:
+    # Outputs are read from the YAML the entrypoint writes to stdout, i.e., under the 'output' key
+    - name: output
+      type: string
+"#;
+
+/// The entrypoint of a new code package written in Python.
+const ECU_PYTHON: &str = r#"#!/usr/bin/env python3
+
+# CODE.py
+#
+# Implements the functions of the <NAME> package.
+#
+
+import json
+import os
+import sys
+
+import yaml
+
+
+def hello(name: str) -> str:
+    """
+        Greets the given name.
+    """
+
+    return f"Hello, {name}!"
+
+
+# The entrypoint of the script
+if __name__ == "__main__":
+    # The function to call is given as the first argument (see 'command' in container.yml)
+    if len(sys.argv) != 2 or sys.argv[1] != "hello":
+        print(f"Usage: {sys.argv[0]} hello", file=sys.stderr)
+        exit(1)
+
+    # Arguments are given as JSON in environment variables, and outputs are returned as YAML on stdout
+    name = json.loads(os.environ["NAME"])
+    print(yaml.dump({ "output": hello(name) }))
+"#;
+
+/// The entrypoint of a new code package written in bash.
+const ECU_BASH: &str = r#"#!/bin/bash
+
+# CODE.sh
+#
+# Implements the functions of the <NAME> package.
+#
+
+# The function to call is given as the first argument (see 'command' in container.yml)
+if [[ "$#" -ne 1 || "$1" != "hello" ]]; then
+    echo "Usage: $0 hello" >&2
+    exit 1
+fi
+
+# Arguments are given as JSON in environment variables, so strip the quotes of the string
+name="${NAME#\"}"
+name="${name%\"}"
+
+# Outputs are returned as YAML on stdout
+echo "output: \"Hello, ${name}!\""
+"#;
+
+/// The document of a new OpenAPI package. `<NAME>` is replaced when scaffolding.
+const OAS_DOCUMENT: &str = r#"# <NAME>.yml
+#
+# Defines the <NAME> package as an OpenAPI document (see https://spec.openapis.org/oas/v3.0.0).
+# Build it with:
+#   brane build <NAME>.yml
+# and test it with:
+#   brane test <NAME> --args tests/hello.json
+#
+
+# The title and version become the name and version of the package
+openapi: 3.0.0
+info:
+  title: <NAME>
+  version: 1.0.0
+
+# Define the server(s) to perform the API calls to
+servers:
+- url: https://httpbin.org
+
+# Define the paths and HTTP methods that are functions
+paths:
+  '/anything/{name}':
+    get:
+      # The operation ID becomes the function name
+      operationId: hello
+      parameters:
+      - name: name
+        in: path
+        required: true
+        schema:
+          type: string
+      # The response becomes the output of the function
+      responses:
+        '200':
+          description: The request as received by the server
+          content:
+            application/json:
+              schema:
+                type: object
+                required:
+                - url
+                properties:
+                  url:
+                    type: string
+"#;
+
+/// The test fixture of a new package (see `brane test --args`).
+const FIXTURE: &str = r#"{
+  "function": "hello",
+  "args": {
+    "name": "world"
+  }
+}
+"#;
+
+/// The `.gitignore` of a new package.
+const GITIGNORE: &str = "# Python\n__pycache__/\n*.pyc\n.venv/\n\n# Editors\n.vscode/\n.idea/\n*.swp\n";
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Writes a file of a new package.
+///
+/// # Arguments
+/// - `dir`: The directory of the package.
+/// - `name`: The name of the file, relative to `dir`.
+/// - `contents`: The contents to write.
+/// - `executable`: Whether to make the file executable (on Unix).
+///
+/// # Errors
+/// This function errors if we failed to write the file or its parent directory.
+fn write_file(dir: &Path, name: &str, contents: &str, executable: bool) -> Result<(), Error> {
+    let path: PathBuf = dir.join(name);
+    if let Some(parent) = path.parent() {
+        if !parent.exists() {
+            if let Err(err) = fs::create_dir_all(parent) { return Err(Error::DirCreateError{ path: parent.into(), err }); }
+        }
+    }
+    if let Err(err) = fs::write(&path, contents) { return Err(Error::FileWriteError{ path, err }); }
+
+    #[cfg(unix)]
+    if executable {
+        use std::os::unix::fs::PermissionsExt as _;
+        if let Err(err) = fs::set_permissions(&path, fs::Permissions::from_mode(0o755)) { return Err(Error::FilePermissionsError{ path, err }); }
+    }
+    #[cfg(not(unix))]
+    let _ = executable;
+
+    println!("  Created {}", style(name).bold());
+    Ok(())
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Scaffolds a new package with an example function `hello`, a test fixture for it and a `.gitignore`.
+///
+/// # Arguments
+/// - `kind`: The kind of package to scaffold. Only code (`ecu`) and OpenAPI (`oas`) packages are supported.
+/// - `name`: The name of the new package.
+/// - `path`: The directory to scaffold the package in. Defaults to `./<name>`.
+/// - `language`: The language of the entrypoint of a code package (`python` or `bash`).
+///
+/// # Errors
+/// This function errors if the kind, name or language are not supported, if the directory already exists and is not empty, or if we failed to write the files.
+pub fn handle(kind: PackageKind, name: String, path: Option<PathBuf>, language: String) -> Result<(), Error> {
+    if !matches!(kind, PackageKind::Ecu | PackageKind::Oas) { return Err(Error::UnsupportedKind{ kind }); }
+    if let Err(err) = assert_valid_bakery_name(&name) { return Err(Error::IllegalName{ name, err }); }
+    let (exec, entrypoint, dependencies): (&str, &str, &str) = match language.to_lowercase().as_str() {
+        "python" | "py" => ("code.py", ECU_PYTHON, "\n# Define the dependencies (as Ubuntu packages)\ndependencies:\n- python3\n- python3-yaml\n"),
+        "bash" | "sh"   => ("code.sh", ECU_BASH, ""),
+        _               => { return Err(Error::UnknownLanguage{ raw: language }); },
+    };
+
+    // Make sure we don't overwrite anything
+    let dir: PathBuf = path.unwrap_or_else(|| PathBuf::from(&name));
+    if dir.exists() {
+        match fs::read_dir(&dir) {
+            Ok(mut entries) => if entries.next().is_some() { return Err(Error::DirNotEmpty{ path: dir }); },
+            Err(err)        => { return Err(Error::DirReadError{ path: dir, err }); },
+        }
+    } else if let Err(err) = fs::create_dir_all(&dir) {
+        return Err(Error::DirCreateError{ path: dir, err });
+    }
+
+    // Write the files for the kind of package
+    println!("Scaffolding {} {} in '{}'...", kind.pretty(), style(&name).bold().cyan(), dir.display());
+    let build_file: String = match kind {
+        PackageKind::Ecu => {
+            write_file(&dir, "container.yml", &ECU_CONTAINER.replace("<NAME>", &name).replace("<EXEC>", exec).replace("<DEPENDENCIES>", dependencies), false)?;
+            write_file(&dir, exec, &entrypoint.replace("<NAME>", &name), true)?;
+            "container.yml".into()
+        },
+        PackageKind::Oas => {
+            let file: String = format!("{}.yml", name);
+            write_file(&dir, &file, &OAS_DOCUMENT.replace("<NAME>", &name), false)?;
+            file
+        },
+        _ => { unreachable!(); },
+    };
+    write_file(&dir, "tests/hello.json", FIXTURE, false)?;
+    write_file(&dir, ".gitignore", GITIGNORE, false)?;
+
+    // Tell the user what's next
+    println!();
+    println!("Build the package with 'brane build {}',", dir.join(&build_file).display());
+    println!("then test it with 'brane test {} --args {}'.", name, dir.join("tests").join("hello.json").display());
+    Ok(())
+}
@@ -0,0 +1,204 @@
+//  SCRIPT.rs
+//    by agent
+//
+//  Created:
+//    16 Oct 2026, 11:29:08
+//  Last edited:
+//    16 Oct 2026, 11:29:08
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements the `script` subcommand(s), which work on the
+//!   BraneScript (or Bakery) code of a workflow itself. Most notably,
+//!   this runs the unit tests (`#[test]` functions) of a workflow on a
+//!   dummy VM that mocks the outputs of its tasks.
+//
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use console::style;
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+use brane_ast::{ParserOptions, Workflow};
+use brane_ast::state::CompileState;
+use brane_dsl::Language;
+use brane_dsl::ast::{Program, Stmt};
+use brane_exe::FullValue;
+use brane_exe::dummy::{DummyPlanner, DummyPlugin, DummyVm};
+use brane_exe::vm::Vm as _;
+use specifications::data::DataIndex;
+use specifications::package::PackageIndex;
+
+pub use crate::errors::ScriptError as Error;
+use crate::run::compile;
+use crate::workflow::{local_indices, read_source, resolve};
+
+
+/***** AUXILLARY *****/
+/// Defines the mocked outputs of tasks with which the unit tests of a workflow are run.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct MockFixture {
+    /// The value returned by every mocked task, by function name. Tasks that are not in here return nothing.
+    #[serde(default)]
+    pub outputs : HashMap<String, FullValue>,
+}
+
+impl MockFixture {
+    /// Constructor for the MockFixture that loads it from the given (YAML) file.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the fixture file to load.
+    ///
+    /// # Returns
+    /// A new MockFixture with the outputs in the file.
+    ///
+    /// # Errors
+    /// This function errors if we failed to open or parse the file.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path: &Path = path.as_ref();
+        let handle: File = match File::open(path) {
+            Ok(handle) => handle,
+            Err(err)   => { return Err(Error::FixtureOpenError{ path: path.into(), err }); },
+        };
+        match serde_yaml::from_reader(handle) {
+            Ok(fixture) => Ok(fixture),
+            Err(err)    => Err(Error::FixtureParseError{ path: path.into(), err }),
+        }
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Runs a single unit test of a workflow on a fresh DummyVm.
+///
+/// The whole workflow is run first, such that the test may use its global variables, after which the test function itself is called.
+///
+/// # Arguments
+/// - `options`: The ParserOptions that describe how to parse the workflow.
+/// - `what`: A description of the workflow's source (e.g., the filename or `<stdin>`).
+/// - `source`: The source text of the workflow.
+/// - `pindex`: The PackageIndex to resolve package imports with.
+/// - `dindex`: The DataIndex to resolve data instantiations with.
+/// - `fixture`: The MockFixture with the outputs of the tasks called by the workflow.
+/// - `name`: The name of the test function to call.
+///
+/// # Returns
+/// The text written to stdout by the workflow and the test, and, if the test failed, the reason why.
+async fn run_test(options: &ParserOptions, what: &str, source: &str, pindex: &PackageIndex, dindex: &DataIndex, fixture: &MockFixture, name: &str) -> (String, Option<String>) {
+    let mut state : CompileState         = CompileState::new();
+    let mut seen  : String               = String::new();
+    let vm        : Arc<RwLock<DummyVm>> = Arc::new(RwLock::new(DummyVm::with_outputs(fixture.outputs.clone())));
+
+    // Run the workflow itself, then call the test function
+    let mut result: FullValue = FullValue::Void;
+    for snippet in [ source.to_string(), format!("{}();", name) ] {
+        let workflow: Workflow = match compile(&mut state, &mut seen, pindex, dindex, options, what, &snippet) {
+            Ok(workflow) => DummyPlanner::plan(workflow),
+            Err(err)     => { return (vm.read().unwrap().take_stdout(), Some(err.to_string())); },
+        };
+        result = match DummyVm::run::<DummyPlugin>(vm.clone(), workflow).await {
+            Ok(result) => result,
+            Err(err)   => { return (vm.read().unwrap().take_stdout(), Some(err.to_string())); },
+        };
+    }
+    debug!("Test '{}' returned {:?}", name, result);
+
+    // The test passes unless it returned false
+    let stdout: String = vm.read().unwrap().take_stdout();
+    match result {
+        FullValue::Boolean(false) => (stdout, Some("the test returned false".into())),
+        _                         => (stdout, None),
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Runs the unit tests of a workflow, i.e., its top-level functions annotated with `#[test]`.
+///
+/// Every test runs on a fresh dummy VM that does not run any containers, but instead returns the outputs in the given fixture for every task. A test fails if it errors or returns `false`.
+///
+/// # Arguments
+/// - `language`: The language with which to compile the file.
+/// - `file`: The file with the workflow to test. Can also be '-', in which case it is read from stdin instead.
+/// - `fixture`: The (YAML) file with the mocked task outputs, if any.
+///
+/// # Returns
+/// Nothing, but does print the result of every test to stdout.
+///
+/// # Errors
+/// This function errors if we failed to read or resolve the workflow, failed to load the fixture, or if any of the tests failed.
+pub async fn test(language: Language, file: PathBuf, fixture: Option<PathBuf>) -> Result<(), Error> {
+    let options: ParserOptions = ParserOptions::new(language);
+
+    // Read the workflow and the mocks
+    let (what, source): (Cow<str>, String) = match read_source(&file) {
+        Ok(res)  => res,
+        Err(err) => { return Err(Error::WorkflowError{ err }); },
+    };
+    let fixture: MockFixture = match fixture {
+        Some(path) => MockFixture::from_path(path)?,
+        None       => MockFixture::default(),
+    };
+    let (pindex, dindex): (PackageIndex, DataIndex) = match local_indices() {
+        Ok(indices) => indices,
+        Err(err)    => { return Err(Error::WorkflowError{ err }); },
+    };
+
+    // Discover the tests
+    let program: Program = match resolve(&options, &what, &source) {
+        Ok(program) => program,
+        Err(err)    => { return Err(Error::WorkflowError{ err }); },
+    };
+    let tests: Vec<(&str, usize)> = program.block.stmts.iter().filter_map(|s| match s {
+        Stmt::FuncDef{ ident, params, test: true, .. } => Some((ident.value.as_str(), params.len())),
+        _                                              => None,
+    }).collect();
+    if tests.is_empty() {
+        println!("{} defines no tests.", style(&what).bold());
+        return Ok(());
+    }
+
+    // Run them one-by-one
+    println!("Running {} test{} in {}", tests.len(), if tests.len() == 1 { "" } else { "s" }, style(&what).bold());
+    let mut failures: Vec<(&str, String, String)> = vec![];
+    for &(name, nparams) in &tests {
+        let (stdout, failure): (String, Option<String>) = if nparams > 0 {
+            (String::new(), Some(format!("test functions may not take arguments, but it takes {}", nparams)))
+        } else {
+            run_test(&options, &what, &source, &pindex, &dindex, &fixture, name).await
+        };
+        match failure {
+            Some(reason) => {
+                println!("test {} ... {}", name, style("FAILED").bold().red());
+                failures.push((name, reason, stdout));
+            },
+            None => { println!("test {} ... {}", name, style("ok").bold().green()); },
+        }
+    }
+
+    // Report on the failed ones
+    for (name, reason, stdout) in &failures {
+        println!();
+        println!("---- {} ----", style(name).bold());
+        if !stdout.is_empty() { print!("{}", stdout); }
+        println!("{}", reason);
+    }
+    println!();
+    println!("Test result: {}. {} passed; {} failed", if failures.is_empty() { style("ok").bold().green() } else { style("FAILED").bold().red() }, tests.len() - failures.len(), failures.len());
+    if !failures.is_empty() { return Err(Error::TestsFailed{ failed: failures.len(), total: tests.len() }); }
+
+    // Done
+    Ok(())
+}
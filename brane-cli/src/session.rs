@@ -0,0 +1,109 @@
+//  SESSION.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 10:28:41
+//  Last edited:
+//    16 Oct 2026, 12:51:53
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements the `session` subcommand(s), which find and terminate
+//!   (dangling) sessions on a remote instance.
+// 
+
+use console::style;
+use prettytable::format::FormatBuilder;
+use prettytable::Table;
+use tonic::transport::Channel;
+
+use brane_tsk::grpc::{DriverServiceClient, KillSessionRequest, ListSessionsRequest, SessionInfo};
+use brane_tsk::spec::AppId;
+
+pub use crate::errors::SessionError as Error;
+use crate::instance::client_tls;
+
+
+/***** HELPER FUNCTIONS *****/
+/// Connects to the given remote driver.
+/// 
+/// # Arguments
+/// - `endpoint`: The `brane-drv` endpoint to connect to.
+/// 
+/// # Returns
+/// A new DriverServiceClient connected to the endpoint.
+/// 
+/// # Errors
+/// This function errors if we failed to connect.
+async fn connect(endpoint: &str) -> Result<DriverServiceClient<Channel>, Error> {
+    debug!("Connecting to driver '{}'...", endpoint);
+    match client_tls().connect_driver(endpoint).await {
+        Ok(client) => Ok(client),
+        Err(err)   => Err(Error::ClientConnectError{ address: endpoint.into(), err }),
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Lists the sessions that are known to the remote instance.
+/// 
+/// # Arguments
+/// - `endpoint`: The `brane-drv` endpoint to connect to.
+/// 
+/// # Errors
+/// This function errors if we failed to connect to the driver or if it failed to list the sessions.
+pub async fn list(endpoint: impl AsRef<str>) -> Result<(), Error> {
+    let endpoint: &str = endpoint.as_ref();
+
+    let mut client: DriverServiceClient<Channel> = connect(endpoint).await?;
+    let sessions: Vec<SessionInfo> = match client.list_sessions(ListSessionsRequest{}).await {
+        Ok(reply) => reply.into_inner().sessions,
+        Err(err)  => { return Err(Error::ListSessionsError{ address: endpoint.into(), err }); },
+    };
+
+    // Prepare display table.
+    let format = FormatBuilder::new()
+        .column_separator('\0')
+        .borders('\0')
+        .padding(1, 1)
+        .build();
+    let mut table = Table::new();
+    table.set_format(format);
+    table.add_row(row!["ID", "CREATED", "STATUS"]);
+    for session in sessions {
+        let status: &str = match (session.running, session.attached) {
+            (true, true)  => "running",
+            (true, false) => "running (detached)",
+            (false, _)    => "idle",
+        };
+        table.add_row(row![session.uuid, session.created, status]);
+    }
+    table.printstd();
+
+    Ok(())
+}
+
+/// Kills a session on the remote instance, aborting the workflow it is running (if any).
+/// 
+/// # Arguments
+/// - `endpoint`: The `brane-drv` endpoint to connect to.
+/// - `id`: The identifier of the session to kill.
+/// 
+/// # Errors
+/// This function errors if we failed to connect to the driver or if it failed to kill the session.
+pub async fn kill(endpoint: impl AsRef<str>, id: AppId) -> Result<(), Error> {
+    let endpoint: &str = endpoint.as_ref();
+
+    let mut client: DriverServiceClient<Channel> = connect(endpoint).await?;
+    let aborted: bool = match client.kill_session(KillSessionRequest{ uuid: id.to_string() }).await {
+        Ok(reply) => reply.into_inner().aborted,
+        Err(err)  => { return Err(Error::KillSessionError{ address: endpoint.into(), id: id.into(), err }); },
+    };
+
+    println!("Successfully killed session {}{}", style(id).bold().cyan(), if aborted { " (aborted its running workflow)" } else { "" });
+    Ok(())
+}
@@ -19,6 +19,8 @@ use brane_exe::spec::CustomGlobalState;
 use specifications::data::DataIndex;
 use specifications::package::PackageIndex;
 
+use crate::render::BranchOutput;
+
 
 /***** LIBRARY *****/
 /// The global state for the OfflineVm.
@@ -35,6 +37,9 @@ pub struct GlobalState {
     pub pindex : Arc<PackageIndex>,
     /// The data index that contains info about each package.
     pub dindex : Arc<DataIndex>,
+
+    /// Prints the text written by the workflow, grouping it per parallel branch if asked to.
+    pub output : BranchOutput,
 }
 impl CustomGlobalState for GlobalState {}
 
@@ -0,0 +1,705 @@
+//  SWEEP.rs
+//    by agent
+//
+//  Created:
+//    16 Oct 2026, 09:07:30
+//  Last edited:
+//    16 Oct 2026, 12:51:53
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements the `sweep` subcommand(s), which run a workflow once for
+//!   every combination of values in a parameter matrix and track the
+//!   resulting runs as a single sweep group.
+//
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use chrono::{DateTime, Local, Utc};
+use console::style;
+use futures::future::join_all;
+use prettytable::format::FormatBuilder;
+use prettytable::Table;
+use serde::{Deserialize, Serialize};
+use tonic::Code;
+use tonic::transport::Channel;
+use uuid::Uuid;
+
+use brane_ast::{ParserOptions, Workflow};
+use brane_ast::ast::{Edge, EdgeInstr};
+use brane_dsl::Language;
+use brane_exe::FullValue;
+use brane_tsk::grpc::{CreateSessionRequest, DriverServiceClient, ExecuteRequest, ReconnectRequest};
+use brane_tsk::spec::AppId;
+
+pub use crate::errors::SweepError as Error;
+use crate::instance::client_tls;
+use crate::render::{render_value, RenderOptions};
+use crate::run::{compile, initialize_instance_vm, InstanceVmState};
+use crate::utils::ensure_config_dir;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use brane_ast::{compile_program, CompileResult};
+    use brane_shr::utilities::{create_data_index, create_package_index};
+
+    use super::*;
+
+
+    /// Compiles the given source with the placeholders for the given parameters in front of it, like `handle()` does.
+    fn compile_sweep(params: &BTreeMap<String, SweepValue>, source: &str) -> Workflow {
+        let snippet: String = params.iter().map(|(name, value)| value.placeholder(name)).collect::<String>() + source;
+        match compile_program(snippet.as_bytes(), &create_package_index(), &create_data_index(), &ParserOptions::bscript()) {
+            CompileResult::Workflow(workflow, _) => workflow,
+            CompileResult::Eof(err)              => { err.prettyprint("<sweep>", &snippet); panic!("Failed to compile to workflow (see output above)"); },
+            CompileResult::Err(errs)             => { for e in errs { e.prettyprint("<sweep>", &snippet); } panic!("Failed to compile to workflow (see output above)"); },
+            _                                    => { unreachable!(); },
+        }
+    }
+
+    /// Returns the instruction that pushes the value assigned to the given parameter first.
+    fn literal_of(workflow: &Workflow, name: &str) -> EdgeInstr {
+        let def: usize = workflow.table.vars.enumerate().find(|(_, v)| v.name == name).unwrap().0;
+        let mut graph: Vec<Edge> = (*workflow.graph).clone();
+        placeholder_of(&mut graph, def).unwrap().clone()
+    }
+
+
+    #[test]
+    fn test_instantiate() {
+        let params: BTreeMap<String, SweepValue> = BTreeMap::from([
+            ("epochs".into(), SweepValue::Integer(20)),
+            ("rate".into(), SweepValue::Real(0.5)),
+            ("name".into(), SweepValue::String("run \"one\"".into())),
+            ("verbose".into(), SweepValue::Boolean(true)),
+        ]);
+        let workflow: Workflow = compile_sweep(&params, "epochs := epochs + 1;\nprintln(name);\nprintln(rate);\nprintln(verbose);\n");
+
+        // Every placeholder is replaced, with the value of the right type
+        let instance: Workflow = instantiate(&workflow, &params).unwrap();
+        assert!(matches!(literal_of(&instance, "epochs"), EdgeInstr::Integer{ value: 20 }));
+        assert!(matches!(literal_of(&instance, "rate"), EdgeInstr::Real{ value } if value == 0.5));
+        assert!(matches!(literal_of(&instance, "name"), EdgeInstr::String{ ref value } if value == "run \"one\""));
+        assert!(matches!(literal_of(&instance, "verbose"), EdgeInstr::Boolean{ value: true }));
+        // ...while the shared workflow is left alone
+        assert!(matches!(literal_of(&workflow, "epochs"), EdgeInstr::Integer{ value: 0 }));
+
+        // Instantiating the same workflow again does not see the values of the previous run
+        let params: BTreeMap<String, SweepValue> = BTreeMap::from([ ("epochs".into(), SweepValue::Integer(-3)) ]);
+        let instance: Workflow = instantiate(&workflow, &params).unwrap();
+        assert!(matches!(literal_of(&instance, "epochs"), EdgeInstr::Integer{ value: -3 }));
+        assert!(matches!(literal_of(&instance, "rate"), EdgeInstr::Real{ value } if value == 0.0));
+    }
+
+    #[test]
+    fn test_instantiate_missing() {
+        let params: BTreeMap<String, SweepValue> = BTreeMap::from([ ("epochs".into(), SweepValue::Integer(20)) ]);
+        let workflow: Workflow = compile_sweep(&params, "println(epochs);\n");
+
+        // Parameters that are not in the workflow
+        let params: BTreeMap<String, SweepValue> = BTreeMap::from([ ("rate".into(), SweepValue::Real(0.5)) ]);
+        assert!(matches!(instantiate(&workflow, &params), Err(Error::ParameterNotFound{ ref name }) if name == "rate"));
+
+        // Variables that are not defined by a placeholder are never overwritten
+        let workflow: Workflow = compile_sweep(&BTreeMap::new(), "let epochs := 5;\nprintln(epochs);\n");
+        let params: BTreeMap<String, SweepValue> = BTreeMap::from([ ("epochs".into(), SweepValue::Integer(20)) ]);
+        assert!(matches!(instantiate(&workflow, &params), Err(Error::ParameterNotFound{ ref name }) if name == "epochs"));
+        let workflow: Workflow = compile_sweep(&BTreeMap::new(), "let epochs := 1 + 1;\nprintln(epochs);\n");
+        assert!(matches!(instantiate(&workflow, &params), Err(Error::ParameterNotFound{ ref name }) if name == "epochs"));
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Returns the directory with the sweep groups, creating it if it does not exist yet.
+///
+/// # Errors
+/// This function errors if we failed to find or create the Brane configuration directory or the sweeps directory in it.
+fn ensure_sweeps_dir() -> Result<PathBuf, Error> {
+    let dir: PathBuf = match ensure_config_dir(true) {
+        Ok(dir)  => dir.join("sweeps"),
+        Err(err) => { return Err(Error::ConfigDirError{ err }); },
+    };
+    if !dir.exists() {
+        if let Err(err) = fs::create_dir_all(&dir) { return Err(Error::SweepsDirCreateError{ path: dir, err }); }
+    }
+    Ok(dir)
+}
+
+/// Reads the parameter matrix from the given file.
+///
+/// # Arguments
+/// - `path`: The path of the YAML file with the matrix, which maps every parameter to the list of values to sweep it over.
+///
+/// # Returns
+/// The matrix, as a map of parameter names to their values.
+///
+/// # Errors
+/// This function errors if the file could not be read or parsed, if a parameter has no values or if its values are not all of the same type.
+fn read_matrix(path: &Path) -> Result<BTreeMap<String, Vec<SweepValue>>, Error> {
+    let handle: File = match File::open(path) {
+        Ok(handle) => handle,
+        Err(err)   => { return Err(Error::MatrixOpenError{ path: path.into(), err }); },
+    };
+    let matrix: BTreeMap<String, Vec<SweepValue>> = match serde_yaml::from_reader(handle) {
+        Ok(matrix) => matrix,
+        Err(err)   => { return Err(Error::MatrixParseError{ path: path.into(), err }); },
+    };
+
+    // Every parameter should be swept over at least one value, and they all need to fit in the same variable
+    if matrix.is_empty() { return Err(Error::EmptyMatrix{ path: path.into() }); }
+    for (name, values) in &matrix {
+        let first: &SweepValue = match values.first() {
+            Some(first) => first,
+            None        => { return Err(Error::EmptyParameter{ name: name.clone() }); },
+        };
+        if let Some(other) = values.iter().find(|v| v.kind() != first.kind()) {
+            return Err(Error::MixedParameter{ name: name.clone(), first: first.kind(), other: other.kind() });
+        }
+    }
+    Ok(matrix)
+}
+
+/// Expands the given matrix into every combination of its values.
+///
+/// # Arguments
+/// - `matrix`: The matrix to expand.
+///
+/// # Returns
+/// The parameters of every run, with the last parameter varying fastest.
+fn expand(matrix: &BTreeMap<String, Vec<SweepValue>>) -> Vec<BTreeMap<String, SweepValue>> {
+    let mut runs: Vec<BTreeMap<String, SweepValue>> = vec![ BTreeMap::new() ];
+    for (name, values) in matrix {
+        runs = runs.into_iter().flat_map(|params| {
+            values.iter().map(move |value| {
+                let mut params: BTreeMap<String, SweepValue> = params.clone();
+                params.insert(name.clone(), value.clone());
+                params
+            })
+        }).collect();
+    }
+    runs
+}
+
+/// Finds the literal that the placeholder definition of the given variable pushes.
+///
+/// # Arguments
+/// - `graph`: The graph of the workflow to search.
+/// - `def`: The definition of the variable.
+///
+/// # Returns
+/// The instruction pushing the placeholder's value, or `None` if the variable is first assigned something that is not a placeholder.
+fn placeholder_of(graph: &mut [Edge], def: usize) -> Option<&mut EdgeInstr> {
+    // The first assignment of the variable is the placeholder definition, since it comes before anything else in the source
+    let (instrs, i): (&mut Vec<EdgeInstr>, usize) = graph.iter_mut().find_map(|edge| match edge {
+        Edge::Linear{ instrs, .. } => {
+            let i: usize = instrs.iter().position(|instr| matches!(instr, EdgeInstr::VarSet{ def: d } if *d == def))?;
+            Some((instrs, i))
+        },
+        _ => None,
+    })?;
+
+    // It should assign exactly the default value that `SweepValue::placeholder()` writes
+    let literal: &mut EdgeInstr = instrs.get_mut(i.checked_sub(1)?)?;
+    let is_placeholder: bool = match literal {
+        EdgeInstr::Boolean{ value } => !*value,
+        EdgeInstr::Integer{ value } => *value == 0,
+        EdgeInstr::Real{ value }    => *value == 0.0,
+        EdgeInstr::String{ value }  => value.is_empty(),
+        _                           => false,
+    };
+    if is_placeholder { Some(literal) } else { None }
+}
+
+/// Instantiates the given, shared workflow with the parameters of a single run.
+///
+/// The workflow is compiled with a placeholder definition for every parameter in front of it, which we replace with the run's value.
+///
+/// # Arguments
+/// - `workflow`: The shared Workflow to instantiate.
+/// - `params`: The values of the parameters for this run.
+///
+/// # Returns
+/// A new Workflow that defines the parameters with the given values.
+///
+/// # Errors
+/// This function errors if we could not find the placeholder definition of a parameter in the workflow.
+fn instantiate(workflow: &Workflow, params: &BTreeMap<String, SweepValue>) -> Result<Workflow, Error> {
+    let mut graph: Vec<Edge> = (*workflow.graph).clone();
+    for (name, value) in params {
+        // Find the global variable of the parameter
+        let def: usize = match workflow.table.vars.enumerate().find(|(_, v)| &v.name == name) {
+            Some((def, _)) => def,
+            None           => { return Err(Error::ParameterNotFound{ name: name.clone() }); },
+        };
+
+        // Replace the literal assigned to it by the placeholder
+        match placeholder_of(&mut graph, def) {
+            Some(literal) => { *literal = value.to_instr(); },
+            None          => { return Err(Error::ParameterNotFound{ name: name.clone() }); },
+        }
+    }
+
+    Ok(Workflow {
+        table : workflow.table.clone(),
+        graph : Arc::new(graph),
+        funcs : workflow.funcs.clone(),
+
+        requires : workflow.requires.clone(),
+
+        params    : workflow.params.clone(),
+        residency : workflow.residency.clone(),
+    })
+}
+
+/// Follows a single run of a sweep until it completes, without printing its output.
+///
+/// # Arguments
+/// - `endpoint`: The `brane-drv` endpoint that runs the sweep (used for debugging only).
+/// - `client`: The client to the driver.
+/// - `session`: The session in which the run was submitted.
+///
+/// # Returns
+/// The outcome of the run, or None if we lost the connection before it completed (so we may try again later).
+async fn follow(endpoint: &str, mut client: DriverServiceClient<Channel>, session: &str) -> Option<RunOutcome> {
+    debug!("Following sweep run in session '{}'...", session);
+    let request = ReconnectRequest { uuid: session.into(), after: 0 };
+    let mut stream = match client.reconnect(request).await {
+        Ok(response)                                => response.into_inner(),
+        Err(err) if err.code() == Code::Unavailable => { return None; },
+        Err(err)                                    => { return Some(RunOutcome::Failed{ error: format!("could not attach to run on '{}': {}", endpoint, err.message()) }); },
+    };
+
+    // Collect the result and any errors
+    let mut result : FullValue   = FullValue::Void;
+    let mut errors : Vec<String> = vec![];
+    loop {
+        match stream.message().await {
+            Ok(Some(reply)) => {
+                if reply.heartbeat { continue; }
+                if let Some(stderr) = reply.stderr { errors.push(stderr.trim().into()); }
+                if let Some(value) = reply.value {
+                    match serde_json::from_str(&value) {
+                        Ok(value) => { result = value; },
+                        Err(err)  => { errors.push(format!("could not parse result '{}': {}", value, err)); },
+                    }
+                }
+                if reply.close { break; }
+            },
+            Ok(None) => {
+                // Without a close, the stream ended because the connection dropped (unless the run failed)
+                if errors.is_empty() { return None; }
+                break;
+            },
+            Err(status) => {
+                debug!("Sweep run in session '{}' returned status: {}", session, status);
+                errors.push(status.message().into());
+                break;
+            },
+        }
+    }
+
+    // Done
+    if errors.is_empty() {
+        Some(RunOutcome::Succeeded{ result })
+    } else {
+        Some(RunOutcome::Failed{ error: errors.join("; ") })
+    }
+}
+
+/// Follows the runs of the given sweep group that have not completed yet, updating their outcomes in the group.
+///
+/// # Arguments
+/// - `group`: The SweepGroup to follow.
+///
+/// # Errors
+/// This function errors if we failed to connect to the driver.
+async fn follow_all(group: &mut SweepGroup) -> Result<(), Error> {
+    let pending: usize = group.runs.iter().filter(|r| r.outcome.is_none()).count();
+    if pending == 0 { return Ok(()); }
+    println!("Waiting for {} run(s) of sweep {} to complete...", pending, style(&group.id).bold().cyan());
+
+    // Connect once, then follow all runs concurrently
+    debug!("Connecting to driver '{}'...", group.endpoint);
+    let client: DriverServiceClient<Channel> = match client_tls().connect_driver(group.endpoint.clone()).await {
+        Ok(client) => client,
+        Err(err)   => { return Err(Error::ClientConnectError{ address: group.endpoint.clone(), err }); },
+    };
+    let endpoint: &str = &group.endpoint;
+    let outcomes: Vec<Option<RunOutcome>> = join_all(group.runs.iter().map(|run| {
+        let client: DriverServiceClient<Channel> = client.clone();
+        async move {
+            if run.outcome.is_some() { return run.outcome.clone(); }
+            follow(endpoint, client, &run.session).await
+        }
+    })).await;
+
+    for (run, outcome) in group.runs.iter_mut().zip(outcomes) { run.outcome = outcome; }
+    Ok(())
+}
+
+/// Prints the report of the given sweep group.
+///
+/// # Arguments
+/// - `group`: The SweepGroup to report on.
+/// - `render`: Determines how much of the results we print.
+fn print_report(group: &SweepGroup, render: &RenderOptions) {
+    // Prepare display table.
+    let format = FormatBuilder::new()
+        .column_separator('\0')
+        .borders('\0')
+        .padding(1, 1)
+        .build();
+    let mut table = Table::new();
+    table.set_format(format);
+    table.add_row(row!["RUN", "SESSION", "PARAMETERS", "STATUS", "RESULT"]);
+    for (i, run) in group.runs.iter().enumerate() {
+        let params: String = run.params.iter().map(|(name, value)| format!("{}={}", name, value)).collect::<Vec<String>>().join(", ");
+        let (status, result): (String, String) = match &run.outcome {
+            Some(RunOutcome::Succeeded{ result }) => (style("succeeded").green().to_string(), render_value(result, render)),
+            Some(RunOutcome::Failed{ error })     => (style("failed").red().to_string(), error.clone()),
+            None                                  => (style("unknown").yellow().to_string(), String::new()),
+        };
+        table.add_row(row![i, run.session, params, status, result]);
+    }
+    table.printstd();
+
+    // Summarize
+    let succeeded : usize = group.runs.iter().filter(|r| matches!(r.outcome, Some(RunOutcome::Succeeded{ .. }))).count();
+    let failed    : usize = group.runs.iter().filter(|r| matches!(r.outcome, Some(RunOutcome::Failed{ .. }))).count();
+    let unknown   : usize = group.runs.len() - succeeded - failed;
+    println!();
+    println!("Sweep {} over '{}': {} run(s), {} succeeded, {} failed{}",
+        style(&group.id).bold().cyan(),
+        group.file.display(),
+        group.runs.len(),
+        style(succeeded).green(),
+        style(failed).red(),
+        if unknown > 0 { format!(", {} unknown (run 'brane sweep report {}' to try again)", style(unknown).yellow(), group.id) } else { String::new() },
+    );
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Defines a single value that a parameter is swept over.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum SweepValue {
+    /// A boolean value.
+    Boolean(bool),
+    /// An integral value.
+    Integer(i64),
+    /// A real value.
+    Real(f64),
+    /// A string value.
+    String(String),
+}
+
+impl SweepValue {
+    /// Returns the name of the type of this value.
+    #[inline]
+    pub fn kind(&self) -> &'static str {
+        use SweepValue::*;
+        match self {
+            Boolean(_) => "boolean",
+            Integer(_) => "integer",
+            Real(_)    => "real",
+            String(_)  => "string",
+        }
+    }
+
+    /// Returns BraneScript that defines a placeholder variable of the same type as this value.
+    ///
+    /// # Arguments
+    /// - `name`: The name of the variable to define.
+    ///
+    /// # Returns
+    /// A `let`-statement (without newline, so that the line numbers of the workflow stay the same).
+    pub fn placeholder(&self, name: &str) -> String {
+        use SweepValue::*;
+        match self {
+            Boolean(_) => format!("let {} := false; ", name),
+            Integer(_) => format!("let {} := 0; ", name),
+            Real(_)    => format!("let {} := 0.0; ", name),
+            String(_)  => format!("let {} := \"\"; ", name),
+        }
+    }
+
+    /// Returns the instruction that pushes this value onto the stack.
+    pub fn to_instr(&self) -> EdgeInstr {
+        match self {
+            SweepValue::Boolean(value) => EdgeInstr::Boolean{ value: *value },
+            SweepValue::Integer(value) => EdgeInstr::Integer{ value: *value },
+            SweepValue::Real(value)    => EdgeInstr::Real{ value: *value },
+            SweepValue::String(value)  => EdgeInstr::String{ value: value.clone() },
+        }
+    }
+}
+
+impl Display for SweepValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use SweepValue::*;
+        match self {
+            Boolean(value) => write!(f, "{}", value),
+            Integer(value) => write!(f, "{}", value),
+            Real(value)    => write!(f, "{}", value),
+            String(value)  => write!(f, "{:?}", value),
+        }
+    }
+}
+
+
+
+/// Defines how a run of a sweep ended.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum RunOutcome {
+    /// The run completed and returned the given value.
+    Succeeded {
+        /// The value that the workflow returned.
+        result : FullValue,
+    },
+    /// The run failed.
+    Failed {
+        /// The error(s) with which it failed.
+        error : String,
+    },
+}
+
+
+
+/// Defines a single run in a sweep group.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SweepRun {
+    /// The session in which the run was submitted.
+    pub session : String,
+    /// The values of the parameters in this run.
+    pub params  : BTreeMap<String, SweepValue>,
+    /// How the run ended, if we know already.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outcome : Option<RunOutcome>,
+}
+
+
+
+/// Defines a group of runs that were submitted as a single sweep.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SweepGroup {
+    /// The identifier of the sweep.
+    pub id       : String,
+    /// The workflow file that was swept.
+    pub file     : PathBuf,
+    /// The `brane-drv` endpoint that runs the sweep.
+    pub endpoint : String,
+    /// When the sweep was submitted.
+    pub created  : DateTime<Utc>,
+    /// The runs in the sweep.
+    pub runs     : Vec<SweepRun>,
+}
+
+impl SweepGroup {
+    /// Loads the SweepGroup with the given identifier.
+    ///
+    /// # Arguments
+    /// - `id`: The identifier of the sweep.
+    ///
+    /// # Returns
+    /// The SweepGroup as it was last stored.
+    ///
+    /// # Errors
+    /// This function errors if there is no sweep with that identifier or its file could not be read or parsed.
+    pub fn load(id: &str) -> Result<Self, Error> {
+        let path: PathBuf = ensure_sweeps_dir()?.join(format!("{}.yml", id));
+        if !path.exists() { return Err(Error::UnknownSweep{ id: id.into() }); }
+        Self::from_path(path)
+    }
+
+    /// Loads a SweepGroup from the given path.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the file to load.
+    ///
+    /// # Returns
+    /// A new SweepGroup with the contents of the file.
+    ///
+    /// # Errors
+    /// This function errors if the file could not be read or parsed.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path: &Path = path.as_ref();
+        let handle: File = match File::open(path) {
+            Ok(handle) => handle,
+            Err(err)   => { return Err(Error::FileOpenError{ path: path.into(), err }); },
+        };
+        match serde_yaml::from_reader(handle) {
+            Ok(group) => Ok(group),
+            Err(err)  => Err(Error::FileParseError{ path: path.into(), err }),
+        }
+    }
+
+    /// Stores the SweepGroup in the sweeps directory, under its identifier.
+    ///
+    /// # Errors
+    /// This function errors if the file could not be created or written to.
+    pub fn store(&self) -> Result<(), Error> {
+        let path: PathBuf = ensure_sweeps_dir()?.join(format!("{}.yml", self.id));
+        let handle: File = match File::create(&path) {
+            Ok(handle) => handle,
+            Err(err)   => { return Err(Error::FileCreateError{ path, err }); },
+        };
+        match serde_yaml::to_writer(handle, self) {
+            Ok(_)    => Ok(()),
+            Err(err) => Err(Error::FileWriteError{ path, err }),
+        }
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Runs the given workflow once for every combination of parameters in the given matrix, as a single sweep group.
+///
+/// The workflow is compiled only once. Every run gets its own session on the driver and is submitted detached, so it keeps running if we stop following it.
+///
+/// # Arguments
+/// - `endpoint`: The `brane-drv` endpoint to run the sweep on.
+/// - `file`: The BraneScript file with the workflow. It can use the parameters as if they were global variables.
+/// - `matrix`: The YAML file that maps every parameter to the values to sweep it over.
+/// - `detach`: Whether to return immediately after submitting the runs, instead of waiting for them and printing the report.
+/// - `render`: Determines how much of the results we print.
+///
+/// # Errors
+/// This function errors if we failed to read the workflow or matrix, compile the workflow or submit any of the runs.
+pub async fn run(endpoint: impl AsRef<str>, file: PathBuf, matrix: PathBuf, detach: bool, render: RenderOptions) -> Result<(), Error> {
+    let endpoint: &str = endpoint.as_ref();
+
+    // Read the inputs
+    let source: String = match fs::read_to_string(&file) {
+        Ok(source) => source,
+        Err(err)   => { return Err(Error::FileReadError{ path: file, err }); },
+    };
+    let matrix: BTreeMap<String, Vec<SweepValue>> = read_matrix(&matrix)?;
+    let runs: Vec<BTreeMap<String, SweepValue>> = expand(&matrix);
+
+    // Compile the workflow once, with placeholders for the parameters in front of it
+    let mut state: InstanceVmState = match initialize_instance_vm(endpoint, None, ParserOptions::new(Language::BraneScript)).await {
+        Ok(state) => state,
+        Err(err)  => { return Err(Error::InitializeError{ err }); },
+    };
+    let snippet: String = matrix.iter().map(|(name, values)| values[0].placeholder(name)).collect::<String>() + &source;
+    let workflow: Workflow = match compile(&mut state.state, &mut state.source, &state.pindex, &state.dindex, &state.options, file.to_string_lossy(), snippet) {
+        Ok(workflow) => workflow,
+        Err(err)     => { return Err(Error::CompileError{ err }); },
+    };
+
+    // Submit every run in a session of its own
+    let mut group: SweepGroup = SweepGroup {
+        id       : Uuid::new_v4().to_simple().to_string()[..8].into(),
+        file     : file.clone(),
+        endpoint : endpoint.into(),
+        created  : Utc::now(),
+        runs     : Vec::with_capacity(runs.len()),
+    };
+    for params in runs {
+        let sworkflow: String = match serde_json::to_string(&instantiate(&workflow, &params)?) {
+            Ok(sworkflow) => sworkflow,
+            Err(err)      => { return Err(Error::WorkflowSerializeError{ err }); },
+        };
+
+        let session: String = match state.client.create_session(CreateSessionRequest {}).await {
+            Ok(reply) => reply.into_inner().uuid,
+            Err(err)  => { return Err(Error::SessionCreateError{ address: endpoint.into(), err }); },
+        };
+        if let Err(err) = AppId::from_str(&session) { return Err(Error::AppIdError{ address: endpoint.into(), raw: session, err }); }
+        let request = ExecuteRequest { uuid: session.clone(), input: sworkflow, detach: true, max_steps: None };
+        if let Err(err) = state.client.execute(request).await { return Err(Error::ExecuteError{ address: endpoint.into(), session, err }); }
+        debug!("Submitted sweep run with {:?} in session '{}'", params, session);
+
+        group.runs.push(SweepRun{ session, params, outcome: None });
+        // Store after every run, so that the ones we did submit are not lost if a later one fails
+        group.store()?;
+    }
+    println!("Submitted {} run(s) of '{}' as sweep {}", group.runs.len(), file.display(), style(&group.id).bold().cyan());
+
+    // Wait for them, unless told not to
+    if detach {
+        println!("Run 'brane sweep report {}' to see their statuses and results.", group.id);
+        return Ok(());
+    }
+    follow_all(&mut group).await?;
+    group.store()?;
+    println!();
+    print_report(&group, &render);
+    Ok(())
+}
+
+/// Prints the statuses and results of the runs in the given sweep group, waiting for the ones that did not complete yet.
+///
+/// # Arguments
+/// - `id`: The identifier of the sweep.
+/// - `render`: Determines how much of the results we print.
+///
+/// # Errors
+/// This function errors if the sweep is unknown or we failed to connect to its driver.
+pub async fn report(id: String, render: RenderOptions) -> Result<(), Error> {
+    let mut group: SweepGroup = SweepGroup::load(&id)?;
+    follow_all(&mut group).await?;
+    group.store()?;
+    print_report(&group, &render);
+    Ok(())
+}
+
+/// Lists the sweep groups that were submitted from this machine.
+///
+/// # Errors
+/// This function errors if we failed to read the sweeps directory or any of the sweeps in it.
+pub fn list() -> Result<(), Error> {
+    let dir: PathBuf = ensure_sweeps_dir()?;
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(err)    => { return Err(Error::SweepsDirReadError{ path: dir, err }); },
+    };
+    let mut groups: Vec<SweepGroup> = vec![];
+    for entry in entries {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(err)  => { return Err(Error::SweepsDirReadError{ path: dir, err }); },
+        };
+        if entry.path().extension().map(|e| e == "yml").unwrap_or(false) { groups.push(SweepGroup::from_path(entry.path())?); }
+    }
+    groups.sort_by_key(|g| g.created);
+
+    // Prepare display table.
+    let format = FormatBuilder::new()
+        .column_separator('\0')
+        .borders('\0')
+        .padding(1, 1)
+        .build();
+    let mut table = Table::new();
+    table.set_format(format);
+    table.add_row(row!["ID", "FILE", "ENDPOINT", "CREATED", "RUNS", "SUCCEEDED", "FAILED"]);
+    for group in &groups {
+        let succeeded : usize = group.runs.iter().filter(|r| matches!(r.outcome, Some(RunOutcome::Succeeded{ .. }))).count();
+        let failed    : usize = group.runs.iter().filter(|r| matches!(r.outcome, Some(RunOutcome::Failed{ .. }))).count();
+        let created   : String = DateTime::<Local>::from(group.created).format("%Y-%m-%d %H:%M:%S").to_string();
+        table.add_row(row![group.id, group.file.display(), group.endpoint, created, group.runs.len(), succeeded, failed]);
+    }
+    table.printstd();
+
+    Ok(())
+}
@@ -15,13 +15,14 @@
 use std::collections::HashMap;
 use std::fmt::{Debug, Display};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 use console::{style, Term};
 use dialoguer::theme::ColorfulTheme;
 use dialoguer::Confirm;
 use dialoguer::{Input as Prompt, Select};
+use serde::{Deserialize, Serialize};
 
 use brane_ast::{DataType, ParserOptions};
 use brane_ast::spec::BuiltinClasses;
@@ -33,7 +34,8 @@ use specifications::package::PackageInfo;
 use specifications::version::Version;
 
 use crate::errors::TestError;
-use crate::utils::{ensure_datasets_dir, ensure_package_dir};
+use crate::utils::{ensure_datasets_dir, ensure_package_dir, get_fixtures_dir};
+use crate::render::{render_table, render_value, RenderOptions};
 use crate::run::{initialize_offline_vm, run_offline_vm, OfflineVmState};
 
 
@@ -42,6 +44,17 @@ type Map<T> = std::collections::HashMap<String, T>;
 
 
 
+/// Defines a set of arguments for a package function, which can be saved to test the function again later.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Fixture {
+    /// The name of the function to test.
+    pub function : String,
+    /// The values of its arguments, mapped by name.
+    pub args     : Map<FullValue>,
+}
+
+
+
 
 
 /***** HELPER FUNCTIONS *****/
@@ -111,6 +124,118 @@ fn prompt_for_input(name: impl AsRef<str>, version: &Version, functions: &Map<Fu
     Ok((function_name.clone(), args))
 }
 
+/// Checks whether the given fixture can be used to test the given functions.
+/// 
+/// Optional arguments that the fixture omits are set to `null`, and integers given for reals are converted.
+/// 
+/// # Arguments
+/// - `name`: The name of the package (used for debugging).
+/// - `functions`: The list of functions that may be tested (and are thus defined in the package).
+/// - `fixture`: The Fixture to check.
+/// 
+/// # Returns
+/// The name of the function in the fixture and a map of values for the function to run with.
+/// 
+/// # Errors
+/// This function errors if the fixture's function does not exist, or if any of its arguments are missing or have the wrong type.
+fn check_fixture(name: impl AsRef<str>, functions: &Map<Function>, fixture: Fixture) -> Result<(String, Map<FullValue>), TestError> {
+    let Fixture{ function: function_name, mut args } = fixture;
+
+    // Resolve the function
+    let function: &Function = match functions.get(&function_name) {
+        Some(function) => function,
+        None           => { return Err(TestError::UnknownFunction{ package: name.as_ref().into(), function: function_name }); },
+    };
+
+    // Check the arguments
+    for p in &function.parameters {
+        let expected: DataType = DataType::from(&p.data_type);
+        match args.get_mut(&p.name) {
+            Some(value) => {
+                // JSON does not distinguish integral reals from integers, so be lenient there
+                if let (FullValue::Integer(i), DataType::Real) = (&*value, &expected) { *value = FullValue::Real(*i as f64); }
+
+                let got: DataType = value.data_type();
+                if !got.allowed_by(&expected) { return Err(TestError::ArgumentTypeError{ function: function_name, name: p.name.clone(), got, expected }); }
+            },
+            None => {
+                if !p.optional.unwrap_or(false) { return Err(TestError::MissingArgument{ function: function_name, name: p.name.clone() }); }
+                args.insert(p.name.clone(), FullValue::Null);
+            },
+        }
+    }
+    debug!("Arguments: {:#?}", args);
+
+    // Done
+    Ok((function_name, args))
+}
+
+/// Resolves the path of the fixture with the given name.
+/// 
+/// # Arguments
+/// - `package`: The name of the package to which the fixture belongs.
+/// - `name`: The name of the fixture.
+/// 
+/// # Returns
+/// The path of the fixture's file.
+/// 
+/// # Errors
+/// This function errors if the name is not a valid filename or if we failed to get the fixtures directory.
+fn fixture_path(package: &str, name: &str) -> Result<PathBuf, TestError> {
+    if name.is_empty() || name.starts_with('.') || name.contains('/') || name.contains('\\') { return Err(TestError::IllegalFixtureName{ name: name.into() }); }
+    match get_fixtures_dir(package) {
+        Ok(dir)  => Ok(dir.join(format!("{}.json", name))),
+        Err(err) => Err(TestError::FixturesDirError{ err }),
+    }
+}
+
+/// Reads a fixture from the given file.
+/// 
+/// # Arguments
+/// - `path`: The path of the file to read.
+/// 
+/// # Returns
+/// The parsed Fixture.
+/// 
+/// # Errors
+/// This function errors if we failed to read or parse the file.
+fn read_fixture(path: &Path) -> Result<Fixture, TestError> {
+    let raw: String = match fs::read_to_string(path) {
+        Ok(raw)  => raw,
+        Err(err) => { return Err(TestError::FixtureReadError{ path: path.into(), err }); },
+    };
+    match serde_json::from_str(&raw) {
+        Ok(fixture) => Ok(fixture),
+        Err(err)    => Err(TestError::FixtureParseError{ path: path.into(), err }),
+    }
+}
+
+/// Saves the given fixture under the given name, overwriting any existing fixture with that name.
+/// 
+/// # Arguments
+/// - `package`: The name of the package to which the fixture belongs.
+/// - `name`: The name of the fixture.
+/// - `fixture`: The Fixture to save.
+/// 
+/// # Errors
+/// This function errors if the name is not a valid filename or if we failed to write the fixture.
+fn save_fixture(package: &str, name: &str, fixture: &Fixture) -> Result<(), TestError> {
+    let path: PathBuf = fixture_path(package, name)?;
+
+    // Make sure the directory exists
+    let dir: &Path = path.parent().unwrap();
+    if let Err(err) = fs::create_dir_all(dir) { return Err(TestError::FixturesDirCreateError{ path: dir.into(), err }); }
+
+    // Write it
+    let sfixture: String = match serde_json::to_string_pretty(fixture) {
+        Ok(sfixture) => sfixture,
+        Err(err)     => { return Err(TestError::FixtureSerializeError{ err }); },
+    };
+    if let Err(err) = fs::write(&path, sfixture) { return Err(TestError::FixtureWriteError{ path, err }); }
+    println!("Saved arguments as fixture {} ('{}')", style(name).bold().cyan(), path.display());
+    Ok(())
+}
+
 /// Prompts the user to enter the value for a single function argument.
 /// 
 /// # Arguments
@@ -196,6 +321,45 @@ fn prompt_for_param(what: impl AsRef<str>, name: impl AsRef<str>, data_type: Dat
             // Done
             FullValue::Array(values)
         },
+        DataType::Map{ elem_type } => {
+            // If there is a default, we are forced to ask it beforehand.
+            if let Some(default) = default {
+                // Ensure the default has the correct value
+                if default.data_type() != (DataType::Map{ elem_type: elem_type.clone() }) { panic!("{} cannot have a value of type {} as default value", DataType::Map{ elem_type }, default.data_type()); }
+
+                // Prompt the user to use it
+                if match Confirm::new()
+                    .with_prompt(format!("{} has a default value: {}; would you like to use that?", style(name).bold().cyan(), style(format!("{}", default)).bold()))
+                    .interact() {
+                    Ok(use_default) => use_default,
+                    Err(err)        => { return Err(TestError::YesNoQueryError{ err }); },
+                } {
+                    return Ok(default);
+                }
+            }
+
+            // Add as many entries as the user likes
+            let mut values: HashMap<std::string::String, FullValue> = HashMap::with_capacity(16);
+            loop {
+                // Query the user for the key, then the value
+                let key: std::string::String = prompt(format!("{} [String] <key {}>", name, values.len()), false, None)?;
+                let res = prompt_for_param(format!("{} [{}] <value of '{}'>", name, elem_type, key), name, *elem_type.clone(), false, None, types)?;
+                values.insert(key, res);
+
+                // Ask if they want to ask more
+                if !match Confirm::new()
+                    .with_prompt("Add more entries?")
+                    .interact() {
+                    Ok(cont) => cont,
+                    Err(err) => { return Err(TestError::YesNoQueryError{ err }); },
+                } {
+                    break;
+                }
+            }
+
+            // Done
+            FullValue::Map(values)
+        },
         Class{ name: c_name } => {
             // If there is a default, we are forced to ask it beforehand.
             if let Some(default) = default {
@@ -334,6 +498,10 @@ fn write_value(value: FullValue) -> String {
             // Write them all in an instance expression
             format!("new {}{{ {} }}", name, props.into_iter().map(|(n, v)| format!("{} := {}", n, v)).collect::<Vec<String>>().join(", "))
         },
+        FullValue::Map(values) => {
+            // Write them all in a map literal
+            format!("{{ {} }}", values.into_iter().map(|(k, v)| format!("\"{}\": {}", k.replace('\\', "\\\\").replace('\"', "\\\""), write_value(v))).collect::<Vec<String>>().join(", "))
+        },
         FullValue::Data(name) => {
             // Write it as a new Data declaration
             format!("new Data{{ name := \"{}\" }}", name)
@@ -364,15 +532,32 @@ fn write_value(value: FullValue) -> String {
 /// - `name`: The name of the package to test.
 /// - `version`: The version of the package to test.
 /// - `show_result`: Whether or not to `cat` the resulting file if any.
+/// - `fixture`: If given, tests the function with the arguments in the saved fixture with this name instead of asking the user for them.
+/// - `args_file`: If given, tests the function with the arguments in this JSON file (in the same format as fixtures) instead of asking the user for them.
+/// - `save_as`: If given, saves the arguments the function is tested with as a fixture with this name.
+/// - `render`: Determines how much of the returned value we print.
 /// 
 /// # Returns
 /// Nothing, but does do a whole dance of querying the user and executing a package based on that.
 /// 
 /// # Errors
 /// This function errors if any part of that dance failed.
-pub async fn handle(name: impl Into<String>, version: Version, show_result: Option<PathBuf>) -> Result<(), TestError> {
+pub async fn handle(name: impl Into<String>, version: Version, show_result: Option<PathBuf>, fixture: Option<String>, args_file: Option<PathBuf>, save_as: Option<String>, render: RenderOptions) -> Result<(), TestError> {
     let name: String = name.into();
 
+    // Load the arguments if we're not asking for them
+    let fixture: Option<Fixture> = match (fixture, args_file) {
+        (Some(fixture), _) => {
+            let path: PathBuf = fixture_path(&name, &fixture)?;
+            if !path.exists() { return Err(TestError::FixtureNotFound{ package: name, name: fixture, path }); }
+            Some(read_fixture(&path)?)
+        },
+        (None, Some(path)) => Some(read_fixture(&path)?),
+        (None, None)       => None,
+    };
+    // Check the name we save under before asking anything, so the user doesn't lose their input
+    if let Some(save_as) = &save_as { fixture_path(&name, save_as)?; }
+
     // Read the package info of the given package
     let package_dir = match ensure_package_dir(&name, Some(&version), false) {
         Ok(dir)  => dir,
@@ -384,10 +569,16 @@ pub async fn handle(name: impl Into<String>, version: Version, show_result: Opti
     };
 
     // Run the test for this info
-    let output: FullValue = test_generic(package_info, show_result).await?;
+    let output: FullValue = test_generic(package_info, fixture, save_as.as_deref(), show_result).await?;
 
     // Print it, done
-    println!("Result: {} [{}]", style(format!("{}", output)).bold().cyan(), style(format!("{}", output.data_type())).bold());
+    match render_table(&output, &render) {
+        Some(table) => {
+            println!("Result: [{}]", style(format!("{}", output.data_type())).bold());
+            table.printstd();
+        },
+        None => println!("Result: {} [{}]", style(render_value(&output, &render)).bold().cyan(), style(format!("{}", output.data_type())).bold()),
+    }
     Ok(())
 }
 
@@ -397,23 +588,29 @@ pub async fn handle(name: impl Into<String>, version: Version, show_result: Opti
 /// 
 /// # Arguments
 /// - `info`: The PackageInfo that describes the package to test.
+/// - `fixture`: If given, the Fixture with the function and arguments to test. Otherwise, the user is asked for them.
+/// - `save_as`: If given, saves the function and arguments that are tested as a fixture with this name.
 /// - `show_result`: Whether or not to `cat` the resulting file if any.
 /// 
 /// # Returns
 /// The value of the chosen function in that package (which may be Void this time).
-pub async fn test_generic(info: PackageInfo, show_result: Option<PathBuf>) -> Result<FullValue, TestError> {
-    // Query the user what they'd like to do (we quickly convert the common Type to a ClassDef)
-    let (function, mut args) = prompt_for_input(&info.name, &info.version, &info.functions, info.types.iter().map(|(n, t)| (n.clone(), ClassDef {
-        name    : t.name.clone(),
-        package : Some(info.name.clone()),
-        version : Some(info.version.clone()),
-
-        props   : t.properties.iter().map(|p| VarDef {
-            name      : p.name.clone(),
-            data_type : DataType::from(&p.data_type),
-        }).collect(),
-        methods : vec![],
-    })).collect())?;
+pub async fn test_generic(info: PackageInfo, fixture: Option<Fixture>, save_as: Option<&str>, show_result: Option<PathBuf>) -> Result<FullValue, TestError> {
+    // Use the fixture or query the user what they'd like to do (we quickly convert the common Type to a ClassDef)
+    let (function, mut args) = match fixture {
+        Some(fixture) => check_fixture(&info.name, &info.functions, fixture)?,
+        None          => prompt_for_input(&info.name, &info.version, &info.functions, info.types.iter().map(|(n, t)| (n.clone(), ClassDef {
+            name    : t.name.clone(),
+            package : Some(info.name.clone()),
+            version : Some(info.version.clone()),
+
+            props   : t.properties.iter().map(|p| VarDef {
+                name      : p.name.clone(),
+                data_type : DataType::from(&p.data_type),
+            }).collect(),
+            methods : vec![],
+        })).collect())?,
+    };
+    if let Some(save_as) = save_as { save_fixture(&info.name, save_as, &Fixture{ function: function.clone(), args: args.clone() })?; }
 
     // Build a phony workflow with that
     let workflow: String = format!("import {}[{}]; return {}({});",
@@ -18,6 +18,7 @@ use std::fs::{self, File};
 use std::io::Read;
 use std::path::{Path, PathBuf};
 
+use specifications::data::data_id;
 use specifications::package::PackageKind;
 use specifications::registry::RegistryConfig;
 use specifications::version::Version;
@@ -344,6 +345,26 @@ pub fn ensure_history_file(create: bool) -> Result<PathBuf, UtilError> {
     Ok(history_file)
 }
 
+/// Returns the directory where the saved test fixtures of the given package live.
+/// 
+/// Does not guarantee that the directory also exists.
+/// 
+/// # Arguments
+/// - `package`: The name of the package to get the fixtures directory of.
+/// 
+/// # Returns
+/// The path of the package's fixtures directory.
+/// 
+/// # Errors
+/// This function errors if we failed to get the Brane configuration directory (see `get_config_dir()`).
+pub fn get_fixtures_dir(package: &str) -> Result<PathBuf, UtilError> {
+    // Get the config dir
+    let config_dir = get_config_dir()?;
+
+    // Add the path and return
+    Ok(config_dir.join("fixtures").join(package))
+}
+
 
 
 /// Returns the general data directory based on the user's home folder.
@@ -632,16 +653,70 @@ pub fn ensure_dataset_dir<S: AsRef<str>>(name: S, create: bool) -> Result<PathBu
     Ok(data_dir)
 }
 
+/// Gets the directory where we likely stored a specific version of a dataset.  
+/// Does not guarantee that the directory also exists; check `ensure_dataset_version_dir()` for that.
+/// 
+/// # Arguments
+/// - `name`: The name of the dataset we want to get the path of.
+/// - `version`: The version of the dataset we want to get the path of.
+/// 
+/// # Returns
+/// A PathBuf with the (absolute path to the) directory.
+/// 
+/// # Errors
+/// This function may error if we failed to get the parent datasets directory (see `get_datasets_dir()`).
+#[inline]
+pub fn get_dataset_version_dir(name: impl AsRef<str>, version: &Version) -> Result<PathBuf, UtilError> {
+    Ok(get_dataset_dir(name)?.join(version.to_string()))
+}
+
+/// Makes sure that the directory for the given version of the given dataset exists, then returns the path to it.
+/// 
+/// # Arguments
+/// - `name`: The name of the dataset we want to get/create the directory for.
+/// - `version`: The version of the dataset we want to get/create the directory for.
+/// - `create`: If set to true, creates the missing directories instead of throwing errors.
+/// 
+/// # Returns
+/// A PathBuf with the directory.
+/// 
+/// # Errors
+/// This function may error if we failed to get the parent datasets directory (see `get_datasets_dir()`) or if we failed to verify/create the dataset.
+pub fn ensure_dataset_version_dir(name: impl AsRef<str>, version: &Version, create: bool) -> Result<PathBuf, UtilError> {
+    let name: &str = name.as_ref();
+
+    // Retrieve the path for this version
+    let data_dir: PathBuf = get_dataset_version_dir(name, version)?;
+
+    // Make sure it exists
+    if !data_dir.exists() {
+        if create {
+            ensure_dataset_dir(name, create)?;
+            if let Err(err) = fs::create_dir_all(&data_dir) { return Err(UtilError::BraneDatasetDirCreateError{ name: data_id(name, version), path: data_dir, err }); }
+        } else {
+            return Err(UtilError::BraneDatasetDirNotFound{ name: data_id(name, version), path: data_dir });
+        }
+    }
+
+    // It's alright
+    Ok(data_dir)
+}
+
 
 
 /// Reads a RegistryConfig from the configuration file (`config_dir/registry.yml`).
 /// 
+/// If an instance was given for the current command (see `crate::instance::activate()`), its registry is returned instead.
+/// 
 /// # Returns
 /// The parsed RegistryConfig.
 /// 
 /// # Errors
 /// This function may error if we could not find, read or parse the config file that is the RegistryFile. If not found, this likely indicates the user hasn't logged-in yet.
 pub fn get_registry_file() -> Result<RegistryConfig, UtilError> {
+    // Use the instance given for this command instead, if any
+    if let Some(info) = crate::instance::active() { return Ok(info.registry_config()); }
+
     // Get the configuration file path
     let config_file = get_config_dir().unwrap().join("registry.yml");
 
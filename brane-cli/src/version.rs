@@ -17,11 +17,13 @@ use std::str::FromStr;
 use log::debug;
 use reqwest::{Response, StatusCode};
 
+use brane_cfg::node::FeatureFlags;
 use specifications::arch::Arch;
 use specifications::registry::RegistryConfig;
 use specifications::version::Version;
 
 use crate::errors::VersionError;
+use crate::instance::client_tls;
 use crate::utils::get_config_dir;
 
 
@@ -70,9 +72,11 @@ impl LocalVersion {
 #[derive(Debug)]
 struct RemoteVersion {
     /// The architecture as reported by the remote
-    _arch   : Arch,
+    _arch    : Arch,
     /// The version as downloaded from the remote
-    version : Version,
+    version  : Version,
+    /// The feature flags of the remote, if it reports them (older instances do not)
+    features : Option<FeatureFlags>,
 }
 
 impl RemoteVersion {
@@ -113,7 +117,7 @@ impl RemoteVersion {
         // Use reqwest for the API call
         debug!(" > Querying...");
         let mut url: String = registry.url.clone(); url.push_str("/version");
-        let response: Response = match reqwest::get(&url).await {
+        let response: Response = match client_tls().api().get(&url).send().await {
             Ok(version) => version,
             Err(err)    => { return Err(VersionError::RequestError{ url, err }); }
         };
@@ -132,11 +136,24 @@ impl RemoteVersion {
             Err(err)    => { return Err(VersionError::VersionParseError{ raw: version_body, err }); }  
         };
 
+        // Also ask for the feature flags, which is fine to fail for older instances
+        debug!(" > Querying features...");
+        let url: String = format!("{}/version/features", registry.url);
+        let features: Option<FeatureFlags> = match client_tls().api().get(&url).send().await {
+            Ok(response) if response.status() == StatusCode::OK => match response.json().await {
+                Ok(features) => Some(features),
+                Err(err)     => { debug!("Failed to parse feature flags from '{}': {}", url, err); None },
+            },
+            Ok(response) => { debug!("Request to '{}' returned status code {}; assuming remote does not report feature flags", url, response.status().as_u16()); None },
+            Err(err)     => { debug!("Failed to query feature flags at '{}': {}", url, err); None },
+        };
+
         // Done!
         debug!("Remote version number: {}", &version);
         Ok(Self {
             _arch : Arch::x86_64,
             version,
+            features,
         })
     }
 }
@@ -215,6 +232,7 @@ pub async fn handle() -> Result<(), VersionError> {
         let remote = RemoteVersion::from_registry_file(registry).await?;
         println!(" - Version      : v{}", remote.version);
         println!(" - Architecture : <TBD>");
+        if let Some(features) = remote.features { println!(" - Features     : {}", features); }
         println!();
     }
 
@@ -15,32 +15,42 @@
 // 
 
 use std::collections::HashMap;
+use std::convert::Infallible;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::Duration;
 
+use bollard::API_DEFAULT_VERSION;
 use chrono::Utc;
-use log::{debug, info};
+use log::{debug, info, warn};
 use tokio::fs as tfs;
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 
 use brane_ast::Workflow;
 use brane_ast::locations::Location;
 use brane_ast::ast::DataName;
+use brane_cfg::node::DEFAULT_MAX_SCRATCH_SIZE;
+use brane_cfg::secrets::Secret;
 use brane_exe::Vm;
 use brane_exe::errors::VmError;
-use brane_exe::spec::{RunState, TaskInfo, VmPlugin};
+use brane_exe::plugins::{MockPlugin, PluginEntry, PluginRegistry, RecorderPlugin};
+use brane_exe::spec::{ApprovalDecision, Checkpoint, RunState, TaskInfo, VmPlugin};
+use brane_exe::trace::Tracer;
 use brane_exe::value::FullValue;
 use brane_shr::debug::BlockFormatter;
+use brane_shr::disk::dir_size;
 use brane_shr::fs::copy_dir_recursively_async;
-use brane_tsk::errors::{CommitError, ExecuteError, PreprocessError, StdoutError};
+use brane_tsk::errors::{CommitError, EventError, ExecuteError, PreprocessError, StdoutError};
 use brane_tsk::spec::{LOCALHOST, Planner as _};
 use brane_tsk::tools::decode_base64;
-use brane_tsk::docker::{self, ExecuteInfo, ImageSource, Network};
-use specifications::container::{Image, VolumeBind};
-use specifications::data::{AccessKind, DataIndex, DataInfo, PreprocessKind};
+use brane_tsk::docker::{self, ExecuteInfo, ImageSource, Network, StagedInputs};
+use specifications::container::{Image, VolumeBind, VolumeBindOption};
+use specifications::data::{default_data_version, merge_residency, AccessKind, DataIndex, DataInfo, PreprocessKind};
 use specifications::package::{PackageIndex, PackageInfo};
 
 pub use crate::errors::OfflineVmError as Error;
+use crate::build_common::DevInfo;
+use crate::render::BranchOutput;
 use crate::spec::{GlobalState, LocalState};
 use crate::planner::OfflinePlanner;
 
@@ -58,6 +68,10 @@ impl VmPlugin for OfflinePlugin {
     type ExecuteError    = ExecuteError;
     type StdoutError     = StdoutError;
     type CommitError     = CommitError;
+    type EventError      = EventError;
+    type NotifyError     = StdoutError;
+    type IndexError      = Infallible;
+    type CheckpointError = Infallible;
 
 
     async fn preprocess(_global: Arc<RwLock<Self::GlobalState>>, _local: Self::LocalState, _loc: Location, name: DataName, preprocess: PreprocessKind) -> Result<AccessKind, Self::PreprocessError> {
@@ -81,9 +95,9 @@ impl VmPlugin for OfflinePlugin {
         debug!("Task generates result? {}", if info.result.is_some() { "yes" } else { "no" });
 
         // First, we query the global state to find the result directory and required indices
-        let (package_dir, results_dir, pindex): (PathBuf, PathBuf, Arc<PackageIndex>) = {
+        let (package_dir, dataset_dir, results_dir, pindex): (PathBuf, PathBuf, PathBuf, Arc<PackageIndex>) = {
             let state: RwLockReadGuard<GlobalState> = global.read().unwrap();
-            (state.package_dir.clone(), state.results_dir.clone(), state.pindex.clone())
+            (state.package_dir.clone(), state.dataset_dir.clone(), state.results_dir.clone(), state.pindex.clone())
         };
 
         // Next, we resolve the package
@@ -92,19 +106,32 @@ impl VmPlugin for OfflinePlugin {
             None        => { return Err(ExecuteError::UnknownPackage { name: info.package_name.into(), version: info.package_version.clone() }) }
         };
 
+        // Fetch any inputs that live on scientific storage systems (using the proxy from the user's environment, if any) or that are registered by URL (which we cache with the datasets)
+        let _staged: StagedInputs = docker::stage_inputs(&mut info.input, &HashMap::new(), std::env::temp_dir().join("brane-staging"), dataset_dir.join(".cache"), Some(DEFAULT_MAX_SCRATCH_SIZE)).await?;
+
         // Resolve the input arguments, generating the folders we have to bind
-        let binds  : Vec<VolumeBind> = docker::preprocess_args(&mut info.args, &info.input, info.result, None::<String>, results_dir).await?;
-        let params : String          = match serde_json::to_string(&info.args) {
+        let mut binds : Vec<VolumeBind> = docker::preprocess_args(&mut info.args, &info.input, info.result, None::<String>, results_dir).await?;
+        let params    : String          = match serde_json::to_string(&info.args) {
             Ok(params) => params,
             Err(err)   => { return Err(ExecuteError::ArgsEncodeError{ err }); },
         };
 
+        // Packages built with `brane build --dev` run with their working directory straight from the host
+        let version_dir: PathBuf = package_dir.join(info.package_name).join(info.package_version.to_string());
+        if let Some(dev) = DevInfo::from_package_dir(&version_dir) {
+            debug!("Mounting working directory '{}' of development build", dev.workdir.display());
+            match VolumeBind::new(dev.workdir, "/opt/wd", vec![ VolumeBindOption::ReadOnly ]) {
+                Ok(bind) => binds.push(bind),
+                Err(err) => { return Err(ExecuteError::VolumeBindError{ err }); },
+            }
+        }
+
         // Create an ExecuteInfo with that
         let image: Image = Image::new(info.package_name, Some(info.package_version), Some(pinfo.digest.as_ref().unwrap()));
         let einfo: ExecuteInfo = ExecuteInfo {
             name         : info.name.into(),
             image        : image.clone(),
-            image_source : ImageSource::Path(package_dir.join(info.package_name).join(info.package_version.to_string()).join("image.tar")),
+            image_source : ImageSource::Path(version_dir.join("image.tar")),
 
             command : vec![
                 "-d".into(),
@@ -120,21 +147,42 @@ impl VmPlugin for OfflinePlugin {
             ],
             binds,
             network      : Network::None,
+            aliases      : vec![],
             capabilities : info.requirements.clone(),
+            scratch      : Some(DEFAULT_MAX_SCRATCH_SIZE),
+            // There is no worker to ask locally, so the secrets come from the user's own environment instead
+            env          : info.secrets.iter().filter_map(|name| std::env::var(name).ok().map(|value| (name.clone(), Secret::from(value)))).collect(),
         };
 
-        // We can now execute the task on the local Docker daemon
+        // We can now execute the task on the local Docker daemon (killing it if it runs out of time)
         debug!("Executing task '{}'...", info.name);
-        let (code, stdout, stderr) = match docker::run_and_wait(einfo, false).await {
-            Ok(res)  => res,
-            Err(err) => { return Err(ExecuteError::DockerError{ name: info.name.into(), image, err }); }
+        let (code, stdout, stderr) = match info.timeout {
+            Some(timeout) => {
+                let name: String = match docker::launch(einfo, "/var/run/docker.sock", *API_DEFAULT_VERSION).await {
+                    Ok(name) => name,
+                    Err(err) => { return Err(ExecuteError::DockerError{ name: info.name.into(), image, err }); }
+                };
+                match tokio::time::timeout(Duration::from_secs(timeout), docker::join(&name, "/var/run/docker.sock", *API_DEFAULT_VERSION, false)).await {
+                    Ok(Ok(res))  => res,
+                    Ok(Err(err)) => { return Err(ExecuteError::DockerError{ name: info.name.into(), image, err }); },
+                    Err(_)       => {
+                        if let Err(err) = docker::kill(&name, "/var/run/docker.sock", *API_DEFAULT_VERSION, false).await { warn!("Failed to kill container '{}': {}", name, err); }
+                        return Err(ExecuteError::Timeout{ name: info.name.into(), image, timeout });
+                    },
+                }
+            },
+            None => match docker::run_and_wait(einfo, false).await {
+                Ok(res)  => res,
+                Err(err) => { return Err(ExecuteError::DockerError{ name: info.name.into(), image, err }); }
+            },
         };
         debug!("Container return code: {}", code);
         debug!("Container stdout/stderr:\n\nstdout:\n{}\n\nstderr:\n{}\n", BlockFormatter::new(&stdout), BlockFormatter::new(&stderr));
 
         // If the return code is no bueno, error and show stderr
         if code != 0 {
-            return Err(ExecuteError::ExternalCallFailed{ name: info.name.into(), image, code, stdout, stderr });
+            if let Some(limit) = docker::scratch_exhausted(&stderr) { return Err(ExecuteError::ScratchExhausted{ name: info.name.into(), image, limit }); }
+            return Err(ExecuteError::from_exit_code(info.name, image, code, stdout, stderr));
         }
 
         // Otherwise, decode the output of branelet to the value returned
@@ -150,16 +198,18 @@ impl VmPlugin for OfflinePlugin {
         Ok(value)
     }
 
+    #[inline]
+    fn is_transient(err: &Self::ExecuteError) -> bool { err.is_transient() }
+
 
 
-    async fn stdout(_global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, text: &str, newline: bool) -> Result<(), Self::StdoutError> {
+    async fn stdout(global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, branch: &[usize], text: &str, newline: bool) -> Result<(), Self::StdoutError> {
         info!("Writing '{}' to stdout (newline: {}) in an offline environment...", text, if newline { "yes" } else { "no" });
 
-        // Simply write
-        if !newline {
-            print!("{}", text);
-        } else {
-            println!("{}", text);
+        // Simply write (which may be buffered if we group the output of branches)
+        {
+            let mut state: RwLockWriteGuard<GlobalState> = global.write().unwrap();
+            state.output.write(branch, &format!("{}{}", text, if newline { "\n" } else { "" }));
         }
 
         // Done
@@ -178,7 +228,7 @@ impl VmPlugin for OfflinePlugin {
         Ok(())
     }
 
-    async fn commit(global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, _loc: &Location, name: &str, path: &Path, data_name: &str) -> Result<(), Self::CommitError> {
+    async fn commit(global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, _loc: &Location, name: &str, path: &Path, data_name: &str, residency: Option<&[Location]>) -> Result<(), Self::CommitError> {
         info!("Committing intermediate result '{}' to '{}' in an offline environment...", name, data_name);
         debug!("Physical file(s): {}", path.display());
 
@@ -199,6 +249,21 @@ impl VmPlugin for OfflinePlugin {
                     AccessKind::File { path: data_path } => {
                         // Simply copy the one directory over the other and it's updated
                         if let Err(err) = copy_dir_recursively_async(results_dir.join(path), data_path).await { return Err(CommitError::DataCopyError{ err }); }
+
+                        // The dataset now also keeps the restrictions of the data it was derived from
+                        if residency.is_some() {
+                            let mut info: DataInfo = info.clone();
+                            info.residency = merge_residency(info.residency.take(), residency);
+                            let info_path: PathBuf = data_path.with_file_name("data.yml");
+                            let sinfo: String = match serde_json::to_string_pretty(&info) {
+                                Ok(sinfo) => sinfo,
+                                Err(err)  => { return Err(CommitError::DataInfoSerializeError{ err }); },
+                            };
+                            if let Err(err) = tfs::write(&info_path, sinfo).await { return Err(CommitError::DataInfoWriteError{ path: info_path, err }); }
+                        }
+                    },
+                    AccessKind::XRootD { url } | AccessKind::GridFtp { url } | AccessKind::Url { url, .. } => {
+                        return Err(CommitError::RemoteDataError{ name: data_name.into(), url: url.clone() });
                     },
                 }
 
@@ -219,9 +284,13 @@ impl VmPlugin for OfflinePlugin {
             // Create a new DataInfo struct
             let info: DataInfo = DataInfo {
                 name        : data_name.into(),
+                version     : default_data_version(),
                 owners      : None, // TODO: Merge parent datasets??
                 description : None, // TODO: Add parents & algorithm in description??
                 created     : Utc::now(),
+                residency   : residency.map(|locs| locs.to_vec()),
+                size        : None,
+                revisions   : HashMap::new(),
 
                 access : HashMap::from([
                     ("localhost".into(), AccessKind::File{ path: dir.join("data") }),
@@ -255,6 +324,84 @@ impl VmPlugin for OfflinePlugin {
         // Done
         Ok(())
     }
+
+
+
+    async fn wait_event(_global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, name: &str) -> Result<String, Self::EventError> {
+        info!("Waiting for event '{}' in an offline environment...", name);
+
+        // Nobody can send us events locally, so ask the user for the payload instead
+        println!("Waiting for event '{}'; enter its payload to continue:", name);
+        let mut payload: String = String::new();
+        match BufReader::new(tokio::io::stdin()).read_line(&mut payload).await {
+            Ok(0)    => { return Err(EventError::SourceClosed{ name: name.into() }); },
+            Ok(_)    => {},
+            Err(err) => { return Err(EventError::StdinReadError{ name: name.into(), err }); },
+        }
+
+        // Done
+        Ok(payload.trim_end_matches(&[ '\r', '\n' ][..]).into())
+    }
+
+    async fn require_approval(_global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, message: &str) -> Result<ApprovalDecision, Self::EventError> {
+        info!("Requiring approval for '{}' in an offline environment...", message);
+
+        // The only user that can approve is the one running the workflow
+        println!("Approval required: {}", message);
+        println!("Approve? [y/N]");
+        let mut answer: String = String::new();
+        match BufReader::new(tokio::io::stdin()).read_line(&mut answer).await {
+            Ok(0)    => { return Err(EventError::SourceClosed{ name: "approval".into() }); },
+            Ok(_)    => {},
+            Err(err) => { return Err(EventError::StdinReadError{ name: "approval".into(), err }); },
+        }
+
+        // Done
+        Ok(ApprovalDecision {
+            approved : matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"),
+            user     : std::env::var("USER").unwrap_or_else(|_| "local".into()),
+            reason   : None,
+        })
+    }
+
+
+
+    async fn notify(_global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, channel: &str, message: &str) -> Result<(), Self::NotifyError> {
+        info!("Sending notification on channel '{}' in an offline environment...", channel);
+
+        // There are no notification channels locally, so we notify the user running the workflow directly
+        println!("Notification on channel '{}': {}", channel, message);
+        Ok(())
+    }
+
+    async fn indices(global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState) -> Result<(Arc<PackageIndex>, Arc<DataIndex>), Self::IndexError> {
+        info!("Querying the local indices in an offline environment...");
+        let (pindex, dindex): (Arc<PackageIndex>, Arc<DataIndex>) = {
+            let state: RwLockReadGuard<GlobalState> = global.read().unwrap();
+            (state.pindex.clone(), state.dindex.clone())
+        };
+
+        // There is no registry to tell us the sizes of local datasets, so we compute them ourselves
+        let mut dindex: DataIndex = (*dindex).clone();
+        for info in dindex.iter_mut() {
+            if let Some(AccessKind::File{ path }) = info.access.get(LOCALHOST) {
+                match dir_size(path).await {
+                    Ok(size) => { info.size = Some(size); },
+                    Err(err) => { warn!("Failed to compute the size of dataset '{}': {}", info.name, err); },
+                }
+            }
+        }
+        Ok((pindex, Arc::new(dindex)))
+    }
+
+
+
+    async fn checkpoint(_global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, checkpoint: &Checkpoint) -> Result<(), Self::CheckpointError> {
+        debug!("Not checkpointing workflow at edge {} in an offline environment", checkpoint.pc.1);
+
+        // Local runs do not survive the CLI anyway, so there is nothing to resume later
+        Ok(())
+    }
 }
 
 
@@ -262,10 +409,37 @@ impl VmPlugin for OfflinePlugin {
 
 
 /***** LIBRARY *****/
+/// The name of the plugin with which the OfflineVm runs by default.
+pub const DEFAULT_PLUGIN: &str = "offline";
+
+/// Returns the plugins with which the OfflineVm may run.
+/// 
+/// These are:
+/// - `offline`: The OfflinePlugin, which runs workflows on the local machine (the default).
+/// - `mock`: A MockPlugin, which pretends to run workflows without running any containers.
+/// - `record`: The OfflinePlugin, but logging every call it handles.
+/// 
+/// # Returns
+/// A PluginRegistry with the plugins by name.
+pub fn plugins() -> PluginRegistry<OfflineVm> {
+    PluginRegistry::new()
+        .register::<OfflinePlugin>(DEFAULT_PLUGIN)
+        .register::<MockPlugin<GlobalState, LocalState>>("mock")
+        .register::<RecorderPlugin<OfflinePlugin>>("record")
+}
+
+
+
 /// Defines a VM that has no online interaction and does everything locally.
 pub struct OfflineVm {
     /// The runtime state for the VM
-    state : RunState<GlobalState>,
+    state     : RunState<GlobalState>,
+    /// The Tracer that records the edges visited by workflows, if we're tracing them.
+    tracer    : Option<Arc<Tracer>>,
+    /// The plugin with which we run workflows.
+    plugin    : PluginEntry<Self>,
+    /// The maximum number of steps that a single run may take, if any.
+    max_steps : Option<u64>,
 }
 
 impl OfflineVm {
@@ -283,17 +457,71 @@ impl OfflineVm {
     #[inline]
     pub fn new(package_dir: impl Into<PathBuf>, dataset_dir: impl Into<PathBuf>, results_dir: impl Into<PathBuf>, package_index: Arc<PackageIndex>, data_index: Arc<DataIndex>) -> Self {
         Self {
-            state : Self::new_state(GlobalState {
+            state     : Self::new_state(GlobalState {
                 package_dir : package_dir.into(),
                 dataset_dir : dataset_dir.into(),
                 results_dir : results_dir.into(),
 
                 pindex : package_index,
                 dindex : data_index,
+
+                output : BranchOutput::new(false),
             }),
+            tracer    : None,
+            plugin    : PluginEntry::new::<OfflinePlugin>(),
+            max_steps : None,
         }
     }
 
+    /// Makes the OfflineVm record the edges visited by the workflows it runs to the given Tracer.
+    /// 
+    /// # Arguments
+    /// - `tracer`: The Tracer to record to.
+    /// 
+    /// # Returns
+    /// The same OfflineVm, but now tracing.
+    #[inline]
+    pub fn with_tracer(mut self, tracer: Tracer) -> Self {
+        self.tracer = Some(Arc::new(tracer));
+        self
+    }
+
+    /// Makes the OfflineVm print the text written by every parallel branch contiguously, once the branches complete.
+    /// 
+    /// # Returns
+    /// The same OfflineVm, but now grouping the output of branches.
+    #[inline]
+    pub fn with_grouped_output(self) -> Self {
+        self.state.global.write().unwrap().output = BranchOutput::new(true);
+        self
+    }
+
+    /// Makes the OfflineVm run workflows with the given plugin instead of the OfflinePlugin.
+    /// 
+    /// # Arguments
+    /// - `plugin`: The PluginEntry of the plugin to run with (see `plugins()`).
+    /// 
+    /// # Returns
+    /// The same OfflineVm, but now running with the given plugin.
+    #[inline]
+    pub fn with_plugin(mut self, plugin: PluginEntry<Self>) -> Self {
+        self.plugin = plugin;
+        self
+    }
+
+    /// Makes the OfflineVm abort workflows that take more than the given number of steps (i.e., edges and instructions).
+    /// 
+    /// # Arguments
+    /// - `max_steps`: The maximum number of steps per run.
+    /// 
+    /// # Returns
+    /// The same OfflineVm, but now with a limited number of steps.
+    #[inline]
+    pub fn with_max_steps(mut self, max_steps: u64) -> Self {
+        self.max_steps = Some(max_steps);
+        self
+    }
+
 
 
     /// Runs the given workflow on this VM.
@@ -320,7 +548,8 @@ impl OfflineVm {
         let this: Arc<RwLock<Self>> = Arc::new(RwLock::new(self));
 
         // Run the VM and get self back
-        let result: Result<FullValue, VmError> = Self::run::<OfflinePlugin>(this.clone(), plan).await;
+        let plugin: PluginEntry<Self> = this.read().unwrap().plugin;
+        let result: Result<FullValue, VmError> = plugin.run(this.clone(), plan).await;
         let this: Self = match Arc::try_unwrap(this) {
             Ok(this) => this.into_inner().unwrap(),
             Err(_)   => { panic!("Could not get self back"); },
@@ -329,6 +558,9 @@ impl OfflineVm {
 
 
         // Step 3: Result
+        // Print whatever the branches wrote last
+        this.state.global.write().unwrap().output.flush();
+
         // Match the result to potentially error
         let value: FullValue = match result {
             Ok(value) => value,
@@ -362,4 +594,14 @@ impl Vm for OfflineVm {
         let lock: RwLockReadGuard<Self> = this.read().unwrap();
         Ok(lock.state.clone())
     }
+
+    #[inline]
+    fn tracer(this: &Arc<RwLock<Self>>) -> Option<Arc<Tracer>> {
+        this.read().unwrap().tracer.clone()
+    }
+
+    #[inline]
+    fn max_steps(this: &Arc<RwLock<Self>>) -> Option<u64> {
+        this.read().unwrap().max_steps
+    }
 }
@@ -0,0 +1,313 @@
+//  WORKFLOW.rs
+//    by agent
+//
+//  Created:
+//    16 Oct 2026, 08:25:59
+//  Last edited:
+//    16 Oct 2026, 11:29:08
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements the `workflow` subcommand(s), which inspect compiled
+//!   workflows without running them.
+//
+
+use std::borrow::Cow;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+use console::style;
+
+use brane_ast::{compile_program, compile_program_to, CompileResult, CompileStage, ParserOptions, Workflow};
+use brane_dsl::Language;
+use brane_dsl::ast::{Program, Stmt};
+use specifications::data::DataIndex;
+use specifications::package::PackageIndex;
+
+pub use crate::errors::WorkflowError as Error;
+use crate::utils::{ensure_datasets_dir, ensure_packages_dir};
+
+
+/***** HELPER FUNCTIONS *****/
+/// Reads the source text of a workflow from the given file.
+///
+/// # Arguments
+/// - `file`: The file to read. Can also be '-', in which case it is read from stdin instead.
+///
+/// # Returns
+/// A description of what we read (i.e., the filename or `<stdin>`) and the source text itself.
+///
+/// # Errors
+/// This function errors if we failed to read the file or stdin.
+pub(crate) fn read_source(file: &Path) -> Result<(Cow<str>, String), Error> {
+    if file == Path::new("-") {
+        let mut result: String = String::new();
+        if let Err(err) = std::io::stdin().read_to_string(&mut result) { return Err(Error::StdinReadError{ err }); };
+        Ok(("<stdin>".into(), result))
+    } else {
+        match fs::read_to_string(file) {
+            Ok(res)  => Ok((file.to_string_lossy(), res)),
+            Err(err) => Err(Error::FileReadError{ path: file.into(), err }),
+        }
+    }
+}
+
+/// Loads the local package and data indices.
+///
+/// # Returns
+/// The PackageIndex and DataIndex of the locally available packages and datasets, respectively.
+///
+/// # Errors
+/// This function errors if we failed to find or read the local packages or datasets.
+pub(crate) fn local_indices() -> Result<(PackageIndex, DataIndex), Error> {
+    let packages_dir: PathBuf = match ensure_packages_dir(false) {
+        Ok(dir)  => dir,
+        Err(err) => { return Err(Error::PackagesDirError{ err }); },
+    };
+    let datasets_dir: PathBuf = match ensure_datasets_dir(false) {
+        Ok(dir)  => dir,
+        Err(err) => { return Err(Error::DatasetsDirError{ err }); },
+    };
+    let pindex: PackageIndex = match brane_tsk::local::get_package_index(packages_dir) {
+        Ok(index) => index,
+        Err(err)  => { return Err(Error::LocalPackageIndexError{ err }); },
+    };
+    let dindex: DataIndex = match brane_tsk::local::get_data_index(datasets_dir) {
+        Ok(index) => index,
+        Err(err)  => { return Err(Error::LocalDataIndexError{ err }); },
+    };
+    Ok((pindex, dindex))
+}
+
+/// Compiles the given source text to a Workflow using the local package and data indices.
+///
+/// # Arguments
+/// - `options`: The ParserOptions that describe how to parse the given source.
+/// - `what`: A description of the source we're compiling (e.g., the filename or `<stdin>`).
+/// - `source`: The source text to compile.
+///
+/// # Returns
+/// The compiled Workflow.
+///
+/// # Errors
+/// This function errors if we failed to load the local indices or if the source was not a valid workflow. In the latter case, the errors are pretty-printed with source context.
+fn compile(options: &ParserOptions, what: &str, source: &str) -> Result<Workflow, Error> {
+    let (pindex, dindex): (PackageIndex, DataIndex) = local_indices()?;
+
+    // Compile the source
+    match compile_program(source.as_bytes(), &pindex, &dindex, options) {
+        CompileResult::Workflow(workflow, warns) => {
+            for w in warns {
+                w.prettyprint(what, source);
+            }
+            Ok(workflow)
+        },
+
+        CompileResult::Eof(err) => {
+            err.prettyprint(what, source);
+            Err(Error::CompileError{ what: what.into(), errs: vec![ err ] })
+        },
+        CompileResult::Err(errs) => {
+            for e in &errs {
+                e.prettyprint(what, source);
+            }
+            Err(Error::CompileError{ what: what.into(), errs })
+        },
+
+        // Any others should not occur
+        _ => { unreachable!(); },
+    }
+}
+
+/// Parses and resolves the given source text using the local package and data indices, without compiling it any further.
+///
+/// # Arguments
+/// - `options`: The ParserOptions that describe how to parse the given source.
+/// - `what`: A description of the source we're resolving (e.g., the filename or `<stdin>`).
+/// - `source`: The source text to resolve.
+///
+/// # Returns
+/// The resolved Program, of which the symbol tables are populated.
+///
+/// # Errors
+/// This function errors if we failed to load the local indices or if the source could not be resolved. In the latter case, the errors are pretty-printed with source context.
+pub(crate) fn resolve(options: &ParserOptions, what: &str, source: &str) -> Result<Program, Error> {
+    let (pindex, dindex): (PackageIndex, DataIndex) = local_indices()?;
+
+    // Only run the compiler up to the resolve pass
+    match compile_program_to(source.as_bytes(), &pindex, &dindex, options, CompileStage::Resolve) {
+        CompileResult::Program(program, warns) => {
+            for w in warns {
+                w.prettyprint(what, source);
+            }
+            Ok(program)
+        },
+
+        CompileResult::Eof(err) => {
+            err.prettyprint(what, source);
+            Err(Error::CompileError{ what: what.into(), errs: vec![ err ] })
+        },
+        CompileResult::Err(errs) => {
+            for e in &errs {
+                e.prettyprint(what, source);
+            }
+            Err(Error::CompileError{ what: what.into(), errs })
+        },
+
+        // Any others should not occur
+        _ => { unreachable!(); },
+    }
+}
+
+/// Prints the documentation of the given function or class definition (and of its methods, if it is a class).
+///
+/// # Arguments
+/// - `stmt`: The statement to print. Anything but function and class definitions is ignored.
+/// - `indent`: The number of spaces to indent the definition with.
+fn show_def(stmt: &Stmt, indent: usize) {
+    // Print the definition itself
+    let docs: &Option<String> = match stmt {
+        Stmt::FuncDef{ ident, params, docs, .. } => {
+            println!("{}{} {}({})", " ".repeat(indent), style("func").bold(), style(&ident.value).bold().cyan(), params.iter().map(|p| p.value.as_str()).collect::<Vec<&str>>().join(", "));
+            docs
+        },
+        Stmt::ClassDef{ ident, docs, .. } => {
+            println!("{}{} {}", " ".repeat(indent), style("class").bold(), style(&ident.value).bold().cyan());
+            docs
+        },
+        _ => { return; },
+    };
+
+    // Print its documentation below it
+    match docs {
+        Some(docs) => {
+            for line in docs.lines() {
+                println!("{}    {}", " ".repeat(indent), line);
+            }
+        },
+        None => { println!("{}    {}", " ".repeat(indent), style("(undocumented)").dim()); },
+    }
+
+    // Recurse into the methods of classes
+    if let Stmt::ClassDef{ methods, .. } = stmt {
+        for method in methods {
+            println!();
+            show_def(method, indent + 4);
+        }
+    }
+}
+
+/// Lets Graphviz' `dot` layout the given graph as SVG.
+///
+/// # Arguments
+/// - `dot`: The graph to layout, in DOT.
+///
+/// # Returns
+/// The resulting SVG image.
+///
+/// # Errors
+/// This function errors if `dot` could not be launched or failed to layout the graph.
+fn layout_svg(dot: &[u8]) -> Result<Vec<u8>, Error> {
+    debug!("Launching 'dot -Tsvg'...");
+    let mut child = match Command::new("dot").arg("-Tsvg").stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn() {
+        Ok(child) => child,
+        Err(err)  => { return Err(Error::DotSpawnError{ err }); },
+    };
+
+    // Write the graph, then close stdin so dot knows we're done
+    {
+        let mut stdin = child.stdin.take().unwrap();
+        if let Err(err) = stdin.write_all(dot) { return Err(Error::DotWriteError{ err }); }
+    }
+    let output: Output = match child.wait_with_output() {
+        Ok(output) => output,
+        Err(err)   => { return Err(Error::DotWaitError{ err }); },
+    };
+    if !output.status.success() { return Err(Error::DotFailure{ status: output.status, stderr: String::from_utf8_lossy(&output.stderr).trim().into() }); }
+
+    // Done
+    Ok(output.stdout)
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Compiles the given file and renders the edges of the resulting workflow as a Graphviz graph.
+///
+/// Every edge of the workflow becomes a node in the graph, including the locations a task may run and the datasets and intermediate results it depends on.
+///
+/// # Arguments
+/// - `language`: The language with which to compile the file.
+/// - `file`: The file to read and compile. Can also be '-', in which case it is read from stdin instead.
+/// - `output`: The file to write the graph to. If omitted, writes it to stdout instead.
+/// - `svg`: Whether to layout the graph as an SVG image (using Graphviz' `dot`) instead of emitting the DOT source.
+///
+/// # Returns
+/// Nothing, but does write the graph to the given output.
+///
+/// # Errors
+/// This function errors if we failed to read or compile the file, layout the graph or write it.
+pub fn graph(language: Language, file: PathBuf, output: Option<PathBuf>, svg: bool) -> Result<(), Error> {
+    // Either read the file or read stdin
+    let (what, source): (Cow<str>, String) = read_source(&file)?;
+
+    // Compile it
+    let workflow: Workflow = compile(&ParserOptions::new(language), &what, &source)?;
+
+    // Render it as DOT, optionally laying it out as SVG
+    let mut graph: Vec<u8> = vec![];
+    if let Err(errs) = brane_ast::traversals::print::dot::do_traversal(workflow, &mut graph) { return Err(Error::GraphRenderError{ errs }); }
+    if svg { graph = layout_svg(&graph)?; }
+
+    // Write it to the output
+    match output {
+        Some(path) => {
+            if let Err(err) = fs::write(&path, graph) { return Err(Error::OutputWriteError{ path, err }); }
+        },
+        None => {
+            if let Err(err) = std::io::stdout().write_all(&graph) { return Err(Error::StdoutWriteError{ err }); }
+        },
+    }
+
+    // Done
+    Ok(())
+}
+
+
+
+/// Parses the given file and prints the functions and classes it defines, together with their documentation comments (`///`).
+///
+/// # Arguments
+/// - `language`: The language with which to parse the file.
+/// - `file`: The file to read and parse. Can also be '-', in which case it is read from stdin instead.
+///
+/// # Returns
+/// Nothing, but does print the definitions to stdout.
+///
+/// # Errors
+/// This function errors if we failed to read or resolve the file.
+pub fn show(language: Language, file: PathBuf) -> Result<(), Error> {
+    // Either read the file or read stdin
+    let (what, source): (Cow<str>, String) = read_source(&file)?;
+
+    // Resolve it such that we know it is a sensible workflow
+    let program: Program = resolve(&ParserOptions::new(language), &what, &source)?;
+
+    // Print the top-level definitions in the order they are written
+    let mut first: bool = true;
+    for stmt in &program.block.stmts {
+        if !matches!(stmt, Stmt::FuncDef{ .. } | Stmt::ClassDef{ .. }) { continue; }
+        if !first { println!(); }
+        show_def(stmt, 0);
+        first = false;
+    }
+    if first { println!("{} defines no functions or classes.", style(&what).bold()); }
+
+    // Done
+    Ok(())
+}
@@ -0,0 +1,135 @@
+//  AUDIT.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 10:43:36
+//  Last edited:
+//    16 Oct 2026, 10:43:36
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements the subcommands that inspect the audit log of a worker
+//!   node.
+// 
+
+use std::fs::File;
+use std::io::{Write as _, stdout};
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use console::style;
+use log::{debug, info};
+
+use brane_cfg::node::{NodeConfig, NodeKindConfig, WorkerConfig};
+use brane_shr::audit::{self, AuditRecord};
+
+pub use crate::errors::AuditError as Error;
+
+
+/***** HELPER FUNCTIONS *****/
+/// Reads all records in the audit log of the given worker node.
+/// 
+/// # Arguments
+/// - `node_config_path`: The path to the node config file that contains environment settings for this node.
+/// 
+/// # Returns
+/// All records in the audit log, of all services, oldest first.
+/// 
+/// # Errors
+/// This function errors if we failed to load the node config file, the node is not a worker node, it does not keep an audit log or we failed to read it.
+fn read_records(node_config_path: PathBuf) -> Result<Vec<AuditRecord>, Error> {
+    // Load the node config file
+    debug!("Loading node config file '{}'...", node_config_path.display());
+    let node_config: NodeConfig = match NodeConfig::from_path(&node_config_path) {
+        Ok(config) => config,
+        Err(err)   => { return Err(Error::NodeConfigLoadError{ err }); },
+    };
+    let worker: WorkerConfig = match node_config.node {
+        NodeKindConfig::Worker(worker) => worker,
+        NodeKindConfig::Central(_)     => { return Err(Error::NotAWorker{ path: node_config_path }); },
+    };
+    let dir: PathBuf = match worker.paths.audit {
+        Some(dir) => dir,
+        None      => { return Err(Error::NoAuditLog{ path: node_config_path }); },
+    };
+
+    // Read the records
+    debug!("Reading audit log '{}'...", dir.display());
+    match audit::read_dir(&dir) {
+        Ok(records) => Ok(records),
+        Err(err)    => Err(Error::ReadError{ err }),
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Prints the most recent records in the audit log of a worker node.
+/// 
+/// # Arguments
+/// - `node_config_path`: The path to the node config file that contains environment settings for this node.
+/// - `lines`: The number of records to print.
+/// 
+/// # Returns
+/// Nothing, but does print the records to `stdout`.
+/// 
+/// # Errors
+/// This function errors if we failed to load the node config file, the node is not a worker node, it does not keep an audit log or we failed to read it.
+pub fn tail(node_config_path: impl Into<PathBuf>, lines: usize) -> Result<(), Error> {
+    let node_config_path: PathBuf = node_config_path.into();
+    info!("Showing audit log of node '{}'...", node_config_path.display());
+
+    let records: Vec<AuditRecord> = read_records(node_config_path)?;
+    for record in &records[records.len().saturating_sub(lines)..] {
+        println!("{}", record);
+    }
+    Ok(())
+}
+
+/// Exports the records in the audit log of a worker node as JSONL.
+/// 
+/// # Arguments
+/// - `node_config_path`: The path to the node config file that contains environment settings for this node.
+/// - `output`: The file to write the records to, or `None` to write them to `stdout`.
+/// - `since`: If given, only exports records from this moment onwards.
+/// - `until`: If given, only exports records from before this moment.
+/// 
+/// # Returns
+/// Nothing, but does write the records to the given file (or `stdout`).
+/// 
+/// # Errors
+/// This function errors if we failed to read the audit log or to write the output.
+pub fn export(node_config_path: impl Into<PathBuf>, output: Option<PathBuf>, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>) -> Result<(), Error> {
+    let node_config_path: PathBuf = node_config_path.into();
+    info!("Exporting audit log of node '{}'...", node_config_path.display());
+
+    let records: Vec<AuditRecord> = read_records(node_config_path)?;
+    let records: Vec<&AuditRecord> = records.iter().filter(|r| since.map(|since| r.time >= since).unwrap_or(true) && until.map(|until| r.time < until).unwrap_or(true)).collect();
+
+    // Open the output
+    let to_file: bool = output.is_some();
+    let (path, mut handle): (PathBuf, Box<dyn std::io::Write>) = match output {
+        Some(path) => match File::create(&path) {
+            Ok(handle) => (path, Box::new(handle)),
+            Err(err)   => { return Err(Error::OutputCreateError{ path, err }); },
+        },
+        None => ("<stdout>".into(), Box::new(stdout())),
+    };
+
+    // Write the records to it, one per line
+    for record in &records {
+        let line: String = match serde_json::to_string(record) {
+            Ok(line) => line,
+            Err(err) => { return Err(Error::RecordSerializeError{ err }); },
+        };
+        if let Err(err) = writeln!(handle, "{}", line) { return Err(Error::OutputWriteError{ path, err }); }
+    }
+    if let Err(err) = handle.flush() { return Err(Error::OutputWriteError{ path, err }); }
+
+    // Done
+    if to_file { println!("Exported {} record(s) to {}", style(records.len()).bold().cyan(), style(path.display()).bold().green()); }
+    Ok(())
+}
@@ -0,0 +1,117 @@
+//  CONFIG.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 09:31:15
+//  Last edited:
+//    16 Oct 2026, 09:31:15
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements subcommands relating to the configuration files of a
+//!   node.
+// 
+
+use std::path::PathBuf;
+
+use console::style;
+use log::{debug, info};
+
+use brane_cfg::infra::{hash_config_file, ConfigHashes};
+use brane_cfg::node::{NodeConfig, WorkerConfig};
+
+pub use crate::errors::ConfigError as Error;
+
+
+/***** LIBRARY *****/
+/// Compares the hashes of the local node's configuration files with those that the central node expects, reporting any drift.
+/// 
+/// # Arguments
+/// - `node_config_path`: The path to the node config file that contains environment settings for this node.
+/// - `against`: The address of the central node's API service to compare with.
+/// - `infra_path`: If given, the path of a local copy of the `infra.yml` file to compare as well. Ignored for central nodes, which always compare their own.
+/// 
+/// # Returns
+/// Nothing directly, but does print the comparison to `stdout`.
+/// 
+/// # Errors
+/// This function errors if we failed to hash the local files or to fetch the expected hashes, or if any of the files has drifted.
+pub async fn diff(node_config_path: impl Into<PathBuf>, against: impl Into<String>, infra_path: Option<PathBuf>) -> Result<(), Error> {
+    let node_config_path : PathBuf = node_config_path.into();
+    let against          : String  = against.into();
+    info!("Comparing configuration with central node '{}'...", against);
+
+    // Load the node config file
+    debug!("Loading node config file '{}'...", node_config_path.display());
+    let node_config: NodeConfig = match NodeConfig::from_path(&node_config_path) {
+        Ok(config) => config,
+        Err(err)   => { return Err(Error::NodeConfigLoadError{ err }); },
+    };
+
+    // Decide which files to compare and where to ask for their expected hashes
+    let (address, files): (String, [(&'static str, Option<PathBuf>); 3]) = if node_config.node.is_central() {
+        (format!("{}/infra/config", against), [
+            ("infra.yml", Some(node_config.node.central().paths.infra.clone())),
+            ("backend.yml", None),
+            ("policies.yml", None),
+        ])
+    } else {
+        let worker: &WorkerConfig = node_config.node.worker();
+        (format!("{}/infra/config/{}", against, worker.location_id), [
+            ("infra.yml", infra_path),
+            ("backend.yml", Some(worker.paths.backend.clone())),
+            ("policies.yml", Some(worker.paths.policies.clone())),
+        ])
+    };
+
+    // Fetch the expected hashes
+    debug!("Fetching expected hashes from '{}'...", address);
+    let res: reqwest::Response = match reqwest::get(&address).await {
+        Ok(res)  => res,
+        Err(err) => { return Err(Error::RequestError{ address, err }); },
+    };
+    if !res.status().is_success() {
+        return Err(Error::RequestFailure{ address, code: res.status(), message: res.text().await.ok() });
+    }
+    let expected: ConfigHashes = match res.json().await {
+        Ok(expected) => expected,
+        Err(err)     => { return Err(Error::ResponseParseError{ address, err }); },
+    };
+
+    // Compare them with the local files
+    let mut drifted: Vec<&'static str> = vec![];
+    for ((name, path), expected) in files.into_iter().zip([ expected.infra, expected.backend, expected.policies ]) {
+        // Hash the local file, if any
+        let local: Option<String> = match &path {
+            Some(path) => {
+                debug!("Hashing {} '{}'...", name, path.display());
+                match hash_config_file(path) {
+                    Ok(hash) => Some(hash),
+                    Err(err) => { return Err(Error::FileHashError{ what: name, err }); },
+                }
+            },
+            None => None,
+        };
+
+        // Report the result
+        match (&local, &expected) {
+            (Some(local), Some(expected)) if local == expected => {
+                println!("{:<14} {}  ({})", name, style("ok").bold().green(), local);
+            },
+            (Some(local), Some(expected)) => {
+                println!("{:<14} {}  (local: {}, expected: {})", name, style("DRIFT").bold().red(), local, expected);
+                drifted.push(name);
+            },
+            (Some(local), None) => {
+                println!("{:<14} {}  ({}; no hash expected by central node)", name, style("unchecked").bold().yellow(), local);
+            },
+            (None, _) => {
+                println!("{:<14} {}  (no local file to compare)", name, style("unchecked").bold().yellow());
+            },
+        }
+    }
+
+    // Done
+    if drifted.is_empty() { Ok(()) } else { Err(Error::DriftDetected{ files: drifted }) }
+}
@@ -47,6 +47,8 @@ pub enum GenerateError {
     FileHeaderWriteError{ path: PathBuf, err: std::io::Error },
     /// Failed to write the main body to the new file.
     NodeWriteError{ path: PathBuf, err: brane_cfg::node::Error },
+    /// Failed to create the (empty) audit log.
+    AuditCreateError{ path: PathBuf, err: std::io::Error },
 
     /// The given location is unknown.
     UnknownLocation{ loc: String },
@@ -72,6 +74,7 @@ impl Display for GenerateError {
             FileCreateError{ path, err }      => write!(f, "Failed to create new node.yml file '{}': {}", path.display(), err),
             FileHeaderWriteError{ path, err } => write!(f, "Failed to write header to node.yml file '{}': {}", path.display(), err),
             NodeWriteError{ err, .. }         => write!(f, "Failed to write body to node.yml file: {}", err),
+            AuditCreateError{ path, err }     => write!(f, "Failed to create audit log '{}': {}", path.display(), err),
 
             UnknownLocation{ loc }     => write!(f, "Unknown location '{}' (did you forget to specify it in the LOCATIONS argument?)", loc),
             InfraWriteError{ err, .. } => write!(f, "Failed to write body to infra.yml file: {}", err),
@@ -126,7 +129,7 @@ impl Display for LifetimeError {
             ImageDigestError{ path, err }        => write!(f, "Failed to get digest of image {}: {}", style(path.display()).bold(), err),
             ImageLoadError{ image, source, err } => write!(f, "Failed to load image {} from '{}': {}", style(image).bold(), style(source).bold(), err),
 
-            NodeConfigLoadError{ err }                 => write!(f, "Failed to load node.yml file: {}", err),
+            NodeConfigLoadError{ err }                => write!(f, "Failed to load node.yml file: {}", err),
             DockerConnectError{ socket, version, err } => write!(f, "Failed to connect to local Docker socket '{}' using API version {}: {}", socket.display(), version, err),
             UnmatchedNodeKind{ got, expected }         => write!(f, "Got command to start {} node, but 'node.yml' defined a {} node", got.variant(), expected.variant()),
 
@@ -139,6 +142,136 @@ impl Error for LifetimeError {}
 
 
 
+/// Errors that relate to config subcommands.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// Failed to load the given node config file.
+    NodeConfigLoadError{ err: brane_cfg::node::Error },
+    /// Failed to hash a local configuration file.
+    FileHashError{ what: &'static str, err: brane_cfg::infra::Error },
+    /// Failed to send the request for the expected hashes.
+    RequestError{ address: String, err: reqwest::Error },
+    /// The central node did not accept the request for the expected hashes.
+    RequestFailure{ address: String, code: reqwest::StatusCode, message: Option<String> },
+    /// Failed to parse the expected hashes sent by the central node.
+    ResponseParseError{ address: String, err: reqwest::Error },
+    /// Some of the configuration files do not have the hashes they are expected to have.
+    DriftDetected{ files: Vec<&'static str> },
+}
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use ConfigError::*;
+        match self {
+            NodeConfigLoadError{ err }                => write!(f, "Failed to load node.yml file: {}", err),
+            FileHashError{ what, err }                => write!(f, "Failed to hash local {} file: {}", what, err),
+            RequestError{ address, err }              => write!(f, "Failed to send GET-request to '{}': {}", address, err),
+            RequestFailure{ address, code, message }  => write!(f, "Request to '{}' failed with status code {} ({}){}", address, code, code.canonical_reason().unwrap_or("???"), if let Some(message) = message { format!(": {}", message) } else { String::new() }),
+            ResponseParseError{ address, err }        => write!(f, "Failed to parse expected hashes sent by '{}': {}", address, err),
+            DriftDetected{ files }                    => write!(f, "Configuration has drifted from what the central node expects for: {}", files.iter().map(|f| style(f).bold().to_string()).collect::<Vec<String>>().join(", ")),
+        }
+    }
+}
+impl Error for ConfigError {}
+
+
+
+/// Errors that relate to the smoke test.
+#[derive(Debug)]
+pub enum SmoketestError {
+    /// Failed to load the given node config file.
+    NodeConfigLoadError{ err: brane_cfg::node::Error },
+    /// Failed to send a request to the instance.
+    RequestError{ stage: &'static str, address: String, err: reqwest::Error },
+    /// The instance did not accept a request.
+    RequestFailure{ stage: &'static str, address: String, code: reqwest::StatusCode, message: Option<String> },
+
+    /// Failed to archive the package to upload.
+    PackageArchiveError{ path: PathBuf, err: std::io::Error },
+    /// Failed to fetch the package index of the instance.
+    PackageIndexError{ address: String, err: brane_tsk::api::Error },
+    /// The package is not known to the instance.
+    UnknownPackage{ name: String, uploaded: bool },
+
+    /// Failed to write the dataset.
+    DatasetWriteError{ path: PathBuf, err: std::io::Error },
+    /// Failed to fetch the data index of the instance.
+    DataIndexError{ address: String, err: brane_tsk::api::Error },
+    /// The dataset did not become available in time.
+    DatasetNotFound{ name: String, location: String, path: PathBuf },
+
+    /// Failed to compile the workflow.
+    CompileError{ errs: Vec<brane_ast::Error> },
+    /// Failed to serialize the compiled workflow.
+    WorkflowSerializeError{ err: serde_json::Error },
+
+    /// Failed to connect to the driver.
+    DriverConnectError{ address: String, err: tonic::transport::Error },
+    /// Failed to create a new session on the driver.
+    SessionCreateError{ address: String, err: tonic::Status },
+    /// The driver sent a session ID that is not valid.
+    SessionIdParseError{ raw: String, err: brane_tsk::errors::IdError },
+
+    /// Failed to run the workflow on the driver.
+    ExecuteError{ address: String, err: tonic::Status },
+    /// The workflow ran, but failed.
+    WorkflowFailed{ stderr: String },
+}
+impl Display for SmoketestError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use SmoketestError::*;
+        match self {
+            NodeConfigLoadError{ err }                      => write!(f, "Failed to load node.yml file: {}", err),
+            RequestError{ stage, address, err }             => write!(f, "Stage '{}': Failed to send request to '{}': {}", stage, address, err),
+            RequestFailure{ stage, address, code, message } => write!(f, "Stage '{}': Request to '{}' failed with status code {} ({}){}", stage, address, code, code.canonical_reason().unwrap_or("???"), if let Some(message) = message { format!(": {}", message) } else { String::new() }),
+
+            PackageArchiveError{ path, err }  => write!(f, "Stage 'package': Failed to archive package in '{}': {}", path.display(), err),
+            PackageIndexError{ address, err } => write!(f, "Stage 'package': Failed to fetch package index from '{}': {}", address, err),
+            UnknownPackage{ name, uploaded }  => write!(f, "Stage 'package': Package '{}' is not available in the instance{}", name, if *uploaded { " even after uploading it" } else { " (build it with 'brane build' and give its directory with '--package-dir', or push it with 'brane push')" }),
+
+            DatasetWriteError{ path, err }          => write!(f, "Stage 'dataset': Failed to write dataset file '{}': {}", path.display(), err),
+            DataIndexError{ address, err }          => write!(f, "Failed to fetch data index from '{}': {}", address, err),
+            DatasetNotFound{ name, location, path } => write!(f, "Stage 'dataset': Dataset '{}' written to '{}' did not become available at location '{}' (is the local registry service running?)", name, path.display(), location),
+
+            CompileError{ errs }          => write!(f, "Stage 'compile': Failed to compile workflow ({} errors, see output above)", errs.len()),
+            WorkflowSerializeError{ err } => write!(f, "Stage 'compile': Failed to serialize workflow: {}", err),
+
+            DriverConnectError{ address, err } => write!(f, "Stage 'driver': Failed to connect to driver '{}': {}", address, err),
+            SessionCreateError{ address, err } => write!(f, "Stage 'driver': Failed to create session on driver '{}': {}", address, err),
+            SessionIdParseError{ raw, err }    => write!(f, "Stage 'driver': Driver sent illegal session ID '{}': {}", raw, err),
+
+            ExecuteError{ address, err } => write!(f, "Stage 'execute': Failed to run workflow on driver '{}': {}", address, err),
+            WorkflowFailed{ stderr }     => write!(f, "Stage 'execute': Workflow failed in the planner or on a worker:\n{}", stderr),
+        }
+    }
+}
+impl Error for SmoketestError {}
+
+
+
+/// Errors that relate to telemetry subcommands.
+#[derive(Debug)]
+pub enum TelemetryError {
+    /// Failed to load the given node config file.
+    NodeConfigLoadError{ err: brane_cfg::node::Error },
+    /// Failed to write the updated node config file.
+    NodeConfigWriteError{ err: brane_cfg::node::Error },
+    /// Telemetry was enabled without an endpoint to report to.
+    MissingEndpoint,
+}
+impl Display for TelemetryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use TelemetryError::*;
+        match self {
+            NodeConfigLoadError{ err }  => write!(f, "Failed to load node.yml file: {}", err),
+            NodeConfigWriteError{ err } => write!(f, "Failed to write node.yml file: {}", err),
+            MissingEndpoint             => write!(f, "No telemetry endpoint configured (give one with '--endpoint')"),
+        }
+    }
+}
+impl Error for TelemetryError {}
+
+
+
 /// Errors that relate to package subcommands.
 #[derive(Debug)]
 pub enum PackagesError {
@@ -156,18 +289,21 @@ pub enum PackagesError {
     UnknownImage{ path: PathBuf, name: String, version: Version },
     /// Failed to hash the found image file.
     HashError{ err: brane_tsk::docker::Error },
+    /// Failed to read the content-addressed store of a worker node.
+    StoreError{ err: brane_shr::cas::Error },
 }
 impl Display for PackagesError {
     fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
         use PackagesError::*;
         match self {
-            NodeConfigLoadError{ err }                  => write!(f, "Failed to load node.yml file: {}", err),
+            NodeConfigLoadError{ err }                => write!(f, "Failed to load node.yml file: {}", err),
             FileNotAFile{ path }                        => write!(f, "Given image path '{}' exists but is not a file", path.display()),
             IllegalNameVersionPair{ raw, err }          => write!(f, "Failed to parse given image name[:version] pair '{}': {}", raw, err),
             DirReadError{ what, path, err }             => write!(f, "Failed to read {} directory '{}': {}", what, path.display(), err),
             DirEntryReadError{ what, entry, path, err } => write!(f, "Failed to read entry {} in {} directory '{}': {}", entry, what, path.display(), err),
             UnknownImage{ path, name, version }         => write!(f, "No image for package '{}', version {} found in '{}'", name, version, path.display()),
             HashError{ err }                            => write!(f, "Failed to hash image: {}", err),
+            StoreError{ err }                           => write!(f, "Failed to read content-addressed store: {}", err),
         }
     }
 }
@@ -175,6 +311,118 @@ impl Error for PackagesError {}
 
 
 
+/// Errors that relate to garbage collecting the content-addressed store.
+#[derive(Debug)]
+pub enum GcError {
+    /// Failed to load the given node config file.
+    NodeConfigLoadError{ err: brane_cfg::node::Error },
+    /// The given node config file is not for a worker node.
+    NotAWorker{ path: PathBuf },
+    /// Failed to operate on the content-addressed store.
+    StoreError{ err: brane_shr::cas::Error },
+    /// Failed to remove the link to a released intermediate result.
+    LinkRemoveError{ path: PathBuf, err: std::io::Error },
+}
+impl Display for GcError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use GcError::*;
+        match self {
+            NodeConfigLoadError{ err }   => write!(f, "Failed to load node.yml file: {}", err),
+            NotAWorker{ path }           => write!(f, "Node config file '{}' is not for a worker node (only worker nodes have a content-addressed store)", path.display()),
+            StoreError{ err }            => write!(f, "Failed to collect content-addressed store: {}", err),
+            LinkRemoveError{ path, err } => write!(f, "Failed to remove intermediate result link '{}': {}", path.display(), err),
+        }
+    }
+}
+impl Error for GcError {}
+
+
+
+/// Errors that relate to inspecting the audit log.
+#[derive(Debug)]
+pub enum AuditError {
+    /// Failed to load the given node config file.
+    NodeConfigLoadError{ err: brane_cfg::node::Error },
+    /// The given node config file is not for a worker node.
+    NotAWorker{ path: PathBuf },
+    /// The worker node does not keep an audit log.
+    NoAuditLog{ path: PathBuf },
+    /// Failed to read the audit log.
+    ReadError{ err: brane_shr::audit::Error },
+
+    /// Failed to serialize a record.
+    RecordSerializeError{ err: serde_json::Error },
+    /// Failed to create the output file.
+    OutputCreateError{ path: PathBuf, err: std::io::Error },
+    /// Failed to write to the output file.
+    OutputWriteError{ path: PathBuf, err: std::io::Error },
+}
+impl Display for AuditError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use AuditError::*;
+        match self {
+            NodeConfigLoadError{ err } => write!(f, "Failed to load node.yml file: {}", err),
+            NotAWorker{ path }         => write!(f, "Node config file '{}' is not for a worker node (only worker nodes have an audit log)", path.display()),
+            NoAuditLog{ path }         => write!(f, "Node config file '{}' does not define an audit log directory (regenerate it with `branectl generate node worker --audit ...`)", path.display()),
+            ReadError{ err }           => write!(f, "Failed to read audit log: {}", err),
+
+            RecordSerializeError{ err }    => write!(f, "Failed to serialize audit record: {}", err),
+            OutputCreateError{ path, err } => write!(f, "Failed to create output file '{}': {}", path.display(), err),
+            OutputWriteError{ path, err }  => write!(f, "Failed to write to output file '{}': {}", path.display(), err),
+        }
+    }
+}
+impl Error for AuditError {}
+
+
+
+/// Errors that relate to managing the secrets of a worker node.
+#[derive(Debug)]
+pub enum SecretsError {
+    /// Failed to load the given node config file.
+    NodeConfigLoadError{ err: brane_cfg::node::Error },
+    /// The given node config file is not for a worker node.
+    NotAWorker{ path: PathBuf },
+    /// The worker node does not have a secrets file.
+    NoSecretsFile{ path: PathBuf },
+    /// Failed to load the secrets file.
+    FileLoadError{ err: brane_cfg::secrets::Error },
+    /// The secrets file is backed by Vault instead of storing the secrets itself.
+    NotAFileBackend{ path: PathBuf },
+    /// Failed to create the secrets file.
+    FileCreateError{ path: PathBuf, err: std::io::Error },
+    /// Failed to write the secrets file.
+    FileWriteError{ path: PathBuf, err: brane_cfg::secrets::Error },
+
+    /// The given name cannot be the name of a secret.
+    IllegalName{ name: String },
+    /// Failed to read the value of the secret from stdin.
+    ValueReadError{ err: std::io::Error },
+    /// The secret to remove does not exist.
+    UnknownSecret{ name: String },
+}
+impl Display for SecretsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use SecretsError::*;
+        match self {
+            NodeConfigLoadError{ err }   => write!(f, "Failed to load node.yml file: {}", err),
+            NotAWorker{ path }           => write!(f, "Node config file '{}' is not for a worker node (only worker nodes have secrets)", path.display()),
+            NoSecretsFile{ path }        => write!(f, "Node config file '{}' does not define a secrets file (add 'secrets' to its 'paths')", path.display()),
+            FileLoadError{ err }         => write!(f, "Failed to load secrets file: {}", err),
+            NotAFileBackend{ path }      => write!(f, "Secrets file '{}' refers to Vault; manage its secrets there instead", path.display()),
+            FileCreateError{ path, err } => write!(f, "Failed to create secrets file '{}': {}", path.display(), err),
+            FileWriteError{ path, err }  => write!(f, "Failed to write secrets file '{}': {}", path.display(), err),
+
+            IllegalName{ name }   => write!(f, "Illegal secret name '{}' (must be a valid environment variable name, i.e., only letters, digits and underscores, not starting with a digit)", name),
+            ValueReadError{ err } => write!(f, "Failed to read secret value from stdin: {}", err),
+            UnknownSecret{ name } => write!(f, "Unknown secret '{}'", name),
+        }
+    }
+}
+impl Error for SecretsError {}
+
+
+
 /// Errors that relate to parsing Docker client version numbers.
 #[derive(Debug)]
 pub enum DockerClientVersionParseError {
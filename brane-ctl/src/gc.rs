@@ -0,0 +1,96 @@
+//  GC.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 10:26:40
+//  Last edited:
+//    16 Oct 2026, 10:26:40
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements the subcommand that garbage collects the content-addressed
+//!   store of a worker node.
+// 
+
+use std::fs;
+use std::path::PathBuf;
+
+use console::style;
+use log::{debug, info};
+
+use brane_cfg::node::{NodeConfig, NodeKindConfig, WorkerConfig};
+use brane_shr::cas::{ContentStore, TagKind};
+
+pub use crate::errors::GcError as Error;
+
+
+/***** LIBRARY *****/
+/// Removes all objects from the content-addressed store of a worker node that are no longer referenced by any package, dataset or intermediate result.
+/// 
+/// # Arguments
+/// - `node_config_path`: The path to the node config file that contains environment settings for this node.
+/// - `dry_run`: If true, only reports which objects would be removed.
+/// - `results`: If true, also releases all intermediate results first. Only do this while no workflows are running on the node.
+/// 
+/// # Returns
+/// Nothing, but does print the removed objects to `stdout`.
+/// 
+/// # Errors
+/// This function errors if we failed to load the node config file, the node is not a worker node or we failed to collect the store.
+pub async fn gc(node_config_path: impl Into<PathBuf>, dry_run: bool, results: bool) -> Result<(), Error> {
+    let node_config_path: PathBuf = node_config_path.into();
+    info!("Collecting garbage for node '{}'...", node_config_path.display());
+
+    // Load the node config file
+    debug!("Loading node config file '{}'...", node_config_path.display());
+    let node_config: NodeConfig = match NodeConfig::from_path(&node_config_path) {
+        Ok(config) => config,
+        Err(err)   => { return Err(Error::NodeConfigLoadError{ err }); },
+    };
+    let worker: WorkerConfig = match node_config.node {
+        NodeKindConfig::Worker(worker) => worker,
+        NodeKindConfig::Central(_)     => { return Err(Error::NotAWorker{ path: node_config_path }); },
+    };
+
+    // Open the store
+    debug!("Opening content-addressed store '{}'...", worker.paths.store.display());
+    let store: ContentStore = match ContentStore::new(&worker.paths.store).await {
+        Ok(store) => store,
+        Err(err)  => { return Err(Error::StoreError{ err }); },
+    };
+
+    // Release the intermediate results if told to do so
+    if results {
+        let tags: Vec<(String, String)> = match store.tags(TagKind::IntermediateResult).await {
+            Ok(tags) => tags,
+            Err(err) => { return Err(Error::StoreError{ err }); },
+        };
+        for (name, _) in tags {
+            debug!("Releasing intermediate result '{}'...", name);
+            if !dry_run {
+                if let Err(err) = store.untag(TagKind::IntermediateResult, &name).await { return Err(Error::StoreError{ err }); }
+
+                // Also remove the link that exposes it to the registry
+                let link: PathBuf = worker.paths.results.join(&name);
+                if link.is_symlink() {
+                    if let Err(err) = fs::remove_file(&link) { return Err(Error::LinkRemoveError{ path: link, err }); }
+                }
+            }
+        }
+    }
+
+    // Collect the store
+    let removed: Vec<(String, u64)> = match store.gc(dry_run).await {
+        Ok(removed) => removed,
+        Err(err)    => { return Err(Error::StoreError{ err }); },
+    };
+    let total: u64 = removed.iter().map(|(_, size)| size).sum();
+    for (digest, size) in &removed {
+        println!("{} {} ({} bytes)", if dry_run { "Would remove" } else { "Removed" }, style(digest).bold(), size);
+    }
+    println!("{} {} object(s), freeing {} bytes", if dry_run { "Would remove" } else { "Removed" }, style(removed.len()).bold().cyan(), style(total).bold().cyan());
+
+    // Done
+    Ok(())
+}
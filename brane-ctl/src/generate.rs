@@ -12,8 +12,8 @@
 //!   Handles commands relating to node.yml generation.
 // 
 
-use std::collections::HashMap;
-use std::fs::{self, File};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File, OpenOptions};
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::net::{IpAddr, Ipv4Addr, SocketAddrV4};
@@ -23,9 +23,9 @@ use enum_debug::EnumDebug as _;
 use log::{debug, info, warn};
 
 use brane_cfg::spec::Address;
-use brane_cfg::infra::{InfraFile, InfraLocation};
+use brane_cfg::infra::{ConfigHashes, InfraFile, InfraLocation};
 use brane_cfg::backend::{BackendFile, Credentials};
-use brane_cfg::node::{CentralConfig, CentralKafkaTopics, CentralNames, CentralPaths, CentralPorts, CentralServices, CommonNames, CommonPaths, CommonPorts, CommonServices, NodeConfig, NodeKindConfig, WorkerConfig, WorkerNames, WorkerPaths, WorkerPorts, WorkerServices};
+use brane_cfg::node::{CentralConfig, CentralKafkaTopics, CentralNames, CentralPaths, CentralPorts, CentralServices, CommonNames, CommonPaths, CommonPorts, CommonServices, FeatureFlags, ImageRegistryConfig, NodeConfig, NodeKindConfig, PlannerTransport, PlanningStrategy, WorkerConfig, WorkerNames, WorkerPaths, WorkerPorts, WorkerServices};
 use brane_cfg::policies::{ContainerPolicy, PolicyFile, UserPolicy};
 use specifications::package::Capability;
 
@@ -271,6 +271,7 @@ fn write_policy_header(writer: &mut impl Write) -> Result<(), std::io::Error> {
 /// - `path`: The path to write the central node.yml to.
 /// - `hosts`: List of additional hostnames to set in the launched containers.
 /// - `proxy`: The address to proxy to, if any (not the address of the proxy service, but rather that of a 'real' proxy).
+/// - `image_registry`: The address of the OCI registry in which package images are stored, if any.
 /// - `fix_dirs`: if true, will generate missing directories instead of complaining.
 /// - `config_path`: The path to the config directory that other paths may use as their base.
 /// - `command`: The GenerateSubcommand that contains the specific values to write, as well as whether to write a central or worker node.
@@ -280,7 +281,7 @@ fn write_policy_header(writer: &mut impl Write) -> Result<(), std::io::Error> {
 /// 
 /// # Errors
 /// This function may error if I/O errors occur while writing the file.
-pub fn node(path: impl Into<PathBuf>, hosts: Vec<HostnamePair>, proxy: Option<Address>, fix_dirs: bool, config_path: impl Into<PathBuf>, command: GenerateNodeSubcommand) -> Result<(), Error> {
+pub fn node(path: impl Into<PathBuf>, hosts: Vec<HostnamePair>, proxy: Option<Address>, image_registry: Option<String>, fix_dirs: bool, config_path: impl Into<PathBuf>, command: GenerateNodeSubcommand) -> Result<(), Error> {
     let path        : PathBuf = path.into();
     let config_path : PathBuf = config_path.into();
     info!("Generating node.yml for a {}...", match &command { GenerateNodeSubcommand::Central { .. } => { "central node".into() }, GenerateNodeSubcommand::Worker{ location_id, .. } => { format!("worker node with location ID '{}'", location_id) } });
@@ -301,15 +302,22 @@ pub fn node(path: impl Into<PathBuf>, hosts: Vec<HostnamePair>, proxy: Option<Ad
     debug!("Generating node config...");
     let node_config: NodeConfig = match command {
         // Generate the central node
-        GenerateNodeSubcommand::Central { infra, certs, packages, prx_name, api_name, drv_name, plr_name, prx_port, api_port, drv_port, plr_cmd_topic, plr_res_topic } => {
+        GenerateNodeSubcommand::Central { infra, certs, packages, audit, checkpoints, prx_name, api_name, drv_name, plr_name, prx_port, api_port, drv_port, plr_cmd_topic, plr_res_topic } => {
             // Resolve any path depending on the '$CONFIG'
-            let infra : PathBuf = resolve_config_path(infra, &config_path);
-            let certs : PathBuf = resolve_config_path(certs, &config_path);
+            let infra       : PathBuf = resolve_config_path(infra, &config_path);
+            let certs       : PathBuf = resolve_config_path(certs, &config_path);
+            let audit       : PathBuf = resolve_config_path(audit, &config_path);
+            let checkpoints : PathBuf = resolve_config_path(checkpoints, &config_path);
 
             // Ensure the directory structure is there
             ensure_dir_of(&infra, fix_dirs)?;
             ensure_dir(&certs, fix_dirs)?;
             ensure_dir(&packages, fix_dirs)?;
+            ensure_dir_of(&audit, fix_dirs)?;
+            ensure_dir(&checkpoints, fix_dirs)?;
+
+            // Make sure the audit log exists, so it can be mounted as a file
+            if let Err(err) = OpenOptions::new().create(true).append(true).open(&audit) { return Err(Error::AuditCreateError{ path: audit, err }); }
 
             // Generate the config's contents
             NodeConfig {
@@ -321,20 +329,34 @@ pub fn node(path: impl Into<PathBuf>, hosts: Vec<HostnamePair>, proxy: Option<Ad
                 ports    : CommonPorts{ prx : SocketAddrV4::new(Ipv4Addr::new(0, 0, 0, 0), prx_port).into() },
                 services : CommonServices{ prx : Address::Hostname(format!("http://{}", prx_name), prx_port) },
 
+                telemetry      : None,
+                tracing        : None,
+                image_registry : image_registry.map(|address| ImageRegistryConfig{ address, username: None, password: None }),
+                features       : FeatureFlags::default(),
+
                 node : NodeKindConfig::Central(CentralConfig {
                     names : CentralNames{ api: api_name.clone(), drv: drv_name, plr: plr_name },
                     paths : CentralPaths {
-                        infra : canonicalize(infra)?,
+                        infra       : canonicalize(infra)?,
+                        audit       : Some(canonicalize(audit)?),
+                        checkpoints : Some(canonicalize(checkpoints)?),
                     },
                     ports    : CentralPorts { api: SocketAddrV4::new(Ipv4Addr::new(0, 0, 0, 0), api_port).into(), drv: SocketAddrV4::new(Ipv4Addr::new(0, 0, 0, 0), drv_port).into() },
                     services : CentralServices{ brokers: vec![ Address::Hostname("aux-kafka".into(), 9092) ], scylla: Address::Hostname("aux-scylla".into(), 9042), api: Address::Hostname(format!("http://{}", api_name), api_port) },
                     topics   : CentralKafkaTopics{ planner_command: plr_cmd_topic, planner_results: plr_res_topic },
+                    planner  : PlannerTransport::Kafka,
+                    strategy : PlanningStrategy::DataLocalityFirst,
+
+                    notifications : HashMap::new(),
+                    peers         : HashMap::new(),
+                    scanner       : None,
+                    tls           : None,
                 }),
             }
         },
 
         // Generate the worker node
-        GenerateNodeSubcommand::Worker { location_id, backend, policies, certs, packages, data, results, temp_data, temp_results, prx_name, reg_name, job_name, chk_name, prx_port, reg_port, job_port, chk_port } => {
+        GenerateNodeSubcommand::Worker { location_id, backend, policies, certs, packages, data, results, temp_data, temp_results, store, audit, max_scratch_size, min_free_space, capabilities, prx_name, reg_name, job_name, chk_name, prx_port, reg_port, job_port, chk_port } => {
             // Resolve the service names
             let prx_name: String = prx_name.replace("$LOCATION", &location_id);
             let reg_name: String = reg_name.replace("$LOCATION", &location_id);
@@ -355,6 +377,8 @@ pub fn node(path: impl Into<PathBuf>, hosts: Vec<HostnamePair>, proxy: Option<Ad
             ensure_dir(&results, fix_dirs)?;
             ensure_dir(&temp_data, fix_dirs)?;
             ensure_dir(&temp_results, fix_dirs)?;
+            ensure_dir(&store, fix_dirs)?;
+            ensure_dir(&audit, fix_dirs)?;
 
             // Generate the config's contents
             NodeConfig {
@@ -366,6 +390,11 @@ pub fn node(path: impl Into<PathBuf>, hosts: Vec<HostnamePair>, proxy: Option<Ad
                 ports    : CommonPorts{ prx : SocketAddrV4::new(Ipv4Addr::new(0, 0, 0, 0), prx_port).into() },
                 services : CommonServices{ prx : Address::Hostname(format!("http://{}", prx_name), prx_port) },
 
+                telemetry      : None,
+                tracing        : None,
+                image_registry : image_registry.map(|address| ImageRegistryConfig{ address, username: None, password: None }),
+                features       : FeatureFlags::default(),
+
                 node : NodeKindConfig::Worker(WorkerConfig {
                     location_id,
                     names : WorkerNames { reg: reg_name.clone(), job: job_name, chk: chk_name.clone() },
@@ -376,9 +405,20 @@ pub fn node(path: impl Into<PathBuf>, hosts: Vec<HostnamePair>, proxy: Option<Ad
                         results      : canonicalize(results)?,
                         temp_data    : canonicalize(temp_data)?,
                         temp_results : canonicalize(temp_results)?,
+                        store        : canonicalize(store)?,
+                        audit        : Some(canonicalize(audit)?),
+                        secrets      : None,
                     },
                     ports    : WorkerPorts { reg: SocketAddrV4::new(Ipv4Addr::new(0, 0, 0, 0), reg_port).into(), job: SocketAddrV4::new(Ipv4Addr::new(0, 0, 0, 0), job_port).into() },
                     services : WorkerServices { reg: Address::Hostname(format!("https://{}", reg_name), reg_port), chk: Address::Hostname(format!("http://{}", chk_name), chk_port) },
+
+                    max_scratch_size,
+                    min_free_space,
+                    proxies          : HashMap::new(),
+                    capabilities     : capabilities.into_iter().collect(),
+                    firewall         : None,
+                    run_networks     : false,
+                    result_retention : None,
                 }),
             }
         },
@@ -427,9 +467,11 @@ pub fn infra(locations: Vec<LocationPair<':', String>>, fix_dirs: bool, path: im
     let mut locs: HashMap<String, InfraLocation> = HashMap::with_capacity(locations.len());
     for loc in locations {
         locs.insert(loc.0.clone(), InfraLocation {
-            name     : beautify_id(loc.0),
-            registry : Address::hostname(format!("https://{}", loc.1), 50051),
-            delegate : Address::hostname(format!("grpc://{}", loc.1), 50052),
+            name         : beautify_id(loc.0),
+            registry     : Address::hostname(format!("https://{}", loc.1), 50051),
+            delegate     : Address::hostname(format!("grpc://{}", loc.1), 50052),
+            expected     : ConfigHashes::default(),
+            capabilities : HashSet::new(),
         });
     }
 
@@ -503,6 +545,7 @@ pub fn backend(fix_dirs: bool, path: impl Into<PathBuf>, capabilities: Vec<Capab
             BackendFile {
                 capabilities : Some(capabilities.into_iter().collect()),
                 method       : Credentials::Local{ path: Some(socket), version: client_version.map(|v| (v.0.major_version, v.0.minor_version)) },
+                burst        : None,
             }
         },
     };
@@ -20,3 +20,9 @@ pub mod utils;
 pub mod generate;
 pub mod lifetime;
 pub mod packages;
+pub mod config;
+pub mod smoketest;
+pub mod telemetry;
+pub mod gc;
+pub mod audit;
+pub mod secrets;
@@ -238,7 +238,7 @@ fn construct_envs(version: &Version, node_config_path: &Path, node_config: &Node
         NodeKindConfig::Central(central) => {
             // Now we do a little ugly something, but we unpack the paths and ports here so that we get compile errors if we add more later on
             let CommonPaths{ certs, packages } = &node_config.paths;
-            let CentralPaths{ infra } = &central.paths;
+            let CentralPaths{ infra, audit, checkpoints } = &central.paths;
             let CentralPorts{ api, drv }       = &central.ports;
 
             // Add the environment variables, which are basically just central-specific paths and ports to mount in the compose file
@@ -258,13 +258,22 @@ fn construct_envs(version: &Version, node_config_path: &Path, node_config: &Node
                 ("API_PORT", OsString::from(format!("{}", api.port()))),
                 ("DRV_PORT", OsString::from(format!("{}", drv.port()))),
             ]);
+
+            // The audit log is optional; if omitted, the compose file mounts a dummy instead
+            if let Some(audit) = audit {
+                res.insert("AUDIT", canonicalize(node_config_dir.join(audit))?.as_os_str().into());
+            }
+            // Same for the checkpoints directory
+            if let Some(checkpoints) = checkpoints {
+                res.insert("CHECKPOINTS", canonicalize(node_config_dir.join(checkpoints))?.as_os_str().into());
+            }
         },
 
         NodeKindConfig::Worker(worker) => {
             // Now we do a little ugly something, but we unpack the paths here so that we get compile errors if we add more later on
-            let CommonPaths{ certs, packages }                                           = &node_config.paths;
-            let WorkerPaths{ backend, policies, data, results, temp_data, temp_results } = &worker.paths;
-            let WorkerPorts{ reg, job }                                                  = &worker.ports;
+            let CommonPaths{ certs, packages }                                                                  = &node_config.paths;
+            let WorkerPaths{ backend, policies, data, results, temp_data, temp_results, store, audit, secrets } = &worker.paths;
+            let WorkerPorts{ reg, job }                                                                         = &worker.ports;
 
             // Add the environment variables, which are basically just central-specific paths to mount in the compose file
             res.extend([
@@ -286,11 +295,21 @@ fn construct_envs(version: &Version, node_config_path: &Path, node_config: &Node
                 ("RESULTS", canonicalize(node_config_dir.join(results))?.as_os_str().into()),
                 ("TEMP_DATA", canonicalize(node_config_dir.join(temp_data))?.as_os_str().into()),
                 ("TEMP_RESULTS", canonicalize(node_config_dir.join(temp_results))?.as_os_str().into()),
+                ("STORE", canonicalize(node_config_dir.join(store))?.as_os_str().into()),
 
                 // Ports
                 ("REG_PORT", OsString::from(format!("{}", reg.port()))),
                 ("JOB_PORT", OsString::from(format!("{}", job.port()))),
             ]);
+
+            // The audit log is optional; if omitted, the compose file mounts a dummy instead
+            if let Some(audit) = audit {
+                res.insert("AUDIT", canonicalize(node_config_dir.join(audit))?.as_os_str().into());
+            }
+            // The same goes for the secrets
+            if let Some(secrets) = secrets {
+                res.insert("SECRETS", canonicalize(node_config_dir.join(secrets))?.as_os_str().into());
+            }
         },
     }
 
@@ -14,6 +14,7 @@
 
 use std::path::PathBuf;
 
+use chrono::{DateTime, Utc};
 use clap::{Parser, Subcommand};
 use dotenvy::dotenv;
 use log::{error, LevelFilter};
@@ -23,7 +24,7 @@ use specifications::package::Capability;
 use specifications::version::Version;
 
 use brane_ctl::spec::{DockerClientVersion, GenerateBackendSubcommand, GenerateNodeSubcommand, HostnamePair, LocationPair, StartSubcommand};
-use brane_ctl::{generate, lifetime, packages};
+use brane_ctl::{audit, config, gc, generate, lifetime, packages, secrets, smoketest, telemetry};
 
 
 /***** STATICS *****/
@@ -61,6 +62,9 @@ enum CtlSubcommand {
     #[clap(subcommand)]
     Certs(Box<CertSubcommand>),
 
+    #[clap(subcommand)]
+    Config(Box<ConfigSubcommand>),
+
     #[clap(subcommand)]
     Packages(Box<PackageSubcommand>),
 
@@ -90,6 +94,47 @@ enum CtlSubcommand {
         kind : Box<StartSubcommand>,
     },
 
+    #[clap(name = "smoketest", about = "Runs an end-to-end canary workflow on the instance to check a freshly started node, reporting at which stage it fails.")]
+    Smoketest {
+        /// The address of the central node's API service.
+        #[clap(short, long, default_value = "http://127.0.0.1:50051", help = "The address of the central node's API service.")]
+        api         : String,
+        /// The address of the central node's driver service.
+        #[clap(short, long, default_value = "grpc://127.0.0.1:50053", help = "The address of the central node's driver service.")]
+        drv         : String,
+        /// The directory of a built hello-world package to upload.
+        #[clap(short='P', long, help = "If given, the directory of a built hello-world package (i.e., with a 'package.yml' and 'image.tar', as created by 'brane build') to upload to the instance first. Otherwise, the package must already be available in the instance.")]
+        package_dir : Option<PathBuf>,
+        /// The name of the hello-world package.
+        #[clap(short, long, default_value = "hello_world", help = "The name of the package to call in the workflow.")]
+        package     : String,
+        /// The name of the function to call.
+        #[clap(short, long, default_value = "hello_world", help = "The name of the function to call in the workflow. It may not take any arguments.")]
+        function    : String,
+        /// The location to run on.
+        #[clap(short, long, help = "The location to run the task on. Defaults to this node if it is a worker node, or else leaves it up to the planner.")]
+        location    : Option<String>,
+    },
+
+    #[clap(subcommand)]
+    Telemetry(Box<TelemetrySubcommand>),
+
+    #[clap(name = "gc", about = "Removes all objects from the content-addressed store of the local worker node that are no longer referenced by any package, dataset or intermediate result.")]
+    Gc {
+        /// Whether to only report what would be removed.
+        #[clap(short, long, help = "If given, only reports which objects would be removed instead of removing them.")]
+        dry_run : bool,
+        /// Whether to release intermediate results too.
+        #[clap(short, long, help = "If given, also releases all intermediate results before collecting. Only use this while no workflows are running on the node, or they may lose their results.")]
+        results : bool,
+    },
+
+    #[clap(subcommand)]
+    Audit(Box<AuditSubcommand>),
+
+    #[clap(subcommand)]
+    Secrets(Box<SecretsSubcommand>),
+
     #[clap(name = "stop", about = "Stops the local node if it is running.")]
     Stop {
         /// The docker-compose file that we start.
@@ -122,6 +167,9 @@ enum GenerateSubcommand {
         /// Defines any proxy node to proxy control messages through.
         #[clap(long, help = "If given, reroutes all control network traffic for this node through the given proxy.")]
         proxy : Option<Address>,
+        /// Defines any OCI registry in which package images are stored.
+        #[clap(long, help = "If given, package images may be stored in (and pulled from) this OCI registry instead of brane-api. Should include the project or namespace (e.g., 'harbor.example.com/brane').")]
+        image_registry : Option<String>,
 
         /// If given, will generate missing directories instead of throwing errors.
         #[clap(short='f', long, help = " If given, will generate any missing directories.")]
@@ -199,6 +247,102 @@ enum CertSubcommand {
     
 }
 
+/// Defines config-related subcommands for the `branectl` tool.
+#[derive(Debug, Subcommand)]
+#[clap(name = "config", about = "Groups commands about the configuration files of the local node.")]
+enum ConfigSubcommand {
+    /// Compares the local configuration files with what the central node expects.
+    #[clap(name = "diff", about = "Compares the hashes of the local node's configuration files (infra.yml, backend.yml and policies.yml) with those that the central node expects, reporting any drift. Exits with a non-zero exit code if any has drifted.")]
+    Diff {
+        /// The address of the central node's API service.
+        #[clap(short, long, help = "The address of the central node's API service to compare with (e.g., 'http://brane-api:50051').")]
+        against : String,
+        /// The path to a local copy of the infrastructure file.
+        #[clap(short, long, help = "If given, also compares the given local copy of the 'infra.yml' file on a worker node. Ignored on a central node, which always compares its own.")]
+        infra   : Option<PathBuf>,
+    },
+}
+
+/// Defines telemetry-related subcommands for the `branectl` tool.
+#[derive(Debug, Subcommand)]
+#[clap(name = "telemetry", about = "Groups commands about reporting anonymized usage statistics of the local node.")]
+enum TelemetrySubcommand {
+    /// Enables reporting usage statistics.
+    #[clap(name = "enable", about = "Opts in to periodically reporting anonymized usage statistics (the number of workflows and tasks run, and the version of the node) to the given endpoint. Takes effect after restarting the node.")]
+    Enable {
+        /// The endpoint to report to.
+        #[clap(short, long, help = "The address of the endpoint to POST reports to. May be omitted if telemetry was enabled before, in which case the previous endpoint is used.")]
+        endpoint : Option<String>,
+        /// The interval between reports.
+        #[clap(short, long, help = "The number of seconds between two reports. Defaults to one day, or to the previous interval if telemetry was enabled before.")]
+        interval : Option<u64>,
+    },
+    /// Disables reporting usage statistics.
+    #[clap(name = "disable", about = "Opts out of reporting anonymized usage statistics. Takes effect after restarting the node.")]
+    Disable {},
+    /// Shows whether usage statistics are reported.
+    #[clap(name = "status", about = "Shows whether the local node reports anonymized usage statistics, and what it reports.")]
+    Status {},
+}
+
+/// Defines audit log-related subcommands for the `branectl` tool.
+#[derive(Debug, Subcommand)]
+#[clap(name = "audit", about = "Groups commands about inspecting the audit log of the local worker node.")]
+enum AuditSubcommand {
+    /// Shows the most recent records.
+    #[clap(name = "tail", about = "Shows the most recent task executions, data downloads and result commits on the local worker node, together with who requested them and what the checker decided.")]
+    Tail {
+        /// The number of records to show.
+        #[clap(short = 'n', long, default_value = "10", help = "The number of records to show.")]
+        lines : usize,
+    },
+    /// Exports the records as JSONL.
+    #[clap(name = "export", about = "Exports the audit log of the local worker node (including rotated files) as JSONL, one record per line and oldest first.")]
+    Export {
+        /// The file to write to.
+        #[clap(short, long, help = "The file to write the records to. Writes them to stdout if omitted.")]
+        output : Option<PathBuf>,
+        /// The start of the exported period.
+        #[clap(short, long, help = "If given, only exports records from this moment onwards (as an RFC 3339 timestamp, e.g., '2026-10-17T00:00:00Z').")]
+        since  : Option<DateTime<Utc>>,
+        /// The end of the exported period.
+        #[clap(short, long, help = "If given, only exports records from before this moment (as an RFC 3339 timestamp).")]
+        until  : Option<DateTime<Utc>>,
+    },
+}
+
+/// Defines secret-related subcommands for the `branectl` tool.
+#[derive(Debug, Subcommand)]
+#[clap(name = "secrets", about = "Groups commands about managing the secrets that the local worker node injects into package containers.")]
+enum SecretsSubcommand {
+    /// Adds or updates a secret.
+    #[clap(name = "set", about = "Adds a secret to the secrets file of the local worker node, or updates its value if it already exists.")]
+    Set {
+        /// The name of the secret.
+        #[clap(name = "NAME", help = "The name of the secret. This is also the name of the environment variable that packages see it as.")]
+        name     : String,
+        /// The value of the secret.
+        #[clap(short, long, help = "The value of the secret. If omitted, it is read as a single line from stdin, which keeps it out of your shell history.")]
+        value    : Option<String>,
+        /// The packages that may use the secret.
+        #[clap(long = "package", help = "The name of a package that may use the secret. Can be given multiple times. If neither this nor '--digest' is given, the secret keeps the allowlist it had; secrets without one cannot be used by any package.")]
+        packages : Vec<String>,
+        /// The package images that may use the secret.
+        #[clap(long = "digest", help = "The digest of a package image that may use the secret, regardless of the package's name. Can be given multiple times.")]
+        digests  : Vec<String>,
+    },
+    /// Removes a secret.
+    #[clap(name = "remove", about = "Removes a secret from the secrets file of the local worker node.")]
+    Remove {
+        /// The name of the secret.
+        #[clap(name = "NAME", help = "The name of the secret to remove.")]
+        name : String,
+    },
+    /// Lists the names of the secrets.
+    #[clap(name = "list", about = "Lists the names (but not the values) of the secrets in the secrets file of the local worker node.")]
+    List {},
+}
+
 /// Defines package-related subcommands for the `branectl` tool.
 #[derive(Debug, Subcommand)]
 #[clap(name = "packages", about = "Groups commands about package management.")]
@@ -251,9 +395,9 @@ async fn main() {
     // Now match on the command
     match args.subcommand {
         CtlSubcommand::Generate(subcommand) => match *subcommand {
-            GenerateSubcommand::Node{ hosts, proxy, fix_dirs, config_path, kind } => {
+            GenerateSubcommand::Node{ hosts, proxy, image_registry, fix_dirs, config_path, kind } => {
                 // Call the thing
-                if let Err(err) = generate::node(args.node_config, hosts, proxy, fix_dirs, config_path, *kind) { error!("{}", err); std::process::exit(1); }
+                if let Err(err) = generate::node(args.node_config, hosts, proxy, image_registry, fix_dirs, config_path, *kind) { error!("{}", err); std::process::exit(1); }
             },
 
             GenerateSubcommand::Infra{ locations, fix_dirs, path, names, reg_ports, job_ports } => {
@@ -275,6 +419,13 @@ async fn main() {
             
         },
 
+        CtlSubcommand::Config(subcommand) => match *subcommand {
+            ConfigSubcommand::Diff{ against, infra } => {
+                // Call the thing
+                if let Err(err) = config::diff(args.node_config, against, infra).await { error!("{}", err); std::process::exit(1); }
+            },
+        },
+
         CtlSubcommand::Packages(subcommand) => match *subcommand {
             PackageSubcommand::Hash{ image } => {
                 // Call the thing
@@ -290,6 +441,47 @@ async fn main() {
             if let Err(err) = lifetime::start(file, docker_socket, docker_version, version, args.node_config, mode, *kind).await { error!("{}", err); std::process::exit(1); }
         },
 
+        CtlSubcommand::Smoketest{ api, drv, package_dir, package, function, location } => {
+            if let Err(err) = smoketest::run(args.node_config, api, drv, package_dir, package, function, location).await { error!("{}", err); std::process::exit(1); }
+        },
+
+        CtlSubcommand::Telemetry(subcommand) => match *subcommand {
+            TelemetrySubcommand::Enable{ endpoint, interval } => {
+                if let Err(err) = telemetry::enable(args.node_config, endpoint, interval) { error!("{}", err); std::process::exit(1); }
+            },
+            TelemetrySubcommand::Disable{} => {
+                if let Err(err) = telemetry::disable(args.node_config) { error!("{}", err); std::process::exit(1); }
+            },
+            TelemetrySubcommand::Status{} => {
+                if let Err(err) = telemetry::status(args.node_config) { error!("{}", err); std::process::exit(1); }
+            },
+        },
+
+        CtlSubcommand::Gc{ dry_run, results } => {
+            if let Err(err) = gc::gc(args.node_config, dry_run, results).await { error!("{}", err); std::process::exit(1); }
+        },
+
+        CtlSubcommand::Audit(subcommand) => match *subcommand {
+            AuditSubcommand::Tail{ lines } => {
+                if let Err(err) = audit::tail(args.node_config, lines) { error!("{}", err); std::process::exit(1); }
+            },
+            AuditSubcommand::Export{ output, since, until } => {
+                if let Err(err) = audit::export(args.node_config, output, since, until) { error!("{}", err); std::process::exit(1); }
+            },
+        },
+
+        CtlSubcommand::Secrets(subcommand) => match *subcommand {
+            SecretsSubcommand::Set{ name, value, packages, digests } => {
+                if let Err(err) = secrets::set(args.node_config, name, value, packages, digests) { error!("{}", err); std::process::exit(1); }
+            },
+            SecretsSubcommand::Remove{ name } => {
+                if let Err(err) = secrets::remove(args.node_config, name) { error!("{}", err); std::process::exit(1); }
+            },
+            SecretsSubcommand::List{} => {
+                if let Err(err) = secrets::list(args.node_config) { error!("{}", err); std::process::exit(1); }
+            },
+        },
+
         CtlSubcommand::Stop{ file } => {
             if let Err(err) = lifetime::stop(file, args.node_config) { error!("{}", err); std::process::exit(1); }
         },
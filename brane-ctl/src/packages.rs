@@ -20,7 +20,8 @@ use std::str::FromStr;
 
 use log::{debug, info, warn};
 
-use brane_cfg::node::NodeConfig;
+use brane_cfg::node::{NodeConfig, NodeKindConfig};
+use brane_shr::cas::{ContentStore, TagKind};
 use brane_tsk::docker;
 use specifications::version::Version;
 
@@ -64,37 +65,62 @@ pub async fn hash(node_config_path: impl Into<PathBuf>, image: impl Into<String>
             Err(err) => { return Err(Error::IllegalNameVersionPair{ raw: image, err }); },
         };
 
-        // Start reading the packages directory
-        let entries: ReadDir = match fs::read_dir(&node_config.paths.packages) {
-            Ok(entries) => entries,
-            Err(err)    => { return Err(Error::DirReadError{ what: "packages", path: node_config.paths.packages, err }); },
+        // Collect the candidate images as (path, `<name>-<version>`) pairs
+        let (dir, candidates): (PathBuf, Vec<(PathBuf, String)>) = match &node_config.node {
+            // Workers keep their images in the content-addressed store
+            NodeKindConfig::Worker(worker) => {
+                let store: ContentStore = match ContentStore::new(&worker.paths.store).await {
+                    Ok(store) => store,
+                    Err(err)  => { return Err(Error::StoreError{ err }); },
+                };
+                let tags: Vec<(String, String)> = match store.tags(TagKind::Package).await {
+                    Ok(tags) => tags,
+                    Err(err) => { return Err(Error::StoreError{ err }); },
+                };
+                (worker.paths.store.clone(), tags.into_iter().map(|(tag, digest)| (store.object_path(digest), tag)).collect())
+            },
+
+            // The central node keeps them as `<name>-<version>.tar` files in the packages directory
+            NodeKindConfig::Central(_) => {
+                let entries: ReadDir = match fs::read_dir(&node_config.paths.packages) {
+                    Ok(entries) => entries,
+                    Err(err)    => { return Err(Error::DirReadError{ what: "packages", path: node_config.paths.packages, err }); },
+                };
+                let mut candidates: Vec<(PathBuf, String)> = vec![];
+                for (i, entry) in entries.enumerate() {
+                    // Unwrap the entry
+                    let entry: DirEntry = match entry {
+                        Ok(entry) => entry,
+                        Err(err)  => { return Err(Error::DirEntryReadError { what: "packages", entry: i, path: node_config.paths.packages, err }); },
+                    };
+
+                    // Strip the extension
+                    let entry_name: OsString = entry.file_name();
+                    let entry_name: Cow<str> = entry_name.to_string_lossy();
+                    if entry_name.starts_with('.') { continue; }
+                    match entry_name.rfind('.') {
+                        Some(pos) => { candidates.push((entry.path(), entry_name[..pos].into())); },
+                        None      => { warn!("Missing extension dot ('.') in file '{}' (skipping)", entry.path().display()); },
+                    }
+                }
+                (node_config.paths.packages.clone(), candidates)
+            },
         };
-        let mut file: Option<(PathBuf, Version)> = None;
-        for (i, entry) in entries.enumerate() {
-            // Unwrap the entry
-            let entry: DirEntry = match entry {
-                Ok(entry) => entry,
-                Err(err)  => { return Err(Error::DirEntryReadError { what: "packages", entry: i, path: node_config.paths.packages, err }); },
-            };
 
-            // Attempt to analyse the filename by parsing it as a (name, version) pair
-            let entry_name: OsString = entry.file_name();
-            let entry_name: Cow<str> = entry_name.to_string_lossy();
+        let mut file: Option<(PathBuf, Version)> = None;
+        for (path, entry_name) in candidates {
+            // Attempt to analyse the name by parsing it as a (name, version) pair
             let dash_pos: usize = match entry_name.find('-') {
                 Some(pos) => pos,
-                None      => { warn!("Missing dash ('-') in file '{}' (skipping)", entry.path().display()); continue; }
-            };
-            let dot_pos: usize = match entry_name.rfind('.') {
-                Some(pos) => pos,
-                None      => { warn!("Missing extension dot ('.') in file '{}' (skipping)", entry.path().display()); continue; }
+                None      => { warn!("Missing dash ('-') in image '{}' (skipping)", path.display()); continue; }
             };
             let ename    : &str = &entry_name[..dash_pos];
-            let eversion : &str = &entry_name[dash_pos + 1..dot_pos];
+            let eversion : &str = &entry_name[dash_pos + 1..];
 
             // Attempt to parse the eversion
             let eversion: Version = match Version::from_str(eversion) {
                 Ok(eversion) => eversion,
-                Err(err)     => { warn!("File '{}' has illegal version number '{}': {} (skipping)", entry.path().display(), eversion, err); continue; },  
+                Err(err)     => { warn!("Image '{}' has illegal version number '{}': {} (skipping)", path.display(), eversion, err); continue; },  
             };
 
             // Check if this package checks out
@@ -104,12 +130,12 @@ pub async fn hash(node_config_path: impl Into<PathBuf>, image: impl Into<String>
                     // Check if it's 'latest' too or the highest
                     if eversion.is_latest() || file.is_none() || eversion > file.as_ref().unwrap().1 {
                         let is_latest: bool = eversion.is_latest();
-                        file = Some((entry.path(), eversion));
+                        file = Some((path, eversion));
                         if is_latest { break; }
                     }
                 } else if version == eversion {
                     // Always accept it and stop searching
-                    file = Some((entry.path(), eversion));
+                    file = Some((path, eversion));
                     break;
                 }
             }
@@ -119,7 +145,7 @@ pub async fn hash(node_config_path: impl Into<PathBuf>, image: impl Into<String>
         if let Some((path, _)) = file {
             image_path = path;
         } else {
-            return Err(Error::UnknownImage{ path: node_config.paths.packages, name, version });
+            return Err(Error::UnknownImage{ path: dir, name, version });
         }
     }
 
@@ -0,0 +1,191 @@
+//  SECRETS.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 12:15:53
+//  Last edited:
+//    16 Oct 2026, 12:15:53
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements the subcommands that manage the secrets of a worker node,
+//!   which its packages may ask to have injected into their containers.
+// 
+
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead as _, stdin};
+use std::os::unix::fs::OpenOptionsExt as _;
+use std::path::{Path, PathBuf};
+
+use log::{debug, info, warn};
+
+use brane_cfg::node::{NodeConfig, NodeKindConfig};
+use brane_cfg::secrets::{Secret, SecretAccess, SecretsBackend, SecretsFile};
+use specifications::container::is_valid_secret_name;
+
+pub use crate::errors::SecretsError as Error;
+
+
+/***** HELPER FUNCTIONS *****/
+/// Finds the secrets file of the given worker node.
+/// 
+/// # Arguments
+/// - `node_config_path`: The path to the node config file that contains environment settings for this node.
+/// 
+/// # Returns
+/// The path to the secrets file. Note that it may not exist yet.
+/// 
+/// # Errors
+/// This function errors if we failed to load the node config file, the node is not a worker node or it does not define a secrets file.
+fn secrets_path(node_config_path: PathBuf) -> Result<PathBuf, Error> {
+    debug!("Loading node config file '{}'...", node_config_path.display());
+    let node_config: NodeConfig = match NodeConfig::from_path(&node_config_path) {
+        Ok(config) => config,
+        Err(err)   => { return Err(Error::NodeConfigLoadError{ err }); },
+    };
+    match node_config.node {
+        NodeKindConfig::Worker(worker) => match worker.paths.secrets {
+            Some(path) => Ok(path),
+            None       => Err(Error::NoSecretsFile{ path: node_config_path }),
+        },
+        NodeKindConfig::Central(_) => Err(Error::NotAWorker{ path: node_config_path }),
+    }
+}
+
+/// Loads the secrets stored in the given secrets file.
+/// 
+/// # Arguments
+/// - `path`: The path to the secrets file. If it does not exist, it is treated as an empty one.
+/// 
+/// # Returns
+/// The secrets in the file and their allowlists, both mapped by name.
+/// 
+/// # Errors
+/// This function errors if we failed to load the file, or if it refers to Vault instead.
+fn load_secrets(path: &Path) -> Result<(HashMap<String, Secret>, HashMap<String, SecretAccess>), Error> {
+    if !path.exists() { return Ok((HashMap::new(), HashMap::new())); }
+    debug!("Loading secrets file '{}'...", path.display());
+    match SecretsFile::from_path(path) {
+        Ok(SecretsFile{ backend: SecretsBackend::File{ secrets }, access }) => Ok((secrets, access)),
+        Ok(SecretsFile{ backend: SecretsBackend::Vault(_), .. })            => Err(Error::NotAFileBackend{ path: path.into() }),
+        Err(err)                                                           => Err(Error::FileLoadError{ err }),
+    }
+}
+
+/// Writes the given secrets to the given secrets file, which is only readable by its owner.
+/// 
+/// # Arguments
+/// - `path`: The path to the secrets file to (over)write.
+/// - `secrets`: The secrets to write, mapped by name.
+/// - `access`: The allowlists of the secrets to write, mapped by name.
+/// 
+/// # Errors
+/// This function errors if we failed to create or write the file.
+fn store_secrets(path: &Path, secrets: HashMap<String, Secret>, access: HashMap<String, SecretAccess>) -> Result<(), Error> {
+    debug!("Writing secrets file '{}'...", path.display());
+    let handle: File = match OpenOptions::new().write(true).create(true).truncate(true).mode(0o600).open(path) {
+        Ok(handle) => handle,
+        Err(err)   => { return Err(Error::FileCreateError{ path: path.into(), err }); },
+    };
+    match (SecretsFile{ backend: SecretsBackend::File{ secrets }, access }).to_writer(handle) {
+        Ok(_)    => Ok(()),
+        Err(err) => Err(Error::FileWriteError{ path: path.into(), err }),
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Adds a secret to the secrets file of a worker node, or replaces its value if it already exists.
+/// 
+/// # Arguments
+/// - `node_config_path`: The path to the node config file that contains environment settings for this node.
+/// - `name`: The name of the secret (and of the environment variable it becomes in the container).
+/// - `value`: The value of the secret. If omitted, it is read as a single line from stdin instead, so it does not end up in the shell history.
+/// - `packages`: The names of the packages that may use the secret. If both this and `digests` are empty, the secret keeps the allowlist it had (if any).
+/// - `digests`: The digests of the package images that may use the secret.
+/// 
+/// # Returns
+/// Nothing, but does update the secrets file of the node.
+/// 
+/// # Errors
+/// This function errors if the name is illegal, or if we failed to read the value or to update the secrets file.
+pub fn set(node_config_path: impl Into<PathBuf>, name: String, value: Option<String>, packages: Vec<String>, digests: Vec<String>) -> Result<(), Error> {
+    let node_config_path: PathBuf = node_config_path.into();
+    info!("Setting secret '{}' of node '{}'...", name, node_config_path.display());
+    if !is_valid_secret_name(&name) { return Err(Error::IllegalName{ name }); }
+
+    // Get the value
+    let value: String = match value {
+        Some(value) => value,
+        None        => {
+            let mut value: String = String::new();
+            if let Err(err) = stdin().lock().read_line(&mut value) { return Err(Error::ValueReadError{ err }); }
+            value.trim_end_matches(['\r', '\n']).into()
+        },
+    };
+
+    // Update the file
+    let path: PathBuf = secrets_path(node_config_path)?;
+    let (mut secrets, mut access): (HashMap<String, Secret>, HashMap<String, SecretAccess>) = load_secrets(&path)?;
+    if !packages.is_empty() || !digests.is_empty() {
+        access.insert(name.clone(), SecretAccess{ packages, digests });
+    } else if !access.contains_key(&name) {
+        warn!("Secret '{}' is not allowed to be used by any package yet (give '--package' or '--digest' to allow it)", name);
+    }
+    secrets.insert(name, value.into());
+    store_secrets(&path, secrets, access)
+}
+
+/// Removes a secret from the secrets file of a worker node.
+/// 
+/// # Arguments
+/// - `node_config_path`: The path to the node config file that contains environment settings for this node.
+/// - `name`: The name of the secret to remove.
+/// 
+/// # Returns
+/// Nothing, but does update the secrets file of the node.
+/// 
+/// # Errors
+/// This function errors if the secret does not exist or if we failed to update the secrets file.
+pub fn remove(node_config_path: impl Into<PathBuf>, name: String) -> Result<(), Error> {
+    let node_config_path: PathBuf = node_config_path.into();
+    info!("Removing secret '{}' of node '{}'...", name, node_config_path.display());
+
+    let path: PathBuf = secrets_path(node_config_path)?;
+    let (mut secrets, mut access): (HashMap<String, Secret>, HashMap<String, SecretAccess>) = load_secrets(&path)?;
+    if secrets.remove(&name).is_none() { return Err(Error::UnknownSecret{ name }); }
+    access.remove(&name);
+    store_secrets(&path, secrets, access)
+}
+
+/// Lists the names (but never the values) of the secrets in the secrets file of a worker node, together with who may use them.
+/// 
+/// # Arguments
+/// - `node_config_path`: The path to the node config file that contains environment settings for this node.
+/// 
+/// # Returns
+/// Nothing, but does print the names to `stdout`.
+/// 
+/// # Errors
+/// This function errors if we failed to load the secrets file.
+pub fn list(node_config_path: impl Into<PathBuf>) -> Result<(), Error> {
+    let node_config_path: PathBuf = node_config_path.into();
+    info!("Listing secrets of node '{}'...", node_config_path.display());
+
+    let path: PathBuf = secrets_path(node_config_path)?;
+    let (secrets, access): (HashMap<String, Secret>, HashMap<String, SecretAccess>) = load_secrets(&path)?;
+    let mut names: Vec<String> = secrets.into_keys().collect();
+    names.sort();
+    for name in names {
+        match access.get(&name) {
+            Some(access) => println!("{} (packages: {}; digests: {})", name, access.packages.join(", "), access.digests.join(", ")),
+            None         => println!("{} (not allowed for any package)", name),
+        }
+    }
+    Ok(())
+}
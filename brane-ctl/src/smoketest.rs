@@ -0,0 +1,339 @@
+//  SMOKETEST.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 09:33:50
+//  Last edited:
+//    16 Oct 2026, 12:59:49
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements the `branectl smoketest` subcommand, which runs an
+//!   end-to-end canary workflow on a freshly started instance to see if
+//!   all of its services play nice together.
+// 
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use console::style;
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use log::{debug, info, warn};
+use tonic::Streaming;
+use tonic::transport::Channel;
+
+use brane_ast::{compile_program, CompileResult, ParserOptions, Workflow};
+use brane_cfg::node::NodeConfig;
+use brane_tsk::api::ApiClient;
+use brane_tsk::grpc::{CreateSessionRequest, DriverServiceClient, ExecuteReply, ExecuteRequest};
+use brane_tsk::spec::AppId;
+use specifications::data::DataIndex;
+use specifications::package::PackageIndex;
+
+pub use crate::errors::SmoketestError as Error;
+
+
+/***** CONSTANTS *****/
+/// The name of the dataset that is registered as part of the smoke test.
+pub const SMOKETEST_DATASET: &str = "brane_smoketest";
+
+/// The number of times we ask the API for the dataset before giving up.
+const DATASET_ATTEMPTS: usize = 10;
+/// The time between asking the API for the dataset.
+const DATASET_INTERVAL: Duration = Duration::from_secs(1);
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Prints the start of a stage.
+/// 
+/// # Arguments
+/// - `i`: The (one-indexed) number of the stage.
+/// - `stage`: The name of the stage.
+fn start_stage(i: usize, stage: &str) {
+    print!("[{}/6] {:<8} ... ", i, stage);
+    if let Err(err) = std::io::stdout().flush() { warn!("Failed to flush stdout: {}", err); }
+}
+
+/// Prints the result of a stage.
+/// 
+/// # Arguments
+/// - `res`: The result of the stage. If it is `Ok`, its value is a (short) description of what we found.
+/// 
+/// # Returns
+/// The given result, for chaining.
+fn end_stage<T>(res: Result<(T, String), Error>) -> Result<T, Error> {
+    match res {
+        Ok((res, detail)) => { println!("{} ({})", style("ok").bold().green(), detail); Ok(res) },
+        Err(err)          => { println!("{}", style("FAILED").bold().red()); Err(err) },
+    }
+}
+
+
+
+/// Uploads the package in the given directory to the instance.
+/// 
+/// # Arguments
+/// - `api`: The address of the central node's API service.
+/// - `package_dir`: The directory with the `package.yml` and `image.tar` of the package.
+/// 
+/// # Errors
+/// This function errors if we failed to archive the package or the API did not accept it.
+async fn upload_package(api: &str, package_dir: &Path) -> Result<(), Error> {
+    // Archive the package in-memory
+    debug!("Archiving package '{}'...", package_dir.display());
+    let mut tar = tar::Builder::new(GzEncoder::new(vec![], Compression::fast()));
+    for file in [ "package.yml", "image.tar" ] {
+        if let Err(err) = tar.append_path_with_name(package_dir.join(file), file) { return Err(Error::PackageArchiveError{ path: package_dir.into(), err }); }
+    }
+    let archive: Vec<u8> = match tar.into_inner().and_then(|gz| gz.finish()) {
+        Ok(archive) => archive,
+        Err(err)    => { return Err(Error::PackageArchiveError{ path: package_dir.into(), err }); },
+    };
+
+    // Upload it
+    let address: String = format!("{}/packages", api);
+    debug!("Uploading package to '{}'...", address);
+    let res: reqwest::Response = match reqwest::Client::new().post(&address).header("Content-Type", "application/gzip").body(archive).send().await {
+        Ok(res)  => res,
+        Err(err) => { return Err(Error::RequestError{ stage: "package", address, err }); },
+    };
+    if !res.status().is_success() {
+        return Err(Error::RequestFailure{ stage: "package", address, code: res.status(), message: res.text().await.ok() });
+    }
+    Ok(())
+}
+
+/// Writes the smoke test dataset to the given data directory.
+/// 
+/// # Arguments
+/// - `data_dir`: The data directory of the local worker node.
+/// 
+/// # Returns
+/// The path of the directory with the new dataset.
+/// 
+/// # Errors
+/// This function errors if we failed to write the dataset's files.
+fn write_dataset(data_dir: &Path) -> Result<PathBuf, Error> {
+    let dir: PathBuf = data_dir.join(SMOKETEST_DATASET);
+    if let Err(err) = fs::create_dir_all(&dir) { return Err(Error::DatasetWriteError{ path: dir, err }); }
+
+    // Write the data itself...
+    let data_path: PathBuf = dir.join("data.txt");
+    if let Err(err) = fs::write(&data_path, "Hello, world!\n") { return Err(Error::DatasetWriteError{ path: data_path, err }); }
+
+    // ...and the file describing it
+    let info_path: PathBuf = dir.join("data.yml");
+    let info: String = format!("name: {}\ndescription: Small dataset registered by 'branectl smoketest'. Safe to remove.\naccess:\n  kind: file\n  path: {}\n", SMOKETEST_DATASET, data_path.display());
+    let mut handle: File = match File::create(&info_path) {
+        Ok(handle) => handle,
+        Err(err)   => { return Err(Error::DatasetWriteError{ path: info_path, err }); },
+    };
+    if let Err(err) = handle.write_all(info.as_bytes()) { return Err(Error::DatasetWriteError{ path: info_path, err }); }
+
+    // Done
+    Ok(dir)
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Runs an end-to-end smoke test on the instance, reporting at which stage it fails (if any).
+/// 
+/// The stages are:
+/// 1. `api`: Checks that the central node's API service is up.
+/// 2. `package`: Uploads the hello-world package (if given) and checks that the instance knows it.
+/// 3. `dataset`: Registers a small dataset on the local worker node and checks that the instance knows it. Skipped on a central node.
+/// 4. `compile`: Compiles a workflow that calls the package's function once.
+/// 5. `driver`: Creates a new session on the central node's driver service.
+/// 6. `execute`: Runs the workflow through the driver, planner and worker.
+/// 
+/// # Arguments
+/// - `node_config_path`: The path to the node config file that contains environment settings for this node.
+/// - `api`: The address of the central node's API service.
+/// - `drv`: The address of the central node's driver service.
+/// - `package_dir`: If given, the directory of a built hello-world package (i.e., with a `package.yml` and `image.tar`) to upload first. Otherwise, it must already be available in the instance.
+/// - `package`: The name of the hello-world package.
+/// - `function`: The name of the function in the package to call. It should not take any arguments.
+/// - `location`: If given, the location to run the task on. On a worker node, defaults to the node itself.
+/// 
+/// # Returns
+/// Nothing directly, but does print the progress of the test to `stdout`.
+/// 
+/// # Errors
+/// This function errors if any of the stages fails.
+pub async fn run(node_config_path: impl Into<PathBuf>, api: impl Into<String>, drv: impl Into<String>, package_dir: Option<PathBuf>, package: impl Into<String>, function: impl Into<String>, location: Option<String>) -> Result<(), Error> {
+    let node_config_path : PathBuf = node_config_path.into();
+    let api              : String  = api.into();
+    let drv              : String  = drv.into();
+    let package          : String  = package.into();
+    let function         : String  = function.into();
+    info!("Running smoke test on instance (API '{}', driver '{}')...", api, drv);
+
+    // Load the node config file
+    debug!("Loading node config file '{}'...", node_config_path.display());
+    let node_config: NodeConfig = match NodeConfig::from_path(&node_config_path) {
+        Ok(config) => config,
+        Err(err)   => { return Err(Error::NodeConfigLoadError{ err }); },
+    };
+    let location: Option<String> = location.or_else(|| if node_config.node.is_worker() { Some(node_config.node.worker().location_id.clone()) } else { None });
+
+    // Stage 1: is the API up?
+    start_stage(1, "api");
+    end_stage(async {
+        let address: String = format!("{}/health", api);
+        let res: reqwest::Response = match reqwest::get(&address).await {
+            Ok(res)  => res,
+            Err(err) => { return Err(Error::RequestError{ stage: "api", address, err }); },
+        };
+        if !res.status().is_success() {
+            return Err(Error::RequestFailure{ stage: "api", address, code: res.status(), message: res.text().await.ok() });
+        }
+        Ok(((), format!("'{}' is healthy", api)))
+    }.await)?;
+
+    // Stage 2: do we have the package?
+    start_stage(2, "package");
+    let pindex: PackageIndex = end_stage(async {
+        if let Some(package_dir) = &package_dir { upload_package(&api, package_dir).await?; }
+
+        let address: String = format!("{}/graphql", api);
+        let pindex: PackageIndex = match brane_tsk::api::get_package_index(&address).await {
+            Ok(pindex) => pindex,
+            Err(err)   => { return Err(Error::PackageIndexError{ address, err }); },
+        };
+        let version: String = match pindex.get(&package, None) {
+            Some(info) => info.version.to_string(),
+            None       => { return Err(Error::UnknownPackage{ name: package.clone(), uploaded: package_dir.is_some() }); },
+        };
+        Ok((pindex, format!("found '{}' version {}{}", package, version, if package_dir.is_some() { " after uploading" } else { "" })))
+    }.await)?;
+
+    // Stage 3: can we register a dataset?
+    start_stage(3, "dataset");
+    let dataset_dir: Option<PathBuf> = if node_config.node.is_worker() {
+        let location_id: &str = &node_config.node.worker().location_id;
+        end_stage(async {
+            let dir: PathBuf = write_dataset(&node_config.node.worker().paths.data)?;
+
+            // Wait for the registry to pick it up
+            let address: String = format!("{}/data/info", api);
+            for i in 0..DATASET_ATTEMPTS {
+                let dindex: DataIndex = match ApiClient::new(&api).data_index().await {
+                    Ok(dindex) => dindex,
+                    Err(err)   => { return Err(Error::DataIndexError{ address, err }); },
+                };
+                if dindex.get(SMOKETEST_DATASET).map(|info| info.access.keys().any(|l| l == location_id)).unwrap_or(false) {
+                    return Ok((Some(dir), format!("'{}' is available at '{}'", SMOKETEST_DATASET, location_id)));
+                }
+                debug!("Dataset '{}' not yet available (attempt {}/{})", SMOKETEST_DATASET, i + 1, DATASET_ATTEMPTS);
+                tokio::time::sleep(DATASET_INTERVAL).await;
+            }
+            Err(Error::DatasetNotFound{ name: SMOKETEST_DATASET.into(), location: location_id.into(), path: dir })
+        }.await)?
+    } else {
+        println!("{} (not a worker node)", style("skipped").bold().yellow());
+        None
+    };
+
+    // Stage 4-6: run a workflow (but clean the dataset regardless of how that goes)
+    let res: Result<(), Error> = async {
+        // Stage 4: can we compile the workflow?
+        start_stage(4, "compile");
+        let workflow: String = end_stage(async {
+            let call: String = format!("return {}();", function);
+            let source: String = match &location {
+                Some(location) => format!("import {};\non \"{}\" {{\n    {}\n}}\n", package, location, call),
+                None           => format!("import {};\n{}\n", package, call),
+            };
+            debug!("Compiling workflow:\n{}", source);
+
+            // Fetch the data index like any client would, then compile
+            let address: String = format!("{}/data/info", api);
+            let dindex: DataIndex = match ApiClient::new(&api).data_index().await {
+                Ok(dindex) => dindex,
+                Err(err)   => { return Err(Error::DataIndexError{ address, err }); },
+            };
+            let workflow: Workflow = match compile_program(source.as_bytes(), &pindex, &dindex, &ParserOptions::bscript()) {
+                CompileResult::Workflow(workflow, _) => workflow,
+                CompileResult::Err(errs) => {
+                    for err in &errs { err.prettyprint("<smoketest>", &source); }
+                    return Err(Error::CompileError{ errs });
+                },
+                CompileResult::Eof(err) => {
+                    err.prettyprint("<smoketest>", &source);
+                    return Err(Error::CompileError{ errs: vec![ err ] });
+                },
+                CompileResult::Unresolved(_, _) | CompileResult::Program(_, _) => unreachable!(),
+            };
+            match serde_json::to_string(&workflow) {
+                Ok(workflow) => Ok((workflow, format!("one call to '{}'{}", function, if let Some(location) = &location { format!(" on '{}'", location) } else { String::new() }))),
+                Err(err)     => Err(Error::WorkflowSerializeError{ err }),
+            }
+        }.await)?;
+
+        // Stage 5: can we reach the driver?
+        start_stage(5, "driver");
+        let (mut client, session): (DriverServiceClient<Channel>, AppId) = end_stage(async {
+            let mut client: DriverServiceClient<Channel> = match DriverServiceClient::connect(drv.clone()).await {
+                Ok(client) => client,
+                Err(err)   => { return Err(Error::DriverConnectError{ address: drv.clone(), err }); },
+            };
+            let raw: String = match client.create_session(CreateSessionRequest{}).await {
+                Ok(reply) => reply.into_inner().uuid,
+                Err(err)  => { return Err(Error::SessionCreateError{ address: drv.clone(), err }); },
+            };
+            let session: AppId = match AppId::from_str(&raw) {
+                Ok(session) => session,
+                Err(err)    => { return Err(Error::SessionIdParseError{ raw, err }); },
+            };
+            let detail: String = format!("session '{}'", session);
+            Ok(((client, session), detail))
+        }.await)?;
+
+        // Stage 6: does the workflow run?
+        start_stage(6, "execute");
+        end_stage(async {
+            let mut stream: Streaming<ExecuteReply> = match client.execute(ExecuteRequest{ uuid: session.to_string(), input: workflow, detach: false, max_steps: None }).await {
+                Ok(stream) => stream.into_inner(),
+                Err(err)   => { return Err(Error::ExecuteError{ address: drv.clone(), err }); },
+            };
+            let mut value  : Option<String> = None;
+            let mut stderr : Vec<String>    = vec![];
+            loop {
+                match stream.message().await {
+                    Ok(Some(reply)) => {
+                        if reply.heartbeat { continue; }
+                        if let Some(debug) = reply.debug { debug!("Remote: {}", debug); }
+                        if let Some(err) = reply.stderr { stderr.push(err); }
+                        if reply.value.is_some() { value = reply.value; }
+                        if reply.close { break; }
+                    },
+                    Ok(None)    => { break; },
+                    Err(status) => { return Err(Error::ExecuteError{ address: drv.clone(), err: status }); },
+                }
+            }
+            if !stderr.is_empty() { return Err(Error::WorkflowFailed{ stderr: stderr.join("\n") }); }
+            Ok(((), format!("returned {}", value.unwrap_or_else(|| "nothing".into()))))
+        }.await)
+    }.await;
+
+    // Remove the dataset again
+    if let Some(dir) = dataset_dir {
+        debug!("Removing smoke test dataset '{}'...", dir.display());
+        if let Err(err) = fs::remove_dir_all(&dir) { warn!("Failed to remove smoke test dataset '{}': {}", dir.display(), err); }
+    }
+
+    // Done
+    if res.is_ok() { println!("\nSmoke test {}", style("passed").bold().green()); }
+    res
+}
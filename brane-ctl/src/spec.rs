@@ -22,6 +22,7 @@ use clap::Subcommand;
 use enum_debug::EnumDebug;
 
 use brane_tsk::docker::ImageSource;
+use specifications::package::Capability;
 
 use crate::errors::{DockerClientVersionParseError, HostnamePairParseError, LocationPairParseError};
 
@@ -151,13 +152,19 @@ pub enum GenerateNodeSubcommand {
     Central {
         /// Custom `infra.yml` path.
         #[clap(short, long, default_value = "$CONFIG/infra.yml", help = "The location of the 'infra.yml' file. Use '$CONFIG' to reference the value given by --config-path.")]
-        infra    : PathBuf,
+        infra       : PathBuf,
         /// Custom certificates path.
         #[clap(short, long, default_value = "$CONFIG/certs", help = "The location of the certificate directory. Use '$CONFIG' to reference the value given by --config-path.")]
-        certs    : PathBuf,
+        certs       : PathBuf,
         /// Custom packages path.
         #[clap(long, default_value = "./packages", help = "The location of the package directory.")]
-        packages : PathBuf,
+        packages    : PathBuf,
+        /// Custom audit log path.
+        #[clap(long, default_value = "$CONFIG/audit.log", help = "The location of the audit log, to which decisions such as workflow approvals are appended. Will be created if it does not exist. Use '$CONFIG' to reference the value given by --config-path.")]
+        audit       : PathBuf,
+        /// Custom checkpoints path.
+        #[clap(long, default_value = "$CONFIG/checkpoints", help = "The location of the directory in which the driver checkpoints running workflows, so they can be resumed after a restart. Use '$CONFIG' to reference the value given by --config-path.")]
+        checkpoints : PathBuf,
 
         /// The name of the proxy service.
         #[clap(long, default_value = "brane-prx", help = "The name of the proxy service's container.")]
@@ -221,6 +228,22 @@ pub enum GenerateNodeSubcommand {
         /// Custom results path.
         #[clap(short = 'R', long, default_value = "/tmp/results", help = "The location of the temporary/download results directory.")]
         temp_results : PathBuf,
+        /// Custom store path.
+        #[clap(long, default_value = "./store", help = "The location of the content-addressed store that keeps package images, committed datasets and intermediate results. Clean it up with `branectl gc`.")]
+        store        : PathBuf,
+        /// Custom audit log path.
+        #[clap(long, default_value = "./audit", help = "The directory of the audit log, to which every task execution, data download and result commit is appended. Inspect it with `branectl audit`.")]
+        audit        : PathBuf,
+
+        /// The maximum scratch space per task.
+        #[clap(long, default_value = "1073741824", help = "The maximum size (in bytes) of the private scratch space that is provisioned for every task.")]
+        max_scratch_size : u64,
+        /// The disk space to always keep free.
+        #[clap(long, default_value = "5368709120", help = "The amount of disk space (in bytes) that is always kept free on the node. Tasks that would eat into it are refused.")]
+        min_free_space   : u64,
+        /// The capabilities to advertise.
+        #[clap(long = "capability", help = "A capability (e.g., 'cuda' or 'x86_64') that this node advertises on top of those of its backend. May be given multiple times.")]
+        capabilities     : Vec<Capability>,
 
         /// The name of the proxy service.
         #[clap(long, default_value = "brane-prx-$LOCATION", help = "The name of the local proxy service's container. Use '$LOCATION' to use the location ID.")]
@@ -0,0 +1,144 @@
+//  TELEMETRY.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 09:39:28
+//  Last edited:
+//    16 Oct 2026, 09:39:28
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements subcommands that control whether a node reports
+//!   anonymized usage statistics.
+// 
+
+use std::path::{Path, PathBuf};
+
+use console::style;
+use log::{debug, info};
+
+use brane_cfg::node::NodeConfig;
+use brane_shr::telemetry::TelemetryConfig;
+
+pub use crate::errors::TelemetryError as Error;
+
+
+/***** HELPER FUNCTIONS *****/
+/// Loads the node config file at the given path.
+/// 
+/// # Arguments
+/// - `node_config_path`: The path to the node config file to load.
+/// 
+/// # Returns
+/// The loaded NodeConfig.
+/// 
+/// # Errors
+/// This function errors if we failed to load the file.
+fn load_node_config(node_config_path: &Path) -> Result<NodeConfig, Error> {
+    debug!("Loading node config file '{}'...", node_config_path.display());
+    match NodeConfig::from_path(node_config_path) {
+        Ok(config) => Ok(config),
+        Err(err)   => Err(Error::NodeConfigLoadError{ err }),
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Enables reporting anonymized usage statistics for this node.
+/// 
+/// # Arguments
+/// - `node_config_path`: The path to the node config file that contains environment settings for this node.
+/// - `endpoint`: The address of the endpoint to report to. May only be omitted if the node has reported before, in which case the previous endpoint is used.
+/// - `interval`: If given, the number of seconds between two reports.
+/// 
+/// # Returns
+/// Nothing, but does update the node config file.
+/// 
+/// # Errors
+/// This function errors if we failed to read or write the node config file, or if no endpoint is known.
+pub fn enable(node_config_path: impl Into<PathBuf>, endpoint: Option<String>, interval: Option<u64>) -> Result<(), Error> {
+    let node_config_path: PathBuf = node_config_path.into();
+    info!("Enabling telemetry for node '{}'...", node_config_path.display());
+
+    // Update the config, keeping the instance identifier if we had one
+    let mut node_config: NodeConfig = load_node_config(&node_config_path)?;
+    let mut telemetry: TelemetryConfig = match (node_config.telemetry.take(), endpoint) {
+        (Some(mut telemetry), endpoint) => {
+            if let Some(endpoint) = endpoint { telemetry.endpoint = endpoint; }
+            telemetry.enabled = true;
+            telemetry
+        },
+        (None, Some(endpoint)) => TelemetryConfig::new(endpoint),
+        (None, None)           => { return Err(Error::MissingEndpoint); },
+    };
+    if let Some(interval) = interval { telemetry.interval = interval; }
+    println!("Reporting anonymized usage statistics to {} every {} seconds", style(&telemetry.endpoint).bold().cyan(), telemetry.interval);
+    node_config.telemetry = Some(telemetry);
+
+    // Write it back
+    if let Err(err) = node_config.to_path(&node_config_path) { return Err(Error::NodeConfigWriteError{ err }); }
+    println!("Restart the node for the change to take effect.");
+    Ok(())
+}
+
+/// Disables reporting anonymized usage statistics for this node.
+/// 
+/// # Arguments
+/// - `node_config_path`: The path to the node config file that contains environment settings for this node.
+/// 
+/// # Returns
+/// Nothing, but does update the node config file.
+/// 
+/// # Errors
+/// This function errors if we failed to read or write the node config file.
+pub fn disable(node_config_path: impl Into<PathBuf>) -> Result<(), Error> {
+    let node_config_path: PathBuf = node_config_path.into();
+    info!("Disabling telemetry for node '{}'...", node_config_path.display());
+
+    // Update the config if there is anything to disable
+    let mut node_config: NodeConfig = load_node_config(&node_config_path)?;
+    match &mut node_config.telemetry {
+        Some(telemetry) if telemetry.enabled => { telemetry.enabled = false; },
+        _                                    => { println!("Telemetry is already disabled."); return Ok(()); },
+    }
+
+    // Write it back
+    if let Err(err) = node_config.to_path(&node_config_path) { return Err(Error::NodeConfigWriteError{ err }); }
+    println!("Disabled telemetry. Restart the node for the change to take effect.");
+    Ok(())
+}
+
+/// Shows whether this node reports anonymized usage statistics, and what it reports.
+/// 
+/// # Arguments
+/// - `node_config_path`: The path to the node config file that contains environment settings for this node.
+/// 
+/// # Returns
+/// Nothing, but does print the status to `stdout`.
+/// 
+/// # Errors
+/// This function errors if we failed to read the node config file.
+pub fn status(node_config_path: impl Into<PathBuf>) -> Result<(), Error> {
+    let node_config_path: PathBuf = node_config_path.into();
+    let node_config: NodeConfig = load_node_config(&node_config_path)?;
+
+    match &node_config.telemetry {
+        Some(telemetry) if telemetry.enabled => {
+            println!("Telemetry is {}", style("enabled").bold().green());
+            println!("  Endpoint : {}", telemetry.endpoint);
+            println!("  Instance : {}", telemetry.instance);
+            println!("  Interval : {} seconds", telemetry.interval);
+            println!();
+            println!("Every report contains the instance identifier above, the kind and version of the node, and the number of workflows and tasks run since the previous report. Nothing else is sent.");
+        },
+        _ => {
+            println!("Telemetry is {}", style("disabled").bold().red());
+            println!("Enable it with 'branectl telemetry enable --endpoint <ADDRESS>'.");
+        },
+    }
+    Ok(())
+}
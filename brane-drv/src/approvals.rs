@@ -0,0 +1,282 @@
+//  APPROVALS.rs
+//    by agent
+//
+//  Created:
+//    16 Oct 2026, 08:02:35
+//  Last edited:
+//    16 Oct 2026, 13:12:31
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements the registry of pending approvals, which park a workflow
+//!   until a user has decided on them.
+//
+
+use std::collections::HashSet;
+use std::fs::OpenOptions;
+use std::io::Write as _;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use log::info;
+
+use brane_exe::spec::ApprovalDecision;
+use brane_tsk::spec::AppId;
+
+pub use crate::errors::ApprovalError as Error;
+use crate::events::EventRegistry;
+
+
+/***** UNIT TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr as _;
+
+    use super::*;
+
+    /// Returns a registry in which only `alice` may decide, and which writes its audit log to the given path.
+    fn registry(audit: PathBuf) -> ApprovalRegistry {
+        ApprovalRegistry::new(Arc::new(EventRegistry::new()), Some(audit), HashSet::from([ "alice".to_string() ]))
+    }
+
+    /// Returns a decision by the given user.
+    fn decision(user: &str) -> ApprovalDecision { ApprovalDecision{ approved: true, user: user.into(), reason: None } }
+
+    #[tokio::test]
+    async fn test_approve() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry: ApprovalRegistry = registry(dir.path().join("audit.log"));
+        let app_id: AppId = AppId::from_str("3f6b4a35-6e80-4b2b-a25c-5c0f9a0e8d6c").unwrap();
+        let id: String = registry.request(&app_id, "Deploy?");
+
+        // Deciding resumes the waiting workflow with the authenticated user, and records it in the audit log
+        registry.decide(&id, decision("alice")).unwrap();
+        let result: ApprovalDecision = registry.wait(&app_id, &id).await.unwrap();
+        assert!(result.approved);
+        assert_eq!(result.user, "alice");
+        assert!(registry.list().is_empty());
+        let audit: String = std::fs::read_to_string(dir.path().join("audit.log")).unwrap();
+        assert!(audit.contains("\"user\":\"alice\""));
+        assert!(audit.contains(&id));
+    }
+
+    #[test]
+    fn test_reject_unauthorized() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry: ApprovalRegistry = registry(dir.path().join("audit.log"));
+        let app_id: AppId = AppId::from_str("3f6b4a35-6e80-4b2b-a25c-5c0f9a0e8d6c").unwrap();
+        let id: String = registry.request(&app_id, "Deploy?");
+
+        // Users that are not approvers cannot decide, and the approval remains pending
+        assert!(matches!(registry.decide(&id, decision("mallory")), Err(Error::UnauthorizedApprover{ .. })));
+        assert!(matches!(registry.decide(&id, decision("")), Err(Error::UnauthorizedApprover{ .. })));
+        assert_eq!(registry.list().len(), 1);
+        assert!(!dir.path().join("audit.log").exists());
+
+        // Unknown approvals are refused too
+        assert!(matches!(registry.decide("unknown", decision("alice")), Err(Error::UnknownApproval{ .. })));
+    }
+
+    #[test]
+    fn test_reserved_events() {
+        assert!(is_reserved_event("approval/1234-0"));
+        assert!(is_reserved_event("approval/"));
+        assert!(!is_reserved_event("data-ready"));
+        assert!(!is_reserved_event("my-approval/1"));
+    }
+}
+
+
+
+
+
+/***** CONSTANTS *****/
+/// The prefix of the events that carry the decision on an approval.
+const APPROVAL_EVENT_PREFIX: &str = "approval/";
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Defines an approval that is still waiting for a decision.
+#[derive(Clone, Debug)]
+pub struct PendingApproval {
+    /// The session of the workflow that requires the approval.
+    pub app_id    : AppId,
+    /// The message that describes what should be approved.
+    pub message   : String,
+    /// The moment the approval was requested.
+    pub requested : DateTime<Utc>,
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Returns whether the given event name is reserved for the decisions on approvals, and thus may not be sent by clients directly.
+///
+/// # Arguments
+/// - `name`: The name of the event to check.
+///
+/// # Returns
+/// True if the name is reserved, or false otherwise.
+#[inline]
+pub fn is_reserved_event(name: &str) -> bool { name.starts_with(APPROVAL_EVENT_PREFIX) }
+
+
+
+/// Keeps track of the approvals that workflows are waiting for, and records the decisions on them in the audit log.
+#[derive(Debug)]
+pub struct ApprovalRegistry {
+    /// The pending approvals, by their identifier.
+    pending : DashMap<String, PendingApproval>,
+    /// The counter used to generate unique identifiers.
+    next_id : AtomicU64,
+
+    /// The registry used to wake up the workflow once a decision has been made.
+    events : Arc<EventRegistry>,
+    /// The path of the audit log to append decisions to, if any.
+    audit     : Option<PathBuf>,
+    /// The names of the users that may decide on approvals.
+    approvers : HashSet<String>,
+}
+
+impl ApprovalRegistry {
+    /// Constructor for the ApprovalRegistry.
+    ///
+    /// # Arguments
+    /// - `events`: The EventRegistry used to wake up workflows once their approval has been decided on.
+    /// - `audit`: The path of the audit log to append decisions to. If omitted, they are only logged.
+    /// - `approvers`: The names of the users that may decide on approvals (as given in their client certificates).
+    ///
+    /// # Returns
+    /// A new ApprovalRegistry without any pending approvals.
+    #[inline]
+    pub fn new(events: Arc<EventRegistry>, audit: Option<PathBuf>, approvers: HashSet<String>) -> Self {
+        Self {
+            pending : DashMap::new(),
+            next_id : AtomicU64::new(0),
+
+            events,
+            audit,
+            approvers,
+        }
+    }
+
+
+
+    /// Records a new pending approval.
+    ///
+    /// # Arguments
+    /// - `app_id`: The session of the workflow that requires the approval.
+    /// - `message`: The message that describes what should be approved.
+    ///
+    /// # Returns
+    /// The identifier of the new approval.
+    pub fn request(&self, app_id: &AppId, message: impl Into<String>) -> String {
+        let id: String = format!("{}-{}", app_id, self.next_id.fetch_add(1, Ordering::Relaxed));
+        self.pending.insert(id.clone(), PendingApproval{ app_id: app_id.clone(), message: message.into(), requested: Utc::now() });
+        id
+    }
+
+    /// Waits until a decision has been made on the given approval.
+    ///
+    /// # Arguments
+    /// - `app_id`: The session of the workflow that requires the approval.
+    /// - `id`: The identifier of the approval, as returned by `ApprovalRegistry::request()`.
+    ///
+    /// # Returns
+    /// The decision, or `None` if the event channel was closed in the meantime.
+    pub async fn wait(&self, app_id: &AppId, id: &str) -> Option<ApprovalDecision> {
+        let payload: String = self.events.wait(app_id, &format!("{}{}", APPROVAL_EVENT_PREFIX, id)).await?;
+        let decision: serde_json::Value = serde_json::from_str(&payload).ok()?;
+        Some(ApprovalDecision {
+            approved : decision["approved"].as_bool()?,
+            user     : decision["user"].as_str()?.into(),
+            reason   : decision["reason"].as_str().map(String::from),
+        })
+    }
+
+    /// Returns whether the given user may decide on approvals.
+    ///
+    /// # Arguments
+    /// - `user`: The name of the user (as given in their client certificate).
+    ///
+    /// # Returns
+    /// Whether the user is one of the approvers.
+    #[inline]
+    pub fn is_approver(&self, user: &str) -> bool { self.approvers.contains(user) }
+
+    /// Returns the approvals that are still waiting for a decision.
+    ///
+    /// # Returns
+    /// A list of (identifier, approval) pairs, oldest first.
+    pub fn list(&self) -> Vec<(String, PendingApproval)> {
+        let mut pending: Vec<(String, PendingApproval)> = self.pending.iter().map(|e| (e.key().clone(), e.value().clone())).collect();
+        pending.sort_by_key(|(_, a)| a.requested);
+        pending
+    }
+
+    /// Drops the pending approvals of the given session, e.g., because it was killed.
+    ///
+    /// # Arguments
+    /// - `app_id`: The session of which to drop the approvals.
+    ///
+    /// # Returns
+    /// The number of approvals that were dropped.
+    pub fn forget(&self, app_id: &AppId) -> usize {
+        let before: usize = self.pending.len();
+        self.pending.retain(|_, approval| &approval.app_id != app_id);
+        before - self.pending.len()
+    }
+
+    /// Decides on a pending approval, recording the decision in the audit log and resuming the workflow waiting for it.
+    ///
+    /// # Arguments
+    /// - `id`: The identifier of the approval to decide on.
+    /// - `decision`: The ApprovalDecision made by the user. Its `user` must be the authenticated identity of whoever made it.
+    ///
+    /// # Errors
+    /// This function errors if the user is not one of the approvers, if the approval is not (or no longer) pending, or if we failed to write to the audit log. In the latter case, the approval remains pending.
+    pub fn decide(&self, id: &str, decision: ApprovalDecision) -> Result<(), Error> {
+        if !self.approvers.contains(&decision.user) { return Err(Error::UnauthorizedApprover{ user: decision.user }); }
+        let approval: PendingApproval = match self.pending.get(id) {
+            Some(approval) => approval.clone(),
+            None           => { return Err(Error::UnknownApproval{ id: id.into() }); },
+        };
+
+        // Write the decision to the audit log first, so no decision goes unrecorded
+        let record: serde_json::Value = serde_json::json!({
+            "time"      : Utc::now().to_rfc3339(),
+            "kind"      : "approval",
+            "id"        : id,
+            "session"   : approval.app_id.to_string(),
+            "message"   : approval.message,
+            "requested" : approval.requested.to_rfc3339(),
+            "approved"  : decision.approved,
+            "user"      : decision.user,
+            "reason"    : decision.reason,
+        });
+        info!("AUDIT: {}", record);
+        if let Some(audit) = &self.audit {
+            if let Err(err) = OpenOptions::new().create(true).append(true).open(audit).and_then(|mut h| writeln!(h, "{}", record)) {
+                return Err(Error::AuditWriteError{ path: audit.clone(), err });
+            }
+        }
+
+        // Only then resume the workflow
+        if self.pending.remove(id).is_none() { return Err(Error::UnknownApproval{ id: id.into() }); }
+        self.events.send(&approval.app_id, &format!("{}{}", APPROVAL_EVENT_PREFIX, id), serde_json::json!({
+            "approved" : decision.approved,
+            "user"     : decision.user,
+            "reason"   : decision.reason,
+        }).to_string());
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+//  COMPILE.rs
+//    by agent
+//
+//  Created:
+//    16 Oct 2026, 08:19:47
+//  Last edited:
+//    16 Oct 2026, 12:59:49
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements compiling BraneScript on behalf of clients, using the
+//!   package and data indices of the instance itself.
+//
+
+use std::str::FromStr as _;
+
+use log::debug;
+use tokio::task::spawn_blocking;
+
+use brane_ast::{compile_program, CompileResult, ParserOptions, TextRange};
+use brane_dsl::Language;
+use brane_tsk::api::{get_package_index, ApiClient};
+use brane_tsk::grpc;
+use specifications::data::DataIndex;
+use specifications::package::PackageIndex;
+
+pub use crate::errors::CompileError as Error;
+
+
+/***** HELPER FUNCTIONS *****/
+/// Converts a TextRange to its gRPC counterpart.
+///
+/// # Arguments
+/// - `range`: The TextRange to convert.
+///
+/// # Returns
+/// An equivalent SourceRange, or None if the TextRange represents 'no range'.
+#[inline]
+fn to_source_range(range: &TextRange) -> Option<grpc::SourceRange> {
+    if range.is_none() { return None; }
+    Some(grpc::SourceRange {
+        start_line : range.start.line as u64,
+        start_col  : range.start.col as u64,
+        end_line   : range.end.line as u64,
+        end_col    : range.end.col as u64,
+    })
+}
+
+/// Compiles the given source text with the given indices.
+///
+/// This is deliberately not `async`, as the compiler's AST may not be held across await points. Being CPU-bound, it should be run with `spawn_blocking()`.
+///
+/// # Arguments
+/// - `input`: The source text to compile.
+/// - `options`: The ParserOptions with which we parse it.
+/// - `pindex`: The PackageIndex that is used to resolve imports.
+/// - `dindex`: The DataIndex that is used to resolve `Data`-structs.
+///
+/// # Returns
+/// A CompileReply with the serialized workflow if it compiled, and any errors or warnings as diagnostics.
+///
+/// # Errors
+/// This function errors if we failed to serialize the compiled workflow.
+fn compile_source(input: &str, options: &ParserOptions, pindex: &PackageIndex, dindex: &DataIndex) -> Result<grpc::CompileReply, Error> {
+    match compile_program(input.as_bytes(), pindex, dindex, options) {
+        CompileResult::Workflow(workflow, warns) => {
+            let sworkflow: String = match serde_json::to_string(&workflow) {
+                Ok(sworkflow) => sworkflow,
+                Err(err)      => { return Err(Error::WorkflowSerializeError{ err }); },
+            };
+            Ok(grpc::CompileReply {
+                workflow    : Some(sworkflow),
+                diagnostics : warns.iter().map(|warn| grpc::Diagnostic {
+                    severity : "warning".into(),
+                    message  : warn.to_string(),
+                    range    : warn.range().and_then(to_source_range),
+                }).collect(),
+            })
+        },
+        CompileResult::Unresolved(_, _) => unreachable!(),
+        CompileResult::Program(_, _)    => unreachable!(),
+
+        CompileResult::Eof(err) => Ok(grpc::CompileReply {
+            workflow    : None,
+            diagnostics : vec![ grpc::Diagnostic{ severity: "error".into(), message: err.to_string(), range: None } ],
+        }),
+        CompileResult::Err(errs) => Ok(grpc::CompileReply {
+            workflow    : None,
+            diagnostics : errs.iter().map(|err| grpc::Diagnostic {
+                severity : "error".into(),
+                message  : err.to_string(),
+                range    : err.range().and_then(to_source_range),
+            }).collect(),
+        }),
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Compiles the given BraneScript (or Bakery) source text to a workflow.
+///
+/// The package and data indices are fetched from the instance's API service, so the result is exactly what the instance would accept.
+///
+/// # Arguments
+/// - `api`: The address of the `brane-api` service to fetch the indices from.
+/// - `input`: The source text to compile.
+/// - `language`: The language of the source text, if not BraneScript.
+///
+/// # Returns
+/// A CompileReply with the serialized workflow if it compiled, and any errors or warnings as diagnostics. Note that a program that fails to compile is _not_ an error of this function.
+///
+/// # Errors
+/// This function errors if the language is unknown, we failed to fetch the indices or failed to serialize the compiled workflow.
+pub async fn compile(api: &str, input: &str, language: Option<&str>) -> Result<grpc::CompileReply, Error> {
+    // Parse the language first
+    let options: ParserOptions = match language {
+        Some(raw) => match Language::from_str(raw) {
+            Ok(lang) => ParserOptions::new(lang),
+            Err(err) => { return Err(Error::IllegalLanguage{ raw: raw.into(), err }); },
+        },
+        None => ParserOptions::bscript(),
+    };
+
+    // Fetch the instance's indices
+    let package_index_addr: String = format!("{}/graphql", api);
+    debug!("Fetching package index from '{}'...", package_index_addr);
+    let pindex: PackageIndex = match get_package_index(&package_index_addr).await {
+        Ok(pindex) => pindex,
+        Err(err)   => { return Err(Error::PackageIndexError{ address: package_index_addr, err }); },
+    };
+    let data_index_addr: String = format!("{}/data/info", api);
+    debug!("Fetching data index from '{}'...", data_index_addr);
+    let dindex: DataIndex = match ApiClient::new(api).data_index().await {
+        Ok(dindex) => dindex,
+        Err(err)   => { return Err(Error::DataIndexError{ address: data_index_addr, err }); },
+    };
+
+    // Compile it on a thread of its own, so that large programs do not block other requests
+    debug!("Compiling {} characters of source...", input.len());
+    let input: String = input.into();
+    match spawn_blocking(move || compile_source(&input, &options, &pindex, &dindex)).await {
+        Ok(res)  => res,
+        Err(err) => Err(Error::CompileTaskError{ err }),
+    }
+}
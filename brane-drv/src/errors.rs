@@ -14,6 +14,7 @@
 
 use std::error::Error;
 use std::fmt::{Display, Formatter, Result as FResult};
+use std::path::PathBuf;
 
 
 /***** ERRORS *****/
@@ -24,16 +25,235 @@ pub enum RemoteVmError {
     PlanError{ err: brane_tsk::errors::PlanError },
     /// Failed to run a workflow.
     ExecError{ err: brane_exe::Error },
+    /// Failed to parse the workflow stored in a checkpoint.
+    CheckpointWorkflowParseError{ err: serde_json::Error },
+    /// Failed to find the revisions of the datasets that a workflow may commit to.
+    RevisionsError{ err: IndexError },
+}
+
+impl RemoteVmError {
+    /// Returns whether this error means that the workflow failed to commit a result because another workflow committed to the same dataset first.
+    /// 
+    /// # Returns
+    /// True if the workflow lost such a race (and re-running it may succeed), or false otherwise.
+    pub fn is_conflict(&self) -> bool {
+        match self {
+            Self::ExecError{ err: brane_exe::Error::Custom{ err, .. } } => err.downcast_ref::<brane_tsk::errors::CommitError>().map(|err| err.is_conflict()).unwrap_or(false),
+            _                                                           => false,
+        }
+    }
 }
 
 impl Display for RemoteVmError {
     fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
         use RemoteVmError::*;
         match self {
-            PlanError{ err } => write!(f, "Failed to plan workflow: {}", err),
-            ExecError{ err } => write!(f, "Failed to execute workflow: {}", err),
+            PlanError{ err }                    => write!(f, "Failed to plan workflow: {}", err),
+            ExecError{ err }                    => write!(f, "Failed to execute workflow: {}", err),
+            CheckpointWorkflowParseError{ err } => write!(f, "Failed to parse workflow of checkpoint: {}", err),
+            RevisionsError{ err }               => write!(f, "Failed to find the revisions of the datasets: {}", err),
         }
     }
 }
 
 impl Error for RemoteVmError {}
+
+
+
+/// Defines errors that relate to deciding on approvals.
+#[derive(Debug)]
+pub enum ApprovalError {
+    /// The given user may not decide on approvals.
+    UnauthorizedApprover{ user: String },
+    /// The given approval is not pending (anymore).
+    UnknownApproval{ id: String },
+    /// Failed to append a decision to the audit log.
+    AuditWriteError{ path: PathBuf, err: std::io::Error },
+}
+
+impl Display for ApprovalError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use ApprovalError::*;
+        match self {
+            UnauthorizedApprover{ user } => write!(f, "User '{}' is not allowed to decide on approvals", user),
+            UnknownApproval{ id }        => write!(f, "No pending approval with ID '{}' found", id),
+            AuditWriteError{ path, err } => write!(f, "Failed to write to audit log '{}': {}", path.display(), err),
+        }
+    }
+}
+
+impl Error for ApprovalError {}
+
+
+
+/// Defines errors that relate to sending notifications.
+#[derive(Debug)]
+pub enum NotifyError {
+    /// Failed to load the node config file.
+    NodeConfigReadError{ path: PathBuf, err: brane_cfg::node::Error },
+    /// The given channel is not configured.
+    UnknownChannel{ name: String },
+    /// Failed to create the client with which we post to webhooks.
+    ClientCreateError{ err: reqwest::Error },
+
+    /// Failed to send the request to a webhook.
+    WebhookRequestError{ name: String, url: String, err: reqwest::Error },
+    /// The webhook did not accept the message.
+    WebhookFailure{ name: String, url: String, status: reqwest::StatusCode },
+
+    /// Failed to parse an e-mail address.
+    IllegalAddress{ name: String, raw: String, err: lettre::address::AddressError },
+    /// Failed to build the e-mail.
+    EmailCreateError{ name: String, err: lettre::error::Error },
+    /// Failed to read the password for the SMTP relay.
+    PasswordError{ name: String, err: brane_cfg::errors::SecretReferenceError },
+    /// Failed to send the e-mail through the SMTP relay.
+    EmailSendError{ name: String, host: String, err: lettre::transport::smtp::Error },
+}
+
+impl Display for NotifyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use NotifyError::*;
+        match self {
+            NodeConfigReadError{ path, err } => write!(f, "Failed to load node config file '{}': {}", path.display(), err),
+            UnknownChannel{ name }           => write!(f, "Unknown notification channel '{}'", name),
+            ClientCreateError{ err }         => write!(f, "Failed to create HTTP client for notifications: {}", err),
+
+            WebhookRequestError{ name, url, err } => write!(f, "Failed to send notification on channel '{}' to webhook '{}': {}", name, url, err),
+            WebhookFailure{ name, url, status }   => write!(f, "Webhook '{}' of notification channel '{}' returned {} ({})", url, name, status.as_u16(), status.canonical_reason().unwrap_or("???")),
+
+            IllegalAddress{ name, raw, err }     => write!(f, "Illegal e-mail address '{}' in notification channel '{}': {}", raw, name, err),
+            EmailCreateError{ name, err }        => write!(f, "Failed to create e-mail for notification channel '{}': {}", name, err),
+            PasswordError{ name, err }           => write!(f, "Failed to read SMTP password of notification channel '{}': {}", name, err),
+            EmailSendError{ name, host, err }    => write!(f, "Failed to send notification on channel '{}' through SMTP relay '{}': {}", name, host, err),
+        }
+    }
+}
+
+impl Error for NotifyError {}
+
+
+
+/// Defines errors that relate to querying the instance's indices on behalf of a workflow.
+#[derive(Debug)]
+pub enum IndexError {
+    /// Failed to load the node config file.
+    NodeConfigReadError{ path: PathBuf, err: brane_cfg::node::Error },
+    /// Failed to fetch the package index from the API service.
+    PackageIndexError{ address: String, err: brane_tsk::api::Error },
+    /// Failed to fetch the data index from the API service.
+    DataIndexError{ address: String, err: brane_tsk::api::Error },
+}
+
+impl Display for IndexError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use IndexError::*;
+        match self {
+            NodeConfigReadError{ path, err }  => write!(f, "Failed to load node config file '{}': {}", path.display(), err),
+            PackageIndexError{ address, err } => write!(f, "Failed to fetch package index from '{}': {}", address, err),
+            DataIndexError{ address, err }    => write!(f, "Failed to fetch data index from '{}': {}", address, err),
+        }
+    }
+}
+
+impl Error for IndexError {}
+
+
+
+/// Defines errors that relate to checkpointing workflows and resuming them.
+#[derive(Debug)]
+pub enum CheckpointError {
+    /// Failed to serialize a checkpoint.
+    SerializeError{ err: serde_json::Error },
+    /// Failed to write a checkpoint to a (temporary) file.
+    FileWriteError{ path: PathBuf, err: std::io::Error },
+    /// Failed to move a written checkpoint in place of the previous one.
+    FileRenameError{ from: PathBuf, to: PathBuf, err: std::io::Error },
+
+    /// There is no checkpoint for the given session.
+    NotFound{ path: PathBuf },
+    /// Failed to read a checkpoint file.
+    FileReadError{ path: PathBuf, err: std::io::Error },
+    /// Failed to parse a checkpoint file.
+    FileParseError{ path: PathBuf, err: serde_json::Error },
+}
+
+impl Display for CheckpointError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use CheckpointError::*;
+        match self {
+            SerializeError{ err }            => write!(f, "Failed to serialize checkpoint: {}", err),
+            FileWriteError{ path, err }      => write!(f, "Failed to write checkpoint file '{}': {}", path.display(), err),
+            FileRenameError{ from, to, err } => write!(f, "Failed to move checkpoint file '{}' to '{}': {}", from.display(), to.display(), err),
+
+            NotFound{ path }            => write!(f, "Checkpoint file '{}' not found", path.display()),
+            FileReadError{ path, err }  => write!(f, "Failed to read checkpoint file '{}': {}", path.display(), err),
+            FileParseError{ path, err } => write!(f, "Failed to parse checkpoint file '{}': {}", path.display(), err),
+        }
+    }
+}
+
+impl Error for CheckpointError {}
+
+
+
+/// Defines errors that relate to compiling BraneScript on behalf of clients.
+#[derive(Debug)]
+pub enum CompileError {
+    /// The client asked for a language we do not know.
+    IllegalLanguage{ raw: String, err: brane_dsl::errors::LanguageParseError },
+    /// Failed to fetch the package index from the API service.
+    PackageIndexError{ address: String, err: brane_tsk::api::Error },
+    /// Failed to fetch the data index from the API service.
+    DataIndexError{ address: String, err: brane_tsk::api::Error },
+    /// Failed to serialize the compiled workflow.
+    WorkflowSerializeError{ err: serde_json::Error },
+    /// The compiler panicked (or was cancelled) while compiling.
+    CompileTaskError{ err: tokio::task::JoinError },
+}
+
+impl Display for CompileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use CompileError::*;
+        match self {
+            IllegalLanguage{ raw, err }       => write!(f, "Illegal language '{}': {}", raw, err),
+            PackageIndexError{ address, err } => write!(f, "Failed to fetch package index from '{}': {}", address, err),
+            DataIndexError{ address, err }    => write!(f, "Failed to fetch data index from '{}': {}", address, err),
+            WorkflowSerializeError{ err }     => write!(f, "Failed to serialize the compiled workflow: {}", err),
+            CompileTaskError{ err }           => write!(f, "Failed to run the compiler to completion: {}", err),
+        }
+    }
+}
+
+impl Error for CompileError {}
+
+
+
+/// Defines errors that relate to persisting the replies of detached workflows.
+#[derive(Debug)]
+pub enum ReplayError {
+    /// Failed to write (part of) a reply log.
+    FileWriteError{ path: PathBuf, err: std::io::Error },
+    /// Failed to move a written reply log in place of the previous one.
+    FileRenameError{ from: PathBuf, to: PathBuf, err: std::io::Error },
+
+    /// There is no reply log for the given session.
+    NotFound{ path: PathBuf },
+    /// Failed to read a reply log.
+    FileReadError{ path: PathBuf, err: std::io::Error },
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use ReplayError::*;
+        match self {
+            FileWriteError{ path, err }      => write!(f, "Failed to write reply log '{}': {}", path.display(), err),
+            FileRenameError{ from, to, err } => write!(f, "Failed to move reply log '{}' to '{}': {}", from.display(), to.display(), err),
+
+            NotFound{ path }           => write!(f, "Reply log '{}' not found", path.display()),
+            FileReadError{ path, err } => write!(f, "Failed to read reply log '{}': {}", path.display(), err),
+        }
+    }
+}
+
+impl Error for ReplayError {}
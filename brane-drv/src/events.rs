@@ -0,0 +1,186 @@
+//  EVENTS.rs
+//    by agent
+//
+//  Created:
+//    16 Oct 2026, 07:58:16
+//  Last edited:
+//    16 Oct 2026, 13:20:15
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements the registry that routes external events to the
+//!   workflows waiting for them.
+//
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use dashmap::DashMap;
+use tokio::sync::Mutex as TokioMutex;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+use brane_tsk::spec::AppId;
+
+
+/***** UNIT TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_buffered() {
+        let registry: EventRegistry = EventRegistry::new();
+        let app_id: AppId = AppId::generate();
+
+        // Events sent before anyone waits are not lost, and are received in order
+        registry.send(&app_id, "hook", "first".into());
+        registry.send(&app_id, "hook", "second".into());
+        assert_eq!(registry.wait(&app_id, "hook").await, Some("first".into()));
+        assert_eq!(registry.channels.len(), 1);
+        assert_eq!(registry.wait(&app_id, "hook").await, Some("second".into()));
+    }
+
+    #[tokio::test]
+    async fn test_removed_on_consume() {
+        let registry: Arc<EventRegistry> = Arc::new(EventRegistry::new());
+        let app_id: AppId = AppId::generate();
+
+        // Wait for an event that is only sent later
+        let waiter = {
+            let registry: Arc<EventRegistry> = registry.clone();
+            let app_id: AppId = app_id.clone();
+            tokio::spawn(async move { registry.wait(&app_id, "hook").await })
+        };
+        while registry.channels.is_empty() { tokio::task::yield_now().await; }
+        registry.send(&app_id, "hook", "payload".into());
+        assert_eq!(waiter.await.unwrap(), Some("payload".into()));
+
+        // Nothing is left behind once it has been consumed
+        assert!(registry.channels.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_forget() {
+        let registry: Arc<EventRegistry> = Arc::new(EventRegistry::new());
+        let app_id: AppId = AppId::generate();
+        let other: AppId = AppId::generate();
+        registry.send(&other, "hook", "payload".into());
+
+        // Forgetting a session wakes up its waiters, but leaves other sessions alone
+        let waiter = {
+            let registry: Arc<EventRegistry> = registry.clone();
+            let app_id: AppId = app_id.clone();
+            tokio::spawn(async move { registry.wait(&app_id, "hook").await })
+        };
+        while registry.channels.len() < 2 { tokio::task::yield_now().await; }
+        registry.forget(&app_id);
+        assert_eq!(waiter.await.unwrap(), None);
+        assert_eq!(registry.channels.len(), 1);
+        assert_eq!(registry.wait(&other, "hook").await, Some("payload".into()));
+    }
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Defines the channel on which the events for a single (session, name) pair are sent.
+#[derive(Clone, Debug)]
+struct EventChannel {
+    /// The end on which new events are pushed.
+    tx : UnboundedSender<String>,
+    /// The end on which waiting threads receive events. Wrapped in a mutex, since multiple threads may wait for the same event; each event will wake only one of them.
+    rx      : Arc<TokioMutex<UnboundedReceiver<String>>>,
+    /// The number of events that were sent but not yet received.
+    pending : Arc<AtomicUsize>,
+}
+
+impl EventChannel {
+    /// Constructor for the EventChannel.
+    ///
+    /// # Returns
+    /// A new EventChannel that has not seen any events yet.
+    #[inline]
+    fn new() -> Self {
+        let (tx, rx): (UnboundedSender<String>, UnboundedReceiver<String>) = mpsc::unbounded_channel();
+        Self { tx, rx: Arc::new(TokioMutex::new(rx)), pending: Arc::new(AtomicUsize::new(0)) }
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Routes external events (e.g., webhooks or human approvals) to the workflows waiting for them.
+///
+/// Events are buffered, so an event that arrives before a workflow waits for it is not lost.
+#[derive(Debug, Default)]
+pub struct EventRegistry {
+    /// The channels per session and event name.
+    channels : DashMap<(AppId, String), EventChannel>,
+}
+
+impl EventRegistry {
+    /// Constructor for the EventRegistry.
+    ///
+    /// # Returns
+    /// A new EventRegistry without any events in it.
+    #[inline]
+    pub fn new() -> Self { Self::default() }
+
+
+
+    /// Sends a new event to the given session, waking up one thread that waits for it (or buffering it until one does).
+    ///
+    /// # Arguments
+    /// - `app_id`: The session to which the event belongs.
+    /// - `name`: The name of the event.
+    /// - `payload`: The payload that the waiting thread will receive.
+    pub fn send(&self, app_id: &AppId, name: &str, payload: String) {
+        // Send while holding the entry, so that `wait()` cannot remove the channel in between
+        let channel = self.channels.entry((app_id.clone(), name.into())).or_insert_with(EventChannel::new);
+        channel.pending.fetch_add(1, Ordering::SeqCst);
+        // Since we keep the receiving end ourselves, this can never fail
+        channel.tx.send(payload).expect("EventChannel receiver was dropped while still in the registry");
+    }
+
+    /// Waits until the given event is sent to the given session.
+    ///
+    /// Once the event is received, the channel is removed from the registry if no other events are buffered in it and no other thread is waiting on it.
+    ///
+    /// # Arguments
+    /// - `app_id`: The session to which the event belongs.
+    /// - `name`: The name of the event.
+    ///
+    /// # Returns
+    /// The payload of the event, or `None` if the channel was closed in the meantime.
+    pub async fn wait(&self, app_id: &AppId, name: &str) -> Option<String> {
+        let key: (AppId, String) = (app_id.clone(), name.into());
+        // Only keep the receiving end, so that `forget()` closes the channel by dropping the last sender
+        let (rx, pending): (Arc<TokioMutex<UnboundedReceiver<String>>>, Arc<AtomicUsize>) = {
+            let channel = self.channels.entry(key.clone()).or_insert_with(EventChannel::new);
+            (channel.rx.clone(), channel.pending.clone())
+        };
+        let payload: Option<String> = rx.lock().await.recv().await;
+        if payload.is_none() { return None; }
+        pending.fetch_sub(1, Ordering::SeqCst);
+        drop(rx);
+
+        // Clean up after ourselves if nobody needs the channel anymore (the registry holds the only other reference to it)
+        self.channels.remove_if(&key, |_, channel| Arc::strong_count(&channel.rx) == 1 && channel.pending.load(Ordering::SeqCst) == 0);
+        payload
+    }
+
+    /// Drops all (buffered) events of the given session, e.g., because it was killed.
+    ///
+    /// Threads still waiting for any of them receive `None`.
+    ///
+    /// # Arguments
+    /// - `app_id`: The session of which to drop the events.
+    pub fn forget(&self, app_id: &AppId) {
+        self.channels.retain(|(id, _), _| id != app_id);
+    }
+}
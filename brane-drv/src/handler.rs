@@ -12,28 +12,78 @@
 //!   Implements the command handler from the client.
 // 
 
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
+use chrono::{DateTime, Utc};
 use dashmap::DashMap;
-use log::{debug, error};
+use dashmap::mapref::entry::Entry;
+use log::{debug, error, warn};
+use tokio::fs as tfs;
+use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
 use tokio_stream::wrappers::ReceiverStream;
 use tonic::{Request, Response, Status};
+use tracing::{info_span, Instrument as _, Span};
 
 use brane_ast::Workflow;
+use brane_ast::capabilities::EngineRequirements;
+use brane_ast::schedule::TaskTiming;
+use brane_cfg::certs::extract_client_name;
 use brane_cfg::node::NodeConfig;
+use brane_exe::cancel::CancelToken;
 use brane_exe::FullValue;
+use brane_exe::plugins::PluginEntry;
+use brane_exe::spec::{ApprovalDecision, FrameSummary};
 use brane_prx::client::ProxyClient;
-use brane_tsk::spec::{AppId, Planner};
+use brane_shr::telemetry;
+use brane_tsk::spec::AppId;
 use brane_tsk::grpc;
+use brane_tsk::tools::{conflict_status, extract_client};
+use specifications::profiling::TransferTiming;
 
-use crate::errors::RemoteVmError;
+use crate::approvals::{is_reserved_event, ApprovalRegistry, Error as ApprovalError};
+use crate::compile::{compile, Error as CompileError};
+use crate::errors::{CheckpointError, RemoteVmError, ReplayError};
+use crate::events::EventRegistry;
+use crate::notify::Notifier;
 use crate::planner::InstancePlanner;
+use crate::progress::ProgressHub;
+use crate::replay::ReplayBuffer;
+use crate::spec::SessionCheckpoint;
 use crate::vm::InstanceVm;
 
 
+/***** UNIT TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_may_access() {
+        let admins: HashSet<String> = HashSet::from([ "admin".into() ]);
+
+        // Sessions without an owner belong to everybody
+        assert!(may_access(&admins, &Caller::Anonymous, None));
+        assert!(may_access(&admins, &Caller::Client("alice".into()), None));
+
+        // Sessions with one only to their owner, the administrators and the node itself
+        assert!(may_access(&admins, &Caller::Client("alice".into()), Some("alice")));
+        assert!(may_access(&admins, &Caller::Client("admin".into()), Some("alice")));
+        assert!(may_access(&admins, &Caller::Node, Some("alice")));
+        assert!(!may_access(&admins, &Caller::Client("bob".into()), Some("alice")));
+        assert!(!may_access(&admins, &Caller::Anonymous, Some("alice")));
+    }
+}
+
+
+
+
+
 /***** HELPER MACROS *****/
 /// Sends an error back to the client, also logging it here. Is like `err!` but returning the stream.
 macro_rules! fatal_err {
@@ -85,6 +135,163 @@ macro_rules! fatal_err {
 
 
 
+/***** HELPER ENUMS *****/
+/// Identifies who sent a request to the driver.
+#[derive(Clone, Debug)]
+enum Caller {
+    /// One of the other services on this node, which reached us on the internal address. It may use every session.
+    Node,
+    /// A client that authenticated itself with a certificate, with the given name.
+    Client(String),
+    /// A client that did not authenticate itself.
+    Anonymous,
+}
+impl Caller {
+    /// Returns the name of the client, if it authenticated itself.
+    #[inline]
+    fn name(&self) -> Option<&str> {
+        match self {
+            Self::Client(name) => Some(name),
+            _                  => None,
+        }
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Returns the name of the client that sent the given request, as given in the certificate it authenticated itself with.
+/// 
+/// # Arguments
+/// - `request`: The incoming request.
+/// 
+/// # Returns
+/// The `CN` of the client certificate, or `None` if the client did not connect over TLS with a (valid) certificate.
+fn client_name<T>(request: &Request<T>) -> Option<String> {
+    request.peer_certs().and_then(|certs| certs.first().and_then(|cert| extract_client_name(cert.get_ref())))
+}
+
+/// Removes the persisted replies of a detached workflow, if there are any.
+/// 
+/// # Arguments
+/// - `path`: The path of the reply log to remove.
+async fn remove_replay_log(path: &Path) {
+    if let Err(err) = tfs::remove_file(path).await {
+        if err.kind() != std::io::ErrorKind::NotFound { warn!("Failed to remove reply log '{}': {}", path.display(), err); }
+    }
+}
+
+/// Decides whether a client may see and act on a session, i.e., whether it created the session or is an administrator.
+/// 
+/// Sessions created by clients that did not authenticate themselves belong to nobody, and may thus be used by anyone.
+/// 
+/// # Arguments
+/// - `admins`: The names of the clients (as given in their certificates) that may use the sessions of every client.
+/// - `caller`: The client that wants to use the session.
+/// - `owner`: The name of the client that created the session, if it authenticated itself.
+/// 
+/// # Returns
+/// Whether the client may use the session.
+fn may_access(admins: &HashSet<String>, caller: &Caller, owner: Option<&str>) -> bool {
+    match (caller, owner) {
+        (_, None)                           => true,
+        (Caller::Node, _)                   => true,
+        (Caller::Client(name), Some(owner)) => name == owner || admins.contains(name),
+        (Caller::Anonymous, Some(_))        => false,
+    }
+}
+
+/// Sends the result of a workflow back to the client.
+/// 
+/// # Arguments
+/// - `tx`: The channel to the client.
+/// - `res`: The result of the workflow, which is sent as an error if it failed.
+/// 
+/// # Returns
+/// Nothing, but does send the result on `tx`. Any failure to do so is logged.
+async fn send_result(tx: mpsc::Sender<Result<grpc::ExecuteReply, Status>>, res: Result<FullValue, RemoteVmError>) {
+    // Switch on the actual result and send that back to the user
+    match res {
+        Ok(res)  => {
+            debug!("Completed execution.");
+
+            // Serialize the value
+            let sres: String = match serde_json::to_string(&res) {
+                Ok(sres) => sres,
+                Err(err) => { fatal_err!(tx, Status::internal, err); }  
+            };
+
+            // Create the reply text
+            let msg = String::from("Driver completed execution.");
+            let reply = grpc::ExecuteReply {
+                close  : true,
+                debug  : Some(msg.clone()),
+                stderr : None,
+                stdout : None,
+                branch : vec![],
+                value  : Some(sres),
+
+                progress : None,
+                usage    : None,
+                output   : None,
+
+                seq       : 0,
+                heartbeat : false,
+            };
+
+            // Send it
+            if let Err(err) = tx.send(Ok(reply)).await {
+                error!("Failed to send workflow result back to client: {}", err);
+            }
+        },
+        // Lost races on committing results are reported as such, so the client knows it can retry
+        Err(err) if err.is_conflict() => {
+            fatal_err!(tx, conflict_status(err.to_string()));
+        },
+        Err(err) => {
+            fatal_err!(tx, Status::internal, err);
+        },
+    };
+}
+
+/// Tells the client why the planner planned every task where it did.
+/// 
+/// # Arguments
+/// - `tx`: The channel to the client.
+/// - `reasoning`: The planner's explanation, one line per task.
+/// 
+/// # Returns
+/// Nothing, but does send every line as a debug message on `tx`. Any failure to do so is logged.
+async fn send_reasoning(tx: &mpsc::Sender<Result<grpc::ExecuteReply, Status>>, reasoning: Vec<String>) {
+    for reason in reasoning {
+        debug!("Planner: {}", reason);
+        let reply = grpc::ExecuteReply {
+            close  : false,
+            debug  : Some(format!("Planner: {}", reason)),
+            stderr : None,
+            stdout : None,
+            branch : vec![],
+            value  : None,
+
+            progress : None,
+            usage    : None,
+            output   : None,
+
+            seq       : 0,
+            heartbeat : false,
+        };
+        if let Err(err) = tx.send(Ok(reply)).await {
+            error!("Failed to send planner reasoning to client: {}", err);
+        }
+    }
+}
+
+
+
+
+
 /***** LIBRARY *****/
 /// The DriverHandler handles incoming gRPC requests. This is effectively what 'drives' the driver.
 #[derive(Clone)]
@@ -93,11 +300,52 @@ pub struct DriverHandler {
     node_config_path : PathBuf,
     /// The ProxyClient that we use to connect to/through `brane-prx`.
     proxy            : Arc<ProxyClient>,
+    /// The Notifier that the VMs use to send notifications to users.
+    notifier         : Arc<Notifier>,
     /// The planner we use to plan stuff.
     planner          : Arc<InstancePlanner>,
+    /// The plugin with which the VMs run workflows.
+    plugin           : PluginEntry<InstanceVm>,
 
     /// Current sessions and active VMs. Note that this only concerns states if connected via a REPL-session; any in-statement state (i.e., calling nodes) is handled by virtue of the VM being implemented as `async`.
-    sessions : Arc<DashMap<AppId, InstanceVm>>,
+    sessions  : Arc<DashMap<AppId, InstanceVm>>,
+    /// When each of the sessions was created (or resumed).
+    created   : Arc<DashMap<AppId, DateTime<Utc>>>,
+    /// The client that created each of the sessions (i.e., the `CN` in its certificate), if it authenticated itself.
+    owners    : Arc<DashMap<AppId, String>>,
+    /// The task running the last workflow of each session, so that it may be killed.
+    workflows : Arc<DashMap<AppId, JoinHandle<()>>>,
+    /// The token with which the last workflow of each session may be cancelled.
+    cancels   : Arc<DashMap<AppId, CancelToken>>,
+    /// The external events sent to any of the sessions.
+    events    : Arc<EventRegistry>,
+    /// The approvals that any of the sessions are waiting for.
+    approvals : Arc<ApprovalRegistry>,
+    /// The structured events of every session, for UIs that subscribe to them.
+    progress  : Arc<ProgressHub>,
+
+    /// The names of the clients (i.e., the `CN` in their certificate) that may see and act on the sessions of every client.
+    admins      : Arc<HashSet<String>>,
+    /// Whether this handler serves the internal address, which only the other services on the node can reach.
+    internal    : bool,
+    /// The directory to which running workflows are checkpointed, if any.
+    checkpoints : Option<PathBuf>,
+
+    /// The replies of the last workflow of every session, for clients that reconnect.
+    replays   : Arc<DashMap<AppId, Arc<ReplayBuffer>>>,
+    /// The number of replies per session that we keep for replaying.
+    window    : usize,
+    /// The number of replies of detached workflows that we keep for replaying.
+    detached  : usize,
+    /// How long we keep the replies of detached workflows after they are done.
+    ttl       : Duration,
+    /// How long a reply stream may be quiet before we send a heartbeat.
+    heartbeat : Duration,
+
+    /// The maximum number of branches of a single parallel statement that a workflow runs at the same time, if any.
+    max_parallel : Option<usize>,
+    /// The maximum number of steps that a single workflow run may take, if any.
+    max_steps    : Option<u64>,
 }
 
 impl DriverHandler {
@@ -106,40 +354,257 @@ impl DriverHandler {
     /// # Arguments
     /// - `node_config_path`: The path to the `node.yml` file that describes this node's environment. For the handler, this is the path to the `infra.yml` file (and an optional `secrets.yml`) and the topic to send commands to the planner on.
     /// - `proxy`: The (shared) ProxyClient that we use to connect to/through `brane-prx`.
+    /// - `notifier`: The (shared) Notifier that the VMs use to send notifications to users.
     /// - `planner`: The InstancePlanner that handles our side of planning.
+    /// - `plugin`: The plugin with which the VMs run workflows (see `crate::vm::plugins()`).
+    /// - `audit`: The path of the audit log to which decisions on approvals are appended, if any.
+    /// - `approvers`: The names of the clients (as given in their certificates) that may decide on approvals.
+    /// - `admins`: The names of the clients (as given in their certificates) that may see and act on the sessions of every client.
+    /// - `checkpoints`: The directory to which running workflows are checkpointed, if any. The replies of detached workflows are persisted there as well.
+    /// - `window`: The number of replies per session that we keep for clients that reconnect. Also used as the number of events kept for subscribers that lag behind.
+    /// - `detached`: The number of replies of detached workflows that we keep until a client attaches to them.
+    /// - `ttl`: How long we keep the replies of detached workflows after they are done.
+    /// - `heartbeat`: How long a reply stream may be quiet before we send a heartbeat.
+    /// - `max_parallel`: The maximum number of branches of a single parallel statement that a workflow runs at the same time, if any.
+    /// - `max_steps`: The maximum number of steps that a single workflow run may take, if any. Clients may only lower it per run.
     /// 
     /// # Returns
     /// A new DriverHandler instance.
     #[inline]
-    pub fn new(node_config_path: impl Into<PathBuf>, proxy: Arc<ProxyClient>, planner: Arc<InstancePlanner>) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(node_config_path: impl Into<PathBuf>, proxy: Arc<ProxyClient>, notifier: Arc<Notifier>, planner: Arc<InstancePlanner>, plugin: PluginEntry<InstanceVm>, audit: Option<PathBuf>, approvers: HashSet<String>, admins: HashSet<String>, checkpoints: Option<PathBuf>, window: usize, detached: usize, ttl: Duration, heartbeat: Duration, max_parallel: Option<usize>, max_steps: Option<u64>) -> Self {
+        let events: Arc<EventRegistry> = Arc::new(EventRegistry::new());
         Self {
             node_config_path : node_config_path.into(),
             proxy,
+            notifier,
             planner,
+            plugin,
+
+            sessions  : Arc::new(DashMap::new()),
+            created   : Arc::new(DashMap::new()),
+            owners    : Arc::new(DashMap::new()),
+            workflows : Arc::new(DashMap::new()),
+            cancels   : Arc::new(DashMap::new()),
+            approvals : Arc::new(ApprovalRegistry::new(events.clone(), audit, approvers)),
+            events,
+            progress  : Arc::new(ProgressHub::new(window)),
+
+            admins   : Arc::new(admins),
+            internal : false,
+            checkpoints,
+
+            replays : Arc::new(DashMap::new()),
+            window,
+            detached,
+            ttl,
+            heartbeat,
+
+            max_parallel,
+            max_steps,
+        }
+    }
+
+
+
+    /// Returns a copy of this handler for the internal address, on which clients without a certificate are taken to be the other services on the node (or the clients they say to call on behalf of).
+    /// 
+    /// # Returns
+    /// A new DriverHandler that shares its sessions with this one.
+    #[inline]
+    pub fn internal(&self) -> Self { Self { internal: true, ..self.clone() } }
+
+
 
-            sessions : Arc::new(DashMap::new()),
+    /// Relays the replies of a new workflow in the given session through a ReplayBuffer, so that its client may reconnect to them.
+    /// 
+    /// # Arguments
+    /// - `app_id`: The session in which the workflow runs.
+    /// - `tx`: The channel to the client that started the workflow.
+    /// - `detach`: Whether the client will not follow the workflow, in which case we keep (and persist) a larger window of its replies until they expire.
+    /// 
+    /// # Returns
+    /// The channel on which the workflow should send its replies instead.
+    async fn replayed(&self, app_id: &AppId, tx: mpsc::Sender<Result<grpc::ExecuteReply, Status>>, detach: bool) -> mpsc::Sender<Result<grpc::ExecuteReply, Status>> {
+        let log: Option<PathBuf> = self.replay_log(app_id);
+        if !detach {
+            let replay: Arc<ReplayBuffer> = Arc::new(ReplayBuffer::new(self.window, self.heartbeat, tx));
+            self.replays.insert(app_id.clone(), replay.clone());
+            // The output of any previous detached workflow is superseded by this one
+            if let Some(log) = log { remove_replay_log(&log).await; }
+            return replay.relay();
+        }
+
+        // Detached workflows get a larger window that outlives the driver, but only for so long
+        let mut replay: ReplayBuffer = ReplayBuffer::new(self.detached, self.heartbeat, tx);
+        if let Some(log) = log {
+            let owner: Option<String> = self.owners.get(app_id).map(|owner| owner.value().clone());
+            replay = replay.with_log(log, owner);
         }
+        let replay: Arc<ReplayBuffer> = Arc::new(replay);
+        self.replays.insert(app_id.clone(), replay.clone());
+        let tx: mpsc::Sender<Result<grpc::ExecuteReply, Status>> = replay.relay();
+        self.expire(app_id, replay);
+        tx
+    }
+
+    /// Returns the path of the log to which the replies of detached workflows in the given session are persisted.
+    /// 
+    /// # Arguments
+    /// - `app_id`: The session of the workflows.
+    /// 
+    /// # Returns
+    /// The path of the log, or `None` if checkpointing (and thus persisting) is not enabled on this instance.
+    #[inline]
+    fn replay_log(&self, app_id: &AppId) -> Option<PathBuf> { self.checkpoints.as_ref().map(|dir| dir.join(format!("{}.replay", app_id))) }
+
+    /// Forgets the replies of a detached workflow (and removes their log) once they have been kept for long enough after the workflow is done.
+    /// 
+    /// # Arguments
+    /// - `app_id`: The session in which the workflow runs.
+    /// - `replay`: The ReplayBuffer with the workflow's replies.
+    fn expire(&self, app_id: &AppId, replay: Arc<ReplayBuffer>) {
+        let replays: Arc<DashMap<AppId, Arc<ReplayBuffer>>> = self.replays.clone();
+        let log: Option<PathBuf> = self.replay_log(app_id);
+        let ttl: Duration = self.ttl;
+        let app_id: AppId = app_id.clone();
+        tokio::spawn(async move {
+            replay.finished().await;
+            tokio::time::sleep(ttl).await;
+
+            // Only forget them if they have not been replaced by those of a newer workflow (or the session was killed) in the meantime
+            if replays.remove_if(&app_id, |_, current| Arc::ptr_eq(current, &replay)).is_none() { return; }
+            debug!("Replies of detached workflow in session '{}' expired", app_id);
+            if let Some(log) = log { remove_replay_log(&log).await; }
+        });
+    }
+
+    /// Restores the replies of a detached workflow from its log, e.g., because the driver was restarted since it ran.
+    /// 
+    /// # Arguments
+    /// - `caller`: The client that wants to attach to the replies.
+    /// - `app_id`: The session in which the workflow ran.
+    /// 
+    /// # Returns
+    /// The restored ReplayBuffer, which expires as usual.
+    /// 
+    /// # Errors
+    /// This function errors with a `NOT_FOUND` status if there is no log for the session, or with `PERMISSION_DENIED` if the session belonged to another client.
+    async fn restore_replay(&self, caller: &Caller, app_id: &AppId) -> Result<Arc<ReplayBuffer>, Status> {
+        let not_found = || Status::not_found(format!("No workflow to reconnect to in session '{}'", app_id));
+        let log: PathBuf = match self.replay_log(app_id) {
+            Some(log) => log,
+            None      => { return Err(not_found()); },
+        };
+        let (replay, owner): (ReplayBuffer, Option<String>) = match ReplayBuffer::restore(&log, self.detached, self.heartbeat).await {
+            Ok(res)                          => res,
+            Err(ReplayError::NotFound{ .. }) => { return Err(not_found()); },
+            Err(err)                         => {
+                error!("Failed to restore replies of session '{}': {}", app_id, err);
+                return Err(Status::internal("An internal error has occurred."));
+            },
+        };
+        if !may_access(&self.admins, caller, owner.as_deref()) { return Err(Status::permission_denied(format!("Session '{}' belongs to another client", app_id))); }
+        debug!("Restored replies of detached workflow in session '{}' from '{}'", app_id, log.display());
+
+        // Keep it around, unless a concurrent request beat us to it
+        let replay: Arc<ReplayBuffer> = Arc::new(replay);
+        let inserted: bool = match self.replays.entry(app_id.clone()) {
+            Entry::Occupied(_)   => false,
+            Entry::Vacant(entry) => { entry.insert(replay.clone()); true },
+        };
+        if !inserted { return self.replays.get(app_id).map(|replay| replay.clone()).ok_or_else(not_found); }
+        self.expire(app_id, replay.clone());
+        Ok(replay)
+    }
+
+    /// Removes the persisted replies of detached workflows that expired while the driver was not running.
+    pub async fn sweep_replay_logs(&self) {
+        let dir: &Path = match &self.checkpoints {
+            Some(dir) => dir,
+            None      => { return; },
+        };
+        let mut entries: tfs::ReadDir = match tfs::read_dir(dir).await {
+            Ok(entries) => entries,
+            Err(err)    => { warn!("Failed to read checkpoint directory '{}': {} (not removing expired reply logs)", dir.display(), err); return; },
+        };
+        loop {
+            let entry: tfs::DirEntry = match entries.next_entry().await {
+                Ok(Some(entry)) => entry,
+                Ok(None)        => { break; },
+                Err(err)        => { warn!("Failed to read checkpoint directory '{}': {} (not removing expired reply logs)", dir.display(), err); break; },
+            };
+            let path: PathBuf = entry.path();
+            if path.extension().map(|ext| ext != "replay").unwrap_or(true) { continue; }
+
+            // Logs are last written when their workflow is done
+            let expired: bool = match entry.metadata().await.and_then(|meta| meta.modified()) {
+                Ok(modified) => modified.elapsed().map(|age| age > self.ttl).unwrap_or(false),
+                Err(err)     => { warn!("Failed to get modification time of reply log '{}': {}", path.display(), err); continue; },
+            };
+            if expired {
+                debug!("Removing expired reply log '{}'...", path.display());
+                remove_replay_log(&path).await;
+            }
+        }
+    }
+
+    /// Determines who sent the given request.
+    /// 
+    /// # Arguments
+    /// - `request`: The incoming request.
+    /// 
+    /// # Returns
+    /// The client named in the certificate of the request if it has one. Otherwise, if we serve the internal address, the client on whose behalf another service on the node calls us or else the node itself. Anyone else is an anonymous client.
+    fn caller<T>(&self, request: &Request<T>) -> Caller {
+        if let Some(name) = client_name(request) { return Caller::Client(name); }
+        if !self.internal { return Caller::Anonymous; }
+        match extract_client(request) {
+            Some(name) => Caller::Client(name),
+            None       => Caller::Node,
+        }
+    }
+
+    /// Checks whether a client may see and act on the given session (see `may_access()`).
+    /// 
+    /// # Arguments
+    /// - `caller`: The client that wants to use the session.
+    /// - `app_id`: The session that the client wants to use.
+    /// 
+    /// # Errors
+    /// This function errors with a `PERMISSION_DENIED` status if the session belongs to another client.
+    fn authorize(&self, caller: &Caller, app_id: &AppId) -> Result<(), Status> {
+        let owner: Option<String> = self.owners.get(app_id).map(|owner| owner.value().clone());
+        if may_access(&self.admins, caller, owner.as_deref()) { return Ok(()); }
+        Err(Status::permission_denied(format!("Session '{}' belongs to another client", app_id)))
     }
 }
 
 #[tonic::async_trait]
 impl grpc::DriverService for DriverHandler {
-    type ExecuteStream = ReceiverStream<Result<grpc::ExecuteReply, Status>>;
+    type ExecuteStream   = ReceiverStream<Result<grpc::ExecuteReply, Status>>;
+    type ResumeStream    = ReceiverStream<Result<grpc::ExecuteReply, Status>>;
+    type ReconnectStream = ReceiverStream<Result<grpc::ExecuteReply, Status>>;
+    type SubscribeStream = ReceiverStream<Result<grpc::SubscribeReply, Status>>;
 
     /// Creates a new BraneScript session.
     /// 
     /// # Arguments
-    /// - `request`: The request to create a response to.
+    /// - `request`: The request to create a response to. If it was sent with a client certificate, the session belongs to that client.
     /// 
     /// # Returns
     /// The response to the request, which only contains a new AppId.
     /// 
     /// # Errors
     /// This function doesn't typically error.
-    async fn create_session(&self, _request: Request<grpc::CreateSessionRequest>) -> Result<Response<grpc::CreateSessionReply>, Status> {
+    async fn create_session(&self, request: Request<grpc::CreateSessionRequest>) -> Result<Response<grpc::CreateSessionReply>, Status> {
+        let owner: Option<String> = self.caller(&request).name().map(String::from);
+
         // Create a new VM for this session
         let app_id: AppId = AppId::generate();
-        self.sessions.insert(app_id.clone(), InstanceVm::new(&self.node_config_path, app_id.clone(), self.proxy.clone(), self.planner.clone()));
+        self.sessions.insert(app_id.clone(), InstanceVm::new(&self.node_config_path, app_id.clone(), self.proxy.clone(), self.notifier.clone(), self.events.clone(), self.approvals.clone(), self.progress.clone(), self.planner.clone(), self.checkpoints.as_deref()).with_plugin(self.plugin).with_max_parallel(self.max_parallel).with_max_steps(self.max_steps).with_owner(owner.clone()));
+        self.created.insert(app_id.clone(), Utc::now());
+        if let Some(owner) = owner { self.owners.insert(app_id.clone(), owner); }
 
         // Now return the ID to the user for future reference
         debug!("Created new session '{}'", app_id);
@@ -158,8 +623,9 @@ impl grpc::DriverService for DriverHandler {
     /// The response to the request, which contains the result of this workflow (if any).
     /// 
     /// # Errors
-    /// This function may error for any reason a job might fail.
+    /// This function may error for any reason a job might fail, or if the session belongs to another client.
     async fn execute(&self, request: Request<grpc::ExecuteRequest>) -> Result<Response<Self::ExecuteStream>, Status> {
+        let caller: Caller = self.caller(&request);
         let request = request.into_inner();
         debug!("Receiving execute request for session '{}'", request.uuid);
 
@@ -171,6 +637,7 @@ impl grpc::DriverService for DriverHandler {
             Ok(app_id) => app_id,
             Err(err)   => { fatal_err!(tx, rx, Status::invalid_argument, err); },
         };
+        if let Err(status) = self.authorize(&caller, &app_id) { fatal_err!(tx, rx, status); }
 
         // Load the config, making sure it's a central config
         let node_config: NodeConfig = match NodeConfig::from_path(&self.node_config_path) {
@@ -187,17 +654,29 @@ impl grpc::DriverService for DriverHandler {
             Some(vm) => vm.clone(),
             None     => { fatal_err!(tx, rx, Status::internal(format!("No session with ID '{}' found", app_id))); }
         };
+        let cancel: CancelToken = CancelToken::new();
+        self.cancels.insert(app_id.clone(), cancel.clone());
+        // Clients may lower the execution limit for this run, but not raise it above ours
+        let max_steps: Option<u64> = match (request.max_steps, self.max_steps) {
+            (Some(requested), Some(max)) => Some(requested.min(max)),
+            (requested, max)             => requested.or(max),
+        };
+        let vm: InstanceVm = vm.with_cancel(cancel).with_max_steps(max_steps);
 
         // We're gonna run the rest asynchronous, to allow the client to earlier receive callbacks
+        if request.detach { debug!("Keeping the output of detached workflow in session '{}' until it expires", app_id); }
+        let tx: mpsc::Sender<Result<grpc::ExecuteReply, Status>> = self.replayed(&app_id, tx, request.detach).await;
         let planner: Arc<InstancePlanner> = self.planner.clone();
-        tokio::spawn(async move {
+        let workflow_id: AppId = app_id.clone();
+        let span: Span = info_span!("workflow", session = %app_id);
+        let handle: JoinHandle<()> = tokio::spawn(async move {
             debug!("Executing workflow for session '{}'", app_id);
     
             // We assume that the input is an already compiled workflow; so no need to fire up any parsers/compilers
 
             // We only have to use JSON magic
             debug!("Parsing workflow of {} characters", request.input.len());
-            let workflow: Workflow = match serde_json::from_str(&request.input) {
+            let mut workflow: Workflow = match serde_json::from_str(&request.input) {
                 Ok(workflow) => workflow,
                 Err(err)     => {
                     debug!("Workflow:\n{}\n{}\n{}\n\n", (0..80).map(|_| '-').collect::<String>(), request.input, (0..80).map(|_| '-').collect::<String>());
@@ -205,54 +684,513 @@ impl grpc::DriverService for DriverHandler {
                 },
             };
 
+            // Reject it if it needs anything we cannot do. We deduce that from the workflow itself instead of trusting the client, but keep what the client claims on top (since it may know of capabilities we do not)
+            let mut requires: EngineRequirements = EngineRequirements::from_edges(&workflow.table, &workflow.graph, &workflow.funcs);
+            requires.merge(&workflow.requires);
+            if let Err(err) = requires.check() {
+                fatal_err!(tx, Status::failed_precondition, err);
+            }
+            workflow.requires = requires;
+
             // Spend some time resolving the workflow with the planner
             debug!("Planning workflow on Kafka topic '{}'", node_config.node.central().topics.planner_command);
-            let plan: Workflow = match planner.plan(workflow).await {
-                Ok(plan) => plan,
-                Err(err) => { fatal_err!(tx, Status::internal, err); },
+            let plan: Workflow = match planner.plan_explained(workflow).await {
+                Ok((plan, reasoning)) => { send_reasoning(&tx, reasoning).await; plan },
+                Err(err)              => { fatal_err!(tx, Status::internal, err); },
             };
 
             // We now have a runnable plan ( ͡° ͜ʖ ͡°), so run it
             debug!("Executing workflow of {} edges", plan.graph.len());
+            telemetry::record_run();
             let (vm, res): (InstanceVm, Result<FullValue, RemoteVmError>) = vm.exec(tx.clone(), plan).await;
 
             // Insert the VM again
             debug!("Saving state session state");
             sessions.insert(app_id, vm);
 
-            // Switch on the actual result and send that back to the user
-            match res {
-                Ok(res)  => {
-                    debug!("Completed execution.");
-
-                    // Serialize the value
-                    let sres: String = match serde_json::to_string(&res) {
-                        Ok(sres) => sres,
-                        Err(err) => { fatal_err!(tx, Status::internal, err); }  
-                    };
-
-                    // Create the reply text
-                    let msg = String::from("Driver completed execution.");
-                    let reply = grpc::ExecuteReply {
-                        close  : true,
-                        debug  : Some(msg.clone()),
-                        stderr : None,
-                        stdout : None,
-                        value  : Some(sres),
-                    };
-
-                    // Send it
-                    if let Err(err) = tx.send(Ok(reply)).await {
-                        error!("Failed to send workflow result back to client: {}", err);
-                    }
-                },
-                Err(err) => {
-                    fatal_err!(tx, Status::internal, err);
-                },
-            };
-        });
+            // Send the result back to the user
+            send_result(tx, res).await;
+        }.instrument(span));
+        self.workflows.insert(workflow_id, handle);
+
+        // Return the receiver stream so the client can find us
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+
+
+    /// Sends an external event to a session, resuming the workflow thread(s) waiting for it.
+    /// 
+    /// # Arguments
+    /// - `request`: The request with the session, the name of the event and its payload.
+    /// 
+    /// # Returns
+    /// An empty response once the event has been delivered (or buffered, if no thread is waiting for it yet).
+    /// 
+    /// # Errors
+    /// This function errors if the given session does not exist, belongs to another client, or if the event is reserved for decisions on approvals (which must go through `decide_approval()`).
+    async fn send_event(&self, request: Request<grpc::SendEventRequest>) -> Result<Response<grpc::SendEventReply>, Status> {
+        let caller: Caller = self.caller(&request);
+        let request = request.into_inner();
+        debug!("Receiving event '{}' for session '{}'", request.name, request.uuid);
+        if is_reserved_event(&request.name) { return Err(Status::permission_denied(format!("Event '{}' is reserved for decisions on approvals", request.name))); }
+
+        // Parse the given ID
+        let app_id: AppId = match AppId::from_str(&request.uuid) {
+            Ok(app_id) => app_id,
+            Err(err)   => { return Err(Status::invalid_argument(err.to_string())); },
+        };
+        if !self.sessions.contains_key(&app_id) { return Err(Status::not_found(format!("No session with ID '{}' found", app_id))); }
+        self.authorize(&caller, &app_id)?;
+
+        // Deliver it
+        self.events.send(&app_id, &request.name, request.payload);
+        Ok(Response::new(grpc::SendEventReply {}))
+    }
+
+
+
+    /// Lists the approvals that workflows are waiting for.
+    /// 
+    /// Approvers see the approvals of every session, since they are the ones deciding on them. Anyone else only sees those of the sessions they may access (see `may_access()`).
+    /// 
+    /// # Arguments
+    /// - `request`: The (empty) request.
+    /// 
+    /// # Returns
+    /// The response to the request, which contains the pending approvals visible to the client (oldest first).
+    /// 
+    /// # Errors
+    /// This function doesn't typically error.
+    async fn list_approvals(&self, request: Request<grpc::ListApprovalsRequest>) -> Result<Response<grpc::ListApprovalsReply>, Status> {
+        let caller: Caller = self.caller(&request);
+        debug!("Receiving list approvals request");
+
+        // Only keep the approvals the client may see
+        let approver: bool = caller.name().map(|name| self.approvals.is_approver(name)).unwrap_or(false);
+        let approvals: Vec<grpc::Approval> = self.approvals.list().into_iter().filter(|(_, approval)| {
+            let owner: Option<String> = self.owners.get(&approval.app_id).map(|owner| owner.value().clone());
+            approver || may_access(&self.admins, &caller, owner.as_deref())
+        }).map(|(id, approval)| grpc::Approval {
+            id,
+            uuid      : approval.app_id.to_string(),
+            message   : approval.message,
+            requested : approval.requested.to_rfc3339(),
+        }).collect();
+        Ok(Response::new(grpc::ListApprovalsReply{ approvals }))
+    }
+
+    /// Approves or rejects a pending approval, resuming the workflow waiting for it.
+    /// 
+    /// # Arguments
+    /// - `request`: The request with the approval and the decision. The user making it is taken from the client certificate it was sent with or, on the internal address, from the client on whose behalf another service forwards it.
+    /// 
+    /// # Returns
+    /// An empty response once the decision has been recorded in the audit log.
+    /// 
+    /// # Errors
+    /// This function errors if the client did not authenticate itself, is not one of the approvers, if the approval is unknown or we failed to write to the audit log.
+    async fn decide_approval(&self, request: Request<grpc::DecideApprovalRequest>) -> Result<Response<grpc::DecideApprovalReply>, Status> {
+        let user: String = match self.caller(&request) {
+            Caller::Client(user) => user,
+            _                    => { return Err(Status::unauthenticated("Decisions on approvals must be made by an authenticated client")); },
+        };
+        let request = request.into_inner();
+        debug!("Receiving decision on approval '{}' by '{}'", request.id, user);
+
+        // Record the decision
+        match self.approvals.decide(&request.id, ApprovalDecision{ approved: request.approve, user, reason: request.reason }) {
+            Ok(_)                                                => Ok(Response::new(grpc::DecideApprovalReply {})),
+            Err(err @ ApprovalError::UnauthorizedApprover{ .. }) => Err(Status::permission_denied(err.to_string())),
+            Err(err @ ApprovalError::UnknownApproval{ .. })      => Err(Status::not_found(err.to_string())),
+            Err(err)                                             => { error!("{}", err); Err(Status::internal("An internal error has occurred.")) },
+        }
+    }
+
+
+
+    /// Compiles BraneScript (or Bakery) on behalf of the client, using the package and data indices of this instance.
+    /// 
+    /// # Arguments
+    /// - `request`: The request with the source text to compile and, optionally, its language.
+    /// 
+    /// # Returns
+    /// The response to the request, which contains the serialized workflow if it compiled and any errors or warnings as diagnostics.
+    /// 
+    /// # Errors
+    /// This function errors if the language is unknown or we failed to fetch the indices. Source text that does not compile is reported as diagnostics instead.
+    async fn compile(&self, request: Request<grpc::CompileRequest>) -> Result<Response<grpc::CompileReply>, Status> {
+        let request = request.into_inner();
+        debug!("Receiving compile request for {} characters of source", request.input.len());
+
+        // Load the config to find the API service
+        let node_config: NodeConfig = match NodeConfig::from_path(&self.node_config_path) {
+            Ok(config) => config,
+            Err(err)   => {
+                error!("Failed to load the NodeConfig: {}", err);
+                return Err(Status::internal("An internal error has occurred."));
+            },
+        };
+
+        // Compile it
+        match compile(&node_config.node.central().services.api.to_string(), &request.input, request.language.as_deref()).await {
+            Ok(reply)                                      => Ok(Response::new(reply)),
+            Err(err @ CompileError::IllegalLanguage{ .. }) => Err(Status::invalid_argument(err.to_string())),
+            Err(err)                                       => { error!("{}", err); Err(Status::internal("An internal error has occurred.")) },
+        }
+    }
+
+
+
+    /// Summarizes the variables defined in an existing session, so attached clients know what they are continuing.
+    /// 
+    /// # Arguments
+    /// - `request`: The request with the session to inspect.
+    /// 
+    /// # Returns
+    /// The response to the request, which contains the serialized frame stack of the session.
+    /// 
+    /// # Errors
+    /// This function errors if the given session does not exist, belongs to another client or we failed to serialize its frames.
+    async fn inspect_session(&self, request: Request<grpc::InspectSessionRequest>) -> Result<Response<grpc::InspectSessionReply>, Status> {
+        let caller: Caller = self.caller(&request);
+        let request = request.into_inner();
+        debug!("Receiving inspect request for session '{}'", request.uuid);
+
+        // Parse the given ID
+        let app_id: AppId = match AppId::from_str(&request.uuid) {
+            Ok(app_id) => app_id,
+            Err(err)   => { return Err(Status::invalid_argument(err.to_string())); },
+        };
+        self.authorize(&caller, &app_id)?;
+
+        // Summarize the VM (which, if it is currently running, reflects the state before that run)
+        let frames: Vec<FrameSummary> = match self.sessions.get(&app_id) {
+            Some(vm) => vm.summary(),
+            None     => { return Err(Status::not_found(format!("No session with ID '{}' found", app_id))); },
+        };
+        match serde_json::to_string(&frames) {
+            Ok(frames) => Ok(Response::new(grpc::InspectSessionReply{ frames })),
+            Err(err)   => { error!("Failed to serialize frames of session '{}': {}", app_id, err); Err(Status::internal("An internal error has occurred.")) },
+        }
+    }
+
+    /// Returns how long the tasks and data transfers in the last run of a session took, so clients can analyze its critical path or profile it.
+    ///
+    /// # Arguments
+    /// - `request`: The request with the session to get the timings of.
+    ///
+    /// # Returns
+    /// The response to the request, which contains the serialized workflow, task timings and transfer timings of the session's last run.
+    ///
+    /// # Errors
+    /// This function errors if the given session does not exist, belongs to another client or we failed to serialize its timings.
+    async fn get_timings(&self, request: Request<grpc::GetTimingsRequest>) -> Result<Response<grpc::GetTimingsReply>, Status> {
+        let caller: Caller = self.caller(&request);
+        let request = request.into_inner();
+        debug!("Receiving timings request for session '{}'", request.uuid);
+
+        // Parse the given ID
+        let app_id: AppId = match AppId::from_str(&request.uuid) {
+            Ok(app_id) => app_id,
+            Err(err)   => { return Err(Status::invalid_argument(err.to_string())); },
+        };
+        self.authorize(&caller, &app_id)?;
+
+        // Get the timings of the last run (which, if it is still running, are the tasks done so far)
+        let (workflow, timings, transfers): (Option<String>, Vec<TaskTiming>, Vec<TransferTiming>) = match self.sessions.get(&app_id) {
+            Some(vm) => vm.timings(),
+            None     => { return Err(Status::not_found(format!("No session with ID '{}' found", app_id))); },
+        };
+        match (serde_json::to_string(&timings), serde_json::to_string(&transfers)) {
+            (Ok(timings), Ok(transfers))  => Ok(Response::new(grpc::GetTimingsReply{ workflow, timings, transfers })),
+            (Err(err), _) | (_, Err(err)) => { error!("Failed to serialize timings of session '{}': {}", app_id, err); Err(Status::internal("An internal error has occurred.")) },
+        }
+    }
+
+
+
+    /// Resumes the workflow of a session from its last checkpoint, e.g., after the driver was restarted while running it.
+    /// 
+    /// # Arguments
+    /// - `request`: The request with the session to resume.
+    /// 
+    /// # Returns
+    /// The response to the request, which contains the result of the resumed workflow (if any).
+    /// 
+    /// # Errors
+    /// This function errors if the session is still active, if there is no checkpoint for it, if it belonged to another client or for any reason a job might fail.
+    async fn resume(&self, request: Request<grpc::ResumeRequest>) -> Result<Response<Self::ResumeStream>, Status> {
+        let caller: Caller = self.caller(&request);
+        let request = request.into_inner();
+        debug!("Receiving resume request for session '{}'", request.uuid);
+
+        // Prepare gRPC stream between client and (this) driver.
+        let (tx, rx) = mpsc::channel::<Result<grpc::ExecuteReply, Status>>(10);
+
+        // Parse the given ID
+        let app_id: AppId = match AppId::from_str(&request.uuid) {
+            Ok(app_id) => app_id,
+            Err(err)   => { fatal_err!(tx, rx, Status::invalid_argument, err); },
+        };
+
+        // Load its checkpoint
+        let dir: &Path = match &self.checkpoints {
+            Some(dir) => dir,
+            None      => { fatal_err!(tx, rx, Status::failed_precondition("Checkpointing is not enabled on this instance")); },
+        };
+        let checkpoint: SessionCheckpoint = match SessionCheckpoint::from_path(dir.join(format!("{}.json", app_id))) {
+            Ok(checkpoint)                       => checkpoint,
+            Err(CheckpointError::NotFound{ .. }) => { fatal_err!(tx, rx, Status::not_found(format!("No checkpoint for session '{}' found", app_id))); },
+            Err(err)                             => {
+                error!("Failed to load checkpoint of session '{}': {}", app_id, err);
+                fatal_err!(tx, rx, Status::internal, "An internal error has occurred.");
+            },
+        };
+        if !may_access(&self.admins, &caller, checkpoint.owner.as_deref()) { fatal_err!(tx, rx, Status::permission_denied(format!("Session '{}' belongs to another client", app_id))); }
+
+        // Recreate the session's VM
+        let cancel: CancelToken = CancelToken::new();
+        let vm: InstanceVm = InstanceVm::new(&self.node_config_path, app_id.clone(), self.proxy.clone(), self.notifier.clone(), self.events.clone(), self.approvals.clone(), self.progress.clone(), self.planner.clone(), Some(dir)).with_plugin(self.plugin).with_max_parallel(self.max_parallel).with_max_steps(self.max_steps).with_owner(checkpoint.owner.clone()).with_cancel(cancel.clone());
+
+        // Sessions that are still known have not lost their progress, so only resume those that aren't; we reserve it in one go, such that two concurrent resumes cannot both run it
+        let reserved: bool = match self.sessions.entry(app_id.clone()) {
+            Entry::Occupied(_)   => false,
+            Entry::Vacant(entry) => { entry.insert(vm.clone()); true },
+        };
+        if !reserved { fatal_err!(tx, rx, Status::already_exists(format!("Session '{}' is still active", app_id))); }
+        self.cancels.insert(app_id.clone(), cancel);
+
+        // We're gonna run the rest asynchronous, to allow the client to earlier receive callbacks
+        let tx: mpsc::Sender<Result<grpc::ExecuteReply, Status>> = self.replayed(&app_id, tx, false).await;
+        let sessions: Arc<DashMap<AppId, InstanceVm>> = self.sessions.clone();
+        self.created.insert(app_id.clone(), Utc::now());
+        if let Some(owner) = checkpoint.owner.clone() { self.owners.insert(app_id.clone(), owner); }
+        let workflow_id: AppId = app_id.clone();
+        let span: Span = info_span!("workflow", session = %app_id, resumed = true);
+        let handle: JoinHandle<()> = tokio::spawn(async move {
+            debug!("Resuming workflow for session '{}' at edge {}", app_id, checkpoint.checkpoint.pc.1);
+            let (vm, res): (InstanceVm, Result<FullValue, RemoteVmError>) = vm.exec_checkpoint(tx.clone(), checkpoint).await;
+
+            // Insert the VM, so the session may be continued as usual
+            debug!("Saving state session state");
+            sessions.insert(app_id, vm);
+
+            // Send the result back to the user
+            send_result(tx, res).await;
+        }.instrument(span));
+        self.workflows.insert(workflow_id, handle);
 
         // Return the receiver stream so the client can find us
         Ok(Response::new(ReceiverStream::new(rx)))
     }
+
+
+
+    /// Reconnects a client to the reply stream of the last workflow in a session, e.g., after its connection dropped.
+    /// 
+    /// # Arguments
+    /// - `request`: The request with the session and the sequence number of the last reply that the client received.
+    /// 
+    /// # Returns
+    /// The response to the request, which first replays the replies that the client missed and then continues with the workflow's new ones.
+    /// 
+    /// # Errors
+    /// This function errors if the session has no workflow to reconnect to, belongs to another client or if the replies that the client missed are no longer available.
+    async fn reconnect(&self, request: Request<grpc::ReconnectRequest>) -> Result<Response<Self::ReconnectStream>, Status> {
+        let caller: Caller = self.caller(&request);
+        let request = request.into_inner();
+        debug!("Receiving reconnect request for session '{}' after reply {}", request.uuid, request.after);
+
+        // Parse the given ID
+        let app_id: AppId = match AppId::from_str(&request.uuid) {
+            Ok(app_id) => app_id,
+            Err(err)   => { return Err(Status::invalid_argument(err.to_string())); },
+        };
+        self.authorize(&caller, &app_id)?;
+
+        // Attach to its replies, which may have to be restored if they were those of a detached workflow that ran before the driver restarted
+        let replay: Option<Arc<ReplayBuffer>> = self.replays.get(&app_id).map(|replay| replay.clone());
+        let replay: Arc<ReplayBuffer> = match replay {
+            Some(replay) => replay,
+            None         => self.restore_replay(&caller, &app_id).await?,
+        };
+        let rx: mpsc::Receiver<Result<grpc::ExecuteReply, Status>> = replay.attach(request.after)?;
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+
+
+    /// Cancels the workflow that is running in a session, which stops it between edges and stops the tasks it is waiting for.
+    /// 
+    /// The session itself remains, so it may be used to run other workflows.
+    /// 
+    /// # Arguments
+    /// - `request`: The request with the session of which to cancel the workflow.
+    /// 
+    /// # Returns
+    /// The response to the request, which tells whether there was a running workflow to cancel. The workflow itself reports that it was cancelled to its client.
+    /// 
+    /// # Errors
+    /// This function errors if the given session does not exist or belongs to another client.
+    async fn cancel(&self, request: Request<grpc::CancelRequest>) -> Result<Response<grpc::CancelReply>, Status> {
+        let caller: Caller = self.caller(&request);
+        let request = request.into_inner();
+        debug!("Receiving cancel request for session '{}'", request.uuid);
+
+        // Parse the given ID
+        let app_id: AppId = match AppId::from_str(&request.uuid) {
+            Ok(app_id) => app_id,
+            Err(err)   => { return Err(Status::invalid_argument(err.to_string())); },
+        };
+        if !self.sessions.contains_key(&app_id) { return Err(Status::not_found(format!("No session with ID '{}' found", app_id))); }
+        self.authorize(&caller, &app_id)?;
+
+        // Only cancel it if it is still running
+        let running: bool = self.replays.get(&app_id).map(|replay| replay.is_running()).unwrap_or(false);
+        let cancelled: bool = match self.cancels.get(&app_id) {
+            Some(cancel) if running => { cancel.cancel(); true },
+            _                       => false,
+        };
+        debug!("Cancelled workflow in session '{}': {}", app_id, cancelled);
+        Ok(Response::new(grpc::CancelReply{ cancelled }))
+    }
+
+
+
+    /// Lists the sessions that are currently known to the driver, so that clients may find the ones they left dangling.
+    /// 
+    /// # Arguments
+    /// - `request`: The (empty) request. Only the sessions that the client that sent it may use are listed (see `may_access()`).
+    /// 
+    /// # Returns
+    /// The response to the request, which contains the sessions (oldest first) and whether they are running a workflow and have a client following it.
+    /// 
+    /// # Errors
+    /// This function doesn't typically error.
+    async fn list_sessions(&self, request: Request<grpc::ListSessionsRequest>) -> Result<Response<grpc::ListSessionsReply>, Status> {
+        let caller: Caller = self.caller(&request);
+        debug!("Receiving list sessions request");
+        let mut sessions: Vec<(AppId, DateTime<Utc>)> = self.sessions.iter().filter(|e| {
+            let owner: Option<String> = self.owners.get(e.key()).map(|owner| owner.value().clone());
+            may_access(&self.admins, &caller, owner.as_deref())
+        }).map(|e| {
+            let created: DateTime<Utc> = self.created.get(e.key()).map(|c| *c.value()).unwrap_or_else(Utc::now);
+            (e.key().clone(), created)
+        }).collect();
+        sessions.sort_by_key(|(_, created)| *created);
+
+        // Add whether they are running
+        let sessions: Vec<grpc::SessionInfo> = sessions.into_iter().map(|(app_id, created)| {
+            let (running, attached): (bool, bool) = match self.replays.get(&app_id) {
+                Some(replay) => (replay.is_running(), replay.is_attached()),
+                None         => (false, false),
+            };
+            grpc::SessionInfo {
+                uuid    : app_id.into(),
+                created : created.to_rfc3339(),
+                running,
+                attached,
+            }
+        }).collect();
+        Ok(Response::new(grpc::ListSessionsReply{ sessions }))
+    }
+
+    /// Kills a session, aborting the workflow that it is running (if any) and forgetting its state, pending approvals and checkpoint.
+    /// 
+    /// # Arguments
+    /// - `request`: The request with the session to kill.
+    /// 
+    /// # Returns
+    /// The response to the request, which tells whether a running workflow was aborted.
+    /// 
+    /// # Errors
+    /// This function errors if the given session does not exist or belongs to another client.
+    async fn kill_session(&self, request: Request<grpc::KillSessionRequest>) -> Result<Response<grpc::KillSessionReply>, Status> {
+        let caller: Caller = self.caller(&request);
+        let request = request.into_inner();
+        debug!("Receiving kill request for session '{}'", request.uuid);
+
+        // Parse the given ID
+        let app_id: AppId = match AppId::from_str(&request.uuid) {
+            Ok(app_id) => app_id,
+            Err(err)   => { return Err(Status::invalid_argument(err.to_string())); },
+        };
+        if !self.sessions.contains_key(&app_id) { return Err(Status::not_found(format!("No session with ID '{}' found", app_id))); }
+        self.authorize(&caller, &app_id)?;
+
+        // Tell its client (if any), then abort its workflow and wait until it is gone so that it cannot re-insert the session
+        let mut aborted: bool = false;
+        if let Some((_, replay)) = self.replays.remove(&app_id) {
+            aborted = replay.is_running();
+            replay.kill(format!("Session '{}' was killed", app_id));
+        }
+        if let Some((_, handle)) = self.workflows.remove(&app_id) {
+            handle.abort();
+            if let Err(err) = handle.await {
+                if !err.is_cancelled() { error!("Workflow of session '{}' panicked: {}", app_id, err); }
+            }
+        }
+
+        // Then forget everything about it
+        if let Some((_, vm)) = self.sessions.remove(&app_id) { vm.clear_checkpoint().await; }
+        if let Some(log) = self.replay_log(&app_id) { remove_replay_log(&log).await; }
+        self.created.remove(&app_id);
+        self.owners.remove(&app_id);
+        self.cancels.remove(&app_id);
+        let approvals: usize = self.approvals.forget(&app_id);
+        self.events.forget(&app_id);
+        self.progress.forget(&app_id);
+        debug!("Killed session '{}' (aborted workflow: {}, dropped approvals: {})", app_id, aborted, approvals);
+        Ok(Response::new(grpc::KillSessionReply{ aborted }))
+    }
+
+
+
+    /// Subscribes to the structured events of a session (i.e., which tasks and transfers start and end, what is printed and how its workflows end), for UIs that want to follow it.
+    /// 
+    /// Unlike the reply stream of `execute()`, any number of clients may subscribe to the same session, and subscribing does not affect the workflow. Subscribers only receive the events that happen after they subscribed.
+    /// 
+    /// # Arguments
+    /// - `request`: The request with the session to subscribe to.
+    /// 
+    /// # Returns
+    /// The response to the request, which streams the events of the session as JSON until the session is killed or the client leaves. Every event tells how many events were missed before it because the client could not keep up.
+    /// 
+    /// # Errors
+    /// This function errors if the given session does not exist or belongs to another client.
+    async fn subscribe(&self, request: Request<grpc::SubscribeRequest>) -> Result<Response<Self::SubscribeStream>, Status> {
+        let caller: Caller = self.caller(&request);
+        let request = request.into_inner();
+        debug!("Receiving subscribe request for session '{}'", request.uuid);
+
+        // Parse the given ID
+        let app_id: AppId = match AppId::from_str(&request.uuid) {
+            Ok(app_id) => app_id,
+            Err(err)   => { return Err(Status::invalid_argument(err.to_string())); },
+        };
+        if !self.sessions.contains_key(&app_id) { return Err(Status::not_found(format!("No session with ID '{}' found", app_id))); }
+        self.authorize(&caller, &app_id)?;
+
+        // Relay the events of the session to the client
+        let mut events = self.progress.subscribe(&app_id);
+        let (tx, rx) = mpsc::channel::<Result<grpc::SubscribeReply, Status>>(10);
+        tokio::spawn(async move {
+            let mut missed: u64 = 0;
+            loop {
+                let mut reply: grpc::SubscribeReply = match events.recv().await {
+                    Ok(reply)                 => reply,
+                    Err(RecvError::Lagged(n)) => { missed += n; continue; },
+                    Err(RecvError::Closed)    => { break; },
+                };
+                reply.missed = missed;
+                missed = 0;
+                if tx.send(Ok(reply)).await.is_err() {
+                    debug!("Subscriber of session '{}' left", app_id);
+                    break;
+                }
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
 }
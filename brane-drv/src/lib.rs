@@ -16,8 +16,14 @@
 // 
 
 // Declare the modules
+pub mod approvals;
+pub mod compile;
 pub mod errors;
+pub mod events;
+pub mod notify;
 pub mod spec;
 pub mod planner;
+pub mod progress;
+pub mod replay;
 pub mod vm;
 pub mod handler;
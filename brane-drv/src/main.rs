@@ -12,20 +12,27 @@
 //!   Entrypoint to the `brane-drv` service.
 // 
 
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
 use clap::Parser;
 use dotenvy::dotenv;
-use log::{debug, error, info, LevelFilter};
-use tonic::transport::Server;
+use log::{debug, error, info, warn, LevelFilter};
+use tonic::transport::{Certificate, Identity, Server, ServerTlsConfig};
 
 use brane_cfg::node::NodeConfig;
+use brane_exe::plugins::{PluginEntry, PluginRegistry};
 use brane_prx::client::ProxyClient;
+use brane_shr::otel;
+use brane_shr::telemetry;
 use brane_tsk::grpc::DriverServiceServer;
 
 use brane_drv::planner::InstancePlanner;
 use brane_drv::handler::DriverHandler;
+use brane_drv::notify::{self, Notifier};
+use brane_drv::vm::{plugins, InstanceVm, DEFAULT_PLUGIN};
 
 
 /***** ARGUMENTS *****/
@@ -43,6 +50,29 @@ struct Opts {
     /// Node environment metadata store.
     #[clap(short, long, default_value = "/node.yml", help = "The path to the node environment configuration. This defines things such as where local services may be found or where to store files, as wel as this service's service address.", env = "NODE_CONFIG_PATH")]
     node_config_path : PathBuf,
+
+    /// The number of replies to keep for reconnecting clients.
+    #[clap(long, default_value = "1024", help = "The number of replies of the last workflow in every session that we keep, so that clients whose connection dropped can reconnect without losing output.", env = "REPLAY_WINDOW")]
+    replay_window   : usize,
+    /// The number of replies to keep for detached workflows.
+    #[clap(long, default_value = "65536", help = "The number of replies of detached workflows that we keep (and persist next to the checkpoints, if enabled) until a client attaches to them. Older replies are dropped.", env = "DETACHED_WINDOW")]
+    detached_window : usize,
+    /// How long to keep the replies of detached workflows.
+    #[clap(long, default_value = "86400", help = "The number of seconds that we keep the replies of detached workflows after they are done, after which clients can no longer attach to them.", env = "DETACHED_TTL")]
+    detached_ttl    : u64,
+    /// The heartbeat interval.
+    #[clap(long, default_value = "10", help = "The number of seconds that a reply stream may be quiet before we send a heartbeat to the client, so it can detect dropped connections.", env = "HEARTBEAT_INTERVAL")]
+    heartbeat       : u64,
+    /// The maximum number of parallel branches to run at once.
+    #[clap(long, help = "The maximum number of branches of a single parallel statement that a workflow runs at the same time. Any remaining branches are started as running ones complete. If omitted, all branches are started at once.", env = "MAX_PARALLEL")]
+    max_parallel    : Option<usize>,
+    /// The maximum number of steps that a single workflow run may take.
+    #[clap(long, help = "The maximum number of steps (i.e., edges and instructions) that a single workflow run may take before it is aborted, protecting the driver against workflows that loop forever. Clients may ask for a lower limit per run, but not for a higher one. If omitted, runs are not limited.", env = "MAX_STEPS")]
+    max_steps       : Option<u64>,
+
+    /// The plugin with which to run workflows.
+    #[clap(long, default_value = DEFAULT_PLUGIN, help = "The plugin with which workflows are run. Can be 'instance' to run them on the instance, 'mock' to pretend to run them without running any tasks or transferring any data, or 'record' to run them on the instance while logging every task call, transfer and event.", env = "VM_PLUGIN")]
+    plugin : String,
 }
 
 
@@ -75,6 +105,15 @@ async fn main() {
         },
     };
     if !node_config.node.is_central() { error!("Given NodeConfig file '{}' does not have properties for a central node.", opts.node_config_path.display()); std::process::exit(1); }
+    info!("Enabled features: {}", node_config.features);
+
+    // Resolve the plugin to run workflows with
+    let registry: PluginRegistry<InstanceVm> = plugins();
+    let plugin: PluginEntry<InstanceVm> = match registry.get(&opts.plugin) {
+        Some(plugin) => plugin,
+        None         => { error!("Unknown VM plugin '{}' (known plugins: {})", opts.plugin, registry.names().join(", ")); std::process::exit(1); },
+    };
+    if opts.plugin != DEFAULT_PLUGIN { info!("Running workflows with the '{}' VM plugin", opts.plugin); }
 
     // Create our side of the planner, and launch its event monitor
     let planner: Arc<InstancePlanner> = match InstancePlanner::new(node_config.clone()) {
@@ -83,21 +122,88 @@ async fn main() {
     };
     if let Err(err) = planner.start_event_monitor(&opts.group_id).await { error!("Failed to start InstancePlanner event monitor: {}", err); std::process::exit(1); }
 
+    // Start reporting usage statistics, if the node opted-in
+    telemetry::spawn_reporter(node_config.telemetry.as_ref(), "central", env!("CARGO_PKG_VERSION"));
+    // Set up tracing, exporting spans if the node is configured to
+    if let Err(err) = otel::init("brane-drv", node_config.tracing.as_ref()) { warn!("{} (spans will not be exported)", err); }
+
     // Start the DriverHandler
+    let notifier: Arc<Notifier> = match Notifier::new(notify::DEFAULT_TIMEOUT) {
+        Ok(notifier) => Arc::new(notifier),
+        Err(err)     => { error!("Failed to create Notifier: {}", err); std::process::exit(1); },
+    };
     let handler = DriverHandler::new(
         &opts.node_config_path,
         Arc::new(ProxyClient::new(node_config.services.prx)),
+        notifier,
         planner.clone(),
+        plugin,
+        node_config.node.central().paths.audit.clone(),
+        node_config.node.central().tls.iter().flat_map(|tls| tls.approvers.iter().cloned()).collect(),
+        node_config.node.central().tls.iter().flat_map(|tls| tls.admins.iter().cloned()).collect(),
+        node_config.node.central().paths.checkpoints.clone(),
+        opts.replay_window,
+        opts.detached_window,
+        Duration::from_secs(opts.detached_ttl),
+        Duration::from_secs(opts.heartbeat),
+        opts.max_parallel,
+        opts.max_steps,
     );
+    handler.sweep_replay_logs().await;
+
+    // Serve over TLS only, if configured
+    if let Some(tls) = node_config.node.central().tls.clone() {
+        let certs   : &Path        = &node_config.paths.certs;
+        let mut pem : Vec<Vec<u8>> = Vec::with_capacity(3);
+        for file in [ "server.pem", "server-key.pem", "ca.pem" ] {
+            match fs::read(certs.join(file)) {
+                Ok(raw)  => pem.push(raw),
+                Err(err) => { error!("Failed to read TLS file '{}': {}", certs.join(file).display(), err); std::process::exit(1); },
+            }
+        }
+        let config: ServerTlsConfig = ServerTlsConfig::new()
+            .identity(Identity::from_pem(&pem[0], &pem[1]))
+            .client_ca_root(Certificate::from_pem(&pem[2]))
+            .client_auth_optional(!tls.require_client_auth);
+        let mut server: Server = match Server::builder().tls_config(config) {
+            Ok(server) => server,
+            Err(err)   => { error!("Failed to configure TLS for gRPC server: {}", err); std::process::exit(1); },
+        };
+
+        // Only the other services on the node may reach us in plaintext, and only on the internal address
+        if let Some(internal) = tls.internal_drv {
+            let handler: DriverHandler = handler.internal();
+            tokio::spawn(async move {
+                debug!("gRPC server ready to serve in plaintext on internal address '{}'", internal);
+                if let Err(err) = Server::builder().add_service(DriverServiceServer::new(handler)).serve_with_shutdown(internal, otel::shutdown_signal()).await {
+                    error!("Failed to start internal gRPC server: {}", err);
+                    std::process::exit(1);
+                }
+            });
+        }
+
+        debug!("gRPC server ready to serve over TLS on '{}' (client certificates {})", tls.drv, if tls.require_client_auth { "required" } else { "optional" });
+        if let Err(err) = server.add_service(DriverServiceServer::new(handler)).serve_with_shutdown(tls.drv, otel::shutdown_signal()).await {
+            error!("Failed to start gRPC server over TLS: {}", err);
+            otel::shutdown().await;
+            std::process::exit(1);
+        }
+        otel::shutdown().await;
+        return;
+    }
 
     // Start gRPC server with callback service.
     debug!("gRPC server ready to serve on '{}'", node_config.node.central().ports.drv);
     if let Err(err) = Server::builder()
         .add_service(DriverServiceServer::new(handler))
-        .serve(node_config.node.central().ports.drv)
+        .serve_with_shutdown(node_config.node.central().ports.drv, otel::shutdown_signal())
         .await
     {
         error!("Failed to start gRPC server: {}", err);
+        otel::shutdown().await;
         std::process::exit(1);
     }
+
+    // Export the spans of the last workflows before we go
+    otel::shutdown().await;
 }
@@ -0,0 +1,233 @@
+//  NOTIFY.rs
+//    by agent
+//
+//  Created:
+//    16 Oct 2026, 08:04:15
+//  Last edited:
+//    16 Oct 2026, 13:33:30
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements sending notifications on the channels configured in the
+//!   central node's `node.yml` file.
+//
+
+use std::time::Duration;
+
+use lettre::{AsyncSmtpTransport, AsyncTransport as _, Message, Tokio1Executor};
+use lettre::message::{Mailbox, MessageBuilder};
+use lettre::transport::smtp::AsyncSmtpTransportBuilder;
+use lettre::transport::smtp::authentication::Credentials;
+use log::debug;
+use reqwest::{Client, Response};
+
+use brane_cfg::node::NotificationChannel;
+use brane_tsk::spec::AppId;
+
+pub use crate::errors::NotifyError as Error;
+
+
+/***** UNIT TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    /// Starts a mock webhook that records the body of every request it receives.
+    ///
+    /// # Arguments
+    /// - `status`: The status code with which the webhook responds, or `None` to never respond at all.
+    ///
+    /// # Returns
+    /// The URL of the webhook and the bodies it received.
+    async fn webhook(status: Option<u16>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url: String = format!("http://{}/hook", listener.local_addr().unwrap());
+        let bodies: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(vec![]));
+
+        let received: Arc<Mutex<Vec<String>>> = bodies.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                // Read until we have the headers and as much body as they announce
+                let mut raw: Vec<u8> = vec![];
+                let mut buf: [u8; 1024] = [0; 1024];
+                let body: String = loop {
+                    let n: usize = stream.read(&mut buf).await.unwrap();
+                    raw.extend_from_slice(&buf[..n]);
+                    let text: String = String::from_utf8_lossy(&raw).to_string();
+                    if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                        let length: usize = head.lines().find_map(|line| line.to_lowercase().strip_prefix("content-length:").map(|n| n.trim().parse().unwrap())).unwrap_or(0);
+                        if body.len() >= length { break body.to_string(); }
+                    }
+                    if n == 0 { break String::new(); }
+                };
+                received.lock().unwrap().push(body);
+
+                match status {
+                    Some(status) => { stream.write_all(format!("HTTP/1.1 {} Mock\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status).as_bytes()).await.unwrap(); },
+                    // Keep the connection open without answering
+                    None => { tokio::spawn(async move { tokio::time::sleep(Duration::from_secs(60)).await; drop(stream); }); },
+                }
+            }
+        });
+        (url, bodies)
+    }
+
+    #[tokio::test]
+    async fn test_webhook_delivers() {
+        let (url, bodies) = webhook(Some(200)).await;
+        let notifier: Notifier = Notifier::new(DEFAULT_TIMEOUT).unwrap();
+        let app_id: AppId = AppId::generate();
+        notifier.send("slack", &NotificationChannel::Slack{ webhook: url.clone() }, &app_id, "Hello there").await.unwrap();
+        notifier.send("slack", &NotificationChannel::Slack{ webhook: url }, &app_id, "General Kenobi").await.unwrap();
+
+        // Every message should have been posted in the format of Slack's incoming webhooks
+        let bodies: Vec<serde_json::Value> = bodies.lock().unwrap().iter().map(|body| serde_json::from_str(body).unwrap()).collect();
+        assert_eq!(bodies, vec![
+            serde_json::json!({ "text": format!("[{}] Hello there", app_id) }),
+            serde_json::json!({ "text": format!("[{}] General Kenobi", app_id) }),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_webhook_refuses() {
+        let (url, _) = webhook(Some(403)).await;
+        let notifier: Notifier = Notifier::new(DEFAULT_TIMEOUT).unwrap();
+        match notifier.send("slack", &NotificationChannel::Slack{ webhook: url }, &AppId::generate(), "Hello there").await {
+            Err(Error::WebhookFailure{ status, .. }) => assert_eq!(status.as_u16(), 403),
+            res                                      => panic!("Expected a WebhookFailure, got {:?}", res),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_webhook_times_out() {
+        let (url, _) = webhook(None).await;
+        let notifier: Notifier = Notifier::new(Duration::from_millis(200)).unwrap();
+        match notifier.send("slack", &NotificationChannel::Slack{ webhook: url }, &AppId::generate(), "Hello there").await {
+            Err(Error::WebhookRequestError{ err, .. }) => assert!(err.is_timeout(), "Expected a timeout, got {}", err),
+            res                                        => panic!("Expected a WebhookRequestError, got {:?}", res),
+        }
+    }
+}
+
+
+
+
+
+/***** CONSTANTS *****/
+/// How long we wait for webhooks to accept a notification by default.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Parses an e-mail address.
+///
+/// # Arguments
+/// - `name`: The name of the channel that defines the address (used for debugging purposes only).
+/// - `raw`: The raw address to parse.
+///
+/// # Returns
+/// The parsed address as a Mailbox.
+///
+/// # Errors
+/// This function errors if the given address is not a valid e-mail address.
+fn parse_address(name: &str, raw: &str) -> Result<Mailbox, Error> {
+    raw.parse().map_err(|err| Error::IllegalAddress{ name: name.into(), raw: raw.into(), err })
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Sends notifications on the channels configured in the `node.yml` file, sharing its connections between them.
+#[derive(Debug)]
+pub struct Notifier {
+    /// The client with which we post to webhooks.
+    client : Client,
+}
+
+impl Notifier {
+    /// Constructor for the Notifier.
+    ///
+    /// # Arguments
+    /// - `timeout`: How long to wait for a webhook to accept a notification.
+    ///
+    /// # Returns
+    /// A new Notifier instance.
+    ///
+    /// # Errors
+    /// This function errors if we failed to create the HTTP client.
+    pub fn new(timeout: Duration) -> Result<Self, Error> {
+        match Client::builder().timeout(timeout).build() {
+            Ok(client) => Ok(Self{ client }),
+            Err(err)   => Err(Error::ClientCreateError{ err }),
+        }
+    }
+
+    /// Sends a notification on the given channel.
+    ///
+    /// # Arguments
+    /// - `name`: The name of the channel to send the notification on.
+    /// - `channel`: The NotificationChannel that describes how to reach its users.
+    /// - `app_id`: The session that sends the notification, which we mention in the message.
+    /// - `message`: The message to send.
+    ///
+    /// # Errors
+    /// This function errors if we failed to deliver the message.
+    pub async fn send(&self, name: &str, channel: &NotificationChannel, app_id: &AppId, message: &str) -> Result<(), Error> {
+        match channel {
+            NotificationChannel::Slack{ webhook } => {
+                debug!("Posting notification to webhook '{}'...", webhook);
+                let res: Response = match self.client.post(webhook).json(&serde_json::json!({ "text": format!("[{}] {}", app_id, message) })).send().await {
+                    Ok(res)  => res,
+                    Err(err) => { return Err(Error::WebhookRequestError{ name: name.into(), url: webhook.clone(), err }); },
+                };
+                if !res.status().is_success() { return Err(Error::WebhookFailure{ name: name.into(), url: webhook.clone(), status: res.status() }); }
+            },
+
+            NotificationChannel::Email{ host, port, username, password, from, to } => {
+                // Build the e-mail
+                let mut builder: MessageBuilder = Message::builder()
+                    .from(parse_address(name, from)?)
+                    .subject(format!("Notification from Brane workflow '{}'", app_id));
+                for to in to {
+                    builder = builder.to(parse_address(name, to)?);
+                }
+                let email: Message = match builder.body(message.to_string()) {
+                    Ok(email) => email,
+                    Err(err)  => { return Err(Error::EmailCreateError{ name: name.into(), err }); },
+                };
+
+                // Send it through the relay
+                debug!("Sending notification through SMTP relay '{}:{}'...", host, port);
+                let mut transport: AsyncSmtpTransportBuilder = match AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host) {
+                    Ok(transport) => transport.port(*port),
+                    Err(err)      => { return Err(Error::EmailSendError{ name: name.into(), host: host.clone(), err }); },
+                };
+                if let (Some(username), Some(password)) = (username, password) {
+                    let password = match password.resolve() {
+                        Ok(password) => password,
+                        Err(err)     => { return Err(Error::PasswordError{ name: name.into(), err }); },
+                    };
+                    transport = transport.credentials(Credentials::new(username.clone(), password.expose().into()));
+                }
+                if let Err(err) = transport.build().send(email).await {
+                    return Err(Error::EmailSendError{ name: name.into(), host: host.clone(), err });
+                }
+            },
+        }
+
+        // Done
+        Ok(())
+    }
+}
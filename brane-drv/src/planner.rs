@@ -28,12 +28,19 @@ use rdkafka::{ClientConfig, Message};
 use rdkafka::consumer::stream_consumer::StreamConsumer;
 use rdkafka::producer::{FutureProducer, FutureRecord};
 use rdkafka::util::Timeout;
+use tonic::{Code, Request};
+use tonic::transport::Channel;
+use tracing::Span;
 
 use brane_ast::Workflow;
-use brane_cfg::node::NodeConfig;
+use brane_cfg::node::{NodeConfig, PlannerTransport};
+use brane_cfg::spec::Address;
 use brane_shr::kafka::{ensure_topics, restore_committed_offsets};
+use brane_shr::otel;
 use brane_tsk::errors::PlanError;
 use brane_tsk::spec::{Planner, TaskId};
+use brane_tsk::tools::inject_trace;
+use specifications::grpc::{PlanReply, PlanRequest, PlannerServiceClient};
 use specifications::planning::{PlanningStatus, PlanningStatusKind, PlanningUpdate};
 
 
@@ -102,9 +109,9 @@ impl Future for WaitUntilPlanned {
                 }
 
                 // The planning was finished
-                PlanningStatus::Success(_) |
-                PlanningStatus::Failed(_)  |
-                PlanningStatus::Error(_)   => {
+                PlanningStatus::Success(..) |
+                PlanningStatus::Failed(_)   |
+                PlanningStatus::Error(_)    => {
                     debug!("Planning of workflow '{}' completed", self.correlation_id);
                     return Poll::Ready(Some(status));
                 },
@@ -138,11 +145,11 @@ impl Future for WaitUntilPlanned {
 /// - `updates`: The event monitor-updated list of the latest status per correlation ID.
 /// 
 /// # Returns
-/// The planned workflow as a Workflow. It being planned means its tasks and datasets are resolved.
+/// The planned workflow as a Workflow, together with the planner's explanation of why every task was planned where it was. It being planned means its tasks and datasets are resolved.
 /// 
 /// # Errors
 /// This function errors if we either failed to wait on Kafka, or if the remote planner failed.
-async fn wait_planned(correlation_id: impl AsRef<str>, waker: Arc<Mutex<Option<Waker>>>, updates: Arc<DashMap<String, PlanningStatus>>) -> Result<Workflow, PlanError> {
+async fn wait_planned(correlation_id: impl AsRef<str>, waker: Arc<Mutex<Option<Waker>>>, updates: Arc<DashMap<String, PlanningStatus>>) -> Result<(Workflow, Vec<String>), PlanError> {
     let correlation_id: &str = correlation_id.as_ref();
 
     // Wait until a plan result occurs
@@ -165,7 +172,7 @@ async fn wait_planned(correlation_id: impl AsRef<str>, waker: Arc<Mutex<Option<W
     // Match the result itself
     match res {
         // The planning was done
-        PlanningStatus::Success(wf) => {
+        PlanningStatus::Success(wf, reasoning) => {
             // We attempt to parse the result itself as a Workflow
             let workflow: Workflow = match serde_json::from_str(&wf) {
                 Ok(workflow) => workflow,
@@ -173,7 +180,7 @@ async fn wait_planned(correlation_id: impl AsRef<str>, waker: Arc<Mutex<Option<W
             };
 
             // Done, return
-            Ok(workflow)
+            Ok((workflow, reasoning))
         },
 
         // Otherwise, no plan available
@@ -185,18 +192,70 @@ async fn wait_planned(correlation_id: impl AsRef<str>, waker: Arc<Mutex<Option<W
     }
 }
 
+/// Sends the given workflow directly to the gRPC service of the planner, and waits for it to be planned.
+/// 
+/// # Arguments
+/// - `address`: The address of the planner's gRPC service.
+/// - `correlation_id`: The identifier for the workflow, which the planner uses in its logs.
+/// - `workflow`: The workflow to plan, serialized as JSON.
+/// 
+/// # Returns
+/// The planned workflow as a Workflow, together with the planner's explanation of why every task was planned where it was. It being planned means its tasks and datasets are resolved.
+/// 
+/// # Errors
+/// This function errors if we failed to reach the planner, or if the planner failed.
+async fn plan_grpc(address: &Address, correlation_id: String, workflow: String) -> Result<(Workflow, Vec<String>), PlanError> {
+    debug!("Sending workflow '{}' to planner at '{}'...", correlation_id, address);
+    let mut client: PlannerServiceClient<Channel> = match PlannerServiceClient::connect(address.to_string()).await {
+        Ok(client) => client,
+        Err(err)   => { return Err(PlanError::GrpcConnectError{ endpoint: address.clone(), err }); },
+    };
+
+    // Send the workflow, carrying its trace along to the planner
+    let mut request: Request<PlanRequest> = Request::new(PlanRequest{ id: correlation_id.clone(), workflow });
+    inject_trace(&Span::current(), &mut request);
+    let reply: PlanReply = match client.plan(request).await {
+        Ok(reply) => reply.into_inner(),
+        Err(err)  => match err.code() {
+            // The planner tells us whether it found no plan or errored by the status code
+            Code::FailedPrecondition => { return Err(PlanError::PlanningFailed{ correlation_id, reason: Some(err.message().into()) }); },
+            Code::Internal           => { return Err(PlanError::PlanningError{ correlation_id, err: err.message().into() }); },
+            _                        => { return Err(PlanError::GrpcRequestError{ correlation_id, endpoint: address.clone(), err }); },
+        },
+    };
+
+    // We attempt to parse the result itself as a Workflow
+    match serde_json::from_str(&reply.workflow) {
+        Ok(workflow) => Ok((workflow, reply.reasoning)),
+        Err(err)     => Err(PlanError::PlanParseError{ correlation_id, raw: reply.workflow, err }),
+    }
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Defines how we send workflows to the planner, together with what we need to do so.
+enum Transport {
+    /// We send them over Kafka, using the given producer.
+    Kafka(Arc<FutureProducer>),
+    /// We send them to the planner's gRPC service at the given address.
+    Grpc(Address),
+}
+
 
 
 
 
 /***** LIBRARY *****/
-/// The planner is in charge of assigning locations to tasks in a workflow. This one is very simple, assigning 'localhost' to whatever it sees.
+/// The planner is in charge of assigning locations to tasks in a workflow. This one sends workflows to the `brane-plr` service, either over Kafka or gRPC (as configured in the node config).
 pub struct InstancePlanner {
     /// The Kafka servers we're connecting to.
     node_config : NodeConfig,
 
-    /// The Kafka producer with which we send commands and such.
-    producer  : Arc<FutureProducer>,
+    /// How we reach the planner (including the Kafka producer with which we send commands, if we do so over Kafka).
+    transport : Transport,
     /// The waker triggered by the event monitor to trigger futures waiting for event updates.
     waker     : Arc<Mutex<Option<Waker>>>,
     /// The list of states that contains the most recently received planner updates.
@@ -207,20 +266,27 @@ impl InstancePlanner {
     /// Constructor for the InstancePlanner.
     /// 
     /// # Arguments
-    /// - `node_config`: The configuration for this node's environment. For us, mostly Kafka topics and associated broker.
+    /// - `node_config`: The configuration for this node's environment. For us, mostly how to reach the planner (i.e., Kafka topics and associated broker, or the planner's gRPC service).
     /// 
     /// # Returns
     /// A new InstancePlanner instance.
     #[inline]
     pub fn new(node_config: NodeConfig) -> Result<Self, PlanError> {
-        let brokers: String = node_config.node.central().services.brokers.iter().map(|a| a.to_string()).collect::<Vec<String>>().join(",");
+        let transport: Transport = match &node_config.node.central().planner {
+            PlannerTransport::Kafka => {
+                let brokers: String = node_config.node.central().services.brokers.iter().map(|a| a.to_string()).collect::<Vec<String>>().join(",");
+                match ClientConfig::new().set("bootstrap.servers", &brokers).set("message.timeout.ms", "5000").create() {
+                    Ok(producer) => Transport::Kafka(Arc::new(producer)),
+                    Err(err)     => { return Err(PlanError::KafkaProducerError { err }); },
+                }
+            },
+            PlannerTransport::Grpc{ address, .. } => Transport::Grpc(address.clone()),
+        };
+
         Ok(Self {
             node_config,
 
-            producer  : match ClientConfig::new().set("bootstrap.servers", &brokers).set("message.timeout.ms", "5000").create() {
-                Ok(producer) => Arc::new(producer),
-                Err(err)     => { return Err(PlanError::KafkaProducerError { err }); },
-            },
+            transport,
             waker     : Arc::new(Mutex::new(None)),
             updates   : Arc::new(DashMap::new()),
         })
@@ -232,6 +298,8 @@ impl InstancePlanner {
     /// 
     /// Note that the event monitor itself is launched asynchronously. When this function returns, it has merely started (even though it's an async function itself - but that's only used during setup, I swear).
     /// 
+    /// If we talk to the planner over gRPC, there are no updates to monitor and this function does nothing.
+    /// 
     /// # Arguments
     /// - `group_id`: The Kafka group ID to listen on.
     /// 
@@ -239,6 +307,10 @@ impl InstancePlanner {
     /// This function errors if we failed to start listening on the Kafka stream and create a future for that.
     pub async fn start_event_monitor(&self, group_id: impl AsRef<str>) -> Result<(), PlanError> {
         let group_id  : &str = group_id.as_ref();
+        if let Transport::Grpc(address) = &self.transport {
+            debug!("Planning workflows using the planner at '{}' over gRPC; not starting event monitor", address);
+            return Ok(());
+        }
 
         // Ensure that the to-be-listened on topic exists
         let brokers: String = self.node_config.node.central().services.brokers.iter().map(|a| a.to_string()).collect::<Vec<String>>().join(",");
@@ -296,7 +368,7 @@ impl InstancePlanner {
                                     };
         
                                     // Store it
-                                    owned_updates.insert(msg.id, PlanningStatus::Success(plan));
+                                    owned_updates.insert(msg.id, PlanningStatus::Success(plan, msg.reasoning));
                                 },
                                 Some(PlanningStatusKind::Failed) => {
                                     debug!("Status update: Workflow '{}' failed to been planned{}", msg.id, if let Some(reason) = &msg.result { format!(": {}", reason) } else { String::new() });
@@ -334,36 +406,63 @@ impl InstancePlanner {
         // Done
         Ok(())
     }
-}
 
-#[async_trait::async_trait]
-impl Planner for InstancePlanner {
-    async fn plan(&self, workflow: Workflow) -> Result<Workflow, PlanError> {
-        // Ensure that the to-be-send-on topic exists
-        let brokers: String = self.node_config.node.central().services.brokers.iter().map(|a| a.to_string()).collect::<Vec<String>>().join(",");
-        if let Err(err) = ensure_topics(vec![ &self.node_config.node.central().topics.planner_command ], &brokers).await { return Err(PlanError::KafkaTopicError { brokers, topics: vec![ self.node_config.node.central().topics.planner_command.clone() ], err }); };
+
+
+    /// Plans the given workflow, like `Planner::plan()`, but also returns why the planner chose every location.
+    /// 
+    /// # Arguments
+    /// - `workflow`: The workflow to plan.
+    /// 
+    /// # Returns
+    /// The planned workflow, together with the planner's explanation (one line per task) of why every task was planned where it was.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to send the workflow to the planner, or if the planner failed.
+    pub async fn plan_explained(&self, workflow: Workflow) -> Result<(Workflow, Vec<String>), PlanError> {
+        // Workflows without any tasks have nothing to plan, so run them in-memory without the roundtrip to the planner
+        if !workflow.has_tasks() {
+            debug!("Workflow does not call any tasks; skipping planning");
+            return Ok((workflow, vec![]));
+        }
 
         // Serialize the workflow
         let swork: String = match serde_json::to_string(&workflow) {
             Ok(swork) => swork,
             Err(err)  => { return Err(PlanError::WorkflowSerializeError{ err }); },  
         };
-
-        // Populate a "PlanningCommand" with that (i.e., just populate a future record with the string)
         let correlation_id: String = format!("{}", TaskId::generate());
-        let message: FutureRecord<String, [u8]> = FutureRecord::to(&self.node_config.node.central().topics.planner_command)
-            .key(&correlation_id)
-            .payload(swork.as_bytes());
 
-        // Send the message
-        if let Err((err, _)) = self.producer.send(message, Timeout::After(Duration::from_secs(5))).await {
-            return Err(PlanError::KafkaSendError { correlation_id, topic: self.node_config.node.central().topics.planner_command.clone(), err });
-        }
+        // Send it to the planner using the configured transport
+        match &self.transport {
+            Transport::Kafka(producer) => {
+                // Ensure that the to-be-send-on topic exists
+                let brokers: String = self.node_config.node.central().services.brokers.iter().map(|a| a.to_string()).collect::<Vec<String>>().join(",");
+                if let Err(err) = ensure_topics(vec![ &self.node_config.node.central().topics.planner_command ], &brokers).await { return Err(PlanError::KafkaTopicError { brokers, topics: vec![ self.node_config.node.central().topics.planner_command.clone() ], err }); };
+
+                // Populate a "PlanningCommand" with that (i.e., just populate a future record with the string), carrying the workflow's trace along to the planner
+                let message: FutureRecord<String, [u8]> = FutureRecord::to(&self.node_config.node.central().topics.planner_command)
+                    .key(&correlation_id)
+                    .headers(otel::kafka_headers(&Span::current()))
+                    .payload(swork.as_bytes());
+
+                // Send the message
+                if let Err((err, _)) = producer.send(message, Timeout::After(Duration::from_secs(5))).await {
+                    return Err(PlanError::KafkaSendError { correlation_id, topic: self.node_config.node.central().topics.planner_command.clone(), err });
+                }
 
-        // Now we wait until the message has been planned.
-        let plan: Workflow = wait_planned(&correlation_id, self.waker.clone(), self.updates.clone()).await?;
+                // Now we wait until the message has been planned.
+                wait_planned(&correlation_id, self.waker.clone(), self.updates.clone()).await
+            },
+            Transport::Grpc(address) => plan_grpc(address, correlation_id, swork).await,
+        }
+    }
+}
 
-        // Done
+#[async_trait::async_trait]
+impl Planner for InstancePlanner {
+    async fn plan(&self, workflow: Workflow) -> Result<Workflow, PlanError> {
+        let (plan, _): (Workflow, Vec<String>) = self.plan_explained(workflow).await?;
         Ok(plan)
     }
 }
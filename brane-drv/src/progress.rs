@@ -0,0 +1,87 @@
+//  PROGRESS.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 12:40:53
+//  Last edited:
+//    16 Oct 2026, 12:40:53
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements the hub that broadcasts the structured events of every
+//!   session to the (user) interfaces that subscribe to it.
+// 
+
+use chrono::Utc;
+use dashmap::DashMap;
+use log::warn;
+use tokio::sync::broadcast::{self, Receiver, Sender};
+
+use brane_tsk::grpc::SubscribeReply;
+use brane_tsk::spec::AppId;
+use specifications::status::SessionEvent;
+
+
+/***** LIBRARY *****/
+/// Broadcasts the events of every session to its subscribers.
+///
+/// Events are not buffered for sessions without subscribers, and subscribers that cannot keep up miss the oldest events instead of slowing down the workflow (which is reported to them).
+#[derive(Debug)]
+pub struct ProgressHub {
+    /// The channel per session that has (or had) subscribers.
+    channels : DashMap<AppId, Sender<SubscribeReply>>,
+    /// The number of events we keep per session for subscribers that lag behind.
+    capacity : usize,
+}
+
+impl ProgressHub {
+    /// Constructor for the ProgressHub.
+    ///
+    /// # Arguments
+    /// - `capacity`: The number of events we keep per session for subscribers that lag behind.
+    ///
+    /// # Returns
+    /// A new ProgressHub without any subscribers.
+    #[inline]
+    pub fn new(capacity: usize) -> Self { Self { channels: DashMap::new(), capacity: capacity.max(1) } }
+
+
+
+    /// Sends a new event to the subscribers of the given session, if any.
+    ///
+    /// # Arguments
+    /// - `app_id`: The session in which the event happened.
+    /// - `event`: The SessionEvent to send.
+    pub fn emit(&self, app_id: &AppId, event: SessionEvent) {
+        let tx: Sender<SubscribeReply> = match self.channels.get(app_id) {
+            Some(tx) if tx.receiver_count() > 0 => tx.clone(),
+            _                                   => { return; },
+        };
+        let event: String = match serde_json::to_string(&event) {
+            Ok(event) => event,
+            Err(err)  => { warn!("Failed to serialize event of session '{}': {}", app_id, err); return; },
+        };
+
+        // Subscribers may have left in the meantime, which is fine
+        let _ = tx.send(SubscribeReply { timestamp: Utc::now().to_rfc3339(), event, missed: 0 });
+    }
+
+    /// Subscribes to the events of the given session.
+    ///
+    /// # Arguments
+    /// - `app_id`: The session to subscribe to.
+    ///
+    /// # Returns
+    /// A Receiver on which the events of the session arrive from now on. It is closed when the session is forgotten.
+    pub fn subscribe(&self, app_id: &AppId) -> Receiver<SubscribeReply> {
+        self.channels.entry(app_id.clone()).or_insert_with(|| broadcast::channel(self.capacity).0).subscribe()
+    }
+
+    /// Forgets the given session, e.g., because it was killed. This ends the streams of its subscribers.
+    ///
+    /// # Arguments
+    /// - `app_id`: The session to forget.
+    #[inline]
+    pub fn forget(&self, app_id: &AppId) { self.channels.remove(app_id); }
+}
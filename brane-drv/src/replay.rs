@@ -0,0 +1,613 @@
+//  REPLAY.rs
+//    by agent
+//
+//  Created:
+//    16 Oct 2026, 08:57:30
+//  Last edited:
+//    16 Oct 2026, 10:58:45
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements the buffer that numbers the replies of a running
+//!   workflow and keeps the most recent ones around, so that a client
+//!   that lost its connection can reconnect and resume its stream
+//!   without losing output. The replies of detached workflows may also
+//!   be persisted, so that they survive a restart of the driver.
+//
+
+use std::collections::VecDeque;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use log::{debug, warn};
+use prost::Message;
+use tokio::fs as tfs;
+use tokio::io::AsyncWriteExt as _;
+use tokio::sync::Notify;
+use tokio::sync::mpsc::{self, Receiver, Sender};
+use tokio::time::timeout;
+use tonic::{Code, Status};
+
+use brane_tsk::grpc::ExecuteReply;
+
+pub use crate::errors::ReplayError as Error;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Creates a reply with the given stdout.
+    fn reply(stdout: &str) -> ExecuteReply {
+        ExecuteReply {
+            stdout : Some(stdout.into()),
+            branch : vec![],
+            stderr : None,
+            debug  : None,
+            value  : None,
+
+            progress : None,
+            usage    : None,
+            output   : None,
+
+            close : false,
+
+            seq       : 0,
+            heartbeat : false,
+        }
+    }
+
+    /// Runs a workflow that sends the given replies through a buffer with the given window, and waits until it is done.
+    async fn replay(window: usize, stdouts: &[&str]) -> (Arc<ReplayBuffer>, Receiver<Result<ExecuteReply, Status>>) {
+        let (client, client_rx): (Sender<Result<ExecuteReply, Status>>, Receiver<Result<ExecuteReply, Status>>) = mpsc::channel(CLIENT_BUFFER);
+        let buffer: Arc<ReplayBuffer> = Arc::new(ReplayBuffer::new(window, Duration::from_secs(60), client));
+        let tx: Sender<Result<ExecuteReply, Status>> = buffer.relay();
+        for stdout in stdouts { tx.send(Ok(reply(stdout))).await.unwrap(); }
+        drop(tx);
+        while buffer.is_running() { tokio::time::sleep(Duration::from_millis(1)).await; }
+        (buffer, client_rx)
+    }
+
+    /// Collects the (successful) replies on the given stream until it ends.
+    async fn collect(mut rx: Receiver<Result<ExecuteReply, Status>>) -> Vec<(u64, String)> {
+        let mut res: Vec<(u64, String)> = vec![];
+        while let Some(reply) = rx.recv().await {
+            let reply: ExecuteReply = reply.unwrap();
+            res.push((reply.seq, reply.stdout.unwrap()));
+        }
+        res
+    }
+
+
+    #[tokio::test]
+    async fn test_numbering() {
+        let (_buffer, client_rx) = replay(8, &[ "a", "b", "c" ]).await;
+        assert_eq!(collect(client_rx).await, vec![ (1, "a".into()), (2, "b".into()), (3, "c".into()) ]);
+    }
+
+    #[tokio::test]
+    async fn test_resume_point() {
+        let (buffer, _client_rx) = replay(8, &[ "a", "b", "c" ]).await;
+
+        // Only what the client missed is replayed, and the stream ends since the workflow is done
+        assert_eq!(collect(buffer.attach(1).unwrap()).await, vec![ (2, "b".into()), (3, "c".into()) ]);
+        assert_eq!(collect(buffer.attach(0).unwrap()).await.len(), 3);
+        assert!(collect(buffer.attach(3).unwrap()).await.is_empty());
+        // Even for clients that claim to be ahead of us
+        assert!(collect(buffer.attach(u64::MAX).unwrap()).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_eviction() {
+        let (buffer, _client_rx) = replay(2, &[ "a", "b", "c", "d" ]).await;
+
+        // The oldest replies have left the window
+        match buffer.attach(1) {
+            Err(status) => { assert_eq!(status.code(), Code::DataLoss); },
+            Ok(_)       => { panic!("Replayed replies that have left the window"); },
+        }
+        assert_eq!(collect(buffer.attach(2).unwrap()).await, vec![ (3, "c".into()), (4, "d".into()) ]);
+    }
+
+    #[tokio::test]
+    async fn test_persist() {
+        let dir: tempfile::TempDir = tempfile::tempdir().unwrap();
+        let path: PathBuf = dir.path().join("session.replay");
+
+        // Run a workflow with a log, so that its replies survive the buffer
+        let (client, _client_rx): (Sender<Result<ExecuteReply, Status>>, Receiver<Result<ExecuteReply, Status>>) = mpsc::channel(CLIENT_BUFFER);
+        let buffer: Arc<ReplayBuffer> = Arc::new(ReplayBuffer::new(2, Duration::from_secs(60), client).with_log(&path, Some("alice".into())));
+        let tx: Sender<Result<ExecuteReply, Status>> = buffer.relay();
+        for stdout in [ "a", "b", "c", "d", "e", "f" ] { tx.send(Ok(reply(stdout))).await.unwrap(); }
+        drop(tx);
+        buffer.finished().await;
+        drop(buffer);
+
+        // The restored buffer only keeps its window, and remembers who owns it
+        let (restored, owner): (ReplayBuffer, Option<String>) = ReplayBuffer::restore(&path, 2, Duration::from_secs(60)).await.unwrap();
+        assert_eq!(owner.as_deref(), Some("alice"));
+        assert!(!restored.is_running());
+        assert_eq!(collect(restored.attach(4).unwrap()).await, vec![ (5, "e".into()), (6, "f".into()) ]);
+        assert_eq!(restored.attach(3).unwrap_err().code(), Code::DataLoss);
+
+        // The log was rewritten along the way, so it does not grow beyond a few windows either
+        let raw: Vec<u8> = std::fs::read(&path).unwrap();
+        let mut buf: &[u8] = &raw;
+        let mut records: usize = 0;
+        while !buf.is_empty() { ReplayRecord::decode_length_delimited(&mut buf).unwrap(); records += 1; }
+        assert!(records <= 1 + 2 * 2 + 1, "Log has {} records", records);
+    }
+
+    #[tokio::test]
+    async fn test_restore_interrupted() {
+        let dir: tempfile::TempDir = tempfile::tempdir().unwrap();
+        let path: PathBuf = dir.path().join("session.replay");
+
+        // A log that was never closed (and was cut off halfway a record) belongs to a run that the driver did not see through
+        let mut raw: Vec<u8> = vec![];
+        ReplayRecord{ owner: None, ..Default::default() }.encode_length_delimited(&mut raw).unwrap();
+        ReplayRecord{ reply: Some(ExecuteReply{ seq: 1, ..reply("a") }), ..Default::default() }.encode_length_delimited(&mut raw).unwrap();
+        raw.extend_from_slice(&[ 42, 1 ]);
+        std::fs::write(&path, raw).unwrap();
+
+        let (restored, owner): (ReplayBuffer, Option<String>) = ReplayBuffer::restore(&path, 8, Duration::from_secs(60)).await.unwrap();
+        assert!(owner.is_none());
+        let mut rx: Receiver<Result<ExecuteReply, Status>> = restored.attach(0).unwrap();
+        assert_eq!(rx.recv().await.unwrap().unwrap().stdout.as_deref(), Some("a"));
+        assert_eq!(rx.recv().await.unwrap().unwrap_err().code(), Code::Unavailable);
+        assert!(rx.recv().await.is_none());
+
+        // Missing logs are reported as such
+        assert!(matches!(ReplayBuffer::restore(dir.path().join("other.replay"), 8, Duration::from_secs(60)).await, Err(Error::NotFound{ .. })));
+    }
+
+    #[tokio::test]
+    async fn test_kill() {
+        let (buffer, _client_rx) = replay(8, &[ "a" ]).await;
+        buffer.kill("Killed by test");
+
+        // Reconnecting clients get the replies they missed, and then learn how it ended
+        let mut rx: Receiver<Result<ExecuteReply, Status>> = buffer.attach(0).unwrap();
+        assert_eq!(rx.recv().await.unwrap().unwrap().stdout.as_deref(), Some("a"));
+        assert_eq!(rx.recv().await.unwrap().unwrap_err().code(), Code::Aborted);
+        assert!(rx.recv().await.is_none());
+    }
+}
+
+
+/***** CONSTANTS *****/
+/// The number of replies that may be in flight to a client before sending blocks.
+const CLIENT_BUFFER: usize = 10;
+
+
+
+
+
+/***** AUXILLARY *****/
+/// A single record in the log of a persisted ReplayBuffer, which is a sequence of length-delimited records.
+#[derive(Clone, PartialEq, Message)]
+struct ReplayRecord {
+    /// The client that owns the session, if any. Only given in the first record of a log.
+    #[prost(string, optional, tag = "1")]
+    owner   : Option<String>,
+    /// A (numbered) reply of the workflow.
+    #[prost(message, optional, tag = "2")]
+    reply   : Option<ExecuteReply>,
+    /// The code of the status with which the stream failed.
+    #[prost(int32, optional, tag = "3")]
+    code    : Option<i32>,
+    /// The message of the status with which the stream failed.
+    #[prost(string, optional, tag = "4")]
+    message : Option<String>,
+    /// Whether the workflow is done sending replies.
+    #[prost(bool, tag = "5")]
+    closed  : bool,
+}
+
+/// The open log of a persisted ReplayBuffer.
+#[derive(Debug)]
+struct ReplayLog {
+    /// The path of the log.
+    path    : PathBuf,
+    /// The log itself, opened for appending.
+    file    : tfs::File,
+    /// The number of records in the log.
+    records : usize,
+}
+
+impl ReplayLog {
+    /// Writes a new log with the given replies, replacing any previous one at the same path.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the log.
+    /// - `owner`: The client that owns the session, if any.
+    /// - `replies`: The replies to start the log with.
+    ///
+    /// # Returns
+    /// The new ReplayLog, opened for appending more records.
+    ///
+    /// # Errors
+    /// This function errors if we failed to write the log or move it in place.
+    async fn create(path: &Path, owner: Option<&str>, replies: &[ExecuteReply]) -> Result<Self, Error> {
+        let mut raw: Vec<u8> = ReplayRecord{ owner: owner.map(String::from), ..Default::default() }.encode_length_delimited_to_vec();
+        for reply in replies {
+            raw.extend(ReplayRecord{ reply: Some(reply.clone()), ..Default::default() }.encode_length_delimited_to_vec());
+        }
+
+        // Write it next to the previous one first, so that a crash while writing never leaves us without a valid log
+        let temp: PathBuf = path.with_extension("replay.tmp");
+        if let Err(err) = tfs::write(&temp, raw).await { return Err(Error::FileWriteError{ path: temp, err }); }
+        if let Err(err) = tfs::rename(&temp, path).await { return Err(Error::FileRenameError{ from: temp, to: path.into(), err }); }
+        let file: tfs::File = match tfs::OpenOptions::new().append(true).open(path).await {
+            Ok(file) => file,
+            Err(err) => { return Err(Error::FileWriteError{ path: path.into(), err }); },
+        };
+        Ok(Self{ path: path.into(), file, records: 1 + replies.len() })
+    }
+
+    /// Appends a record to the log.
+    ///
+    /// # Arguments
+    /// - `record`: The ReplayRecord to append.
+    ///
+    /// # Errors
+    /// This function errors if we failed to write to the log.
+    async fn append(&mut self, record: &ReplayRecord) -> Result<(), Error> {
+        let raw: Vec<u8> = record.encode_length_delimited_to_vec();
+        if let Err(err) = self.file.write_all(&raw).await { return Err(Error::FileWriteError{ path: self.path.clone(), err }); }
+        if let Err(err) = self.file.flush().await { return Err(Error::FileWriteError{ path: self.path.clone(), err }); }
+        self.records += 1;
+        Ok(())
+    }
+}
+
+/// The mutable part of the ReplayBuffer.
+#[derive(Debug)]
+struct ReplayState {
+    /// The most recent replies, ordered by sequence number.
+    replies : VecDeque<ExecuteReply>,
+    /// The sequence number of the next reply.
+    next    : u64,
+    /// The channel to the client that currently follows the stream, if any.
+    client  : Option<Sender<Result<ExecuteReply, Status>>>,
+    /// The error with which the stream failed, if any. Kept as a (code, message) pair, since Status cannot be cloned.
+    failure : Option<(Code, String)>,
+    /// Whether the workflow is done sending replies.
+    closed  : bool,
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Relays the replies of a running workflow to its client, numbering them and keeping the most recent ones for clients that reconnect.
+///
+/// While the workflow is quiet, the buffer sends heartbeats so that clients can tell a slow workflow apart from a dead connection.
+#[derive(Debug)]
+pub struct ReplayBuffer {
+    /// The number of replies that we keep for replaying.
+    window    : usize,
+    /// How long the stream may be quiet before we send a heartbeat.
+    heartbeat : Duration,
+    /// The replies and the client following them.
+    state     : Mutex<ReplayState>,
+    /// Wakes up those waiting for the workflow to be done (see `ReplayBuffer::finished()`).
+    done      : Notify,
+
+    /// The path of the log to which the replies are persisted, if any.
+    log   : Option<PathBuf>,
+    /// The client that owns the session, which is persisted with the replies so that it can still be checked after a restart.
+    owner : Option<String>,
+}
+
+impl ReplayBuffer {
+    /// Constructor for the ReplayBuffer.
+    ///
+    /// # Arguments
+    /// - `window`: The number of replies that we keep for replaying.
+    /// - `heartbeat`: How long the stream may be quiet before we send a heartbeat.
+    /// - `client`: The channel to the client that started the workflow.
+    ///
+    /// # Returns
+    /// A new ReplayBuffer that has not seen any replies yet.
+    #[inline]
+    pub fn new(window: usize, heartbeat: Duration, client: Sender<Result<ExecuteReply, Status>>) -> Self {
+        Self {
+            window,
+            heartbeat,
+            state : Mutex::new(ReplayState {
+                replies : VecDeque::new(),
+                next    : 1,
+                client  : Some(client),
+                failure : None,
+                closed  : false,
+            }),
+            done      : Notify::new(),
+
+            log   : None,
+            owner : None,
+        }
+    }
+
+    /// Persists the replies to the given log, e.g., for detached runs. It is rewritten from the replay window every so often, so it stays bounded as well.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the log. Any existing log at that path is replaced once the buffer starts relaying.
+    /// - `owner`: The client that owns the session, if any, which is stored in the log.
+    ///
+    /// # Returns
+    /// The same ReplayBuffer, but persisting its replies.
+    #[inline]
+    pub fn with_log(mut self, path: impl Into<PathBuf>, owner: Option<String>) -> Self {
+        self.log   = Some(path.into());
+        self.owner = owner;
+        self
+    }
+
+    /// Restores the (closed) ReplayBuffer of a workflow from its log, e.g., after the driver was restarted.
+    ///
+    /// If the log does not say how the workflow ended, the driver stopped before it did, and the restored buffer reports the stream as unavailable after its replies.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the log.
+    /// - `window`: The number of replies that we keep for replaying.
+    /// - `heartbeat`: How long the stream may be quiet before we send a heartbeat.
+    ///
+    /// # Returns
+    /// A new ReplayBuffer with the most recent replies in the log, and the client that owns the session (if any).
+    ///
+    /// # Errors
+    /// This function errors if the log does not exist or could not be read. A damaged end of the log (e.g., because the driver stopped halfway writing it) is ignored.
+    pub async fn restore(path: impl Into<PathBuf>, window: usize, heartbeat: Duration) -> Result<(Self, Option<String>), Error> {
+        let path: PathBuf = path.into();
+        let raw: Vec<u8> = match tfs::read(&path).await {
+            Ok(raw)                                       => raw,
+            Err(err) if err.kind() == ErrorKind::NotFound => { return Err(Error::NotFound{ path }); },
+            Err(err)                                      => { return Err(Error::FileReadError{ path, err }); },
+        };
+
+        // Replay the records
+        let mut state: ReplayState = ReplayState{ replies: VecDeque::new(), next: 1, client: None, failure: None, closed: false };
+        let mut owner: Option<String> = None;
+        let mut buf: &[u8] = &raw;
+        while !buf.is_empty() {
+            let record: ReplayRecord = match ReplayRecord::decode_length_delimited(&mut buf) {
+                Ok(record) => record,
+                Err(err)   => { warn!("Ignoring damaged end of reply log '{}': {}", path.display(), err); break; },
+            };
+            if record.owner.is_some() { owner = record.owner; }
+            if let Some(reply) = record.reply {
+                state.next = reply.seq.saturating_add(1);
+                if state.replies.len() >= window { state.replies.pop_front(); }
+                state.replies.push_back(reply);
+            }
+            if let Some(code) = record.code { state.failure = Some((Code::from_i32(code), record.message.unwrap_or_default())); }
+            state.closed |= record.closed;
+        }
+        if !state.closed && state.failure.is_none() { state.failure = Some((Code::Unavailable, "The driver stopped before the workflow finished".into())); }
+        state.closed = true;
+
+        Ok((Self {
+            window,
+            heartbeat,
+            state : Mutex::new(state),
+            done  : Notify::new(),
+
+            log   : Some(path),
+            owner : owner.clone(),
+        }, owner))
+    }
+
+
+
+    /// Starts relaying the replies of a workflow in the background.
+    ///
+    /// # Returns
+    /// The channel on which the workflow should send its replies instead of to the client directly.
+    pub fn relay(self: &Arc<Self>) -> Sender<Result<ExecuteReply, Status>> {
+        let (tx, rx): (Sender<Result<ExecuteReply, Status>>, Receiver<Result<ExecuteReply, Status>>) = mpsc::channel(CLIENT_BUFFER);
+        let this: Arc<Self> = self.clone();
+        tokio::spawn(async move { this.run(rx).await });
+        tx
+    }
+
+    /// Relays the replies on the given channel until the workflow is done with it.
+    ///
+    /// # Arguments
+    /// - `rx`: The channel on which the workflow sends its replies.
+    async fn run(&self, mut rx: Receiver<Result<ExecuteReply, Status>>) {
+        let mut log: Option<ReplayLog> = match &self.log {
+            Some(path) => match ReplayLog::create(path, self.owner.as_deref(), &[]).await {
+                Ok(log)  => Some(log),
+                Err(err) => { warn!("{} (not persisting replies)", err); None },
+            },
+            None => None,
+        };
+
+        loop {
+            // Wait for the next reply, sending a heartbeat if it takes a while
+            let reply: Result<ExecuteReply, Status> = match timeout(self.heartbeat, rx.recv()).await {
+                Ok(Some(reply)) => reply,
+                Ok(None)        => break,
+                Err(_)          => {
+                    let (last, client): (u64, Option<Sender<Result<ExecuteReply, Status>>>) = {
+                        let state = self.state.lock().unwrap();
+                        (state.next - 1, state.client.clone())
+                    };
+                    if let Some(client) = client {
+                        self.send(client, Ok(ExecuteReply {
+                            stdout : None,
+                            branch : vec![],
+                            stderr : None,
+                            debug  : None,
+                            value  : None,
+
+                            progress : None,
+                            usage    : None,
+                            output   : None,
+
+                            close : false,
+
+                            seq       : last,
+                            heartbeat : true,
+                        })).await;
+                    }
+                    continue;
+                },
+            };
+
+            // Number and remember the reply before passing it on
+            let (reply, client): (Result<ExecuteReply, Status>, Option<Sender<Result<ExecuteReply, Status>>>) = {
+                let mut state = self.state.lock().unwrap();
+                let reply: Result<ExecuteReply, Status> = match reply {
+                    Ok(mut reply) => {
+                        reply.seq = state.next;
+                        state.next += 1;
+                        if state.replies.len() >= self.window { state.replies.pop_front(); }
+                        state.replies.push_back(reply.clone());
+                        Ok(reply)
+                    },
+                    Err(status) => {
+                        state.failure = Some((status.code(), status.message().into()));
+                        Err(status)
+                    },
+                };
+                (reply, state.client.clone())
+            };
+            let record: ReplayRecord = match &reply {
+                Ok(reply)   => ReplayRecord{ reply: Some(reply.clone()), ..Default::default() },
+                Err(status) => ReplayRecord{ code: Some(status.code() as i32), message: Some(status.message().into()), ..Default::default() },
+            };
+            self.persist(&mut log, record).await;
+            if let Some(client) = client { self.send(client, reply).await; }
+        }
+
+        // The workflow is done; hang up on the client, so its stream ends once it has received everything
+        self.persist(&mut log, ReplayRecord{ closed: true, ..Default::default() }).await;
+        {
+            let mut state = self.state.lock().unwrap();
+            state.closed = true;
+            state.client = None;
+        }
+        self.done.notify_waiters();
+    }
+
+    /// Persists a record to the log (if any), rewriting the log from the replay window once it has grown well beyond it.
+    ///
+    /// If we fail to do so, the replies are no longer persisted but still relayed.
+    ///
+    /// # Arguments
+    /// - `log`: The log to persist the record to, if any.
+    /// - `record`: The ReplayRecord to persist. If it is a reply, it must already be in the replay window.
+    async fn persist(&self, log: &mut Option<ReplayLog>, record: ReplayRecord) {
+        let current: &mut ReplayLog = match log {
+            Some(current) => current,
+            None          => { return; },
+        };
+        let res: Result<(), Error> = if record.reply.is_some() && current.records > self.window.saturating_mul(2) {
+            let replies: Vec<ExecuteReply> = self.state.lock().unwrap().replies.iter().cloned().collect();
+            ReplayLog::create(&current.path, self.owner.as_deref(), &replies).await.map(|new| { *current = new; })
+        } else {
+            current.append(&record).await
+        };
+        if let Err(err) = res {
+            warn!("{} (no longer persisting replies)", err);
+            *log = None;
+        }
+    }
+
+    /// Sends a reply to the given client, detaching it if it is gone or does not keep up.
+    ///
+    /// # Arguments
+    /// - `client`: The channel to the client.
+    /// - `reply`: The reply to send.
+    async fn send(&self, client: Sender<Result<ExecuteReply, Status>>, reply: Result<ExecuteReply, Status>) {
+        let err: String = match timeout(self.heartbeat, client.send(reply)).await {
+            Ok(Ok(_))    => { return; },
+            Ok(Err(err)) => err.to_string(),
+            Err(_)       => "client did not keep up".into(),
+        };
+
+        // Detach the client, unless it has been replaced by a reconnected one in the meantime
+        debug!("Detaching client from reply stream: {}", err);
+        let mut state = self.state.lock().unwrap();
+        if state.client.as_ref().map(|c| c.same_channel(&client)).unwrap_or(false) { state.client = None; }
+    }
+
+
+
+    /// Returns whether the workflow is still sending replies.
+    #[inline]
+    pub fn is_running(&self) -> bool { !self.state.lock().unwrap().closed }
+
+    /// Returns whether a client currently follows the stream.
+    #[inline]
+    pub fn is_attached(&self) -> bool { self.state.lock().unwrap().client.is_some() }
+
+    /// Fails the stream because its workflow was killed, telling the client that currently follows it (if any) and any client that reconnects later.
+    ///
+    /// # Arguments
+    /// - `message`: The message that explains why the workflow was killed.
+    pub fn kill(&self, message: impl Into<String>) {
+        let message: String = message.into();
+        let mut state = self.state.lock().unwrap();
+        if let Some(client) = state.client.take() {
+            if let Err(err) = client.try_send(Err(Status::aborted(message.clone()))) { warn!("Failed to notify client of killed workflow: {}", err); }
+        }
+        state.failure = Some((Code::Aborted, message));
+        state.closed  = true;
+        self.done.notify_waiters();
+    }
+
+    /// Waits until the workflow is done sending replies (or was killed).
+    pub async fn finished(&self) {
+        // Create the future before checking, so we cannot miss the notification in between
+        let notified = self.done.notified();
+        if !self.is_running() { return; }
+        notified.await;
+    }
+
+
+
+    /// Attaches a (reconnected) client to the stream, first replaying the replies it missed.
+    ///
+    /// If the workflow is already done, the client's stream ends after the replayed replies.
+    ///
+    /// # Arguments
+    /// - `after`: The sequence number of the last reply that the client received.
+    ///
+    /// # Returns
+    /// The stream of replies for the client.
+    ///
+    /// # Errors
+    /// This function errors if some of the replies that the client missed have already left the replay window.
+    pub fn attach(&self, after: u64) -> Result<Receiver<Result<ExecuteReply, Status>>, Status> {
+        let mut state = self.state.lock().unwrap();
+
+        // Find the replies that the client missed
+        let first: u64 = state.replies.front().map(|r| r.seq).unwrap_or(state.next);
+        if after.saturating_add(1) < first { return Err(Status::data_loss(format!("Replies {} to {} are no longer available", after.saturating_add(1), first - 1))); }
+        let missed: Vec<ExecuteReply> = state.replies.iter().filter(|r| r.seq > after).cloned().collect();
+        debug!("Replaying {} replies after {} to reconnected client", missed.len(), after);
+
+        // Queue them on a fresh channel that is large enough to hold them all, so we can do so without awaiting
+        let (tx, rx): (Sender<Result<ExecuteReply, Status>>, Receiver<Result<ExecuteReply, Status>>) = mpsc::channel(missed.len() + CLIENT_BUFFER);
+        for reply in missed {
+            if let Err(err) = tx.try_send(Ok(reply)) { warn!("Failed to replay reply to reconnected client: {}", err); }
+        }
+
+        // Either attach the client for the upcoming replies or tell it how the workflow ended
+        if let Some((code, message)) = &state.failure {
+            if let Err(err) = tx.try_send(Err(Status::new(*code, message.clone()))) { warn!("Failed to replay failure to reconnected client: {}", err); }
+        } else if !state.closed {
+            state.client = Some(tx);
+        }
+        Ok(rx)
+    }
+}
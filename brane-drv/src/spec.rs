@@ -12,16 +12,31 @@
 //!   Defines (public) interfaces and structs for the `brane-drv` crate.
 // 
 
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc::Sender;
 use tonic::Status;
+use tracing::Span;
 
-use brane_exe::spec::CustomGlobalState;
+use brane_ast::locations::Location;
+use brane_ast::schedule::TaskTiming;
+use brane_exe::spec::{Checkpoint, CustomGlobalState};
 use brane_prx::client::ProxyClient;
 use brane_tsk::spec::AppId;
 use brane_tsk::grpc::ExecuteReply;
+use specifications::profiling::TransferTiming;
+use specifications::status::SessionEvent;
+
+use crate::errors::CheckpointError;
+use crate::approvals::ApprovalRegistry;
+use crate::events::EventRegistry;
+use crate::notify::Notifier;
+use crate::progress::ProgressHub;
 
 
 /***** LIBRARY *****/
@@ -32,18 +47,89 @@ pub struct GlobalState {
     pub node_config_path : PathBuf,
     /// The application identifier for this session.
     pub app_id           : AppId,
+    /// The client that created this session (i.e., the `CN` in its certificate), if it authenticated itself.
+    pub owner            : Option<String>,
     /// The (shared) proxy client we use to communicate, well, through proxies.
     pub proxy            : Arc<ProxyClient>,
+    /// The (shared) notifier we use to send notifications to users.
+    pub notifier         : Arc<Notifier>,
+    /// The (shared) registry that routes external events to the threads waiting for them.
+    pub events           : Arc<EventRegistry>,
+    /// The (shared) registry of approvals that are waiting for a decision.
+    pub approvals        : Arc<ApprovalRegistry>,
+    /// The (shared) hub that broadcasts the events of this session to its subscribers.
+    pub progress         : Arc<ProgressHub>,
 
     /// The workflow for this session, which will be updated when a new one is received.
-    pub workflow : Option<String>,
+    pub workflow   : Option<String>,
+    /// The file to which this session's running workflow is checkpointed, if checkpointing is enabled.
+    pub checkpoint : Option<PathBuf>,
+    /// How long the tasks of this session's (last) workflow took, in the order they completed.
+    pub timings    : Vec<TaskTiming>,
+    /// How long the data transfers of this session's (last) workflow took, in the order they completed.
+    pub transfers  : Vec<TransferTiming>,
+    /// The revision of every dataset (per location) that this session's running workflow may replace by committing a result, which is the one it saw when it started (or the one it committed itself since).
+    pub revisions  : HashMap<(String, Location), u64>,
+    /// The tracing span of this session's running workflow, which is the parent of the spans of its tasks (that may run on other threads).
+    pub span       : Span,
 
     /// The callback for the client to receive prints and other status updates on (such as the final result).
     /// 
     /// Note that this value is updated for every new connection the client makes.
     pub tx : Option<Arc<Sender<Result<ExecuteReply, Status>>>>,
 }
+impl GlobalState {
+    /// Sends the given event to the subscribers of this session, if any.
+    /// 
+    /// # Arguments
+    /// - `event`: The SessionEvent to send.
+    #[inline]
+    pub fn emit(&self, event: SessionEvent) { self.progress.emit(&self.app_id, event) }
+}
 impl CustomGlobalState for GlobalState {}
 
 /// The local state for the RemoteVm is unused.
 pub type LocalState = ();
+
+
+
+/// Defines what we persist of a session's running workflow so that it can be resumed after a restart.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SessionCheckpoint {
+    /// The (planned, serialized) workflow that was running.
+    pub workflow   : String,
+    /// The Checkpoint of the VM that was running it.
+    pub checkpoint : Checkpoint,
+    /// The client that created the session, if it authenticated itself. Only it (or an administrator) may resume it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner      : Option<String>,
+}
+
+impl SessionCheckpoint {
+    /// Loads a SessionCheckpoint from the given file.
+    /// 
+    /// # Arguments
+    /// - `path`: The path of the checkpoint file to load.
+    /// 
+    /// # Returns
+    /// A new SessionCheckpoint with the contents of the file.
+    /// 
+    /// # Errors
+    /// This function errors if the file does not exist, could not be read or is not a valid checkpoint.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, CheckpointError> {
+        let path: &Path = path.as_ref();
+
+        // Read the file
+        let raw: String = match fs::read_to_string(path) {
+            Ok(raw)                                       => raw,
+            Err(err) if err.kind() == ErrorKind::NotFound => { return Err(CheckpointError::NotFound{ path: path.into() }); },
+            Err(err)                                      => { return Err(CheckpointError::FileReadError{ path: path.into(), err }); },
+        };
+
+        // Parse it
+        match serde_json::from_str(&raw) {
+            Ok(checkpoint) => Ok(checkpoint),
+            Err(err)       => Err(CheckpointError::FileParseError{ path: path.into(), err }),
+        }
+    }
+}
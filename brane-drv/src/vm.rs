@@ -15,33 +15,93 @@
 //!   complicating the `stdout()` function.
 // 
 
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
 
+use chrono::Utc;
 use enum_debug::EnumDebug as _;
 use log::{debug, info, warn};
+use tokio::fs as tfs;
 use tokio::sync::mpsc::Sender;
 use serde_json_any_key::MapIterToJson;
-use tonic::{Response, Status, Streaming};
+use tonic::{Request, Response, Status, Streaming};
 use tonic::transport::Channel;
+use tracing::{info_span, Span};
 
 use brane_ast::Workflow;
 use brane_ast::locations::Location;
+use brane_ast::schedule::TaskTiming;
 use brane_ast::ast::DataName;
 use brane_cfg::spec::Address;
 use brane_cfg::infra::InfraFile;
-use brane_cfg::node::NodeConfig;
+use brane_cfg::node::{NodeConfig, NotificationChannel};
 use brane_exe::{Error as VmError, FullValue, RunState, Vm};
-use brane_exe::spec::{TaskInfo, VmPlugin};
+use brane_exe::cancel::CancelToken;
+use brane_exe::plugins::{MockPlugin, PluginEntry, PluginRegistry, RecorderPlugin};
+use brane_exe::spec::{ApprovalDecision, Checkpoint, FrameSummary, TaskInfo, VmPlugin};
 use brane_prx::client::ProxyClient;
-use brane_tsk::errors::{CommitError, ExecuteError, PreprocessError, StdoutError};
+use brane_prx::spec::NewPathRequestTlsOptions;
+use brane_shr::telemetry;
+use brane_tsk::api::{get_package_index, ApiClient};
+use brane_tsk::errors::{CommitError, EventError, ExecuteError, PreprocessError, StdoutError};
 use brane_tsk::spec::{AppId, JobStatus, Planner};
+use brane_tsk::tools::{inject_trace, is_conflict};
 use brane_tsk::grpc::{self, CommitReply, CommitRequest, DataKind, ExecuteReply, PreprocessKind as RawPreprocessKind, PreprocessReply, PreprocessRequest, TaskReply, TaskRequest, TaskStatus};
-use specifications::data::{AccessKind, PreprocessKind};
+use specifications::container::Image;
+use specifications::data::{AccessKind, DataIndex, PreprocessKind};
+use specifications::package::PackageIndex;
+use specifications::profiling::TransferTiming;
+use specifications::status::{ResourceUsage, SessionEvent, TaskOutput, TaskProgress};
 
 pub use crate::errors::RemoteVmError as Error;
-use crate::spec::{GlobalState, LocalState};
+use crate::approvals::ApprovalRegistry;
+use crate::errors::{CheckpointError, IndexError, NotifyError};
+use crate::events::EventRegistry;
+use crate::notify::Notifier;
+use crate::spec::{GlobalState, LocalState, SessionCheckpoint};
 use crate::planner::InstancePlanner;
+use crate::progress::ProgressHub;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use specifications::data::{AssetInfo, DataInfo};
+
+    use super::*;
+
+
+    /// Creates a DataInfo of a dataset at the given location and revision, as `brane-api` reports it.
+    fn dataset(name: &str, version: &str, loc: &str, revision: u64) -> DataInfo {
+        let info: AssetInfo = AssetInfo::from_reader(format!("name: {}\nversion: {}\nrevision: {}\naccess:\n  kind: file\n  path: /data/{}\n", name, version, revision, name).as_bytes()).unwrap();
+        info.into_data_info(loc)
+    }
+
+    /// Tests collecting the revisions that a workflow may replace from the data index.
+    #[test]
+    fn test_collect_revisions() {
+        let dindex: DataIndex = DataIndex::from_infos(vec![
+            dataset("a", "1.0.0", "site1", 2),
+            dataset("a", "1.0.0", "site2", 5),
+            // Only one version of a dataset lives at a location, but be safe and take the latest revision anyway
+            dataset("b", "1.0.0", "site1", 1),
+            dataset("b", "2.0.0", "site1", 3),
+            // Datasets that were never committed to are at revision 0, which is not listed
+            dataset("c", "1.0.0", "site1", 0),
+        ]).unwrap();
+
+        let revisions: HashMap<(String, Location), u64> = collect_revisions(&dindex);
+        assert_eq!(revisions, HashMap::from([
+            (("a".into(), "site1".into()), 2),
+            (("a".into(), "site2".into()), 5),
+            (("b".into(), "site1".into()), 3),
+        ]));
+    }
+}
+
+
+
 
 
 /***** HELPER MACROS *****/
@@ -56,7 +116,314 @@ macro_rules! mundane_status_update {
 
 
 
+/***** HELPER FUNCTIONS *****/
+/// Collects the revision of every dataset at every location that advertises it.
+/// 
+/// # Arguments
+/// - `dindex`: The DataIndex to collect the revisions from.
+/// 
+/// # Returns
+/// A map from the name of a dataset and a location to the revision that the dataset is at there. Datasets that do not exist at a location (or are at revision 0 there) are omitted.
+fn collect_revisions(dindex: &DataIndex) -> HashMap<(String, Location), u64> {
+    let mut revisions: HashMap<(String, Location), u64> = HashMap::new();
+    for info in dindex.iter_versions() {
+        for (loc, revision) in &info.revisions {
+            let entry: &mut u64 = revisions.entry((info.name.clone(), loc.clone())).or_default();
+            *entry = (*entry).max(*revision);
+        }
+    }
+    revisions
+}
+
+/// Fetches the data index of the instance to collect the revisions of the datasets in it.
+/// 
+/// # Arguments
+/// - `node_config_path`: The path to the node config file that tells us where the API service lives.
+/// 
+/// # Returns
+/// The revisions of the datasets, as returned by `collect_revisions()`.
+/// 
+/// # Errors
+/// This function errors if we failed to read the node config file or to fetch the data index.
+async fn fetch_revisions(node_config_path: &Path) -> Result<HashMap<(String, Location), u64>, IndexError> {
+    let api: String = match NodeConfig::from_path(node_config_path) {
+        Ok(node_config) => node_config.node.central().services.api.to_string(),
+        Err(err)        => { return Err(IndexError::NodeConfigReadError{ path: node_config_path.into(), err }); },
+    };
+    match ApiClient::new(&api).data_index().await {
+        Ok(dindex) => Ok(collect_revisions(&dindex)),
+        Err(err)   => Err(IndexError::DataIndexError{ address: format!("{}/data/info", api), err }),
+    }
+}
+
+/// Decides how to secure the connection to the job service of a location.
+/// 
+/// # Arguments
+/// - `delegate`: The address of the job service, as given in the `infra.yml` file.
+/// - `location`: The location that hosts the job service.
+/// 
+/// # Returns
+/// TLS options that authenticate us with our client certificate for that location if the address uses the `grpcs` scheme, or `None` to connect in plaintext.
+fn job_tls(delegate: &Address, location: &str) -> Option<NewPathRequestTlsOptions> {
+    if delegate.domain().starts_with("grpcs://") {
+        Some(NewPathRequestTlsOptions{ location: location.into(), use_client_auth: true })
+    } else {
+        None
+    }
+}
+
+/// Relays a progress update of a running task to the client.
+/// 
+/// # Arguments
+/// - `global`: The GlobalState that contains the channel to the client.
+/// - `name`: The name of the task that reported the progress.
+/// - `progress`: The TaskProgress to relay.
+/// 
+/// # Errors
+/// This function errors if we failed to write to the client's channel.
+async fn report_progress(global: &Arc<RwLock<GlobalState>>, name: &str, progress: &TaskProgress) -> Result<(), StdoutError> {
+    // Get the TX (so that the lock does not live over an `.await`)
+    let tx: Arc<Sender<Result<ExecuteReply, Status>>> = {
+        let state: RwLockReadGuard<GlobalState> = global.read().unwrap();
+        state.emit(SessionEvent::TaskProgress{ name: name.into(), progress: progress.clone() });
+        state.tx.as_ref().expect("Missing `tx` in GlobalState; did you forget to update it before this poll?").clone()
+    };
+
+    // Send it as a (task, progress) pair
+    if let Err(err) = tx.send(Ok(ExecuteReply {
+        stdout : None,
+        branch : vec![],
+        stderr : None,
+        debug  : None,
+        value  : None,
+
+        progress : Some(serde_json::to_string(&(name, progress)).unwrap()),
+        usage    : None,
+        output   : None,
+
+        close : false,
+
+        seq       : 0,
+        heartbeat : false,
+    })).await {
+        return Err(StdoutError::TxWriteError{ err });
+    }
+
+    // Done
+    Ok(())
+}
+
+/// Relays a line of output written by a running task to the client.
+/// 
+/// # Arguments
+/// - `global`: The GlobalState that contains the channel to the client.
+/// - `name`: The name of the task that wrote the output.
+/// - `output`: The TaskOutput to relay.
+/// 
+/// # Errors
+/// This function errors if we failed to write to the client's channel.
+async fn report_output(global: &Arc<RwLock<GlobalState>>, name: &str, output: &TaskOutput) -> Result<(), StdoutError> {
+    // Get the TX (so that the lock does not live over an `.await`)
+    let tx: Arc<Sender<Result<ExecuteReply, Status>>> = {
+        let state: RwLockReadGuard<GlobalState> = global.read().unwrap();
+        state.emit(SessionEvent::TaskOutput{ name: name.into(), output: output.clone() });
+        state.tx.as_ref().expect("Missing `tx` in GlobalState; did you forget to update it before this poll?").clone()
+    };
+
+    // Send it as a (task, output) pair
+    if let Err(err) = tx.send(Ok(ExecuteReply {
+        stdout : None,
+        branch : vec![],
+        stderr : None,
+        debug  : None,
+        value  : None,
+
+        progress : None,
+        usage    : None,
+        output   : Some(serde_json::to_string(&(name, output)).unwrap()),
+
+        close : false,
+
+        seq       : 0,
+        heartbeat : false,
+    })).await {
+        return Err(StdoutError::TxWriteError{ err });
+    }
+
+    // Done
+    Ok(())
+}
+
+/// Relays the resources that a completed task consumed to the client.
+/// 
+/// # Arguments
+/// - `global`: The GlobalState that contains the channel to the client.
+/// - `name`: The name of the task that consumed the resources.
+/// - `usage`: The ResourceUsage to relay.
+/// 
+/// # Errors
+/// This function errors if we failed to write to the client's channel.
+async fn report_usage(global: &Arc<RwLock<GlobalState>>, name: &str, usage: &ResourceUsage) -> Result<(), StdoutError> {
+    // Get the TX (so that the lock does not live over an `.await`)
+    let tx: Arc<Sender<Result<ExecuteReply, Status>>> = {
+        let state: RwLockReadGuard<GlobalState> = global.read().unwrap();
+        state.tx.as_ref().expect("Missing `tx` in GlobalState; did you forget to update it before this poll?").clone()
+    };
+
+    // Send it as a (task, usage) pair
+    if let Err(err) = tx.send(Ok(ExecuteReply {
+        stdout : None,
+        branch : vec![],
+        stderr : None,
+        debug  : None,
+        value  : None,
+
+        progress : None,
+        usage    : Some(serde_json::to_string(&(name, usage)).unwrap()),
+        output   : None,
+
+        close : false,
+
+        seq       : 0,
+        heartbeat : false,
+    })).await {
+        return Err(StdoutError::TxWriteError{ err });
+    }
+
+    // Done
+    Ok(())
+}
+
+/// Records a note about the run in its log, i.e., sends it to the client as a debug message.
+/// 
+/// # Arguments
+/// - `global`: The GlobalState that contains the channel to the client.
+/// - `note`: The note to record.
+/// 
+/// # Errors
+/// This function errors if we failed to write to the client's channel.
+async fn report_note(global: &Arc<RwLock<GlobalState>>, note: String) -> Result<(), StdoutError> {
+    // Get the TX (so that the lock does not live over an `.await`)
+    let tx: Arc<Sender<Result<ExecuteReply, Status>>> = {
+        let state: RwLockReadGuard<GlobalState> = global.read().unwrap();
+        state.emit(SessionEvent::Note{ message: note.clone() });
+        state.tx.as_ref().expect("Missing `tx` in GlobalState; did you forget to update it before this poll?").clone()
+    };
+
+    if let Err(err) = tx.send(Ok(ExecuteReply {
+        stdout : None,
+        branch : vec![],
+        stderr : None,
+        debug  : Some(note),
+        value  : None,
+
+        progress : None,
+        usage    : None,
+        output   : None,
+
+        close : false,
+
+        seq       : 0,
+        heartbeat : false,
+    })).await {
+        return Err(StdoutError::TxWriteError{ err });
+    }
+
+    // Done
+    Ok(())
+}
+
+/// Asks the job node of a location to preprocess a piece of data for a task that runs there.
+/// 
+/// # Arguments
+/// - `proxy`: The proxy client through which we connect to the job node.
+/// - `parent`: The span of the workflow, which the job node continues.
+/// - `delegate`: The address of the job node, as given in the `infra.yml` file.
+/// - `loc`: The location that hosts the job node.
+/// - `api`: The address of the API service, which the job node consults to verify newly seen certificates.
+/// - `name`: The data to preprocess.
+/// - `preprocess`: How to preprocess it (i.e., where to transfer it from).
+/// 
+/// # Returns
+/// The AccessKind with which the task may access the preprocessed data.
+/// 
+/// # Errors
+/// This function errors if we failed to reach the job node, or if it failed to preprocess the data.
+async fn request_preprocess(proxy: &ProxyClient, parent: &Span, delegate: &Address, loc: &str, api: &Address, name: &DataName, preprocess: PreprocessKind) -> Result<AccessKind, PreprocessError> {
+    // Prepare the request to send to the delegate node
+    debug!("Sending preprocess request to job node '{}'...", delegate);
+    let message: PreprocessRequest = match preprocess {
+        PreprocessKind::TransferRegistryTar{ location, address } => PreprocessRequest {
+            data_kind : match name {
+                DataName::Data(_)               => DataKind::Data as i32,
+                DataName::IntermediateResult(_) => DataKind::IntermediateResult as i32,
+            },
+            data_name : name.name().into(),
+
+            kind      : RawPreprocessKind::TransferRegistryTar as i32,
+            data      : Some(serde_json::to_string(&(location, address)).unwrap()),
+
+            api       : Some(api.to_string()),
+        },
+    };
+
+    // Create the client
+    let mut client: grpc::JobServiceClient<Channel> = match proxy.connect_to_job(delegate.to_string(), job_tls(delegate, loc)).await {
+        Ok(result) => match result {
+            Ok(client) => client,
+            Err(err)   => { return Err(PreprocessError::GrpcConnectError{ endpoint: delegate.clone(), err }); },
+        },
+        Err(err) => { return Err(PreprocessError::ProxyError{ err: err.to_string() }); },
+    };
+
+    // Send the request to the job node, which continues the workflow's trace
+    let span: Span = info_span!(parent: parent, "preprocess", data = %name.name(), location = %loc);
+    let mut request: Request<PreprocessRequest> = Request::new(message);
+    inject_trace(&span, &mut request);
+    let response: Response<PreprocessReply> = match client.preprocess(request).await {
+        Ok(response) => response,
+        Err(err)     => { return Err(PreprocessError::GrpcRequestError{ what: "PreprocessRequest", endpoint: delegate.clone(), err }); },
+    };
+    let result: PreprocessReply = response.into_inner();
+
+    // Check if it was a success
+    if !result.ok {
+        return Err(PreprocessError::PreprocessError{ endpoint: delegate.clone(), kind: name.variant().to_string(), name: name.name().into(), err: result.access });
+    }
+
+    // If it was, attempt to deserialize the accesskind
+    match serde_json::from_str(&result.access) {
+        Ok(access) => Ok(access),
+        Err(err)   => Err(PreprocessError::AccessKindParseError{ endpoint: delegate.clone(), raw: result.access, err }),
+    }
+}
+
+
+
+
+
 /***** LIBRARY *****/
+/// The name of the plugin with which the InstanceVm runs by default.
+pub const DEFAULT_PLUGIN: &str = "instance";
+
+/// Returns the plugins with which the InstanceVm may run.
+/// 
+/// These are:
+/// - `instance`: The InstancePlugin, which runs workflows on the instance (the default).
+/// - `mock`: A MockPlugin, which pretends to run workflows without running any tasks or transferring any data.
+/// - `record`: The InstancePlugin, but logging every call it handles.
+/// 
+/// # Returns
+/// A PluginRegistry with the plugins by name.
+pub fn plugins() -> PluginRegistry<InstanceVm> {
+    PluginRegistry::new()
+        .register::<InstancePlugin>(DEFAULT_PLUGIN)
+        .register::<MockPlugin<GlobalState, LocalState>>("mock")
+        .register::<RecorderPlugin<InstancePlugin>>("record")
+}
+
+
+
 /// The InstancePlugin provides `brane-exe` functions for task execution.
 pub struct InstancePlugin;
 
@@ -69,14 +436,19 @@ impl VmPlugin for InstancePlugin {
     type ExecuteError    = ExecuteError;
     type StdoutError     = StdoutError;
     type CommitError     = CommitError;
+    type EventError      = EventError;
+    type NotifyError     = NotifyError;
+    type IndexError      = IndexError;
+    type CheckpointError = CheckpointError;
 
 
     async fn preprocess(global: Arc<RwLock<Self::GlobalState>>, _local: Self::LocalState, loc: Location, name: DataName, preprocess: PreprocessKind) -> Result<AccessKind, Self::PreprocessError> {
         info!("Preprocessing {} '{}' on '{}' in a distributed environment...", name.variant(), name.name(), loc);
         debug!("Preprocessing to be done: {:?}", preprocess);
+        let parent: Span = global.read().unwrap().span.clone();
 
-        // Resolve the location to an address (and get the proxy while we have a lock anyway)
-        let (proxy, delegate_address): (Arc<ProxyClient>, Address) = {
+        // Load the infrastructure file (and get the proxy and API address while we have a lock anyway)
+        let (proxy, api_address, infra): (Arc<ProxyClient>, Address, InfraFile) = {
             // Load the node config file to get the path to...
             let state : RwLockReadGuard<GlobalState> = global.read().unwrap();
             let node_config: NodeConfig = match NodeConfig::from_path(&state.node_config_path) {
@@ -89,62 +461,101 @@ impl VmPlugin for InstancePlugin {
                 Ok(infra) => infra,
                 Err(err)  => { return Err(PreprocessError::InfraReadError{ path: node_config.node.central().paths.infra.clone(), err }); },  
             };
-
-            // Resolve to an address
-            match infra.get(&loc) {
-                Some(info) => (state.proxy.clone(), info.delegate.clone()),
-                None       => { return Err(PreprocessError::UnknownLocationError{ loc }); },
-            }
+            (state.proxy.clone(), node_config.node.central().services.api.clone(), infra)
         };
 
-        // Prepare the request to send to the delegate node
-        debug!("Sending preprocess request to job node '{}'...", delegate_address);
-        let message: PreprocessRequest = match preprocess {
-            PreprocessKind::TransferRegistryTar{ location, address } => PreprocessRequest {
-                data_kind : match name {
-                    DataName::Data(_)               => DataKind::Data as i32,
-                    DataName::IntermediateResult(_) => DataKind::IntermediateResult as i32,
-                },
-                data_name : name.name().into(),
+        // Resolve the location to an address
+        let delegate_address: Address = match infra.get(&loc) {
+            Some(info) => info.delegate.clone(),
+            None       => { return Err(PreprocessError::UnknownLocationError{ loc }); },
+        };
 
-                kind      : RawPreprocessKind::TransferRegistryTar as i32,
-                data      : Some(serde_json::to_string(&(location, address)).unwrap()),
+        // Try to preprocess the data as planned
+        let start: i64 = Utc::now().timestamp_millis();
+        let source: Location = match &preprocess {
+            PreprocessKind::TransferRegistryTar{ location, .. } => location.clone(),
+        };
+        global.read().unwrap().emit(SessionEvent::TransferStarted{ data: name.name().into(), location: loc.clone(), source: source.clone() });
+        let err: PreprocessError = match request_preprocess(&proxy, &parent, &delegate_address, &loc, &api_address, &name, preprocess).await {
+            Ok(access) => {
+                // Remember how long it took for later analysis
+                let end: i64 = Utc::now().timestamp_millis();
+                let mut state: RwLockWriteGuard<GlobalState> = global.write().unwrap();
+                state.emit(SessionEvent::TransferCompleted{ data: name.name().into(), location: loc.clone(), source, duration_ms: end - start });
+                state.transfers.push(TransferTiming {
+                    name     : name.name().into(),
+                    location : loc,
+                    start,
+                    end,
+                });
+                return Ok(access);
             },
+            Err(err) => err,
         };
 
-        // Create the client
-        let mut client: grpc::JobServiceClient<Channel> = match proxy.connect_to_job(delegate_address.to_string()).await {
-            Ok(result) => match result {
-                Ok(client) => client,
-                Err(err)   => { return Err(PreprocessError::GrpcConnectError{ endpoint: delegate_address, err }); },
+        // If that failed for a dataset, the data index may know other locations that have the same dataset
+        let data: &str = match &name {
+            DataName::Data(data)            => data,
+            DataName::IntermediateResult(_) => {
+                global.read().unwrap().emit(SessionEvent::TransferFailed{ data: name.name().into(), location: loc, source, error: err.to_string() });
+                return Err(err);
             },
-            Err(err) => { return Err(PreprocessError::ProxyError{ err: err.to_string() }); },
         };
-
-        // Send the request to the job node
-        let response: Response<PreprocessReply> = match client.preprocess(message).await {
-            Ok(response) => response,
-            Err(err)     => { return Err(PreprocessError::GrpcRequestError{ what: "PreprocessRequest", endpoint: delegate_address, err }); },
+        warn!("Failed to transfer dataset '{}' from '{}' to '{}': {} (trying alternative locations)", data, source, loc, err);
+        let data_index_addr: String = format!("{}/data/info", api_address);
+        let dindex: DataIndex = match ApiClient::new(api_address.to_string()).data_index().await {
+            Ok(dindex) => dindex,
+            Err(ierr)  => {
+                warn!("Failed to fetch data index from '{}' to find alternative locations: {}", data_index_addr, ierr);
+                global.read().unwrap().emit(SessionEvent::TransferFailed{ data: data.into(), location: loc, source, error: err.to_string() });
+                return Err(err);
+            },
         };
-        let result: PreprocessReply = response.into_inner();
-
-        // Check if it was a success
-        if !result.ok {
-            return Err(PreprocessError::PreprocessError{ endpoint: delegate_address, kind: name.variant().to_string(), name: name.name().into(), err: result.access });
-        }
-
-        // If it was, attempt to deserialize the accesskind
-        let access: AccessKind = match serde_json::from_str(&result.access) {
-            Ok(access) => access,
-            Err(err)   => { return Err(PreprocessError::AccessKindParseError{ endpoint: delegate_address, raw: result.access, err }); },
+        let mut alternatives: Vec<(&String, &AccessKind)> = match dindex.get(data) {
+            Some(info) => info.access.iter().filter(|(alt, _)| **alt != source).collect(),
+            None       => vec![],
         };
+        alternatives.sort_by_key(|(alt, _)| *alt);
+
+        // Try them in turn; whether the dataset may be transferred from there is up to the policy of that location
+        for (alt, access) in alternatives {
+            let access: AccessKind = if *alt == loc {
+                // The task's location has (since) got a copy of its own
+                access.clone()
+            } else {
+                let registry: &Address = match infra.get(alt) {
+                    Some(info) => &info.registry,
+                    None       => { debug!("Skipping alternative location '{}' for dataset '{}', as it is not part of this instance", alt, data); continue; },
+                };
+                let preprocess: PreprocessKind = PreprocessKind::TransferRegistryTar{ location: alt.clone(), address: format!("{}/data/download/{}", registry, data) };
+                match request_preprocess(&proxy, &parent, &delegate_address, &loc, &api_address, &name, preprocess).await {
+                    Ok(access) => access,
+                    Err(err)   => { warn!("Failed to transfer dataset '{}' from alternative location '{}' to '{}': {}", data, alt, loc, err); continue; },
+                }
+            };
 
-        // Done
-        Ok(access)
+            // Record the substitution in the run log
+            let note: String = format!("Dataset '{}' could not be transferred from '{}' to '{}'; used its copy at '{}' instead", data, source, loc, alt);
+            info!("{}", note);
+            if let Err(err) = report_note(&global, note).await { warn!("Failed to report substitution to client: {}", err); }
+            let end: i64 = Utc::now().timestamp_millis();
+            let mut state: RwLockWriteGuard<GlobalState> = global.write().unwrap();
+            state.emit(SessionEvent::TransferCompleted{ data: data.into(), location: loc.clone(), source: alt.clone(), duration_ms: end - start });
+            state.transfers.push(TransferTiming {
+                name     : name.name().into(),
+                location : loc,
+                start,
+                end,
+            });
+            return Ok(access);
+        }
+        global.read().unwrap().emit(SessionEvent::TransferFailed{ data: data.into(), location: loc, source, error: err.to_string() });
+        Err(err)
     }
 
 
 
+
     async fn execute(global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, info: TaskInfo<'_>) -> Result<Option<FullValue>, Self::ExecuteError> {
         info!("Executing task '{}' at '{}' in a distributed environment...", info.name, info.location);
         debug!("Package: '{}' v{}", info.package_name, info.package_version);
@@ -152,6 +563,10 @@ impl VmPlugin for InstancePlugin {
         debug!("Result: {:?}", info.result);
         debug!("Input arguments: {:#?}", info.args);
         debug!("Requirements: {:?}", info.requirements);
+        let start: i64 = Utc::now().timestamp_millis();
+        telemetry::record_task();
+        let parent: Span = global.read().unwrap().span.clone();
+        let app_id: String = global.read().unwrap().app_id.to_string();
 
         // Resolve the location to an address (and get the proxy and the workflow while we have a lock anyway)
         let (proxy, api_address, delegate_address, workflow): (Arc<ProxyClient>, Address, Address, String) = {
@@ -193,10 +608,13 @@ impl VmPlugin for InstancePlugin {
             result       : info.result.clone(),
             args         : serde_json::to_string(&info.args).unwrap(),
             requirements : info.requirements.iter().map(|c| serde_json::to_string(&c).unwrap()).collect(),
+            timeout      : info.timeout,
+
+            app_id : Some(app_id),
         };
 
         // Create the client
-        let mut client: grpc::JobServiceClient<Channel> = match proxy.connect_to_job(delegate_address.to_string()).await {
+        let mut client: grpc::JobServiceClient<Channel> = match proxy.connect_to_job(delegate_address.to_string(), job_tls(&delegate_address, info.location)).await {
             Ok(result) => match result {
                 Ok(client) => client,
                 Err(err)   => { return Err(ExecuteError::GrpcConnectError{ endpoint: delegate_address, err }); },
@@ -204,17 +622,23 @@ impl VmPlugin for InstancePlugin {
             Err(err) => { return Err(ExecuteError::ProxyError{ err: err.to_string() }); },
         };
 
-        // Send the request to the job node
-        let response: Response<Streaming<TaskReply>> = match client.execute(message).await {
+        // Send the request to the job node, which continues the workflow's trace
+        let span: Span = info_span!(parent: &parent, "execute", task = %info.name, location = %info.location);
+        let mut request: Request<TaskRequest> = Request::new(message);
+        inject_trace(&span, &mut request);
+        let response: Response<Streaming<TaskReply>> = match client.execute(request).await {
             Ok(response) => response,
             Err(err)     => { return Err(ExecuteError::GrpcRequestError{ what: "TaskRequest", endpoint: delegate_address, err }); },
         };
         let mut stream: Streaming<TaskReply> = response.into_inner();
+        global.read().unwrap().emit(SessionEvent::TaskStarted{ name: info.name.into(), location: info.location.clone() });
 
         // Now we tick off incoming messages
-        let mut state  : JobStatus                 = JobStatus::Unknown;
-        // let mut error : Option<String> = None;
-        let mut result : Result<FullValue, String> = Err("No response".into());
+        // Failures that the job node only described are wrapped with the state that the task got to
+        let remote_err = |err: String, status: &JobStatus| -> ExecuteError { ExecuteError::ExecuteError{ endpoint: delegate_address.clone(), name: info.name.into(), status: status.into(), err } };
+        let mut state  : JobStatus                               = JobStatus::Unknown;
+        let mut result : Option<Result<FullValue, ExecuteError>> = None;
+        let mut used   : Option<ResourceUsage>                   = None;
         #[allow(irrefutable_let_patterns)]
         while let message = stream.message().await {
             match message {
@@ -240,26 +664,46 @@ impl VmPlugin for InstancePlugin {
                         JobStatus::Received => { mundane_status_update!(state, status); },
 
                         JobStatus::Authorized               => { mundane_status_update!(state, status); },
-                        JobStatus::Denied                   => { result = Err("Permission denied".into()); state = status; break; },
-                        JobStatus::AuthorizationFailed(err) => { result = Err(err.clone()); state = status; break; },
+                        JobStatus::Denied                   => { result = Some(Err(remote_err("Permission denied".into(), &status))); break; },
+                        JobStatus::AuthorizationFailed(err) => { result = Some(Err(remote_err(err.clone(), &status))); break; },
 
                         JobStatus::Created             => { mundane_status_update!(state, status); },
-                        JobStatus::CreationFailed(err) => { result = Err(err.clone()); state = status; break; },
+                        JobStatus::CreationFailed(err) => { result = Some(Err(remote_err(err.clone(), &status))); break; },
 
                         JobStatus::Ready                     => { mundane_status_update!(state, status); },
                         JobStatus::Initialized               => { mundane_status_update!(state, status); },
-                        JobStatus::InitializationFailed(err) => { result = Err(err.clone()); state = status; break; },
+                        JobStatus::InitializationFailed(err) => { result = Some(Err(remote_err(err.clone(), &status))); break; },
                         JobStatus::Started                   => { mundane_status_update!(state, status); },
-                        JobStatus::StartingFailed(err)       => { result = Err(err.clone()); state = status; break; },
-
-                        JobStatus::Heartbeat             => { mundane_status_update!(state, status); },
-                        JobStatus::Completed             => { mundane_status_update!(state, status); },
-                        JobStatus::CompletionFailed(err) => { result = Err(err.clone()); state = status; break; },
-
-                        JobStatus::Finished(value)              => { result = Ok(value.clone()); state = status; break; },
-                        JobStatus::Stopped                      => { result = Err("Job was stopped".into()); state = status; break; },
-                        JobStatus::DecodingFailed(err)          => { result = Err(err.clone()); state = status; break; },
-                        JobStatus::Failed(code, stdout, stderr) => { result = Err(format!("Job failed with exit code {}\n\nstdout:\n{}\n{}\n{}\n\nstderr:\n{}\n{}\n{}\n", code, (0..80).map(|_| '-').collect::<String>(), stdout, (0..80).map(|_| '-').collect::<String>(), (0..80).map(|_| '-').collect::<String>(), stderr, (0..80).map(|_| '-').collect::<String>())); state = status; break; },
+                        JobStatus::StartingFailed(err)       => { result = Some(Err(remote_err(err.clone(), &status))); break; },
+
+                        JobStatus::Heartbeat(progress)   => {
+                            // Relay any progress to the client
+                            if let Some(progress) = progress {
+                                if let Err(err) = report_progress(global, info.name, progress).await { warn!("{}", err); }
+                            }
+                            mundane_status_update!(state, status);
+                        },
+                        JobStatus::Output(output)        => {
+                            // Relay the output to the client as it comes in
+                            if let Err(err) = report_output(global, info.name, output).await { warn!("{}", err); }
+                            mundane_status_update!(state, status);
+                        },
+                        JobStatus::Completed(usage)      => {
+                            // Relay what the task consumed to the client
+                            if let Some(usage) = usage {
+                                if let Err(err) = report_usage(global, info.name, usage).await { warn!("{}", err); }
+                                used = Some(usage.clone());
+                            }
+                            mundane_status_update!(state, status);
+                        },
+                        JobStatus::CompletionFailed(err) => { result = Some(Err(remote_err(err.clone(), &status))); break; },
+
+                        JobStatus::Finished(value)              => { result = Some(Ok(value.clone())); break; },
+                        JobStatus::Stopped                      => { result = Some(Err(remote_err("Job was stopped".into(), &status))); break; },
+                        JobStatus::DecodingFailed(err)          => { result = Some(Err(remote_err(err.clone(), &status))); break; },
+                        JobStatus::Failed(code, stdout, stderr) => { result = Some(Err(ExecuteError::from_exit_code(info.name, Image::new(info.package_name, Some(info.package_version), None::<String>), *code, stdout, stderr))); break; },
+                        JobStatus::TimedOut(timeout)            => { result = Some(Err(ExecuteError::Timeout{ name: info.name.into(), image: Image::new(info.package_name, Some(info.package_version), None::<String>), timeout: *timeout })); break; },
+                        JobStatus::ScratchExhausted(limit)      => { result = Some(Err(ExecuteError::ScratchExhausted{ name: info.name.into(), image: Image::new(info.package_name, Some(info.package_version), None::<String>), limit: *limit })); break; },
                     }
                 },
                 Ok(None) => {
@@ -269,43 +713,73 @@ impl VmPlugin for InstancePlugin {
 
                 Err(status) => {
                     // Something went wrong
-                    result = Err(format!("Status error: {}", status));
+                    result = Some(Err(remote_err(format!("Status error: {}", status), &state)));
                     break;
                 },
             }
         }
 
         // Now we simply match on the value to see if we got something
-        let result: FullValue = match result {
+        let result: FullValue = match result.unwrap_or_else(|| Err(remote_err("No response".into(), &state))) {
             Ok(result) => result,
-            Err(err)   => { return Err(ExecuteError::ExecuteError{ endpoint: delegate_address, name: info.name.into(), status: state.into(), err }); },
+            Err(err)   => {
+                global.read().unwrap().emit(SessionEvent::TaskFailed{ name: info.name.into(), location: info.location.clone(), error: err.to_string() });
+                return Err(err);
+            },
         };
 
+        // Remember how long it took for later analysis
+        {
+            let end: i64 = Utc::now().timestamp_millis();
+            let mut gstate: RwLockWriteGuard<GlobalState> = global.write().unwrap();
+            gstate.emit(SessionEvent::TaskCompleted{ name: info.name.into(), location: info.location.clone(), duration_ms: end - start, usage: used });
+            gstate.timings.push(TaskTiming {
+                pc       : info.pc,
+                name     : format!("{}::{}", info.package_name, info.name),
+                location : info.location.clone(),
+                start,
+                end,
+                usage    : used,
+            });
+        }
+
         // That's it!
         debug!("Task '{}' result: {:?}", info.name, result);
         Ok(if let FullValue::Void = result { None } else { Some(result) })
     }
 
+    #[inline]
+    fn is_transient(err: &Self::ExecuteError) -> bool { err.is_transient() }
+
 
 
-    async fn stdout(global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, text: &str, newline: bool) -> Result<(), Self::StdoutError> {
+    async fn stdout(global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, branch: &[usize], text: &str, newline: bool) -> Result<(), Self::StdoutError> {
         info!("Writing '{}' to stdout in a distributed environment...", text);
         debug!("Newline: {}", if newline { "yes" } else { "no" });
 
         // Get the TX (so that the lock does not live over an `.await`)
         let tx: Arc<Sender<Result<ExecuteReply, Status>>> = {
             let state: RwLockReadGuard<GlobalState> = global.read().unwrap();
+            state.emit(SessionEvent::Print{ branch: branch.to_vec(), text: format!("{}{}", text, if newline { "\n" } else { "" }) });
             state.tx.as_ref().expect("Missing `tx` in GlobalState; did you forget to update it before this poll?").clone()
         };
 
         // Write stdout to the tx
         if let Err(err) = tx.send(Ok(ExecuteReply {
             stdout : Some(format!("{}{}", text, if newline { "\n" } else { "" })),
+            branch : branch.iter().map(|b| *b as u64).collect(),
             stderr : None,
             debug  : None,
             value  : None,
 
+            progress : None,
+            usage    : None,
+            output   : None,
+
             close : false,
+
+            seq       : 0,
+            heartbeat : false,
         })).await {
             return Err(StdoutError::TxWriteError{ err });
         }
@@ -325,9 +799,10 @@ impl VmPlugin for InstancePlugin {
         Ok(())
     }
 
-    async fn commit(global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, loc: &Location, name: &str, path: &Path, data_name: &str) -> Result<(), Self::CommitError> {
+    async fn commit(global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, loc: &Location, name: &str, path: &Path, data_name: &str, residency: Option<&[Location]>) -> Result<(), Self::CommitError> {
         info!("Committing intermediate result '{}' living at '{}' as '{}' in a distributed environment...", name, loc, data_name);
         debug!("File: '{}'", path.display());
+        let parent: Span = global.read().unwrap().span.clone();
 
         // We submit a commit request to the job node
 
@@ -352,15 +827,18 @@ impl VmPlugin for InstancePlugin {
             }
         };
 
-        // Prepare the request to send to the delegate node
+        // Prepare the request to send to the delegate node, which only replaces the revision of the dataset that this workflow saw
         debug!("Sending commit request to job node '{}'...", delegate_address);
+        let key: (String, Location) = (data_name.into(), loc.clone());
         let message: CommitRequest = CommitRequest {
             name      : name.into(),
             data_name : data_name.into(),
+            residency : residency.map(|locs| locs.to_vec()).unwrap_or_default(),
+            revision  : Some(global.read().unwrap().revisions.get(&key).copied().unwrap_or(0)),
         };
 
         // Create the client
-        let mut client: grpc::JobServiceClient<Channel> = match proxy.connect_to_job(delegate_address.to_string()).await {
+        let mut client: grpc::JobServiceClient<Channel> = match proxy.connect_to_job(delegate_address.to_string(), job_tls(&delegate_address, loc)).await {
             Ok(result) => match result {
                 Ok(client) => client,
                 Err(err)   => { return Err(CommitError::GrpcConnectError{ endpoint: delegate_address, err }); },
@@ -368,10 +846,14 @@ impl VmPlugin for InstancePlugin {
             Err(err) => { return Err(CommitError::ProxyError{ err: err.to_string() }); },
         };
 
-        // Send the request to the job node
-        let response: Response<CommitReply> = match client.commit(message).await {
-            Ok(response) => response,
-            Err(err)     => { return Err(CommitError::GrpcRequestError{ what: "CommitRequest", endpoint: delegate_address, err }); },
+        // Send the request to the job node, which continues the workflow's trace
+        let span: Span = info_span!(parent: &parent, "commit", result = %name, location = %loc);
+        let mut request: Request<CommitRequest> = Request::new(message);
+        inject_trace(&span, &mut request);
+        let response: Response<CommitReply> = match client.commit(request).await {
+            Ok(response)                  => response,
+            Err(err) if is_conflict(&err) => { return Err(CommitError::ConflictError{ endpoint: delegate_address, name: data_name.into(), err: err.message().into() }); },
+            Err(err)                      => { return Err(CommitError::GrpcRequestError{ what: "CommitRequest", endpoint: delegate_address, err }); },
         };
         let result: CommitReply = response.into_inner();
 
@@ -380,9 +862,164 @@ impl VmPlugin for InstancePlugin {
             return Err(CommitError::CommitError{ endpoint: delegate_address, name: name.into(), err: result.error });
         }
 
+        // Later commits of this workflow to the same dataset replace the revision we just made
+        if let Some(revision) = result.revision {
+            global.write().unwrap().revisions.insert(key, revision);
+        }
+
         // Done (nothing to return)
         Ok(())
     }
+
+
+
+    async fn wait_event(global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, name: &str) -> Result<String, Self::EventError> {
+        // Get the registry (so that the lock does not live over an `.await`)
+        let (app_id, events, tx): (AppId, Arc<EventRegistry>, Arc<Sender<Result<ExecuteReply, Status>>>) = {
+            let state: RwLockReadGuard<GlobalState> = global.read().unwrap();
+            (state.app_id.clone(), state.events.clone(), state.tx.as_ref().expect("Missing `tx` in GlobalState; did you forget to update it before this poll?").clone())
+        };
+        info!("Waiting for event '{}' in session '{}'...", name, app_id);
+
+        // Tell the client how to resume us, since they may be the one to send the event
+        if let Err(err) = tx.send(Ok(ExecuteReply {
+            stdout : None,
+            branch : vec![],
+            stderr : Some(format!("Waiting for event '{}' in session '{}'...", name, app_id)),
+            debug  : None,
+            value  : None,
+
+            progress : None,
+            usage    : None,
+            output   : None,
+
+            close : false,
+
+            seq       : 0,
+            heartbeat : false,
+        })).await {
+            warn!("Failed to notify client of waiting for event '{}': {}", name, err);
+        }
+
+        // Park until someone sends it through `SendEvent`
+        match events.wait(&app_id, name).await {
+            Some(payload) => Ok(payload),
+            None          => Err(EventError::SourceClosed{ name: name.into() }),
+        }
+    }
+
+    async fn require_approval(global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, message: &str) -> Result<ApprovalDecision, Self::EventError> {
+        // Get the registry (so that the lock does not live over an `.await`)
+        let (app_id, approvals, tx): (AppId, Arc<ApprovalRegistry>, Arc<Sender<Result<ExecuteReply, Status>>>) = {
+            let state: RwLockReadGuard<GlobalState> = global.read().unwrap();
+            (state.app_id.clone(), state.approvals.clone(), state.tx.as_ref().expect("Missing `tx` in GlobalState; did you forget to update it before this poll?").clone())
+        };
+
+        // Record the approval
+        let id: String = approvals.request(&app_id, message);
+        info!("Session '{}' requires approval '{}' for '{}'...", app_id, id, message);
+
+        // Tell the client how to decide on it
+        if let Err(err) = tx.send(Ok(ExecuteReply {
+            stdout : None,
+            branch : vec![],
+            stderr : Some(format!("Waiting for approval '{}': {}", id, message)),
+            debug  : None,
+            value  : None,
+
+            progress : None,
+            usage    : None,
+            output   : None,
+
+            close : false,
+
+            seq       : 0,
+            heartbeat : false,
+        })).await {
+            warn!("Failed to notify client of waiting for approval '{}': {}", id, err);
+        }
+
+        // Park until someone decides on it through `DecideApproval`
+        match approvals.wait(&app_id, &id).await {
+            Some(decision) => Ok(decision),
+            None           => Err(EventError::SourceClosed{ name: id }),
+        }
+    }
+
+
+
+    async fn notify(global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, channel: &str, message: &str) -> Result<(), Self::NotifyError> {
+        // Resolve the channel in the node config file (so that the lock does not live over an `.await`)
+        let (app_id, notifier, config): (AppId, Arc<Notifier>, NotificationChannel) = {
+            let state: RwLockReadGuard<GlobalState> = global.read().unwrap();
+            let mut node_config: NodeConfig = match NodeConfig::from_path(&state.node_config_path) {
+                Ok(config) => config,
+                Err(err)   => { return Err(NotifyError::NodeConfigReadError{ path: state.node_config_path.clone(), err }); },
+            };
+            match node_config.node.central_mut().notifications.remove(channel) {
+                Some(config) => (state.app_id.clone(), state.notifier.clone(), config),
+                None         => { return Err(NotifyError::UnknownChannel{ name: channel.into() }); },
+            }
+        };
+        info!("Sending notification on {} channel '{}' for session '{}'...", config.variant(), channel, app_id);
+
+        // Send it
+        notifier.send(channel, &config, &app_id, message).await
+    }
+
+    async fn indices(global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState) -> Result<(Arc<PackageIndex>, Arc<DataIndex>), Self::IndexError> {
+        // Resolve the API service in the node config file (so that the lock does not live over an `.await`)
+        let api: String = {
+            let state: RwLockReadGuard<GlobalState> = global.read().unwrap();
+            match NodeConfig::from_path(&state.node_config_path) {
+                Ok(node_config) => node_config.node.central().services.api.to_string(),
+                Err(err)        => { return Err(IndexError::NodeConfigReadError{ path: state.node_config_path.clone(), err }); },
+            }
+        };
+
+        // Fetch the instance's indices, so the workflow sees the same as it was compiled against
+        let package_index_addr: String = format!("{}/graphql", api);
+        debug!("Fetching package index from '{}'...", package_index_addr);
+        let pindex: PackageIndex = match get_package_index(&package_index_addr).await {
+            Ok(pindex) => pindex,
+            Err(err)   => { return Err(IndexError::PackageIndexError{ address: package_index_addr, err }); },
+        };
+        let data_index_addr: String = format!("{}/data/info", api);
+        debug!("Fetching data index from '{}'...", data_index_addr);
+        let dindex: DataIndex = match ApiClient::new(&api).data_index().await {
+            Ok(dindex) => dindex,
+            Err(err)   => { return Err(IndexError::DataIndexError{ address: data_index_addr, err }); },
+        };
+        Ok((Arc::new(pindex), Arc::new(dindex)))
+    }
+
+
+
+    async fn checkpoint(global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, checkpoint: &Checkpoint) -> Result<(), Self::CheckpointError> {
+        // Get the file and the workflow (so that the lock does not live over an `.await`)
+        let (path, workflow, owner): (PathBuf, String, Option<String>) = {
+            let state: RwLockReadGuard<GlobalState> = global.read().unwrap();
+            match &state.checkpoint {
+                Some(path) => (path.clone(), state.workflow.clone().expect("Missing `workflow` in GlobalState; did you forget to update it before this poll?"), state.owner.clone()),
+                None       => { return Ok(()); },
+            }
+        };
+        debug!("Checkpointing workflow at edge {} to '{}'...", checkpoint.pc.1, path.display());
+
+        // Serialize it with the workflow, so we know what to resume
+        let scheckpoint: String = match serde_json::to_string(&SessionCheckpoint{ workflow, checkpoint: checkpoint.clone(), owner }) {
+            Ok(scheckpoint) => scheckpoint,
+            Err(err)        => { return Err(CheckpointError::SerializeError{ err }); },
+        };
+
+        // Write it next to the previous one first, so that a crash while writing never leaves us without a valid checkpoint
+        let temp: PathBuf = path.with_extension("json.tmp");
+        if let Err(err) = tfs::write(&temp, scheckpoint).await { return Err(CheckpointError::FileWriteError{ path: temp, err }); }
+        if let Err(err) = tfs::rename(&temp, &path).await { return Err(CheckpointError::FileRenameError{ from: temp, to: path, err }); }
+
+        // Done
+        Ok(())
+    }
 }
 
 
@@ -395,6 +1032,15 @@ pub struct InstanceVm {
 
     /// The planner that we use for planning.
     planner : Arc<InstancePlanner>,
+    /// The plugin with which we run workflows.
+    plugin  : PluginEntry<Self>,
+    /// The token with which the running workflow may be cancelled, if any.
+    cancel  : Option<CancelToken>,
+
+    /// The maximum number of branches of a single parallel statement that are run at the same time, if any.
+    max_parallel : Option<usize>,
+    /// The maximum number of steps that a single run may take, if any.
+    max_steps    : Option<u64>,
 }
 
 impl InstanceVm {
@@ -404,30 +1050,133 @@ impl InstanceVm {
     /// - `node_config_path`: The path to the configuration for this node's environment. For us, contains the path to the infra.yml and (optional) secrets.yml files.
     /// - `app_id`: The application ID for this session.
     /// - `proxy`: The ProxyClient that we use to connect to/through `brane-prx`.
+    /// - `notifier`: The Notifier that we use to send notifications to users.
+    /// - `events`: The EventRegistry that routes external events to this session.
+    /// - `approvals`: The ApprovalRegistry that keeps track of the approvals this session waits for.
+    /// - `progress`: The ProgressHub that broadcasts the events of this session to its subscribers.
     /// - `planner`: The client-side of a planner that we use to plan.
+    /// - `checkpoints`: The directory to checkpoint running workflows to, if any.
     /// 
     /// # Returns
     /// A new InstanceVm instance.
     #[inline]
-    pub fn new(node_config_path: impl Into<PathBuf>, app_id: AppId, proxy: Arc<ProxyClient>, planner: Arc<InstancePlanner>) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(node_config_path: impl Into<PathBuf>, app_id: AppId, proxy: Arc<ProxyClient>, notifier: Arc<Notifier>, events: Arc<EventRegistry>, approvals: Arc<ApprovalRegistry>, progress: Arc<ProgressHub>, planner: Arc<InstancePlanner>, checkpoints: Option<&Path>) -> Self {
+        let checkpoint: Option<PathBuf> = checkpoints.map(|dir| dir.join(format!("{}.json", app_id)));
         Self {
             // InfraPath::new(&node_config.node.central().paths.infra, &node_config.node.central().paths.secrets)
             state : Self::new_state(GlobalState {
                 node_config_path : node_config_path.into(),
                 app_id,
+                owner            : None,
                 proxy,
-
-                workflow : None,
+                notifier,
+                events,
+                approvals,
+                progress,
+
+                workflow  : None,
+                checkpoint,
+                timings   : vec![],
+                transfers : vec![],
+                revisions : HashMap::new(),
+                span      : Span::none(),
 
                 tx : None,
             }),
 
             planner,
+            plugin : PluginEntry::new::<InstancePlugin>(),
+            cancel : None,
+
+            max_parallel : None,
+            max_steps    : None,
         }
     }
 
+    /// Makes the InstanceVm run workflows with the given plugin instead of the InstancePlugin.
+    /// 
+    /// # Arguments
+    /// - `plugin`: The PluginEntry of the plugin to run with (see `plugins()`).
+    /// 
+    /// # Returns
+    /// The same InstanceVm, but now running with the given plugin.
+    #[inline]
+    pub fn with_plugin(mut self, plugin: PluginEntry<Self>) -> Self {
+        self.plugin = plugin;
+        self
+    }
+
+    /// Records which client created this InstanceVm's session, so that it is checkpointed with its workflows.
+    /// 
+    /// # Arguments
+    /// - `owner`: The name of the client (as given in its certificate), if it authenticated itself.
+    /// 
+    /// # Returns
+    /// The same InstanceVm, but now with an owner.
+    #[inline]
+    pub fn with_owner(self, owner: Option<String>) -> Self {
+        self.state.global.write().unwrap().owner = owner;
+        self
+    }
+
+    /// Makes the next workflow that this InstanceVm runs cancellable with the given token.
+    /// 
+    /// # Arguments
+    /// - `cancel`: The CancelToken with which the workflow may be cancelled.
+    /// 
+    /// # Returns
+    /// The same InstanceVm, but now cancellable.
+    #[inline]
+    pub fn with_cancel(mut self, cancel: CancelToken) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Makes the InstanceVm run at most the given number of branches of a parallel statement at the same time, so large fan-outs do not flood the instance.
+    /// 
+    /// # Arguments
+    /// - `max_parallel`: The maximum number of concurrent branches, or `None` to run all branches at once.
+    /// 
+    /// # Returns
+    /// The same InstanceVm, but now with a bounded fan-out.
+    #[inline]
+    pub fn with_max_parallel(mut self, max_parallel: Option<usize>) -> Self {
+        self.max_parallel = max_parallel;
+        self
+    }
+
+    /// Makes the InstanceVm abort workflows that take more than the given number of steps, so that infinite loops do not occupy the driver forever.
+    /// 
+    /// # Arguments
+    /// - `max_steps`: The maximum number of steps (i.e., edges and instructions) per run, or `None` to not limit runs.
+    /// 
+    /// # Returns
+    /// The same InstanceVm, but now with a limited number of steps.
+    #[inline]
+    pub fn with_max_steps(mut self, max_steps: Option<u64>) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
 
 
+    /// Summarizes the variables currently defined in this VM's session.
+    /// 
+    /// # Returns
+    /// A list of FrameSummaries, starting with the main frame (which, in between two runs, is typically the only one).
+    #[inline]
+    pub fn summary(&self) -> Vec<FrameSummary> { self.state.fstack.summary() }
+
+    /// Returns the (last) workflow of this VM's session together with how long its tasks and data transfers took.
+    /// 
+    /// # Returns
+    /// A tuple of the planned workflow (serialized, or None if the session did not run anything yet), the TaskTimings of the tasks that completed and the TransferTimings of the transfers that completed, both in the order they completed.
+    pub fn timings(&self) -> (Option<String>, Vec<TaskTiming>, Vec<TransferTiming>) {
+        let state: RwLockReadGuard<GlobalState> = self.state.global.read().unwrap();
+        (state.workflow.clone(), state.timings.clone(), state.transfers.clone())
+    }
+
     /// Runs the given workflow on this VM.
     /// 
     /// There is a bit of ownership awkwardness going on, but that's due to the need for the struct to outlive threads.
@@ -445,11 +1194,23 @@ impl InstanceVm {
             Err(err) => { return (self, Err(Error::PlanError{ err })); },
         };
 
+        // Note which revisions of the datasets the workflow sees, which are the ones it may replace when committing results
+        let node_config_path: PathBuf = self.state.global.read().unwrap().node_config_path.clone();
+        let revisions: HashMap<(String, Location), u64> = match fetch_revisions(&node_config_path).await {
+            Ok(revisions) => revisions,
+            Err(err)      => { return (self, Err(Error::RevisionsError{ err })); },
+        };
+
         // Also update the TX & workflow in the internal state
         {
             let mut state: RwLockWriteGuard<GlobalState> = self.state.global.write().unwrap();
             state.workflow = Some(serde_json::to_string(&plan).unwrap());
+            state.timings.clear();
+            state.transfers.clear();
+            state.revisions = revisions;
+            state.span = Span::current();
             state.tx = Some(Arc::new(tx));
+            state.emit(SessionEvent::RunStarted);
         }
 
 
@@ -459,23 +1220,105 @@ impl InstanceVm {
         let this: Arc<RwLock<Self>> = Arc::new(RwLock::new(self));
 
         // Run the VM and get self back
-        let result: Result<FullValue, VmError> = Self::run::<InstancePlugin>(this.clone(), plan).await;
+        let plugin: PluginEntry<Self> = this.read().unwrap().plugin;
+        let result: Result<FullValue, VmError> = plugin.run(this.clone(), plan).await;
         let this: Self = match Arc::try_unwrap(this) {
             Ok(this) => this.into_inner().unwrap(),
             Err(_)   => { panic!("Could not get self back"); },
         };
+        // Failed runs keep their checkpoint, so they may be resumed once the problem is fixed (killing the session removes it)
+        if result.is_ok() { this.clear_checkpoint().await; }
 
 
 
         // Step 3: Result
         // Match the result to potentially error
-        let value: FullValue = match result {
-            Ok(value) => value,
-            Err(err)  => { return (this, Err(Error::ExecError{ err })); },
+        let result: Result<FullValue, Error> = result.map_err(|err| Error::ExecError{ err });
+        this.emit_result(&result);
+        (this, result)
+    }
+
+    /// Resumes the workflow in the given checkpoint on this VM, e.g., after the driver was restarted.
+    /// 
+    /// # Arguments
+    /// - `tx`: The transmission channel to send feedback to the client on.
+    /// - `checkpoint`: The SessionCheckpoint with the (already planned) workflow and where to resume it.
+    /// 
+    /// # Returns
+    /// The result of the workflow, if any. It also returns `self` again for subsequent runs.
+    pub async fn exec_checkpoint(self, tx: Sender<Result<ExecuteReply, Status>>, checkpoint: SessionCheckpoint) -> (Self, Result<FullValue, Error>) {
+        // Parse the workflow that was running
+        let plan: Workflow = match serde_json::from_str(&checkpoint.workflow) {
+            Ok(plan) => plan,
+            Err(err) => { return (self, Err(Error::CheckpointWorkflowParseError{ err })); },
+        };
+
+        // The revisions that the workflow saw before the restart are lost, so it may replace the current ones
+        let node_config_path: PathBuf = self.state.global.read().unwrap().node_config_path.clone();
+        let revisions: HashMap<(String, Location), u64> = match fetch_revisions(&node_config_path).await {
+            Ok(revisions) => revisions,
+            Err(err)      => { return (self, Err(Error::RevisionsError{ err })); },
+        };
+
+        // Also update the TX & workflow in the internal state
+        {
+            let mut state: RwLockWriteGuard<GlobalState> = self.state.global.write().unwrap();
+            state.workflow = Some(checkpoint.workflow);
+            state.timings.clear();
+            state.transfers.clear();
+            state.revisions = revisions;
+            state.span = Span::current();
+            state.tx = Some(Arc::new(tx));
+            state.emit(SessionEvent::RunStarted);
+        }
+
+        // Run the VM from the checkpoint and get self back
+        let this: Arc<RwLock<Self>> = Arc::new(RwLock::new(self));
+        let plugin: PluginEntry<Self> = this.read().unwrap().plugin;
+        let result: Result<FullValue, VmError> = plugin.resume(this.clone(), plan, checkpoint.checkpoint).await;
+        let this: Self = match Arc::try_unwrap(this) {
+            Ok(this) => this.into_inner().unwrap(),
+            Err(_)   => { panic!("Could not get self back"); },
+        };
+        // Failed runs keep their checkpoint, so they may be resumed once the problem is fixed (killing the session removes it)
+        if result.is_ok() { this.clear_checkpoint().await; }
+
+        // Match the result to potentially error
+        let result: Result<FullValue, Error> = result.map_err(|err| Error::ExecError{ err });
+        this.emit_result(&result);
+        (this, result)
+    }
+
+    /// Tells the subscribers of this session how its workflow ended.
+    /// 
+    /// # Arguments
+    /// - `result`: The result of the workflow.
+    fn emit_result(&self, result: &Result<FullValue, Error>) {
+        let event: SessionEvent = match result {
+            Ok(FullValue::Void) => SessionEvent::RunCompleted{ value: None },
+            Ok(value)           => SessionEvent::RunCompleted{ value: serde_json::to_string(value).ok() },
+            Err(err)            => SessionEvent::RunFailed{ error: err.to_string() },
         };
+        self.state.global.read().unwrap().emit(event);
+    }
 
-        // Done, return
-        (this, Ok(value))
+    /// Removes the checkpoint of this session's workflow (if any), as it has finished successfully or was killed.
+    /// 
+    /// Failing to do so is not fatal, and is only logged as a warning.
+    pub async fn clear_checkpoint(&self) {
+        // Get the path (so that the lock does not live over an `.await`)
+        let path: PathBuf = {
+            let state: RwLockReadGuard<GlobalState> = self.state.global.read().unwrap();
+            match &state.checkpoint {
+                Some(path) => path.clone(),
+                None       => { return; },
+            }
+        };
+
+        // Remove it if the workflow got to the point of making one
+        if !path.exists() { return; }
+        debug!("Removing checkpoint '{}'...", path.display());
+        if let Err(err) = tfs::remove_file(&path).await { warn!("Failed to remove checkpoint '{}': {}", path.display(), err); }
     }
 }
 
@@ -496,4 +1339,16 @@ impl Vm for InstanceVm {
         let lock: RwLockReadGuard<Self> = this.read().unwrap();
         Ok(lock.state.clone())
     }
+
+    fn cancel_token(this: &Arc<RwLock<Self>>) -> Option<CancelToken> {
+        this.read().unwrap().cancel.clone()
+    }
+
+    fn max_parallel(this: &Arc<RwLock<Self>>) -> Option<usize> {
+        this.read().unwrap().max_parallel
+    }
+
+    fn max_steps(this: &Arc<RwLock<Self>>) -> Option<u64> {
+        this.read().unwrap().max_steps
+    }
 }
@@ -13,6 +13,9 @@
 //!   (incomplete) AST.
 // 
 
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr as _;
+
 use nom::InputLength;
 
 use nom::error::VerboseErrorKind;
@@ -20,16 +23,20 @@ use specifications::package::PackageIndex;
 
 pub use crate::errors::ParseError as Error;
 use crate::errors;
-use crate::spec::Language;
+use crate::spec::{Edition, Language};
+use crate::location::Location;
+use crate::vocabulary::Vocabulary;
 use crate::scanner::{self, Span, Token, Tokens};
 use crate::parser::{bakery, bscript};
-use crate::parser::ast::Program;
+use crate::data_type::DataType;
+use crate::parser::ast::{Expr, Identifier, Literal, Param, Program, PropertyExpr, Stmt};
 
 
 /***** TESTS *****/
 #[cfg(test)]
 pub mod tests {
     use brane_shr::utilities::{create_package_index, test_on_dsl_files};
+    use crate::parser::ast::{Expr, Stmt};
     use super::*;
 
 
@@ -80,6 +87,140 @@ pub mod tests {
             println!("{}\n\n", (0..80).map(|_| '-').collect::<String>());
         });
     }
+
+    /// Tests that words reserved by newer editions may still be used as identifiers in older ones.
+    #[test]
+    fn test_editions() {
+        let pindex: PackageIndex = create_package_index();
+        let code: &str = "let match := 42;\nlet try := match;\n";
+
+        // The default edition does not reserve them
+        if let Err(err) = parse(code, &pindex, &ParserOptions::bscript()) { panic!("Failed to parse identifiers reserved by newer editions: {}", err); }
+
+        // The latest one does
+        match parse(code, &pindex, &ParserOptions::bscript().with_edition(Edition::LATEST)) {
+            Err(Error::ReservedKeyword{ keyword, edition, range }) => {
+                assert_eq!(keyword, "match");
+                assert_eq!(edition, Edition::LATEST);
+                assert_eq!((range.start.line, range.start.col), (1, 5));
+            },
+            Err(err) => { panic!("Expected a reserved keyword error, got: {}", err); },
+            Ok(_)    => { panic!("Parsed reserved keyword as an identifier"); },
+        }
+    }
+
+    /// Tests that loops over arrays parse in every edition, while `in` remains usable as an identifier elsewhere in older ones.
+    #[test]
+    fn test_for_in() {
+        let pindex: PackageIndex = create_package_index();
+
+        // Loops over arrays are desugared to a block with the array and a counter loop in it
+        let code: &str = "for x in [ 1, 2, 3 ] {\n    println(x);\n}\n";
+        for edition in [ Edition::E2022, Edition::E2026 ] {
+            let program: Program = match parse(code, &pindex, &ParserOptions::bscript().with_edition(edition)) {
+                Ok(program) => program,
+                Err(err)    => { panic!("Failed to parse loop over array in edition {}: {}", edition, err); },
+            };
+            match &program.block.stmts[..] {
+                [ Stmt::Block{ block } ] => { assert!(matches!(&block.stmts[..], [ Stmt::LetAssign{ .. }, Stmt::For{ .. } ])); },
+                stmts                    => { panic!("Expected a single block, got {:?}", stmts); },
+            }
+        }
+
+        // Existing scripts may still call things `in`, but new ones may not
+        let code: &str = "let in := [ 1, 2 ];\nfor x in in {\n    println(x);\n}\n";
+        if let Err(err) = parse(code, &pindex, &ParserOptions::bscript()) { panic!("Failed to parse 'in' as an identifier: {}", err); }
+        assert!(parse(code, &pindex, &ParserOptions::bscript().with_edition(Edition::LATEST)).is_err());
+    }
+
+    /// Tests that Bakery sentences are read with the selected vocabulary.
+    #[test]
+    fn test_vocabulary() {
+        let pindex: PackageIndex = create_package_index();
+
+        // Parses the given code and returns the function called by, and the number of arguments of, the last statement
+        let last_call = |code: &str, options: &ParserOptions| -> (String, usize) {
+            let program: Program = match parse(code, &pindex, options) {
+                Ok(program) => program,
+                Err(err)    => { panic!("Failed to parse Bakery sentence: {}", err); },
+            };
+            match program.block.stmts.last() {
+                Some(Stmt::Expr{ expr: Expr::Call{ expr, args, .. }, .. }) => match &**expr {
+                    Expr::VarRef{ name, .. } => (name.value.clone(), args.len()),
+                    expr                     => { panic!("Expected a call to a function, got: {:?}", expr); },
+                },
+                stmt => { panic!("Expected a call statement, got: {:?}", stmt); },
+            }
+        };
+
+        // The English vocabulary drops the fillers
+        assert_eq!(last_call("let x := 42;\nsay the x to \"Amy\";\n", &ParserOptions::bakery()), ("println".into(), 2));
+        // Unknown verbs call the function with the same name
+        assert_eq!(last_call("let x := 42;\nprint x;\n", &ParserOptions::bakery()), ("print".into(), 1));
+        // Other vocabularies may be plugged in
+        let dutch: Vocabulary = Vocabulary::new("nl").with_verb("zeg", "println").with_filler("de").with_filler("tegen");
+        assert_eq!(last_call("let x := 42;\nzeg de x tegen \"Amy\";\n", &ParserOptions::bakery().with_vocabulary(dutch)), ("println".into(), 2));
+    }
+
+    /// Tests that parameters are bound to the given values or their defaults.
+    #[test]
+    fn test_params() {
+        let pindex: PackageIndex = create_package_index();
+        let code: &str = "param k: int := 5;\nparam input: Data;\nprintln(k);\n";
+        let with = |params: &[(&str, &str)]| -> ParserOptions { ParserOptions::bscript().with_params(params.iter().map(|(n, v)| (n.to_string(), v.to_string())).collect()) };
+
+        // Given values are parsed according to their type, while the defaults are remembered
+        let program: Program = match parse(code, &pindex, &with(&[ ("k", "10"), ("input", "Data<\"xyz\">") ])) {
+            Ok(program) => program,
+            Err(err)    => { panic!("Failed to parse parameters: {}", err); },
+        };
+        assert_eq!(program.params.iter().map(|p| (p.name.as_str(), p.default.as_deref())).collect::<Vec<_>>(), vec![ ("k", Some("5")), ("input", None) ]);
+        match &program.block.stmts[1] {
+            Stmt::LetAssign{ value: Expr::Instance{ properties, .. }, .. } => match &*properties[0].value {
+                Expr::Literal{ literal: Literal::String{ value, .. } } => assert_eq!(value, "xyz"),
+                expr                                                   => { panic!("Expected a dataset name, got: {:?}", expr); },
+            },
+            stmt => { panic!("Expected a dataset parameter, got: {:?}", stmt); },
+        }
+        match &program.block.stmts[0] {
+            Stmt::LetAssign{ value: Expr::Literal{ literal: Literal::Integer{ value, .. } }, .. } => assert_eq!(*value, 10),
+            stmt                                                                               => { panic!("Expected an overridden integer parameter, got: {:?}", stmt); },
+        }
+
+        // Parameters without defaults must be given, and given ones must exist and be well-typed
+        assert!(parse(code, &pindex, &with(&[ ("input", "xyz") ])).is_ok());
+        assert!(matches!(parse(code, &pindex, &ParserOptions::bscript()), Err(Error::MissingParam{ name, .. }) if name == "input"));
+        assert!(matches!(parse(code, &pindex, &with(&[ ("input", "xyz"), ("j", "1") ])), Err(Error::UnknownParam{ name }) if name == "j"));
+        assert!(matches!(parse(code, &pindex, &with(&[ ("input", "xyz"), ("k", "ten") ])), Err(Error::IllegalParamValue{ name, .. }) if name == "k"));
+    }
+
+    /// Tests that calls may only be given a positive timeout.
+    #[test]
+    fn test_timeout() {
+        let pindex: PackageIndex = create_package_index();
+        match parse("@timeout(300)\nprintln(42);\n", &pindex, &ParserOptions::bscript()) {
+            Ok(program) => match &program.block.stmts[0] {
+                Stmt::Expr{ expr: Expr::Call{ timeout, .. }, .. } => assert_eq!(*timeout, Some(300)),
+                stmt                                              => { panic!("Expected a call statement, got: {:?}", stmt); },
+            },
+            Err(err) => { panic!("Failed to parse timeout: {}", err); },
+        }
+        assert!(parse("@timeout(0)\nprintln(42);\n", &pindex, &ParserOptions::bscript()).is_err());
+        assert!(parse("@timeout(-5)\nprintln(42);\n", &pindex, &ParserOptions::bscript()).is_err());
+    }
+
+    /// Tests that snippets are only incomplete if they leave a bracket, string or comment open.
+    #[test]
+    fn test_is_complete() {
+        // Complete snippets, including brackets that only appear in strings or comments (and unbalanced closing brackets, which are the compiler's problem)
+        for code in [ "", "let x := 1;", "println(\"(\");", "// (", "/* { */ println(1);", "let s := \"[\"; // {", ")" ] {
+            assert!(is_complete(code), "Snippet {:?} was reported as incomplete", code);
+        }
+        // Incomplete snippets
+        for code in [ "(", "func f() {", "let xs := [1, 2,", "println(\"abc", "/* not done", "if (true) {\n    println(\")\");\n" ] {
+            assert!(!is_complete(code), "Snippet {:?} was reported as complete", code);
+        }
+    }
 }
 
 
@@ -91,7 +232,15 @@ pub mod tests {
 #[derive(Clone, Debug)]
 pub struct ParserOptions {
     /// The language the compiler will parse (i.e., BraneScript or Bakery).
-    pub lang: Language,
+    pub lang       : Language,
+    /// The edition of the language, which determines the reserved keywords.
+    pub edition    : Edition,
+    /// The vocabulary with which Bakery sentences are read (ignored for BraneScript).
+    pub vocabulary : Vocabulary,
+    /// The locations known in the instance for which we compile, if available. If given, the compiler checks that calls are only pinned or restricted to these.
+    pub locations  : Option<HashSet<Location>>,
+    /// The values given to the parameters of the program, by name, as written on the command line (e.g., `10` or `Data<"xyz">`).
+    pub params     : HashMap<String, String>,
 }
 
 impl ParserOptions {
@@ -101,11 +250,15 @@ impl ParserOptions {
     /// - `lang`: The language which the compiler will parse.
     /// 
     /// # Returns
-    /// A new ParserOptions with the given settings.
+    /// A new ParserOptions with the given settings. It parses the default edition with the default (English) vocabulary (see `ParserOptions::with_edition()` and `ParserOptions::with_vocabulary()` to change that).
     #[inline]
     pub fn new(lang: Language) -> Self {
         Self {
             lang,
+            edition    : Edition::default(),
+            vocabulary : Vocabulary::default(),
+            locations  : None,
+            params     : HashMap::new(),
         }
     }
 
@@ -116,7 +269,11 @@ impl ParserOptions {
     #[inline]
     pub fn bscript() -> Self {
         Self {
-            lang : Language::BraneScript,
+            lang       : Language::BraneScript,
+            edition    : Edition::default(),
+            vocabulary : Vocabulary::default(),
+            locations  : None,
+            params     : HashMap::new(),
         }
     }
 
@@ -127,9 +284,188 @@ impl ParserOptions {
     #[inline]
     pub fn bakery() -> Self {
         Self {
-            lang : Language::Bakery,
+            lang       : Language::Bakery,
+            edition    : Edition::default(),
+            vocabulary : Vocabulary::default(),
+            locations  : None,
+            params     : HashMap::new(),
+        }
+    }
+
+
+
+    /// Changes the edition of the language to parse.
+    /// 
+    /// # Arguments
+    /// - `edition`: The Edition to parse.
+    /// 
+    /// # Returns
+    /// The same ParserOptions, but now parsing the given edition.
+    #[inline]
+    pub fn with_edition(mut self, edition: Edition) -> Self {
+        self.edition = edition;
+        self
+    }
+
+    /// Changes the vocabulary with which Bakery sentences are read.
+    /// 
+    /// # Arguments
+    /// - `vocabulary`: The Vocabulary that maps verbs to functions and lists the filler words.
+    /// 
+    /// # Returns
+    /// The same ParserOptions, but now reading sentences with the given vocabulary.
+    #[inline]
+    pub fn with_vocabulary(mut self, vocabulary: Vocabulary) -> Self {
+        self.vocabulary = vocabulary;
+        self
+    }
+
+    /// Sets the locations known in the instance for which we compile, such that the compiler can check location annotations.
+    /// 
+    /// # Arguments
+    /// - `locations`: The names of the locations in the instance.
+    /// 
+    /// # Returns
+    /// The same ParserOptions, but now rejecting calls that are pinned or restricted to unknown locations.
+    #[inline]
+    pub fn with_locations<L: Into<Location>>(mut self, locations: impl IntoIterator<Item = L>) -> Self {
+        self.locations = Some(locations.into_iter().map(|l| l.into()).collect());
+        self
+    }
+
+    /// Sets the values of the parameters that the program declares (i.e., `param <name>: <type>;`).
+    /// 
+    /// # Arguments
+    /// - `params`: The values of the parameters by name, as written on the command line (e.g., `10` or `Data<"xyz">`).
+    /// 
+    /// # Returns
+    /// The same ParserOptions, but now binding the given parameters. Parameters that are not given fall back to their default value.
+    #[inline]
+    pub fn with_params(mut self, params: HashMap<String, String>) -> Self {
+        self.params = params;
+        self
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Strips the quotes around the given string, if it has any.
+#[inline]
+fn unquote(raw: &str) -> &str {
+    if raw.len() >= 2 && raw.starts_with('"') && raw.ends_with('"') { &raw[1..raw.len() - 1] } else { raw }
+}
+
+/// Writes the given literal the way it would be given as a parameter value on the command line.
+/// 
+/// # Arguments
+/// - `literal`: The Literal to write.
+/// 
+/// # Returns
+/// The literal's value as a string.
+fn literal_to_param(literal: &Literal) -> String {
+    match literal {
+        Literal::Null{ .. }              => "null".into(),
+        Literal::Boolean{ value, .. }    => value.to_string(),
+        Literal::Integer{ value, .. }    => value.to_string(),
+        Literal::Real{ value, .. }       => value.to_string(),
+        Literal::String{ value, .. }     => value.clone(),
+        Literal::Semver{ value, .. }     => value.clone(),
+        Literal::Void{ .. }              => String::new(),
+    }
+}
+
+/// Parses the value given for a parameter as an expression of the parameter's type.
+/// 
+/// # Arguments
+/// - `name`: The name of the parameter.
+/// - `data_type`: The type of the parameter.
+/// - `raw`: The value as given on the command line. Strings may optionally be quoted, and datasets are given as either `Data<"<name>">` or just their name.
+/// - `range`: The range of the parameter's declaration, which we use for the resulting expression.
+/// 
+/// # Returns
+/// An expression that evaluates to the given value.
+/// 
+/// # Errors
+/// This function errors if the value could not be parsed as the parameter's type.
+fn param_to_expr(name: &str, data_type: &DataType, raw: &str, range: &TextRange) -> Result<Expr, Error> {
+    let raw: &str = raw.trim();
+    let literal: Literal = match data_type {
+        DataType::Boolean => match bool::from_str(raw) {
+            Ok(value) => Literal::Boolean{ value, range: range.clone() },
+            Err(_)    => { return Err(Error::IllegalParamValue{ name: name.into(), data_type: data_type.clone(), raw: raw.into() }); },
+        },
+        DataType::Integer => match i64::from_str(raw) {
+            Ok(value) => Literal::Integer{ value, range: range.clone() },
+            Err(_)    => { return Err(Error::IllegalParamValue{ name: name.into(), data_type: data_type.clone(), raw: raw.into() }); },
+        },
+        DataType::Real => match f64::from_str(raw) {
+            Ok(value) => Literal::Real{ value, range: range.clone() },
+            Err(_)    => { return Err(Error::IllegalParamValue{ name: name.into(), data_type: data_type.clone(), raw: raw.into() }); },
+        },
+        DataType::String => Literal::String{ value: unquote(raw).into(), range: range.clone() },
+
+        // Datasets are written as instances of the builtin Data class
+        DataType::Class(class) if class == "Data" => {
+            let dataset: &str = unquote(raw.strip_prefix("Data<").and_then(|raw| raw.strip_suffix('>')).unwrap_or(raw));
+            if dataset.is_empty() { return Err(Error::IllegalParamValue{ name: name.into(), data_type: data_type.clone(), raw: raw.into() }); }
+            return Ok(Expr::new_instance(Identifier::new("Data".into(), range.clone()), vec![ PropertyExpr {
+                name  : Identifier::new("name".into(), range.clone()),
+                value : Box::new(Expr::Literal{ literal: Literal::String{ value: dataset.into(), range: range.clone() } }),
+
+                range : range.clone(),
+            } ], range.clone()));
+        },
+
+        _ => { unreachable!(); },
+    };
+    Ok(Expr::Literal{ literal })
+}
+
+/// Binds the parameters declared in the toplevel of the given program to the given values, falling back to their defaults where no value is given.
+/// 
+/// # Arguments
+/// - `program`: The Program of which to bind the parameters. Its list of parameters is populated along the way.
+/// - `params`: The values given for the parameters by name, as written on the command line.
+/// 
+/// # Errors
+/// This function errors if a parameter has a type that cannot be given, a parameter without a default is not given, a given value is not of its parameter's type or a value is given for a parameter that is not declared.
+fn bind_params(program: &mut Program, params: &HashMap<String, String>) -> Result<(), Error> {
+    for stmt in program.block.stmts.iter_mut() {
+        if let Stmt::LetAssign{ name, value, param: Some(data_type), range, .. } = stmt {
+            // Only simple values and datasets can be written on the command line
+            let supported: bool = match data_type {
+                DataType::Boolean | DataType::Integer | DataType::Real | DataType::String => true,
+                DataType::Class(class)                                                       => class == "Data",
+                _                                                                             => false,
+            };
+            if !supported { return Err(Error::UnsupportedParamType{ name: name.value.clone(), data_type: data_type.clone(), range: range.clone() }); }
+
+            // Replace the default with the given value, if any
+            let default: Option<String> = if let Expr::Literal{ literal } = value { Some(literal_to_param(literal)) } else { None };
+            match params.get(&name.value) {
+                Some(raw) => { *value = param_to_expr(&name.value, data_type, raw, range)?; },
+                None      => if default.is_none() { return Err(Error::MissingParam{ name: name.value.clone(), range: range.clone() }); },
+            }
+
+            // Remember it for the workflow
+            program.params.push(Param {
+                name      : name.value.clone(),
+                data_type : data_type.clone(),
+                default,
+            });
         }
     }
+
+    // Make sure that all given values were used
+    let mut names: Vec<&String> = params.keys().collect();
+    names.sort();
+    if let Some(name) = names.into_iter().find(|name| !program.params.iter().any(|param| &param.name == *name)) {
+        return Err(Error::UnknownParam{ name: name.clone() });
+    }
+    Ok(())
 }
 
 
@@ -137,6 +473,39 @@ impl ParserOptions {
 
 
 /***** LIBRARY *****/
+/// Determines whether the given snippet is complete, i.e., whether it does not leave a bracket, string or comment open.
+/// 
+/// This is used by interactive frontends to decide whether to run a snippet or to let the user continue it on the next line. Brackets inside strings and comments are ignored.
+/// 
+/// # Arguments
+/// - `source`: The snippet to check.
+/// 
+/// # Returns
+/// True if the snippet may be run as-is (even if it is not valid), or false if the user is likely still typing it.
+pub fn is_complete(source: &str) -> bool {
+    // Scan the tokens, ignoring whatever is in strings and comments
+    let tokens: Vec<Token> = match scanner::scan_tokens(Span::from(source)) {
+        Ok((_, tokens)) => tokens,
+
+        // Strings and multi-line comments commit to being closed, so if they fail at the end of the input (or right after the `/*`), they are still open
+        Err(nom::Err::Error(err)) | Err(nom::Err::Failure(err)) => {
+            return match err.errors.first() {
+                Some((span, _)) => { let offset: usize = span.location_offset(); offset < source.len() && !source[..offset].ends_with("/*") },
+                None            => true,
+            };
+        },
+        Err(nom::Err::Incomplete(_)) => { return false; },
+    };
+
+    // Otherwise, only open brackets make it incomplete
+    let depth: i64 = tokens.iter().map(|token| match token {
+        Token::LeftBrace(_) | Token::LeftBracket(_) | Token::LeftParen(_)    => 1,
+        Token::RightBrace(_) | Token::RightBracket(_) | Token::RightParen(_) => -1,
+        _                                                                    => 0,
+    }).sum();
+    depth <= 0
+}
+
 /// Parses the given reader to a BraneScript / Bakery Program.
 /// 
 /// # Generic arguments
@@ -156,16 +525,20 @@ pub fn parse<S: AsRef<str>>(source: S, pindex: &PackageIndex, options: &ParserOp
     let source: &str = source.as_ref();
 
     // Run that through the scanner
-    let (remain, tokens): (Span, Vec<Token>) = match scanner::scan_tokens(Span::from(source)) {
+    let (remain, mut tokens): (Span, Vec<Token>) = match scanner::scan_tokens(Span::from(source)) {
         Ok(res)                                             => res,
         Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => { return Err(Error::ScanError{ err: errors::convert_scanner_error(Span::from(source), e) }); },
         Err(err)                                            => { return Err(Error::ScannerError{ err: format!("{}", err) }); },
     };
     if remain.input_len() > 0 && !remain.fragment().to_string().trim().is_empty() { return Err(Error::LeftoverSourceError); }
+    scanner::promote_keywords(&mut tokens, options.edition);
+    if let Some(span) = scanner::find_reserved(&tokens, options.edition) {
+        return Err(Error::ReservedKeyword{ keyword: span.fragment().to_string(), edition: options.edition, range: span.into() });
+    }
 
     // Run the tokens through the parser (depending on the selected language)
-    let tks = Tokens::new(&tokens);
-    let (remain, ast): (Tokens, Program) = match options.lang {
+    let tks = Tokens::new(&tokens).with_lang(options.lang);
+    let (remain, mut ast): (Tokens, Program) = match options.lang {
         Language::BraneScript => match bscript::parse_ast(tks) {
             Ok(ast) => ast,
 
@@ -177,8 +550,11 @@ pub fn parse<S: AsRef<str>>(source: S, pindex: &PackageIndex, options: &ParserOp
             Err(err) => { return Err(Error::ParserError { lang: Language::BraneScript, err: format!("{}", err) }); },
         },
 
-        Language::Bakery => match bakery::parse_ast(tks, pindex.clone()) {
-            Ok(ast) => ast,
+        Language::Bakery => match bakery::parse_ast(tks) {
+            Ok((remain, ast)) => match bakery::resolve_sentences(ast, pindex, &options.vocabulary) {
+                Ok(ast)  => (remain, ast),
+                Err(err) => { return Err(Error::SentenceError{ err }); },
+            },
 
             Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => {
                 // Match the EOF-error
@@ -190,6 +566,9 @@ pub fn parse<S: AsRef<str>>(source: S, pindex: &PackageIndex, options: &ParserOp
     };
     if remain.input_len() > 0 { return Err(Error::LeftoverTokensError{ lang: options.lang }); }
 
+    // Give the parameters their values
+    bind_params(&mut ast, &options.params)?;
+
     // Alright, that's a parsed program
     Ok(ast)
 }
@@ -131,6 +131,8 @@ pub enum DataType {
     // Ref(Box<DataType>),
     /// Arrays (i.e., a memory area divided into homogeneous types)
     Array(Box<DataType>),
+    /// Maps (i.e., a dictionary from string keys to homogeneous types)
+    Map(Box<DataType>),
     /// Functions (i.e., executable pieces of code)
     Function(Box<FunctionSignature>),
     /// Classes (i.e., a memory area divided into heterogeneous types)
@@ -166,6 +168,8 @@ impl DataType {
 
             // Trivial conversions
             (Array(t1), Array(t2)) => t1.coercible_to(t2),
+            (Map(t1), Map(t2))     => t1.coercible_to(t2),
+            (Map(_), Array(_))     => false,
             (t1, Array(t2))        => t1.coercible_to(t2),
             (Class(n1), Class(n2)) => {
                 // We do allow data to be demoted to intermediate results
@@ -214,6 +218,10 @@ impl From<&str> for DataType {
         } else if value.len() >= 2 && &value[value.len() - 2..] == "[]" {
             return Self::Array(Box::new(Self::from(&value[..value.len() - 2])));
         }
+        // Maps are done recursively too
+        if value.len() >= 5 && value.get(..4).map(|p| p.eq_ignore_ascii_case("map<")).unwrap_or(false) && value.ends_with('>') {
+            return Self::Map(Box::new(Self::from(&value[4..value.len() - 1])));
+        }
 
         // Otherwise, match literals & classes
         use DataType::*;
@@ -271,6 +279,7 @@ impl Display for DataType {
             Semver  => write!(f, "Semver"),
 
             Array(t)    => write!(f, "Array<{}>", t),
+            Map(t)      => write!(f, "Map<{}>", t),
             Function(s) => write!(f, "Func<{}>", s),
             Class(n)    => write!(f, "Class<{}>", n),
         }
@@ -15,10 +15,12 @@
 
 use std::error::Error;
 use std::fmt::{Display, Formatter, Result as FResult};
+use std::path::PathBuf;
 
 use nom::error::{VerboseError, VerboseErrorKind};
 
-use crate::spec::{Language, TextRange};
+use crate::spec::{Edition, Language, TextRange};
+use crate::data_type::DataType;
 use crate::scanner::{Span, Tokens};
 
 
@@ -250,6 +252,8 @@ pub enum SymbolTableError {
     DuplicateVariable{ name: String, existing: TextRange, got: TextRange },
     /// A given field (property or method) already existing in the given class.
     DuplicateField{ c_name: String, name: String, existing: TextRange, got: TextRange },
+    /// A given function has an alias that another function in the SymbolTable already has, which would make calls by that alias ambiguous.
+    DuplicateAlias{ alias: String, name: String, other: String, existing: TextRange, got: TextRange },
 }
 
 impl Display for SymbolTableError {
@@ -261,6 +265,7 @@ impl Display for SymbolTableError {
             DuplicateClass{ name, .. }         => write!(f, "Duplicate definition of class '{}'", name),
             DuplicateVariable{ name, .. }      => write!(f, "Duplicate definition of variable '{}'", name),
             DuplicateField{ c_name, name, .. } => write!(f, "Duplicate definition of field '{}' in class '{}'", name, c_name),
+            DuplicateAlias{ alias, name, other, .. } => write!(f, "Function '{}' cannot be called by alias '{}' because function '{}' already is", name, alias, other),
         }
     }
 }
@@ -274,6 +279,8 @@ impl Error for SymbolTableError {}
 pub enum LanguageParseError {
     /// Encountered an unknown language ID.
     UnknownLanguageId{ raw: String },
+    /// Encountered an unknown language edition.
+    UnknownEdition{ raw: String },
 }
 
 impl Display for LanguageParseError {
@@ -281,6 +288,7 @@ impl Display for LanguageParseError {
         use LanguageParseError::*;
         match self {
             UnknownLanguageId{ raw } => write!(f, "Unknown language ID '{}'", raw),
+            UnknownEdition{ raw }    => write!(f, "Unknown language edition '{}' (options are '2022', '2026' or 'latest')", raw),
         }
     }
 }
@@ -292,6 +300,8 @@ impl Error for LanguageParseError {}
 pub enum PatternError {
     /// The given pattern was unknown
     UnknownPattern{ raw: String, range: TextRange },
+    /// A sentence started with a literal instead of a verb.
+    NoVerb{ range: TextRange },
 }
 
 impl Display for PatternError {
@@ -300,12 +310,35 @@ impl Display for PatternError {
         use PatternError::*;
         match self {
             UnknownPattern{ raw, .. } => write!(f, "Pattern '{}' is unknown (are you missing a package import?)", raw),
+            NoVerb{ range }           => write!(f, "Sentence (at {}) starts with a literal instead of a verb (sentences must start with a function name or a verb from the vocabulary)", range.start),
         }
     }
 }
 
 impl Error for PatternError {}
 
+/// Defines errors that occur when loading a vocabulary file.
+#[derive(Debug)]
+pub enum VocabularyError {
+    /// Failed to open the vocabulary file.
+    FileOpenError{ path: PathBuf, err: std::io::Error },
+    /// Failed to parse the vocabulary file.
+    FileParseError{ path: PathBuf, err: serde_yaml::Error },
+}
+
+impl Display for VocabularyError {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use VocabularyError::*;
+        match self {
+            FileOpenError{ path, err }  => write!(f, "Failed to open vocabulary file '{}': {}", path.display(), err),
+            FileParseError{ path, err } => write!(f, "Failed to parse vocabulary file '{}' as YAML: {}", path.display(), err),
+        }
+    }
+}
+
+impl Error for VocabularyError {}
+
 
 
 /// Defines errors that occur in the topmost process of conveting raw readers into Programs.
@@ -326,6 +359,19 @@ pub enum ParseError {
     Eof{ lang: Language, err: String },
     /// Not all tokens were parsed (indicating an error).
     LeftoverTokensError{ lang: Language },
+    /// An identifier was used that is a reserved keyword in the selected edition.
+    ReservedKeyword{ keyword: String, edition: Edition, range: TextRange },
+    /// A Bakery sentence could not be converted to a function call.
+    SentenceError{ err: PatternError },
+
+    /// A value was given for a parameter that the program does not declare.
+    UnknownParam{ name: String },
+    /// A parameter without a default value was not given a value.
+    MissingParam{ name: String, range: TextRange },
+    /// A parameter was declared with a type that cannot be given a value from the outside.
+    UnsupportedParamType{ name: String, data_type: DataType, range: TextRange },
+    /// The value given for a parameter could not be parsed as its type.
+    IllegalParamValue{ name: String, data_type: DataType, raw: String },
 }
 
 impl Display for ParseError {
@@ -341,6 +387,14 @@ impl Display for ParseError {
             ParserError{ lang, err }    => write!(f, "{} parse error: {}", lang, err),
             Eof{ lang, err }            => write!(f, "{} parse error: reached end-of-file unexpectedly ({})", lang, err),
             LeftoverTokensError{ lang } => write!(f, "{} parse error: not all input could be parsed", lang),
+
+            ReservedKeyword{ keyword, edition, range } => write!(f, "Syntax error: '{}' (at {}) is a reserved keyword in edition {} and cannot be used as an identifier", keyword, range.start, edition),
+            SentenceError{ err }                       => write!(f, "Bakery parse error: {}", err),
+
+            UnknownParam{ name }                           => write!(f, "Unknown parameter '{}' (the program does not declare it)", name),
+            MissingParam{ name, range }                    => write!(f, "Parameter '{}' (at {}) has no default value and must be given", name, range.start),
+            UnsupportedParamType{ name, data_type, range } => write!(f, "Parameter '{}' (at {}) has type {}, but only booleans, integers, reals, strings and Data can be given as parameters", name, range.start, data_type),
+            IllegalParamValue{ name, data_type, raw }      => write!(f, "Illegal value '{}' for parameter '{}' of type {}", raw, name, data_type),
         }
     }
 }
@@ -24,17 +24,19 @@ pub mod spec;
 pub mod data_type;
 pub mod location;
 pub mod symbol_table;
+pub mod vocabulary;
 pub mod compiler;
 
 
 // Bring some stuff into the crate namespace
 pub use errors::ParseError as Error;
-pub use spec::{Language, TextPos, TextRange};
+pub use spec::{Edition, Language, TextPos, TextRange};
 pub use data_type::DataType;
 pub use location::Location;
 pub use symbol_table::SymbolTable;
+pub use vocabulary::Vocabulary;
 pub use parser::ast;
-pub use compiler::{parse, ParserOptions};
+pub use compiler::{is_complete, parse, ParserOptions};
 
 
 // Some useful, crate-local macros
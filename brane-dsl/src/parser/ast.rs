@@ -20,7 +20,7 @@ use std::str::FromStr;
 use enum_debug::EnumDebug;
 use specifications::version::{ParseError, Version};
 
-use crate::spec::{TextPos, TextRange};
+use crate::spec::{RetryPolicy, TextPos, TextRange};
 use crate::data_type::DataType;
 use crate::location::AllowedLocations;
 use crate::symbol_table::{ClassEntry, FunctionEntry, SymbolTable, SymbolTableEntry, VarEntry};
@@ -58,7 +58,9 @@ pub trait Node: Clone + Debug {
 #[derive(Clone, Debug)]
 pub struct Program {
     /// The toplevel program is simply a code block with global variables.
-    pub block : Block,
+    pub block  : Block,
+    /// The parameters declared by the program (i.e., `param <name>: <type>;`), in order of declaration.
+    pub params : Vec<Param>,
 }
 
 impl Node for Program {
@@ -69,6 +71,19 @@ impl Node for Program {
 
 
 
+/// Defines a parameter of a program, which may be given a value when the program is compiled.
+#[derive(Clone, Debug)]
+pub struct Param {
+    /// The name of the parameter.
+    pub name      : String,
+    /// The type of the parameter.
+    pub data_type : DataType,
+    /// The default value of the parameter, written as it would be given on the command line. If omitted, the parameter must always be given.
+    pub default   : Option<String>,
+}
+
+
+
 /// Defines a code block (i.e., a series of statements).
 #[derive(Clone, Debug)]
 pub struct Block {
@@ -160,6 +175,10 @@ pub enum Stmt {
         params : Vec<Identifier>,
         /// The code to execute when running this function.
         code   : Box<Block>,
+        /// The documentation comments (`///`) written above the function, if any.
+        docs   : Option<String>,
+        /// Whether the function is annotated with `#[test]`, i.e., is a unit test of the workflow.
+        test   : bool,
 
         /// Reference to the symbol table entry this function generates.
         st_entry : Option<Rc<RefCell<FunctionEntry>>>,
@@ -175,6 +194,8 @@ pub enum Stmt {
         props   : Vec<Property>,
         /// The methods belonging to this class, as a vector of function definitions.
         methods : Vec<Box<Stmt>>,
+        /// The documentation comments (`///`) written above the class, if any.
+        docs    : Option<String>,
 
         /// Reference to the symbol table entry this class generates.
         st_entry     : Option<Rc<RefCell<ClassEntry>>>,
@@ -257,12 +278,14 @@ pub enum Stmt {
         range : TextRange,
     },
 
-    /// Defines a variable definition (i.e., `let <name> := <expr>`).
+    /// Defines a variable definition (i.e., `let <name> := <expr>`), or the declaration of a program parameter (i.e., `param <name>: <type> [:= <literal>]`).
     LetAssign {
         /// The name of the variable referenced.
         name  : Identifier,
         /// The expression that gives a value to the assignment.
         value : Expr,
+        /// If this declares a program parameter, the type declared for it.
+        param : Option<DataType>,
 
         /// Reference to the variable to which the let-assign writes.
         st_entry : Option<Rc<RefCell<VarEntry>>>,
@@ -327,16 +350,20 @@ impl Stmt {
     /// - `ident`: The name of the function, as an identifier.
     /// - `params`: The parameters of the function, as identifiers.
     /// - `code`: The code to execute when running this function.
+    /// - `docs`: The documentation comments written above the function, if any.
+    /// - `test`: Whether the function is annotated with `#[test]`.
     /// - `range`: The TextRange that relates this node to the source text.
     /// 
     /// # Returns
     /// A new `Stmt::FuncDef` instance.
     #[inline]
-    pub fn new_funcdef(ident: Identifier, params: Vec<Identifier>, code: Box<Block>, range: TextRange) -> Self {
+    pub fn new_funcdef(ident: Identifier, params: Vec<Identifier>, code: Box<Block>, docs: Option<String>, test: bool, range: TextRange) -> Self {
         Self::FuncDef {
             ident,
             params,
             code,
+            docs,
+            test,
 
             st_entry : None,
 
@@ -350,16 +377,18 @@ impl Stmt {
     /// - `ident`: The name of the class, as an identifier.
     /// - `props`: The properties of the class, as (identifier, type) pairs.
     /// - `methods`: The methods belonging to this class, as a vector of function definitions.
+    /// - `docs`: The documentation comments written above the class, if any.
     /// - `range`: The TextRange that relates this node to the source text.
     /// 
     /// # Returns
     /// A new `Stmt::ClassDef` instance.
     #[inline]
-    pub fn new_classdef(ident: Identifier, props : Vec<Property>, methods : Vec<Box<Stmt>>, range: TextRange) -> Self {
+    pub fn new_classdef(ident: Identifier, props : Vec<Property>, methods : Vec<Box<Stmt>>, docs: Option<String>, range: TextRange) -> Self {
         Self::ClassDef {
             ident,
             props,
             methods,
+            docs,
 
             st_entry     : None,
             symbol_table : SymbolTable::new(),
@@ -423,6 +452,30 @@ impl Stmt {
         Self::LetAssign {
             name,
             value,
+            param : None,
+
+            st_entry : None,
+
+            range,
+        }
+    }
+
+    /// Creates a new LetAssign node that declares a program parameter, with some auxillary fields set to empty.
+    /// 
+    /// # Arguments
+    /// - `name`: The identifier of the parameter.
+    /// - `data_type`: The type declared for the parameter.
+    /// - `default`: The literal that gives the parameter its default value, if any. If omitted, the value is left empty until the parameter is bound.
+    /// - `range`: The TextRange that relates this node to the source text.
+    /// 
+    /// # Returns
+    /// A new `Stmt::LetAssign` instance.
+    #[inline]
+    pub fn new_param(name: Identifier, data_type: DataType, default: Option<Literal>, range: TextRange) -> Self {
+        Self::LetAssign {
+            name,
+            value : default.map(|literal| Expr::Literal{ literal }).unwrap_or(Expr::Empty{}),
+            param : Some(data_type),
 
             st_entry : None,
 
@@ -578,6 +631,10 @@ pub enum Expr {
         st_entry  : Option<Rc<RefCell<FunctionEntry>>>,
         /// The locations where this Call is allowed to run based on the location of the datasets.
         locations : AllowedLocations,
+        /// How often to retry this Call if it fails, if at all. Will only ever be used if this call is an external call.
+        retry     : Option<RetryPolicy>,
+        /// The time (in seconds) after which this Call is killed if it has not completed yet, if any. Will only ever be used if this call is an external call.
+        timeout   : Option<u64>,
         /// If this call takes in Data or IntermediateResult, then this field will list their names. Will only ever be the case if this call is an external call.
         input     : Vec<Data>,
         /// The intermediate result that this Call creates, if any. Will only ever be the case if this call is an external call.
@@ -608,6 +665,16 @@ pub enum Expr {
         /// The range of the index-expression in the source text.
         range  : TextRange,
     },
+    /// A map expression.
+    Map {
+        /// The (key, value) pairs in the map. The keys evaluate to strings.
+        entries   : Vec<(Box<Expr>, Box<Expr>)>,
+        /// The type of the values in the Map.
+        data_type : DataType,
+
+        /// The range of the map-expression in the source text.
+        range  : TextRange,
+    },
     /// Bakery-specific Pattern expression.
     Pattern {
         /// The expressions in this pattern.
@@ -728,17 +795,21 @@ impl Expr {
     /// - `args`: The arguments to call it with.
     /// - `range`: The TextRange that relates this node to the source text.
     /// - `locations`: The list of locations (as an AllowedLocation) where the call may be executed.
+    /// - `retry`: How often to retry the call if it fails, if at all.
+    /// - `timeout`: The time (in seconds) after which the call is killed, if any.
     /// 
     /// # Returns
     /// A new `Expr::Call` instance.
     #[inline]
-    pub fn new_call(expr: Box<Expr>, args: Vec<Box<Expr>>, range: TextRange, locations: AllowedLocations) -> Self {
+    pub fn new_call(expr: Box<Expr>, args: Vec<Box<Expr>>, range: TextRange, locations: AllowedLocations, retry: Option<RetryPolicy>, timeout: Option<u64>) -> Self {
         Self::Call {
             expr,
             args,
 
             st_entry : None,
             locations,
+            retry,
+            timeout,
             input    : vec![],
             result   : None,
 
@@ -780,6 +851,21 @@ impl Expr {
         }
     }
 
+    /// Creates a new Map expression with some auxillary fields set to empty.
+    /// 
+    /// # Arguments
+    /// - `entries`: The list of (key, value) pairs that make up this Map.
+    /// - `range`: The TextRange that links this Map to the source text.
+    #[inline]
+    pub fn new_map(entries: Vec<(Box<Expr>, Box<Expr>)>, range: TextRange) -> Self {
+        Self::Map {
+            entries,
+            data_type : DataType::Any,
+
+            range,
+        }
+    }
+
 
 
     /// Creates a new UnaOp expression with some auxillary fields set to empty.
@@ -908,6 +994,7 @@ impl Node for Expr {
             Call{ range, .. }       => range,
             Array{ range, .. }      => range,
             ArrayIndex{ range, .. } => range,
+            Map{ range, .. }        => range,
             Pattern{ range, .. }    => range,
 
             UnaOp{ range, .. } => range,
@@ -1,269 +1,338 @@
-// use super::ast::{Expr, Ident, Operator, Stmt, UnOp};
-// use crate::parser::{identifier, literal, operator, pattern};
-// use crate::scanner::{Token, Tokens};
-// use crate::tag_token;
-// use nom::{branch, combinator as comb, multi, sequence as seq};
-// use nom::{
-//     error::{ContextError, ErrorKind, ParseError, VerboseError},
-//     Needed,
-// };
-// use nom::{IResult, Parser};
-use crate::scanner::Tokens;
-use crate::spec::TextRange;
-use nom::IResult;
-use nom::error::VerboseError;
+//  BAKERY.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 11:13:15
+//  Last edited:
+//    16 Oct 2026, 11:13:15
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements the Bakery parser. Bakery accepts every BraneScript
+//!   statement, but additionally allows functions to be called with
+//!   natural-language sentences, e.g.:
+//!   ```bakery
+//!   import hello_world;
+//!   let greeting := hello_world();
+//!   say the greeting to "Amy";
+//!   ```
+//!   Sentences are parsed as Pattern expressions, which are resolved to
+//!   calls using a Vocabulary once the whole program has been parsed.
+// 
+
+use std::collections::HashMap;
+
+use log::debug;
+use nom::error::{ContextError, ParseError, VerboseError};
+use nom::{branch, combinator as comb, multi, sequence as seq};
+use nom::{IResult, Parser};
+
 use specifications::package::PackageIndex;
-use super::ast::{Block, Expr, Literal, Program, Stmt};
-// use std::num::NonZeroUsize;
-
-///
-///
-///
-pub fn parse_ast(
-    _input: Tokens,
-    _package_index: PackageIndex,
-) -> IResult<Tokens, Program, VerboseError<Tokens>> {
-    // For now, let's be really naive
-    Ok((Tokens::new(&[]), Program {
-        block : Block::new(
-            vec![ Stmt::new_expr(Expr::Literal{ literal: Literal::String{ value: "<TODO>".into(), range : TextRange::none() } }, TextRange::none()) ],
-            TextRange::none(),
-        ),
-    }))
-    // comb::all_consuming(multi::many0(parse_stmt))
-    //     .parse(input)
-    //     .map(|(tokens, program)| {
-    //         let program = pattern::resolve_patterns(program, &package_index)
-    //             .map_err(|_| nom::Err::Incomplete(Needed::Size(NonZeroUsize::new(1).unwrap())))?;
-
-    //         Ok((tokens, program))
-    //     })?
+
+pub use crate::errors::PatternError as Error;
+use super::{enter_pp, exit_pp, wrap_pp};
+use super::ast::{Block, Expr, Identifier, Node, Program, Stmt};
+use super::{bscript, identifier, literal};
+use crate::spec::{Language, TextPos, TextRange};
+use crate::location::AllowedLocations;
+use crate::scanner::{Token, Tokens};
+use crate::tag_token;
+use crate::vocabulary::Vocabulary;
+
+
+/***** HELPER FUNCTIONS *****/
+/// Parses a single word of a sentence, which is either a literal or an identifier.
+/// 
+/// # Arguments
+/// - `input`: The token stream that will be parsed.
+/// 
+/// # Returns
+/// A pair of remaining tokens and the word as either a `Expr::Literal` or a `Expr::VarRef`.
+/// 
+/// # Errors
+/// This function may error if the tokens do not start with a literal or an identifier.
+fn word<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(
+    input: Tokens<'a>
+) -> IResult<Tokens, Expr, E> {
+    branch::alt((
+        comb::map(literal::parse,    |l| Expr::Literal{ literal: l }),
+        comb::map(identifier::parse, Expr::new_varref),
+    ))
+    .parse(input)
+}
+
+/// Parses a sentence, which is two or more words in a row.
+/// 
+/// # Arguments
+/// - `input`: The token stream that will be parsed.
+/// 
+/// # Returns
+/// A pair of remaining tokens and the sentence as an `Expr::Pattern`.
+/// 
+/// # Errors
+/// This function may error if the tokens do not start with at least two words.
+fn sentence<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(
+    input: Tokens<'a>
+) -> IResult<Tokens, Expr, E> {
+    let (r, (first, rest)) = seq::pair(word, multi::many1(word)).parse(input)?;
+
+    // Wrap them in a pattern
+    let range: TextRange = TextRange::new(first.start().clone(), rest[rest.len() - 1].end().clone());
+    let mut exprs: Vec<Box<Expr>> = Vec::with_capacity(1 + rest.len());
+    exprs.push(Box::new(first));
+    exprs.extend(rest.into_iter().map(Box::new));
+    Ok((r, Expr::Pattern{ exprs, range }))
+}
+
+
+
+/// Collects the verbs that packages define for their functions (as the prefix of their call pattern).
+/// 
+/// # Arguments
+/// - `pindex`: The PackageIndex with the packages to collect the verbs of.
+/// 
+/// # Returns
+/// A map of (lowercase) verbs to the name of the function they call.
+fn package_verbs(pindex: &PackageIndex) -> HashMap<String, String> {
+    let mut verbs: HashMap<String, String> = HashMap::new();
+    for info in pindex.packages.values() {
+        for (name, function) in &info.functions {
+            if let Some(prefix) = function.pattern.as_ref().and_then(|p| p.prefix.as_ref()) {
+                verbs.insert(prefix.to_lowercase(), name.clone());
+            }
+        }
+    }
+    verbs
+}
+
+/// Resolves a sentence to a call of the function named by its verb.
+/// 
+/// # Arguments
+/// - `exprs`: The words of the sentence.
+/// - `range`: The range of the sentence in the source text.
+/// - `verbs`: The verbs defined by packages, which are used if the vocabulary does not know a verb.
+/// - `vocabulary`: The Vocabulary that maps verbs to functions and lists the filler words.
+/// 
+/// # Returns
+/// A new `Expr::Call` that calls the function with the remaining (non-filler) words as arguments.
+/// 
+/// # Errors
+/// This function errors if the sentence starts with a literal.
+fn resolve_sentence(exprs: Vec<Box<Expr>>, range: TextRange, verbs: &HashMap<String, String>, vocabulary: &Vocabulary) -> Result<Expr, Error> {
+    let mut exprs = exprs.into_iter();
+
+    // The first word names the function
+    let verb: Identifier = match exprs.next().map(|e| *e) {
+        Some(Expr::VarRef{ name, .. }) => name,
+        Some(_)                        => { return Err(Error::NoVerb{ range }); },
+        None                           => { unreachable!(); },
+    };
+    let function: String = match vocabulary.function(&verb.value) {
+        Some(function) => function.into(),
+        None           => verbs.get(&verb.value.to_lowercase()).cloned().unwrap_or_else(|| verb.value.clone()),
+    };
+    debug!("Resolved sentence verb '{}' to function '{}'", verb.value, function);
+
+    // The rest are arguments, unless they are filler words
+    let args: Vec<Box<Expr>> = exprs.filter(|e| match &**e {
+        Expr::VarRef{ name, .. } => !vocabulary.is_filler(&name.value),
+        _                        => true,
+    }).collect();
+    Ok(Expr::new_call(
+        Box::new(Expr::new_varref(Identifier::new(function, verb.range))),
+        args,
+
+        range,
+        AllowedLocations::All,
+        None,
+        None,
+    ))
+}
+
+/// Resolves the sentences in the given block.
+/// 
+/// # Arguments
+/// - `block`: The Block to resolve the sentences in.
+/// - `verbs`: The verbs defined by packages.
+/// - `vocabulary`: The Vocabulary that maps verbs to functions and lists the filler words.
+/// 
+/// # Errors
+/// This function errors if any of the sentences could not be resolved.
+fn resolve_block(block: &mut Block, verbs: &HashMap<String, String>, vocabulary: &Vocabulary) -> Result<(), Error> {
+    for stmt in &mut block.stmts {
+        resolve_stmt(stmt, verbs, vocabulary)?;
+    }
+    Ok(())
+}
+
+/// Resolves the sentences in the given statement (and any nested statements).
+/// 
+/// # Arguments
+/// - `stmt`: The Stmt to resolve the sentences in.
+/// - `verbs`: The verbs defined by packages.
+/// - `vocabulary`: The Vocabulary that maps verbs to functions and lists the filler words.
+/// 
+/// # Errors
+/// This function errors if any of the sentences could not be resolved.
+fn resolve_stmt(stmt: &mut Stmt, verbs: &HashMap<String, String>, vocabulary: &Vocabulary) -> Result<(), Error> {
+    match stmt {
+        Stmt::Block{ block } => resolve_block(block, verbs, vocabulary),
+
+        Stmt::FuncDef{ code, .. }     => resolve_block(code, verbs, vocabulary),
+        Stmt::ClassDef{ methods, .. } => {
+            for method in methods {
+                resolve_stmt(method, verbs, vocabulary)?;
+            }
+            Ok(())
+        },
+
+        Stmt::If{ consequent, alternative, .. } => {
+            resolve_block(consequent, verbs, vocabulary)?;
+            if let Some(alternative) = alternative { resolve_block(alternative, verbs, vocabulary)?; }
+            Ok(())
+        },
+        Stmt::For{ initializer, increment, consequent, .. } => {
+            resolve_stmt(initializer, verbs, vocabulary)?;
+            resolve_stmt(increment, verbs, vocabulary)?;
+            resolve_block(consequent, verbs, vocabulary)
+        },
+        Stmt::While{ consequent, .. } => resolve_block(consequent, verbs, vocabulary),
+        Stmt::On{ block, .. }         => resolve_block(block, verbs, vocabulary),
+        Stmt::Parallel{ blocks, .. }  => {
+            for block in blocks {
+                resolve_stmt(block, verbs, vocabulary)?;
+            }
+            Ok(())
+        },
+
+        Stmt::LetAssign{ value, .. } |
+        Stmt::Assign{ value, .. }    |
+        Stmt::Expr{ expr: value, .. } => {
+            // Sentences only occur as the topmost expression of a statement
+            if let Expr::Pattern{ exprs, range } = value {
+                *value = resolve_sentence(std::mem::take(exprs), range.clone(), verbs, vocabulary)?;
+            }
+            Ok(())
+        },
+
+        Stmt::Import{ .. } | Stmt::Return{ .. } | Stmt::Empty{} => Ok(()),
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Parses a stream of tokens into a full Bakery AST.
+/// 
+/// Any sentences in the AST are still Pattern expressions; use `resolve_sentences()` to turn them into calls.
+/// 
+/// # Arguments
+/// - `input`: The token stream that will be parsed.
+/// 
+/// # Returns
+/// A pair of remaining tokens and the parsed program.
+/// 
+/// # Errors
+/// This function may error if the tokens do not comprise valid Bakery.
+#[inline]
+pub fn parse_ast(input: Tokens) -> IResult<Tokens, Program, VerboseError<Tokens>> {
+    // Bakery is BraneScript with sentences, which the BraneScript parser accepts if we tell it to
+    bscript::parse_ast(input.with_lang(Language::Bakery))
 }
 
-// ///
-// ///
-// ///
-// pub fn parse_stmt<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(
-//     input: Tokens<'a>
-// ) -> IResult<Tokens, Stmt, E> {
-//     if input.tok.is_empty() {
-//         return Err(nom::Err::Error(nom::error_position!(input, ErrorKind::Tag)));
-//     }
-
-//     branch::alt((import_stmt, assign_stmt, return_stmt, expr_stmt)).parse(input)
-// }
-
-// ///
-// ///
-// ///
-// pub fn assign_stmt<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(
-//     input: Tokens<'a>
-// ) -> IResult<Tokens, Stmt, E> {
-//     comb::map(
-//         seq::terminated(
-//             seq::separated_pair(identifier::parse, tag_token!(Token::Assign), expr),
-//             comb::cut(tag_token!(Token::Semicolon)),
-//         ),
-//         |(ident, expr)| Stmt::Assign(ident, expr),
-//     )
-//     .parse(input)
-// }
-
-// ///
-// ///
-// ///
-// pub fn import_stmt<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(
-//     input: Tokens<'a>
-// ) -> IResult<Tokens, Stmt, E> {
-//     nom::error::context(
-//         "'import' statement",
-//         comb::map(
-//             seq::preceded(
-//                 tag_token!(Token::Import),
-//                 comb::cut(seq::terminated(
-//                     seq::pair(
-//                         identifier::parse,
-//                         comb::opt(multi::many0(seq::preceded(tag_token!(Token::Comma), identifier::parse))),
-//                     ),
-//                     tag_token!(Token::Semicolon),
-//                 )),
-//             ),
-//             |(package, packages)| {
-//                 let mut packages: Vec<Ident> = packages.unwrap_or_default();
-//                 packages.insert(0, package);
-//                 packages.dedup_by(|Ident(a), Ident(b)| a.eq_ignore_ascii_case(b));
-
-//                 let imports = packages
-//                     .into_iter()
-//                     .map(|package| Stmt::Import { package, version: None })
-//                     .collect();
-
-//                 Stmt::Block(imports)
-//             },
-//         ),
-//     )
-//     .parse(input)
-// }
-
-// ///
-// ///
-// ///
-// pub fn return_stmt<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(
-//     input: Tokens<'a>
-// ) -> IResult<Tokens, Stmt, E> {
-//     comb::map(
-//         seq::delimited(
-//             tag_token!(Token::Return),
-//             comb::opt(expr),
-//             comb::cut(tag_token!(Token::Semicolon)),
-//         ),
-//         Stmt::Return,
-//     )
-//     .parse(input)
-// }
-
-// ///
-// ///
-// ///
-// pub fn expr_stmt<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(
-//     input: Tokens<'a>
-// ) -> IResult<Tokens, Stmt, E> {
-//     comb::map(seq::terminated(expr, comb::cut(tag_token!(Token::Semicolon))), |e| {
-//         Stmt::Expr(e)
-//     })
-//     .parse(input)
-// }
-
-// ///
-// ///
-// ///
-// pub fn expr<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(input: Tokens<'a>) -> IResult<Tokens, Expr, E> {
-//     expr_pratt(input, 0)
-// }
-
-// ///
-// ///
-// ///
-// fn expr_pratt<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(
-//     input: Tokens<'a>,
-//     min_bp: u8,
-// ) -> IResult<Tokens, Expr, E> {
-//     let (mut remainder, mut lhs) = match operator::unary_operator::<E>(input) {
-//         Ok((r, UnOp::Idx)) => {
-//             let (r2, entries) = seq::terminated(
-//                 comb::opt(seq::terminated(
-//                     seq::pair(expr, multi::many0(seq::preceded(tag_token!(Token::Comma), expr))),
-//                     comb::opt(tag_token!(Token::Comma)),
-//                 )),
-//                 tag_token!(Token::RightBracket),
-//             )
-//             .parse(r)?;
-
-//             let expr = if let Some((head, entries)) = entries {
-//                 let e = [&[head], &entries[..]].concat().to_vec();
-
-//                 Expr::Array(e)
-//             } else {
-//                 Expr::Array(vec![])
-//             };
-
-//             (r2, expr)
-//         }
-//         Ok((r, UnOp::Prio)) => seq::terminated(expr, tag_token!(Token::RightParen)).parse(r)?,
-//         Ok((r, operator)) => {
-//             let (_, r_bp) = operator.binding_power();
-//             let (r, rhs) = expr_pratt(r, r_bp)?;
-
-//             (
-//                 r,
-//                 Expr::Unary {
-//                     operator,
-//                     operand: Box::new(rhs),
-//                 },
-//             )
-//         }
-//         _ => expr_atom(input)?,
-//     };
-
-//     loop {
-//         // Append any subsequent atoms to LHS.
-//         // The LHS will be turned into a pattern expression.
-//         if let Ok((r, ident)) = expr_atom::<E>(remainder) {
-//             let terms = match lhs {
-//                 Expr::Pattern(terms) => {
-//                     let mut terms = terms;
-//                     terms.push(ident);
-
-//                     terms
-//                 }
-//                 current => {
-//                     vec![current, ident]
-//                 }
-//             };
-
-//             lhs = Expr::Pattern(terms);
-
-//             remainder = r;
-//             continue;
-//         }
-
-//         //
-//         //
-//         //
-//         match operator::parse::<E>(remainder) {
-//             Ok((r, Operator::Binary(operator))) => {
-//                 let (left_bp, right_bp) = operator.binding_power();
-//                 if left_bp < min_bp {
-//                     break;
-//                 }
-
-//                 // Recursive until lower binding power is encountered.
-//                 let (remainder_3, rhs) = expr_pratt(r, right_bp)?;
-
-//                 remainder = remainder_3;
-//                 lhs = Expr::Binary {
-//                     operator,
-//                     lhs_operand: Box::new(lhs),
-//                     rhs_operand: Box::new(rhs),
-//                 };
-//             }
-//             Ok((r, Operator::Unary(operator))) => {
-//                 let (left_bp, _) = operator.binding_power();
-//                 if left_bp < min_bp {
-//                     break;
-//                 }
-
-//                 lhs = if let UnOp::Idx = operator {
-//                     let (r2, rhs) = seq::terminated(expr, tag_token!(Token::RightBracket)).parse(r)?;
-//                     remainder = r2;
-
-//                     Expr::Index {
-//                         array: Box::new(lhs),
-//                         index: Box::new(rhs),
-//                     }
-//                 } else {
-//                     Expr::Unary {
-//                         operator,
-//                         operand: Box::new(lhs),
-//                     }
-//                 };
-//             }
-//             _ => break,
-//         }
-//     }
-
-//     Ok((remainder, lhs))
-// }
-
-// ///
-// ///
-// ///
-// pub fn expr_atom<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(
-//     input: Tokens<'a>
-// ) -> IResult<Tokens, Expr, E> {
-//     branch::alt((
-//         comb::map(literal::parse, Expr::Literal),
-//         comb::map(identifier::parse, Expr::Ident),
-//     ))
-//     .parse(input)
-// }
+/// Resolves all sentences in the given program to function calls.
+/// 
+/// The first word of every sentence is a verb, which calls the function it maps to in the given vocabulary. If the vocabulary does not know it, the function with the same prefix (in its package's call pattern) is called, and otherwise the function with the same name. The remaining words are passed as arguments, except for filler words.
+/// 
+/// # Arguments
+/// - `program`: The Program (as parsed by `parse_ast()`) to resolve the sentences of.
+/// - `pindex`: The PackageIndex that contains the call patterns of the available packages.
+/// - `vocabulary`: The Vocabulary that maps verbs to functions and lists the filler words.
+/// 
+/// # Returns
+/// The same Program, but now without any Pattern expressions.
+/// 
+/// # Errors
+/// This function errors if any of the sentences could not be resolved.
+pub fn resolve_sentences(mut program: Program, pindex: &PackageIndex, vocabulary: &Vocabulary) -> Result<Program, Error> {
+    let verbs: HashMap<String, String> = package_verbs(pindex);
+    resolve_block(&mut program.block, &verbs, vocabulary)?;
+    Ok(program)
+}
+
+
+
+/// Parses a sentence statement.
+/// 
+/// For example:
+/// ```bakery
+/// say "Hello, world!";
+/// ```
+/// 
+/// # Arguments
+/// - `input`: The token stream that will be parsed.
+/// 
+/// # Returns
+/// A pair of remaining tokens and a parsed `Stmt::Expr` with the sentence as an `Expr::Pattern`.
+/// 
+/// # Errors
+/// This function may error if the tokens do not comprise a valid statement.
+pub fn sentence_stmt<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(
+    input: Tokens<'a>
+) -> IResult<Tokens, Stmt, E> {
+    enter_pp!("SENTENCE_STMT");
+
+    // Parse the sentence + semicolon
+    let (r, sentence) = sentence(input)?;
+    let (r, s) = tag_token!(Token::Semicolon).parse(r)?;
+
+    // Return as Stmt::Expr
+    let range: TextRange = TextRange::new(sentence.start().clone(), TextPos::end_of(s.tok[0].inner()));
+    exit_pp!(
+        Ok((r, Stmt::new_expr(
+            sentence,
+
+            range,
+        ))),
+    "SENTENCE_STMT")
+}
+
+/// Parses a (let) assign statement with a sentence as value.
+/// 
+/// For example:
+/// ```bakery
+/// let greeting := greet "Amy";
+/// ```
+/// 
+/// # Arguments
+/// - `input`: The token stream that will be parsed.
+/// 
+/// # Returns
+/// A pair of remaining tokens and a parsed `Stmt::LetAssign` or `Stmt::Assign` with the sentence as an `Expr::Pattern`.
+/// 
+/// # Errors
+/// This function may error if the tokens do not comprise a valid statement.
+pub fn assign_sentence_stmt<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(
+    input: Tokens<'a>
+) -> IResult<Tokens, Stmt, E> {
+    wrap_pp!(
+        comb::map(
+            seq::tuple((
+                comb::opt(tag_token!(Token::Let)),
+                seq::separated_pair(identifier::parse, tag_token!(Token::Assign), sentence),
+                tag_token!(Token::Semicolon),
+            )),
+            |(l, (name, value), s)| {
+                let range: TextRange = TextRange::new(l.map(|l| TextPos::from(l.tok[0].inner())).unwrap_or_else(|| name.start().clone()), TextPos::end_of(s.tok[0].inner()));
+                if l.is_some() {
+                    Stmt::new_letassign(name, value, range)
+                } else {
+                    Stmt::new_assign(name, value, range)
+                }
+            },
+        ).parse(input),
+    "ASSIGN_SENTENCE_STMT")
+}
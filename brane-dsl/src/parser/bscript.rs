@@ -20,10 +20,11 @@ use nom::{branch, combinator as comb, multi, sequence as seq};
 use nom::{IResult, Parser};
 
 use super::{enter_pp, exit_pp, wrap_pp};
-use super::ast::{Block, Identifier, Literal, Node, Program, Property, Stmt};
-use crate::spec::{TextPos, TextRange};
+use super::ast::{BinOp, Block, Expr, Identifier, Literal, Node, Program, Property, Stmt};
+use crate::spec::{Language, TextPos, TextRange};
 use crate::data_type::DataType;
-use crate::parser::{expression, identifier};
+use crate::location::AllowedLocations;
+use crate::parser::{bakery, expression, identifier, literal};
 use crate::scanner::{Token, Tokens};
 use crate::tag_token;
 
@@ -158,6 +159,34 @@ fn class_stmt<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(
     "CLASS_STMT")
 }
 
+/// Parses the documentation comments (`///`) that precede a function or class definition.
+/// 
+/// # Arguments
+/// - `input`: The token stream that will be parsed.
+/// 
+/// # Returns
+/// A pair of the remaining tokens and the text of the comments as one string (one line per comment, without the slashes and the space following them), or `None` if there were none.
+/// 
+/// # Errors
+/// This function does not error, but is still a nom parser.
+fn docs<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(
+    input: Tokens<'a>
+) -> IResult<Tokens, Option<String>, E> {
+    wrap_pp!(
+        comb::map(
+            multi::many0(tag_token!(Token::DocComment)),
+            |comments: Vec<Tokens<'a>>| {
+                if comments.is_empty() { return None; }
+                let lines: Vec<&str> = comments.iter().map(|c| {
+                    let line: &str = c.tok[0].inner().fragment();
+                    line.strip_prefix(' ').unwrap_or(line).trim_end()
+                }).collect();
+                Some(lines.join("\n"))
+            },
+        ).parse(input),
+    "DOCS")
+}
+
 
 
 
@@ -176,15 +205,16 @@ fn class_stmt<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(
 pub fn parse_ast(input: Tokens) -> IResult<Tokens, Program, VerboseError<Tokens>> {
     enter_pp!("AST");
 
-    // Parse it all as statements
-    let (r, stmts) = comb::all_consuming(multi::many0(parse_stmt))(input)?;
+    // Parse it all as statements (parameters may only be declared in the toplevel)
+    let (r, stmts) = comb::all_consuming(multi::many0(branch::alt((param_stmt, parse_stmt))))(input)?;
 
     // Wrap it in a program and done
     let start_pos : TextPos = stmts.first().map(|s| s.start().clone()).unwrap_or(TextPos::none());
     let end_pos   : TextPos = stmts.iter().last().map(|s| s.end().clone()).unwrap_or(TextPos::none());
     exit_pp!(
         Ok((r, Program {
-            block : Block::new(stmts, TextRange::new(start_pos, end_pos)),
+            block  : Block::new(stmts, TextRange::new(start_pos, end_pos)),
+            params : vec![],
         })),
     "AST")
 }
@@ -214,6 +244,13 @@ pub fn parse_stmt<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(
         return Err(nom::Err::Error(nom::error_position!(input, ErrorKind::Tag)));
     }
 
+    // Bakery also allows sentences, which would otherwise be (invalid) expression statements
+    if input.lang == Language::Bakery {
+        if let Ok(res) = branch::alt((bakery::assign_sentence_stmt::<E>, bakery::sentence_stmt::<E>)).parse(input) {
+            return exit_pp!(Ok(res), "STMT");
+        }
+    }
+
     // Otherwise, parse one of the following statements
     exit_pp!(
         branch::alt((
@@ -275,6 +312,50 @@ pub fn let_assign_stmt<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>
     "LET_ASSIGN")
 }
 
+/// Parses a parameter declaration statement.
+/// 
+/// Note that `param` is not a keyword, so existing scripts may still use it as an identifier.
+/// 
+/// For example:
+/// ```branescript
+/// param k: int := 5;
+/// ```
+/// 
+/// # Arguments
+/// - `input`: The token stream that will be parsed.
+/// 
+/// # Returns
+/// A pair of remaining tokens and a parsed `Stmt::LetAssign` that declares a parameter.
+/// 
+/// # Errors
+/// This function may error if the tokens do not comprise a valid statement.
+pub fn param_stmt<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(
+    input: Tokens<'a>
+) -> IResult<Tokens, Stmt, E> {
+    enter_pp!("PARAM");
+
+    // Parse the 'param' first
+    let (r, p) = tag_token!(Token::Ident).parse(input)?;
+    if p.tok[0].as_string() != "param" { return Err(nom::Err::Error(nom::error_position!(input, ErrorKind::Tag))); }
+    // Then the name and type, after which we know it's a parameter
+    let (r, (name, data_type)) = seq::separated_pair(identifier::parse, tag_token!(Token::Colon), tag_token!(Token::Ident)).parse(r)?;
+    // Parse the optional default value
+    let (r, default) = comb::cut(comb::opt(seq::preceded(tag_token!(Token::Assign), literal::parse))).parse(r)?;
+    // Finally, parse the semicolon
+    let (r, s) = comb::cut(tag_token!(Token::Semicolon)).parse(r)?;
+
+    // Put it in a letassign and done
+    exit_pp!(
+        Ok((r, Stmt::new_param(
+            name,
+            DataType::from(data_type.tok[0].as_string()),
+            default,
+
+            TextRange::from((p.tok[0].inner(), s.tok[0].inner())),
+        ))),
+    "PARAM")
+}
+
 /// Parses an assign statement.
 /// 
 /// For example:
@@ -479,8 +560,8 @@ pub fn declare_class_stmt<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a
 ) -> IResult<Tokens, Stmt, E> {
     enter_pp!("CLASS");
 
-    // Parse the class keyword first
-    let (r, c) = tag_token!(Token::Class).parse(input)?;
+    // Parse the documentation and the class keyword first
+    let (r, (docs, c)) = seq::pair(docs, tag_token!(Token::Class)).parse(input)?;
     // Parse the class body
     let (r, (ident, body)) = seq::pair(
         identifier::parse,
@@ -508,6 +589,7 @@ pub fn declare_class_stmt<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a
             ident,
             props,
             methods,
+            docs,
 
             TextRange::from((c.tok[0].inner(), b.tok[0].inner())),
         ))),
@@ -542,8 +624,16 @@ pub fn declare_func_stmt<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>
 ) -> IResult<Tokens, Stmt, E> {
     enter_pp!("FUNC");
 
-    // Hit the function token first
-    let (r, f) = tag_token!(Token::Function).parse(input)?;
+    // Hit the documentation, the test attribute and the function token first
+    let (r, (docs, test, f)) = seq::tuple((
+        docs,
+        comb::opt(seq::delimited(
+            seq::pair(tag_token!(Token::Hash), tag_token!(Token::LeftBracket)),
+            comb::verify(tag_token!(Token::Ident), |t: &Tokens<'a>| t.tok[0].as_string() == "test"),
+            tag_token!(Token::RightBracket),
+        )),
+        tag_token!(Token::Function),
+    )).parse(input)?;
     // Parse everything else
     let (r, ((ident, params), code)) = seq::tuple((
         comb::cut(seq::pair(
@@ -575,6 +665,8 @@ pub fn declare_func_stmt<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>
             ident,
             params,
             Box::new(code),
+            docs,
+            test.is_some(),
 
             range,
         ))),
@@ -683,18 +775,23 @@ pub fn import_stmt<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(
 
 /// Parses a for-loop.
 /// 
+/// This is either a counter loop or a loop over the elements of an array.
+/// 
 /// For example:
 /// ```branescript
 /// for (let i := 0; i < 10; i++) {
 ///     print("Hello there!");
 /// }
+/// for name in [ "Amy", "Bob" ] {
+///     print("Hello " + name + "!");
+/// }
 /// ```
 /// 
 /// # Arguments
 /// - `input`: The token stream that will be parsed.
 /// 
 /// # Returns
-/// A pair of remaining tokens and a parsed `Stmt::For`.
+/// A pair of remaining tokens and a parsed `Stmt::For`. Loops over arrays are immediately desugared to a `Stmt::Block` with a counter loop in it (see `desugar_for_in()`).
 /// 
 /// # Errors
 /// This function may error if the tokens do not comprise a valid statement.
@@ -705,48 +802,126 @@ pub fn for_stmt<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(
 
     // Parse the for token first
     let (r, f) = nom::error::context("'for' statement", tag_token!(Token::For)).parse(input)?;
-    // Parse the rest
-    let (r, ((initializer, condition, increment), consequent)) = nom::error::context("'for' statement",
-        comb::cut(seq::pair(
-            seq::delimited(
-                tag_token!(Token::LeftParen),
+    let start: TextPos = f.tok[0].inner().into();
+    // Parse the rest, which is either the header of a loop over an array or of a counter loop
+    let (r, stmt) = nom::error::context("'for' statement",
+        comb::cut(branch::alt((
+            comb::map(
                 seq::tuple((
-                    let_assign_stmt,
-                    seq::terminated(expression::parse, tag_token!(Token::Semicolon)),
-                    comb::map(
-                        seq::separated_pair(identifier::parse, tag_token!(Token::Assign), expression::parse),
-                        |(name, value)| {
-                            // Get the start and end pos for this assign
-                            let range: TextRange = TextRange::new(name.start().clone(), value.end().clone());
-
-                            // Return as the proper struct
-                            Stmt::new_assign(
-                                name,
-                                value,
-
-                                range,
-                            )
-                        },
-                    ),
+                    identifier::parse,
+                    seq::preceded(tag_token!(Token::In), expression::parse),
+                    block,
                 )),
-                tag_token!(Token::RightParen),
+                |(name, iterable, consequent)| desugar_for_in(start.clone(), name, iterable, consequent),
             ),
-            block,
-        ))
+            comb::map(
+                seq::pair(
+                    seq::delimited(
+                        tag_token!(Token::LeftParen),
+                        seq::tuple((
+                            let_assign_stmt,
+                            seq::terminated(expression::parse, tag_token!(Token::Semicolon)),
+                            comb::map(
+                                seq::separated_pair(identifier::parse, tag_token!(Token::Assign), expression::parse),
+                                |(name, value)| {
+                                    // Get the start and end pos for this assign
+                                    let range: TextRange = TextRange::new(name.start().clone(), value.end().clone());
+
+                                    // Return as the proper struct
+                                    Stmt::new_assign(
+                                        name,
+                                        value,
+
+                                        range,
+                                    )
+                                },
+                            ),
+                        )),
+                        tag_token!(Token::RightParen),
+                    ),
+                    block,
+                ),
+                |((initializer, condition, increment), consequent)| {
+                    // Hey-ho, let's go put it in a struct
+                    let range: TextRange = TextRange::new(start.clone(), consequent.end().clone());
+                    Stmt::For {
+                        initializer : Box::new(initializer),
+                        condition,
+                        increment   : Box::new(increment),
+                        consequent  : Box::new(consequent),
+
+                        range,
+                    }
+                },
+            ),
+        )))
     ).parse(r)?;
 
-    // Hey-ho, let's go put it in a struct
-    let range: TextRange = TextRange::new(f.tok[0].inner().into(), consequent.end().clone());
-    exit_pp!(
-        Ok((r, Stmt::For {
-            initializer : Box::new(initializer),
-            condition,
-            increment   : Box::new(increment),
-            consequent  : Box::new(consequent),
+    // Done
+    exit_pp!(Ok((r, stmt)), "FOR")
+}
 
-            range,
-        })),
-    "FOR")
+/// Desugars a loop over the elements of an array to a counter loop.
+/// 
+/// Concretely, `for <name> in <iterable> { ... }` becomes:
+/// ```branescript
+/// {
+///     let #array := <iterable>;
+///     for (let #index := 0; #index < len(#array); #index := #index + 1) {
+///         let <name> := #array[#index];
+///         ...
+///     }
+/// }
+/// ```
+/// where the (hidden) names of `#array` and `#index` cannot be written by the user and are unique per loop. Since they are resolved as any other variable, the loop is then compiled to the usual loop edges.
+/// 
+/// # Arguments
+/// - `start`: The position of the `for`-keyword.
+/// - `name`: The name of the variable that is bound to every element.
+/// - `iterable`: The expression that evaluates to the array to loop over.
+/// - `consequent`: The body of the loop.
+/// 
+/// # Returns
+/// A `Stmt::Block` that implements the loop.
+fn desugar_for_in(start: TextPos, name: Identifier, iterable: Expr, consequent: Block) -> Stmt {
+    let range  : TextRange = TextRange::new(start, consequent.end().clone());
+    let irange : TextRange = iterable.range().clone();
+    let nrange : TextRange = name.range.clone();
+
+    // Generate the hidden names, and some shortcuts to refer to them
+    let array: String = format!("#array@{}:{}", range.start.line, range.start.col);
+    let index: String = format!("#index@{}:{}", range.start.line, range.start.col);
+    let ident  = |value: &str| Identifier::new(value.into(), irange.clone());
+    let varref = |value: &str| Box::new(Expr::new_varref(ident(value)));
+    let int    = |value: i64| Box::new(Expr::Literal{ literal: Literal::Integer{ value, range: irange.clone() } });
+
+    // `let #array := <iterable>;`
+    let array_stmt: Stmt = Stmt::new_letassign(ident(&array), iterable, irange.clone());
+
+    // `let #index := 0;`, `#index < len(#array)` and `#index := #index + 1`
+    let initializer: Stmt = Stmt::new_letassign(ident(&index), *int(0), irange.clone());
+    let condition: Expr = Expr::new_binop(
+        BinOp::Lt{ range: irange.clone() },
+        varref(&index),
+        Box::new(Expr::new_call(Box::new(Expr::new_identifier(ident("len"))), vec![ varref(&array) ], irange.clone(), AllowedLocations::All, None, None)),
+        irange.clone(),
+    );
+    let increment: Stmt = Stmt::new_assign(ident(&index), Expr::new_binop(BinOp::Add{ range: irange.clone() }, varref(&index), int(1), irange.clone()), irange.clone());
+
+    // `let <name> := #array[#index];` as the first statement of the body
+    let mut consequent: Block = consequent;
+    consequent.stmts.insert(0, Stmt::new_letassign(name, Expr::new_array_index(varref(&array), varref(&index), nrange.clone()), nrange));
+
+    // Wrap it in a block to scope the hidden array
+    let for_stmt: Stmt = Stmt::For {
+        initializer : Box::new(initializer),
+        condition,
+        increment   : Box::new(increment),
+        consequent  : Box::new(consequent),
+
+        range : range.clone(),
+    };
+    Stmt::Block{ block: Box::new(Block::new(vec![ array_stmt, for_stmt ], range)) }
 }
 
 /// Parses a while-loop.
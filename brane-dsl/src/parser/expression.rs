@@ -20,13 +20,29 @@ use nom::{IResult, Parser};
 
 use super::{enter_pp, exit_pp, wrap_pp};
 use super::ast::{Expr, Identifier, Node, Operator, UnaOp};
-use crate::spec::{TextPos, TextRange};
+use crate::spec::{RetryPolicy, TextPos, TextRange};
 use crate::parser::{identifier, instance, literal, operator};
 use crate::scanner::{Token, Tokens};
 use crate::tag_token;
 use crate::location::AllowedLocations;
 
 
+/***** HELPER ENUMS *****/
+/// Defines the annotations that may precede a call.
+enum CallAnnotation<'a> {
+    /// `@["loc1", ...]` restricts where the call may be executed, and `on "loc"` or `#[location("loc")]` pin it to a single location.
+    Locations(Vec<Tokens<'a>>),
+    /// `@retry(N[, BACKOFF])` retries the call up to `N` times if it fails, waiting `BACKOFF` milliseconds (doubling every time) in between.
+    Retry(RetryPolicy),
+    /// `@timeout(SECONDS)` kills the call if it did not complete within `SECONDS` seconds, which must be positive.
+    Timeout(u64),
+}
+
+
+
+
+
+/***** LIBRARY *****/
 /// Parses an expression.
 ///
 /// # Arguments
@@ -166,7 +182,9 @@ pub fn expr_atom<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(
     wrap_pp!(
         branch::alt((
             instance::parse,
+            instance::data_ref,
             call_expr,
+            map_expr,
             comb::map(literal::parse,    |l| Expr::Literal{ literal: l }),
             proj_expr,
             comb::map(identifier::parse, Expr::new_varref),
@@ -192,20 +210,83 @@ pub fn call_expr<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(
 ) -> IResult<Tokens, Expr, E> {
     enter_pp!("CALL");
 
+    // Parse optionally the location the call is pinned to
+    let (r, on) = comb::opt(seq::pair(tag_token!(Token::On), tag_token!(Token::String))).parse(input)?;
     // Parse optionally annotations
-    let (r, at) = comb::opt(tag_token!(Token::At)).parse(input)?;
-    let (r, annot) = if at.is_some() {
-        let (r, annot) = comb::cut(
+    let (r, annots) = multi::many0(branch::alt((seq::pair(
+        tag_token!(Token::At),
+        comb::cut(branch::alt((
+            comb::map(
+                seq::delimited(
+                    tag_token!(Token::LeftBracket),
+                    multi::separated_list1(tag_token!(Token::Comma), tag_token!(Token::String)),
+                    tag_token!(Token::RightBracket),
+                ),
+                CallAnnotation::Locations,
+            ),
+            comb::map(
+                seq::preceded(
+                    comb::verify(tag_token!(Token::Ident), |t: &Tokens<'a>| t.tok[0].as_string() == "retry"),
+                    seq::delimited(
+                        tag_token!(Token::LeftParen),
+                        seq::pair(
+                            tag_token!(Token::Integer),
+                            comb::opt(seq::preceded(tag_token!(Token::Comma), tag_token!(Token::Integer))),
+                        ),
+                        tag_token!(Token::RightParen),
+                    ),
+                ),
+                |(max_retries, backoff)| {
+                    let max_retries: u32 = u32::try_from(max_retries.tok[0].as_i64().max(0)).unwrap_or(u32::MAX);
+                    CallAnnotation::Retry(match backoff {
+                        Some(backoff) => RetryPolicy{ max_retries, backoff: backoff.tok[0].as_i64().max(0) as u64 },
+                        None          => RetryPolicy::new(max_retries),
+                    })
+                },
+            ),
+            comb::map(
+                seq::preceded(
+                    comb::verify(tag_token!(Token::Ident), |t: &Tokens<'a>| t.tok[0].as_string() == "timeout"),
+                    seq::delimited(
+                        tag_token!(Token::LeftParen),
+                        // A call that may take no time at all could never complete
+                        comb::verify(tag_token!(Token::Integer), |t: &Tokens<'a>| t.tok[0].as_i64() > 0),
+                        tag_token!(Token::RightParen),
+                    ),
+                ),
+                |secs: Tokens<'a>| CallAnnotation::Timeout(secs.tok[0].as_i64() as u64),
+            ),
+        ))),
+    ), seq::pair(
+        tag_token!(Token::Hash),
+        comb::cut(comb::map(
             seq::delimited(
                 tag_token!(Token::LeftBracket),
-                multi::separated_list1(tag_token!(Token::Comma), tag_token!(Token::String)),
+                seq::preceded(
+                    comb::verify(tag_token!(Token::Ident), |t: &Tokens<'a>| t.tok[0].as_string() == "location"),
+                    seq::delimited(
+                        tag_token!(Token::LeftParen),
+                        tag_token!(Token::String),
+                        tag_token!(Token::RightParen),
+                    ),
+                ),
                 tag_token!(Token::RightBracket),
-            )
-        ).parse(r)?;
-        (r, Some(annot))
-    } else {
-        (r, None)
-    };
+            ),
+            |loc: Tokens<'a>| CallAnnotation::Locations(vec![ loc ]),
+        )),
+    )))).parse(r)?;
+    let at: Option<TextPos> = on.map(|(o, _)| o).or_else(|| annots.first().map(|(at, _)| *at)).map(|t| t.tok[0].inner().into());
+    // Multiple location annotations all restrict the call, so it may only run where they overlap
+    let mut locs    : AllowedLocations    = AllowedLocations::All;
+    let mut retry   : Option<RetryPolicy> = None;
+    let mut timeout : Option<u64>         = None;
+    for annot in on.map(|(_, loc)| CallAnnotation::Locations(vec![ loc ])).into_iter().chain(annots.into_iter().map(|(_, annot)| annot)) {
+        match annot {
+            CallAnnotation::Locations(l) => { locs.intersection(&mut AllowedLocations::Exclusive(l.into_iter().map(|l| l.tok[0].as_string().into()).collect())); },
+            CallAnnotation::Retry(p)     => { retry = Some(p); },
+            CallAnnotation::Timeout(t)   => { timeout = Some(t); },
+        }
+    }
 
     // Parse the call thingy itself
     let (r, (expr, args)) = seq::pair(
@@ -239,14 +320,16 @@ pub fn call_expr<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(
     };
 
     // Put it in an Expr::Call and return
-    let range: TextRange = TextRange::new(at.map(|a| a.tok[0].inner().into()).unwrap_or_else(|| expr.start().clone()), TextPos::end_of(paren.tok[0].inner()));
+    let range: TextRange = TextRange::new(at.unwrap_or_else(|| expr.start().clone()), TextPos::end_of(paren.tok[0].inner()));
     exit_pp!(
         Ok((r, Expr::new_call(
             Box::new(expr),
             args,
 
             range,
-            annot.map(|l| AllowedLocations::Exclusive(l.into_iter().map(|l| l.tok[0].as_string().into()).collect())).unwrap_or(AllowedLocations::All),
+            locs,
+            retry,
+            timeout,
         ))),
         // Ok((input, Expr::Literal { literal: crate::ast::Literal::String{ value: "HELLO THERE".into(), range: TextRange::none() } })),
     "CALL")
@@ -335,3 +418,37 @@ fn array_expr<'a, 'b, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(
         }
     }
 }
+
+/// Parses the given token stream as a map expression (i.e., `{ "key": value, ... }`).
+/// 
+/// # Arguments
+/// - `input`: The input stream of tokens that we use to parse expressions from.
+/// 
+/// # Returns
+/// A tuple of the remaining tokens and a parsed expression if there was an expression on top.
+/// 
+/// # Errors
+/// This function returns a nom::Error if it failed to parse an expression.
+fn map_expr<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(
+    input: Tokens<'a>
+) -> IResult<Tokens, Expr, E> {
+    enter_pp!("MAP");
+
+    // Parse the opening brace
+    // Note: we don't cut after it, since it may also be a block that we are (wrongly) trying to parse as an expression
+    let (r, brace) = tag_token!(Token::LeftBrace).parse(input)?;
+
+    // Parse a comma-separated list of key/value pairs
+    let (r, entries) = multi::separated_list0(
+        tag_token!(Token::Comma),
+        seq::separated_pair(self::parse, tag_token!(Token::Colon), self::parse),
+    ).parse(r)?;
+    let (r, _)       = comb::opt(tag_token!(Token::Comma)).parse(r)?;
+    let (r, closing) = tag_token!(Token::RightBrace).parse(r)?;
+
+    // Return the map with its entries
+    let range: TextRange = TextRange::new(brace.tok[0].inner().into(), TextPos::end_of(closing.tok[0].inner()));
+    exit_pp!(
+        Ok((r, Expr::new_map(entries.into_iter().map(|(k, v)| (Box::new(k), Box::new(v))).collect(), range))),
+    "MAP")
+}
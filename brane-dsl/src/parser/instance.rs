@@ -10,18 +10,25 @@
 // 
 //  Description:
 //!   Defines functions that parse an instance expression from the tokens.
+//!   
+//!   Also parses the `Data<"name", "1.2.0">` shorthand, which is
+//!   syntactic sugar for `new Data{ name := "name@1.2.0" }`.
 // 
 
 use std::num::NonZeroUsize;
+use std::str::FromStr;
 
 use nom::error::{ContextError, ParseError};
 use nom::{combinator as comb, multi, sequence as seq};
 use nom::{IResult, Parser};
 
+use specifications::data::data_id;
+use specifications::version::Version;
+
 use super::{enter_pp, exit_pp};
-use super::ast::{Expr, Identifier, Node, PropertyExpr};
+use super::ast::{Expr, Identifier, Literal, Node, PropertyExpr};
 use crate::spec::{TextPos, TextRange};
-use crate::parser::{expression, identifier};
+use crate::parser::{expression, identifier, literal};
 use crate::scanner::{Token, Tokens};
 use crate::tag_token;
 
@@ -60,6 +67,23 @@ pub fn instance_property<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>
 
 
 
+/// Parses a string literal as such.
+///
+/// # Arguments
+/// - `input`: The list of tokens to parse from.
+/// 
+/// # Returns
+/// The remaining list of tokens and the value and range of the parsed string if there was anything to parse. Otherwise, a `nom::Error` is returned (which may be a real error or simply 'could not parse').
+fn string_literal<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(
+    input: Tokens<'a>
+) -> IResult<Tokens, (String, TextRange), E> {
+    comb::map_opt(literal::parse, |l| if let Literal::String{ value, range } = l { Some((value, range)) } else { None }).parse(input)
+}
+
+
+
+
+
 /***** LIBRARY *****/
 /// Parses an instance expression to an Expr (`Expr::Instance`).
 ///
@@ -98,3 +122,56 @@ pub fn parse<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(input: To
         ))),
     "INSTANCE")
 }
+
+/// Parses a versioned data reference (`Data<"name">` or `Data<"name", "1.2.0">`) to an Expr (`Expr::Instance`).
+/// 
+/// This is syntactic sugar for `new Data{ name := "name@1.2.0" }`, or `new Data{ name := "name" }` (i.e., the latest version) if the version is omitted.
+///
+/// # Arguments
+/// - `input`: The list of tokens to parse from.
+/// 
+/// # Returns
+/// The remaining list of tokens and the parsed Expr if there was anything to parse. Otherwise, a `nom::Error` is returned (which may be a real error or simply 'could not parse').
+pub fn data_ref<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(input: Tokens<'a>) -> IResult<Tokens, Expr, E> {
+    enter_pp!("DATA_REF");
+
+    // Get the 'Data<' first
+    let (r, class): (Tokens<'a>, Identifier) = seq::terminated(
+        comb::verify(identifier::parse, |i: &Identifier| i.value == "Data"),
+        tag_token!(Token::Less),
+    )(input)?;
+    // Parse the name and the optional version
+    let (r, ((name, name_range), version)): (Tokens<'a>, ((String, TextRange), Option<(String, TextRange)>)) = comb::cut(
+        seq::pair(
+            string_literal,
+            comb::opt(seq::preceded(
+                tag_token!(Token::Comma),
+                comb::verify(string_literal, |(v, _): &(String, TextRange)| Version::from_str(v).is_ok()),
+            )),
+        )
+    )(r)?;
+    // Parse the closing bracket
+    let (r, b): (Tokens<'a>, Tokens<'a>) = comb::cut(tag_token!(Token::Greater))(r)?;
+
+    // Resolve the identifier and the ranges
+    let (id, value_range): (String, TextRange) = match version {
+        Some((version, version_range)) => (data_id(name, &Version::from_str(&version).unwrap()), TextRange::new(name_range.start, version_range.end)),
+        None                           => (name, name_range),
+    };
+    let range: TextRange = TextRange::new(class.start().clone(), TextPos::end_of(b.tok[0].inner()));
+
+    // Now put that in an Expr as if it was an instance expression and return
+    exit_pp!(
+        Ok((r, Expr::new_instance(
+            class,
+            vec![ PropertyExpr {
+                name  : Identifier::new("name".into(), value_range.clone()),
+                value : Box::new(Expr::Literal{ literal: Literal::String{ value: id, range: value_range.clone() } }),
+
+                range : value_range,
+            } ],
+
+            range,
+        ))),
+    "DATA_REF")
+}
@@ -81,6 +81,8 @@ fn pattern_to_call(
 
         range,
         AllowedLocations::All,
+        None,
+        None,
     ))
 }
 
@@ -21,6 +21,34 @@ use super::tokens::Token;
 
 
 /***** SCANNING FUNCTIONS *****/
+/// Parses a documentation comment (i.e., a single-line comment starting with exactly three slashes) off the top of the given input.
+/// 
+/// # Arguments
+/// - `input`: The input text to scan.
+/// 
+/// # Returns
+/// The remaining tokens and a `Token::DocComment` with the text of the comment (without the slashes).
+/// 
+/// # Errors
+/// This function errors if we could not parse a documentation comment.
+pub fn doc_comment<'a, E: ParseError<Span<'a>> + ContextError<Span<'a>>>(
+    input: Span<'a>
+) -> IResult<Span<'a>, Token, E> {
+    wrap_pp!(
+        comb::map(
+            seq::delimited(
+                seq::pair(bc::tag("///"), comb::not(bc::tag("/"))),
+                bc::take_till(|c| c == '\n'),
+                branch::alt((
+                    bc::tag("\n"),
+                    comb::eof,
+                )),
+            ),
+            Token::DocComment,
+        ).parse(input),
+    "DOC COMMENT")
+}
+
 /// Parses a single-line comment off the top of the given input.
 /// 
 /// # Arguments
@@ -81,13 +109,13 @@ pub fn multi_line_comment<'a, E: ParseError<Span<'a>> + ContextError<Span<'a>>>(
 /// - `input`: The input text to scan.
 /// 
 /// # Returns
-/// The remaining tokens and a `Token::None`, representing that we did not really parse useful information.
+/// The remaining tokens and a `Token::DocComment` if it was a documentation comment, or a `Token::None` otherwise (representing that we did not really parse useful information).
 /// 
 /// # Errors
 /// This function errors if we could not parse a comment.
 pub fn parse<'a, E: ParseError<Span<'a>> + ContextError<Span<'a>>>(input: Span<'a>) -> IResult<Span<'a>, Token, E> {
     // println!("COMMENTS")
     wrap_pp!(
-        branch::alt((single_line_comment, multi_line_comment)).parse(input),
+        branch::alt((doc_comment, single_line_comment, multi_line_comment)).parse(input),
     "COMMENT")
 }
@@ -20,7 +20,7 @@ pub mod scanning;
 
 // Bring some stuff into this namespace
 pub use tokens::{Token, Tokens};
-pub use scanning::scan_tokens;
+pub use scanning::{find_reserved, promote_keywords, scan_tokens};
 
 
 // Define some useful types for this module
@@ -16,12 +16,60 @@ use nom::error::{ContextError, ParseError, VerboseError};
 use nom::{branch, combinator as comb, multi, sequence as seq};
 use nom::{bytes::complete as bc, character::complete as cc, IResult, Parser};
 
+use crate::spec::Edition;
 use super::{wrap_pp, Span};
 use super::tokens::Token;
 use super::comments;
 use super::literal;
 
 
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Scans the given code in the given edition, returning where the `in`-keywords ended up (by their index in the list of tokens).
+    fn keywords(code: &str, edition: Edition) -> Vec<usize> {
+        let (remain, mut tokens): (Span, Vec<Token>) = scan_tokens(Span::new(code)).unwrap();
+        assert!(remain.fragment().is_empty());
+        promote_keywords(&mut tokens, edition);
+        tokens.iter().enumerate().filter_map(|(i, t)| if let Token::In(_) = t { Some(i) } else { None }).collect()
+    }
+
+    /// Tests that `in` is only scanned as a keyword where the edition says it is one.
+    #[test]
+    fn test_promote_keywords() {
+        // Edition 2022 only knows it in the header of a loop over an array...
+        assert_eq!(keywords("for x in xs {}", Edition::E2022), vec![ 2 ]);
+        assert_eq!(keywords("let in := 1; println(in);", Edition::E2022), Vec::<usize>::new());
+        // ...where the loop variable may even be called `in` itself
+        assert_eq!(keywords("for in in xs {}", Edition::E2022), vec![ 2 ]);
+        assert_eq!(keywords("for x in in {}", Edition::E2022), vec![ 2 ]);
+        // Counter loops are left alone
+        assert_eq!(keywords("for (let in := 0; in < 3; in := in + 1) {}", Edition::E2022), Vec::<usize>::new());
+
+        // Edition 2026 knows it everywhere
+        assert_eq!(keywords("for x in xs {}", Edition::E2026), vec![ 2 ]);
+        assert_eq!(keywords("let in := 1;", Edition::E2026), vec![ 1 ]);
+        // But not as part of another identifier
+        assert_eq!(keywords("let inside := index;", Edition::E2026), Vec::<usize>::new());
+    }
+
+    /// Tests that words reserved by later editions are found as identifiers.
+    #[test]
+    fn test_find_reserved() {
+        let (_, tokens): (Span, Vec<Token>) = scan_tokens(Span::new("let x := 1;\nlet try := x;")).unwrap();
+        assert_eq!(find_reserved(&tokens, Edition::E2022), None);
+        let span: &Span = find_reserved(&tokens, Edition::E2026).unwrap();
+        assert_eq!((*span.fragment(), span.location_line(), span.get_utf8_column()), ("try", 2, 5));
+    }
+}
+
+
+
+
+
 /***** CONSTANTS *****/
 /// Define characters that separate tokens
 const SEPARATORS: &str = " \n\t\r{}[]()-=+;:'\"\\|/?>.<,`~*&^%$#@!";
@@ -162,6 +210,7 @@ fn punctuation<'a, E: ParseError<Span<'a>> + ContextError<Span<'a>>>(input: Span
             comb::map(bc::tag("("), Token::LeftParen),
             comb::map(bc::tag(")"), Token::RightParen),
             comb::map(bc::tag(","), Token::Comma),
+            comb::map(bc::tag("#"), Token::Hash),
             comb::map(bc::tag("."), Token::Dot),
             comb::map(bc::tag(":"), Token::Colon),
             comb::map(bc::tag(";"), Token::Semicolon),
@@ -223,6 +272,8 @@ fn separator<'a, E: ParseError<Span<'a>> + ContextError<Span<'a>>>(input: Span<'
 /***** LIBRARY *****/
 /// Parses the given text to a list of tokens, abstracting away over the most nitpicky syntax.
 /// 
+/// Documentation comments are only kept if they precede a function or class definition; anywhere else, they are treated like normal comments.
+/// 
 /// # Arguments
 /// - `input`: The String input (wrapped in a Span for token localization later on) to parse.
 /// 
@@ -239,7 +290,60 @@ pub fn scan_tokens(input: Span) -> IResult<Span, Vec<Token>, VerboseError<Span>>
                 let mut t = t;
                 t.retain(|t| !t.is_none());
 
+                // Drop the documentation comments that do not document anything (walking backwards to see what follows them)
+                let mut documents: bool = false;
+                let mut keep: Vec<bool> = Vec::with_capacity(t.len());
+                for token in t.iter().rev() {
+                    match token {
+                        Token::DocComment(_)                 => { keep.push(documents); },
+                        Token::Function(_) | Token::Class(_) => { documents = true; keep.push(true); },
+                        // Attributes (e.g., `#[test]`) may sit in between the documentation and the function
+                        Token::Hash(_) | Token::LeftBracket(_) | Token::Ident(_) | Token::RightBracket(_) => { keep.push(true); },
+                        _                                    => { documents = false; keep.push(true); },
+                    }
+                }
+                let mut keep = keep.into_iter().rev();
+                t.retain(|_| keep.next().unwrap());
+
                 (s, t)
             }),
     "TOKENS")
 }
+
+/// Turns the identifiers in the given list of tokens that are keywords in the given edition into those keywords.
+/// 
+/// Since edition 2026, `in` is a keyword. Before that, it is only treated as one right after the loop variable of a `for`-loop (i.e., `for <name> in`), such that older scripts may still use it as an identifier anywhere else.
+/// 
+/// # Arguments
+/// - `tokens`: The list of tokens to update.
+/// - `edition`: The Edition that determines which words are keywords.
+pub fn promote_keywords(tokens: &mut [Token], edition: Edition) {
+    let reserved: bool = edition.reserved().contains(&"in");
+    for i in 0..tokens.len() {
+        let span: Span = match &tokens[i] {
+            Token::Ident(span) if *span.fragment() == "in" => *span,
+            _                                              => { continue; },
+        };
+        if reserved || (i >= 2 && matches!(tokens[i - 2], Token::For(_)) && matches!(tokens[i - 1], Token::Ident(_))) {
+            tokens[i] = Token::In(span);
+        }
+    }
+}
+
+/// Finds the first identifier in the given list of tokens that is a reserved keyword in the given edition.
+/// 
+/// Words that are reserved in every edition are already scanned as keywords. Those reserved by later editions are scanned as identifiers, such that older editions may still use them as such (and are turned into keywords by `promote_keywords()` if they have a meaning).
+/// 
+/// # Arguments
+/// - `tokens`: The list of tokens to search.
+/// - `edition`: The Edition that determines which words are reserved.
+/// 
+/// # Returns
+/// The span of the first identifier that is reserved, or `None` if there is none.
+pub fn find_reserved<'a, 'b>(tokens: &'b [Token<'a>], edition: Edition) -> Option<&'b Span<'a>> {
+    let reserved: &[&str] = edition.reserved();
+    tokens.iter().find_map(|t| match t {
+        Token::Ident(span) if reserved.contains(span.fragment()) => Some(span),
+        _                                                        => None,
+    })
+}
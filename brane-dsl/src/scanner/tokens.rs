@@ -3,6 +3,8 @@ use std::iter::Enumerate;
 use std::ops::{Range, RangeFrom, RangeFull, RangeTo};
 use std::str::FromStr;
 
+use crate::spec::Language;
+
 type Span<'a> = nom_locate::LocatedSpan<&'a str>;
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -37,6 +39,9 @@ pub enum Token<'a> {
     /// `import`
     Import(Span<'a>),
 
+    /// `in`
+    In(Span<'a>),
+
     /// `let`
     Let(Span<'a>),
 
@@ -70,6 +75,9 @@ pub enum Token<'a> {
     /// ,
     Comma(Span<'a>),
 
+    /// #
+    Hash(Span<'a>),
+
     /// {
     LeftBrace(Span<'a>),
 
@@ -151,6 +159,9 @@ pub enum Token<'a> {
     /// Identifier
     Ident(Span<'a>),
 
+    /// Documentation comment (`/// ...`), as the text after the slashes
+    DocComment(Span<'a>),
+
     /// None
     None,
 }
@@ -199,13 +210,13 @@ impl<'a> Token<'a> {
 
         match self {
             At(span) | And(span) | Break(span) | Class(span) | Continue(span) | Else(span) | For(span) | Function(span)
-            | If(span) | Import(span) | Let(span) | On(span) | Or(span) | Return(span) | Unit(span) | While(span)
-            | Dot(span) | Colon(span) | Comma(span) | LeftBrace(span) | LeftBracket(span) | LeftParen(span)
+            | If(span) | Import(span) | In(span) | Let(span) | On(span) | Or(span) | Return(span) | Unit(span) | While(span)
+            | Dot(span) | Colon(span) | Comma(span) | Hash(span) | LeftBrace(span) | LeftBracket(span) | LeftParen(span)
             | Parallel(span) | RightBrace(span) | RightBracket(span) | RightParen(span) | Semicolon(span)
             | Assign(span) | Equal(span) | Greater(span) | GreaterOrEqual(span) | Less(span) | LessOrEqual(span)
             | Minus(span) | Not(span) | NotEqual(span) | Plus(span) | Slash(span) | Star(span) | Percentage(span)
             | Null(span) | Boolean(span) | Integer(span) | Real(span) | SemVer(span) | String(span) | Ident(span)
-            | New(span) => span,
+            | New(span) | DocComment(span) => span,
             // None should have been filtered out already.
             None => unreachable!(),
         }
@@ -217,6 +228,8 @@ pub struct Tokens<'a> {
     pub tok: &'a [Token<'a>],
     pub start: usize,
     pub end: usize,
+    /// The language that is being parsed, which enables dialect-specific statements (e.g., Bakery sentences).
+    pub lang: Language,
 }
 
 impl<'a> Tokens<'a> {
@@ -225,8 +238,17 @@ impl<'a> Tokens<'a> {
             tok: vec,
             start: 0,
             end: vec.len(),
+            lang: Language::BraneScript,
         }
     }
+
+    pub fn with_lang(
+        mut self,
+        lang: Language,
+    ) -> Self {
+        self.lang = lang;
+        self
+    }
 }
 
 impl<'a> InputLength for Tokens<'a> {
@@ -246,6 +268,7 @@ impl<'a> InputTake for Tokens<'a> {
             tok: &self.tok[0..count],
             start: 0,
             end: count,
+            lang: self.lang,
         }
     }
 
@@ -259,11 +282,13 @@ impl<'a> InputTake for Tokens<'a> {
             tok: prefix,
             start: 0,
             end: prefix.len(),
+            lang: self.lang,
         };
         let second = Tokens {
             tok: suffix,
             start: 0,
             end: suffix.len(),
+            lang: self.lang,
         };
         (second, first)
     }
@@ -286,6 +311,7 @@ impl<'a> Slice<Range<usize>> for Tokens<'a> {
             tok: self.tok.slice(range.clone()),
             start: self.start + range.start,
             end: self.start + range.end,
+            lang: self.lang,
         }
     }
 }
@@ -320,6 +346,7 @@ impl<'a> Slice<RangeFull> for Tokens<'a> {
             tok: self.tok,
             start: self.start,
             end: self.end,
+            lang: self.lang,
         }
     }
 }
@@ -23,6 +23,37 @@ use serde::{Deserialize, Serialize};
 use crate::errors::LanguageParseError;
 
 
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Tests that the backoff doubles for every retry, without overflowing or exceeding the maximum.
+    #[test]
+    fn test_retry_delay() {
+        let policy: RetryPolicy = RetryPolicy{ max_retries: 5, backoff: 500 };
+        assert_eq!((1..=5).map(|attempt| policy.delay(attempt)).collect::<Vec<u64>>(), vec![ 500, 1000, 2000, 4000, 8000 ]);
+        // Attempt 0 is not a retry, but does not underflow either
+        assert_eq!(policy.delay(0), 500);
+
+        // Long waits are capped
+        assert_eq!(policy.delay(20), RetryPolicy::MAX_BACKOFF);
+        assert_eq!(policy.delay(64), RetryPolicy::MAX_BACKOFF);
+        assert_eq!(policy.delay(u32::MAX), RetryPolicy::MAX_BACKOFF);
+        assert_eq!(RetryPolicy{ max_retries: 1, backoff: u64::MAX }.delay(2), RetryPolicy::MAX_BACKOFF);
+
+        // No backoff means no waiting
+        assert_eq!(RetryPolicy{ max_retries: 3, backoff: 0 }.delay(3), 0);
+        assert_eq!(RetryPolicy::new(3).delay(1), RetryPolicy::default_backoff());
+    }
+}
+
+
+
+
+
+
 /***** LIBRARY *****/
 /// Defines a position in the input text.
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -282,6 +313,67 @@ impl FromStr for Language {
 
 
 
+/// Defines the editions of the languages. An edition determines which words are reserved as keywords, such that new keywords can be introduced without breaking scripts that use them as identifiers.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub enum Edition {
+    /// The original edition.
+    E2022,
+    /// Reserves `in` for loops over arrays, and `match` and `try` for pattern matching and error handling.
+    E2026,
+}
+
+impl Edition {
+    /// The most recent edition, which should be used for new scripts.
+    pub const LATEST: Self = Self::E2026;
+
+
+
+    /// Returns the words that are reserved as keywords in this edition, on top of those reserved in all editions.
+    /// 
+    /// # Returns
+    /// A list of words that may not be used as identifiers.
+    #[inline]
+    pub fn reserved(&self) -> &'static [&'static str] {
+        use Edition::*;
+        match self {
+            E2022 => &[],
+            E2026 => &[ "in", "match", "try" ],
+        }
+    }
+}
+
+impl Default for Edition {
+    /// Defaults to the original edition, such that existing scripts keep working unless they opt-in to a new one.
+    #[inline]
+    fn default() -> Self { Self::E2022 }
+}
+
+impl Display for Edition {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use Edition::*;
+        match self {
+            E2022 => write!(f, "2022"),
+            E2026 => write!(f, "2026"),
+        }
+    }
+}
+
+impl FromStr for Edition {
+    type Err = LanguageParseError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "2022"   => Ok(Self::E2022),
+            "2026"   => Ok(Self::E2026),
+            "latest" => Ok(Self::LATEST),
+            raw      => Err(LanguageParseError::UnknownEdition { raw: raw.into() }),
+        }
+    }
+}
+
+
+
 /// Defines merge strategies for the parallel statements.
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Hash, Serialize)]
 pub enum MergeStrategy {
@@ -346,6 +438,50 @@ impl From<String> for MergeStrategy {
 
 
 
+/// Defines how often a failed task call is retried, and how long to wait in between.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Hash, Serialize)]
+pub struct RetryPolicy {
+    /// The maximum number of times the call is retried after it failed the first time.
+    #[serde(rename = "m")]
+    pub max_retries : u32,
+    /// The time to wait before the first retry, in milliseconds. It is doubled for every retry after that.
+    #[serde(rename = "b", default = "RetryPolicy::default_backoff")]
+    pub backoff     : u64,
+}
+
+impl RetryPolicy {
+    /// The maximum time we wait before a single retry, in milliseconds.
+    pub const MAX_BACKOFF: u64 = 5 * 60 * 1000;
+
+    /// Constructor for the RetryPolicy that uses the default backoff.
+    ///
+    /// # Arguments
+    /// - `max_retries`: The maximum number of times a failed call is retried.
+    ///
+    /// # Returns
+    /// A new RetryPolicy instance.
+    #[inline]
+    pub fn new(max_retries: u32) -> Self { Self { max_retries, backoff: Self::default_backoff() } }
+
+    /// Returns the default backoff before the first retry, in milliseconds.
+    #[inline]
+    pub fn default_backoff() -> u64 { 1000 }
+
+    /// Computes how long to wait before the given retry.
+    ///
+    /// # Arguments
+    /// - `attempt`: The retry to compute the wait time of (one-indexed, i.e., `1` is the first retry).
+    ///
+    /// # Returns
+    /// The time to wait, in milliseconds. This is exponential in `attempt`, but never more than `RetryPolicy::MAX_BACKOFF`.
+    pub fn delay(&self, attempt: u32) -> u64 {
+        let factor: u64 = 1u64.checked_shl(attempt.saturating_sub(1)).unwrap_or(u64::MAX);
+        self.backoff.saturating_mul(factor).min(Self::MAX_BACKOFF)
+    }
+}
+
+
+
 // /// Defines the supported data types in BraneScript/Bakery.
 // #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 // pub enum DataType {
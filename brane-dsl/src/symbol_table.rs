@@ -19,6 +19,7 @@ use std::fmt::Debug;
 use std::mem;
 use std::rc::Rc;
 
+use specifications::common::Deprecation;
 use specifications::package::Capability;
 use specifications::version::Version;
 
@@ -27,6 +28,52 @@ use crate::spec::TextRange;
 use crate::data_type::{DataType, ClassSignature, FunctionSignature};
 
 
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Tests that functions can be found by their aliases, and that aliases cannot be shared by functions in the same table.
+    #[test]
+    fn test_func_aliases() {
+        let st: Rc<RefCell<SymbolTable>> = SymbolTable::new();
+        let mut old: FunctionEntry = FunctionEntry::from_builtin("new_name", FunctionSignature::default(), TextRange::none());
+        old.aliases = vec![ "old_name".into(), "older_name".into() ];
+        st.borrow_mut().add_func(old).unwrap();
+
+        // The function is found by any of its aliases, but not by names that are not one
+        assert_eq!(st.borrow().get_func_by_alias("old_name").map(|f| f.borrow().name.clone()), Some("new_name".into()));
+        assert_eq!(st.borrow().get_func_by_alias("older_name").map(|f| f.borrow().name.clone()), Some("new_name".into()));
+        assert!(st.borrow().get_func_by_alias("new_name").is_none());
+
+        // Another function may not claim one of those aliases...
+        let mut other: FunctionEntry = FunctionEntry::from_builtin("other_name", FunctionSignature::default(), TextRange::none());
+        other.aliases = vec![ "unused_name".into(), "older_name".into() ];
+        match st.borrow_mut().add_func(other) {
+            Err(Error::DuplicateAlias{ alias, name, other, .. }) => { assert_eq!((alias.as_str(), name.as_str(), other.as_str()), ("older_name", "other_name", "new_name")); },
+            Err(err)                                            => { panic!("Adding a function with a taken alias failed for the wrong reason: {}", err); },
+            Ok(_)                                               => { panic!("Adding a function with a taken alias succeeded"); },
+        }
+        // ...and is not added when it does
+        assert!(st.borrow().get_func("other_name").is_none());
+        assert!(st.borrow().get_func_by_alias("unused_name").is_none());
+        assert_eq!(st.borrow().get_func_by_alias("older_name").map(|f| f.borrow().name.clone()), Some("new_name".into()));
+
+        // Nested tables may shadow the aliases of their parents, like they do with names
+        let nested: Rc<RefCell<SymbolTable>> = SymbolTable::new();
+        nested.borrow_mut().parent = Some(st.clone());
+        let mut shadow: FunctionEntry = FunctionEntry::from_builtin("shadow_name", FunctionSignature::default(), TextRange::none());
+        shadow.aliases = vec![ "old_name".into() ];
+        nested.borrow_mut().add_func(shadow).unwrap();
+        assert_eq!(nested.borrow().get_func_by_alias("old_name").map(|f| f.borrow().name.clone()), Some("shadow_name".into()));
+        assert_eq!(nested.borrow().get_func_by_alias("older_name").map(|f| f.borrow().name.clone()), Some("new_name".into()));
+    }
+}
+
+
+
+
 /// Defines a symbol table entry within the SymbolTable.
 #[derive(Clone, Debug)]
 pub enum SymbolTableEntry {
@@ -117,6 +164,14 @@ pub struct FunctionEntry {
     pub arg_names    : Vec<String>,
     /// Any requirements the function has in terms of hardware support. Only ever not-None if an external function.
     pub requirements : Option<HashSet<Capability>>,
+    /// If this function is external, then this is set if its package marks it as deprecated.
+    pub deprecated   : Option<Deprecation>,
+    /// If this function is external, then this lists the other names under which it may also be called.
+    pub aliases      : Vec<String>,
+    /// If this function is external, then this lists the names of the secrets it needs from the worker.
+    pub secrets      : Vec<String>,
+    /// The documentation comments (`///`) written above the function definition, if any.
+    pub docs         : Option<String>,
 
     /// The index in the workflow buffer of this function.
     pub index : usize,
@@ -153,6 +208,10 @@ impl FunctionEntry {
 
             arg_names    : vec![],
             requirements : None,
+            deprecated   : None,
+            aliases      : vec![],
+            secrets      : vec![],
+            docs         : None,
 
             index : usize::MAX,
 
@@ -184,6 +243,10 @@ impl FunctionEntry {
 
             arg_names    : vec![],
             requirements : None,
+            deprecated   : None,
+            aliases      : vec![],
+            secrets      : vec![],
+            docs         : None,
 
             index : usize::MAX,
 
@@ -221,6 +284,10 @@ impl FunctionEntry {
 
             arg_names,
             requirements : Some(requirements),
+            deprecated   : None,
+            aliases      : vec![],
+            secrets      : vec![],
+            docs         : None,
 
             index : usize::MAX,
 
@@ -254,6 +321,10 @@ impl FunctionEntry {
 
             arg_names    : vec![],
             requirements : None,
+            deprecated   : None,
+            aliases      : vec![],
+            secrets      : vec![],
+            docs         : None,
 
             index : usize::MAX,
 
@@ -276,6 +347,8 @@ pub struct ClassEntry {
     pub package_name    : Option<String>,
     /// If set to non-zero, then this function is imported from a package with the given version.
     pub package_version : Option<Version>,
+    /// The documentation comments (`///`) written above the class definition, if any.
+    pub docs            : Option<String>,
 
     /// The index in the workflow buffer of this class.
     pub index : usize,
@@ -304,6 +377,7 @@ impl ClassEntry {
 
             package_name    : None,
             package_version : None,
+            docs            : None,
 
             index : usize::MAX,
 
@@ -328,6 +402,7 @@ impl ClassEntry {
 
             package_name    : None,
             package_version : None,
+            docs            : None,
 
             index : usize::MAX,
 
@@ -357,6 +432,7 @@ impl ClassEntry {
 
             package_name    : Some(package.into()),
             package_version : Some(package_version),
+            docs            : None,
 
             index : usize::MAX,
 
@@ -518,12 +594,20 @@ impl SymbolTable {
     /// The newly created entry (or rather, a reference-counted pointer to it).
     /// 
     /// # Errors
-    /// This function may error if an entry with this name in this namespace already exists.
+    /// This function may error if an entry with this name in this namespace already exists, or if another entry in this namespace already has one of its aliases.
     pub fn add_func(&mut self, entry: FunctionEntry) -> Result<Rc<RefCell<FunctionEntry>>, Error> {
         // Extract some properties of the entry we (might) need later
         let name  : String    = entry.name.clone();
         let range : TextRange = entry.range.clone();
 
+        // Refuse aliases that are already taken, since we could not tell which function a call by them refers to
+        for alias in &entry.aliases {
+            if let Some(other) = self.functions.values().find(|f| { let f: Ref<FunctionEntry> = f.borrow(); f.name != name && f.aliases.contains(alias) }) {
+                let other: Ref<FunctionEntry> = other.borrow();
+                return Err(Error::DuplicateAlias{ alias: alias.clone(), name, other: other.name.clone(), existing: other.range.clone(), got: range });
+            }
+        }
+
         // Add it to the table (we overwrite any old ones to better support future errors).
         let entry: Rc<RefCell<FunctionEntry>> = Rc::new(RefCell::new(entry));
         let old: Option<Rc<RefCell<FunctionEntry>>> = self.functions.insert(name, entry.clone());
@@ -649,6 +733,33 @@ impl SymbolTable {
         }
     }
 
+    /// Returns the entry in the function namespace that has the given name as an alias, if any.
+    /// 
+    /// This is used to resolve calls to functions that have been renamed in their package.
+    /// 
+    /// # Generic arguments
+    /// - `S`: The &str-like type of the target `alias`.
+    /// 
+    /// # Arguments
+    /// - `alias`: The alias of the entry to retrieve.
+    /// 
+    /// # Returns
+    /// A reference-counter pointer to the entry if it exists, or else None.
+    pub fn get_func_by_alias<S: AsRef<str>>(&self, alias: S) -> Option<Rc<RefCell<FunctionEntry>>> {
+        // Try ourselves or else the parent
+        match self.functions.values().find(|f| f.borrow().aliases.iter().any(|a| a == alias.as_ref())) {
+            Some(entry) => Some(entry.clone()),
+            None        => match &self.parent {
+                Some(parent) => {
+                    // Try our parent instead
+                    let st: Ref<SymbolTable> = parent.borrow();
+                    st.get_func_by_alias(alias)
+                },
+                None => None,
+            }
+        }
+    }
+
     /// Returns the entry in the class namespace with the given name if it exists.
     /// 
     /// # Generic arguments
@@ -0,0 +1,161 @@
+//  VOCABULARY.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 11:13:15
+//  Last edited:
+//    16 Oct 2026, 11:13:15
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Defines the vocabulary with which Bakery sentences are read. It maps
+//!   verbs to the functions they call and lists the filler words that
+//!   make sentences read naturally, such that domain-specific or
+//!   non-English verb sets can be plugged in.
+// 
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+pub use crate::errors::VocabularyError as Error;
+
+
+/***** LIBRARY *****/
+/// Defines the words that Bakery sentences may use.
+/// 
+/// A sentence like `say the greeting to "Amy";` is read as a call to the function that the first word (the verb) maps to, with the remaining words as arguments. Filler words (like `the` or `to`) are dropped, such that the sentence above calls `println(greeting, "Amy")` in the English vocabulary.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Vocabulary {
+    /// The (natural) language of the vocabulary, as a code like `en` or `nl`.
+    pub language : String,
+    /// Maps verbs to the name of the function they call. Verbs that are not in here call the function with the same name.
+    #[serde(default)]
+    pub verbs    : HashMap<String, String>,
+    /// The words that only make sentences read naturally. They are not passed as arguments, so variables with these names cannot be used in sentences.
+    #[serde(default)]
+    pub fillers  : HashSet<String>,
+}
+
+impl Vocabulary {
+    /// Constructor for a Vocabulary that has no verbs and no fillers.
+    /// 
+    /// # Arguments
+    /// - `language`: The (natural) language of the vocabulary.
+    /// 
+    /// # Returns
+    /// A new Vocabulary in which every verb calls the function with the same name.
+    #[inline]
+    pub fn new(language: impl Into<String>) -> Self {
+        Self {
+            language : language.into(),
+            verbs    : HashMap::new(),
+            fillers  : HashSet::new(),
+        }
+    }
+
+    /// Constructor for the default, English Vocabulary.
+    /// 
+    /// # Returns
+    /// A new Vocabulary that knows some English verbs for the builtin functions and the most common English articles and prepositions.
+    pub fn english() -> Self {
+        let mut vocabulary: Self = Self::new("en");
+        for verb in &[ "say", "show", "display" ] {
+            vocabulary = vocabulary.with_verb(*verb, "println");
+        }
+        for filler in &[ "a", "an", "the", "and", "as", "by", "from", "of", "to", "using", "with" ] {
+            vocabulary = vocabulary.with_filler(*filler);
+        }
+        vocabulary
+    }
+
+    /// Constructor for the Vocabulary that loads it from the given (YAML) file.
+    /// 
+    /// # Arguments
+    /// - `path`: The path of the vocabulary file to load.
+    /// 
+    /// # Returns
+    /// A new Vocabulary with the verbs and fillers in the file.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to open or parse the file.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path: &Path = path.as_ref();
+
+        // Open the file
+        let handle: File = match File::open(path) {
+            Ok(handle) => handle,
+            Err(err)   => { return Err(Error::FileOpenError{ path: path.into(), err }); },
+        };
+
+        // Parse it, normalizing the words such that they are matched case-insensitively
+        let vocabulary: Self = match serde_yaml::from_reader(handle) {
+            Ok(vocabulary) => vocabulary,
+            Err(err)       => { return Err(Error::FileParseError{ path: path.into(), err }); },
+        };
+        Ok(Self {
+            language : vocabulary.language,
+            verbs    : vocabulary.verbs.into_iter().map(|(verb, function)| (verb.to_lowercase(), function)).collect(),
+            fillers  : vocabulary.fillers.into_iter().map(|filler| filler.to_lowercase()).collect(),
+        })
+    }
+
+
+
+    /// Adds a verb to the vocabulary.
+    /// 
+    /// # Arguments
+    /// - `verb`: The verb to add.
+    /// - `function`: The name of the function that the verb calls.
+    /// 
+    /// # Returns
+    /// The same Vocabulary, but now knowing the given verb.
+    #[inline]
+    pub fn with_verb(mut self, verb: impl AsRef<str>, function: impl Into<String>) -> Self {
+        self.verbs.insert(verb.as_ref().to_lowercase(), function.into());
+        self
+    }
+
+    /// Adds a filler word to the vocabulary.
+    /// 
+    /// # Arguments
+    /// - `filler`: The word to add.
+    /// 
+    /// # Returns
+    /// The same Vocabulary, but now ignoring the given word in sentences.
+    #[inline]
+    pub fn with_filler(mut self, filler: impl AsRef<str>) -> Self {
+        self.fillers.insert(filler.as_ref().to_lowercase());
+        self
+    }
+
+
+
+    /// Returns the function called by the given verb, if the vocabulary knows it.
+    /// 
+    /// # Arguments
+    /// - `verb`: The verb to look up (case-insensitively).
+    /// 
+    /// # Returns
+    /// The name of the function, or `None` if this is not a verb in this vocabulary.
+    #[inline]
+    pub fn function(&self, verb: &str) -> Option<&str> { self.verbs.get(&verb.to_lowercase()).map(|f| f.as_str()) }
+
+    /// Returns whether the given word is a filler word in this vocabulary.
+    /// 
+    /// # Arguments
+    /// - `word`: The word to check (case-insensitively).
+    /// 
+    /// # Returns
+    /// True if the word should be dropped from sentences, or false otherwise.
+    #[inline]
+    pub fn is_filler(&self, word: &str) -> bool { self.fillers.contains(&word.to_lowercase()) }
+}
+
+impl Default for Vocabulary {
+    #[inline]
+    fn default() -> Self { Self::english() }
+}
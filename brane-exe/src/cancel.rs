@@ -0,0 +1,96 @@
+//  CANCEL.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 10:31:21
+//  Last edited:
+//    16 Oct 2026, 10:31:21
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements cooperative cancellation of running workflows. Threads
+//!   check the token between edges, and abandon the edge they are
+//!   executing (e.g., a task call) once it is cancelled.
+// 
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tokio::sync::Notify;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use super::*;
+
+
+    /// Tests whether waiting on a token returns once it is cancelled, and only then.
+    #[tokio::test]
+    async fn test_cancelled() {
+        let token: CancelToken = CancelToken::new();
+        assert!(!token.is_cancelled());
+        assert!(tokio::time::timeout(Duration::from_millis(50), token.cancelled()).await.is_err());
+
+        // Cancel it from somewhere else
+        let waiter = {
+            let token: CancelToken = token.clone();
+            tokio::spawn(async move { token.cancelled().await })
+        };
+        token.cancel();
+        assert!(tokio::time::timeout(Duration::from_secs(5), waiter).await.is_ok());
+        assert!(token.is_cancelled());
+
+        // Waiting on an already cancelled token returns immediately
+        assert!(tokio::time::timeout(Duration::from_millis(50), token.cancelled()).await.is_ok());
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// A token that may be shared by all threads of a workflow to cancel it cooperatively.
+/// 
+/// Clones of the token refer to the same cancellation state.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken {
+    /// Whether the token has been cancelled.
+    cancelled : Arc<AtomicBool>,
+    /// Wakes up the threads waiting for the token to be cancelled.
+    notify    : Arc<Notify>,
+}
+
+impl CancelToken {
+    /// Constructor for the CancelToken.
+    /// 
+    /// # Returns
+    /// A new CancelToken that is not cancelled.
+    #[inline]
+    pub fn new() -> Self { Self::default() }
+
+
+
+    /// Cancels the token, waking up everyone waiting for it. Cancelling an already cancelled token does nothing.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        self.notify.notify_waiters();
+    }
+
+    /// Returns whether the token has been cancelled.
+    #[inline]
+    pub fn is_cancelled(&self) -> bool { self.cancelled.load(Ordering::SeqCst) }
+
+    /// Waits until the token is cancelled.
+    pub async fn cancelled(&self) {
+        loop {
+            // Register before checking, so that we do not miss a cancel in between
+            let notified = self.notify.notified();
+            if self.is_cancelled() { return; }
+            notified.await;
+        }
+    }
+}
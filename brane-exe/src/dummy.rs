@@ -23,10 +23,11 @@ use log::info;
 use brane_ast::Workflow;
 use brane_ast::locations::Location;
 use brane_ast::ast::{DataName, Edge, SymTable};
-use specifications::data::{AccessKind, AvailabilityKind};
+use specifications::data::{AccessKind, AvailabilityKind, DataIndex};
+use specifications::package::PackageIndex;
 
 pub use crate::errors::VmError as Error;
-use crate::spec::{CustomGlobalState, RunState, TaskInfo, VmPlugin};
+use crate::spec::{ApprovalDecision, Checkpoint, CustomGlobalState, RunState, TaskInfo, VmPlugin};
 use crate::value::FullValue;
 use crate::vm::Vm;
 
@@ -38,7 +39,11 @@ pub struct DummyState {
     /// The text to buffer when writing to stdout.
     /// 
     /// It looks overkill to have a mutex here, but this is required in the test of `thread.rs` due to it not using a wrapping VM.
-    pub text : Arc<Mutex<String>>,
+    pub text        : Arc<Mutex<String>>,
+    /// The checkpoints made while executing, in the order they were made.
+    pub checkpoints : Arc<Mutex<Vec<Checkpoint>>>,
+    /// The (mocked) outputs of tasks, by function name. These take precedence over the builtin dummy outputs.
+    pub outputs     : Arc<HashMap<String, FullValue>>,
 }
 
 impl CustomGlobalState for DummyState {}
@@ -57,6 +62,10 @@ impl VmPlugin for DummyPlugin {
     type ExecuteError    = Error;
     type StdoutError     = Error;
     type CommitError     = Error;
+    type EventError      = Error;
+    type NotifyError     = Error;
+    type IndexError      = Error;
+    type CheckpointError = Error;
 
 
     async fn preprocess(_global: Arc<RwLock<Self::GlobalState>>, _local: Self::LocalState, _loc: Location, name: DataName, _preprocess: specifications::data::PreprocessKind) -> Result<AccessKind, Self::PreprocessError> {
@@ -66,7 +75,7 @@ impl VmPlugin for DummyPlugin {
         Ok(AccessKind::File{ path: PathBuf::new() })
     }
 
-    async fn execute(_global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, info: TaskInfo<'_>) -> Result<Option<FullValue>, Self::ExecuteError> {
+    async fn execute(global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, info: TaskInfo<'_>) -> Result<Option<FullValue>, Self::ExecuteError> {
         info!("Processing dummy call to '{}'@'{}' with {} in {}[{}]...",
             info.name,
             info.location,
@@ -75,7 +84,10 @@ impl VmPlugin for DummyPlugin {
             info.package_version,
         );
 
-        // Return according to the name of the function called
+        // Return the mocked output if there is one
+        if let Some(output) = global.read().unwrap().outputs.get(info.name) { return Ok(Some(output.clone())); }
+
+        // Otherwise, return according to the name of the function called
         match info.name {
             "hello_world"   => Ok(Some(FullValue::String("Hello, world!".into()))),
             "run_script"    => Ok(Some(FullValue::Void)),
@@ -85,7 +97,10 @@ impl VmPlugin for DummyPlugin {
         }
     }
 
-    async fn stdout(global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, text: &str, newline: bool) -> Result<(), Self::StdoutError> {
+    #[inline]
+    fn is_transient(_err: &Self::ExecuteError) -> bool { false }
+
+    async fn stdout(global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, _branch: &[usize], text: &str, newline: bool) -> Result<(), Self::StdoutError> {
         info!("Processing dummy stdout write (newline: {})...",
             if newline { "yes" } else { "no" },
         );
@@ -107,7 +122,7 @@ impl VmPlugin for DummyPlugin {
         // We don't really do anything, unfortunately
         Ok(())
     }
-    async fn commit(_global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, _loc: &Location, name: &str, path: &Path, data_name: &str) -> Result<(), Self::CommitError> {
+    async fn commit(_global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, _loc: &Location, name: &str, path: &Path, data_name: &str, _residency: Option<&[Location]>) -> Result<(), Self::CommitError> {
         info!("Processing dummy commit for result '{}' @ '{:?}' to '{}'...",
             name, path.display(), data_name,
         );
@@ -115,6 +130,45 @@ impl VmPlugin for DummyPlugin {
         // We don't really do anything, unfortunately
         Ok(())
     }
+
+    async fn wait_event(_global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, name: &str) -> Result<String, Self::EventError> {
+        info!("Processing dummy wait for event '{}'...", name);
+
+        // Events arrive immediately, and are always empty
+        Ok(String::new())
+    }
+
+    async fn require_approval(_global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, message: &str) -> Result<ApprovalDecision, Self::EventError> {
+        info!("Processing dummy approval request '{}'...", message);
+
+        // Everything is approved immediately
+        Ok(ApprovalDecision{ approved: true, user: "dummy".into(), reason: None })
+    }
+
+    async fn notify(_global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, channel: &str, message: &str) -> Result<(), Self::NotifyError> {
+        info!("Processing dummy notification on channel '{}': '{}'", channel, message);
+
+        // We don't really do anything, unfortunately
+        Ok(())
+    }
+
+    async fn indices(_global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState) -> Result<(Arc<PackageIndex>, Arc<DataIndex>), Self::IndexError> {
+        info!("Processing dummy index query...");
+
+        // The dummy instance has nothing to offer
+        Ok((Arc::new(PackageIndex::empty()), Arc::new(DataIndex::default())))
+    }
+
+
+
+    async fn checkpoint(global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, checkpoint: &Checkpoint) -> Result<(), Self::CheckpointError> {
+        info!("Processing dummy checkpoint at edge {}...", checkpoint.pc.1);
+
+        // Keep them in memory so tests can resume from them
+        let state: RwLockReadGuard<DummyState> = global.read().unwrap();
+        state.checkpoints.lock().unwrap().push(checkpoint.clone());
+        Ok(())
+    }
 }
 
 
@@ -231,9 +285,19 @@ impl DummyVm {
     /// # Returns
     /// A new instance of a DummyVm.
     #[inline]
-    pub fn new() -> Self {
+    pub fn new() -> Self { Self::with_outputs(HashMap::new()) }
+
+    /// Constructor for the DummyVm that mocks the outputs of the tasks it runs.
+    /// 
+    /// # Arguments
+    /// - `outputs`: The outputs to return for tasks, by function name. Tasks that are not in here return the builtin dummy outputs.
+    /// 
+    /// # Returns
+    /// A new instance of a DummyVm.
+    #[inline]
+    pub fn with_outputs(outputs: HashMap<String, FullValue>) -> Self {
         Self {
-            state : Self::new_state(DummyState{ text: Arc::new(Mutex::new(String::new())) }),
+            state : Self::new_state(DummyState{ text: Arc::new(Mutex::new(String::new())), checkpoints: Arc::new(Mutex::new(vec![])), outputs: Arc::new(outputs) }),
         }
     }
 
@@ -250,6 +314,16 @@ impl DummyVm {
         print!("{}", text);
         *text = String::new();
     }
+
+    /// Returns the buffered text instead of printing it, clearing it again.
+    /// 
+    /// # Returns
+    /// The text written to stdout since the last flush.
+    pub fn take_stdout(&self) -> String {
+        let state: RwLockWriteGuard<DummyState> = self.state.global.write().unwrap();
+        let mut text: MutexGuard<String> = state.text.lock().unwrap();
+        mem::take(&mut *text)
+    }
 }
 
 impl Default for DummyVm {
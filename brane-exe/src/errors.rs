@@ -221,6 +221,33 @@ impl Error for VarRegError {}
 
 
 
+/// Defines errors that relate to execution traces.
+#[derive(Debug)]
+pub enum TraceError {
+    /// Failed to create the file to write the trace to.
+    FileCreateError{ path: PathBuf, err: std::io::Error },
+    /// Failed to read a trace file.
+    FileReadError{ path: PathBuf, err: std::io::Error },
+    /// Failed to parse an event in a trace file.
+    EventParseError{ path: PathBuf, line: usize, err: serde_json::Error },
+}
+
+impl Display for TraceError {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use TraceError::*;
+        match self {
+            FileCreateError{ path, err }       => write!(f, "Failed to create trace file '{}': {}", path.display(), err),
+            FileReadError{ path, err }         => write!(f, "Failed to read trace file '{}': {}", path.display(), err),
+            EventParseError{ path, line, err } => write!(f, "Failed to parse event on line {} of trace file '{}': {}", line, path.display(), err),
+        }
+    }
+}
+
+impl Error for TraceError {}
+
+
+
 /// Defines errors that relate to a VM's execution.
 #[derive(Debug)]
 pub enum VmError {
@@ -245,6 +272,12 @@ pub enum VmError {
     CastError{ edge: usize, instr: usize, err: ValueError },
     /// The given integer was out-of-bounds for an array with given length.
     ArrIdxOutOfBoundsError{ edge: usize, instr: usize, got: i64, max: usize },
+    /// An integer was divided by zero (or its remainder taken).
+    DivisionByZero{ edge: usize, instr: usize },
+    /// The result of integer arithmetic does not fit in 64 bits.
+    IntegerOverflow{ edge: usize, instr: usize, expr: String },
+    /// The given key was not present in the indexed map.
+    MapKeyError{ edge: usize, instr: usize, key: String },
     /// The given field was not present in the given class
     ProjUnknownFieldError{ edge: usize, instr: usize, class: String, field: String },
     /// Could not get the value of a variable.
@@ -280,6 +313,8 @@ pub enum VmError {
 
     /// A given asset was not found at all.
     UnknownData{ edge: usize, name: String },
+    /// The size of a given asset is not known to the instance.
+    UnknownDataSize{ edge: usize, name: String },
     /// A given intermediate result was not found at all.
     UnknownResult{ edge: usize, name: String },
     /// The given package was not known.
@@ -287,6 +322,28 @@ pub enum VmError {
     /// Failed to serialize the given argument list.
     ArgumentsSerializeError{ edge: usize, err: serde_json::Error },
 
+    /// A thread attempted to take a lock it already holds.
+    LockAlreadyHeld{ edge: usize, name: String },
+    /// A thread attempted to release a lock it does not hold.
+    LockNotHeld{ edge: usize, name: String },
+    /// A thread attempted to sleep for a negative amount of time.
+    IllegalSleepDuration{ edge: usize, seconds: i64 },
+    /// A thread attempted to wait until a point in time that we could not parse.
+    IllegalTimestamp{ edge: usize, raw: String, err: chrono::ParseError },
+    /// A user rejected the approval that a thread required.
+    ApprovalRejected{ edge: usize, message: String, user: String, reason: Option<String> },
+    /// The workflow was cancelled while a thread was at the given edge.
+    Cancelled{ edge: usize },
+    /// The workflow took more steps than it was allowed to.
+    ExecutionLimitExceeded{ edge: usize, limit: u64 },
+
+    /// A thread attempted to take a substring that does not lie within the string.
+    IllegalSubstring{ edge: usize, start: i64, end: i64, len: usize },
+    /// A thread attempted to parse a string that is not an integer.
+    IllegalInteger{ edge: usize, raw: String, err: std::num::ParseIntError },
+    /// A thread attempted to parse a string that is not a real.
+    IllegalReal{ edge: usize, raw: String, err: std::num::ParseFloatError },
+
     /// An error that relates to the stack.
     StackError{ edge: usize, instr: Option<usize>, err: StackError },
     /// A Vm-defined error.
@@ -310,6 +367,9 @@ impl VmError {
             InstanceTypeError{ edge, instr, .. }       => prettyprint_err_instr(*edge, Some(*instr), self),
             CastError{ edge, instr, .. }               => prettyprint_err_instr(*edge, Some(*instr), self),
             ArrIdxOutOfBoundsError { edge, instr, .. } => prettyprint_err_instr(*edge, Some(*instr), self),
+            DivisionByZero{ edge, instr }              => prettyprint_err_instr(*edge, Some(*instr), self),
+            IntegerOverflow{ edge, instr, .. }         => prettyprint_err_instr(*edge, Some(*instr), self),
+            MapKeyError{ edge, instr, .. }             => prettyprint_err_instr(*edge, Some(*instr), self),
             ProjUnknownFieldError{ edge, instr, .. }   => prettyprint_err_instr(*edge, Some(*instr), self),
             VarGetError{ edge, instr, .. }             => prettyprint_err_instr(*edge, Some(*instr), self),
             VarSetError{ edge, instr, .. }             => prettyprint_err_instr(*edge, Some(*instr), self),
@@ -329,10 +389,23 @@ impl VmError {
             TaskTypeError{ edge, .. } => prettyprint_err(*edge, self),
 
             UnknownData{ edge, .. }             => prettyprint_err(*edge, self),
+            UnknownDataSize{ edge, .. }         => prettyprint_err(*edge, self),
             UnknownResult{ edge, .. }           => prettyprint_err(*edge, self),
             UnknownPackage{ edge, .. }          => prettyprint_err(*edge, self),
             ArgumentsSerializeError{ edge, .. } => prettyprint_err(*edge, self),
 
+            LockAlreadyHeld{ edge, .. }        => prettyprint_err(*edge, self),
+            LockNotHeld{ edge, .. }            => prettyprint_err(*edge, self),
+            IllegalSleepDuration{ edge, .. }   => prettyprint_err(*edge, self),
+            IllegalTimestamp{ edge, .. }       => prettyprint_err(*edge, self),
+            ApprovalRejected{ edge, .. }       => prettyprint_err(*edge, self),
+            Cancelled{ edge }                  => prettyprint_err(*edge, self),
+            ExecutionLimitExceeded{ edge, .. } => prettyprint_err(*edge, self),
+
+            IllegalSubstring{ edge, .. } => prettyprint_err(*edge, self),
+            IllegalInteger{ edge, .. }   => prettyprint_err(*edge, self),
+            IllegalReal{ edge, .. }      => prettyprint_err(*edge, self),
+
             StackError{ edge, instr, .. } => prettyprint_err_instr(*edge, *instr, self),
             Custom{ edge, .. }            => prettyprint_err(*edge, self),
         }
@@ -355,6 +428,9 @@ impl Display for VmError {
             InstanceTypeError{ class, field, got, expected, .. } => write!(f, "Expected field '{}' of class '{}' to have type {}, but found type {}", field, class, expected, got),
             CastError{ err, .. }                                 => write!(f, "Failed to cast top value on the stack: {}", err),
             ArrIdxOutOfBoundsError { got, max, .. }              => write!(f, "Index {} is out-of-bounds for an array of length {}", got, max),
+            DivisionByZero{ .. }                                 => write!(f, "Division by zero"),
+            IntegerOverflow{ expr, .. }                          => write!(f, "Integer overflow: the result of '{}' does not fit in a 64-bit integer", expr),
+            MapKeyError{ key, .. }                               => write!(f, "Key '{}' does not exist in the map", key),
             ProjUnknownFieldError{ class, field, .. }            => write!(f, "Class '{}' has not field '{}'", class, field),
             VarGetError{ err, .. }                               => write!(f, "Could not get variable: {}", err),
             VarSetError{ err, .. }                               => write!(f, "Could not set variable: {}", err),
@@ -374,10 +450,23 @@ impl Display for VmError {
             TaskTypeError{ name, arg, got, expected, .. } => write!(f, "Task '{}' expected argument {} to be of type {}, but got {}", name, arg, expected, got),
 
             UnknownData{ name, .. }             => write!(f, "Encountered unknown dataset '{}'", name),
+            UnknownDataSize{ name, .. }         => write!(f, "The size of dataset '{}' is not known (only datasets stored as files report their size)", name),
             UnknownResult{ name, .. }           => write!(f, "Encountered unknown result '{}'", name),
             UnknownPackage{ name, version, .. } => write!(f, "Unknown package with name '{}'{}", name, if !version.is_latest() { format!(" and version {}", version) } else { String::new() }),
             ArgumentsSerializeError{ err, .. }  => write!(f, "Could not serialize task arguments: {}", err),
 
+            LockAlreadyHeld{ name, .. }                   => write!(f, "Cannot take lock '{}' because this branch already holds it", name),
+            LockNotHeld{ name, .. }                       => write!(f, "Cannot release lock '{}' because this branch does not hold it", name),
+            IllegalSleepDuration{ seconds, .. }           => write!(f, "Cannot sleep for a negative amount of time ({} seconds)", seconds),
+            IllegalTimestamp{ raw, err, .. }              => write!(f, "Cannot wait until '{}': not a valid RFC 3339 timestamp: {}", raw, err),
+            ApprovalRejected{ message, user, reason, .. } => write!(f, "User '{}' rejected approval for '{}'{}", user, message, if let Some(reason) = reason { format!(": {}", reason) } else { String::new() }),
+            Cancelled{ .. }                               => write!(f, "Workflow was cancelled"),
+            ExecutionLimitExceeded{ limit, .. }           => write!(f, "Execution limit exceeded: the workflow took more than {} steps (it may contain an infinite loop)", limit),
+
+            IllegalSubstring{ start, end, len, .. } => write!(f, "Cannot take substring [{}, {}) of a string of {} characters", start, end, len),
+            IllegalInteger{ raw, err, .. }          => write!(f, "Cannot parse '{}' as an integer: {}", raw, err),
+            IllegalReal{ raw, err, .. }             => write!(f, "Cannot parse '{}' as a real: {}", raw, err),
+
             StackError{ err, .. } => write!(f, "{}", err),
             Custom{ err, .. }     => write!(f, "{}", err),
         }
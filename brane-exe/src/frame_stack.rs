@@ -16,17 +16,20 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use serde::{Deserialize, Serialize};
+
 use brane_ast::DataType;
 use brane_ast::ast::{SymTable, VarDef};
 
 pub use crate::errors::FrameStackError as Error;
+use crate::spec::{FrameSummary, VarSummary};
 use crate::vtable::VirtualSymTable;
 use crate::value::Value;
 
 
 /***** HELPER SRUCTS *****/
 /// Defines a single Frame on the FrameStack.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 struct Frame {
     /// The function definition of the calling function. If usize::MAX, that means it's the main.
     def    : usize,
@@ -65,6 +68,17 @@ impl Frame {
 
 
 /***** LIBRARY *****/
+/// Defines the serializable part of a FrameStack, which can be used to restore it later.
+/// 
+/// The virtual symbol table is not part of it, as that can be rebuilt from the workflow's table and the functions in the frames.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FrameStackCheckpoint {
+    /// The frames on the stack, starting with the main frame.
+    frames : Vec<Frame>,
+}
+
+
+
 /// Implements a FrameStack, which is used to keep track of function calls and their expected return types.
 #[derive(Clone, Debug)]
 pub struct FrameStack {
@@ -96,6 +110,34 @@ impl FrameStack {
         }
     }
 
+    /// Constructor for the FrameStack that restores it from a checkpoint.
+    /// 
+    /// # Arguments
+    /// - `size`: The size of the FrameStack.
+    /// - `checkpoint`: The FrameStackCheckpoint (as returned by `FrameStack::checkpoint()`) to restore.
+    /// - `table`: The global scope of the workflow that was running when the checkpoint was made.
+    /// 
+    /// # Returns
+    /// A new FrameStack instance with the same frames and scopes as the checkpointed one.
+    pub fn from_checkpoint(size: usize, checkpoint: FrameStackCheckpoint, table: Arc<SymTable>) -> Self {
+        // Rebuild the virtual table by pushing the scope of every called function again
+        let mut vtable: VirtualSymTable = VirtualSymTable::with(table);
+        for f in &checkpoint.frames {
+            if f.def < usize::MAX {
+                let ftable: SymTable = vtable.func(f.def).table.clone();
+                vtable.push(Arc::new(ftable));
+            }
+        }
+
+        // Restore the frames themselves
+        let mut data: Vec<Frame> = Vec::with_capacity(size.max(checkpoint.frames.len()));
+        data.extend(checkpoint.frames);
+        Self {
+            data,
+            table : vtable,
+        }
+    }
+
     /// Forks the framestack, which copies the existing variables in-scope into a single frame that is the new main.
     /// 
     /// # Returns
@@ -250,6 +292,43 @@ impl FrameStack {
 
 
 
+    /// Returns the serializable part of the FrameStack, which can be used to restore it later.
+    /// 
+    /// # Returns
+    /// A FrameStackCheckpoint that can be given to `FrameStack::from_checkpoint()`.
+    #[inline]
+    pub fn checkpoint(&self) -> FrameStackCheckpoint {
+        FrameStackCheckpoint {
+            frames : self.data.clone(),
+        }
+    }
+
+    /// Summarizes the frames currently on the FrameStack and the variables defined in them.
+    /// 
+    /// # Returns
+    /// A list of FrameSummaries, starting with the main frame.
+    pub fn summary(&self) -> Vec<FrameSummary> {
+        self.data.iter().map(|f| {
+            // Sort the variables on definition order
+            let mut defs: Vec<&usize> = f.vars.keys().collect();
+            defs.sort();
+
+            FrameSummary {
+                function : if f.def < usize::MAX { Some(self.table.func(f.def).name.clone()) } else { None },
+                vars     : defs.into_iter().map(|def| {
+                    let var: &VarDef = self.table.var(*def);
+                    VarSummary {
+                        name      : var.name.clone(),
+                        data_type : var.data_type.clone(),
+                        value     : f.vars[def].to_full(&self.table),
+                    }
+                }).collect(),
+            }
+        }).collect()
+    }
+
+
+
     /// Returns the total capacity of the FrameStack. Using any more than this will result in overflows.
     #[inline]
     pub fn capacity(&self) -> usize { self.data.capacity() }
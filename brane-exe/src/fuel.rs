@@ -0,0 +1,94 @@
+//  FUEL.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 12:31:38
+//  Last edited:
+//    16 Oct 2026, 12:31:38
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements the execution limit of workflows. Threads burn fuel for
+//!   every edge (and instruction) they execute, and abort once the fuel
+//!   of their run is exhausted, so infinite loops cannot spin forever.
+// 
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Tests whether fuel is shared by clones and runs out at the limit.
+    #[test]
+    fn test_fuel() {
+        let fuel: Fuel = Fuel::new(10);
+        assert!(fuel.burn(4));
+        assert!(fuel.clone().burn(6));
+        assert_eq!(fuel.remaining(), 0);
+
+        // Any more is too much, and burning does not underflow
+        assert!(!fuel.burn(1));
+        assert_eq!(fuel.remaining(), 0);
+        assert_eq!(fuel.limit(), 10);
+
+        // Burning more than there is left fails as a whole
+        let fuel: Fuel = Fuel::new(5);
+        assert!(!fuel.burn(6));
+        assert!(fuel.burn(5));
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// The fuel of a single run, which may be shared by all threads of a workflow.
+/// 
+/// Clones of the fuel refer to the same tank.
+#[derive(Clone, Debug)]
+pub struct Fuel {
+    /// The amount of fuel that the run started with.
+    limit     : u64,
+    /// The amount of fuel that is left.
+    remaining : Arc<AtomicU64>,
+}
+
+impl Fuel {
+    /// Constructor for the Fuel.
+    /// 
+    /// # Arguments
+    /// - `limit`: The number of steps (i.e., edges and instructions) that the run may take.
+    /// 
+    /// # Returns
+    /// A new Fuel with a full tank.
+    #[inline]
+    pub fn new(limit: u64) -> Self { Self { limit, remaining: Arc::new(AtomicU64::new(limit)) } }
+
+
+
+    /// Burns the given amount of fuel.
+    /// 
+    /// # Arguments
+    /// - `amount`: The amount to burn.
+    /// 
+    /// # Returns
+    /// Whether there was enough fuel left. If not, nothing is burned.
+    pub fn burn(&self, amount: u64) -> bool {
+        self.remaining.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |remaining| remaining.checked_sub(amount)).is_ok()
+    }
+
+    /// Returns the amount of fuel that the run started with.
+    #[inline]
+    pub fn limit(&self) -> u64 { self.limit }
+
+    /// Returns the amount of fuel that is left.
+    #[inline]
+    pub fn remaining(&self) -> u64 { self.remaining.load(Ordering::SeqCst) }
+}
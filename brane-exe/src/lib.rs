@@ -21,6 +21,10 @@ pub mod stack;
 pub mod varreg;
 pub mod frame_stack;
 pub mod thread;
+pub mod trace;
+pub mod cancel;
+pub mod fuel;
+pub mod plugins;
 pub mod vm;
 #[cfg(test)]
 pub mod dummy;
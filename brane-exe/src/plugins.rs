@@ -0,0 +1,400 @@
+//  PLUGINS.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 09:49:27
+//  Last edited:
+//    16 Oct 2026, 11:59:34
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements a registry of VmPlugins, such that the plugin with
+//!   which a Vm runs can be selected at runtime (e.g., from the
+//!   command-line) instead of being hard-wired as a generic parameter.
+//!   Also provides plugins that work for any Vm.
+// 
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use enum_debug::EnumDebug as _;
+use futures::future::BoxFuture;
+use log::info;
+
+use brane_ast::Workflow;
+use brane_ast::locations::Location;
+use brane_ast::ast::DataName;
+use specifications::data::{AccessKind, DataIndex, PreprocessKind};
+use specifications::package::PackageIndex;
+
+use crate::errors::VmError;
+use crate::spec::{ApprovalDecision, Checkpoint, CustomGlobalState, CustomLocalState, TaskInfo, VmPlugin};
+use crate::value::FullValue;
+use crate::vm::Vm;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use brane_ast::{compile_program, CompileResult, ParserOptions};
+    use brane_shr::utilities::{create_data_index, create_package_index};
+    use specifications::data::DataIndex;
+    use specifications::package::PackageIndex;
+    use super::*;
+    use crate::dummy::{DummyPlanner, DummyPlugin, DummyState, DummyVm};
+
+
+    /// Tests whether the plugin selected from a registry is actually the one that runs the workflow.
+    #[tokio::test]
+    async fn test_registry() {
+        // Prepare a registry with a few plugins
+        let registry: PluginRegistry<DummyVm> = PluginRegistry::new()
+            .register::<DummyPlugin>("dummy")
+            .register::<MockPlugin<DummyState, ()>>("mock")
+            .register::<RecorderPlugin<DummyPlugin>>("record");
+        assert_eq!(registry.names(), vec![ "dummy", "mock", "record" ]);
+        assert!(registry.get("unknown").is_none());
+
+        // Compile a workflow that calls a task
+        let pindex: PackageIndex = create_package_index();
+        let dindex: DataIndex    = create_data_index();
+        let workflow: Workflow = match compile_program("import hello_world; return hello_world();".as_bytes(), &pindex, &dindex, &ParserOptions::bscript()) {
+            CompileResult::Workflow(wf, _) => DummyPlanner::plan(wf),
+            _                              => { panic!("Failed to compile test workflow"); },
+        };
+
+        // The dummy (and the recorder of it) return the task's result, but the mock doesn't run it at all
+        for (name, expected) in [ ("dummy", FullValue::String("Hello, world!".into())), ("mock", FullValue::Void), ("record", FullValue::String("Hello, world!".into())) ] {
+            let vm: Arc<RwLock<DummyVm>> = Arc::new(RwLock::new(DummyVm::new()));
+            let res: FullValue = match registry.get(name).unwrap().run(vm, workflow.clone()).await {
+                Ok(res)  => res,
+                Err(err) => { err.prettyprint(); panic!("Failed to run workflow with plugin '{}' (see output above)", name); },
+            };
+            assert_eq!(res, expected, "Plugin '{}' returned an unexpected value", name);
+        }
+    }
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// The signature of a function that runs a workflow on a Vm with a particular plugin (see `Vm::run()`).
+pub type RunFn<V> = fn(Arc<RwLock<V>>, Workflow) -> BoxFuture<'static, Result<FullValue, VmError>>;
+/// The signature of a function that resumes a workflow on a Vm with a particular plugin (see `Vm::resume()`).
+pub type ResumeFn<V> = fn(Arc<RwLock<V>>, Workflow, Checkpoint) -> BoxFuture<'static, Result<FullValue, VmError>>;
+
+
+
+
+
+/***** LIBRARY *****/
+/// Binds a particular VmPlugin to a Vm, such that the Vm can run with it without having to know which plugin it is at compile time.
+/// 
+/// # Generic arguments
+/// - `V`: The Vm that runs with the plugin.
+pub struct PluginEntry<V> {
+    /// Runs a workflow with the plugin.
+    run    : RunFn<V>,
+    /// Resumes a workflow with the plugin.
+    resume : ResumeFn<V>,
+}
+
+impl<V: 'static + Send + Sync + Vm> PluginEntry<V> {
+    /// Constructor for the PluginEntry that binds the given plugin.
+    /// 
+    /// # Generic arguments
+    /// - `P`: The VmPlugin to bind. Must have the same states as the Vm.
+    /// 
+    /// # Returns
+    /// A new PluginEntry instance.
+    #[inline]
+    pub fn new<P: VmPlugin<GlobalState = V::GlobalState, LocalState = V::LocalState>>() -> Self {
+        Self {
+            run    : |this, snippet| V::run::<P>(this, snippet),
+            resume : |this, snippet, checkpoint| V::resume::<P>(this, snippet, checkpoint),
+        }
+    }
+
+
+
+    /// Runs the given workflow on the given Vm with this plugin. See `Vm::run()` for more information.
+    /// 
+    /// # Arguments
+    /// - `this`: The Vm to run on.
+    /// - `snippet`: The workflow (or snippet of it) to run.
+    /// 
+    /// # Returns
+    /// The result if the Workflow returned any.
+    #[inline]
+    pub async fn run(&self, this: Arc<RwLock<V>>, snippet: Workflow) -> Result<FullValue, VmError> { (self.run)(this, snippet).await }
+
+    /// Resumes the given workflow on the given Vm with this plugin. See `Vm::resume()` for more information.
+    /// 
+    /// # Arguments
+    /// - `this`: The Vm to run on.
+    /// - `snippet`: The workflow (or snippet of it) that was running when the checkpoint was made.
+    /// - `checkpoint`: The Checkpoint to resume from.
+    /// 
+    /// # Returns
+    /// The result if the Workflow returned any.
+    #[inline]
+    pub async fn resume(&self, this: Arc<RwLock<V>>, snippet: Workflow, checkpoint: Checkpoint) -> Result<FullValue, VmError> { (self.resume)(this, snippet, checkpoint).await }
+}
+
+impl<V> Clone for PluginEntry<V> {
+    #[inline]
+    fn clone(&self) -> Self { *self }
+}
+impl<V> Copy for PluginEntry<V> {}
+
+
+
+/// Keeps track of the plugins with which a particular Vm may run, by name.
+/// 
+/// # Generic arguments
+/// - `V`: The Vm that runs with the plugins.
+pub struct PluginRegistry<V> {
+    /// The plugins in this registry, by name.
+    plugins : HashMap<String, PluginEntry<V>>,
+}
+
+impl<V: 'static + Send + Sync + Vm> PluginRegistry<V> {
+    /// Constructor for an empty PluginRegistry.
+    /// 
+    /// # Returns
+    /// A new PluginRegistry instance without any plugins.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            plugins : HashMap::new(),
+        }
+    }
+
+
+
+    /// Registers a new plugin in this registry, overwriting any plugin already registered under the same name.
+    /// 
+    /// # Generic arguments
+    /// - `P`: The VmPlugin to register. Must have the same states as the Vm.
+    /// 
+    /// # Arguments
+    /// - `name`: The name under which the plugin may be selected.
+    /// 
+    /// # Returns
+    /// The same PluginRegistry, for chaining.
+    #[inline]
+    pub fn register<P: VmPlugin<GlobalState = V::GlobalState, LocalState = V::LocalState>>(mut self, name: impl Into<String>) -> Self {
+        self.plugins.insert(name.into(), PluginEntry::new::<P>());
+        self
+    }
+
+    /// Returns the plugin with the given name.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the plugin to return.
+    /// 
+    /// # Returns
+    /// The PluginEntry of the plugin, or `None` if no plugin with that name was registered.
+    #[inline]
+    pub fn get(&self, name: impl AsRef<str>) -> Option<PluginEntry<V>> { self.plugins.get(name.as_ref()).copied() }
+
+    /// Returns the names of all plugins in this registry.
+    /// 
+    /// # Returns
+    /// The names of the registered plugins, in alphabetical order.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.plugins.keys().map(|name| name.as_str()).collect();
+        names.sort_unstable();
+        names
+    }
+}
+
+impl<V: 'static + Send + Sync + Vm> Default for PluginRegistry<V> {
+    #[inline]
+    fn default() -> Self { Self::new() }
+}
+
+
+
+/// The MockPlugin pretends to do everything the workflow asks of it, but doesn't actually do any of it. Useful to test the control flow of a workflow without running any tasks.
+/// 
+/// Tasks all return `Void`, events arrive immediately and approvals are always granted.
+/// 
+/// # Generic arguments
+/// - `G`: The custom global state of the Vm that runs with this plugin.
+/// - `L`: The custom local state of the Vm that runs with this plugin.
+pub struct MockPlugin<G, L> {
+    _state : PhantomData<fn() -> (G, L)>,
+}
+
+#[async_trait::async_trait]
+impl<G: CustomGlobalState, L: CustomLocalState> VmPlugin for MockPlugin<G, L> {
+    type GlobalState = G;
+    type LocalState  = L;
+
+    type PreprocessError = Infallible;
+    type ExecuteError    = Infallible;
+    type StdoutError     = Infallible;
+    type CommitError     = Infallible;
+    type EventError      = Infallible;
+    type NotifyError     = Infallible;
+    type IndexError      = Infallible;
+    type CheckpointError = Infallible;
+
+
+    async fn preprocess(_global: Arc<RwLock<Self::GlobalState>>, _local: Self::LocalState, loc: Location, name: DataName, _preprocess: PreprocessKind) -> Result<AccessKind, Self::PreprocessError> {
+        info!("[mock] Preprocessing {} '{}' on '{}'", name.variant(), name.name(), loc);
+        Ok(AccessKind::File{ path: PathBuf::from(name.name()) })
+    }
+
+    async fn execute(_global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, info: TaskInfo<'_>) -> Result<Option<FullValue>, Self::ExecuteError> {
+        info!("[mock] Executing '{}' from package '{}' (version {}) on '{}'", info.name, info.package_name, info.package_version, info.location);
+        Ok(None)
+    }
+
+    #[inline]
+    fn is_transient(err: &Self::ExecuteError) -> bool { match *err {} }
+
+    async fn stdout(_global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, _branch: &[usize], text: &str, newline: bool) -> Result<(), Self::StdoutError> {
+        if newline { println!("{}", text); } else { print!("{}", text); }
+        Ok(())
+    }
+
+    async fn publicize(_global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, loc: &Location, name: &str, path: &Path) -> Result<(), Self::CommitError> {
+        info!("[mock] Publicizing intermediate result '{}' at '{}' on '{}'", name, path.display(), loc);
+        Ok(())
+    }
+
+    async fn commit(_global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, loc: &Location, name: &str, path: &Path, data_name: &str, residency: Option<&[Location]>) -> Result<(), Self::CommitError> {
+        info!("[mock] Committing intermediate result '{}' at '{}' on '{}' as dataset '{}' (residency: {:?})", name, path.display(), loc, data_name, residency);
+        Ok(())
+    }
+
+    async fn wait_event(_global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, name: &str) -> Result<String, Self::EventError> {
+        info!("[mock] Waiting for event '{}'", name);
+        Ok(String::new())
+    }
+
+    async fn require_approval(_global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, message: &str) -> Result<ApprovalDecision, Self::EventError> {
+        info!("[mock] Requiring approval for '{}'", message);
+        Ok(ApprovalDecision{ approved: true, user: "mock".into(), reason: None })
+    }
+
+    async fn notify(_global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, channel: &str, message: &str) -> Result<(), Self::NotifyError> {
+        info!("[mock] Notifying channel '{}': '{}'", channel, message);
+        Ok(())
+    }
+
+    async fn indices(_global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState) -> Result<(Arc<PackageIndex>, Arc<DataIndex>), Self::IndexError> {
+        info!("[mock] Querying indices");
+        Ok((Arc::new(PackageIndex::empty()), Arc::new(DataIndex::default())))
+    }
+
+    async fn checkpoint(_global: &Arc<RwLock<Self::GlobalState>>, _local: &Self::LocalState, checkpoint: &Checkpoint) -> Result<(), Self::CheckpointError> {
+        info!("[mock] Checkpointing at edge {}", checkpoint.pc.1);
+        Ok(())
+    }
+}
+
+
+
+/// The RecorderPlugin logs every call (and its outcome) before passing it on to another plugin. Useful to record what a workflow does while it runs as usual.
+/// 
+/// # Generic arguments
+/// - `P`: The VmPlugin that actually handles the calls.
+pub struct RecorderPlugin<P> {
+    _plugin : PhantomData<fn() -> P>,
+}
+
+#[async_trait::async_trait]
+impl<P: VmPlugin> VmPlugin for RecorderPlugin<P> {
+    type GlobalState = P::GlobalState;
+    type LocalState  = P::LocalState;
+
+    type PreprocessError = P::PreprocessError;
+    type ExecuteError    = P::ExecuteError;
+    type StdoutError     = P::StdoutError;
+    type CommitError     = P::CommitError;
+    type EventError      = P::EventError;
+    type NotifyError     = P::NotifyError;
+    type IndexError      = P::IndexError;
+    type CheckpointError = P::CheckpointError;
+
+
+    async fn preprocess(global: Arc<RwLock<Self::GlobalState>>, local: Self::LocalState, loc: Location, name: DataName, preprocess: PreprocessKind) -> Result<AccessKind, Self::PreprocessError> {
+        info!("[record] preprocess({} '{}', location: '{}', how: {:?})", name.variant(), name.name(), loc, preprocess);
+        let res: Result<AccessKind, Self::PreprocessError> = P::preprocess(global, local, loc, name, preprocess).await;
+        info!("[record] preprocess -> {:?}", res.as_ref().map_err(|err| err.to_string()));
+        res
+    }
+
+    async fn execute(global: &Arc<RwLock<Self::GlobalState>>, local: &Self::LocalState, info: TaskInfo<'_>) -> Result<Option<FullValue>, Self::ExecuteError> {
+        info!("[record] execute('{}' from '{}' (version {}), location: '{}', args: {:?})", info.name, info.package_name, info.package_version, info.location, info.args);
+        let res: Result<Option<FullValue>, Self::ExecuteError> = P::execute(global, local, info).await;
+        info!("[record] execute -> {:?}", res.as_ref().map_err(|err| err.to_string()));
+        res
+    }
+
+    #[inline]
+    fn is_transient(err: &Self::ExecuteError) -> bool { P::is_transient(err) }
+
+    async fn stdout(global: &Arc<RwLock<Self::GlobalState>>, local: &Self::LocalState, branch: &[usize], text: &str, newline: bool) -> Result<(), Self::StdoutError> {
+        info!("[record] stdout({:?}, branch: {:?}, newline: {})", text, branch, newline);
+        P::stdout(global, local, branch, text, newline).await
+    }
+
+    async fn publicize(global: &Arc<RwLock<Self::GlobalState>>, local: &Self::LocalState, loc: &Location, name: &str, path: &Path) -> Result<(), Self::CommitError> {
+        info!("[record] publicize('{}', path: '{}', location: '{}')", name, path.display(), loc);
+        let res: Result<(), Self::CommitError> = P::publicize(global, local, loc, name, path).await;
+        info!("[record] publicize -> {:?}", res.as_ref().map_err(|err| err.to_string()));
+        res
+    }
+
+    async fn commit(global: &Arc<RwLock<Self::GlobalState>>, local: &Self::LocalState, loc: &Location, name: &str, path: &Path, data_name: &str, residency: Option<&[Location]>) -> Result<(), Self::CommitError> {
+        info!("[record] commit('{}', path: '{}', location: '{}', dataset: '{}', residency: {:?})", name, path.display(), loc, data_name, residency);
+        let res: Result<(), Self::CommitError> = P::commit(global, local, loc, name, path, data_name, residency).await;
+        info!("[record] commit -> {:?}", res.as_ref().map_err(|err| err.to_string()));
+        res
+    }
+
+    async fn wait_event(global: &Arc<RwLock<Self::GlobalState>>, local: &Self::LocalState, name: &str) -> Result<String, Self::EventError> {
+        info!("[record] wait_event('{}')", name);
+        let res: Result<String, Self::EventError> = P::wait_event(global, local, name).await;
+        info!("[record] wait_event -> {:?}", res.as_ref().map_err(|err| err.to_string()));
+        res
+    }
+
+    async fn require_approval(global: &Arc<RwLock<Self::GlobalState>>, local: &Self::LocalState, message: &str) -> Result<ApprovalDecision, Self::EventError> {
+        info!("[record] require_approval({:?})", message);
+        let res: Result<ApprovalDecision, Self::EventError> = P::require_approval(global, local, message).await;
+        info!("[record] require_approval -> {:?}", res.as_ref().map_err(|err| err.to_string()));
+        res
+    }
+
+    async fn notify(global: &Arc<RwLock<Self::GlobalState>>, local: &Self::LocalState, channel: &str, message: &str) -> Result<(), Self::NotifyError> {
+        info!("[record] notify('{}', {:?})", channel, message);
+        let res: Result<(), Self::NotifyError> = P::notify(global, local, channel, message).await;
+        info!("[record] notify -> {:?}", res.as_ref().map_err(|err| err.to_string()));
+        res
+    }
+
+    async fn indices(global: &Arc<RwLock<Self::GlobalState>>, local: &Self::LocalState) -> Result<(Arc<PackageIndex>, Arc<DataIndex>), Self::IndexError> {
+        info!("[record] indices()");
+        let res: Result<(Arc<PackageIndex>, Arc<DataIndex>), Self::IndexError> = P::indices(global, local).await;
+        info!("[record] indices -> {:?}", res.as_ref().map(|(pindex, dindex)| (pindex.packages.len(), dindex.iter().count())).map_err(|err| err.to_string()));
+        res
+    }
+
+    async fn checkpoint(global: &Arc<RwLock<Self::GlobalState>>, local: &Self::LocalState, checkpoint: &Checkpoint) -> Result<(), Self::CheckpointError> {
+        info!("[record] checkpoint(edge {})", checkpoint.pc.1);
+        let res: Result<(), Self::CheckpointError> = P::checkpoint(global, local, checkpoint).await;
+        info!("[record] checkpoint -> {:?}", res.as_ref().map_err(|err| err.to_string()));
+        res
+    }
+}
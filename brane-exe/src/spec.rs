@@ -18,14 +18,19 @@ use std::error::Error;
 use std::path::Path;
 use std::sync::{Arc, RwLock};
 
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use brane_ast::DataType;
 use brane_ast::locations::Location;
 use brane_ast::ast::{DataName, SymTable};
-use specifications::data::{AccessKind, PreprocessKind};
-use specifications::package::Capability;
+use specifications::data::{AccessKind, DataIndex, PreprocessKind};
+use specifications::package::{Capability, PackageIndex};
 use specifications::version::Version;
 
 use crate::value::FullValue;
-use crate::frame_stack::FrameStack;
+use crate::stack::StackSlot;
+use crate::frame_stack::{FrameStack, FrameStackCheckpoint};
 
 
 /***** LIBRARY *****/
@@ -51,6 +56,19 @@ impl CustomLocalState for () {
 
 
 
+/// Defines the decision that a user made on a request for approval.
+#[derive(Clone, Debug)]
+pub struct ApprovalDecision {
+    /// Whether the user approved (true) or rejected (false) the request.
+    pub approved : bool,
+    /// The name of the user that made the decision.
+    pub user     : String,
+    /// The reason the user gave for their decision, if any.
+    pub reason   : Option<String>,
+}
+
+
+
 /// A trait that implements various missing pieces in task execution. See the `brane-tsk` crate for implementations.
 #[async_trait::async_trait]
 pub trait VmPlugin: 'static + Send + Sync {
@@ -67,6 +85,14 @@ pub trait VmPlugin: 'static + Send + Sync {
     type StdoutError     : 'static + Send + Sync + Error;
     /// The error type of the publicize and commit functions.
     type CommitError     : 'static + Send + Sync + Error;
+    /// The error type of the wait_event function.
+    type EventError      : 'static + Send + Sync + Error;
+    /// The error type of the notify function.
+    type NotifyError     : 'static + Send + Sync + Error;
+    /// The error type of the indices function.
+    type IndexError      : 'static + Send + Sync + Error;
+    /// The error type of the checkpoint function.
+    type CheckpointError : 'static + Send + Sync + Error;
 
 
     /// A function that preprocesses a given dataset in the given way. Typically, this involves "transferring data" as a preprocessing step.
@@ -107,6 +133,17 @@ pub trait VmPlugin: 'static + Send + Sync {
     /// This function may error whenever it likes.
     async fn execute(global: &Arc<RwLock<Self::GlobalState>>, local: &Self::LocalState, info: TaskInfo<'_>) -> Result<Option<FullValue>, Self::ExecuteError>;
 
+    /// A function that decides whether an error returned by `execute()` is transient, i.e., whether executing the same task again may succeed.
+    /// 
+    /// Calls with a retry policy (see `@retry(...)`) are only retried if this returns true.
+    /// 
+    /// # Arguments
+    /// - `err`: The error returned by `execute()`.
+    /// 
+    /// # Returns
+    /// True if the call may be retried, or false if it would fail the same way again.
+    fn is_transient(err: &Self::ExecuteError) -> bool;
+
 
 
     /// A function that prints a message to stdout - whatever that may be.
@@ -119,12 +156,13 @@ pub trait VmPlugin: 'static + Send + Sync {
     /// # Arguments
     /// - `global`: The custom global state for keeping track of your own things during execution.
     /// - `local`: The custom local state for keeping track of your own things faster but only local to this (execution) thread.
+    /// - `branch`: Identifies the thread that printed the text, as the index of its branch in every `parallel`-statement it is nested in (outermost first). Empty for the main thread.
     /// - `text`: The text to write to your version of stdout.
     /// - `newline`: Whether or not to print a closing newline after the text (i.e., whether to use `println` or `print`).
     /// 
     /// # Errors
     /// This function may error whenever it likes.
-    async fn stdout(global: &Arc<RwLock<Self::GlobalState>>, local: &Self::LocalState, text: &str, newline: bool) -> Result<(), Self::StdoutError>;
+    async fn stdout(global: &Arc<RwLock<Self::GlobalState>>, local: &Self::LocalState, branch: &[usize], text: &str, newline: bool) -> Result<(), Self::StdoutError>;
 
 
 
@@ -160,10 +198,93 @@ pub trait VmPlugin: 'static + Send + Sync {
     /// - `name`: The name of the intermediate result to promoto (you'll typically use this for debugging only).
     /// - `path`: The path where the intermediate result is available. You'll probably want to archive this somewhere else before continuing. **Note**: Be aware that this path is relative to some directory you still have to prepend.
     /// - `data_name`: The identifier of the dataset once the intermediate result is promoted. If it already exists, you'll probably want to override the old value with the new one.
+    /// - `residency`: The locations where the intermediate result may be processed if it was derived from restricted data, which the dataset should inherit. `None` if it may be processed anywhere.
+    /// 
+    /// # Errors
+    /// This function may error whenever it likes.
+    async fn commit(global: &Arc<RwLock<Self::GlobalState>>, local: &Self::LocalState, loc: &Location, name: &str, path: &Path, data_name: &str, residency: Option<&[Location]>) -> Result<(), Self::CommitError>;
+
+
+
+    /// A function that suspends the calling thread until the named external event arrives.
+    /// 
+    /// This function is called whenever BraneScript's `wait_for_event` is called.
+    /// 
+    /// # Arguments
+    /// - `global`: The custom global state for keeping track of your own things during execution.
+    /// - `local`: The custom local state for keeping track of your own things faster but only local to this (execution) thread.
+    /// - `name`: The name of the event to wait for.
+    /// 
+    /// # Returns
+    /// The payload that was sent along with the event.
+    /// 
+    /// # Errors
+    /// This function may error whenever it likes.
+    async fn wait_event(global: &Arc<RwLock<Self::GlobalState>>, local: &Self::LocalState, name: &str) -> Result<String, Self::EventError>;
+
+    /// A function that records a pending approval and suspends the calling thread until a user has decided on it.
+    /// 
+    /// This function is called whenever BraneScript's `require_approval` is called.
+    /// 
+    /// # Arguments
+    /// - `global`: The custom global state for keeping track of your own things during execution.
+    /// - `local`: The custom local state for keeping track of your own things faster but only local to this (execution) thread.
+    /// - `message`: The message that describes what the user is asked to approve.
+    /// 
+    /// # Returns
+    /// The ApprovalDecision of the user. Rejections should be returned as such instead of as an error.
+    /// 
+    /// # Errors
+    /// This function may error whenever it likes.
+    async fn require_approval(global: &Arc<RwLock<Self::GlobalState>>, local: &Self::LocalState, message: &str) -> Result<ApprovalDecision, Self::EventError>;
+
+
+
+    /// A function that sends a message to the user(s) listening on the given notification channel (e.g., an e-mail address or a Slack webhook).
+    /// 
+    /// This function is called whenever BraneScript's `notify` is called.
+    /// 
+    /// # Arguments
+    /// - `global`: The custom global state for keeping track of your own things during execution.
+    /// - `local`: The custom local state for keeping track of your own things faster but only local to this (execution) thread.
+    /// - `channel`: The name of the notification channel to send the message on.
+    /// - `message`: The message to send.
     /// 
     /// # Errors
     /// This function may error whenever it likes.
-    async fn commit(global: &Arc<RwLock<Self::GlobalState>>, local: &Self::LocalState, loc: &Location, name: &str, path: &Path, data_name: &str) -> Result<(), Self::CommitError>;
+    async fn notify(global: &Arc<RwLock<Self::GlobalState>>, local: &Self::LocalState, channel: &str, message: &str) -> Result<(), Self::NotifyError>;
+
+
+
+    /// A function that returns the package and data indices of the instance, so that workflows can decide at runtime what to process.
+    /// 
+    /// This function is called whenever BraneScript's `list_packages`, `list_datasets` or `dataset_size` are called.
+    /// 
+    /// # Arguments
+    /// - `global`: The custom global state for keeping track of your own things during execution.
+    /// - `local`: The custom local state for keeping track of your own things faster but only local to this (execution) thread.
+    /// 
+    /// # Returns
+    /// The PackageIndex and the DataIndex of the instance, in that order.
+    /// 
+    /// # Errors
+    /// This function may error whenever it likes.
+    async fn indices(global: &Arc<RwLock<Self::GlobalState>>, local: &Self::LocalState) -> Result<(Arc<PackageIndex>, Arc<DataIndex>), Self::IndexError>;
+
+
+
+    /// A function that persists a snapshot of the running workflow, so that it may be resumed later (e.g., after a restart).
+    /// 
+    /// This function is called by the main thread after every task it completed, as long as it has no branches running in parallel. Each checkpoint supersedes the previous one.
+    /// 
+    /// # Arguments
+    /// - `global`: The custom global state for keeping track of your own things during execution.
+    /// - `local`: The custom local state for keeping track of your own things faster but only local to this (execution) thread.
+    /// - `checkpoint`: The Checkpoint that describes where the workflow is and what it knows. It can be given to `Thread::from_checkpoint()` to resume the workflow.
+    /// 
+    /// # Errors
+    /// This function may error whenever it likes. Failing to checkpoint does not fail the workflow, however.
+    async fn checkpoint(global: &Arc<RwLock<Self::GlobalState>>, local: &Self::LocalState, checkpoint: &Checkpoint) -> Result<(), Self::CheckpointError>;
 }
 
 
@@ -202,6 +323,46 @@ impl<G: CustomGlobalState> RunState<G> {
 
 
 
+/// Defines a snapshot of a running workflow from which it can be resumed.
+/// 
+/// Note that the custom global and local states are not part of it; it is up to the VmPlugin to persist whatever it needs of those.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Checkpoint {
+    /// The program counter of the main thread, which points to the edge to execute next.
+    pub pc     : (usize, usize),
+    /// The slots on the main thread's stack.
+    pub stack  : Vec<StackSlot>,
+    /// The frames on the main thread's frame stack, including the values of any variables.
+    pub fstack : FrameStackCheckpoint,
+    /// If the main thread was sleeping, the moment at which it wakes up again before continuing at `pc`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wake   : Option<DateTime<Utc>>,
+}
+
+
+
+/// Summarizes a variable that is currently defined in a RunState, so that (attached) clients may inspect a session.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct VarSummary {
+    /// The name of the variable.
+    pub name      : String,
+    /// The (declared) type of the variable.
+    pub data_type : DataType,
+    /// The current value of the variable.
+    pub value     : FullValue,
+}
+
+/// Summarizes a single frame on the FrameStack of a RunState.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FrameSummary {
+    /// The name of the function that this frame belongs to, or None if it is the main frame.
+    pub function : Option<String>,
+    /// The variables defined in this frame, in order of definition.
+    pub vars     : Vec<VarSummary>,
+}
+
+
+
 /// Defines that which the execute closure needs to know about a task.
 #[derive(Clone, Debug)]
 pub struct TaskInfo<'a> {
@@ -213,6 +374,8 @@ pub struct TaskInfo<'a> {
     pub package_version : &'a Version,
     /// The requirements that the task has.
     pub requirements    : &'a HashSet<Capability>,
+    /// The names of the secrets that the task needs. Only the names travel with the workflow; the worker resolves their values.
+    pub secrets         : &'a [String],
 
     /// The arguments that are given for this Task. Note that data & intermediate results have to be resolved before passing this to the function.
     pub args     : HashMap<String, FullValue>,
@@ -222,4 +385,9 @@ pub struct TaskInfo<'a> {
     pub input    : HashMap<DataName, AccessKind>,
     /// If this task returns an intermediate result, then this specifies the name it should have.
     pub result   : &'a Option<String>,
+    /// The time (in seconds) after which the task should be killed if it has not completed yet, if any.
+    pub timeout  : Option<u64>,
+
+    /// The edge that calls this task, as a `(body, offset)` pair.
+    pub pc : (usize, usize),
 }
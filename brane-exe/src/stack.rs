@@ -17,6 +17,7 @@ use std::mem;
 use std::ops::{Deref, DerefMut, Index, IndexMut, Range, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive};
 
 use log::warn;
+use serde::{Deserialize, Serialize};
 
 pub use crate::errors::StackError as Error;
 use crate::value::Value;
@@ -24,7 +25,7 @@ use crate::value::Value;
 
 /***** LIBRARY *****/
 /// Defines a single slot on the Stack.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub enum StackSlot {
     /// The slot contains a real value
     Value(Value),
@@ -328,6 +329,24 @@ impl Stack {
         }
     }
 
+    /// Constructor for the Stack that restores it from the slots of a checkpoint.
+    /// 
+    /// # Arguments
+    /// - `size`: The size of the stack. This is actually non-configurable during execution.
+    /// - `slots`: The StackSlots (as returned by `Stack::checkpoint()`) to restore.
+    /// 
+    /// # Returns
+    /// A new instance of a Stack with `size` slots available, of which the first ones are the given slots.
+    #[inline]
+    pub fn from_checkpoint(size: usize, slots: Vec<StackSlot>) -> Self {
+        // Make sure we keep the capacity, as that is what we use to detect overflows
+        let mut res: Vec<StackSlot> = Vec::with_capacity(size.max(slots.len()));
+        res.extend(slots);
+        Self {
+            slots : res,
+        }
+    }
+
     /// Constructor for the Slack that takes a raw StackSlot slice.
     /// 
     /// # Arguments
@@ -344,6 +363,15 @@ impl Stack {
 
 
 
+    /// Returns a copy of the slots on the stack, which can be serialized to checkpoint it.
+    /// 
+    /// # Returns
+    /// A list of StackSlots that can be given to `Stack::from_checkpoint()` to restore this stack.
+    #[inline]
+    pub fn checkpoint(&self) -> Vec<StackSlot> { self.slots.clone() }
+
+
+
     /// Returns the top value of the stack, popping it.
     /// 
     /// # Returns
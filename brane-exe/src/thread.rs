@@ -13,30 +13,37 @@
 //!   given stream of Edges.
 // 
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, VecDeque};
 use std::path::PathBuf;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
 
 use async_recursion::async_recursion;
+use chrono::{DateTime, Utc};
 use enum_debug::EnumDebug as _;
-use futures::future::{BoxFuture, FutureExt};
-use log::debug;
+use futures::future::{self, BoxFuture, Either, FutureExt};
+use log::{debug, warn};
 use tokio::spawn;
+use tokio::sync::{Mutex as TokioMutex, OwnedMutexGuard};
 use tokio::task::JoinHandle;
 
 use brane_ast::{DataType, MergeStrategy, Workflow};
 use brane_ast::spec::{BuiltinClasses, BuiltinFunctions};
 use brane_ast::locations::Location;
 use brane_ast::ast::{ClassDef, DataName, Edge, EdgeInstr, FunctionDef, TaskDef};
-use specifications::data::{AccessKind, AvailabilityKind};
+use specifications::data::{AccessKind, AvailabilityKind, DataIndex};
+use specifications::package::PackageIndex;
 
 use crate::dbg_node;
+use crate::cancel::CancelToken;
+use crate::fuel::Fuel;
 pub use crate::errors::VmError as Error;
 use crate::errors::ReturnEdge;
-use crate::spec::{CustomGlobalState, CustomLocalState, RunState, TaskInfo, VmPlugin};
+use crate::spec::{ApprovalDecision, Checkpoint, CustomGlobalState, CustomLocalState, RunState, TaskInfo, VmPlugin};
 use crate::value::{FullValue, Value};
 use crate::stack::Stack;
 use crate::frame_stack::FrameStack;
+use crate::trace::{TraceEvent, Tracer};
 
 
 /***** TESTS *****/
@@ -50,6 +57,28 @@ mod tests {
     use specifications::package::PackageIndex;
     use super::*;
     use crate::dummy::{DummyPlanner, DummyPlugin, DummyState};
+    use crate::spec::FrameSummary;
+
+
+    /// Compiles the given snippet to a workflow and plans it with the DummyPlanner, panicking if it does not compile.
+    ///
+    /// # Arguments
+    /// - `code`: The BraneScript snippet to compile.
+    /// - `name`: The name of the snippet, used to report compile errors.
+    ///
+    /// # Returns
+    /// The planned Workflow.
+    fn compile_and_plan(code: &str, name: &str) -> Workflow {
+        let pindex: PackageIndex = create_package_index();
+        let dindex: DataIndex    = create_data_index();
+        let workflow: Workflow = match compile_program(code.as_bytes(), &pindex, &dindex, &ParserOptions::bscript()) {
+            CompileResult::Workflow(wf, _) => wf,
+            CompileResult::Eof(err)        => { err.prettyprint(name, code); panic!("Failed to compile to workflow (see output above)"); },
+            CompileResult::Err(errs)       => { for e in errs { e.prettyprint(name, code); } panic!("Failed to compile to workflow (see output above)"); },
+            _                              => { unreachable!(); },
+        };
+        DummyPlanner::plan(workflow)
+    }
 
 
     /// Tests the traversal by generating symbol tables for every file.
@@ -106,7 +135,7 @@ mod tests {
 
                 // Run the program
                 let text: Arc<Mutex<String>>     = Arc::new(Mutex::new(String::new()));
-                let main: Thread<DummyState, ()> = Thread::new(&workflow, DummyState{ text: text.clone() });
+                let main: Thread<DummyState, ()> = Thread::new(&workflow, DummyState{ text: text.clone(), checkpoints: Arc::new(Mutex::new(vec![])), outputs: Arc::new(HashMap::new()) });
                 match main.run::<DummyPlugin>().await {
                     Ok(value) => {
                         println!("Workflow stdout:");
@@ -123,6 +152,189 @@ mod tests {
             }
         }).await;
     }
+    /// Tests whether a workflow can be resumed from the checkpoints it makes.
+    #[tokio::test]
+    async fn test_checkpoint() {
+        let code: &str = "import hello_world;\nlet a := hello_world();\nlet b := hello_world();\nprintln(a);\nprintln(b);\n";
+
+        // Compile and plan the workflow
+        let workflow: Workflow = compile_and_plan(code, "<checkpoint>");
+
+        // Run it in full, collecting the checkpoints
+        let text: Arc<Mutex<String>>                 = Arc::new(Mutex::new(String::new()));
+        let checkpoints: Arc<Mutex<Vec<Checkpoint>>> = Arc::new(Mutex::new(vec![]));
+        let main: Thread<DummyState, ()> = Thread::new(&workflow, DummyState{ text: text.clone(), checkpoints: checkpoints.clone(), outputs: Arc::new(HashMap::new()) });
+        if let Err(err) = main.run::<DummyPlugin>().await { err.prettyprint(); panic!("Failed to execute workflow (see output above)"); }
+        let checkpoints: Vec<Checkpoint> = checkpoints.lock().unwrap().clone();
+        assert_eq!(checkpoints.len(), 2);
+
+        // Resume from the first one, after sending it through serde to be sure
+        let checkpoint: Checkpoint = serde_json::from_str(&serde_json::to_string(&checkpoints[0]).unwrap()).unwrap();
+        let rtext: Arc<Mutex<String>> = Arc::new(Mutex::new(String::new()));
+        let global: Arc<RwLock<DummyState>> = Arc::new(RwLock::new(DummyState{ text: rtext.clone(), checkpoints: Arc::new(Mutex::new(vec![])), outputs: Arc::new(HashMap::new()) }));
+        let main: Thread<DummyState, ()> = Thread::from_checkpoint(&workflow, checkpoint, global.clone());
+        if let Err(err) = main.run::<DummyPlugin>().await { err.prettyprint(); panic!("Failed to resume workflow (see output above)"); }
+
+        // The resumed run should have picked up where the first left off
+        assert_eq!(*rtext.lock().unwrap(), *text.lock().unwrap());
+        assert_eq!(global.read().unwrap().checkpoints.lock().unwrap().len(), 1);
+    }
+    /// Tests whether a run that is checkpointed while sleeping only sleeps for what is left of it when resumed.
+    #[tokio::test]
+    async fn test_checkpoint_sleep() {
+        let code: &str = "println(\"before\");\nsleep(0);\nprintln(\"after\");\n";
+
+        // Compile and plan the workflow
+        let workflow: Workflow = compile_and_plan(code, "<checkpoint_sleep>");
+
+        // Run it in full; the sleep is checkpointed with its wake-up time, continuing after it
+        let checkpoints: Arc<Mutex<Vec<Checkpoint>>> = Arc::new(Mutex::new(vec![]));
+        let main: Thread<DummyState, ()> = Thread::new(&workflow, DummyState{ text: Arc::new(Mutex::new(String::new())), checkpoints: checkpoints.clone(), outputs: Arc::new(HashMap::new()) });
+        let start: DateTime<Utc> = Utc::now();
+        if let Err(err) = main.run::<DummyPlugin>().await { err.prettyprint(); panic!("Failed to execute workflow (see output above)"); }
+        let checkpoints: Vec<Checkpoint> = checkpoints.lock().unwrap().clone();
+        assert_eq!(checkpoints.len(), 1);
+        let wake: DateTime<Utc> = checkpoints[0].wake.expect("Sleep was checkpointed without a wake-up time");
+        assert!(wake >= start && wake <= Utc::now());
+
+        // Resuming one that should still sleep for a while only sleeps until then
+        for (wake, min, max) in [ (Utc::now() + chrono::Duration::milliseconds(500), 400, 5000), (Utc::now() - chrono::Duration::hours(1), 0, 400) ] {
+            let mut checkpoint: Checkpoint = serde_json::from_str(&serde_json::to_string(&checkpoints[0]).unwrap()).unwrap();
+            checkpoint.wake = Some(wake);
+            let text: Arc<Mutex<String>> = Arc::new(Mutex::new(String::new()));
+            let global: Arc<RwLock<DummyState>> = Arc::new(RwLock::new(DummyState{ text: text.clone(), checkpoints: Arc::new(Mutex::new(vec![])), outputs: Arc::new(HashMap::new()) }));
+            let main: Thread<DummyState, ()> = Thread::from_checkpoint(&workflow, checkpoint, global);
+            let start: DateTime<Utc> = Utc::now();
+            if let Err(err) = main.run::<DummyPlugin>().await { err.prettyprint(); panic!("Failed to resume workflow (see output above)"); }
+            let slept: i64 = (Utc::now() - start).num_milliseconds();
+            assert!(slept >= min && slept < max, "Resumed run slept for {}ms, expected {}ms to {}ms", slept, min, max);
+            assert_eq!(*text.lock().unwrap(), "after\n");
+        }
+    }
+    /// Tests whether the frame stack of a (checkpointed) run summarizes the variables defined in it, as inspected by attached clients.
+    #[tokio::test]
+    async fn test_summary() {
+        let code: &str = "import hello_world;\nlet a := hello_world();\nlet n := 42;\nlet b := hello_world();\nprintln(b);\n";
+
+        // Compile and plan the workflow
+        let workflow: Workflow = compile_and_plan(code, "<summary>");
+
+        // Run it, collecting the checkpoints
+        let checkpoints: Arc<Mutex<Vec<Checkpoint>>> = Arc::new(Mutex::new(vec![]));
+        let main: Thread<DummyState, ()> = Thread::new(&workflow, DummyState{ text: Arc::new(Mutex::new(String::new())), checkpoints: checkpoints.clone(), outputs: Arc::new(HashMap::new()) });
+        if let Err(err) = main.run::<DummyPlugin>().await { err.prettyprint(); panic!("Failed to execute workflow (see output above)"); }
+        let checkpoint: Checkpoint = checkpoints.lock().unwrap()[1].clone();
+
+        // After the second task, only the main frame exists and it defines the variables before it
+        let frames: Vec<FrameSummary> = FrameStack::from_checkpoint(16, checkpoint.fstack, workflow.table.clone()).summary();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].function, None);
+        let names: Vec<&str> = frames[0].vars.iter().map(|var| var.name.as_str()).collect();
+        assert_eq!(names, vec![ "a", "n" ]);
+        assert_eq!(frames[0].vars[1].data_type, DataType::Integer);
+        assert!(matches!(frames[0].vars[1].value, FullValue::Integer(42)));
+    }
+    /// Tests whether a parallel statement with more branches than may run at once still yields the same result.
+    #[tokio::test]
+    async fn test_max_parallel() {
+        let code: &str = "let sum := parallel [sum] [{\n    return 1;\n}, {\n    return 2;\n}, {\n    return 3;\n}, {\n    return 4;\n}, {\n    return 5;\n}];\nprintln(sum);\n";
+
+        // Compile and plan the workflow
+        let workflow: Workflow = compile_and_plan(code, "<max_parallel>");
+
+        // Run it with at most two branches at a time
+        let text: Arc<Mutex<String>> = Arc::new(Mutex::new(String::new()));
+        let main: Thread<DummyState, ()> = Thread::new(&workflow, DummyState{ text: text.clone(), checkpoints: Arc::new(Mutex::new(vec![])), outputs: Arc::new(HashMap::new()) }).with_max_parallel(2);
+        if let Err(err) = main.run::<DummyPlugin>().await { err.prettyprint(); panic!("Failed to execute workflow (see output above)"); }
+        assert_eq!(*text.lock().unwrap(), "15\n");
+    }
+
+    /// Tests whether a workflow that loops forever is aborted once it runs out of fuel.
+    #[tokio::test]
+    async fn test_max_steps() {
+        let code: &str = "let i := 0;\nwhile (true) {\n    i := i + 1;\n}\n";
+
+        // Compile and plan the workflow
+        let workflow: Workflow = compile_and_plan(code, "<max_steps>");
+
+        // Run it with a limited number of steps
+        let main: Thread<DummyState, ()> = Thread::new(&workflow, DummyState{ text: Arc::new(Mutex::new(String::new())), checkpoints: Arc::new(Mutex::new(vec![])), outputs: Arc::new(HashMap::new()) }).with_fuel(Fuel::new(1000));
+        match main.run::<DummyPlugin>().await {
+            Err(Error::ExecutionLimitExceeded{ limit, .. }) => { assert_eq!(limit, 1000); },
+            Err(err)                                        => { err.prettyprint(); panic!("Workflow failed for another reason than its execution limit (see output above)"); },
+            Ok(_)                                           => { panic!("Workflow that loops forever completed"); },
+        }
+    }
+
+    /// Tests whether integer arithmetic that is undefined or does not fit fails with a typed error instead of panicking or wrapping, while edge cases that do fit succeed.
+    #[tokio::test]
+    async fn test_arithmetic_errors() {
+        for (code, overflow) in [ ("let r := 1 / 0;", false), ("let r := 1 % 0;", false), ("let r := 9223372036854775807 + 1;", true), ("let x := 0 - 9223372036854775807 - 1;\nlet r := x / -1;", true), ("let r := 4611686018427387904 * 2;", true) ] {
+            // Compile and plan the workflow
+            let workflow: Workflow = compile_and_plan(code, "<arithmetic>");
+
+            // Run it and check it fails for the right reason
+            let main: Thread<DummyState, ()> = Thread::new(&workflow, DummyState{ text: Arc::new(Mutex::new(String::new())), checkpoints: Arc::new(Mutex::new(vec![])), outputs: Arc::new(HashMap::new()) });
+            match (main.run::<DummyPlugin>().await, overflow) {
+                (Err(Error::DivisionByZero{ .. }), false) | (Err(Error::IntegerOverflow{ .. }), true) => {},
+                (Err(err), _) => { err.prettyprint(); panic!("Workflow '{}' failed for the wrong reason (see output above)", code); },
+                (Ok(value), _) => { panic!("Workflow '{}' returned {:?} instead of failing", code, value); },
+            }
+        }
+
+        // `i64::MIN % -1` does not fit an intermediate division, but its remainder (0) does
+        let code: &str = "let x := 0 - 9223372036854775807 - 1;\nprintln(x % -1);";
+        let workflow: Workflow = compile_and_plan(code, "<arithmetic>");
+        let text: Arc<Mutex<String>>     = Arc::new(Mutex::new(String::new()));
+        let main: Thread<DummyState, ()> = Thread::new(&workflow, DummyState{ text: text.clone(), checkpoints: Arc::new(Mutex::new(vec![])), outputs: Arc::new(HashMap::new()) });
+        if let Err(err) = main.run::<DummyPlugin>().await { err.prettyprint(); panic!("Workflow '{}' failed (see output above)", code); }
+        assert_eq!(text.lock().unwrap().trim(), "0");
+    }
+
+    /// Tests whether parallel branches that take the same lock run their critical sections one after the other, and whether locks are released when a branch finishes.
+    #[tokio::test]
+    async fn test_lock() {
+        let code: &str = "parallel [{\n    lock(\"api\");\n    println(\"in\");\n    sleep(1);\n    println(\"out\");\n    unlock(\"api\");\n}, {\n    lock(\"api\");\n    println(\"in\");\n    sleep(1);\n    println(\"out\");\n    unlock(\"api\");\n}];\n";
+
+        // Compile and plan the workflow
+        let workflow: Workflow = compile_and_plan(code, "<lock>");
+
+        // Without the lock, both branches would enter before either leaves
+        let text: Arc<Mutex<String>>     = Arc::new(Mutex::new(String::new()));
+        let main: Thread<DummyState, ()> = Thread::new(&workflow, DummyState{ text: text.clone(), checkpoints: Arc::new(Mutex::new(vec![])), outputs: Arc::new(HashMap::new()) });
+        if let Err(err) = main.run::<DummyPlugin>().await { err.prettyprint(); panic!("Failed to execute workflow (see output above)"); }
+        assert_eq!(*text.lock().unwrap(), "in\nout\nin\nout\n");
+
+        // A branch that finishes without unlocking releases its locks anyway
+        let code: &str = "parallel [{\n    lock(\"api\");\n}];\nlock(\"api\");\nprintln(\"done\");\nunlock(\"api\");\n";
+        let workflow: Workflow = compile_and_plan(code, "<lock>");
+        let text: Arc<Mutex<String>>     = Arc::new(Mutex::new(String::new()));
+        let main: Thread<DummyState, ()> = Thread::new(&workflow, DummyState{ text: text.clone(), checkpoints: Arc::new(Mutex::new(vec![])), outputs: Arc::new(HashMap::new()) });
+        match tokio::time::timeout(Duration::from_secs(10), main.run::<DummyPlugin>()).await {
+            Ok(Ok(_))    => { assert_eq!(*text.lock().unwrap(), "done\n"); },
+            Ok(Err(err)) => { err.prettyprint(); panic!("Failed to execute workflow (see output above)"); },
+            Err(_)       => { panic!("Lock was not released when the branch holding it finished"); },
+        }
+    }
+
+    /// Tests whether taking a lock twice or releasing one that is not held fails with a typed error instead of deadlocking.
+    #[tokio::test]
+    async fn test_lock_errors() {
+        // Note that branches do not inherit the locks of the thread that spawns them
+        for (code, held) in [ ("lock(\"api\");\nlock(\"api\");\n", true), ("unlock(\"api\");\n", false), ("lock(\"api\");\nunlock(\"api\");\nunlock(\"api\");\n", false), ("lock(\"api\");\nparallel [{\n    unlock(\"api\");\n}];\n", false) ] {
+            // Compile and plan the workflow
+            let workflow: Workflow = compile_and_plan(code, "<lock_errors>");
+
+            // Run it and check it fails for the right reason
+            let main: Thread<DummyState, ()> = Thread::new(&workflow, DummyState{ text: Arc::new(Mutex::new(String::new())), checkpoints: Arc::new(Mutex::new(vec![])), outputs: Arc::new(HashMap::new()) });
+            match (tokio::time::timeout(Duration::from_secs(10), main.run::<DummyPlugin>()).await, held) {
+                (Ok(Err(Error::LockAlreadyHeld{ name, .. })), true) | (Ok(Err(Error::LockNotHeld{ name, .. })), false) => { assert_eq!(name, "api"); },
+                (Ok(Err(err)), _) => { err.prettyprint(); panic!("Workflow '{}' failed for the wrong reason (see output above)", code); },
+                (Ok(Ok(value)), _) => { panic!("Workflow '{}' returned {:?} instead of failing", code, value); },
+                (Err(_), _) => { panic!("Workflow '{}' deadlocked", code); },
+            }
+        }
+    }
 }
 
 
@@ -167,6 +379,7 @@ async fn preprocess_value<P: VmPlugin>(global: &Arc<RwLock<P::GlobalState>>, loc
         // Also handle any nested stuff
         FullValue::Array(values)      => { for v in values { preprocess_value::<P>(global, local, pc, task, at, v, input, data).await?; } return Ok(()); },
         FullValue::Instance(_, props) => { for v in props.values() { preprocess_value::<P>(global, local, pc, task, at, v, input, data).await?; } return Ok(()); },
+        FullValue::Map(values)        => { for v in values.values() { preprocess_value::<P>(global, local, pc, task, at, v, input, data).await?; } return Ok(()); },
 
         // The rest is irrelevant
         _ => { return Ok(()); },
@@ -326,8 +539,12 @@ fn exec_instr(edge: usize, idx: usize, instr: &EdgeInstr, stack: &mut Stack, fst
             // Get it as an integer or real value
             match value {
                 Value::Integer { value } => {
-                    // Put the negated value back
-                    stack.push(Value::Integer { value: -value }).to_instr(edge, idx)?;
+                    // Put the negated value back, unless it does not fit (i.e., for the smallest integer)
+                    let negated: i64 = match value.checked_neg() {
+                        Some(negated) => negated,
+                        None          => { return Err(Error::IntegerOverflow{ edge, instr: idx, expr: format!("-({})", value) }); },
+                    };
+                    stack.push(Value::Integer { value: negated }).to_instr(edge, idx)?;
                 },
                 Value::Real { value } => {
                     // Put the negated value back
@@ -397,8 +614,12 @@ fn exec_instr(edge: usize, idx: usize, instr: &EdgeInstr, stack: &mut Stack, fst
             // Get them both as either numeric _or_ string values
             match (lhs, rhs) {
                 (Value::Integer { value: lhs }, Value::Integer { value: rhs }) => {
-                    // Put the added value back
-                    stack.push(Value::Integer { value: lhs + rhs }).to_instr(edge, idx)?;
+                    // Put the added value back, unless it does not fit
+                    let value: i64 = match lhs.checked_add(rhs) {
+                        Some(value) => value,
+                        None        => { return Err(Error::IntegerOverflow{ edge, instr: idx, expr: format!("{} + {}", lhs, rhs) }); },
+                    };
+                    stack.push(Value::Integer { value }).to_instr(edge, idx)?;
                 },
                 (Value::Real { value: lhs }, Value::Real { value: rhs }) => {
                     // Put the added value back
@@ -429,8 +650,12 @@ fn exec_instr(edge: usize, idx: usize, instr: &EdgeInstr, stack: &mut Stack, fst
             // Get them both as either numeric _or_ string values
             match (lhs, rhs) {
                 (Value::Integer { value: lhs }, Value::Integer { value: rhs }) => {
-                    // Put the subtracted value back
-                    stack.push(Value::Integer { value: lhs - rhs }).to_instr(edge, idx)?;
+                    // Put the subtracted value back, unless it does not fit
+                    let value: i64 = match lhs.checked_sub(rhs) {
+                        Some(value) => value,
+                        None        => { return Err(Error::IntegerOverflow{ edge, instr: idx, expr: format!("{} - {}", lhs, rhs) }); },
+                    };
+                    stack.push(Value::Integer { value }).to_instr(edge, idx)?;
                 },
                 (Value::Real { value: lhs }, Value::Real { value: rhs }) => {
                     // Put the subtracted value back
@@ -456,8 +681,12 @@ fn exec_instr(edge: usize, idx: usize, instr: &EdgeInstr, stack: &mut Stack, fst
             // Get them both as either numeric _or_ string values
             match (lhs, rhs) {
                 (Value::Integer { value: lhs }, Value::Integer { value: rhs }) => {
-                    // Put the multiplied value back
-                    stack.push(Value::Integer { value: lhs * rhs }).to_instr(edge, idx)?;
+                    // Put the multiplied value back, unless it does not fit
+                    let value: i64 = match lhs.checked_mul(rhs) {
+                        Some(value) => value,
+                        None        => { return Err(Error::IntegerOverflow{ edge, instr: idx, expr: format!("{} * {}", lhs, rhs) }); },
+                    };
+                    stack.push(Value::Integer { value }).to_instr(edge, idx)?;
                 },
                 (Value::Real { value: lhs }, Value::Real { value: rhs }) => {
                     // Put the multiplied value back
@@ -483,8 +712,13 @@ fn exec_instr(edge: usize, idx: usize, instr: &EdgeInstr, stack: &mut Stack, fst
             // Get them both as either numeric _or_ string values
             match (lhs, rhs) {
                 (Value::Integer { value: lhs }, Value::Integer { value: rhs }) => {
-                    // Put the divided value back
-                    stack.push(Value::Integer { value: lhs / rhs }).to_instr(edge, idx)?;
+                    if rhs == 0 { return Err(Error::DivisionByZero{ edge, instr: idx }); }
+                    // Put the divided value back, unless it does not fit
+                    let value: i64 = match lhs.checked_div(rhs) {
+                        Some(value) => value,
+                        None        => { return Err(Error::IntegerOverflow{ edge, instr: idx, expr: format!("{} / {}", lhs, rhs) }); },
+                    };
+                    stack.push(Value::Integer { value }).to_instr(edge, idx)?;
                 },
                 (Value::Real { value: lhs }, Value::Real { value: rhs }) => {
                     // Put the divided value back
@@ -513,8 +747,10 @@ fn exec_instr(edge: usize, idx: usize, instr: &EdgeInstr, stack: &mut Stack, fst
                 (_, _)                 => { return Err(Error::StackLhsRhsTypeError { edge, instr: idx, got: (lhs_type, rhs_type), expected: DataType::Integer }); }
             };
 
-            // Push the modulo of the two on top again
-            stack.push(Value::Integer { value: lhs % rhs }).to_instr(edge, idx)?;
+            // Push the modulo of the two on top again, unless it is undefined. Only `i64::MIN % -1` wraps, and its (correct) result is 0.
+            if rhs == 0 { return Err(Error::DivisionByZero{ edge, instr: idx }); }
+            let value: i64 = lhs.wrapping_rem(rhs);
+            stack.push(Value::Integer { value }).to_instr(edge, idx)?;
             1
         },
 
@@ -683,12 +919,65 @@ fn exec_instr(edge: usize, idx: usize, instr: &EdgeInstr, stack: &mut Stack, fst
             stack.push(Value::Array{ values: elems }).to_instr(edge, idx)?;
             1
         },
+        Map{ length, res_type } => {
+            let mut res_type: DataType = res_type.clone();
+
+            // Pop enough key/value pairs off the stack
+            let mut values: HashMap<std::string::String, Value> = HashMap::with_capacity(*length);
+            for _ in 0..*length {
+                // Pop the value (which is pushed after its key)
+                let value: Value = match stack.pop() {
+                    Some(value) => value,
+                    None        => { return Err(Error::EmptyStackError { edge, instr: Some(idx), expected: res_type }); }  
+                };
+
+                // Update the res_type if necessary; otherwise, make sure this is of the correct type
+                if let DataType::Any = &res_type { res_type = value.data_type(fstack.table()); }
+                else if res_type != value.data_type(fstack.table()) { return Err(Error::ArrayTypeError { edge, instr: idx, got: value.data_type(fstack.table()), expected: res_type }) }
+
+                // Pop the key
+                let key: Value = match stack.pop() {
+                    Some(key) => key,
+                    None      => { return Err(Error::EmptyStackError { edge, instr: Some(idx), expected: DataType::String }); }  
+                };
+                let key_type: DataType = key.data_type(fstack.table());
+                let key: std::string::String = match key.try_as_string() {
+                    Some(key) => key,
+                    None      => { return Err(Error::StackTypeError { edge, instr: Some(idx), got: key_type, expected: DataType::String }); }
+                };
+
+                // Add the entry, unless an earlier entry (which we pop later, because the stack is reversed) already overwrote it
+                values.entry(key).or_insert(value);
+            }
+
+            // Create the map and push it back
+            stack.push(Value::Map{ values }).to_instr(edge, idx)?;
+            1
+        },
         ArrayIndex{ res_type } => {
             // Pop the index
             let index: Value = match stack.pop() {
                 Some(index) => index,
                 None        => { return Err(Error::EmptyStackError { edge, instr: Some(idx), expected: DataType::Integer }); }
             };
+
+            // If the indexed value is a map, index it by key instead
+            if let Some(Value::Map{ .. }) = stack.peek() {
+                let index_type: DataType = index.data_type(fstack.table());
+                let key: std::string::String = match index.try_as_string() {
+                    Some(key) => key,
+                    None      => { return Err(Error::StackTypeError { edge, instr: Some(idx), got: index_type, expected: DataType::String }); }
+                };
+                let mut map: HashMap<std::string::String, Value> = stack.pop().unwrap().try_as_map().unwrap();
+
+                // Push the value belonging to the key back
+                match map.remove(&key) {
+                    Some(value) => { stack.push(value).to_instr(edge, idx)?; },
+                    None        => { return Err(Error::MapKeyError{ edge, instr: idx, key }); },
+                }
+                return Ok(1);
+            }
+
             // as an integer
             let index_type: DataType = index.data_type(fstack.table());
             let index: i64 = match index.try_as_int() {
@@ -854,9 +1143,11 @@ fn exec_instr(edge: usize, idx: usize, instr: &EdgeInstr, stack: &mut Stack, fst
 /// Represents a single thread that may be executed.
 pub struct Thread<G: CustomGlobalState, L: CustomLocalState> {
     /// The graph containing the main edges to execute (indexed by `usize::MAX`).
-    graph : Arc<Vec<Edge>>,
+    graph     : Arc<Vec<Edge>>,
     /// The list of function edges to execute.
-    funcs : Arc<HashMap<usize, Vec<Edge>>>,
+    funcs     : Arc<HashMap<usize, Vec<Edge>>>,
+    /// The locations where the restricted inputs of the workflow, and the results derived from them, may be processed.
+    residency : Arc<HashMap<DataName, Vec<Location>>>,
 
     /// The 'program counter' of this thread. It first indexed the correct body (`usize::MAX` for main, or else the index of the function), and then the offset within that body.
     pc : (usize, usize),
@@ -868,6 +1159,33 @@ pub struct Thread<G: CustomGlobalState, L: CustomLocalState> {
 
     /// The threads that we're blocking on.
     blocking_threads : Vec<(usize, JoinHandle<Result<Value, Error>>)>,
+    /// The branches of the current parallel statement that have not been forked yet, as `(branch index, offset)` pairs, because too many were already running.
+    pending_branches : VecDeque<(usize, (usize, usize))>,
+    /// The maximum number of branches of a single parallel statement that this thread (and the ones it forks) runs at the same time. If `None`, all branches are run at once.
+    max_parallel     : Option<usize>,
+
+    /// The named locks (as used by the `lock()` and `unlock()` builtins) that are shared by all threads in this run.
+    locks : Arc<Mutex<HashMap<String, Arc<TokioMutex<()>>>>>,
+    /// The named locks that this thread currently holds. They are released when it calls `unlock()` or when it finishes.
+    held  : HashMap<String, OwnedMutexGuard<()>>,
+
+    /// Whether this thread lets the plugin checkpoint it after it completed a task. Only true for main threads, as forked threads cannot be resumed on their own.
+    checkpointing : bool,
+    /// The moment until which this thread sleeps before continuing, if it was resumed from a checkpoint made while it was sleeping.
+    wake          : Option<DateTime<Utc>>,
+
+    /// The identifier of this thread in execution traces. The main thread is always `0`.
+    id     : usize,
+    /// The identifier of the thread that forked this one, if any.
+    parent : Option<usize>,
+    /// Where this thread lives in the tree of parallel branches, as the index of its branch in every `parallel`-statement it is nested in (outermost first). Empty for the main thread.
+    branch : Vec<usize>,
+    /// The Tracer that records the edges visited by this thread (and the ones it forks), if we're tracing.
+    tracer : Option<Arc<Tracer>>,
+    /// The CancelToken with which this thread (and the ones it forks) may be cancelled, if any.
+    cancel : Option<CancelToken>,
+    /// The Fuel that this thread (and the ones it forks) burns for every step it takes, if its run is limited.
+    fuel   : Option<Fuel>,
 
     /// The thread-global custom part of the RunState.
     global : Arc<RwLock<G>>,
@@ -890,8 +1208,9 @@ impl<G: CustomGlobalState, L: CustomLocalState> Thread<G, L> {
     pub fn new(workflow: &Workflow, global: G) -> Self {
         let global: Arc<RwLock<G>> = Arc::new(RwLock::new(global));
         Self {
-            graph : workflow.graph.clone(),
-            funcs : workflow.funcs.clone(),
+            graph     : workflow.graph.clone(),
+            funcs     : workflow.funcs.clone(),
+            residency : Arc::new(workflow.residency.clone()),
 
             pc : (usize::MAX, 0),
 
@@ -899,6 +1218,21 @@ impl<G: CustomGlobalState, L: CustomLocalState> Thread<G, L> {
             fstack : FrameStack::new(512, workflow.table.clone()),
 
             blocking_threads : vec![],
+            pending_branches : VecDeque::new(),
+            max_parallel     : None,
+
+            locks : Arc::new(Mutex::new(HashMap::new())),
+            held  : HashMap::new(),
+
+            checkpointing : true,
+            wake          : None,
+
+            id     : 0,
+            parent : None,
+            branch : vec![],
+            tracer : None,
+            cancel : None,
+            fuel   : None,
 
             global : global.clone(),
             local  : L::new(&global),
@@ -913,8 +1247,9 @@ impl<G: CustomGlobalState, L: CustomLocalState> Thread<G, L> {
     #[inline]
     pub fn from_state(workflow: &Workflow, state: RunState<G>) -> Self {
         Self {
-            graph : workflow.graph.clone(),
-            funcs : workflow.funcs.clone(),
+            graph     : workflow.graph.clone(),
+            funcs     : workflow.funcs.clone(),
+            residency : Arc::new(workflow.residency.clone()),
 
             pc : (usize::MAX, 0),
 
@@ -922,24 +1257,88 @@ impl<G: CustomGlobalState, L: CustomLocalState> Thread<G, L> {
             fstack : state.fstack,
 
             blocking_threads : vec![],
+            pending_branches : VecDeque::new(),
+            max_parallel     : None,
+
+            locks : Arc::new(Mutex::new(HashMap::new())),
+            held  : HashMap::new(),
+
+            checkpointing : true,
+            wake          : None,
+
+            id     : 0,
+            parent : None,
+            branch : vec![],
+            tracer : None,
+            cancel : None,
+            fuel   : None,
 
             global : state.global.clone(),
             local  : L::new(&state.global),
         }
     }
 
+    /// Spawns a new main thread that resumes a workflow from the given Checkpoint.
+    /// 
+    /// # Arguments
+    /// - `workflow`: The workflow that was running when the checkpoint was made.
+    /// - `checkpoint`: The Checkpoint (as given to `VmPlugin::checkpoint()`) to resume from.
+    /// - `global`: The (already restored) app-wide custom state with which to initialize this thread.
+    /// 
+    /// # Returns
+    /// A new Thread that continues with the edge after the task that was last completed (or first sleeps for what is left, if it was checkpointed while sleeping).
+    #[inline]
+    pub fn from_checkpoint(workflow: &Workflow, checkpoint: Checkpoint, global: Arc<RwLock<G>>) -> Self {
+        Self {
+            graph     : workflow.graph.clone(),
+            funcs     : workflow.funcs.clone(),
+            residency : Arc::new(workflow.residency.clone()),
+
+            pc : checkpoint.pc,
+
+            stack  : Stack::from_checkpoint(2048, checkpoint.stack),
+            fstack : FrameStack::from_checkpoint(512, checkpoint.fstack, workflow.table.clone()),
+
+            blocking_threads : vec![],
+            pending_branches : VecDeque::new(),
+            max_parallel     : None,
+
+            locks : Arc::new(Mutex::new(HashMap::new())),
+            held  : HashMap::new(),
+
+            checkpointing : true,
+            wake          : checkpoint.wake,
+
+            id     : 0,
+            parent : None,
+            branch : vec![],
+            tracer : None,
+            cancel : None,
+            fuel   : None,
+
+            global : global.clone(),
+            local  : L::new(&global),
+        }
+    }
+
     /// 'Forks' this thread such that it may branch in a parallel statement.
     /// 
     /// # Arguments
     /// - `offset`: The offset (as a `(body, idx)` pair) where the thread will begin computation in the edges list.
+    /// - `branch`: The index of the branch (in the parallel statement) that the new thread will execute.
     /// 
     /// # Returns
     /// A new Thread that is partly cloned of this one.
     #[inline]
-    pub fn fork(&self, offset: (usize, usize)) -> Self {
+    pub fn fork(&self, offset: (usize, usize), branch: usize) -> Self {
+        let mut path: Vec<usize> = Vec::with_capacity(self.branch.len() + 1);
+        path.extend_from_slice(&self.branch);
+        path.push(branch);
+
         Self {
-            graph : self.graph.clone(),
-            funcs : self.funcs.clone(),
+            graph     : self.graph.clone(),
+            funcs     : self.funcs.clone(),
+            residency : self.residency.clone(),
 
             pc : offset,
 
@@ -947,6 +1346,22 @@ impl<G: CustomGlobalState, L: CustomLocalState> Thread<G, L> {
             fstack : self.fstack.fork(),
 
             blocking_threads : vec![],
+            pending_branches : VecDeque::new(),
+            max_parallel     : self.max_parallel,
+
+            // Note that the branch shares the locks, but does not inherit the ones held by this thread
+            locks : self.locks.clone(),
+            held  : HashMap::new(),
+
+            checkpointing : false,
+            wake          : None,
+
+            id     : self.tracer.as_ref().map(|t| t.next_thread()).unwrap_or(0),
+            parent : Some(self.id),
+            branch : path,
+            tracer : self.tracer.clone(),
+            cancel : self.cancel.clone(),
+            fuel   : self.fuel.clone(),
 
             global : self.global.clone(),
             local  : L::new(&self.global),
@@ -955,6 +1370,62 @@ impl<G: CustomGlobalState, L: CustomLocalState> Thread<G, L> {
 
 
 
+    /// Lets this thread (and the threads it forks) record every edge it visits to the given Tracer.
+    /// 
+    /// # Arguments
+    /// - `tracer`: The Tracer to record to.
+    /// 
+    /// # Returns
+    /// The same Thread, but now tracing.
+    #[inline]
+    pub fn with_tracer(mut self, tracer: Arc<Tracer>) -> Self {
+        self.tracer = Some(tracer);
+        self
+    }
+
+    /// Lets this thread (and the threads it forks) be cancelled with the given CancelToken.
+    /// 
+    /// # Arguments
+    /// - `cancel`: The CancelToken to listen to.
+    /// 
+    /// # Returns
+    /// The same Thread, but now cancellable.
+    #[inline]
+    pub fn with_cancel(mut self, cancel: CancelToken) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Limits the number of steps that this thread (and the threads it forks) may take together, aborting the run once the given Fuel is exhausted.
+    /// 
+    /// # Arguments
+    /// - `fuel`: The Fuel to burn.
+    /// 
+    /// # Returns
+    /// The same Thread, but now with a limited number of steps.
+    #[inline]
+    pub fn with_fuel(mut self, fuel: Fuel) -> Self {
+        self.fuel = Some(fuel);
+        self
+    }
+
+    /// Limits how many branches of a single parallel statement this thread (and the threads it forks) runs at the same time.
+    /// 
+    /// Any remaining branches are forked as soon as running ones complete, so oversized fan-outs are executed in batches without changing their result.
+    /// 
+    /// # Arguments
+    /// - `max_parallel`: The maximum number of branches to run at once. Values of `0` are treated as `1`.
+    /// 
+    /// # Returns
+    /// The same Thread, but now with a bounded fan-out.
+    #[inline]
+    pub fn with_max_parallel(mut self, max_parallel: usize) -> Self {
+        self.max_parallel = Some(max_parallel.max(1));
+        self
+    }
+
+
+
     /// Saves the important bits of this Thread for a next execution round.
     #[inline]
     fn into_state(self) -> RunState<G> {
@@ -967,6 +1438,136 @@ impl<G: CustomGlobalState, L: CustomLocalState> Thread<G, L> {
 
 
 
+    /// Returns whether the edge at the given program counter is a task call.
+    /// 
+    /// # Arguments
+    /// - `pc`: Points to the edge to examine (as a `(body, offset)` pair).
+    /// 
+    /// # Returns
+    /// True if there is an edge at `pc` and it is a `Node`, or false otherwise.
+    fn is_task(&self, pc: (usize, usize)) -> bool {
+        matches!(self.edge(pc), Some(Edge::Node{ .. }))
+    }
+
+    /// Burns the fuel needed to execute the edge at the given program counter, if the run is limited.
+    /// 
+    /// Every edge costs one step, except for linear edges, which cost one step per instruction in them.
+    /// 
+    /// # Arguments
+    /// - `pc`: Points to the edge that is about to be executed (as a `(body, offset)` pair).
+    /// 
+    /// # Errors
+    /// This function errors if there is not enough fuel left to execute the edge.
+    fn burn_fuel(&self, pc: (usize, usize)) -> Result<(), Error> {
+        let fuel: &Fuel = match &self.fuel {
+            Some(fuel) => fuel,
+            None       => { return Ok(()); },
+        };
+        let cost: u64 = match self.edge(pc) {
+            Some(Edge::Linear{ instrs, .. }) => instrs.len().max(1) as u64,
+            _                                => 1,
+        };
+        if fuel.burn(cost) { Ok(()) } else { Err(Error::ExecutionLimitExceeded{ edge: pc.1, limit: fuel.limit() }) }
+    }
+
+    /// Returns the edge at the given program counter.
+    /// 
+    /// # Arguments
+    /// - `pc`: Points to the edge to return (as a `(body, offset)` pair).
+    /// 
+    /// # Returns
+    /// The Edge, or None if `pc` is out-of-bounds.
+    fn edge(&self, pc: (usize, usize)) -> Option<&Edge> {
+        if pc.0 == usize::MAX {
+            self.graph.get(pc.1)
+        } else {
+            self.funcs.get(&pc.0).and_then(|edges| edges.get(pc.1))
+        }
+    }
+
+    /// Records the execution of the current edge to the Tracer, if we're tracing.
+    /// 
+    /// # Arguments
+    /// - `start`: When we started executing the edge (in microseconds since the Unix epoch).
+    /// - `res`: The EdgeResult of executing it.
+    fn trace(&self, start: i64, res: &EdgeResult) {
+        let tracer: &Tracer = match &self.tracer {
+            Some(tracer) => tracer,
+            None         => { return; },
+        };
+        // Edges out-of-bounds are implicit returns, which we don't record
+        let edge: &Edge = match self.edge(self.pc) {
+            Some(edge) => edge,
+            None       => { return; },
+        };
+
+        let next: Option<(usize, usize)> = if let EdgeResult::Pending(next) = res { Some(*next) } else { None };
+        tracer.record(&TraceEvent {
+            thread : self.id,
+            parent : self.parent,
+            pc     : self.pc,
+            kind   : edge.variant().to_string(),
+            branch : if let Edge::Branch{ true_next, .. } = edge { Some(next == Some((self.pc.0, *true_next))) } else { None },
+            next,
+            start,
+            end    : Utc::now().timestamp_micros(),
+        });
+    }
+
+    /// Lets the plugin checkpoint this thread, if it is in a state from which it can be resumed.
+    /// 
+    /// Failing to checkpoint is not fatal, and is only logged as a warning.
+    /// 
+    /// # Generic arguments
+    /// - `P`: The VmPlugin that persists the checkpoint.
+    /// 
+    /// # Arguments
+    /// - `pc`: The edge at which a resumed thread should continue.
+    /// - `wake`: The moment until which a resumed thread should sleep first, if this thread is checkpointed while sleeping.
+    async fn checkpoint<P: VmPlugin<GlobalState = G, LocalState = L>>(&self, pc: (usize, usize), wake: Option<DateTime<Utc>>) {
+        // We cannot resume from the middle of a parallel statement or while holding locks
+        if !self.checkpointing || !self.blocking_threads.is_empty() || !self.pending_branches.is_empty() || !self.held.is_empty() { return; }
+
+        // Otherwise, snapshot ourselves and hand it to the plugin
+        let checkpoint: Checkpoint = Checkpoint {
+            pc,
+            stack  : self.stack.checkpoint(),
+            fstack : self.fstack.checkpoint(),
+            wake,
+        };
+        if let Err(err) = P::checkpoint(&self.global, &self.local, &checkpoint).await {
+            warn!("Failed to checkpoint workflow at edge {} in {}: {} (continuing without it)", pc.1, if pc.0 == usize::MAX { "<main>".into() } else { format!("function {}", pc.0) }, err);
+        }
+    }
+
+    /// Sleeps until the given moment (or not at all if it's in the past), unless the thread is cancelled first.
+    /// 
+    /// # Arguments
+    /// - `until`: The moment at which to wake up.
+    /// 
+    /// # Errors
+    /// This function errors if the thread was cancelled while sleeping.
+    async fn sleep_until(&self, until: DateTime<Utc>) -> Result<(), Error> {
+        let wait: Duration = match (until - Utc::now()).to_std() {
+            Ok(wait) => wait,
+            Err(_)   => { return Ok(()); },
+        };
+        debug!("Sleeping until {} ({}s)...", until, wait.as_secs());
+        let cancel: CancelToken = match &self.cancel {
+            Some(cancel) => cancel.clone(),
+            None         => { tokio::time::sleep(wait).await; return Ok(()); },
+        };
+
+        // Race the sleep against the token
+        let sleep     = tokio::time::sleep(wait);
+        let cancelled = cancel.cancelled();
+        futures::pin_mut!(sleep, cancelled);
+        match future::select(sleep, cancelled).await {
+            Either::Left(_)  => Ok(()),
+            Either::Right(_) => Err(Error::Cancelled{ edge: self.pc.1 }),
+        }
+    }
+
     /// Executes a single edge, modifying the given stacks and variable register.
     /// 
     /// # Arguments
@@ -1004,13 +1605,13 @@ impl<G: CustomGlobalState, L: CustomLocalState> Thread<G, L> {
         // Match on the specific edge
         use Edge::*;
         let next: (usize, usize) = match edge {
-            Node{ task, at, input, result, next, .. } => {
+            Node{ task, at, input, result, retry, timeout, next, .. } => {
                 // Resolve the task
                 let task: &TaskDef = self.fstack.table().task(*task);
 
                 // Match the thing to do
                 match task {
-                    TaskDef::Compute { package, version, function, args_names, requirements } => {
+                    TaskDef::Compute { package, version, function, args_names, requirements, secrets } => {
                         debug!("Calling compute task '{}' ('{}' v{})", task.name(), package, version);
 
                         // Collect the arguments from the stack (remember, reverse order)
@@ -1063,17 +1664,32 @@ impl<G: CustomGlobalState, L: CustomLocalState> Thread<G, L> {
                             package_name    : package,
                             package_version : version,
                             requirements,
+                            secrets,
 
                             args,
                             location : at,
                             input    : data,
                             result,
+                            timeout  : *timeout,
+
+                            pc,
                         };
 
-                        // Call the external call function with the correct arguments
-                        let mut res: Option<Value> = match P::execute(&self.global, &self.local, info).await {
-                            Ok(res)  => res.map(|v| v.into_value(self.fstack.table())),
-                            Err(err) => { return EdgeResult::Err(Error::Custom{ edge: pc.1, err: Box::new(err) }); },
+                        // Call the external call function with the correct arguments, retrying it (with exponential backoff) if it fails transiently and the call allows it
+                        let mut attempt: u32 = 0;
+                        let mut res: Option<Value> = loop {
+                            match P::execute(&self.global, &self.local, info.clone()).await {
+                                Ok(res)  => { break res.map(|v| v.into_value(self.fstack.table())); },
+                                Err(err) => match retry {
+                                    Some(retry) if attempt < retry.max_retries && P::is_transient(&err) => {
+                                        attempt += 1;
+                                        let delay: u64 = retry.delay(attempt);
+                                        warn!("Task '{}' failed (attempt {} of {}); retrying in {}ms: {}", task.name(), attempt, retry.max_retries + 1, delay, err);
+                                        tokio::time::sleep(Duration::from_millis(delay)).await;
+                                    },
+                                    _ => { return EdgeResult::Err(Error::Custom{ edge: pc.1, err: Box::new(err) }); },
+                                },
+                            }
                         };
 
                         // If the function returns an intermediate result but returned nothing, that's fine; we inject the result here
@@ -1150,30 +1766,36 @@ impl<G: CustomGlobalState, L: CustomLocalState> Thread<G, L> {
                 }
             },
             Parallel{ branches, merge } => {
-                // Fork this thread for every branch
+                // Queue every branch, and then fork as many of them as we may run at once
                 self.blocking_threads.clear();
-                self.blocking_threads.reserve(branches.len());
-                for (i, b) in branches.iter().enumerate() {
+                self.pending_branches = branches.iter().enumerate().map(|(i, b)| (i, (pc.0, *b))).collect();
+                let batch: usize = self.max_parallel.unwrap_or(branches.len()).min(branches.len());
+                if batch < branches.len() { debug!("Running {} branches of parallel statement in batches of {}", branches.len(), batch); }
+                self.blocking_threads.reserve(batch);
+                for (i, offset) in self.pending_branches.drain(..batch).collect::<Vec<_>>() {
                     // Fork the thread for that branch
-                    self.blocking_threads.push((i, spawn(self.fork((pc.0, *b)).run::<P>())));
+                    self.blocking_threads.push((i, spawn(self.fork(offset, i).run::<P>())));
                 }
 
                 // Mark those threads to wait for, and then move to the join
                 (pc.0, *merge)
             },
             Join{ merge, next } => {
-                // Await the threads first (if any)
-                let mut results: Vec<(usize, Value)> = Vec::with_capacity(self.blocking_threads.len());
-                for (i, t) in &mut self.blocking_threads {
+                // Await the threads first (if any), forking the branches that are still waiting as running ones complete
+                let mut results: Vec<(usize, Value)> = Vec::with_capacity(self.blocking_threads.len() + self.pending_branches.len());
+                while !self.blocking_threads.is_empty() {
+                    let (i, t): (usize, JoinHandle<Result<Value, Error>>) = self.blocking_threads.remove(0);
                     match t.await {
                         Ok(status) => match status {
-                            Ok(res)  => { results.push((*i, res)); },
-                            Err(err) => { return EdgeResult::Err(err); },
+                            Ok(res)  => { results.push((i, res)); },
+                            Err(err) => { self.pending_branches.clear(); return EdgeResult::Err(err); },
                         },
-                        Err(err)   => { return EdgeResult::Err(Error::SpawnError{ edge: pc.1, err }); }
+                        Err(err)   => { self.pending_branches.clear(); return EdgeResult::Err(Error::SpawnError{ edge: pc.1, err }); }
+                    }
+                    if let Some((i, offset)) = self.pending_branches.pop_front() {
+                        self.blocking_threads.push((i, spawn(self.fork(offset, i).run::<P>())));
                     }
                 }
-                self.blocking_threads.clear();
 
                 // Join their values into one according to the merge strategy
                 let result: Option<Value> = match merge {
@@ -1417,7 +2039,7 @@ impl<G: CustomGlobalState, L: CustomLocalState> Thread<G, L> {
                 if sig.name == BuiltinFunctions::Print.name() {
                     // We have one variable that is a string; so print it
                     let text: String = self.stack.pop().unwrap().try_as_string().unwrap();
-                    if let Err(err) = P::stdout(&self.global, &self.local, &text, false).await {
+                    if let Err(err) = P::stdout(&self.global, &self.local, &self.branch, &text, false).await {
                         return EdgeResult::Err(Error::Custom{ edge: pc.1, err: Box::new(err) });
                     }
 
@@ -1427,7 +2049,7 @@ impl<G: CustomGlobalState, L: CustomLocalState> Thread<G, L> {
                 } else if sig.name == BuiltinFunctions::PrintLn.name() {
                     // We have one variable that is a string; so print it
                     let text: String = self.stack.pop().unwrap().try_as_string().unwrap();
-                    if let Err(err) = P::stdout(&self.global, &self.local, &text, true).await {
+                    if let Err(err) = P::stdout(&self.global, &self.local, &self.branch, &text, true).await {
                         return EdgeResult::Err(Error::Custom{ edge: pc.1, err: Box::new(err) });
                     }
 
@@ -1444,6 +2066,106 @@ impl<G: CustomGlobalState, L: CustomLocalState> Thread<G, L> {
                     // We can then go to the next one immediately
                     (pc.0, *next)
 
+                } else if sig.name == BuiltinFunctions::Keys.name() {
+                    // Fetch the map
+                    let map: HashMap<String, Value> = self.stack.pop().unwrap().try_as_map().unwrap();
+
+                    // Push its keys back onto the stack, sorted to keep iteration deterministic
+                    let mut keys: Vec<String> = map.into_keys().collect();
+                    keys.sort();
+                    if let Err(err) = self.stack.push(Value::Array { values: keys.into_iter().map(|k| Value::String { value: k }).collect() }) { return EdgeResult::Err(Error::StackError{ edge: pc.1, instr: None, err }); }
+
+                    // We can then go to the next one immediately
+                    (pc.0, *next)
+
+                } else if sig.name == BuiltinFunctions::Split.name() {
+                    // Fetch the string and the separator (in reverse order, since it's a stack)
+                    let sep   : String = self.stack.pop().unwrap().try_as_string().unwrap();
+                    let value : String = self.stack.pop().unwrap().try_as_string().unwrap();
+
+                    // Push the parts back onto the stack
+                    if let Err(err) = self.stack.push(Value::Array { values: value.split(sep.as_str()).map(|p| Value::String { value: p.into() }).collect() }) { return EdgeResult::Err(Error::StackError{ edge: pc.1, instr: None, err }); }
+
+                    // We can then go to the next one immediately
+                    (pc.0, *next)
+
+                } else if sig.name == BuiltinFunctions::Join.name() {
+                    // Fetch the parts and the separator (in reverse order, since it's a stack)
+                    let sep   : String     = self.stack.pop().unwrap().try_as_string().unwrap();
+                    let parts : Vec<Value> = self.stack.pop().unwrap().try_as_array().unwrap();
+
+                    // Push the joined string back onto the stack
+                    let parts: Vec<String> = parts.into_iter().map(|p| p.try_as_string().unwrap()).collect();
+                    if let Err(err) = self.stack.push(Value::String { value: parts.join(&sep) }) { return EdgeResult::Err(Error::StackError{ edge: pc.1, instr: None, err }); }
+
+                    // We can then go to the next one immediately
+                    (pc.0, *next)
+
+                } else if sig.name == BuiltinFunctions::ToUpper.name() {
+                    // Fetch the string
+                    let value: String = self.stack.pop().unwrap().try_as_string().unwrap();
+
+                    // Push its uppercase version back onto the stack
+                    if let Err(err) = self.stack.push(Value::String { value: value.to_uppercase() }) { return EdgeResult::Err(Error::StackError{ edge: pc.1, instr: None, err }); }
+
+                    // We can then go to the next one immediately
+                    (pc.0, *next)
+
+                } else if sig.name == BuiltinFunctions::Substring.name() {
+                    // Fetch the string and the range (in reverse order, since it's a stack)
+                    let end   : i64    = self.stack.pop().unwrap().try_as_int().unwrap();
+                    let start : i64    = self.stack.pop().unwrap().try_as_int().unwrap();
+                    let value : String = self.stack.pop().unwrap().try_as_string().unwrap();
+
+                    // Make sure the range is within the string (counting in characters, not bytes)
+                    let len: usize = value.chars().count();
+                    if start < 0 || end < start || end as usize > len { return EdgeResult::Err(Error::IllegalSubstring{ edge: pc.1, start, end, len }); }
+
+                    // Push the substring back onto the stack
+                    if let Err(err) = self.stack.push(Value::String { value: value.chars().skip(start as usize).take((end - start) as usize).collect() }) { return EdgeResult::Err(Error::StackError{ edge: pc.1, instr: None, err }); }
+
+                    // We can then go to the next one immediately
+                    (pc.0, *next)
+
+                } else if sig.name == BuiltinFunctions::Contains.name() {
+                    // Fetch the string and the one to search for (in reverse order, since it's a stack)
+                    let needle : String = self.stack.pop().unwrap().try_as_string().unwrap();
+                    let value  : String = self.stack.pop().unwrap().try_as_string().unwrap();
+
+                    // Push whether it was found back onto the stack
+                    if let Err(err) = self.stack.push(Value::Boolean { value: value.contains(needle.as_str()) }) { return EdgeResult::Err(Error::StackError{ edge: pc.1, instr: None, err }); }
+
+                    // We can then go to the next one immediately
+                    (pc.0, *next)
+
+                } else if sig.name == BuiltinFunctions::ParseInt.name() {
+                    // Fetch the string
+                    let raw: String = self.stack.pop().unwrap().try_as_string().unwrap();
+
+                    // Parse it and push it back onto the stack
+                    let value: i64 = match raw.trim().parse() {
+                        Ok(value) => value,
+                        Err(err)  => { return EdgeResult::Err(Error::IllegalInteger{ edge: pc.1, raw, err }); },
+                    };
+                    if let Err(err) = self.stack.push(Value::Integer { value }) { return EdgeResult::Err(Error::StackError{ edge: pc.1, instr: None, err }); }
+
+                    // We can then go to the next one immediately
+                    (pc.0, *next)
+
+                } else if sig.name == BuiltinFunctions::ParseReal.name() {
+                    // Fetch the string
+                    let raw: String = self.stack.pop().unwrap().try_as_string().unwrap();
+
+                    // Parse it and push it back onto the stack
+                    let value: f64 = match raw.trim().parse() {
+                        Ok(value) => value,
+                        Err(err)  => { return EdgeResult::Err(Error::IllegalReal{ edge: pc.1, raw, err }); },
+                    };
+                    if let Err(err) = self.stack.push(Value::Real { value }) { return EdgeResult::Err(Error::StackError{ edge: pc.1, instr: None, err }); }
+
+                    // We can then go to the next one immediately
+                    (pc.0, *next)
+
                 } else if sig.name == BuiltinFunctions::CommitResult.name() {
                     // Fetch the arguments
                     let res_name  : String   = self.stack.pop().unwrap().try_as_intermediate_result().unwrap();
@@ -1455,8 +2177,9 @@ impl<G: CustomGlobalState, L: CustomLocalState> Thread<G, L> {
                         None      => { return EdgeResult::Err(Error::UnknownResult{ edge: pc.1, name: res_name }); },
                     };
 
-                    // Call the external data committer
-                    if let Err(err) = P::commit(&self.global, &self.local, loc, &res_name, &PathBuf::from(&res_name), &data_name).await {
+                    // Call the external data committer, which should keep the restrictions of the data the result was derived from
+                    let residency: Option<&[Location]> = self.residency.get(&DataName::IntermediateResult(res_name.clone())).map(|locs| locs.as_slice());
+                    if let Err(err) = P::commit(&self.global, &self.local, loc, &res_name, &PathBuf::from(&res_name), &data_name, residency).await {
                         return EdgeResult::Err(Error::Custom{ edge: pc.1, err: Box::new(err) });
                     };
 
@@ -1466,6 +2189,176 @@ impl<G: CustomGlobalState, L: CustomLocalState> Thread<G, L> {
                     // We can then go to the next one immediately
                     (pc.0, *next)
 
+                } else if sig.name == BuiltinFunctions::Lock.name() {
+                    // Fetch the name of the lock
+                    let name: String = self.stack.pop().unwrap().try_as_string().unwrap();
+                    // Taking a lock twice would wait on ourselves forever
+                    if self.held.contains_key(&name) { return EdgeResult::Err(Error::LockAlreadyHeld{ edge: pc.1, name }); }
+
+                    // Get the lock (creating it if no-one used it before)
+                    let lock: Arc<TokioMutex<()>> = {
+                        let mut locks = self.locks.lock().unwrap();
+                        locks.entry(name.clone()).or_default().clone()
+                    };
+
+                    // Wait until it's ours
+                    debug!("Acquiring lock '{}'...", name);
+                    let guard: OwnedMutexGuard<()> = lock.lock_owned().await;
+                    debug!("Acquired lock '{}'", name);
+                    self.held.insert(name, guard);
+
+                    // We can then go to the next one
+                    (pc.0, *next)
+
+                } else if sig.name == BuiltinFunctions::Unlock.name() {
+                    // Fetch the name of the lock
+                    let name: String = self.stack.pop().unwrap().try_as_string().unwrap();
+
+                    // Release it by dropping the guard
+                    if self.held.remove(&name).is_none() { return EdgeResult::Err(Error::LockNotHeld{ edge: pc.1, name }); }
+                    debug!("Released lock '{}'", name);
+
+                    // We can then go to the next one immediately
+                    (pc.0, *next)
+
+                } else if sig.name == BuiltinFunctions::Sleep.name() {
+                    // Fetch the number of seconds
+                    let seconds: i64 = self.stack.pop().unwrap().try_as_int().unwrap();
+                    if seconds < 0 { return EdgeResult::Err(Error::IllegalSleepDuration{ edge: pc.1, seconds }); }
+
+                    // Suspend this branch; it holds no container, so this costs nothing but a timer
+                    let duration: Duration = Duration::from_secs(seconds as u64);
+                    match chrono::Duration::from_std(duration).ok().and_then(|duration| Utc::now().checked_add_signed(duration)) {
+                        Some(until) => {
+                            // Remember when to wake up, so that a resumed run only sleeps for what is left of it
+                            self.checkpoint::<P>((pc.0, *next), Some(until)).await;
+                            debug!("Sleeping for {}s (until {})...", seconds, until);
+                            if let Ok(wait) = (until - Utc::now()).to_std() { tokio::time::sleep(wait).await; }
+                        },
+                        None => {
+                            // Too far in the future to be represented as a moment (and thus to be checkpointed)
+                            debug!("Sleeping for {}s...", seconds);
+                            tokio::time::sleep(duration).await;
+                        },
+                    }
+
+                    // We can then go to the next one
+                    (pc.0, *next)
+
+                } else if sig.name == BuiltinFunctions::WaitUntil.name() {
+                    // Fetch the point in time
+                    let raw: String = self.stack.pop().unwrap().try_as_string().unwrap();
+                    let until: DateTime<Utc> = match DateTime::parse_from_rfc3339(&raw) {
+                        Ok(until) => until.with_timezone(&Utc),
+                        Err(err)  => { return EdgeResult::Err(Error::IllegalTimestamp{ edge: pc.1, raw, err }); },
+                    };
+
+                    // Suspend this branch until then (or not at all if it's in the past)
+                    if let Ok(wait) = (until - Utc::now()).to_std() {
+                        debug!("Waiting until {} ({}s)...", until, wait.as_secs());
+                        tokio::time::sleep(wait).await;
+                    }
+
+                    // We can then go to the next one
+                    (pc.0, *next)
+
+                } else if sig.name == BuiltinFunctions::WaitForEvent.name() {
+                    // Fetch the name of the event
+                    let name: String = self.stack.pop().unwrap().try_as_string().unwrap();
+
+                    // Let the plugin park us until it arrives
+                    debug!("Waiting for event '{}'...", name);
+                    let payload: String = match P::wait_event(&self.global, &self.local, &name).await {
+                        Ok(payload) => payload,
+                        Err(err)    => { return EdgeResult::Err(Error::Custom{ edge: pc.1, err: Box::new(err) }); },
+                    };
+                    debug!("Received event '{}'", name);
+
+                    // Push the payload onto the stack
+                    if let Err(err) = self.stack.push(Value::String{ value: payload }) { return EdgeResult::Err(Error::StackError{ edge: pc.1, instr: None, err }); }
+
+                    // We can then go to the next one
+                    (pc.0, *next)
+
+                } else if sig.name == BuiltinFunctions::RequireApproval.name() {
+                    // Fetch the message to approve
+                    let message: String = self.stack.pop().unwrap().try_as_string().unwrap();
+
+                    // Let the plugin park us until someone decided on it
+                    debug!("Waiting for approval of '{}'...", message);
+                    let decision: ApprovalDecision = match P::require_approval(&self.global, &self.local, &message).await {
+                        Ok(decision) => decision,
+                        Err(err)     => { return EdgeResult::Err(Error::Custom{ edge: pc.1, err: Box::new(err) }); },
+                    };
+                    if !decision.approved { return EdgeResult::Err(Error::ApprovalRejected{ edge: pc.1, message, user: decision.user, reason: decision.reason }); }
+                    debug!("User '{}' approved '{}'", decision.user, message);
+
+                    // We can then go to the next one
+                    (pc.0, *next)
+
+                } else if sig.name == BuiltinFunctions::Notify.name() {
+                    // Fetch the message and the channel (in reverse order, since it's a stack)
+                    let message: String = self.stack.pop().unwrap().try_as_string().unwrap();
+                    let channel: String = self.stack.pop().unwrap().try_as_string().unwrap();
+
+                    // Let the plugin deliver it
+                    debug!("Sending notification on channel '{}'...", channel);
+                    if let Err(err) = P::notify(&self.global, &self.local, &channel, &message).await { return EdgeResult::Err(Error::Custom{ edge: pc.1, err: Box::new(err) }); }
+
+                    // We can then go to the next one
+                    (pc.0, *next)
+
+                } else if sig.name == BuiltinFunctions::ListPackages.name() {
+                    // Ask the plugin what the instance knows
+                    let (pindex, _): (Arc<PackageIndex>, Arc<DataIndex>) = match P::indices(&self.global, &self.local).await {
+                        Ok(indices) => indices,
+                        Err(err)    => { return EdgeResult::Err(Error::Custom{ edge: pc.1, err: Box::new(err) }); },
+                    };
+
+                    // Push the names of its packages onto the stack, once per package (regardless of versions) and sorted to keep iteration deterministic
+                    let names: BTreeSet<String> = pindex.packages.values().map(|info| info.name.clone()).collect();
+                    if let Err(err) = self.stack.push(Value::Array { values: names.into_iter().map(|n| Value::String { value: n }).collect() }) { return EdgeResult::Err(Error::StackError{ edge: pc.1, instr: None, err }); }
+
+                    // We can then go to the next one
+                    (pc.0, *next)
+
+                } else if sig.name == BuiltinFunctions::ListDatasets.name() {
+                    // Ask the plugin what the instance knows
+                    let (_, dindex): (Arc<PackageIndex>, Arc<DataIndex>) = match P::indices(&self.global, &self.local).await {
+                        Ok(indices) => indices,
+                        Err(err)    => { return EdgeResult::Err(Error::Custom{ edge: pc.1, err: Box::new(err) }); },
+                    };
+
+                    // Push the names of its datasets onto the stack, sorted to keep iteration deterministic
+                    let names: BTreeSet<String> = dindex.iter().map(|info| info.name.clone()).collect();
+                    if let Err(err) = self.stack.push(Value::Array { values: names.into_iter().map(|n| Value::String { value: n }).collect() }) { return EdgeResult::Err(Error::StackError{ edge: pc.1, instr: None, err }); }
+
+                    // We can then go to the next one
+                    (pc.0, *next)
+
+                } else if sig.name == BuiltinFunctions::DatasetSize.name() {
+                    // Fetch the name of the dataset
+                    let name: String = self.stack.pop().unwrap().try_as_string().unwrap();
+
+                    // Ask the plugin what the instance knows about it
+                    let (_, dindex): (Arc<PackageIndex>, Arc<DataIndex>) = match P::indices(&self.global, &self.local).await {
+                        Ok(indices) => indices,
+                        Err(err)    => { return EdgeResult::Err(Error::Custom{ edge: pc.1, err: Box::new(err) }); },
+                    };
+                    let size: u64 = match dindex.get(&name) {
+                        Some(info) => match info.size {
+                            Some(size) => size,
+                            None       => { return EdgeResult::Err(Error::UnknownDataSize{ edge: pc.1, name }); },
+                        },
+                        None => { return EdgeResult::Err(Error::UnknownData{ edge: pc.1, name }); },
+                    };
+
+                    // Push the size onto the stack
+                    if let Err(err) = self.stack.push(Value::Integer { value: size as i64 }) { return EdgeResult::Err(Error::StackError{ edge: pc.1, instr: None, err }); }
+
+                    // We can then go to the next one
+                    (pc.0, *next)
+
                 } else {
                     // Push the return address onto the frame stack and then go to the correct function
                     if let Err(err) = self.fstack.push(def, (pc.0, *next)) { return EdgeResult::Err(Error::FrameStackPushError{ edge: pc.1, err }); }
@@ -1509,6 +2402,38 @@ impl<G: CustomGlobalState, L: CustomLocalState> Thread<G, L> {
 
 
 
+    /// Executes the given edge like `Thread::exec_edge()`, but abandons it as soon as the thread is cancelled.
+    /// 
+    /// Dropping the edge halfway is what stops any external call it is waiting for (e.g., a task running on another node).
+    /// 
+    /// # Arguments
+    /// - `pc`: Points to the edge to execute (as a `(body, offset)` pair).
+    /// 
+    /// # Returns
+    /// The EdgeResult of the edge, or an error if the thread was cancelled before or while executing it.
+    async fn exec_cancellable<P: VmPlugin<GlobalState = G, LocalState = L>>(&mut self, pc: (usize, usize)) -> EdgeResult {
+        let cancel: CancelToken = match &self.cancel {
+            Some(cancel) => cancel.clone(),
+            None         => { return self.exec_edge::<P>(pc).await; },
+        };
+        if cancel.is_cancelled() { return EdgeResult::Err(Error::Cancelled{ edge: pc.1 }); }
+
+        // Race the edge against the token
+        let id: usize = self.id;
+        let edge      = self.exec_edge::<P>(pc);
+        let cancelled = cancel.cancelled();
+        futures::pin_mut!(edge, cancelled);
+        match future::select(edge, cancelled).await {
+            Either::Left((res, _)) => res,
+            Either::Right(_)       => {
+                debug!("Thread {} cancelled at edge {}", id, pc.1);
+                EdgeResult::Err(Error::Cancelled{ edge: pc.1 })
+            },
+        }
+    }
+
+
+
     /// Runs the thread once until it is pending for something (either other threads or external function calls).
     /// 
     /// # Returns
@@ -1518,14 +2443,27 @@ impl<G: CustomGlobalState, L: CustomLocalState> Thread<G, L> {
     /// This function may error if execution of an edge or instruction failed. This is typically due to incorrect runtime typing.
     pub fn run<P: VmPlugin<GlobalState = G, LocalState = L>>(mut self) -> BoxFuture<'static, Result<Value, Error>> {
         async move {
+            // If we were checkpointed while sleeping, first sleep for whatever is left
+            if let Some(wake) = self.wake.take() { self.sleep_until(wake).await?; }
+
             // Start executing edges from where we left off
             loop {
+                // Make sure we may take another step
+                self.burn_fuel(self.pc)?;
+
                 // Run the edge
-                self.pc = match self.exec_edge::<P>(self.pc).await {
+                let task  : bool       = self.is_task(self.pc);
+                let start : i64        = Utc::now().timestamp_micros();
+                let res   : EdgeResult = self.exec_cancellable::<P>(self.pc).await;
+                self.trace(start, &res);
+                self.pc = match res {
                     EdgeResult::Ok(value)     => { return Ok(value); },
                     EdgeResult::Pending(next) => next,
                     EdgeResult::Err(err)      => { return Err(err); },
                 };
+
+                // Snapshot the workflow every time it completed a task
+                if task { self.checkpoint::<P>(self.pc, None).await; }
             }
         }.boxed()
     }
@@ -1543,13 +2481,23 @@ impl<G: CustomGlobalState, L: CustomLocalState> Thread<G, L> {
         async move {
             // Start executing edges from where we left off
             loop {
+                // Make sure we may take another step
+                self.burn_fuel(self.pc)?;
+
                 // Run the edge
-                self.pc = match self.exec_edge::<P>(self.pc).await {
+                let task  : bool       = self.is_task(self.pc);
+                let start : i64        = Utc::now().timestamp_micros();
+                let res   : EdgeResult = self.exec_cancellable::<P>(self.pc).await;
+                self.trace(start, &res);
+                self.pc = match res {
                     // Return not just the value, but also the VmState part of this thread to keep.
                     EdgeResult::Ok(value)     => { return Ok((value, self.into_state())); },
                     EdgeResult::Pending(next) => next,
                     EdgeResult::Err(err)      => { return Err(err); },
                 };
+
+                // Snapshot the workflow every time it completed a task
+                if task { self.checkpoint::<P>(self.pc, None).await; }
             }
         }.boxed()
     }
@@ -0,0 +1,329 @@
+//  TRACE.rs
+//    by agent
+//
+//  Created:
+//    16 Oct 2026, 08:42:41
+//  Last edited:
+//    16 Oct 2026, 08:42:41
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements execution traces, which record every edge visited by the
+//!   threads of a workflow to a JSONL file. These can be analyzed
+//!   afterwards to find the branch coverage and critical path of a run.
+//
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{self, File};
+use std::io::{LineWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use brane_ast::Workflow;
+use brane_ast::ast::Edge;
+use brane_ast::schedule::{self, TaskTiming};
+
+pub use crate::errors::TraceError as Error;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Shorthand for creating a TraceEvent.
+    fn event(thread: usize, parent: Option<usize>, pc: usize, kind: &str, start: i64, end: i64) -> TraceEvent {
+        TraceEvent { thread, parent, pc: (usize::MAX, pc), kind: kind.into(), branch: None, next: Some((usize::MAX, pc + 1)), start, end }
+    }
+
+    /// Tests whether the critical path follows the slowest branch of a parallel statement.
+    #[test]
+    fn test_analyze() {
+        let mut branch: TraceEvent = event(0, None, 0, "Branch", 0, 1);
+        branch.branch = Some(true);
+        let events: Vec<TraceEvent> = vec![
+            branch,
+            event(0, None, 1, "Parallel", 1, 2),
+            event(1, Some(0), 2, "Node", 3, 90),
+            event(2, Some(0), 3, "Node", 3, 40),
+            event(0, None, 4, "Join", 2, 95),
+            event(0, None, 5, "Stop", 95, 96),
+        ];
+
+        // Analyze it
+        let analysis: TraceAnalysis = analyze(&events, None);
+        assert_eq!(analysis.duration, 96);
+        assert_eq!(analysis.threads, 3);
+        assert_eq!(analysis.visits.len(), 6);
+        assert_eq!(analysis.branches.get(&(usize::MAX, 0)), Some(&(true, false)));
+        assert_eq!(analysis.critical.iter().map(|e| (e.thread, e.pc.1)).collect::<Vec<(usize, usize)>>(), vec![ (0, 0), (0, 1), (1, 2), (0, 4), (0, 5) ]);
+    }
+
+    /// Tests the critical path through nested parallel statements and edges that took no time.
+    #[test]
+    fn test_critical_path_nested() {
+        let events: Vec<TraceEvent> = vec![
+            event(0, None, 0, "Parallel", 0, 1),
+            // The first branch forks again, and its slowest child determines the run
+            event(1, Some(0), 1, "Parallel", 1, 2),
+            event(3, Some(1), 2, "Node", 2, 50),
+            event(4, Some(1), 3, "Node", 2, 70),
+            event(1, Some(0), 4, "Join", 2, 70),
+            event(1, Some(0), 5, "Return", 70, 70),
+            event(2, Some(0), 6, "Node", 1, 60),
+            event(0, None, 7, "Join", 1, 71),
+            event(0, None, 8, "Stop", 71, 71),
+        ];
+        let critical: Vec<(usize, usize)> = critical_path(&events).iter().map(|e| (e.thread, e.pc.1)).collect();
+        assert_eq!(critical, vec![ (0, 0), (1, 1), (4, 3), (1, 4), (1, 5), (0, 7), (0, 8) ]);
+
+        // Nothing happened, so nothing was critical either
+        assert!(critical_path(&[]).is_empty());
+    }
+
+    /// Tests writing a trace and reading it back.
+    #[test]
+    fn test_trace_file() {
+        let dir: tempfile::TempDir = tempfile::tempdir().unwrap();
+        let path: PathBuf = dir.path().join("trace.jsonl");
+
+        // Every recorded event ends up on its own line
+        let tracer: Tracer = Tracer::new(&path).unwrap();
+        assert_eq!(tracer.next_thread(), 1);
+        assert_eq!(tracer.next_thread(), 2);
+        let mut branch: TraceEvent = event(0, None, 0, "Branch", 0, 1);
+        branch.branch = Some(false);
+        tracer.record(&branch);
+        tracer.record(&event(1, Some(0), 1, "Node", 1, 5));
+        drop(tracer);
+        let events: Vec<TraceEvent> = read_trace(&path).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!((events[0].branch, events[0].parent), (Some(false), None));
+        assert_eq!((events[1].thread, events[1].parent, events[1].pc, events[1].next), (1, Some(0), (usize::MAX, 1), Some((usize::MAX, 2))));
+
+        // Empty lines are skipped, but anything else that is not an event is reported with its line number
+        fs::write(&path, format!("{}\n\n{{ \"thread\": 0 }}\n", serde_json::to_string(&branch).unwrap())).unwrap();
+        assert!(matches!(read_trace(&path), Err(Error::EventParseError{ line: 3, .. })));
+        assert!(matches!(read_trace(dir.path().join("missing.jsonl")), Err(Error::FileReadError{ .. })));
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Finds the critical path in the given events, i.e., the chain of edges (across threads) that determined the duration of the run.
+///
+/// This treats the edges like the tasks in `brane_ast::schedule::critical_path()`, except that a join only starts doing its own work once the last of the threads it waits on is done.
+///
+/// # Arguments
+/// - `events`: The events to find the critical path in.
+///
+/// # Returns
+/// The events on the critical path, in the order they were executed.
+fn critical_path(events: &[TraceEvent]) -> Vec<TraceEvent> {
+    // Find when every edge really started, and order them by that
+    let mut timings: Vec<(&TraceEvent, TaskTiming)> = events.iter().map(|e| {
+        let start: i64 = if e.kind == "Join" {
+            events.iter().filter(|c| c.parent == Some(e.thread) && c.start >= e.start && c.end <= e.end).map(|c| c.end).max().unwrap_or(e.start)
+        } else {
+            e.start
+        };
+        (e, TaskTiming { pc: e.pc, name: e.kind.clone(), location: e.thread.to_string(), start, end: e.end, usage: None })
+    }).collect();
+    timings.sort_by_key(|(_, t)| (t.start, t.end));
+
+    // Let the scheduler find the chain that determined how long it took
+    let (events, timings): (Vec<&TraceEvent>, Vec<TaskTiming>) = timings.into_iter().unzip();
+    schedule::critical_path(&timings).critical.into_iter().map(|i| events[i].clone()).collect()
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines a single visit of an edge in an execution trace.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TraceEvent {
+    /// The thread that visited the edge. The main thread is always `0`.
+    pub thread : usize,
+    /// The thread that forked the visiting thread, or None if it is the main thread.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent : Option<usize>,
+    /// The edge that was visited, as a `(body, offset)` pair (where the body is `usize::MAX` for the main body).
+    pub pc     : (usize, usize),
+    /// The kind of edge that was visited (e.g., `Node` or `Branch`).
+    pub kind   : String,
+    /// If the edge is a branch, whether its true-branch was taken.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch : Option<bool>,
+    /// The edge that the thread visits next, or None if it stopped (or failed) after this one.
+    pub next   : Option<(usize, usize)>,
+    /// When the thread started executing the edge, in microseconds since the Unix epoch.
+    pub start  : i64,
+    /// When the thread was done executing the edge, in microseconds since the Unix epoch.
+    pub end    : i64,
+}
+
+
+
+/// Records the edges visited by the threads of a workflow to a JSONL file.
+///
+/// It is shared by all threads of a run, which record to it concurrently. Failing to record an event is not fatal, and is only logged as a warning.
+#[derive(Debug)]
+pub struct Tracer {
+    /// The path of the file we write to (for debugging purposes).
+    path    : PathBuf,
+    /// The file we write to. It is flushed after every event, so the trace survives crashes.
+    file    : Mutex<LineWriter<File>>,
+    /// The identifier of the next thread that is forked.
+    threads : AtomicUsize,
+}
+
+impl Tracer {
+    /// Constructor for the Tracer that creates (or truncates) the file to write to.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the JSONL file to write the trace to.
+    ///
+    /// # Returns
+    /// A new Tracer instance.
+    ///
+    /// # Errors
+    /// This function errors if we failed to create the file.
+    pub fn new(path: impl Into<PathBuf>) -> Result<Self, Error> {
+        let path: PathBuf = path.into();
+        let file: File = match File::create(&path) {
+            Ok(file) => file,
+            Err(err) => { return Err(Error::FileCreateError{ path, err }); },
+        };
+        Ok(Self {
+            path,
+            file    : Mutex::new(LineWriter::new(file)),
+            threads : AtomicUsize::new(1),
+        })
+    }
+
+
+
+    /// Returns a new, unique identifier for a thread that is forked.
+    #[inline]
+    pub fn next_thread(&self) -> usize { self.threads.fetch_add(1, Ordering::Relaxed) }
+
+    /// Records the given event as a new line in the trace.
+    ///
+    /// # Arguments
+    /// - `event`: The TraceEvent to record.
+    pub fn record(&self, event: &TraceEvent) {
+        let sevent: String = match serde_json::to_string(event) {
+            Ok(sevent) => sevent,
+            Err(err)   => { warn!("Failed to serialize trace event: {} (skipping it)", err); return; },
+        };
+        let mut file = self.file.lock().unwrap();
+        if let Err(err) = writeln!(file, "{}", sevent) {
+            warn!("Failed to write trace event to '{}': {} (skipping it)", self.path.display(), err);
+        }
+    }
+}
+
+
+
+/// Defines what we learned from an execution trace.
+#[derive(Clone, Debug)]
+pub struct TraceAnalysis {
+    /// The time between the start of the first edge and the end of the last one, in microseconds.
+    pub duration : i64,
+    /// The number of threads that visited edges.
+    pub threads  : usize,
+    /// The number of times every edge was visited.
+    pub visits   : BTreeMap<(usize, usize), usize>,
+    /// The total number of edges in the workflow, if it was given.
+    pub edges    : Option<usize>,
+    /// For every branch, whether its true- and false-branch were taken, respectively. Also includes branches that were never visited if the workflow was given.
+    pub branches : BTreeMap<(usize, usize), (bool, bool)>,
+    /// The edges on the critical path of the run, in the order they were executed.
+    pub critical : Vec<TraceEvent>,
+}
+
+
+
+/// Reads the events of an execution trace from the given JSONL file.
+///
+/// # Arguments
+/// - `path`: The path of the file to read.
+///
+/// # Returns
+/// The TraceEvents in the file, in the order they were recorded.
+///
+/// # Errors
+/// This function errors if we failed to read the file or if one of its lines is not a valid event.
+pub fn read_trace(path: impl AsRef<Path>) -> Result<Vec<TraceEvent>, Error> {
+    let path: &Path = path.as_ref();
+    let raw: String = match fs::read_to_string(path) {
+        Ok(raw)  => raw,
+        Err(err) => { return Err(Error::FileReadError{ path: path.into(), err }); },
+    };
+
+    // Parse every (non-empty) line as an event
+    let mut events: Vec<TraceEvent> = vec![];
+    for (i, line) in raw.lines().enumerate() {
+        if line.trim().is_empty() { continue; }
+        match serde_json::from_str(line) {
+            Ok(event) => { events.push(event); },
+            Err(err)  => { return Err(Error::EventParseError{ path: path.into(), line: i + 1, err }); },
+        }
+    }
+    Ok(events)
+}
+
+/// Analyzes the given execution trace for its branch coverage and critical path.
+///
+/// # Arguments
+/// - `events`: The TraceEvents of a single run.
+/// - `workflow`: The Workflow that was run, if known. If given, the coverage is computed relative to all of its edges instead of only the visited ones.
+///
+/// # Returns
+/// A TraceAnalysis with the results.
+pub fn analyze(events: &[TraceEvent], workflow: Option<&Workflow>) -> TraceAnalysis {
+    // Count the visits and branch directions
+    let mut visits   : BTreeMap<(usize, usize), usize>        = BTreeMap::new();
+    let mut branches : BTreeMap<(usize, usize), (bool, bool)> = BTreeMap::new();
+    for e in events {
+        *visits.entry(e.pc).or_default() += 1;
+        if let Some(taken) = e.branch {
+            let entry: &mut (bool, bool) = branches.entry(e.pc).or_default();
+            if taken { entry.0 = true; } else { entry.1 = true; }
+        }
+    }
+
+    // Add the branches we never visited if we know the workflow
+    let edges: Option<usize> = workflow.map(|workflow| {
+        let bodies = std::iter::once((usize::MAX, &*workflow.graph)).chain(workflow.funcs.iter().map(|(i, edges)| (*i, edges)));
+        let mut n: usize = 0;
+        for (body, edges) in bodies {
+            for (i, edge) in edges.iter().enumerate() {
+                if let Edge::Branch{ .. } = edge { branches.entry((body, i)).or_default(); }
+            }
+            n += edges.len();
+        }
+        n
+    });
+
+    // Done
+    TraceAnalysis {
+        duration : events.iter().map(|e| e.end).max().unwrap_or(0) - events.iter().map(|e| e.start).min().unwrap_or(0),
+        threads  : events.iter().map(|e| e.thread).collect::<HashSet<usize>>().len(),
+        visits,
+        edges,
+        branches,
+        critical : critical_path(events),
+    }
+}
@@ -123,6 +123,10 @@ mod tests {
             "\"three\":[[\"TestNested1\",{\"one\":1}],[\"TestNested2\",{\"two\":2}],[\"TestNested3\",{\"three\":3}]]".into(),
         ]);
 
+        // Maps
+        assert_eq!(serde_json::to_string(&FullValue::Map(HashMap::from([]))).ok(), Some("{}".into()));
+        assert_eq!(serde_json::to_string(&FullValue::Map(HashMap::from([ ("one".into(), FullValue::Integer(42)) ]))).ok(), Some("{\"one\":42}".into()));
+
         // Data
         assert_eq!(serde_json::to_string(&FullValue::Data("testset".into())).ok(), Some("\"Data<testset>\"".into()));
 
@@ -187,6 +191,10 @@ mod tests {
             ])),
         ])));
 
+        // Maps
+        assert_eq!(serde_json::from_str::<FullValue>("{}").unwrap_or_else(|err| panic!("{}", err)), FullValue::Map(HashMap::from([])));
+        assert_eq!(serde_json::from_str::<FullValue>("{\"one\":42,\"two\":[\"Test\",{}]}").unwrap_or_else(|err| panic!("{}", err)), FullValue::Map(HashMap::from([ ("one".into(), FullValue::Integer(42)), ("two".into(), FullValue::Instance("Test".into(), HashMap::from([]))) ])));
+
         // Data
         assert_eq!(serde_json::from_str::<FullValue>("\"Data<testset>\"").unwrap_or_else(|err| panic!("{}", err)), FullValue::Data("testset".into()));
 
@@ -270,6 +278,12 @@ impl<'a, 'b> Display for ValueDisplay<'a, 'b> {
                 values.iter().map(|(n, v)| format!("{} := {}", n, v.display(self.table))).collect::<Vec<std::string::String>>().join(", "),
                 if values.is_empty() { "" } else { " " },
             ),
+            Map{ values }            => {
+                // Sort the keys to have a deterministic output
+                let mut keys: Vec<&std::string::String> = values.keys().collect();
+                keys.sort();
+                write!(f, "{{{}}}", keys.into_iter().map(|k| format!("{}: {}", k, values[k].display(self.table))).collect::<Vec<std::string::String>>().join(", "))
+            },
             Method{ cdef, fdef, .. } => write!(f, "{}::{}({}) -> {}",
                 self.table.class(*cdef).name,
                 self.table.func(*fdef).name,
@@ -437,7 +451,7 @@ impl From<&mut ResultId> for String {
 
 /***** LIBRARY *****/
 /// Defines a single Value while executing a Workflow. That's basically an instantiated DataType.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum Value {
     /// It's a boolean value (true/false)
     Boolean{ value: bool },
@@ -454,6 +468,8 @@ pub enum Value {
     Function{ def: usize },
     /// It's an instance object, that maps field names to values.
     Instance{ values: HashMap<String, Self>, def: usize },
+    /// It's a map object, that maps (string) keys to values of the same type.
+    Map{ values: HashMap<String, Self> },
     /// It's a method object, which merges a function and an instance together into one.
     Method{ values: HashMap<String, Self>, cdef: usize, fdef: usize },
     /// It's a data object that contains the identifier of the dataset referenced.
@@ -520,6 +536,19 @@ impl Value {
         }
     }
 
+    /// Returns the top value on the stack as if it was a map (of any type).
+    /// 
+    /// # Returns
+    /// The map's key/value pairs if it actually was a map, or else `None`.
+    #[inline]
+    pub fn try_as_map(self) -> Option<HashMap<String, Self>> {
+        use Value::*;
+        match self {
+            Map{ values } => Some(values),
+            _             => None,
+        }
+    }
+
     /// Returns the top value on the stack as if it was a callable (function) of some sort (of any signature).
     /// 
     /// # Returns
@@ -628,6 +657,19 @@ impl Value {
             (Instance{ values, def }, DataType::Class{ name }) => Ok(if &table.class(def).name == name { Self::Instance{ values, def } } else { return Err(Error::CastError { got: DataType::Class{ name: table.class(def).name.clone() }, target: target.clone() }) }),
             (Instance{ values, def }, DataType::String)        => Ok(Self::String{ value: format!("{}", Self::Instance{ values, def }.display(table)) }),
 
+            (Map{ values }, DataType::Any)               => Ok(Self::Map{ values }),
+            (Map{ values }, DataType::String)            => Ok(Self::String{ value: format!("{}", Self::Map{ values }.display(table)) }),
+            (Map{ values }, DataType::Map{ elem_type })  => {
+                // Cast all of the internal values
+                let mut casted_values: HashMap<std::string::String, Self> = HashMap::with_capacity(values.len());
+                for (k, v) in values {
+                    casted_values.insert(k, v.cast(elem_type, table)?);
+                }
+
+                // Return
+                Ok(Self::Map{ values: casted_values })
+            },
+
             (Method{ values, cdef, fdef }, DataType::Any)           => Ok(Self::Method{ values, cdef, fdef }),
             (Method{ values, cdef, fdef }, DataType::Class{ name }) => Ok(if &table.class(cdef).name == name { Self::Method{ values, cdef, fdef } } else { return Err(Error::CastError { got: DataType::Class{ name: table.class(cdef).name.clone() }, target: target.clone() }) }),
             (Method{ values, cdef, fdef }, DataType::String)        => Ok(Self::String{ value: format!("{}", Self::Method{ values, cdef, fdef }.display(table)) }),
@@ -670,6 +712,7 @@ impl Value {
             Array { values }         => DataType::Array{ elem_type: Box::new(values.iter().next().map(|v| v.data_type(table)).unwrap_or(DataType::Any)) },
             Function { def }         => DataType::Function { args: table.func(*def).args.clone(), ret: Box::new(table.func(*def).ret.clone()) },
             Instance{ def, .. }      => if table.class(*def).name == BuiltinClasses::Data.name() { DataType::Data } else { DataType::Class{ name: table.class(*def).name.clone() } },
+            Map{ values }            => DataType::Map{ elem_type: Box::new(values.values().next().map(|v| v.data_type(table)).unwrap_or(DataType::Any)) },
             Method{ fdef, .. }       => DataType::Function{ args: table.func(*fdef).args.clone(), ret: Box::new(table.func(*fdef).ret.clone()) },
             Data{ .. }               => DataType::Data,
             IntermediateResult{ .. } => DataType::IntermediateResult,
@@ -716,6 +759,7 @@ impl Value {
             Array{ values }            => FullValue::Array(values.iter().map(|v| v.to_full(table)).collect()),
             Function{ .. }             => { panic!("Value::Function has no business being converted into a FullValue"); },
             Instance{ values, def }    => FullValue::Instance(table.class(*def).name.clone(), values.iter().map(|(n, v)| (n.clone(), v.to_full(table))).collect()),
+            Map{ values }              => FullValue::Map(values.iter().map(|(k, v)| (k.clone(), v.to_full(table))).collect()),
             Method{ .. }               => { panic!("Value::Method has no business being converted into a FullValue"); },
             Data{ name }               => FullValue::Data(DataId(name.clone())),
             IntermediateResult{ name } => FullValue::IntermediateResult(ResultId(name.clone())),
@@ -746,6 +790,7 @@ impl Value {
             Array{ values }            => FullValue::Array(values.into_iter().map(|v| v.into_full(table)).collect()),
             Function{ .. }             => { panic!("Value::Function has no business being converted into a FullValue"); },
             Instance{ values, def }    => FullValue::Instance(table.class(def).name.clone(), values.into_iter().map(|(n, v)| (n, v.into_full(table))).collect()),
+            Map{ values }              => FullValue::Map(values.into_iter().map(|(k, v)| (k, v.into_full(table))).collect()),
             Method{ .. }               => { panic!("Value::Method has no business being converted into a FullValue"); },
             Data{ name }               => FullValue::Data(DataId(name)),
             IntermediateResult{ name } => FullValue::IntermediateResult(ResultId(name)),
@@ -767,6 +812,8 @@ pub enum FullValue {
     Array(Vec<Self>),
     /// It's an instance object, that maps field names to values.
     Instance(String, HashMap<String, Self>),
+    /// It's a map object, that maps (string) keys to values of the same type.
+    Map(HashMap<String, Self>),
     /// It's a data object that contains the identifier of the dataset referenced.
     Data(DataId),
     /// It's an intermediate result object that contains the identifier of the dataset or result referenced.
@@ -864,6 +911,7 @@ impl FullValue {
 
             Array(values)         => DataType::Array{ elem_type: Box::new(values.iter().next().map(|v| v.data_type()).unwrap_or(DataType::Any)) },
             Instance(name, _)     => if name == BuiltinClasses::Data.name() { DataType::Data } else { DataType::Class{ name: name.clone() } },
+            Map(values)           => DataType::Map{ elem_type: Box::new(values.values().next().map(|v| v.data_type()).unwrap_or(DataType::Any)) },
             Data(_)               => DataType::Data,
             IntermediateResult(_) => DataType::IntermediateResult,
 
@@ -892,6 +940,7 @@ impl FullValue {
 
             Array(values)            => Value::Array{ values: values.iter().map(|v| v.to_value(table)).collect() },
             Instance(name, values)   => Value::Instance{ values: values.iter().map(|(n, v)| (n.clone(), v.to_value(table))).collect(), def: table.classes().find_map(|(i, c)| if &c.name == name { Some(i) } else { None }).unwrap() },
+            Map(values)              => Value::Map{ values: values.iter().map(|(k, v)| (k.clone(), v.to_value(table))).collect() },
             Data(name)               => Value::Data{ name: name.0.clone() },
             IntermediateResult(name) => Value::IntermediateResult{ name: name.0.clone() },
 
@@ -920,6 +969,7 @@ impl FullValue {
 
             Array(values)            => Value::Array{ values: values.into_iter().map(|v| v.into_value(table)).collect() },
             Instance(name, values)   => Value::Instance{ values: values.into_iter().map(|(n, v)| (n, v.into_value(table))).collect(), def: table.classes().find_map(|(i, c)| if c.name == name { Some(i) } else { None }).unwrap() },
+            Map(values)              => Value::Map{ values: values.into_iter().map(|(k, v)| (k, v.into_value(table))).collect() },
             Data(name)               => Value::Data{ name: name.0 },
             IntermediateResult(name) => Value::IntermediateResult{ name: name.0 },
 
@@ -948,6 +998,12 @@ impl Display for FullValue {
                 values.iter().map(|(n, v)| format!("{} := {}", n, v)).collect::<Vec<std::string::String>>().join(", "),
                 if values.is_empty() { "" } else { " " },
             ),
+            Map(values)            => {
+                // Sort the keys to have a deterministic output
+                let mut keys: Vec<&std::string::String> = values.keys().collect();
+                keys.sort();
+                write!(f, "{{{}}}", keys.into_iter().map(|k| format!("{}: {}", k, values[k])).collect::<Vec<std::string::String>>().join(", "))
+            },
             Data(name)               => write!(f, "{}", name),
             IntermediateResult(name) => write!(f, "{}", name),
 
@@ -19,10 +19,13 @@ use async_trait::async_trait;
 
 use brane_ast::{SymTable, Workflow};
 
+use crate::cancel::CancelToken;
+use crate::fuel::Fuel;
 use crate::errors::VmError;
-use crate::spec::{CustomGlobalState, CustomLocalState, RunState, VmPlugin};
+use crate::spec::{Checkpoint, CustomGlobalState, CustomLocalState, RunState, VmPlugin};
 use crate::value::FullValue;
 use crate::thread::Thread;
+use crate::trace::Tracer;
 
 
 /***** TESTS *****/
@@ -168,6 +171,44 @@ pub trait Vm {
 
 
 
+    /// A function that returns the Tracer with which to record the execution of workflows, if any.
+    /// 
+    /// By default, workflows are not traced.
+    /// 
+    /// # Returns
+    /// The Tracer to record to, or None if we're not tracing.
+    #[inline]
+    fn tracer(_this: &Arc<RwLock<Self>>) -> Option<Arc<Tracer>> { None }
+
+    /// A function that returns the CancelToken with which the workflows run by this VM may be cancelled, if any.
+    /// 
+    /// By default, workflows cannot be cancelled.
+    /// 
+    /// # Returns
+    /// The CancelToken to listen to, or None if the workflows cannot be cancelled.
+    #[inline]
+    fn cancel_token(_this: &Arc<RwLock<Self>>) -> Option<CancelToken> { None }
+
+    /// A function that returns the maximum number of branches of a single parallel statement that are run at the same time, if any.
+    /// 
+    /// By default, all branches are run at once.
+    /// 
+    /// # Returns
+    /// The maximum number of concurrent branches, or None if fan-outs are not bounded.
+    #[inline]
+    fn max_parallel(_this: &Arc<RwLock<Self>>) -> Option<usize> { None }
+
+    /// A function that returns the maximum number of steps (i.e., edges and instructions) that a single run of a workflow may take, if any.
+    /// 
+    /// By default, workflows may take as many steps as they like.
+    /// 
+    /// # Returns
+    /// The maximum number of steps per run, or None if runs are not limited.
+    #[inline]
+    fn max_steps(_this: &Arc<RwLock<Self>>) -> Option<u64> { None }
+
+
+
     // Global
     /// Initializes a new global state based on the given custom part.
     /// 
@@ -210,7 +251,11 @@ pub trait Vm {
         state.fstack.update_table(snippet.table.clone());
 
         // Create a new thread with (a copy of) the internal state, if any.
-        let main: Thread<Self::GlobalState, Self::LocalState> = Thread::from_state(&snippet, state);
+        let mut main: Thread<Self::GlobalState, Self::LocalState> = Thread::from_state(&snippet, state);
+        if let Some(tracer) = Self::tracer(&this) { main = main.with_tracer(tracer); }
+        if let Some(cancel) = Self::cancel_token(&this) { main = main.with_cancel(cancel); }
+        if let Some(max) = Self::max_parallel(&this) { main = main.with_max_parallel(max); }
+        if let Some(max) = Self::max_steps(&this) { main = main.with_fuel(Fuel::new(max)); }
 
         // Run the workflow
         match main.run_snippet::<P>().await {
@@ -227,4 +272,42 @@ pub trait Vm {
             Err(err) => Err(err),
         }
     }
+
+    /// Resumes the given workflow from a checkpoint that was made while running it earlier, e.g., before a restart.
+    /// 
+    /// # Arguments
+    /// - `snippet`: The workflow (or snippet of it) that was running when the checkpoint was made. Like `Vm::run()`, the internal state will be updated afterwards.
+    /// - `checkpoint`: The Checkpoint (as given to `VmPlugin::checkpoint()`) to resume from.
+    /// 
+    /// # Returns
+    /// The result if the Workflow returned any.
+    async fn resume<P: VmPlugin<GlobalState = Self::GlobalState, LocalState = Self::LocalState>>(this: Arc<RwLock<Self>>, snippet: Workflow, checkpoint: Checkpoint) -> Result<FullValue, VmError>
+    where
+        Self: Sync,
+    {
+        // Fetch the previous state for its custom part; the rest is restored from the checkpoint
+        let state: RunState<Self::GlobalState> = Self::load_state(&this)?;
+
+        // Create a new thread that continues where the checkpointed one was
+        let mut main: Thread<Self::GlobalState, Self::LocalState> = Thread::from_checkpoint(&snippet, checkpoint, state.global);
+        if let Some(tracer) = Self::tracer(&this) { main = main.with_tracer(tracer); }
+        if let Some(cancel) = Self::cancel_token(&this) { main = main.with_cancel(cancel); }
+        if let Some(max) = Self::max_parallel(&this) { main = main.with_max_parallel(max); }
+        if let Some(max) = Self::max_steps(&this) { main = main.with_fuel(Fuel::new(max)); }
+
+        // Run the rest of the workflow
+        match main.run_snippet::<P>().await {
+            Ok((res, state)) => {
+                // Convert the value into a full value (if any)
+                let res: FullValue = res.into_full(state.fstack.table());
+
+                // Store the state
+                Self::store_state(&this, state)?;
+
+                // Done, return
+                Ok(res)
+            },
+            Err(err) => Err(err),
+        }
+    }
 }
@@ -0,0 +1,186 @@
+//  FIREWALL.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 11:47:05
+//  Last edited:
+//    16 Oct 2026, 11:47:05
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements the application-level firewall of the job service, which
+//!   only lets through requests from the clients allowed in the
+//!   `node.yml` file.
+// 
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use log::warn;
+use tonic::{Request, Status};
+use tonic::service::Interceptor;
+
+use brane_cfg::certs::extract_client_name;
+use brane_cfg::node::JobFirewallConfig;
+use brane_shr::audit::{self, AuditKind, AuditLog, AuditRecord, Decision};
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr as _;
+
+    use brane_cfg::spec::IpRange;
+
+    use super::*;
+
+
+    /// Creates a firewall config from the given names and ranges.
+    fn config(names: &[&str], ranges: &[&str]) -> JobFirewallConfig {
+        JobFirewallConfig {
+            names  : names.iter().map(|name| name.to_string()).collect(),
+            ranges : ranges.iter().map(|range| IpRange::from_str(range).unwrap()).collect(),
+        }
+    }
+
+    /// Shorthand for parsing a socket address.
+    fn addr(raw: &str) -> Option<SocketAddr> { Some(SocketAddr::from_str(raw).unwrap()) }
+
+
+    /// Tests refusing clients by their address.
+    #[test]
+    fn test_refusal_ranges() {
+        let config: JobFirewallConfig = config(&[], &[ "10.0.0.0/8", "192.168.1.7", "fd00::/16" ]);
+        assert_eq!(refusal(&config, addr("10.1.2.3:50051"), None), None);
+        assert_eq!(refusal(&config, addr("192.168.1.7:1234"), None), None);
+        assert_eq!(refusal(&config, addr("[fd00:1::1]:1234"), None), None);
+        // IPv4 clients connecting to a dual-stack socket show up as IPv4-mapped IPv6 addresses
+        assert_eq!(refusal(&config, addr("[::ffff:10.9.9.9]:1234"), None), None);
+
+        assert_eq!(refusal(&config, addr("11.0.0.1:50051"), None).unwrap(), "address 11.0.0.1:50051 is not in any of the allowed ranges");
+        assert!(refusal(&config, addr("192.168.1.8:1234"), None).is_some());
+        assert!(refusal(&config, addr("[fe80::1]:1234"), None).is_some());
+        // Clients of which we do not know the address cannot be in any range
+        assert_eq!(refusal(&config, None, None).unwrap(), "address <unknown> is not in any of the allowed ranges");
+
+        // Without ranges, every address is fine
+        assert_eq!(refusal(&JobFirewallConfig::default(), addr("11.0.0.1:50051"), None), None);
+        assert_eq!(refusal(&JobFirewallConfig::default(), None, None), None);
+    }
+
+    /// Tests refusing clients by the name in their certificate.
+    #[test]
+    fn test_refusal_names() {
+        let config: JobFirewallConfig = config(&[ "central" ], &[]);
+        assert_eq!(refusal(&config, addr("10.1.2.3:50051"), Some("central")), None);
+        assert_eq!(refusal(&config, addr("10.1.2.3:50051"), Some("intruder")).unwrap(), "client certificate name 'intruder' is not allowed");
+        assert_eq!(refusal(&config, addr("10.1.2.3:50051"), None).unwrap(), "client did not provide a valid certificate");
+
+        // Both have to match if both are given, and the address is checked first
+        let config: JobFirewallConfig = config(&[ "central" ], &[ "10.0.0.0/8" ]);
+        assert_eq!(refusal(&config, addr("10.1.2.3:50051"), Some("central")), None);
+        assert!(refusal(&config, addr("11.1.2.3:50051"), Some("central")).unwrap().starts_with("address"));
+        assert!(refusal(&config, addr("11.1.2.3:50051"), Some("intruder")).unwrap().starts_with("address"));
+        assert!(refusal(&config, addr("10.1.2.3:50051"), Some("intruder")).unwrap().starts_with("client certificate"));
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Decides whether the firewall refuses a client.
+/// 
+/// # Arguments
+/// - `config`: The allowlist to enforce.
+/// - `remote`: The address of the client, if known.
+/// - `name`: The name in the client's certificate, if it provided a valid one.
+/// 
+/// # Returns
+/// The reason for refusing the client, or `None` if it may pass.
+fn refusal(config: &JobFirewallConfig, remote: Option<SocketAddr>, name: Option<&str>) -> Option<String> {
+    if !config.ranges.is_empty() && !remote.map(|addr| config.ranges.iter().any(|range| range.contains(&addr.ip()))).unwrap_or(false) {
+        Some(format!("address {} is not in any of the allowed ranges", remote.map(|addr| addr.to_string()).unwrap_or_else(|| "<unknown>".into())))
+    } else if config.requires_tls() {
+        match name {
+            Some(name) if config.names.iter().any(|allowed| allowed == name) => None,
+            Some(name)                                                       => Some(format!("client certificate name '{}' is not allowed", name)),
+            None                                                             => Some("client did not provide a valid certificate".into()),
+        }
+    } else {
+        None
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Enforces the [`JobFirewallConfig`] of this node on incoming requests, recording any that it refuses in the audit log.
+#[derive(Clone)]
+pub struct Firewall {
+    /// The allowlist to enforce. If omitted, every request is let through.
+    config : Option<Arc<JobFirewallConfig>>,
+    /// The audit log to record refused requests in, if any.
+    audit  : Option<Arc<AuditLog>>,
+}
+
+impl Firewall {
+    /// Constructor for the Firewall.
+    /// 
+    /// # Arguments
+    /// - `config`: The allowlist to enforce, if any.
+    /// - `audit`: The audit log to record refused requests in, if any.
+    /// 
+    /// # Returns
+    /// A new Firewall instance.
+    #[inline]
+    pub fn new(config: Option<JobFirewallConfig>, audit: Option<Arc<AuditLog>>) -> Self {
+        Self {
+            config : config.map(Arc::new),
+            audit,
+        }
+    }
+
+
+
+    /// Checks whether the given request may pass.
+    /// 
+    /// # Arguments
+    /// - `request`: The incoming request, of which we inspect the remote address and any client certificate.
+    /// 
+    /// # Errors
+    /// This function errors with a `PERMISSION_DENIED` status if the client is not allowed by the configured allowlist.
+    pub fn check<T>(&self, request: &Request<T>) -> Result<(), Status> {
+        let config: &JobFirewallConfig = match &self.config {
+            Some(config) => config,
+            None         => { return Ok(()); },
+        };
+
+        // Find out who's knocking
+        let remote: Option<SocketAddr> = request.remote_addr();
+        let client: String = remote.map(|addr| addr.to_string()).unwrap_or_else(|| "<unknown>".into());
+        let name: Option<String> = request.peer_certs().and_then(|certs| certs.first().and_then(|cert| extract_client_name(cert.get_ref())));
+
+        // Refuse (and record) the request if it doesn't match the allowlist
+        match refusal(config, remote, name.as_deref()) {
+            Some(reason) => {
+                warn!("Refusing request from {}: {}", client, reason);
+                audit::write(self.audit.as_deref(), AuditRecord::new("brane-job", AuditKind::Connection, name, client, Decision::Denied).with_details(reason.clone()));
+                Err(Status::permission_denied(reason))
+            },
+            None => Ok(()),
+        }
+    }
+}
+
+impl Interceptor for Firewall {
+    #[inline]
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        self.check(&request)?;
+        Ok(request)
+    }
+}
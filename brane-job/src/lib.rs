@@ -15,4 +15,6 @@
 
 // Declare modules
 pub mod errors;
+pub mod firewall;
+pub mod retention;
 pub mod worker;
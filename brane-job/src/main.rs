@@ -12,19 +12,25 @@
 //!   Entrypoint to the `brane-job` service.
 // 
 
+use std::fs;
 use std::path::PathBuf;
 use std::sync::Arc;
 
 use clap::Parser;
 use dotenvy::dotenv;
 use log::LevelFilter;
-use log::{debug, error, info};
-use tonic::transport::Server;
+use log::{debug, error, info, warn};
+use tonic::transport::{Certificate, Identity, Server, ServerTlsConfig};
 
 use brane_cfg::node::NodeConfig;
 use brane_prx::client::ProxyClient;
+use brane_shr::audit::AuditLog;
+use brane_shr::otel;
+use brane_shr::telemetry;
 use brane_tsk::grpc::JobServiceServer;
 
+use brane_job::firewall::Firewall;
+use brane_job::retention;
 use brane_job::worker::WorkerServer;
 
 
@@ -75,27 +81,63 @@ async fn main() {
         },
     };
     if !node_config.node.is_worker() { error!("Given NodeConfig file '{}' does not have properties for a worker node.", opts.node_config_path.display()); std::process::exit(1); }
+    info!("Enabled features: {}", node_config.features);
 
     // Initialize the Xenon thingy
     // debug!("Initializing Xenon...");
     // let xenon_schedulers = Arc::new(DashMap::<String, Arc<RwLock<Scheduler>>>::new());
     // let xenon_endpoint = utilities::ensure_http_schema(&opts.xenon, !opts.debug)?;
 
+    // Start reporting usage statistics, if the node opted-in
+    telemetry::spawn_reporter(node_config.telemetry.as_ref(), "worker", env!("CARGO_PKG_VERSION"));
+    // Set up tracing, exporting spans if the node is configured to
+    if let Err(err) = otel::init("brane-job", node_config.tracing.as_ref()) { warn!("{} (spans will not be exported)", err); }
+    // Release intermediate results once they are older than the retention window, if any
+    retention::spawn_expirer(node_config.node.worker());
+
     // Start the JobHandler
+    let audit: Option<Arc<AuditLog>> = node_config.node.worker().paths.audit.as_ref().map(|dir| Arc::new(AuditLog::new(dir, "brane-job")));
     let server = WorkerServer::new(
         opts.node_config_path,
         opts.keep_containers,
         Arc::new(ProxyClient::new(node_config.services.prx)),
+        audit.clone(),
     );
+    let firewall: Firewall = Firewall::new(node_config.node.worker().firewall.clone(), audit);
+
+    // Require client certificates if the firewall allows clients by name
+    let mut builder: Server = Server::builder();
+    if node_config.node.worker().firewall.as_ref().map(|firewall| firewall.requires_tls()).unwrap_or(false) {
+        debug!("Loading cryptography...");
+        let certs: PathBuf = node_config.paths.certs.clone();
+        let mut pems: Vec<Vec<u8>> = Vec::with_capacity(3);
+        for file in [ "server.pem", "server-key.pem", "ca.pem" ] {
+            match fs::read(certs.join(file)) {
+                Ok(pem)  => { pems.push(pem); },
+                Err(err) => { error!("Failed to read '{}': {}", certs.join(file).display(), err); std::process::exit(1); },
+            }
+        }
+        let tls: ServerTlsConfig = ServerTlsConfig::new()
+            .identity(Identity::from_pem(&pems[0], &pems[1]))
+            .client_ca_root(Certificate::from_pem(&pems[2]));
+        builder = match builder.tls_config(tls) {
+            Ok(builder) => builder,
+            Err(err)    => { error!("Failed to configure TLS for gRPC server: {}", err); std::process::exit(1); },
+        };
+    }
 
     // Start gRPC server with callback service.
     debug!("gRPC server ready to serve on '{}'", node_config.node.worker().ports.job);
-    if let Err(err) = Server::builder()
-        .add_service(JobServiceServer::new(server))
-        .serve(node_config.node.worker().ports.job)
+    if let Err(err) = builder
+        .add_service(JobServiceServer::with_interceptor(server, firewall))
+        .serve_with_shutdown(node_config.node.worker().ports.job, otel::shutdown_signal())
         .await
     {
         error!("Failed to start gRPC server: {}", err);
+        otel::shutdown().await;
         std::process::exit(1);
     }
+
+    // Export the spans of the last tasks before we go
+    otel::shutdown().await;
 }
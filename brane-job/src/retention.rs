@@ -0,0 +1,86 @@
+//  RETENTION.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 12:20:59
+//  Last edited:
+//    16 Oct 2026, 12:20:59
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements the background task that releases intermediate results
+//!   once they are older than the retention window of the worker.
+// 
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use log::{debug, info, warn};
+use tokio::fs as tfs;
+use tokio::task::JoinHandle;
+
+use brane_cfg::node::WorkerConfig;
+use brane_shr::cas::{ContentStore, TagKind};
+
+
+/***** CONSTANTS *****/
+/// The most time we let pass between two checks for expired results.
+const MAX_CHECK_INTERVAL: Duration = Duration::from_secs(3600);
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Releases the intermediate results that are older than the given age.
+/// 
+/// The objects themselves stay in the store until it is collected (e.g., because the disk runs full or by `branectl gc`).
+/// 
+/// # Arguments
+/// - `worker`: The WorkerConfig that tells us where to find the store and the results directory.
+/// - `max_age`: The age after which a result is released.
+/// 
+/// # Errors
+/// This function errors if we failed to open the store, or to release a result in it. Failing to remove the link to a result is only logged.
+async fn expire_results(worker: &WorkerConfig, max_age: Duration) -> Result<(), brane_shr::cas::Error> {
+    let store: ContentStore = ContentStore::new(&worker.paths.store).await?;
+    for name in store.stale_tags(TagKind::IntermediateResult, max_age).await? {
+        debug!("Releasing expired intermediate result '{}'...", name);
+        store.untag(TagKind::IntermediateResult, &name).await?;
+
+        // Also remove the link that exposes it to the registry
+        let link: PathBuf = worker.paths.results.join(&name);
+        if link.is_symlink() {
+            if let Err(err) = tfs::remove_file(&link).await { warn!("Failed to remove link '{}' to expired intermediate result: {}", link.display(), err); }
+        }
+    }
+    Ok(())
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Launches a background task that periodically releases intermediate results that are older than the retention window of the worker.
+/// 
+/// # Arguments
+/// - `worker`: The WorkerConfig of this node. Nothing is launched if it does not define a `result_retention`.
+/// 
+/// # Returns
+/// The handle of the launched task, or `None` if results are kept until they are released manually.
+pub fn spawn_expirer(worker: &WorkerConfig) -> Option<JoinHandle<()>> {
+    let max_age: Duration = Duration::from_secs(worker.result_retention?);
+    let worker: WorkerConfig = worker.clone();
+
+    info!("Keeping intermediate results for {} seconds", max_age.as_secs());
+    Some(tokio::spawn(async move {
+        // Check often enough that results do not outlive their window by much
+        let mut interval = tokio::time::interval((max_age / 10).clamp(Duration::from_secs(1), MAX_CHECK_INTERVAL));
+        loop {
+            interval.tick().await;
+            if let Err(err) = expire_results(&worker, max_age).await { warn!("Failed to release expired intermediate results: {}", err); }
+        }
+    }))
+}
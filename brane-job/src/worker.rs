@@ -14,49 +14,105 @@
 //!   execution to publicizing/committing.
 // 
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 
 use bollard::{API_DEFAULT_VERSION, ClientVersion};
 use chrono::Utc;
 use futures_util::StreamExt;
 use hyper::body::Bytes;
 use log::{debug, error, info, warn};
+use serde::Deserialize;
 use serde_json_any_key::json_to_map;
 use tokio::fs as tfs;
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncWriteExt, DuplexStream};
 use tokio::sync::mpsc::{self, Sender};
 use tokio_stream::wrappers::ReceiverStream;
 use tonic::{Response, Request, Status};
+use tracing::{info_span, Instrument as _, Span};
 
 use brane_ast::Workflow;
 use brane_ast::locations::Location;
 use brane_ast::ast::DataName;
-use brane_cfg::backend::{BackendFile, Credentials};
-use brane_cfg::node::NodeConfig;
+use brane_cfg::backend::{AwsCredentials, BackendFile, BurstConfig, Credentials, SshCredentials};
+use brane_cfg::infra::split_peer_location;
+use brane_cfg::node::{ImageRegistryConfig, NodeConfig, WorkerConfig};
+use brane_cfg::pins::{fingerprint, PinStore, SignedCaLog};
 use brane_cfg::policies::{ContainerPolicy, PolicyFile};
+use brane_cfg::secrets::{Secret, SecretsBackend, SecretsFile};
 use brane_exe::FullValue;
 use brane_prx::spec::NewPathRequestTlsOptions;
 use brane_prx::client::ProxyClient;
+use brane_shr::audit::{self, AuditKind, AuditLog, AuditRecord, Decision};
+use brane_shr::cas::{ContentStore, TagKind};
 use brane_shr::debug::BlockFormatter;
-use brane_shr::fs::{copy_dir_recursively_async, unarchive_async};
+use brane_shr::disk::{self, Pressure};
+use brane_shr::fs::{copy_dir_recursively_async, unarchive_async, unarchive_from_async};
+use brane_shr::otel;
+use brane_shr::telemetry;
+use brane_tsk::aws::{self, AwsClientCache, AwsClients, JobState, Staging};
+use brane_tsk::ssh;
 use brane_tsk::errors::{AuthorizeError, CommitError, ExecuteError, PreprocessError};
 use brane_tsk::spec::JobStatus;
 use brane_tsk::grpc::{CommitReply, CommitRequest, DataKind, JobService, PreprocessKind, PreprocessReply, PreprocessRequest, TaskReply, TaskRequest, TaskStatus};
-use brane_tsk::tools::decode_base64;
-use brane_tsk::docker::{self, ExecuteInfo, ImageSource, Network};
+use brane_tsk::tools::{conflict_status, decode_base64, extract_trace};
+use brane_tsk::docker::{self, ExecuteInfo, ImageSource, Network, StagedInputs};
+use specifications::arch::Arch;
 use specifications::container::{Image, VolumeBind};
-use specifications::data::{AccessKind, AssetInfo};
-use specifications::package::{Capability, PackageIndex, PackageInfo, PackageKind};
+use specifications::data::{merge_residency, AccessKind, AssetInfo, TRANSFER_DIRECT, TRANSFER_HEADER};
+use specifications::package::{Capability, PackageIndex, PackageInfo, PackageKind, OAS_CACHE_DIR_ENV, OAS_STATE_DIR_ENV};
+use specifications::status::{ResourceUsage, TaskOutput, TaskProgress};
 use specifications::version::Version;
 
 
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Tests checking the revision that a commit expects to replace.
+    #[test]
+    fn test_next_revision() {
+        // Commits that expect the current revision bump it, including new datasets (at revision 0)
+        assert_eq!(next_revision("a", 0, Some(0)).unwrap(), 1);
+        assert_eq!(next_revision("a", 3, Some(3)).unwrap(), 4);
+        // Commits that do not expect any replace whatever is there
+        assert_eq!(next_revision("a", 0, None).unwrap(), 1);
+        assert_eq!(next_revision("a", 7, None).unwrap(), 8);
+
+        // Commits that expect another revision lost a race, either with a commit to an existing dataset or with one that created it
+        assert!(matches!(next_revision("a", 4, Some(3)), Err(CommitError::RevisionConflict{ ref name, expected: 3, actual: 4 }) if name == "a"));
+        assert!(matches!(next_revision("a", 1, Some(0)), Err(CommitError::RevisionConflict{ expected: 0, actual: 1, .. })));
+        assert!(next_revision("a", 1, Some(0)).unwrap_err().is_conflict());
+    }
+}
+
+
+
+
+
 /***** CONSTANTS *****/
 /// Path to the temporary folder.
 pub const TEMPORARY_DIR: &str = "/tmp";
 
+/// The path at which the directory that persists between calls to the same OAS package is mounted in its containers.
+pub const OAS_STATE_DIR: &str = "/oas";
+
+
+
+
+
+/***** GLOBALS *****/
+/// The number of tasks currently running on the local backend, used to decide when to burst to the cloud.
+static LOCAL_TASKS: AtomicUsize = AtomicUsize::new(0);
+/// The number of tasks currently connected to every run network, used to remove a network once the last task of its run on this worker completes.
+static RUN_NETWORKS: Mutex<BTreeMap<String, usize>> = Mutex::new(BTreeMap::new());
+
 
 
 
@@ -83,6 +139,25 @@ macro_rules! err {
 
 
 /***** HELPER FUNCTIONS *****/
+/// Checks that a commit replaces the revision of a dataset that it expects to replace.
+/// 
+/// # Arguments
+/// - `name`: The name of the dataset (used for debugging only).
+/// - `actual`: The revision the dataset is at (0 if it does not exist yet).
+/// - `expected`: The revision the commit expects to replace, or `None` if it replaces any.
+/// 
+/// # Returns
+/// The revision of the dataset after the commit.
+/// 
+/// # Errors
+/// This function errors if the dataset is not at the expected revision, i.e., another commit changed it in the meantime.
+fn next_revision(name: &str, actual: u64, expected: Option<u64>) -> Result<u64, CommitError> {
+    match expected {
+        Some(expected) if expected != actual => Err(CommitError::RevisionConflict{ name: name.into(), expected, actual }),
+        _                                    => Ok(actual + 1),
+    }
+}
+
 /// Updates the client with a status update.
 /// 
 /// # Arguments
@@ -111,11 +186,153 @@ async fn update_client(tx: &Sender<Result<TaskReply, Status>>, status: JobStatus
     Ok(())
 }
 
+/// Builds the arguments for branelet that run the given task.
+/// 
+/// # Arguments
+/// - `node_config`: The configuration for this node's environment. For us, contains the location ID of this location.
+/// - `tinfo`: The TaskInfo that describes the task to run. Its arguments must already have been preprocessed.
+/// - `options`: Any additional options for branelet itself (e.g., which data to stage in).
+/// 
+/// # Returns
+/// The list of arguments to pass to branelet.
+/// 
+/// # Errors
+/// This function errors if we failed to serialize the task's arguments.
+fn branelet_command(node_config: &NodeConfig, tinfo: &TaskInfo, options: &[String]) -> Result<Vec<String>, JobStatus> {
+    let params: String = match serde_json::to_string(&tinfo.args) {
+        Ok(params) => params,
+        Err(err)   => { return Err(JobStatus::CreationFailed(format!("Failed to serialize arguments: {}", err))); },
+    };
+    let mut command: Vec<String> = vec![
+        "-d".into(),
+        "--application-id".into(),
+        "unspecified".into(),
+        "--location-id".into(),
+        node_config.node.worker().location_id.clone(),
+        "--job-id".into(),
+        "unspecified".into(),
+    ];
+    command.extend(options.iter().cloned());
+    command.extend([
+        tinfo.kind.unwrap().into(),
+        tinfo.name.clone(),
+        base64::encode(params),
+    ]);
+    Ok(command)
+}
+
+/// Resolves how to connect to the local Docker daemon as configured for the local backend.
+/// 
+/// # Arguments
+/// - `path`: The path to the Docker socket, if it is not the default one.
+/// - `version`: The client version to connect with, if it is not the default one.
+/// 
+/// # Returns
+/// A new DockerInfo with the defaults filled in.
+fn docker_info(path: &Option<PathBuf>, version: &Option<(usize, usize)>) -> DockerInfo {
+    DockerInfo::new(path.clone().unwrap_or_else(|| PathBuf::from("/var/run/docker.sock")), version.map(|(major, minor)| ClientVersion{ major_version: major, minor_version: minor }).unwrap_or(*API_DEFAULT_VERSION))
+}
+
+/// Decodes the value returned by branelet from its stdout.
+/// 
+/// # Arguments
+/// - `stdout`: Everything branelet wrote to stdout (or to its output URL). The value is expected on the last line.
+/// 
+/// # Returns
+/// The value returned by the task.
+/// 
+/// # Errors
+/// This function errors if the last line was not valid base64-encoded JSON.
+fn decode_output(stdout: &str) -> Result<FullValue, JobStatus> {
+    let output = stdout.lines().last().unwrap_or_default().to_string();
+    let raw: String = match decode_base64(output) {
+        Ok(raw)  => raw,
+        Err(err) => { return Err(JobStatus::DecodingFailed(format!("Failed to decode output ase base64: {}", err))); },
+    };
+    match serde_json::from_str::<Option<FullValue>>(&raw) {
+        Ok(value) => Ok(value.unwrap_or(FullValue::Void)),
+        Err(err)  => Err(JobStatus::DecodingFailed(format!("Failed to decode output as JSON: {}", err))),
+    }
+}
+
 
 
 
 
 /***** AUXILLARY STRUCTURES *****/
+/// Counts a task as running on the local backend for as long as it lives.
+struct LocalTaskGuard;
+impl LocalTaskGuard {
+    /// Constructor for the LocalTaskGuard that increments the number of running local tasks.
+    #[inline]
+    fn new() -> Self {
+        LOCAL_TASKS.fetch_add(1, Ordering::SeqCst);
+        Self
+    }
+}
+impl Drop for LocalTaskGuard {
+    #[inline]
+    fn drop(&mut self) { LOCAL_TASKS.fetch_sub(1, Ordering::SeqCst); }
+}
+
+/// Keeps the network of a run alive for as long as it lives, and removes the network when the last task of the run on this worker is done with it.
+struct RunNetworkGuard {
+    /// The name of the network.
+    name  : String,
+    /// Where to find the Docker daemon that hosts the network.
+    dinfo : DockerInfo,
+}
+impl RunNetworkGuard {
+    /// Constructor for the RunNetworkGuard that creates the network if this is the first task of the run to use it.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the network.
+    /// - `dinfo`: Information that determines where and how to connect to the local Docker deamon.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to create the network.
+    async fn new(name: String, dinfo: &DockerInfo) -> Result<Self, docker::Error> {
+        *RUN_NETWORKS.lock().unwrap().entry(name.clone()).or_insert(0) += 1;
+        let guard: Self = Self{ name, dinfo: dinfo.clone() };
+        docker::ensure_network(&guard.name, &guard.dinfo.socket_path, guard.dinfo.client_version).await?;
+        Ok(guard)
+    }
+}
+impl Drop for RunNetworkGuard {
+    fn drop(&mut self) {
+        // Only the last task of the run removes it
+        {
+            let mut networks: MutexGuard<BTreeMap<String, usize>> = RUN_NETWORKS.lock().unwrap();
+            if let Some(count) = networks.get_mut(&self.name) {
+                *count -= 1;
+                if *count > 0 { return; }
+            }
+            networks.remove(&self.name);
+        }
+
+        // We cannot wait in a destructor, so remove it in the background
+        let name: String = self.name.clone();
+        let dinfo: DockerInfo = self.dinfo.clone();
+        tokio::spawn(async move {
+            if let Err(err) = docker::remove_network(&name, &dinfo.socket_path, dinfo.client_version).await { warn!("Failed to remove network of finished run: {}", err); }
+        });
+    }
+}
+
+/// Defines the parts of a Vault KV (version 2) read response that we are interested in.
+#[derive(Deserialize)]
+struct VaultResponse {
+    /// The secret itself, along with its metadata.
+    data : VaultSecret,
+}
+
+/// Defines the secret in a Vault KV (version 2) read response.
+#[derive(Deserialize)]
+struct VaultSecret {
+    /// The key/value pairs stored in the secret.
+    data : HashMap<String, Secret>,
+}
+
 /// Helper structure for grouping together Docker environment information.
 #[derive(Clone, Debug)]
 pub struct DockerInfo {
@@ -188,6 +405,13 @@ pub struct TaskInfo {
     pub args         : HashMap<String, FullValue>,
     /// The requirements for this task.
     pub requirements : HashSet<Capability>,
+    /// The time (in seconds) after which the task is killed if it has not completed yet, if any.
+    pub timeout      : Option<u64>,
+
+    /// The ID of the workflow run that this task is part of, if the driver told us.
+    pub app_id  : Option<String>,
+    /// The names of the secrets that the task needs injected as environment variables, as declared by its package. Note: won't be populated until later.
+    pub secrets : Vec<String>,
 }
 impl TaskInfo {
     /// Constructor for the TaskInfo.
@@ -202,7 +426,7 @@ impl TaskInfo {
     /// - `requirements`: The list of required capabilities for this task.
     /// 
     /// # Returns
-    /// A new TaskInfo instance.
+    /// A new TaskInfo instance, without a timeout (set `TaskInfo::timeout` for that).
     #[inline]
     pub fn new(name: impl Into<String>, package_name: impl Into<String>, package_version: impl Into<Version>, input: HashMap<DataName, AccessKind>, result: Option<String>, args: HashMap<String, FullValue>, requirements: HashSet<Capability>) -> Self {
         Self {
@@ -218,6 +442,10 @@ impl TaskInfo {
 
             args,
             requirements,
+            timeout      : None,
+
+            app_id  : None,
+            secrets : vec![],
         }
     }
 }
@@ -227,6 +455,133 @@ impl TaskInfo {
 
 
 /***** PLANNING FUNCTIONS *****/
+/// Checks that the CA certificate with which we trust the given location is the one we pinned, or, if it is newly seen, that the central node's CA log vouches for it.
+/// 
+/// # Arguments
+/// - `node_config`: The configuration for this node's environment, which tells us where the certificates (and our pins) live.
+/// - `proxy`: The proxy client to fetch the CA log through.
+/// - `api`: The address of the central node's API service, if the driver told us.
+/// - `location`: The location of which to check the certificate.
+/// 
+/// # Errors
+/// This function errors if we failed to fetch the log or if it does not vouch for the certificate, in which case the certificate may have been substituted.
+async fn verify_ca_pin(node_config: &NodeConfig, proxy: &ProxyClient, api: Option<&str>, location: &str) -> Result<(), PreprocessError> {
+    // Locations hosted by peer instances are trusted using the certificates of the peer itself
+    let name: &str = split_peer_location(location).map(|(peer, _)| peer).unwrap_or(location);
+    let fingerprint: String = match fingerprint(node_config.paths.certs.join(name).join("ca.pem")) {
+        Ok(fingerprint) => fingerprint,
+        Err(err)        => { return Err(PreprocessError::CaPinError{ location: name.into(), err }); },
+    };
+    let pins_path: PathBuf = node_config.paths.certs.join(PinStore::FILENAME);
+    let mut pins: PinStore = match PinStore::from_path(&pins_path) {
+        Ok(pins) => pins,
+        Err(err) => { return Err(PreprocessError::CaPinError{ location: name.into(), err }); },
+    };
+    if pins.is_pinned(name, &fingerprint) { return Ok(()); }
+
+    // It's a new certificate, so consult the log
+    let api: &str = match api {
+        Some(api) => api,
+        None      => { return Err(PreprocessError::MissingCaLog{ location: name.into() }); },
+    };
+    debug!("CA certificate of location '{}' is newly seen; consulting CA log...", name);
+    let address: String = format!("{}/infra/ca-log", api);
+    let res: reqwest::Response = match proxy.get(&address, None).await {
+        Ok(result) => match result {
+            Ok(res)  => res,
+            Err(err) => { return Err(PreprocessError::DownloadRequestError{ address, err }); },
+        },
+        Err(err) => { return Err(PreprocessError::ProxyError { err: err.to_string() }); },
+    };
+    if !res.status().is_success() {
+        return Err(PreprocessError::DownloadRequestFailure { address, code: res.status(), message: res.text().await.ok() });
+    }
+    let log: SignedCaLog = match res.json().await {
+        Ok(log)  => log,
+        Err(err) => { return Err(PreprocessError::CaLogParseError{ address, err }); },
+    };
+
+    // Pin it if the log agrees
+    if let Err(err) = pins.update(name, &fingerprint, &log) { return Err(PreprocessError::CaPinError{ location: name.into(), err }); }
+    match pins.to_path(&pins_path) {
+        Ok(_)    => Ok(()),
+        Err(err) => Err(PreprocessError::CaPinError{ location: name.into(), err }),
+    }
+}
+
+/// Sends the request that downloads an intermediate result or dataset from another domain's registry.
+/// 
+/// # Arguments
+/// - `proxy`: The proxy client to send the request through.
+/// - `location`: The location of the registry, which determines the certificates to use.
+/// - `address`: The address + path of the result or dataset to download.
+/// - `direct`: Whether to ask the registry for a direct transfer. It may ignore this if it does not support it.
+/// 
+/// # Returns
+/// The response of the registry, which carries the archive as its body.
+/// 
+/// # Errors
+/// This function errors if we failed to send the request or the registry replied with a non-success status code.
+async fn request_transfer(proxy: &ProxyClient, location: &Location, address: &str, direct: bool) -> Result<reqwest::Response, PreprocessError> {
+    let client: reqwest::Client = reqwest::Client::new();
+    let mut request: reqwest::RequestBuilder = client.get(address);
+    if direct { request = request.header(TRANSFER_HEADER, TRANSFER_DIRECT); }
+    let request: reqwest::Request = match request.build() {
+        Ok(request) => request,
+        Err(err)    => { return Err(PreprocessError::DownloadRequestError{ address: address.into(), err }); },
+    };
+
+    // Send it through the proxy, which authenticates us to the registry
+    let res: reqwest::Response = match proxy.execute(client, request, Some(NewPathRequestTlsOptions{ location: location.clone(), use_client_auth: true })).await {
+        Ok(result) => match result {
+            Ok(res)  => res,
+            Err(err) => { return Err(PreprocessError::DownloadRequestError{ address: address.into(), err }); },
+        },
+        Err(err) => { return Err(PreprocessError::ProxyError { err: err.to_string() }); },
+    };
+    if !res.status().is_success() {
+        return Err(PreprocessError::DownloadRequestFailure { address: address.into(), code: res.status(), message: res.text().await.ok() });
+    }
+    Ok(res)
+}
+
+/// Extracts the archive in a direct transfer while it is being received.
+/// 
+/// # Arguments
+/// - `res`: The response of the registry that agreed to a direct transfer.
+/// - `address`: The address + path of the result or dataset, used in debug prints and errors.
+/// - `data_path`: The directory to extract the archive to. Must not exist yet.
+/// 
+/// # Errors
+/// This function errors if the transfer broke off or we failed to extract the archive.
+async fn extract_direct_transfer(res: reqwest::Response, address: &str, data_path: &Path) -> Result<(), PreprocessError> {
+    // Pass the chunks to one end of a pipe while we extract whatever comes out of the other end
+    let (mut writer, reader): (DuplexStream, DuplexStream) = tokio::io::duplex(1024 * 16);
+    let extract = unarchive_from_async(tokio::io::BufReader::new(reader), address, data_path);
+    let receive = async move {
+        let mut stream = res.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            let mut chunk: Bytes = match chunk {
+                Ok(chunk) => chunk,
+                Err(err)  => { return Err(PreprocessError::DownloadStreamError { address: address.into(), err }); },
+            };
+            if let Err(err) = writer.write_all_buf(&mut chunk).await { return Err(PreprocessError::DirectTransferError{ address: address.into(), err }); }
+        }
+        if let Err(err) = writer.shutdown().await { return Err(PreprocessError::DirectTransferError{ address: address.into(), err }); }
+        Ok(())
+    };
+    let (extracted, received): (Result<(), brane_shr::fs::Error>, Result<(), PreprocessError>) = tokio::join!(extract, receive);
+
+    // A broken transfer also breaks the extraction, so report that first
+    received?;
+    match extracted {
+        Ok(_)    => Ok(()),
+        Err(err) => Err(PreprocessError::DataExtractError{ err }),
+    }
+}
+
+
+
 /// Function that preprocesses by downloading the given tar and extracting it.
 /// 
 /// # Arguments
@@ -235,17 +590,21 @@ impl TaskInfo {
 /// - `location`: The location to download the tarball from.
 /// - `address`: The address to download the tarball from.
 /// - `data_name`: The type of the data (i.e., Data or IntermediateResult) combined with its identifier.
+/// - `api`: The address of the central node's API service, which we consult to verify newly seen CA certificates.
 /// 
 /// # Returns
 /// The AccessKind to access the extracted data.
 /// 
 /// # Errors
 /// This function can error for literally a million reasons - but they mostly relate to IO (file access, request success etc).
-pub async fn preprocess_transfer_tar(node_config: &NodeConfig, proxy: Arc<ProxyClient>, location: Location, address: impl AsRef<str>, data_name: DataName) -> Result<AccessKind, PreprocessError> {
+pub async fn preprocess_transfer_tar(node_config: &NodeConfig, proxy: Arc<ProxyClient>, location: Location, address: impl AsRef<str>, data_name: DataName, api: Option<&str>) -> Result<AccessKind, PreprocessError> {
     debug!("Preprocessing by executing a data transfer");
     let address: &str  = address.as_ref();
     debug!("Downloading from {} ({})", location, address);
 
+    // Make sure we still trust the certificate of the location before talking to it
+    verify_ca_pin(node_config, &proxy, api, &location).await?;
+
 
 
     // Prepare the folder where we will download the data to
@@ -311,17 +670,26 @@ pub async fn preprocess_transfer_tar(node_config: &NodeConfig, proxy: Arc<ProxyC
 
 
 
-    // Send a reqwest
+    // Send a reqwest, asking the producer for a direct transfer
     debug!("Sending download request...");
-    let res = match proxy.get(address, Some(NewPathRequestTlsOptions{ location: location.clone(), use_client_auth: true })).await {
-        Ok(result) => match result {
-            Ok(res)  => res,
-            Err(err) => { return Err(PreprocessError::DownloadRequestError{ address: address.into(), err }); },
-        },
-        Err(err) => { return Err(PreprocessError::ProxyError { err: err.to_string() }); },
-    };
-    if !res.status().is_success() {
-        return Err(PreprocessError::DownloadRequestFailure { address: address.into(), code: res.status(), message: res.text().await.ok() });
+    let mut res: reqwest::Response = request_transfer(&proxy, &location, address, true).await?;
+
+    // If the producer agreed, we extract the result while it comes in; otherwise (or if that fails), we fall back to downloading the archive first
+    if res.headers().get(TRANSFER_HEADER).map(|value| value == TRANSFER_DIRECT).unwrap_or(false) {
+        debug!("Extracting direct transfer to '{}'...", data_path.display());
+        match extract_direct_transfer(res, address, &data_path).await {
+            Ok(_)    => { return Ok(AccessKind::File{ path: data_path }); },
+            Err(err) => { warn!("{} (falling back to downloading archive)", err); },
+        }
+
+        // Restore the target directory to what it was before the attempt
+        if data_path.exists() {
+            if let Err(err) = tfs::remove_dir_all(&data_path).await { return Err(PreprocessError::DirRemoveError{ what: "temporary data", path: data_path, err }); }
+        }
+        if let DataName::Data(_) = &data_name {
+            if let Err(err) = tfs::create_dir_all(&data_path).await { return Err(PreprocessError::DirCreateError{ what: "temporary data", path: data_path, err }); }
+        }
+        res = request_transfer(&proxy, &location, address, false).await?;
     }
 
 
@@ -455,13 +823,87 @@ async fn assert_workflow_permission(node_config: &NodeConfig, _workflow: &Workfl
 
 
 
-/// Downloads a container to the local registry.
+/// Returns the tag under which the given image is kept in the content-addressed store.
+/// 
+/// # Arguments
+/// - `image`: The image to return the tag of.
+/// 
+/// # Returns
+/// The tag, as `<name>-<version>`.
+#[inline]
+fn image_tag(image: &Image) -> String { format!("{}-{}", image.name, image.version.as_ref().unwrap_or(&"latest".into())) }
+
+/// Returns the free disk space of the worker, as seen by its content-addressed store.
+/// 
+/// # Arguments
+/// - `worker`: The configuration of this worker node.
+/// 
+/// # Returns
+/// The number of bytes that are still free.
+/// 
+/// # Errors
+/// This function errors if we failed to query the disk.
+#[inline]
+fn free_space(worker: &WorkerConfig) -> Result<u64, ExecuteError> {
+    match disk::free_space(&worker.paths.store) {
+        Ok(free) => Ok(free),
+        Err(err) => Err(ExecuteError::DiskSpaceError{ err }),
+    }
+}
+
+/// Makes sure there is enough disk space to admit a task, evicting caches if there is not (or if the disk is under pressure).
+/// 
+/// Eviction first collects the objects in the content-addressed store that are no longer referenced, and then releases cached package images (which can always be downloaded again) until the task fits.
 /// 
 /// # Arguments
-/// - `node_config`: The configuration for this node's environment. For us, contains if and where we should proxy the request through and where we may download package images to.
+/// - `worker`: The configuration of this worker node. Determines how much space to keep free.
+/// - `store`: The content-addressed store to evict from.
+/// - `needed`: The estimated number of bytes that the task needs on top of the reserved space.
+/// - `keep`: The tag of the package image that the task uses, which is never evicted.
+/// 
+/// # Errors
+/// This function errors if there is still not enough space left after evicting everything we could, or if we failed to query the disk or to evict anything.
+async fn admit_task(worker: &WorkerConfig, store: &ContentStore, needed: u64, keep: &str) -> Result<(), ExecuteError> {
+    let reserve  : u64 = worker.min_free_space;
+    let required : u64 = needed.saturating_add(reserve);
+    let mut free : u64 = free_space(worker)?;
+    if free >= required && Pressure::of(free, reserve) == Pressure::Normal { return Ok(()); }
+
+    // Collect the garbage first
+    warn!("Disk pressure is {} ({} bytes free, {} bytes reserved); evicting unreferenced objects...", Pressure::of(free, reserve), free, reserve);
+    if let Err(err) = store.gc(false).await { return Err(ExecuteError::StoreError{ err }); }
+    free = free_space(worker)?;
+
+    // Then release package images, until the task fits
+    if free < required {
+        let tags: Vec<(String, String)> = match store.tags(TagKind::Package).await {
+            Ok(tags) => tags,
+            Err(err) => { return Err(ExecuteError::StoreError{ err }); },
+        };
+        for (name, _) in tags {
+            if free >= required { break; }
+            if name == keep { continue; }
+
+            debug!("Evicting package image '{}'...", name);
+            if let Err(err) = store.untag(TagKind::Package, &name).await { return Err(ExecuteError::StoreError{ err }); }
+            if let Err(err) = store.gc(false).await { return Err(ExecuteError::StoreError{ err }); }
+            free = free_space(worker)?;
+        }
+    }
+
+    // Refuse the task if that was not enough
+    if free < required { return Err(ExecuteError::InsufficientDiskSpace{ needed, free, reserve }); }
+    Ok(())
+}
+
+/// Downloads a container to the local content-addressed store.
+/// 
+/// # Arguments
+/// - `store`: The content-addressed store to keep the image in. It is tagged as `<name>-<version>`, which releases any older image with the same name and version.
 /// - `proxy`: The proxy client we use to proxy the data transfer.
 /// - `endpoint`: The address where to download the container from.
 /// - `image`: The image name (including digest, for caching) to download.
+/// - `cache`: Whether we may use an image that was downloaded before (i.e., whether the `enable_cache` feature is enabled). If not, the image is always downloaded again.
 /// 
 /// # Returns
 /// The path of the downloaded image file combined with the hash of the image. It's very good practise to use this one, since the actual path is subject to change.
@@ -470,14 +912,18 @@ async fn assert_workflow_permission(node_config: &NodeConfig, _workflow: &Workfl
 /// 
 /// # Errors
 /// This function may error if we failed to reach the remote host, download the file or write the file.
-async fn download_container(node_config: &NodeConfig, proxy: Arc<ProxyClient>, endpoint: impl AsRef<str>, image: &mut Image) -> Result<(PathBuf, String), ExecuteError> {
+async fn download_container(store: &ContentStore, proxy: Arc<ProxyClient>, endpoint: impl AsRef<str>, image: &mut Image, cache: bool) -> Result<(PathBuf, String), ExecuteError> {
     let endpoint: &str = endpoint.as_ref();
     debug!("Downloading image '{}' from '{}'...", image, endpoint);
 
     // Check if we have already downloaded it, by any chance
-    let image_path : PathBuf = node_config.paths.packages.join(format!("{}-{}.tar", image.name, image.version.as_ref().unwrap_or(&"latest".into())));
-    let hash_path  : PathBuf = node_config.paths.packages.join(format!("{}-{}.sha256", image.name, image.version.as_ref().unwrap_or(&"latest".into())));
-    if image_path.exists() {
+    let tag: String = image_tag(image);
+    let cached: Option<(String, PathBuf)> = match store.resolve(TagKind::Package, &tag).await {
+        Ok(cached) if cache => cached,
+        Ok(_)               => { debug!("Caching is disabled; downloading image regardless"); None },
+        Err(err)            => { return Err(ExecuteError::StoreError{ err }); },
+    };
+    if let Some((digest, image_path)) = cached {
         debug!("Image file '{}' already exists; checking if it's up-to-date...", image_path.display());
 
         // Get the digest of the local image
@@ -488,36 +934,11 @@ async fn download_container(node_config: &NodeConfig, proxy: Arc<ProxyClient>, e
 
         // Compare the digests if they've given us one as well
         match &image.digest {
-            Some(digest) => {
-                if digest == &image_digest {
+            Some(wanted) => {
+                if wanted == &image_digest {
+                    // The store already knows the hash of the image, since that's its address
                     debug!("Local image is up-to-date");
-
-                    debug!("Seeing if hash has already been computed...");
-                    let hash: String = if hash_path.exists() {
-                        debug!("Loading hash...");
-                        match tfs::read_to_string(&hash_path).await {
-                            Ok(hash) => hash,
-                            Err(err) => { return Err(ExecuteError::HashReadError{ path: hash_path, err }); },
-                        }
-                    } else {
-                        debug!("Hashing image (this might take a while)...");
-                        // Get the image hash
-                        let hash: String = match docker::hash_container(&image_path).await {
-                            Ok(hash) => hash,
-                            Err(err) => { return Err(ExecuteError::HashError{ err }); },
-                        };
-
-                        // Write it
-                        if let Err(err) = tfs::write(&hash_path, hash.as_bytes()).await {
-                            return Err(ExecuteError::HashWriteError{ path: hash_path, err });
-                        }
-
-                        // Done, return the hash
-                        hash
-                    };
-
-                    // Return both of them
-                    return Ok((image_path, hash));
+                    return Ok((image_path, docker::hash_from_digest(&digest)));
                 }
             },
             None => {
@@ -530,8 +951,12 @@ async fn download_container(node_config: &NodeConfig, proxy: Arc<ProxyClient>, e
         debug!("Local image is outdated; overwriting...");
     }
 
-    // Send a GET-request to the correct location
-    let address: String = format!("{}/packages/{}/{}", endpoint, image.name, image.version.as_ref().unwrap_or(&"latest".into()));
+    // Send a GET-request to the correct location, asking for the image that matches our architecture
+    let mut address: String = format!("{}/packages/{}/{}", endpoint, image.name, image.version.as_ref().unwrap_or(&"latest".into()));
+    match Arch::host() {
+        Ok(arch) => { address.push_str(&format!("?arch={}", arch)); },
+        Err(err) => { warn!("Failed to get host architecture ({}); downloading the default image of '{}' instead", err, image); },
+    }
     debug!("Performing request to '{}'...", address);
     let res = match proxy.get(&address, None).await {
         Ok(result) => match result {
@@ -544,12 +969,13 @@ async fn download_container(node_config: &NodeConfig, proxy: Arc<ProxyClient>, e
         return Err(ExecuteError::DownloadRequestFailure{ address, code: res.status(), message: res.text().await.ok() });
     }
 
-    // With the request success, download it in parts
-    debug!("Writing request stream to '{}'...", image_path.display());
+    // With the request success, download it in parts to the store's scratch directory
+    let download_path: PathBuf = store.temp_path();
+    debug!("Writing request stream to '{}'...", download_path.display());
     {
-        let mut handle: tfs::File = match tfs::File::create(&image_path).await {
+        let mut handle: tfs::File = match tfs::File::create(&download_path).await {
             Ok(handle) => handle,
-            Err(err)   => { return Err(ExecuteError::ImageCreateError{ path: image_path, err }); },
+            Err(err)   => { return Err(ExecuteError::ImageCreateError{ path: download_path, err }); },
         };
         let mut stream = res.bytes_stream();
         while let Some(chunk) = stream.next().await {
@@ -561,31 +987,108 @@ async fn download_container(node_config: &NodeConfig, proxy: Arc<ProxyClient>, e
 
             // Write it to the file
             if let Err(err) = handle.write_all_buf(&mut chunk).await {
-                return Err(ExecuteError::ImageWriteError{ path: image_path, err });
+                return Err(ExecuteError::ImageWriteError{ path: download_path, err });
             }
         }
     }
 
-    // Hash the image while at it
-    debug!("Hashing image (this might take a while)...");
-    let hash: String = {
-        // Get the image hash
-        let hash: String = match docker::hash_container(&image_path).await {
-            Ok(hash) => hash,
-            Err(err) => { return Err(ExecuteError::HashError{ err }); },
-        };
+    // Move it into the store, which hashes it while at it
+    debug!("Adding image to the store (this might take a while)...");
+    let (digest, image_path): (String, PathBuf) = match store.add(&download_path, TagKind::Package, &tag).await {
+        Ok(res)  => res,
+        Err(err) => { return Err(ExecuteError::StoreError{ err }); },
+    };
 
-        // Write it
-        if let Err(err) = tfs::write(&hash_path, hash.as_bytes()).await {
-            return Err(ExecuteError::HashWriteError{ path: hash_path, err });
-        }
+    // That's OK - now return
+    Ok((image_path, docker::hash_from_digest(&digest)))
+}
+
+
+
+/// Pulls a container from an OCI registry into the local Docker daemon.
+/// 
+/// # Arguments
+/// - `registry`: The ImageRegistryConfig that describes the registry to pull from.
+/// - `dinfo`: Information that determines where and how to connect to the local Docker deamon.
+/// - `image`: The image (including digest) to pull.
+/// 
+/// # Returns
+/// The reference of the pulled image combined with the hash of the image. Since there is no image file to hash, the hash is derived from the image's digest instead, which Docker has verified while pulling.
+/// 
+/// # Errors
+/// This function may error if the image has no digest or if we failed to pull it (e.g., because it was pushed to `brane-api` instead).
+async fn pull_container(registry: &ImageRegistryConfig, dinfo: &DockerInfo, image: &Image) -> Result<(String, String), ExecuteError> {
+    let digest: &str = match &image.digest {
+        Some(digest) => digest,
+        None         => { return Err(ExecuteError::MissingImageDigest{ image: image.clone() }); },
+    };
+    let reference: String = registry.reference(&image.name, image.version.as_ref().unwrap_or(&"latest".into()));
+    debug!("Pulling image '{}' from '{}'...", image, reference);
+
+    // Pull it
+    let credentials: Option<(String, String)> = registry.username.clone().zip(registry.password.clone());
+    if let Err(err) = docker::pull(image.clone(), &reference, credentials, &dinfo.socket_path, dinfo.client_version).await {
+        return Err(ExecuteError::ImagePullError{ source: reference, err });
+    }
+    Ok((reference, docker::hash_from_digest(digest.trim_start_matches("sha256:"))))
+}
+
+
+
+/// Resolves the values of the secrets that a task needs from the secrets file of this worker.
+/// 
+/// # Arguments
+/// - `worker`: The WorkerConfig that tells us where to find the secrets file.
+/// - `tinfo`: The TaskInfo of the task that needs the secrets.
+/// - `digest`: The digest of the image of the task's package, if known, which the allowlists of the secrets may name.
+/// 
+/// # Returns
+/// The values of the secrets, mapped by their names.
+/// 
+/// # Errors
+/// This function errors if the worker has no secrets file, if we failed to load it or to read the Vault it points to, or if the task needs a secret that we do not know or that its package may not use.
+async fn resolve_secrets(worker: &WorkerConfig, tinfo: &TaskInfo, digest: Option<&str>) -> Result<HashMap<String, Secret>, ExecuteError> {
+    let path: &Path = match &worker.paths.secrets {
+        Some(path) => path,
+        None       => { return Err(ExecuteError::NoSecretsFile{ name: tinfo.name.clone() }); },
+    };
+    let file: SecretsFile = match SecretsFile::from_path(path) {
+        Ok(file) => file,
+        Err(err) => { return Err(ExecuteError::SecretsFileError{ path: path.into(), err }); },
+    };
 
-        // Done, return the hash
-        hash
+    // Refuse any secret that the package is not allowed to use before reading any of them
+    for name in &tinfo.secrets {
+        if !file.allows(name, &tinfo.package_name, digest) { return Err(ExecuteError::SecretNotAllowed{ name: tinfo.name.clone(), package: tinfo.package_name.clone(), secret: name.clone() }); }
+    }
+
+    // Collect the secrets that the backend knows of
+    let known: HashMap<String, Secret> = match file.backend {
+        SecretsBackend::File{ secrets } => secrets,
+        SecretsBackend::Vault(vault)    => {
+            let address: String = format!("{}/v1/{}/data/{}", vault.address.trim_end_matches('/'), vault.mount, vault.path);
+            debug!("Fetching secrets from Vault at '{}'...", address);
+            let res: reqwest::Response = match reqwest::Client::new().get(&address).header("X-Vault-Token", vault.token.expose()).send().await {
+                Ok(res)  => res,
+                Err(err) => { return Err(ExecuteError::VaultRequestError{ address, err }); },
+            };
+            if !res.status().is_success() { return Err(ExecuteError::VaultRequestFailure{ address, code: res.status() }); }
+            match res.json::<VaultResponse>().await {
+                Ok(res)  => res.data.data,
+                Err(err) => { return Err(ExecuteError::VaultResponseError{ address, err }); },
+            }
+        },
     };
 
-    // That's OK - now return
-    Ok((image_path, hash))
+    // Pick the ones that the task needs
+    let mut secrets: HashMap<String, Secret> = HashMap::with_capacity(tinfo.secrets.len());
+    for name in &tinfo.secrets {
+        match known.get(name) {
+            Some(value) => { secrets.insert(name.clone(), value.clone()); },
+            None        => { return Err(ExecuteError::UnknownSecret{ name: tinfo.name.clone(), secret: name.clone() }); },
+        }
+    }
+    Ok(secrets)
 }
 
 
@@ -596,8 +1099,9 @@ async fn download_container(node_config: &NodeConfig, proxy: Arc<ProxyClient>, e
 /// - `node_config`: The configuration for this node's environment. For us, contains the location ID of this location and where to find data & intermediate results.
 /// - `dinfo`: Information that determines where and how to connect to the local Docker deamon.
 /// - `tx`: The transmission channel over which we should update the client of our progress.
-/// - `container_path`: The path of the downloaded container that we should execute.
+/// - `container_source`: Where to get the container that we should execute from (i.e., the downloaded file or the OCI registry).
 /// - `tinfo`: The TaskInfo that describes the task itself to execute.
+/// - `secrets`: The values of the secrets that the task needs, which are passed to the container as environment variables.
 /// - `keep_container`: Whether to keep the container after execution or not.
 /// 
 /// # Returns
@@ -605,10 +1109,9 @@ async fn download_container(node_config: &NodeConfig, proxy: Arc<ProxyClient>, e
 /// 
 /// # Errors
 /// This function errors if the task fails for whatever reason or we didn't even manage to launch it.
-async fn execute_task_local(node_config: &NodeConfig, dinfo: DockerInfo, tx: &Sender<Result<TaskReply, Status>>, container_path: impl AsRef<Path>, tinfo: TaskInfo, keep_container: bool) -> Result<FullValue, JobStatus> {
-    let container_path : &Path    = container_path.as_ref();
-    let mut tinfo      : TaskInfo = tinfo;
-    let image          : Image    = tinfo.image.unwrap();
+async fn execute_task_local(node_config: &NodeConfig, dinfo: DockerInfo, tx: &Sender<Result<TaskReply, Status>>, container_source: ImageSource, tinfo: TaskInfo, secrets: HashMap<String, Secret>, keep_container: bool) -> Result<FullValue, JobStatus> {
+    let mut tinfo : TaskInfo = tinfo;
+    let image     : Image    = tinfo.image.unwrap();
     debug!("Spawning container '{}' as a local container...", image);
 
     // First, we preprocess the arguments
@@ -617,33 +1120,43 @@ async fn execute_task_local(node_config: &NodeConfig, dinfo: DockerInfo, tx: &Se
         Err(err)  => { return Err(JobStatus::CreationFailed(format!("Failed to preprocess arguments: {}", err))); },
     };
 
+    // OAS packages share state between their calls (e.g., to enforce their rate limit or to cache responses), which we keep in a directory per package
+    let mut binds: Vec<VolumeBind> = binds;
+    let mut env: HashMap<String, Secret> = secrets;
+    if tinfo.kind == Some(PackageKind::Oas) {
+        let state: PathBuf = node_config.node.worker().paths.temp_data.join(".oas").join(&tinfo.package_name);
+        if let Err(err) = tfs::create_dir_all(&state).await { return Err(JobStatus::CreationFailed(format!("Failed to create state directory '{}' of package '{}': {}", state.display(), tinfo.package_name, err))); }
+        match VolumeBind::new(state, OAS_STATE_DIR, vec![]) {
+            Ok(bind) => binds.push(bind),
+            Err(err) => { return Err(JobStatus::CreationFailed(format!("Failed to mount state directory of package '{}': {}", tinfo.package_name, err))); },
+        }
+        env.insert(OAS_STATE_DIR_ENV.into(), OAS_STATE_DIR.into());
+        env.insert(OAS_CACHE_DIR_ENV.into(), format!("{}/cache", OAS_STATE_DIR).into());
+    }
+
     // Serialize them next
-    let params: String = match serde_json::to_string(&tinfo.args) {
-        Ok(params) => params,
-        Err(err)   => { return Err(JobStatus::CreationFailed(format!("Failed to serialize arguments: {}", err))); },
+    let command: Vec<String> = branelet_command(node_config, &tinfo, &[])?;
+
+    // Connect it to the network of its run if told to do so, where other containers of the run can find it by its package name
+    let network: Option<RunNetworkGuard> = match tinfo.app_id.as_ref().filter(|_| node_config.node.worker().run_networks) {
+        Some(app_id) => match RunNetworkGuard::new(format!("brane-run-{}", app_id), &dinfo).await {
+            Ok(guard) => Some(guard),
+            Err(err)  => { return Err(JobStatus::CreationFailed(format!("Failed to create network of run: {}", err))); },
+        },
+        None => None,
     };
 
     // Prepare the ExecuteInfo
     let info: ExecuteInfo = ExecuteInfo::new(
         &tinfo.name,
         image,
-        ImageSource::Path(container_path.into()),
-        vec![
-            "-d".into(),
-            "--application-id".into(),
-            "unspecified".into(),
-            "--location-id".into(),
-            node_config.node.worker().location_id.clone(),
-            "--job-id".into(),
-            "unspecified".into(),
-            tinfo.kind.unwrap().into(),
-            tinfo.name.clone(),
-            base64::encode(params),
-        ],
+        container_source,
+        command,
         binds,
         tinfo.requirements,
-        Network::None,
-    );
+        network.as_ref().map(|guard| Network::Custom(guard.name.clone())).unwrap_or(Network::None),
+        Some(node_config.node.worker().max_scratch_size),
+    ).with_aliases([ tinfo.package_name.clone() ]).with_env(env);
 
     // Now we can launch the container...
     let name: String = match docker::launch(info, &dinfo.socket_path, dinfo.client_version).await {
@@ -653,32 +1166,326 @@ async fn execute_task_local(node_config: &NodeConfig, dinfo: DockerInfo, tx: &Se
     if let Err(err) = update_client(tx, JobStatus::Created).await { error!("{}", err); }
     if let Err(err) = update_client(tx, JobStatus::Started).await { error!("{}", err); }
 
-    // ...and wait for it to complete
-    let (code, stdout, stderr): (i32, String, String) = match docker::join(name, dinfo.socket_path, dinfo.client_version, keep_container).await {
-        Ok(name) => name,
-        Err(err) => { return Err(JobStatus::CompletionFailed(format!("Failed to join container: {}", err))); },
+    // Follow any progress and output the task reports in the background...
+    let (ptx, mut prx) = mpsc::channel::<TaskProgress>(10);
+    let (otx, mut orx) = mpsc::channel::<TaskOutput>(64);
+    {
+        let name: String = name.clone();
+        let socket_path: PathBuf = dinfo.socket_path.clone();
+        tokio::spawn(async move {
+            if let Err(err) = docker::follow_progress(&name, socket_path, dinfo.client_version, ptx, otx).await {
+                warn!("Failed to follow progress of container '{}': {}", name, err);
+            }
+        });
+    }
+
+    // ...sample the resources it consumes in the background...
+    let stats = {
+        let name: String = name.clone();
+        let socket_path: PathBuf = dinfo.socket_path.clone();
+        tokio::spawn(async move { docker::follow_stats(&name, socket_path, dinfo.client_version).await })
     };
+
+    // ...and wait for it to complete, relaying that progress to the client as heartbeats and the output as it comes in (unless the `enable_streaming_logs` feature is disabled)
+    let stream_output: bool = node_config.features.enable_streaming_logs;
+    // (killing it if it runs out of time or the client hangs up, e.g., because the workflow was cancelled)
+    let join = docker::join(name.clone(), &dinfo.socket_path, dinfo.client_version, keep_container);
+    let deadline = tokio::time::sleep(tinfo.timeout.map(Duration::from_secs).unwrap_or(Duration::MAX));
+    tokio::pin!(join);
+    tokio::pin!(deadline);
+    let (code, stdout, stderr): (i32, String, String) = loop {
+        tokio::select! {
+            res = &mut join => match res {
+                Ok(res)  => { break res; },
+                Err(err) => { return Err(JobStatus::CompletionFailed(format!("Failed to join container: {}", err))); },
+            },
+            _ = &mut deadline, if tinfo.timeout.is_some() => {
+                let timeout: u64 = tinfo.timeout.unwrap();
+                warn!("Task '{}' did not complete within {} seconds; killing container '{}'", tinfo.name, timeout, name);
+                if let Err(err) = docker::kill(&name, &dinfo.socket_path, dinfo.client_version, keep_container).await { error!("Failed to kill container '{}': {}", name, err); }
+                stats.abort();
+                return Err(JobStatus::TimedOut(timeout));
+            },
+            _ = tx.closed() => {
+                warn!("Client of task '{}' hung up; killing container '{}'", tinfo.name, name);
+                if let Err(err) = docker::kill(&name, &dinfo.socket_path, dinfo.client_version, keep_container).await { error!("Failed to kill container '{}': {}", name, err); }
+                stats.abort();
+                return Err(JobStatus::CompletionFailed("Task was cancelled by its client".into()));
+            },
+            Some(progress) = prx.recv() => {
+                if let Err(err) = update_client(tx, JobStatus::Heartbeat(Some(progress))).await { error!("{}", err); }
+            },
+            Some(output) = orx.recv() => {
+                if !stream_output { continue; }
+                if let Err(err) = update_client(tx, JobStatus::Output(output)).await { error!("{}", err); }
+            },
+        }
+    };
+    // Relay any output that was still underway when the container stopped
+    while let Ok(output) = orx.try_recv() {
+        if !stream_output { continue; }
+        if let Err(err) = update_client(tx, JobStatus::Output(output)).await { error!("{}", err); }
+    }
     debug!("Container return code: {}", code);
     debug!("Container stdout/stderr:\n\nstdout:\n{}\n\nstderr:\n{}\n", BlockFormatter::new(&stdout), BlockFormatter::new(&stderr));
-    if let Err(err) = update_client(tx, JobStatus::Completed).await { error!("{}", err); }
+    let usage: Option<ResourceUsage> = match stats.await {
+        Ok(Ok(usage)) => Some(usage),
+        Ok(Err(err))  => { warn!("Failed to sample resource usage of task '{}': {}", tinfo.name, err); None },
+        Err(err)      => { warn!("Failed to join resource usage sampler of task '{}': {}", tinfo.name, err); None },
+    };
+    if let Err(err) = update_client(tx, JobStatus::Completed(usage)).await { error!("{}", err); }
 
     // If the return code is no bueno, error and show stderr
     if code != 0 {
+        if let Some(limit) = docker::scratch_exhausted(&stderr) { return Err(JobStatus::ScratchExhausted(limit)); }
         return Err(JobStatus::Failed(code, stdout, stderr));
     }
 
     // Otherwise, decode the output of branelet to the value returned
-    let output = stdout.lines().last().unwrap_or_default().to_string();
-    let raw: String = match decode_base64(output) {
-        Ok(raw)  => raw,
-        Err(err) => { return Err(JobStatus::DecodingFailed(format!("Failed to decode output ase base64: {}", err))); },
+    let value: FullValue = decode_output(&stdout)?;
+
+    // Done
+    debug!("Task '{}' returned value: '{:?}'", tinfo.name, value);
+    Ok(value)
+}
+
+/// Runs the given task on AWS, either as an AWS Batch job or as an AWS Lambda function.
+/// 
+/// The datasets and intermediate results of the task are exchanged with AWS through the configured bucket, and so is its return value.
+/// 
+/// # Arguments
+/// - `node_config`: The configuration for this node's environment. For us, contains the location ID of this location and where to find data & intermediate results.
+/// - `clients`: The cache of AwsClients to take the clients for the given account from.
+/// - `creds`: The AwsCredentials that describe the account, registry, bucket, job queue and job definitions to use.
+/// - `dinfo`: The information about the local Docker daemon, which we use to push the image of the package to the registry.
+/// - `tx`: The transmission channel over which we should update the client of our progress.
+/// - `container_source`: Where to get the container that we should push from (i.e., the downloaded file or the OCI registry).
+/// - `tinfo`: The TaskInfo that describes the task itself to execute.
+/// 
+/// # Returns
+/// The return value of the task when it completes.
+/// 
+/// # Errors
+/// This function errors if the task fails for whatever reason or we didn't even manage to submit it.
+async fn execute_task_aws(node_config: &NodeConfig, clients: &AwsClientCache, creds: &AwsCredentials, dinfo: DockerInfo, tx: &Sender<Result<TaskReply, Status>>, container_source: ImageSource, tinfo: TaskInfo) -> Result<FullValue, JobStatus> {
+    let mut tinfo : TaskInfo   = tinfo;
+    let image     : Image      = tinfo.image.clone().unwrap();
+    let clients   : AwsClients = clients.get(creds).await;
+    debug!("Running task '{}' on AWS...", tinfo.name);
+
+    // Make sure AWS can pull the image
+    if let Err(err) = aws::push_image(&clients, creds, &image, container_source, &dinfo.socket_path, dinfo.client_version).await {
+        return Err(JobStatus::CreationFailed(format!("{}", err)));
+    }
+
+    // Preprocess the arguments, and upload the data they refer to
+    let binds: Vec<VolumeBind> = match docker::preprocess_args(&mut tinfo.args, &tinfo.input, &tinfo.result, Some(&node_config.node.worker().paths.data), &node_config.node.worker().paths.results).await {
+        Ok(binds) => binds,
+        Err(err)  => { return Err(JobStatus::CreationFailed(format!("Failed to preprocess arguments: {}", err))); },
     };
-    let value: FullValue = match serde_json::from_str::<Option<FullValue>>(&raw) {
-        Ok(value) => value.unwrap_or(FullValue::Void),
-        Err(err)  => { return Err(JobStatus::DecodingFailed(format!("Failed to decode output as JSON: {}", err))); },
+    let staging: Staging = match aws::stage(&clients, creds, &tinfo.name, &binds, &node_config.node.worker().paths.temp_data).await {
+        Ok(staging) => staging,
+        Err(err)    => { return Err(JobStatus::CreationFailed(format!("{}", err))); },
     };
+    let command: Vec<String> = branelet_command(node_config, &tinfo, &staging.args)?;
 
-    // Done
+    // Run it, and always clean the bucket afterwards
+    let res: Result<FullValue, JobStatus> = run_task_aws(&clients, creds, tx, &image, &tinfo, command, binds.is_empty(), &staging).await;
+    aws::cleanup(&clients, creds, &staging).await;
+    res
+}
+
+/// Runs a task that has been prepared by `execute_task_aws()`, either as an AWS Batch job or as an AWS Lambda function.
+/// 
+/// # Arguments
+/// - `clients`: The AwsClients to connect with.
+/// - `creds`: The AwsCredentials that describe the account, bucket, job queue and job definitions to use.
+/// - `tx`: The transmission channel over which we should update the client of our progress.
+/// - `image`: The image of the package to run.
+/// - `tinfo`: The TaskInfo that describes the task itself to execute.
+/// - `command`: The arguments to pass to branelet.
+/// - `lambda`: Whether the task may run on AWS Lambda, which cannot write to the paths of datasets and intermediate results.
+/// - `staging`: The Staging that describes where to find the outputs of the task.
+/// 
+/// # Returns
+/// The return value of the task when it completes.
+/// 
+/// # Errors
+/// This function errors if the task fails for whatever reason or we didn't even manage to submit it.
+#[allow(clippy::too_many_arguments)]
+async fn run_task_aws(clients: &AwsClients, creds: &AwsCredentials, tx: &Sender<Result<TaskReply, Status>>, image: &Image, tinfo: &TaskInfo, command: Vec<String>, lambda: bool, staging: &Staging) -> Result<FullValue, JobStatus> {
+    // Small functions may be run on Lambda instead
+    let function: Option<&str> = if lambda { aws::lambda_function(creds, &tinfo.package_name, &tinfo.requirements) } else { None };
+    let (code, stdout, stderr): (i32, String, String) = if let Some(function) = function {
+        if let Err(err) = update_client(tx, JobStatus::Created).await { error!("{}", err); }
+        if let Err(err) = update_client(tx, JobStatus::Started).await { error!("{}", err); }
+        let invoke = aws::invoke(clients, function, command);
+        let res = match tinfo.timeout {
+            Some(timeout) => match tokio::time::timeout(Duration::from_secs(timeout), invoke).await {
+                Ok(res) => res,
+                Err(_)  => { return Err(JobStatus::TimedOut(timeout)); },
+            },
+            None => invoke.await,
+        };
+        match res {
+            Ok(res)  => res,
+            Err(err) => { return Err(JobStatus::CompletionFailed(format!("{}", err))); },
+        }
+
+    } else {
+        // Submit the job with a job definition that matches the requirements
+        let spec = match aws::job_definition_spec(creds, &tinfo.requirements) {
+            Ok(spec) => spec,
+            Err(err) => { return Err(JobStatus::CreationFailed(format!("{}", err))); },
+        };
+        let definition: String = match aws::ensure_job_definition(clients, creds, image, &spec).await {
+            Ok(definition) => definition,
+            Err(err)       => { return Err(JobStatus::CreationFailed(format!("{}", err))); },
+        };
+        let id: String = match aws::submit(clients, creds, &tinfo.name, &definition, command, tinfo.timeout).await {
+            Ok(id)   => id,
+            Err(err) => { return Err(JobStatus::CreationFailed(format!("{}", err))); },
+        };
+        if let Err(err) = update_client(tx, JobStatus::Created).await { error!("{}", err); }
+
+        // Wait for it to complete, killing it if it runs out of time (which only starts counting once it runs)
+        let mut started: Option<Instant> = None;
+        let (code, log_stream, reason): (Option<i32>, Option<String>, Option<String>) = loop {
+            tokio::time::sleep(aws::POLL_INTERVAL).await;
+            match aws::poll(clients, &id).await {
+                Ok(JobState::Queued)  => {},
+                Ok(JobState::Running) => {
+                    if started.is_none() {
+                        started = Some(Instant::now());
+                        if let Err(err) = update_client(tx, JobStatus::Started).await { error!("{}", err); }
+                    }
+                },
+                Ok(JobState::Finished{ code, log_stream, reason }) => { break (code, log_stream, reason); },
+                Err(err) => { return Err(JobStatus::CompletionFailed(format!("{}", err))); },
+            }
+
+            if let (Some(started), Some(timeout)) = (started, tinfo.timeout) {
+                if started.elapsed() >= Duration::from_secs(timeout) {
+                    warn!("Task '{}' did not complete within {} seconds; terminating AWS Batch job '{}'", tinfo.name, timeout, id);
+                    if let Err(err) = aws::terminate(clients, &id, "Task timed out").await { error!("{}", err); }
+                    return Err(JobStatus::TimedOut(timeout));
+                }
+            }
+        };
+
+        // Read the logs, which are only shown to the user if the task failed
+        let code: i32 = match code {
+            Some(code) => code,
+            None       => { return Err(JobStatus::CompletionFailed(format!("AWS Batch job '{}' stopped without running: {}", id, reason.unwrap_or_else(|| "unknown reason".into())))); },
+        };
+        let logs: String = match log_stream {
+            Some(stream) => match aws::logs(clients, stream).await {
+                Ok(logs) => logs,
+                Err(err) => { return Err(JobStatus::CompletionFailed(format!("{}", err))); },
+            },
+            None => String::new(),
+        };
+        (code, logs, String::new())
+    };
+    debug!("Task return code: {}", code);
+    debug!("Task stdout/stderr:\n\nstdout:\n{}\n\nstderr:\n{}\n", BlockFormatter::new(&stdout), BlockFormatter::new(&stderr));
+
+    // If the return code is no bueno, error and show the output
+    if code != 0 {
+        if let Err(err) = update_client(tx, JobStatus::Completed(None)).await { error!("{}", err); }
+        return Err(JobStatus::Failed(code, stdout, stderr));
+    }
+
+    // Otherwise, download what the task wrote and decode the value it returned
+    let output: String = match aws::collect(clients, creds, staging).await {
+        Ok(output) => output,
+        Err(err)   => { return Err(JobStatus::CompletionFailed(format!("{}", err))); },
+    };
+    if let Err(err) = update_client(tx, JobStatus::Completed(None)).await { error!("{}", err); }
+    let value: FullValue = decode_output(&output)?;
+    debug!("Task '{}' returned value: '{:?}'", tinfo.name, value);
+    Ok(value)
+}
+
+/// Runs the given task directly on a remote machine over SSH, without any container runtime.
+/// 
+/// # Arguments
+/// - `node_config`: The configuration for this node's environment. For us, contains the location ID of this location and where to find data & intermediate results.
+/// - `creds`: The SshCredentials that describe the machine to run the task on.
+/// - `tx`: The transmission channel over which we should update the client of our progress.
+/// - `container_path`: The path to the package's image file, of which the filesystem is copied to the remote machine.
+/// - `tinfo`: The TaskInfo that describes the task itself to execute.
+/// 
+/// # Returns
+/// The return value of the task when it completes.
+/// 
+/// # Errors
+/// This function errors if the task fails for whatever reason or we didn't even manage to launch it.
+async fn execute_task_ssh(node_config: &NodeConfig, creds: &SshCredentials, tx: &Sender<Result<TaskReply, Status>>, container_path: impl AsRef<Path>, tinfo: TaskInfo) -> Result<FullValue, JobStatus> {
+    let mut tinfo: TaskInfo = tinfo;
+    debug!("Running task '{}' on '{}' over SSH...", tinfo.name, creds.address);
+
+    // Make sure the package is available on the remote machine
+    let package_dir: PathBuf = match ssh::install(creds, &tinfo.package_name, &tinfo.package_version, container_path).await {
+        Ok(dir)  => dir,
+        Err(err) => { return Err(JobStatus::CreationFailed(format!("Failed to install package on remote machine: {}", err))); },
+    };
+
+    // Preprocess the arguments, and copy the data they refer to over
+    let binds: Vec<VolumeBind> = match docker::preprocess_args(&mut tinfo.args, &tinfo.input, &tinfo.result, Some(&node_config.node.worker().paths.data), &node_config.node.worker().paths.results).await {
+        Ok(binds) => binds,
+        Err(err)  => { return Err(JobStatus::CreationFailed(format!("Failed to preprocess arguments: {}", err))); },
+    };
+    let task_dir: PathBuf = match ssh::prepare(creds, &tinfo.name, &mut tinfo.args, &binds).await {
+        Ok(dir)  => dir,
+        Err(err) => { return Err(JobStatus::CreationFailed(format!("Failed to prepare task on remote machine: {}", err))); },
+    };
+    let command: Vec<String> = branelet_command(node_config, &tinfo, &[])?;
+    if let Err(err) = update_client(tx, JobStatus::Created).await { error!("{}", err); }
+    if let Err(err) = update_client(tx, JobStatus::Started).await { error!("{}", err); }
+
+    // Run it, killing the connection if it runs out of time or the client hangs up (e.g., because the workflow was cancelled)
+    let run = ssh::execute(creds, &package_dir, &task_dir, command);
+    let deadline = tokio::time::sleep(tinfo.timeout.map(Duration::from_secs).unwrap_or(Duration::MAX));
+    tokio::pin!(run);
+    tokio::pin!(deadline);
+    let res = tokio::select! {
+        res = &mut run => res,
+        _ = &mut deadline, if tinfo.timeout.is_some() => {
+            let timeout: u64 = tinfo.timeout.unwrap();
+            warn!("Task '{}' did not complete within {} seconds; abandoning it on '{}'", tinfo.name, timeout, creds.address);
+            if let Err(err) = ssh::cleanup(creds, &task_dir).await { error!("{}", err); }
+            return Err(JobStatus::TimedOut(timeout));
+        },
+        _ = tx.closed() => {
+            warn!("Client of task '{}' hung up; abandoning it on '{}'", tinfo.name, creds.address);
+            if let Err(err) = ssh::cleanup(creds, &task_dir).await { error!("{}", err); }
+            return Err(JobStatus::CompletionFailed("Task was cancelled by its client".into()));
+        },
+    };
+    let (code, stdout, stderr): (i32, String, String) = match res {
+        Ok(res)  => res,
+        Err(err) => { return Err(JobStatus::CompletionFailed(format!("{}", err))); },
+    };
+    debug!("Task return code: {}", code);
+    debug!("Task stdout/stderr:\n\nstdout:\n{}\n\nstderr:\n{}\n", BlockFormatter::new(&stdout), BlockFormatter::new(&stderr));
+
+    // Retrieve the result, if any, before cleaning up
+    if code == 0 {
+        if let Some(result) = &tinfo.result {
+            if let Err(err) = ssh::retrieve(creds, &task_dir, node_config.node.worker().paths.results.join(result)).await {
+                return Err(JobStatus::CompletionFailed(format!("Failed to retrieve result '{}': {}", result, err)));
+            }
+        }
+    }
+    if let Err(err) = ssh::cleanup(creds, &task_dir).await { warn!("Failed to clean up task directory on '{}': {}", creds.address, err); }
+    if let Err(err) = update_client(tx, JobStatus::Completed(None)).await { error!("{}", err); }
+
+    // If the return code is no bueno, error and show the output
+    if code != 0 { return Err(JobStatus::Failed(code, stdout, stderr)); }
+
+    // Otherwise, decode the output of branelet to the value returned
+    let value: FullValue = decode_output(&stdout)?;
     debug!("Task '{}' returned value: '{:?}'", tinfo.name, value);
     Ok(value)
 }
@@ -695,17 +1502,21 @@ async fn execute_task_local(node_config: &NodeConfig, dinfo: DockerInfo, tx: &Se
 /// - `cinfo`: The ControlNodeInfo that specifies where to find services over at the control node.
 /// - `tinfo`: The TaskInfo that describes the task itself to execute.
 /// - `keep_container`: Whether to keep the container after execution or not.
+/// - `audit`: The audit log to record the policy decision on the task in, if any.
+/// - `aws_clients`: The cache of clients to use for tasks that run on AWS.
 /// 
 /// # Returns
 /// Nothing directly, although it does communicate updates, results and errors back to the client via the given `tx`.
 /// 
 /// # Errors
 /// This fnction may error for many many reasons, but chief among those are unavailable backends or a crashing task.
-async fn execute_task(node_config: &NodeConfig, proxy: Arc<ProxyClient>, tx: Sender<Result<TaskReply, Status>>, workflow: Workflow, cinfo: ControlNodeInfo, tinfo: TaskInfo, keep_container: bool) -> Result<(), ExecuteError> {
+#[allow(clippy::too_many_arguments)]
+async fn execute_task(node_config: &NodeConfig, proxy: Arc<ProxyClient>, tx: Sender<Result<TaskReply, Status>>, workflow: Workflow, cinfo: ControlNodeInfo, tinfo: TaskInfo, keep_container: bool, audit: Option<Arc<AuditLog>>, aws_clients: Arc<AwsClientCache>) -> Result<(), ExecuteError> {
     let mut tinfo          = tinfo;
 
     // We update the user first on that the job has been received
     info!("Starting execution of task '{}'", tinfo.name);
+    telemetry::record_task();
     if let Err(err) = update_client(&tx, JobStatus::Received).await { error!("{}", err); }
 
 
@@ -729,6 +1540,8 @@ async fn execute_task(node_config: &NodeConfig, proxy: Arc<ProxyClient>, tx: Sen
     // Deduce the image name from that
     tinfo.kind  = Some(info.kind);
     tinfo.image = Some(Image::new(&tinfo.package_name, Some(tinfo.package_version.clone()), info.digest.clone()));
+    // Take the secrets that the task needs from the package itself, so that nobody can ask for others
+    tinfo.secrets = info.functions.get(&tinfo.name).and_then(|func| func.secrets.clone()).unwrap_or_default();
 
     // Now load the credentials file to get things going
     let creds: BackendFile = match BackendFile::from_path(&node_config.node.worker().paths.backend) {
@@ -736,40 +1549,118 @@ async fn execute_task(node_config: &NodeConfig, proxy: Arc<ProxyClient>, tx: Sen
         Err(err)  => { return err!(tx, ExecuteError::BackendFileError{ path: node_config.node.worker().paths.backend.clone(), err }); },
     };
 
-    // Download the container from the central node
-    let (container_path, container_hash): (PathBuf, String) = download_container(node_config, proxy, &cinfo.api_endpoint, tinfo.image.as_mut().unwrap()).await?;
+    // Open the content-addressed store that keeps the container and the task's result
+    let store: ContentStore = match ContentStore::new(&node_config.node.worker().paths.store).await {
+        Ok(store) => store,
+        Err(err)  => { return err!(tx, ExecuteError::StoreError{ err }); },
+    };
+
+    // Make sure the task fits on disk; we estimate it by its scratch space, and assume the image fits in the reserve until it is downloaded
+    if let Err(err) = admit_task(node_config.node.worker(), &store, node_config.node.worker().max_scratch_size, &image_tag(tinfo.image.as_ref().unwrap())).await { return err!(tx, err); }
+
+    // Get the container, either from the OCI registry (if any, and only for local containers) or by downloading it from the central node
+    let registry: Option<(&ImageRegistryConfig, DockerInfo)> = match (&node_config.image_registry, &creds.method) {
+        (Some(registry), Credentials::Local{ path, version }) => Some((registry, docker_info(path, version))),
+        _                                                     => None,
+    };
+    let pulled: Option<(String, String)> = match registry {
+        Some((registry, dinfo)) => match pull_container(registry, &dinfo, tinfo.image.as_ref().unwrap()).await {
+            Ok(pulled) => Some(pulled),
+            Err(err)   => { warn!("{}; downloading it from the central node instead", err); None },
+        },
+        None => None,
+    };
+    let (container_source, container_hash): (ImageSource, String) = match pulled {
+        Some((reference, hash)) => (ImageSource::Registry(reference), hash),
+        None                    => {
+            let (container_path, container_hash): (PathBuf, String) = download_container(&store, proxy, &cinfo.api_endpoint, tinfo.image.as_mut().unwrap(), node_config.features.enable_cache).await?;
+            (ImageSource::Path(container_path), container_hash)
+        },
+    };
 
 
 
     /* AUTHORIZATION */
-    // First: make sure that the workflow is allowed by the checker
+    // First: make sure that the workflow is allowed by the checker (and record what it said)
+    let record = |decision: Decision| -> AuditRecord {
+        AuditRecord::new("brane-job", AuditKind::TaskExecution, Some(cinfo.api_endpoint.clone()), &tinfo.name, decision)
+            .with_details(format!("package {} v{}", tinfo.package_name, tinfo.package_version))
+    };
     match assert_workflow_permission(node_config, &workflow, container_hash).await {
         Ok(true) => {
             debug!("Checker accepted incoming workflow");
+            audit::write(audit.as_deref(), record(Decision::Allowed));
             if let Err(err) = update_client(&tx, JobStatus::Authorized).await { error!("{}", err); }
         },
         Ok(false) => {
             debug!("Checker rejected incoming workflow");
+            audit::write(audit.as_deref(), record(Decision::Denied));
             if let Err(err) = update_client(&tx, JobStatus::Denied).await { error!("{}", err); }
             return Err(ExecuteError::AuthorizationFailure{ checker: node_config.node.worker().services.reg.clone() });
         },
 
         Err(err) => {
+            audit::write(audit.as_deref(), record(Decision::Failed));
             return err!(tx, JobStatus::AuthorizationFailed, ExecuteError::AuthorizationError{ checker: node_config.node.worker().services.reg.clone(), err });
         },
     }
 
+    // Then: make sure that none of the inputs is restricted to other locations (the planner should never send us such a task, but we don't rely on it)
+    let location: &str = &node_config.node.worker().location_id;
+    for name in tinfo.input.keys() {
+        if let Some(allowed) = workflow.residency.get(name) {
+            if !allowed.iter().any(|loc| loc == location) {
+                audit::write(audit.as_deref(), record(Decision::Denied));
+                return err!(tx, JobStatus::AuthorizationFailed, ExecuteError::ResidencyViolation{ name: tinfo.name.clone(), data: name.clone(), loc: location.into() });
+            }
+        }
+    }
+
+
+
+    /* SECRETS */
+    // Resolve any secrets the task needs; we only do so now, so that unauthorized tasks never cause them to be read
+    let secrets: HashMap<String, Secret> = if !tinfo.secrets.is_empty() {
+        // Only the local backend knows how to inject them
+        if !matches!(creds.method, Credentials::Local{ .. }) { return err!(tx, ExecuteError::UnsupportedSecrets{ name: tinfo.name.clone() }); }
+        debug!("Resolving secrets {} for task '{}'...", tinfo.secrets.iter().map(|s| format!("'{}'", s)).collect::<Vec<String>>().join(", "), tinfo.name);
+        let digest: Option<String> = tinfo.image.as_ref().and_then(|image| image.digest.clone());
+        match resolve_secrets(node_config.node.worker(), &tinfo, digest.as_deref()).await {
+            Ok(secrets) => secrets,
+            Err(err)    => { return err!(tx, err); },
+        }
+    } else {
+        HashMap::new()
+    };
+
+
+
+    /* STAGING */
+    // Fetch any inputs that live on scientific storage systems (which count towards the task's scratch space) or that are registered by URL (which we cache for later tasks)
+    let temp_data: &Path = &node_config.node.worker().paths.temp_data;
+    let _staged: StagedInputs = match docker::stage_inputs(&mut tinfo.input, &node_config.node.worker().proxies, temp_data, temp_data.join(".cache"), Some(node_config.node.worker().max_scratch_size)).await {
+        Ok(staged) => staged,
+        Err(err)   => { return err!(tx, err); },
+    };
+
 
 
     /* SCHEDULE */
     // Match on the specific type to find the specific backend
+    let result: Option<String> = tinfo.result.clone();
     let value: FullValue = match creds.method {
         Credentials::Local { path, version } => {
-            // Prepare the DockerInfo
-            let dinfo: DockerInfo = DockerInfo::new(path.unwrap_or_else(|| PathBuf::from("/var/run/docker.sock")), version.map(|(major, minor)| ClientVersion{ major_version: major, minor_version: minor }).unwrap_or(*API_DEFAULT_VERSION));
-
-            // Do the call
-            match execute_task_local(node_config, dinfo, &tx, container_path, tinfo, keep_container).await {
+            // Burst to the cloud if the local backend is saturated, as long as the task does not need any local data (or secrets)
+            let burst: Option<&BurstConfig> = creds.burst.as_ref().filter(|burst| LOCAL_TASKS.load(Ordering::SeqCst) >= burst.max_local_tasks && tinfo.input.is_empty() && tinfo.result.is_none() && tinfo.secrets.is_empty());
+            let res: Result<FullValue, JobStatus> = if let Some(burst) = burst {
+                info!("Local backend is saturated ({} tasks running); bursting task '{}' to AWS", LOCAL_TASKS.load(Ordering::SeqCst), tinfo.name);
+                execute_task_aws(node_config, &aws_clients, &burst.aws, docker_info(&path, &version), &tx, container_source, tinfo).await
+            } else {
+                // Do the call
+                let _guard = LocalTaskGuard::new();
+                execute_task_local(node_config, docker_info(&path, &version), &tx, container_source, tinfo, secrets, keep_container).await
+            };
+            match res {
                 Ok(value)   => value,
                 Err(status) => {
                     error!("Job failed with status: {:?}", status);
@@ -779,10 +1670,20 @@ async fn execute_task(node_config: &NodeConfig, proxy: Arc<ProxyClient>, tx: Sen
             }
         },
 
-        Credentials::Ssh { .. } => {
-            error!("SSH backend is not yet supported");
-            if let Err(err) = update_client(&tx, JobStatus::CreationFailed("SSH backend is not yet supported".into())).await { error!("{}", err); }
-            return Ok(())
+        Credentials::Ssh(remote) => {
+            // Images are only pulled from the registry for the local backend, so this is always a file
+            let container_path: PathBuf = match container_source {
+                ImageSource::Path(path)     => path,
+                ImageSource::Registry(from) => { unreachable!("Image '{}' was pulled from a registry for the SSH backend", from); },
+            };
+            match execute_task_ssh(node_config, &remote, &tx, container_path, tinfo).await {
+                Ok(value)   => value,
+                Err(status) => {
+                    error!("Job failed with status: {:?}", status);
+                    if let Err(err) = update_client(&tx, status).await { error!("{}", err); }
+                    return Ok(());
+                },
+            }
         },
 
         Credentials::Kubernetes { .. } => {
@@ -795,11 +1696,31 @@ async fn execute_task(node_config: &NodeConfig, proxy: Arc<ProxyClient>, tx: Sen
             if let Err(err) = update_client(&tx, JobStatus::CreationFailed("Slurm backend is not yet supported".into())).await { error!("{}", err); }
             return Ok(())
         },
+
+        Credentials::AwsBatch(aws) => {
+            // The image is pushed to the registry through the default Docker daemon
+            match execute_task_aws(node_config, &aws_clients, &aws, docker_info(&None, &None), &tx, container_source, tinfo).await {
+                Ok(value)   => value,
+                Err(status) => {
+                    error!("Job failed with status: {:?}", status);
+                    if let Err(err) = update_client(&tx, status).await { error!("{}", err); }
+                    return Ok(());
+                },
+            }
+        },
     };
     debug!("Job completed");
 
 
 
+    /* STORE */
+    // Move the result (if any) into the content-addressed store
+    if let Some(result) = result {
+        if let Err(err) = store_result(&store, &node_config.node.worker().paths.results, &result).await { return err!(tx, err); }
+    }
+
+
+
     /* RETURN */
     // Alright, we are done; the rest is up to the little branelet itself.
     if let Err(err) = update_client(&tx, JobStatus::Finished(value)).await { error!("{}", err); }
@@ -808,6 +1729,35 @@ async fn execute_task(node_config: &NodeConfig, proxy: Arc<ProxyClient>, tx: Sen
 
 
 
+/// Moves an intermediate result that a task has just produced into the content-addressed store.
+/// 
+/// The result is tagged by its name, and its directory in the results folder is replaced by a symlink to its object in the store, so that the registry and later tasks find it where they always did.
+/// 
+/// # Arguments
+/// - `store`: The content-addressed store to move the result into.
+/// - `results_path`: The directory where the task wrote its result.
+/// - `name`: The name of the intermediate result.
+/// 
+/// # Errors
+/// This function errors if we failed to move the result into the store or to link it back.
+async fn store_result(store: &ContentStore, results_path: &Path, name: &str) -> Result<(), ExecuteError> {
+    let path: PathBuf = results_path.join(name);
+    if !path.exists() { warn!("Task did not produce intermediate result '{}' in '{}'; not adding it to the store", name, path.display()); return Ok(()); }
+
+    // Move it into the store...
+    debug!("Moving intermediate result '{}' into the store...", name);
+    let (_, target): (String, PathBuf) = match store.add(&path, TagKind::IntermediateResult, name).await {
+        Ok(res)  => res,
+        Err(err) => { return Err(ExecuteError::ResultStoreError{ name: name.into(), err }); },
+    };
+
+    // ...and link it back
+    if let Err(err) = tfs::symlink(&target, &path).await { return Err(ExecuteError::ResultLinkError{ path, target, err }); }
+    Ok(())
+}
+
+
+
 /// Commits the given intermediate result.
 /// 
 /// # Arguments
@@ -815,10 +1765,15 @@ async fn execute_task(node_config: &NodeConfig, proxy: Arc<ProxyClient>, tx: Sen
 /// - `results_path`: Path to the shared data results directory. This is where the results live.
 /// - `name`: The name of the intermediate result to promote.
 /// - `data_name`: The name of the intermediate result to promote it as.
+/// - `residency`: The locations the dataset may be processed at, inherited from the data the result was derived from. `None` if unrestricted.
+/// - `revision`: The revision of the dataset that the commit expects to replace (0 if it should not exist yet), or `None` to replace any.
+/// 
+/// # Returns
+/// The revision of the dataset after the commit.
 /// 
 /// # Errors
-/// This function may error for many many reasons, but chief among those are unavailable registries and such.
-async fn commit_result(node_config: &NodeConfig, name: impl AsRef<str>, data_name: impl AsRef<str>) -> Result<(), CommitError> {
+/// This function may error for many many reasons, but chief among those are unavailable registries and such. It also errors if the dataset is not at the expected `revision`, without touching it.
+async fn commit_result(node_config: &NodeConfig, name: impl AsRef<str>, data_name: impl AsRef<str>, residency: Option<Vec<String>>, revision: Option<u64>) -> Result<u64, CommitError> {
     let name         : &str  = name.as_ref();
     let data_name    : &str  = data_name.as_ref();
     debug!("Commit intermediate result '{}' as '{}'...", name, data_name);
@@ -827,7 +1782,7 @@ async fn commit_result(node_config: &NodeConfig, name: impl AsRef<str>, data_nam
 
     // Step 1: Check if the dataset already exists (locally)
     let data_path: &Path = &node_config.node.worker().paths.data;
-    let info: Option<AssetInfo> = {
+    let info: Option<(PathBuf, AssetInfo)> = {
         // Get the entries in the dataset directory
         let mut entries: tfs::ReadDir = match tfs::read_dir(data_path).await {
             Ok(entries) => entries,
@@ -835,8 +1790,8 @@ async fn commit_result(node_config: &NodeConfig, name: impl AsRef<str>, data_nam
         };
 
         // Iterate through them
-        let mut found_info : Option<AssetInfo> = None;
-        let mut i          : usize             = 0;
+        let mut found_info : Option<(PathBuf, AssetInfo)> = None;
+        let mut i          : usize                        = 0;
         #[allow(irrefutable_let_patterns)]
         while let entry = entries.next_entry().await {
             // Unwrap it
@@ -866,12 +1821,13 @@ async fn commit_result(node_config: &NodeConfig, name: impl AsRef<str>, data_nam
                         if path.is_relative() {
                             *path = entry_path.join(&path);
                         }
-                    }
+                    },
+                    AccessKind::XRootD { .. } | AccessKind::GridFtp { .. } | AccessKind::Url { .. } => {},
                 }
 
                 // Keep it if it has the target name
                 if info.name == data_name {
-                    found_info = Some(info);
+                    found_info = Some((entry_path, info));
                     break;
                 }
             }
@@ -884,17 +1840,55 @@ async fn commit_result(node_config: &NodeConfig, name: impl AsRef<str>, data_nam
         found_info
     };
 
+    // We never overwrite datasets that live elsewhere
+    if let Some((_, info)) = &info {
+        match &info.access {
+            AccessKind::File { .. } => {},
+            AccessKind::XRootD { url } | AccessKind::GridFtp { url } | AccessKind::Url { url, .. } => {
+                return Err(CommitError::RemoteDataError{ name: data_name.into(), url: url.clone() });
+            },
+        }
+    }
+
+    // Only replace the revision that the committer saw, or else we would silently undo another commit
+    let revision: u64 = next_revision(data_name, info.as_ref().map(|(_, info)| info.revision).unwrap_or(0), revision)?;
 
 
-    // Step 2: Match on whether it already exists or not and copy the file
-    let results_path: &Path = &node_config.node.worker().paths.results;
-    if let Some(info) = info {
-        debug!("Dataset '{}' already exists; overwriting file...", data_name);
 
-        // Copy the source to the target destination (file, in this case)
-        match &info.access {
-            AccessKind::File { path: data_path } => {
-                // Remove the old directory first (or file)
+    // Step 2: Tag the result as the dataset in the store (which is free if it's already there, as it should be)
+    let store: ContentStore = match ContentStore::new(&node_config.node.worker().paths.store).await {
+        Ok(store) => store,
+        Err(err)  => { return Err(CommitError::StoreError{ err }); },
+    };
+    let target: PathBuf = match store.resolve(TagKind::IntermediateResult, name).await {
+        Ok(Some((digest, _))) => match store.tag(TagKind::Data, data_name, &digest).await {
+            Ok(target) => target,
+            Err(err)   => { return Err(CommitError::StoreError{ err }); },
+        },
+        Ok(None) => {
+            // It was produced before this node had a store, so copy it in
+            debug!("Intermediate result '{}' is not in the store; copying it in...", name);
+            let temp: PathBuf = store.temp_path();
+            if let Err(err) = copy_dir_recursively_async(node_config.node.worker().paths.results.join(name), &temp).await {
+                return Err(CommitError::DataCopyError{ err });
+            };
+            match store.add(&temp, TagKind::Data, data_name).await {
+                Ok((_, target)) => target,
+                Err(err)        => { return Err(CommitError::StoreError{ err }); },
+            }
+        },
+        Err(err) => { return Err(CommitError::StoreError{ err }); },
+    };
+
+
+
+    // Step 3: Point the dataset to it
+    let (dir, info): (PathBuf, AssetInfo) = if let Some((dir, mut info)) = info {
+        debug!("Dataset '{}' already exists; overwriting its data...", data_name);
+
+        // Remove the old data if it was not in the store yet (data in the store is collected once nothing references it anymore)
+        if let AccessKind::File { path: data_path } = &info.access {
+            if !data_path.starts_with(&node_config.node.worker().paths.store) {
                 if data_path.is_file() {
                     if let Err(err) = tfs::remove_file(&data_path).await {
                         return Err(CommitError::FileRemoveError{ path: data_path.clone(), err });
@@ -912,14 +1906,15 @@ async fn commit_result(node_config: &NodeConfig, name: impl AsRef<str>, data_nam
                     // Nothing to remove
                     warn!("Previous dataset '{}' is marked as existing, but its data doesn't exist", data_path.display());
                 }
-
-                // Simply copy the one directory over the other and it's updated
-                if let Err(err) = copy_dir_recursively_async(results_path.join(name), data_path).await {
-                    return Err(CommitError::DataCopyError{ err });
-                };
-            },
+            }
         }
 
+        // Update the existing AssetInfo; it may only become more restricted by what it was derived from
+        info.access    = AccessKind::File{ path: target };
+        info.residency = merge_residency(info.residency.take(), residency.as_deref());
+        info.revision  = revision;
+        (dir, info)
+
     } else {
         debug!("Dataset '{}' doesn't exist; creating new entry...", data_name);
 
@@ -930,40 +1925,47 @@ async fn commit_result(node_config: &NodeConfig, name: impl AsRef<str>, data_nam
             if let Err(err) = tfs::create_dir_all(&dir).await { return Err(CommitError::DataDirCreateError{ path: dir, err }); }
         }
 
-        // Copy the directory first, to not have the registry use it yet while copying
-        if let Err(err) = copy_dir_recursively_async(results_path.join(name), dir.join("data")).await {
-            return Err(CommitError::DataCopyError{ err });
-        };
-
         // Create a new AssetInfo struct
         let info: AssetInfo = AssetInfo {
             name        : data_name.into(),
+            version     : None,
             owners      : None, // TODO: Merge parent datasets??
             description : None, // TODO: Add parents & algorithm in description??
             created     : Utc::now(),
+            residency,
+            size        : None,
+            revision,
 
-            access : AccessKind::File{ path: dir.join("data") },
+            access : AccessKind::File{ path: target },
         };
+        (dir, info)
+    };
 
-        // Now write that
-        let info_path  : PathBuf   = dir.join("data.yml");
-        let mut handle : tfs::File = match tfs::File::create(&info_path).await {
-            Ok(handle) => handle,
-            Err(err)   => { return Err(CommitError::DataInfoCreateError{ path: info_path, err }); },
-        };
-        let sinfo: String = match serde_json::to_string_pretty(&info) {
-            Ok(sinfo) => sinfo,
-            Err(err)  => { return Err(CommitError::DataInfoSerializeError{ err }); },
-        };
-        if let Err(err) = handle.write_all(sinfo.as_bytes()).await {
-            return Err(CommitError::DataInfoWriteError{ path: info_path, err });
-        }
+    // Now write that; we write it next to the real one first and then move it in place, so the registry never sees a half-written file
+    let info_path  : PathBuf   = dir.join("data.yml");
+    let temp_path  : PathBuf   = dir.join(".data.yml.part");
+    let mut handle : tfs::File = match tfs::File::create(&temp_path).await {
+        Ok(handle) => handle,
+        Err(err)   => { return Err(CommitError::DataInfoCreateError{ path: temp_path, err }); },
+    };
+    let sinfo: String = match serde_json::to_string_pretty(&info) {
+        Ok(sinfo) => sinfo,
+        Err(err)  => { return Err(CommitError::DataInfoSerializeError{ err }); },
+    };
+    if let Err(err) = handle.write_all(sinfo.as_bytes()).await {
+        return Err(CommitError::DataInfoWriteError{ path: temp_path, err });
+    }
+    if let Err(err) = handle.sync_all().await {
+        return Err(CommitError::DataInfoWriteError{ path: temp_path, err });
+    }
+    if let Err(err) = tfs::rename(&temp_path, &info_path).await {
+        return Err(CommitError::DataInfoWriteError{ path: info_path, err });
     }
 
 
 
-    // Step 3: Enjoy
-    Ok(())
+    // Step 4: Enjoy
+    Ok(revision)
 }
 
 
@@ -978,6 +1980,12 @@ pub struct WorkerServer {
     node_config_path : PathBuf,
     /// Whether to remove containers after execution or not (but negated).
     keep_containers  : bool,
+    /// The audit log to record task executions and result commits in, if any.
+    audit            : Option<Arc<AuditLog>>,
+    /// A lock per dataset, so that concurrent commits to the same dataset cannot interleave their changes to its directory.
+    commit_locks     : Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+    /// The clients for AWS, which are shared between the tasks that run there.
+    aws_clients      : Arc<AwsClientCache>,
 
     /// The proxy client to connect to the proxy service with.
     proxy : Arc<ProxyClient>,
@@ -990,14 +1998,18 @@ impl WorkerServer {
     /// - `node_config_path`: The path to the `node.yml` file that describes this node's environment.
     /// - `keep_containers`: If true, then we will not remove containers after execution (useful for debugging).
     /// - `proxy`: The proxy client to connect to the proxy service with.
+    /// - `audit`: The audit log to record task executions and result commits in, if any.
     /// 
     /// # Returns
     /// A new JobHandler instance.
     #[inline]
-    pub fn new(node_config_path: impl Into<PathBuf>, keep_containers: bool, proxy: Arc<ProxyClient>) -> Self {
+    pub fn new(node_config_path: impl Into<PathBuf>, keep_containers: bool, proxy: Arc<ProxyClient>, audit: Option<Arc<AuditLog>>) -> Self {
         Self {
             node_config_path : node_config_path.into(),
             keep_containers,
+            audit,
+            commit_locks     : Mutex::new(HashMap::new()),
+            aws_clients      : Arc::new(AwsClientCache::new()),
             proxy,
         }
     }
@@ -1008,6 +2020,8 @@ impl JobService for WorkerServer {
     type ExecuteStream = ReceiverStream<Result<TaskReply, Status>>;
 
     async fn preprocess(&self, request: Request<PreprocessRequest>) -> Result<Response<PreprocessReply>, Status> {
+        let span: Span = info_span!("preprocess", data = %request.get_ref().data_name);
+        otel::set_parent(&span, &extract_trace(&request));
         let request = request.into_inner();
         debug!("Receiving preprocess request");
 
@@ -1049,7 +2063,7 @@ impl JobService for WorkerServer {
                 };
 
                 // Run the function that way
-                let access: AccessKind = match preprocess_transfer_tar(&node_config, self.proxy.clone(), location, address, data_name).await {
+                let access: AccessKind = match preprocess_transfer_tar(&node_config, self.proxy.clone(), location, address, data_name, request.api.as_deref()).instrument(span).await {
                     Ok(access) => access,
                     Err(err)   => {
                         error!("{}", err);
@@ -1084,6 +2098,8 @@ impl JobService for WorkerServer {
 
 
     async fn execute(&self, request: Request<TaskRequest>) -> Result<Response<Self::ExecuteStream>, Status> {
+        let span: Span = info_span!("execute", task = %request.get_ref().name, package = %request.get_ref().package_name);
+        otel::set_parent(&span, &extract_trace(&request));
         let request = request.into_inner();
         debug!("Receiving execute request");
 
@@ -1155,8 +2171,8 @@ impl JobService for WorkerServer {
         };
 
         // Collect some request data into ControlNodeInfo's and TaskInfo's.
-        let cinfo : ControlNodeInfo = ControlNodeInfo::new(request.api);
-        let tinfo : TaskInfo        = TaskInfo::new(
+        let cinfo     : ControlNodeInfo = ControlNodeInfo::new(request.api);
+        let mut tinfo : TaskInfo        = TaskInfo::new(
             request.name,
             request.package_name,
             version,
@@ -1166,14 +2182,18 @@ impl JobService for WorkerServer {
             args,
             requirements,
         );
+        tinfo.timeout = request.timeout;
+        tinfo.app_id  = request.app_id;
 
         // Now move the rest to a separate task so we can return the start of the stream
-        let keep_containers : bool             = self.keep_containers;
-        let proxy           : Arc<ProxyClient> = self.proxy.clone();
+        let keep_containers : bool                  = self.keep_containers;
+        let proxy           : Arc<ProxyClient>      = self.proxy.clone();
+        let audit           : Option<Arc<AuditLog>> = self.audit.clone();
+        let aws_clients     : Arc<AwsClientCache>   = self.aws_clients.clone();
         tokio::spawn(async move {
             let node_config: NodeConfig = node_config;
-            execute_task(&node_config, proxy, tx, workflow, cinfo, tinfo, keep_containers).await
-        });
+            execute_task(&node_config, proxy, tx, workflow, cinfo, tinfo, keep_containers, audit, aws_clients).await
+        }.instrument(span));
 
         // Return the stream so the user can get updates
         Ok(Response::new(ReceiverStream::new(rx)))
@@ -1182,6 +2202,8 @@ impl JobService for WorkerServer {
 
 
     async fn commit(&self, request: Request<CommitRequest>) -> Result<Response<CommitReply>, Status> {
+        let span: Span = info_span!("commit", result = %request.get_ref().name);
+        otel::set_parent(&span, &extract_trace(&request));
         let request = request.into_inner();
         debug!("Receiving commit request");
 
@@ -1194,13 +2216,30 @@ impl JobService for WorkerServer {
             },
         };
 
-        // Run the function
-        if let Err(err) = commit_result(&node_config, &request.name, &request.data_name).await {
-            error!("{}", err);
-            return Err(Status::internal("An internal error occurred"));
-        }
+        // Commits are not subject to a policy (yet), but we do record them
+        audit::write(self.audit.as_deref(), AuditRecord::new("brane-job", AuditKind::ResultCommit, None, &request.name, Decision::Unchecked).with_details(format!("as dataset {}", request.data_name)));
+
+        // Run the function (one commit per dataset at a time, since they read and then update its directory)
+        let residency: Option<Vec<String>> = if request.residency.is_empty() { None } else { Some(request.residency) };
+        let lock: Arc<tokio::sync::Mutex<()>> = {
+            let mut locks: MutexGuard<HashMap<String, Arc<tokio::sync::Mutex<()>>>> = self.commit_locks.lock().unwrap();
+            locks.entry(request.data_name.clone()).or_default().clone()
+        };
+        let _guard = lock.lock().await;
+        let revision: u64 = match commit_result(&node_config, &request.name, &request.data_name, residency, request.revision).instrument(span).await {
+            Ok(revision) => revision,
+            // Conflicts are the committer's to resolve, so tell them what happened
+            Err(err) if err.is_conflict() => {
+                warn!("{}", err);
+                return Err(conflict_status(err.to_string()));
+            },
+            Err(err) => {
+                error!("{}", err);
+                return Err(Status::internal("An internal error occurred"));
+            },
+        };
 
         // Be done without any error
-        Ok(Response::new(CommitReply{ ok: true, error: None }))
+        Ok(Response::new(CommitReply{ ok: true, error: None, revision: Some(revision) }))
     }
 }
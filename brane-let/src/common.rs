@@ -12,12 +12,61 @@
 //!   Contains common definitions across all executions.
 // 
 
+use std::ffi::CString;
+use std::os::unix::ffi::OsStrExt as _;
+use std::path::{Path, PathBuf};
+
+use tokio::sync::watch;
+
 use crate::errors::LetError;
 
 use brane_ast::DataType;
 use brane_exe::FullValue;
 use specifications::common::Parameter;
 use specifications::package::PackageKind;
+use specifications::status::{OUTPUT_PREFIX, PROGRESS_PREFIX, SCRATCH_LIMIT_ENV, TaskOutput, TaskProgress};
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Tests that the scratch space is measured by what the task wrote to it, and that the quota is enforced on that.
+    #[tokio::test]
+    async fn test_scratch_quota() {
+        let scratch: tempfile::TempDir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(scratch.path().join("nested")).unwrap();
+        std::fs::write(scratch.path().join("nested").join("a.bin"), vec![ 0u8; 1000 ]).unwrap();
+        std::fs::write(scratch.path().join("b.bin"), vec![ 0u8; 24 ]).unwrap();
+        // Links are neither followed nor counted, even if they would make us walk in circles
+        std::os::unix::fs::symlink(scratch.path(), scratch.path().join("nested").join("loop")).unwrap();
+        std::os::unix::fs::symlink(scratch.path().join("b.bin"), scratch.path().join("c.bin")).unwrap();
+
+        // Within the quota
+        let usage: ScratchUsage = scratch_usage(scratch.path(), Some(4096)).await.unwrap();
+        assert_eq!(usage.used, 1024);
+        assert_eq!(usage.limit, 4096);
+        assert!(!usage.exhausted());
+
+        // Right at and over the quota
+        let usage: ScratchUsage = scratch_usage(scratch.path(), Some(1024)).await.unwrap();
+        assert!(usage.exhausted());
+        let usage: ScratchUsage = scratch_usage(scratch.path(), Some(1000)).await.unwrap();
+        assert!(usage.used > usage.limit);
+
+        // Without a quota, only the disk limits the task
+        let usage: ScratchUsage = scratch_usage(scratch.path(), None).await.unwrap();
+        assert_eq!(usage.limit, usage.used + usage.available);
+
+        // Without a scratch space at all, there is nothing to measure
+        assert!(scratch_usage(scratch.path().join("b.bin"), Some(4096)).await.is_none());
+    }
+}
+
+
+
 
 
 /***** CONSTANTS *****/
@@ -81,6 +130,7 @@ fn assert_type(got: &DataType, expected: &DataType) -> bool {
 
         // Recursive cases
         (DataType::Array{ elem_type: got }, DataType::Array{ elem_type: expected }) => assert_type(got, expected),
+        (DataType::Map{ elem_type: got }, DataType::Map{ elem_type: expected })     => assert_type(got, expected),
 
         // General cases
         (DataType::Any, _) => true,
@@ -93,6 +143,155 @@ fn assert_type(got: &DataType, expected: &DataType) -> bool {
 
 
 
+/***** PROGRESS *****/
+/// Takes the latest progress update posted to the sidecar API and, if it changed since the last time, reports it on stderr so the worker can pick it up.
+/// 
+/// # Arguments
+/// - `progress`: The receiver of the progress updates posted by the package code (see `Sidecar::progress()`).
+/// - `last`: The last progress update that we reported. Will be updated if a new one is reported.
+pub fn report_progress(progress: &watch::Receiver<Option<TaskProgress>>, last: &mut Option<TaskProgress>) {
+    // Only the latest update is relevant, if the package posted any at all
+    let progress: TaskProgress = match progress.borrow().clone() {
+        Some(progress) => progress,
+        None           => { return; },
+    };
+    if last.as_ref() == Some(&progress) { return; }
+
+    // Report it on stderr
+    match serde_json::to_string(&progress) {
+        Ok(sprogress) => {
+            debug!("Reporting progress: {}", progress);
+            eprintln!("{}{}", PROGRESS_PREFIX, sprogress);
+            *last = Some(progress);
+        },
+        Err(err) => { warn!("Could not serialize progress update: {}", err); },
+    }
+}
+
+/// Reports a line of output written by the package code on stderr, so the worker can stream it to the client while the package runs.
+/// 
+/// # Arguments
+/// - `output`: The TaskOutput to report.
+pub fn report_output(output: &TaskOutput) {
+    match serde_json::to_string(output) {
+        Ok(soutput) => { eprintln!("{}{}", OUTPUT_PREFIX, soutput); },
+        Err(err)    => { warn!("Could not serialize line of output: {}", err); },
+    }
+}
+
+
+
+
+
+/***** SCRATCH SPACE *****/
+/// Describes how much of a task's scratch space is in use.
+#[derive(Clone, Copy, Debug)]
+pub struct ScratchUsage {
+    /// The number of bytes currently written to the scratch space.
+    pub used      : u64,
+    /// The number of bytes that may still be written before the disk backing it is full.
+    pub available : u64,
+    /// The number of bytes the task is allowed to write, which is the smallest of the node's and the package's limits (or everything it can, if neither limits it).
+    pub limit     : u64,
+}
+
+impl ScratchUsage {
+    /// Returns whether the task has run out of scratch space.
+    #[inline]
+    pub fn exhausted(&self) -> bool { self.available == 0 || self.used >= self.limit }
+}
+
+
+
+/// Computes how many bytes the task may write to its scratch space.
+/// 
+/// # Arguments
+/// - `package`: Any limit (in bytes) that the package imposes on itself in its `container.yml`.
+/// 
+/// # Returns
+/// The smallest of the package's limit and the node's limit (which the worker passes in `$BRANE_SCRATCH_LIMIT`), or `None` if neither is given.
+pub fn scratch_limit(package: Option<u64>) -> Option<u64> {
+    let node: Option<u64> = match std::env::var(SCRATCH_LIMIT_ENV) {
+        Ok(limit) => match limit.trim().parse::<u64>() {
+            Ok(limit) => Some(limit),
+            Err(err)  => { warn!("Ignoring illegal scratch limit '{}' in ${}: {}", limit, SCRATCH_LIMIT_ENV, err); None },
+        },
+        Err(_) => None,
+    };
+    [ package, node ].into_iter().flatten().min()
+}
+
+/// Computes the number of bytes in the files below the given directory.
+/// 
+/// Symbolic links are neither followed nor counted, so a link that points back up the tree cannot make us loop forever. Entries that cannot be read (e.g., because the package removed them while we walked) are skipped.
+/// 
+/// # Arguments
+/// - `path`: The directory to compute the size of.
+/// 
+/// # Returns
+/// The total size of the regular files in it, in bytes.
+fn dir_size(path: &Path) -> u64 {
+    let mut size: u64          = 0;
+    let mut todo: Vec<PathBuf> = vec![ path.into() ];
+    while let Some(dir) = todo.pop() {
+        let entries: std::fs::ReadDir = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_)      => { continue; },
+        };
+        for entry in entries.flatten() {
+            let path: PathBuf = entry.path();
+            match std::fs::symlink_metadata(&path) {
+                Ok(meta) if meta.is_dir()  => { todo.push(path); },
+                Ok(meta) if meta.is_file() => { size = size.saturating_add(meta.len()); },
+                _                          => {},
+            }
+        }
+    }
+    size
+}
+
+/// Queries the usage of the scratch space mounted at the given path, without blocking the runtime.
+/// 
+/// Since the scratch space is a directory on the node's disk, this walks it to find out how much the task has written (which the worker has us enforce, see `scratch_limit()`).
+/// 
+/// # Arguments
+/// - `path`: The path where the scratch space is mounted.
+/// - `limit`: The number of bytes the task may write to it, if limited (see `scratch_limit()`).
+/// 
+/// # Returns
+/// The current ScratchUsage, or `None` if there is no scratch space mounted at `path` (or we failed to query it).
+pub async fn scratch_usage(path: impl Into<PathBuf>, limit: Option<u64>) -> Option<ScratchUsage> {
+    let path: PathBuf = path.into();
+    match tokio::task::spawn_blocking(move || {
+        if !path.is_dir() { return None; }
+
+        // Query the filesystem for how much space is left on the disk as a whole
+        let cpath: CString = CString::new(path.as_os_str().as_bytes()).ok()?;
+        // SAFETY: `statvfs` is a plain-old-data struct for which zeroes are a valid value, and libc only writes to it.
+        let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+        if unsafe { libc::statvfs(cpath.as_ptr(), &mut stat) } != 0 {
+            debug!("Could not query scratch space '{}': {}", path.display(), std::io::Error::last_os_error());
+            return None;
+        }
+        let available: u64 = stat.f_bavail as u64 * stat.f_frsize as u64;
+
+        // ...and walk the scratch space for how much the task wrote
+        let used: u64 = dir_size(&path);
+        Some(ScratchUsage {
+            used,
+            available,
+            limit : limit.unwrap_or_else(|| used.saturating_add(available)),
+        })
+    }).await {
+        Ok(usage) => usage,
+        Err(err)  => { debug!("Could not query scratch space: {}", err); None },
+    }
+}
+
+
+
+
+
 /***** INITIALIZATION *****/
 /// **Edited: now returning LetErrors.**
 /// 
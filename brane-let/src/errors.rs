@@ -85,9 +85,13 @@ pub enum LetError {
     IllegalNestedURL{ name: String, field: String, },
     /// We got an error launching the package
     PackageLaunchError{ command: String, err: std::io::Error },
+    /// Could not serve the sidecar API to the package
+    SidecarServeError{ err: warp::Error },
 
     /// The given Open API Standard file does not parse as OAS
     IllegalOasDocument{ path: PathBuf, err: anyhow::Error },
+    /// The rate limit declared in the Open API Standard file is invalid
+    IllegalRateLimit{ path: PathBuf, err: anyhow::Error },
 
     /// Somehow, we got an error while waiting for the subprocess
     PackageRunError{ err: std::io::Error },
@@ -99,6 +103,8 @@ pub enum LetError {
     StdoutReadError{ err: std::io::Error },
     /// Could not open stderr
     StderrReadError{ err: std::io::Error },
+    /// The package wrote more to its scratch space than it was allowed to
+    ScratchExhausted{ path: PathBuf, limit: u64 },
 
     /// Something went wrong while decoding the package output as YAML
     DecodeError{ stdout: String, err: serde_yaml::Error },
@@ -115,6 +121,9 @@ pub enum LetError {
     ClassSerializeError{ argument: String, class: String, err: serde_json::Error },
     /// Could not write the resulting value to JSON
     ResultJSONError{ value: String, err: serde_json::Error },
+
+    /// Failed to move data in or out of the container
+    StagingError{ err: StagingError },
 }
 
 impl Display for LetError {
@@ -153,14 +162,17 @@ impl Display for LetError {
             UnsupportedStructField{ name, field, elem_type } => write!(f, "Field '{}' of struct '{}' has type '{}'; this type is not (yet) supported in structs, please use other types", field, name, elem_type),
             IllegalNestedURL{ name, field }                  => write!(f, "Field '{}' of struct '{}' is a Directory or a File struct, but misses the 'URL' field", field, name),
             PackageLaunchError{ command, err }               => write!(f, "Could not run nested package call '{}': {}", command, err),
+            SidecarServeError{ err }                         => write!(f, "Could not serve sidecar API to package: {}", err),
 
             IllegalOasDocument{ path, err } => write!(f, "Could not parse OpenAPI specification '{}': {}", path.display(), err),
+            IllegalRateLimit{ path, err }   => write!(f, "Illegal rate limit in OpenAPI specification '{}': {}", path.display(), err),
 
-            ClosedStdout           => write!(f, "Could not open subprocess stdout"),
-            ClosedStderr           => write!(f, "Could not open subprocess stdout"),
-            StdoutReadError{ err } => write!(f, "Could not read from stdout: {}", err),
-            StderrReadError{ err } => write!(f, "Could not read from stderr: {}", err),
-            PackageRunError{ err } => write!(f, "Could not get package run status: {}", err),
+            ClosedStdout                    => write!(f, "Could not open subprocess stdout"),
+            ClosedStderr                    => write!(f, "Could not open subprocess stdout"),
+            StdoutReadError{ err }          => write!(f, "Could not read from stdout: {}", err),
+            StderrReadError{ err }          => write!(f, "Could not read from stderr: {}", err),
+            PackageRunError{ err }          => write!(f, "Could not get package run status: {}", err),
+            ScratchExhausted{ path, limit } => write!(f, "Package exhausted its scratch space '{}' (limit: {} bytes)", path.display(), limit),
 
             DecodeError{ stdout, err }      => write!(f, "Could not parse package stdout: {}\n\nstdout:\n{}\n{}\n{}\n\n", err, (0..80).map(|_| '-').collect::<String>(), stdout, (0..80).map(|_| '-').collect::<String>()),
             OasDecodeError{ stdout, err }   => write!(f, "Could not parse package stdout: {}\n\nstdout:\n{}\n{}\n{}\n\n", err, (0..80).map(|_| '-').collect::<String>(), stdout, (0..80).map(|_| '-').collect::<String>()),
@@ -170,6 +182,8 @@ impl Display for LetError {
             ArraySerializeError{ argument, err }        => write!(f, "Failed to serialize Array in argument '{}' to JSON: {}", argument, err),
             ClassSerializeError{ argument, class, err } => write!(f, "Failed to serialize Class '{}' in argument '{}' to JSON: {}", class, argument, err),
             ResultJSONError{ value, err }               => write!(f, "Could not serialize value '{}' to JSON: {}", value, err),
+
+            StagingError{ err } => write!(f, "Failed to stage data: {}", err),
         }
     }
 }
@@ -178,6 +192,40 @@ impl Error for LetError {}
 
 
 
+/// Defines errors that occur while moving data in or out of the container.
+#[derive(Debug)]
+pub enum StagingError {
+    /// A transfer was not given as `<path>=<url>`.
+    IllegalTransfer{ raw: String },
+
+    /// Failed to send a request for a transfer.
+    RequestError{ path: PathBuf, err: reqwest::Error },
+    /// The server refused a transfer.
+    StatusError{ path: PathBuf, status: reqwest::StatusCode },
+    /// Failed to read or write a file that is transferred.
+    FileError{ path: PathBuf, err: std::io::Error },
+    /// Failed to create or extract the archive of a directory.
+    ArchiveError{ path: PathBuf, err: std::io::Error },
+}
+
+impl Display for StagingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use StagingError::*;
+        match self {
+            IllegalTransfer{ raw } => write!(f, "Illegal transfer '{}' (expected '<path>=<url>')", raw),
+
+            RequestError{ path, err }   => write!(f, "Failed to transfer '{}': {}", path.display(), err),
+            StatusError{ path, status } => write!(f, "Failed to transfer '{}': server returned {} ({})", path.display(), status.as_u16(), status.canonical_reason().unwrap_or("???")),
+            FileError{ path, err }      => write!(f, "Failed to read or write '{}': {}", path.display(), err),
+            ArchiveError{ path, err }   => write!(f, "Failed to archive or extract '{}': {}", path.display(), err),
+        }
+    }
+}
+
+impl Error for StagingError {}
+
+
+
 /// Defines errors that can occur during decoding.
 #[derive(Debug)]
 pub enum DecodeError {
@@ -18,16 +18,19 @@ use std::os::unix::process::ExitStatusExt;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 
-use tokio::io::AsyncReadExt;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
 use tokio::process::{Command as TokioCommand, Child as TokioChild};
+use tokio::sync::watch;
 use tokio::time::{self, Duration};
 
 use brane_exe::FullValue;
 use specifications::container::{Action, ActionCommand, LocalContainerInfo};
+use specifications::status::{OutputStream, SCRATCH_DIR, SCRATCH_DIR_ENV, SIDECAR_ENV, TaskOutput, TaskProgress};
 
 // use crate::callback::Callback;
-use crate::common::{assert_input, HEARTBEAT_DELAY, Map, PackageResult, PackageReturnState};
+use crate::common::{assert_input, report_output, report_progress, scratch_limit, scratch_usage, HEARTBEAT_DELAY, Map, PackageResult, PackageReturnState};
 use crate::errors::LetError;
+use crate::sidecar::Sidecar;
 
 
 /***** CONSTANTS *****/
@@ -83,8 +86,11 @@ pub async fn handle(
         }
     };
 
+    // Serve the sidecar API to the package for as long as it runs
+    let sidecar: Sidecar = Sidecar::serve()?;
+
     // Launch the job
-    let (command, process) = match start(&container_info, &function, &arguments, &working_dir) {
+    let (command, process) = match start(&container_info, &function, &arguments, &working_dir, &sidecar) {
         Ok(result) => {
             // if let Some(callback) = callback {
             //     if let Err(err) = callback.started().await { warn!("Could not update driver on Started: {}", err); }
@@ -102,7 +108,7 @@ pub async fn handle(
     };
 
     // Wait until the job is completed
-    let result = match complete(process).await {
+    let result = match complete(process, scratch_limit(container_info.scratch), &command.capture, &sidecar).await {
         Ok(result) => {
             // if let Some(callback) = callback {
             //     if let Err(err) = callback.completed().await { warn!("Could not update driver on Completed: {}", err); }
@@ -217,6 +223,7 @@ fn initialize(
 ///  * `function`: The function to call.
 ///  * `arguments`: The arguments to pass to the function.
 ///  * `working_dir`: The working directory for the function.
+///  * `sidecar`: The Sidecar whose URL we advertise to the function.
 /// 
 /// **Returns**  
 /// The ActionCommand used + a process handle on success, or a LetError on failure.
@@ -225,6 +232,7 @@ fn start(
     function: &Action,
     arguments: &Map<FullValue>,
     working_dir: &Path,
+    sidecar: &Sidecar,
 ) -> Result<(ActionCommand, TokioChild), LetError> {
     // Determine entrypoint and, optionally, command and arguments
     let entrypoint = &container_info.entrypoint.exec;
@@ -258,6 +266,8 @@ fn start(
     // Finally, prepare the subprocess
     exec_command.args(&command.args);
     exec_command.envs(envs);
+    exec_command.env(SIDECAR_ENV, sidecar.url());
+    if Path::new(SCRATCH_DIR).is_dir() { exec_command.env(SCRATCH_DIR_ENV, SCRATCH_DIR); }
     exec_command.stdout(Stdio::piped());
     exec_command.stderr(Stdio::piped());
     let process = match exec_command.spawn() {
@@ -383,17 +393,35 @@ fn construct_envs(
 /// 
 /// **Arguments**
 ///  * `process`: The handle to the asynchronous tokio process.
+///  * `scratch_limit`: The maximum number of bytes the package may write to its scratch space, if any (see `scratch_limit()`).
+///  * `mode`: The capture mode of the function, which determines which lines of stdout are its output (and thus not relayed to the worker).
+///  * `sidecar`: The Sidecar to which the package posts its progress updates.
 ///  * `callback`: A Callback object to send heartbeats with.
 /// 
 /// **Returns**  
 /// The PackageReturnState describing how the call went on success, or a LetError on failure.
 async fn complete(
     process: TokioChild,
+    scratch_limit: Option<u64>,
+    mode: &Option<String>,
+    sidecar: &Sidecar,
     // callback: &mut Option<&mut Callback>,
 ) -> Result<PackageReturnState, LetError> {
     let mut process = process;
+    let updates: watch::Receiver<Option<TaskProgress>> = sidecar.progress();
+
+    // Relay the package's output while it runs (which also prevents it from blocking on full pipes)
+    let stdout = match process.stdout.take() {
+        Some(stdout) => tokio::spawn(relay_output(stdout, OutputStream::Stdout, mode.clone())),
+        None         => { return Err(LetError::ClosedStdout); },
+    };
+    let stderr = match process.stderr.take() {
+        Some(stderr) => tokio::spawn(relay_output(stderr, OutputStream::Stderr, None)),
+        None         => { return Err(LetError::ClosedStderr); },
+    };
 
     // Handle waiting for the subprocess and doing heartbeats in a neat way, using select
+    let mut progress: Option<TaskProgress> = None;
     let status = loop {
         // Prepare the timer
         let sleep = time::sleep(Duration::from_millis(HEARTBEAT_DELAY));
@@ -413,6 +441,17 @@ async fn complete(
                     //     else { debug!("Sent Heartbeat to driver."); }
                     // }
 
+                    // Pass any progress the package reported on to the worker
+                    report_progress(&updates, &mut progress);
+
+                    // Stop the package if it went over its scratch limit (which only we enforce, since the scratch space is a plain directory on the node's disk)
+                    if let Some(usage) = scratch_usage(SCRATCH_DIR, scratch_limit).await {
+                        if usage.used > usage.limit {
+                            if let Err(err) = process.kill().await { warn!("Could not kill package that exhausted its scratch space: {}", err); }
+                            return Err(LetError::ScratchExhausted{ path: SCRATCH_DIR.into(), limit: usage.limit });
+                        }
+                    }
+
                     // Stop without result
                     break None;
                 },
@@ -429,32 +468,25 @@ async fn complete(
         Err(err)   => { return Err(LetError::PackageRunError{ err }); }
     };
 
-    // Try to get stdout and stderr readers
-    let mut stdout = match process.stdout {
-        Some(stdout) => stdout,
-        None         => { return Err(LetError::ClosedStdout); },
-    };
-    let mut stderr = match process.stderr {
-        Some(stderr) => stderr,
-        None         => { return Err(LetError::ClosedStderr); },
+    // Wait for the relays to read the rest of the output
+    let stdout: String = match stdout.await {
+        Ok(Ok(stdout)) => stdout,
+        Ok(Err(err))   => { return Err(LetError::StdoutReadError{ err }); },
+        Err(err)       => { return Err(LetError::StdoutReadError{ err: std::io::Error::new(std::io::ErrorKind::Other, err) }); },
     };
-    // Consume the readers into the raw text
-    let mut stdout_text: Vec<u8> = Vec::with_capacity(DEFAULT_STD_BUFFER_SIZE);
-    let _n_stdout = match stdout.read_to_end(&mut stdout_text).await {
-        Ok(n_stdout) => n_stdout,
-        Err(err)     => { return Err(LetError::StdoutReadError{ err }); }
+    let stderr: String = match stderr.await {
+        Ok(Ok(stderr)) => stderr,
+        Ok(Err(err))   => { return Err(LetError::StderrReadError{ err }); },
+        Err(err)       => { return Err(LetError::StderrReadError{ err: std::io::Error::new(std::io::ErrorKind::Other, err) }); },
     };
-    let mut stderr_text: Vec<u8> = Vec::with_capacity(DEFAULT_STD_BUFFER_SIZE);
-    let _n_stderr = match stderr.read_to_end(&mut stderr_text).await {
-        Ok(n_stderr) => n_stderr,
-        Err(err)     => { return Err(LetError::StderrReadError{ err }); }
-    };
-    // Convert the bytes to text
-    let stdout = String::from_utf8_lossy(&stdout_text).to_string();
-    let stderr = String::from_utf8_lossy(&stderr_text).to_string();
 
     // If the process failed, return it does
     if !status.success() {
+        // Blame the scratch space if it is full, since that's likely the reason the package fell over
+        if let Some(usage) = scratch_usage(SCRATCH_DIR, scratch_limit).await {
+            if usage.exhausted() { return Err(LetError::ScratchExhausted{ path: SCRATCH_DIR.into(), limit: usage.limit }); }
+        }
+
         // Check if it was killed
         if status.signal().is_some() { return Ok(PackageReturnState::Stopped{ signal: status.signal().unwrap() }); }
         return Ok(PackageReturnState::Failed{ code: status.code().unwrap_or(-1), stdout, stderr });
@@ -464,6 +496,53 @@ async fn complete(
     Ok(PackageReturnState::Finished{ stdout })
 }
 
+/// Reads the given output stream of the package line-by-line, relaying every line to the worker as soon as it is written.
+/// 
+/// **Arguments**
+///  * `reader`: The stream to read.
+///  * `stream`: Which of the package's streams it is.
+///  * `mode`: The capture mode of the function. Lines of stdout that are captured as the function's output are not relayed.
+/// 
+/// **Returns**  
+/// Everything that was read from the stream, or an IO error if we failed to read it.
+async fn relay_output(reader: impl AsyncRead + Unpin, stream: OutputStream, mode: Option<String>) -> Result<String, std::io::Error> {
+    let mut reader = BufReader::new(reader);
+    let mut raw: Vec<u8> = Vec::with_capacity(DEFAULT_STD_BUFFER_SIZE);
+    let mut capturing: bool = false;
+    loop {
+        // Read the next line, stopping when the package closes the stream
+        let start: usize = raw.len();
+        if reader.read_until(b'\n', &mut raw).await? == 0 { break; }
+        let line: String = String::from_utf8_lossy(&raw[start..]).trim_end_matches(&['\n', '\r'][..]).to_string();
+
+        // Relay it, unless it is part of the function's output
+        if stream == OutputStream::Stdout && is_captured(&line, &mode, &mut capturing) { continue; }
+        report_output(&TaskOutput{ stream, line });
+    }
+    Ok(String::from_utf8_lossy(&raw).to_string())
+}
+
+/// Decides whether the given line of stdout is part of the function's output, as determined by its capture mode.
+/// 
+/// **Arguments**
+///  * `line`: The line to examine.
+///  * `mode`: The capture mode of the function.
+///  * `capturing`: Whether we are currently in a marked capture area. Will be updated if the line opens or closes one.
+/// 
+/// **Returns**  
+/// True if the line is (part of) the output, or false if it's something the package printed for the user.
+fn is_captured(line: &str, mode: &Option<String>, capturing: &mut bool) -> bool {
+    match mode.as_deref().unwrap_or("complete") {
+        "marked" => {
+            if line.trim_start().starts_with(MARK_START) { *capturing = true; return true; }
+            if *capturing && line.trim_start().starts_with(MARK_END) { *capturing = false; return true; }
+            *capturing
+        },
+        "prefixed" => line.starts_with(PREFIX),
+        _ => true,
+    }
+}
+
 /// **Edited: returns LetErrors + changed to accept string instead of split stuff.**
 /// 
 /// Preprocesses stdout by only leaving the stuff that is relevant for the branelet (i.e., only that which is marked as captured by the mode).
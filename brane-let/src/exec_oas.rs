@@ -19,6 +19,8 @@ use tokio::time::{self, Duration};
 
 use brane_exe::FullValue;
 use brane_oas::OpenAPI;
+use brane_oas::cache::{ResponseCache, CACHE_DIR_ENV};
+use brane_oas::ratelimit::{RateLimit, RateLimiter, SharedTokenBucket, BUCKET_FILE, STATE_DIR_ENV};
 use specifications::package::{PackageInfo, PackageKind};
 use specifications::version::Version;
 
@@ -49,7 +51,7 @@ pub async fn handle(
     debug!("Executing '{}' (oas) using arguments:\n{:#?}", function, arguments);
 
     // Initialize the package
-    let oas_document = match initialize(&function, &arguments, &working_dir) {
+    let (oas_document, rate_limit) = match initialize(&function, &arguments, &working_dir) {
         Ok(results) => {
             // if let Some(callback) = callback {
             //     if let Err(err) = callback.initialized().await { warn!("Could not update driver on Initialized: {}", err); }
@@ -69,7 +71,7 @@ pub async fn handle(
     };
 
     // Do the API call, sending heartbeat updates while at it
-    let result = match complete(&function, &arguments, &oas_document).await {
+    let result = match complete(&function, &arguments, &working_dir, &oas_document, rate_limit).await {
         Ok(result) => {
             // if let Some(callback) = callback {
             //     if let Err(err) = callback.completed().await { warn!("Could not update driver on Completed: {}", err); }
@@ -117,15 +119,15 @@ pub async fn handle(
 /// 
 /// **Returns**  
 ///  * On success, a tuple with (in order):
-///    * The PackageInfo struct representing the package.yml in this package
-///    * The function represented as an Action that we should execute
+///    * The parsed OpenAPI document of this package
+///    * The RateLimit declared in that document, if any
 ///  * On failure:
 ///    * A LetError describing what went wrong.
 fn initialize(
     function: &str,
     arguments: &Map<FullValue>,
     working_dir: &Path,
-) -> Result<OpenAPI, LetError> {
+) -> Result<(OpenAPI, Option<RateLimit>), LetError> {
     // Get the OasDocument from path
     let oas_file = working_dir.join("document.yml");
     let oas_document = match brane_oas::parse_oas_file(&oas_file) {
@@ -148,8 +150,14 @@ fn initialize(
     // Make sure the input matches what we expect
     assert_input(&function_info.parameters, arguments, function, &package_info.name, package_info.kind)?;
 
+    // Read the rate limit the package wants us to respect, if any
+    let rate_limit = match RateLimit::from_document(&oas_document) {
+        Ok(rate_limit) => rate_limit,
+        Err(err)       => { return Err(LetError::IllegalRateLimit{ path: oas_file, err }); }
+    };
+
     // Done!
-    Ok(oas_document)
+    Ok((oas_document, rate_limit))
 }
 
 
@@ -197,7 +205,9 @@ fn create_package_info(
 /// **Arguments**
 ///  * `function`: The OpenAPI function to run.
 ///  * `arguments`: The Arguments to pass to the OpenAPI call.
+///  * `working_dir`: The working directory for this package, in which we cache responses (unless overridden by `$BRANE_OAS_CACHE_DIR`).
 ///  * `oas_doc`: The parsed document with the call to execute.
+///  * `rate_limit`: The RateLimit to respect for every request of the call, if any. It is shared with all other calls to the same package through the token bucket in `$BRANE_OAS_STATE_DIR` (which the worker shares between them), or in the working directory otherwise.
 ///  * `callback`: A Callback object to send heartbeats with.
 /// 
 /// **Returns**  
@@ -205,10 +215,29 @@ fn create_package_info(
 async fn complete(
     function: &str,
     arguments: &Map<FullValue>,
+    working_dir: &Path,
     oas_doc: &OpenAPI,
+    rate_limit: Option<RateLimit>,
     // callback: &mut Option<&mut Callback>,
 ) -> Result<PackageReturnState, LetError> {
-    // Handle waiting for the subprocess and doing heartbeats in a neat way, using select
+    // Decide where to cache responses of operations that allow it
+    let cache: ResponseCache = match std::env::var(CACHE_DIR_ENV) {
+        Ok(dir) => ResponseCache::new(dir),
+        Err(_)  => ResponseCache::new(working_dir.join(".cache")),
+    };
+
+    // Take a token from the bucket of the package for every request that the call sends (and not just once per call, since paginated calls may send many)
+    let limiter: Option<RateLimiter> = rate_limit.map(|rate_limit| {
+        let state_dir: PathBuf = match std::env::var(STATE_DIR_ENV) {
+            Ok(dir) => PathBuf::from(dir),
+            Err(_)  => working_dir.into(),
+        };
+        RateLimiter::new(rate_limit, SharedTokenBucket::new(state_dir.join(BUCKET_FILE)))
+    });
+
+    // Handle waiting for the call and doing heartbeats in a neat way, using select. Note that the call is only created once, since it may wait for the rate limit in between requests
+    let call = brane_oas::execute(function, arguments, oas_doc, Some(&cache), limiter.as_ref());
+    tokio::pin!(call);
     let result = loop {
         // Prepare the timer
         let sleep = time::sleep(Duration::from_millis(HEARTBEAT_DELAY));
@@ -217,7 +246,7 @@ async fn complete(
         // Wait for either the timer or the process
         let status = loop {
             tokio::select! {
-                result = brane_oas::execute(function, arguments, oas_doc) => {
+                result = &mut call => {
                     // Process is finished!
                     break Some(result);
                 },
@@ -8,3 +8,5 @@ pub mod errors;
 pub mod exec_ecu;
 pub mod exec_nop;
 pub mod exec_oas;
+pub mod sidecar;
+pub mod staging;
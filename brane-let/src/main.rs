@@ -26,6 +26,8 @@ use brane_let::errors::LetError;
 use brane_let::exec_ecu;
 use brane_let::exec_nop;
 use brane_let::exec_oas;
+use brane_let::staging::{self, Transfer};
+use specifications::status::SCRATCH_EXHAUSTED_PREFIX;
 
 
 /***** ARGUMENTS *****/
@@ -44,6 +46,18 @@ struct Opts {
     proxy_address: Option<String>,
     #[clap(short, long, env = "BRANE_MOUNT_DFS")]
     mount_dfs: Option<String>,
+    /// Directories to download (as `.tar.gz`) and extract before running the package, as `<path>=<url>`
+    #[clap(long)]
+    stage_in: Vec<Transfer>,
+    /// Files to download before running the package, as `<path>=<url>`
+    #[clap(long)]
+    stage_in_file: Vec<Transfer>,
+    /// Directories to archive (as `.tar.gz`) and upload after the package completes successfully, as `<path>=<url>`
+    #[clap(long)]
+    stage_out: Vec<Transfer>,
+    /// If given, the (encoded) return value is also uploaded to this URL
+    #[clap(long, env = "BRANE_OUTPUT_URL")]
+    output_url: Option<String>,
     /// Prints debug info
     #[clap(short, long, action, env = "DEBUG")]
     debug: bool,
@@ -82,12 +96,29 @@ enum SubCommand {
 
 
 
+/***** AUXILLARY *****/
+/// Collects the options that move data in and out of the container.
+struct Transfers {
+    /// Directories to stage in.
+    stage_in      : Vec<Transfer>,
+    /// Files to stage in.
+    stage_in_file : Vec<Transfer>,
+    /// Directories to stage out.
+    stage_out     : Vec<Transfer>,
+    /// Where to publish the return value, if anywhere.
+    output_url    : Option<String>,
+}
+
+
+
+
+
 /***** ENTRYPOINT *****/
 #[tokio::main]
 async fn main() {
     // Parse the arguments
     dotenv().ok();
-    let Opts{ proxy_address, debug, sub_command, .. } = Opts::parse();
+    let Opts{ proxy_address, stage_in, stage_in_file, stage_out, output_url, debug, sub_command, .. } = Opts::parse();
 
     // Configure logger.
     let mut logger = env_logger::builder();
@@ -138,10 +169,12 @@ async fn main() {
     // };
 
     // Wrap actual execution, so we can always log errors.
-    match run(sub_command).await {
+    match run(sub_command, Transfers{ stage_in, stage_in_file, stage_out, output_url }).await {
         Ok(code) => process::exit(code),
         Err(err) => {
             log::error!("{}", err);
+            // Let the worker know that it's the scratch space to blame
+            if let LetError::ScratchExhausted{ limit, .. } = err { eprintln!("{}{}", SCRATCH_EXHAUSTED_PREFIX, limit); }
             process::exit(-1);
         }
     }
@@ -153,14 +186,28 @@ async fn main() {
 /// 
 /// **Arguments**
 ///  * `sub_command`: The subcommand to execute (is it code, oas or nop?)
+///  * `transfers`: The data to move in and out of the container, and where to publish the result.
 ///  * `callback`: The Callback future that asynchronously constructs a Callback instance.
 /// 
 /// **Returns**  
 /// The exit code of the nested application on success, or a LetError otherwise.
 async fn run(
     sub_command: SubCommand,
+    transfers: Transfers,
     // callback: Option<Callback>,
 ) -> Result<i32, LetError> {
+    // Fetch the data the package needs, and prepare the directories it writes to
+    let client: reqwest::Client = reqwest::Client::new();
+    for transfer in &transfers.stage_in {
+        if let Err(err) = staging::stage_in_dir(&client, transfer).await { return Err(LetError::StagingError{ err }); }
+    }
+    for transfer in &transfers.stage_in_file {
+        if let Err(err) = staging::stage_in_file(&client, transfer).await { return Err(LetError::StagingError{ err }); }
+    }
+    for transfer in &transfers.stage_out {
+        if let Err(err) = tokio::fs::create_dir_all(&transfer.path).await { return Err(LetError::StagingError{ err: staging::Error::FileError{ path: transfer.path.clone(), err } }); }
+    }
+
     // // We've initialized!
     // if let Some(ref mut callback) = callback {
     //     if let Err(err) = callback.ready().await { log::error!("Could not update driver on Ready: {}", err); }
@@ -203,9 +250,18 @@ async fn run(
             //     if let Err(err) = callback.finished(output).await { log::error!("Could not update driver on Finished: {}", err); }
             // } else {
                 // Print to stdout as (base64-encoded) JSON
-                println!("{}", base64::encode(output));
+                let output: String = base64::encode(output);
+                println!("{}", output);
             // }
 
+            // Hand back what the package wrote and returned, if the worker cannot read it from the container itself
+            for transfer in &transfers.stage_out {
+                if let Err(err) = staging::stage_out_dir(&client, transfer).await { return Err(LetError::StagingError{ err }); }
+            }
+            if let Some(url) = &transfers.output_url {
+                if let Err(err) = staging::publish(&client, url, output).await { return Err(LetError::StagingError{ err }); }
+            }
+
             Ok(0)
         },
 
@@ -234,7 +290,8 @@ async fn run(
                 log::error!("Internal package call was forcefully stopped with signal {}", signal);
             // }
 
-            Ok(-1)
+            // Mimic the shell's convention, so the worker can tell (e.g.) an OOM-kill or segfault of the package apart
+            Ok(128 + signal)
         },
 
         Err(err) => {
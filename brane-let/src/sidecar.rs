@@ -0,0 +1,176 @@
+//  SIDECAR.rs
+//    by agent
+//
+//  Created:
+//    16 Oct 2026, 16:02:11
+//  Last edited:
+//    16 Oct 2026, 16:02:11
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements the sidecar API that branelet serves to the package code
+//!   it runs, on the loopback interface of the container. For now, it
+//!   only accepts progress updates.
+//
+
+use std::net::SocketAddr;
+
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use warp::Filter;
+use warp::http::StatusCode;
+use warp::hyper::body::Bytes;
+
+use specifications::status::TaskProgress;
+
+use crate::errors::LetError;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Tests the sidecar over HTTP, as package code would use it.
+    #[tokio::test]
+    async fn test_sidecar_progress() {
+        let sidecar: Sidecar = Sidecar::serve().unwrap();
+        let progress: watch::Receiver<Option<TaskProgress>> = sidecar.progress();
+        let client: reqwest::Client = reqwest::Client::new();
+
+        // Plain lines are parsed like `TaskProgress::from_line()` does
+        let res: reqwest::Response = client.post(format!("{}/progress", sidecar.url())).body("42 Training epoch 3").send().await.unwrap();
+        assert_eq!(res.status(), reqwest::StatusCode::NO_CONTENT);
+        assert_eq!(*progress.borrow(), Some(TaskProgress{ percentage: Some(42.0), message: Some("Training epoch 3".into()) }));
+
+        // JSON is accepted as well, and clamped the same way
+        let res: reqwest::Response = client.post(format!("{}/progress", sidecar.url())).body(r#"{ "percentage": 120, "message": "Done" }"#).send().await.unwrap();
+        assert_eq!(res.status(), reqwest::StatusCode::NO_CONTENT);
+        assert_eq!(*progress.borrow(), Some(TaskProgress{ percentage: Some(100.0), message: Some("Done".into()) }));
+
+        // Empty updates are rejected and do not overwrite the last one
+        let res: reqwest::Response = client.post(format!("{}/progress", sidecar.url())).body("  ").send().await.unwrap();
+        assert_eq!(res.status(), reqwest::StatusCode::BAD_REQUEST);
+        assert_eq!(*progress.borrow(), Some(TaskProgress{ percentage: Some(100.0), message: Some("Done".into()) }));
+
+        // Anything else does not exist
+        let res: reqwest::Response = client.get(format!("{}/progress", sidecar.url())).send().await.unwrap();
+        assert_eq!(res.status(), reqwest::StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    /// Tests parsing the bodies of progress updates.
+    #[test]
+    fn test_parse_progress() {
+        assert_eq!(parse_progress(b"50% Halfway"), Some(TaskProgress{ percentage: Some(50.0), message: Some("Halfway".into()) }));
+        assert_eq!(parse_progress(br#"{ "message": "Downloading" }"#), Some(TaskProgress{ percentage: None, message: Some("Downloading".into()) }));
+        assert_eq!(parse_progress(br#"{ "percentage": -3 }"#), Some(TaskProgress{ percentage: Some(0.0), message: None }));
+        // A JSON object without anything in it is no progress, and neither is something that is not UTF-8
+        assert_eq!(parse_progress(b"{}"), None);
+        assert_eq!(parse_progress(&[ 0xFF, 0xFE ]), None);
+    }
+}
+
+
+
+
+
+/***** CONSTANTS *****/
+/// The largest progress update we accept, in bytes.
+const MAX_PROGRESS: u64 = 4096;
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Parses the body of a progress update sent by package code.
+///
+/// # Arguments
+/// - `body`: The raw body, which is either a JSON-encoded TaskProgress or a line of the form `<percentage> <message>` (see `TaskProgress::from_line()`).
+///
+/// # Returns
+/// The parsed TaskProgress, or `None` if the body was empty or not UTF-8.
+fn parse_progress(body: &[u8]) -> Option<TaskProgress> {
+    let body: &str = std::str::from_utf8(body).ok()?;
+    match serde_json::from_str::<TaskProgress>(body) {
+        Ok(progress) => TaskProgress::new(progress.percentage, progress.message),
+        Err(_)       => TaskProgress::from_line(body),
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Serves the sidecar API to the package code on an ephemeral port on the loopback interface, until it is dropped.
+///
+/// Package code finds it through `$BRANE_SIDECAR`, and may `POST` progress updates to `$BRANE_SIDECAR/progress`.
+#[derive(Debug)]
+pub struct Sidecar {
+    /// The address on which we serve.
+    address  : SocketAddr,
+    /// The last progress update posted by the package code.
+    progress : watch::Receiver<Option<TaskProgress>>,
+    /// The task that serves the API.
+    handle   : JoinHandle<()>,
+}
+
+impl Sidecar {
+    /// Starts serving the sidecar API in the background.
+    ///
+    /// # Returns
+    /// A new Sidecar that serves until it is dropped.
+    ///
+    /// # Errors
+    /// This function errors if we failed to bind to the loopback interface.
+    pub fn serve() -> Result<Self, LetError> {
+        let (tx, progress): (watch::Sender<Option<TaskProgress>>, watch::Receiver<Option<TaskProgress>>) = watch::channel(None);
+
+        // Accept progress updates on `POST /progress`
+        let routes = warp::post()
+            .and(warp::path("progress"))
+            .and(warp::path::end())
+            .and(warp::body::content_length_limit(MAX_PROGRESS))
+            .and(warp::body::bytes())
+            .map(move |body: Bytes| {
+                match parse_progress(&body) {
+                    Some(progress) => {
+                        debug!("Package reported progress: {}", progress);
+                        tx.send_replace(Some(progress));
+                        StatusCode::NO_CONTENT
+                    },
+                    None => StatusCode::BAD_REQUEST,
+                }
+            });
+
+        // Bind to whatever port is free
+        let (address, server) = match warp::serve(routes).try_bind_ephemeral(([ 127, 0, 0, 1 ], 0)) {
+            Ok(res)  => res,
+            Err(err) => { return Err(LetError::SidecarServeError{ err }); },
+        };
+        debug!("Serving sidecar API on '{}'", address);
+        Ok(Self {
+            address,
+            progress,
+            handle : tokio::spawn(server),
+        })
+    }
+
+
+
+    /// Returns the URL of the sidecar API, as advertised to the package code.
+    #[inline]
+    pub fn url(&self) -> String { format!("http://{}", self.address) }
+
+    /// Returns a receiver for the progress updates posted by the package code.
+    #[inline]
+    pub fn progress(&self) -> watch::Receiver<Option<TaskProgress>> { self.progress.clone() }
+}
+
+impl Drop for Sidecar {
+    #[inline]
+    fn drop(&mut self) { self.handle.abort(); }
+}
@@ -0,0 +1,277 @@
+//  STAGING.rs
+//    by agent
+//
+//  Created:
+//    16 Oct 2026, 13:31:38
+//  Last edited:
+//    16 Oct 2026, 13:31:38
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements moving data in and out of the container over (presigned)
+//!   URLs, for backends that cannot mount the worker's datasets and
+//!   intermediate results (e.g., AWS).
+//
+
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use async_compression::tokio::bufread::GzipDecoder;
+use async_compression::tokio::write::GzipEncoder;
+use reqwest::{Body, Client, Response};
+use reqwest::header::CONTENT_LENGTH;
+use tokio::fs::{self as tfs, File as TFile};
+use tokio::io::{AsyncWriteExt, BufReader};
+use tokio_tar::{Archive, Builder};
+use tokio_util::codec::{BytesCodec, FramedRead};
+
+pub use crate::errors::StagingError as Error;
+
+
+/***** UNIT TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transfer_from_str() {
+        // The URL may contain equals signs of its own
+        let transfer: Transfer = Transfer::from_str("/data/test=https://bucket.s3.amazonaws.com/key?X-Amz-Expires=604800&X-Amz-Signature=abc").unwrap();
+        assert_eq!(transfer.path, PathBuf::from("/data/test"));
+        assert_eq!(transfer.url, "https://bucket.s3.amazonaws.com/key?X-Amz-Expires=604800&X-Amz-Signature=abc");
+
+        // Both sides are required
+        assert!(Transfer::from_str("/data/test").is_err());
+        assert!(Transfer::from_str("=https://example.com").is_err());
+        assert!(Transfer::from_str("/data/test=").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_archive_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let source: PathBuf = dir.path().join("source");
+        tfs::create_dir_all(source.join("nested")).await.unwrap();
+        tfs::write(source.join("a.txt"), "Hello").await.unwrap();
+        tfs::write(source.join("nested").join("b.txt"), "there").await.unwrap();
+
+        let tarball: PathBuf = dir.path().join("source.tar.gz");
+        archive(&source, &tarball).await.unwrap();
+        let target: PathBuf = dir.path().join("target");
+        unarchive(&tarball, &target).await.unwrap();
+        assert_eq!(tfs::read_to_string(target.join("a.txt")).await.unwrap(), "Hello");
+        assert_eq!(tfs::read_to_string(target.join("nested").join("b.txt")).await.unwrap(), "there");
+    }
+}
+
+
+
+
+
+/***** CONSTANTS *****/
+/// The directory in which tarballs are kept while they are being transferred.
+pub const TRANSFER_DIR: &str = "/tmp/brane-staging";
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Checks that the given response reports success.
+///
+/// # Arguments
+/// - `path`: The path in the container that the transfer is about, for debugging purposes.
+/// - `res`: The result of the request to check.
+///
+/// # Returns
+/// The response if it was successful.
+///
+/// # Errors
+/// This function errors if the request failed or the server responded with a non-success status code.
+fn check(path: &Path, res: Result<Response, reqwest::Error>) -> Result<Response, Error> {
+    let res: Response = match res {
+        Ok(res)  => res,
+        Err(err) => { return Err(Error::RequestError{ path: path.into(), err }); },
+    };
+    if !res.status().is_success() { return Err(Error::StatusError{ path: path.into(), status: res.status() }); }
+    Ok(res)
+}
+
+/// Archives the contents of the given directory as a `.tar.gz` file.
+///
+/// # Arguments
+/// - `source`: The directory to archive. Its contents end up in the root of the archive.
+/// - `tarball`: The path of the archive to write.
+///
+/// # Errors
+/// This function errors if we failed to read the directory or to write the archive.
+async fn archive(source: &Path, tarball: &Path) -> Result<(), Error> {
+    let handle: TFile = match TFile::create(tarball).await {
+        Ok(handle) => handle,
+        Err(err)   => { return Err(Error::FileError{ path: tarball.into(), err }); },
+    };
+    let mut builder: Builder<GzipEncoder<TFile>> = Builder::new(GzipEncoder::new(handle));
+    if let Err(err) = builder.append_dir_all(".", source).await { return Err(Error::ArchiveError{ path: source.into(), err }); }
+    let mut encoder: GzipEncoder<TFile> = match builder.into_inner().await {
+        Ok(encoder) => encoder,
+        Err(err)    => { return Err(Error::ArchiveError{ path: source.into(), err }); },
+    };
+    if let Err(err) = encoder.shutdown().await { return Err(Error::FileError{ path: tarball.into(), err }); }
+    Ok(())
+}
+
+/// Extracts the given `.tar.gz` file into the given directory.
+///
+/// # Arguments
+/// - `tarball`: The archive to extract.
+/// - `target`: The directory to extract it to. It is created if it does not exist yet.
+///
+/// # Errors
+/// This function errors if we failed to read the archive or to write its contents.
+async fn unarchive(tarball: &Path, target: &Path) -> Result<(), Error> {
+    let handle: TFile = match TFile::open(tarball).await {
+        Ok(handle) => handle,
+        Err(err)   => { return Err(Error::FileError{ path: tarball.into(), err }); },
+    };
+    if let Err(err) = tfs::create_dir_all(target).await { return Err(Error::FileError{ path: target.into(), err }); }
+    match Archive::new(GzipDecoder::new(BufReader::new(handle))).unpack(target).await {
+        Ok(_)    => Ok(()),
+        Err(err) => Err(Error::ArchiveError{ path: target.into(), err }),
+    }
+}
+
+/// Returns the path of a fresh file in the `TRANSFER_DIR` to keep the archive of the given path in.
+///
+/// # Errors
+/// This function errors if we failed to create the `TRANSFER_DIR`.
+async fn transfer_file(path: &Path) -> Result<PathBuf, Error> {
+    if let Err(err) = tfs::create_dir_all(TRANSFER_DIR).await { return Err(Error::FileError{ path: TRANSFER_DIR.into(), err }); }
+    Ok(PathBuf::from(TRANSFER_DIR).join(format!("{}.tar.gz", path.to_string_lossy().replace('/', "_"))))
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines a single file or directory that is moved between the container and a URL.
+#[derive(Clone, Debug)]
+pub struct Transfer {
+    /// The path of the file or directory in the container.
+    pub path : PathBuf,
+    /// The (presigned) URL to download it from or upload it to.
+    pub url  : String,
+}
+
+impl Display for Transfer {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        // Don't show the URL, since it grants access to whomever has it
+        write!(f, "{}", self.path.display())
+    }
+}
+
+impl FromStr for Transfer {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Split on the first equals sign, since the URL may contain more
+        match s.split_once('=') {
+            Some((path, url)) if !path.is_empty() && !url.is_empty() => Ok(Self{ path: path.into(), url: url.into() }),
+            _                                                        => Err(Error::IllegalTransfer{ raw: s.into() }),
+        }
+    }
+}
+
+
+
+/// Downloads the given archive and extracts it to the path of the transfer.
+///
+/// # Arguments
+/// - `client`: The Client to download with.
+/// - `transfer`: The Transfer that describes the directory to stage in.
+///
+/// # Errors
+/// This function errors if we failed to download or extract the archive.
+pub async fn stage_in_dir(client: &Client, transfer: &Transfer) -> Result<(), Error> {
+    debug!("Staging in directory '{}'...", transfer);
+    let tarball: PathBuf = transfer_file(&transfer.path).await?;
+    stage_in_file(client, &Transfer{ path: tarball.clone(), url: transfer.url.clone() }).await?;
+    unarchive(&tarball, &transfer.path).await?;
+    if let Err(err) = tfs::remove_file(&tarball).await { return Err(Error::FileError{ path: tarball, err }); }
+    Ok(())
+}
+
+/// Downloads the given file to the path of the transfer.
+///
+/// # Arguments
+/// - `client`: The Client to download with.
+/// - `transfer`: The Transfer that describes the file to stage in.
+///
+/// # Errors
+/// This function errors if we failed to download or write the file.
+pub async fn stage_in_file(client: &Client, transfer: &Transfer) -> Result<(), Error> {
+    debug!("Staging in file '{}'...", transfer);
+    let mut res: Response = check(&transfer.path, client.get(&transfer.url).send().await)?;
+
+    // Write it chunk-by-chunk, as it may be large
+    if let Some(parent) = transfer.path.parent() {
+        if let Err(err) = tfs::create_dir_all(parent).await { return Err(Error::FileError{ path: parent.into(), err }); }
+    }
+    let mut handle: TFile = match TFile::create(&transfer.path).await {
+        Ok(handle) => handle,
+        Err(err)   => { return Err(Error::FileError{ path: transfer.path.clone(), err }); },
+    };
+    loop {
+        match res.chunk().await {
+            Ok(Some(chunk)) => if let Err(err) = handle.write_all(&chunk).await { return Err(Error::FileError{ path: transfer.path.clone(), err }); },
+            Ok(None)        => { break; },
+            Err(err)        => { return Err(Error::RequestError{ path: transfer.path.clone(), err }); },
+        }
+    }
+    if let Err(err) = handle.flush().await { return Err(Error::FileError{ path: transfer.path.clone(), err }); }
+    Ok(())
+}
+
+/// Archives the directory of the given transfer and uploads it.
+///
+/// # Arguments
+/// - `client`: The Client to upload with.
+/// - `transfer`: The Transfer that describes the directory to stage out.
+///
+/// # Errors
+/// This function errors if we failed to archive or upload the directory.
+pub async fn stage_out_dir(client: &Client, transfer: &Transfer) -> Result<(), Error> {
+    debug!("Staging out directory '{}'...", transfer);
+    let tarball: PathBuf = transfer_file(&transfer.path).await?;
+    archive(&transfer.path, &tarball).await?;
+
+    // Stream it, but tell the server how much to expect (presigned uploads refuse chunked bodies)
+    let handle: TFile = match TFile::open(&tarball).await {
+        Ok(handle) => handle,
+        Err(err)   => { return Err(Error::FileError{ path: tarball, err }); },
+    };
+    let size: u64 = match handle.metadata().await {
+        Ok(metadata) => metadata.len(),
+        Err(err)     => { return Err(Error::FileError{ path: tarball, err }); },
+    };
+    check(&transfer.path, client.put(&transfer.url).header(CONTENT_LENGTH, size).body(Body::wrap_stream(FramedRead::new(handle, BytesCodec::new()))).send().await)?;
+    if let Err(err) = tfs::remove_file(&tarball).await { return Err(Error::FileError{ path: tarball, err }); }
+    Ok(())
+}
+
+/// Uploads the (encoded) value returned by the package, so the worker can read it back without relying on the container's logs.
+///
+/// # Arguments
+/// - `client`: The Client to upload with.
+/// - `url`: The (presigned) URL to upload the value to.
+/// - `output`: The value to upload.
+///
+/// # Errors
+/// This function errors if we failed to upload the value.
+pub async fn publish(client: &Client, url: &str, output: String) -> Result<(), Error> {
+    debug!("Publishing output...");
+    check(Path::new("<output>"), client.put(url).body(output).send().await)?;
+    Ok(())
+}
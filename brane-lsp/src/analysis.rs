@@ -0,0 +1,586 @@
+//  ANALYSIS.rs
+//    by agent
+//
+//  Created:
+//    16 Oct 2026, 08:18:41
+//  Last edited:
+//    16 Oct 2026, 11:21:01
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Runs the BraneScript compiler on a single document and collects
+//!   everything the language server needs to know about it.
+//!
+//!   Note that the compiler's AST is not thread-safe, so everything is
+//!   converted to LSP types before it leaves this module.
+//
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use tower_lsp::lsp_types::{CompletionItemKind, Diagnostic, DiagnosticSeverity, Position, Range};
+
+use brane_ast::{compile_program_to, CompileResult, CompileStage, Error, ParserOptions, TextPos, TextRange, Warning};
+use brane_ast::spec::{BuiltinClasses, BuiltinFunctions};
+use brane_dsl::SymbolTable;
+use brane_dsl::ast::{Block, Expr, Node as _, Program, Stmt};
+use specifications::data::DataIndex;
+use specifications::package::PackageIndex;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Tests whether usages of variables and functions are linked to their definitions.
+    #[test]
+    fn test_definitions() {
+        let source: &str = "func f(x) {\n    return x;\n}\nlet y := f(42);\nprintln(y);\n";
+        let analysis: Analysis = analyse(source, &PackageIndex::empty(), &DataIndex::from_infos(vec![]).unwrap());
+        assert!(analysis.diagnostics.iter().all(|d| d.severity != Some(DiagnosticSeverity::ERROR)), "Unexpected errors: {:?}", analysis.diagnostics);
+        let resolved: Resolved = analysis.resolved.expect("Failed to resolve test source");
+
+        // `y` in `println(y)` refers to the let-statement on line 4
+        let target: Range = resolved.definition_at(Position::new(4, 8)).expect("No definition found for 'y'");
+        assert_eq!(target.start.line, 3);
+        // `f` in `f(42)` refers to the function definition on line 1
+        let target: Range = resolved.definition_at(Position::new(3, 9)).expect("No definition found for 'f'");
+        assert_eq!(target.start.line, 0);
+        // The builtin `println` has no definition
+        assert!(resolved.definition_at(Position::new(4, 2)).is_none());
+    }
+
+    /// Tests whether positions are given in UTF-16 code units, as the LSP wants them, instead of the bytes the compiler counts.
+    #[test]
+    fn test_utf16() {
+        // `crab` is used at byte 28, character 25 and UTF-16 code unit 26
+        let source: &str = "let crab := \"🦀\"; println(crab);\n";
+        let analysis: Analysis = analyse(source, &PackageIndex::empty(), &DataIndex::from_infos(vec![]).unwrap());
+        assert!(analysis.diagnostics.iter().all(|d| d.severity != Some(DiagnosticSeverity::ERROR)), "Unexpected errors: {:?}", analysis.diagnostics);
+        let resolved: Resolved = analysis.resolved.expect("Failed to resolve test source");
+        assert!(resolved.definitions.iter().any(|d| d.usage.start == Position::new(0, 26)), "No usage of 'crab' at UTF-16 offset 26: {:?}", resolved.definitions);
+        assert_eq!(resolved.definition_at(Position::new(0, 27)).map(|r| r.start.line), Some(0));
+        assert!(resolved.definition_at(Position::new(0, 25)).is_none());
+
+        // Columns past the end of a line or in the middle of a character are clamped
+        let lines: Vec<&str> = vec![ "a🦀b", "é" ];
+        assert_eq!(to_utf16(&lines, Position::new(0, 5)), Position::new(0, 3));
+        assert_eq!(to_utf16(&lines, Position::new(0, 3)), Position::new(0, 1));
+        assert_eq!(to_utf16(&lines, Position::new(1, 9)), Position::new(1, 1));
+        assert_eq!(to_utf16(&lines, Position::new(5, 2)), Position::new(5, 2));
+        assert_eq!(end_of("a\n🦀x"), Position::new(1, 5));
+    }
+
+    /// Tests whether documentation comments are found both at definitions and usages.
+    #[test]
+    fn test_docs() {
+        let source: &str = "/// Doubles the given number.\n///\n/// Only works for integers.\nfunc double(x) {\n    /// Not attached to anything\n    return x * 2;\n}\nprintln(double(21));\n";
+        let analysis: Analysis = analyse(source, &PackageIndex::empty(), &DataIndex::from_infos(vec![]).unwrap());
+        assert!(analysis.diagnostics.iter().all(|d| d.severity != Some(DiagnosticSeverity::ERROR)), "Unexpected errors: {:?}", analysis.diagnostics);
+        let resolved: Resolved = analysis.resolved.expect("Failed to resolve test source");
+
+        // Both the definition and the usage of `double` are documented
+        let (_, text): (Range, &str) = resolved.docs_at(Position::new(3, 6)).expect("No docs found for the definition of 'double'");
+        assert_eq!(text, "Doubles the given number.\n\nOnly works for integers.");
+        let (_, text): (Range, &str) = resolved.docs_at(Position::new(7, 10)).expect("No docs found for the usage of 'double'");
+        assert_eq!(text, "Doubles the given number.\n\nOnly works for integers.");
+        // `x` is not
+        assert!(resolved.docs_at(Position::new(5, 11)).is_none());
+    }
+}
+
+
+
+
+
+
+/***** CONSTANTS *****/
+/// The keywords of BraneScript, which are always suggested when completing.
+pub const KEYWORDS: [ &str; 16 ] = [ "break", "class", "continue", "else", "for", "func", "if", "import", "in", "let", "new", "on", "parallel", "return", "unit", "while" ];
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Converts a (one-indexed) TextPos to a (zero-indexed) LSP Position.
+///
+/// # Arguments
+/// - `pos`: The TextPos to convert.
+///
+/// # Returns
+/// An equivalent Position, or the start of the document if the TextPos represents 'no position'.
+#[inline]
+fn to_position(pos: &TextPos) -> Position {
+    if pos.is_none() { return Position::default(); }
+    Position::new(pos.line.saturating_sub(1) as u32, pos.col.saturating_sub(1) as u32)
+}
+
+/// Returns the position of the end of the given source text.
+///
+/// # Arguments
+/// - `source`: The source text to find the end of.
+///
+/// # Returns
+/// The Position just after the last character in the source, with its column in bytes (like the compiler's).
+fn end_of(source: &str) -> Position {
+    let line: usize = source.matches('\n').count();
+    let col: usize  = source.rsplit('\n').next().map(str::len).unwrap_or(0);
+    Position::new(line as u32, col as u32)
+}
+
+/// Converts a Position with its column in bytes (as the compiler counts them) to one with its column in UTF-16 code units (as the LSP counts them).
+///
+/// # Arguments
+/// - `lines`: The lines of the source text that the Position points into.
+/// - `pos`: The Position to convert.
+///
+/// # Returns
+/// An equivalent Position. Columns in the middle of a character or past the end of a line are clamped, and positions on lines that do not exist are returned as-is.
+fn to_utf16(lines: &[&str], pos: Position) -> Position {
+    let line: &str = match lines.get(pos.line as usize) {
+        Some(line) => line,
+        None       => { return pos; },
+    };
+    let mut col: usize = (pos.character as usize).min(line.len());
+    while !line.is_char_boundary(col) { col -= 1; }
+    Position::new(pos.line, line[..col].encode_utf16().count() as u32)
+}
+
+/// Converts a Range with its columns in bytes to one with its columns in UTF-16 code units.
+///
+/// # Arguments
+/// - `lines`: The lines of the source text that the Range points into.
+/// - `range`: The Range to convert.
+///
+/// # Returns
+/// An equivalent Range (see `to_utf16()`).
+#[inline]
+fn range_to_utf16(lines: &[&str], range: Range) -> Range { Range::new(to_utf16(lines, range.start), to_utf16(lines, range.end)) }
+
+/// Converts a compiler error to an LSP Diagnostic.
+///
+/// # Arguments
+/// - `err`: The Error to convert.
+/// - `fallback`: The Range to use if the error has no range of its own.
+///
+/// # Returns
+/// A new Diagnostic describing the error.
+fn error_diagnostic(err: &Error, fallback: Range) -> Diagnostic {
+    Diagnostic {
+        range    : err.range().map(to_range).unwrap_or(fallback),
+        severity : Some(DiagnosticSeverity::ERROR),
+        source   : Some("branec".into()),
+        message  : err.to_string(),
+        ..Default::default()
+    }
+}
+
+/// Converts a compiler warning to an LSP Diagnostic.
+///
+/// # Arguments
+/// - `warn`: The Warning to convert.
+///
+/// # Returns
+/// A new Diagnostic describing the warning.
+fn warning_diagnostic(warn: &Warning) -> Diagnostic {
+    Diagnostic {
+        range    : warn.range().map(to_range).unwrap_or_default(),
+        severity : Some(DiagnosticSeverity::WARNING),
+        source   : Some("branec".into()),
+        message  : warn.to_string(),
+        ..Default::default()
+    }
+}
+
+
+
+/// Remembers that the given usage refers to the given definition.
+///
+/// # Arguments
+/// - `usage`: The range of the identifier that is used.
+/// - `target`: The range of the definition that it refers to.
+/// - `defs`: The list of Definitions to add it to.
+///
+/// # Returns
+/// Nothing, but does push a new Definition if both ranges actually exist (i.e., builtins are skipped).
+#[inline]
+fn push_definition(usage: &TextRange, target: &TextRange, defs: &mut Vec<Definition>) {
+    if usage.is_some() && target.is_some() {
+        defs.push(Definition{ usage: to_range(usage), target: to_range(target) });
+    }
+}
+
+/// Remembers that the given usage or definition has the given documentation.
+///
+/// # Arguments
+/// - `usage`: The range of the identifier that is documented.
+/// - `text`: The documentation comments of whatever the identifier refers to, if any.
+/// - `docs`: The list of Documentations to add it to.
+///
+/// # Returns
+/// Nothing, but does push a new Documentation if there is any and the range actually exists.
+#[inline]
+fn push_docs(usage: &TextRange, text: &Option<String>, docs: &mut Vec<Documentation>) {
+    if let Some(text) = text {
+        if usage.is_some() { docs.push(Documentation{ usage: to_range(usage), text: text.clone() }); }
+    }
+}
+
+/// Collects the names defined in the given SymbolTable.
+///
+/// # Arguments
+/// - `table`: The SymbolTable to collect the names of.
+/// - `symbols`: The list of Symbols to add them to.
+fn collect_table(table: &Rc<RefCell<SymbolTable>>, symbols: &mut Vec<Symbol>) {
+    let table: std::cell::Ref<SymbolTable> = table.borrow();
+    for (name, _) in table.functions() {
+        symbols.push(Symbol{ name: name.clone(), kind: CompletionItemKind::FUNCTION });
+    }
+    for (name, _) in table.classes() {
+        symbols.push(Symbol{ name: name.clone(), kind: CompletionItemKind::CLASS });
+    }
+    for (name, _) in table.variables() {
+        symbols.push(Symbol{ name: name.clone(), kind: CompletionItemKind::VARIABLE });
+    }
+}
+
+
+
+/// Traverses a Block to find the usages and definitions in it.
+///
+/// # Arguments
+/// - `block`: The Block to traverse.
+/// - `defs`: The list of Definitions to add any usage to.
+/// - `docs`: The list of Documentations to add any documented usage or definition to.
+/// - `symbols`: The list of Symbols to add any defined name to.
+fn pass_block(block: &Block, defs: &mut Vec<Definition>, docs: &mut Vec<Documentation>, symbols: &mut Vec<Symbol>) {
+    collect_table(&block.table, symbols);
+    for stmt in &block.stmts {
+        pass_stmt(stmt, defs, docs, symbols);
+    }
+}
+
+/// Traverses a Stmt to find the usages and definitions in it.
+///
+/// # Arguments
+/// - `stmt`: The Stmt to traverse.
+/// - `defs`: The list of Definitions to add any usage to.
+/// - `docs`: The list of Documentations to add any documented usage or definition to.
+/// - `symbols`: The list of Symbols to add any defined name to.
+fn pass_stmt(stmt: &Stmt, defs: &mut Vec<Definition>, docs: &mut Vec<Documentation>, symbols: &mut Vec<Symbol>) {
+    use Stmt::*;
+    match stmt {
+        Block{ block } => { pass_block(block, defs, docs, symbols); },
+
+        FuncDef{ ident, code, docs: text, .. } => {
+            push_docs(&ident.range, text, docs);
+            pass_block(code, defs, docs, symbols);
+        },
+        ClassDef{ ident, methods, docs: text, .. } => {
+            push_docs(&ident.range, text, docs);
+            for method in methods {
+                pass_stmt(method, defs, docs, symbols);
+            }
+        },
+        Return{ expr, .. } => {
+            if let Some(expr) = expr { pass_expr(expr, defs, docs); }
+        },
+
+        If{ cond, consequent, alternative, .. } => {
+            pass_expr(cond, defs, docs);
+            pass_block(consequent, defs, docs, symbols);
+            if let Some(alternative) = alternative { pass_block(alternative, defs, docs, symbols); }
+        },
+        For{ initializer, condition, increment, consequent, .. } => {
+            pass_stmt(initializer, defs, docs, symbols);
+            pass_expr(condition, defs, docs);
+            pass_stmt(increment, defs, docs, symbols);
+            pass_block(consequent, defs, docs, symbols);
+        },
+        While{ condition, consequent, .. } => {
+            pass_expr(condition, defs, docs);
+            pass_block(consequent, defs, docs, symbols);
+        },
+        On{ location, block, .. } => {
+            pass_expr(location, defs, docs);
+            pass_block(block, defs, docs, symbols);
+        },
+        Parallel{ blocks, .. } => {
+            for block in blocks {
+                pass_stmt(block, defs, docs, symbols);
+            }
+        },
+
+        LetAssign{ value, .. } => { pass_expr(value, defs, docs); },
+        Assign{ name, value, st_entry, .. } => {
+            if let Some(entry) = st_entry { push_definition(&name.range, &entry.borrow().range, defs); }
+            pass_expr(value, defs, docs);
+        },
+        Expr{ expr, .. } => { pass_expr(expr, defs, docs); },
+
+        Import{ .. } |
+        Empty{}      => {},
+    }
+}
+
+/// Traverses an Expr to find the usages in it.
+///
+/// # Arguments
+/// - `expr`: The Expr to traverse.
+/// - `defs`: The list of Definitions to add any usage to.
+/// - `docs`: The list of Documentations to add any documented usage to.
+fn pass_expr(expr: &Expr, defs: &mut Vec<Definition>, docs: &mut Vec<Documentation>) {
+    use brane_dsl::symbol_table::SymbolTableEntry;
+    match expr {
+        Expr::Cast{ expr, .. }  => { pass_expr(expr, defs, docs); },
+        Expr::Call{ expr, args, .. } => {
+            pass_expr(expr, defs, docs);
+            for arg in args {
+                pass_expr(arg, defs, docs);
+            }
+        },
+        Expr::Array{ values, .. } => {
+            for value in values {
+                pass_expr(value, defs, docs);
+            }
+        },
+        Expr::ArrayIndex{ array, index, .. } => {
+            pass_expr(array, defs, docs);
+            pass_expr(index, defs, docs);
+        },
+        Expr::Map{ entries, .. } => {
+            for (key, value) in entries {
+                pass_expr(key, defs, docs);
+                pass_expr(value, defs, docs);
+            }
+        },
+        Expr::Pattern{ exprs, .. } => {
+            for expr in exprs {
+                pass_expr(expr, defs, docs);
+            }
+        },
+        Expr::UnaOp{ expr, .. }     => { pass_expr(expr, defs, docs); },
+        Expr::BinOp{ lhs, rhs, .. } => {
+            pass_expr(lhs, defs, docs);
+            pass_expr(rhs, defs, docs);
+        },
+        Expr::Proj{ lhs, rhs, st_entry, .. } => {
+            pass_expr(lhs, defs, docs);
+            match st_entry {
+                Some(SymbolTableEntry::FunctionEntry(entry)) => {
+                    push_definition(rhs.range(), &entry.borrow().range, defs);
+                    push_docs(rhs.range(), &entry.borrow().docs, docs);
+                },
+                Some(SymbolTableEntry::ClassEntry(entry)) => {
+                    push_definition(rhs.range(), &entry.borrow().range, defs);
+                    push_docs(rhs.range(), &entry.borrow().docs, docs);
+                },
+                Some(SymbolTableEntry::VarEntry(entry))      => { push_definition(rhs.range(), &entry.borrow().range, defs); },
+                None                                         => {},
+            }
+        },
+        Expr::Instance{ name, properties, st_entry, .. } => {
+            if let Some(entry) = st_entry {
+                push_definition(&name.range, &entry.borrow().range, defs);
+                push_docs(&name.range, &entry.borrow().docs, docs);
+            }
+            for prop in properties {
+                pass_expr(&prop.value, defs, docs);
+            }
+        },
+
+        Expr::VarRef{ name, st_entry } => {
+            if let Some(entry) = st_entry { push_definition(&name.range, &entry.borrow().range, defs); }
+        },
+        Expr::Identifier{ name, st_entry } => {
+            if let Some(entry) = st_entry {
+                push_definition(&name.range, &entry.borrow().range, defs);
+                push_docs(&name.range, &entry.borrow().docs, docs);
+            }
+        },
+
+        Expr::Literal{ .. } |
+        Expr::Empty{}       => {},
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Converts a (one-indexed, end-exclusive) TextRange to a (zero-indexed) LSP Range.
+///
+/// Note that the columns of the Range are still in bytes, like those of the compiler; `analyse()` converts them to UTF-16 code units for the LSP.
+///
+/// # Arguments
+/// - `range`: The TextRange to convert.
+///
+/// # Returns
+/// An equivalent Range.
+#[inline]
+pub fn to_range(range: &TextRange) -> Range { Range::new(to_position(&range.start), to_position(&range.end)) }
+
+
+
+/// Links a usage of some identifier to where it is defined.
+#[derive(Clone, Debug)]
+pub struct Definition {
+    /// The range of the identifier that is used.
+    pub usage  : Range,
+    /// The range of the definition it refers to.
+    pub target : Range,
+}
+
+/// Links a usage or definition of some function or class to its documentation comments.
+#[derive(Clone, Debug)]
+pub struct Documentation {
+    /// The range of the identifier that is documented.
+    pub usage : Range,
+    /// The text of the documentation comments (without the slashes).
+    pub text  : String,
+}
+
+/// Defines a name that is defined in the document, for completion purposes.
+#[derive(Clone, Debug)]
+pub struct Symbol {
+    /// The name of the symbol.
+    pub name : String,
+    /// What kind of thing the symbol refers to.
+    pub kind : CompletionItemKind,
+}
+
+/// Defines the result of resolving a document (i.e., what names it defines and where they are used).
+#[derive(Clone, Debug, Default)]
+pub struct Resolved {
+    /// The usages found in the document.
+    pub definitions : Vec<Definition>,
+    /// The documented usages and definitions found in the document.
+    pub docs        : Vec<Documentation>,
+    /// The names defined in the document.
+    pub symbols     : Vec<Symbol>,
+}
+
+impl Resolved {
+    /// Finds the definition of whatever is used at the given position.
+    ///
+    /// # Arguments
+    /// - `pos`: The Position of the usage.
+    ///
+    /// # Returns
+    /// The Range of the definition if anything with a definition is used there, or else None.
+    pub fn definition_at(&self, pos: Position) -> Option<Range> {
+        self.definitions.iter()
+            .filter(|d| d.usage.start <= pos && pos <= d.usage.end)
+            .min_by_key(|d| (d.usage.end.line - d.usage.start.line, d.usage.end.character.saturating_sub(d.usage.start.character)))
+            .map(|d| d.target)
+    }
+
+    /// Finds the documentation of whatever is used or defined at the given position.
+    ///
+    /// # Arguments
+    /// - `pos`: The Position of the usage or definition.
+    ///
+    /// # Returns
+    /// The range of the identifier at that position and the text of its documentation comments, or else None if nothing documented is there.
+    pub fn docs_at(&self, pos: Position) -> Option<(Range, &str)> {
+        self.docs.iter()
+            .filter(|d| d.usage.start <= pos && pos <= d.usage.end)
+            .min_by_key(|d| (d.usage.end.line - d.usage.start.line, d.usage.end.character.saturating_sub(d.usage.start.character)))
+            .map(|d| (d.usage, d.text.as_str()))
+    }
+}
+
+/// Defines the result of analysing a single document.
+#[derive(Clone, Debug, Default)]
+pub struct Analysis {
+    /// The errors and warnings found in the document.
+    pub diagnostics : Vec<Diagnostic>,
+    /// The names defined in the document, if it could be resolved.
+    pub resolved    : Option<Resolved>,
+}
+
+
+
+/// Analyses the given BraneScript source text.
+///
+/// This runs the entire compiler for the diagnostics, and only the resolve pass for the definitions and symbols (so those remain available when, e.g., typing fails).
+///
+/// # Arguments
+/// - `source`: The BraneScript source text to analyse.
+/// - `pindex`: The PackageIndex that is used to resolve imports.
+/// - `dindex`: The DataIndex that is used to resolve `Data`-structs.
+///
+/// # Returns
+/// An Analysis with the found diagnostics and, if the document could be resolved, its definitions. All positions in it are in UTF-16 code units, as the LSP specifies.
+pub fn analyse(source: &str, pindex: &PackageIndex, dindex: &DataIndex) -> Analysis {
+    let options: ParserOptions = ParserOptions::bscript();
+
+    // Compile the whole thing for the diagnostics
+    let diagnostics: Vec<Diagnostic> = match compile_program_to(source.as_bytes(), pindex, dindex, &options, CompileStage::All) {
+        CompileResult::Workflow(_, warns)   |
+        CompileResult::Unresolved(_, warns) |
+        CompileResult::Program(_, warns)    => warns.iter().map(warning_diagnostic).collect(),
+        CompileResult::Eof(err)             => {
+            let end: Position = end_of(source);
+            vec![ error_diagnostic(&err, Range::new(end, end)) ]
+        },
+        CompileResult::Err(errs) => errs.iter().map(|err| error_diagnostic(err, Range::default())).collect(),
+    };
+
+    // Only resolve it for the definitions
+    let resolved: Option<Resolved> = match compile_program_to(source.as_bytes(), pindex, dindex, &options, CompileStage::Resolve) {
+        CompileResult::Program(program, _) => Some(resolve(&program)),
+        _                                  => None,
+    };
+
+    // The compiler counts columns in bytes, but the LSP in UTF-16 code units
+    let lines: Vec<&str> = source.split('\n').collect();
+    let diagnostics: Vec<Diagnostic> = diagnostics.into_iter().map(|d| Diagnostic{ range: range_to_utf16(&lines, d.range), ..d }).collect();
+    let resolved: Option<Resolved> = resolved.map(|resolved| Resolved {
+        definitions : resolved.definitions.into_iter().map(|d| Definition{ usage: range_to_utf16(&lines, d.usage), target: range_to_utf16(&lines, d.target) }).collect(),
+        docs        : resolved.docs.into_iter().map(|d| Documentation{ usage: range_to_utf16(&lines, d.usage), ..d }).collect(),
+        symbols     : resolved.symbols,
+    });
+
+    // Done
+    Analysis{ diagnostics, resolved }
+}
+
+/// Collects the definitions and symbols in a resolved Program.
+///
+/// # Arguments
+/// - `program`: The Program to traverse. Must have been run through the resolve pass.
+///
+/// # Returns
+/// The definitions, documentation and symbols found in the program, as a Resolved. Note that its columns are in bytes (see `to_range()`).
+pub fn resolve(program: &Program) -> Resolved {
+    let mut defs: Vec<Definition>   = vec![];
+    let mut docs: Vec<Documentation> = vec![];
+    let mut symbols: Vec<Symbol>     = vec![];
+    pass_block(&program.block, &mut defs, &mut docs, &mut symbols);
+    Resolved{ definitions: defs, docs, symbols }
+}
+
+/// Returns the names that are always available in BraneScript, regardless of the document.
+///
+/// # Arguments
+/// - `pindex`: The PackageIndex with the packages that may be imported.
+///
+/// # Returns
+/// A list of Symbols for the keywords, builtins, packages and package functions.
+pub fn global_symbols(pindex: &PackageIndex) -> Vec<Symbol> {
+    let mut symbols: Vec<Symbol> = KEYWORDS.iter().map(|k| Symbol{ name: k.to_string(), kind: CompletionItemKind::KEYWORD }).collect();
+    symbols.extend(BuiltinFunctions::all().iter().map(|f| Symbol{ name: f.name().into(), kind: CompletionItemKind::FUNCTION }));
+    symbols.extend(BuiltinClasses::all().iter().map(|c| Symbol{ name: c.name().into(), kind: CompletionItemKind::CLASS }));
+    for info in pindex.packages.values() {
+        symbols.push(Symbol{ name: info.name.clone(), kind: CompletionItemKind::MODULE });
+        symbols.extend(info.functions.keys().map(|f| Symbol{ name: f.clone(), kind: CompletionItemKind::FUNCTION }));
+    }
+    symbols
+}
+
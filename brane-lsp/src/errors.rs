@@ -0,0 +1,45 @@
+//  ERRORS.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 08:18:41
+//  Last edited:
+//    16 Oct 2026, 08:18:41
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Defines errors for the `brane-lsp` crate.
+// 
+
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FResult};
+
+
+/***** LIBRARY *****/
+/// Collects errors that relate to running the language server.
+#[derive(Debug)]
+pub enum LspError {
+    /// Failed to fetch the remote package index.
+    RemotePackageIndexError{ endpoint: String, err: brane_tsk::api::Error },
+    /// Failed to fetch the remote data index.
+    RemoteDataIndexError{ endpoint: String, err: brane_tsk::api::Error },
+    /// Failed to fetch the local package index.
+    LocalPackageIndexError{ err: brane_tsk::local::Error },
+    /// Failed to fetch the local data index.
+    LocalDataIndexError{ err: brane_tsk::local::Error },
+}
+
+impl Display for LspError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use self::LspError::*;
+        match self {
+            RemotePackageIndexError{ endpoint, err } => write!(f, "Failed to fetch remote package index from '{}': {}", endpoint, err),
+            RemoteDataIndexError{ endpoint, err }    => write!(f, "Failed to fetch remote data index from '{}': {}", endpoint, err),
+            LocalPackageIndexError{ err }            => write!(f, "Failed to fetch local package index: {}", err),
+            LocalDataIndexError{ err }               => write!(f, "Failed to fetch local data index: {}", err),
+        }
+    }
+}
+
+impl Error for LspError {}
@@ -0,0 +1,23 @@
+//  LIB.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 08:18:41
+//  Last edited:
+//    16 Oct 2026, 08:18:41
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   The `brane-lsp` executable implements a Language Server Protocol
+//!   server for BraneScript.
+//!   
+//!   It reuses the scanner, parser and compiler passes of `brane-dsl` and
+//!   `brane-ast` to provide editors with diagnostics, go-to-definition and
+//!   completion.
+// 
+
+// Declare modules
+pub mod errors;
+pub mod analysis;
+pub mod server;
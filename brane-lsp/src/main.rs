@@ -0,0 +1,164 @@
+//  MAIN.rs
+//    by agent
+//
+//  Created:
+//    16 Oct 2026, 08:18:41
+//  Last edited:
+//    16 Oct 2026, 12:59:49
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Entrypoint to the `brane-lsp` binary.
+//
+
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+use dotenvy::dotenv;
+use expanduser::expanduser;
+use human_panic::setup_panic;
+use log::{debug, info, error, LevelFilter};
+use tower_lsp::{LspService, Server};
+
+use brane_cc::spec::IndexLocation;
+use brane_tsk::api::ApiClient;
+use specifications::data::DataIndex;
+use specifications::package::PackageIndex;
+
+use brane_lsp::errors::LspError;
+use brane_lsp::server::Backend;
+
+
+/***** ARGUMENTS *****/
+/// The arguments for the `brane-lsp` binary.
+#[derive(Parser)]
+#[clap(name = "brane-lsp", author, about = "A Language Server Protocol server for BraneScript. Communicates with the editor over stdin/stdout.")]
+struct Arguments {
+    /// If given, shows debug prints.
+    #[clap(long, help="If given, shows additional prints in the log (on stderr).", env = "DEBUG")]
+    debug : bool,
+
+    /// The path / address of the packages index.
+    #[clap(short, long, default_value="~/.local/share/brane/packages", help="The location to read the package index from. If it's a path, reads it from the local machine; if it's an address, attempts to read it from the Brane instance instead. You can wrap your input in 'Local<...>' or 'Remote<...>' to disambiguate between the two.")]
+    packages : IndexLocation,
+    /// The path / address of the data index.
+    #[clap(short, long, default_value="~/.local/share/brane/data", help="The location to read the data index from. If it's a path, reads it from the local machine; if it's an address, attempts to read it from the Brane instance instead. You can wrap your input in 'Local<...>' or 'Remote<...>' to disambiguate between the two.")]
+    data     : IndexLocation,
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Resolves any tildes in the given local path.
+///
+/// # Arguments
+/// - `local`: The path to resolve.
+///
+/// # Returns
+/// The resolved path, or the original one if it could not be resolved.
+#[inline]
+fn resolve_local(local: &Path) -> PathBuf {
+    match expanduser(local.to_string_lossy()) {
+        Ok(local) => local,
+        Err(_)    => local.to_path_buf(),
+    }
+}
+
+/// Loads the package and data indices that we use to resolve imports and datasets.
+///
+/// # Arguments
+/// - `packages_loc`: Where to get the package index from. Implemented as an IndexLocation so it may be both local or remote.
+/// - `data_loc`: Where to get the data index from. Implemented as an IndexLocation so it may be both local or remote.
+///
+/// # Returns
+/// The loaded PackageIndex and DataIndex.
+///
+/// # Errors
+/// This function errors if we failed to fetch either of the indices.
+async fn load_indices(packages_loc: &IndexLocation, data_loc: &IndexLocation) -> Result<(PackageIndex, DataIndex), LspError> {
+    let pindex: PackageIndex = match packages_loc {
+        IndexLocation::Remote(remote) => {
+            debug!("Fetching remote package index from '{}'...", remote);
+            match brane_tsk::api::get_package_index(remote).await {
+                Ok(pindex) => pindex,
+                Err(err)   => { return Err(LspError::RemotePackageIndexError { endpoint: remote.clone(), err }); },
+            }
+        },
+
+        IndexLocation::Local(local) => {
+            let local: PathBuf = resolve_local(local);
+            debug!("Fetching local package index from '{}'...", local.display());
+            match brane_tsk::local::get_package_index(local) {
+                Ok(pindex) => pindex,
+                Err(err)   => { return Err(LspError::LocalPackageIndexError { err }); },
+            }
+        },
+    };
+    let dindex: DataIndex = match data_loc {
+        IndexLocation::Remote(remote) => {
+            debug!("Fetching remote data index from '{}'...", remote);
+            // Accept both the address of the API and (as before) that of its `/data/info` path
+            match ApiClient::new(remote.strip_suffix("/data/info").unwrap_or(remote)).data_index().await {
+                Ok(dindex) => dindex,
+                Err(err)   => { return Err(LspError::RemoteDataIndexError { endpoint: remote.clone(), err }); },
+            }
+        },
+
+        IndexLocation::Local(local) => {
+            let local: PathBuf = resolve_local(local);
+            debug!("Fetching local data index from '{}'...", local.display());
+            match brane_tsk::local::get_data_index(local) {
+                Ok(dindex) => dindex,
+                Err(err)   => { return Err(LspError::LocalDataIndexError { err }); },
+            }
+        },
+    };
+
+    // Done
+    Ok((pindex, dindex))
+}
+
+
+
+
+
+/***** ENTRYPOINT *****/
+#[tokio::main]
+async fn main() {
+    // Parse any environment file
+    dotenv().ok();
+
+    // Parse the arguments
+    let args: Arguments = Arguments::parse();
+
+    // Setup the logger (which writes to stderr, as stdout is reserved for the protocol)
+    let mut logger = env_logger::builder();
+    logger.format_module_path(false);
+
+    if args.debug {
+        logger.filter_module("brane", LevelFilter::Debug).init();
+    } else {
+        logger.filter_module("brane", LevelFilter::Warn).init();
+
+        setup_panic!(Metadata {
+            name: "Brane LSP".into(),
+            version: env!("CARGO_PKG_VERSION").into(),
+            authors: env!("CARGO_PKG_AUTHORS").replace(":", ", ").into(),
+            homepage: env!("CARGO_PKG_HOMEPAGE").into(),
+        });
+    }
+    info!("Initializing brane-lsp v{}", env!("CARGO_PKG_VERSION"));
+
+    // Load the indices once
+    let (pindex, dindex): (PackageIndex, DataIndex) = match load_indices(&args.packages, &args.data).await {
+        Ok(indices) => indices,
+        Err(err)    => { error!("{}", err); std::process::exit(1); },
+    };
+
+    // Serve on stdin/stdout
+    let (service, socket) = LspService::new(|client| Backend::new(client, pindex, dindex));
+    Server::new(tokio::io::stdin(), tokio::io::stdout(), socket).serve(service).await;
+}
@@ -0,0 +1,176 @@
+//  SERVER.rs
+//    by agent
+//
+//  Created:
+//    16 Oct 2026, 08:18:41
+//  Last edited:
+//    16 Oct 2026, 11:21:01
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements the Language Server Protocol side of `brane-lsp`, which
+//!   keeps track of the open documents and answers the editor's requests
+//!   from their analyses.
+//
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use log::debug;
+use tower_lsp::{Client, LanguageServer};
+use tower_lsp::jsonrpc::Result as RpcResult;
+use tower_lsp::lsp_types::{
+    CompletionItem, CompletionOptions, CompletionParams, CompletionResponse, DidChangeTextDocumentParams, DidCloseTextDocumentParams,
+    DidOpenTextDocumentParams, GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverContents, HoverParams, HoverProviderCapability,
+    InitializeParams, InitializeResult, InitializedParams, Location, MarkupContent, MarkupKind, MessageType, OneOf, Range, ServerCapabilities,
+    ServerInfo, TextDocumentSyncCapability, TextDocumentSyncKind, Url,
+};
+
+use specifications::data::DataIndex;
+use specifications::package::PackageIndex;
+
+use crate::analysis::{analyse, global_symbols, Analysis, Resolved, Symbol};
+
+
+/***** HELPER STRUCTS *****/
+/// Defines what we remember of every open document.
+#[derive(Debug)]
+struct Document {
+    /// The last successful resolve of the document, which we keep around while the user is typing (and the document is likely broken).
+    resolved : Resolved,
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Implements the BraneScript language server.
+pub struct Backend {
+    /// The client (editor) to send notifications to.
+    client    : Client,
+    /// The PackageIndex used to resolve imports.
+    pindex    : PackageIndex,
+    /// The DataIndex used to resolve `Data`-structs.
+    dindex    : DataIndex,
+    /// The names that are available in every document.
+    globals   : Vec<Symbol>,
+    /// The documents that are currently open in the editor.
+    documents : Mutex<HashMap<Url, Document>>,
+}
+
+impl Backend {
+    /// Constructor for the Backend.
+    ///
+    /// # Arguments
+    /// - `client`: The Client to send notifications to.
+    /// - `pindex`: The PackageIndex used to resolve imports.
+    /// - `dindex`: The DataIndex used to resolve `Data`-structs.
+    ///
+    /// # Returns
+    /// A new Backend instance without any open documents.
+    #[inline]
+    pub fn new(client: Client, pindex: PackageIndex, dindex: DataIndex) -> Self {
+        Self {
+            client,
+            globals   : global_symbols(&pindex),
+            pindex,
+            dindex,
+            documents : Mutex::new(HashMap::new()),
+        }
+    }
+
+
+
+    /// Re-analyses a document and publishes its diagnostics.
+    ///
+    /// # Arguments
+    /// - `uri`: The Url of the document.
+    /// - `source`: The new contents of the document.
+    /// - `version`: The version of the document, as given by the client.
+    async fn update(&self, uri: Url, source: &str, version: Option<i32>) {
+        debug!("Analysing document '{}'...", uri);
+        let analysis: Analysis = analyse(source, &self.pindex, &self.dindex);
+
+        // Only overwrite the definitions if we got any
+        if let Some(resolved) = analysis.resolved {
+            self.documents.lock().unwrap().insert(uri.clone(), Document{ resolved });
+        }
+        self.client.publish_diagnostics(uri, analysis.diagnostics, version).await;
+    }
+}
+
+#[tower_lsp::async_trait]
+impl LanguageServer for Backend {
+    async fn initialize(&self, _params: InitializeParams) -> RpcResult<InitializeResult> {
+        Ok(InitializeResult {
+            capabilities : ServerCapabilities {
+                text_document_sync  : Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+                definition_provider : Some(OneOf::Left(true)),
+                hover_provider      : Some(HoverProviderCapability::Simple(true)),
+                completion_provider : Some(CompletionOptions::default()),
+                ..Default::default()
+            },
+            server_info : Some(ServerInfo{ name: env!("CARGO_PKG_NAME").into(), version: Some(env!("CARGO_PKG_VERSION").into()) }),
+        })
+    }
+
+    async fn initialized(&self, _params: InitializedParams) {
+        self.client.log_message(MessageType::INFO, "BraneScript language server initialized").await;
+    }
+
+    async fn shutdown(&self) -> RpcResult<()> { Ok(()) }
+
+
+
+    async fn did_open(&self, params: DidOpenTextDocumentParams) {
+        self.update(params.text_document.uri, &params.text_document.text, Some(params.text_document.version)).await;
+    }
+
+    async fn did_change(&self, params: DidChangeTextDocumentParams) {
+        // We use full synchronization, so the last change is the entire document
+        if let Some(change) = params.content_changes.into_iter().last() {
+            self.update(params.text_document.uri, &change.text, Some(params.text_document.version)).await;
+        }
+    }
+
+    async fn did_close(&self, params: DidCloseTextDocumentParams) {
+        self.documents.lock().unwrap().remove(&params.text_document.uri);
+        self.client.publish_diagnostics(params.text_document.uri, vec![], None).await;
+    }
+
+
+
+    async fn goto_definition(&self, params: GotoDefinitionParams) -> RpcResult<Option<GotoDefinitionResponse>> {
+        let uri: Url = params.text_document_position_params.text_document.uri;
+        let target: Option<Range> = self.documents.lock().unwrap()
+            .get(&uri)
+            .and_then(|doc| doc.resolved.definition_at(params.text_document_position_params.position));
+        Ok(target.map(|range| GotoDefinitionResponse::Scalar(Location::new(uri, range))))
+    }
+
+    async fn hover(&self, params: HoverParams) -> RpcResult<Option<Hover>> {
+        let docs = self.documents.lock().unwrap();
+        let hover: Option<Hover> = docs.get(&params.text_document_position_params.text_document.uri)
+            .and_then(|doc| doc.resolved.docs_at(params.text_document_position_params.position))
+            .map(|(range, text)| Hover {
+                contents : HoverContents::Markup(MarkupContent{ kind: MarkupKind::Markdown, value: text.into() }),
+                range    : Some(range),
+            });
+        Ok(hover)
+    }
+
+    async fn completion(&self, params: CompletionParams) -> RpcResult<Option<CompletionResponse>> {
+        let docs = self.documents.lock().unwrap();
+        let locals: &[Symbol] = docs.get(&params.text_document_position.text_document.uri).map(|doc| doc.resolved.symbols.as_slice()).unwrap_or_default();
+
+        // Collect the local and global names, without duplicates
+        let mut items: Vec<CompletionItem> = vec![];
+        for symbol in locals.iter().chain(self.globals.iter()) {
+            if items.iter().any(|i| i.label == symbol.name) { continue; }
+            items.push(CompletionItem{ label: symbol.name.clone(), kind: Some(symbol.kind), ..Default::default() });
+        }
+        Ok(Some(CompletionResponse::Array(items)))
+    }
+}
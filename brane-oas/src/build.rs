@@ -1,4 +1,5 @@
 use super::*;
+use crate::pagination::Pagination;
 use crate::resolver::{self, resolve_schema};
 use anyhow::Result;
 use openapiv3::{Components, Parameter as OParameter, Type as OType};
@@ -6,7 +7,8 @@ use openapiv3::{OpenAPI, ReferenceOr, SecurityScheme};
 use openapiv3::{Operation, ParameterSchemaOrContent, Schema, SchemaKind};
 use rand::distributions::Alphanumeric;
 use rand::{self, Rng};
-use specifications::common::{CallPattern, Function, Parameter, Property, Type};
+use serde_json::Value;
+use specifications::common::{CallPattern, Deprecation, Function, Parameter, Property, Type};
 
 type Map<T> = std::collections::HashMap<String, T>;
 type FunctionsAndTypes = (Map<Function>, Map<Type>);
@@ -15,6 +17,12 @@ const OAS_ADD_OPERATION_ID: &str = "Please add an operation ID (operationId) to
 const OAS_CONTENT_NOT_SUPPORTED: &str = "OpenAPI parameter content mapping is not supported.";
 const OAS_JSON_MEDIA_NOT_FOUND: &str = "JSON media type not found (application/json).";
 const OAS_NESTED_OBJECTS_NOT_SUPPORTED: &str = "Nested objects are not supported.";
+const OAS_PAGINATION_ITEMS_NOT_FOUND: &str = "Paginated operations must respond with an array of items (or an object with the array in the field given by 'items').";
+
+/// The name of the OpenAPI extension that deprecated operations use to name the function that replaces them.
+pub const REPLACEMENT_EXTENSION: &str = "x-brane-replacement";
+/// The name of the OpenAPI extension that operations use to list other (typically older) names they may be called by.
+pub const ALIASES_EXTENSION: &str = "x-brane-aliases";
 
 /// Traverses a valid OpenAPI document and builds a function
 /// for every operation it finds. Corresponding input/output
@@ -152,6 +160,38 @@ pub fn get_operation_id(
     Ok(operation_id)
 }
 
+/// Reads whether the given operation is deprecated (using the standard `deprecated` field), and
+/// which function replaces it (using the `x-brane-replacement` extension).
+pub fn build_oas_deprecation(operation: &Operation) -> Result<Option<Deprecation>> {
+    let replacement = match operation.extensions.get(REPLACEMENT_EXTENSION) {
+        Some(Value::String(replacement)) => Some(replacement.to_lowercase()),
+        Some(_) => bail!("'{}' must be the name of the function that replaces the operation", REPLACEMENT_EXTENSION),
+        None => None,
+    };
+    if !operation.deprecated {
+        ensure!(replacement.is_none(), "'{}' may only be declared on deprecated operations", REPLACEMENT_EXTENSION);
+        return Ok(None);
+    }
+
+    Ok(Some(Deprecation { replacement, message: None }))
+}
+
+/// Reads the other names the given operation may be called by (using the `x-brane-aliases` extension), if any.
+pub fn build_oas_aliases(operation: &Operation) -> Result<Option<Vec<String>>> {
+    match operation.extensions.get(ALIASES_EXTENSION) {
+        Some(Value::Array(values)) => values
+            .iter()
+            .map(|v| match v {
+                Value::String(alias) => Ok(alias.to_lowercase()),
+                _ => bail!("'{}' must be a list of function names", ALIASES_EXTENSION),
+            })
+            .collect::<Result<Vec<String>>>()
+            .map(Some),
+        Some(_) => bail!("'{}' must be a list of function names", ALIASES_EXTENSION),
+        None => Ok(None),
+    }
+}
+
 /// Builds a function for a OpenAPI operation. Corresponding
 /// input/output types, if any, are returned as well.
 pub fn build_oas_function(
@@ -160,14 +200,18 @@ pub fn build_oas_function(
     components: &Option<Components>,
     server_known: bool,
 ) -> Result<FunctionsAndTypes> {
-    let (input, i_types) = build_oas_function_input(&operation_id, operation, components, server_known)?;
-    let (output, o_types) = build_oas_function_output(&operation_id, operation, components)?;
+    let pagination = Pagination::from_operation(operation)?;
+    let (input, i_types) = build_oas_function_input(&operation_id, operation, components, server_known, pagination.as_ref())?;
+    let (output, o_types) = build_oas_function_output(&operation_id, operation, components, pagination.as_ref())?;
 
     // Build function
     let name = operation_id.to_lowercase();
     let call_pattern = CallPattern::new(Some(name.clone()), None, None);
+    let mut function = Function::new(input, Some(call_pattern), output, None);
+    function.deprecated = build_oas_deprecation(operation)?;
+    function.aliases = build_oas_aliases(operation)?;
     let functions = hashmap! {
-        name => Function::new(input, Some(call_pattern), output, None)
+        name => function
     };
 
     // Combine input and output types
@@ -186,6 +230,7 @@ fn build_oas_function_input(
     operation: &Operation,
     components: &Option<Components>,
     server_known: bool,
+    pagination: Option<&Pagination>,
 ) -> Result<(Vec<Parameter>, Map<Type>)> {
     let mut input_properties = Vec::<Property>::new();
     let mut input_types = Map::<Type>::new();
@@ -198,6 +243,11 @@ fn build_oas_function_input(
         input_properties.append(&mut properties);
     }
 
+    // The pagination parameter is set by the package itself, so it's not an input.
+    if let Some(pagination) = pagination {
+        input_properties.retain(|p| p.name != pagination.parameter());
+    }
+
     // Determine input from request body.
     if let Some(request_body) = &operation.request_body {
         let request_body = resolver::resolve_request_body(request_body, components)?;
@@ -281,6 +331,7 @@ fn build_oas_function_output(
     operation_id: &str,
     operation: &Operation,
     components: &Option<Components>,
+    pagination: Option<&Pagination>,
 ) -> Result<(String, Map<Type>)> {
     let mut output_properties = Vec::<Property>::new();
     let mut output_types = Map::<Type>::new();
@@ -310,6 +361,18 @@ fn build_oas_function_output(
         return Err(anyhow!(OAS_JSON_MEDIA_NOT_FOUND));
     }
 
+    // Paginated operations return the aggregated items of all pages.
+    if let Some(pagination) = pagination {
+        let items = match &pagination.items {
+            Some(field) => output_properties.iter().find(|p| &p.name == field),
+            None => output_properties.first().filter(|_| output_properties.len() == 1),
+        };
+        return match items {
+            Some(Property { data_type, .. }) if data_type.ends_with("[]") => Ok((data_type.clone(), output_types)),
+            _ => Err(anyhow!(OAS_PAGINATION_ITEMS_NOT_FOUND)),
+        };
+    }
+
     // Special treatment for array types.
     if output_properties.len() == 1 {
         if let Some(Property { data_type, .. }) = output_properties.first() {
@@ -0,0 +1,139 @@
+use anyhow::Result;
+use openapiv3::Operation;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+/// The name of the OpenAPI extension that operations use to declare how long their responses may be cached.
+pub const CACHE_TTL_EXTENSION: &str = "x-brane-cache-ttl";
+/// The environment variable that may be used to point the cache to a different directory (e.g., one shared by the worker).
+pub use specifications::package::OAS_CACHE_DIR_ENV as CACHE_DIR_ENV;
+
+///
+/// Reads how long the responses of the given operation may be cached, if at all.
+///
+/// Only idempotent operations (i.e., `GET`-requests) may be cached; declaring a TTL on any other operation is an error.
+///
+pub fn cache_ttl(
+    method: &str,
+    operation: &Operation,
+) -> Result<Option<Duration>> {
+    let value = match operation.extensions.get(CACHE_TTL_EXTENSION) {
+        Some(value) => value,
+        None => return Ok(None),
+    };
+    if method != "get" {
+        bail!("'{}' may only be declared on idempotent (GET) operations, not on a {} operation", CACHE_TTL_EXTENSION, method.to_uppercase());
+    }
+
+    match value {
+        Value::Number(ttl) => match ttl.as_f64() {
+            Some(ttl) if ttl.is_finite() && ttl >= 0.0 => Ok(Some(Duration::from_secs_f64(ttl))),
+            _ => bail!("'{}' must be a non-negative number of seconds (got {})", CACHE_TTL_EXTENSION, ttl),
+        },
+        _ => bail!("'{}' must be a number of seconds", CACHE_TTL_EXTENSION),
+    }
+}
+
+///
+/// A simple on-disk cache for the responses of OpenAPI calls.
+///
+#[derive(Clone, Debug)]
+pub struct ResponseCache {
+    /// The directory in which we store the cached responses.
+    dir: PathBuf,
+}
+
+impl ResponseCache {
+    ///
+    /// Constructor for the ResponseCache.
+    ///
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    ///
+    /// Computes the key of a request as the SHA-256 hash of its method, URL (including query), headers, cookies and body.
+    ///
+    /// The headers and cookies are part of the key because they may carry credentials (e.g., an `Authorization` header or an API key), and the cache is shared by every call to the package on a worker; so callers with different credentials never see each other's responses.
+    ///
+    pub fn key(
+        method: &str,
+        url: &str,
+        headers: &[(String, String)],
+        cookies: &[(String, String)],
+        body: &[u8],
+    ) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(method.to_lowercase().as_bytes());
+        hasher.update([0]);
+        hasher.update(url.as_bytes());
+        hasher.update([0]);
+
+        // Sort them, since their order does not change the response (and header names are case-insensitive)
+        let mut headers: Vec<(String, &str)> = headers.iter().map(|(name, value)| (name.to_lowercase(), value.as_str())).collect();
+        headers.sort();
+        let mut cookies: Vec<(String, &str)> = cookies.iter().map(|(name, value)| (name.clone(), value.as_str())).collect();
+        cookies.sort();
+        for (kind, pairs) in [("header", headers), ("cookie", cookies)] {
+            for (name, value) in pairs {
+                for part in [kind, name.as_str(), value] {
+                    hasher.update(part.as_bytes());
+                    hasher.update([0]);
+                }
+            }
+        }
+
+        hasher.update(body);
+        format!("{:x}", hasher.finalize())
+    }
+
+    ///
+    /// Returns the cached response for the given key if it exists and is younger than `ttl`.
+    ///
+    pub fn get(
+        &self,
+        key: &str,
+        ttl: Duration,
+    ) -> Option<String> {
+        let path = self.dir.join(key);
+        let modified = fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+        let age = SystemTime::now().duration_since(modified).unwrap_or_default();
+        if age > ttl {
+            debug!("Cached response '{}' has expired ({}s old, TTL is {}s)", path.display(), age.as_secs(), ttl.as_secs());
+            return None;
+        }
+
+        match fs::read_to_string(&path) {
+            Ok(response) => Some(response),
+            Err(err) => {
+                warn!("Failed to read cached response '{}': {}", path.display(), err);
+                None
+            }
+        }
+    }
+
+    ///
+    /// Stores the response for the given key, overwriting any previous one. Failures are logged but otherwise ignored, as the cache is only an optimization.
+    ///
+    pub fn put(
+        &self,
+        key: &str,
+        response: &str,
+    ) {
+        if let Err(err) = fs::create_dir_all(&self.dir) {
+            warn!("Failed to create response cache directory '{}': {}", self.dir.display(), err);
+            return;
+        }
+
+        // Write to a temporary file first so concurrent readers never observe half a response
+        let path = self.dir.join(key);
+        let temp = self.dir.join(format!("{}.{}.tmp", key, std::process::id()));
+        if let Err(err) = fs::write(&temp, response).and_then(|_| fs::rename(&temp, &path)) {
+            warn!("Failed to write cached response '{}': {}", path.display(), err);
+            let _ = fs::remove_file(&temp);
+        }
+    }
+}
@@ -1,3 +1,6 @@
+use crate::cache::{self, ResponseCache};
+use crate::pagination::Pagination;
+use crate::ratelimit::RateLimiter;
 use crate::{build, resolver};
 use anyhow::Result;
 use backoff::{retry, Error, ExponentialBackoff};
@@ -8,6 +11,7 @@ use openapiv3::{OpenAPI, Operation, Parameter as OParameter, ReferenceOr, Securi
 use reqwest::blocking::RequestBuilder;
 use reqwest::Url;
 use reqwest_cookie_store::CookieStoreRwLock;
+use std::time::Duration;
 use std::{collections::HashMap, sync::Arc};
 
 type Map<T> = std::collections::HashMap<String, T>;
@@ -19,6 +23,8 @@ pub async fn execute(
     operation_id: &str,
     arguments: &Map<FullValue>,
     oas_document: &OpenAPI,
+    cache: Option<&ResponseCache>,
+    limiter: Option<&RateLimiter>,
 ) -> Result<String> {
     let mut arguments = arguments.clone();
     debug!("Arguments: {:?}", arguments);
@@ -34,6 +40,7 @@ pub async fn execute(
 
     let components = oas_document.components.clone();
     let (path, method, operation) = get_operation(operation_id, oas_document)?;
+    let pagination = Pagination::from_operation(&operation)?;
 
     // Prioritize server:
     // 1. argument
@@ -57,6 +64,8 @@ pub async fn execute(
 
     let mut operation_url = base_url.join(&path)?.as_str().to_string();
     let mut cookies = CookieStore::default();
+    // Also remember the cookies by name, since they are part of the cache key
+    let mut cookie_pairs = vec![];
     let mut headers = vec![];
     let mut query = vec![];
 
@@ -70,6 +79,7 @@ pub async fn execute(
                 let cookie = RawCookie::new(name.clone(), value.to_string());
                 let cookie = Cookie::try_from_raw_cookie(&cookie, &base_url)?;
                 cookies.insert(cookie, &base_url)?;
+                cookie_pairs.push((name.clone(), value.to_string()));
             }
             OParameter::Header { parameter_data, .. } => {
                 let name = &parameter_data.name;
@@ -86,6 +96,10 @@ pub async fn execute(
             }
             OParameter::Query { parameter_data, .. } => {
                 let name = &parameter_data.name;
+                // The pagination parameter is set per page, below
+                if pagination.as_ref().map(|p| p.parameter() == name).unwrap_or(false) {
+                    continue;
+                }
                 let value = arguments.get(name).expect("Missing argument.");
 
                 query.push((name.clone(), value.to_string()));
@@ -115,6 +129,7 @@ pub async fn execute(
                             let cookie = RawCookie::new(name.clone(), value.to_string());
                             let cookie = Cookie::try_from_raw_cookie(&cookie, &base_url)?;
                             cookies.insert(cookie, &base_url)?;
+                            cookie_pairs.push((name.clone(), value.to_string()));
                         }
                     }
                 }
@@ -180,7 +195,77 @@ pub async fn execute(
         }
     }
 
-    perform_request(client).await.map_err(|_| anyhow!("a"))
+    // Do a single (possibly cached) request if the operation is not paginated
+    let ttl = cache::cache_ttl(&method, &operation)?;
+    let pagination = match pagination {
+        Some(pagination) => pagination,
+        None => return perform_cached_request(client, &cookie_pairs, &method, ttl, cache, limiter).await,
+    };
+
+    // Otherwise, walk all pages and aggregate their items into one array
+    let mut items = vec![];
+    let mut current = pagination.first();
+    for i in 0..pagination.max_pages {
+        let mut page_client = client.try_clone().ok_or_else(|| anyhow!("Cannot clone request to fetch the next page"))?;
+        if let Some(current) = &current {
+            page_client = page_client.query(&[(pagination.parameter(), current)]);
+        }
+
+        // Every page is a request of its own, so it counts towards the rate limit too
+        let response = perform_cached_request(page_client, &cookie_pairs, &method, ttl, cache, limiter).await?;
+        let response: serde_json::Value = serde_json::from_str(&response)?;
+        let (page, next) = pagination.page(current.as_deref(), response)?;
+        debug!("Fetched page {} of operation '{}' ({} item(s))", i + 1, operation_id, page.len());
+        items.extend(page);
+
+        match next {
+            Some(next) => current = Some(next),
+            None => return Ok(serde_json::to_string(&items)?),
+        }
+    }
+
+    warn!("Stopped paginating operation '{}' after the maximum of {} pages", operation_id, pagination.max_pages);
+    Ok(serde_json::to_string(&items)?)
+}
+
+///
+/// Performs the given request, serving it from / storing it in the cache if the operation has a TTL, and respecting the rate limit of the package (if any).
+///
+/// The `cookies` are those that the client sends with the request, which are part of the cache key.
+///
+async fn perform_cached_request(
+    client: RequestBuilder,
+    cookies: &[(String, String)],
+    method: &str,
+    ttl: Option<Duration>,
+    cache: Option<&ResponseCache>,
+    limiter: Option<&RateLimiter>,
+) -> Result<String> {
+    let cache_entry = match (cache, ttl) {
+        (Some(cache), Some(ttl)) => {
+            let request = client.try_clone().ok_or_else(|| anyhow!("Cannot clone request to compute its cache key"))?.build()?;
+            let headers: Vec<(String, String)> = request.headers().iter().map(|(name, value)| (name.to_string(), String::from_utf8_lossy(value.as_bytes()).into_owned())).collect();
+            let body = request.body().and_then(|b| b.as_bytes()).unwrap_or_default();
+            let key = ResponseCache::key(method, request.url().as_str(), &headers, cookies, body);
+            if let Some(response) = cache.get(&key, ttl) {
+                debug!("Using cached response '{}' for {}", key, request.url());
+                return Ok(response);
+            }
+            Some((cache, key))
+        }
+        _ => None,
+    };
+
+    // Only requests that actually go out count towards the rate limit
+    if let Some(limiter) = limiter {
+        limiter.acquire().await?;
+    }
+
+    let response = perform_request(client).await.map_err(|_| anyhow!("a"))?;
+    if let Some((cache, key)) = cache_entry {
+        cache.put(&key, &response);
+    }
+    Ok(response)
 }
 
 async fn perform_request(client: RequestBuilder) -> Result<String, Error<reqwest::Error>> {
@@ -12,7 +12,10 @@ use std::io::BufReader;
 use std::path::PathBuf;
 
 pub mod build;
+pub mod cache;
 pub mod execute;
+pub mod pagination;
+pub mod ratelimit;
 pub mod resolver;
 
 pub use execute::execute;
@@ -0,0 +1,157 @@
+use anyhow::Result;
+use openapiv3::Operation;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// The name of the OpenAPI extension that operations use to declare how they are paginated.
+pub const PAGINATION_EXTENSION: &str = "x-brane-pagination";
+
+/// The number of pages we fetch at most if the operation does not say otherwise.
+const DEFAULT_MAX_PAGES: usize = 1000;
+
+///
+/// Describes how to walk the pages of a paginated operation, such that all of them can be aggregated into a single array.
+///
+/// It is declared on the operation in the OpenAPI document, e.g.:
+/// ```yaml
+/// x-brane-pagination:
+///   mode: cursor
+///   parameter: cursor
+///   next: next_cursor
+///   items: results
+/// ```
+/// or:
+/// ```yaml
+/// x-brane-pagination:
+///   mode: offset
+///   parameter: offset
+///   page_size: 50
+/// ```
+///
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct Pagination {
+    /// The field in the response that contains the items of a page. If omitted, the response itself must be the array of items.
+    #[serde(default)]
+    pub items: Option<String>,
+    /// How the next page is requested.
+    #[serde(flatten)]
+    pub mode: PaginationMode,
+    /// The maximum number of pages to fetch, as a safeguard against APIs that never terminate.
+    #[serde(default = "default_max_pages")]
+    pub max_pages: usize,
+}
+
+/// Returns the default value for `Pagination::max_pages`.
+fn default_max_pages() -> usize {
+    DEFAULT_MAX_PAGES
+}
+
+///
+/// Defines the supported ways of requesting the next page.
+///
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum PaginationMode {
+    /// The response tells us the cursor for the next page. Pagination ends when it is missing, `null` or empty.
+    Cursor {
+        /// The query parameter in which we pass the cursor.
+        parameter: String,
+        /// The field in the response that contains the cursor of the next page.
+        next: String,
+    },
+    /// We pass the index of the first item we want. Pagination ends when a page is empty, or holds fewer than `page_size` items.
+    Offset {
+        /// The query parameter in which we pass the offset.
+        parameter: String,
+        /// The number of items in a full page, if known.
+        #[serde(default)]
+        page_size: Option<usize>,
+        /// The offset of the first item.
+        #[serde(default)]
+        start: u64,
+    },
+}
+
+impl Pagination {
+    ///
+    /// Reads the pagination declared on the given operation, if any.
+    ///
+    pub fn from_operation(operation: &Operation) -> Result<Option<Self>> {
+        match operation.extensions.get(PAGINATION_EXTENSION) {
+            Some(value) => {
+                let pagination: Self = serde_json::from_value(value.clone())
+                    .map_err(|err| anyhow!("Illegal '{}': {}", PAGINATION_EXTENSION, err))?;
+                ensure!(pagination.max_pages > 0, "'{}.max_pages' must be at least 1", PAGINATION_EXTENSION);
+                Ok(Some(pagination))
+            }
+            None => Ok(None),
+        }
+    }
+
+    ///
+    /// Returns the name of the query parameter that is set by the pagination (and thus not by the user).
+    ///
+    pub fn parameter(&self) -> &str {
+        match &self.mode {
+            PaginationMode::Cursor { parameter, .. } => parameter,
+            PaginationMode::Offset { parameter, .. } => parameter,
+        }
+    }
+
+    ///
+    /// Returns the value of the pagination parameter for the first page, if any.
+    ///
+    pub fn first(&self) -> Option<String> {
+        match &self.mode {
+            PaginationMode::Cursor { .. } => None,
+            PaginationMode::Offset { start, .. } => Some(start.to_string()),
+        }
+    }
+
+    ///
+    /// Splits a page into its items and the value of the pagination parameter for the next page (or `None` if this was the last one).
+    ///
+    pub fn page(
+        &self,
+        current: Option<&str>,
+        mut response: Value,
+    ) -> Result<(Vec<Value>, Option<String>)> {
+        // Find the next cursor first, since extracting the items consumes the response
+        let next_cursor = match &self.mode {
+            PaginationMode::Cursor { next, .. } => match response.get(next) {
+                Some(Value::String(cursor)) if !cursor.is_empty() => Some(cursor.clone()),
+                Some(Value::Number(cursor)) => Some(cursor.to_string()),
+                _ => None,
+            },
+            PaginationMode::Offset { .. } => None,
+        };
+
+        let items = match &self.items {
+            Some(field) => response.get_mut(field).map(Value::take).unwrap_or(Value::Null),
+            None => response,
+        };
+        let items = match items {
+            Value::Array(items) => items,
+            // Some APIs omit the field altogether on the last (empty) page
+            Value::Null => vec![],
+            _ => bail!("Paginated response does not contain an array of items{}", self.items.as_ref().map(|f| format!(" in field '{}'", f)).unwrap_or_default()),
+        };
+
+        let next = match &self.mode {
+            PaginationMode::Cursor { .. } => next_cursor,
+            PaginationMode::Offset { page_size, start, .. } => {
+                if items.is_empty() || page_size.map(|size| items.len() < size).unwrap_or(false) {
+                    None
+                } else {
+                    let current: u64 = match current {
+                        Some(current) => current.parse()?,
+                        None => *start,
+                    };
+                    Some((current + items.len() as u64).to_string())
+                }
+            }
+        };
+
+        Ok((items, next))
+    }
+}
@@ -0,0 +1,186 @@
+use anyhow::{Context, Result};
+use file_lock::{FileLock, FileOptions};
+use openapiv3::OpenAPI;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The name of the OpenAPI extension that packages use to declare their rate limit.
+pub const RATE_LIMIT_EXTENSION: &str = "x-brane-rate-limit";
+/// The environment variable with which the worker points `branelet` to a directory that is shared by all calls to the same package on that worker (and in which it keeps its token bucket).
+pub use specifications::package::OAS_STATE_DIR_ENV as STATE_DIR_ENV;
+/// The name of the file in the state directory that holds the token bucket.
+pub const BUCKET_FILE: &str = "ratelimit.json";
+
+///
+/// Describes how many requests per second a package may send to its API.
+///
+/// It is declared in the top-level of the OpenAPI document as either a plain number:
+/// ```yaml
+/// x-brane-rate-limit: 2.5
+/// ```
+/// or as an object that also specifies how many requests may be sent in a burst:
+/// ```yaml
+/// x-brane-rate-limit:
+///   rate: 2.5
+///   burst: 5
+/// ```
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateLimit {
+    /// The number of requests per second that we may send (on average).
+    pub rate: f64,
+    /// The number of requests that we may send at once after having been idle.
+    pub burst: f64,
+}
+
+impl RateLimit {
+    ///
+    /// Reads the rate limit declared in the given OpenAPI document, if any.
+    ///
+    pub fn from_document(oas_document: &OpenAPI) -> Result<Option<Self>> {
+        let value = match oas_document.extensions.get(RATE_LIMIT_EXTENSION) {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+
+        let (rate, burst) = match value {
+            Value::Number(rate) => (rate.as_f64(), None),
+            Value::Object(map) => (
+                map.get("rate").and_then(Value::as_f64),
+                map.get("burst").map(|b| b.as_f64().ok_or_else(|| anyhow!("'{}.burst' is not a number", RATE_LIMIT_EXTENSION))).transpose()?,
+            ),
+            _ => bail!("'{}' must be a number or an object with a 'rate' field", RATE_LIMIT_EXTENSION),
+        };
+        let rate = rate.ok_or_else(|| anyhow!("'{}' does not specify a numeric rate", RATE_LIMIT_EXTENSION))?;
+        if !rate.is_finite() || rate <= 0.0 {
+            bail!("'{}' must specify a positive rate (got {})", RATE_LIMIT_EXTENSION, rate);
+        }
+
+        // Default to a burst of at least one request, or one second worth of requests
+        let burst = burst.unwrap_or_else(|| rate.max(1.0));
+        if !burst.is_finite() || burst < 1.0 {
+            bail!("'{}.burst' must be at least 1 (got {})", RATE_LIMIT_EXTENSION, burst);
+        }
+
+        Ok(Some(Self { rate, burst }))
+    }
+}
+
+/// The state of a SharedTokenBucket as it is stored on disk.
+#[derive(Debug, Deserialize, Serialize)]
+struct BucketState {
+    /// The rate of the limit that the bucket enforces.
+    rate: f64,
+    /// The burst of the limit that the bucket enforces.
+    burst: f64,
+    /// The number of tokens in the bucket (negative if callers are waiting for them).
+    tokens: f64,
+    /// The last time we refilled the bucket, in seconds since the Unix epoch.
+    last: f64,
+}
+
+///
+/// A token bucket that keeps its state in a file, so that it enforces a RateLimit over calls in separate processes (e.g., the `branelet` of every task of a package on the same worker).
+///
+/// Note that the worker shares one bucket between all calls to a package, regardless of the session they belong to: the API sees all of them coming from the same address, so that is the scope in which the limit has to hold.
+///
+/// The file is locked while a token is taken, so concurrent callers are spread out properly too.
+///
+#[derive(Clone, Debug)]
+pub struct SharedTokenBucket {
+    /// The file that holds the state of the bucket.
+    path: PathBuf,
+}
+
+impl SharedTokenBucket {
+    ///
+    /// Constructor for the SharedTokenBucket, which is backed by the given file. It is created (full) once the first token is taken.
+    ///
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    ///
+    /// Takes a token from the bucket, which enforces the given limit. If the bucket enforced another limit before, it is reset to a full bucket with the new one.
+    ///
+    /// Returns how long the caller has to wait before it may send its request, or an error if the file could not be read or written.
+    ///
+    pub fn take(
+        &self,
+        limit: RateLimit,
+    ) -> Result<Duration> {
+        self.take_at(limit, SystemTime::now())
+    }
+
+    ///
+    /// Takes a token from the bucket like `SharedTokenBucket::take()`, but as if it happens at the given time.
+    ///
+    pub fn take_at(
+        &self,
+        limit: RateLimit,
+        now: SystemTime,
+    ) -> Result<Duration> {
+        let mut lock = FileLock::lock(&self.path, true, FileOptions::new().create(true).read(true).write(true))
+            .with_context(|| format!("Failed to lock token bucket '{}'", self.path.display()))?;
+        let now = now.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs_f64();
+
+        // Read what the last caller left behind, starting with a full bucket if there is nothing (usable)
+        let mut raw = String::new();
+        lock.file.read_to_string(&mut raw).with_context(|| format!("Failed to read token bucket '{}'", self.path.display()))?;
+        let mut state = match serde_json::from_str::<BucketState>(&raw) {
+            Ok(state) if state.rate == limit.rate && state.burst == limit.burst => state,
+            _ => BucketState { rate: limit.rate, burst: limit.burst, tokens: limit.burst, last: now },
+        };
+
+        // Refill the bucket based on the time elapsed since the last caller, then take the token (going into debt if there are none left)
+        state.tokens = (state.tokens + (now - state.last).max(0.0) * limit.rate).min(limit.burst) - 1.0;
+        state.last = now;
+        let wait = if state.tokens >= 0.0 { Duration::ZERO } else { Duration::from_secs_f64(-state.tokens / limit.rate) };
+
+        // Write it back for the next caller
+        let raw = serde_json::to_string(&state)?;
+        lock.file.set_len(0).and_then(|_| lock.file.seek(SeekFrom::Start(0))).and_then(|_| lock.file.write_all(raw.as_bytes()))
+            .with_context(|| format!("Failed to write token bucket '{}'", self.path.display()))?;
+        Ok(wait)
+    }
+}
+
+///
+/// Enforces a RateLimit on every request sent to the API of a package, by taking a token from a SharedTokenBucket before each of them (and waiting if there are none).
+///
+#[derive(Clone, Debug)]
+pub struct RateLimiter {
+    /// The limit that we enforce.
+    limit: RateLimit,
+    /// The bucket (shared with the other calls to the same package) that we take tokens from.
+    bucket: SharedTokenBucket,
+}
+
+impl RateLimiter {
+    ///
+    /// Constructor for the RateLimiter.
+    ///
+    pub fn new(
+        limit: RateLimit,
+        bucket: SharedTokenBucket,
+    ) -> Self {
+        Self { limit, bucket }
+    }
+
+    ///
+    /// Waits until the limit allows us to send another request.
+    ///
+    /// Returns an error if the token bucket could not be read or written.
+    ///
+    pub async fn acquire(&self) -> Result<()> {
+        let wait = self.bucket.take(self.limit)?;
+        if !wait.is_zero() {
+            debug!("Delaying request by {:.3}s to respect rate limit of {} request(s)/s", wait.as_secs_f64(), self.limit.rate);
+            tokio::time::sleep(wait).await;
+        }
+        Ok(())
+    }
+}
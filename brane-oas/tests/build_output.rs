@@ -99,3 +99,31 @@ fn resp_booleanarray_booleanarray() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn resp_paginatedcursor_itemsarray() -> Result<()> {
+    let (function, types) = common::build_oas_function_resp("/paginated-cursor", "paginatedCursor")?;
+    assert_eq!(function.return_type, String::from("string[]"));
+    assert_eq!(function.parameters.len(), 0);
+    assert_eq!(types.len(), 0);
+
+    Ok(())
+}
+
+#[test]
+fn resp_paginatedoffset_array() -> Result<()> {
+    let (function, types) = common::build_oas_function_resp("/paginated-offset", "paginatedOffset")?;
+    assert_eq!(function.return_type, String::from("integer[]"));
+    assert_eq!(function.parameters.len(), 0);
+    assert_eq!(types.len(), 0);
+
+    Ok(())
+}
+
+#[test]
+fn resp_paginatednoitems_err() -> Result<()> {
+    let result = common::build_oas_function_resp("/paginated-no-items", "paginatedNoItems");
+    assert!(result.is_err());
+
+    Ok(())
+}
@@ -0,0 +1,159 @@
+use anyhow::Result;
+use brane_exe::FullValue;
+use brane_oas::cache::ResponseCache;
+use brane_oas::ratelimit::{RateLimit, RateLimiter, SharedTokenBucket};
+use brane_oas::OpenAPI;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+///
+/// Starts a server that answers every request with the same JSON object, and returns its port and the number of requests it served.
+///
+fn start_server() -> Result<(u16, Arc<AtomicUsize>)> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let port = listener.local_addr()?.port();
+    let served = Arc::new(AtomicUsize::new(0));
+
+    let counter = served.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = match stream {
+                Ok(stream) => stream,
+                Err(_) => continue,
+            };
+            let mut buffer = [0; 4096];
+            let _ = stream.read(&mut buffer);
+            let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+
+            let body = format!("{{\"value\":{}}}", n);
+            let _ = write!(stream, "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body);
+        }
+    });
+    Ok((port, served))
+}
+
+///
+/// Returns a document with a single cached operation on the server at the given port, which optionally requires a bearer token.
+///
+fn document(
+    port: u16,
+    bearer: bool,
+) -> Result<OpenAPI> {
+    let security = if bearer { "      security:\n        - bearer: []\n" } else { "" };
+    Ok(serde_yaml::from_str(&format!(
+        r#"
+openapi: 3.0.0
+info:
+  title: cached
+  version: 1.0.0
+servers:
+  - url: http://127.0.0.1:{}
+components:
+  securitySchemes:
+    bearer:
+      type: http
+      scheme: bearer
+paths:
+  /value:
+    get:
+      operationId: getValue
+      x-brane-cache-ttl: 60
+{}      responses:
+        '200':
+          description: OK
+          content:
+            application/json:
+              schema:
+                type: object
+                properties:
+                  value:
+                    type: integer
+"#,
+        port, security
+    ))?)
+}
+
+#[test]
+fn cache_hits_across_invocations() -> Result<()> {
+    let (port, served) = start_server()?;
+    let document = document(port, false)?;
+    let dir = tempfile::tempdir()?;
+    let arguments = HashMap::new();
+
+    // Every invocation opens the cache anew, like every branelet does in the directory that the worker mounts
+    let first = futures::executor::block_on(brane_oas::execute("getValue", &arguments, &document, Some(&ResponseCache::new(dir.path())), None))?;
+    let second = futures::executor::block_on(brane_oas::execute("getValue", &arguments, &document, Some(&ResponseCache::new(dir.path())), None))?;
+    assert_eq!(first, second);
+    assert_eq!(served.load(Ordering::SeqCst), 1);
+
+    // Without the shared directory, the API is called again
+    let other = tempfile::tempdir()?;
+    futures::executor::block_on(brane_oas::execute("getValue", &arguments, &document, Some(&ResponseCache::new(other.path())), None))?;
+    assert_eq!(served.load(Ordering::SeqCst), 2);
+
+    Ok(())
+}
+
+#[test]
+fn cache_separates_credentials() -> Result<()> {
+    let (port, served) = start_server()?;
+    let document = document(port, true)?;
+    let dir = tempfile::tempdir()?;
+    let alice = HashMap::from([(String::from("token"), FullValue::String("alice".into()))]);
+    let bob = HashMap::from([(String::from("token"), FullValue::String("bob".into()))]);
+
+    // The same caller hits the cache...
+    let first = futures::executor::block_on(brane_oas::execute("getValue", &alice, &document, Some(&ResponseCache::new(dir.path())), None))?;
+    let second = futures::executor::block_on(brane_oas::execute("getValue", &alice, &document, Some(&ResponseCache::new(dir.path())), None))?;
+    assert_eq!(first, second);
+    assert_eq!(served.load(Ordering::SeqCst), 1);
+
+    // ...but a caller with another token never sees their response
+    let other = futures::executor::block_on(brane_oas::execute("getValue", &bob, &document, Some(&ResponseCache::new(dir.path())), None))?;
+    assert_ne!(first, other);
+    assert_eq!(served.load(Ordering::SeqCst), 2);
+
+    Ok(())
+}
+
+#[test]
+fn cache_hits_take_no_tokens() -> Result<()> {
+    let (port, served) = start_server()?;
+    let document = document(port, false)?;
+    let dir = tempfile::tempdir()?;
+    let arguments = HashMap::new();
+
+    // Use a bucket that is large enough to never make us wait, but refills too slowly to notice
+    let path = dir.path().join("ratelimit.json");
+    let limiter = RateLimiter::new(RateLimit { rate: 0.001, burst: 10.0 }, SharedTokenBucket::new(&path));
+    for _ in 0..3 {
+        futures::executor::block_on(brane_oas::execute("getValue", &arguments, &document, Some(&ResponseCache::new(dir.path().join("cache"))), Some(&limiter)))?;
+    }
+    assert_eq!(served.load(Ordering::SeqCst), 1);
+
+    // Only the request that actually went out should have taken a token
+    let state: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+    let tokens = state["tokens"].as_f64().unwrap();
+    assert!((8.9..9.1).contains(&tokens), "unexpected number of tokens left {}", tokens);
+
+    Ok(())
+}
+
+#[test]
+fn cache_key_includes_credentials() {
+    let key = |headers: &[(&str, &str)], cookies: &[(&str, &str)]| {
+        let headers: Vec<(String, String)> = headers.iter().map(|(n, v)| (n.to_string(), v.to_string())).collect();
+        let cookies: Vec<(String, String)> = cookies.iter().map(|(n, v)| (n.to_string(), v.to_string())).collect();
+        ResponseCache::key("get", "http://example.com/value", &headers, &cookies, b"")
+    };
+
+    // The order of headers and the case of their names does not matter...
+    assert_eq!(key(&[("Authorization", "Bearer a"), ("X-Api-Key", "k")], &[]), key(&[("x-api-key", "k"), ("authorization", "Bearer a")], &[]));
+    // ...but their values and the cookies do
+    assert_ne!(key(&[("Authorization", "Bearer a")], &[]), key(&[("Authorization", "Bearer b")], &[]));
+    assert_ne!(key(&[], &[("session", "a")]), key(&[], &[("session", "b")]));
+    assert_ne!(key(&[], &[]), key(&[], &[("session", "a")]));
+}
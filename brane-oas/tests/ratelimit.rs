@@ -0,0 +1,129 @@
+use anyhow::Result;
+use brane_oas::ratelimit::{RateLimit, RateLimiter, SharedTokenBucket};
+use brane_oas::OpenAPI;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::time::{Duration, UNIX_EPOCH};
+use tokio::time::Instant;
+
+#[test]
+fn shared_bucket_spans_calls() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let limit = RateLimit { rate: 1.0, burst: 2.0 };
+    // Start at a whole second, so the times are exact as floating-point seconds
+    let start = UNIX_EPOCH + Duration::from_secs(1_000_000);
+
+    // Every call opens the bucket anew, like every branelet on the worker does
+    let take = |after: f64| SharedTokenBucket::new(dir.path().join("ratelimit.json")).take_at(limit, start + Duration::from_secs_f64(after));
+    assert_eq!(take(0.0)?, Duration::ZERO);
+    assert_eq!(take(0.0)?, Duration::ZERO);
+
+    // The burst is used up, so the third and fourth call have to wait one and two seconds
+    assert_eq!(take(0.0)?, Duration::from_secs(1));
+    assert_eq!(take(0.0)?, Duration::from_secs(2));
+
+    // Time refills the bucket, but never beyond its burst
+    assert_eq!(take(2.5)?, Duration::from_millis(500));
+    assert_eq!(take(60.0)?, Duration::ZERO);
+    assert_eq!(take(60.0)?, Duration::ZERO);
+    assert_eq!(take(60.0)?, Duration::from_secs(1));
+
+    Ok(())
+}
+
+#[test]
+fn shared_bucket_resets_on_new_limit() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let bucket = SharedTokenBucket::new(dir.path().join("ratelimit.json"));
+    let now = UNIX_EPOCH + Duration::from_secs(1_000_000);
+
+    assert_eq!(bucket.take_at(RateLimit { rate: 1.0, burst: 1.0 }, now)?, Duration::ZERO);
+    assert_eq!(bucket.take_at(RateLimit { rate: 1.0, burst: 1.0 }, now)?, Duration::from_secs(1));
+
+    // A package update that changes the limit starts with a full bucket
+    assert_eq!(bucket.take_at(RateLimit { rate: 10.0, burst: 1.0 }, now)?, Duration::ZERO);
+
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn limiter_waits_for_tokens() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let limiter = RateLimiter::new(RateLimit { rate: 1.0, burst: 1.0 }, SharedTokenBucket::new(dir.path().join("ratelimit.json")));
+
+    // The first request may go immediately...
+    let start = Instant::now();
+    limiter.acquire().await?;
+    assert_eq!(start.elapsed(), Duration::ZERO);
+
+    // ...but the second one has to wait (about) a second, which the paused clock skips
+    limiter.acquire().await?;
+    let waited = start.elapsed();
+    assert!(waited > Duration::from_millis(900) && waited <= Duration::from_secs(1), "unexpected wait {:?}", waited);
+
+    Ok(())
+}
+
+#[test]
+fn paginated_calls_take_a_token_per_page() -> Result<()> {
+    // Serve three pages of one item each, linked by a cursor
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let port = listener.local_addr()?.port();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = match stream {
+                Ok(stream) => stream,
+                Err(_) => continue,
+            };
+            let mut buffer = [0; 4096];
+            let n = stream.read(&mut buffer).unwrap_or(0);
+            let request = String::from_utf8_lossy(&buffer[..n]);
+            let body = if request.contains("cursor=b") {
+                r#"{"results":[3]}"#
+            } else if request.contains("cursor=a") {
+                r#"{"results":[2],"next":"b"}"#
+            } else {
+                r#"{"results":[1],"next":"a"}"#
+            };
+            let _ = write!(stream, "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body);
+        }
+    });
+    let document: OpenAPI = serde_yaml::from_str(&format!(
+        r#"
+openapi: 3.0.0
+info:
+  title: paginated
+  version: 1.0.0
+servers:
+  - url: http://127.0.0.1:{}
+paths:
+  /items:
+    get:
+      operationId: getItems
+      x-brane-pagination:
+        mode: cursor
+        parameter: cursor
+        next: next
+        items: results
+      responses:
+        '200':
+          description: OK
+"#,
+        port
+    ))?;
+
+    // Use a bucket that is large enough to never make us wait, but refills too slowly to notice
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("ratelimit.json");
+    let limiter = RateLimiter::new(RateLimit { rate: 0.001, burst: 10.0 }, SharedTokenBucket::new(&path));
+    let items = futures::executor::block_on(brane_oas::execute("getItems", &HashMap::new(), &document, None, Some(&limiter)))?;
+    assert_eq!(items, "[1,2,3]");
+
+    // Every page should have taken a token
+    let state: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+    let tokens = state["tokens"].as_f64().unwrap();
+    assert!((6.9..7.1).contains(&tokens), "unexpected number of tokens left {}", tokens);
+
+    Ok(())
+}
@@ -0,0 +1,104 @@
+//  HANDLER.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 11:01:36
+//  Last edited:
+//    16 Oct 2026, 11:06:02
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements the gRPC frontend of the planner, which lets the driver
+//!   send workflows to plan directly instead of over Kafka.
+// 
+
+use std::path::PathBuf;
+
+use log::{debug, error, info};
+use tonic::{Request, Response, Status};
+use tracing::{info_span, Instrument as _, Span};
+
+use brane_ast::Workflow;
+use brane_cfg::node::{CentralConfig, NodeConfig, NodeKindConfig};
+use brane_shr::otel;
+use brane_tsk::tools::extract_trace;
+use specifications::grpc::{PlannerService, PlanReply, PlanRequest};
+use specifications::planning::PlanningStatus;
+
+use crate::planner::plan_workflow;
+
+
+/***** LIBRARY *****/
+/// Handles the gRPC requests of the driver.
+#[derive(Clone, Debug)]
+pub struct PlannerHandler {
+    /// The path to the node config file, which we reload for every request so changes to the infrastructure are picked up.
+    node_config_path : PathBuf,
+}
+
+impl PlannerHandler {
+    /// Constructor for the PlannerHandler.
+    /// 
+    /// # Arguments
+    /// - `node_config_path`: The path to the node config file that contains environment settings for this node.
+    /// 
+    /// # Returns
+    /// A new PlannerHandler instance.
+    #[inline]
+    pub fn new(node_config_path: impl Into<PathBuf>) -> Self {
+        Self {
+            node_config_path : node_config_path.into(),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl PlannerService for PlannerHandler {
+    async fn plan(&self, request: Request<PlanRequest>) -> Result<Response<PlanReply>, Status> {
+        // Continue the trace of the workflow that is sent to us
+        let span: Span = info_span!("plan", correlation_id = %request.get_ref().id);
+        otel::set_parent(&span, &extract_trace(&request));
+        let request: PlanRequest = request.into_inner();
+
+        async move {
+            info!("Received new plan request with ID '{}' over gRPC", request.id);
+
+            // Fetch the most recent NodeConfig
+            let node_config: NodeConfig = match NodeConfig::from_path(&self.node_config_path) {
+                Ok(config) => config,
+                Err(err)   => {
+                    error!("Failed to load NodeConfig file: {}", err);
+                    return Err(Status::internal("An internal error occurred"));
+                },
+            };
+            let central: CentralConfig = match node_config.node {
+                NodeKindConfig::Central(central) => central,
+                NodeKindConfig::Worker(_)        => {
+                    error!("Given NodeConfig file '{}' does not have properties for a central node.", self.node_config_path.display());
+                    return Err(Status::internal("An internal error occurred"));
+                },
+            };
+
+            // Attempt to parse the workflow
+            debug!("Parsing workflow of {} characters for session '{}'", request.workflow.len(), request.id);
+            let workflow: Workflow = match serde_json::from_str(&request.workflow) {
+                Ok(workflow) => workflow,
+                Err(err)     => {
+                    error!("Failed to parse incoming workflow with ID '{}' as Workflow JSON: {}", request.id, err);
+                    return Err(Status::invalid_argument(format!("Failed to parse workflow: {}", err)));
+                },
+            };
+
+            // Plan it, telling the driver whether no plan exists or we errored by the status code
+            match plan_workflow(&central, &request.id, workflow).await {
+                PlanningStatus::Success(plan, reasoning) => Ok(Response::new(PlanReply{ workflow: plan, reasoning })),
+                PlanningStatus::Failed(reason)           => Err(Status::failed_precondition(reason.unwrap_or_default())),
+                PlanningStatus::Error(err)               => Err(Status::internal(err)),
+
+                // The planner only returns final statuses
+                PlanningStatus::None | PlanningStatus::Started(_) => { unreachable!(); },
+            }
+        }.instrument(span).await
+    }
+}
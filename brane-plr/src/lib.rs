@@ -14,4 +14,6 @@
 // 
 
 // Declare modules
+pub mod handler;
 pub mod planner;
+pub mod strategy;
@@ -30,9 +30,14 @@ use std::path::PathBuf;
 
 use clap::Parser;
 use dotenvy::dotenv;
-use log::{debug, error, info, LevelFilter};
-use brane_cfg::node::NodeConfig;
+use log::{debug, error, info, warn, LevelFilter};
+use tonic::transport::Server;
 
+use brane_cfg::node::{NodeConfig, PlannerTransport};
+use brane_shr::otel;
+use specifications::grpc::PlannerServiceServer;
+
+use brane_plr::handler::PlannerHandler;
 use brane_plr::planner::planner_server;
 
 
@@ -82,12 +87,35 @@ async fn main() {
         },
     };
     if !node_config.node.is_central() { error!("Given NodeConfig file '{}' does not have properties for a central node.", opts.node_config_path.display()); std::process::exit(1); }
+    info!("Enabled features: {}", node_config.features);
+
+    // Set up tracing, exporting spans if the node is configured to
+    if let Err(err) = otel::init("brane-plr", node_config.tracing.as_ref()) { warn!("{} (spans will not be exported)", err); }
+
+    // We simply start a new planner on the configured transport, which takes over this function
+    match node_config.node.central().planner.clone() {
+        PlannerTransport::Kafka => {
+            if let Some(Err(err)) = otel::until_shutdown(planner_server(opts.node_config_path, node_config, opts.group_id)).await {
+                error!("Failed to run InstancePlanner server: {}", err);
+                otel::shutdown().await;
+                std::process::exit(1);
+            }
+        },
 
-    // We simply start a new planner, which takes over this function
-    if let Err(err) = planner_server(opts.node_config_path, node_config, opts.group_id).await {
-        error!("Failed to run InstancePlanner server: {}", err);
-        std::process::exit(1);
+        PlannerTransport::Grpc{ bind, .. } => {
+            debug!("gRPC server ready to serve on '{}'", bind);
+            if let Err(err) = Server::builder()
+                .add_service(PlannerServiceServer::new(PlannerHandler::new(opts.node_config_path)))
+                .serve_with_shutdown(bind, otel::shutdown_signal())
+                .await
+            {
+                error!("Failed to start gRPC server: {}", err);
+                otel::shutdown().await;
+                std::process::exit(1);
+            }
+        },
     }
 
-    // We're done if the stream is done
+    // We're done if the stream is done (or we are asked to stop), but export the spans of the last plans first
+    otel::shutdown().await;
 }
@@ -17,6 +17,7 @@
 use std::collections::{HashMap, HashSet};
 use std::mem;
 use std::path::PathBuf;
+use std::str::FromStr as _;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -32,19 +33,77 @@ use rdkafka::message::OwnedMessage;
 use rdkafka::producer::{FutureProducer, FutureRecord};
 use rdkafka::util::Timeout;
 use reqwest::Response;
+use tracing::{info_span, Instrument as _, Span};
 
 use brane_ast::Workflow;
 use brane_ast::locations::Locations;
 use brane_ast::ast::{DataName, Edge, SymTable, TaskDef};
 use brane_cfg::spec::Address;
-use brane_cfg::infra::InfraFile;
+use brane_cfg::infra::{split_peer_location, InfraFile};
 use brane_cfg::node::{CentralConfig, NodeConfig, NodeKindConfig};
 use brane_shr::kafka::{ensure_topics, restore_committed_offsets};
+use brane_shr::otel;
 use brane_tsk::errors::PlanError;
-use brane_tsk::api::get_data_index;
-use specifications::data::{AccessKind, AvailabilityKind, DataIndex, PreprocessKind};
-use specifications::package::Capability;
+use brane_tsk::api::{get_package_index, ApiClient};
+use specifications::data::{merge_residency, AccessKind, AvailabilityKind, DataIndex, PreprocessKind};
+use specifications::package::{Capability, PackageIndex};
 use specifications::planning::{PlanningStatus, PlanningStatusKind, PlanningUpdate};
+use specifications::registry::LocationAvailability;
+use specifications::version::Version;
+
+use crate::strategy::{self, Choice, Strategy, TaskInfo};
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use specifications::registry::CachedPackage;
+    use super::*;
+
+
+    /// Returns a LocationAvailability with the given capabilities and cached packages.
+    fn availability(capabilities: &[Capability], packages: &[(&str, &str)]) -> LocationAvailability {
+        LocationAvailability {
+            capabilities : capabilities.iter().copied().collect(),
+            packages     : packages.iter().map(|(name, version)| CachedPackage{ name: (*name).into(), version: (*version).into(), size: 0 }).collect(),
+            datasets     : HashMap::new(),
+        }
+    }
+
+    /// Tests that every location is judged on what it has available itself.
+    #[test]
+    fn test_check_location() {
+        let version: Version = Version::new(1, 0, 0);
+        let gpu: HashSet<Capability> = HashSet::from([ Capability::CudaGpu ]);
+
+        // A location that has cached the package can run it, even if the instance no longer has it
+        let cached: LocationAvailability = availability(&[], &[ ("hello", "1.0.0") ]);
+        assert_eq!(check_location("cached", &cached, "hello", &version, false, &HashSet::new()), Ok(()));
+        // ...but not another version of it
+        assert!(check_location("cached", &cached, "hello", &Version::new(2, 0, 0), false, &HashSet::new()).unwrap_err().contains("has not cached package 'hello' (version 2.0.0)"));
+
+        // A location that has not cached it can still fetch it from the instance
+        let empty: LocationAvailability = availability(&[], &[]);
+        assert_eq!(check_location("empty", &empty, "hello", &version, true, &HashSet::new()), Ok(()));
+        assert!(check_location("empty", &empty, "hello", &version, false, &HashSet::new()).unwrap_err().starts_with("location 'empty'"));
+
+        // Having the package does not help if the location does not support what it needs
+        assert_eq!(check_location("cached", &cached, "hello", &version, true, &gpu), Err("location 'cached' lacks capabilities cuda_gpu".into()));
+        let gpus: LocationAvailability = availability(&[ Capability::CudaGpu ], &[]);
+        assert_eq!(check_location("gpus", &gpus, "hello", &version, true, &gpu), Ok(()));
+
+        // Resources are only checked when the location advertises them
+        let small: LocationAvailability = availability(&[ Capability::Memory(1024) ], &[ ("hello", "1.0.0") ]);
+        assert_eq!(check_location("small", &small, "hello", &version, false, &HashSet::from([ Capability::Memory(512) ])), Ok(()));
+        assert!(check_location("small", &small, "hello", &version, false, &HashSet::from([ Capability::Memory(2048) ])).is_err());
+        assert_eq!(check_location("cached", &cached, "hello", &version, false, &HashSet::from([ Capability::Memory(2048) ])), Ok(()));
+    }
+}
+
+
+
+
+
 
 
 /***** HELPER FUNCTIONS *****/
@@ -63,23 +122,24 @@ async fn send_update(producer: Arc<FutureProducer>, topic: impl AsRef<str>, corr
     let correlation_id : &str = correlation_id.as_ref();
     debug!("Sending update '{:?}' on topic '{}' for workflow '{}'", status, topic, correlation_id);
 
-    // Translate the status into a (kind, string) pair.
-    let (kind, result): (PlanningStatusKind, Option<String>) = match status {
-        PlanningStatus::Started(result) => (PlanningStatusKind::Started, result),
+    // Translate the status into a (kind, string, reasoning) triplet.
+    let (kind, result, reasoning): (PlanningStatusKind, Option<String>, Vec<String>) = match status {
+        PlanningStatus::Started(result) => (PlanningStatusKind::Started, result, vec![]),
 
-        PlanningStatus::Success(result) => (PlanningStatusKind::Success, Some(result)),
-        PlanningStatus::Failed(result)  => (PlanningStatusKind::Failed, result),
-        PlanningStatus::Error(result)   => (PlanningStatusKind::Error, Some(result)),
+        PlanningStatus::Success(result, reasoning) => (PlanningStatusKind::Success, Some(result), reasoning),
+        PlanningStatus::Failed(result)             => (PlanningStatusKind::Failed, result, vec![]),
+        PlanningStatus::Error(result)              => (PlanningStatusKind::Error, Some(result), vec![]),
 
         PlanningStatus::None => { panic!("Cannot update the client on `PlanningStatus::None`"); },
     };
-    let result_len: usize = result.as_ref().map(|r| r.len()).unwrap_or(0);
+    let result_len: usize = result.as_ref().map(|r| r.len()).unwrap_or(0) + reasoning.iter().map(|r| r.len()).sum::<usize>();
 
     // Create a planning update
     let update : PlanningUpdate = PlanningUpdate{
         id   : correlation_id.into(),
         kind : kind.into(),
         result,
+        reasoning,
     };
 
     // Encode it
@@ -106,6 +166,199 @@ async fn send_update(producer: Arc<FutureProducer>, topic: impl AsRef<str>, corr
 
 
 
+/// Checks whether a location with the given capabilities satisfies all of the given requirements.
+/// 
+/// # Arguments
+/// - `capabilities`: The capabilities advertised by the location.
+/// - `requirements`: The requirements of the task.
+/// 
+/// # Returns
+/// True if the task may run on the location, or false otherwise. Note that resource requirements (e.g., memory) are only checked if the location advertises how much it offers.
+#[inline]
+fn supports(capabilities: &HashSet<Capability>, requirements: &HashSet<Capability>) -> bool { requirements.iter().all(|r| r.is_satisfied_by(capabilities)) }
+
+/// Helper function that fetches the capabilities supported by the given location.
+/// 
+/// # Arguments
+/// - `api_addr`: The address where we can reach the `brane-api` service on.
+/// - `location`: The location to fetch the capabilities of.
+/// 
+/// # Returns
+/// The set of Capabilities supported by that location.
+/// 
+/// # Errors
+/// This function errors if we failed to reach the `brane-api` service or if its response was ill-formed.
+async fn get_capabilities(api_addr: &Address, location: &str) -> Result<HashSet<Capability>, PlanError> {
+    let address: String = format!("{}/infra/capabilities/{}", api_addr, location);
+    let res: Response = match reqwest::get(&address).await {
+        Ok(req)  => req,
+        Err(err) => { return Err(PlanError::RequestError{ address, err }); },
+    };
+    if !res.status().is_success() { return Err(PlanError::RequestFailure{ address, code: res.status(), err: res.text().await.ok() }); }
+    let capabilities: String = match res.text().await {
+        Ok(caps) => caps,
+        Err(err) => { return Err(PlanError::RequestBodyError{ address, err }); },
+    };
+    match serde_json::from_str(&capabilities) {
+        Ok(caps) => Ok(caps),
+        Err(err) => Err(PlanError::RequestParseError{ address, raw: capabilities, err }),
+    }
+}
+
+/// Helper function that fetches what the given location has available locally.
+/// 
+/// # Arguments
+/// - `api_addr`: The address where we can reach the `brane-api` service on.
+/// - `location`: The location to fetch the availability of.
+/// 
+/// # Returns
+/// The LocationAvailability reported by that location (including the capabilities the administrator advertises for it).
+/// 
+/// # Errors
+/// This function errors if we failed to reach the `brane-api` service or if its response was ill-formed.
+async fn get_availability(api_addr: &Address, location: &str) -> Result<LocationAvailability, PlanError> {
+    let address: String = format!("{}/infra/availability/{}", api_addr, location);
+    let res: Response = match reqwest::get(&address).await {
+        Ok(req)  => req,
+        Err(err) => { return Err(PlanError::RequestError{ address, err }); },
+    };
+    if !res.status().is_success() { return Err(PlanError::RequestFailure{ address, code: res.status(), err: res.text().await.ok() }); }
+    let availability: String = match res.text().await {
+        Ok(availability) => availability,
+        Err(err)         => { return Err(PlanError::RequestBodyError{ address, err }); },
+    };
+    match serde_json::from_str(&availability) {
+        Ok(availability) => Ok(availability),
+        Err(err)         => Err(PlanError::RequestParseError{ address, raw: availability, err }),
+    }
+}
+
+/// Helper function that resolves the address of the registry of the given location.
+/// 
+/// # Arguments
+/// - `api_addr`: The address where we can reach the `brane-api` service on. Used to resolve locations hosted by peer instances.
+/// - `infra`: The infrastructure to resolve locations of this instance.
+/// - `location`: The location to resolve the registry of.
+/// 
+/// # Returns
+/// The address of the location's registry.
+/// 
+/// # Errors
+/// This function errors if the location is not in the infrastructure file and we failed to resolve it using the `brane-api` service.
+async fn get_registry(api_addr: &Address, infra: &InfraFile, location: &str) -> Result<Address, PlanError> {
+    // Locations of this instance are simply in the infrastructure file
+    if let Some(info) = infra.get(location) { return Ok(info.registry.clone()); }
+
+    // Otherwise, the API may know it from a peer
+    let address: String = format!("{}/infra/registries/{}", api_addr, location);
+    let res: Response = match reqwest::get(&address).await {
+        Ok(req)  => req,
+        Err(err) => { return Err(PlanError::RequestError{ address, err }); },
+    };
+    if !res.status().is_success() { return Err(PlanError::RequestFailure{ address, code: res.status(), err: res.text().await.ok() }); }
+    let registry: String = match res.text().await {
+        Ok(registry) => registry,
+        Err(err)     => { return Err(PlanError::RequestBodyError{ address, err }); },
+    };
+    match Address::from_str(registry.trim()) {
+        Ok(registry) => Ok(registry),
+        Err(err)     => Err(PlanError::RegistryParseError{ address, raw: registry, err }),
+    }
+}
+
+
+
+/// Helper function that decides whether the given location can run a task, based on what it reports to have available.
+/// 
+/// # Arguments
+/// - `location`: The name of the location (only used in the reason).
+/// - `availability`: The LocationAvailability reported by the location.
+/// - `package`: The name of the task's package.
+/// - `version`: The version of the task's package.
+/// - `indexed`: Whether the package is in this instance's PackageIndex. If so, the location may fetch it from there when it has not cached it.
+/// - `requirements`: The capabilities required by the task's package.
+/// 
+/// # Errors
+/// This function errors with the reason why the location cannot run the task, if it cannot.
+fn check_location(location: &str, availability: &LocationAvailability, package: &str, version: &Version, indexed: bool, requirements: &HashSet<Capability>) -> Result<(), String> {
+    // The location must support what the package needs...
+    if !supports(&availability.capabilities, requirements) {
+        let mut missing: Vec<String> = requirements.iter().filter(|c| !c.is_satisfied_by(&availability.capabilities)).map(|c| format!("{:?}", c)).collect();
+        missing.sort();
+        return Err(format!("location '{}' lacks capabilities {}", location, missing.join(", ")));
+    }
+
+    // ...and have the package, or be able to get it
+    let cached: bool = availability.packages.iter().any(|p| p.name == package && Version::from_str(&p.version).map(|v| &v == version).unwrap_or(false));
+    if !cached && !indexed {
+        return Err(format!("location '{}' has not cached package '{}' (version {}) and cannot fetch it from this instance", location, package, version));
+    }
+    Ok(())
+}
+
+/// Helper function that statically checks whether every task in the given list of edges can be scheduled on at least one location.
+/// 
+/// A location is a candidate for a task if the user allows it (i.e., it passes the task's locality constraints) and if, according to what it reports to have available, it supports the capabilities required by the task's package and has that package (either cached, or fetchable from the instance; see `check_location()`). Unlike `plan_edges()`, this function does not stop at the first problem but collects all of them, so the user gets a single report.
+/// 
+/// # Arguments
+/// - `table`: The SymbolTable where these edges live in.
+/// - `edges`: The given list of edges to check.
+/// - `api_addr`: The address where we can reach the `brane-api` service on.
+/// - `pindex`: The PackageIndex that lists the packages available in the instance.
+/// - `infra`: The infrastructure to resolve locations.
+/// - `availabilities`: A cache of what every location has available (or the reason why we could not get it), to avoid asking the same thing multiple times.
+/// - `unschedulable`: The list of (task, reasons) pairs to which we add any task that cannot be scheduled anywhere.
+async fn check_reachability(table: &SymTable, edges: &[Edge], api_addr: &Address, pindex: &PackageIndex, infra: &InfraFile, availabilities: &mut HashMap<String, Result<LocationAvailability, String>>, unschedulable: &mut Vec<(String, Vec<String>)>) {
+    // Since we are interested in all tasks regardless of control flow, we can simply examine the edges in-order
+    for edge in edges {
+        let (task, locs): (usize, &Locations) = match edge {
+            Edge::Node{ task, locs, .. } => (*task, locs),
+            _                            => { continue; },
+        };
+        let (package, version, function, requirements) = match &table.tasks[task] {
+            TaskDef::Compute{ package, version, function, requirements, .. } => (package, version, function, requirements),
+            TaskDef::Transfer{ .. }                                          => { continue; },
+        };
+        let name: String = format!("{}<{}>::{}", package, version, function.name);
+        debug!("Checking reachability of task '{}'...", name);
+
+        // Examine every location the user allows
+        let indexed: bool = pindex.get(package, Some(version)).is_some();
+        let candidates: Vec<String> = match locs {
+            Locations::All              => infra.iter().map(|(loc, _)| loc.clone()).collect(),
+            Locations::Restricted(locs) => locs.clone(),
+        };
+        if candidates.is_empty() {
+            unschedulable.push((name, vec![ "no locations are allowed".into() ]));
+            continue;
+        }
+        let mut reasons: Vec<String> = Vec::with_capacity(candidates.len());
+        for location in candidates {
+            if infra.get(&location).is_none() {
+                match split_peer_location(&location) {
+                    Some((peer, _)) => { reasons.push(format!("location '{}' is hosted by peer instance '{}'", location, peer)); },
+                    None            => { reasons.push(format!("location '{}' is unknown", location)); },
+                }
+                continue;
+            }
+            if !availabilities.contains_key(&location) {
+                let availability: Result<LocationAvailability, String> = get_availability(api_addr, &location).await.map_err(|err| err.to_string());
+                availabilities.insert(location.clone(), availability);
+            }
+            match &availabilities[&location] {
+                Ok(availability) => match check_location(&location, availability, package, version, indexed, requirements) {
+                    Ok(_)       => { reasons.clear(); break; },
+                    Err(reason) => { reasons.push(reason); },
+                },
+                Err(err) => { reasons.push(format!("location '{}' is unreachable ({})", location, err)); },
+            }
+        }
+        if !reasons.is_empty() { unschedulable.push((name, reasons)); }
+    }
+}
+
+
+
 /// Helper function that plans the given list of edges.
 /// 
 /// # Arguments
@@ -118,6 +371,9 @@ async fn send_update(producer: Arc<FutureProducer>, topic: impl AsRef<str>, corr
 /// - `merge`: The number of the edge until which we will run. You should use 'None' if you're calling this function.
 /// - `deferred`: Whether or not to show errors when an intermediate result is not generated yet (false) or not (true).
 /// - `done`: A list we use to keep track of edges we've already analyzed (to prevent endless loops).
+/// - `residency`: The locations where restricted data (and the results derived from it) may be processed. Results planned here are added to it.
+/// - `strategy`: The Strategy that chooses the location of tasks that are not pinned to one.
+/// - `reasoning`: A list to which we add, for every planned task, why it was planned where it was.
 /// 
 /// # Returns
 /// Nothing, but does change the given list.
@@ -126,7 +382,7 @@ async fn send_update(producer: Arc<FutureProducer>, topic: impl AsRef<str>, corr
 /// This function may error if the given list of edges was malformed (usually due to unknown or inaccessible datasets or results).
 #[allow(clippy::too_many_arguments)]
 #[async_recursion]
-async fn plan_edges(table: &mut SymTable, edges: &mut [Edge], api_addr: &Address, dindex: &DataIndex, infra: &InfraFile, pc: usize, merge: Option<usize>, deferred: bool, done: &mut HashSet<usize>) -> Result<(), PlanError> {
+async fn plan_edges(table: &mut SymTable, edges: &mut [Edge], api_addr: &Address, dindex: &DataIndex, infra: &InfraFile, pc: usize, merge: Option<usize>, deferred: bool, done: &mut HashSet<usize>, residency: &mut HashMap<DataName, Vec<String>>, strategy: &dyn Strategy, reasoning: &mut Vec<String>) -> Result<(), PlanError> {
     // We cannot get away simply examining all edges in-order; we have to follow their execution structure
     let mut pc: usize = pc;
     while pc < edges.len() && (merge.is_none() || pc != merge.unwrap()) {
@@ -135,63 +391,100 @@ async fn plan_edges(table: &mut SymTable, edges: &mut [Edge], api_addr: &Address
         if done.contains(&pc) { break; }
         done.insert(pc);
         match edge {
-            Edge::Node{ task, locs, at, input, result, next } => {
+            Edge::Node{ task, locs, at, input, result, next, .. } => {
                 // This is the node where it all revolves around, in the end
                 debug!("Planning task '{}' (edge {})...", table.tasks[*task].name(), pc);
+                let name: String = table.tasks[*task].name().into();
+                let requirements: &HashSet<Capability> = if let TaskDef::Compute{ requirements, .. } = &table.tasks[*task] {
+                    requirements
+                } else {
+                    panic!("Non-compute tasks are not (yet) supported.");
+                };
 
-                // If everything is allowed, we make it one easier for the planner by checking we happen to find only one occurrance based on the datasets
-                if locs.is_all() {
-                    // Search all of the input to collect a list of possible locations
-                    let mut data_locs: Vec<&String> = vec![];
-                    for (d, _) in input.iter() {
-                        // We only take data into account (for now, at least)
-                        if let DataName::Data(name) = d {
-                            // Attempt to find it
-                            if let Some(info) = dindex.get(name) {
-                                // Simply add all locations where it lives
-                                data_locs.append(&mut info.access.keys().collect::<Vec<&String>>());
-                            } else {
-                                return Err(PlanError::UnknownDataset{ name: name.clone() });
-                            }
+                // Data may restrict where it is processed, and so do the results derived from it
+                let mut allowed: Option<Vec<String>> = None;
+                for (d, _) in input.iter() {
+                    let restriction: Option<Vec<String>> = match d {
+                        DataName::Data(dname)           => dindex.get(dname).and_then(|info| info.residency.clone()),
+                        DataName::IntermediateResult(_) => residency.get(d).cloned(),
+                    };
+                    if let Some(restriction) = restriction {
+                        // Remember the dataset's restriction, so that the worker can check it too
+                        if let DataName::Data(_) = d { residency.insert(d.clone(), restriction.clone()); }
+                        allowed = merge_residency(allowed, Some(&restriction));
+                    }
+                }
+                let is_allowed = |location: &str| -> bool { allowed.as_ref().map(|allowed| allowed.iter().any(|loc| loc == location)).unwrap_or(true) };
+
+                // If the user pinned the task or it already has a location (because it was planned before), that's where it goes; otherwise, we let the strategy choose one
+                let pinned: Option<(String, &str)> = if locs.is_restrictive() && locs.restricted().len() == 1 {
+                    Some((locs.restricted()[0].clone(), "the user pinned it there"))
+                } else if let Some(at) = at.as_ref().filter(|at| is_allowed(at)) {
+                    // Unlike the user, an earlier planning may be overruled if the location no longer supports the task (or, as above, may not process its inputs)
+                    match get_capabilities(api_addr, at).await {
+                        Ok(capabilities) if !supports(&capabilities, requirements) => {
+                            debug!("Re-routing task '{}', since its earlier location '{}' does not support its requirements", name, at);
+                            None
+                        },
+                        _ => Some((at.clone(), "it was already planned there")),
+                    }
+                } else {
+                    None
+                };
+                let location: String = if let Some((location, why)) = pinned {
+                    // Tasks may only run on the locations of this instance, which are the only ones our users are authorized on
+                    if infra.get(&location).is_none() { return Err(PlanError::ForeignLocation{ task: name, loc: location }); }
+                    // Nor may it run where its inputs may not be processed
+                    if let Some(allowed) = allowed.as_ref().filter(|allowed| !allowed.contains(&location)) { return Err(PlanError::ResidencyViolation{ task: name, loc: location, allowed: allowed.clone() }); }
+
+                    // Assert that the location supports what we need
+                    let capabilities: HashSet<Capability> = get_capabilities(api_addr, &location).await?;
+                    if !supports(&capabilities, requirements) { return Err(PlanError::UnsupportedCapabilities{ task: name, loc: location, expected: requirements.clone(), got: capabilities }); }
+
+                    reasoning.push(format!("Task '{}' planned at '{}' because {}", name, location, why));
+                    location
+                } else {
+                    // Collect the locations of this instance that the user allows and that support what we need
+                    let mut candidates: Vec<String> = match locs {
+                        Locations::All              => infra.iter().map(|(loc, _)| loc.clone()).collect(),
+                        Locations::Restricted(locs) => locs.iter().filter(|loc| infra.get(loc).is_some()).cloned().collect(),
+                    };
+                    candidates.retain(|loc| is_allowed(loc));
+                    candidates.sort();
+                    let mut supported: Vec<String> = Vec::with_capacity(candidates.len());
+                    for location in candidates {
+                        match get_capabilities(api_addr, &location).await {
+                            Ok(capabilities) => { if supports(&capabilities, requirements) { supported.push(location); } },
+                            Err(err)         => { debug!("Not considering location '{}' for task '{}': {}", location, name, err); },
                         }
                     }
+                    if supported.is_empty() { return Err(PlanError::NoCandidateLocations{ task: name }); }
 
-                    // If there is only one location, then we override locs
-                    if data_locs.len() == 1 {
-                        *locs = Locations::Restricted(vec![ data_locs[0].clone() ]);
+                    // Collect where the task's inputs already live
+                    let mut inputs: Vec<(String, Vec<String>)> = Vec::with_capacity(input.len());
+                    for (d, _) in input.iter() {
+                        match d {
+                            DataName::Data(dname) => match dindex.get(dname) {
+                                Some(info) => { inputs.push((dname.clone(), info.access.keys().cloned().collect())); },
+                                None       => { return Err(PlanError::UnknownDataset{ name: dname.clone() }); },
+                            },
+                            DataName::IntermediateResult(rname) => { inputs.push((rname.clone(), table.results.get(rname).into_iter().cloned().collect())); },
+                        }
                     }
-                }
-
-                // We resolve all locations by collapsing them to the only possibility indicated by the user. More or less than zero? Error!
-                if !locs.is_restrictive() || locs.restricted().len() != 1 { return Err(PlanError::AmbigiousLocationError{ name: table.tasks[*task].name().into(), locs: locs.clone() }); }
-                let location: &str = &locs.restricted()[0];
 
-                // Fetch the list of capabilities supported by the planned location
-                let address: String = format!("{}/infra/capabilities/{}", api_addr, location);
-                let res: Response = match reqwest::get(&address).await {
-                    Ok(req)  => req,
-                    Err(err) => { return Err(PlanError::RequestError{ address, err }); },
-                };
-                if !res.status().is_success() { return Err(PlanError::RequestFailure{ address, code: res.status(), err: res.text().await.ok() }); }
-                let capabilities: String = match res.text().await {
-                    Ok(caps) => caps,
-                    Err(err) => { return Err(PlanError::RequestBodyError{ address, err }); },
-                };
-                let capabilities: HashSet<Capability> = match serde_json::from_str(&capabilities) {
-                    Ok(caps) => caps,
-                    Err(err) => { return Err(PlanError::RequestParseError{ address, raw: capabilities, err }); },
-                };
-
-                // Assert that this is what we need
-                if let TaskDef::Compute{ function, requirements, .. } = &table.tasks[*task] {
-                    if !capabilities.is_superset(requirements) { return Err(PlanError::UnsupportedCapabilities{ task: function.name.clone(), loc: location.into(), expected: requirements.clone(), got: capabilities }); }
-                } else {
-                    panic!("Non-compute tasks are not (yet) supported.");
+                    // Let the strategy choose among them
+                    let choice: Choice = match strategy.choose(&TaskInfo{ candidates: &supported, inputs: &inputs }) {
+                        Ok(choice)  => choice,
+                        Err(reason) => { return Err(PlanError::StrategyFailed{ task: name, strategy: strategy.name(), reason }); },
+                    };
+                    reasoning.push(format!("Task '{}' planned at '{}' by the {} strategy because {}", name, choice.location, strategy.name(), choice.reason));
+                    choice.location
                 };
 
                 // It checks out, plan it
-                *at = Some(location.into());
-                debug!("Task '{}' planned at '{}'", table.tasks[*task].name(), location);
+                *at = Some(location.clone());
+                debug!("Task '{}' planned at '{}'", name, location);
+                let location: &str = &location;
 
                 // For all dataset/intermediate result inputs, we check if these are available on the planned location.
                 for (name, avail) in input {
@@ -203,13 +496,18 @@ async fn plan_edges(table: &mut SymTable, edges: &mut [Edge], api_addr: &Address
                                     debug!("Input dataset '{}' is locally available", name);
                                     *avail = Some(AvailabilityKind::Available { how: access.clone() });
                                 } else {
-                                    // Select one of the other locations it's available (for now, random?)
+                                    // Select one of the other locations it's available (for now, random?), preferring those of this instance over those of peers
                                     if info.access.is_empty() { return Err(PlanError::DatasetUnavailable { name: name.clone(), locs: vec![] }); }
-                                    let mut rng = rand::thread_rng();
-                                    let location: &str = info.access.keys().choose(&mut rng).unwrap();
+                                    let location: &str = {
+                                        let mut rng = rand::thread_rng();
+                                        match info.access.keys().filter(|loc| infra.get(loc).is_some()).choose(&mut rng) {
+                                            Some(location) => location,
+                                            None           => info.access.keys().choose(&mut rng).unwrap(),
+                                        }
+                                    };
 
                                     // Get the registry of that location
-                                    let registry : &Address = &infra.get(location).unwrap_or_else(|| panic!("DataIndex advertises location '{}', but that location is unknown", location)).registry;
+                                    let registry : Address = get_registry(api_addr, infra, location).await?;
                                     let address  : String  = format!("{}/data/download/{}", registry, name);
                                     debug!("Input dataset '{}' will be transferred in from '{}'", name, address);
 
@@ -253,6 +551,8 @@ async fn plan_edges(table: &mut SymTable, edges: &mut [Edge], api_addr: &Address
                     // Insert an entry in the list detailling where to access it and how
                     debug!("Making intermediate result '{}' accessible after execution of '{}' on '{}'", name, table.tasks[*task].name(), location);
                     table.results.insert(name.clone(), location.into());
+                    // It may only be processed where all of its inputs may be
+                    if let Some(allowed) = allowed { residency.insert(DataName::IntermediateResult(name.clone()), allowed); }
                 }
 
                 // Move to the one indicated by 'next'
@@ -274,10 +574,10 @@ async fn plan_edges(table: &mut SymTable, edges: &mut [Edge], api_addr: &Address
                 let merge     : Option<usize> = *merge;
 
                 // First analyse the true_next branch, until it reaches the merge (or quits)
-                plan_edges(table, edges, api_addr, dindex, infra, true_next, merge, deferred, done).await?;
+                plan_edges(table, edges, api_addr, dindex, infra, true_next, merge, deferred, done, residency, strategy, reasoning).await?;
                 // If there is a false branch, do that one too
                 if let Some(false_next) = false_next {
-                    plan_edges(table, edges, api_addr, dindex, infra, false_next, merge, deferred, done).await?;
+                    plan_edges(table, edges, api_addr, dindex, infra, false_next, merge, deferred, done, residency, strategy, reasoning).await?;
                 }
 
                 // If there is a merge, continue there; otherwise, we can assume that we've returned fully in the branch
@@ -295,7 +595,7 @@ async fn plan_edges(table: &mut SymTable, edges: &mut [Edge], api_addr: &Address
                 // Analyse any of the branches
                 for b in branches {
                     // No merge needed since we can be safe in assuming parallel branches end with returns
-                    plan_edges(table, edges, api_addr, dindex, infra, b, None, deferred, done).await?;
+                    plan_edges(table, edges, api_addr, dindex, infra, b, None, deferred, done, residency, strategy, reasoning).await?;
                 }
 
                 // Continue at the merge
@@ -313,8 +613,8 @@ async fn plan_edges(table: &mut SymTable, edges: &mut [Edge], api_addr: &Address
                 let next : Option<usize> = *next;
 
                 // Run the conditions and body in a first pass, with deferation enabled, to do as much as we can
-                plan_edges(table, edges, api_addr, dindex, infra, cond, Some(body), true, done).await?;
-                plan_edges(table, edges, api_addr, dindex, infra, body, Some(cond), true, done).await?;
+                plan_edges(table, edges, api_addr, dindex, infra, cond, Some(body), true, done, residency, strategy, reasoning).await?;
+                plan_edges(table, edges, api_addr, dindex, infra, body, Some(cond), true, done, residency, strategy, reasoning).await?;
 
                 // Then we run through the condition and body again to resolve any unknown things
                 plan_deferred(table, edges, infra, cond, Some(body), &mut HashSet::new())?;
@@ -497,6 +797,139 @@ fn plan_deferred(table: &SymTable, edges: &mut [Edge], infra: &InfraFile, pc: us
 
 
 /***** LIBRARY *****/
+/// Plans the given workflow, i.e., assigns a location to every task and resolves where the datasets it uses come from.
+/// 
+/// This is the part of the planner shared by its Kafka and gRPC frontends.
+/// 
+/// # Arguments
+/// - `central`: The configuration of the central node, which tells us where to find the API and the infrastructure file.
+/// - `id`: The correlation ID of the workflow (used for debugging).
+/// - `workflow`: The Workflow to plan.
+/// 
+/// # Returns
+/// The outcome of planning: `PlanningStatus::Success` with the plan (as JSON) and why every task was planned where it was, `PlanningStatus::Failed` if some tasks cannot be scheduled anywhere, or `PlanningStatus::Error` if we failed to plan the workflow.
+pub async fn plan_workflow(central: &CentralConfig, id: &str, mut workflow: Workflow) -> PlanningStatus {
+    // Fetch the data index
+    let api: ApiClient = ApiClient::new(central.services.api.to_string());
+    let dindex: DataIndex = match api.data_index().await {
+        Ok(dindex) => dindex,
+        Err(err)   => {
+            error!("Failed to fetch DataIndex from '{}': {}", api.address(), err);
+            return PlanningStatus::Error(format!("Failed to fetch DataIndex from '{}': {}", api.address(), err));
+        }
+    };
+
+    // Now we do the planning, remembering why we planned every task where we did
+    let mut reasoning: Vec<String> = vec![];
+    {
+        // Load the infrastructure file
+        let infra: InfraFile = match InfraFile::from_path(&central.paths.infra) {
+            Ok(infra) => infra,
+            Err(err)  => {
+                error!("Failed to load infrastructure file '{}': {}", central.paths.infra.display(), err);
+                return PlanningStatus::Error(format!("Failed to load infrastructure file '{}': {}", central.paths.infra.display(), err));
+            }
+        };
+
+        // Before we plan anything, check that every task can run somewhere at all
+        {
+            let package_index_addr: String = format!("{}/graphql", central.services.api);
+            let pindex: PackageIndex = match get_package_index(&package_index_addr).await {
+                Ok(pindex) => pindex,
+                Err(err)   => {
+                    let err = PlanError::PackageIndexError{ address: package_index_addr, err };
+                    error!("{}", err);
+                    return PlanningStatus::Error(format!("{}", err));
+                },
+            };
+
+            debug!("Checking task reachability...");
+            let mut availabilities: HashMap<String, Result<LocationAvailability, String>> = HashMap::new();
+            let mut unschedulable: Vec<(String, Vec<String>)> = vec![];
+            check_reachability(&workflow.table, &workflow.graph, &central.services.api, &pindex, &infra, &mut availabilities, &mut unschedulable).await;
+            for edges in workflow.funcs.values() {
+                check_reachability(&workflow.table, edges, &central.services.api, &pindex, &infra, &mut availabilities, &mut unschedulable).await;
+            }
+            if !unschedulable.is_empty() {
+                let err = PlanError::UnschedulableTasks{ tasks: unschedulable };
+                error!("Cannot plan workflow with correlation ID '{}': {}", id, err);
+                return PlanningStatus::Failed(Some(format!("{}", err)));
+            }
+        }
+
+        // Prepare the configured strategy for choosing locations
+        let strategy: Box<dyn Strategy> = strategy::from_config(&central.strategy);
+        debug!("Choosing locations using the {} strategy", strategy.name());
+
+        // Keep track of where restricted data and the results derived from it may be processed, starting with what the compiler already knew
+        let mut residency: HashMap<DataName, Vec<String>> = mem::take(&mut workflow.residency);
+
+        // Get the symbol table muteable, so we can... mutate... it
+        let mut table: Arc<SymTable> = Arc::new(SymTable::new());
+        mem::swap(&mut workflow.table, &mut table);
+        let mut table: SymTable      = Arc::try_unwrap(table).unwrap();
+
+        // Do the main edges first
+        {
+            // Start by getting a list of all the edges
+            let mut edges: Arc<Vec<Edge>> = Arc::new(vec![]);
+            mem::swap(&mut workflow.graph, &mut edges);
+            let mut edges: Vec<Edge>      = Arc::try_unwrap(edges).unwrap();
+
+            // Plan them
+            debug!("Planning main edges...");
+            if let Err(err) = plan_edges(&mut table, &mut edges, &central.services.api, &dindex, &infra, 0, None, false, &mut HashSet::new(), &mut residency, &*strategy, &mut reasoning).await {
+                error!("Failed to plan main edges for workflow with correlation ID '{}': {}", id, err);
+                return PlanningStatus::Error(format!("{}", err));
+            };
+
+            // Move the edges back
+            let mut edges: Arc<Vec<Edge>> = Arc::new(edges);
+            mem::swap(&mut edges, &mut workflow.graph);
+        }
+
+        // Then we do the function edges
+        {
+            // Start by getting the map
+            let mut funcs: Arc<HashMap<usize, Vec<Edge>>> = Arc::new(HashMap::new());
+            mem::swap(&mut workflow.funcs, &mut funcs);
+            let mut funcs: HashMap<usize, Vec<Edge>>      = Arc::try_unwrap(funcs).unwrap();
+
+            // Iterate through all of the edges
+            for (idx, edges) in &mut funcs {
+                debug!("Planning '{}' edges...", table.funcs[*idx].name);
+                if let Err(err) = plan_edges(&mut table, edges, &central.services.api, &dindex, &infra, 0, None, false, &mut HashSet::new(), &mut residency, &*strategy, &mut reasoning).await {
+                    error!("Failed to plan function '{}' edges for workflow with correlation ID '{}': {}", table.funcs[*idx].name, id, err);
+                    return PlanningStatus::Error(format!("{}", err));
+                }
+            }
+
+            // Put the map back
+            let mut funcs: Arc<HashMap<usize, Vec<Edge>>> = Arc::new(funcs);
+            mem::swap(&mut funcs, &mut workflow.funcs);
+        }
+
+        // Then, put the table back (together with the residency, so the workers can enforce it as well)
+        let mut table: Arc<SymTable> = Arc::new(table);
+        mem::swap(&mut table, &mut workflow.table);
+        workflow.residency = residency;
+    }
+
+    // With the planning done, re-serialize
+    debug!("Serializing plan...");
+    let splan: String = match serde_json::to_string(&workflow) {
+        Ok(splan) => splan,
+        Err(err)  => {
+            error!("Failed to serialize plan: {}", err);
+            return PlanningStatus::Error(format!("{}", err));
+        },
+    };
+
+    // Done
+    debug!("Planning OK");
+    PlanningStatus::Success(splan, reasoning)
+}
+
 /// This function hosts the actual planner, which uses an event monitor to receive plans which are then planned.
 /// 
 /// # Arguments
@@ -555,6 +988,10 @@ pub async fn planner_server(node_config_path: impl Into<PathBuf>, node_config: N
         let producer          : Arc<FutureProducer> = producer.clone();
         let node_config_path  : PathBuf             = node_config_path.clone();
 
+        // Continue the trace of the workflow that is sent to us
+        let span: Span = info_span!("plan", correlation_id = %String::from_utf8_lossy(owned_message.key().unwrap_or(&[])));
+        otel::set_parent(&span, &otel::from_kafka_headers(owned_message.headers()));
+
         // Do the rest in a future that takes ownership of the clones
         async move {
             // Fetch the most recent NodeConfig
@@ -583,7 +1020,7 @@ pub async fn planner_server(node_config_path: impl Into<PathBuf>, node_config: N
 
                 // Attempt to parse the workflow
                 debug!("Parsing workflow of {} characters for session '{}'", message.len(), id);
-                let mut workflow: Workflow = match serde_json::from_str(&message) {
+                let workflow: Workflow = match serde_json::from_str(&message) {
                     Ok(workflow) => workflow,
                     Err(err)     => {
                         error!("Failed to parse incoming message workflow on topic '{}' as Workflow JSON: {}\n\nworkflow:\n{}\n{}\n{}\n", central.topics.planner_command, err, (0..80).map(|_| '-').collect::<String>(), message, (0..80).map(|_| '-').collect::<String>());
@@ -594,98 +1031,14 @@ pub async fn planner_server(node_config_path: impl Into<PathBuf>, node_config: N
                 // Send that we've started planning
                 if let Err(err) = send_update(producer.clone(), &central.topics.planner_results, &id, PlanningStatus::Started(None)).await { error!("Failed to update client that planning has started: {}", err); };
 
-                // Fetch the data index
-                let data_index_addr: String = format!("{}/data/info", central.services.api);
-                let dindex: DataIndex = match get_data_index(&data_index_addr).await {
-                    Ok(dindex) => dindex,
-                    Err(err)   => {
-                        error!("Failed to fetch DataIndex from '{}': {}", data_index_addr, err);
-                        return Ok(());
-                    }
-                };
-
-                // Now we do the planning
-                {
-                    // Load the infrastructure file
-                    let infra: InfraFile = match InfraFile::from_path(&central.paths.infra) {
-                        Ok(infra) => infra,
-                        Err(err)  => {
-                            error!("Failed to load infrastructure file '{}': {}", central.paths.infra.display(), err);
-                            return Ok(());
-                        }
-                    };
-
-                    // Get the symbol table muteable, so we can... mutate... it
-                    let mut table: Arc<SymTable> = Arc::new(SymTable::new());
-                    mem::swap(&mut workflow.table, &mut table);
-                    let mut table: SymTable      = Arc::try_unwrap(table).unwrap();
-
-                    // Do the main edges first
-                    {
-                        // Start by getting a list of all the edges
-                        let mut edges: Arc<Vec<Edge>> = Arc::new(vec![]);
-                        mem::swap(&mut workflow.graph, &mut edges);
-                        let mut edges: Vec<Edge>      = Arc::try_unwrap(edges).unwrap();
-
-                        // Plan them
-                        debug!("Planning main edges...");
-                        if let Err(err) = plan_edges(&mut table, &mut edges, &central.services.api, &dindex, &infra, 0, None, false, &mut HashSet::new()).await {
-                            error!("Failed to plan main edges for workflow with correlation ID '{}': {}", id, err);
-                            if let Err(err) = send_update(producer.clone(), &central.topics.planner_results, &id, PlanningStatus::Error(format!("{}", err))).await { error!("Failed to update client that planning has failed: {}", err); }
-                            return Ok(());
-                        };
-
-                        // Move the edges back
-                        let mut edges: Arc<Vec<Edge>> = Arc::new(edges);
-                        mem::swap(&mut edges, &mut workflow.graph);
-                    }
-
-                    // Then we do the function edges
-                    {
-                        // Start by getting the map
-                        let mut funcs: Arc<HashMap<usize, Vec<Edge>>> = Arc::new(HashMap::new());
-                        mem::swap(&mut workflow.funcs, &mut funcs);
-                        let mut funcs: HashMap<usize, Vec<Edge>>      = Arc::try_unwrap(funcs).unwrap();
-
-                        // Iterate through all of the edges
-                        for (idx, edges) in &mut funcs {
-                            debug!("Planning '{}' edges...", table.funcs[*idx].name);
-                            if let Err(err) = plan_edges(&mut table, edges, &central.services.api, &dindex, &infra, 0, None, false, &mut HashSet::new()).await {
-                                error!("Failed to plan function '{}' edges for workflow with correlation ID '{}': {}", table.funcs[*idx].name, id, err);
-                                if let Err(err) = send_update(producer.clone(), &central.topics.planner_results, &id, PlanningStatus::Error(format!("{}", err))).await { error!("Failed to update client that planning has failed: {}", err); }
-                                return Ok(());
-                            }
-                        }
-
-                        // Put the map back
-                        let mut funcs: Arc<HashMap<usize, Vec<Edge>>> = Arc::new(funcs);
-                        mem::swap(&mut funcs, &mut workflow.funcs);
-                    }
-
-                    // Then, put the table back
-                    let mut table: Arc<SymTable> = Arc::new(table);
-                    mem::swap(&mut table, &mut workflow.table);
-                }
-
-                // With the planning done, re-serialize
-                debug!("Serializing plan...");
-                let splan: String = match serde_json::to_string(&workflow) {
-                    Ok(splan) => splan,
-                    Err(err)  => {
-                        error!("Failed to serialize plan: {}", err);
-                        if let Err(err) = send_update(producer.clone(), &central.topics.planner_results, &id, PlanningStatus::Error(format!("{}", err))).await { error!("Failed to update client that planning has failed: {}", err); }
-                        return Ok(());
-                    },
-                };
-
-                // Send the result
-                if let Err(err) = send_update(producer.clone(), &central.topics.planner_results, &id, PlanningStatus::Success(splan)).await { error!("Failed to update client that planning has succeeded: {}", err); }
-                debug!("Planning OK");
+                // Plan it, and send the outcome back
+                let status: PlanningStatus = plan_workflow(&central, &id, workflow).await;
+                if let Err(err) = send_update(producer.clone(), &central.topics.planner_results, &id, status).await { error!("Failed to update client on the outcome of planning: {}", err); }
             }
 
             // Done
             Ok(())
-        }
+        }.instrument(span)
     }).await {
         Ok(_)    => Ok(()),
         Err(err) => Err(PlanError::KafkaStreamError{ err }),
@@ -0,0 +1,280 @@
+//  STRATEGY.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 11:06:02
+//  Last edited:
+//    16 Oct 2026, 11:06:02
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Defines the strategies with which the planner chooses a location for
+//!   tasks that the user did not pin to a single one. Every choice comes
+//!   with a human-readable reason, which is reported back to the user.
+// 
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use brane_cfg::node::PlanningStrategy as StrategyConfig;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Shorthand for creating a list of locations.
+    fn locs(locs: &[&str]) -> Vec<String> { locs.iter().map(|l| l.to_string()).collect() }
+
+
+    /// Tests that the strategies are created as configured.
+    #[test]
+    fn test_from_config() {
+        assert_eq!(from_config(&StrategyConfig::DataLocalityFirst).name(), "data-locality-first");
+        assert_eq!(from_config(&StrategyConfig::RoundRobin).name(), "round-robin");
+        assert_eq!(from_config(&StrategyConfig::CostAnnotated{ costs: HashMap::new() }).name(), "cost-annotated");
+        assert_eq!(from_config(&StrategyConfig::UserPinned).name(), "user-pinned");
+    }
+
+    /// Tests choosing the location with the most of a task's inputs.
+    #[test]
+    fn test_data_locality_first() {
+        let candidates: Vec<String> = locs(&[ "a", "b", "c" ]);
+
+        // Without inputs, the first candidate is as good as any
+        let choice: Choice = DataLocalityFirst.choose(&TaskInfo{ candidates: &candidates, inputs: &[] }).unwrap();
+        assert_eq!(choice.location, "a");
+
+        // Otherwise, the location with the most inputs wins
+        let inputs: Vec<(String, Vec<String>)> = vec![
+            ("x".into(), locs(&[ "b", "c" ])),
+            ("y".into(), locs(&[ "c" ])),
+            ("z".into(), locs(&[ "b" ])),
+            ("w".into(), locs(&[ "c", "elsewhere" ])),
+        ];
+        let choice: Choice = DataLocalityFirst.choose(&TaskInfo{ candidates: &candidates, inputs: &inputs }).unwrap();
+        assert_eq!(choice.location, "c");
+        assert_eq!(choice.reason, "it already holds 3 of the task's 4 inputs");
+
+        // Ties go to the first candidate, also if no candidate has any inputs
+        let choice: Choice = DataLocalityFirst.choose(&TaskInfo{ candidates: &candidates, inputs: &inputs[..1] }).unwrap();
+        assert_eq!(choice.location, "b");
+        let inputs: Vec<(String, Vec<String>)> = vec![ ("x".into(), locs(&[ "elsewhere" ])) ];
+        let choice: Choice = DataLocalityFirst.choose(&TaskInfo{ candidates: &candidates, inputs: &inputs }).unwrap();
+        assert_eq!(choice.location, "a");
+        assert_eq!(choice.reason, "it already holds 0 of the task's 1 inputs");
+    }
+
+    /// Tests cycling through the candidates.
+    #[test]
+    fn test_round_robin() {
+        let strategy: RoundRobin = RoundRobin::new();
+        let candidates: Vec<String> = locs(&[ "a", "b", "c" ]);
+        let task: TaskInfo = TaskInfo{ candidates: &candidates, inputs: &[] };
+        let chosen: Vec<String> = (0..7).map(|_| strategy.choose(&task).unwrap().location).collect();
+        assert_eq!(chosen, locs(&[ "a", "b", "c", "a", "b", "c", "a" ]));
+
+        // The turn is shared between tasks with different candidates
+        let candidates: Vec<String> = locs(&[ "a", "b" ]);
+        let choice: Choice = strategy.choose(&TaskInfo{ candidates: &candidates, inputs: &[] }).unwrap();
+        assert_eq!(choice.location, "b");
+        assert_eq!(choice.reason, "it is next in turn among the 2 possible locations");
+    }
+
+    /// Tests choosing the cheapest candidate.
+    #[test]
+    fn test_cost_annotated() {
+        let strategy: CostAnnotated = CostAnnotated{ costs: HashMap::from([ ("a".into(), 3.0), ("b".into(), 1.5), ("c".into(), 2.0), ("d".into(), 0.5) ]) };
+
+        // The cheapest candidate wins, even if cheaper locations exist elsewhere
+        let candidates: Vec<String> = locs(&[ "a", "b", "c" ]);
+        let choice: Choice = strategy.choose(&TaskInfo{ candidates: &candidates, inputs: &[] }).unwrap();
+        assert_eq!(choice.location, "b");
+        assert_eq!(choice.reason, "it is the cheapest possible location (cost 1.5)");
+
+        // Candidates without a cost are skipped, and ties go to the first candidate
+        let strategy: CostAnnotated = CostAnnotated{ costs: HashMap::from([ ("b".into(), 2.0), ("c".into(), 2.0) ]) };
+        assert_eq!(strategy.choose(&TaskInfo{ candidates: &candidates, inputs: &[] }).unwrap().location, "b");
+
+        // If no candidate has a cost, nothing can be chosen
+        let candidates: Vec<String> = locs(&[ "a", "e" ]);
+        assert_eq!(strategy.choose(&TaskInfo{ candidates: &candidates, inputs: &[] }).unwrap_err(), "none of the possible locations 'a', 'e' has a cost");
+    }
+
+    /// Tests that nothing is chosen if the user has to pin tasks.
+    #[test]
+    fn test_user_pinned() {
+        let candidates: Vec<String> = locs(&[ "a", "b" ]);
+        let err: String = UserPinned.choose(&TaskInfo{ candidates: &candidates, inputs: &[] }).unwrap_err();
+        assert!(err.starts_with("the task may run on 'a', 'b', but"));
+    }
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Describes a task for which a strategy has to choose a location.
+#[derive(Clone, Debug)]
+pub struct TaskInfo<'a> {
+    /// The locations on which the task may run, i.e., those allowed by the user that are part of this instance and that support the task's capabilities. Never empty, and always in the same (sorted) order.
+    pub candidates : &'a [String],
+    /// The inputs (datasets and intermediate results) of the task, together with the locations where they already live.
+    pub inputs     : &'a [(String, Vec<String>)],
+}
+
+/// Defines the location chosen by a strategy for a task.
+#[derive(Clone, Debug)]
+pub struct Choice {
+    /// The chosen location.
+    pub location : String,
+    /// Explains why the strategy chose this location.
+    pub reason   : String,
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines a strategy that chooses the location of tasks that the user did not pin to a single one.
+pub trait Strategy: Send + Sync {
+    /// Returns the name of the strategy as it may be shown to the user.
+    fn name(&self) -> &'static str;
+
+    /// Chooses a location for the given task.
+    /// 
+    /// # Arguments
+    /// - `task`: The TaskInfo that describes the task and where it may run.
+    /// 
+    /// # Returns
+    /// The chosen location and the reason for choosing it.
+    /// 
+    /// # Errors
+    /// This function errors (with the reason why) if the strategy cannot choose any of the candidate locations.
+    fn choose(&self, task: &TaskInfo) -> Result<Choice, String>;
+}
+
+/// Creates the strategy configured in the node config file.
+/// 
+/// # Arguments
+/// - `config`: The PlanningStrategy as given in the `node.yml` file.
+/// 
+/// # Returns
+/// A new Strategy that implements the configured behaviour.
+pub fn from_config(config: &StrategyConfig) -> Box<dyn Strategy> {
+    match config {
+        StrategyConfig::DataLocalityFirst      => Box::new(DataLocalityFirst),
+        StrategyConfig::RoundRobin             => Box::new(RoundRobin::new()),
+        StrategyConfig::CostAnnotated{ costs } => Box::new(CostAnnotated{ costs: costs.clone() }),
+        StrategyConfig::UserPinned             => Box::new(UserPinned),
+    }
+}
+
+
+
+/// Chooses the location that already holds the most of the task's inputs, such that the least data has to be transferred. Ties are broken by taking the first candidate.
+#[derive(Clone, Copy, Debug)]
+pub struct DataLocalityFirst;
+
+impl Strategy for DataLocalityFirst {
+    #[inline]
+    fn name(&self) -> &'static str { "data-locality-first" }
+
+    fn choose(&self, task: &TaskInfo) -> Result<Choice, String> {
+        if task.inputs.is_empty() {
+            return Ok(Choice{ location: task.candidates[0].clone(), reason: "the task has no inputs, so every location is equally close to its data".into() });
+        }
+
+        // Count the inputs that live on every candidate
+        let mut best: Option<(&String, usize)> = None;
+        for location in task.candidates {
+            let local: usize = task.inputs.iter().filter(|(_, locs)| locs.contains(location)).count();
+            if best.map(|(_, n)| local > n).unwrap_or(true) { best = Some((location, local)); }
+        }
+        let (location, local): (&String, usize) = best.unwrap();
+        Ok(Choice{ location: location.clone(), reason: format!("it already holds {} of the task's {} inputs", local, task.inputs.len()) })
+    }
+}
+
+
+
+/// Spreads tasks over the candidate locations by cycling through them.
+#[derive(Debug)]
+pub struct RoundRobin {
+    /// The number of tasks we have planned so far.
+    next : AtomicUsize,
+}
+
+impl RoundRobin {
+    /// Constructor for the RoundRobin strategy.
+    /// 
+    /// # Returns
+    /// A new RoundRobin that starts at the first candidate.
+    #[inline]
+    pub fn new() -> Self { Self{ next: AtomicUsize::new(0) } }
+}
+
+impl Default for RoundRobin {
+    #[inline]
+    fn default() -> Self { Self::new() }
+}
+
+impl Strategy for RoundRobin {
+    #[inline]
+    fn name(&self) -> &'static str { "round-robin" }
+
+    fn choose(&self, task: &TaskInfo) -> Result<Choice, String> {
+        let index: usize = self.next.fetch_add(1, Ordering::Relaxed) % task.candidates.len();
+        Ok(Choice{ location: task.candidates[index].clone(), reason: format!("it is next in turn among the {} possible locations", task.candidates.len()) })
+    }
+}
+
+
+
+/// Chooses the candidate location with the lowest cost, as annotated by the administrator. Locations without a cost are never chosen.
+#[derive(Clone, Debug)]
+pub struct CostAnnotated {
+    /// The cost of every location, by name.
+    pub costs : HashMap<String, f64>,
+}
+
+impl Strategy for CostAnnotated {
+    #[inline]
+    fn name(&self) -> &'static str { "cost-annotated" }
+
+    fn choose(&self, task: &TaskInfo) -> Result<Choice, String> {
+        let mut best: Option<(&String, f64)> = None;
+        for location in task.candidates {
+            let cost: f64 = match self.costs.get(location) {
+                Some(cost) => *cost,
+                None       => { continue; },
+            };
+            if best.map(|(_, c)| cost < c).unwrap_or(true) { best = Some((location, cost)); }
+        }
+        match best {
+            Some((location, cost)) => Ok(Choice{ location: location.clone(), reason: format!("it is the cheapest possible location (cost {})", cost) }),
+            None                   => Err(format!("none of the possible locations {} has a cost", task.candidates.iter().map(|l| format!("'{}'", l)).collect::<Vec<String>>().join(", "))),
+        }
+    }
+}
+
+
+
+/// Never chooses a location, requiring the user to pin every task.
+#[derive(Clone, Copy, Debug)]
+pub struct UserPinned;
+
+impl Strategy for UserPinned {
+    #[inline]
+    fn name(&self) -> &'static str { "user-pinned" }
+
+    #[inline]
+    fn choose(&self, task: &TaskInfo) -> Result<Choice, String> {
+        Err(format!("the task may run on {}, but this instance requires every task to be pinned to a single location (use On-structs for that)", task.candidates.iter().map(|l| format!("'{}'", l)).collect::<Vec<String>>().join(", ")))
+    }
+}
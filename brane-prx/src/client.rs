@@ -289,13 +289,14 @@ impl ProxyClient {
     /// 
     /// # Arguments
     /// - `address`: The address of the remote to connect to.
+    /// - `tls`: If given, the proxy wraps the connection in TLS (using the certificates of the given location) before it leaves this node.
     /// 
     /// # Returns
     /// The result of the connection, as a `Result<JobServiceClient<Channel>, tonic::transport::Error>`.
     /// 
     /// # Errors
     /// This function errors if we fail to reserve any new paths if necessary.
-    pub async fn connect_to_job(&self, address: impl AsRef<str>) -> Result<Result<JobServiceClient<Channel>, tonic::transport::Error>, Error> {
+    pub async fn connect_to_job(&self, address: impl AsRef<str>, tls: Option<NewPathRequestTlsOptions>) -> Result<Result<JobServiceClient<Channel>, tonic::transport::Error>, Error> {
         let address: &str = address.as_ref();
 
         // Parse the address as a URL
@@ -311,7 +312,7 @@ impl ProxyClient {
         let remote: String = format!("{}://{}:{}", address.scheme(), address.domain().unwrap(), address.port().unwrap());
         let port: Option<u16> = {
             let lock: RwLockReadGuard<HashMap<(String, Option<NewPathRequestTlsOptions>), u16>> = self.paths.read().unwrap();
-            lock.get(&(remote.clone(), None)).cloned()
+            lock.get(&(remote.clone(), tls.clone())).cloned()
         };
 
         // If not, request one
@@ -319,11 +320,11 @@ impl ProxyClient {
             Some(port) => port,
             None       => {
                 // Create the path
-                let port: u16 = create_path(&self.endpoint, &remote, &None).await?;
+                let port: u16 = create_path(&self.endpoint, &remote, &tls).await?;
 
                 // Store it in the internal map for next time
                 let mut lock: RwLockWriteGuard<HashMap<(String, Option<NewPathRequestTlsOptions>), u16>> = self.paths.write().unwrap();
-                lock.insert((remote.clone(), None), port);
+                lock.insert((remote.clone(), tls.clone()), port);
 
                 // And return the port
                 port
@@ -342,7 +343,7 @@ impl ProxyClient {
             Err(err) => {
                 // If it fails, remove the mapping so we are forced to ask a new one next time
                 let mut lock: RwLockWriteGuard<HashMap<(String, Option<NewPathRequestTlsOptions>), u16>> = self.paths.write().unwrap();
-                lock.remove(&(remote, None));
+                lock.remove(&(remote, tls));
                 Err(err)
             },
         })
@@ -75,6 +75,7 @@ async fn main() {
             std::process::exit(1);
         },
     };
+    info!("Enabled features: {}", node_config.features);
 
     // Parse the port range
     debug!("Parsing port range...");
@@ -126,8 +127,12 @@ async fn main() {
         .and(warp::body::bytes())
         .and(context.clone())
         .and_then(manage::new_path);
+    let health = warp::get()
+        .and(warp::path("health"))
+        .and(warp::path::end())
+        .map(|| "OK!\n");
 
     // Run the server
     info!("Reading to accept new connections @ '{}'...", node_config.ports.prx);
-    warp::serve(filter).run(node_config.ports.prx).await
+    warp::serve(filter.or(health)).run(node_config.ports.prx).await
 }
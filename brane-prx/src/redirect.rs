@@ -29,6 +29,7 @@ use tokio_rustls::client::TlsStream;
 use url::Url;
 
 use brane_cfg::certs::{load_certstore, load_identity};
+use brane_cfg::infra::split_peer_location;
 use brane_cfg::spec::Address;
 use brane_cfg::node::NodeConfig;
 
@@ -193,8 +194,14 @@ pub async fn path_server(node_config_path: PathBuf, listener: TcpListener, clien
                 },
             };
 
+            // Locations hosted by peer instances are trusted using the certificates of the peer itself
+            let certs_dir: PathBuf = match split_peer_location(&tls.location) {
+                Some((peer, _)) => node_config.paths.certs.join(peer),
+                None            => node_config.paths.certs.join(&tls.location),
+            };
+
             // Load the root CA certificate file
-            let ca_path: PathBuf = node_config.paths.certs.join(&tls.location).join("ca.pem");
+            let ca_path: PathBuf = certs_dir.join("ca.pem");
             let ca: RootCertStore = match load_certstore(&ca_path) {
                 Ok(store) => store,
                 Err(err)  => {
@@ -206,7 +213,7 @@ pub async fn path_server(node_config_path: PathBuf, listener: TcpListener, clien
             // If any, also load the client file
             let client: Option<(PathBuf, Vec<Certificate>, PrivateKey)> = if tls.use_client_auth {
                 debug!(":{}->{}: Adding client certificate...", socket_addr.port(), address);
-                let client_path: PathBuf = node_config.paths.certs.join(&tls.location).join("client-id.pem");
+                let client_path: PathBuf = certs_dir.join("client-id.pem");
                 match load_identity(&client_path) {
                     Ok((certs, key)) => Some((client_path, certs, key)),
                     Err(err)         => {
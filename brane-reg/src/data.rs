@@ -20,19 +20,21 @@ use log::{debug, error, info};
 use rustls::Certificate;
 use tempfile::TempDir;
 use tokio::fs as tfs;
-use tokio::io::AsyncReadExt;
+use tokio::io::{AsyncReadExt, DuplexStream};
 use warp::{Rejection, Reply};
 use warp::http::HeaderValue;
 use warp::hyper::{Body, StatusCode};
 use warp::hyper::body::{Bytes, Sender};
 use warp::reply::{self, Response};
-use x509_parser::certificate::X509Certificate;
-use x509_parser::prelude::FromDer;
 
+use brane_cfg::certs::client_name;
 use brane_cfg::node::NodeConfig;
 use brane_cfg::policies::{PolicyFile, UserPolicy};
-use brane_shr::fs::archive_async;
-use specifications::data::{AccessKind, AssetInfo};
+use brane_shr::audit::{self, AuditKind, AuditRecord, Decision};
+use brane_shr::fs::{archive_async, archive_to_async};
+use brane_shr::fetch;
+use brane_shr::grid::{self, Protocol};
+use specifications::data::{AccessKind, AssetInfo, TRANSFER_DIRECT, TRANSFER_HEADER};
 
 pub use crate::errors::DataError as Error;
 use crate::errors::AuthorizeError;
@@ -58,21 +60,10 @@ pub fn extract_client_name(cert: Option<Certificate>) -> Result<String, Authoriz
         None       => { return Err(AuthorizeError::ClientNoCert); },
     };
 
-    // Attempt to parse the certificate as a real x509 one
-    match X509Certificate::from_der(&cert.0) {
-        Ok((_, cert)) => {
-            // Get the part after 'CN = ' and before end-of-string or comma (since that's canonically the domain name)
-            let subject: String = cert.subject.to_string();
-            let name_loc: usize = match subject.find("CN=") {
-                Some(name_loc) => name_loc + 3,
-                None           => { return Err(AuthorizeError::ClientCertNoCN { subject }); },
-            };
-            let name_end: usize = subject[name_loc..].find(',').map(|c| name_loc + c).unwrap_or(subject.len());
-
-            // Extract it as the name
-            Ok(subject[name_loc..name_end].to_string())
-        },
-        Err(err) => Err(AuthorizeError::ClientCertParseError{ err }),
+    // Read the name from it
+    match client_name(&cert.0) {
+        Ok(name) => Ok(name),
+        Err(err) => Err(AuthorizeError::ClientCertError{ err }),
     }
 }
 
@@ -220,6 +211,49 @@ pub async fn assert_result_permission(node_config: &NodeConfig, identifier: impl
 
 
 
+/// Archives the given file or directory into a body while it is being sent.
+/// 
+/// # Arguments
+/// - `source`: The file or directory to archive.
+/// - `target`: A description of the stream to use in errors.
+/// 
+/// # Returns
+/// A Body that receives the compressed archive as it is created. If archiving fails halfway, the body is aborted so the receiver does not mistake the partial archive for a complete one.
+fn stream_archive(source: PathBuf, target: PathBuf) -> Body {
+    let (mut body_sender, body): (Sender, Body) = Body::channel();
+    tokio::spawn(async move {
+        // Archive into one end of a pipe while we send whatever comes out of the other end
+        let (writer, mut reader): (DuplexStream, DuplexStream) = tokio::io::duplex(1024 * 16);
+        let archive = archive_to_async(source, writer, &target, true);
+        let send = async {
+            let mut buf: [u8; 1024 * 16] = [0; 1024 * 16];
+            loop {
+                let bytes: usize = match reader.read(&mut buf).await {
+                    Ok(bytes) => bytes,
+                    Err(err)  => { return Err(Error::TarReadError{ path: target.clone(), err }); },
+                };
+                if bytes == 0 { return Ok(()); }
+                if let Err(err) = body_sender.send_data(Bytes::copy_from_slice(&buf[..bytes])).await { return Err(Error::TarSendError{ err }); }
+            }
+        };
+        let (archived, sent): (Result<(), brane_shr::fs::Error>, Result<(), Error>) = tokio::join!(archive, send);
+
+        // Abort the body if anything went wrong
+        let err: Option<Error> = match (archived, sent) {
+            (Err(err), _) => Some(Error::DataArchiveError{ err }),
+            (_, Err(err)) => Some(err),
+            _             => None,
+        };
+        if let Some(err) = err {
+            error!("{}", err);
+            body_sender.abort();
+        }
+    });
+    body
+}
+
+
+
 
 
 /***** LIBRARY *****/
@@ -282,7 +316,7 @@ pub async fn list(context: Arc<Context>) -> Result<impl Reply, Rejection> {
 /// Handles a GET on a specific datasets in a child-path of the `/data`-path, returning a JSON with more information about this dataset.
 /// 
 /// # Arguments
-/// - `name`: The name of the dataset to retrieve the metadata for.
+/// - `name`: The name of the dataset to retrieve the metadata for. May be a specific version (`NAME@VERSION`); otherwise, the latest version is returned.
 /// - `context`: The context that carries options and some shared structures between the warp paths.
 /// 
 /// # Returns
@@ -345,11 +379,75 @@ pub async fn get(name: String, context: Arc<Context>) -> Result<impl Reply, Reje
 
 
 
+/// Handles a GET on the versions of a specific dataset, returning a JSON with the metadata of every version of it.
+/// 
+/// # Arguments
+/// - `name`: The name of the dataset to retrieve the versions of.
+/// - `context`: The context that carries options and some shared structures between the warp paths.
+/// 
+/// # Returns
+/// The response that can be send back to the client. Contains a JSON-encoded list (`Vec`) of AssetInfo structs, sorted from oldest to newest version.
+/// 
+/// # Errors
+/// This function may error (i.e., reject) if we didn't know the given name or we failed to serialize the relevant AssetInfos.
+pub async fn versions(name: String, context: Arc<Context>) -> Result<impl Reply, Rejection> {
+    info!("Handling GET on `/data/versions/{}` (i.e., list dataset versions)...", name);
+
+    // Load the config file
+    let node_config: NodeConfig = match NodeConfig::from_path(&context.node_config_path) {
+        Ok(config) => config,
+        Err(err)   => {
+            error!("Failed to load NodeConfig file: {}", err);
+            return Err(warp::reject::reject());
+        },
+    };
+    if !node_config.node.is_worker() { error!("Given NodeConfig file '{}' does not have properties for a worker node.", context.node_config_path.display()); return Err(warp::reject::reject()); }
+
+    // Load the store
+    debug!("Loading data ('{}') and results ('{}')...", node_config.node.worker().paths.data.display(), node_config.node.worker().paths.results.display());
+    let store: Store = match Store::from_dirs(&node_config.node.worker().paths.data, &node_config.node.worker().paths.results).await {
+        Ok(store) => store,
+        Err(err)  => {
+            error!("Failed to load the store: {}", err);
+            return Err(warp::reject::reject());
+        }
+    };
+
+    // Collect the versions of the dataset
+    let versions: Vec<&AssetInfo> = store.get_versions(&name);
+    if versions.is_empty() {
+        error!("Unknown dataset '{}'", name);
+        return Err(warp::reject::not_found());
+    }
+
+    // Serialize them
+    debug!("Dataset found, returning {} version(s)", versions.len());
+    let body: String = match serde_json::to_string(&versions) {
+        Ok(body) => body,
+        Err(err) => {
+            return Err(warp::reject::custom(Error::AssetSerializeError { name, err }));
+        },
+    };
+    let body_len: usize = body.len();
+
+    // Construct a response with the body and the content-length header
+    let mut response = Response::new(Body::from(body));
+    response.headers_mut().insert(
+        "Content-Length",
+        HeaderValue::from(body_len),
+    );
+
+    // Done
+    Ok(response)
+}
+
+
+
 /// Handles a GET that downloads an entire dataset. This basically emulates a data transfer.
 /// 
 /// # Arguments
 /// - `cert`: The client certificate by which we may extract some identity. Only clients that are authenticated by the local store may connect.
-/// - `name`: The name of the dataset to download.
+/// - `name`: The name of the dataset to download. May be a specific version (`NAME@VERSION`); otherwise, the latest version is downloaded.
 /// - `context`: The context that carries options and some shared structures between the warp paths.
 /// 
 /// # Returns
@@ -394,98 +492,131 @@ pub async fn download_data(cert: Option<Certificate>, name: String, context: Arc
         Ok(name) => name,
         Err(err) => {
             error!("{} (client unauthenticated)", err);
+            audit::write(context.audit.as_deref(), AuditRecord::new("brane-reg", AuditKind::DataDownload, None, info.name.as_str(), Decision::Denied).with_details("client unauthenticated"));
             return Ok(reply::with_status(Response::new(Body::empty()), StatusCode::FORBIDDEN));
         },
     };
 
     // Before we continue, assert that this dataset may be downloaded by this person (uh-oh, how we gon' do that)
+    let record = |decision: Decision| -> AuditRecord { AuditRecord::new("brane-reg", AuditKind::DataDownload, Some(client_name.clone()), info.name.as_str(), decision) };
     match assert_data_permission(&node_config, &client_name, &info.name).await {
         Ok(true)  => {
             info!("Checker authorized download of dataset '{}' by '{}'", info.name, client_name);
+            audit::write(context.audit.as_deref(), record(Decision::Allowed));
         },
 
         Ok(false) => {
             info!("Checker denied download of dataset '{}' by '{}'", info.name, client_name);
+            audit::write(context.audit.as_deref(), record(Decision::Denied));
             return Ok(reply::with_status(Response::new(Body::empty()), StatusCode::FORBIDDEN));
         },
         Err(err) => {
             error!("Failed to consult the checker: {}", err);
+            audit::write(context.audit.as_deref(), record(Decision::Failed));
             return Err(warp::reject::reject());
         },
     }
 
+    // First, get a temporary directory
+    let tmpdir: TempDir = match TempDir::new() {
+        Ok(tmpdir) => tmpdir,
+        Err(err)   => {
+            let err = Error::TempDirCreateError{ err };
+            error!("{}", err);
+            return Err(warp::reject::custom(err));
+        }
+    };
+
     // Access the dataset in the way it likes to be accessed
-    match &info.access {
+    let path: PathBuf = match &info.access {
         AccessKind::File { path } => {
             debug!("Accessing file '{}' @ '{}' as AccessKind::File...", name, path.display());
-            let path: PathBuf = node_config.node.worker().paths.data.join(&name).join(path);
+            let path: PathBuf = store.get_data_dir(&node_config.node.worker().paths.data, info).join(path);
             debug!("File can be found under: '{}'", path.display());
+            path
+        },
 
-            // First, get a temporary directory
-            let tmpdir: TempDir = match TempDir::new() {
-                Ok(tmpdir) => tmpdir,
-                Err(err)   => {
-                    let err = Error::TempDirCreateError{ err };
+        AccessKind::XRootD { .. } | AccessKind::GridFtp { .. } => {
+            // Stage it from its storage system first (with our own credentials), after which we can send it like any other file
+            let (protocol, url): (Protocol, &str) = Protocol::of(&info.access).unwrap();
+            debug!("Staging '{}' @ '{}' from {} endpoint...", name, url, protocol);
+            match grid::stage(protocol, url, &node_config.node.worker().proxies, tmpdir.path(), None).await {
+                Ok((path, _)) => path,
+                Err(err)      => {
+                    let err = Error::DataStageError{ name: info.name.clone(), err };
                     error!("{}", err);
                     return Err(warp::reject::custom(err));
-                }
-            };
+                },
+            }
+        },
+
+        AccessKind::Url { url, digest } => {
+            // Fetch it first (checking its digest), after which we can send it like any other file
+            debug!("Fetching '{}' @ '{}'...", name, url);
+            match fetch::fetch(url, digest, tmpdir.path()).await {
+                Ok(path) => path,
+                Err(err) => {
+                    let err = Error::DataFetchError{ name: info.name.clone(), err };
+                    error!("{}", err);
+                    return Err(warp::reject::custom(err));
+                },
+            }
+        },
+    };
 
-            // Next, create an archive in the temporary directory
-            let tar_path: PathBuf = tmpdir.path().join("data.tar.gz");
-            if let Err(err) = archive_async(&path, &tar_path, true).await {
-                let err = Error::DataArchiveError{ err };
+    // Next, create an archive in the temporary directory
+    let tar_path: PathBuf = tmpdir.path().join("data.tar.gz");
+    if let Err(err) = archive_async(&path, &tar_path, true).await {
+        let err = Error::DataArchiveError{ err };
+        error!("{}", err);
+        return Err(warp::reject::custom(err));
+    }
+
+    // Now we send the tarball as a file in the reply
+    debug!("Sending back reply with compressed archive...");
+    let (mut body_sender, body): (Sender, Body) = Body::channel();
+
+    // Spawn a future that reads the file chunk-by-chunk (in case of large files)
+    tokio::spawn(async move {
+        // We move the temporary directory here just to keep it in scope
+        let _tmpdir: TempDir = tmpdir;
+
+        // Open the archive file to read
+        let mut handle: tfs::File = match tfs::File::open(&tar_path).await {
+            Ok(handle) => handle,
+            Err(err)   => {
+                let err = Error::TarOpenError{ path: tar_path, err };
                 error!("{}", err);
                 return Err(warp::reject::custom(err));
-            }
+            },
+        };
 
-            // Now we send the tarball as a file in the reply
-            debug!("Sending back reply with compressed archive...");
-            let (mut body_sender, body): (Sender, Body) = Body::channel();
-
-            // Spawn a future that reads the file chunk-by-chunk (in case of large files)
-            tokio::spawn(async move {
-                // We move the temporary directory here just to keep it in scope
-                let _tmpdir: TempDir = tmpdir;
-
-                // Open the archive file to read
-                let mut handle: tfs::File = match tfs::File::open(&tar_path).await {
-                    Ok(handle) => handle,
-                    Err(err)   => {
-                        let err = Error::TarOpenError{ path: tar_path, err };
-                        error!("{}", err);
-                        return Err(warp::reject::custom(err));
-                    },
-                };
+        // Read it chunk-by-chunk
+        // (The size of the buffer, like most of the code but edited for not that library cuz it crashes during compilation, has been pulled from https://docs.rs/stream-body/latest/stream_body/)
+        let mut buf: [u8; 1024 * 16] = [0; 1024 * 16];
+        loop {
+            // Read the chunk
+            let bytes: usize = match handle.read(&mut buf).await {
+                Ok(bytes) => bytes,
+                Err(err)  => {
+                    error!("{}", Error::TarReadError{ path: tar_path, err });
+                    break;
+                },
+            };
+            if bytes == 0 { break; }
 
-                // Read it chunk-by-chunk
-                // (The size of the buffer, like most of the code but edited for not that library cuz it crashes during compilation, has been pulled from https://docs.rs/stream-body/latest/stream_body/)
-                let mut buf: [u8; 1024 * 16] = [0; 1024 * 16];
-                loop {
-                    // Read the chunk
-                    let bytes: usize = match handle.read(&mut buf).await {
-                        Ok(bytes) => bytes,
-                        Err(err)  => {
-                            error!("{}", Error::TarReadError{ path: tar_path, err });
-                            break;
-                        },
-                    };
-                    if bytes == 0 { break; }
-
-                    // Send that with the body
-                    if let Err(err) = body_sender.send_data(Bytes::copy_from_slice(&buf[..bytes])).await {
-                        error!("{}", Error::TarSendError{ err });
-                    }
-                }
+            // Send that with the body
+            if let Err(err) = body_sender.send_data(Bytes::copy_from_slice(&buf[..bytes])).await {
+                error!("{}", Error::TarSendError{ err });
+            }
+        }
 
-                // Done
-                Ok(())
-            });
+        // Done
+        Ok(())
+    });
 
-            // We use the handle as a stream.
-            Ok(reply::with_status(Response::new(body), StatusCode::OK))
-        },
-    }
+    // We use the handle as a stream.
+    Ok(reply::with_status(Response::new(body), StatusCode::OK))
 }
 
 /// Handles a GET that downloads an intermediate result. This basically emulates a data transfer.
@@ -493,6 +624,7 @@ pub async fn download_data(cert: Option<Certificate>, name: String, context: Arc
 /// # Arguments
 /// - `cert`: The client certificate by which we may extract some identity. Only clients that are authenticated by the local store may connect.
 /// - `name`: The name of the intermediate result to download.
+/// - `transfer`: The transfer mode the client asks for in the `X-Brane-Transfer` header, if any. If it is `direct`, the archive is streamed while it is being created (and the reply confirms this with the same header).
 /// - `context`: The context that carries options and some shared structures between the warp paths.
 /// 
 /// # Returns
@@ -500,7 +632,7 @@ pub async fn download_data(cert: Option<Certificate>, name: String, context: Arc
 /// 
 /// # Errors
 /// This function may error (i.e., reject) if we didn't know the given name or we failed to serialize the relevant AssetInfo.
-pub async fn download_result(cert: Option<Certificate>, name: String, context: Arc<Context>) -> Result<impl Reply, Rejection> {
+pub async fn download_result(cert: Option<Certificate>, name: String, transfer: Option<String>, context: Arc<Context>) -> Result<impl Reply, Rejection> {
     info!("Handling GET on `/results/download/{}` (i.e., download intermediate result)...", name);
 
     // Load the config file
@@ -537,26 +669,39 @@ pub async fn download_result(cert: Option<Certificate>, name: String, context: A
         Ok(name) => name,
         Err(err) => {
             error!("{} (client unauthenticated)", err);
+            audit::write(context.audit.as_deref(), AuditRecord::new("brane-reg", AuditKind::ResultDownload, None, name.as_str(), Decision::Denied).with_details("client unauthenticated"));
             return Ok(reply::with_status(Response::new(Body::empty()), StatusCode::FORBIDDEN));
         },
     };
 
     // Before we continue, assert that this dataset may be downloaded by this person (uh-oh, how we gon' do that)
+    let record = |decision: Decision| -> AuditRecord { AuditRecord::new("brane-reg", AuditKind::ResultDownload, Some(client_name.clone()), name.as_str(), decision) };
     match assert_result_permission(&node_config, &client_name, &name).await {
         Ok(true)  => {
             info!("Checker authorized download of intermediate result '{}' by '{}'", name, client_name);
+            audit::write(context.audit.as_deref(), record(Decision::Allowed));
         },
 
         Ok(false) => {
             info!("Checker denied download of intermediate result '{}' by '{}'", name, client_name);
+            audit::write(context.audit.as_deref(), record(Decision::Denied));
             return Ok(reply::with_status(Response::new(Body::empty()), StatusCode::FORBIDDEN));
         },
         Err(err) => {
             error!("Failed to consult the checker: {}", err);
+            audit::write(context.audit.as_deref(), record(Decision::Failed));
             return Err(warp::reject::reject());
         },
     }
 
+    // If the consumer asks for it, stream the archive while we create it instead of staging it on disk first
+    if transfer.as_deref() == Some(TRANSFER_DIRECT) {
+        debug!("Sending back reply with directly streamed archive...");
+        let mut response: Response = Response::new(stream_archive(path.into(), format!("<direct transfer of '{}'>", name).into()));
+        response.headers_mut().insert(TRANSFER_HEADER, HeaderValue::from_static(TRANSFER_DIRECT));
+        return Ok(reply::with_status(response, StatusCode::OK));
+    }
+
     // Start the upload; first, get a temporary directory
     let tmpdir: TempDir = match TempDir::new() {
         Ok(tmpdir) => tmpdir,
@@ -97,6 +97,10 @@ pub enum DataError {
 
     /// Failed to create a temporary directory.
     TempDirCreateError{ err: std::io::Error },
+    /// Failed to stage the given dataset from its scientific storage system.
+    DataStageError{ name: String, err: brane_shr::grid::Error },
+    /// Failed to fetch the given dataset from its URL.
+    DataFetchError{ name: String, err: brane_shr::fetch::Error },
     /// Failed to archive the given dataset.
     DataArchiveError{ err: brane_shr::fs::Error },
     /// Failed to re-open the tar file after compressing.
@@ -121,6 +125,8 @@ impl Display for DataError {
             AssetSerializeError{ name, err } => write!(f, "Failed to serialize dataset metadata for dataset '{}': {}", name, err),
 
             TempDirCreateError{ err }              => write!(f, "Failed to create a temporary directory: {}", err),
+            DataStageError{ name, err }            => write!(f, "Failed to stage dataset '{}': {}", name, err),
+            DataFetchError{ name, err }            => write!(f, "Failed to fetch dataset '{}': {}", name, err),
             DataArchiveError{ err }                => write!(f, "Failed to archive data: {}", err),
             TarOpenError{ path, err }              => write!(f, "Failed to re-open tarball file '{}': {}", path.display(), err),
             TarReadError{ path, err }              => write!(f, "Failed to read from tarball file '{}': {}", path.display(), err),
@@ -143,10 +149,8 @@ impl warp::reject::Reject for DataError {}
 pub enum AuthorizeError {
     /// The client did not provide us with a certificate.
     ClientNoCert,
-    /// We failed to parse the client's certificate as a certificate.
-    ClientCertParseError{ err: x509_parser::nom::Err<x509_parser::prelude::X509Error> },
-    /// The incoming certificate has no 'CN' field.
-    ClientCertNoCN{ subject: String },
+    /// We failed to get the client's name from its certificate (e.g., because it could not be parsed or has no 'CN' field).
+    ClientCertError{ err: brane_cfg::certs::Error },
 
     /// Failed to load the policy file.
     PolicyFileError{ err: brane_cfg::policies::Error },
@@ -158,9 +162,8 @@ impl Display for AuthorizeError {
     fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
         use AuthorizeError::*;
         match self {
-            ClientNoCert                => write!(f, "No certificate provided"),
-            ClientCertParseError{ err } => write!(f, "Failed to parse incoming client certificate: {}", err),
-            ClientCertNoCN{ subject }   => write!(f, "Incoming client certificate does not have a CN field specified in subject '{}'", subject),
+            ClientNoCert           => write!(f, "No certificate provided"),
+            ClientCertError{ err } => write!(f, "{}", err),
 
             PolicyFileError{ err }     => write!(f, "Failed to load policy file: {}", err),
             NoUserPolicy{ user, data } => write!(f, "No matching policy rule found for user '{}' / data '{}' (did you forget a final AllowAll/DenyAll?)", user, data),
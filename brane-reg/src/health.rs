@@ -13,29 +13,87 @@
 //!   `/health` path(s).
 // 
 
-use log::debug;
+use std::sync::Arc;
+
+use log::{debug, warn};
 use warp::{Rejection, Reply};
-use warp::http::HeaderValue;
+use warp::http::{HeaderValue, StatusCode};
 use warp::hyper::Body;
 use warp::reply::Response;
 
+use brane_cfg::node::{NodeConfig, NodeKindConfig, WorkerConfig};
+use brane_shr::cas::ContentStore;
+use brane_shr::disk::{DiskUsage, Pressure};
+
+use crate::spec::Context;
+
+
+/***** HELPER FUNCTIONS *****/
+/// Measures the disk usage of this worker node.
+/// 
+/// # Arguments
+/// - `context`: The context that tells us where to find the node config file.
+/// 
+/// # Returns
+/// The disk usage of the node together with the space it should always keep free, or `None` if we failed to measure it (which is logged).
+async fn disk_usage(context: &Context) -> Option<(DiskUsage, u64)> {
+    let node_config: NodeConfig = match NodeConfig::from_path(&context.node_config_path) {
+        Ok(config) => config,
+        Err(err)   => { warn!("Failed to load NodeConfig file: {} (not reporting disk usage)", err); return None; },
+    };
+    let worker_config: &WorkerConfig = if let NodeKindConfig::Worker(config) = &node_config.node {
+        config
+    } else {
+        panic!("Got a non-worker node config for the registry service");
+    };
+
+    let store: ContentStore = match ContentStore::new(&worker_config.paths.store).await {
+        Ok(store) => store,
+        Err(err)  => { warn!("Failed to open store '{}': {} (not reporting disk usage)", worker_config.paths.store.display(), err); return None; },
+    };
+    match DiskUsage::measure(&store, &worker_config.paths.store, &[ &worker_config.paths.temp_data, &worker_config.paths.temp_results ]).await {
+        Ok(usage) => Some((usage, worker_config.min_free_space)),
+        Err(err)  => { warn!("Failed to measure disk usage: {} (not reporting disk usage)", err); None },
+    }
+}
+
+
+
+
 
 /***** LIBRARY *****/
-/// Handles a GET on the main `/health` path, returning that this service is alive.
+/// Handles a GET on the main `/health` path, returning that this service is alive and how much pressure there is on the node's disk.
+/// 
+/// # Arguments
+/// - `context`: The context that tells us where to find the node config file.
 /// 
 /// # Returns
-/// The response that can be send back to the client. Simply contains the string "OK!\n".
+/// The response that can be send back to the client. Contains the string "OK!\n", followed by the disk pressure and usage (in bytes) of the node as `key: value` lines. If the disk pressure is critical (i.e., the node refuses new tasks), the first line reads "LOW DISK SPACE!\n" instead and the status code is 503.
 /// 
 /// # Errors
 /// This function doesn't usually error.
-pub async fn get() -> Result<impl Reply, Rejection> {
+pub async fn get(context: Arc<Context>) -> Result<impl Reply, Rejection> {
     debug!("Handling GET on `/health` (i.e., confirming service is alive)...");
 
+    // Report the disk pressure alongside the liveness
+    let (body, status): (String, StatusCode) = match disk_usage(&context).await {
+        Some((usage, reserve)) => {
+            let pressure: Pressure = usage.pressure(reserve);
+            (
+                format!("{}\npressure: {}\nfree: {}\nreserved: {}\npackages: {}\ndata: {}\nresults: {}\nscratch: {}\n", if pressure == Pressure::Critical { "LOW DISK SPACE!" } else { "OK!" }, pressure, usage.free, reserve, usage.packages, usage.data, usage.results, usage.scratch),
+                if pressure == Pressure::Critical { StatusCode::SERVICE_UNAVAILABLE } else { StatusCode::OK },
+            )
+        },
+        None => ("OK!\n".into(), StatusCode::OK),
+    };
+    let body_len: usize = body.len();
+
     // Construct a response with the body and the content-length header
-    let mut response = Response::new(Body::from("OK!\n"));
+    let mut response = Response::new(Body::from(body));
+    *response.status_mut() = status;
     response.headers_mut().insert(
         "Content-Length",
-        HeaderValue::from(4),
+        HeaderValue::from(body_len),
     );
 
     // Done
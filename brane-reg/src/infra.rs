@@ -12,10 +12,12 @@
 //!   Defines path functions for infrastructure-related querying.
 // 
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-use log::{error, info};
+use log::{debug, error, info};
 use warp::{Rejection, Reply};
 use warp::http::HeaderValue;
 use warp::hyper::Body;
@@ -23,9 +25,161 @@ use warp::reply::Response;
 
 use brane_cfg::backend::BackendFile;
 use brane_cfg::node::{NodeConfig, NodeKindConfig, WorkerConfig};
+use brane_shr::cas::{ContentStore, TagKind};
+use specifications::data::AccessKind;
 use specifications::package::Capability;
+use specifications::registry::{CachedPackage, LocationAvailability};
 
 use crate::spec::Context;
+use crate::store::Store;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Tests computing the size of files and directories, without following links.
+    #[test]
+    fn test_disk_size() {
+        let dir: tempfile::TempDir = tempfile::tempdir().unwrap();
+        let root: &Path = dir.path();
+        fs::write(root.join("a"), [ 0u8; 100 ]).unwrap();
+        fs::create_dir(root.join("sub")).unwrap();
+        fs::write(root.join("sub").join("b"), [ 0u8; 50 ]).unwrap();
+
+        assert_eq!(disk_size(&root.join("a")), 100);
+        assert_eq!(disk_size(&root.join("sub")), 50);
+        assert_eq!(disk_size(root), 150);
+        assert_eq!(disk_size(&root.join("missing")), 0);
+
+        // Links are not counted, so a cycle terminates and files are not counted twice
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(root, root.join("sub").join("cycle")).unwrap();
+            std::os::unix::fs::symlink(root.join("a"), root.join("link")).unwrap();
+            assert_eq!(disk_size(root), 150);
+            assert_eq!(disk_size(&root.join("link")), 0);
+        }
+    }
+
+    /// Tests computing the sizes of datasets off the async runtime.
+    #[tokio::test]
+    async fn test_disk_sizes() {
+        let dir: tempfile::TempDir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a"), [ 0u8; 10 ]).unwrap();
+
+        let sizes: HashMap<String, u64> = disk_sizes(vec![ ("local@1.0.0".into(), Some(dir.path().join("a"))), ("remote@1.0.0".into(), None) ]).await.unwrap();
+        assert_eq!(sizes, HashMap::from([ ("local@1.0.0".into(), 10), ("remote@1.0.0".into(), 0) ]));
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Loads the capabilities supported by this domain from its backend file.
+/// 
+/// # Arguments
+/// - `worker_config`: The WorkerConfig that tells us where to find the backend file, and that may advertise additional capabilities itself.
+/// 
+/// # Returns
+/// The set of capabilities supported, i.e., those of the backend together with those advertised in the node config.
+/// 
+/// # Errors
+/// This function errors (i.e., rejects) if we failed to load the backend file.
+fn load_capabilities(worker_config: &WorkerConfig) -> Result<HashSet<Capability>, Rejection> {
+    match BackendFile::from_path(&worker_config.paths.backend) {
+        Ok(backend) => {
+            let mut capabilities: HashSet<Capability> = backend.capabilities.unwrap_or_default();
+            capabilities.extend(worker_config.capabilities.iter().copied());
+            Ok(capabilities)
+        },
+        Err(err)    => {
+            error!("Failed to load backend file: {}", err);
+            Err(warp::reject::reject())
+        },
+    }
+}
+
+/// Computes the size of the given file or directory on disk.
+/// 
+/// # Arguments
+/// - `path`: The path of the file or directory to compute the size of. Directories are traversed recursively.
+/// 
+/// # Returns
+/// The size in bytes. Any entries that we fail to read are silently counted as empty, and so are symbolic links (which are not followed, since they may point outside of the dataset or back to one of its parents).
+fn disk_size(path: &Path) -> u64 {
+    let metadata: fs::Metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(_)       => { return 0; },
+    };
+    if metadata.file_type().is_symlink() { return 0; }
+    if !metadata.is_dir() { return metadata.len(); }
+    match fs::read_dir(path) {
+        Ok(entries) => entries.filter_map(|e| e.ok()).map(|e| disk_size(&e.path())).sum(),
+        Err(_)      => 0,
+    }
+}
+
+/// Computes the sizes of the given datasets on disk, on a thread where blocking is allowed.
+/// 
+/// # Arguments
+/// - `datasets`: The identifiers of the datasets, together with the path of their data (or `None` if they are not stored here).
+/// 
+/// # Returns
+/// The size of every dataset, in bytes (see `disk_size()`).
+/// 
+/// # Errors
+/// This function errors if the blocking thread panicked.
+async fn disk_sizes(datasets: Vec<(String, Option<PathBuf>)>) -> Result<HashMap<String, u64>, tokio::task::JoinError> {
+    tokio::task::spawn_blocking(move || {
+        datasets.into_iter().map(|(id, path)| {
+            let size: u64 = path.map(|path| disk_size(&path)).unwrap_or(0);
+            (id, size)
+        }).collect()
+    }).await
+}
+
+/// Lists the package images cached in the given content-addressed store.
+/// 
+/// # Arguments
+/// - `store_path`: The root directory of the store, in which images are tagged as `<name>-<version>`.
+/// 
+/// # Returns
+/// A list of CachedPackages, one per image found. If the store cannot be read, then we assume nothing has been cached yet.
+async fn cached_packages(store_path: &Path) -> Vec<CachedPackage> {
+    let (store, tags): (ContentStore, Vec<(String, String)>) = match ContentStore::new(store_path).await {
+        Ok(store) => match store.tags(TagKind::Package).await {
+            Ok(tags) => (store, tags),
+            Err(err) => {
+                debug!("Failed to read package tags in store '{}': {} (assuming no packages cached)", store_path.display(), err);
+                return vec![];
+            },
+        },
+        Err(err) => {
+            debug!("Failed to open store '{}': {} (assuming no packages cached)", store_path.display(), err);
+            return vec![];
+        },
+    };
+
+    let mut packages: Vec<CachedPackage> = vec![];
+    for (tag, digest) in tags {
+        if let Some((name, version)) = tag.rsplit_once('-') {
+            packages.push(CachedPackage {
+                name    : name.into(),
+                version : version.into(),
+                size    : fs::metadata(store.object_path(&digest)).map(|m| m.len()).unwrap_or(0),
+            });
+        }
+    }
+    packages
+}
+
+
+
 
 
 /***** LIBRARY *****/
@@ -53,17 +207,8 @@ pub async fn get_capabilities(context: Arc<Context>) -> Result<impl Reply, Rejec
         panic!("Got a non-worker node config for the registry service");
     };
 
-    // Read the backend file
-    let backend: BackendFile = match BackendFile::from_path(&worker_config.paths.backend) {
-        Ok(backend) => backend,
-        Err(err)    => {
-            error!("Failed to load backend file: {}", err);
-            return Err(warp::reject::reject());
-        },
-    };
-
     // Serialize the capabilities
-    let capabilities: HashSet<Capability> = backend.capabilities.unwrap_or_default();
+    let capabilities: HashSet<Capability> = load_capabilities(&worker_config)?;
     let capabilities: String = match serde_json::to_string(&capabilities) {
         Ok(capabilities) => capabilities,
         Err(err)         => {
@@ -84,3 +229,78 @@ pub async fn get_capabilities(context: Arc<Context>) -> Result<impl Reply, Rejec
     Ok(response)
 }
 
+
+
+/// Handles a GET on the `/infra/availability` path, returning what this domain has available locally.
+/// 
+/// # Returns
+/// The response that can be send back to the client. Contains a JSON-encoded LocationAvailability with the supported capabilities, the cached package images and the advertised datasets (with their sizes).
+/// 
+/// # Errors
+/// This function may error (i.e., reject) if we failed to load the node config, backend file or data store.
+pub async fn get_availability(context: Arc<Context>) -> Result<impl Reply, Rejection> {
+    info!("Handling GET on `/infra/availability` (i.e., get local availability)...");
+
+    // Read the node file
+    let node_config: NodeConfig = match NodeConfig::from_path(&context.node_config_path) {
+        Ok(config) => config,
+        Err(err)   => {
+            error!("Failed to load NodeConfig file: {}", err);
+            return Err(warp::reject::reject());
+        },
+    };
+    let worker_config: &WorkerConfig = if let NodeKindConfig::Worker(config) = &node_config.node {
+        config
+    } else {
+        panic!("Got a non-worker node config for the registry service");
+    };
+
+    // Load the datasets and their sizes
+    let store: Store = match Store::from_dirs(&worker_config.paths.data, &worker_config.paths.results).await {
+        Ok(store) => store,
+        Err(err)  => {
+            error!("Failed to load the store: {}", err);
+            return Err(warp::reject::reject());
+        }
+    };
+    let paths: Vec<(String, Option<PathBuf>)> = store.datasets.iter().map(|(id, info)| {
+        let path: Option<PathBuf> = match &info.access {
+            AccessKind::File{ path }                                                  => Some(store.get_data_dir(&worker_config.paths.data, info).join(path)),
+            // Remote datasets take up no space here until a task needs them
+            AccessKind::XRootD{ .. } | AccessKind::GridFtp{ .. } | AccessKind::Url{ .. } => None,
+        };
+        (id.clone(), path)
+    }).collect();
+    let datasets: HashMap<String, u64> = match disk_sizes(paths).await {
+        Ok(datasets) => datasets,
+        Err(err)     => {
+            error!("Failed to compute the sizes of the datasets: {}", err);
+            return Err(warp::reject::reject());
+        },
+    };
+
+    // Collect everything
+    let availability: LocationAvailability = LocationAvailability {
+        capabilities : load_capabilities(worker_config)?,
+        packages     : cached_packages(&worker_config.paths.store).await,
+        datasets,
+    };
+    let body: String = match serde_json::to_string(&availability) {
+        Ok(body) => body,
+        Err(err) => {
+            error!("Failed to serialize location availability: {}", err);
+            return Err(warp::reject::reject());
+        },
+    };
+    let body_len: usize = body.len();
+
+    // Construct a response with the body and the content-length header
+    let mut response = Response::new(Body::from(body));
+    response.headers_mut().insert(
+        "Content-Length",
+        HeaderValue::from(body_len),
+    );
+
+    // Done
+    Ok(response)
+}
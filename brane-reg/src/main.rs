@@ -17,11 +17,14 @@ use std::sync::Arc;
 
 use clap::Parser;
 use dotenvy::dotenv;
-use log::{debug, error, info, LevelFilter};
+use log::{debug, error, info, warn, LevelFilter};
 use rustls::Certificate;
 use warp::Filter;
 
 use brane_cfg::node::NodeConfig;
+use brane_shr::audit::AuditLog;
+use brane_shr::otel;
+use specifications::data::TRANSFER_HEADER;
 
 use brane_reg::spec::Context;
 use brane_reg::server::serve_with_auth;
@@ -74,11 +77,16 @@ async fn main() {
         },
     };
     if !node_config.node.is_worker() { error!("Given NodeConfig file '{}' does not have properties for a worker node.", args.node_config_path.display()); std::process::exit(1); }
+    info!("Enabled features: {}", node_config.features);
+
+    // Set up tracing, exporting spans if the node is configured to
+    if let Err(err) = otel::init("brane-reg", node_config.tracing.as_ref()) { warn!("{} (spans will not be exported)", err); }
 
 
 
     // Put the path in a context
     let context : Arc<Context> = Arc::new(Context {
+        audit            : node_config.node.worker().paths.audit.as_ref().map(|dir| Arc::new(AuditLog::new(dir, "brane-reg"))),
         node_config_path : args.node_config_path,
     });
     let context = warp::any().map(move || context.clone());
@@ -99,6 +107,13 @@ async fn main() {
         .and(warp::path::end())
         .and(context.clone())
         .and_then(data::get);
+    let get_asset_versions = warp::get()
+        .and(warp::path("data"))
+        .and(warp::path("versions"))
+        .and(warp::path::param())
+        .and(warp::path::end())
+        .and(context.clone())
+        .and_then(data::versions);
     let download_asset = warp::get()
         .and(warp::ext::get::<Option<Certificate>>())
         .and(warp::path("data"))
@@ -113,6 +128,7 @@ async fn main() {
         .and(warp::path("download"))
         .and(warp::path::param())
         .and(warp::path::end())
+        .and(warp::header::optional::<String>(TRANSFER_HEADER))
         .and(context.clone())
         .and_then(data::download_result);
     let infra_capabilities = warp::get()
@@ -121,20 +137,37 @@ async fn main() {
         .and(warp::path::end())
         .and(context.clone())
         .and_then(infra::get_capabilities);
+    let infra_availability = warp::get()
+        .and(warp::path("infra"))
+        .and(warp::path("availability"))
+        .and(warp::path::end())
+        .and(context.clone())
+        .and_then(infra::get_availability);
     let version = warp::path("version")
         .and(warp::path::end())
         .and_then(version::get);
+    let features = warp::get()
+        .and(warp::path("version"))
+        .and(warp::path("features"))
+        .and(warp::path::end())
+        .and(context.clone())
+        .and_then(version::features);
     let health = warp::path("health")
         .and(warp::path::end())
+        .and(context.clone())
         .and_then(health::get);
-    let filter = list_assets.or(get_asset).or(download_asset).or(download_result).or(infra_capabilities).or(version).or(health);
+    let filter = list_assets.or(get_asset).or(get_asset_versions).or(download_asset).or(download_result).or(infra_capabilities).or(infra_availability).or(version).or(features).or(health);
 
-    // Run it
-    match serve_with_auth(node_config.paths.certs.join("server.pem"), node_config.paths.certs.join("server-key.pem"), node_config.paths.certs.join("ca.pem"), filter, node_config.node.worker().ports.reg).await {
-        Ok(_)    => {},
-        Err(err) => {
+    // Run it until we are asked to stop
+    match otel::until_shutdown(serve_with_auth(node_config.paths.certs.join("server.pem"), node_config.paths.certs.join("server-key.pem"), node_config.paths.certs.join("ca.pem"), filter, node_config.node.worker().ports.reg)).await {
+        Some(Ok(_)) | None => {},
+        Some(Err(err))     => {
             error!("{}", err);
+            otel::shutdown().await;
             std::process::exit(1);
         },
     }
+
+    // Export the spans of the last requests before we go
+    otel::shutdown().await;
 }
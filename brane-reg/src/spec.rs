@@ -13,6 +13,9 @@
 // 
 
 use std::path::PathBuf;
+use std::sync::Arc;
+
+use brane_shr::audit::AuditLog;
 
 
 /***** LIBRARY *****/
@@ -21,4 +24,6 @@ use std::path::PathBuf;
 pub struct Context {
     /// The path to the node config file.
     pub node_config_path : PathBuf,
+    /// The audit log to record data and result downloads in, if any.
+    pub audit            : Option<Arc<AuditLog>>,
 }
@@ -21,11 +21,41 @@ use std::path::{Path, PathBuf};
 use log::{debug, warn};
 use tokio::fs as tfs;
 
-use specifications::data::AssetInfo;
+use brane_shr::disk::dir_size;
+use specifications::data::{split_data_id, AccessKind, AssetInfo};
+use specifications::version::Version;
 
 pub use crate::errors::StoreError as Error;
 
 
+/***** HELPER FUNCTIONS *****/
+/// Attempts to load the AssetInfo in the given dataset directory.
+/// 
+/// # Arguments
+/// - `dir`: The directory that (maybe) contains a `data.yml` file.
+/// 
+/// # Returns
+/// The AssetInfo in the directory, or `None` if it does not have any.
+/// 
+/// # Errors
+/// This function errors if the directory had a `data.yml` file but we failed to read it.
+fn load_asset_info(dir: &Path) -> Result<Option<AssetInfo>, Error> {
+    // Try to find the data.yml
+    let info_path: PathBuf = dir.join("data.yml");
+    if !info_path.exists() { return Ok(None); }
+    if !info_path.is_file() { warn!("Directory '{}' is in the data folder, but the nested `data.yml` file is not a file", dir.display()); return Ok(None); }
+
+    // Load it
+    match AssetInfo::from_path(&info_path) {
+        Ok(info) => Ok(Some(info)),
+        Err(err) => Err(Error::AssetInfoReadError{ path: info_path, err }),
+    }
+}
+
+
+
+
+
 /***** LIBRARY *****/
 /// Defines a JSON file that the administrator writes that contains the hardcoded data files.
 /// 
@@ -34,8 +64,10 @@ pub use crate::errors::StoreError as Error;
 /// For network serialization/deserialization, it is preferred to send the entire map in one go.
 #[derive(Clone, Debug)]
 pub struct Store {
-    /// A list of locally defined AssetInfos.
+    /// A list of locally defined AssetInfos, mapped by their identifier (i.e., `NAME@VERSION`).
     pub datasets : HashMap<String, AssetInfo>,
+    /// The directories in which the datasets live, mapped by their identifier. Datasets that are missing here live in a directory with their name.
+    pub dirs     : HashMap<String, PathBuf>,
     /// A list of locally defined AssetInfos for the intermediate results.
     pub results  : HashMap<String, PathBuf>,
 }
@@ -86,12 +118,13 @@ impl Store {
         // Put that in a map
         let mut res: HashMap<String, AssetInfo> = HashMap::with_capacity(infos.len());
         for i in infos {
-            res.insert(i.name.clone(), i);
+            res.insert(i.id(), i);
         }
 
         // Done, return us
         Ok(Self {
             datasets : res,
+            dirs     : HashMap::new(),
             results  : HashMap::new(),
         })
     }
@@ -112,7 +145,7 @@ impl Store {
         let results_path : &Path = results_path.as_ref();
 
         // Attempt to read the directory of datasets
-        let datasets: HashMap<String, AssetInfo> = {
+        let (datasets, dirs): (HashMap<String, AssetInfo>, HashMap<String, PathBuf>) = {
             // Fetch the entries in this directory
             let mut entries: tfs::ReadDir = match tfs::read_dir(&data_path).await {
                 Ok(entries) => entries,
@@ -121,6 +154,7 @@ impl Store {
 
             // Iterate through all entries
             let mut datasets : HashMap<String, AssetInfo> = HashMap::new();
+            let mut dirs     : HashMap<String, PathBuf>   = HashMap::new();
             let mut i        : usize                      = 0;
             #[allow(irrefutable_let_patterns)]
             while let entry = entries.next_entry().await {
@@ -134,20 +168,50 @@ impl Store {
                 // Match on directory or not
                 let entry_path: PathBuf = entry.path();
                 if entry_path.is_dir() {
-                    // Try to find the data.yml
-                    let info_path: PathBuf = entry_path.join("data.yml");
-                    if !info_path.exists() { warn!("Directory '{}' is in the data folder, but does not have a `data.yml` file", entry_path.display()); continue; }
-                    if !info_path.is_file() { warn!("Directory '{}' is in the data folder, but the nested `data.yml` file is not a file", entry_path.display()); continue; }
+                    // Collect the directories with a data.yml; either this one, or one per version of the dataset nested in it
+                    let mut found: Vec<(PathBuf, AssetInfo)> = vec![];
+                    if let Some(info) = load_asset_info(&entry_path)? {
+                        found.push((entry_path.clone(), info));
+                    } else {
+                        let mut versions: tfs::ReadDir = match tfs::read_dir(&entry_path).await {
+                            Ok(versions) => versions,
+                            Err(err)     => { return Err(Error::DirReadError{ path: entry_path, err }); },
+                        };
+                        let mut j: usize = 0;
+                        #[allow(irrefutable_let_patterns)]
+                        while let version = versions.next_entry().await {
+                            let version: tfs::DirEntry = match version {
+                                Ok(Some(version)) => version,
+                                Ok(None)          => { break; },
+                                Err(err)          => { return Err(Error::DirReadEntryError{ path: entry_path, i: j, err }); },
+                            };
+                            let version_path: PathBuf = version.path();
+                            if version_path.is_dir() {
+                                if let Some(info) = load_asset_info(&version_path)? { found.push((version_path, info)); }
+                            }
+                            j += 1;
+                        }
+                    }
+                    if found.is_empty() { warn!("Directory '{}' is in the data folder, but does not have a `data.yml` file", entry_path.display()); }
 
-                    // Load it
-                    let info: AssetInfo = match AssetInfo::from_path(&info_path) {
-                        Ok(info) => info,
-                        Err(err) => { return Err(Error::AssetInfoReadError{ path: info_path, err }); },
-                    };
+                    // Insert them
+                    for (dir, mut info) in found {
+                        let id: String = info.id();
 
-                    // Insert it
-                    debug!("Noting down local dataset '{}'", info.name);
-                    datasets.insert(info.name.clone(), info);
+                        // Compute the size of datasets that live here, so it can be advertised to the instance
+                        if let AccessKind::File{ path } = &info.access {
+                            match dir_size(dir.join(path)).await {
+                                Ok(size) => { info.size = Some(size); },
+                                Err(err) => { warn!("Failed to compute the size of dataset '{}': {}", id, err); info.size = None; },
+                            }
+                        } else {
+                            info.size = None;
+                        }
+
+                        debug!("Noting down local dataset '{}'", id);
+                        dirs.insert(id.clone(), dir);
+                        datasets.insert(id, info);
+                    }
                 }
 
                 // Continue
@@ -155,7 +219,7 @@ impl Store {
             }
 
             // Done with the datasets
-            datasets
+            (datasets, dirs)
         };
 
         // Now do the same for the results
@@ -202,6 +266,7 @@ impl Store {
         // Done, return ourselves
         Ok(Self {
             datasets,
+            dirs,
             results,
         })
     }
@@ -211,12 +276,48 @@ impl Store {
     /// Get the AssetInfo for the given dataset.
     /// 
     /// # Arguments
-    /// - `name`: The name of the dataset to get the AssetInfo for.
+    /// - `id`: The identifier of the dataset to get the AssetInfo for. This is either a plain name, in which case the latest version is returned, or a specific version (`NAME@VERSION`).
     /// 
     /// # Returns
     /// The dataset if it exists, or else `None`.
+    pub fn get_data(&self, id: impl AsRef<str>) -> Option<&AssetInfo> {
+        let (name, version): (&str, Version) = split_data_id(id.as_ref()).ok()?;
+        if version.is_latest() {
+            self.datasets.values().filter(|info| info.name == name).max_by_key(|info| info.resolved_version())
+        } else {
+            self.datasets.values().find(|info| info.name == name && info.resolved_version() == version)
+        }
+    }
+
+    /// Get all versions of the given dataset.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the dataset to get the versions of.
+    /// 
+    /// # Returns
+    /// The AssetInfos of every version of the dataset, sorted from oldest to newest. Empty if we do not know the dataset at all.
+    pub fn get_versions(&self, name: impl AsRef<str>) -> Vec<&AssetInfo> {
+        let name: &str = name.as_ref();
+        let mut versions: Vec<&AssetInfo> = self.datasets.values().filter(|info| info.name == name).collect();
+        versions.sort_by_key(|info| info.resolved_version());
+        versions
+    }
+
+    /// Get the directory in which the given dataset lives.
+    /// 
+    /// # Arguments
+    /// - `data_path`: The path of the directory where all datasets are stored.
+    /// - `info`: The AssetInfo of the dataset to get the directory of.
+    /// 
+    /// # Returns
+    /// The path to the directory of this (version of the) dataset.
     #[inline]
-    pub fn get_data(&self, name: impl AsRef<str>) -> Option<&AssetInfo> { self.datasets.get(name.as_ref()) }
+    pub fn get_data_dir(&self, data_path: impl AsRef<Path>, info: &AssetInfo) -> PathBuf {
+        match self.dirs.get(&info.id()) {
+            Some(dir) => dir.clone(),
+            None      => data_path.as_ref().join(&info.name),
+        }
+    }
 
     /// Get the path for the given intermediate result.
     /// 
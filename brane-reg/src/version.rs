@@ -12,12 +12,18 @@
 //!   Implements the function(s) that handle the `/version` path(s).
 // 
 
-use log::debug;
+use std::sync::Arc;
+
+use log::{debug, error};
 use warp::{Rejection, Reply};
 use warp::http::HeaderValue;
 use warp::hyper::Body;
 use warp::reply::Response;
 
+use brane_cfg::node::NodeConfig;
+
+use crate::spec::Context;
+
 
 /***** LIBRARY *****/
 /// Handles a GET on the main `/version` path, returning the version number of this service.
@@ -48,3 +54,31 @@ pub async fn get() -> Result<impl Reply, Rejection> {
     // Done
     Ok(response)
 }
+
+
+
+/// Handles a GET on the `/version/features` path, returning the feature flags of this node.
+/// 
+/// # Arguments
+/// - `context`: The context that tells us where to find the node config file.
+/// 
+/// # Returns
+/// The response that can be send back to the client. Contains the FeatureFlags of the node as JSON, so that clients may adapt to the subsystems it has enabled.
+/// 
+/// # Errors
+/// This function errors if we failed to load the node config file.
+pub async fn features(context: Arc<Context>) -> Result<impl Reply, Rejection> {
+    debug!("Handling GET on `/version/features` (i.e., get feature flags)...");
+
+    // Load the node config file
+    let node_config: NodeConfig = match NodeConfig::from_path(&context.node_config_path) {
+        Ok(config) => config,
+        Err(err)   => {
+            error!("Failed to load NodeConfig file: {}", err);
+            return Err(warp::reject::reject());
+        },
+    };
+
+    // Serialize the flags
+    Ok(warp::reply::json(&node_config.features))
+}
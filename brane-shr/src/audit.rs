@@ -0,0 +1,446 @@
+//  AUDIT.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 10:43:36
+//  Last edited:
+//    16 Oct 2026, 11:47:05
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements the append-only audit log of worker nodes, which records
+//!   every task execution, data download and result commit as one JSON
+//!   object per line. Every service writes its own log file, which is
+//!   rotated once it grows too large.
+// 
+
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::fs::{self, DirEntry, File, OpenOptions, ReadDir};
+use std::io::{BufRead as _, BufReader, Write as _};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use log::{debug, error, info};
+use serde::{Deserialize, Serialize};
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+    use super::*;
+
+
+    /// Creates a record for the given subject.
+    fn record(subject: &str) -> AuditRecord {
+        AuditRecord::new("brane-test", AuditKind::DataDownload, Some("amy".into()), subject, Decision::Allowed)
+    }
+
+
+    #[test]
+    fn test_append_and_read() {
+        let tempdir: TempDir = TempDir::new().unwrap();
+        let log: AuditLog = AuditLog::new(tempdir.path(), "brane-test");
+        log.append(&record("first")).unwrap();
+        log.append(&record("second")).unwrap();
+
+        let records: Vec<AuditRecord> = read_dir(tempdir.path()).unwrap();
+        assert_eq!(records.iter().map(|r| r.subject.as_str()).collect::<Vec<&str>>(), vec![ "first", "second" ]);
+        assert_eq!(records[0].identity.as_deref(), Some("amy"));
+    }
+
+    #[test]
+    fn test_rotation() {
+        let tempdir: TempDir = TempDir::new().unwrap();
+        let log: AuditLog = AuditLog::with_limits(tempdir.path(), "brane-test", 1, 2);
+        for subject in &[ "a", "b", "c", "d" ] {
+            log.append(&record(subject)).unwrap();
+        }
+
+        // Every record ended up in its own file, of which only the newest three (the current one and two rotated ones) survive
+        assert!(tempdir.path().join("brane-test.jsonl").exists());
+        assert!(tempdir.path().join("brane-test.jsonl.1").exists());
+        assert!(tempdir.path().join("brane-test.jsonl.2").exists());
+        assert!(!tempdir.path().join("brane-test.jsonl.3").exists());
+        let records: Vec<AuditRecord> = read_dir(tempdir.path()).unwrap();
+        assert_eq!(records.iter().map(|r| r.subject.as_str()).collect::<Vec<&str>>(), vec![ "b", "c", "d" ]);
+    }
+}
+
+
+
+
+
+/***** CONSTANTS *****/
+/// The size (in bytes) after which an audit log file is rotated (64 MiB).
+pub const DEFAULT_MAX_SIZE: u64 = 64 * 1024 * 1024;
+/// The number of rotated audit log files that are kept per service.
+pub const DEFAULT_KEEP: usize = 10;
+/// The extension of audit log files.
+pub const EXTENSION: &str = "jsonl";
+
+
+
+
+
+/***** ERRORS *****/
+/// Defines the errors that may occur when writing or reading the audit log.
+#[derive(Debug)]
+pub enum Error {
+    /// Failed to serialize a record.
+    SerializeError{ err: serde_json::Error },
+    /// Failed to rotate a log file.
+    RotateError{ source: PathBuf, target: PathBuf, err: std::io::Error },
+    /// Failed to open a log file.
+    FileOpenError{ path: PathBuf, err: std::io::Error },
+    /// Failed to write to a log file.
+    FileWriteError{ path: PathBuf, err: std::io::Error },
+
+    /// Failed to read the log directory.
+    DirReadError{ path: PathBuf, err: std::io::Error },
+    /// Failed to read an entry in the log directory.
+    DirEntryReadError{ path: PathBuf, entry: usize, err: std::io::Error },
+    /// Failed to read a line in a log file.
+    FileReadError{ path: PathBuf, err: std::io::Error },
+    /// Failed to parse a line in a log file.
+    RecordParseError{ path: PathBuf, line: usize, err: serde_json::Error },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use Error::*;
+        match self {
+            SerializeError{ err }               => write!(f, "Failed to serialize audit record: {}", err),
+            RotateError{ source, target, err }  => write!(f, "Failed to rotate audit log '{}' to '{}': {}", source.display(), target.display(), err),
+            FileOpenError{ path, err }          => write!(f, "Failed to open audit log '{}': {}", path.display(), err),
+            FileWriteError{ path, err }         => write!(f, "Failed to write to audit log '{}': {}", path.display(), err),
+
+            DirReadError{ path, err }             => write!(f, "Failed to read audit log directory '{}': {}", path.display(), err),
+            DirEntryReadError{ path, entry, err } => write!(f, "Failed to read entry {} in audit log directory '{}': {}", entry, path.display(), err),
+            FileReadError{ path, err }            => write!(f, "Failed to read audit log '{}': {}", path.display(), err),
+            RecordParseError{ path, line, err }   => write!(f, "Failed to parse line {} in audit log '{}' as an audit record: {}", line, path.display(), err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Analyses the name of a file in the audit log directory.
+/// 
+/// # Arguments
+/// - `name`: The name of the file.
+/// 
+/// # Returns
+/// The name of the current log file it belongs to and its rotation index (where `0` is the current file itself), or `None` if it is not an audit log file.
+fn log_index(name: &str) -> Option<(&str, usize)> {
+    let suffix: String = format!(".{}", EXTENSION);
+    if name.ends_with(&suffix) { return Some((name, 0)); }
+    match name.rsplit_once('.') {
+        Some((base, n)) if base.ends_with(&suffix) => n.parse().ok().map(|n| (base, n)),
+        _                                          => None,
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines the kinds of events that are recorded in the audit log.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditKind {
+    /// A task was submitted to be executed on this node.
+    TaskExecution,
+    /// A dataset was downloaded from this node.
+    DataDownload,
+    /// An intermediate result was downloaded from this node.
+    ResultDownload,
+    /// An intermediate result was committed as a dataset on this node.
+    ResultCommit,
+    /// A client connected to one of the services of this node (only recorded when it is refused by the service's firewall).
+    Connection,
+}
+
+impl Display for AuditKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use AuditKind::*;
+        match self {
+            TaskExecution  => write!(f, "task execution"),
+            DataDownload   => write!(f, "data download"),
+            ResultDownload => write!(f, "result download"),
+            ResultCommit   => write!(f, "result commit"),
+            Connection     => write!(f, "connection"),
+        }
+    }
+}
+
+
+
+/// Defines the policy decision on an audited event.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Decision {
+    /// The checker allowed the event.
+    Allowed,
+    /// The checker (or the lack of an authenticated identity) denied the event.
+    Denied,
+    /// We failed to consult the checker, so the event was refused.
+    Failed,
+    /// The event is not subject to a policy.
+    Unchecked,
+}
+
+impl Display for Decision {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use Decision::*;
+        match self {
+            Allowed   => write!(f, "allowed"),
+            Denied    => write!(f, "denied"),
+            Failed    => write!(f, "failed"),
+            Unchecked => write!(f, "unchecked"),
+        }
+    }
+}
+
+
+
+/// Defines a single line in the audit log.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AuditRecord {
+    /// The moment the event happened.
+    pub time     : DateTime<Utc>,
+    /// The service that recorded the event (e.g., `brane-job`).
+    pub service  : String,
+    /// The kind of event.
+    pub kind     : AuditKind,
+    /// The identity of whoever requested the event (e.g., the `CN` of their client certificate), if known.
+    pub identity : Option<String>,
+    /// The task, dataset or result the event is about.
+    pub subject  : String,
+    /// The policy decision on the event.
+    pub decision : Decision,
+    /// Any additional, human-readable details (e.g., the package of a task).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details  : Option<String>,
+}
+
+impl AuditRecord {
+    /// Constructor for the AuditRecord that timestamps it with the current time.
+    /// 
+    /// # Arguments
+    /// - `service`: The service that records the event.
+    /// - `kind`: The kind of event.
+    /// - `identity`: The identity of whoever requested the event, if known.
+    /// - `subject`: The task, dataset or result the event is about.
+    /// - `decision`: The policy decision on the event.
+    /// 
+    /// # Returns
+    /// A new AuditRecord without any details.
+    pub fn new(service: impl Into<String>, kind: AuditKind, identity: Option<String>, subject: impl Into<String>, decision: Decision) -> Self {
+        Self {
+            time    : Utc::now(),
+            service : service.into(),
+            kind,
+            identity,
+            subject : subject.into(),
+            decision,
+            details : None,
+        }
+    }
+
+    /// Adds details to the record.
+    /// 
+    /// # Arguments
+    /// - `details`: The human-readable details to add.
+    /// 
+    /// # Returns
+    /// The same record, but now with details.
+    #[inline]
+    pub fn with_details(mut self, details: impl Into<String>) -> Self { self.details = Some(details.into()); self }
+}
+
+impl Display for AuditRecord {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        write!(f, "{} [{}] {} of '{}' by {}: {}{}", self.time.to_rfc3339(), self.service, self.kind, self.subject, self.identity.as_deref().unwrap_or("<unknown>"), self.decision, if let Some(details) = &self.details { format!(" ({})", details) } else { String::new() })
+    }
+}
+
+
+
+/// The audit log of a single service, which appends records to `<dir>/<service>.jsonl` and rotates it to `<service>.jsonl.1`, `<service>.jsonl.2`, ... once it grows too large.
+#[derive(Debug)]
+pub struct AuditLog {
+    /// The path of the current log file.
+    path     : PathBuf,
+    /// The size (in bytes) after which the log file is rotated.
+    max_size : u64,
+    /// The number of rotated log files to keep.
+    keep     : usize,
+    /// Serializes writes, so that records are never interleaved and rotation does not race.
+    lock     : Mutex<()>,
+}
+
+impl AuditLog {
+    /// Constructor for the AuditLog with the default rotation limits.
+    /// 
+    /// # Arguments
+    /// - `dir`: The directory that contains the audit logs.
+    /// - `service`: The name of the service that writes this log.
+    /// 
+    /// # Returns
+    /// A new AuditLog that creates its file on the first write.
+    #[inline]
+    pub fn new(dir: impl AsRef<Path>, service: impl AsRef<str>) -> Self { Self::with_limits(dir, service, DEFAULT_MAX_SIZE, DEFAULT_KEEP) }
+
+    /// Constructor for the AuditLog with custom rotation limits.
+    /// 
+    /// # Arguments
+    /// - `dir`: The directory that contains the audit logs.
+    /// - `service`: The name of the service that writes this log.
+    /// - `max_size`: The size (in bytes) after which the log file is rotated.
+    /// - `keep`: The number of rotated log files to keep.
+    /// 
+    /// # Returns
+    /// A new AuditLog that creates its file on the first write.
+    pub fn with_limits(dir: impl AsRef<Path>, service: impl AsRef<str>, max_size: u64, keep: usize) -> Self {
+        Self {
+            path : dir.as_ref().join(format!("{}.{}", service.as_ref(), EXTENSION)),
+            max_size,
+            keep,
+            lock : Mutex::new(()),
+        }
+    }
+
+
+
+    /// Appends a record to the log, rotating it first if it has grown too large.
+    /// 
+    /// The record is also written to the service's own log.
+    /// 
+    /// # Arguments
+    /// - `record`: The AuditRecord to append.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to serialize the record, rotate the log or write to it.
+    pub fn append(&self, record: &AuditRecord) -> Result<(), Error> {
+        info!("AUDIT: {}", record);
+        let line: String = match serde_json::to_string(record) {
+            Ok(line) => line,
+            Err(err) => { return Err(Error::SerializeError{ err }); },
+        };
+
+        // Rotate if necessary, making sure nobody writes in the meantime
+        let _guard = self.lock.lock().unwrap();
+        if fs::metadata(&self.path).map(|m| m.len() >= self.max_size).unwrap_or(false) { self.rotate()?; }
+
+        // Append the record
+        let mut handle: File = match OpenOptions::new().create(true).append(true).open(&self.path) {
+            Ok(handle) => handle,
+            Err(err)   => { return Err(Error::FileOpenError{ path: self.path.clone(), err }); },
+        };
+        if let Err(err) = writeln!(handle, "{}", line) { return Err(Error::FileWriteError{ path: self.path.clone(), err }); }
+        Ok(())
+    }
+
+    /// Rotates the log files, dropping the oldest one if there are too many.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to rename or remove any of the files.
+    fn rotate(&self) -> Result<(), Error> {
+        debug!("Rotating audit log '{}'...", self.path.display());
+        let rotated = |i: usize| -> PathBuf { PathBuf::from(format!("{}.{}", self.path.display(), i)) };
+
+        // If we keep nothing, simply start over
+        if self.keep == 0 {
+            if let Err(err) = fs::remove_file(&self.path) { return Err(Error::RotateError{ source: self.path.clone(), target: rotated(1), err }); }
+            return Ok(());
+        }
+
+        // Otherwise, shift every file one place up (which overwrites the oldest one)
+        for i in (0..self.keep).rev() {
+            let source: PathBuf = if i == 0 { self.path.clone() } else { rotated(i) };
+            if !source.exists() { continue; }
+            let target: PathBuf = rotated(i + 1);
+            if let Err(err) = fs::rename(&source, &target) { return Err(Error::RotateError{ source, target, err }); }
+        }
+        Ok(())
+    }
+}
+
+
+
+/// Writes a record to the given audit log, if there is one.
+/// 
+/// Failing to write the record does not stop the audited event; instead, the failure is logged.
+/// 
+/// # Arguments
+/// - `audit`: The audit log to write to. If omitted, the record is only written to the service's own log.
+/// - `record`: The AuditRecord to write.
+pub fn write(audit: Option<&AuditLog>, record: AuditRecord) {
+    match audit {
+        Some(audit) => if let Err(err) = audit.append(&record) { error!("Failed to write audit record: {}", err); },
+        None        => { info!("AUDIT: {}", record); },
+    }
+}
+
+/// Reads all the audit records in the given directory, of all services.
+/// 
+/// # Arguments
+/// - `dir`: The directory that contains the audit logs.
+/// 
+/// # Returns
+/// All records in the current and rotated log files, sorted by time (oldest first).
+/// 
+/// # Errors
+/// This function errors if we failed to read the directory or any of the log files, or if they contained something that is not an audit record.
+pub fn read_dir(dir: impl AsRef<Path>) -> Result<Vec<AuditRecord>, Error> {
+    let dir: &Path = dir.as_ref();
+    let entries: ReadDir = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err)    => { return Err(Error::DirReadError{ path: dir.into(), err }); },
+    };
+
+    // Find the log files (current or rotated) in the directory, oldest first per service
+    let mut files: Vec<(String, usize, PathBuf)> = vec![];
+    for (i, entry) in entries.enumerate() {
+        let entry: DirEntry = match entry {
+            Ok(entry) => entry,
+            Err(err)  => { return Err(Error::DirEntryReadError{ path: dir.into(), entry: i, err }); },
+        };
+        let name: String = entry.file_name().to_string_lossy().into();
+        if let Some((base, index)) = log_index(&name) { files.push((base.into(), index, entry.path())); }
+    }
+    files.sort_by(|(base1, index1, _), (base2, index2, _)| base1.cmp(base2).then(index2.cmp(index1)));
+
+    // Read them all
+    let mut records: Vec<AuditRecord> = vec![];
+    for (_, _, path) in files {
+        let handle: File = match File::open(&path) {
+            Ok(handle) => handle,
+            Err(err)   => { return Err(Error::FileOpenError{ path, err }); },
+        };
+        for (l, line) in BufReader::new(handle).lines().enumerate() {
+            let line: String = match line {
+                Ok(line) => line,
+                Err(err) => { return Err(Error::FileReadError{ path, err }); },
+            };
+            if line.trim().is_empty() { continue; }
+            match serde_json::from_str(&line) {
+                Ok(record) => { records.push(record); },
+                Err(err)   => { return Err(Error::RecordParseError{ path, line: l + 1, err }); },
+            }
+        }
+    }
+
+    // Sort them by time; the sort is stable, so records with equal timestamps keep the order of their file
+    records.sort_by_key(|r| r.time);
+    Ok(records)
+}
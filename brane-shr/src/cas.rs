@@ -0,0 +1,806 @@
+//  CAS.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 10:26:40
+//  Last edited:
+//    16 Oct 2026, 15:02:11
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements the content-addressed store in which worker nodes keep
+//!   package images, datasets and intermediate results. Objects are
+//!   stored by their SHA-256 digest and kept alive by named tags, such
+//!   that objects without any tags can be garbage collected.
+// 
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::fs::{File, Metadata, OpenOptions};
+use std::os::unix::io::AsRawFd as _;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use log::debug;
+use sha2::{Digest as _, Sha256};
+use tokio::fs as tfs;
+use tokio::io::AsyncReadExt as _;
+
+use crate::fs::copy_dir_recursively_async;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+    use super::*;
+
+
+    /// Creates a new store in a temporary directory.
+    async fn new_store() -> (TempDir, ContentStore) {
+        let tempdir: TempDir = match TempDir::new() {
+            Ok(tempdir) => tempdir,
+            Err(err)    => { panic!("Failed to create a temporary directory: {}", err); },
+        };
+        let store: ContentStore = match ContentStore::new(tempdir.path().join("store")).await {
+            Ok(store) => store,
+            Err(err)  => { panic!("Failed to create store: {}", err); },
+        };
+        (tempdir, store)
+    }
+
+    /// Writes a small directory tree with the given contents to a fresh temporary path in the store.
+    async fn write_tree(store: &ContentStore, contents: &str) -> PathBuf {
+        let path: PathBuf = store.temp_path();
+        tfs::create_dir_all(path.join("nested")).await.unwrap();
+        tfs::write(path.join("file.txt"), contents).await.unwrap();
+        tfs::write(path.join("nested").join("other.txt"), "Hello there!").await.unwrap();
+        path
+    }
+
+
+
+    #[tokio::test]
+    async fn test_identical_trees_are_deduplicated() {
+        let (_tempdir, store) = new_store().await;
+
+        // Add the same tree under two names
+        let (digest1, path1): (String, PathBuf) = store.add(write_tree(&store, "Hello world!").await, TagKind::Data, "a").await.unwrap();
+        let (digest2, path2): (String, PathBuf) = store.add(write_tree(&store, "Hello world!").await, TagKind::IntermediateResult, "b").await.unwrap();
+        assert_eq!(digest1, digest2);
+        assert_eq!(path1, path2);
+        assert_eq!(tfs::read_to_string(path1.join("file.txt")).await.unwrap(), "Hello world!");
+        assert_eq!(store.refcounts().await.unwrap().get(&digest1), Some(&2));
+
+        // A different tree gets a different digest
+        let (digest3, _): (String, PathBuf) = store.add(write_tree(&store, "Goodbye world!").await, TagKind::Data, "c").await.unwrap();
+        assert_ne!(digest1, digest3);
+    }
+
+    #[tokio::test]
+    async fn test_gc_collects_unreferenced_objects() {
+        let (_tempdir, store) = new_store().await;
+        let (digest, path): (String, PathBuf) = store.add(write_tree(&store, "Hello world!").await, TagKind::Data, "a").await.unwrap();
+        store.tag(TagKind::IntermediateResult, "b", &digest).await.unwrap();
+
+        // As long as any tag references it, it stays
+        assert!(store.untag(TagKind::Data, "a").await.unwrap());
+        assert!(store.gc(false).await.unwrap().is_empty());
+        assert!(path.is_dir());
+
+        // Dropping the last reference makes it garbage, but a dry-run leaves it be
+        assert!(store.untag(TagKind::IntermediateResult, "b").await.unwrap());
+        assert_eq!(store.gc(true).await.unwrap().len(), 1);
+        assert!(path.is_dir());
+        assert_eq!(store.gc(false).await.unwrap().into_iter().map(|(digest, _)| digest).collect::<Vec<String>>(), vec![ digest ]);
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_retagging_releases_old_object() {
+        let (_tempdir, store) = new_store().await;
+        let file: PathBuf = store.temp_path();
+        tfs::write(&file, "v1").await.unwrap();
+        let (old, _): (String, PathBuf) = store.add(&file, TagKind::Package, "hello_world-1.0.0").await.unwrap();
+        tfs::write(&file, "v2").await.unwrap();
+        let (new, _): (String, PathBuf) = store.add(&file, TagKind::Package, "hello_world-1.0.0").await.unwrap();
+
+        assert_eq!(store.resolve(TagKind::Package, "hello_world-1.0.0").await.unwrap().map(|(digest, _)| digest), Some(new));
+        assert_eq!(store.gc(false).await.unwrap().into_iter().map(|(digest, _)| digest).collect::<Vec<String>>(), vec![ old ]);
+    }
+
+    #[tokio::test]
+    async fn test_stale_tags() {
+        let (_tempdir, store) = new_store().await;
+        store.add(write_tree(&store, "Hello world!").await, TagKind::IntermediateResult, "a").await.unwrap();
+
+        // A fresh tag is only stale if nothing may be kept at all
+        assert!(store.stale_tags(TagKind::IntermediateResult, Duration::from_secs(3600)).await.unwrap().is_empty());
+        assert_eq!(store.stale_tags(TagKind::IntermediateResult, Duration::ZERO).await.unwrap(), vec![ "a".to_string() ]);
+        assert!(store.stale_tags(TagKind::Data, Duration::ZERO).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_gc_waits_for_add() {
+        let (_tempdir, store) = new_store().await;
+
+        // While something holds a reference in the making, a collection may not start
+        let guard: File = store.lock(false).await.unwrap();
+        let mut gc = tokio::spawn({ let store: ContentStore = store.clone(); async move { store.gc(false).await } });
+        assert!(tokio::time::timeout(Duration::from_millis(100), &mut gc).await.is_err());
+
+        // Once it's done, the collection continues
+        drop(guard);
+        assert!(gc.await.unwrap().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_illegal_tag_names() {
+        let (_tempdir, store) = new_store().await;
+        assert!(matches!(store.resolve(TagKind::Data, "../escape").await, Err(Error::IllegalTagName{ .. })));
+        assert!(matches!(store.resolve(TagKind::Data, ".hidden").await, Err(Error::IllegalTagName{ .. })));
+        assert!(matches!(store.resolve(TagKind::Data, "").await, Err(Error::IllegalTagName{ .. })));
+    }
+}
+
+
+
+
+
+/***** ERRORS *****/
+/// Defines the errors that may occur when working with the content-addressed store.
+#[derive(Debug)]
+pub enum Error {
+    /// The given tag name cannot be used.
+    IllegalTagName{ name: String },
+    /// The given tag did not contain a digest.
+    IllegalTag{ path: PathBuf, raw: String },
+    /// The given object is not in the store.
+    UnknownObject{ digest: String },
+
+    /// Failed to create a directory in the store.
+    DirCreateError{ path: PathBuf, err: std::io::Error },
+    /// Failed to read a directory.
+    DirReadError{ path: PathBuf, err: std::io::Error },
+    /// Failed to read an entry in a directory.
+    DirEntryReadError{ path: PathBuf, entry: usize, err: std::io::Error },
+    /// Failed to get the metadata of a file or directory.
+    MetadataError{ path: PathBuf, err: std::io::Error },
+    /// Failed to open a file to hash it.
+    FileOpenError{ path: PathBuf, err: std::io::Error },
+    /// Failed to read a file to hash it.
+    FileReadError{ path: PathBuf, err: std::io::Error },
+
+    /// Failed to read a tag.
+    TagReadError{ path: PathBuf, err: std::io::Error },
+    /// Failed to write a tag.
+    TagWriteError{ path: PathBuf, err: std::io::Error },
+    /// Failed to remove a tag.
+    TagRemoveError{ path: PathBuf, err: std::io::Error },
+
+    /// Failed to copy a file into the store.
+    FileCopyError{ source: PathBuf, target: PathBuf, err: std::io::Error },
+    /// Failed to copy a directory into the store.
+    DirCopyError{ source: PathBuf, target: PathBuf, err: crate::fs::Error },
+    /// Failed to move a file or directory into place.
+    MoveError{ source: PathBuf, target: PathBuf, err: std::io::Error },
+    /// Failed to remove a file or directory.
+    RemoveError{ path: PathBuf, err: std::io::Error },
+
+    /// Failed to open the lock file of the store.
+    LockOpenError{ path: PathBuf, err: std::io::Error },
+    /// Failed to lock the lock file of the store.
+    LockError{ path: PathBuf, err: std::io::Error },
+    /// The task that waited for the lock of the store panicked.
+    LockTaskError{ path: PathBuf, err: tokio::task::JoinError },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use Error::*;
+        match self {
+            IllegalTagName{ name }  => write!(f, "Illegal tag name '{}' (it may not be empty, start with a dot or contain slashes)", name),
+            IllegalTag{ path, raw } => write!(f, "Tag '{}' does not contain a SHA-256 digest (got '{}')", path.display(), raw),
+            UnknownObject{ digest } => write!(f, "Object '{}' is not in the store", digest),
+
+            DirCreateError{ path, err }           => write!(f, "Failed to create directory '{}': {}", path.display(), err),
+            DirReadError{ path, err }             => write!(f, "Failed to read directory '{}': {}", path.display(), err),
+            DirEntryReadError{ path, entry, err } => write!(f, "Failed to read entry {} in directory '{}': {}", entry, path.display(), err),
+            MetadataError{ path, err }            => write!(f, "Failed to get metadata of '{}': {}", path.display(), err),
+            FileOpenError{ path, err }            => write!(f, "Failed to open file '{}': {}", path.display(), err),
+            FileReadError{ path, err }            => write!(f, "Failed to read file '{}': {}", path.display(), err),
+
+            TagReadError{ path, err }   => write!(f, "Failed to read tag '{}': {}", path.display(), err),
+            TagWriteError{ path, err }  => write!(f, "Failed to write tag '{}': {}", path.display(), err),
+            TagRemoveError{ path, err } => write!(f, "Failed to remove tag '{}': {}", path.display(), err),
+
+            FileCopyError{ source, target, err } => write!(f, "Failed to copy file '{}' to '{}': {}", source.display(), target.display(), err),
+            DirCopyError{ source, target, err }  => write!(f, "Failed to copy directory '{}' to '{}': {}", source.display(), target.display(), err),
+            MoveError{ source, target, err }     => write!(f, "Failed to move '{}' to '{}': {}", source.display(), target.display(), err),
+            RemoveError{ path, err }             => write!(f, "Failed to remove '{}': {}", path.display(), err),
+
+            LockOpenError{ path, err } => write!(f, "Failed to open lock file '{}': {}", path.display(), err),
+            LockError{ path, err }     => write!(f, "Failed to lock '{}': {}", path.display(), err),
+            LockTaskError{ path, err } => write!(f, "Failed to wait for lock '{}': {}", path.display(), err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Returns the metadata of the given path, following symlinks.
+/// 
+/// # Arguments
+/// - `path`: The path to get the metadata of.
+/// 
+/// # Returns
+/// The path's metadata.
+/// 
+/// # Errors
+/// This function errors if we failed to get the metadata (e.g., because the path does not exist).
+async fn metadata(path: &Path) -> Result<Metadata, Error> {
+    match tfs::metadata(path).await {
+        Ok(metadata) => Ok(metadata),
+        Err(err)     => Err(Error::MetadataError{ path: path.into(), err }),
+    }
+}
+
+/// Lists everything in the given directory, recursively.
+/// 
+/// # Arguments
+/// - `dir`: The directory to walk.
+/// 
+/// # Returns
+/// A list of the nested paths (relative to `dir`, with `/` as separator) together with their absolute path and metadata, sorted by the relative path.
+/// 
+/// # Errors
+/// This function errors if we failed to read any of the (nested) directories.
+async fn walk(dir: &Path) -> Result<Vec<(String, PathBuf, Metadata)>, Error> {
+    let mut res: Vec<(String, PathBuf, Metadata)> = vec![];
+    let mut todo: Vec<(String, PathBuf)> = vec![ (String::new(), dir.into()) ];
+    while let Some((prefix, path)) = todo.pop() {
+        let mut entries: tfs::ReadDir = match tfs::read_dir(&path).await {
+            Ok(entries) => entries,
+            Err(err)    => { return Err(Error::DirReadError{ path, err }); },
+        };
+        let mut i: usize = 0;
+        loop {
+            let entry: tfs::DirEntry = match entries.next_entry().await {
+                Ok(Some(entry)) => entry,
+                Ok(None)        => { break; },
+                Err(err)        => { return Err(Error::DirEntryReadError{ path, entry: i, err }); },
+            };
+
+            // Note it, and recurse into directories
+            let rel: String = format!("{}{}", prefix, entry.file_name().to_string_lossy());
+            let entry_path: PathBuf = entry.path();
+            let entry_metadata: Metadata = metadata(&entry_path).await?;
+            if entry_metadata.is_dir() { todo.push((format!("{}/", rel), entry_path.clone())); }
+            res.push((rel, entry_path, entry_metadata));
+
+            i += 1;
+        }
+    }
+    res.sort_by(|(lhs, _, _), (rhs, _, _)| lhs.cmp(rhs));
+    Ok(res)
+}
+
+/// Feeds the contents of the given file to the given hasher.
+/// 
+/// # Arguments
+/// - `hasher`: The hasher to update.
+/// - `path`: The file to hash.
+/// 
+/// # Errors
+/// This function errors if we failed to read the file.
+async fn hash_file(hasher: &mut Sha256, path: &Path) -> Result<(), Error> {
+    let mut handle: tfs::File = match tfs::File::open(path).await {
+        Ok(handle) => handle,
+        Err(err)   => { return Err(Error::FileOpenError{ path: path.into(), err }); },
+    };
+    let mut buf: [u8; 1024 * 16] = [0; 1024 * 16];
+    loop {
+        let n_bytes: usize = match handle.read(&mut buf).await {
+            Ok(n_bytes) => n_bytes,
+            Err(err)    => { return Err(Error::FileReadError{ path: path.into(), err }); },
+        };
+        if n_bytes == 0 { return Ok(()); }
+        hasher.update(&buf[..n_bytes]);
+    }
+}
+
+/// Computes the digest of the given file or directory.
+/// 
+/// Files are hashed by their contents only, so their digest is the usual SHA-256 of the file. Directories are hashed by the (sorted) relative paths of everything in them together with the contents of the files, so that equal trees get equal digests regardless of where they live.
+/// 
+/// # Arguments
+/// - `path`: The file or directory to hash.
+/// 
+/// # Returns
+/// The digest as 64 lowercase hexadecimal characters.
+/// 
+/// # Errors
+/// This function errors if we failed to read the file or any of the (nested) directories and files.
+async fn digest(path: &Path) -> Result<String, Error> {
+    let mut hasher: Sha256 = Sha256::new();
+    if metadata(path).await?.is_dir() {
+        for (rel, entry_path, entry_metadata) in walk(path).await? {
+            hasher.update(rel.as_bytes());
+            if entry_metadata.is_dir() {
+                hasher.update(b"\0d\0");
+            } else {
+                hasher.update(b"\0f");
+                hasher.update(entry_metadata.len().to_le_bytes());
+                hash_file(&mut hasher, &entry_path).await?;
+            }
+        }
+    } else {
+        hash_file(&mut hasher, path).await?;
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Computes the size of the given file or directory on disk.
+/// 
+/// # Arguments
+/// - `path`: The file or directory to compute the size of.
+/// 
+/// # Returns
+/// The size, in bytes, of the file or of all files in the directory.
+/// 
+/// # Errors
+/// This function errors if we failed to read the file or any of the (nested) directories.
+async fn disk_size(path: &Path) -> Result<u64, Error> {
+    let path_metadata: Metadata = metadata(path).await?;
+    if path_metadata.is_dir() {
+        Ok(walk(path).await?.into_iter().filter(|(_, _, metadata)| metadata.is_file()).map(|(_, _, metadata)| metadata.len()).sum())
+    } else {
+        Ok(path_metadata.len())
+    }
+}
+
+/// Removes the given file or directory.
+/// 
+/// # Arguments
+/// - `path`: The file or directory to remove.
+/// 
+/// # Errors
+/// This function errors if we failed to remove it.
+async fn remove(path: &Path) -> Result<(), Error> {
+    let res: Result<(), std::io::Error> = if metadata(path).await?.is_dir() { tfs::remove_dir_all(path).await } else { tfs::remove_file(path).await };
+    if let Err(err) = res { return Err(Error::RemoveError{ path: path.into(), err }); }
+    Ok(())
+}
+
+/// Locks the given lock file, waiting until we can.
+/// 
+/// The lock is advisory and held until the returned file is dropped. Because every call opens the file anew, it also excludes other tasks in the same process.
+/// 
+/// # Arguments
+/// - `path`: The path of the lock file. It is created if it does not exist yet.
+/// - `exclusive`: Whether to take the lock exclusively (true) or shared with other shared holders (false).
+/// 
+/// # Returns
+/// The open lock file, which keeps the lock until it is dropped.
+/// 
+/// # Errors
+/// This function errors if we failed to open or lock the file.
+async fn lock(path: &Path, exclusive: bool) -> Result<File, Error> {
+    let path: PathBuf = path.into();
+    let task_path: PathBuf = path.clone();
+    match tokio::task::spawn_blocking(move || {
+        let path: PathBuf = task_path;
+        let file: File = match OpenOptions::new().create(true).write(true).open(&path) {
+            Ok(file) => file,
+            Err(err) => { return Err(Error::LockOpenError{ path, err }); },
+        };
+        // SAFETY: The file descriptor is valid for as long as `file` lives, which outlives the call.
+        if unsafe { libc::flock(file.as_raw_fd(), if exclusive { libc::LOCK_EX } else { libc::LOCK_SH }) } != 0 {
+            return Err(Error::LockError{ path, err: std::io::Error::last_os_error() });
+        }
+        Ok(file)
+    }).await {
+        Ok(res)  => res,
+        Err(err) => Err(Error::LockTaskError{ path, err }),
+    }
+}
+
+/// Reads the digest in the given tag file.
+/// 
+/// # Arguments
+/// - `path`: The path of the tag.
+/// 
+/// # Returns
+/// The digest in the tag.
+/// 
+/// # Errors
+/// This function errors if we failed to read the tag or it did not contain a digest.
+async fn read_tag(path: &Path) -> Result<String, Error> {
+    let raw: String = match tfs::read_to_string(path).await {
+        Ok(raw)  => raw,
+        Err(err) => { return Err(Error::TagReadError{ path: path.into(), err }); },
+    };
+    let digest: &str = raw.trim();
+    if digest.len() != 64 || !digest.chars().all(|c| c.is_ascii_hexdigit()) { return Err(Error::IllegalTag{ path: path.into(), raw }); }
+    Ok(digest.into())
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Defines the kinds of tags that may reference objects in the store.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TagKind {
+    /// The image of a package, tagged as `<name>-<version>`.
+    Package,
+    /// A dataset, tagged by its name.
+    Data,
+    /// An intermediate result, tagged by its name.
+    IntermediateResult,
+}
+
+impl TagKind {
+    /// All the kinds of tags.
+    pub const ALL: [Self; 3] = [ Self::Package, Self::Data, Self::IntermediateResult ];
+
+    /// Returns the name of the directory in which tags of this kind are stored.
+    #[inline]
+    pub fn dir(&self) -> &'static str {
+        match self {
+            Self::Package            => "packages",
+            Self::Data               => "data",
+            Self::IntermediateResult => "results",
+        }
+    }
+}
+
+impl Display for TagKind {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        match self {
+            Self::Package            => write!(f, "package"),
+            Self::Data               => write!(f, "dataset"),
+            Self::IntermediateResult => write!(f, "intermediate result"),
+        }
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines the content-addressed store on a worker node.
+/// 
+/// Its root directory has the following layout:
+/// - `objects/<digest>`: The stored files and directories, named after their digest (see `ContentStore::add()`).
+/// - `tags/<kind>/<name>`: Files with the digest of the object that the tag references. Objects are only kept alive by tags; any object without one is removed by `ContentStore::gc()`.
+/// - `.lock`: The lock that keeps `ContentStore::gc()` from running while tags are being added (also by other processes sharing the store).
+/// - `tmp/`: A scratch directory on the same filesystem as the objects, such that things prepared there can be moved into the store cheaply.
+#[derive(Clone, Debug)]
+pub struct ContentStore {
+    /// The root directory of the store.
+    root : PathBuf,
+}
+
+impl ContentStore {
+    /// Constructor for the ContentStore, which creates its layout in the given root directory if it does not exist yet.
+    /// 
+    /// # Arguments
+    /// - `root`: The root directory of the store.
+    /// 
+    /// # Returns
+    /// A new ContentStore instance.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to create the directories of the store.
+    pub async fn new(root: impl Into<PathBuf>) -> Result<Self, Error> {
+        let root: PathBuf = root.into();
+        for dir in vec![ root.join("objects"), root.join("tmp") ].into_iter().chain(TagKind::ALL.iter().map(|kind| root.join("tags").join(kind.dir()))) {
+            if let Err(err) = tfs::create_dir_all(&dir).await { return Err(Error::DirCreateError{ path: dir, err }); }
+        }
+        Ok(Self { root })
+    }
+
+
+
+    /// Returns a fresh path in the store's scratch directory.
+    /// 
+    /// Because it lives on the same filesystem as the objects, files and directories prepared here are moved into the store without copying them.
+    #[inline]
+    pub fn temp_path(&self) -> PathBuf { self.root.join("tmp").join(uuid::Uuid::new_v4().to_string()) }
+
+    /// Returns the path where the object with the given digest is (or would be) stored.
+    #[inline]
+    pub fn object_path(&self, digest: impl AsRef<str>) -> PathBuf { self.root.join("objects").join(digest.as_ref()) }
+
+    /// Returns the path of the given tag.
+    /// 
+    /// # Errors
+    /// This function errors if the given name is empty, starts with a dot or contains path separators.
+    fn tag_path(&self, kind: TagKind, name: &str) -> Result<PathBuf, Error> {
+        if name.is_empty() || name.starts_with('.') || name.contains('/') || name.contains('\\') { return Err(Error::IllegalTagName{ name: name.into() }); }
+        Ok(self.root.join("tags").join(kind.dir()).join(name))
+    }
+
+    /// Takes the lock of the store. Adding tags takes it shared, such that they may happen concurrently; garbage collection takes it exclusively.
+    /// 
+    /// # Returns
+    /// The open lock file, which keeps the lock until it is dropped.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to take the lock.
+    #[inline]
+    async fn lock(&self, exclusive: bool) -> Result<File, Error> { lock(&self.root.join(".lock"), exclusive).await }
+
+
+
+    /// Moves the given file or directory into the store and tags it.
+    /// 
+    /// If the store already has an object with the same contents, the given file or directory is simply removed. Any object previously referenced by the same tag is released.
+    /// 
+    /// # Arguments
+    /// - `source`: The file or directory to move into the store. Preferably, this is a `ContentStore::temp_path()`; otherwise, it is copied into the store before it is removed.
+    /// - `kind`: The kind of tag to reference it with.
+    /// - `name`: The name of the tag to reference it with.
+    /// 
+    /// # Returns
+    /// The digest of the object, together with its path in the store.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to hash the source, write the tag or move the source into the store.
+    pub async fn add(&self, source: impl AsRef<Path>, kind: TagKind, name: impl AsRef<str>) -> Result<(String, PathBuf), Error> {
+        let source : &Path = source.as_ref();
+        let name   : &str  = name.as_ref();
+        let tag_path: PathBuf = self.tag_path(kind, name)?;
+
+        // Find out what it is
+        debug!("Hashing '{}' to add it to the store as {} '{}'...", source.display(), kind, name);
+        let digest: String = digest(source).await?;
+        let target: PathBuf = self.object_path(&digest);
+
+        // Tag it and move it in while holding the lock, since a concurrent garbage collection would otherwise see the object without having seen its tag
+        let _guard: File = self.lock(false).await?;
+        if let Err(err) = tfs::write(&tag_path, &digest).await { return Err(Error::TagWriteError{ path: tag_path, err }); }
+
+        // Move it into place, unless it's already there
+        if target.exists() {
+            debug!("Store already contains object '{}'; discarding '{}'", digest, source.display());
+            remove(source).await?;
+        } else if tfs::rename(source, &target).await.is_err() {
+            // It's probably on another filesystem, so copy it to one on ours first
+            let temp: PathBuf = self.temp_path();
+            debug!("Copying '{}' to '{}' to add it to the store...", source.display(), temp.display());
+            if metadata(source).await?.is_dir() {
+                if let Err(err) = copy_dir_recursively_async(source, &temp).await { return Err(Error::DirCopyError{ source: source.into(), target: temp, err }); }
+            } else if let Err(err) = tfs::copy(source, &temp).await {
+                return Err(Error::FileCopyError{ source: source.into(), target: temp, err });
+            }
+            if let Err(err) = tfs::rename(&temp, &target).await {
+                // Maybe someone beat us to it
+                remove(&temp).await?;
+                if !target.exists() { return Err(Error::MoveError{ source: temp, target, err }); }
+            }
+            remove(source).await?;
+        }
+
+        // Done
+        debug!("Added object '{}' to the store as {} '{}'", digest, kind, name);
+        Ok((digest, target))
+    }
+
+    /// Tags an object that is already in the store. Any object previously referenced by the same tag is released.
+    /// 
+    /// # Arguments
+    /// - `kind`: The kind of tag to reference the object with.
+    /// - `name`: The name of the tag to reference the object with.
+    /// - `digest`: The digest of the object to reference.
+    /// 
+    /// # Returns
+    /// The path of the object in the store.
+    /// 
+    /// # Errors
+    /// This function errors if the object is not in the store or we failed to write the tag.
+    pub async fn tag(&self, kind: TagKind, name: impl AsRef<str>, digest: impl AsRef<str>) -> Result<PathBuf, Error> {
+        let name     : &str    = name.as_ref();
+        let digest   : &str    = digest.as_ref();
+        let tag_path : PathBuf = self.tag_path(kind, name)?;
+
+        // Make sure we know it (and keep it that way until it is tagged)
+        let _guard: File = self.lock(false).await?;
+        let target: PathBuf = self.object_path(digest);
+        if !target.exists() { return Err(Error::UnknownObject{ digest: digest.into() }); }
+
+        // Write the tag
+        if let Err(err) = tfs::write(&tag_path, digest).await { return Err(Error::TagWriteError{ path: tag_path, err }); }
+        debug!("Tagged object '{}' as {} '{}'", digest, kind, name);
+        Ok(target)
+    }
+
+    /// Removes the given tag, releasing the object it references.
+    /// 
+    /// # Arguments
+    /// - `kind`: The kind of tag to remove.
+    /// - `name`: The name of the tag to remove.
+    /// 
+    /// # Returns
+    /// Whether the tag existed.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to remove the tag.
+    pub async fn untag(&self, kind: TagKind, name: impl AsRef<str>) -> Result<bool, Error> {
+        let tag_path: PathBuf = self.tag_path(kind, name.as_ref())?;
+        if !tag_path.exists() { return Ok(false); }
+        if let Err(err) = tfs::remove_file(&tag_path).await { return Err(Error::TagRemoveError{ path: tag_path, err }); }
+        Ok(true)
+    }
+
+    /// Resolves the given tag to the object it references.
+    /// 
+    /// # Arguments
+    /// - `kind`: The kind of tag to resolve.
+    /// - `name`: The name of the tag to resolve.
+    /// 
+    /// # Returns
+    /// The digest and path of the referenced object, or `None` if there is no such tag (or the object it references has not been stored yet).
+    /// 
+    /// # Errors
+    /// This function errors if we failed to read the tag or if it is not a tag.
+    pub async fn resolve(&self, kind: TagKind, name: impl AsRef<str>) -> Result<Option<(String, PathBuf)>, Error> {
+        let tag_path: PathBuf = self.tag_path(kind, name.as_ref())?;
+        if !tag_path.exists() { return Ok(None); }
+        let digest: String = read_tag(&tag_path).await?;
+        let target: PathBuf = self.object_path(&digest);
+        if !target.exists() { return Ok(None); }
+        Ok(Some((digest, target)))
+    }
+
+    /// Lists the tags of the given kind.
+    /// 
+    /// # Arguments
+    /// - `kind`: The kind of tags to list.
+    /// 
+    /// # Returns
+    /// A list of the names of the tags together with the digest of the object they reference, sorted by name.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to read the tags.
+    pub async fn tags(&self, kind: TagKind) -> Result<Vec<(String, String)>, Error> {
+        let dir: PathBuf = self.root.join("tags").join(kind.dir());
+        let mut res: Vec<(String, String)> = vec![];
+        for (name, tag_path, tag_metadata) in walk(&dir).await? {
+            if !tag_metadata.is_file() || name.starts_with('.') { continue; }
+            let digest: String = read_tag(&tag_path).await?;
+            res.push((name, digest));
+        }
+        Ok(res)
+    }
+
+    /// Lists the tags of the given kind that were (re)written longer ago than the given age.
+    /// 
+    /// # Arguments
+    /// - `kind`: The kind of tags to list.
+    /// - `max_age`: The age after which a tag is considered stale.
+    /// 
+    /// # Returns
+    /// A list of the names of the stale tags, sorted by name.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to read the tags or when they were written.
+    pub async fn stale_tags(&self, kind: TagKind, max_age: Duration) -> Result<Vec<String>, Error> {
+        let dir: PathBuf = self.root.join("tags").join(kind.dir());
+        let mut res: Vec<String> = vec![];
+        for (name, tag_path, tag_metadata) in walk(&dir).await? {
+            if !tag_metadata.is_file() || name.starts_with('.') { continue; }
+            let age: Duration = match tag_metadata.modified() {
+                Ok(modified) => modified.elapsed().unwrap_or(Duration::ZERO),
+                Err(err)     => { return Err(Error::MetadataError{ path: tag_path, err }); },
+            };
+            if age >= max_age { res.push(name); }
+        }
+        res.sort();
+        Ok(res)
+    }
+
+    /// Computes how much space the objects referenced by the given kind of tags take up.
+    /// 
+    /// Objects that are referenced by multiple tags of this kind are only counted once.
+    /// 
+    /// # Arguments
+    /// - `kind`: The kind of tags whose objects to count.
+    /// 
+    /// # Returns
+    /// The size, in bytes, of all objects referenced by tags of this kind.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to read the tags or objects.
+    pub async fn size(&self, kind: TagKind) -> Result<u64, Error> {
+        let mut digests: Vec<String> = self.tags(kind).await?.into_iter().map(|(_, digest)| digest).collect();
+        digests.sort();
+        digests.dedup();
+
+        let mut total: u64 = 0;
+        for digest in digests {
+            let target: PathBuf = self.object_path(&digest);
+            if target.exists() { total += disk_size(&target).await?; }
+        }
+        Ok(total)
+    }
+
+    /// Counts the references to the objects in the store.
+    /// 
+    /// # Returns
+    /// A map of object digests to the number of tags (of any kind) that reference them. Objects without references are omitted.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to read the tags.
+    pub async fn refcounts(&self) -> Result<HashMap<String, usize>, Error> {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for kind in TagKind::ALL {
+            for (_, digest) in self.tags(kind).await? {
+                *counts.entry(digest).or_insert(0) += 1;
+            }
+        }
+        Ok(counts)
+    }
+
+    /// Removes all objects that are not referenced by any tag.
+    /// 
+    /// It waits for any tags that are being added to the store, and keeps new ones from being added until it is done.
+    /// 
+    /// # Arguments
+    /// - `dry_run`: If true, only reports which objects would be removed.
+    /// 
+    /// # Returns
+    /// The digests of the removed objects together with the number of bytes they took up.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to read the tags or objects, or failed to remove an unreferenced object.
+    pub async fn gc(&self, dry_run: bool) -> Result<Vec<(String, u64)>, Error> {
+        let _guard: File = self.lock(true).await?;
+        let counts: HashMap<String, usize> = self.refcounts().await?;
+
+        // Go through all the objects
+        let dir: PathBuf = self.root.join("objects");
+        let mut entries: tfs::ReadDir = match tfs::read_dir(&dir).await {
+            Ok(entries) => entries,
+            Err(err)    => { return Err(Error::DirReadError{ path: dir, err }); },
+        };
+        let mut removed: Vec<(String, u64)> = vec![];
+        let mut i: usize = 0;
+        loop {
+            let entry: tfs::DirEntry = match entries.next_entry().await {
+                Ok(Some(entry)) => entry,
+                Ok(None)        => { break; },
+                Err(err)        => { return Err(Error::DirEntryReadError{ path: dir, entry: i, err }); },
+            };
+            i += 1;
+
+            // Skip it if it's still referenced
+            let digest: String = entry.file_name().to_string_lossy().into();
+            if counts.contains_key(&digest) { continue; }
+
+            // Otherwise, it's garbage
+            let entry_path: PathBuf = entry.path();
+            let size: u64 = disk_size(&entry_path).await?;
+            if !dry_run {
+                debug!("Removing unreferenced object '{}' ({} bytes)...", digest, size);
+                remove(&entry_path).await?;
+            }
+            removed.push((digest, size));
+        }
+
+        // Done
+        removed.sort();
+        Ok(removed)
+    }
+}
+
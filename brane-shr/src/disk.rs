@@ -0,0 +1,297 @@
+//  DISK.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 10:35:31
+//  Last edited:
+//    16 Oct 2026, 10:35:31
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements disk usage tracking for worker nodes, such that they can
+//!   refuse tasks that would not fit on disk and report when they are
+//!   running low on space.
+// 
+
+use std::ffi::CString;
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::fs::Metadata;
+use std::mem::MaybeUninit;
+use std::os::unix::ffi::OsStrExt as _;
+use std::path::{Path, PathBuf};
+
+use tokio::fs as tfs;
+
+use crate::cas::{ContentStore, TagKind};
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+    use super::*;
+
+
+    #[tokio::test]
+    async fn test_dir_size() {
+        let tempdir: TempDir = TempDir::new().unwrap();
+        tfs::create_dir_all(tempdir.path().join("nested")).await.unwrap();
+        tfs::write(tempdir.path().join("file.txt"), "Hello there!").await.unwrap();
+        tfs::write(tempdir.path().join("nested").join("other.txt"), "General Kenobi").await.unwrap();
+
+        assert_eq!(dir_size(tempdir.path()).await.unwrap(), 12 + 14);
+        assert_eq!(dir_size(tempdir.path().join("file.txt")).await.unwrap(), 12);
+        assert_eq!(dir_size(tempdir.path().join("does_not_exist")).await.unwrap(), 0);
+    }
+
+    #[test]
+    fn test_free_space() {
+        let tempdir: TempDir = TempDir::new().unwrap();
+        assert!(free_space(tempdir.path()).is_ok());
+        assert!(matches!(free_space(tempdir.path().join("does_not_exist")), Err(Error::StatError{ .. })));
+    }
+
+    #[test]
+    fn test_pressure() {
+        assert_eq!(Pressure::of(300, 100), Pressure::Normal);
+        assert_eq!(Pressure::of(200, 100), Pressure::Normal);
+        assert_eq!(Pressure::of(150, 100), Pressure::High);
+        assert_eq!(Pressure::of(99, 100), Pressure::Critical);
+        assert_eq!(Pressure::of(0, 0), Pressure::Normal);
+    }
+}
+
+
+
+
+
+/***** ERRORS *****/
+/// Defines the errors that may occur when tracking disk usage.
+#[derive(Debug)]
+pub enum Error {
+    /// The given path cannot be passed to the OS.
+    IllegalPath{ path: PathBuf },
+    /// Failed to query the filesystem that the given path lives on.
+    StatError{ path: PathBuf, err: std::io::Error },
+
+    /// Failed to read a directory.
+    DirReadError{ path: PathBuf, err: std::io::Error },
+    /// Failed to read an entry in a directory.
+    DirEntryReadError{ path: PathBuf, entry: usize, err: std::io::Error },
+    /// Failed to get the metadata of a file or directory.
+    MetadataError{ path: PathBuf, err: std::io::Error },
+    /// Failed to compute the size of the objects in the content-addressed store.
+    StoreError{ err: crate::cas::Error },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use Error::*;
+        match self {
+            IllegalPath{ path }    => write!(f, "Path '{}' contains a null byte", path.display()),
+            StatError{ path, err } => write!(f, "Failed to query filesystem of '{}': {}", path.display(), err),
+
+            DirReadError{ path, err }             => write!(f, "Failed to read directory '{}': {}", path.display(), err),
+            DirEntryReadError{ path, entry, err } => write!(f, "Failed to read entry {} in directory '{}': {}", entry, path.display(), err),
+            MetadataError{ path, err }            => write!(f, "Failed to get metadata of '{}': {}", path.display(), err),
+            StoreError{ err }                     => write!(f, "Failed to compute size of content-addressed store: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Returns the space that is available to unprivileged users on the filesystem of the given path.
+/// 
+/// # Arguments
+/// - `path`: Some path on the filesystem to query. Must exist.
+/// 
+/// # Returns
+/// The number of bytes that may still be written to the filesystem.
+/// 
+/// # Errors
+/// This function errors if the path does not exist or we failed to query its filesystem.
+pub fn free_space(path: impl AsRef<Path>) -> Result<u64, Error> {
+    let path: &Path = path.as_ref();
+    let cpath: CString = match CString::new(path.as_os_str().as_bytes()) {
+        Ok(cpath) => cpath,
+        Err(_)    => { return Err(Error::IllegalPath{ path: path.into() }); },
+    };
+
+    // Ask the OS
+    let mut stat: MaybeUninit<libc::statvfs> = MaybeUninit::uninit();
+    // SAFETY: `cpath` is a valid, null-terminated string and `stat` is large enough to hold the result.
+    if unsafe { libc::statvfs(cpath.as_ptr(), stat.as_mut_ptr()) } != 0 {
+        return Err(Error::StatError{ path: path.into(), err: std::io::Error::last_os_error() });
+    }
+    // SAFETY: `statvfs()` has initialized the struct if it succeeded.
+    let stat: libc::statvfs = unsafe { stat.assume_init() };
+    #[allow(clippy::unnecessary_cast)]
+    Ok((stat.f_bavail as u64).saturating_mul(stat.f_frsize as u64))
+}
+
+/// Computes the size of the given file or directory on disk.
+/// 
+/// Symbolic links are not followed, so links into the content-addressed store (like intermediate results) are not counted twice.
+/// 
+/// # Arguments
+/// - `path`: The file or directory to compute the size of.
+/// 
+/// # Returns
+/// The size, in bytes, of the file or of all files in the directory. Paths that do not exist have a size of 0.
+/// 
+/// # Errors
+/// This function errors if we failed to read the file or any of the (nested) directories.
+pub async fn dir_size(path: impl AsRef<Path>) -> Result<u64, Error> {
+    let path: &Path = path.as_ref();
+    if tfs::symlink_metadata(path).await.is_err() { return Ok(0); }
+
+    // Walk the tree without recursion
+    let mut total : u64          = 0;
+    let mut todo  : Vec<PathBuf> = vec![ path.into() ];
+    while let Some(path) = todo.pop() {
+        let metadata: Metadata = match tfs::symlink_metadata(&path).await {
+            Ok(metadata) => metadata,
+            Err(err)     => { return Err(Error::MetadataError{ path, err }); },
+        };
+        if metadata.is_file() { total += metadata.len(); continue; }
+        if !metadata.is_dir() { continue; }
+
+        let mut entries: tfs::ReadDir = match tfs::read_dir(&path).await {
+            Ok(entries) => entries,
+            Err(err)    => { return Err(Error::DirReadError{ path, err }); },
+        };
+        let mut i: usize = 0;
+        loop {
+            match entries.next_entry().await {
+                Ok(Some(entry)) => { todo.push(entry.path()); },
+                Ok(None)        => { break; },
+                Err(err)        => { return Err(Error::DirEntryReadError{ path, entry: i, err }); },
+            }
+            i += 1;
+        }
+    }
+
+    // Done
+    Ok(total)
+}
+
+
+
+/// Defines how much pressure there is on the disk of a node.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Pressure {
+    /// There is plenty of space left.
+    Normal,
+    /// The free space is approaching the reserve; caches should be evicted.
+    High,
+    /// The free space has dropped below the reserve; no new tasks are admitted.
+    Critical,
+}
+
+impl Pressure {
+    /// Determines the pressure on a disk.
+    /// 
+    /// # Arguments
+    /// - `free`: The number of bytes that are still free on the disk.
+    /// - `reserve`: The number of bytes that should always be kept free.
+    /// 
+    /// # Returns
+    /// `Critical` if there is less space left than the reserve, `High` if there is less than twice the reserve left or `Normal` otherwise.
+    pub fn of(free: u64, reserve: u64) -> Self {
+        if free < reserve { Self::Critical }
+        else if free < reserve.saturating_mul(2) { Self::High }
+        else { Self::Normal }
+    }
+}
+
+impl Display for Pressure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use Pressure::*;
+        match self {
+            Normal   => write!(f, "normal"),
+            High     => write!(f, "high"),
+            Critical => write!(f, "critical"),
+        }
+    }
+}
+
+
+
+/// Summarizes what takes up the disk space of a worker node.
+#[derive(Clone, Copy, Debug)]
+pub struct DiskUsage {
+    /// The space taken up by package images, in bytes.
+    pub packages : u64,
+    /// The space taken up by datasets, in bytes.
+    pub data     : u64,
+    /// The space taken up by intermediate results, in bytes.
+    pub results  : u64,
+    /// The space taken up by staged and fetched inputs and downloaded results, in bytes.
+    pub scratch  : u64,
+    /// The space that is still free on the disk, in bytes.
+    pub free     : u64,
+}
+
+impl DiskUsage {
+    /// Measures the disk usage of a worker node.
+    /// 
+    /// # Arguments
+    /// - `store`: The content-addressed store that keeps the node's packages, datasets and intermediate results.
+    /// - `store_path`: The path of the content-addressed store, which determines the disk of which to report the free space.
+    /// - `scratch`: The directories that hold temporary data on the node.
+    /// 
+    /// # Returns
+    /// A new DiskUsage with the usage of every category.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to read the store or any of the scratch directories, or to query the free space on the disk.
+    pub async fn measure(store: &ContentStore, store_path: impl AsRef<Path>, scratch: &[&Path]) -> Result<Self, Error> {
+        // Ask the store what the objects of every kind take up
+        let packages: u64 = match store.size(TagKind::Package).await {
+            Ok(size) => size,
+            Err(err) => { return Err(Error::StoreError{ err }); },
+        };
+        let data: u64 = match store.size(TagKind::Data).await {
+            Ok(size) => size,
+            Err(err) => { return Err(Error::StoreError{ err }); },
+        };
+        let results: u64 = match store.size(TagKind::IntermediateResult).await {
+            Ok(size) => size,
+            Err(err) => { return Err(Error::StoreError{ err }); },
+        };
+
+        // Walk the scratch directories ourselves
+        let mut scratch_size: u64 = 0;
+        for dir in scratch {
+            scratch_size += dir_size(dir).await?;
+        }
+
+        // Done
+        Ok(Self {
+            packages,
+            data,
+            results,
+            scratch : scratch_size,
+            free    : free_space(store_path)?,
+        })
+    }
+
+
+
+    /// Determines the pressure on the disk.
+    /// 
+    /// # Arguments
+    /// - `reserve`: The number of bytes that should always be kept free.
+    /// 
+    /// # Returns
+    /// The Pressure on the disk, as given by `Pressure::of()`.
+    #[inline]
+    pub fn pressure(&self, reserve: u64) -> Pressure { Pressure::of(self.free, reserve) }
+}
@@ -0,0 +1,251 @@
+//  FETCH.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 10:14:41
+//  Last edited:
+//    16 Oct 2026, 10:14:41
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements lazily fetching datasets that are registered by URL and
+//!   digest, caching them by their digest so every node downloads them
+//!   at most once.
+// 
+
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::path::{Path, PathBuf};
+
+use log::debug;
+use reqwest::{Response, StatusCode};
+use sha2::{Digest as _, Sha256};
+use tokio::fs as tfs;
+use tokio::io::AsyncWriteExt as _;
+use url::Url;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use sha2::Digest as _;
+
+    use super::*;
+
+
+    /// The SHA-256 digest of the empty string.
+    const EMPTY: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+
+    /// Tests parsing valid digests.
+    #[test]
+    fn test_parse_digest() {
+        assert_eq!(parse_digest(EMPTY).unwrap(), EMPTY);
+        assert_eq!(parse_digest(format!("sha256:{}", EMPTY)).unwrap(), EMPTY);
+        // Digests are normalized to lowercase, so they can be compared (and used as cache directories)
+        assert_eq!(parse_digest(EMPTY.to_ascii_uppercase()).unwrap(), EMPTY);
+        assert_eq!(parse_digest(format!("sha256:{}", EMPTY.to_ascii_uppercase())).unwrap(), EMPTY);
+        // ...which matches what we compute ourselves
+        assert_eq!(parse_digest(format!("{:x}", Sha256::digest(b""))).unwrap(), EMPTY);
+    }
+
+    /// Tests rejecting malformed digests.
+    #[test]
+    fn test_parse_digest_illegal() {
+        let illegal = |raw: String| { assert!(matches!(parse_digest(&raw), Err(Error::IllegalDigest{ raw: ref r }) if *r == raw), "'{}' was accepted", raw); };
+
+        // Other (or misspelled) algorithms
+        illegal(format!("sha512:{}", EMPTY));
+        illegal(format!("md5:{}", EMPTY));
+        illegal(format!("SHA256:{}", EMPTY));
+        illegal(format!("sha256::{}", EMPTY));
+        illegal(format!("sha256:sha256:{}", EMPTY));
+        illegal(format!(":{}", EMPTY));
+        // Digests of the wrong length, including a SHA-512 digest without prefix
+        illegal(String::new());
+        illegal("sha256:".into());
+        illegal(EMPTY[..63].into());
+        illegal(format!("{}0", EMPTY));
+        illegal(format!("{}{}", EMPTY, EMPTY));
+        // Characters that are not hexadecimal
+        illegal(format!("{}g", &EMPTY[..63]));
+        illegal(format!(" {}", &EMPTY[..63]));
+        illegal(format!("{}\n", &EMPTY[..63]));
+        illegal(format!("0x{}", &EMPTY[..62]));
+        // Multi-byte characters do not make up for missing ones
+        illegal(format!("{}é", &EMPTY[..62]));
+    }
+}
+
+
+
+
+
+/***** ERRORS *****/
+/// Defines the errors that may occur when fetching datasets by URL.
+#[derive(Debug)]
+pub enum Error {
+    /// The given digest is not a SHA-256 digest.
+    IllegalDigest{ raw: String },
+    /// Failed to parse the URL of the dataset.
+    UrlParseError{ url: String, err: url::ParseError },
+
+    /// Failed to create a directory in the cache.
+    DirCreateError{ path: PathBuf, err: std::io::Error },
+    /// Failed to create the downloaded file.
+    FileCreateError{ path: PathBuf, err: std::io::Error },
+    /// Failed to write to the downloaded file.
+    FileWriteError{ path: PathBuf, err: std::io::Error },
+    /// Failed to move the downloaded dataset into the cache.
+    CacheMoveError{ source: PathBuf, target: PathBuf, err: std::io::Error },
+
+    /// Failed to send the request for the dataset.
+    RequestError{ url: String, err: reqwest::Error },
+    /// The server did not return the dataset.
+    RequestFailure{ url: String, code: StatusCode },
+    /// Failed to download the next chunk of the dataset.
+    DownloadError{ url: String, err: reqwest::Error },
+    /// The downloaded dataset did not have the digest it was registered with.
+    DigestMismatch{ url: String, expected: String, got: String },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use Error::*;
+        match self {
+            IllegalDigest{ raw }      => write!(f, "Digest '{}' is not a SHA-256 digest (expected 64 hexadecimal characters, optionally prefixed with 'sha256:')", raw),
+            UrlParseError{ url, err } => write!(f, "Failed to parse dataset URL '{}': {}", url, err),
+
+            DirCreateError{ path, err }           => write!(f, "Failed to create cache directory '{}': {}", path.display(), err),
+            FileCreateError{ path, err }          => write!(f, "Failed to create downloaded file '{}': {}", path.display(), err),
+            FileWriteError{ path, err }           => write!(f, "Failed to write to downloaded file '{}': {}", path.display(), err),
+            CacheMoveError{ source, target, err } => write!(f, "Failed to move downloaded dataset '{}' into the cache as '{}': {}", source.display(), target.display(), err),
+
+            RequestError{ url, err }             => write!(f, "Failed to send GET request to '{}': {}", url, err),
+            RequestFailure{ url, code }          => write!(f, "GET request to '{}' failed with status code {} ({})", url, code, code.canonical_reason().unwrap_or("???")),
+            DownloadError{ url, err }            => write!(f, "Failed to download next chunk from '{}': {}", url, err),
+            DigestMismatch{ url, expected, got } => write!(f, "Dataset downloaded from '{}' has digest '{}', but it was registered with digest '{}'", url, got, expected),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Downloads the given URL to the given file, checking its digest while at it.
+/// 
+/// # Arguments
+/// - `url`: The URL to download.
+/// - `digest`: The (normalized) digest that the file should have.
+/// - `path`: The path of the file to write to.
+/// 
+/// # Errors
+/// This function errors if the download failed, we failed to write the file or the file did not have the given digest.
+async fn download(url: &str, digest: &str, path: &Path) -> Result<(), Error> {
+    // Send the request
+    let mut res: Response = match reqwest::get(url).await {
+        Ok(res)  => res,
+        Err(err) => { return Err(Error::RequestError{ url: url.into(), err }); },
+    };
+    if !res.status().is_success() { return Err(Error::RequestFailure{ url: url.into(), code: res.status() }); }
+
+    // Write it chunk-by-chunk, hashing as we go
+    let mut handle: tfs::File = match tfs::File::create(path).await {
+        Ok(handle) => handle,
+        Err(err)   => { return Err(Error::FileCreateError{ path: path.into(), err }); },
+    };
+    let mut hasher: Sha256 = Sha256::new();
+    loop {
+        let chunk = match res.chunk().await {
+            Ok(Some(chunk)) => chunk,
+            Ok(None)        => { break; },
+            Err(err)        => { return Err(Error::DownloadError{ url: url.into(), err }); },
+        };
+        hasher.update(&chunk);
+        if let Err(err) = handle.write_all(&chunk).await { return Err(Error::FileWriteError{ path: path.into(), err }); }
+    }
+    if let Err(err) = handle.flush().await { return Err(Error::FileWriteError{ path: path.into(), err }); }
+
+    // Make sure it's what we expected
+    let got: String = format!("{:x}", hasher.finalize());
+    if got != digest { return Err(Error::DigestMismatch{ url: url.into(), expected: digest.into(), got }); }
+    Ok(())
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Parses the given digest as a SHA-256 digest.
+/// 
+/// # Arguments
+/// - `raw`: The digest to parse. May be prefixed with `sha256:`.
+/// 
+/// # Returns
+/// The digest as 64 lowercase hexadecimal characters (without prefix).
+/// 
+/// # Errors
+/// This function errors if the given digest is not a SHA-256 digest.
+pub fn parse_digest(raw: impl AsRef<str>) -> Result<String, Error> {
+    let raw: &str = raw.as_ref();
+    let digest: &str = raw.strip_prefix("sha256:").unwrap_or(raw);
+    if digest.len() != 64 || !digest.chars().all(|c| c.is_ascii_hexdigit()) { return Err(Error::IllegalDigest{ raw: raw.into() }); }
+    Ok(digest.to_ascii_lowercase())
+}
+
+
+
+/// Fetches a dataset that is registered by URL, unless it is already cached.
+/// 
+/// The dataset is stored as `<cache_dir>/<digest>/<file name>`, where the file name is the last segment of the URL (or `data` if it has none). It is only moved into the cache once its digest has been verified, so concurrent fetches of the same dataset never see half-written files.
+/// 
+/// # Arguments
+/// - `url`: The URL of the dataset.
+/// - `digest`: The SHA-256 digest that the dataset was registered with (see `parse_digest()`).
+/// - `cache_dir`: The directory that caches the datasets.
+/// 
+/// # Returns
+/// The path to the directory in the cache that contains the dataset.
+/// 
+/// # Errors
+/// This function errors if the digest or URL were invalid, the download failed or did not have the given digest or we failed to write to the cache.
+pub async fn fetch(url: &str, digest: &str, cache_dir: impl AsRef<Path>) -> Result<PathBuf, Error> {
+    let cache_dir: &Path = cache_dir.as_ref();
+    let digest: String = parse_digest(digest)?;
+
+    // If we already have it, we're done
+    let target: PathBuf = cache_dir.join(&digest);
+    if target.is_dir() {
+        debug!("Dataset '{}' is already cached at '{}'", url, target.display());
+        return Ok(target);
+    }
+
+    // Find the name of the file in the URL
+    let parsed: Url = match Url::parse(url) {
+        Ok(parsed) => parsed,
+        Err(err)   => { return Err(Error::UrlParseError{ url: url.into(), err }); },
+    };
+    let file: String = parsed.path_segments().and_then(|mut segments| segments.next_back()).filter(|file| !file.is_empty()).unwrap_or("data").into();
+
+    // Download it to a private directory first
+    let part: PathBuf = cache_dir.join(format!(".{}-{}.part", digest, uuid::Uuid::new_v4()));
+    if let Err(err) = tfs::create_dir_all(&part).await { return Err(Error::DirCreateError{ path: part, err }); }
+    debug!("Fetching dataset '{}' to '{}'...", url, part.display());
+    if let Err(err) = download(url, &digest, &part.join(file)).await {
+        if let Err(err) = tfs::remove_dir_all(&part).await { debug!("Failed to remove partial download '{}': {}", part.display(), err); }
+        return Err(err);
+    }
+
+    // Move it into the cache, unless another fetch beat us to it
+    if let Err(err) = tfs::rename(&part, &target).await {
+        if let Err(err) = tfs::remove_dir_all(&part).await { debug!("Failed to remove partial download '{}': {}", part.display(), err); }
+        if !target.is_dir() { return Err(Error::CacheMoveError{ source: part, target, err }); }
+        debug!("Dataset '{}' was cached concurrently", url);
+    }
+    Ok(target)
+}
@@ -203,6 +203,26 @@ pub mod tests {
     async fn test_tarball_without_root_extra_path() {
         test_archive_unarchive("some/extra/folders/lol".into(), true).await;
     }
+
+    /// Test if an archive can be extracted while it is being created, as is done for streamed transfers.
+    #[tokio::test]
+    async fn test_tarball_streamed() {
+        let tempdir: TempDir = TempDir::new().unwrap();
+        let source: PathBuf = tempdir.path().join("src");
+        fs::create_dir_all(source.join("nested")).unwrap();
+        fs::write(source.join("file.txt"), "Hello there!").unwrap();
+        fs::write(source.join("nested").join("other.txt"), "General Kenobi").unwrap();
+
+        // Pipe the archive from one end to the other
+        let (writer, reader) = tio::duplex(64);
+        let archiver = tokio::spawn(archive_to_async(source, writer, "<stream>", true));
+        let target: PathBuf = tempdir.path().join("tgt");
+        unarchive_from_async(tio::BufReader::new(reader), "<stream>", &target).await.unwrap();
+        archiver.await.unwrap().unwrap();
+
+        assert_eq!(fs::read_to_string(target.join("file.txt")).unwrap(), "Hello there!");
+        assert_eq!(fs::read_to_string(target.join("nested").join("other.txt")).unwrap(), "General Kenobi");
+    }
 }
 
 
@@ -385,7 +405,6 @@ pub async fn copy_dir_recursively_async(source: impl AsRef<Path>, target: impl A
 pub async fn archive_async(source: impl AsRef<Path>, tarball: impl AsRef<Path>, skip_root_dir: bool) -> Result<(), Error> {
     let source  : &Path = source.as_ref();
     let tarball : &Path = tarball.as_ref();
-    debug!("Archiving '{}' to '{}'...", source.display(), tarball.display());
 
     // Open the target file
     let handle: tfs::File = match tfs::File::create(tarball).await {
@@ -393,8 +412,30 @@ pub async fn archive_async(source: impl AsRef<Path>, tarball: impl AsRef<Path>,
         Err(err)   => { return Err(Error::FileCreateError{ what: "target tarball", path: tarball.into(), err }); },
     };
 
+    // Write the archive to it
+    archive_to_async(source, handle, tarball, skip_root_dir).await
+}
+
+/// Archives the given file or directory as a `.tar.gz` stream to the given writer.
+/// 
+/// This allows archives to be sent while they are being created, without writing them to disk first.
+/// 
+/// # Arguments
+/// - `source`: The source file or directory to archive.
+/// - `writer`: The writer to write the compressed archive to.
+/// - `tarball`: The name of the target to use in debug prints and errors (e.g., the path of a file or the address of a peer).
+/// - `skip_root_dir`: If the `source` points to a directory, then this determines whether to trim it (true) or not (false) in the resulting tarfile
+///   (i.e., the files in the root dir will be in the tar's root instead of the directory). Ignore otherwise.
+/// 
+/// # Errors
+/// This function errors if we failed to read the source or to write to the writer.
+pub async fn archive_to_async<W: tio::AsyncWrite + Unpin + Send + 'static>(source: impl AsRef<Path>, writer: W, tarball: impl AsRef<Path>, skip_root_dir: bool) -> Result<(), Error> {
+    let source  : &Path = source.as_ref();
+    let tarball : &Path = tarball.as_ref();
+    debug!("Archiving '{}' to '{}'...", source.display(), tarball.display());
+
     // Create the encoder & tarfile around this file
-    let enc     : GzipEncoder<_>          = GzipEncoder::new(handle);
+    let enc     : GzipEncoder<_>          = GzipEncoder::new(writer);
     let mut tar : Builder<GzipEncoder<_>> = Builder::new(enc);
 
     // Now add the source recursively
@@ -468,11 +509,6 @@ pub async fn archive_async(source: impl AsRef<Path>, tarball: impl AsRef<Path>,
 pub async fn unarchive_async(tarball: impl AsRef<Path>, target: impl AsRef<Path>) -> Result<(), Error> {
     let tarball : &Path = tarball.as_ref();
     let target  : &Path = target.as_ref();
-    debug!("Extracting '{}' to '{}'...", tarball.display(), target.display());
-
-    // Whine if the target already exists
-    if target.exists() { return Err(Error::PathExistsError{ what: "target", path: target.into() }); }
-    if let Err(err) = tfs::create_dir(target).await { return Err(Error::DirCreateError { what: "target", path: target.into(), err }); }
 
     // Open the source tarfile
     let handle: tfs::File = match tfs::File::open(tarball).await {
@@ -480,8 +516,32 @@ pub async fn unarchive_async(tarball: impl AsRef<Path>, target: impl AsRef<Path>
         Err(err)   => { return Err(Error::FileOpenError{ what: "source tarball", path: tarball.into(), err }); },
     };
 
-    // Create the decoder & tarfile around this file
-    let dec         : GzipDecoder<_>          = GzipDecoder::new(tio::BufReader::new(handle));
+    // Extract it
+    unarchive_from_async(tio::BufReader::new(handle), tarball, target).await
+}
+
+/// Unarchives a `.tar.gz` stream read from the given reader to the given location.
+/// 
+/// This allows archives to be extracted while they are being received, without writing them to disk first.
+/// 
+/// # Arguments
+/// - `reader`: The reader to read the compressed archive from.
+/// - `tarball`: The name of the source to use in debug prints and errors (e.g., the path of a file or the address of a peer).
+/// - `target`: The target directory to write to. Note that we will throw all sorts of nasty errors if it already exists somehow.
+/// 
+/// # Errors
+/// This function errors if we failed to read or write anything or if some directories do or do not exist.
+pub async fn unarchive_from_async<R: tio::AsyncBufRead + Unpin + Send>(reader: R, tarball: impl AsRef<Path>, target: impl AsRef<Path>) -> Result<(), Error> {
+    let tarball : &Path = tarball.as_ref();
+    let target  : &Path = target.as_ref();
+    debug!("Extracting '{}' to '{}'...", tarball.display(), target.display());
+
+    // Whine if the target already exists
+    if target.exists() { return Err(Error::PathExistsError{ what: "target", path: target.into() }); }
+    if let Err(err) = tfs::create_dir(target).await { return Err(Error::DirCreateError { what: "target", path: target.into(), err }); }
+
+    // Create the decoder & tarfile around this reader
+    let dec         : GzipDecoder<_>          = GzipDecoder::new(reader);
     let mut tar     : Archive<GzipDecoder<_>> = Archive::new(dec);
     let mut entries : Entries<GzipDecoder<_>> = match tar.entries() {
         Ok(entries) => entries,
@@ -0,0 +1,399 @@
+//  GRID.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 10:12:13
+//  Last edited:
+//    16 Oct 2026, 10:14:41
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements staging of datasets that live on the scientific storage
+//!   systems common in physics collaborations (i.e., XRootD and GridFTP
+//!   endpoints), by streaming them through the respective clients.
+// 
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::path::{Path, PathBuf};
+use std::process::{Output, Stdio};
+
+use log::debug;
+use tokio::fs as tfs;
+use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+use tokio::process::{Child, ChildStdout, Command};
+use url::Url;
+
+use specifications::data::AccessKind;
+
+use crate::debug::BlockFormatter;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+    use super::*;
+
+
+    /// Returns a command that runs the given shell script, standing in for the clients.
+    fn script(script: &str) -> Command {
+        let mut cmd: Command = Command::new("sh");
+        cmd.args([ "-c", script ]);
+        cmd
+    }
+
+
+    /// Tests that the proxy of the most specific domain is picked, and only for hosts that are actually part of it.
+    #[test]
+    fn test_find_proxy() {
+        let proxies: HashMap<String, PathBuf> = HashMap::from([
+            ("cern.ch".into(), PathBuf::from("/proxies/cern")),
+            (".eos.cern.ch".into(), PathBuf::from("/proxies/eos")),
+            ("fnal.gov".into(), PathBuf::from("/proxies/fnal")),
+        ]);
+        assert_eq!(find_proxy(&proxies, "cern.ch"), Some(Path::new("/proxies/cern")));
+        assert_eq!(find_proxy(&proxies, "xrootd.cern.ch"), Some(Path::new("/proxies/cern")));
+        assert_eq!(find_proxy(&proxies, "eos.cern.ch"), Some(Path::new("/proxies/eos")));
+        assert_eq!(find_proxy(&proxies, "public.eos.cern.ch"), Some(Path::new("/proxies/eos")));
+        assert_eq!(find_proxy(&proxies, "gridftp.fnal.gov"), Some(Path::new("/proxies/fnal")));
+        // Hosts that merely end with the same characters are not part of the domain
+        assert_eq!(find_proxy(&proxies, "notcern.ch"), None);
+        assert_eq!(find_proxy(&proxies, "example.org"), None);
+        assert_eq!(find_proxy(&HashMap::new(), "cern.ch"), None);
+    }
+
+    /// Tests that only datasets on scientific storage systems are staged by us, with the right client.
+    #[test]
+    fn test_protocol() {
+        assert_eq!(Protocol::of(&AccessKind::XRootD{ url: "root://eos.cern.ch//data/run1.root".into() }), Some((Protocol::XRootD, "root://eos.cern.ch//data/run1.root")));
+        assert_eq!(Protocol::of(&AccessKind::GridFtp{ url: "gsiftp://gridftp.fnal.gov/data/run1.root".into() }), Some((Protocol::GridFtp, "gsiftp://gridftp.fnal.gov/data/run1.root")));
+        assert_eq!(Protocol::of(&AccessKind::File{ path: "/data/run1.root".into() }), None);
+        assert_eq!(Protocol::XRootD.client(), "xrdcp");
+        assert_eq!(Protocol::GridFtp.client(), "globus-url-copy");
+    }
+
+    /// Tests streaming the output of a client to a file, within and beyond the limit, and when the client fails.
+    #[tokio::test]
+    async fn test_stream() {
+        let dir: TempDir = TempDir::new().unwrap();
+        let path: PathBuf = dir.path().join("run1.root");
+
+        // The output ends up in the file
+        assert_eq!(stream("sh", script("printf 'Hello, world!'"), "root://host/run1.root", &path, Some(13)).await.unwrap(), 13);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "Hello, world!");
+
+        // Anything bigger than the limit is aborted
+        match stream("sh", script("printf 'Hello, world!'"), "root://host/run1.root", &path, Some(12)).await {
+            Err(Error::LimitExceeded{ limit, .. }) => { assert_eq!(limit, 12); },
+            Err(err)                               => { panic!("Streaming failed for the wrong reason: {}", err); },
+            Ok(size)                               => { panic!("Streaming {} bytes with a limit of 12 succeeded", size); },
+        }
+
+        // Failing clients are reported with their stderr
+        match stream("sh", script("echo 'No such file' >&2; exit 54"), "root://host/run1.root", &path, None).await {
+            Err(Error::TransferFailure{ code, stderr, .. }) => { assert_eq!((code, stderr.as_str()), (Some(54), "No such file\n")); },
+            Err(err)                                        => { panic!("Streaming failed for the wrong reason: {}", err); },
+            Ok(size)                                        => { panic!("Streaming with a failing client succeeded ({} bytes)", size); },
+        }
+    }
+
+    /// Tests that invalid URLs and failed transfers are reported without leaving anything behind.
+    #[tokio::test]
+    async fn test_stage_errors() {
+        let dir: TempDir = TempDir::new().unwrap();
+        for url in [ "not a url", "root:run1.root", "root://eos.cern.ch/data/" ] {
+            match stage(Protocol::XRootD, url, &HashMap::new(), dir.path(), None).await {
+                Err(Error::UrlParseError{ .. }) | Err(Error::UrlWithoutHost{ .. }) | Err(Error::UrlWithoutFile{ .. }) => {},
+                Err(err)                                                                                         => { panic!("Staging '{}' failed for the wrong reason: {}", url, err); },
+                Ok((path, _))                                                                                    => { panic!("Staging '{}' succeeded (to '{}')", url, path.display()); },
+            }
+        }
+
+        // The transfer itself fails too, as there is no such host (or no client at all)
+        assert!(stage(Protocol::XRootD, "root://nonexistent.invalid//data/run1.root", &HashMap::new(), dir.path(), None).await.is_err());
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+}
+
+
+
+
+
+/***** ERRORS *****/
+/// Defines the errors that may occur when staging datasets from scientific storage systems.
+#[derive(Debug)]
+pub enum Error {
+    /// Failed to parse the URL of the dataset.
+    UrlParseError{ url: String, err: url::ParseError },
+    /// The URL of the dataset did not have a host.
+    UrlWithoutHost{ url: String },
+    /// The URL of the dataset did not point to a file.
+    UrlWithoutFile{ url: String },
+
+    /// Failed to create the staging directory.
+    DirCreateError{ path: PathBuf, err: std::io::Error },
+    /// Failed to create the staged file.
+    FileCreateError{ path: PathBuf, err: std::io::Error },
+    /// Failed to write to the staged file.
+    FileWriteError{ path: PathBuf, err: std::io::Error },
+
+    /// Failed to launch the client for the protocol.
+    SpawnError{ client: &'static str, err: std::io::Error },
+    /// Failed to read the stream of the client.
+    StreamError{ url: String, err: std::io::Error },
+    /// Failed to wait for the client to finish.
+    WaitError{ client: &'static str, err: std::io::Error },
+    /// The client failed to fetch the dataset.
+    TransferFailure{ client: &'static str, url: String, code: Option<i32>, stderr: String },
+    /// The dataset was larger than we may stage.
+    LimitExceeded{ url: String, limit: u64 },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use Error::*;
+        match self {
+            UrlParseError{ url, err } => write!(f, "Failed to parse dataset URL '{}': {}", url, err),
+            UrlWithoutHost{ url }     => write!(f, "Dataset URL '{}' does not have a host", url),
+            UrlWithoutFile{ url }     => write!(f, "Dataset URL '{}' does not point to a file", url),
+
+            DirCreateError{ path, err }  => write!(f, "Failed to create staging directory '{}': {}", path.display(), err),
+            FileCreateError{ path, err } => write!(f, "Failed to create staged file '{}': {}", path.display(), err),
+            FileWriteError{ path, err }  => write!(f, "Failed to write to staged file '{}': {}", path.display(), err),
+
+            SpawnError{ client, err }                    => write!(f, "Failed to launch '{}' (is it installed?): {}", client, err),
+            StreamError{ url, err }                      => write!(f, "Failed to read stream of '{}': {}", url, err),
+            WaitError{ client, err }                     => write!(f, "Failed to wait for '{}' to finish: {}", client, err),
+            TransferFailure{ client, url, code, stderr } => write!(f, "'{}' failed to fetch '{}' ({})\n\nstderr:\n{}\n\n", client, url, if let Some(code) = code { format!("exit code {}", code) } else { "killed".into() }, BlockFormatter::new(stderr)),
+            LimitExceeded{ url, limit }                  => write!(f, "Dataset '{}' does not fit in the {} bytes of scratch space of the task", url, limit),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Defines the protocols of the scientific storage systems we can stage datasets from.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Protocol {
+    /// The XRootD protocol, fetched with `xrdcp`.
+    XRootD,
+    /// The GridFTP protocol, fetched with `globus-url-copy`.
+    GridFtp,
+}
+
+impl Protocol {
+    /// Returns the protocol and URL of the given AccessKind, if it describes a dataset on a scientific storage system.
+    /// 
+    /// # Arguments
+    /// - `access`: The AccessKind to analyse.
+    /// 
+    /// # Returns
+    /// The Protocol and URL to stage the dataset with, or `None` if it doesn't live on a scientific storage system.
+    #[inline]
+    pub fn of(access: &AccessKind) -> Option<(Self, &str)> {
+        match access {
+            AccessKind::File{ .. }     => None,
+            AccessKind::XRootD{ url }  => Some((Self::XRootD, url)),
+            AccessKind::GridFtp{ url } => Some((Self::GridFtp, url)),
+            AccessKind::Url{ .. }      => None,
+        }
+    }
+
+    /// Returns the name of the command-line client used to fetch files over this protocol.
+    #[inline]
+    pub fn client(&self) -> &'static str {
+        use Protocol::*;
+        match self {
+            XRootD  => "xrdcp",
+            GridFtp => "globus-url-copy",
+        }
+    }
+
+    /// Returns a command that fetches the given URL over this protocol and writes it to stdout.
+    /// 
+    /// # Arguments
+    /// - `url`: The URL of the file to fetch.
+    /// 
+    /// # Returns
+    /// A new Command that only has to be spawned.
+    fn command(&self, url: &str) -> Command {
+        use Protocol::*;
+        let mut cmd: Command = Command::new(self.client());
+        match self {
+            XRootD  => { cmd.args([ "--nopbar", url, "-" ]); },
+            GridFtp => { cmd.args([ url, "file:///dev/stdout" ]); },
+        }
+        cmd
+    }
+}
+
+impl Display for Protocol {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use Protocol::*;
+        match self {
+            XRootD  => write!(f, "XRootD"),
+            GridFtp => write!(f, "GridFTP"),
+        }
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Streams the output of the given client to the given file.
+/// 
+/// # Arguments
+/// - `client`: The name of the client (used for debugging).
+/// - `cmd`: The command that launches the client.
+/// - `url`: The URL that the client fetches (used for debugging).
+/// - `path`: The path of the file to write to.
+/// - `limit`: If given, the maximum number of bytes that may be written.
+/// 
+/// # Returns
+/// The number of bytes written.
+/// 
+/// # Errors
+/// This function errors if we failed to run the client or write the file, or if the file exceeded the limit.
+async fn stream(client: &'static str, mut cmd: Command, url: &str, path: &Path, limit: Option<u64>) -> Result<u64, Error> {
+    // Launch the client, which is killed as soon as we bail out
+    cmd.stdin(Stdio::null());
+    cmd.stdout(Stdio::piped());
+    cmd.stderr(Stdio::piped());
+    cmd.kill_on_drop(true);
+    let mut child: Child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(err)  => { return Err(Error::SpawnError{ client, err }); },
+    };
+
+    // Stream its output to the file, keeping an eye on the limit
+    let mut stdout: ChildStdout = child.stdout.take().unwrap();
+    let mut handle: tfs::File   = match tfs::File::create(path).await {
+        Ok(handle) => handle,
+        Err(err)   => { return Err(Error::FileCreateError{ path: path.into(), err }); },
+    };
+    let mut buf   : [u8; 1024 * 16] = [0; 1024 * 16];
+    let mut total : u64             = 0;
+    loop {
+        // Read the next chunk
+        let bytes: usize = match stdout.read(&mut buf).await {
+            Ok(bytes) => bytes,
+            Err(err)  => { return Err(Error::StreamError{ url: url.into(), err }); },
+        };
+        if bytes == 0 { break; }
+
+        // Check if it still fits
+        total += bytes as u64;
+        if let Some(limit) = limit {
+            if total > limit { return Err(Error::LimitExceeded{ url: url.into(), limit }); }
+        }
+
+        // Write it
+        if let Err(err) = handle.write_all(&buf[..bytes]).await { return Err(Error::FileWriteError{ path: path.into(), err }); }
+    }
+    if let Err(err) = handle.flush().await { return Err(Error::FileWriteError{ path: path.into(), err }); }
+
+    // Wait for the client to report how it went
+    let output: Output = match child.wait_with_output().await {
+        Ok(output) => output,
+        Err(err)   => { return Err(Error::WaitError{ client, err }); },
+    };
+    if !output.status.success() {
+        return Err(Error::TransferFailure{ client, url: url.into(), code: output.status.code(), stderr: String::from_utf8_lossy(&output.stderr).into() });
+    }
+
+    // Done
+    Ok(total)
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Finds the proxy certificate to use for the given endpoint.
+/// 
+/// # Arguments
+/// - `proxies`: The X.509 proxy certificates to choose from, per domain.
+/// - `host`: The host name of the endpoint.
+/// 
+/// # Returns
+/// The path to the proxy of the most specific domain that the host is part of, or `None` if it isn't part of any.
+pub fn find_proxy<'p>(proxies: &'p HashMap<String, PathBuf>, host: &str) -> Option<&'p Path> {
+    proxies.iter()
+        .filter(|(domain, _)| {
+            let domain: &str = domain.trim_start_matches('.');
+            host == domain || (host.ends_with(domain) && host[..host.len() - domain.len()].ends_with('.'))
+        })
+        .max_by_key(|(domain, _)| domain.trim_start_matches('.').len())
+        .map(|(_, proxy)| proxy.as_path())
+}
+
+
+
+/// Stages a dataset from a scientific storage system by streaming it into a fresh directory.
+/// 
+/// The file is written as `<dir>/<random ID>/<file name>`, where the file name is the last segment of the URL. Only single files are supported.
+/// 
+/// # Arguments
+/// - `protocol`: The Protocol with which to fetch the dataset.
+/// - `url`: The URL of the dataset.
+/// - `proxies`: The X.509 proxy certificates to use per domain of the endpoint (see `find_proxy()`). If the endpoint is not part of any, the client falls back to its own defaults.
+/// - `dir`: The directory in which to create the staging directory.
+/// - `limit`: If given, the maximum number of bytes that may be staged. The transfer is aborted as soon as it exceeds this.
+/// 
+/// # Returns
+/// The path to the (new) directory that contains the staged file, together with the number of bytes staged. It is up to the caller to remove the directory when it's no longer needed.
+/// 
+/// # Errors
+/// This function errors if the URL was invalid, the transfer failed or exceeded the limit or we failed to write the file. In that case, nothing is left behind.
+pub async fn stage(protocol: Protocol, url: &str, proxies: &HashMap<String, PathBuf>, dir: impl AsRef<Path>, limit: Option<u64>) -> Result<(PathBuf, u64), Error> {
+    let dir: &Path = dir.as_ref();
+
+    // Find the host and the file name in the URL
+    let parsed: Url = match Url::parse(url) {
+        Ok(parsed) => parsed,
+        Err(err)   => { return Err(Error::UrlParseError{ url: url.into(), err }); },
+    };
+    let host: &str = match parsed.host_str() {
+        Some(host) => host,
+        None       => { return Err(Error::UrlWithoutHost{ url: url.into() }); },
+    };
+    let file: String = match parsed.path_segments().and_then(|mut segments| segments.next_back()).filter(|file| !file.is_empty()) {
+        Some(file) => file.into(),
+        None       => { return Err(Error::UrlWithoutFile{ url: url.into() }); },
+    };
+
+    // Prepare a fresh directory to stage to
+    let staged: PathBuf = dir.join(uuid::Uuid::new_v4().to_string());
+    if let Err(err) = tfs::create_dir_all(&staged).await { return Err(Error::DirCreateError{ path: staged, err }); }
+
+    // Prepare the client, with the proxy for this endpoint (if any)
+    let mut cmd: Command = protocol.command(url);
+    if let Some(proxy) = find_proxy(proxies, host) {
+        debug!("Using proxy '{}' for {} endpoint '{}'", proxy.display(), protocol, host);
+        cmd.env("X509_USER_PROXY", proxy);
+    }
+
+    // Stream the file in, cleaning up if that fails
+    let path: PathBuf = staged.join(file);
+    debug!("Staging '{}' to '{}' with {}...", url, path.display(), protocol.client());
+    match stream(protocol.client(), cmd, url, &path, limit).await {
+        Ok(size) => {
+            debug!("Staged {} bytes from '{}'", size, url);
+            Ok((staged, size))
+        },
+        Err(err) => {
+            if let Err(err) = tfs::remove_dir_all(&staged).await { debug!("Failed to remove staging directory '{}': {}", staged.display(), err); }
+            Err(err)
+        },
+    }
+}
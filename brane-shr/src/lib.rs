@@ -16,8 +16,15 @@
 // 
 
 // Declare some modules
+pub mod audit;
+pub mod cas;
 pub mod debug;
+pub mod disk;
+pub mod fetch;
 pub mod fs;
+pub mod grid;
 pub mod jobs;
 pub mod kafka;
+pub mod otel;
+pub mod telemetry;
 pub mod utilities;
@@ -0,0 +1,208 @@
+//  OTEL.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 10:55:07
+//  Last edited:
+//    16 Oct 2026, 13:08:00
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements the shared setup of distributed tracing (using
+//!   OpenTelemetry and Jaeger), and the propagation of trace contexts
+//!   between services, such that a single workflow execution may be
+//!   followed from the driver through the planner to the workers.
+// 
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::future::Future;
+
+use log::{debug, info, warn};
+use opentelemetry::global;
+use opentelemetry::propagation::TextMapPropagator as _;
+use opentelemetry::sdk::propagation::TraceContextPropagator;
+use rdkafka::message::{Headers as _, OwnedHeaders};
+use serde::{Deserialize, Serialize};
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt as _;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt as _;
+
+
+/***** ERRORS *****/
+/// Defines the errors that may occur when setting up tracing.
+#[derive(Debug)]
+pub enum TracingError {
+    /// Failed to create the pipeline that exports spans to Jaeger.
+    PipelineError{ endpoint: String, err: opentelemetry::trace::TraceError },
+    /// Failed to install the tracing subscriber.
+    SubscriberError{ err: tracing::subscriber::SetGlobalDefaultError },
+}
+
+impl Display for TracingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use TracingError::*;
+        match self {
+            PipelineError{ endpoint, err } => write!(f, "Failed to create pipeline to Jaeger agent '{}': {}", endpoint, err),
+            SubscriberError{ err }         => write!(f, "Failed to install tracing subscriber: {}", err),
+        }
+    }
+}
+
+impl Error for TracingError {}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines the tracing settings of a node, as stored in its `node.yml` file.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TracingConfig {
+    /// The address (as `<host>:<port>`) of the Jaeger agent to which spans are exported.
+    pub endpoint : String,
+}
+
+
+
+/// Sets up distributed tracing for the calling service.
+/// 
+/// Trace contexts are always propagated to other services, such that a trace is not broken by a service that does not export spans itself. Spans are only exported if tracing is configured.
+/// 
+/// # Arguments
+/// - `service`: The name of the calling service, as it will show up in Jaeger.
+/// - `config`: The TracingConfig of this node, if any.
+/// 
+/// # Errors
+/// This function errors if we failed to create the pipeline to Jaeger or to install the subscriber that feeds it.
+pub fn init(service: impl Into<String>, config: Option<&TracingConfig>) -> Result<(), TracingError> {
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    let config: &TracingConfig = match config {
+        Some(config) => config,
+        None         => { debug!("Tracing is disabled"); return Ok(()); },
+    };
+
+    // Build the pipeline to Jaeger
+    debug!("Exporting spans to Jaeger agent '{}'", config.endpoint);
+    let tracer = match opentelemetry_jaeger::new_agent_pipeline()
+        .with_endpoint(&config.endpoint)
+        .with_service_name(service)
+        .install_batch(opentelemetry::runtime::Tokio)
+    {
+        Ok(tracer) => tracer,
+        Err(err)   => { return Err(TracingError::PipelineError{ endpoint: config.endpoint.clone(), err }); },
+    };
+
+    // Feed it with the spans of the `tracing` crate (skipping the low-level ones of our dependencies)
+    let subscriber = tracing_subscriber::registry()
+        .with(LevelFilter::INFO)
+        .with(tracing_opentelemetry::layer().with_tracer(tracer));
+    match tracing::subscriber::set_global_default(subscriber) {
+        Ok(_)    => Ok(()),
+        Err(err) => Err(TracingError::SubscriberError{ err }),
+    }
+}
+
+/// Exports any spans that have not been exported yet. Should be called before the service exits.
+///
+/// Flushing blocks until the exporter is done, so it is done on a blocking thread to keep the runtime (which the exporter itself runs on) free.
+pub async fn shutdown() {
+    debug!("Flushing remaining spans...");
+    if let Err(err) = tokio::task::spawn_blocking(global::shutdown_tracer_provider).await { warn!("Failed to flush remaining spans: {}", err); }
+}
+
+/// Waits until the service is asked to stop, i.e., until it receives SIGINT or (on Unix) SIGTERM.
+///
+/// Services pass this to their servers as graceful shutdown signal, such that they get to call [`shutdown()`] before they exit.
+pub async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => { tokio::select! { _ = tokio::signal::ctrl_c() => {}, _ = terminate.recv() => {} } },
+            Err(err)          => { warn!("Failed to listen for SIGTERM: {} (only stopping on SIGINT)", err); let _ = tokio::signal::ctrl_c().await; },
+        }
+    }
+    #[cfg(not(unix))]
+    { let _ = tokio::signal::ctrl_c().await; }
+    info!("Received shutdown signal; stopping...");
+}
+
+/// Runs the given future until it completes or the service is asked to stop (see [`shutdown_signal()`]), whichever comes first.
+///
+/// # Arguments
+/// - `fut`: The future to run (typically a server that cannot be stopped gracefully on its own).
+///
+/// # Returns
+/// The output of the future, or `None` if the service was asked to stop first.
+pub async fn until_shutdown<F: Future>(fut: F) -> Option<F::Output> {
+    tokio::select! {
+        res = fut               => Some(res),
+        _   = shutdown_signal() => None,
+    }
+}
+
+
+
+/// Serializes the trace context of the given span, such that it may be sent to another service.
+/// 
+/// # Arguments
+/// - `span`: The Span to serialize the context of.
+/// 
+/// # Returns
+/// A map of (W3C Trace Context) headers that describe the span's context.
+pub fn context(span: &Span) -> HashMap<String, String> {
+    let mut carrier: HashMap<String, String> = HashMap::new();
+    global::get_text_map_propagator(|propagator| propagator.inject_context(&span.context(), &mut carrier));
+    carrier
+}
+
+/// Makes the given span a child of the span described by a trace context received from another service.
+/// 
+/// # Arguments
+/// - `span`: The Span to set the parent of.
+/// - `carrier`: The map of headers that describe the parent's context (see `context()`). If it does not describe any, the span is left as-is.
+pub fn set_parent(span: &Span, carrier: &HashMap<String, String>) {
+    if carrier.is_empty() { return; }
+    span.set_parent(global::get_text_map_propagator(|propagator| propagator.extract(carrier)));
+}
+
+
+
+/// Serializes the trace context of the given span as headers of a Kafka message.
+/// 
+/// # Arguments
+/// - `span`: The Span to serialize the context of.
+/// 
+/// # Returns
+/// A new set of OwnedHeaders that describe the span's context.
+pub fn kafka_headers(span: &Span) -> OwnedHeaders {
+    let carrier: HashMap<String, String> = context(span);
+    let mut headers: OwnedHeaders = OwnedHeaders::new_with_capacity(carrier.len());
+    for (key, value) in &carrier {
+        headers = headers.add(key, value.as_str());
+    }
+    headers
+}
+
+/// Deserializes the trace context in the headers of a Kafka message.
+/// 
+/// # Arguments
+/// - `headers`: The headers of the message, if it has any.
+/// 
+/// # Returns
+/// A map of headers that describe the context, to be given to `set_parent()`. Headers that are not valid UTF-8 are ignored.
+pub fn from_kafka_headers(headers: Option<&OwnedHeaders>) -> HashMap<String, String> {
+    let mut carrier: HashMap<String, String> = HashMap::new();
+    if let Some(headers) = headers {
+        for i in 0..headers.count() {
+            if let Some((key, value)) = headers.get(i) {
+                if let Ok(value) = std::str::from_utf8(value) { carrier.insert(key.into(), value.into()); }
+            }
+        }
+    }
+    carrier
+}
@@ -0,0 +1,318 @@
+//  TELEMETRY.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 09:39:28
+//  Last edited:
+//    16 Oct 2026, 09:39:28
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements the opt-in telemetry of a node, which periodically
+//!   reports anonymized usage counts (i.e., the number of workflows and
+//!   tasks run, and the version of the framework) to a configurable
+//!   endpoint.
+// 
+
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use log::{debug, warn};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead as _, BufReader, Read as _, Write as _};
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+    use std::time::Instant;
+    use super::*;
+
+
+    /// Returns a UsageReport to send in the tests.
+    fn usage() -> UsageReport {
+        UsageReport{ instance: "test".into(), node: "central".into(), version: "1.0.0".into(), runs: 3, tasks: 42 }
+    }
+
+    /// Serves a single request on the given listener with the given status line, and returns the body it received.
+    fn serve_once(listener: TcpListener, status: &'static str) -> thread::JoinHandle<String> {
+        thread::spawn(move || {
+            let (stream, _): (TcpStream, _) = listener.accept().unwrap();
+            let mut reader: BufReader<TcpStream> = BufReader::new(stream);
+
+            // Read the headers to find out how long the body is
+            let mut length: usize = 0;
+            loop {
+                let mut line: String = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line == "\r\n" { break; }
+                if let Some((key, value)) = line.split_once(':') {
+                    if key.eq_ignore_ascii_case("content-length") { length = value.trim().parse().unwrap(); }
+                }
+            }
+            let mut body: Vec<u8> = vec![ 0; length ];
+            reader.read_exact(&mut body).unwrap();
+
+            // Answer it
+            write!(reader.get_mut(), "HTTP/1.1 {}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n", status).unwrap();
+            String::from_utf8(body).unwrap()
+        })
+    }
+
+
+    /// Tests that reports are sent as JSON, and that refusals are reported.
+    #[tokio::test]
+    async fn test_report() {
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint: String = format!("http://{}/report", listener.local_addr().unwrap());
+        let server: thread::JoinHandle<String> = serve_once(listener, "204 No Content");
+        report(&endpoint, &usage()).await.unwrap();
+        let body: UsageReport = serde_json::from_str(&server.join().unwrap()).unwrap();
+        assert_eq!((body.instance.as_str(), body.node.as_str(), body.version.as_str(), body.runs, body.tasks), ("test", "central", "1.0.0", 3, 42));
+
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint: String = format!("http://{}/report", listener.local_addr().unwrap());
+        let server: thread::JoinHandle<String> = serve_once(listener, "503 Service Unavailable");
+        match report(&endpoint, &usage()).await {
+            Err(TelemetryError::RequestFailure{ code, .. }) => { assert_eq!(code, StatusCode::SERVICE_UNAVAILABLE); },
+            Err(err)                                        => { panic!("Report failed for the wrong reason: {}", err); },
+            Ok(_)                                           => { panic!("Refused report succeeded"); },
+        }
+        server.join().unwrap();
+    }
+
+    /// Tests that a collector that accepts the connection but never answers does not stall the reporter.
+    #[tokio::test]
+    async fn test_report_timeout() {
+        // The listener is never accepted from, but the kernel completes the connection anyway
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint: String = format!("http://{}/report", listener.local_addr().unwrap());
+
+        let start: Instant = Instant::now();
+        match report_within(&endpoint, &usage(), Duration::from_millis(100), Duration::from_millis(200)).await {
+            Err(TelemetryError::RequestError{ err, .. }) => { assert!(err.is_timeout(), "Report failed for the wrong reason: {}", err); },
+            Err(err)                                     => { panic!("Report failed for the wrong reason: {}", err); },
+            Ok(_)                                        => { panic!("Unanswered report succeeded"); },
+        }
+        assert!(start.elapsed() < Duration::from_secs(5));
+        drop(listener);
+    }
+}
+
+
+
+
+
+/***** CONSTANTS *****/
+/// The default number of seconds between two reports (one day).
+pub const DEFAULT_TELEMETRY_INTERVAL: u64 = 24 * 60 * 60;
+
+/// The time we wait for a connection to the telemetry endpoint.
+pub const TELEMETRY_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// The time we wait for the telemetry endpoint to accept a report, including connecting to it.
+pub const TELEMETRY_TIMEOUT: Duration = Duration::from_secs(30);
+
+
+
+
+
+/***** GLOBALS *****/
+/// The number of workflows run since the last report.
+static RUNS: AtomicU64 = AtomicU64::new(0);
+/// The number of tasks run since the last report.
+static TASKS: AtomicU64 = AtomicU64::new(0);
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Returns the default telemetry interval for serde.
+#[inline]
+fn default_interval() -> u64 { DEFAULT_TELEMETRY_INTERVAL }
+
+/// Sends a single report to the given endpoint, giving up if that takes too long.
+/// 
+/// # Arguments
+/// - `endpoint`: The address of the endpoint to POST the report to.
+/// - `usage`: The UsageReport to send.
+/// - `connect_timeout`: The time we wait for a connection to the endpoint.
+/// - `timeout`: The time we wait for the endpoint to accept the report, including connecting to it.
+/// 
+/// # Errors
+/// This function errors if we failed to reach the endpoint in time or if it did not accept the report.
+async fn report_within(endpoint: &str, usage: &UsageReport, connect_timeout: Duration, timeout: Duration) -> Result<(), TelemetryError> {
+    let client: reqwest::Client = match reqwest::Client::builder().connect_timeout(connect_timeout).timeout(timeout).build() {
+        Ok(client) => client,
+        Err(err)   => { return Err(TelemetryError::ClientCreateError{ err }); },
+    };
+    let res: reqwest::Response = match client.post(endpoint).json(usage).send().await {
+        Ok(res)  => res,
+        Err(err) => { return Err(TelemetryError::RequestError{ endpoint: endpoint.into(), err }); },
+    };
+    if !res.status().is_success() { return Err(TelemetryError::RequestFailure{ endpoint: endpoint.into(), code: res.status() }); }
+    Ok(())
+}
+
+
+
+
+
+/***** ERRORS *****/
+/// Defines the errors that may occur when reporting telemetry.
+#[derive(Debug)]
+pub enum TelemetryError {
+    /// Failed to create the client with which we send reports.
+    ClientCreateError{ err: reqwest::Error },
+    /// Failed to send the report.
+    RequestError{ endpoint: String, err: reqwest::Error },
+    /// The endpoint did not accept the report.
+    RequestFailure{ endpoint: String, code: StatusCode },
+}
+
+impl Display for TelemetryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use TelemetryError::*;
+        match self {
+            ClientCreateError{ err }         => write!(f, "Failed to create client for usage reports: {}", err),
+            RequestError{ endpoint, err }    => write!(f, "Failed to send usage report to '{}': {}", endpoint, err),
+            RequestFailure{ endpoint, code } => write!(f, "Usage report to '{}' failed with status code {} ({})", endpoint, code, code.canonical_reason().unwrap_or("???")),
+        }
+    }
+}
+
+impl Error for TelemetryError {}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines the telemetry settings of a node, as stored in its `node.yml` file.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TelemetryConfig {
+    /// Whether the node reports anything at all.
+    pub enabled  : bool,
+    /// The address of the endpoint to which reports are POSTed.
+    pub endpoint : String,
+    /// A random identifier for this node, such that reports of the same node may be correlated without revealing which node it is.
+    pub instance : String,
+    /// The number of seconds between two reports.
+    #[serde(default = "default_interval")]
+    pub interval : u64,
+}
+
+impl TelemetryConfig {
+    /// Constructor for a TelemetryConfig that is enabled and has a fresh, random instance identifier.
+    /// 
+    /// # Arguments
+    /// - `endpoint`: The address of the endpoint to which reports are POSTed.
+    /// 
+    /// # Returns
+    /// A new TelemetryConfig instance.
+    #[inline]
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Self {
+            enabled  : true,
+            endpoint : endpoint.into(),
+            instance : Uuid::new_v4().to_string(),
+            interval : DEFAULT_TELEMETRY_INTERVAL,
+        }
+    }
+}
+
+
+
+/// Defines a single report as it is sent to the telemetry endpoint. Contains nothing but counts and versions.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct UsageReport {
+    /// The random identifier of the reporting node.
+    pub instance : String,
+    /// The kind of node that reports (e.g., `central` or `worker`).
+    pub node     : String,
+    /// The version of the service that reports.
+    pub version  : String,
+    /// The number of workflows run since the last report.
+    pub runs     : u64,
+    /// The number of tasks run since the last report.
+    pub tasks    : u64,
+}
+
+
+
+/// Records that a workflow has been run, to be included in the next report.
+#[inline]
+pub fn record_run() { RUNS.fetch_add(1, Ordering::Relaxed); }
+
+/// Records that a task has been run, to be included in the next report.
+#[inline]
+pub fn record_task() { TASKS.fetch_add(1, Ordering::Relaxed); }
+
+
+
+/// Sends a single report to the given endpoint.
+/// 
+/// # Arguments
+/// - `endpoint`: The address of the endpoint to POST the report to.
+/// - `usage`: The UsageReport to send.
+/// 
+/// # Errors
+/// This function errors if we failed to reach the endpoint within `TELEMETRY_TIMEOUT` or if it did not accept the report.
+#[inline]
+pub async fn report(endpoint: impl AsRef<str>, usage: &UsageReport) -> Result<(), TelemetryError> {
+    report_within(endpoint.as_ref(), usage, TELEMETRY_CONNECT_TIMEOUT, TELEMETRY_TIMEOUT).await
+}
+
+/// Launches a background task that periodically reports the counts recorded with `record_run()` and `record_task()`.
+/// 
+/// Counts that could not be reported are kept for the next report. Failures are only logged, as telemetry should never get in the way of the node itself.
+/// 
+/// # Arguments
+/// - `config`: The TelemetryConfig of this node, if any. Nothing is launched if it is omitted or disabled.
+/// - `node`: The kind of node that reports (e.g., `central` or `worker`).
+/// - `version`: The version of the service that reports.
+/// 
+/// # Returns
+/// The handle of the launched task, or `None` if telemetry is disabled.
+pub fn spawn_reporter(config: Option<&TelemetryConfig>, node: impl Into<String>, version: impl Into<String>) -> Option<JoinHandle<()>> {
+    let config: TelemetryConfig = match config {
+        Some(config) if config.enabled => config.clone(),
+        _                              => { debug!("Telemetry is disabled"); return None; },
+    };
+    let node    : String = node.into();
+    let version : String = version.into();
+
+    debug!("Reporting anonymized usage statistics to '{}' every {} seconds", config.endpoint, config.interval);
+    Some(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(config.interval.max(1)));
+        // The first tick completes immediately, which we skip since we've got nothing to report yet
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+
+            // Collect the counts since the last report
+            let usage: UsageReport = UsageReport {
+                instance : config.instance.clone(),
+                node     : node.clone(),
+                version  : version.clone(),
+                runs     : RUNS.swap(0, Ordering::Relaxed),
+                tasks    : TASKS.swap(0, Ordering::Relaxed),
+            };
+
+            // Send them, restoring them on failure
+            if let Err(err) = report(&config.endpoint, &usage).await {
+                warn!("{}", err);
+                RUNS.fetch_add(usage.runs, Ordering::Relaxed);
+                TASKS.fetch_add(usage.tasks, Ordering::Relaxed);
+            }
+        }
+    }))
+}
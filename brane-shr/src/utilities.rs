@@ -14,7 +14,7 @@
 
 use std::fs::{self, DirEntry, ReadDir};
 use std::future::Future;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use log::{debug, warn};
 use regex::Regex;
@@ -59,6 +59,10 @@ mod tests {
 /***** TEST HELPERS *****/
 /// Defines the path of the tests folder.
 pub const TESTS_DIR: &str = "../tests";
+/// Defines the path of the folder with the golden outputs of the traversals.
+pub const GOLDEN_DIR: &str = "../tests/golden";
+/// Defines the environment variable that, when set, makes `assert_golden()` overwrite the golden outputs instead of comparing against them.
+pub const UPDATE_GOLDEN_ENV: &str = "BRANE_UPDATE_GOLDEN";
 
 
 
@@ -317,6 +321,98 @@ where
     }
 }
 
+/// Normalizes the output of a traversal such that it can be compared across runs.
+/// 
+/// Concretely, this replaces the random identifiers of intermediate results (e.g., `result_a_1b2c3d`) with a fixed placeholder and removes carriage returns.
+/// 
+/// # Arguments
+/// - `output`: The output to normalize.
+/// 
+/// # Returns
+/// The normalized output.
+fn normalize_golden(output: &str) -> String {
+    let re: Regex = Regex::new(r"\b(result_\w+)_[0-9a-f]{6}\b").unwrap();
+    re.replace_all(&output.replace('\r', ""), "${1}_<id>").into()
+}
+
+/// Computes a line-by-line diff between the given texts.
+/// 
+/// # Arguments
+/// - `expected`: The text we expected.
+/// - `got`: The text we got.
+/// 
+/// # Returns
+/// The diff, where every line is prefixed with `-` if it is only in `expected`, `+` if it is only in `got` or a space if it is in both.
+fn diff_lines(expected: &str, got: &str) -> String {
+    let expected : Vec<&str> = expected.lines().collect();
+    let got      : Vec<&str> = got.lines().collect();
+
+    // Compute the lengths of the longest common subsequences of all suffixes
+    let mut lcs: Vec<Vec<usize>> = vec![ vec![ 0; got.len() + 1 ]; expected.len() + 1 ];
+    for i in (0..expected.len()).rev() {
+        for j in (0..got.len()).rev() {
+            lcs[i][j] = if expected[i] == got[j] { lcs[i + 1][j + 1] + 1 } else { lcs[i + 1][j].max(lcs[i][j + 1]) };
+        }
+    }
+
+    // Walk them to find the diff
+    let mut diff: String = String::new();
+    let (mut i, mut j): (usize, usize) = (0, 0);
+    while i < expected.len() || j < got.len() {
+        if i < expected.len() && j < got.len() && expected[i] == got[j] {
+            diff.push_str(&format!("  {}\n", expected[i]));
+            i += 1;
+            j += 1;
+        } else if j < got.len() && (i == expected.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            diff.push_str(&format!("+ {}\n", got[j]));
+            j += 1;
+        } else {
+            diff.push_str(&format!("- {}\n", expected[i]));
+            i += 1;
+        }
+    }
+    diff
+}
+
+/// Compares the output of a traversal on a test file against its golden output in the `tests/golden` folder (see the constant defined in this function's source file).
+/// 
+/// The golden output of a file `tests/<path>.bs` for a traversal `<traversal>` lives at `tests/golden/<traversal>/<path>.txt`. If the environment variable in `UPDATE_GOLDEN_ENV` is set, or if there is no golden output yet, the given output is written as the new golden output instead.
+/// 
+/// # Arguments
+/// - `traversal`: The name of the traversal that produced the output (e.g., `resolve`).
+/// - `path`: The path of the test file that was compiled, as given by `test_on_dsl_files()`.
+/// - `output`: The output of the traversal on the test file.
+/// 
+/// # Panics
+/// This function panics if the output differs from the golden output, or if we failed to read or write the golden output.
+pub fn assert_golden(traversal: &str, path: impl AsRef<Path>, output: impl AsRef<[u8]>) {
+    let path: &Path = path.as_ref();
+
+    // Resolve where the golden output of this file lives
+    let rel: &Path = path.strip_prefix(TESTS_DIR).unwrap_or_else(|_| panic!("Test file '{}' is not in the tests directory '{}'", path.display(), TESTS_DIR));
+    let golden_path: PathBuf = PathBuf::from(GOLDEN_DIR).join(traversal).join(rel).with_extension("txt");
+    let output: String = normalize_golden(&String::from_utf8_lossy(output.as_ref()));
+
+    // Either write it (when asked to, or when there is none yet) or compare against it
+    let missing: bool = !golden_path.exists();
+    if missing || std::env::var_os(UPDATE_GOLDEN_ENV).is_some() {
+        if missing { println!("WARNING: No golden output of traversal '{}' for '{}' yet; recording it (review and commit '{}')", traversal, path.display(), golden_path.display()); }
+        println!("Writing golden output of traversal '{}' for '{}' to '{}'", traversal, path.display(), golden_path.display());
+        if let Some(parent) = golden_path.parent() {
+            if let Err(err) = fs::create_dir_all(parent) { panic!("Failed to create golden directory '{}': {}", parent.display(), err); }
+        }
+        if let Err(err) = fs::write(&golden_path, output) { panic!("Failed to write golden file '{}': {}", golden_path.display(), err); }
+        return;
+    }
+    let golden: String = match fs::read_to_string(&golden_path) {
+        Ok(golden) => normalize_golden(&golden),
+        Err(err)   => { panic!("Failed to read golden file '{}': {}", golden_path.display(), err); },
+    };
+    if golden != output {
+        panic!("Output of traversal '{}' for '{}' differs from golden file '{}' (run with {}=1 to update it):\n{}", traversal, path.display(), golden_path.display(), UPDATE_GOLDEN_ENV, diff_lines(&golden, &output));
+    }
+}
+
 
 
 
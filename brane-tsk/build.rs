@@ -9,13 +9,21 @@
 //    Yes
 // 
 //  Description:
-//!   Build script for the `brane-tsk` crate. Basically just compiles the
-//!   `.proto` file(s) to Rust.
+//!   Build script for the `brane-tsk` crate. Compiles the `.proto`
+//!   file(s) to Rust, and generates the typed client of `brane-api`.
 // 
 
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
 
 /***** ENTRYPOINT *****/
 fn main() -> Result<(), std::io::Error> {
     tonic_build::configure()
-        .compile(&["proto/driver.proto", "proto/job.proto"], &["proto"])
+        .compile(&["proto/driver.proto", "proto/job.proto"], &["proto"])?;
+
+    // Generate the client of the API (which shares our workspace version)
+    let out: PathBuf = PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR not set by Cargo"));
+    fs::write(out.join("api_client.rs"), specifications::openapi::client(env!("CARGO_PKG_VERSION")))
 }
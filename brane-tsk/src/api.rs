@@ -10,7 +10,9 @@
 // 
 //  Description:
 //!   Implements functions that we use to connect to the Brane API.
-//!   Concretely, it is used to retrieve package/data indices.
+//!   Concretely, it is used to retrieve package/data indices, and
+//!   implements the typed client generated from the API's OpenAPI
+//!   description (see `specifications::openapi`).
 // 
 
 use std::collections::HashMap;
@@ -18,17 +20,72 @@ use std::str::FromStr;
 
 use chrono::{DateTime, Utc};
 use graphql_client::{GraphQLQuery, Response};
-use reqwest::Client;
+use log::debug;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use reqwest::{Client, StatusCode};
+use reqwest::header::HeaderMap;
+use serde::de::DeserializeOwned;
 use uuid::Uuid;
 
 use specifications::common::{Function, Type};
 use specifications::data::{DataIndex, DataInfo};
+use specifications::openapi::API_VERSION_HEADER;
 use specifications::package::{PackageKind, PackageIndex, PackageInfo};
 use specifications::version::Version;
 
 pub use crate::errors::ApiError as Error;
 
 
+/***** UNIT TESTS *****/
+#[cfg(test)]
+mod tests {
+    use reqwest::header::{HeaderName, HeaderValue};
+
+    use super::*;
+
+    /// Returns headers that report the given API version.
+    fn headers(version: &str) -> HeaderMap {
+        let mut headers: HeaderMap = HeaderMap::new();
+        headers.insert(HeaderName::from_str(API_VERSION_HEADER).unwrap(), HeaderValue::from_str(version).unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_check_version() {
+        let local: Version = Version::from_str(API_VERSION).unwrap();
+
+        // APIs that do not report a version and APIs with the same (breaking) version are compatible
+        assert!(check_version("test", &HeaderMap::new()).is_ok());
+        assert!(check_version("test", &headers(API_VERSION)).is_ok());
+        assert!(check_version("test", &headers(&Version::new(local.major, local.minor, local.patch + 1).to_string())).is_ok());
+        if local.major > 0 { assert!(check_version("test", &headers(&Version::new(local.major, local.minor + 1, 0).to_string())).is_ok()); }
+
+        // Others are not
+        assert!(matches!(check_version("test", &headers(&Version::new(local.major + 1, 0, 0).to_string())), Err(Error::IncompatibleVersion{ .. })));
+        if local.major == 0 { assert!(matches!(check_version("test", &headers(&Version::new(0, local.minor + 1, 0).to_string())), Err(Error::IncompatibleVersion{ .. }))); }
+        assert!(matches!(check_version("test", &headers("not-a-version")), Err(Error::ApiVersionParseError{ .. })));
+    }
+
+    #[test]
+    fn test_encode_segment() {
+        assert_eq!(encode_segment("hello_world-1.0~"), "hello_world-1.0~");
+        assert_eq!(encode_segment("peer:site 1"), "peer%3Asite%201");
+        assert_eq!(encode_segment("../secrets?x=1#y"), "..%2Fsecrets%3Fx%3D1%23y");
+    }
+}
+
+
+
+
+
+/***** CONSTANTS *****/
+/// The characters that are percent-encoded in path segments, i.e., all but the unreserved ones (see RFC 3986).
+const SEGMENT: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~');
+
+
+
+
+
 /***** CUSTOM TYPES *****/
 /// Defines the DateTime in UTC-type that the GraphQLQuery needs (apparently).
 pub type DateTimeUtc = DateTime<Utc>;
@@ -37,6 +94,75 @@ pub type DateTimeUtc = DateTime<Utc>;
 
 
 
+/***** HELPER FUNCTIONS *****/
+/// Percent-encodes a parameter of the path of an operation, so that it is sent as a single path segment.
+///
+/// # Arguments
+/// - `segment`: The (raw) parameter to encode.
+///
+/// # Returns
+/// The encoded segment.
+#[inline]
+fn encode_segment(segment: &str) -> String { utf8_percent_encode(segment, SEGMENT).to_string() }
+
+/// Checks whether the API that sent the given response is compatible with this client.
+///
+/// APIs are compatible if they have the same major version (or, before 1.0.0, the same minor version). APIs that do not report their version are assumed to be compatible.
+///
+/// # Arguments
+/// - `address`: The address that the response came from (used for debugging).
+/// - `headers`: The headers of the response to check.
+///
+/// # Errors
+/// This function errors if the API reported a version that we could not parse or that is incompatible.
+fn check_version(address: &str, headers: &HeaderMap) -> Result<(), Error> {
+    // Get the version from the header
+    let raw: &str = match headers.get(API_VERSION_HEADER).and_then(|raw| raw.to_str().ok()) {
+        Some(raw) => raw,
+        None      => { debug!("'{}' did not report its API version; assuming it is compatible", address); return Ok(()); },
+    };
+    let remote: Version = match Version::from_str(raw) {
+        Ok(remote) => remote,
+        Err(err)   => { return Err(Error::ApiVersionParseError{ address: address.into(), raw: raw.into(), err }); },
+    };
+
+    // Compare it to ours (which we know is valid, since we generated it)
+    let local: Version = Version::from_str(API_VERSION).unwrap_or_else(|err| panic!("Failed to parse generated API version '{}': {}", API_VERSION, err));
+    if remote.major != local.major || (local.major == 0 && remote.minor != local.minor) {
+        return Err(Error::IncompatibleVersion{ address: address.into(), remote, local });
+    }
+    Ok(())
+}
+
+
+
+/// Sends a GET-request to the given address and returns the response if it has one of the given status codes and a compatible version.
+///
+/// # Arguments
+/// - `client`: The Client to send the request with.
+/// - `address`: The address to send the request to.
+/// - `accepted`: The status codes that the operation responds with a body to.
+///
+/// # Returns
+/// The response of the API.
+///
+/// # Errors
+/// This function errors if we failed to send the request, or if the API responded with another status code or an incompatible version.
+async fn get(client: &Client, address: &str, accepted: &[u16]) -> Result<reqwest::Response, Error> {
+    let res: reqwest::Response = match client.get(address).send().await {
+        Ok(res)  => res,
+        Err(err) => { return Err(Error::RequestError{ address: address.into(), err }); },
+    };
+    check_version(address, res.headers())?;
+    let code: StatusCode = res.status();
+    if !accepted.contains(&code.as_u16()) { return Err(Error::RequestFailure{ address: address.into(), code }); }
+    Ok(res)
+}
+
+
+
+
+
 /***** LIBRARY *****/
 /// Downloads the current package index from the Brane API service.
 /// 
@@ -48,7 +174,21 @@ pub type DateTimeUtc = DateTime<Utc>;
 /// 
 /// # Errors
 /// This function errors for many reasons, chief of which may be that the endpoint is unavailable or its response was ill-formed.
-pub async fn get_package_index(endpoint: impl AsRef<str>) -> Result<PackageIndex, Error> {
+#[inline]
+pub async fn get_package_index(endpoint: impl AsRef<str>) -> Result<PackageIndex, Error> { get_package_index_with(&Client::new(), endpoint).await }
+
+/// Downloads the current package index from the Brane API service using the given client (e.g., one that presents an identity over TLS).
+/// 
+/// # Arguments
+/// - `client`: The Client to send the request with.
+/// - `endpoint`: The endpoint to send the request to.
+/// 
+/// # Returns
+/// The PackageIndex that represents the packages currently known to the instance at the time of the call.
+/// 
+/// # Errors
+/// This function errors for many reasons, chief of which may be that the endpoint is unavailable or its response was ill-formed.
+pub async fn get_package_index_with(client: &Client, endpoint: impl AsRef<str>) -> Result<PackageIndex, Error> {
     // Load up the query
     #[derive(GraphQLQuery)]
     #[graphql(
@@ -61,8 +201,7 @@ pub async fn get_package_index(endpoint: impl AsRef<str>) -> Result<PackageIndex
     // Resolve &str-like to &str
     let endpoint: &str = endpoint.as_ref();
 
-    // Start preparing the client to send the GraphQL request
-    let client = Client::new();
+    // Start preparing the GraphQL request
     let variables = get_packages::Variables {};
     let graphql_query = GetPackages::build_query(variables);
 
@@ -71,6 +210,7 @@ pub async fn get_package_index(endpoint: impl AsRef<str>) -> Result<PackageIndex
         Ok(response) => response,
         Err(err)     => { return Err(Error::RequestError { address: endpoint.into(), err }); },
     };
+    check_version(endpoint, graphql_response.headers())?;
     let body: String = match graphql_response.text().await {
         Ok(body) => body,
         Err(err) => { return Err(Error::ResponseBodyError{ address: endpoint.into(), err }); },
@@ -128,39 +268,126 @@ pub async fn get_package_index(endpoint: impl AsRef<str>) -> Result<PackageIndex
 
 
 
-/// Downloads the current data index from the Brane API service.
-/// 
-/// # Arguments
-/// - `endpoint`: The endpoint to send the request to.
-/// 
-/// # Returns
-/// The DataIndex that represents the packages currently known to the instance at the time of the call.
-/// 
-/// # Errors
-/// This function errors for many reasons, chief of which may be that the endpoint is unavailable or its response was ill-formed.
-pub async fn get_data_index(endpoint: impl AsRef<str>) -> Result<DataIndex, Error> {
-    let endpoint: &str = endpoint.as_ref();
+/// Defines a client for the REST routes of the Brane API service.
+///
+/// Most of its methods are generated from `specifications::openapi` (one per GET-route); they check that the API is compatible with the version this client was generated for.
+#[derive(Clone, Debug)]
+pub struct ApiClient {
+    /// The client with which to send the requests.
+    client  : Client,
+    /// The address of the API, without a trailing slash (e.g., `http://brane-api:50051`).
+    address : String,
+}
 
-    // Send the reqwest
-    let res: reqwest::Response = match reqwest::get(endpoint).await {
-        Ok(res)  => res,
-        Err(err) => { return Err(Error::RequestError{ address: endpoint.into(), err }); },  
-    };
+impl ApiClient {
+    /// Constructor for the ApiClient.
+    ///
+    /// # Arguments
+    /// - `address`: The address of the API (e.g., `http://brane-api:50051`).
+    ///
+    /// # Returns
+    /// A new ApiClient that sends plain requests to the given address.
+    #[inline]
+    pub fn new(address: impl Into<String>) -> Self { Self::with_client(Client::new(), address) }
 
-    // Fetch the body
-    let body: String = match res.text().await {
-        Ok(body) => body,
-        Err(err) => { return Err(Error::ResponseBodyError{ address: endpoint.into(), err }); },
-    };
-    let datasets: HashMap<String, DataInfo> = match serde_json::from_str(&body) {
-        Ok(datasets) => datasets,
-        Err(err)     => { return Err(Error::ResponseJsonParseError { address: endpoint.into(), raw: body, err }); },
-    };
+    /// Constructor for the ApiClient that sends its requests with the given client (e.g., one that presents an identity over TLS).
+    ///
+    /// # Arguments
+    /// - `client`: The Client to send the requests with.
+    /// - `address`: The address of the API (e.g., `https://brane-api:50053`).
+    ///
+    /// # Returns
+    /// A new ApiClient that sends requests to the given address.
+    pub fn with_client(client: Client, address: impl Into<String>) -> Self {
+        let mut address: String = address.into();
+        while address.ends_with('/') { address.pop(); }
+        Self { client, address }
+    }
 
-    // Re-interpret the map as a vector, then wrap it in an index
-    let datasets: Vec<DataInfo> = datasets.into_iter().map(|(_, d)| d).collect();
-    match DataIndex::from_infos(datasets) {
-        Ok(index) => Ok(index),
-        Err(err)  => Err(Error::DataIndexError{ address: endpoint.into(), err }),  
+
+
+    /// Sends a GET-request to the given path and parses the response as JSON.
+    ///
+    /// # Arguments
+    /// - `path`: The path to send the request to (starting with a slash).
+    /// - `accepted`: The status codes that the operation responds with a body to.
+    ///
+    /// # Returns
+    /// The parsed body of the response.
+    ///
+    /// # Errors
+    /// This function errors if the request failed or the response was not (valid) JSON.
+    async fn fetch_json<T: DeserializeOwned>(&self, path: &str, accepted: &[u16]) -> Result<T, Error> {
+        let body: String = self.fetch_text(path, accepted).await?;
+        match serde_json::from_str(&body) {
+            Ok(body) => Ok(body),
+            Err(err) => Err(Error::ResponseJsonParseError{ address: format!("{}{}", self.address, path), raw: body, err }),
+        }
+    }
+
+    /// Sends a GET-request to the given path and returns the response as text.
+    ///
+    /// # Arguments
+    /// - `path`: The path to send the request to (starting with a slash).
+    /// - `accepted`: The status codes that the operation responds with a body to.
+    ///
+    /// # Returns
+    /// The body of the response.
+    ///
+    /// # Errors
+    /// This function errors if the request failed or the response was not valid UTF-8.
+    async fn fetch_text(&self, path: &str, accepted: &[u16]) -> Result<String, Error> {
+        let address: String = format!("{}{}", self.address, path);
+        let res: reqwest::Response = get(&self.client, &address, accepted).await?;
+        match res.text().await {
+            Ok(body) => Ok(body),
+            Err(err) => Err(Error::ResponseBodyError{ address, err }),
+        }
+    }
+
+
+
+    /// Downloads the current package index from the API.
+    ///
+    /// # Returns
+    /// The PackageIndex that represents the packages currently known to the instance at the time of the call.
+    ///
+    /// # Errors
+    /// This function errors if the API is unavailable or incompatible, or if its response was ill-formed.
+    #[inline]
+    pub async fn package_index(&self) -> Result<PackageIndex, Error> { get_package_index_with(&self.client, format!("{}/graphql", self.address)).await }
+
+    /// Downloads the current data index from the API.
+    ///
+    /// # Returns
+    /// The DataIndex that represents the datasets currently known to the instance at the time of the call.
+    ///
+    /// # Errors
+    /// This function errors if the API is unavailable or incompatible, or if its response was ill-formed.
+    pub async fn data_index(&self) -> Result<DataIndex, Error> {
+        let datasets: HashMap<String, DataInfo> = self.list_datasets().await?;
+        match DataIndex::from_infos(datasets.into_values().collect()) {
+            Ok(index) => Ok(index),
+            Err(err)  => Err(Error::DataIndexError{ address: format!("{}/data/info", self.address), err }),
+        }
     }
+
+    /// Downloads the names of the locations in the instance from the API.
+    ///
+    /// # Returns
+    /// The names of the locations currently known to the instance at the time of the call.
+    ///
+    /// # Errors
+    /// This function errors if the API is unavailable or incompatible, or if its response was ill-formed.
+    #[inline]
+    pub async fn locations(&self) -> Result<Vec<String>, Error> { Ok(self.list_registries().await?.into_keys().collect()) }
+
+
+
+    /// Returns the address of the API that this client sends its requests to.
+    #[inline]
+    pub fn address(&self) -> &str { &self.address }
 }
+
+// The methods for the individual routes (and `API_VERSION`) are generated by the build script
+include!(concat!(env!("OUT_DIR"), "/api_client.rs"));
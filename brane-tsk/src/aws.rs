@@ -0,0 +1,762 @@
+//  AWS.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 09:54:33
+//  Last edited:
+//    16 Oct 2026, 13:31:38
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements functions to run tasks on AWS, either as AWS Batch jobs
+//!   or (for small, requirement-free packages) as AWS Lambda functions.
+// 
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use aws_sdk_batch::model::{ContainerOverrides, ContainerProperties, JobDefinitionType, JobStatus as BatchStatus, JobTimeout, ResourceRequirement, ResourceType};
+use aws_sdk_lambda::types::Blob;
+use aws_sdk_s3::presigning::config::PresigningConfig;
+use aws_sdk_s3::types::ByteStream;
+use aws_types::region::Region;
+use bollard::ClientVersion;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::fs as tfs;
+use tokio::sync::Mutex;
+
+use brane_cfg::backend::{AwsCredentials, JobDefinitionSpec};
+use brane_shr::fs::{archive_async, unarchive_from_async};
+use specifications::container::{Image, VolumeBind};
+use specifications::package::Capability;
+
+use crate::docker::{self, ImageSource};
+pub use crate::errors::AwsError as Error;
+
+
+/***** CONSTANTS *****/
+/// The log group to which AWS Batch writes the output of jobs.
+pub const BATCH_LOG_GROUP: &str = "/aws/batch/job";
+
+/// The time between two polls of the status of a job.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The prefix of the keys of everything we put in the bucket.
+pub const BUCKET_PREFIX: &str = "brane";
+
+/// How long presigned URLs remain valid, which bounds how long a job may be queued and run. This is the maximum that S3 allows.
+pub const PRESIGN_EXPIRY: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Replaces every character that AWS does not accept in names with an underscore, and limits the result to 128 characters.
+/// 
+/// # Arguments
+/// - `name`: The name to sanitize.
+/// 
+/// # Returns
+/// The sanitized name.
+fn sanitize(name: impl AsRef<str>) -> String {
+    name.as_ref().chars().take(128).map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' }).collect()
+}
+
+/// Returns the URI of the given image in the configured registry.
+/// 
+/// # Arguments
+/// - `creds`: The AwsCredentials that define the registry.
+/// - `image`: The Image to find.
+/// 
+/// # Returns
+/// The URI as `<registry>/<name>:<version>`.
+fn image_uri(creds: &AwsCredentials, image: &Image) -> String {
+    format!("{}/{}:{}", creds.registry.trim_end_matches('/'), image.name, image.version.as_deref().unwrap_or("latest"))
+}
+
+/// Builds a single resource requirement for a job definition.
+#[inline]
+fn resource(kind: ResourceType, value: impl ToString) -> ResourceRequirement {
+    ResourceRequirement::builder().r#type(kind).value(value.to_string()).build()
+}
+
+/// Returns the credentials with which to push to the configured registry.
+/// 
+/// # Arguments
+/// - `clients`: The AwsClients to connect with.
+/// - `creds`: The AwsCredentials that define the registry.
+/// 
+/// # Returns
+/// The username and password for an Amazon ECR registry, or `None` if the registry is not part of ECR (in which case we push anonymously).
+/// 
+/// # Errors
+/// This function errors if ECR did not give us a (valid) token.
+async fn registry_credentials(clients: &AwsClients, creds: &AwsCredentials) -> Result<Option<(String, String)>, Error> {
+    if !creds.registry.contains(".dkr.ecr.") { return Ok(None); }
+
+    // ECR hands out tokens that encode `AWS:<password>`
+    let res = match clients.ecr.get_authorization_token().send().await {
+        Ok(res)  => res,
+        Err(err) => { return Err(Error::AuthorizationError{ registry: creds.registry.clone(), err: Box::new(err) }); },
+    };
+    let token: Option<String> = res.authorization_data()
+        .and_then(|data| data.first())
+        .and_then(|data| data.authorization_token())
+        .and_then(|token| base64::decode(token).ok())
+        .and_then(|token| String::from_utf8(token).ok());
+    match token.as_ref().and_then(|token| token.split_once(':')) {
+        Some((username, password)) => Ok(Some((username.into(), password.into()))),
+        None                       => Err(Error::IllegalAuthorizationToken{ registry: creds.registry.clone() }),
+    }
+}
+
+/// Presigns a request for the given object, so that a job can access it without credentials of its own.
+/// 
+/// # Arguments
+/// - `clients`: The AwsClients to connect with.
+/// - `creds`: The AwsCredentials that define the bucket.
+/// - `key`: The key of the object in the bucket.
+/// - `upload`: Whether to presign an upload (`PUT`) or a download (`GET`).
+/// 
+/// # Returns
+/// The presigned URL.
+/// 
+/// # Errors
+/// This function errors if we failed to sign the request.
+async fn presign(clients: &AwsClients, creds: &AwsCredentials, key: &str, upload: bool) -> Result<String, Error> {
+    let config: PresigningConfig = match PresigningConfig::expires_in(PRESIGN_EXPIRY) {
+        Ok(config) => config,
+        Err(err)   => { return Err(Error::PresignError{ key: key.into(), err: Box::new(err) }); },
+    };
+    let req = if upload {
+        match clients.s3.put_object().bucket(&creds.bucket).key(key).presigned(config).await {
+            Ok(req)  => req,
+            Err(err) => { return Err(Error::PresignError{ key: key.into(), err: Box::new(err) }); },
+        }
+    } else {
+        match clients.s3.get_object().bucket(&creds.bucket).key(key).presigned(config).await {
+            Ok(req)  => req,
+            Err(err) => { return Err(Error::PresignError{ key: key.into(), err: Box::new(err) }); },
+        }
+    };
+    Ok(req.uri().to_string())
+}
+
+/// Uploads the given file to the bucket.
+/// 
+/// # Arguments
+/// - `clients`: The AwsClients to connect with.
+/// - `creds`: The AwsCredentials that define the bucket.
+/// - `path`: The file to upload.
+/// - `key`: The key of the object to upload it as.
+/// 
+/// # Errors
+/// This function errors if we failed to read the file or S3 did not accept it.
+async fn upload(clients: &AwsClients, creds: &AwsCredentials, path: &Path, key: &str) -> Result<(), Error> {
+    debug!("Uploading '{}' to 's3://{}/{}'...", path.display(), creds.bucket, key);
+    let body: ByteStream = match ByteStream::from_path(path).await {
+        Ok(body) => body,
+        Err(err) => { return Err(Error::UploadError{ path: path.into(), bucket: creds.bucket.clone(), key: key.into(), err: Box::new(err) }); },
+    };
+    match clients.s3.put_object().bucket(&creds.bucket).key(key).body(body).send().await {
+        Ok(_)    => Ok(()),
+        Err(err) => Err(Error::UploadError{ path: path.into(), bucket: creds.bucket.clone(), key: key.into(), err: Box::new(err) }),
+    }
+}
+
+/// Starts downloading the given object from the bucket.
+/// 
+/// # Arguments
+/// - `clients`: The AwsClients to connect with.
+/// - `creds`: The AwsCredentials that define the bucket.
+/// - `key`: The key of the object to download.
+/// 
+/// # Returns
+/// The ByteStream with the contents of the object.
+/// 
+/// # Errors
+/// This function errors if S3 did not give us the object.
+async fn download(clients: &AwsClients, creds: &AwsCredentials, key: &str) -> Result<ByteStream, Error> {
+    debug!("Downloading 's3://{}/{}'...", creds.bucket, key);
+    match clients.s3.get_object().bucket(&creds.bucket).key(key).send().await {
+        Ok(res)  => Ok(res.body),
+        Err(err) => Err(Error::DownloadError{ bucket: creds.bucket.clone(), key: key.into(), err: Box::new(err) }),
+    }
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Bundles the clients for the AWS services that we use.
+#[derive(Clone, Debug)]
+pub struct AwsClients {
+    /// The client for AWS Batch.
+    pub batch  : aws_sdk_batch::Client,
+    /// The client for CloudWatch Logs, to read the output of Batch jobs.
+    pub logs   : aws_sdk_cloudwatchlogs::Client,
+    /// The client for AWS Lambda.
+    pub lambda : aws_sdk_lambda::Client,
+    /// The client for Amazon S3, to exchange data with jobs.
+    pub s3     : aws_sdk_s3::Client,
+    /// The client for Amazon ECR, to log into the registry.
+    pub ecr    : aws_sdk_ecr::Client,
+
+    /// The images that we have pushed to the registry already.
+    pushed : Arc<Mutex<HashSet<String>>>,
+}
+
+impl AwsClients {
+    /// Constructor for the AwsClients.
+    /// 
+    /// # Arguments
+    /// - `creds`: The AwsCredentials that determine the region and account to connect to.
+    /// 
+    /// # Returns
+    /// A new AwsClients instance.
+    pub async fn new(creds: &AwsCredentials) -> Self {
+        // Use the explicit keys if given, or else the default credential chain
+        let mut loader = aws_config::from_env().region(Region::new(creds.region.clone()));
+        if let (Some(id), Some(secret)) = (&creds.access_key_id, &creds.secret_access_key) {
+            loader = loader.credentials_provider(aws_credential_types::Credentials::from_keys(id, secret, None));
+        }
+        if let Some(endpoint) = &creds.endpoint { loader = loader.endpoint_url(endpoint); }
+        let config = loader.load().await;
+
+        // Custom endpoints typically do not resolve buckets as subdomains
+        let s3 = aws_sdk_s3::config::Builder::from(&config).force_path_style(creds.endpoint.is_some()).build();
+        Self {
+            batch  : aws_sdk_batch::Client::new(&config),
+            logs   : aws_sdk_cloudwatchlogs::Client::new(&config),
+            lambda : aws_sdk_lambda::Client::new(&config),
+            s3     : aws_sdk_s3::Client::from_conf(s3),
+            ecr    : aws_sdk_ecr::Client::new(&config),
+
+            pushed : Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+}
+
+
+
+/// Keeps the AwsClients around between tasks, so that they (and their connections and credentials) are reused.
+#[derive(Debug, Default)]
+pub struct AwsClientCache {
+    /// The clients per account, region and endpoint.
+    clients : Mutex<HashMap<(String, Option<String>, Option<String>, Option<String>), AwsClients>>,
+}
+
+impl AwsClientCache {
+    /// Constructor for the AwsClientCache that initializes it empty.
+    /// 
+    /// # Returns
+    /// A new AwsClientCache instance.
+    #[inline]
+    pub fn new() -> Self { Self::default() }
+
+    /// Returns the clients for the given credentials, creating them if we did not do so before.
+    /// 
+    /// # Arguments
+    /// - `creds`: The AwsCredentials that determine the region and account to connect to.
+    /// 
+    /// # Returns
+    /// The (shared) AwsClients for these credentials.
+    pub async fn get(&self, creds: &AwsCredentials) -> AwsClients {
+        let key = (creds.region.clone(), creds.endpoint.clone(), creds.access_key_id.clone(), creds.secret_access_key.clone());
+        let mut clients = self.clients.lock().await;
+        if let Some(clients) = clients.get(&key) { return clients.clone(); }
+        let new: AwsClients = AwsClients::new(creds).await;
+        clients.insert(key, new.clone());
+        new
+    }
+}
+
+
+
+/// Describes the state of a submitted AWS Batch job, as far as we are concerned.
+#[derive(Clone, Debug)]
+pub enum JobState {
+    /// The job is waiting for capacity.
+    Queued,
+    /// The job's container is running.
+    Running,
+    /// The job has stopped, either successfully or not.
+    Finished {
+        /// The exit code of the container, if it ever ran.
+        code       : Option<i32>,
+        /// The log stream that contains the container's output, if any.
+        log_stream : Option<String>,
+        /// The reason that AWS gives for the job stopping, if any.
+        reason     : Option<String>,
+    },
+}
+
+
+
+/// Describes how the data of a task is exchanged with AWS through the bucket.
+#[derive(Clone, Debug)]
+pub struct Staging {
+    /// The arguments that tell branelet where to download its inputs and upload its outputs.
+    pub args       : Vec<String>,
+    /// The directories on this node to extract the outputs of the task to, with the key of their archives.
+    pub outputs    : Vec<(PathBuf, String)>,
+    /// The key of the object to which branelet uploads the value returned by the task.
+    pub output_key : String,
+    /// The keys of every object that belongs to this task.
+    pub keys       : Vec<String>,
+}
+
+
+
+/// Defines the payload sent to Lambda functions.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LambdaRequest {
+    /// The arguments to pass to branelet.
+    pub args : Vec<String>,
+}
+
+/// Defines the response expected from Lambda functions.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LambdaResponse {
+    /// The exit code of branelet.
+    pub code   : i32,
+    /// Everything branelet wrote to stdout.
+    pub stdout : String,
+    /// Everything branelet wrote to stderr.
+    #[serde(default)]
+    pub stderr : String,
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Pushes the image of a package to the configured registry, so that AWS can run it.
+/// 
+/// Images are only pushed once per AwsClients (and thus, per worker).
+/// 
+/// # Arguments
+/// - `clients`: The AwsClients to connect with.
+/// - `creds`: The AwsCredentials that define the registry.
+/// - `image`: The Image to push.
+/// - `source`: Where to get the image from should it not be in the local Docker daemon.
+/// - `socket`: The path to the socket of the local Docker daemon.
+/// - `version`: The client version to connect to the local Docker daemon with.
+/// 
+/// # Errors
+/// This function errors if we failed to log into the registry or to push the image.
+pub async fn push_image(clients: &AwsClients, creds: &AwsCredentials, image: &Image, source: ImageSource, socket: impl AsRef<Path>, version: ClientVersion) -> Result<(), Error> {
+    let uri: String = image_uri(creds, image);
+    if clients.pushed.lock().await.contains(&uri) { return Ok(()); }
+
+    debug!("Pushing image '{}' to '{}'...", image, uri);
+    let credentials: Option<(String, String)> = registry_credentials(clients, creds).await?;
+    let target: String = format!("{}/{}", creds.registry.trim_end_matches('/'), image.name);
+    if let Err(err) = docker::push(image.clone(), source, target, credentials, socket, version).await {
+        return Err(Error::PushError{ image: image.clone(), registry: creds.registry.clone(), err });
+    }
+    clients.pushed.lock().await.insert(uri);
+    Ok(())
+}
+
+/// Uploads the datasets and intermediate results that a task needs to the bucket, and prepares the URLs through which it returns its results.
+/// 
+/// # Arguments
+/// - `clients`: The AwsClients to connect with.
+/// - `creds`: The AwsCredentials that define the bucket.
+/// - `name`: The name of the task, which is used (together with a random suffix) to prefix the keys of its objects.
+/// - `binds`: The VolumeBinds generated when preprocessing the task's arguments. Read-only binds are uploaded; writable binds are downloaded again by `collect()`.
+/// - `temp_dir`: A directory to keep archives of directories in while they are uploaded.
+/// 
+/// # Returns
+/// A Staging that describes the arguments to pass to branelet and what to download afterwards.
+/// 
+/// # Errors
+/// This function errors if we failed to upload anything or to sign the URLs.
+pub async fn stage(clients: &AwsClients, creds: &AwsCredentials, name: impl AsRef<str>, binds: &[VolumeBind], temp_dir: impl AsRef<Path>) -> Result<Staging, Error> {
+    let temp_dir : &Path  = temp_dir.as_ref();
+    let prefix   : String = format!("{}/{}-{}", BUCKET_PREFIX, sanitize(name), &uuid::Uuid::new_v4().to_string()[..6]);
+
+    let mut staging: Staging = Staging{ args: vec![], outputs: vec![], output_key: format!("{}/output", prefix), keys: vec![] };
+    for (i, bind) in binds.iter().enumerate() {
+        let key: String = format!("{}/{}", prefix, i);
+        staging.keys.push(key.clone());
+        let transfer = |url: String| format!("{}={}", bind.container.display(), url);
+
+        if !bind.options.is_empty() {
+            // Read-only binds are inputs; upload directories as archives and files as-is
+            if bind.host.is_dir() {
+                let tarball: PathBuf = temp_dir.join(format!("{}.tar.gz", key.replace('/', "_")));
+                if let Err(err) = archive_async(&bind.host, &tarball, true).await { return Err(Error::ArchiveError{ path: bind.host.clone(), err }); }
+                let res: Result<(), Error> = upload(clients, creds, &tarball, &key).await;
+                if let Err(err) = tfs::remove_file(&tarball).await { warn!("Failed to remove archive '{}': {}", tarball.display(), err); }
+                res?;
+                staging.args.extend([ "--stage-in".into(), transfer(presign(clients, creds, &key, false).await?) ]);
+            } else {
+                upload(clients, creds, &bind.host, &key).await?;
+                staging.args.extend([ "--stage-in-file".into(), transfer(presign(clients, creds, &key, false).await?) ]);
+            }
+        } else {
+            // Writable binds are outputs, which branelet uploads as archives
+            staging.args.extend([ "--stage-out".into(), transfer(presign(clients, creds, &key, true).await?) ]);
+            staging.outputs.push((bind.host.clone(), key));
+        }
+    }
+
+    // Finally, prepare where branelet should leave the return value
+    staging.keys.push(staging.output_key.clone());
+    staging.args.extend([ "--output-url".into(), presign(clients, creds, &staging.output_key, true).await? ]);
+    Ok(staging)
+}
+
+/// Downloads the outputs of a task that completed successfully, and returns the value it returned.
+/// 
+/// # Arguments
+/// - `clients`: The AwsClients to connect with.
+/// - `creds`: The AwsCredentials that define the bucket.
+/// - `staging`: The Staging returned by `stage()` for this task.
+/// 
+/// # Returns
+/// The (encoded) value returned by the task, as written by branelet.
+/// 
+/// # Errors
+/// This function errors if we failed to download (or extract) any of the outputs.
+pub async fn collect(clients: &AwsClients, creds: &AwsCredentials, staging: &Staging) -> Result<String, Error> {
+    for (path, key) in &staging.outputs {
+        // The directory was created empty before the task ran, but we need it gone to extract to it
+        if path.exists() {
+            if let Err(err) = tfs::remove_dir_all(path).await { return Err(Error::DirRemoveError{ path: path.clone(), err }); }
+        }
+        let body: ByteStream = download(clients, creds, key).await?;
+        if let Err(err) = unarchive_from_async(Box::pin(body.into_async_read()), format!("s3://{}/{}", creds.bucket, key), path).await {
+            return Err(Error::ExtractError{ path: path.clone(), err });
+        }
+    }
+
+    // Read the output itself
+    let body: ByteStream = download(clients, creds, &staging.output_key).await?;
+    let output: Vec<u8> = match body.collect().await {
+        Ok(output) => output.into_bytes().to_vec(),
+        Err(err)   => { return Err(Error::DownloadError{ bucket: creds.bucket.clone(), key: staging.output_key.clone(), err: Box::new(err) }); },
+    };
+    match String::from_utf8(output) {
+        Ok(output) => Ok(output),
+        Err(err)   => Err(Error::OutputUtf8Error{ key: staging.output_key.clone(), err }),
+    }
+}
+
+/// Removes all objects of a task from the bucket.
+/// 
+/// This is best-effort; failures are only logged, since they do not affect the task.
+/// 
+/// # Arguments
+/// - `clients`: The AwsClients to connect with.
+/// - `creds`: The AwsCredentials that define the bucket.
+/// - `staging`: The Staging returned by `stage()` for this task.
+pub async fn cleanup(clients: &AwsClients, creds: &AwsCredentials, staging: &Staging) {
+    for key in &staging.keys {
+        if let Err(err) = clients.s3.delete_object().bucket(&creds.bucket).key(key).send().await {
+            warn!("{}", Error::DeleteError{ bucket: creds.bucket.clone(), key: key.clone(), err: Box::new(err) });
+        }
+    }
+}
+
+
+
+/// Returns the Lambda function that runs the given package, if the task is eligible for Lambda at all.
+/// 
+/// # Arguments
+/// - `creds`: The AwsCredentials that list the Lambda functions.
+/// - `package`: The name of the task's package.
+/// - `requirements`: The requirements of the task. Only tasks without any are eligible.
+/// 
+/// # Returns
+/// The name (or ARN) of the function, or `None` if the task should run on AWS Batch instead.
+pub fn lambda_function<'c>(creds: &'c AwsCredentials, package: &str, requirements: &HashSet<Capability>) -> Option<&'c str> {
+    if !requirements.is_empty() { return None; }
+    creds.lambda.as_ref().and_then(|lambda| lambda.functions.get(package)).map(|function| function.as_str())
+}
+
+/// Maps the requirements of a task to the resources of the job definition that runs it.
+/// 
+/// # Arguments
+/// - `creds`: The AwsCredentials that define the job definitions per capability.
+/// - `requirements`: The requirements of the task.
+/// 
+/// # Returns
+/// The default job definition if there are no requirements, or else the maximum of the job definitions of each requirement. Resource requirements (CPUs, memory and GPUs) raise the reserved resources to at least what they ask for.
+/// 
+/// # Errors
+/// This function errors if one of the (non-resource) requirements has no job definition.
+pub fn job_definition_spec(creds: &AwsCredentials, requirements: &HashSet<Capability>) -> Result<JobDefinitionSpec, Error> {
+    // Iterate in a fixed order, so the roles are picked deterministically
+    let mut requirements: Vec<Capability> = requirements.iter().copied().collect();
+    requirements.sort_by_key(|req| format!("{:?}", req));
+
+    let mut result: Option<JobDefinitionSpec> = None;
+    let (mut min_vcpus, mut min_memory, mut min_gpus): (u32, u64, u32) = (0, 0, 0);
+    for req in requirements {
+        // Resources only raise the minimum (except GPUs, which also need a GPU-enabled job definition)
+        let req: Capability = match req {
+            Capability::MilliCpus(n) => { min_vcpus = min_vcpus.max(n.div_ceil(1000) as u32); continue; },
+            Capability::Memory(n)    => { min_memory = min_memory.max(n.div_ceil(1024 * 1024)); continue; },
+            Capability::Gpus(n)      => { min_gpus = min_gpus.max(n); Capability::CudaGpu },
+            Capability::CudaGpu      => Capability::CudaGpu,
+            // The architecture was already matched when planning
+            Capability::X86_64 | Capability::Aarch64 => { continue; },
+        };
+
+        let spec: &JobDefinitionSpec = match creds.capabilities.get(&req) {
+            Some(spec) => spec,
+            None       => { return Err(Error::UnsupportedRequirement{ requirement: req }); },
+        };
+        result = Some(match result {
+            Some(result) => JobDefinitionSpec {
+                vcpus          : result.vcpus.max(spec.vcpus),
+                memory         : result.memory.max(spec.memory),
+                gpus           : result.gpus.max(spec.gpus),
+                job_role       : result.job_role.or_else(|| spec.job_role.clone()),
+                execution_role : result.execution_role.or_else(|| spec.execution_role.clone()),
+            },
+            None => spec.clone(),
+        });
+    }
+    let mut result: JobDefinitionSpec = result.unwrap_or_else(|| creds.job_definition.clone());
+    result.vcpus  = result.vcpus.max(min_vcpus);
+    result.memory = result.memory.max(min_memory);
+    result.gpus   = result.gpus.max(min_gpus);
+    Ok(result)
+}
+
+/// Makes sure there is a job definition that runs the given image with the given resources, registering one if necessary.
+/// 
+/// The name of the job definition is derived from the image and the resources, so changing either results in a new one.
+/// 
+/// # Arguments
+/// - `clients`: The AwsClients to connect with.
+/// - `creds`: The AwsCredentials that define the registry to pull the image from.
+/// - `image`: The image of the package to run.
+/// - `spec`: The resources and roles of the job definition.
+/// 
+/// # Returns
+/// The name of the job definition.
+/// 
+/// # Errors
+/// This function errors if we failed to query or register the job definition.
+pub async fn ensure_job_definition(clients: &AwsClients, creds: &AwsCredentials, image: &Image, spec: &JobDefinitionSpec) -> Result<String, Error> {
+    let uri: String = image_uri(creds, image);
+
+    // Derive the name of the job definition
+    let mut hasher = Sha256::new();
+    hasher.update(uri.as_bytes());
+    hasher.update(format!("{:?}", spec).as_bytes());
+    let hash: String = format!("{:x}", hasher.finalize());
+    let name: String = sanitize(format!("brane-{}-{}-{}", image.name, image.version.as_deref().unwrap_or("latest"), &hash[..8]));
+
+    // See if it exists already
+    debug!("Looking for AWS Batch job definition '{}'...", name);
+    match clients.batch.describe_job_definitions().job_definition_name(&name).status("ACTIVE").send().await {
+        Ok(res)  => if !res.job_definitions().unwrap_or_default().is_empty() { return Ok(name); },
+        Err(err) => { return Err(Error::DescribeJobDefinitionsError{ name, err: Box::new(err) }); },
+    }
+
+    // Register it otherwise
+    debug!("Registering AWS Batch job definition '{}' for image '{}'...", name, uri);
+    let mut props = ContainerProperties::builder()
+        .image(uri)
+        .resource_requirements(resource(ResourceType::Vcpu, spec.vcpus))
+        .resource_requirements(resource(ResourceType::Memory, spec.memory))
+        .set_job_role_arn(spec.job_role.clone())
+        .set_execution_role_arn(spec.execution_role.clone());
+    if spec.gpus > 0 { props = props.resource_requirements(resource(ResourceType::Gpu, spec.gpus)); }
+    if let Err(err) = clients.batch.register_job_definition()
+        .job_definition_name(&name)
+        .r#type(JobDefinitionType::Container)
+        .container_properties(props.build())
+        .send().await
+    {
+        return Err(Error::RegisterJobDefinitionError{ name, err: Box::new(err) });
+    }
+
+    // Done
+    Ok(name)
+}
+
+/// Submits a job to the configured job queue.
+/// 
+/// # Arguments
+/// - `clients`: The AwsClients to connect with.
+/// - `creds`: The AwsCredentials that define the job queue.
+/// - `name`: The name of the job (typically the name of the task).
+/// - `definition`: The name of the job definition to use (see `ensure_job_definition()`).
+/// - `command`: The arguments to pass to branelet.
+/// - `timeout`: If given, the number of seconds after which AWS kills the job. Note that AWS does not accept anything below 60 seconds.
+/// 
+/// # Returns
+/// The identifier of the new job.
+/// 
+/// # Errors
+/// This function errors if AWS did not accept the job.
+pub async fn submit(clients: &AwsClients, creds: &AwsCredentials, name: impl AsRef<str>, definition: impl AsRef<str>, command: Vec<String>, timeout: Option<u64>) -> Result<String, Error> {
+    let name: String = sanitize(name);
+    debug!("Submitting job '{}' to AWS Batch job queue '{}'...", name, creds.job_queue);
+
+    let res = match clients.batch.submit_job()
+        .job_name(&name)
+        .job_queue(&creds.job_queue)
+        .job_definition(definition.as_ref())
+        .container_overrides(ContainerOverrides::builder().set_command(Some(command)).build())
+        .set_timeout(timeout.map(|timeout| JobTimeout::builder().attempt_duration_seconds(timeout.clamp(60, i32::MAX as u64) as i32).build()))
+        .send().await
+    {
+        Ok(res)  => res,
+        Err(err) => { return Err(Error::SubmitError{ name, queue: creds.job_queue.clone(), err: Box::new(err) }); },
+    };
+    match res.job_id() {
+        Some(id) => Ok(id.into()),
+        None     => Err(Error::MissingJobId{ name }),
+    }
+}
+
+/// Polls the state of the given job.
+/// 
+/// # Arguments
+/// - `clients`: The AwsClients to connect with.
+/// - `id`: The identifier of the job to poll.
+/// 
+/// # Returns
+/// The current JobState of the job.
+/// 
+/// # Errors
+/// This function errors if we failed to reach AWS or it does not know the job.
+pub async fn poll(clients: &AwsClients, id: impl AsRef<str>) -> Result<JobState, Error> {
+    let id: &str = id.as_ref();
+
+    let res = match clients.batch.describe_jobs().jobs(id).send().await {
+        Ok(res)  => res,
+        Err(err) => { return Err(Error::DescribeJobError{ id: id.into(), err: Box::new(err) }); },
+    };
+    let job = match res.jobs().and_then(|jobs| jobs.first()) {
+        Some(job) => job,
+        None      => { return Err(Error::UnknownJob{ id: id.into() }); },
+    };
+
+    Ok(match job.status() {
+        Some(BatchStatus::Running) => JobState::Running,
+        Some(BatchStatus::Succeeded) | Some(BatchStatus::Failed) => JobState::Finished {
+            code       : job.container().and_then(|container| container.exit_code()),
+            log_stream : job.container().and_then(|container| container.log_stream_name()).map(String::from),
+            reason     : job.status_reason().map(String::from),
+        },
+        _ => JobState::Queued,
+    })
+}
+
+/// Reads everything a job wrote to the given log stream.
+/// 
+/// Note that AWS Batch does not separate stdout from stderr, so this contains both.
+/// 
+/// # Arguments
+/// - `clients`: The AwsClients to connect with.
+/// - `stream`: The name of the log stream, as returned by `poll()`.
+/// 
+/// # Returns
+/// The contents of the log stream, one event per line.
+/// 
+/// # Errors
+/// This function errors if we failed to read the log stream.
+pub async fn logs(clients: &AwsClients, stream: impl AsRef<str>) -> Result<String, Error> {
+    let stream: &str = stream.as_ref();
+    debug!("Reading log stream '{}'...", stream);
+
+    let mut output : String         = String::new();
+    let mut token  : Option<String> = None;
+    loop {
+        let res = match clients.logs.get_log_events()
+            .log_group_name(BATCH_LOG_GROUP)
+            .log_stream_name(stream)
+            .start_from_head(true)
+            .set_next_token(token.clone())
+            .send().await
+        {
+            Ok(res)  => res,
+            Err(err) => { return Err(Error::LogsError{ stream: stream.into(), err: Box::new(err) }); },
+        };
+        for event in res.events().unwrap_or_default() {
+            if let Some(message) = event.message() {
+                output.push_str(message);
+                output.push('\n');
+            }
+        }
+
+        // AWS signals the end by returning the token we gave it
+        let next: Option<String> = res.next_forward_token().map(String::from);
+        if next.is_none() || next == token { break; }
+        token = next;
+    }
+    Ok(output)
+}
+
+/// Terminates the given job.
+/// 
+/// # Arguments
+/// - `clients`: The AwsClients to connect with.
+/// - `id`: The identifier of the job to terminate.
+/// - `reason`: The reason to give to AWS.
+/// 
+/// # Errors
+/// This function errors if AWS did not accept the request.
+pub async fn terminate(clients: &AwsClients, id: impl AsRef<str>, reason: impl Into<String>) -> Result<(), Error> {
+    let id: &str = id.as_ref();
+    match clients.batch.terminate_job().job_id(id).reason(reason).send().await {
+        Ok(_)    => Ok(()),
+        Err(err) => Err(Error::TerminateError{ id: id.into(), err: Box::new(err) }),
+    }
+}
+
+/// Runs a task by invoking the given Lambda function, which is expected to run branelet with the given arguments.
+/// 
+/// # Arguments
+/// - `clients`: The AwsClients to connect with.
+/// - `function`: The name (or ARN) of the function to invoke.
+/// - `command`: The arguments to pass to branelet.
+/// 
+/// # Returns
+/// The exit code, stdout and stderr of branelet.
+/// 
+/// # Errors
+/// This function errors if we failed to invoke the function or it crashed.
+pub async fn invoke(clients: &AwsClients, function: impl AsRef<str>, command: Vec<String>) -> Result<(i32, String, String), Error> {
+    let function: &str = function.as_ref();
+    debug!("Invoking AWS Lambda function '{}'...", function);
+
+    // Prepare the payload
+    let payload: Vec<u8> = match serde_json::to_vec(&LambdaRequest{ args: command }) {
+        Ok(payload) => payload,
+        Err(err)    => { return Err(Error::PayloadSerializeError{ function: function.into(), err }); },
+    };
+
+    // Call the function
+    let res = match clients.lambda.invoke().function_name(function).payload(Blob::new(payload)).send().await {
+        Ok(res)  => res,
+        Err(err) => { return Err(Error::InvokeError{ function: function.into(), err: Box::new(err) }); },
+    };
+    let raw: String = res.payload().map(|payload| String::from_utf8_lossy(payload.as_ref()).to_string()).unwrap_or_default();
+    if let Some(kind) = res.function_error() {
+        return Err(Error::FunctionError{ function: function.into(), kind: kind.into(), raw });
+    }
+
+    // Parse the response
+    match serde_json::from_str::<LambdaResponse>(&raw) {
+        Ok(res)  => Ok((res.code, res.stdout, res.stderr)),
+        Err(err) => Err(Error::ResponseParseError{ function: function.into(), raw, err }),
+    }
+}
@@ -19,31 +19,38 @@ use std::str::FromStr;
 
 use base64ct::{Base64, Encoding};
 use bollard::{API_DEFAULT_VERSION, ClientVersion, Docker};
+use bollard::auth::DockerCredentials;
 use bollard::container::{
-    Config, CreateContainerOptions, LogOutput, LogsOptions, RemoveContainerOptions, StartContainerOptions,
-    WaitContainerOptions
+    Config, CreateContainerOptions, KillContainerOptions, LogOutput, LogsOptions, NetworkingConfig, RemoveContainerOptions, StartContainerOptions,
+    Stats, StatsOptions, WaitContainerOptions
 };
-use bollard::image::{CreateImageOptions, ImportImageOptions, RemoveImageOptions, TagImageOptions};
+use bollard::image::{CreateImageOptions, ImportImageOptions, PushImageOptions, RemoveImageOptions, TagImageOptions};
 use bollard::models::{DeviceRequest, EndpointSettings, HostConfig};
+use bollard::network::CreateNetworkOptions;
 use enum_debug::EnumDebug as _;
 use futures_util::stream::TryStreamExt;
 use futures_util::StreamExt;
 use hyper::Body;
-use log::debug;
+use log::{debug, warn};
 use serde::{Deserialize, Serialize};
 use serde::de::{Deserializer, Visitor};
 use serde::ser::Serializer;
 use sha2::{Digest, Sha256};
 use tokio::fs::{self as tfs, File as TFile};
 use tokio::io::AsyncReadExt;
+use tokio::sync::mpsc::Sender;
 use tokio_tar::Archive;
 use tokio_util::codec::{BytesCodec, FramedRead};
 
 use brane_ast::ast::DataName;
+use brane_cfg::secrets::Secret;
 use brane_exe::FullValue;
+use brane_shr::fetch;
+use brane_shr::grid::{self, Protocol};
 use specifications::container::{Image, VolumeBind};
 use specifications::data::AccessKind;
 use specifications::package::Capability;
+use specifications::status::{OUTPUT_PREFIX, PROGRESS_PREFIX, ResourceUsage, SCRATCH_DIR, SCRATCH_EXHAUSTED_PREFIX, SCRATCH_LIMIT_ENV, TaskOutput, TaskProgress};
 
 pub use crate::errors::DockerError as Error;
 use crate::errors::ExecuteError;
@@ -236,6 +243,24 @@ impl From<&Network> for String {
 
 
 
+/// Keeps track of the input datasets staged by `stage_inputs()`, and removes them once it goes out of scope (i.e., when the task is done, however it ended).
+#[derive(Debug, Default)]
+pub struct StagedInputs {
+    /// The directories of the staged datasets.
+    dirs : Vec<PathBuf>,
+}
+
+impl Drop for StagedInputs {
+    fn drop(&mut self) {
+        for dir in &self.dirs {
+            debug!("Removing staged dataset '{}'...", dir.display());
+            if let Err(err) = std::fs::remove_dir_all(dir) { warn!("Failed to remove staged dataset '{}': {}", dir.display(), err); }
+        }
+    }
+}
+
+
+
 /// Collects information we need to perform a container call.
 #[derive(Clone, Debug)]
 pub struct ExecuteInfo {
@@ -250,10 +275,16 @@ pub struct ExecuteInfo {
     pub command      : Vec<String>,
     /// The extra mounts we want to add, if any (this includes any data folders).
     pub binds        : Vec<VolumeBind>,
-    /// The extra device requests we want to add, if any (e.g., GPUs).
+    /// The extra device requests and resource limits we want to add, if any (e.g., GPUs or a memory limit).
     pub capabilities : HashSet<Capability>,
     /// The netwok to connect the container to.
     pub network      : Network,
+    /// The names by which other containers on the same (custom) network can reach this container, if any.
+    pub aliases      : Vec<String>,
+    /// The size (in bytes) of the private scratch space to provision for the container, if any.
+    pub scratch      : Option<u64>,
+    /// Any secrets to pass to the container as environment variables, mapped by variable name.
+    pub env          : HashMap<String, Secret>,
 }
 
 impl ExecuteInfo {
@@ -265,13 +296,15 @@ impl ExecuteInfo {
     /// - `image_source`: The location where we import (as file) or create (from repo) the image from if it's not already loaded.
     /// - `command`: The command(s) to pass to Branelet.
     /// - `binds`: The extra mounts we want to add, if any (this includes any data folders).
-    /// - `capabilities`: The extra device requests we want to add, if any (e.g., GPUs).
+    /// - `capabilities`: The extra device requests and resource limits we want to add, if any (e.g., GPUs or a memory limit).
     /// - `network`: The netwok to connect the container to.
+    /// - `scratch`: The size (in bytes) of the private scratch space to provision for the container, if any.
     /// 
     /// # Returns
     /// A new ExecuteInfo instance populated with the given values.
     #[inline]
-    pub fn new(name: impl Into<String>, image: impl Into<Image>, image_source: impl Into<ImageSource>, command: Vec<String>, binds: Vec<VolumeBind>, capabilities: HashSet<Capability>, network: Network) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(name: impl Into<String>, image: impl Into<Image>, image_source: impl Into<ImageSource>, command: Vec<String>, binds: Vec<VolumeBind>, capabilities: HashSet<Capability>, network: Network, scratch: Option<u64>) -> Self {
         ExecuteInfo {
             name         : name.into(),
             image        : image.into(),
@@ -281,8 +314,39 @@ impl ExecuteInfo {
             binds,
             capabilities,
             network,
+            aliases : vec![],
+            scratch,
+            env     : HashMap::new(),
         }
     }
+
+    /// Sets the names by which other containers on the same network can reach this container.
+    /// 
+    /// Note that this only has effect if the container is connected to a custom network (i.e., [`Network::Custom`]).
+    /// 
+    /// # Arguments
+    /// - `aliases`: The names (i.e., DNS names) to give the container.
+    /// 
+    /// # Returns
+    /// The same ExecuteInfo, but now with the given aliases.
+    #[inline]
+    pub fn with_aliases(mut self, aliases: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.aliases = aliases.into_iter().map(|a| a.into()).collect();
+        self
+    }
+
+    /// Sets the secrets to pass to the container as environment variables.
+    /// 
+    /// # Arguments
+    /// - `env`: The secrets to pass, mapped by the name of the environment variable they become.
+    /// 
+    /// # Returns
+    /// The same ExecuteInfo, but now with the given environment.
+    #[inline]
+    pub fn with_env(mut self, env: HashMap<String, Secret>) -> Self {
+        self.env = env;
+        self
+    }
 }
 
 
@@ -329,6 +393,12 @@ fn preprocess_arg(data_dir: Option<impl AsRef<Path>>, results_dir: impl AsRef<Pa
             }
             return Ok(());
         },
+        FullValue::Map(values) => {
+            for (k, v) in values {
+                preprocess_arg(data_dir, results_dir, binds, input, format!("{}[\"{}\"]", name, k), v)?;
+            }
+            return Ok(());
+        },
 
         // Otherwise, we don't have to preprocess
         _ => { return Ok(()); },
@@ -365,6 +435,11 @@ fn preprocess_arg(data_dir: Option<impl AsRef<Path>>, results_dir: impl AsRef<Pa
             // Replace the argument
             *value = FullValue::String(dst_dir.to_string_lossy().to_string());
         },
+
+        AccessKind::XRootD { url } | AccessKind::GridFtp { url } | AccessKind::Url { url, .. } => {
+            // These should have been turned into files by `stage_inputs()`
+            return Err(ExecuteError::UnstagedData{ name: data_name, url: url.clone() });
+        },
     }
 
     // OK
@@ -389,20 +464,47 @@ async fn create_and_start_container(docker: &Docker, info: &ExecuteInfo) -> Resu
     let container_name: String = format!("{}-{}", info.name, &uuid::Uuid::new_v4().to_string()[..6]);
     let create_options = CreateContainerOptions { name: &container_name };
 
-    // Extract device requests from the capabilities
-    #[allow(clippy::unnecessary_filter_map)]
-    let device_requests: Vec<DeviceRequest> = info.capabilities.iter().filter_map(|c| match c {
-        // We need a CUDA-enabled GPU
-        Capability::CudaGpu => {
-            debug!("Requesting CUDA GPU");
-            Some(DeviceRequest {
-                driver       : Some("nvidia".into()),
-                count        : Some(1),
-                capabilities : Some(vec![ vec![ "gpu".into() ] ]),
-                ..Default::default()
-            })
-        },
-    }).collect();
+    // Extract the resource limits and device requests from the capabilities
+    let mut gpus: Option<u32>      = None;
+    let mut nano_cpus: Option<i64> = None;
+    let mut memory: Option<i64>    = None;
+    for c in &info.capabilities {
+        match c {
+            // We need (a number of) CUDA-enabled GPUs
+            Capability::CudaGpu => { gpus = Some(gpus.unwrap_or(1)); },
+            Capability::Gpus(n) => { gpus = Some(gpus.map(|g| g.max(*n)).unwrap_or(*n)); },
+            // The architecture was already matched when planning
+            Capability::X86_64 | Capability::Aarch64 => {},
+
+            // We need to limit the CPUs and memory
+            Capability::MilliCpus(n) => {
+                debug!("Limiting container to {} CPUs", *n as f64 / 1000.0);
+                nano_cpus = Some((*n as i64).saturating_mul(1_000_000));
+            },
+            Capability::Memory(n) => {
+                debug!("Limiting container to {} bytes of memory", n);
+                memory = Some(*n as i64);
+            },
+        }
+    }
+    let device_requests: Vec<DeviceRequest> = gpus.map(|n| {
+        debug!("Requesting {} CUDA GPU(s)", n);
+        vec![ DeviceRequest {
+            driver       : Some("nvidia".into()),
+            count        : Some(n as i64),
+            capabilities : Some(vec![ vec![ "gpu".into() ] ]),
+            ..Default::default()
+        } ]
+    }).unwrap_or_default();
+
+    // Provision the scratch space as an anonymous volume, which lives on the node's disk (instead of counting against the container's memory, like a tmpfs would) and is removed together with the container
+    // Docker cannot limit the size of such a volume on most filesystems, so we tell branelet to enforce it instead
+    let mut env: Vec<String> = info.env.iter().map(|(name, value)| format!("{}={}", name, value.expose())).collect();
+    let volumes: Option<HashMap<String, HashMap<(), ()>>> = info.scratch.map(|size| {
+        debug!("Provisioning {} bytes of scratch space at '{}'", size, SCRATCH_DIR);
+        env.push(format!("{}={}", SCRATCH_LIMIT_ENV, size));
+        HashMap::from([ (SCRATCH_DIR.into(), HashMap::new()) ])
+    });
 
     // Combine the properties in the execute info into a HostConfig
     let host_config = HostConfig {
@@ -410,14 +512,30 @@ async fn create_and_start_container(docker: &Docker, info: &ExecuteInfo) -> Resu
         network_mode    : Some(info.network.clone().into()),
         privileged      : Some(false),
         device_requests : Some(device_requests),
+        nano_cpus,
+        memory,
         ..Default::default()
     };
 
+    // Give the container its aliases on custom networks, so others on that network can find it by name
+    let networking_config: Option<NetworkingConfig<String>> = match &info.network {
+        Network::Custom(network) if !info.aliases.is_empty() => {
+            debug!("Giving container aliases {} on network '{}'", info.aliases.iter().map(|a| format!("'{}'", a)).collect::<Vec<String>>().join(", "), network);
+            Some(NetworkingConfig {
+                endpoints_config : HashMap::from([ (network.clone(), EndpointSettings{ aliases: Some(info.aliases.clone()), ..Default::default() }) ]),
+            })
+        },
+        _ => None,
+    };
+
     // Create the container confic
     let create_config = Config {
         image       : Some(info.image.name()),
         cmd         : Some(info.command.clone()),
+        env         : if !env.is_empty() { Some(env) } else { None },
         host_config : Some(host_config),
+        volumes,
+        networking_config,
         ..Default::default()
     };
 
@@ -529,9 +647,10 @@ async fn returncode_container(docker: &Docker, name: impl AsRef<str>) -> Result<
 async fn remove_container(docker: &Docker, name: impl AsRef<str>) -> Result<(), Error> {
     let name: &str = name.as_ref();
 
-    // Set the options
+    // Set the options (removing its anonymous volumes, i.e., its scratch space, as well)
     let remove_options = Some(RemoveContainerOptions {
         force: true,
+        v: true,
         ..Default::default()
     });
 
@@ -588,10 +707,11 @@ async fn import_image(docker: &Docker, image: impl Into<Image>, source: impl AsR
 /// - `docker`: An already connected local instance of Docker.
 /// - `image`: The image to pull.
 /// - `source`: The `repo/image[:tag]` to pull it from.
+/// - `credentials`: The credentials with which to log into the registry, if any.
 /// 
 /// # Errors
 /// This function errors if we failed to pull the image, e.g., the Docker engine did not know where to find it, or there was no internet.
-async fn pull_image(docker: &Docker, image: impl Into<Image>, source: impl Into<String>) -> Result<(), Error> {
+async fn pull_image(docker: &Docker, image: impl Into<Image>, source: impl Into<String>, credentials: Option<DockerCredentials>) -> Result<(), Error> {
     let image  : Image  = image.into();
     let source : String = source.into();
 
@@ -602,7 +722,7 @@ async fn pull_image(docker: &Docker, image: impl Into<Image>, source: impl Into<
     });
 
     // Try to create it
-    if let Err(err) = docker.create_image(options, None, None).try_collect::<Vec<_>>().await {
+    if let Err(err) = docker.create_image(options, None, credentials).try_collect::<Vec<_>>().await {
         return Err(Error::ImagePullError{ source, err });
     }
 
@@ -619,6 +739,65 @@ async fn pull_image(docker: &Docker, image: impl Into<Image>, source: impl Into<
 
 
 /***** AUXILLARY FUNCTIONS *****/
+/// Stages any input datasets that are not locally available to the local disk, after which they are accessible as files.
+/// 
+/// Datasets that live on scientific storage systems (i.e., XRootD or GridFTP endpoints) are streamed in for this task only, whereas datasets that are registered by URL are fetched into the cache (unless they already are) and kept there for later tasks.
+/// 
+/// This should be called before `preprocess_args()`, which only knows how to mount local files.
+/// 
+/// # Arguments
+/// - `input`: A list of input datasets & intermediate results to the current task. The access method of every staged dataset is replaced by an `AccessKind::File` pointing to the staged file.
+/// - `proxies`: The X.509 proxy certificates to use per domain of the endpoints.
+/// - `staging_dir`: The directory in which to stage the datasets.
+/// - `cache_dir`: The directory in which to cache the datasets registered by URL.
+/// - `limit`: If given, the maximum number of bytes that may be staged in total (i.e., the task's scratch space). Cached datasets don't count towards this.
+/// 
+/// # Returns
+/// A StagedInputs that removes the staged datasets again once it goes out of scope. Keep it around until the task is done.
+/// 
+/// # Errors
+/// This function errors if we failed to stage any of the datasets, in which case the ones that were already staged are removed again.
+pub async fn stage_inputs(input: &mut HashMap<DataName, AccessKind>, proxies: &HashMap<String, PathBuf>, staging_dir: impl AsRef<Path>, cache_dir: impl AsRef<Path>, limit: Option<u64>) -> Result<StagedInputs, ExecuteError> {
+    let staging_dir : &Path = staging_dir.as_ref();
+    let cache_dir   : &Path = cache_dir.as_ref();
+
+    // Stream the remote datasets in one-by-one, deducting them from the space we may use
+    let mut staged : StagedInputs = StagedInputs::default();
+    let mut left   : Option<u64>  = limit;
+    for (name, access) in input.iter_mut() {
+        // Fetch datasets registered by URL into the cache instead
+        if let AccessKind::Url { url, digest } = access {
+            debug!("Fetching {} '{}' from '{}' (if it isn't cached yet)...", name.variant(), name.name(), url);
+            let dir: PathBuf = match fetch::fetch(url, digest, cache_dir).await {
+                Ok(dir)  => dir,
+                Err(err) => { return Err(ExecuteError::FetchError{ name: name.clone(), err }); },
+            };
+            *access = AccessKind::File{ path: dir };
+            continue;
+        }
+
+        let (protocol, url): (Protocol, &str) = match Protocol::of(access) {
+            Some(remote) => remote,
+            None         => { continue; },
+        };
+        debug!("Staging {} '{}' from {} endpoint '{}'...", name.variant(), name.name(), protocol, url);
+
+        // Stage it
+        let (dir, size): (PathBuf, u64) = match grid::stage(protocol, url, proxies, staging_dir, left).await {
+            Ok(res)  => res,
+            Err(err) => { return Err(ExecuteError::StageError{ name: name.clone(), err }); },
+        };
+        if let Some(left) = &mut left { *left -= size; }
+
+        // Access it as a file from now on
+        *access = AccessKind::File{ path: dir.clone() };
+        staged.dirs.push(dir);
+    }
+
+    // Done
+    Ok(staged)
+}
+
 /// Helps any VM aiming to use Docker by preprocessing the given list of arguments and function result into a list of bindings (and resolving the the arguments while at it).
 /// 
 /// # Arguments
@@ -790,6 +969,19 @@ pub async fn hash_container(container_path: impl AsRef<Path>) -> Result<String,
     Ok(result)
 }
 
+/// Converts the SHA-256 digest of an image file in hexadecimal notation (e.g., its address in the content-addressed store) to the hash that `hash_container()` would compute for it.
+/// 
+/// # Arguments
+/// - `digest`: The hexadecimal digest of the image file.
+/// 
+/// # Returns
+/// The hash of the image, for use in policies.
+pub fn hash_from_digest(digest: impl AsRef<str>) -> String {
+    let digest: &str = digest.as_ref();
+    let bytes: Vec<u8> = (0..digest.len() / 2).filter_map(|i| digest.get(2 * i..2 * i + 2).and_then(|byte| u8::from_str_radix(byte, 16).ok())).collect();
+    Base64::encode_string(&bytes)
+}
+
 /// Tries to import/pull the given image if it does not exist in the local Docker instance.
 /// 
 /// # Arguments
@@ -826,7 +1018,7 @@ pub async fn ensure_image(docker: &Docker, image: impl Into<Image>, source: impl
 
         ImageSource::Registry(source) => {
             debug!(" > Pulling image '{}'...", image);
-            pull_image(docker, image, source).await
+            pull_image(docker, image, source, None).await
         },
     }
 }
@@ -866,6 +1058,165 @@ pub async fn launch(exec: ExecuteInfo, path: impl AsRef<Path>, version: ClientVe
     create_and_start_container(&docker, &exec).await
 }
 
+/// Creates an internal bridge network with the given name, unless it already exists.
+/// 
+/// Containers on the network can reach each other by name (and by any aliases they have on the network), but not the outside world nor the host's ports.
+/// 
+/// Note that this function makes its own connection to the local Docker daemon.
+/// 
+/// # Arguments
+/// - `name`: The name of the network to create.
+/// - `path`: The path to the Docker socket to connect to.
+/// - `version`: The version of the client we use to connect to the daemon.
+/// 
+/// # Errors
+/// This function errors if we failed to connect to Docker or to create the network.
+pub async fn ensure_network(name: impl AsRef<str>, path: impl AsRef<Path>, version: ClientVersion) -> Result<(), Error> {
+    let name : &str  = name.as_ref();
+    let path : &Path = path.as_ref();
+
+    // Connect to docker
+    let docker = match Docker::connect_with_unix(&path.to_string_lossy(), 900, &version) {
+        Ok(res)     => res,
+        Err(reason) => { return Err(Error::ConnectionError{ path: path.into(), version, err: reason }); }
+    };
+
+    // Create the network, which is fine if it already exists
+    debug!("Creating network '{}'...", name);
+    let options = CreateNetworkOptions {
+        name,
+        check_duplicate : true,
+        driver          : "bridge",
+        internal        : true,
+        labels          : HashMap::from([ ("brane.network", "run") ]),
+        ..Default::default()
+    };
+    match docker.create_network(options).await {
+        Ok(_)                                                                          => Ok(()),
+        Err(bollard::errors::Error::DockerResponseServerError{ status_code: 409, .. }) => { debug!("Network '{}' already exists", name); Ok(()) },
+        Err(reason)                                                                    => Err(Error::NetworkCreateError{ name: name.into(), err: reason }),
+    }
+}
+
+/// Removes the network with the given name, unless it is already gone.
+/// 
+/// Note that this function makes its own connection to the local Docker daemon.
+/// 
+/// # Arguments
+/// - `name`: The name of the network to remove.
+/// - `path`: The path to the Docker socket to connect to.
+/// - `version`: The version of the client we use to connect to the daemon.
+/// 
+/// # Errors
+/// This function errors if we failed to connect to Docker or to remove the network (e.g., because containers are still connected to it).
+pub async fn remove_network(name: impl AsRef<str>, path: impl AsRef<Path>, version: ClientVersion) -> Result<(), Error> {
+    let name : &str  = name.as_ref();
+    let path : &Path = path.as_ref();
+
+    // Connect to docker
+    let docker = match Docker::connect_with_unix(&path.to_string_lossy(), 900, &version) {
+        Ok(res)     => res,
+        Err(reason) => { return Err(Error::ConnectionError{ path: path.into(), version, err: reason }); }
+    };
+
+    // Remove the network
+    debug!("Removing network '{}'...", name);
+    match docker.remove_network(name).await {
+        Ok(_)                                                                          => Ok(()),
+        Err(bollard::errors::Error::DockerResponseServerError{ status_code: 404, .. }) => Ok(()),
+        Err(reason)                                                                    => Err(Error::NetworkRemoveError{ name: name.into(), err: reason }),
+    }
+}
+
+/// Pulls the given image from a remote (OCI) registry into the local Docker daemon, unless it is already there.
+/// 
+/// Note that this function makes its own connection to the local Docker daemon.
+/// 
+/// # Arguments
+/// - `image`: The Image to pull. If it has a digest, the pulled image must have it too.
+/// - `source`: The `registry/image[:tag]` to pull it from.
+/// - `credentials`: The username and password with which to log into the registry, if any.
+/// - `path`: The path to the Docker socket to connect to.
+/// - `version`: The version of the client we use to connect to the daemon.
+/// 
+/// # Errors
+/// This function errors if we failed to connect to Docker, failed to pull the image or if the pulled image is not the one we expected.
+pub async fn pull(image: impl Into<Image>, source: impl Into<String>, credentials: Option<(String, String)>, path: impl AsRef<Path>, version: ClientVersion) -> Result<(), Error> {
+    let image  : Image  = image.into();
+    let source : String = source.into();
+    let path   : &Path  = path.as_ref();
+
+    // Connect to docker
+    let docker = match Docker::connect_with_unix(&path.to_string_lossy(), 900, &version) {
+        Ok(res)     => res,
+        Err(reason) => { return Err(Error::ConnectionError{ path: path.into(), version, err: reason }); }
+    };
+
+    // Pull the image if we don't already have it
+    match docker.inspect_image(&image.docker().to_string()).await {
+        Ok(_) => {
+            debug!("Image '{}' already exists in Docker deamon.", image.docker());
+            return Ok(());
+        },
+        Err(bollard::errors::Error::DockerResponseServerError { status_code: 404, message: _ }) => {},
+        Err(err) => { return Err(Error::ImageInspectError{ image, err }); },
+    }
+    debug!(" > Pulling image '{}' from '{}'...", image, source);
+    let credentials: Option<DockerCredentials> = credentials.map(|(username, password)| DockerCredentials{ username: Some(username), password: Some(password), ..Default::default() });
+    pull_image(&docker, image.clone(), &source, credentials).await?;
+
+    // Make sure that we got what we asked for
+    match docker.inspect_image(&image.docker().to_string()).await {
+        Ok(_) => Ok(()),
+        Err(bollard::errors::Error::DockerResponseServerError { status_code: 404, message: _ }) => Err(Error::ImageDigestMismatch{ image, source }),
+        Err(err) => Err(Error::ImageInspectError{ image, err }),
+    }
+}
+
+/// Pushes the given image to a registry, importing or pulling it into the local Docker daemon first if necessary.
+/// 
+/// # Arguments
+/// - `image`: The Image to push.
+/// - `source`: Where to get the image from should it not be present in the daemon already.
+/// - `target`: The `registry/repository` to push it to. It is pushed with the version of the image as tag (or `latest` if it has none).
+/// - `credentials`: The username and password with which to log into the registry, if any.
+/// - `path`: The path to the Docker socket to connect to.
+/// - `version`: The version of the client we use to connect to the daemon.
+/// 
+/// # Errors
+/// This function errors if we failed to connect to Docker, failed to get the image or if the registry did not accept it.
+pub async fn push(image: impl Into<Image>, source: impl Into<ImageSource>, target: impl Into<String>, credentials: Option<(String, String)>, path: impl AsRef<Path>, version: ClientVersion) -> Result<(), Error> {
+    let image  : Image  = image.into();
+    let target : String = target.into();
+    let path   : &Path  = path.as_ref();
+
+    // Connect to docker
+    let docker = match Docker::connect_with_unix(&path.to_string_lossy(), 900, &version) {
+        Ok(res)     => res,
+        Err(reason) => { return Err(Error::ConnectionError{ path: path.into(), version, err: reason }); }
+    };
+
+    // Make sure we have the image, then tag it with the name it has in the registry
+    ensure_image(&docker, image.clone(), source).await?;
+    let tag: String = image.version.clone().unwrap_or_else(|| "latest".into());
+    if let Err(err) = docker.tag_image(&image.docker().to_string(), Some(TagImageOptions{ repo: target.clone(), tag: tag.clone() })).await {
+        return Err(Error::ImageTagError{ image, source: target, err });
+    }
+
+    // Push it
+    debug!(" > Pushing image '{}' to '{}:{}'...", image, target, tag);
+    let credentials: Option<DockerCredentials> = credentials.map(|(username, password)| DockerCredentials{ username: Some(username), password: Some(password), ..Default::default() });
+    let infos = match docker.push_image(&target, Some(PushImageOptions{ tag }), credentials).try_collect::<Vec<_>>().await {
+        Ok(infos) => infos,
+        Err(err)  => { return Err(Error::ImagePushError{ target, err }); },
+    };
+    // The daemon reports refusals by the registry as part of the progress instead
+    if let Some(reason) = infos.into_iter().find_map(|info| info.error) {
+        return Err(Error::ImagePushFailure{ target, reason });
+    }
+    Ok(())
+}
+
 /// Joins the container with the given name, i.e., waits for it to complete and returns its results.
 /// 
 /// # Arguments
@@ -893,6 +1244,39 @@ pub async fn join(name: impl AsRef<str>, path: impl AsRef<Path>, version: Client
     join_container(&docker, name, keep_container).await
 }
 
+/// Kills the container with the given name, e.g., because it ran out of time.
+/// 
+/// Note that this function makes its own connection to the local Docker daemon.
+/// 
+/// # Arguments
+/// - `name`: The name of the container to kill.
+/// - `path`: The path to the Docker socket to connect to.
+/// - `version`: The version of the client we use to connect to the daemon.
+/// - `keep_container`: If true, then will not remove the container after it has been killed. This is very useful for debugging.
+/// 
+/// # Errors
+/// This function errors if we failed to connect to Docker or to kill or remove the container.
+pub async fn kill(name: impl AsRef<str>, path: impl AsRef<Path>, version: ClientVersion, keep_container: bool) -> Result<(), Error> {
+    let name : &str  = name.as_ref();
+    let path : &Path = path.as_ref();
+
+    // Connect to docker
+    let docker = match Docker::connect_with_unix(&path.to_string_lossy(), 900, &version) {
+        Ok(res)     => res,
+        Err(reason) => { return Err(Error::ConnectionError{ path: path.into(), version, err: reason }); }
+    };
+
+    // Removing is forced, so that already kills it; otherwise, kill it explicitly
+    if keep_container {
+        if let Err(reason) = docker.kill_container(name, None::<KillContainerOptions<String>>).await {
+            return Err(Error::ContainerKillError{ name: name.into(), err: reason });
+        }
+        Ok(())
+    } else {
+        remove_container(&docker, name).await
+    }
+}
+
 /// Launches the given container and waits until its completed.
 /// 
 /// Note that this function makes its own connection to the local Docker daemon.
@@ -924,6 +1308,144 @@ pub async fn run_and_wait(exec: ExecuteInfo, keep_container: bool) -> Result<(i3
     join_container(&docker, &name, keep_container).await
 }
 
+/// Follows the logs of the given container while it runs, extracting any progress updates and lines of output that branelet reports on its behalf.
+/// 
+/// Note that this function makes its own connection to the local Docker daemon. It returns once the container's log stream closes (i.e., it stopped).
+/// 
+/// # Arguments
+/// - `name`: The name of the container to follow.
+/// - `path`: The path to the Docker socket to connect to.
+/// - `version`: The version of the client we use to connect to the daemon.
+/// - `tx`: The channel on which to send any progress updates we find.
+/// - `otx`: The channel on which to send any lines of output we find.
+/// 
+/// # Errors
+/// This function errors if we failed to connect to Docker or to read the container's logs.
+pub async fn follow_progress(name: impl AsRef<str>, path: impl AsRef<Path>, version: ClientVersion, tx: Sender<TaskProgress>, otx: Sender<TaskOutput>) -> Result<(), Error> {
+    let name : &str  = name.as_ref();
+    let path : &Path = path.as_ref();
+
+    // Connect to docker
+    let docker = match Docker::connect_with_unix(&path.to_string_lossy(), 900, &version) {
+        Ok(res)     => res,
+        Err(reason) => { return Err(Error::ConnectionError{ path: path.into(), version, err: reason }); }
+    };
+
+    // Open a stream of stderr, which is where branelet reports progress and output
+    let logs_options = Some(LogsOptions::<String> {
+        follow : true,
+        stderr : true,
+        ..Default::default()
+    });
+    let mut logs = docker.logs(name, logs_options);
+
+    // Parse whatever comes in line-by-line
+    let mut buffer: String = String::new();
+    while let Some(log_output) = logs.next().await {
+        let message = match log_output {
+            Ok(LogOutput::StdErr{ message }) => message,
+            Ok(_)                            => { continue; },
+            Err(reason)                      => { return Err(Error::LogsError{ name: name.into(), err: reason }); },
+        };
+        buffer.push_str(&String::from_utf8_lossy(&message));
+
+        // Handle any complete lines
+        while let Some(pos) = buffer.find('\n') {
+            let line: String = buffer.drain(..=pos).collect();
+            if let Some(sprogress) = line.trim_end().strip_prefix(PROGRESS_PREFIX) {
+                match serde_json::from_str::<TaskProgress>(sprogress) {
+                    Ok(progress) => {
+                        debug!("Container '{}' reported progress: {}", name, progress);
+                        // If the receiver is gone, nobody cares anymore about the progress
+                        if tx.send(progress).await.is_err() { return Ok(()); }
+                    },
+                    Err(err) => { debug!("Failed to parse progress update '{}' of container '{}': {}", sprogress, name, err); },
+                }
+            } else if let Some(soutput) = line.trim_end().strip_prefix(OUTPUT_PREFIX) {
+                match serde_json::from_str::<TaskOutput>(soutput) {
+                    // Like progress, output that nobody listens to anymore is simply dropped
+                    Ok(output) => { if otx.send(output).await.is_err() { return Ok(()); } },
+                    Err(err)   => { debug!("Failed to parse output '{}' of container '{}': {}", soutput, name, err); },
+                }
+            }
+        }
+    }
+
+    // Done
+    Ok(())
+}
+
+/// Samples the resource usage statistics of the given container while it runs, accumulating them into a summary of what it consumed.
+/// 
+/// Note that this function makes its own connection to the local Docker daemon. It returns once the container's statistics stream closes (i.e., it stopped). Containers that exit before the first sample is taken are reported as having used nothing.
+/// 
+/// # Arguments
+/// - `name`: The name of the container to follow.
+/// - `path`: The path to the Docker socket to connect to.
+/// - `version`: The version of the client we use to connect to the daemon.
+/// 
+/// # Returns
+/// The ResourceUsage of the container over its entire lifetime.
+/// 
+/// # Errors
+/// This function errors if we failed to connect to Docker or to read the container's statistics.
+pub async fn follow_stats(name: impl AsRef<str>, path: impl AsRef<Path>, version: ClientVersion) -> Result<ResourceUsage, Error> {
+    let name : &str  = name.as_ref();
+    let path : &Path = path.as_ref();
+
+    // Connect to docker
+    let docker = match Docker::connect_with_unix(&path.to_string_lossy(), 900, &version) {
+        Ok(res)     => res,
+        Err(reason) => { return Err(Error::ConnectionError{ path: path.into(), version, err: reason }); }
+    };
+
+    // Open a stream of statistics
+    let stats_options = Some(StatsOptions {
+        stream   : true,
+        one_shot : false,
+    });
+    let mut stats = docker.stats(name, stats_options);
+
+    // Accumulate the samples. Since all counters are cumulative (and the final sample of a stopped container may be zeroed), we simply keep the maximum of each
+    let mut usage: ResourceUsage = ResourceUsage::default();
+    while let Some(sample) = stats.next().await {
+        let sample: Stats = match sample {
+            Ok(sample)  => sample,
+            Err(reason) => { return Err(Error::StatsError{ name: name.into(), err: reason }); },
+        };
+
+        // CPU usage is reported in nanoseconds
+        usage.cpu_seconds = usage.cpu_seconds.max(sample.cpu_stats.cpu_usage.total_usage as f64 / 1_000_000_000.0);
+        // Not every cgroup version reports the maximum memory usage, so we also track the current usage ourselves
+        usage.peak_memory = usage.peak_memory.max(sample.memory_stats.max_usage.unwrap_or(0)).max(sample.memory_stats.usage.unwrap_or(0));
+        // The block I/O is reported per device and operation
+        if let Some(entries) = sample.blkio_stats.io_service_bytes_recursive {
+            let (read, write): (u64, u64) = entries.iter().fold((0, 0), |(read, write), entry| match entry.op.to_lowercase().as_str() {
+                "read"  => (read + entry.value, write),
+                "write" => (read, write + entry.value),
+                _       => (read, write),
+            });
+            usage.io_read  = usage.io_read.max(read);
+            usage.io_write = usage.io_write.max(write);
+        }
+    }
+
+    // Done
+    debug!("Container '{}' used {:?}", name, usage);
+    Ok(usage)
+}
+
+/// Checks the stderr of a finished container for branelet's note that the task ran out of scratch space.
+///
+/// # Arguments
+/// - `stderr`: The stderr of the container, as returned by `join()`.
+///
+/// # Returns
+/// The limit (in bytes) that the task hit if it exhausted its scratch space, or `None` otherwise.
+pub fn scratch_exhausted(stderr: impl AsRef<str>) -> Option<u64> {
+    stderr.as_ref().lines().rev().find_map(|line| line.trim_end().strip_prefix(SCRATCH_EXHAUSTED_PREFIX).and_then(|limit| u64::from_str(limit.trim()).ok()))
+}
+
 /// Tries to return the (IP-)address of the container with the given name.
 /// 
 /// Note that this function makes a separate connection to the local Docker instance.
@@ -34,6 +34,69 @@ use specifications::version::Version;
 use crate::grpc::{ExecuteReply, TaskReply, TaskStatus};
 
 
+/***** UNIT TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    #[test]
+    fn test_from_exit_code() {
+        // Every documented exit code, plus some that are not (and should thus be reported as-is)
+        for (code, expected) in [ (137, "OutOfMemory"), (139, "SegmentationFault"), (1, "ExternalCallFailed"), (2, "ExternalCallFailed"), (42, "ExternalCallFailed"), (-1, "ExternalCallFailed") ] {
+            let err: ExecuteError = ExecuteError::from_exit_code("test_task", "test_image:1.0.0", code, "some stdout", "some stderr");
+            match (&err, expected) {
+                (ExecuteError::OutOfMemory{ name, image, stdout, stderr }, "OutOfMemory") | (ExecuteError::SegmentationFault{ name, image, stdout, stderr }, "SegmentationFault") => {
+                    assert_eq!(name, "test_task");
+                    assert_eq!(image.to_string(), Image::from("test_image:1.0.0").to_string());
+                    assert_eq!((stdout.as_str(), stderr.as_str()), ("some stdout", "some stderr"));
+                },
+                (ExecuteError::ExternalCallFailed{ name, code: got, stdout, stderr, .. }, "ExternalCallFailed") => {
+                    assert_eq!(name, "test_task");
+                    assert_eq!(*got, code);
+                    assert_eq!((stdout.as_str(), stderr.as_str()), ("some stdout", "some stderr"));
+                },
+                (err, expected) => { panic!("Exit code {} was classified as {:?} instead of {}", code, err, expected); },
+            }
+        }
+    }
+
+    #[test]
+    fn test_is_transient() {
+        // Failures of the package code are worth another try...
+        assert!(ExecuteError::from_exit_code("test_task", "test_image:1.0.0", 1, "", "").is_transient());
+        assert!(ExecuteError::from_exit_code("test_task", "test_image:1.0.0", 137, "", "").is_transient());
+        assert!(ExecuteError::Timeout{ name: "test_task".into(), image: "test_image:1.0.0".into(), timeout: 60 }.is_transient());
+        // ...but crashes and exhausted quota are not
+        assert!(!ExecuteError::from_exit_code("test_task", "test_image:1.0.0", 139, "", "").is_transient());
+        assert!(!ExecuteError::ScratchExhausted{ name: "test_task".into(), image: "test_image:1.0.0".into(), limit: 1024 }.is_transient());
+
+        // Neither are things that are not there or not allowed
+        assert!(!ExecuteError::UnknownPackage{ name: "test".into(), version: Version::new(1, 0, 0) }.is_transient());
+        assert!(!ExecuteError::AuthorizationFailure{ checker: Address::hostname("checker", 50053) }.is_transient());
+        assert!(!ExecuteError::UnknownSecret{ name: "test_task".into(), secret: "token".into() }.is_transient());
+
+        // Servers may recover, but not from requests that are wrong
+        let failure = |code: StatusCode| -> ExecuteError { ExecuteError::DownloadRequestFailure{ address: "https://registry/image".into(), code, message: None } };
+        assert!(failure(StatusCode::SERVICE_UNAVAILABLE).is_transient());
+        assert!(failure(StatusCode::TOO_MANY_REQUESTS).is_transient());
+        assert!(!failure(StatusCode::NOT_FOUND).is_transient());
+
+        // Remote failures are judged by the status the worker reported
+        let remote = |status: TaskStatus| -> ExecuteError { ExecuteError::ExecuteError{ endpoint: Address::hostname("worker", 50052), name: "test_task".into(), status, err: String::new() } };
+        for status in [ TaskStatus::CreationFailed, TaskStatus::StartingFailed, TaskStatus::CompletionFailed, TaskStatus::Failed, TaskStatus::TimedOut ] {
+            assert!(remote(status).is_transient(), "Status {:?} should be transient", status);
+        }
+        for status in [ TaskStatus::Denied, TaskStatus::InitializationFailed, TaskStatus::Stopped, TaskStatus::DecodingFailed, TaskStatus::ScratchExhausted ] {
+            assert!(!remote(status).is_transient(), "Status {:?} should not be transient", status);
+        }
+    }
+}
+
+
+
+
+
 /***** LIBRARY *****/
 /// Defines a kind of combination of all the possible errors that may occur in the process.
 #[derive(Debug)]
@@ -78,6 +141,16 @@ pub enum PlanError {
     RequestParseError{ address: String, raw: String, err: serde_json::Error },
     /// The planned domain does not support the task.
     UnsupportedCapabilities{ task: String, loc: String, expected: HashSet<Capability>, got: HashSet<Capability> },
+    /// The task was planned on a location that is not part of this instance (e.g., one hosted by a peer instance).
+    ForeignLocation{ task: String, loc: String },
+    /// None of the locations that the user allows for a task are part of this instance and support its capabilities.
+    NoCandidateLocations{ task: String },
+    /// The task was pinned to a location where (some of) its inputs may not be processed.
+    ResidencyViolation{ task: String, loc: String, allowed: Vec<String> },
+    /// The configured planning strategy could not choose a location for a task.
+    StrategyFailed{ task: String, strategy: &'static str, reason: String },
+    /// Failed to parse the registry address of a location returned by the API service.
+    RegistryParseError{ address: String, raw: String, err: brane_cfg::errors::AddressParseError },
     /// The given dataset was unknown to us.
     UnknownDataset{ name: String },
     /// The given intermediate result was unknown to us.
@@ -88,6 +161,10 @@ pub enum PlanError {
     DatasetUnavailable{ name: String, locs: Vec<String> },
     /// We can't access an intermediate result in the local instance.
     IntermediateResultUnavailable{ name: String, locs: Vec<String> },
+    /// Failed to fetch the package index.
+    PackageIndexError{ address: String, err: ApiError },
+    /// One or more tasks cannot be scheduled on any location. Every task is listed with the reasons why each possible location was rejected.
+    UnschedulableTasks{ tasks: Vec<(String, Vec<String>)> },
 
     // Instance-only
     /// Failed to encode the planning update to send.
@@ -103,6 +180,10 @@ pub enum PlanError {
     PlanningFailed{ correlation_id: String, reason: Option<String> },
     /// The planner errored for some reason. This is different from a failure in that this indicates bad configuration or some service being down.
     PlanningError{ correlation_id: String, err: String },
+    /// Failed to connect to the gRPC service of the planner.
+    GrpcConnectError{ endpoint: Address, err: tonic::transport::Error },
+    /// Failed to send a workflow to the gRPC service of the planner.
+    GrpcRequestError{ correlation_id: String, endpoint: Address, err: tonic::Status },
 
     /// The planner failed to ensure certain topics existed.
     KafkaTopicError{ brokers: String, topics: Vec<String>, err: brane_shr::kafka::Error },
@@ -130,19 +211,28 @@ impl Display for PlanError {
             RequestBodyError{ address, err }                    => write!(f, "Failed to get the body of response from '{}' as UTF-8 text: {}", address, err),
             RequestParseError{ address, raw, err }              => write!(f, "Failed to parse response '{}' from '{}' as valid JSON: {}", raw, address, err),
             UnsupportedCapabilities{ task, loc, expected, got } => write!(f, "Location '{}' only supports capabilities {:?}, whereas task '{}' requires capabilities {:?}", loc, got, task, expected),
+            ForeignLocation{ task, loc }                        => write!(f, "Cannot plan task '{}' on location '{}', as it is not part of this instance", task, loc),
+            NoCandidateLocations{ task }                        => write!(f, "None of the locations allowed for task '{}' are part of this instance and support its capabilities (and may process its inputs)", task),
+            ResidencyViolation{ task, loc, allowed }            => write!(f, "Cannot plan task '{}' on location '{}', as its inputs may only be processed at {}", task, loc, allowed.iter().map(|l| format!("'{}'", l)).collect::<Vec<String>>().join(", ")),
+            StrategyFailed{ task, strategy, reason }            => write!(f, "Planning strategy '{}' failed to choose a location for task '{}': {}", strategy, task, reason),
+            RegistryParseError{ address, raw, err }             => write!(f, "Failed to parse registry address '{}' received from '{}': {}", raw, address, err),
             UnknownDataset{ name }                              => write!(f, "Unknown dataset '{}'", name),
             UnknownIntermediateResult{ name }                   => write!(f, "Unknown intermediate result '{}'", name),
             DataPlanError{ err }                                => write!(f, "Failed to plan dataset: {}", err),
             DatasetUnavailable{ name, locs }                    => write!(f, "Dataset '{}' is unavailable{}", name, if !locs.is_empty() { format!("; however, locations {} do (try to get download permission to those datasets)", locs.iter().map(|l| format!("'{}'", l)).collect::<Vec<String>>().join(", ")) } else { String::new() }),
             IntermediateResultUnavailable{ name, locs }         => write!(f, "Intermediate result '{}' is unavailable{}", name, if !locs.is_empty() { format!("; however, locations {} do (try to get download permission to those datasets)", locs.iter().map(|l| format!("'{}'", l)).collect::<Vec<String>>().join(", ")) } else { String::new() }),
+            PackageIndexError{ address, err }                   => write!(f, "Failed to fetch package index from '{}': {}", address, err),
+            UnschedulableTasks{ tasks }                         => write!(f, "{} task{} cannot be scheduled on any location:{}", tasks.len(), if tasks.len() == 1 { "" } else { "s" }, tasks.iter().map(|(task, reasons)| format!("\n - {}: {}", task, reasons.join("; "))).collect::<String>()),
 
             UpdateEncodeError{ correlation_id, kind, err } => write!(f, "Failed to encode status update '{:?}' for a planning session with ID '{}': {}", kind, correlation_id, err),
             KafkaSendError{ correlation_id, topic, err }   => write!(f, "Failed to send status update on Kafka topic '{}' for a planning session with ID '{}': {}", topic, correlation_id, err),
 
-            PlanningTimeout{ correlation_id, timeout } => write!(f, "The planner didn't start planning workflow with ID '{}' in time (timed out after {} seconds)", correlation_id, timeout / 1000),
-            PlanParseError{ correlation_id, raw, err } => write!(f, "Failed to parse planning result of workflow with ID '{}': {}\n\n{}\n\n", correlation_id, err, BlockFormatter::new(raw)),
-            PlanningFailed{ correlation_id, reason }   => write!(f, "Failed to plan workflow with ID '{}'{}", correlation_id, if let Some(reason) = reason { format!(": {}", reason) } else { String::new() }),
-            PlanningError{ correlation_id, err }       => write!(f, "Encountered an error while planning workflow with ID '{}': {}", correlation_id, err),
+            PlanningTimeout{ correlation_id, timeout }        => write!(f, "The planner didn't start planning workflow with ID '{}' in time (timed out after {} seconds)", correlation_id, timeout / 1000),
+            PlanParseError{ correlation_id, raw, err }        => write!(f, "Failed to parse planning result of workflow with ID '{}': {}\n\n{}\n\n", correlation_id, err, BlockFormatter::new(raw)),
+            PlanningFailed{ correlation_id, reason }          => write!(f, "Failed to plan workflow with ID '{}'{}", correlation_id, if let Some(reason) = reason { format!(": {}", reason) } else { String::new() }),
+            PlanningError{ correlation_id, err }              => write!(f, "Encountered an error while planning workflow with ID '{}': {}", correlation_id, err),
+            GrpcConnectError{ endpoint, err }                 => write!(f, "Failed to connect to planner at '{}': {}", endpoint, err),
+            GrpcRequestError{ correlation_id, endpoint, err } => write!(f, "Failed to send workflow with ID '{}' to planner at '{}': {}", correlation_id, endpoint, err),
 
             KafkaTopicError{ brokers, topics, err }        => write!(f, "Failed to ensure Kafka topics {} on brokers '{}': {}", topics.iter().map(|t| format!("'{}'", t)).collect::<Vec<String>>().join(", "), brokers, err),
             KafkaProducerError{ err }                      => write!(f, "Failed to create Kafka producer: {}", err),
@@ -225,8 +315,16 @@ pub enum PreprocessError {
     TarWriteError{ path: PathBuf, err: std::io::Error },
     /// Failed to extract the downloaded tar.
     DataExtractError{ err: brane_shr::fs::Error },
+    /// Failed to pass the next chunk of a direct transfer on to the extractor.
+    DirectTransferError{ address: String, err: std::io::Error },
     /// Failed to serialize the preprocessrequest.
     AccessKindSerializeError{ err: serde_json::Error },
+    /// The CA certificate of a location is newly seen, but the driver did not tell us where to find the CA log to check it with.
+    MissingCaLog{ location: String },
+    /// Failed to parse the CA log sent by the central node.
+    CaLogParseError{ address: String, err: reqwest::Error },
+    /// Failed to check, update or save the pinned CA certificate of a location.
+    CaPinError{ location: String, err: brane_cfg::pins::Error },
 }
 
 impl Display for PreprocessError {
@@ -265,7 +363,11 @@ impl Display for PreprocessError {
             TarOpenError{ path, err }                        => write!(f, "Failed to re-open tarball file '{}': {}", path.display(), err),
             TarWriteError{ path, err }                       => write!(f, "Failed to write to tarball file '{}': {}", path.display(), err),
             DataExtractError{ err }                          => write!(f, "Failed to extract dataset: {}", err),
+            DirectTransferError{ address, err }              => write!(f, "Failed to extract direct transfer from '{}' while receiving it: {}", address, err),
             AccessKindSerializeError{ err }                  => write!(f, "Failed to serialize the given AccessKind: {}", err),
+            MissingCaLog{ location }                         => write!(f, "CA certificate of location '{}' is newly seen, but no CA log address was given to verify it", location),
+            CaLogParseError{ address, err }                  => write!(f, "Failed to parse CA log sent by '{}': {}", address, err),
+            CaPinError{ location, err }                      => write!(f, "Failed to verify CA certificate of location '{}': {}", location, err),
         }
     }
 }
@@ -286,8 +388,16 @@ pub enum ExecuteError {
     ArgsEncodeError{ err: serde_json::Error },
     /// The external call failed with a nonzero exit code and some stdout/stderr
     ExternalCallFailed{ name: String, image: Image, code: i32, stdout: String, stderr: String },
+    /// The external call was killed (exit code 137), most likely because it ran out of memory
+    OutOfMemory{ name: String, image: Image, stdout: String, stderr: String },
+    /// The external call crashed with a segmentation fault (exit code 139)
+    SegmentationFault{ name: String, image: Image, stdout: String, stderr: String },
     /// Failed to decode the branelet output from base64 to raw bytes
     Base64DecodeError{ raw: String, err: base64::DecodeError },
+    /// The task wrote more to its scratch space than it was allowed to.
+    ScratchExhausted{ name: String, image: Image, limit: u64 },
+    /// The task did not complete within its timeout and was killed.
+    Timeout{ name: String, image: Image, timeout: u64 },
     /// Failed to decode the branelet output from raw bytes to an UTF-8 string
     Utf8DecodeError{ raw: String, err: std::string::FromUtf8Error },
     /// Failed to decode the branelet output from an UTF-8 string to a FullValue
@@ -304,6 +414,16 @@ pub enum ExecuteError {
     ResultDirCreateError{ path: PathBuf, err: std::io::Error },
     /// Failed to run the task as a local Docker container
     DockerError{ name: String, image: Image, err: DockerError },
+    /// An input dataset lives on a scientific storage system, but was not staged before the task was launched.
+    UnstagedData{ name: DataName, url: String },
+    /// Failed to stage an input dataset from a scientific storage system.
+    StageError{ name: DataName, err: brane_shr::grid::Error },
+    /// Failed to fetch an input dataset that is registered by URL.
+    FetchError{ name: DataName, err: brane_shr::fetch::Error },
+    /// Failed to move an intermediate result into the content-addressed store.
+    ResultStoreError{ name: String, err: brane_shr::cas::Error },
+    /// Failed to link an intermediate result in the results directory to where it lives in the content-addressed store.
+    ResultLinkError{ path: PathBuf, target: PathBuf, err: std::io::Error },
 
     // Instance-only (client side)
     /// The given job status was missing a string while we expected one
@@ -346,21 +466,44 @@ pub enum ExecuteError {
     ImageCreateError{ path: PathBuf, err: std::io::Error },
     /// Failed to write to the file where we write the download stream.
     ImageWriteError{ path: PathBuf, err: std::io::Error },
-    /// Failed to hash the given container.
-    HashError{ err: DockerError },
-    /// Failed to write to the file where we write the container hash.
-    HashWriteError{ path: PathBuf, err: std::io::Error },
-    /// Failed to read to the file where we cached the container hash.
-    HashReadError{ path: PathBuf, err: std::io::Error },
+    /// The image to pull from an OCI registry has no digest to verify it with.
+    MissingImageDigest{ image: Image },
+    /// Failed to pull the image from an OCI registry.
+    ImagePullError{ source: String, err: DockerError },
+    /// Failed to access the content-addressed store where we keep the container.
+    StoreError{ err: brane_shr::cas::Error },
+    /// Failed to measure the free disk space on the worker.
+    DiskSpaceError{ err: brane_shr::disk::Error },
+    /// There is not enough disk space left to admit the task, even after evicting the caches.
+    InsufficientDiskSpace{ needed: u64, free: u64, reserve: u64 },
 
     /// The checker rejected the workflow.
     AuthorizationFailure{ checker: Address },
     /// The checker failed to check workflow authorization.
     AuthorizationError{ checker: Address, err: AuthorizeError },
+    /// The task uses data that may not be processed at this location.
+    ResidencyViolation{ name: String, data: DataName, loc: Location },
     /// Failed to get an up-to-date package index.
     PackageIndexError{ endpoint: String, err: ApiError },
     /// Failed to load the backend file.
     BackendFileError{ path: PathBuf, err: brane_cfg::backend::Error },
+
+    /// The task needs secrets, but this worker has no secrets file.
+    NoSecretsFile{ name: String },
+    /// Failed to load the secrets file.
+    SecretsFileError{ path: PathBuf, err: brane_cfg::secrets::Error },
+    /// The task needs a secret that this worker does not know.
+    UnknownSecret{ name: String, secret: String },
+    /// The task needs a secret that the secrets file does not allow its package to use.
+    SecretNotAllowed{ name: String, package: String, secret: String },
+    /// The task needs secrets, but the backend of this worker cannot inject them.
+    UnsupportedSecrets{ name: String },
+    /// Failed to send the request for the secrets to Vault.
+    VaultRequestError{ address: String, err: reqwest::Error },
+    /// Vault did not give us the secrets.
+    VaultRequestFailure{ address: String, code: StatusCode },
+    /// Failed to parse the secrets that Vault gave us.
+    VaultResponseError{ address: String, err: reqwest::Error },
 }
 
 impl Display for ExecuteError {
@@ -370,16 +513,25 @@ impl Display for ExecuteError {
             UnknownPackage{ name, version }                         => write!(f, "Unknown package '{}' (or it does not have version {})", name, version),
             UnknownData{ name }                                     => write!(f, "Unknown {} '{}'", name.variant(), name.name()),
             ArgsEncodeError{ err }                                  => write!(f, "Failed to serialize input arguments: {}", err),
-            ExternalCallFailed{ name, image, code, stdout, stderr } => write!(f, "Task '{}' (image '{}') failed with exit code {}\n\n{}\n\n{}\n\n", name, image, code, BlockFormatter::new(stdout), BlockFormatter::new(stderr)),
+            ExternalCallFailed{ name, image, code, stdout, stderr } => write!(f, "Task '{}' (image '{}') failed with exit code {}\n\n{}\n\n{}\n\nHint: the package code itself reported an error; check its stderr above, and whether the task's inputs are what the package expects.\n", name, image, code, BlockFormatter::new(stdout), BlockFormatter::new(stderr)),
+            OutOfMemory{ name, image, stdout, stderr }              => write!(f, "Task '{}' (image '{}') was killed (exit code 137), most likely because it ran out of memory\n\n{}\n\n{}\n\nHint: run the task on a node with more memory available, or reduce its memory footprint (e.g., by processing its input in smaller chunks).\n", name, image, BlockFormatter::new(stdout), BlockFormatter::new(stderr)),
+            SegmentationFault{ name, image, stdout, stderr }        => write!(f, "Task '{}' (image '{}') crashed with a segmentation fault (exit code 139)\n\n{}\n\n{}\n\nHint: this usually indicates a bug in the package code or in one of its native dependencies; check whether the task's inputs are valid and in the format the package expects.\n", name, image, BlockFormatter::new(stdout), BlockFormatter::new(stderr)),
             Base64DecodeError{ raw, err }                           => write!(f, "Failed to decode task output as valid Base64: {}\n\n{}\n\n", BlockFormatter::new(raw), err),
+            ScratchExhausted{ name, image, limit }                  => write!(f, "Task '{}' (image '{}') exhausted its scratch space (limit: {} bytes)", name, image, limit),
+            Timeout{ name, image, timeout }                         => write!(f, "Task '{}' (image '{}') did not complete within {} seconds and was killed\n\nHint: raise the timeout of the call (see '@timeout(...)') if the task is expected to take this long.\n", name, image, timeout),
             Utf8DecodeError{ raw, err }                             => write!(f, "Failed to decode task output as valid UTF-8: {}\n\n{}\n\n", BlockFormatter::new(raw), err),
             JsonDecodeError{ raw, err }                             => write!(f, "Failed to decode task output as valid JSON: {}\n\n{}\n\n", BlockFormatter::new(raw), err),
 
-            VolumeBindError{ err }            => write!(f, "Failed to create VolumeBind: {}", err),
-            ResultDirNotADir{ path }          => write!(f, "Result directory '{}' exists but is not a directory", path.display()),
-            ResultDirRemoveError{ path, err } => write!(f, "Failed to remove existing result directory '{}': {}", path.display(), err),
-            ResultDirCreateError{ path, err } => write!(f, "Failed to create result directory '{}': {}", path.display(), err),
-            DockerError{ name, image, err }   => write!(f, "Failed to execute task '{}' (image '{}') as a Docker container: {}", name, image, err),
+            VolumeBindError{ err }               => write!(f, "Failed to create VolumeBind: {}", err),
+            ResultDirNotADir{ path }             => write!(f, "Result directory '{}' exists but is not a directory", path.display()),
+            ResultDirRemoveError{ path, err }    => write!(f, "Failed to remove existing result directory '{}': {}", path.display(), err),
+            ResultDirCreateError{ path, err }    => write!(f, "Failed to create result directory '{}': {}", path.display(), err),
+            DockerError{ name, image, err }      => write!(f, "Failed to execute task '{}' (image '{}') as a Docker container: {}", name, image, err),
+            UnstagedData{ name, url }            => write!(f, "{} '{}' lives at '{}', but was not staged before launching the task", name.variant(), name.name(), url),
+            StageError{ name, err }              => write!(f, "Failed to stage {} '{}': {}", name.variant(), name.name(), err),
+            FetchError{ name, err }              => write!(f, "Failed to fetch {} '{}': {}", name.variant(), name.name(), err),
+            ResultStoreError{ name, err }        => write!(f, "Failed to add intermediate result '{}' to the content-addressed store: {}", name, err),
+            ResultLinkError{ path, target, err } => write!(f, "Failed to link intermediate result '{}' to '{}' in the content-addressed store: {}", path.display(), target.display(), err),
 
             StatusEmptyStringError{ status }            => write!(f, "Incoming status update {:?} is missing mandatory `value` field", status),
             StatusValueParseError{ status, raw, err }   => write!(f, "Failed to parse '{}' as a FullValue in incoming status update {:?}: {}", raw, status, err),
@@ -401,14 +553,86 @@ impl Display for ExecuteError {
             DownloadStreamError{ address, err }              => write!(f, "Failed to get next chunk in download stream from '{}': {}", address, err),
             ImageCreateError{ path, err }                    => write!(f, "Failed to create tarball file '{}': {}", path.display(), err),
             ImageWriteError{ path, err }                     => write!(f, "Failed to write to tarball file '{}': {}", path.display(), err),
-            HashError{ err }                                 => write!(f, "Failed to hash image: {}", err),
-            HashWriteError{ path, err }                      => write!(f, "Failed to write image hash to file '{}': {}", path.display(), err),
-            HashReadError{ path, err }                       => write!(f, "Failed to read image hash from file '{}': {}", path.display(), err),
+            MissingImageDigest{ image }                      => write!(f, "Image '{}' has no digest to verify it with", image),
+            ImagePullError{ source, err }                    => write!(f, "Failed to pull image '{}': {}", source, err),
+            StoreError{ err }                                => write!(f, "Failed to access content-addressed store: {}", err),
+            DiskSpaceError{ err }                            => write!(f, "Failed to measure free disk space: {}", err),
+            InsufficientDiskSpace{ needed, free, reserve }   => write!(f, "Not enough disk space to admit task (needs {} bytes, but only {} bytes are free of which {} bytes are reserved)", needed, free, reserve),
 
             AuthorizationFailure{ checker: _ }    => write!(f, "Checker rejected workflow"),
             AuthorizationError{ checker: _, err } => write!(f, "Checker failed to authorize workflow: {}", err),
+            ResidencyViolation{ name, data, loc } => write!(f, "Task '{}' uses {} '{}', which may not be processed at location '{}'", name, data.variant(), data.name(), loc),
             PackageIndexError{ endpoint, err }    => write!(f, "Failed to get PackageIndex from '{}': {}", endpoint, err),
             BackendFileError{ path, err }         => write!(f, "Failed to load backend file '{}': {}", path.display(), err),
+
+            NoSecretsFile{ name }                     => write!(f, "Task '{}' needs secrets, but this worker has no secrets file", name),
+            SecretsFileError{ path, err }             => write!(f, "Failed to load secrets file '{}': {}", path.display(), err),
+            UnknownSecret{ name, secret }             => write!(f, "Task '{}' needs unknown secret '{}'", name, secret),
+            SecretNotAllowed{ name, package, secret } => write!(f, "Task '{}' needs secret '{}', but package '{}' is not allowed to use it", name, secret, package),
+            UnsupportedSecrets{ name }                => write!(f, "Task '{}' needs secrets, but they can only be injected into containers on a local backend", name),
+            VaultRequestError{ address, err }         => write!(f, "Failed to send GET request for secrets to '{}': {}", address, err),
+            VaultRequestFailure{ address, code }      => write!(f, "GET request for secrets to '{}' failed with status code {} ({})", address, code, code.canonical_reason().unwrap_or("???")),
+            VaultResponseError{ address, err }        => write!(f, "Failed to parse secrets from '{}': {}", address, err),
+        }
+    }
+}
+
+impl ExecuteError {
+    /// Constructor for the ExecuteError that classifies the nonzero exit code of a task container into the most specific error we can give.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the task that failed.
+    /// - `image`: The image of the container that ran the task.
+    /// - `code`: The exit code with which the container exited.
+    /// - `stdout`: The stdout of the container.
+    /// - `stderr`: The stderr of the container.
+    /// 
+    /// # Returns
+    /// An `ExecuteError::OutOfMemory` for exit code 137, an `ExecuteError::SegmentationFault` for exit code 139 and an `ExecuteError::ExternalCallFailed` otherwise.
+    pub fn from_exit_code(name: impl Into<String>, image: impl Into<Image>, code: i32, stdout: impl Into<String>, stderr: impl Into<String>) -> Self {
+        let (name, image, stdout, stderr): (String, Image, String, String) = (name.into(), image.into(), stdout.into(), stderr.into());
+        match code {
+            // 128 + SIGKILL, which is what the kernel's OOM-killer sends
+            137 => Self::OutOfMemory{ name, image, stdout, stderr },
+            // 128 + SIGSEGV
+            139 => Self::SegmentationFault{ name, image, stdout, stderr },
+            _   => Self::ExternalCallFailed{ name, image, code, stdout, stderr },
+        }
+    }
+
+
+
+    /// Returns whether this error is transient, i.e., whether running the same task again may succeed.
+    /// 
+    /// This decides whether calls with a retry policy (see `@retry(...)`) are retried. Failures of the task code and of the infrastructure in between are transient; unknown packages or data, decisions of the policy, exhausted quota and tasks that were stopped are not.
+    /// 
+    /// # Returns
+    /// True if the task may be retried, or false if it would fail the same way again.
+    pub fn is_transient(&self) -> bool {
+        use self::ExecuteError::*;
+        match self {
+            // The package code failed, possibly due to something outside of it
+            ExternalCallFailed{ .. } | OutOfMemory{ .. } => true,
+            // A hung task may well complete the next time
+            Timeout{ .. } => true,
+            // The worker failed to prepare or run the container, or to get the things it needs
+            ResultDirRemoveError{ .. } | ResultDirCreateError{ .. } | DockerError{ .. } | StageError{ .. } | FetchError{ .. } | ResultStoreError{ .. } | ResultLinkError{ .. } => true,
+            DigestError{ .. } | DownloadRequestError{ .. } | DownloadStreamError{ .. } | ImageCreateError{ .. } | ImageWriteError{ .. } | ImagePullError{ .. } | StoreError{ .. } | DiskSpaceError{ .. } | InsufficientDiskSpace{ .. } => true,
+            AuthorizationError{ .. } | PackageIndexError{ .. } | VaultRequestError{ .. } | VaultResponseError{ .. } => true,
+            // Servers may fail temporarily, or ask us to slow down
+            DownloadRequestFailure{ code, .. } | VaultRequestFailure{ code, .. } => code.is_server_error() || *code == StatusCode::TOO_MANY_REQUESTS,
+            // We failed to reach the worker
+            GrpcConnectError{ .. } | GrpcRequestError{ .. } => true,
+            // The worker reports how it failed
+            ExecuteError{ status, .. } => !matches!(status, TaskStatus::Denied | TaskStatus::InitializationFailed | TaskStatus::Stopped | TaskStatus::DecodingFailed | TaskStatus::ScratchExhausted),
+
+            // Everything else fails the same way every time
+            UnknownPackage{ .. } | UnknownData{ .. } | ArgsEncodeError{ .. } | SegmentationFault{ .. } | Base64DecodeError{ .. } | ScratchExhausted{ .. } | Utf8DecodeError{ .. } | JsonDecodeError{ .. } => false,
+            VolumeBindError{ .. } | ResultDirNotADir{ .. } | UnstagedData{ .. } => false,
+            StatusEmptyStringError{ .. } | StatusValueParseError{ .. } | StatusTripletParseError{ .. } | ClientUpdateError{ .. } | NodeConfigReadError{ .. } | InfraReadError{ .. } | UnknownLocationError{ .. } | ProxyError{ .. } => false,
+            ProxyCreateError{ .. } | ClientCreateError{ .. } | MissingImageDigest{ .. } => false,
+            AuthorizationFailure{ .. } | ResidencyViolation{ .. } | BackendFileError{ .. } => false,
+            NoSecretsFile{ .. } | SecretsFileError{ .. } | UnknownSecret{ .. } | SecretNotAllowed{ .. } | UnsupportedSecrets{ .. } => false,
         }
     }
 }
@@ -460,6 +684,29 @@ impl Error for StdoutError {}
 
 
 
+/// Defines common errors that occur when waiting for an external event.
+#[derive(Debug)]
+pub enum EventError {
+    /// Failed to read the payload of the event from stdin.
+    StdinReadError{ name: String, err: std::io::Error },
+    /// The source of events was closed before the event arrived.
+    SourceClosed{ name: String },
+}
+
+impl Display for EventError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use EventError::*;
+        match self {
+            StdinReadError{ name, err } => write!(f, "Failed to read payload of event '{}' from stdin: {}", name, err),
+            SourceClosed{ name }        => write!(f, "Event source was closed while waiting for event '{}'", name),
+        }
+    }
+}
+
+impl Error for EventError {}
+
+
+
 /// Defines common errors that occur when trying to commit an intermediate result.
 #[derive(Debug)]
 pub enum CommitError {
@@ -482,6 +729,10 @@ pub enum CommitError {
     DirEntryReadError{ path: PathBuf, i: usize, err: std::io::Error },
     /// Failed to copy the data
     DataCopyError{ err: brane_shr::fs::Error },
+    /// The dataset to overwrite lives on a scientific storage system, which we don't write to.
+    RemoteDataError{ name: String, url: String },
+    /// Failed to access the content-addressed store that keeps the data.
+    StoreError{ err: brane_shr::cas::Error },
 
     // Instance-only (client side)
     /// Failed to load the node config file.
@@ -498,6 +749,8 @@ pub enum CommitError {
     GrpcRequestError{ what: &'static str, endpoint: Address, err: tonic::Status },
     /// Preprocessing failed with the following error.
     CommitError{ endpoint: Address, name: String, err: Option<String> },
+    /// The delegate refused the commit because the dataset was changed by another commit since we last saw it.
+    ConflictError{ endpoint: Address, name: String, err: String },
 
     // Instance-only (worker side)
     /// Failed to read the AssetInfo file.
@@ -508,6 +761,14 @@ pub enum CommitError {
     DirRemoveError{ path: PathBuf, err: std::io::Error },
     /// A given path is neither a file nor a directory.
     PathNotFileNotDir{ path: PathBuf },
+    /// The dataset is not at the revision that the commit expected to replace.
+    RevisionConflict{ name: String, expected: u64, actual: u64 },
+}
+
+impl CommitError {
+    /// Returns whether this error means that the commit lost a race with another commit to the same dataset, in which case retrying it (against the latest revision) may succeed.
+    #[inline]
+    pub fn is_conflict(&self) -> bool { matches!(self, Self::ConflictError{ .. } | Self::RevisionConflict{ .. }) }
 }
 
 impl Display for CommitError {
@@ -523,6 +784,8 @@ impl Display for CommitError {
             DirReadError{ path, err }            => write!(f, "Failed to read directory '{}': {}", path.display(), err),
             DirEntryReadError{ path, i, err }    => write!(f, "Failed to read entry {} in directory '{}': {}", i, path.display(), err),
             DataCopyError{ err }                 => write!(f, "Failed to copy data directory: {}", err),
+            RemoteDataError{ name, url }         => write!(f, "Cannot overwrite dataset '{}', since it lives on a remote system ('{}')", name, url),
+            StoreError{ err }                    => write!(f, "Failed to access content-addressed store: {}", err),
 
             NodeConfigReadError{ err, .. }          => write!(f, "Failed to load node config file: {}", err),
             InfraReadError{ path, err }             => write!(f, "Failed to load infrastructure file '{}': {}", path.display(), err),
@@ -531,11 +794,13 @@ impl Display for CommitError {
             GrpcConnectError{ endpoint, err }       => write!(f, "Failed to start gRPC connection with delegate node '{}': {}", endpoint, err),
             GrpcRequestError{ what, endpoint, err } => write!(f, "Failed to send {} request to delegate node '{}': {}", what, endpoint, err),
             CommitError{ endpoint, name, err }      => write!(f, "Remote delegate '{}' failed to commit intermediate result '{}'{}", endpoint, name, if let Some(err) = err { format!(": {}", err) } else { String::new() }),
+            ConflictError{ endpoint, name, err }    => write!(f, "Remote delegate '{}' refused to commit to dataset '{}', since another commit changed it after this workflow started ({}); re-run the workflow to commit against its latest revision", endpoint, name, err),
 
             AssetInfoReadError{ path, err } => write!(f, "Failed to load asset info file '{}': {}", path.display(), err),
             FileRemoveError{ path, err }    => write!(f, "Failed to remove file '{}': {}", path.display(), err),
             DirRemoveError{ path, err }     => write!(f, "Failed to remove directory '{}': {}", path.display(), err),
             PathNotFileNotDir{ path }       => write!(f, "Given path '{}' neither points to a file nor a directory", path.display()),
+            RevisionConflict{ name, expected, actual } => write!(f, "Dataset '{}' is at revision {}, but the commit expected to replace revision {}", name, actual, expected),
         }
     }
 }
@@ -575,6 +840,8 @@ pub enum DockerError {
     WaitError{ name: String, err: bollard::errors::Error },
     /// Failed to read the logs of a container.
     LogsError{ name: String, err: bollard::errors::Error },
+    /// Failed to read the resource usage statistics of a container.
+    StatsError{ name: String, err: bollard::errors::Error },
 
     /// Failed to inspect the given container.
     InspectContainerError{ name: String, err: bollard::errors::Error },
@@ -593,6 +860,13 @@ pub enum DockerError {
 
     /// Failed to remove the given container.
     ContainerRemoveError{ name: String, err: bollard::errors::Error },
+    /// Failed to kill the given container.
+    ContainerKillError{ name: String, err: bollard::errors::Error },
+
+    /// Failed to create the given network.
+    NetworkCreateError{ name: String, err: bollard::errors::Error },
+    /// Failed to remove the given network.
+    NetworkRemoveError{ name: String, err: bollard::errors::Error },
 
     /// Failed to open the given image file.
     ImageFileOpenError{ path: PathBuf, err: std::io::Error },
@@ -603,6 +877,12 @@ pub enum DockerError {
     ImagePullError{ source: String, err: bollard::errors::Error },
     /// Failed to appropriately tag the pulled image.
     ImageTagError{ image: Image, source: String, err: bollard::errors::Error },
+    /// The image pulled from a registry did not have the digest we expected.
+    ImageDigestMismatch{ image: Image, source: String },
+    /// Failed to push an image to a registry.
+    ImagePushError{ target: String, err: bollard::errors::Error },
+    /// The registry refused an image that we pushed.
+    ImagePushFailure{ target: String, reason: String },
 
     /// Failed to inspect a certain image.
     ImageInspectError{ image: Image, err: bollard::errors::Error },
@@ -638,8 +918,9 @@ impl Display for DockerError {
         match self {
             ConnectionError{ path, version, err } => write!(f, "Failed to connect to the local Docker daemon through socket '{}' and with client version {}: {}", path.display(), version, err),
 
-            WaitError{ name, err } => write!(f, "Failed to wait for Docker container with name '{}': {}", name, err),
-            LogsError{ name, err } => write!(f, "Failed to get logs of Docker container with name '{}': {}", name, err),
+            WaitError{ name, err }  => write!(f, "Failed to wait for Docker container with name '{}': {}", name, err),
+            LogsError{ name, err }  => write!(f, "Failed to get logs of Docker container with name '{}': {}", name, err),
+            StatsError{ name, err } => write!(f, "Failed to get resource usage statistics of Docker container with name '{}': {}", name, err),
 
             InspectContainerError{ name, err } => write!(f, "Failed to inspect Docker container with name '{}': {}", name, err),
             ContainerNoNetwork{ name }         => write!(f, "Docker container with name '{}' is not connected to any networks", name),
@@ -651,12 +932,19 @@ impl Display for DockerError {
             ContainerNoExitCode{ name } => write!(f, "Docker container with name '{}' has no return code (did you wait before completing?)", name),
 
             ContainerRemoveError{ name, err } => write!(f, "Fialed to remove Docker container with name '{}': {}", name, err),
+            ContainerKillError{ name, err }   => write!(f, "Failed to kill Docker container with name '{}': {}", name, err),
+
+            NetworkCreateError{ name, err } => write!(f, "Failed to create Docker network with name '{}': {}", name, err),
+            NetworkRemoveError{ name, err } => write!(f, "Failed to remove Docker network with name '{}': {}", name, err),
 
             ImageFileOpenError{ path, err } => write!(f, "Failed to open image file '{}': {}", path.display(), err),
             ImageImportError{ path, err }   => write!(f, "Failed to import image file '{}' into Docker engine: {}", path.display(), err),
 
-            ImagePullError{ source, err }       => write!(f, "Failed to pull image '{}' into Docker engine: {}", source, err),
-            ImageTagError{ image, source, err } => write!(f, "Failed to tag pulled image '{}' as '{}': {}", source, image, err),
+            ImagePullError{ source, err }        => write!(f, "Failed to pull image '{}' into Docker engine: {}", source, err),
+            ImageTagError{ image, source, err }  => write!(f, "Failed to tag pulled image '{}' as '{}': {}", source, image, err),
+            ImageDigestMismatch{ image, source } => write!(f, "Image pulled from '{}' is not image '{}' (digest mismatch)", source, image),
+            ImagePushError{ target, err }        => write!(f, "Failed to push image '{}' from Docker engine: {}", target, err),
+            ImagePushFailure{ target, reason }   => write!(f, "Registry refused image '{}': {}", target, reason),
 
             ImageInspectError{ image, err }    => write!(f, "Failed to inspect image '{}'{}: {}", image.name(), if let Some(digest) = image.digest() { format!(" ({})", digest) } else { String::new() }, err),
             ImageRemoveError{ image, id, err } => write!(f, "Failed to remove image '{}' (id: {}) from Docker engine: {}", image.name(), id, err),
@@ -744,6 +1032,13 @@ pub enum ApiError {
     ResponseJsonParseError{ address: String, raw: String, err: serde_json::Error },
     /// The remote failed to produce even a single result (not even 'no packages').
     NoResponse{ address: String },
+    /// The remote responded with a status code that the operation does not accept.
+    RequestFailure{ address: String, code: StatusCode },
+
+    /// The remote reported a version that we could not parse.
+    ApiVersionParseError{ address: String, raw: String, err: specifications::version::ParseError },
+    /// The remote reported a version of the API that this client is not compatible with.
+    IncompatibleVersion{ address: String, remote: Version, local: Version },
 
     /// Failed to parse the package kind in a package info.
     PackageKindParseError{ address: String, index: usize, raw: String, err: specifications::package::PackageKindError },
@@ -764,6 +1059,10 @@ impl Display for ApiError {
             ResponseBodyError{ address, err }           => write!(f, "Failed to get body from response from '{}': {}", address, err),
             ResponseJsonParseError{ address, raw, err } => write!(f, "Failed to parse response \"\"\"{}\"\"\" from '{}' as JSON: {}", raw, address, err),
             NoResponse{ address }                       => write!(f, "'{}' responded without a body (not even that no packages are available)", address),
+            RequestFailure{ address, code }             => write!(f, "Request to '{}' failed with {} ({})", address, code.as_u16(), code.canonical_reason().unwrap_or("???")),
+
+            ApiVersionParseError{ address, raw, err }     => write!(f, "Failed to parse '{}' as the API version reported by '{}': {}", raw, address, err),
+            IncompatibleVersion{ address, remote, local }  => write!(f, "API at '{}' has version {}, which is incompatible with this client (generated for version {})", address, remote, local),
 
             PackageKindParseError{ address, index, raw, err } => write!(f, "Failed to parse '{}' as package kind in package {} returned by '{}': {}", raw, index, address, err),
             VersionParseError{ address, index, raw, err }     => write!(f, "Failed to parse '{}' as version in package {} returned by '{}': {}", raw, index, address, err),
@@ -775,3 +1074,198 @@ impl Display for ApiError {
 }
 
 impl Error for ApiError {}
+
+
+
+/// Collects errors that relate to connecting to the central services over TLS.
+#[derive(Debug)]
+pub enum TlsError {
+    /// Failed to read a certificate file.
+    FileReadError{ path: PathBuf, err: std::io::Error },
+    /// Failed to parse the CA certificate.
+    CertificateParseError{ path: PathBuf, err: reqwest::Error },
+    /// Failed to parse our identity (i.e., certificate and key).
+    IdentityParseError{ path: PathBuf, err: reqwest::Error },
+    /// Failed to create the client with the certificates.
+    ClientBuildError{ err: reqwest::Error },
+}
+
+impl Display for TlsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use TlsError::*;
+        match self {
+            FileReadError{ path, err }         => write!(f, "Failed to read certificate file '{}': {}", path.display(), err),
+            CertificateParseError{ path, err } => write!(f, "Failed to parse CA certificate in '{}': {}", path.display(), err),
+            IdentityParseError{ path, err }    => write!(f, "Failed to parse client identity in '{}': {}", path.display(), err),
+            ClientBuildError{ err }            => write!(f, "Failed to create client with certificates: {}", err),
+        }
+    }
+}
+
+impl Error for TlsError {}
+
+
+
+/// Collects errors that relate to running tasks on AWS Batch or AWS Lambda.
+#[derive(Debug)]
+pub enum AwsError {
+    /// None of the configured job definitions supports the given requirement.
+    UnsupportedRequirement{ requirement: Capability },
+    /// Failed to look for an existing job definition.
+    DescribeJobDefinitionsError{ name: String, err: Box<dyn Error + Send + Sync> },
+    /// Failed to register a new job definition.
+    RegisterJobDefinitionError{ name: String, err: Box<dyn Error + Send + Sync> },
+
+    /// Failed to submit a job.
+    SubmitError{ name: String, queue: String, err: Box<dyn Error + Send + Sync> },
+    /// AWS accepted the job but did not tell us its identifier.
+    MissingJobId{ name: String },
+    /// Failed to ask AWS for the status of a job.
+    DescribeJobError{ id: String, err: Box<dyn Error + Send + Sync> },
+    /// AWS does not know the job we submitted.
+    UnknownJob{ id: String },
+    /// Failed to read the logs of a job.
+    LogsError{ stream: String, err: Box<dyn Error + Send + Sync> },
+    /// Failed to terminate a job.
+    TerminateError{ id: String, err: Box<dyn Error + Send + Sync> },
+
+    /// Failed to serialize the payload of a function call.
+    PayloadSerializeError{ function: String, err: serde_json::Error },
+    /// Failed to invoke a function.
+    InvokeError{ function: String, err: Box<dyn Error + Send + Sync> },
+    /// The function itself (i.e., not the task) crashed.
+    FunctionError{ function: String, kind: String, raw: String },
+    /// Failed to parse the response of a function.
+    ResponseParseError{ function: String, raw: String, err: serde_json::Error },
+
+    /// Failed to get a token for the (ECR) registry.
+    AuthorizationError{ registry: String, err: Box<dyn Error + Send + Sync> },
+    /// The token for the (ECR) registry was not `<username>:<password>` in base64.
+    IllegalAuthorizationToken{ registry: String },
+    /// Failed to push the image of a package to the registry.
+    PushError{ image: Image, registry: String, err: DockerError },
+
+    /// Failed to presign a request for an object.
+    PresignError{ key: String, err: Box<dyn Error + Send + Sync> },
+    /// Failed to archive a directory before uploading it.
+    ArchiveError{ path: PathBuf, err: brane_shr::fs::Error },
+    /// Failed to upload a file to the bucket.
+    UploadError{ path: PathBuf, bucket: String, key: String, err: Box<dyn Error + Send + Sync> },
+    /// Failed to download an object from the bucket.
+    DownloadError{ bucket: String, key: String, err: Box<dyn Error + Send + Sync> },
+    /// Failed to remove an (empty) output directory before extracting the output to it.
+    DirRemoveError{ path: PathBuf, err: std::io::Error },
+    /// Failed to extract a downloaded output.
+    ExtractError{ path: PathBuf, err: brane_shr::fs::Error },
+    /// The value returned by a task was not UTF-8.
+    OutputUtf8Error{ key: String, err: std::string::FromUtf8Error },
+    /// Failed to remove an object from the bucket.
+    DeleteError{ bucket: String, key: String, err: Box<dyn Error + Send + Sync> },
+}
+
+impl Display for AwsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use AwsError::*;
+        match self {
+            UnsupportedRequirement{ requirement }    => write!(f, "No AWS Batch job definition is configured for requirement '{:?}'", requirement),
+            DescribeJobDefinitionsError{ name, err } => write!(f, "Failed to describe AWS Batch job definition '{}': {}", name, err),
+            RegisterJobDefinitionError{ name, err }  => write!(f, "Failed to register AWS Batch job definition '{}': {}", name, err),
+
+            SubmitError{ name, queue, err } => write!(f, "Failed to submit job '{}' to AWS Batch job queue '{}': {}", name, queue, err),
+            MissingJobId{ name }            => write!(f, "AWS Batch did not return an identifier for job '{}'", name),
+            DescribeJobError{ id, err }     => write!(f, "Failed to describe AWS Batch job '{}': {}", id, err),
+            UnknownJob{ id }                => write!(f, "AWS Batch does not know job '{}'", id),
+            LogsError{ stream, err }        => write!(f, "Failed to read log stream '{}': {}", stream, err),
+            TerminateError{ id, err }       => write!(f, "Failed to terminate AWS Batch job '{}': {}", id, err),
+
+            PayloadSerializeError{ function, err }   => write!(f, "Failed to serialize payload for AWS Lambda function '{}': {}", function, err),
+            InvokeError{ function, err }             => write!(f, "Failed to invoke AWS Lambda function '{}': {}", function, err),
+            FunctionError{ function, kind, raw }     => write!(f, "AWS Lambda function '{}' failed ({}):\n\n{}\n\n", function, kind, BlockFormatter::new(raw)),
+            ResponseParseError{ function, raw, err } => write!(f, "Failed to parse response \"\"\"{}\"\"\" of AWS Lambda function '{}': {}", raw, function, err),
+
+            AuthorizationError{ registry, err }   => write!(f, "Failed to get authorization token for registry '{}': {}", registry, err),
+            IllegalAuthorizationToken{ registry } => write!(f, "Authorization token for registry '{}' is not a base64-encoded '<username>:<password>' pair", registry),
+            PushError{ image, registry, err }     => write!(f, "Failed to push image '{}' to registry '{}': {}", image, registry, err),
+
+            PresignError{ key, err }                => write!(f, "Failed to presign request for object '{}': {}", key, err),
+            ArchiveError{ path, err }               => write!(f, "Failed to archive '{}': {}", path.display(), err),
+            UploadError{ path, bucket, key, err }   => write!(f, "Failed to upload '{}' to 's3://{}/{}': {}", path.display(), bucket, key, err),
+            DownloadError{ bucket, key, err }       => write!(f, "Failed to download 's3://{}/{}': {}", bucket, key, err),
+            DirRemoveError{ path, err }             => write!(f, "Failed to remove output directory '{}': {}", path.display(), err),
+            ExtractError{ path, err }               => write!(f, "Failed to extract output to '{}': {}", path.display(), err),
+            OutputUtf8Error{ key, err }             => write!(f, "Output '{}' is not valid UTF-8: {}", key, err),
+            DeleteError{ bucket, key, err }         => write!(f, "Failed to remove 's3://{}/{}': {}", bucket, key, err),
+        }
+    }
+}
+
+impl Error for AwsError {}
+
+
+
+
+/// Collects errors that relate to running tasks on a remote machine over SSH.
+#[derive(Debug)]
+pub enum SshError {
+    /// Failed to launch `ssh` itself.
+    SpawnError{ address: String, what: &'static str, err: std::io::Error },
+    /// A command on the remote machine failed.
+    CommandFailure{ address: String, what: &'static str, code: Option<i32>, stderr: String },
+
+    /// Failed to open the package's image file.
+    ImageOpenError{ path: PathBuf, err: std::io::Error },
+    /// Failed to extract the package's image file.
+    ImageExtractError{ path: PathBuf, err: std::io::Error },
+    /// Failed to read the manifest of the package's image.
+    ManifestReadError{ path: PathBuf, err: std::io::Error },
+    /// Failed to parse the manifest of the package's image.
+    ManifestParseError{ path: PathBuf, err: serde_json::Error },
+    /// The manifest of the package's image did not describe exactly one image.
+    IllegalManifestNum{ path: PathBuf, got: usize },
+    /// Failed to open one of the layers of the package's image.
+    LayerOpenError{ path: PathBuf, err: std::io::Error },
+    /// Failed to remove a local directory.
+    DirRemoveError{ path: PathBuf, err: std::io::Error },
+
+    /// A dataset or intermediate result had a path without a parent or a (UTF-8) name.
+    IllegalDataPath{ path: PathBuf },
+    /// Failed to launch `tar` to archive a dataset or intermediate result.
+    TarSpawnError{ path: PathBuf, err: std::io::Error },
+    /// `tar` failed to archive a dataset or intermediate result.
+    TarFailure{ path: PathBuf, code: Option<i32> },
+
+    /// Failed to create the tarball to download the result to.
+    TarballCreateError{ path: PathBuf, err: std::io::Error },
+    /// Failed to extract the downloaded result.
+    ResultExtractError{ path: PathBuf, err: brane_shr::fs::Error },
+    /// Failed to remove the downloaded tarball.
+    TarballRemoveError{ path: PathBuf, err: std::io::Error },
+}
+
+impl Display for SshError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use SshError::*;
+        match self {
+            SpawnError{ address, what, err }              => write!(f, "Failed to launch ssh for {} on '{}': {}", what, address, err),
+            CommandFailure{ address, what, code, stderr } => write!(f, "{} on '{}' failed with exit code {}\n\nstderr:\n{}\n\n", what.capitalize(), address, code.map(|c| c.to_string()).unwrap_or_else(|| "???".into()), BlockFormatter::new(stderr)),
+
+            ImageOpenError{ path, err }     => write!(f, "Failed to open image file '{}': {}", path.display(), err),
+            ImageExtractError{ path, err }  => write!(f, "Failed to extract image file '{}': {}", path.display(), err),
+            ManifestReadError{ path, err }  => write!(f, "Failed to read image manifest '{}': {}", path.display(), err),
+            ManifestParseError{ path, err } => write!(f, "Failed to parse image manifest '{}': {}", path.display(), err),
+            IllegalManifestNum{ path, got } => write!(f, "Image manifest '{}' describes {} images; expected exactly 1", path.display(), got),
+            LayerOpenError{ path, err }     => write!(f, "Failed to open image layer '{}': {}", path.display(), err),
+            DirRemoveError{ path, err }     => write!(f, "Failed to remove directory '{}': {}", path.display(), err),
+
+            IllegalDataPath{ path }    => write!(f, "Cannot copy '{}' to remote machine: path has no parent or no UTF-8 name", path.display()),
+            TarSpawnError{ path, err } => write!(f, "Failed to launch tar to archive '{}': {}", path.display(), err),
+            TarFailure{ path, code }   => write!(f, "Failed to archive '{}': tar returned exit code {}", path.display(), code.map(|c| c.to_string()).unwrap_or_else(|| "???".into())),
+
+            TarballCreateError{ path, err } => write!(f, "Failed to create tarball '{}': {}", path.display(), err),
+            ResultExtractError{ path, err } => write!(f, "Failed to extract result tarball '{}': {}", path.display(), err),
+            TarballRemoveError{ path, err } => write!(f, "Failed to remove tarball '{}': {}", path.display(), err),
+        }
+    }
+}
+
+impl Error for SshError {}
@@ -21,6 +21,9 @@ pub mod tools;
 pub mod docker;
 pub mod local;
 pub mod api;
+pub mod aws;
+pub mod ssh;
+pub mod tls;
 
 // The grpc module is a bit special
 #[allow(clippy::all)]
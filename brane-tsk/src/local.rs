@@ -72,6 +72,30 @@ pub fn get_package_versions(package_name: &str, package_dir: &Path) -> Result<Ve
     Ok(versions)
 }
 
+/// Reads a single DataInfo file of a locally available dataset.
+/// 
+/// # Arguments
+/// - `info_path`: The path to the `data.yml` file to read.
+/// 
+/// # Returns
+/// The DataInfo in the file.
+/// 
+/// # Errors
+/// This function errors if we failed to open or parse the file.
+fn read_data_info(info_path: PathBuf) -> Result<DataInfo, Error> {
+    // Attempt to open the file
+    let handle = match File::open(&info_path) {
+        Ok(handle) => handle,
+        Err(err)   => { return Err(Error::DataInfoOpenError{ path: info_path, err }); }
+    };
+
+    // Attempt to parse it
+    match serde_yaml::from_reader(handle) {
+        Ok(info) => Ok(info),
+        Err(err) => Err(Error::DataInfoReadError{ path: info_path, err }),
+    }
+}
+
 
 
 
@@ -133,6 +157,8 @@ pub fn get_package_index(packages: impl AsRef<Path>) -> Result<PackageIndex, Err
 
 /// Returns the an index of locally available datasets.
 /// 
+/// Datasets are either stored directly in a directory per dataset (`<name>/data.yml`), or in a directory per version of it (`<name>/<version>/data.yml`).
+/// 
 /// # Arguments
 /// - `packages_path`: The path to the directory that we read the packages from.
 /// 
@@ -158,25 +184,30 @@ pub fn get_data_index(datasets: impl AsRef<Path>) -> Result<DataIndex, Error> {
             Ok(d)    => d,
             Err(err) => { return Err(Error::DatasetsReadError { path: datasets_path.into(), err }); }
         };
+        let d_path: PathBuf = d.path();
+        if !d_path.is_dir() { continue; }
 
-        // If it's a directory, tentatively try to find a 'data.yml' file in there
-        let d_path    : PathBuf = d.path();
-        let info_path : PathBuf = d_path.join("data.yml");
-        if d_path.is_dir() && info_path.exists() {
-            // Attempt to open the file
-            let handle = match File::open(&info_path) {
-                Ok(handle) => handle,
-                Err(err)   => { return Err(Error::DataInfoOpenError{ path: info_path, err }); }
-            };
+        // If it has a 'data.yml' file, it's an unversioned dataset
+        let info_path: PathBuf = d_path.join("data.yml");
+        if info_path.exists() {
+            infos.push(read_data_info(info_path)?);
+            continue;
+        }
 
-            // Attempt to parse it
-            let info: DataInfo = match serde_yaml::from_reader(handle) {
-                Ok(info) => info,
-                Err(err) => { return Err(Error::DataInfoReadError{ path: info_path, err }); }
+        // Otherwise, tentatively try to find a 'data.yml' file in every version directory in there
+        let versions: ReadDir = match fs::read_dir(&d_path) {
+            Ok(versions) => versions,
+            Err(err)     => { return Err(Error::DatasetsReadError{ path: d_path, err }); }
+        };
+        for v in versions {
+            let v: DirEntry = match v {
+                Ok(v)    => v,
+                Err(err) => { return Err(Error::DatasetsReadError { path: d_path, err }); }
             };
-
-            // Add it to the index
-            infos.push(info);
+            let info_path: PathBuf = v.path().join("data.yml");
+            if v.path().is_dir() && info_path.exists() {
+                infos.push(read_data_info(info_path)?);
+            }
         }
     }
 
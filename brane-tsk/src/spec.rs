@@ -20,6 +20,7 @@ use uuid::Uuid;
 
 use brane_ast::Workflow;
 use brane_exe::FullValue;
+use specifications::status::{ResourceUsage, TaskOutput, TaskProgress};
 
 use crate::errors::{ExecuteError, IdError, PlanError};
 use crate::grpc::TaskStatus;
@@ -65,6 +66,22 @@ macro_rules! return_status_val {
     };
 }
 
+/// Defines a helper macro that parses an optional value for a JobStatus before returning it.
+macro_rules! return_status_opt_val {
+    (JobStatus::$status:ident, $str:ident) => {
+        {
+            if let Some(s) = $str {
+                match serde_json::from_str(&s) {
+                    Ok(val)  => Ok(JobStatus::$status(Some(val))),
+                    Err(err) => Err(ExecuteError::StatusValueParseError{ status: TaskStatus::$status, raw: s, err }),
+                }
+            } else {
+                Ok(JobStatus::$status(None))
+            }
+        }
+    };
+}
+
 /// Defines a helper macro that parses a code, stdout, stderr triplet for a JobStatus before returning it.
 macro_rules! return_status_failed {
     (JobStatus::$status:ident, $str:ident) => {
@@ -264,10 +281,12 @@ pub enum JobStatus {
     StartingFailed(String),
 
     // Progress events
-    /// Occassional message to let the user know the container is alive and running
-    Heartbeat,
-    /// The package call went successfully from the branelet's side
-    Completed,
+    /// Occassional message to let the user know the container is alive and running, optionally with any progress the task itself reported
+    Heartbeat(Option<TaskProgress>),
+    /// The package code wrote a line of output, which is relayed while it runs
+    Output(TaskOutput),
+    /// The package call went successfully from the branelet's side, optionally with the resources it consumed while doing so
+    Completed(Option<ResourceUsage>),
     /// The package call went wrong from the branelet's side
     CompletionFailed(String),
 
@@ -280,6 +299,10 @@ pub enum JobStatus {
     DecodingFailed(String),
     /// The container has exited with a non-zero status code
     Failed(i32, String, String),
+    /// The container did not complete within the given number of seconds and was killed
+    TimedOut(u64),
+    /// The container wrote more than the given number of bytes to its scratch space and was killed
+    ScratchExhausted(u64),
 }
 
 impl JobStatus {
@@ -315,14 +338,17 @@ impl JobStatus {
             Started              => { return_status!(JobStatus::Started, value) },
             StartingFailed       => { return_status_str!(JobStatus::StartingFailed, value) },
 
-            Heartbeat        => { return_status!(JobStatus::Heartbeat, value) },
-            Completed        => { return_status!(JobStatus::Completed, value) },
+            Heartbeat        => { return_status_opt_val!(JobStatus::Heartbeat, value) },
+            Output           => { return_status_val!(JobStatus::Output, value) },
+            Completed        => { return_status_opt_val!(JobStatus::Completed, value) },
             CompletionFailed => { return_status_str!(JobStatus::CompletionFailed, value) },
 
-            Finished       => { return_status_val!(JobStatus::Finished, value) },
-            Stopped        => { return_status!(JobStatus::Stopped, value) },
-            DecodingFailed => { return_status_str!(JobStatus::DecodingFailed, value) },
-            Failed         => { return_status_failed!(JobStatus::Failed, value) },
+            Finished         => { return_status_val!(JobStatus::Finished, value) },
+            Stopped          => { return_status!(JobStatus::Stopped, value) },
+            DecodingFailed   => { return_status_str!(JobStatus::DecodingFailed, value) },
+            Failed           => { return_status_failed!(JobStatus::Failed, value) },
+            TimedOut         => { return_status_val!(JobStatus::TimedOut, value) },
+            ScratchExhausted => { return_status_val!(JobStatus::ScratchExhausted, value) },
         }
     }
 
@@ -330,7 +356,7 @@ impl JobStatus {
 
     /// Returns whether this status is a heartbeat.
     #[inline]
-    pub fn is_heartbeat(&self) -> bool { matches!(self, Self::Heartbeat) }
+    pub fn is_heartbeat(&self) -> bool { matches!(self, Self::Heartbeat(_)) }
 
     /// Converts the JobStatus into some 'progress index', which is a number that can be used to determine if some JobStatus logically should be send after another.
     /// 
@@ -356,14 +382,17 @@ impl JobStatus {
             Started                 => 6,
             StartingFailed(_)       => 6,
 
-            Heartbeat           => 7,
-            Completed           => 8,
+            Heartbeat(_)        => 7,
+            Output(_)           => 7,
+            Completed(_)        => 8,
             CompletionFailed(_) => 8,
 
-            DecodingFailed(_) => 9,
-            Finished(_)       => 10,
-            Stopped           => 10,
-            Failed(_, _, _)   => 10,
+            DecodingFailed(_)   => 9,
+            Finished(_)         => 10,
+            Stopped             => 10,
+            Failed(_, _, _)     => 10,
+            TimedOut(_)         => 10,
+            ScratchExhausted(_) => 10,
         }
     }
 }
@@ -400,14 +429,17 @@ impl From<&JobStatus> for TaskStatus {
             Started                 => Self::Started,
             StartingFailed(_)       => Self::StartingFailed,
 
-            Heartbeat           => Self::Heartbeat,
-            Completed           => Self::Completed,
+            Heartbeat(_)        => Self::Heartbeat,
+            Output(_)           => Self::Output,
+            Completed(_)        => Self::Completed,
             CompletionFailed(_) => Self::CompletionFailed,
 
-            Finished(_)       => Self::Finished,
-            Stopped           => Self::Stopped,
-            DecodingFailed(_) => Self::DecodingFailed,
-            Failed(_, _, _)   => Self::Failed,
+            Finished(_)         => Self::Finished,
+            Stopped             => Self::Stopped,
+            DecodingFailed(_)   => Self::DecodingFailed,
+            Failed(_, _, _)     => Self::Failed,
+            TimedOut(_)         => Self::TimedOut,
+            ScratchExhausted(_) => Self::ScratchExhausted,
         }
     }
 }
@@ -438,14 +470,17 @@ impl From<&JobStatus> for (TaskStatus, Option<String>) {
             Started                   => (TaskStatus::Started, None),
             StartingFailed(err)       => (TaskStatus::StartingFailed, Some(err.clone())),
 
-            Heartbeat             => (TaskStatus::Heartbeat, None),
-            Completed             => (TaskStatus::Completed, None),
+            Heartbeat(progress)   => (TaskStatus::Heartbeat, progress.as_ref().map(|p| serde_json::to_string(p).unwrap())),
+            Output(output)        => (TaskStatus::Output, Some(serde_json::to_string(output).unwrap())),
+            Completed(usage)      => (TaskStatus::Completed, usage.as_ref().map(|u| serde_json::to_string(u).unwrap())),
             CompletionFailed(err) => (TaskStatus::CompletionFailed, Some(err.clone())),
 
             Finished(val)                => (TaskStatus::Finished, Some(serde_json::to_string(&val).unwrap())),
             Stopped                      => (TaskStatus::Stopped, None),
             DecodingFailed(err)          => (TaskStatus::DecodingFailed, Some(err.clone())),
             Failed(code, stdout, stderr) => (TaskStatus::Failed, Some(serde_json::to_string(&(code, stdout, stderr)).unwrap())),
+            TimedOut(timeout)            => (TaskStatus::TimedOut, Some(timeout.to_string())),
+            ScratchExhausted(limit)      => (TaskStatus::ScratchExhausted, Some(limit.to_string())),
         }
     }
 }
@@ -0,0 +1,413 @@
+//  SSH.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 10:05:01
+//  Last edited:
+//    16 Oct 2026, 10:05:01
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements functions to run tasks directly on a remote machine over
+//!   SSH, for sites that do not have any container runtime. The
+//!   package's filesystem is copied to the machine once, after which
+//!   branelet is run from it like it would be in a container.
+// 
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
+
+use log::debug;
+use serde::Deserialize;
+use tokio::fs as tfs;
+use tokio::process::Command;
+use tokio_tar::Archive;
+
+use brane_cfg::backend::SshCredentials;
+use brane_exe::FullValue;
+use brane_shr::fs::unarchive_async;
+use specifications::container::VolumeBind;
+use specifications::version::Version;
+
+pub use crate::errors::SshError as Error;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Tests quoting arguments for the remote shell.
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("plain"), "'plain'");
+        assert_eq!(quote(""), "''");
+        assert_eq!(quote("with spaces  and\ttabs"), "'with spaces  and\ttabs'");
+        assert_eq!(quote("it's"), "'it'\\''s'");
+        assert_eq!(quote("''"), "''\\'''\\'''");
+        assert_eq!(quote("$HOME; rm -rf / && `id` | cat > /dev/null"), "'$HOME; rm -rf / && `id` | cat > /dev/null'");
+    }
+
+    /// Tests that a shell actually passes quoted arguments through unchanged.
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_quote_shell() {
+        for arg in [ "plain", "", "with spaces  and\ttabs", "it's", "''", "\"double\" \\ back", "$HOME $(id) `id` ${PATH}", "a; b && c || d | e > f < g & h", "*?[a-z]~{x,y}#!", "line\nbreak" ] {
+            let output = Command::new("sh").arg("-c").arg(format!("printf '%s' {}", quote(arg))).output().await.unwrap();
+            assert!(output.status.success());
+            assert_eq!(String::from_utf8(output.stdout).unwrap(), arg);
+        }
+    }
+}
+
+
+
+
+
+/***** CONSTANTS *****/
+/// The name of the directory (in the remote working directory) where packages are installed.
+pub const PACKAGES_DIR: &str = "packages";
+/// The name of the directory (in the remote working directory) where tasks are run.
+pub const TASKS_DIR: &str = "tasks";
+
+/// The name of the environment variable that tells package code where to write its intermediate result, since it cannot use `/result` outside of a container.
+pub const RESULT_DIR_ENV: &str = "BRANE_RESULT_DIR";
+
+
+
+
+
+/***** HELPER STRUCTS *****/
+/// The part of a Docker manifest file that lists the layers of the image.
+#[derive(Clone, Debug, Deserialize)]
+struct DockerLayersManifest {
+    /// The paths (in the image tar) of the layer tars, from bottom to top.
+    #[serde(rename = "Layers")]
+    layers : Vec<String>,
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Quotes the given string such that the remote shell passes it as a single, literal argument.
+/// 
+/// # Arguments
+/// - `arg`: The argument to quote.
+/// 
+/// # Returns
+/// The argument wrapped in single quotes.
+fn quote(arg: impl AsRef<str>) -> String {
+    format!("'{}'", arg.as_ref().replace('\'', "'\\''"))
+}
+
+/// Prepares an `ssh` command that connects to the machine in the given credentials.
+/// 
+/// # Arguments
+/// - `creds`: The SshCredentials that describe the machine to connect to.
+/// - `command`: The (shell) command to run on the remote machine.
+/// 
+/// # Returns
+/// A new Command that can be spawned as-is or with redirected standard streams.
+fn ssh(creds: &SshCredentials, command: impl AsRef<str>) -> Command {
+    let mut cmd: Command = Command::new("ssh");
+    cmd.args([ "-o", "BatchMode=yes", "-i" ]);
+    cmd.arg(&creds.key);
+
+    // Split the port off the address, if any
+    match creds.address.rsplit_once(':') {
+        Some((host, port)) if port.parse::<u16>().is_ok() => { cmd.args([ "-p", port, host ]); },
+        _                                                  => { cmd.arg(&creds.address); },
+    }
+
+    cmd.arg("--").arg(command.as_ref());
+    cmd
+}
+
+/// Runs the given `ssh` command to completion and checks that it succeeded.
+/// 
+/// # Arguments
+/// - `creds`: The SshCredentials that describe the machine we connected to (for debugging purposes).
+/// - `what`: A description of what the command does (for debugging purposes).
+/// - `cmd`: The Command to run.
+/// 
+/// # Errors
+/// This function errors if we failed to launch `ssh` or if the command returned a non-zero exit code.
+async fn run(creds: &SshCredentials, what: &'static str, mut cmd: Command) -> Result<(), Error> {
+    debug!("Running {} on '{}'...", what, creds.address);
+    let output = match cmd.output().await {
+        Ok(output) => output,
+        Err(err)   => { return Err(Error::SpawnError{ address: creds.address.clone(), what, err }); },
+    };
+    if !output.status.success() { return Err(Error::CommandFailure{ address: creds.address.clone(), what, code: output.status.code(), stderr: String::from_utf8_lossy(&output.stderr).into() }); }
+    Ok(())
+}
+
+/// Replaces the given prefix in every path in the given argument.
+/// 
+/// # Arguments
+/// - `value`: The (preprocessed) argument to update.
+/// - `from`: The prefix to replace.
+/// - `to`: The prefix to replace it with.
+fn rebase_arg(value: &mut FullValue, from: &Path, to: &Path) {
+    match value {
+        FullValue::String(path) => {
+            if let Ok(rest) = Path::new(path.as_str()).strip_prefix(from) { *path = to.join(rest).to_string_lossy().to_string(); }
+        },
+
+        FullValue::Array(values)      => { for v in values { rebase_arg(v, from, to); } },
+        FullValue::Instance(_, props) => { for v in props.values_mut() { rebase_arg(v, from, to); } },
+        FullValue::Map(values)        => { for v in values.values_mut() { rebase_arg(v, from, to); } },
+
+        _ => {},
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Returns the directory on the remote machine where the given package is installed.
+/// 
+/// # Arguments
+/// - `creds`: The SshCredentials that define the remote working directory.
+/// - `name`: The name of the package.
+/// - `version`: The version of the package.
+/// 
+/// # Returns
+/// The path to the package's filesystem on the remote machine.
+#[inline]
+pub fn package_dir(creds: &SshCredentials, name: impl AsRef<str>, version: &Version) -> PathBuf {
+    creds.workdir.join(PACKAGES_DIR).join(format!("{}-{}", name.as_ref(), version))
+}
+
+/// Makes sure that the given package is installed on the remote machine, copying its filesystem over if it is not.
+/// 
+/// The filesystem is reconstructed by extracting the image's layers on top of each other on the remote machine. Note that files deleted by later layers are not removed, which is harmless for running branelet.
+/// 
+/// # Arguments
+/// - `creds`: The SshCredentials that describe the machine to install the package on.
+/// - `name`: The name of the package.
+/// - `version`: The version of the package.
+/// - `image_path`: The path to the package's `image.tar` on this node.
+/// 
+/// # Returns
+/// The path to the package's filesystem on the remote machine.
+/// 
+/// # Errors
+/// This function errors if we failed to read the image or to copy it over.
+pub async fn install(creds: &SshCredentials, name: impl AsRef<str>, version: &Version, image_path: impl AsRef<Path>) -> Result<PathBuf, Error> {
+    let name       : &str    = name.as_ref();
+    let image_path : &Path   = image_path.as_ref();
+    let remote     : PathBuf = package_dir(creds, name, version);
+    if creds.preinstalled { return Ok(remote); }
+
+    // Nothing to do if it's already there
+    let status: ExitStatus = match ssh(creds, format!("test -x {}", quote(remote.join("branelet").to_string_lossy()))).stdin(Stdio::null()).status().await {
+        Ok(status) => status,
+        Err(err)   => { return Err(Error::SpawnError{ address: creds.address.clone(), what: "package check", err }); },
+    };
+    if status.success() { debug!("Package '{}' ({}) is already installed on '{}'", name, version, creds.address); return Ok(remote); }
+    debug!("Installing package '{}' ({}) on '{}'...", name, version, creds.address);
+
+    // Unpack the image locally to get to its layers
+    let image_dir: PathBuf = image_path.with_extension("layers");
+    if image_dir.exists() {
+        if let Err(err) = tfs::remove_dir_all(&image_dir).await { return Err(Error::DirRemoveError{ path: image_dir, err }); }
+    }
+    let handle: tfs::File = match tfs::File::open(image_path).await {
+        Ok(handle) => handle,
+        Err(err)   => { return Err(Error::ImageOpenError{ path: image_path.into(), err }); },
+    };
+    if let Err(err) = Archive::new(handle).unpack(&image_dir).await { return Err(Error::ImageExtractError{ path: image_path.into(), err }); }
+
+    // Read which layers there are
+    let manifest_path: PathBuf = image_dir.join("manifest.json");
+    let manifest: Vec<u8> = match tfs::read(&manifest_path).await {
+        Ok(manifest) => manifest,
+        Err(err)     => { return Err(Error::ManifestReadError{ path: manifest_path, err }); },
+    };
+    let mut manifest: Vec<DockerLayersManifest> = match serde_json::from_slice(&manifest) {
+        Ok(manifest) => manifest,
+        Err(err)     => { return Err(Error::ManifestParseError{ path: manifest_path, err }); },
+    };
+    if manifest.len() != 1 { return Err(Error::IllegalManifestNum{ path: manifest_path, got: manifest.len() }); }
+    let layers: Vec<String> = manifest.pop().unwrap().layers;
+
+    // Extract them one-by-one in a temporary directory on the remote, which we only move in place once complete
+    let staging: String = quote(format!("{}.partial", remote.to_string_lossy()));
+    run(creds, "package staging", ssh(creds, format!("rm -rf {staging} && mkdir -p {staging}"))).await?;
+    for layer in layers {
+        let layer_path: PathBuf = image_dir.join(&layer);
+        debug!("Copying layer '{}' to '{}'...", layer, creds.address);
+        let handle: std::fs::File = match std::fs::File::open(&layer_path) {
+            Ok(handle) => handle,
+            Err(err)   => { return Err(Error::LayerOpenError{ path: layer_path, err }); },
+        };
+        let mut cmd: Command = ssh(creds, format!("tar -xf - -C {} --no-same-owner", staging));
+        cmd.stdin(Stdio::from(handle));
+        run(creds, "layer extraction", cmd).await?;
+    }
+    run(creds, "package installation", ssh(creds, format!("rm -rf {remote} && mv {staging} {remote}", remote = quote(remote.to_string_lossy())))).await?;
+
+    // We don't need the local layers anymore
+    if let Err(err) = tfs::remove_dir_all(&image_dir).await { return Err(Error::DirRemoveError{ path: image_dir, err }); }
+    Ok(remote)
+}
+
+/// Prepares a fresh directory for a task on the remote machine, copying any datasets or intermediate results it needs into it.
+/// 
+/// # Arguments
+/// - `creds`: The SshCredentials that describe the machine to run the task on.
+/// - `name`: The name of the task, which is used (together with a random suffix) as the name of the directory.
+/// - `args`: The task's (preprocessed) arguments. Paths to datasets are updated to where they live on the remote machine.
+/// - `binds`: The VolumeBinds generated when preprocessing the arguments, which describe what to copy where.
+/// 
+/// # Returns
+/// The path to the task's directory on the remote machine.
+/// 
+/// # Errors
+/// This function errors if we failed to create the directory or to copy the data.
+pub async fn prepare(creds: &SshCredentials, name: impl AsRef<str>, args: &mut HashMap<String, FullValue>, binds: &[VolumeBind]) -> Result<PathBuf, Error> {
+    let task_dir: PathBuf = creds.workdir.join(TASKS_DIR).join(format!("{}-{}", name.as_ref(), &uuid::Uuid::new_v4().to_string()[..6]));
+    debug!("Preparing task directory '{}' on '{}'...", task_dir.display(), creds.address);
+    run(creds, "task preparation", ssh(creds, format!("mkdir -p {}", quote(task_dir.join("result").to_string_lossy())))).await?;
+
+    // Copy every (read-only) bind over, which are the inputs; the result is written to the directory we just created
+    for bind in binds.iter().filter(|b| !b.options.is_empty()) {
+        let target: PathBuf = task_dir.join(bind.container.strip_prefix("/").unwrap_or(&bind.container));
+        debug!("Copying '{}' to '{}:{}'...", bind.host.display(), creds.address, target.display());
+
+        // Decide how to stream it as a tar; directories are copied by contents, files by name
+        let mut tar: Command = Command::new("tar");
+        let command: String = if bind.host.is_dir() {
+            tar.arg("-cf").arg("-").arg("-C").arg(&bind.host).arg(".");
+            format!("mkdir -p {target} && tar -xf - -C {target}", target = quote(target.to_string_lossy()))
+        } else {
+            let (parent, name, target_parent): (&Path, &str, &Path) = match (bind.host.parent(), bind.host.file_name().and_then(|n| n.to_str()), target.parent()) {
+                (Some(parent), Some(name), Some(target_parent)) => (parent, name, target_parent),
+                _                                               => { return Err(Error::IllegalDataPath{ path: bind.host.clone() }); },
+            };
+            tar.arg("-cf").arg("-").arg("-C").arg(parent).arg(name);
+            let mut command: String = format!("mkdir -p {parent} && tar -xf - -C {parent}", parent = quote(target_parent.to_string_lossy()));
+            if target.file_name().and_then(|n| n.to_str()) != Some(name) { command.push_str(&format!(" && mv -f {} {}", quote(target_parent.join(name).to_string_lossy()), quote(target.to_string_lossy()))); }
+            command
+        };
+        let mut tar = match tar.stdout(Stdio::piped()).spawn() {
+            Ok(tar)  => tar,
+            Err(err) => { return Err(Error::TarSpawnError{ path: bind.host.clone(), err }); },
+        };
+        let stdout: Stdio = match tar.stdout.take().map(|stdout| stdout.try_into()) {
+            Some(Ok(stdout)) => stdout,
+            Some(Err(err))   => { return Err(Error::TarSpawnError{ path: bind.host.clone(), err }); },
+            None             => { unreachable!(); },
+        };
+
+        // Pipe it through SSH
+        let mut cmd: Command = ssh(creds, command);
+        cmd.stdin(stdout);
+        run(creds, "data upload", cmd).await?;
+        match tar.wait().await {
+            Ok(status) if status.success() => {},
+            Ok(status)                     => { return Err(Error::TarFailure{ path: bind.host.clone(), code: status.code() }); },
+            Err(err)                       => { return Err(Error::TarSpawnError{ path: bind.host.clone(), err }); },
+        }
+
+        // Point the arguments to it
+        for value in args.values_mut() { rebase_arg(value, &bind.container, &target); }
+    }
+
+    Ok(task_dir)
+}
+
+/// Runs branelet on the remote machine.
+/// 
+/// # Arguments
+/// - `creds`: The SshCredentials that describe the machine to run the task on.
+/// - `package_dir`: The path to the package's filesystem on the remote machine (see `install()`).
+/// - `task_dir`: The path to the task's directory on the remote machine (see `prepare()`).
+/// - `command`: The arguments to pass to branelet.
+/// 
+/// # Returns
+/// The exit code, stdout and stderr of branelet.
+/// 
+/// # Errors
+/// This function errors if we failed to reach the remote machine. Note that a failing task is _not_ an error.
+pub async fn execute(creds: &SshCredentials, package_dir: impl AsRef<Path>, task_dir: impl AsRef<Path>, command: Vec<String>) -> Result<(i32, String, String), Error> {
+    let package_dir : &Path   = package_dir.as_ref();
+    let task_dir    : &Path   = task_dir.as_ref();
+    let wd          : PathBuf = package_dir.join("opt/wd");
+
+    // Run branelet from the package's working directory, like it would in the container
+    let command: String = format!("cd {wd} && BRANE_WORKDIR={wd} {env}={result} {branelet} {args}",
+        wd       = quote(wd.to_string_lossy()),
+        env      = RESULT_DIR_ENV,
+        result   = quote(task_dir.join("result").to_string_lossy()),
+        branelet = quote(package_dir.join("branelet").to_string_lossy()),
+        args     = command.iter().map(quote).collect::<Vec<String>>().join(" "),
+    );
+    debug!("Running branelet on '{}': {}", creds.address, command);
+    let mut cmd: Command = ssh(creds, command);
+    cmd.stdin(Stdio::null()).kill_on_drop(true);
+    let output = match cmd.output().await {
+        Ok(output) => output,
+        Err(err)   => { return Err(Error::SpawnError{ address: creds.address.clone(), what: "branelet", err }); },
+    };
+
+    // Note that `ssh` itself returns 255 if it failed to connect
+    match output.status.code() {
+        Some(255)  => Err(Error::CommandFailure{ address: creds.address.clone(), what: "branelet", code: Some(255), stderr: String::from_utf8_lossy(&output.stderr).into() }),
+        Some(code) => Ok((code, String::from_utf8_lossy(&output.stdout).into(), String::from_utf8_lossy(&output.stderr).into())),
+        None       => Err(Error::CommandFailure{ address: creds.address.clone(), what: "branelet", code: None, stderr: String::from_utf8_lossy(&output.stderr).into() }),
+    }
+}
+
+/// Copies the intermediate result written by a task back to this node.
+/// 
+/// # Arguments
+/// - `creds`: The SshCredentials that describe the machine the task ran on.
+/// - `task_dir`: The path to the task's directory on the remote machine (see `prepare()`).
+/// - `target`: The local directory to write the result to. Will be replaced if it already exists.
+/// 
+/// # Errors
+/// This function errors if we failed to download or extract the result.
+pub async fn retrieve(creds: &SshCredentials, task_dir: impl AsRef<Path>, target: impl AsRef<Path>) -> Result<(), Error> {
+    let task_dir : &Path = task_dir.as_ref();
+    let target   : &Path = target.as_ref();
+    debug!("Retrieving result from '{}:{}' to '{}'...", creds.address, task_dir.display(), target.display());
+
+    // Download the result directory as a tarball
+    let tarball: PathBuf = target.with_extension("tar.gz");
+    let handle: std::fs::File = match std::fs::File::create(&tarball) {
+        Ok(handle) => handle,
+        Err(err)   => { return Err(Error::TarballCreateError{ path: tarball, err }); },
+    };
+    let mut cmd: Command = ssh(creds, format!("tar -czf - -C {} .", quote(task_dir.join("result").to_string_lossy())));
+    cmd.stdout(Stdio::from(handle));
+    run(creds, "result download", cmd).await?;
+
+    // Extract it in place of the target
+    if target.exists() {
+        if let Err(err) = tfs::remove_dir_all(target).await { return Err(Error::DirRemoveError{ path: target.into(), err }); }
+    }
+    if let Err(err) = unarchive_async(&tarball, target).await { return Err(Error::ResultExtractError{ path: tarball, err }); }
+    if let Err(err) = tfs::remove_file(&tarball).await { return Err(Error::TarballRemoveError{ path: tarball, err }); }
+    Ok(())
+}
+
+/// Removes the directory of a task from the remote machine.
+/// 
+/// # Arguments
+/// - `creds`: The SshCredentials that describe the machine the task ran on.
+/// - `task_dir`: The path to the task's directory on the remote machine (see `prepare()`).
+/// 
+/// # Errors
+/// This function errors if we failed to remove the directory.
+pub async fn cleanup(creds: &SshCredentials, task_dir: impl AsRef<Path>) -> Result<(), Error> {
+    run(creds, "task cleanup", ssh(creds, format!("rm -rf {}", quote(task_dir.as_ref().to_string_lossy())))).await
+}
@@ -0,0 +1,151 @@
+//  TLS.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 12:51:53
+//  Last edited:
+//    16 Oct 2026, 12:51:53
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements the client side of connecting to the central services
+//!   of an instance (`brane-api` and `brane-drv`) over TLS, presenting
+//!   an identity if we have one.
+// 
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::debug;
+use reqwest::{Client, ClientBuilder};
+use tonic::transport::{Channel, ClientTlsConfig, Endpoint};
+
+use crate::grpc::DriverServiceClient;
+
+pub use crate::errors::TlsError as Error;
+
+
+/***** CONSTANTS *****/
+/// The name of the file with the CA that signed the central services' certificates.
+pub const CA_FILE: &str = "ca.pem";
+/// The name of the file with our own certificate and key.
+pub const IDENTITY_FILE: &str = "client-id.pem";
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Reads the given file in the given directory, if it exists.
+///
+/// # Arguments
+/// - `dir`: The directory to read the file from.
+/// - `file`: The name of the file to read.
+///
+/// # Returns
+/// The raw contents of the file, or `None` if there is no such file.
+///
+/// # Errors
+/// This function errors if the file exists but we failed to read it.
+fn read_optional(dir: &Path, file: &str) -> Result<Option<Vec<u8>>, Error> {
+    let path: PathBuf = dir.join(file);
+    if !path.exists() { return Ok(None); }
+    match fs::read(&path) {
+        Ok(raw)  => Ok(Some(raw)),
+        Err(err) => Err(Error::FileReadError{ path, err }),
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines how we connect to the central services of an instance.
+///
+/// Plaintext (`http://`) addresses are always used as-is; the certificates only come into play for `https://` addresses.
+#[derive(Clone, Debug)]
+pub struct ClientTls {
+    /// The client with which to send requests to `brane-api`.
+    api : Client,
+    /// The TLS configuration with which to connect to `brane-drv`, if we have any certificates.
+    drv : Option<ClientTlsConfig>,
+}
+
+impl Default for ClientTls {
+    #[inline]
+    fn default() -> Self { Self { api: Client::new(), drv: None } }
+}
+
+impl ClientTls {
+    /// Constructor for the ClientTls that loads the certificates from the given directory.
+    ///
+    /// The directory may contain a `ca.pem` with the CA that signed the services' certificates, and a `client-id.pem` with our own certificate and key to present to them. Either may be omitted.
+    ///
+    /// # Arguments
+    /// - `dir`: The directory with the certificates.
+    ///
+    /// # Returns
+    /// A new ClientTls that trusts the CA and presents the identity, if any.
+    ///
+    /// # Errors
+    /// This function errors if we failed to read or parse the certificates, or to create a client with them.
+    pub fn from_dir(dir: impl AsRef<Path>) -> Result<Self, Error> {
+        let dir: &Path = dir.as_ref();
+        let ca       : Option<Vec<u8>> = read_optional(dir, CA_FILE)?;
+        let identity : Option<Vec<u8>> = read_optional(dir, IDENTITY_FILE)?;
+        debug!("Loaded central certificates from '{}' (CA: {}, identity: {})", dir.display(), if ca.is_some() { "yes" } else { "no" }, if identity.is_some() { "yes" } else { "no" });
+        if ca.is_none() && identity.is_none() { return Ok(Self::default()); }
+
+        // Prepare the client for the API
+        let mut api : ClientBuilder   = Client::builder().use_rustls_tls();
+        let mut drv : ClientTlsConfig = ClientTlsConfig::new();
+        if let Some(ca) = &ca {
+            match reqwest::Certificate::from_pem(ca) {
+                Ok(cert) => { api = api.add_root_certificate(cert); },
+                Err(err) => { return Err(Error::CertificateParseError{ path: dir.join(CA_FILE), err }); },
+            }
+            drv = drv.ca_certificate(tonic::transport::Certificate::from_pem(ca));
+        }
+        if let Some(identity) = &identity {
+            match reqwest::Identity::from_pem(identity) {
+                Ok(identity) => { api = api.identity(identity); },
+                Err(err)     => { return Err(Error::IdentityParseError{ path: dir.join(IDENTITY_FILE), err }); },
+            }
+            // The file contains both the certificate and the key, and tonic picks the one it needs from each
+            drv = drv.identity(tonic::transport::Identity::from_pem(identity, identity));
+        }
+        let api: Client = match api.build() {
+            Ok(api)  => api,
+            Err(err) => { return Err(Error::ClientBuildError{ err }); },
+        };
+
+        // Done
+        Ok(Self { api, drv: Some(drv) })
+    }
+
+
+
+    /// Connects to the driver at the given address, over TLS if it is an `https://` address.
+    ///
+    /// # Arguments
+    /// - `endpoint`: The address of the driver.
+    ///
+    /// # Returns
+    /// A new DriverServiceClient connected to the driver.
+    ///
+    /// # Errors
+    /// This function errors if the address is invalid or if we failed to connect.
+    pub async fn connect_driver(&self, endpoint: impl Into<String>) -> Result<DriverServiceClient<Channel>, tonic::transport::Error> {
+        let mut endpoint: Endpoint = Endpoint::from_shared(endpoint.into())?;
+        if let (Some(tls), Some("https")) = (&self.drv, endpoint.uri().scheme_str()) {
+            endpoint = endpoint.tls_config(tls.clone())?;
+        }
+        Ok(DriverServiceClient::new(endpoint.connect().await?))
+    }
+
+    /// Returns the client with which to send requests to the API.
+    #[inline]
+    pub fn api(&self) -> &Client { &self.api }
+}
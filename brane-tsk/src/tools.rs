@@ -12,9 +12,43 @@
 //!   Contains generic tools to use across the use-cases.
 // 
 
+use std::collections::HashMap;
+
+use tonic::Status;
+use tonic::metadata::{MetadataKey, MetadataMap, MetadataValue};
+use tracing::Span;
+
+use brane_shr::otel;
+
 use crate::errors::ExecuteError;
 
 
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Tests that conflicts are told apart from other aborted calls.
+    #[test]
+    fn test_is_conflict() {
+        assert!(is_conflict(&conflict_status("Dataset 'a' is at revision 2, but the commit expected to replace revision 1")));
+        assert!(!is_conflict(&Status::aborted("Workflow was killed")));
+        assert!(!is_conflict(&Status::internal("An internal error occurred")));
+    }
+}
+
+/***** CONSTANTS *****/
+/// The gRPC metadata in which the other services on a node tell the service they call on whose behalf they call it (i.e., the `CN` in the certificate of their own client).
+pub const CLIENT_METADATA: &str = "x-brane-client";
+
+/// The gRPC metadata that marks an error status as a lost race on committing a result (see `conflict_status()`).
+pub const CONFLICT_METADATA: &str = "x-brane-conflict";
+
+
+
+
+
 /***** LIBRARY *****/
 /// Decodes the given base64 string to a normal string if it is UTF-8.
 /// 
@@ -43,3 +77,82 @@ pub fn decode_base64(raw: impl AsRef<str>) -> Result<String, ExecuteError> {
 
     // We leave JSON for another day
 }
+
+
+
+/// Adds the trace context of the given span to the metadata of a gRPC request, such that the receiving service may continue the trace.
+/// 
+/// # Arguments
+/// - `span`: The Span to send the context of.
+/// - `request`: The request to add the context to.
+pub fn inject_trace<T>(span: &Span, request: &mut tonic::Request<T>) {
+    for (key, value) in otel::context(span) {
+        if let (Ok(key), Ok(value)) = (MetadataKey::from_bytes(key.as_bytes()), MetadataValue::try_from(value)) {
+            request.metadata_mut().insert(key, value);
+        }
+    }
+}
+
+/// Reads the trace context from the metadata of a gRPC request.
+/// 
+/// # Arguments
+/// - `request`: The request to read the context from.
+/// 
+/// # Returns
+/// A map of headers that describe the context, to be given to `brane_shr::otel::set_parent()`.
+pub fn extract_trace<T>(request: &tonic::Request<T>) -> HashMap<String, String> {
+    request.metadata().iter().filter_map(|entry| match entry {
+        tonic::metadata::KeyAndValueRef::Ascii(key, value) => value.to_str().ok().map(|value| (key.as_str().into(), value.into())),
+        tonic::metadata::KeyAndValueRef::Binary(_, _)      => None,
+    }).collect()
+}
+
+
+
+/// Tells the receiving service on whose behalf a gRPC request is sent, by adding the name of the client to its metadata.
+/// 
+/// Only services that can only be reached by the other services on the same node should trust it (see `extract_client()`).
+/// 
+/// # Arguments
+/// - `name`: The name of the client (as given in its certificate).
+/// - `request`: The request to add the name to.
+pub fn inject_client<T>(name: &str, request: &mut tonic::Request<T>) {
+    if let Ok(value) = MetadataValue::try_from(name) {
+        request.metadata_mut().insert(CLIENT_METADATA, value);
+    }
+}
+
+/// Creates an error status that tells the caller it lost a race on committing a result to a dataset, which it may retry against the dataset's latest revision.
+/// 
+/// # Arguments
+/// - `message`: The message that explains the conflict.
+/// 
+/// # Returns
+/// An `ABORTED` status that `is_conflict()` recognizes (other `ABORTED` statuses, such as those of killed workflows, are not).
+pub fn conflict_status(message: impl Into<String>) -> Status {
+    let mut metadata: MetadataMap = MetadataMap::new();
+    metadata.insert(CONFLICT_METADATA, MetadataValue::from_static("true"));
+    Status::with_metadata(tonic::Code::Aborted, message, metadata)
+}
+
+/// Checks whether the given status was created by `conflict_status()`.
+/// 
+/// # Arguments
+/// - `status`: The status to check.
+/// 
+/// # Returns
+/// True if the status reports a lost race on committing a result, or false otherwise.
+pub fn is_conflict(status: &Status) -> bool {
+    status.code() == tonic::Code::Aborted && status.metadata().contains_key(CONFLICT_METADATA)
+}
+
+/// Reads on whose behalf a gRPC request was sent, as added by `inject_client()`.
+/// 
+/// # Arguments
+/// - `request`: The request to read the name from.
+/// 
+/// # Returns
+/// The name of the client, or `None` if the sender did not call on behalf of a client.
+pub fn extract_client<T>(request: &tonic::Request<T>) -> Option<String> {
+    request.metadata().get(CLIENT_METADATA).and_then(|value| value.to_str().ok()).map(String::from)
+}
@@ -0,0 +1,224 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+use brane_cfg::backend::{AwsCredentials, JobDefinitionSpec};
+use brane_shr::fs::archive_async;
+use brane_tsk::aws::{self, AwsClients, JobState, Staging};
+use specifications::container::{Image, VolumeBind};
+
+
+/***** HELPERS *****/
+/// A request received by the mock.
+#[derive(Clone, Debug)]
+struct Recorded {
+    /// The method of the request.
+    method : String,
+    /// The path (and query) of the request.
+    path   : String,
+    /// The body of the request.
+    body   : Vec<u8>,
+}
+
+/// Decides how the mock responds to a request, as a status code and a body.
+type Handler = Arc<dyn Fn(&Recorded) -> (u16, Vec<u8>) + Send + Sync>;
+
+
+
+/// Starts a mock of the AWS endpoints that answers every request with the given handler.
+///
+/// # Returns
+/// The address of the mock and the list of requests it received.
+async fn mock(handler: Handler) -> (String, Arc<Mutex<Vec<Recorded>>>) {
+    let listener: TcpListener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address: String = format!("http://{}", listener.local_addr().unwrap());
+    let requests: Arc<Mutex<Vec<Recorded>>> = Arc::new(Mutex::new(vec![]));
+
+    let received: Arc<Mutex<Vec<Recorded>>> = requests.clone();
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let handler: Handler = handler.clone();
+            let received: Arc<Mutex<Vec<Recorded>>> = received.clone();
+            tokio::spawn(async move {
+                let mut stream = BufReader::new(stream);
+
+                // Read the request line and the headers we care about
+                let mut line: String = String::new();
+                stream.read_line(&mut line).await.unwrap();
+                let mut parts = line.split_whitespace();
+                let (method, path): (String, String) = (parts.next().unwrap_or_default().into(), parts.next().unwrap_or_default().into());
+                let mut length: usize = 0;
+                loop {
+                    let mut header: String = String::new();
+                    stream.read_line(&mut header).await.unwrap();
+                    if header.trim().is_empty() { break; }
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") { length = value.trim().parse().unwrap(); }
+                    }
+                }
+                let mut body: Vec<u8> = vec![ 0; length ];
+                stream.read_exact(&mut body).await.unwrap();
+
+                // Answer it
+                let request: Recorded = Recorded{ method, path, body };
+                let (status, body): (u16, Vec<u8>) = handler(&request);
+                received.lock().unwrap().push(request);
+                let mut response: Vec<u8> = format!("HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", status, body.len()).into_bytes();
+                response.extend(body);
+                stream.get_mut().write_all(&response).await.unwrap();
+                stream.get_mut().shutdown().await.unwrap();
+            });
+        }
+    });
+    (address, requests)
+}
+
+/// Returns AwsCredentials that send everything to the given endpoint.
+fn creds(endpoint: &str) -> AwsCredentials {
+    AwsCredentials {
+        region            : "eu-west-1".into(),
+        access_key_id     : Some("AKIDEXAMPLE".into()),
+        secret_access_key : Some("secret".into()),
+        endpoint          : Some(endpoint.into()),
+
+        job_queue      : "brane-queue".into(),
+        registry       : "registry.example.com/brane".into(),
+        bucket         : "brane-bucket".into(),
+        job_definition : JobDefinitionSpec{ vcpus: 1, memory: 2048, gpus: 0, job_role: None, execution_role: None },
+        capabilities   : HashMap::new(),
+
+        lambda : None,
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[tokio::test]
+async fn test_ensure_job_definition_registers_missing() {
+    let (endpoint, requests) = mock(Arc::new(|req: &Recorded| match req.path.as_str() {
+        "/v1/describejobdefinitions" => (200, br#"{"jobDefinitions":[]}"#.to_vec()),
+        "/v1/registerjobdefinition"  => (200, br#"{"jobDefinitionName":"brane-hello","jobDefinitionArn":"arn:aws:batch:eu-west-1:0:job-definition/brane-hello:1","revision":1}"#.to_vec()),
+        _                            => (404, vec![]),
+    })).await;
+    let creds: AwsCredentials = creds(&endpoint);
+    let clients: AwsClients = AwsClients::new(&creds).await;
+
+    let image: Image = Image::new("hello", Some("1.0.0"), None::<String>);
+    let name: String = aws::ensure_job_definition(&clients, &creds, &image, &creds.job_definition).await.unwrap();
+    assert!(name.starts_with("brane-hello-1_0_0-"), "Unexpected job definition name '{}'", name);
+
+    // It should have registered it with the image in our registry
+    let requests: Vec<Recorded> = requests.lock().unwrap().clone();
+    let register: &Recorded = requests.iter().find(|req| req.path == "/v1/registerjobdefinition").expect("Job definition was not registered");
+    let body: serde_json::Value = serde_json::from_slice(&register.body).unwrap();
+    assert_eq!(body["jobDefinitionName"], name);
+    assert_eq!(body["containerProperties"]["image"], "registry.example.com/brane/hello:1.0.0");
+}
+
+#[tokio::test]
+async fn test_submit_and_poll() {
+    let (endpoint, requests) = mock(Arc::new(|req: &Recorded| match req.path.as_str() {
+        "/v1/submitjob"    => (200, br#"{"jobId":"job-1","jobName":"hello","jobArn":"arn:aws:batch:eu-west-1:0:job/job-1"}"#.to_vec()),
+        "/v1/describejobs" => (200, br#"{"jobs":[{"jobId":"job-1","jobName":"hello","jobQueue":"brane-queue","jobDefinition":"brane-hello","status":"SUCCEEDED","startedAt":1,"container":{"exitCode":3,"logStreamName":"brane-hello/default/job-1"}}]}"#.to_vec()),
+        _                  => (404, vec![]),
+    })).await;
+    let creds: AwsCredentials = creds(&endpoint);
+    let clients: AwsClients = AwsClients::new(&creds).await;
+
+    let id: String = aws::submit(&clients, &creds, "hello world", "brane-hello", vec![ "-d".into(), "ecu".into() ], Some(10)).await.unwrap();
+    assert_eq!(id, "job-1");
+    match aws::poll(&clients, &id).await.unwrap() {
+        JobState::Finished{ code, log_stream, .. } => {
+            assert_eq!(code, Some(3));
+            assert_eq!(log_stream.as_deref(), Some("brane-hello/default/job-1"));
+        },
+        state => { panic!("Expected a finished job, got {:?}", state); },
+    }
+
+    // The job should have been submitted to our queue, with a sanitized name, our command and the minimum timeout AWS accepts
+    let requests: Vec<Recorded> = requests.lock().unwrap().clone();
+    let submit: serde_json::Value = serde_json::from_slice(&requests.iter().find(|req| req.path == "/v1/submitjob").unwrap().body).unwrap();
+    assert_eq!(submit["jobName"], "hello_world");
+    assert_eq!(submit["jobQueue"], "brane-queue");
+    assert_eq!(submit["containerOverrides"]["command"], serde_json::json!([ "-d", "ecu" ]));
+    assert_eq!(submit["timeout"]["attemptDurationSeconds"], 60);
+}
+
+#[tokio::test]
+async fn test_invoke() {
+    let (endpoint, requests) = mock(Arc::new(|req: &Recorded| {
+        if req.path.starts_with("/2015-03-31/functions/hello-fn/invocations") { (200, br#"{"code":0,"stdout":"out","stderr":"err"}"#.to_vec()) } else { (404, vec![]) }
+    })).await;
+    let creds: AwsCredentials = creds(&endpoint);
+    let clients: AwsClients = AwsClients::new(&creds).await;
+
+    let (code, stdout, stderr): (i32, String, String) = aws::invoke(&clients, "hello-fn", vec![ "no-op".into() ]).await.unwrap();
+    assert_eq!((code, stdout.as_str(), stderr.as_str()), (0, "out", "err"));
+
+    // The arguments should be passed as payload
+    let requests: Vec<Recorded> = requests.lock().unwrap().clone();
+    let payload: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    assert_eq!(payload, serde_json::json!({ "args": [ "no-op" ] }));
+}
+
+#[tokio::test]
+async fn test_stage_and_collect() {
+    let dir = tempfile::tempdir().unwrap();
+
+    // Prepare an input file, an (empty) result directory and the archive that the task will have uploaded to it
+    let input: PathBuf = dir.path().join("input.txt");
+    tokio::fs::write(&input, "Hello there").await.unwrap();
+    let result: PathBuf = dir.path().join("result");
+    tokio::fs::create_dir_all(&result).await.unwrap();
+    let produced: PathBuf = dir.path().join("produced");
+    tokio::fs::create_dir_all(&produced).await.unwrap();
+    tokio::fs::write(produced.join("answer.txt"), "42").await.unwrap();
+    let tarball: PathBuf = dir.path().join("produced.tar.gz");
+    archive_async(&produced, &tarball, true).await.unwrap();
+    let tarball: Vec<u8> = tokio::fs::read(&tarball).await.unwrap();
+
+    let (endpoint, requests) = mock(Arc::new(move |req: &Recorded| match (req.method.as_str(), req.path.split('?').next().unwrap_or_default().rsplit('/').next().unwrap_or_default()) {
+        ("PUT", _)         => (200, vec![]),
+        ("GET", "1")       => (200, tarball.clone()),
+        ("GET", "output")  => (200, b"b64value".to_vec()),
+        ("DELETE", _)      => (204, vec![]),
+        _                  => (404, vec![]),
+    })).await;
+    let creds: AwsCredentials = creds(&endpoint);
+    let clients: AwsClients = AwsClients::new(&creds).await;
+
+    // Stage the binds
+    let binds: Vec<VolumeBind> = vec![
+        VolumeBind::new_readonly(&input, "/data/input/input.txt").unwrap(),
+        VolumeBind::new_readwrite(&result, "/result").unwrap(),
+    ];
+    let staging: Staging = aws::stage(&clients, &creds, "hello", &binds, dir.path()).await.unwrap();
+    let options: HashSet<&str> = staging.args.iter().filter(|arg| arg.starts_with("--")).map(|arg| arg.as_str()).collect();
+    assert_eq!(options, HashSet::from([ "--stage-in-file", "--stage-out", "--output-url" ]));
+    let stage_in: &String = &staging.args[staging.args.iter().position(|arg| arg == "--stage-in-file").unwrap() + 1];
+    assert!(stage_in.starts_with(&format!("/data/input/input.txt={}/brane-bucket/brane/hello-", endpoint)), "Unexpected transfer '{}'", stage_in);
+    assert!(stage_in.contains("X-Amz-Signature="), "Transfer '{}' is not presigned", stage_in);
+
+    // Only the input should have been uploaded
+    let uploads: Vec<Recorded> = requests.lock().unwrap().iter().filter(|req| req.method == "PUT").cloned().collect();
+    assert_eq!(uploads.len(), 1);
+    assert_eq!(uploads[0].body, b"Hello there");
+
+    // Collect the outputs
+    let output: String = aws::collect(&clients, &creds, &staging).await.unwrap();
+    assert_eq!(output, "b64value");
+    assert_eq!(tokio::fs::read_to_string(result.join("answer.txt")).await.unwrap(), "42");
+
+    // Cleaning up should remove everything
+    aws::cleanup(&clients, &creds, &staging).await;
+    let deleted: usize = requests.lock().unwrap().iter().filter(|req| req.method == "DELETE").count();
+    assert_eq!(deleted, staging.keys.len());
+    assert_eq!(deleted, 3);
+}
@@ -0,0 +1,21 @@
+//  BUILD.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 11:01:36
+//  Last edited:
+//    16 Oct 2026, 11:01:36
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Build script for the `specifications` crate. Compiles the `.proto`
+//!   file of the planner's gRPC service to Rust.
+// 
+
+
+/***** ENTRYPOINT *****/
+fn main() -> Result<(), std::io::Error> {
+    tonic_build::configure()
+        .compile(&["proto/planner.proto"], &["proto"])
+}
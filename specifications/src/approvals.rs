@@ -0,0 +1,42 @@
+//  APPROVALS.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 15:02:41
+//  Last edited:
+//    16 Oct 2026, 15:02:41
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Defines the approvals that workflows wait for, as listed and decided
+//!   on through the `/approvals` path of `brane-api`.
+// 
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+
+/***** LIBRARY *****/
+/// Defines an approval that a workflow is waiting for.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ApprovalInfo {
+    /// The identifier of the approval, with which it is decided on.
+    pub id        : String,
+    /// The session of the workflow waiting for the approval.
+    pub session   : String,
+    /// The message that describes what should be approved.
+    pub message   : String,
+    /// When the workflow asked for the approval.
+    pub requested : DateTime<Utc>,
+}
+
+/// Defines a decision on an approval, as sent by an approver.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ApprovalDecision {
+    /// Whether the approval is granted.
+    pub approve : bool,
+    /// Why it was (not) granted, if the approver said so.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason  : Option<String>,
+}
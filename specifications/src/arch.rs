@@ -14,7 +14,9 @@
 
 use std::error::Error;
 use std::fmt::{Display, Formatter, Result as FResult};
+use std::fs::File;
 use std::hash::Hash;
+use std::path::{Path, PathBuf};
 use std::process::{Command, ExitStatus};
 use std::str::FromStr;
 
@@ -50,6 +52,36 @@ impl Error for ArchError {}
 
 
 
+/// Defines the errors that may occur when reading or writing manifest lists.
+#[derive(Debug)]
+pub enum ManifestListError {
+    /// Failed to open the manifest list file.
+    FileOpenError{ path: PathBuf, err: std::io::Error },
+    /// Failed to parse the manifest list file.
+    FileParseError{ path: PathBuf, err: serde_yaml::Error },
+    /// Failed to create the manifest list file.
+    FileCreateError{ path: PathBuf, err: std::io::Error },
+    /// Failed to write the manifest list file.
+    FileWriteError{ path: PathBuf, err: serde_yaml::Error },
+}
+
+impl Display for ManifestListError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use ManifestListError::*;
+        match self {
+            FileOpenError{ path, err }  => write!(f, "Failed to open manifest list file '{}': {}", path.display(), err),
+            FileParseError{ path, err } => write!(f, "Failed to parse manifest list file '{}' as YAML: {}", path.display(), err),
+
+            FileCreateError{ path, err } => write!(f, "Failed to create manifest list file '{}': {}", path.display(), err),
+            FileWriteError{ path, err }  => write!(f, "Failed to write manifest list file '{}': {}", path.display(), err),
+        }
+    }
+}
+
+impl Error for ManifestListError {}
+
+
+
 
 
 /***** LIBRARY *****/
@@ -142,3 +174,102 @@ impl FromStr for Arch {
         }
     }
 }
+
+
+
+/// Describes the image of a package that has been built for a single architecture.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ArchImage {
+    /// The architecture for which the image has been built.
+    pub arch   : Arch,
+    /// The name of the image file, relative to the package directory.
+    pub file   : String,
+    /// The digest of the image, if known.
+    pub digest : Option<String>,
+}
+
+/// Lists the images of a package for every architecture it has been built for, much like a Docker manifest list.
+/// 
+/// The first image is the default one, which is stored as `image.tar` such that tools that are not aware of multiple architectures keep on working.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ManifestList {
+    /// The images in this list, one per architecture.
+    pub images : Vec<ArchImage>,
+}
+
+impl ManifestList {
+    /// The name of the file in a package directory that contains its manifest list.
+    pub const FILENAME: &'static str = "images.yml";
+
+
+
+    /// Returns the name of the image file for the given architecture, relative to the package directory.
+    /// 
+    /// # Arguments
+    /// - `arch`: The architecture of the image.
+    /// - `default`: Whether this is the default image of the package.
+    /// 
+    /// # Returns
+    /// `image.tar` for the default image, or `image-<arch>.tar` for any other.
+    #[inline]
+    pub fn image_file(arch: Arch, default: bool) -> String {
+        if default { "image.tar".into() } else { format!("image-{}.tar", arch) }
+    }
+
+    /// Constructor for the ManifestList that loads it from the given (YAML) file.
+    /// 
+    /// # Arguments
+    /// - `path`: The path of the manifest list to load.
+    /// 
+    /// # Returns
+    /// A new ManifestList with the images in the file.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to open or parse the file.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, ManifestListError> {
+        let path: &Path = path.as_ref();
+        let handle: File = match File::open(path) {
+            Ok(handle) => handle,
+            Err(err)   => { return Err(ManifestListError::FileOpenError{ path: path.into(), err }); },
+        };
+        match serde_yaml::from_reader(handle) {
+            Ok(list) => Ok(list),
+            Err(err) => Err(ManifestListError::FileParseError{ path: path.into(), err }),
+        }
+    }
+
+    /// Writes the ManifestList to the given (YAML) file.
+    /// 
+    /// # Arguments
+    /// - `path`: The path of the file to write to.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to create or write the file.
+    pub fn to_path(&self, path: impl AsRef<Path>) -> Result<(), ManifestListError> {
+        let path: &Path = path.as_ref();
+        let handle: File = match File::create(path) {
+            Ok(handle) => handle,
+            Err(err)   => { return Err(ManifestListError::FileCreateError{ path: path.into(), err }); },
+        };
+        match serde_yaml::to_writer(handle, self) {
+            Ok(_)    => Ok(()),
+            Err(err) => Err(ManifestListError::FileWriteError{ path: path.into(), err }),
+        }
+    }
+
+
+
+    /// Returns the image for the given architecture, if the package has been built for it.
+    /// 
+    /// # Arguments
+    /// - `arch`: The architecture to find the image of.
+    /// 
+    /// # Returns
+    /// The ArchImage that describes the image, or `None` if there is none for this architecture.
+    #[inline]
+    pub fn get(&self, arch: Arch) -> Option<&ArchImage> { self.images.iter().find(|i| i.arch == arch) }
+
+    /// Returns the architectures for which the package has been built.
+    #[inline]
+    pub fn arches(&self) -> impl Iterator<Item = Arch> + '_ { self.images.iter().map(|i| i.arch) }
+}
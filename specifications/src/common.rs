@@ -66,6 +66,12 @@ pub struct Function {
     pub pattern: Option<CallPattern>,
     pub return_type: String,
     pub requirements: Option<HashSet<Capability>>,
+    /// If set, the function is deprecated and the compiler warns whenever it is used.
+    pub deprecated: Option<Deprecation>,
+    /// Other (typically older) names under which the function may also be called.
+    pub aliases: Option<Vec<String>>,
+    /// The names of the secrets that the function needs, which the worker injects as environment variables.
+    pub secrets: Option<Vec<String>>,
 }
 
 impl Function {
@@ -83,12 +89,28 @@ impl Function {
             pattern,
             return_type,
             requirements,
+            deprecated: None,
+            aliases: None,
+            secrets: None,
         }
     }
 }
 
 
 
+/// Defines that a function in a package is deprecated.
+#[skip_serializing_none]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Deprecation {
+    /// The name of the function that replaces the deprecated one, if any.
+    pub replacement: Option<String>,
+    /// An additional message for the users of the function, if any.
+    pub message: Option<String>,
+}
+
+
+
 /// Defines a callpattern for Bakery in the AST.
 #[skip_serializing_none]
 #[derive(Clone, Debug, Deserialize, Serialize)]
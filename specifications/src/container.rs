@@ -9,7 +9,7 @@ use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 
-use crate::common::{CallPattern, Parameter, Type};
+use crate::common::{CallPattern, Deprecation, Parameter, Type};
 use crate::package::{Capability, PackageKind};
 use crate::version::Version;
 
@@ -80,6 +80,16 @@ pub enum ContainerInfoError {
     FileReadError{ path: PathBuf, err: std::io::Error },
     /// Could not parse the target file
     ParseError{ err: serde_yaml::Error },
+    /// The file requests a number of CPUs that is not a positive number.
+    IllegalCpus{ cpu: f64 },
+    /// The file requests no memory at all.
+    IllegalMemory,
+    /// The file requests a secret with a name that cannot be an environment variable.
+    IllegalSecretName{ name: String },
+    /// The file defines a build stage with a name that Docker does not accept.
+    IllegalStageName{ name: String },
+    /// The file defines multiple build stages with the same name.
+    DuplicateStageName{ name: String },
 
     /// Could not create the target file
     FileCreateError{ path: PathBuf, err: std::io::Error },
@@ -92,6 +102,11 @@ impl Display for ContainerInfoError {
         match self {
             ContainerInfoError::FileReadError{ path, err } => write!(f, "Could not open & read container file '{}': {}", path.display(), err),
             ContainerInfoError::ParseError{ err }          => write!(f, "Could not parse container file YAML: {}", err),
+            ContainerInfoError::IllegalCpus{ cpu }         => write!(f, "Illegal number of CPUs {} in container file resources (must be a positive number)", cpu),
+            ContainerInfoError::IllegalMemory              => write!(f, "Illegal amount of memory 0 in container file resources (must be at least 1 byte)"),
+            ContainerInfoError::IllegalSecretName{ name }  => write!(f, "Illegal secret name '{}' in container file (must be a valid environment variable name, i.e., only letters, digits and underscores, not starting with a digit)", name),
+            ContainerInfoError::IllegalStageName{ name }   => write!(f, "Illegal build stage name '{}' in container file (must only contain lowercase letters, digits, '_', '.' and '-', and start with a letter or digit)", name),
+            ContainerInfoError::DuplicateStageName{ name } => write!(f, "Build stage '{}' is defined multiple times in container file", name),
 
             ContainerInfoError::FileCreateError{ path, err } => write!(f, "Could not create container file '{}': {}", path.display(), err),
             ContainerInfoError::FileWriteError{ err }        => write!(f, "Could not serialize & write container file: {}", err),
@@ -105,6 +120,24 @@ impl Error for ContainerInfoError {}
 
 
 
+/***** LIBRARY FUNCTIONS *****/
+/// Checks whether the given name can be used as the name of a secret.
+/// 
+/// Since secrets are injected as environment variables, this means that it may only contain letters, digits and underscores, and not start with a digit.
+/// 
+/// # Arguments
+/// - `name`: The name to check.
+/// 
+/// # Returns
+/// Whether the name is valid.
+pub fn is_valid_secret_name(name: &str) -> bool {
+    !name.is_empty() && !name.starts_with(|c: char| c.is_ascii_digit()) && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+
+
+
+
 /***** SPECIFICATIONS *****/
 /// A special struct that prints a given VolumeBindOption as a Docker-compatible string.
 #[derive(Debug)]
@@ -378,6 +411,9 @@ pub struct LocalContainerInfo {
     pub actions    : Map<Action>,
     /// The list of types that are declared in this package.
     pub types      : Map<Type>,
+    /// The maximum number of bytes this package may write to its scratch space, if it limits itself further than the node does.
+    #[serde(default)]
+    pub scratch    : Option<u64>,
 }
 
 impl LocalContainerInfo {
@@ -476,6 +512,7 @@ impl From<ContainerInfo> for LocalContainerInfo {
             entrypoint : container_info.entrypoint,
             actions    : container_info.actions,
             types      : container_info.types.unwrap_or_default(),
+            scratch    : container_info.scratch,
         }
     }
 }
@@ -487,7 +524,112 @@ impl From<&ContainerInfo> for LocalContainerInfo {
             entrypoint : container_info.entrypoint.clone(),
             actions    : container_info.actions.clone(),
             types      : container_info.types.as_ref().cloned().unwrap_or_default(),
+            scratch    : container_info.scratch,
+        }
+    }
+}
+
+
+
+/// Specifies the resources that every task of a package needs to run.
+#[skip_serializing_none]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Resources {
+    /// The number of CPUs a task needs. May be fractional (e.g., `0.5`).
+    pub cpu    : Option<f64>,
+    /// The number of bytes of memory a task needs.
+    pub memory : Option<u64>,
+    /// The number of CUDA GPUs a task needs.
+    pub gpu    : Option<u32>,
+}
+
+impl Resources {
+    /// Checks whether the requested resources make sense.
+    /// 
+    /// **Returns**  
+    /// Nothing if they do, or a ContainerInfoError describing what's wrong otherwise.
+    pub fn validate(&self) -> Result<(), ContainerInfoError> {
+        if let Some(cpu) = self.cpu {
+            if !cpu.is_finite() || cpu <= 0.0 { return Err(ContainerInfoError::IllegalCpus{ cpu }); }
+        }
+        if self.memory == Some(0) { return Err(ContainerInfoError::IllegalMemory); }
+        Ok(())
+    }
+
+    /// Returns the requested resources as the capabilities that a location running the package must satisfy.
+    /// 
+    /// **Returns**  
+    /// A set with a resource Capability for every requested resource. Requesting zero GPUs is the same as requesting none.
+    pub fn capabilities(&self) -> HashSet<Capability> {
+        let mut capabilities: HashSet<Capability> = HashSet::with_capacity(3);
+        if let Some(cpu) = self.cpu { capabilities.insert(Capability::MilliCpus((cpu * 1000.0).ceil() as u64)); }
+        if let Some(memory) = self.memory { capabilities.insert(Capability::Memory(memory)); }
+        if let Some(gpu) = self.gpu {
+            if gpu > 0 { capabilities.insert(Capability::Gpus(gpu)); }
         }
+        capabilities
+    }
+}
+
+
+
+/// Specifies the package manager with which the `dependencies` of a package are installed in its base image.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PackageManager {
+    /// Debian-like images (`apt-get`).
+    Apt,
+    /// Alpine images (`apk`).
+    Apk,
+    /// Fedora-like images (`dnf`).
+    Dnf,
+    /// The image has no package manager (e.g., distroless images), so nothing is installed at all.
+    None,
+}
+
+impl PackageManager {
+    /// Guesses the package manager of the given base image.
+    /// 
+    /// **Arguments**
+    ///  * `base`: The name of the base image.
+    /// 
+    /// **Returns**  
+    /// `PackageManager::Apk` for Alpine images, `PackageManager::Dnf` for Fedora, CentOS and RHEL images and `PackageManager::Apt` for anything else.
+    pub fn guess(base: &str) -> Self {
+        let name: &str = base.rsplit('/').next().unwrap_or(base);
+        if name.starts_with("alpine") { Self::Apk }
+        else if name.starts_with("fedora") || name.starts_with("centos") || name.starts_with("rockylinux") || name.starts_with("almalinux") || name.starts_with("ubi") { Self::Dnf }
+        else { Self::Apt }
+    }
+}
+
+
+
+/// Specifies a build stage of a package image, which runs before (and separately from) the image itself so that only its results end up in the package.
+#[skip_serializing_none]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BuildStage {
+    /// The name of the stage.
+    pub name      : String,
+    /// The image to run the stage in (e.g., a compiler image).
+    pub base      : String,
+    /// The files (relative to the build context) to copy to the stage's `/build` directory.
+    pub files     : Option<Vec<String>>,
+    /// The commands to run in the stage's `/build` directory.
+    pub run       : Option<Vec<String>>,
+    /// The results of the stage to copy to the package image. Relative paths are resolved in `/build` and copied to the same path in the package's working directory; absolute paths are copied to the same path in the package image.
+    pub artifacts : Option<Vec<String>>,
+}
+
+impl BuildStage {
+    /// Checks whether the name of the stage is one that Docker accepts.
+    /// 
+    /// **Returns**  
+    /// Nothing if it is, or a ContainerInfoError::IllegalStageName otherwise.
+    pub fn validate(&self) -> Result<(), ContainerInfoError> {
+        let valid: bool = self.name.starts_with(|c: char| c.is_ascii_lowercase() || c.is_ascii_digit()) && self.name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '.' || c == '-');
+        if !valid { return Err(ContainerInfoError::IllegalStageName{ name: self.name.clone() }); }
+        Ok(())
     }
 }
 
@@ -515,21 +657,33 @@ pub struct ContainerInfo {
     pub entrypoint : Entrypoint,
     /// The types that this package adds.
     pub types      : Option<Map<Type>>,
+    /// The maximum number of bytes the package may write to its per-task scratch space. Note that the node may impose a stricter limit.
+    pub scratch    : Option<u64>,
+    /// The resources (CPUs, memory and GPUs) that every task of the package needs. These are added to the requirements of every action.
+    pub resources  : Option<Resources>,
+    /// The names of the secrets that every task of the package needs. The worker injects them as environment variables with the same names, and refuses to run the task if it does not know them.
+    pub secrets    : Option<Vec<String>>,
 
     /// The base image to use for the package image.
-    pub base         : Option<String>,
+    pub base            : Option<String>,
+    /// The package manager of the base image, with which the dependencies are installed. Guessed from the name of the base image if omitted.
+    pub package_manager : Option<PackageManager>,
+    /// Any build stages (e.g., to compile the package's code) whose artifacts are copied to the package image.
+    pub stages          : Option<Vec<BuildStage>>,
+    /// Extra Dockerfile instructions, added verbatim to the package image after the `install` commands.
+    pub dockerfile      : Option<Vec<String>>,
     /// The dependencies, as install commands for sudo apt-get install -y <...>
-    pub dependencies : Option<Vec<String>>,
+    pub dependencies    : Option<Vec<String>>,
     /// Any environment variables that the user wants to be set
-    pub environment  : Option<Map<String>>,
+    pub environment     : Option<Map<String>>,
     /// The list of additional files to copy to the image
-    pub files        : Option<Vec<String>>,
+    pub files           : Option<Vec<String>>,
     /// An extra script to run to initialize the working directory
-    pub initialize   : Option<Vec<String>>,
+    pub initialize      : Option<Vec<String>>,
     /// An extra set of commands that will be run _before_ the workspace is copied over. Useful for non-standard general dependencies.
-    pub install      : Option<Vec<String>>,
+    pub install         : Option<Vec<String>>,
     /// An extra set of commands that will be run _after_ the workspace is copied over. Useful for preprocessing or unpacking things.
-    pub unpack       : Option<Vec<String>>,
+    pub unpack          : Option<Vec<String>>,
 }
 
 #[allow(unused)]
@@ -570,10 +724,12 @@ impl ContainerInfo {
     /// **Returns**  
     /// The newly constructed ContainerInfo instance on success, or a ContainerInfoError upon failure.
     pub fn from_reader<R: Read>(r: R) -> Result<ContainerInfo, ContainerInfoError> {
-        match serde_yaml::from_reader(r) {
-            Ok(result) => Ok(result),
-            Err(err)   => Err(ContainerInfoError::ParseError{ err }),
-        }
+        let result: ContainerInfo = match serde_yaml::from_reader(r) {
+            Ok(result) => result,
+            Err(err)   => { return Err(ContainerInfoError::ParseError{ err }); },
+        };
+        result.validate()?;
+        Ok(result)
     }
 
     /// **Edited: now returning ContainerInfoErrors.**
@@ -586,10 +742,29 @@ impl ContainerInfo {
     /// **Returns**  
     /// The newly constructed ContainerInfo instance on success, or a ContainerInfoError upon failure.
     pub fn from_string(contents: String) -> Result<ContainerInfo, ContainerInfoError> {
-        match serde_yaml::from_str(&contents) {
-            Ok(result) => Ok(result),
-            Err(err)   => Err(ContainerInfoError::ParseError{ err }),
+        let result: ContainerInfo = match serde_yaml::from_str(&contents) {
+            Ok(result) => result,
+            Err(err)   => { return Err(ContainerInfoError::ParseError{ err }); },
+        };
+        result.validate()?;
+        Ok(result)
+    }
+
+    /// Checks whether the requested resources, secrets and build stages make sense.
+    /// 
+    /// **Returns**  
+    /// Nothing if they do, or a ContainerInfoError explaining what is wrong otherwise.
+    fn validate(&self) -> Result<(), ContainerInfoError> {
+        if let Some(resources) = &self.resources { resources.validate()?; }
+        for name in self.secrets.iter().flatten() {
+            if !is_valid_secret_name(name) { return Err(ContainerInfoError::IllegalSecretName{ name: name.clone() }); }
         }
+        let mut stages: HashSet<&str> = HashSet::new();
+        for stage in self.stages.iter().flatten() {
+            stage.validate()?;
+            if !stages.insert(&stage.name) { return Err(ContainerInfoError::DuplicateStageName{ name: stage.name.clone() }); }
+        }
+        Ok(())
     }
 
 
@@ -635,6 +810,23 @@ impl ContainerInfo {
             Err(err) => Err(ContainerInfoError::FileWriteError{ err }),
         }
     }
+
+
+
+    /// Returns the requirements of the given action, including the resources that the package needs.
+    /// 
+    /// **Arguments**
+    ///  * `action`: The Action (of this package) to return the requirements of.
+    /// 
+    /// **Returns**  
+    /// The action's own requirements plus the resource capabilities of the package, or `None` if there are neither.
+    pub fn requirements(&self, action: &Action) -> Option<HashSet<Capability>> {
+        let resources: HashSet<Capability> = self.resources.as_ref().map(|r| r.capabilities()).unwrap_or_default();
+        if resources.is_empty() { return action.requirements.clone(); }
+        let mut requirements: HashSet<Capability> = action.requirements.clone().unwrap_or_default();
+        requirements.extend(resources);
+        Some(requirements)
+    }
 }
 
 
@@ -651,6 +843,10 @@ pub struct Action {
     pub pattern: Option<CallPattern>,
     pub input: Option<Vec<Parameter>>,
     pub output: Option<Vec<Parameter>>,
+    /// If set, marks the action as deprecated (optionally with the name of its `replacement`).
+    pub deprecated: Option<Deprecation>,
+    /// Other (typically older) names of the action, so that workflows that use those keep working.
+    pub aliases: Option<Vec<String>>,
 }
 
 
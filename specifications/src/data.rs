@@ -19,10 +19,23 @@ use std::fmt::{Display, Formatter, Result as FResult};
 use std::fs::File;
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::version::{ParseError as VersionParseError, Version};
+
+
+/***** CONSTANTS *****/
+/// The header with which workers negotiate how intermediate results are transferred between them.
+pub const TRANSFER_HEADER: &str = "X-Brane-Transfer";
+/// The value of the `TRANSFER_HEADER` that asks for (or, in a reply, confirms) a direct transfer, where the producer streams the result while archiving it and the consumer extracts it while receiving it.
+pub const TRANSFER_DIRECT: &str = "direct";
+
+
+
+
 
 /***** ERRORS *****/
 /// Defines (parsing) errors that relate to the DataIndex struct.
@@ -57,6 +70,30 @@ impl Error for DataIndexError {}
 
 
 
+/// Defines errors that relate to parsing dataset identifiers.
+#[derive(Debug)]
+pub enum DataIdentifierError {
+    /// The identifier did not have a name before the '@'.
+    MissingName{ raw: String },
+    /// The version after the '@' was not a valid version.
+    IllegalVersion{ raw: String, err: VersionParseError },
+}
+
+impl Display for DataIdentifierError {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use DataIdentifierError::*;
+        match self {
+            MissingName{ raw }         => write!(f, "Dataset identifier '{}' has no name", raw),
+            IllegalVersion{ raw, err } => write!(f, "Failed to parse version in dataset identifier '{}': {}", raw, err),
+        }
+    }
+}
+
+impl Error for DataIdentifierError {}
+
+
+
 /// Defines errors that relate to the RuntimeDataIndex struct.
 #[derive(Debug)]
 pub enum RuntimeDataIndexError {
@@ -199,6 +236,66 @@ impl Error for AssetInfoError {}
 
 
 
+/***** LIBRARY FUNCTIONS *****/
+/// Returns the version of datasets that do not explicitly define one.
+#[inline]
+pub fn default_data_version() -> Version { Version::new(1, 0, 0) }
+
+/// Returns the identifier of a specific version of a dataset (i.e., `NAME@VERSION`).
+/// 
+/// # Arguments
+/// - `name`: The name of the dataset.
+/// - `version`: The version of the dataset.
+/// 
+/// # Returns
+/// The identifier as a String.
+#[inline]
+pub fn data_id(name: impl AsRef<str>, version: &Version) -> String { format!("{}@{}", name.as_ref(), version) }
+
+/// Splits a dataset identifier into its name and version.
+/// 
+/// Identifiers are either a plain name, which refers to the latest version of a dataset, or `NAME@VERSION`.
+/// 
+/// # Arguments
+/// - `id`: The identifier to split.
+/// 
+/// # Returns
+/// The name and the version of the dataset. The latter is `Version::latest()` if the identifier has no version.
+/// 
+/// # Errors
+/// This function errors if the identifier has no name or its version is not a valid version.
+pub fn split_data_id(id: &str) -> Result<(&str, Version), DataIdentifierError> {
+    let (name, version): (&str, Version) = match id.rfind('@') {
+        Some(pos) => match Version::from_str(&id[pos + 1..]) {
+            Ok(version) => (&id[..pos], version),
+            Err(err)    => { return Err(DataIdentifierError::IllegalVersion{ raw: id.into(), err }); },
+        },
+        None => (id, Version::latest()),
+    };
+    if name.is_empty() { return Err(DataIdentifierError::MissingName{ raw: id.into() }); }
+    Ok((name, version))
+}
+
+/// Combines the residency of a dataset with that of other data that ends up in it, such that it keeps the restrictions of both.
+/// 
+/// # Arguments
+/// - `current`: The locations where the dataset may currently be processed, or `None` if it may be processed anywhere.
+/// - `other`: The locations where the other data may be processed, or `None` if it may be processed anywhere.
+/// 
+/// # Returns
+/// The locations allowed by both, or `None` if neither is restricted.
+pub fn merge_residency(current: Option<Vec<Location>>, other: Option<&[Location]>) -> Option<Vec<Location>> {
+    match (current, other) {
+        (Some(current), Some(other)) => Some(current.into_iter().filter(|l| other.contains(l)).collect()),
+        (Some(current), None)        => Some(current),
+        (None, other)                => other.map(|other| other.to_vec()),
+    }
+}
+
+
+
+
+
 /***** LIBRARY *****/
 /// Placeholder for the Location's type.
 pub type Location = String;
@@ -252,6 +349,24 @@ pub enum AccessKind {
         /// The path to the file itself.
         path : PathBuf,
     },
+
+    /// By an XRootD endpoint (e.g., `root://eospublic.cern.ch//eos/...`), from which the dataset is staged before a task may use it.
+    XRootD {
+        /// The `root://` URL of the file.
+        url : String,
+    },
+    /// By a GridFTP endpoint (e.g., `gsiftp://gridftp.example.org/...`), from which the dataset is staged before a task may use it.
+    GridFtp {
+        /// The `gsiftp://` URL of the file.
+        url : String,
+    },
+    /// By a URL from which the dataset is only fetched once a task on a node needs it, after which that node caches it.
+    Url {
+        /// The URL of the file.
+        url    : String,
+        /// The SHA-256 digest of the file, which is verified after fetching it.
+        digest : String,
+    },
 }
 
 /// Defines possible ways of downloading datasets to make them locally available.
@@ -270,10 +385,13 @@ pub enum PreprocessKind {
 
 
 /// Defines an index of all datasets known to the instance.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct DataIndex {
-    /// Stores the list of all DataInfos per dataset identifier.
-    index : HashMap<String, DataInfo>,
+    /// Stores the DataInfo of the latest version of every dataset per dataset name.
+    index    : HashMap<String, DataInfo>,
+    /// Stores the DataInfos of all versions of every dataset per dataset name, ordered from oldest to newest.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    versions : HashMap<String, Vec<DataInfo>>,
 }
 
 impl DataIndex {
@@ -330,6 +448,8 @@ impl DataIndex {
 
     /// Constructor for the DataIndex that creates it from a list of DataInfos.
     /// 
+    /// DataInfos that describe the same version of the same dataset (e.g., as advertised by different locations) are merged into one.
+    /// 
     /// # Arguments
     /// - `infos`: The DataInfos on which to base this index.
     /// 
@@ -338,28 +458,41 @@ impl DataIndex {
     /// 
     /// # Errors
     /// This function errors if there were namespace conflicts and such.
-    #[inline]
     pub fn from_infos(infos: Vec<DataInfo>) -> Result<Self, DataIndexError> {
-        // Merge all datainfo's with the same name into one
-        let mut index: HashMap<String, DataInfo> = HashMap::with_capacity(infos.len());
+        // Merge all datainfo's with the same name and version into one
+        let mut versions: HashMap<String, Vec<DataInfo>> = HashMap::with_capacity(infos.len());
         for info in infos {
+            let entries: &mut Vec<DataInfo> = versions.entry(info.name.clone()).or_default();
+
             // If it already exists, attempt to merge the locations
-            if let Some(einfo) = index.get_mut(&info.name) {
+            if let Some(einfo) = entries.iter_mut().find(|e| e.version == info.version) {
                 einfo.access.reserve(info.access.len());
                 for (l, a) in info.access {
-                    if einfo.access.contains_key(&l) { return Err(DataIndexError::DuplicateAsset { location: l, name: info.name }); }
+                    if einfo.access.contains_key(&l) { return Err(DataIndexError::DuplicateAsset { location: l, name: data_id(&info.name, &info.version) }); }
                     einfo.access.insert(l, a);
                 }
-                break;
+                einfo.revisions.extend(info.revisions);
+
+                // The dataset may only be processed where all of its locations allow it
+                einfo.residency = merge_residency(einfo.residency.take(), info.residency.as_deref());
+                continue;
             }
 
-            // Otherwise, add it as a new info
-            index.insert(info.name.clone(), info);
+            // Otherwise, add it as a new version
+            entries.push(info);
+        }
+
+        // Order the versions and note the latest of each of them
+        let mut index: HashMap<String, DataInfo> = HashMap::with_capacity(versions.len());
+        for (name, entries) in &mut versions {
+            entries.sort_by(|lhs, rhs| lhs.version.cmp(&rhs.version));
+            index.insert(name.clone(), entries[entries.len() - 1].clone());
         }
 
         // Alright, store them in a single location.
         Ok(Self {
             index,
+            versions,
         })
     }
 
@@ -371,13 +504,30 @@ impl DataIndex {
     /// - `S`: The String-like type of the `name`.
     /// 
     /// # Arguments
-    /// - `name`: The dataset identifier to search for.
+    /// - `name`: The dataset identifier to search for. This is either a plain name, which refers to the latest version of that dataset, or a `NAME@VERSION` pair.
     /// 
     /// # Returns
-    /// A DataInfo struct that represents this data asset.
+    /// A DataInfo struct that represents this data asset, or `None` if we do not know it (or the identifier is invalid).
     #[inline]
     pub fn get<S: AsRef<str>>(&self, name: S) -> Option<&DataInfo> {
-        self.index.get(name.as_ref())
+        let (name, version): (&str, Version) = split_data_id(name.as_ref()).ok()?;
+        if version.is_latest() {
+            self.index.get(name)
+        } else {
+            self.versions.get(name)?.iter().find(|info| info.version == version)
+        }
+    }
+
+    /// Returns all versions of the given dataset.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the dataset to search for.
+    /// 
+    /// # Returns
+    /// The DataInfos of every version of the dataset, ordered from oldest to newest, or `None` if we do not know it.
+    #[inline]
+    pub fn versions(&self, name: impl AsRef<str>) -> Option<&[DataInfo]> {
+        self.versions.get(name.as_ref()).map(|entries| entries.as_slice())
     }
 
 
@@ -389,6 +539,10 @@ impl DataIndex {
     /// Returns a(n) (mutable) iterator over the internal DataIndices.
     #[inline]
     pub fn iter_mut(&mut self) -> impl Iterator<Item=&mut DataInfo> { self.into_iter() }
+
+    /// Returns an iterator over every version of every dataset, instead of only the latest ones.
+    #[inline]
+    pub fn iter_versions(&self) -> impl Iterator<Item=&DataInfo> { self.versions.values().flatten() }
 }
 
 impl IntoIterator for DataIndex {
@@ -548,12 +702,24 @@ impl Default for RuntimeDataIndex {
 pub struct DataInfo {
     /// Defines the name (=identifier) of the DataInfo. Must be unique across the instance.
     pub name        : String,
+    /// The version of the dataset. Together with the name, it must be unique across the instance.
+    #[serde(default = "default_data_version")]
+    pub version     : Version,
     /// The list of owners of this asset.
     pub owners      : Option<Vec<String>>,
     /// A (short) description of the asset.
     pub description : Option<String>,
     /// The created timestamp of the asset.
     pub created     : DateTime<Utc>,
+    /// The locations where this dataset may be processed. If omitted, it may be processed anywhere.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub residency   : Option<Vec<Location>>,
+    /// The size of the dataset in bytes, as reported by the domain(s) that have it. If omitted, it is not known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size        : Option<u64>,
+    /// The revision of the dataset per location that advertises it (see `AssetInfo::revision`). Locations that are missing are at revision 0.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub revisions   : HashMap<Location, u64>,
 
     /// Defines how to access this DataInfo per location that advertises it.
     pub access : HashMap<Location, AccessKind>,
@@ -655,6 +821,12 @@ impl DataInfo {
             Err(err) => Err(DataInfoError::WriterWriteError{ err }),
         }
     }
+
+
+
+    /// Returns the identifier of this specific version of the dataset (i.e., `NAME@VERSION`).
+    #[inline]
+    pub fn id(&self) -> String { data_id(&self.name, &self.version) }
 }
 
 
@@ -664,6 +836,9 @@ impl DataInfo {
 pub struct AssetInfo {
     /// Defines the name (=identifier) of the AssetInfo. Must be unique across the instance.
     pub name        : String,
+    /// The version of the asset. If omitted, it is interpreted as `default_data_version()` (or, when building it with `brane data build`, as the next version of an existing dataset).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version     : Option<Version>,
     /// The list of owners of this asset. This is not the domains, but rather the physical people who added it and such.
     pub owners      : Option<Vec<String>>,
     /// A (short) description of the asset.
@@ -671,6 +846,15 @@ pub struct AssetInfo {
     /// The created timestamp of the asset.
     #[serde(skip)]
     pub created     : DateTime<Utc>,
+    /// The locations where this asset may be processed (e.g., because of data residency laws). If omitted, it may be processed anywhere.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub residency   : Option<Vec<Location>>,
+    /// The size of the asset in bytes. This is computed by the registry that serves it (and only for assets stored as files), so any value written in a `data.yml` file is overwritten.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size        : Option<u64>,
+    /// The revision of the asset, which is bumped every time a result is committed to it. Commits may only replace the revision they expect, so that concurrent commits cannot overwrite each other unnoticed.
+    #[serde(default)]
+    pub revision    : u64,
 
     /// Defines the way how to access & distribute this asset to containers.
     pub access : AccessKind,
@@ -736,15 +920,30 @@ impl AssetInfo {
     /// A new DataInfo instance that contains the same information as this AssetInfo but ordered differently.
     #[inline]
     pub fn into_data_info(self, location: impl Into<String>) -> DataInfo {
+        let location: String = location.into();
         DataInfo {
             name        : self.name,
+            version     : self.version.unwrap_or_else(default_data_version),
             owners      : self.owners,
             description : self.description,
             created     : self.created,
+            residency   : self.residency,
+            size        : self.size,
+            revisions   : if self.revision > 0 { HashMap::from([ (location.clone(), self.revision) ]) } else { HashMap::new() },
 
-            access : HashMap::from([ (location.into(), self.access) ]),
+            access : HashMap::from([ (location, self.access) ]),
         }
     }
+
+
+
+    /// Returns the version of this asset, resolving it to `default_data_version()` if it has none.
+    #[inline]
+    pub fn resolved_version(&self) -> Version { self.version.clone().unwrap_or_else(default_data_version) }
+
+    /// Returns the identifier of this specific version of the asset (i.e., `NAME@VERSION`).
+    #[inline]
+    pub fn id(&self) -> String { data_id(&self.name, &self.resolved_version()) }
 }
 
 impl From<AssetInfo> for DataInfo {
@@ -752,9 +951,12 @@ impl From<AssetInfo> for DataInfo {
     fn from(value: AssetInfo) -> Self {
         Self {
             name        : value.name,
+            version     : value.version.unwrap_or_else(default_data_version),
             owners      : value.owners,
             description : value.description,
             created     : value.created,
+            residency   : value.residency,
+            size        : value.size,
 
             access : HashMap::from([ ("localhost".into(), value.access) ]),
         }
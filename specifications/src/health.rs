@@ -0,0 +1,54 @@
+//  HEALTH.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 12:44:23
+//  Last edited:
+//    16 Oct 2026, 12:44:23
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Defines the aggregated health of an instance, as returned by the
+//!   `/health/instance` path of `brane-api`.
+// 
+
+use serde::{Deserialize, Serialize};
+
+
+/***** LIBRARY *****/
+/// Defines the health of a single component of the instance.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ComponentHealth {
+    /// The name of the component (e.g., `drv`, or `registry/<location>` for the registries of worker domains).
+    pub name       : String,
+    /// Whether the component responded (successfully) in time.
+    pub healthy    : bool,
+    /// How long the component took to respond, in milliseconds. Omitted if it did not respond at all.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms : Option<u64>,
+    /// Why the component is not healthy, if it isn't.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error      : Option<String>,
+}
+
+/// Defines the aggregated health of all components of an instance.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct InstanceHealth {
+    /// Whether all components are healthy.
+    pub healthy    : bool,
+    /// The health of every component, in the order they were polled.
+    pub components : Vec<ComponentHealth>,
+}
+
+impl InstanceHealth {
+    /// Constructor for the InstanceHealth that derives whether the instance as a whole is healthy.
+    ///
+    /// # Arguments
+    /// - `components`: The health of every component.
+    ///
+    /// # Returns
+    /// A new InstanceHealth that is healthy iff all of the given components are.
+    #[inline]
+    pub fn new(components: Vec<ComponentHealth>) -> Self { Self { healthy: components.iter().all(|c| c.healthy), components } }
+}
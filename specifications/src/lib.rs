@@ -1,13 +1,27 @@
 #[macro_use]
 extern crate anyhow;
 
+pub mod approvals;
 pub mod arch;
 pub mod common;
 pub mod container;
 pub mod data;
 pub mod errors;
+pub mod health;
+pub mod openapi;
 pub mod planning;
 pub mod package;
+pub mod profiling;
 pub mod registry;
+pub mod search;
 pub mod status;
 pub mod version;
+
+// The grpc module is a bit special
+#[allow(clippy::all)]
+pub mod grpc {
+    tonic::include_proto!("planner");
+
+    pub use planner_service_client::PlannerServiceClient;
+    pub use planner_service_server::{PlannerService, PlannerServiceServer};
+}
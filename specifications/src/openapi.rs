@@ -0,0 +1,611 @@
+//  OPENAPI.rs
+//    by agent
+// 
+//  Created:
+//    16 Oct 2026, 12:59:49
+//  Last edited:
+//    16 Oct 2026, 13:23:02
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Describes the REST routes of `brane-api`, from which the build
+//!   scripts of `brane-api` and `brane-tsk` generate the OpenAPI document
+//!   that the former serves and the typed client that the latter uses,
+//!   respectively.
+// 
+
+use std::fmt::Write as _;
+
+use serde_json::{json, Map, Value};
+
+
+/***** CONSTANTS *****/
+/// The header in which `brane-api` reports its version on every response, with which clients check that they are compatible with it.
+pub const API_VERSION_HEADER: &str = "X-Brane-Api-Version";
+
+/// The tags of the operations, in the order they appear in the document.
+pub const TAGS: &[(&str, &str)] = &[
+    ("data", "The datasets known in the instance."),
+    ("packages", "The packages known in the instance."),
+    ("infra", "The locations in the instance and their configuration."),
+    ("events", "The events that resume workflows waiting for them."),
+    ("approvals", "The approvals that workflows wait for."),
+    ("health", "The health of the API and the rest of the instance."),
+];
+
+/// The REST routes of `brane-api`.
+pub const OPERATIONS: &[Operation] = &[
+    // Data
+    Operation {
+        id       : "list_datasets",
+        method   : Method::Get,
+        path     : "/data/info",
+        tag      : "data",
+        summary  : "Lists the datasets known in the instance (and its peers), mapped by their identifier (i.e., `NAME@VERSION`).",
+        params   : &[],
+        request  : None,
+        response : Content::Json{ schema: r##"{ "type": "object", "additionalProperties": { "$ref": "#/components/schemas/DataInfo" } }"##, rust: "std::collections::HashMap<String, specifications::data::DataInfo>" },
+        statuses : &[ (200, "The known datasets.") ],
+    },
+    Operation {
+        id       : "get_dataset",
+        method   : Method::Get,
+        path     : "/data/info/{name}",
+        tag      : "data",
+        summary  : "Returns everything known about the given dataset.",
+        params   : &[ Param{ name: "name", kind: ParamKind::Path, description: "The name of the dataset." } ],
+        request  : None,
+        response : Content::Json{ schema: r##"{ "$ref": "#/components/schemas/DataInfo" }"##, rust: "specifications::data::DataInfo" },
+        statuses : &[ (200, "The dataset."), (404, "The dataset is not known.") ],
+    },
+
+    // Packages
+    Operation {
+        id       : "get_image_registry",
+        method   : Method::Get,
+        path     : "/packages/registry",
+        tag      : "packages",
+        summary  : "Returns the OCI registry in which package images may be stored instead of in the API.",
+        params   : &[],
+        request  : None,
+        response : Content::Json{ schema: r##"{ "$ref": "#/components/schemas/ImageRegistryInfo" }"##, rust: "specifications::registry::ImageRegistryInfo" },
+        statuses : &[ (200, "The image registry."), (404, "The instance does not use an image registry.") ],
+    },
+    Operation {
+        id       : "download_package",
+        method   : Method::Get,
+        path     : "/packages/{name}/{version}",
+        tag      : "packages",
+        summary  : "Downloads the image of the given package as a tar archive.",
+        params   : &[
+            Param{ name: "name", kind: ParamKind::Path, description: "The name of the package." },
+            Param{ name: "version", kind: ParamKind::Path, description: "The version of the package." },
+            Param{ name: "arch", kind: ParamKind::Query, description: "The architecture of the image. If omitted, the default image of the package is returned." },
+        ],
+        request  : None,
+        response : Content::Binary,
+        statuses : &[ (200, "The image."), (404, "The package (or its image for the given architecture) is not known.") ],
+    },
+    Operation {
+        id       : "upload_package",
+        method   : Method::Post,
+        path     : "/packages",
+        tag      : "packages",
+        summary  : "Uploads a new package as a (gzipped) tar archive with its `package.yml` and, unless the instance uses an image registry, its image.",
+        params   : &[],
+        request  : Some(Content::Binary),
+        response : Content::Empty,
+        statuses : &[ (200, "The package has been stored."), (403, "The package's image has been rejected by the vulnerability scanner."), (503, "The package database is unavailable.") ],
+    },
+
+    // Infra
+    Operation {
+        id       : "list_registries",
+        method   : Method::Get,
+        path     : "/infra/registries",
+        tag      : "infra",
+        summary  : "Lists the addresses of the registries of the locations in the instance, mapped by the names of the locations.",
+        params   : &[],
+        request  : None,
+        response : Content::Json{ schema: r##"{ "type": "object", "additionalProperties": { "type": "string" } }"##, rust: "std::collections::HashMap<String, String>" },
+        statuses : &[ (200, "The registries.") ],
+    },
+    Operation {
+        id       : "get_registry",
+        method   : Method::Get,
+        path     : "/infra/registries/{location}",
+        tag      : "infra",
+        summary  : "Returns the address of the registry of the given location (which may be hosted by a peer, as `<peer>:<location>`).",
+        params   : &[ Param{ name: "location", kind: ParamKind::Path, description: "The name of the location." } ],
+        request  : None,
+        response : Content::Text,
+        statuses : &[ (200, "The address of the registry."), (404, "The location is not known.") ],
+    },
+    Operation {
+        id       : "get_capabilities",
+        method   : Method::Get,
+        path     : "/infra/capabilities/{location}",
+        tag      : "infra",
+        summary  : "Returns the capabilities that the given location advertises.",
+        params   : &[ Param{ name: "location", kind: ParamKind::Path, description: "The name of the location." } ],
+        request  : None,
+        response : Content::Json{ schema: r##"{ "type": "array", "uniqueItems": true, "items": { "$ref": "#/components/schemas/Capability" } }"##, rust: "std::collections::HashSet<specifications::package::Capability>" },
+        statuses : &[ (200, "The capabilities."), (404, "The location is not known.") ],
+    },
+    Operation {
+        id       : "get_config",
+        method   : Method::Get,
+        path     : "/infra/config",
+        tag      : "infra",
+        summary  : "Returns the hash of the infrastructure file as it is known to the central node.",
+        params   : &[],
+        request  : None,
+        response : Content::Json{ schema: r##"{ "$ref": "#/components/schemas/ConfigHashes" }"##, rust: "brane_cfg::infra::ConfigHashes" },
+        statuses : &[ (200, "The hashes.") ],
+    },
+    Operation {
+        id       : "get_location_config",
+        method   : Method::Get,
+        path     : "/infra/config/{location}",
+        tag      : "infra",
+        summary  : "Returns the hashes that the configuration files of the given location are expected to have.",
+        params   : &[ Param{ name: "location", kind: ParamKind::Path, description: "The name of the location." } ],
+        request  : None,
+        response : Content::Json{ schema: r##"{ "$ref": "#/components/schemas/ConfigHashes" }"##, rust: "brane_cfg::infra::ConfigHashes" },
+        statuses : &[ (200, "The hashes."), (404, "The location is not known.") ],
+    },
+    Operation {
+        id       : "get_ca_log",
+        method   : Method::Get,
+        path     : "/infra/ca-log",
+        tag      : "infra",
+        summary  : "Returns the signed log of the root certificates of every location and peer.",
+        params   : &[],
+        request  : None,
+        response : Content::Json{ schema: r##"{ "$ref": "#/components/schemas/SignedCaLog" }"##, rust: "brane_cfg::pins::SignedCaLog" },
+        statuses : &[ (200, "The log.") ],
+    },
+
+    // Events
+    Operation {
+        id       : "send_event",
+        method   : Method::Post,
+        path     : "/events/{app_id}/{name}",
+        tag      : "events",
+        summary  : "Sends an event to a session, resuming the workflow waiting for it (or buffering it until the workflow does). Requires a client certificate.",
+        params   : &[
+            Param{ name: "app_id", kind: ParamKind::Path, description: "The identifier of the session." },
+            Param{ name: "name", kind: ParamKind::Path, description: "The name of the event." },
+        ],
+        request  : Some(Content::Text),
+        response : Content::Empty,
+        statuses : &[
+            (200, "The event has been delivered."),
+            (400, "The session identifier is malformed, or the payload is not UTF-8."),
+            (401, "The client did not present a certificate."),
+            (403, "The event is reserved for decisions on approvals."),
+            (404, "The session is not known."),
+            (503, "The driver is unavailable."),
+        ],
+    },
+
+    // Approvals
+    Operation {
+        id       : "list_approvals",
+        method   : Method::Get,
+        path     : "/approvals",
+        tag      : "approvals",
+        summary  : "Lists the approvals that workflows are waiting for (oldest first). Approvers see all of them; other clients only those of their own sessions. Requires a client certificate.",
+        params   : &[],
+        request  : None,
+        response : Content::Json{ schema: r##"{ "type": "array", "items": { "$ref": "#/components/schemas/ApprovalInfo" } }"##, rust: "Vec<specifications::approvals::ApprovalInfo>" },
+        statuses : &[
+            (200, "The pending approvals."),
+            (401, "The client did not present a certificate."),
+            (503, "The driver is unavailable."),
+        ],
+    },
+    Operation {
+        id       : "decide_approval",
+        method   : Method::Post,
+        path     : "/approvals/{id}",
+        tag      : "approvals",
+        summary  : "Approves or rejects a pending approval, resuming the workflow waiting for it. Requires a client certificate of one of the approvers.",
+        params   : &[ Param{ name: "id", kind: ParamKind::Path, description: "The identifier of the approval." } ],
+        request  : Some(Content::Json{ schema: r##"{ "$ref": "#/components/schemas/ApprovalDecision" }"##, rust: "specifications::approvals::ApprovalDecision" }),
+        response : Content::Empty,
+        statuses : &[
+            (200, "The decision has been recorded."),
+            (400, "The body is not a valid decision."),
+            (401, "The client did not present a certificate."),
+            (403, "The client is not one of the approvers."),
+            (404, "The approval is not (or no longer) pending."),
+            (503, "The driver is unavailable."),
+        ],
+    },
+
+    // Health
+    Operation {
+        id       : "health",
+        method   : Method::Get,
+        path     : "/health",
+        tag      : "health",
+        summary  : "Returns whether the API is alive, as `OK!` (or `DEGRADED!` if it only serves cached package metadata).",
+        params   : &[],
+        request  : None,
+        response : Content::Text,
+        statuses : &[ (200, "The API is alive.") ],
+    },
+    Operation {
+        id       : "instance_health",
+        method   : Method::Get,
+        path     : "/health/instance",
+        tag      : "health",
+        summary  : "Returns the health of every component of the instance.",
+        params   : &[],
+        request  : None,
+        response : Content::Json{ schema: r##"{ "$ref": "#/components/schemas/InstanceHealth" }"##, rust: "specifications::health::InstanceHealth" },
+        statuses : &[ (200, "All components are healthy."), (503, "At least one component is unhealthy.") ],
+    },
+];
+
+/// The schemas of the bodies of the operations, by name.
+pub const SCHEMAS: &[(&str, &str)] = &[
+    ("DataInfo", r##"{
+        "type": "object",
+        "required": [ "name", "created", "access" ],
+        "properties": {
+            "name": { "type": "string" },
+            "version": { "type": "string", "description": "The version of the dataset, as `MAJOR.MINOR.PATCH`." },
+            "owners": { "type": "array", "items": { "type": "string" }, "nullable": true },
+            "description": { "type": "string", "nullable": true },
+            "created": { "type": "string", "format": "date-time" },
+            "residency": { "type": "array", "items": { "type": "string" }, "description": "The locations where the dataset may be processed. If omitted, it may be processed anywhere." },
+            "size": { "type": "integer", "format": "int64", "minimum": 0 },
+            "access": { "type": "object", "additionalProperties": { "$ref": "#/components/schemas/AccessKind" }, "description": "How to access the dataset, per location that advertises it." }
+        }
+    }"##),
+    ("AccessKind", r##"{
+        "type": "object",
+        "required": [ "kind" ],
+        "properties": {
+            "kind": { "type": "string", "enum": [ "file", "xrootd", "gridftp", "url" ] },
+            "path": { "type": "string", "description": "The path of the file (for `file`)." },
+            "url": { "type": "string", "description": "The URL of the file (for `xrootd`, `gridftp` and `url`)." },
+            "digest": { "type": "string", "description": "The SHA-256 digest of the file (for `url`)." }
+        }
+    }"##),
+    ("ImageRegistryInfo", r##"{
+        "type": "object",
+        "required": [ "address" ],
+        "properties": {
+            "address": { "type": "string" }
+        }
+    }"##),
+    ("Capability", r##"{
+        "oneOf": [
+            { "type": "string", "enum": [ "cuda_gpu", "x86_64", "aarch64" ] },
+            { "type": "object", "required": [ "milli_cpus" ], "properties": { "milli_cpus": { "type": "integer", "format": "int64", "minimum": 0 } } },
+            { "type": "object", "required": [ "memory" ], "properties": { "memory": { "type": "integer", "format": "int64", "minimum": 0 } } },
+            { "type": "object", "required": [ "gpus" ], "properties": { "gpus": { "type": "integer", "format": "int32", "minimum": 0 } } }
+        ]
+    }"##),
+    ("ConfigHashes", r##"{
+        "type": "object",
+        "properties": {
+            "infra": { "type": "string" },
+            "backend": { "type": "string" },
+            "policies": { "type": "string" }
+        }
+    }"##),
+    ("SignedCaLog", r##"{
+        "type": "object",
+        "required": [ "log", "public_key", "signature" ],
+        "properties": {
+            "log": {
+                "type": "object",
+                "required": [ "entries" ],
+                "properties": {
+                    "entries": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "required": [ "location", "fingerprint", "hash" ],
+                            "properties": {
+                                "location": { "type": "string" },
+                                "fingerprint": { "type": "string" },
+                                "hash": { "type": "string" }
+                            }
+                        }
+                    }
+                }
+            },
+            "public_key": { "type": "string", "format": "byte" },
+            "signature": { "type": "string", "format": "byte" }
+        }
+    }"##),
+    ("ComponentHealth", r##"{
+        "type": "object",
+        "required": [ "name", "healthy" ],
+        "properties": {
+            "name": { "type": "string" },
+            "healthy": { "type": "boolean" },
+            "latency_ms": { "type": "integer", "format": "int64", "minimum": 0 },
+            "error": { "type": "string" }
+        }
+    }"##),
+    ("ApprovalInfo", r##"{
+        "type": "object",
+        "required": [ "id", "session", "message", "requested" ],
+        "properties": {
+            "id": { "type": "string" },
+            "session": { "type": "string", "description": "The session of the workflow waiting for the approval." },
+            "message": { "type": "string" },
+            "requested": { "type": "string", "format": "date-time" }
+        }
+    }"##),
+    ("ApprovalDecision", r##"{
+        "type": "object",
+        "required": [ "approve" ],
+        "properties": {
+            "approve": { "type": "boolean" },
+            "reason": { "type": "string", "description": "Why the approval was (not) granted." }
+        }
+    }"##),
+    ("InstanceHealth", r##"{
+        "type": "object",
+        "required": [ "healthy", "components" ],
+        "properties": {
+            "healthy": { "type": "boolean" },
+            "components": { "type": "array", "items": { "$ref": "#/components/schemas/ComponentHealth" } }
+        }
+    }"##),
+];
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Defines the HTTP methods used by the operations.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Method {
+    /// A GET-request.
+    Get,
+    /// A POST-request.
+    Post,
+}
+
+impl Method {
+    /// Returns the (lowercase) name of the method as it appears in an OpenAPI document.
+    #[inline]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Get  => "get",
+            Self::Post => "post",
+        }
+    }
+}
+
+/// Defines where a parameter of an operation is given.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ParamKind {
+    /// The parameter is a (required) segment of the path.
+    Path,
+    /// The parameter is an (optional) part of the query.
+    Query,
+}
+
+/// Defines a (string) parameter of an operation.
+#[derive(Clone, Copy, Debug)]
+pub struct Param {
+    /// The name of the parameter.
+    pub name        : &'static str,
+    /// Where the parameter is given.
+    pub kind        : ParamKind,
+    /// What the parameter means.
+    pub description : &'static str,
+}
+
+/// Defines the body of a request or response.
+#[derive(Clone, Copy, Debug)]
+pub enum Content {
+    /// A JSON body.
+    Json {
+        /// The schema of the body, as JSON (which may refer to the [`SCHEMAS`]).
+        schema : &'static str,
+        /// The (fully qualified) Rust type to which the generated client deserializes the body.
+        rust   : &'static str,
+    },
+    /// A plain-text body.
+    Text,
+    /// A binary body (e.g., a tar archive), which the generated client leaves to the caller.
+    Binary,
+    /// No body at all.
+    Empty,
+}
+
+/// Defines a single operation (i.e., a method on a path) of the API.
+#[derive(Clone, Copy, Debug)]
+pub struct Operation {
+    /// The identifier of the operation, which is also the name of its method in the generated client.
+    pub id       : &'static str,
+    /// The method of the operation.
+    pub method   : Method,
+    /// The path of the operation, with its path parameters as `{name}`.
+    pub path     : &'static str,
+    /// The tag under which the operation is listed.
+    pub tag      : &'static str,
+    /// A one-sentence description of what the operation does.
+    pub summary  : &'static str,
+    /// The parameters of the operation.
+    pub params   : &'static [Param],
+    /// The body of the request, if any.
+    pub request  : Option<Content>,
+    /// The body of the response to the successful status codes.
+    pub response : Content,
+    /// The status codes with which the operation may respond, with what they mean. Any 2xx and 503 have the response as body.
+    pub statuses : &'static [(u16, &'static str)],
+}
+
+impl Operation {
+    /// Returns whether the generated client has a method for this operation, i.e., whether it is a GET with a body we can parse.
+    #[inline]
+    pub fn in_client(&self) -> bool { self.method == Method::Get && matches!(self.response, Content::Json{ .. } | Content::Text) }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Returns the OpenAPI media type object of the given content.
+///
+/// # Arguments
+/// - `content`: The Content to describe.
+///
+/// # Returns
+/// The `content` map of a request body or response, or `None` if it has no body.
+///
+/// # Errors
+/// This function errors if the content's schema is not valid JSON.
+fn media(content: &Content) -> Result<Option<Value>, serde_json::Error> {
+    Ok(match content {
+        Content::Json{ schema, .. } => Some(json!({ "application/json": { "schema": serde_json::from_str::<Value>(schema)? } })),
+        Content::Text               => Some(json!({ "text/plain": { "schema": { "type": "string" } } })),
+        Content::Binary             => Some(json!({ "application/octet-stream": { "schema": { "type": "string", "format": "binary" } } })),
+        Content::Empty              => None,
+    })
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Generates the OpenAPI (3.0) document of `brane-api`.
+///
+/// # Arguments
+/// - `version`: The version of `brane-api` that the document describes.
+///
+/// # Returns
+/// The document, as a JSON value.
+///
+/// # Errors
+/// This function errors if any of the schemas is not valid JSON.
+pub fn document(version: &str) -> Result<Value, serde_json::Error> {
+    // Describe the operations, grouped by path
+    let mut paths: Map<String, Value> = Map::new();
+    for op in OPERATIONS {
+        let mut operation: Map<String, Value> = Map::new();
+        operation.insert("operationId".into(), op.id.into());
+        operation.insert("tags".into(), json!([ op.tag ]));
+        operation.insert("summary".into(), op.summary.into());
+        if !op.params.is_empty() {
+            operation.insert("parameters".into(), op.params.iter().map(|param| json!({
+                "name"        : param.name,
+                "in"          : if param.kind == ParamKind::Path { "path" } else { "query" },
+                "required"    : param.kind == ParamKind::Path,
+                "description" : param.description,
+                "schema"      : { "type": "string" },
+            })).collect());
+        }
+        if let Some(request) = &op.request {
+            if let Some(content) = media(request)? { operation.insert("requestBody".into(), json!({ "required": true, "content": content })); }
+        }
+
+        // Only successful responses (and unhealthy ones) have a body; the others have a plain-text error at most
+        let mut responses: Map<String, Value> = Map::new();
+        for (status, description) in op.statuses {
+            let mut response: Map<String, Value> = Map::new();
+            response.insert("description".into(), (*description).into());
+            response.insert("headers".into(), json!({ API_VERSION_HEADER: { "description": "The version of the API.", "schema": { "type": "string" } } }));
+            if (200..300).contains(status) || *status == 503 {
+                if let Some(content) = media(&op.response)? { response.insert("content".into(), content); }
+            }
+            responses.insert(status.to_string(), Value::Object(response));
+        }
+        operation.insert("responses".into(), Value::Object(responses));
+
+        // Add it to the path
+        let path: &mut Value = paths.entry(op.path).or_insert_with(|| json!({}));
+        if let Value::Object(path) = path { path.insert(op.method.as_str().into(), Value::Object(operation)); }
+    }
+
+    // Collect the schemas
+    let mut schemas: Map<String, Value> = Map::new();
+    for (name, schema) in SCHEMAS {
+        schemas.insert((*name).into(), serde_json::from_str(schema)?);
+    }
+
+    // Done
+    Ok(json!({
+        "openapi"    : "3.0.3",
+        "info"       : {
+            "title"       : "brane-api",
+            "description" : "The central registry of a Brane instance, which knows its packages, datasets and locations.",
+            "version"     : version,
+        },
+        "tags"       : TAGS.iter().map(|(name, description)| json!({ "name": name, "description": description })).collect::<Vec<Value>>(),
+        "paths"      : paths,
+        "components" : { "schemas": schemas },
+    }))
+}
+
+
+
+/// Generates the Rust source of the typed client of `brane-api`.
+///
+/// The source implements a method per operation (see [`Operation::in_client()`]) on an `ApiClient` that the including module must define, together with `fetch_json()` and `fetch_text()` methods that send a GET-request to a path (accepting the given status codes) and parse the response, and an `encode_segment()` function that percent-encodes the path parameters.
+///
+/// # Arguments
+/// - `version`: The version of `brane-api` that the client is generated for.
+///
+/// # Returns
+/// The source, which defines the `API_VERSION` constant and an `impl ApiClient`.
+pub fn client(version: &str) -> String {
+    let mut source: String = String::new();
+    // Writing to a String never fails, so we ignore the results
+    let _ = writeln!(source, "// Generated from `specifications::openapi` by the build script of `brane-tsk`; do not edit.");
+    let _ = writeln!(source);
+    let _ = writeln!(source, "/// The version of `brane-api` that this client has been generated for.");
+    let _ = writeln!(source, "pub const API_VERSION: &str = {:?};", version);
+    let _ = writeln!(source);
+    let _ = writeln!(source, "impl ApiClient {{");
+    for op in OPERATIONS.iter().filter(|op| op.in_client()) {
+        let params: Vec<&Param> = op.params.iter().filter(|param| param.kind == ParamKind::Path).collect();
+
+        // Write the documentation
+        let _ = writeln!(source, "    /// {}", op.summary);
+        let _ = writeln!(source, "    ///");
+        let _ = writeln!(source, "    /// Sends a {} to `{}`.", op.method.as_str().to_uppercase(), op.path);
+        if !params.is_empty() {
+            let _ = writeln!(source, "    ///");
+            let _ = writeln!(source, "    /// # Arguments");
+            for param in &params { let _ = writeln!(source, "    /// - `{}`: {}", param.name, param.description); }
+        }
+        let _ = writeln!(source, "    ///");
+        let _ = writeln!(source, "    /// # Errors");
+        let _ = writeln!(source, "    /// This function errors if we failed to reach the API, if it is incompatible with this client, if it responded with another status code than {} or if its response was ill-formed.", op.statuses.iter().filter(|(status, _)| (200..300).contains(status) || *status == 503).map(|(status, _)| status.to_string()).collect::<Vec<String>>().join(" or "));
+
+        // Write the signature
+        let args: String = params.iter().map(|param| format!(", {}: &str", param.name)).collect();
+        let (ret, fetch): (&str, &str) = match &op.response {
+            Content::Json{ rust, .. } => (*rust, "fetch_json"),
+            _                         => ("String", "fetch_text"),
+        };
+        let _ = writeln!(source, "    pub async fn {}(&self{}) -> Result<{}, Error> {{", op.id, args, ret);
+
+        // Write the body
+        let mut path: String = op.path.into();
+        for param in &params { path = path.replace(&format!("{{{}}}", param.name), "{}"); }
+        let path: String = if params.is_empty() { format!("{:?}", path) } else { format!("&format!({:?}, {})", path, params.iter().map(|param| format!("encode_segment({})", param.name)).collect::<Vec<String>>().join(", ")) };
+        let statuses: String = op.statuses.iter().filter(|(status, _)| (200..300).contains(status) || *status == 503).map(|(status, _)| status.to_string()).collect::<Vec<String>>().join(", ");
+        let _ = writeln!(source, "        self.{}({}, &[ {} ]).await", fetch, path, statuses);
+        let _ = writeln!(source, "    }}");
+        let _ = writeln!(source);
+    }
+    let _ = writeln!(source, "}}");
+    source
+}
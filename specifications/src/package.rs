@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::fs::{self, File};
 use std::io::{BufReader, Read, Write};
 use std::path::{Path, PathBuf};
@@ -19,6 +20,16 @@ use crate::container::ContainerInfo;
 use crate::version::Version;
 
 
+/***** CONSTANTS *****/
+/// The environment variable with which workers point `branelet` to a directory that persists between the calls to the same OAS package on that worker (and in which it keeps the state they share, such as the token bucket of their rate limit).
+pub const OAS_STATE_DIR_ENV: &str = "BRANE_OAS_STATE_DIR";
+/// The environment variable with which workers point `branelet` to the directory in which it caches the responses of OAS packages, which persists between their calls on that worker.
+pub const OAS_CACHE_DIR_ENV: &str = "BRANE_OAS_CACHE_DIR";
+
+
+
+
+
 /***** CUSTOM TYPES *****/
 /// Shorthand for a map with String keys.
 type Map<T> = std::collections::HashMap<String, T>;
@@ -62,13 +73,16 @@ impl std::error::Error for PackageKindError {}
 pub enum CapabilityParseError {
     /// An unknown capability was given.
     UnknownCapability{ raw: String },
+    /// The amount of a resource capability was not a valid number.
+    IllegalAmount{ raw: String, err: std::num::ParseIntError },
 }
 impl std::fmt::Display for CapabilityParseError {
     #[inline]
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         use CapabilityParseError::*;
         match self {
-            UnknownCapability{ raw } => write!(f, "Unknown capability '{}'", raw),
+            UnknownCapability{ raw }  => write!(f, "Unknown capability '{}'", raw),
+            IllegalAmount{ raw, err } => write!(f, "Illegal amount in capability '{}': {}", raw, err),
         }
     }
 }
@@ -225,11 +239,50 @@ impl std::fmt::Display for PackageKind {
 
 
 /// Defines if the package has any additional requirements on the system it will run.
+/// 
+/// The resource capabilities (`MilliCpus`, `Memory` and `Gpus`) are requirements on the size of a location rather than on its features. Locations may advertise them to say how much of that resource a single task may use at most.
 #[derive(Clone, Copy, Deserialize, EnumDebug, Eq, Hash, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Capability {
     /// The package requires access to a CUDA GPU
+    #[serde(alias = "cuda")]
     CudaGpu,
+    /// The package requires an x86_64 (i.e., amd64) processor
+    #[serde(rename = "x86_64")]
+    X86_64,
+    /// The package requires an aarch64 (i.e., arm64) processor
+    Aarch64,
+
+    /// The package requires (at least) the given number of CPUs, in thousandths of a CPU.
+    MilliCpus(u64),
+    /// The package requires (at least) the given number of bytes of memory.
+    Memory(u64),
+    /// The package requires access to (at least) the given number of CUDA GPUs.
+    Gpus(u32),
+}
+
+impl Capability {
+    /// Returns whether a location that advertises the given capabilities satisfies this capability as a requirement.
+    /// 
+    /// Feature capabilities must be advertised by the location. Resource capabilities are only checked against locations that advertise how much of that resource they offer; others are assumed to have enough (and let the container runtime enforce the limit).
+    /// 
+    /// # Arguments
+    /// - `capabilities`: The capabilities advertised by the location.
+    /// 
+    /// # Returns
+    /// True if a task requiring this capability may run on the location, or false otherwise.
+    pub fn is_satisfied_by(&self, capabilities: &HashSet<Capability>) -> bool {
+        use Capability::*;
+        match self {
+            CudaGpu |
+            X86_64  |
+            Aarch64 => capabilities.contains(self),
+
+            MilliCpus(required) => capabilities.iter().all(|c| if let MilliCpus(offered) = c { offered >= required } else { true }),
+            Memory(required)    => capabilities.iter().all(|c| if let Memory(offered) = c { offered >= required } else { true }),
+            Gpus(required)      => capabilities.contains(&CudaGpu) && capabilities.iter().all(|c| if let Gpus(offered) = c { offered >= required } else { true }),
+        }
+    }
 }
 
 impl std::fmt::Debug for Capability {
@@ -237,6 +290,12 @@ impl std::fmt::Debug for Capability {
         use Capability::*;
         match self {
             CudaGpu => write!(f, "cuda_gpu"),
+            X86_64  => write!(f, "x86_64"),
+            Aarch64 => write!(f, "aarch64"),
+
+            MilliCpus(amount) => write!(f, "milli_cpus={}", amount),
+            Memory(amount)    => write!(f, "memory={}", amount),
+            Gpus(amount)      => write!(f, "gpus={}", amount),
         }
     }
 }
@@ -250,8 +309,25 @@ impl FromStr for Capability {
     type Err = CapabilityParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Split the amount off resource capabilities first
+        if let Some((name, amount)) = s.split_once('=') {
+            let res: Result<Self, std::num::ParseIntError> = match name {
+                "milli_cpus" => amount.parse().map(Self::MilliCpus),
+                "memory"     => amount.parse().map(Self::Memory),
+                "gpus"       => amount.parse().map(Self::Gpus),
+
+                _ => { return Err(CapabilityParseError::UnknownCapability{ raw: s.into() }); },
+            };
+            return match res {
+                Ok(capability) => Ok(capability),
+                Err(err)       => Err(CapabilityParseError::IllegalAmount{ raw: s.into(), err }),
+            };
+        }
+
         match s {
-            "cuda_gpu" => Ok(Self::CudaGpu),
+            "cuda_gpu" | "cuda" => Ok(Self::CudaGpu),
+            "x86_64"            => Ok(Self::X86_64),
+            "aarch64"           => Ok(Self::Aarch64),
 
             _ => Err(CapabilityParseError::UnknownCapability{ raw: s.into() }),
         }
@@ -430,21 +506,24 @@ impl From<ContainerInfo> for PackageInfo {
     fn from(container: ContainerInfo) -> Self {
         // Construct Function descriptions from the Actions
         let mut functions = Map::<Function>::with_capacity(container.actions.len());
-        for (action_name, action) in container.actions {
+        for (action_name, action) in &container.actions {
             // Get the return values of the function
-            let function_output = action.output.unwrap_or_default();
+            let function_output = action.output.clone().unwrap_or_default();
 
             // Wrap that in the three parameters needed for a function
-            let arguments = action.input.unwrap_or_default();
-            let pattern = action.pattern;
+            let arguments = action.input.clone().unwrap_or_default();
+            let pattern = action.pattern.clone();
             let return_type = match function_output.first() {
                 Some(output) => output.data_type.to_string(),
                 None         => String::from("unit"),
             };
 
             // Save the function under the original name
-            let function = Function::new(arguments, pattern, return_type, action.requirements);
-            functions.insert(action_name, function);
+            let mut function = Function::new(arguments, pattern, return_type, container.requirements(action));
+            function.deprecated = action.deprecated.clone();
+            function.aliases    = action.aliases.clone();
+            function.secrets    = container.secrets.clone();
+            functions.insert(action_name.clone(), function);
         }
 
         // Put it an other values in the new instance
@@ -478,7 +557,10 @@ impl From<&ContainerInfo> for PackageInfo {
             };
 
             // Save the function under the original name
-            let function = Function::new(arguments, pattern, return_type, action.requirements.clone());
+            let mut function = Function::new(arguments, pattern, return_type, container.requirements(action));
+            function.deprecated = action.deprecated.clone();
+            function.aliases    = action.aliases.clone();
+            function.secrets    = container.secrets.clone();
             functions.insert(action_name.clone(), function);
         }
 
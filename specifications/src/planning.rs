@@ -62,6 +62,9 @@ pub struct PlanningUpdate {
     /// For any other value, this field is ignored.
     #[prost(tag = "3", optional, string)]
     pub result : Option<String>,
+    /// If the `kind` is `PlanningStatusKind::Success`, then this explains, per task, why the planner chose the location it did. Ignored otherwise.
+    #[prost(tag = "4", repeated, string)]
+    pub reasoning : Vec<String>,
 }
 
 
@@ -78,8 +81,8 @@ pub enum PlanningStatus {
     /// Planning has started by the (optionally specified) planner.
     Started(Option<String>),
 
-    /// Planning has completed successfully, and this contains the (unparsed) Workflow result.
-    Success(String),
+    /// Planning has completed successfully, and this contains the (unparsed) Workflow result and the planner's explanation of why it chose every location.
+    Success(String, Vec<String>),
     /// Planning has failed due to no valid plan being possible. A possible reason for failure may be given.
     Failed(Option<String>),
     /// Planning has failed due to a (given) error.
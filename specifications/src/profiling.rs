@@ -0,0 +1,457 @@
+//  PROFILING.rs
+//    by agent
+//
+//  Created:
+//    16 Oct 2026, 09:10:00
+//  Last edited:
+//    16 Oct 2026, 09:10:00
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines the profiling report of a workflow run, which aggregates how
+//!   long its tasks, data transfers and task calls (i.e., edges) took,
+//!   together with a flamegraph-style tree of the same timings.
+//
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::status::ResourceUsage;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Shorthand for creating a task sample.
+    fn task(name: &str, start: i64, end: i64, usage: Option<ResourceUsage>) -> ProfileSample {
+        ProfileSample { kind: ProfileKind::Task, name: name.into(), location: "site1".into(), edge: None, start, end, usage }
+    }
+
+    /// Tests that the resource usage of the calls of a task is merged into its entry.
+    #[test]
+    fn test_report_usage() {
+        let samples: Vec<ProfileSample> = vec![
+            task("a::f", 0, 100, Some(ResourceUsage{ cpu_seconds: 1.0, peak_memory: 10, io_read: 1, io_write: 2 })),
+            task("a::f", 100, 300, Some(ResourceUsage{ cpu_seconds: 2.0, peak_memory: 30, io_read: 3, io_write: 4 })),
+            task("a::f", 300, 310, None),
+            task("a::g", 0, 50, None),
+        ];
+        let report: ProfileReport = ProfileReport::new("run", &samples);
+
+        // Sorted by total time, so `f` comes first
+        assert_eq!(report.duration, 310);
+        assert_eq!(report.tasks[0].name, "a::f");
+        assert_eq!(report.tasks[0].count, 3);
+        assert_eq!(report.tasks[0].total, 310);
+        assert_eq!(report.tasks[0].usage, Some(ResourceUsage{ cpu_seconds: 3.0, peak_memory: 30, io_read: 4, io_write: 6 }));
+        // Tasks without any measurement have no usage at all (rather than a zero one)
+        assert_eq!(report.tasks[1].name, "a::g");
+        assert_eq!(report.tasks[1].usage, None);
+    }
+
+    /// Tests that samples are aggregated per task, transfer and edge, and in the flamegraph.
+    #[test]
+    fn test_report_aggregate() {
+        let samples: Vec<ProfileSample> = vec![
+            ProfileSample { edge: Some("<main>:2".into()), ..task("a::f", 1000, 1100, None) },
+            ProfileSample { edge: Some("<main>:5".into()), location: "site2".into(), ..task("a::f", 1200, 1500, None) },
+            ProfileSample { kind: ProfileKind::Transfer, name: "data".into(), location: "site2".into(), edge: None, start: 1100, end: 1200, usage: None },
+        ];
+        let report: ProfileReport = ProfileReport::new("run", &samples);
+
+        assert_eq!(report.duration, 500);
+        assert_eq!(report.tasks.len(), 1);
+        assert_eq!(report.tasks[0].locations, vec![ "site1".to_string(), "site2".into() ]);
+        assert_eq!((report.tasks[0].count, report.tasks[0].total, report.tasks[0].min, report.tasks[0].max), (2, 400, 100, 300));
+        assert_eq!(report.tasks[0].mean(), 200.0);
+        assert_eq!(report.transfers.len(), 1);
+        assert_eq!((report.transfers[0].name.as_str(), report.transfers[0].total), ("data", 100));
+        // Every call is its own edge
+        assert_eq!(report.edges.iter().map(|e| (e.name.as_str(), e.total)).collect::<Vec<_>>(), vec![ ("<main>:5", 300), ("<main>:2", 100) ]);
+
+        // The flamegraph goes location, kind, name and edge
+        assert_eq!((report.flamegraph.name.as_str(), report.flamegraph.value), ("run", 500));
+        assert_eq!(report.flamegraph.children.iter().map(|c| (c.name.as_str(), c.value)).collect::<Vec<_>>(), vec![ ("site1", 100), ("site2", 400) ]);
+        let site2: &FlameNode = &report.flamegraph.children[1];
+        assert_eq!(site2.children.iter().map(|c| (c.name.as_str(), c.value)).collect::<Vec<_>>(), vec![ ("tasks", 300), ("transfers", 100) ]);
+        assert_eq!(site2.children[0].children[0].name, "a::f");
+        assert_eq!(site2.children[0].children[0].children[0].name, "<main>:5");
+        assert!(site2.children[1].children[0].children.is_empty());
+
+        // Nothing to aggregate is fine as well
+        let report: ProfileReport = ProfileReport::new("run", &[]);
+        assert_eq!((report.duration, report.tasks.len(), report.flamegraph.value), (0, 0, 0));
+    }
+
+    /// Tests sorting the entries of a report by every key.
+    #[test]
+    fn test_report_sort() {
+        let samples: Vec<ProfileSample> = vec![
+            task("b", 0, 100, None),
+            task("b", 0, 100, None),
+            task("b", 0, 100, None),
+            task("c", 0, 250, None),
+            task("a", 0, 120, None),
+            task("a", 0, 10, None),
+        ];
+        let mut report: ProfileReport = ProfileReport::new("run", &samples);
+        let names = |report: &ProfileReport| report.tasks.iter().map(|e| e.name.clone()).collect::<Vec<_>>();
+
+        assert_eq!(names(&report), vec![ "b", "c", "a" ]);
+        report.sort("mean".parse().unwrap());
+        assert_eq!(names(&report), vec![ "c", "b", "a" ]);
+        report.sort("max".parse().unwrap());
+        assert_eq!(names(&report), vec![ "c", "a", "b" ]);
+        report.sort("count".parse().unwrap());
+        assert_eq!(names(&report), vec![ "b", "a", "c" ]);
+        report.sort("name".parse().unwrap());
+        assert_eq!(names(&report), vec![ "a", "b", "c" ]);
+        report.sort(ProfileSortKey::default());
+        assert_eq!(names(&report), vec![ "b", "c", "a" ]);
+
+        assert!(matches!("Total".parse::<ProfileSortKey>(), Err(ProfileSortKeyParseError::UnknownKey{ ref raw }) if raw == "Total"));
+    }
+
+    /// Tests formatting durations in every unit.
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::ZERO), "0us");
+        assert_eq!(format_duration(Duration::from_micros(999)), "999us");
+        assert_eq!(format_duration(Duration::from_millis(250)), "250ms");
+        assert_eq!(format_duration(Duration::from_micros(1_234)), "1.23ms");
+        assert_eq!(format_duration(Duration::from_millis(1_500)), "1.50s");
+        assert_eq!(format_duration(Duration::from_millis(59_999)), "60.00s");
+        assert_eq!(format_duration(Duration::from_secs(60)), "1m00s");
+        assert_eq!(format_duration(Duration::from_millis(3_723_900)), "62m03s");
+    }
+}
+
+
+
+
+
+/***** ERRORS *****/
+/// Defines the error for parsing a ProfileSortKey from a string.
+#[derive(Debug)]
+pub enum ProfileSortKeyParseError {
+    /// The given key was not one we know.
+    UnknownKey{ raw: String },
+}
+
+impl Display for ProfileSortKeyParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use ProfileSortKeyParseError::*;
+        match self {
+            UnknownKey{ raw } => write!(f, "Unknown sort key '{}' (expected 'total', 'mean', 'max', 'count' or 'name')", raw),
+        }
+    }
+}
+
+impl std::error::Error for ProfileSortKeyParseError {}
+
+
+
+
+
+/***** LIBRARY FUNCTIONS *****/
+/// Formats the given duration for the user, in the most appropriate unit.
+///
+/// This is shared by everything that reports timings (profiles, what-if schedules and VM traces), so that they all read the same.
+///
+/// # Arguments
+/// - `duration`: The duration to format.
+///
+/// # Returns
+/// A string like `250us`, `250ms`, `1.23ms`, `4.50s` or `2m05s`.
+pub fn format_duration(duration: Duration) -> String {
+    let us: u128 = duration.as_micros();
+    if us < 1_000 {
+        format!("{}us", us)
+    } else if us < 1_000_000 {
+        // Timings with a resolution of milliseconds should not show the fractions they do not have
+        if us % 1_000 == 0 { format!("{}ms", us / 1_000) } else { format!("{:.2}ms", us as f64 / 1_000.0) }
+    } else if us < 60_000_000 {
+        format!("{:.2}s", us as f64 / 1_000_000.0)
+    } else {
+        format!("{}m{:02}s", us / 60_000_000, (us % 60_000_000) / 1_000_000)
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines how long a single data transfer took in a run.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TransferTiming {
+    /// The name of the dataset or intermediate result that was transferred.
+    pub name     : String,
+    /// The location to which it was transferred.
+    pub location : String,
+    /// When the transfer was started, in milliseconds since the Unix epoch.
+    pub start    : i64,
+    /// When the transfer was done, in milliseconds since the Unix epoch.
+    pub end      : i64,
+}
+
+
+
+/// Defines the kinds of things that are timed in a run.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileKind {
+    /// A task call.
+    Task,
+    /// A transfer of a dataset or intermediate result to the location of a task.
+    Transfer,
+}
+
+impl Display for ProfileKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use ProfileKind::*;
+        match self {
+            Task     => write!(f, "tasks"),
+            Transfer => write!(f, "transfers"),
+        }
+    }
+}
+
+
+
+/// Defines a single timed thing in a run, from which a ProfileReport is built.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ProfileSample {
+    /// What was timed.
+    pub kind     : ProfileKind,
+    /// The name of the task (as `package::function`) or of the transferred data.
+    pub name     : String,
+    /// The location where the task ran or the data was transferred to.
+    pub location : String,
+    /// For tasks, a description of the edge that called it (so that calls of the same task in different places can be told apart).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edge     : Option<String>,
+    /// When it started, in milliseconds since the Unix epoch.
+    pub start    : i64,
+    /// When it was done, in milliseconds since the Unix epoch.
+    pub end      : i64,
+    /// For tasks, the resources they consumed (if measured).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage    : Option<ResourceUsage>,
+}
+
+
+
+/// Defines the keys by which the entries in a ProfileReport may be sorted.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileSortKey {
+    /// Sort by total time, longest first.
+    #[default]
+    Total,
+    /// Sort by mean time, longest first.
+    Mean,
+    /// Sort by maximum time, longest first.
+    Max,
+    /// Sort by number of calls, most first.
+    Count,
+    /// Sort by name, alphabetically.
+    Name,
+}
+
+impl FromStr for ProfileSortKey {
+    type Err = ProfileSortKeyParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "total" => Ok(Self::Total),
+            "mean"  => Ok(Self::Mean),
+            "max"   => Ok(Self::Max),
+            "count" => Ok(Self::Count),
+            "name"  => Ok(Self::Name),
+            raw     => Err(ProfileSortKeyParseError::UnknownKey{ raw: raw.into() }),
+        }
+    }
+}
+
+
+
+/// Defines the aggregated timings of all samples with the same name.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ProfileEntry {
+    /// The name of the task, transfer or edge.
+    pub name      : String,
+    /// The locations where it happened, sorted.
+    pub locations : Vec<String>,
+    /// The number of times it happened.
+    pub count     : usize,
+    /// The total time it took, in milliseconds.
+    pub total     : i64,
+    /// The shortest time it took, in milliseconds.
+    pub min       : i64,
+    /// The longest time it took, in milliseconds.
+    pub max       : i64,
+    /// The resources consumed by all of its measured occurrences together, if any were measured.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage     : Option<ResourceUsage>,
+}
+
+impl ProfileEntry {
+    /// Returns the mean time it took, in milliseconds.
+    #[inline]
+    pub fn mean(&self) -> f64 { if self.count > 0 { self.total as f64 / self.count as f64 } else { 0.0 } }
+}
+
+
+
+/// Defines a node in a flamegraph, in the format understood by (e.g.) `d3-flame-graph`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FlameNode {
+    /// The name of the node.
+    pub name     : String,
+    /// The time spent in this node and its children, in milliseconds.
+    pub value    : i64,
+    /// The children of this node.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children : Vec<FlameNode>,
+}
+
+impl FlameNode {
+    /// Constructor for a FlameNode without any time spent in it.
+    ///
+    /// # Arguments
+    /// - `name`: The name of the node.
+    ///
+    /// # Returns
+    /// A new FlameNode.
+    #[inline]
+    pub fn new(name: impl Into<String>) -> Self { Self { name: name.into(), value: 0, children: vec![] } }
+
+    /// Adds time to the node at the given path below this one, creating the nodes on the way if needed.
+    ///
+    /// # Arguments
+    /// - `path`: The names of the nodes on the path, starting with a child of this node.
+    /// - `value`: The time to add, in milliseconds. It is added to every node on the path (including this one).
+    pub fn add(&mut self, path: &[&str], value: i64) {
+        self.value += value;
+        if let Some((name, rest)) = path.split_first() {
+            let i: usize = match self.children.iter().position(|c| c.name == *name) {
+                Some(i) => i,
+                None    => { self.children.push(Self::new(*name)); self.children.len() - 1 },
+            };
+            self.children[i].add(rest, value);
+        }
+    }
+}
+
+
+
+/// Defines the profiling report of a single run.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ProfileReport {
+    /// The time between the start of the first sample and the end of the last one, in milliseconds.
+    pub duration   : i64,
+    /// The timings per task.
+    pub tasks      : Vec<ProfileEntry>,
+    /// The timings per transferred dataset or intermediate result.
+    pub transfers  : Vec<ProfileEntry>,
+    /// The timings per edge that called a task.
+    pub edges      : Vec<ProfileEntry>,
+    /// The timings as a tree of location, kind, name and (for tasks) edge.
+    pub flamegraph : FlameNode,
+}
+
+impl ProfileReport {
+    /// Builds a report from the given samples.
+    ///
+    /// # Arguments
+    /// - `root`: The name of the root of the flamegraph (e.g., the identifier of the run).
+    /// - `samples`: The samples to aggregate.
+    ///
+    /// # Returns
+    /// A new ProfileReport, sorted by total time.
+    pub fn new(root: impl Into<String>, samples: &[ProfileSample]) -> Self {
+        /// What we collect per name: the locations, the durations and the merged resource usage.
+        type Collected = (BTreeSet<String>, Vec<i64>, Option<ResourceUsage>);
+
+        /// Adds the given sample to the entry with the given name.
+        fn add(entries: &mut BTreeMap<String, Collected>, name: &str, sample: &ProfileSample) {
+            let entry: &mut Collected = entries.entry(name.into()).or_default();
+            entry.0.insert(sample.location.clone());
+            entry.1.push(sample.end - sample.start);
+            if let Some(usage) = &sample.usage { entry.2.get_or_insert_with(ResourceUsage::default).merge(usage); }
+        }
+        /// Aggregates the durations collected per name.
+        fn aggregate(entries: BTreeMap<String, Collected>) -> Vec<ProfileEntry> {
+            entries.into_iter().map(|(name, (locations, durations, usage))| ProfileEntry {
+                name,
+                locations : locations.into_iter().collect(),
+                count     : durations.len(),
+                total     : durations.iter().sum(),
+                min       : durations.iter().copied().min().unwrap_or(0),
+                max       : durations.iter().copied().max().unwrap_or(0),
+                usage,
+            }).collect()
+        }
+
+        // Collect the durations per name and build the flamegraph while at it
+        let mut tasks      : BTreeMap<String, Collected> = BTreeMap::new();
+        let mut transfers  : BTreeMap<String, Collected> = BTreeMap::new();
+        let mut edges      : BTreeMap<String, Collected> = BTreeMap::new();
+        let mut flamegraph : FlameNode                  = FlameNode::new(root);
+        for s in samples {
+            let kind: String = s.kind.to_string();
+            match s.kind {
+                ProfileKind::Task => {
+                    add(&mut tasks, &s.name, s);
+                    if let Some(edge) = &s.edge { add(&mut edges, edge, s); }
+                    match &s.edge {
+                        Some(edge) => flamegraph.add(&[ s.location.as_str(), kind.as_str(), s.name.as_str(), edge.as_str() ], s.end - s.start),
+                        None       => flamegraph.add(&[ s.location.as_str(), kind.as_str(), s.name.as_str() ], s.end - s.start),
+                    }
+                },
+                ProfileKind::Transfer => {
+                    add(&mut transfers, &s.name, s);
+                    flamegraph.add(&[ s.location.as_str(), kind.as_str(), s.name.as_str() ], s.end - s.start);
+                },
+            }
+        }
+
+        // Done
+        let mut report: Self = Self {
+            duration  : samples.iter().map(|s| s.end).max().unwrap_or(0) - samples.iter().map(|s| s.start).min().unwrap_or(0),
+            tasks     : aggregate(tasks),
+            transfers : aggregate(transfers),
+            edges     : aggregate(edges),
+            flamegraph,
+        };
+        report.sort(ProfileSortKey::Total);
+        report
+    }
+
+
+
+    /// Sorts the entries in the report.
+    ///
+    /// # Arguments
+    /// - `key`: The key to sort by.
+    pub fn sort(&mut self, key: ProfileSortKey) {
+        for entries in [ &mut self.tasks, &mut self.transfers, &mut self.edges ] {
+            match key {
+                ProfileSortKey::Total => entries.sort_by(|a, b| b.total.cmp(&a.total)),
+                ProfileSortKey::Mean  => entries.sort_by(|a, b| b.mean().total_cmp(&a.mean())),
+                ProfileSortKey::Max   => entries.sort_by(|a, b| b.max.cmp(&a.max)),
+                ProfileSortKey::Count => entries.sort_by(|a, b| b.count.cmp(&a.count)),
+                ProfileSortKey::Name  => entries.sort_by(|a, b| a.name.cmp(&b.name)),
+            }
+        }
+    }
+}
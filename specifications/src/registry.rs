@@ -4,15 +4,16 @@
  * Created:
  *   08 May 2022, 13:57:01
  * Last edited:
- *   08 May 2022, 14:07:07
+ *   16 Oct 2026, 13:02:11
  * Auto updated?
  *   Yes
  *
  * Description:
  *   Contains file and other struct definitions for registry-related stuff
- *   (brane-cli only).
+ *   (the brane-cli login file and what a location's registry reports).
 **/
 
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use std::io::ErrorKind;
 use std::fmt::{Display, Formatter, Result as FResult};
@@ -22,6 +23,8 @@ use std::path::{Path, PathBuf};
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 
+use crate::package::Capability;
+
 
 /***** ERRORS *****/
 /// Defines possible errors when loading a RegistryConfig file.
@@ -92,3 +95,34 @@ impl RegistryConfig {
         }
     }
 }
+
+
+
+/// Describes a package image that a location has cached locally.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CachedPackage {
+    /// The name of the package.
+    pub name    : String,
+    /// The version of the package.
+    pub version : String,
+    /// The size of the image, in bytes.
+    pub size    : u64,
+}
+
+/// Describes what a location (i.e., its `brane-reg` service) has available locally, as reported on its `/infra/availability` path.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct LocationAvailability {
+    /// The capabilities the location supports.
+    pub capabilities : HashSet<Capability>,
+    /// The package images the location has cached.
+    pub packages     : Vec<CachedPackage>,
+    /// The datasets the location advertises (by identifier, i.e., `NAME@VERSION`), mapped to their size in bytes.
+    pub datasets     : HashMap<String, u64>,
+}
+
+/// Describes the OCI registry in which an instance stores package images, as reported on the `/packages/registry` path of its API.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImageRegistryInfo {
+    /// The address of the registry, including the project or namespace in which the images live (e.g., `harbor.example.com/brane`). Images are pushed as `<address>/<name>:<version>`.
+    pub address : String,
+}
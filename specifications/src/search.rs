@@ -0,0 +1,135 @@
+//  SEARCH.rs
+//    by agent
+//
+//  Created:
+//    16 Oct 2026, 08:13:58
+//  Last edited:
+//    16 Oct 2026, 08:13:58
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines the results of an instance-wide search, as returned by the
+//!   `/search` path of `brane-api`.
+//
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+
+/***** ERRORS *****/
+/// Defines the error for parsing a SearchKind from a string.
+#[derive(Debug)]
+pub enum SearchKindParseError {
+    /// The given kind was not one we know.
+    UnknownKind{ raw: String },
+}
+
+impl Display for SearchKindParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use SearchKindParseError::*;
+        match self {
+            UnknownKind{ raw } => write!(f, "Unknown search kind '{}' (expected 'package', 'workflow' or 'dataset')", raw),
+        }
+    }
+}
+
+impl std::error::Error for SearchKindParseError {}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Tests that every kind parses back from how it is displayed.
+    #[test]
+    fn test_kind_roundtrip() {
+        for kind in SearchKind::all() {
+            assert_eq!(SearchKind::from_str(&kind.to_string()).unwrap(), kind);
+            assert_eq!(serde_json::to_string(&kind).unwrap(), format!("\"{}\"", kind));
+        }
+        assert!(SearchKind::from_str("Workflow").is_err());
+        assert!(SearchKind::from_str("result").is_err());
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines the kinds of things that may be found in an instance (i.e., the facets of a search).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchKind {
+    /// The result is a package.
+    Package,
+    /// The result is a registered workflow, i.e., a workflow that has been published as a package (of the `dsl` or `cwl` kind).
+    Workflow,
+    /// The result is a dataset.
+    Dataset,
+}
+
+impl SearchKind {
+    /// Returns all the kinds, in the order in which results are grouped.
+    #[inline]
+    pub fn all() -> [ Self; 3 ] { [ Self::Package, Self::Workflow, Self::Dataset ] }
+}
+
+impl Display for SearchKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use SearchKind::*;
+        match self {
+            Package  => write!(f, "package"),
+            Workflow => write!(f, "workflow"),
+            Dataset  => write!(f, "dataset"),
+        }
+    }
+}
+
+impl FromStr for SearchKind {
+    type Err = SearchKindParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "package"  => Ok(Self::Package),
+            "workflow" => Ok(Self::Workflow),
+            "dataset"  => Ok(Self::Dataset),
+            raw        => Err(SearchKindParseError::UnknownKind{ raw: raw.into() }),
+        }
+    }
+}
+
+
+
+/// Defines a single thing that matched a search.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SearchResult {
+    /// What kind of thing this is.
+    pub kind        : SearchKind,
+    /// The name of the thing.
+    pub name        : String,
+    /// The version of the thing, if it is versioned.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version     : Option<String>,
+    /// A (short) description of the thing, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description : Option<String>,
+}
+
+/// Defines the response to an instance-wide search.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct SearchResults {
+    /// The number of matches per kind, regardless of any kind filter applied to the `results`.
+    pub facets  : HashMap<SearchKind, usize>,
+    /// The matches themselves, grouped by kind and sorted by name.
+    pub results : Vec<SearchResult>,
+}
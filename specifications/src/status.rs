@@ -2,6 +2,68 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 
+
+/***** UNIT TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    #[test]
+    fn test_progress_well_formed() {
+        // Percentage and message, with or without the percent-sign
+        assert_eq!(TaskProgress::from_line("42 Training epoch 3"), Some(TaskProgress{ percentage: Some(42.0), message: Some("Training epoch 3".into()) }));
+        assert_eq!(TaskProgress::from_line("42.5%   Training"),    Some(TaskProgress{ percentage: Some(42.5), message: Some("Training".into()) }));
+        // Only a percentage
+        assert_eq!(TaskProgress::from_line("  100%\n"), Some(TaskProgress{ percentage: Some(100.0), message: None }));
+        // Only a message
+        assert_eq!(TaskProgress::from_line("Downloading dataset"), Some(TaskProgress{ percentage: None, message: Some("Downloading dataset".into()) }));
+    }
+
+    #[test]
+    fn test_progress_malformed() {
+        // Empty lines are no progress at all
+        assert_eq!(TaskProgress::from_line(""), None);
+        assert_eq!(TaskProgress::from_line(" \t\n"), None);
+        // Something that merely starts like a number is kept as message, in full
+        assert_eq!(TaskProgress::from_line("42abc done"), Some(TaskProgress{ percentage: None, message: Some("42abc done".into()) }));
+        assert_eq!(TaskProgress::from_line("% done"),     Some(TaskProgress{ percentage: None, message: Some("% done".into()) }));
+        assert_eq!(TaskProgress::from_line("NaN% done"),  Some(TaskProgress{ percentage: None, message: Some("NaN% done".into()) }));
+    }
+
+    #[test]
+    fn test_progress_new() {
+        // Out-of-range percentages are clamped and NaN is dropped
+        assert_eq!(TaskProgress::new(Some(250.0), None),                Some(TaskProgress{ percentage: Some(100.0), message: None }));
+        assert_eq!(TaskProgress::new(Some(f32::NAN), Some("Hi".into())), Some(TaskProgress{ percentage: None, message: Some("Hi".into()) }));
+        // Without anything meaningful, there is no progress
+        assert_eq!(TaskProgress::new(None, Some("  ".into())), None);
+        assert_eq!(TaskProgress::new(Some(f32::NAN), None),    None);
+    }
+
+    #[test]
+    fn test_progress_out_of_range() {
+        // Percentages are clamped to [0, 100]
+        assert_eq!(TaskProgress::from_line("150% Overachieving"), Some(TaskProgress{ percentage: Some(100.0), message: Some("Overachieving".into()) }));
+        assert_eq!(TaskProgress::from_line("-5"),                 Some(TaskProgress{ percentage: Some(0.0), message: None }));
+        assert_eq!(TaskProgress::from_line("inf"),                Some(TaskProgress{ percentage: Some(100.0), message: None }));
+    }
+
+    #[test]
+    fn test_usage_merge() {
+        // CPU time and I/O add up, while the peak memory is the highest of the two
+        let mut usage: ResourceUsage = ResourceUsage{ cpu_seconds: 1.5, peak_memory: 100, io_read: 10, io_write: 20 };
+        usage.merge(&ResourceUsage{ cpu_seconds: 2.0, peak_memory: 50, io_read: 1, io_write: 2 });
+        assert_eq!(usage, ResourceUsage{ cpu_seconds: 3.5, peak_memory: 100, io_read: 11, io_write: 22 });
+        usage.merge(&ResourceUsage{ cpu_seconds: 0.0, peak_memory: 400, io_read: 0, io_write: 0 });
+        assert_eq!(usage.peak_memory, 400);
+    }
+}
+
+
+
+
+
 #[skip_serializing_none]
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -47,3 +109,187 @@ pub struct TransferStatus {
     pub done_files: Option<i32>,
     pub done_size: Option<i32>,
 }
+
+
+
+/***** TASK PROGRESS *****/
+/// The prefix with which branelet marks progress updates on its stderr, so that the worker can pick them out of the container logs.
+pub const PROGRESS_PREFIX: &str = "[BRANE_PROGRESS] ";
+/// The name of the environment variable that tells package code the URL of the sidecar API that branelet serves to it.
+pub const SIDECAR_ENV: &str = "BRANE_SIDECAR";
+
+/// Defines an intermediate progress update as reported by a (long-running) task.
+/// 
+/// Package code reports these by `POST`ing them to `$BRANE_SIDECAR/progress`, either as JSON or as a line of the form `<percentage> <message>` (where either part may be omitted).
+#[skip_serializing_none]
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct TaskProgress {
+    /// How far along the task is, as a percentage between 0 and 100 (if reported).
+    pub percentage : Option<f32>,
+    /// Any human-readable message describing what the task is doing (if reported).
+    pub message    : Option<String>,
+}
+
+impl TaskProgress {
+    /// Constructor for a TaskProgress that clamps the percentage to [0, 100] and ignores empty messages.
+    /// 
+    /// # Arguments
+    /// - `percentage`: How far along the task is, if reported. NaN is ignored.
+    /// - `message`: What the task is doing, if reported.
+    /// 
+    /// # Returns
+    /// A new TaskProgress, or `None` if neither part was (meaningfully) given.
+    pub fn new(percentage: Option<f32>, message: Option<String>) -> Option<Self> {
+        let percentage: Option<f32>    = percentage.filter(|p| !p.is_nan()).map(|p| p.clamp(0.0, 100.0));
+        let message   : Option<String> = message.filter(|m| !m.trim().is_empty());
+        if percentage.is_none() && message.is_none() { return None; }
+        Some(Self { percentage, message })
+    }
+
+    /// Parses a single line written by package code to a TaskProgress.
+    /// 
+    /// # Arguments
+    /// - `line`: The line to parse. If it begins with a number (optionally suffixed with `%`), it is interpreted as the percentage; the remainder is interpreted as the message.
+    /// 
+    /// # Returns
+    /// A new TaskProgress, or `None` if the line was empty.
+    pub fn from_line(line: impl AsRef<str>) -> Option<Self> {
+        let line: &str = line.as_ref().trim();
+        if line.is_empty() { return None; }
+
+        // Attempt to split the first word off as a percentage
+        let (head, tail): (&str, &str) = match line.find(char::is_whitespace) {
+            Some(pos) => (&line[..pos], line[pos..].trim_start()),
+            None      => (line, ""),
+        };
+        match head.trim_end_matches('%').parse::<f32>() {
+            // NaN cannot be clamped to a percentage, so we treat it as any other word
+            Ok(percentage) if !percentage.is_nan() => Some(Self {
+                percentage : Some(percentage.clamp(0.0, 100.0)),
+                message    : if !tail.is_empty() { Some(tail.into()) } else { None },
+            }),
+            _ => Some(Self {
+                percentage : None,
+                message    : Some(line.into()),
+            }),
+        }
+    }
+}
+
+impl std::fmt::Display for TaskProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.percentage, &self.message) {
+            (Some(percentage), Some(message)) => write!(f, "{:.0}% ({})", percentage, message),
+            (Some(percentage), None)          => write!(f, "{:.0}%", percentage),
+            (None, Some(message))             => write!(f, "{}", message),
+            (None, None)                      => write!(f, "<no progress>"),
+        }
+    }
+}
+
+
+
+/***** TASK OUTPUT *****/
+/// The prefix with which branelet marks the lines that package code writes, so that the worker can pick them out of the container logs and stream them to the client.
+pub const OUTPUT_PREFIX: &str = "[BRANE_OUTPUT] ";
+
+/// Defines the stream on which package code wrote a line of output.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputStream {
+    /// The line was written to stdout.
+    Stdout,
+    /// The line was written to stderr.
+    Stderr,
+}
+
+/// Defines a single line of output written by a (running) task.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct TaskOutput {
+    /// The stream on which the line was written.
+    pub stream : OutputStream,
+    /// The line itself, without its newline.
+    pub line   : String,
+}
+
+
+
+/***** TASK SCRATCH SPACE *****/
+/// The path at which every task finds its private scratch space.
+pub const SCRATCH_DIR: &str = "/scratch";
+/// The name of the environment variable that tells package code where its scratch space may be found.
+pub const SCRATCH_DIR_ENV: &str = "BRANE_SCRATCH_DIR";
+/// The name of the environment variable that tells branelet how many bytes the node allows a task to write to its scratch space.
+pub const SCRATCH_LIMIT_ENV: &str = "BRANE_SCRATCH_LIMIT";
+/// The prefix with which branelet marks on its stderr that a task ran out of scratch space, followed by the limit (in bytes) that it hit.
+pub const SCRATCH_EXHAUSTED_PREFIX: &str = "[BRANE_SCRATCH_EXHAUSTED] ";
+
+
+
+/***** TASK RESOURCE USAGE *****/
+/// Defines the resources that a task has consumed while it ran, as measured by the container engine.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct ResourceUsage {
+    /// The total CPU time spent by the task, in seconds (summed over all cores).
+    pub cpu_seconds : f64,
+    /// The highest amount of memory the task used at any one time, in bytes.
+    pub peak_memory : u64,
+    /// The total number of bytes the task read from block devices.
+    pub io_read     : u64,
+    /// The total number of bytes the task wrote to block devices.
+    pub io_write    : u64,
+}
+
+impl ResourceUsage {
+    /// Adds the usage of another task (or call) to this one, as if they were one.
+    ///
+    /// CPU time and I/O are summed, whereas the peak memory becomes the highest of the two (since we do not know if they overlapped).
+    ///
+    /// # Arguments
+    /// - `other`: The ResourceUsage to add.
+    pub fn merge(&mut self, other: &ResourceUsage) {
+        self.cpu_seconds += other.cpu_seconds;
+        self.peak_memory  = self.peak_memory.max(other.peak_memory);
+        self.io_read     += other.io_read;
+        self.io_write    += other.io_write;
+    }
+}
+
+
+
+/***** SESSION EVENTS *****/
+/// Defines a structured event that happened in a session on the driver, as streamed to the (user) interfaces that subscribe to the session.
+#[skip_serializing_none]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SessionEvent {
+    /// A workflow started running in the session.
+    RunStarted,
+    /// The workflow completed, returning the given value (as JSON), if any.
+    RunCompleted { value: Option<String> },
+    /// The workflow failed (or was cancelled).
+    RunFailed { error: String },
+
+    /// A task was sent to the given location to run.
+    TaskStarted { name: String, location: String },
+    /// A running task reported its progress.
+    TaskProgress { name: String, progress: TaskProgress },
+    /// A running task wrote a line of output.
+    TaskOutput { name: String, output: TaskOutput },
+    /// A task completed, taking the given time and (if measured) resources.
+    TaskCompleted { name: String, location: String, duration_ms: i64, usage: Option<ResourceUsage> },
+    /// A task failed.
+    TaskFailed { name: String, location: String, error: String },
+
+    /// A dataset or intermediate result started to be transferred from the given source to the given location.
+    TransferStarted { data: String, location: String, source: String },
+    /// A transfer completed. The source may differ from the one it started with if the driver fell back to another copy of the dataset.
+    TransferCompleted { data: String, location: String, source: String, duration_ms: i64 },
+    /// A transfer failed (from every source we tried).
+    TransferFailed { data: String, location: String, source: String, error: String },
+
+    /// The workflow printed the given text, in the thread identified by the branch (see `ExecuteReply::branch`).
+    Print { branch: Vec<usize>, text: String },
+    /// The driver noted something about the run (e.g., that it substituted a source of a transfer).
+    Note { message: String },
+}